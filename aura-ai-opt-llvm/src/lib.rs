@@ -0,0 +1,121 @@
+//! The `aura-ai-opt` metadata pass as an IR transform over a parsed LLVM module.
+//!
+//! Does what `aura_ai_opt::optimize_ll` does to the text, but on instructions: calls are found
+//! by their callee rather than by substring, `!range` is typed after the call's result, and
+//! existing metadata is read through LLVM instead of by scanning for `!N` ids.
+
+#![forbid(unsafe_code)]
+
+use std::collections::HashMap;
+
+use inkwell::context::Context;
+use inkwell::memory_buffer::MemoryBuffer;
+use inkwell::module::{FlagBehavior, Module};
+use inkwell::types::AnyTypeEnum;
+use inkwell::values::{BasicValueEnum, CallSiteValue, InstructionOpcode, InstructionValue, Operand};
+use miette::IntoDiagnostic;
+
+use aura_ai_opt::{host_footer, rewrite_kernels, AiOptError, HostFeatures};
+
+/// Rewrites `input_ll`'s kernels (see `aura_ai_opt::rewrite_kernels`), parses it, runs the
+/// pass over the module, and prints it back.
+pub fn optimize_ll_module(input_ll: &str, host: HostFeatures) -> Result<String, AiOptError> {
+    let rewritten = rewrite_kernels(input_ll);
+    let context = Context::create();
+    let buffer = MemoryBuffer::create_from_memory_range_copy(rewritten.as_bytes(), "aura-ai-opt");
+    let module = context.create_module_from_ir(buffer).map_err(|e| AiOptError {
+        message: format!("failed to parse LLVM IR: {e}"),
+    })?;
+
+    optimize_module(&context, &module);
+    module.verify().map_err(|e| AiOptError {
+        message: format!("module is invalid after the pass: {e}"),
+    })?;
+
+    let mut out = module.print_to_string().to_string();
+    out.push_str(&host_footer(host));
+    Ok(out)
+}
+
+pub fn optimize_ll_file(input: &std::path::Path, output: &std::path::Path) -> miette::Result<()> {
+    let ll = std::fs::read_to_string(input).into_diagnostic()?;
+    let optimized = optimize_ll_module(&ll, aura_ai_opt::host_features()).map_err(miette::Report::new)?;
+    std::fs::write(output, optimized).into_diagnostic()?;
+    Ok(())
+}
+
+/// Attaches `!range` to `aura_tensor_len` calls on tensors of a constant length, tags
+/// `aura_ai_infer` calls with `!aura.ai.infer`, and adds the debug info version flag.
+pub fn optimize_module<'ctx>(context: &'ctx Context, module: &Module<'ctx>) {
+    let range_kind = context.get_kind_id("range");
+    let infer_kind = context.get_kind_id("aura.ai.infer");
+    let infer_tag = context.metadata_node(&[context.metadata_string("aura.ai.infer").into()]);
+
+    for function in module.get_functions() {
+        // Tensor handle (the `aura_tensor_new` call) -> constant length.
+        let mut tensor_lens: HashMap<InstructionValue<'ctx>, u64> = HashMap::new();
+        for block in function.get_basic_blocks() {
+            for instr in block.get_instructions() {
+                let Some(callee) = called_name(instr) else {
+                    continue;
+                };
+                match callee.as_str() {
+                    "aura_tensor_new" => {
+                        if let Some(BasicValueEnum::IntValue(len)) = call_arg(instr, 0)
+                            && let Some(len) = len.get_zero_extended_constant()
+                        {
+                            tensor_lens.insert(instr, len);
+                        }
+                    }
+                    "aura_tensor_len" => {
+                        let len = match call_arg(instr, 0) {
+                            Some(BasicValueEnum::IntValue(t)) => t.as_instruction().and_then(|t| tensor_lens.get(&t)),
+                            _ => None,
+                        };
+                        if let (Some(len), AnyTypeEnum::IntType(ty)) = (len, instr.get_type())
+                            && instr.get_metadata(range_kind).is_none()
+                        {
+                            // Exact constant: [len, len+1)
+                            let range = context.metadata_node(&[
+                                ty.const_int(*len, false).into(),
+                                ty.const_int(len.saturating_add(1), false).into(),
+                            ]);
+                            let _ = instr.set_metadata(range, range_kind);
+                        }
+                    }
+                    "aura_ai_infer" => {
+                        if instr.get_metadata(infer_kind).is_none() {
+                            let _ = instr.set_metadata(infer_tag, infer_kind);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    if module.get_flag("Debug Info Version").is_none() {
+        let version = context.i32_type().const_int(3, false);
+        module.add_basic_value_flag("Debug Info Version", FlagBehavior::Warning, version);
+    }
+}
+
+/// The name of the function a direct call instruction calls.
+fn called_name(instr: InstructionValue<'_>) -> Option<String> {
+    if instr.get_opcode() != InstructionOpcode::Call {
+        return None;
+    }
+    let callee = CallSiteValue::try_from(instr).ok()?.get_called_fn_value()?;
+    Some(callee.get_name().to_string_lossy().into_owned())
+}
+
+/// Argument `index` of a call (operands are the arguments, then the callee).
+fn call_arg(instr: InstructionValue<'_>, index: u32) -> Option<BasicValueEnum<'_>> {
+    if index + 1 >= instr.get_num_operands() {
+        return None;
+    }
+    match instr.get_operand(index)? {
+        Operand::Value(v) => Some(v),
+        Operand::Block(_) => None,
+    }
+}
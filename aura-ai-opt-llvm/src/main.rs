@@ -0,0 +1,23 @@
+#![forbid(unsafe_code)]
+
+use std::path::PathBuf;
+
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+#[command(name = "aura-ai-opt-llvm", version, about = "Aura AI IR Optimizer (metadata pass over the LLVM module)")]
+struct Cli {
+    /// Input .ll file
+    input: PathBuf,
+
+    /// Output .ll file
+    #[arg(long)]
+    output: PathBuf,
+}
+
+fn main() -> miette::Result<()> {
+    let cli = Cli::parse();
+    aura_ai_opt_llvm::optimize_ll_file(&cli.input, &cli.output)?;
+    println!("wrote {}", cli.output.display());
+    Ok(())
+}
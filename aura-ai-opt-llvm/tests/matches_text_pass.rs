@@ -0,0 +1,66 @@
+use aura_ai_opt::{optimize_ll, HostFeatures};
+use aura_ai_opt_llvm::optimize_ll_module;
+
+const INPUT: &str = r#"target triple = "x86_64-unknown-linux-gnu"
+
+declare i32 @aura_tensor_new(i32)
+declare i32 @aura_tensor_len(i32)
+declare i32 @aura_ai_infer(i32, i32)
+
+define i32 @main(i32 %m, i32 %n) {
+entry:
+  %t = call i32 @aura_tensor_new(i32 64)
+  %len = call i32 @aura_tensor_len(i32 %t)
+  %u = call i32 @aura_tensor_new(i32 %n)
+  %open = call i32 @aura_tensor_len(i32 %u)
+  %out = call i32 @aura_ai_infer(i32 %m, i32 %t)
+  %sum = add i32 %len, %open
+  %r = add i32 %sum, %out
+  ret i32 %r
+}
+"#;
+
+/// The metadata node `line` attaches as `kind`, resolved to its definition in `ll`.
+fn attached(ll: &str, line: &str, kind: &str) -> Option<String> {
+    let at = ll.lines().find(|l| l.contains(line))?;
+    let id = at.split(&format!("!{kind} !")).nth(1)?.split(|c: char| !c.is_ascii_digit()).next()?;
+    let def = ll.lines().find_map(|l| l.strip_prefix(&format!("!{id} = ")))?;
+    Some(def.trim().to_string())
+}
+
+fn debug_info_version(ll: &str) -> bool {
+    ll.lines().any(|l| l.contains("!\"Debug Info Version\", i32 3"))
+}
+
+#[test]
+fn the_module_pass_adds_the_metadata_the_text_pass_does() {
+    let host = HostFeatures::default();
+    let text = optimize_ll(INPUT, host).expect("text pass");
+    let module = optimize_ll_module(INPUT, host).expect("module pass");
+
+    for out in [&text, &module] {
+        // Only the tensor of a constant length gets a range: exactly its length.
+        assert_eq!(
+            attached(out, "@aura_tensor_len(i32 %t)", "range").as_deref(),
+            Some("!{i32 64, i32 65}"),
+            "{out}"
+        );
+        assert!(!out.lines().any(|l| l.contains("@aura_tensor_len(i32 %u)") && l.contains("!range")), "{out}");
+        assert_eq!(
+            attached(out, "@aura_ai_infer(i32 %m, i32 %t)", "aura.ai.infer").as_deref(),
+            Some("!{!\"aura.ai.infer\"}"),
+            "{out}"
+        );
+        assert!(debug_info_version(out), "{out}");
+    }
+}
+
+#[test]
+fn existing_metadata_is_kept() {
+    let host = HostFeatures::default();
+    let once = optimize_ll_module(INPUT, host).expect("module pass");
+    let twice = optimize_ll_module(&once, host).expect("module pass");
+    assert_eq!(twice.matches("!range !").count(), 1, "{twice}");
+    assert_eq!(twice.matches("\"Debug Info Version\"").count(), 1, "{twice}");
+    assert_eq!(optimize_ll(&once, host).expect("text pass").matches("!range !").count(), 1);
+}
@@ -3,6 +3,12 @@
 use miette::{Diagnostic, IntoDiagnostic};
 use thiserror::Error;
 
+mod loops;
+mod multiversion;
+
+pub use loops::tile_and_fuse;
+pub use multiversion::multiversion_kernels;
+
 #[derive(Debug, Error, Diagnostic)]
 #[error("AI optimizer error: {message}")]
 #[diagnostic(code(aura::ai_opt))]
@@ -12,16 +18,34 @@ pub struct AiOptError {
 
 #[derive(Clone, Copy, Debug, Default)]
 pub struct HostFeatures {
+    pub avx2: bool,
     pub avx512: bool,
     pub amx: bool,
 }
 
-/// Injects a small amount of LLVM metadata.
+impl HostFeatures {
+    /// Width in bits of the widest vector registers the host supports; the baseline is
+    /// SSE2 / NEON.
+    pub fn vector_bits(&self) -> u32 {
+        if self.avx512 {
+            512
+        } else if self.avx2 {
+            256
+        } else {
+            128
+        }
+    }
+}
+
+/// Injects a small amount of LLVM metadata by rewriting the IR text. `aura-ai-opt-llvm` runs
+/// the same pass over a parsed module where LLVM is installed.
 ///
 /// Current implementation is intentionally conservative:
 /// - Adds module flags that enable loop vectorization hints.
 /// - If the IR contains Aura markers, it can rewrite them into LLVM metadata.
 pub fn optimize_ll(input_ll: &str, host: HostFeatures) -> Result<String, AiOptError> {
+    let rewritten = rewrite_kernels(input_ll);
+    let input_ll = rewritten.as_str();
     let mut lines: Vec<String> = input_ll.lines().map(|l| l.to_string()).collect();
 
     // === Pass 1: discover existing metadata IDs and module flags ===
@@ -88,13 +112,7 @@ pub fn optimize_ll(input_ll: &str, host: HostFeatures) -> Result<String, AiOptEr
         out.push('\n');
     }
 
-    out.push_str("\n; === aura-ai-opt metadata footer ===\n");
-    if host.avx512 {
-        out.push_str("; aura.host.avx512 = true\n");
-    }
-    if host.amx {
-        out.push_str("; aura.host.amx = true\n");
-    }
+    out.push_str(&host_footer(host));
 
     if !has_module_flags {
         let flag_id = next_md;
@@ -115,11 +133,30 @@ pub fn optimize_ll(input_ll: &str, host: HostFeatures) -> Result<String, AiOptEr
     Ok(out)
 }
 
+/// The rewrites both modes run on the IR text first: multi-versioning tensor kernels, then
+/// fusing and tiling their loops (in every version).
+pub fn rewrite_kernels(input_ll: &str) -> String {
+    tile_and_fuse(&multiversion_kernels(input_ll))
+}
+
+/// Comment lines recording the host features the pass ran for.
+pub fn host_footer(host: HostFeatures) -> String {
+    let mut out = String::from("\n; === aura-ai-opt metadata footer ===\n");
+    if host.avx512 {
+        out.push_str("; aura.host.avx512 = true\n");
+    }
+    if host.amx {
+        out.push_str("; aura.host.amx = true\n");
+    }
+    out
+}
+
 pub fn host_features() -> HostFeatures {
     // Only meaningful on x86_64; on other platforms just return false.
     #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
     {
         HostFeatures {
+            avx2: std::arch::is_x86_feature_detected!("avx2"),
             avx512: std::arch::is_x86_feature_detected!("avx512f"),
             // NOTE: AMX feature detection currently requires unstable Rust intrinsics.
             // Keep the field for future work, but default it to false on stable.
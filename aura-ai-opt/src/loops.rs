@@ -0,0 +1,345 @@
+//! Tiling hints and fusion for vectorized tensor loops whose lengths are known exactly.
+//!
+//! The LLVM backend emits each element-wise tensor loop as a vector loop (blocks `P.vec`,
+//! `P.vec.body`, `P.vec.exit`) ahead of the scalar loop `P`. When every tensor a vector loop
+//! touches has a constant length at least its bound (the lengths `Tensor<T, [..]>` shape
+//! proofs fix, which reach the IR as `aura_tensor_new` arguments), its trip count is exact:
+//!
+//! - The loop gets `!llvm.loop` hints: an unroll count that divides the trip count (a tile
+//!   of whole vectors with no remainder check), no further vectorization, and
+//!   `aura.loop.tile` recording the tile in elements.
+//! - A vector loop that directly follows another over the same range is fused into it, so
+//!   both bodies run per vector. Its guard joins the first loop's; when the fused loop did
+//!   not run, both scalar loops still cover the whole range. Fusion requires the second
+//!   loop not to write a tensor the first one touches: the first loop's later vectors must
+//!   not see the second loop's writes.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{attach_metadata_before_comment, max_metadata_id, parse_tensor_new_call};
+
+/// Vectors per tile at most.
+const MAX_TILE_VECTORS: u64 = 8;
+
+/// Fuses and annotates the tensor loops of every function in `input_ll`.
+pub fn tile_and_fuse(input_ll: &str) -> String {
+    let mut next_md = max_metadata_id(input_ll).map(|n| n + 1).unwrap_or(0);
+    let mut nodes = Vec::new();
+    let lines: Vec<String> = input_ll.lines().map(str::to_string).collect();
+    let mut out: Vec<String> = Vec::with_capacity(lines.len());
+
+    let mut i = 0;
+    while i < lines.len() {
+        let end = if lines[i].starts_with("define ") {
+            lines[i..].iter().position(|l| l == "}").map(|n| i + n)
+        } else {
+            None
+        };
+        let Some(end) = end else {
+            out.push(lines[i].clone());
+            i += 1;
+            continue;
+        };
+        let mut function = lines[i..=end].to_vec();
+        while fuse_once(&mut function) {}
+        tile(&mut function, &mut next_md, &mut nodes);
+        out.extend(function);
+        i = end + 1;
+    }
+
+    let mut text = out.join("\n");
+    text.push('\n');
+    for node in nodes {
+        text.push_str(&node);
+        text.push('\n');
+    }
+    text
+}
+
+/// What a vector loop `P.vec` reads from its preheader.
+struct VecLoop {
+    prefix: String,
+    start: String,
+    bound: String,
+    lanes: u64,
+    /// Tensor handles, from the `aura_tensor_data` calls of its preheader.
+    tensors: Vec<String>,
+    has_body: bool,
+}
+
+fn find_loops(f: &[String]) -> Vec<VecLoop> {
+    let mut loops = Vec::new();
+    for line in f {
+        let Some(prefix) = line.strip_suffix(".vec:") else {
+            continue;
+        };
+        if prefix.contains(char::is_whitespace) {
+            continue;
+        }
+        let p = format!("%{prefix}.vec");
+        let span = f.iter().find_map(|l| l.trim().strip_prefix(&format!("{p}.span = sub i32 ")));
+        let lanes = f.iter().find_map(|l| l.trim().strip_prefix(&format!("{p}.next = add i32 {p}.j, ")));
+        let (start, bound, lanes) = match (span.and_then(|s| s.split_once(", ")), lanes) {
+            (Some((bound, start)), Some(lanes)) => (start, bound, lanes.parse().ok()),
+            // A fused loop keeps its preheader but no body.
+            _ => match f.iter().find_map(|l| l.trim().strip_prefix(&format!("{p}.from = select i1 "))) {
+                Some(_) => ("", "", None),
+                None => continue,
+            },
+        };
+        let tensors = f
+            .iter()
+            .filter_map(|l| {
+                let rest = l.trim().strip_prefix(&format!("{p}.data"))?;
+                rest.split_once(" = call ptr @aura_tensor_data(i32 ")?.1.strip_suffix(')').map(str::to_string)
+            })
+            .collect();
+        loops.push(VecLoop {
+            prefix: prefix.to_string(),
+            start: start.to_string(),
+            bound: bound.to_string(),
+            lanes: lanes.unwrap_or(0),
+            tensors,
+            has_body: f.iter().any(|l| *l == format!("{prefix}.vec.body:")),
+        });
+    }
+    loops
+}
+
+/// Constant lengths of the tensors `f` creates.
+fn known_lengths(f: &[String]) -> HashMap<String, i64> {
+    f.iter().filter_map(|l| parse_tensor_new_call(l)).collect()
+}
+
+/// Whether every tensor of `l` is known to hold at least its (constant) bound.
+fn exact(l: &VecLoop, lengths: &HashMap<String, i64>) -> Option<(i64, i64)> {
+    let (start, bound) = (l.start.parse::<i64>().ok()?, l.bound.parse::<i64>().ok()?);
+    let fits = l.tensors.iter().all(|t| lengths.get(t).is_some_and(|len| *len >= bound));
+    (fits && start < bound).then_some((start, bound))
+}
+
+/// Block `label`: the index of its label line and of its last line.
+fn block(f: &[String], label: &str) -> Option<(usize, usize)> {
+    let start = f.iter().position(|l| *l == format!("{label}:"))?;
+    let end = (start + 1..f.len())
+        .find(|&i| (f[i].ends_with(':') && !f[i].starts_with(' ')) || f[i] == "}")
+        .unwrap_or(f.len());
+    let last = (start..end).rev().find(|&i| !f[i].trim().is_empty())?;
+    Some((start, last))
+}
+
+/// Number of branches to `label` (phi operands are not branches).
+fn branches_to(f: &[String], label: &str) -> usize {
+    let target = format!("label %{label}");
+    f.iter()
+        .map(|l| {
+            l.match_indices(&target)
+                .filter(|(at, _)| {
+                    !l[at + target.len()..]
+                        .starts_with(|c: char| c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-')
+                })
+                .count()
+        })
+        .sum()
+}
+
+/// Replaces whole-name occurrences of SSA value `from` with `to`.
+fn rename(line: &str, from: &str, to: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(at) = rest.find(from) {
+        let after = &rest[at + from.len()..];
+        out.push_str(&rest[..at]);
+        if after.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '.' || c == '_') {
+            out.push_str(from);
+        } else {
+            out.push_str(to);
+        }
+        rest = after;
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Tensors the bodies in vector loop `host`'s body load from or store to, by handle.
+fn accessed(f: &[String], host: &str) -> (HashSet<String>, HashSet<String>) {
+    let data: HashMap<String, String> = f
+        .iter()
+        .filter_map(|l| {
+            let (name, rest) = l.trim().split_once(" = call ptr @aura_tensor_data(i32 ")?;
+            Some((name.to_string(), rest.strip_suffix(')')?.to_string()))
+        })
+        .collect();
+    let (mut reads, mut writes) = (HashSet::new(), HashSet::new());
+    let Some((start, last)) = block(f, &format!("{host}.vec.body")) else {
+        return (reads, writes);
+    };
+    for l in &f[start..=last] {
+        let Some((name, rest)) = l.trim().split_once(" = getelementptr inbounds i32, ptr ") else {
+            continue;
+        };
+        let Some(tensor) = rest.split(',').next().and_then(|d| data.get(d)) else {
+            continue;
+        };
+        if name.contains(".vec.out") {
+            writes.insert(tensor.clone());
+        } else {
+            reads.insert(tensor.clone());
+        }
+    }
+    (reads, writes)
+}
+
+/// The vector loop whose body runs `prefix`'s vectors: itself, or the loop it was fused into.
+fn host_of(f: &[String], loops: &[VecLoop], prefix: &str) -> Option<String> {
+    let l = loops.iter().find(|l| l.prefix == prefix)?;
+    if l.has_body {
+        return Some(prefix.to_string());
+    }
+    let select = format!("%{prefix}.vec.from = select i1 ");
+    let line = f.iter().find_map(|l| l.trim().strip_prefix(select.as_str()))?;
+    let end = line.split(", i32 ").nth(1)?;
+    end.strip_prefix('%')?.strip_suffix(".vec.end").map(str::to_string)
+}
+
+/// The vector loop that runs right before `z`'s: `z.vec` is entered only from a block that
+/// does nothing but branch there, and that block is the exit of a tensor loop's scalar
+/// loop. Returns that loop's prefix.
+fn predecessor(f: &[String], loops: &[VecLoop], z: &str) -> Option<String> {
+    if branches_to(f, &format!("{z}.vec")) != 1 {
+        return None;
+    }
+    let jump = format!("  br label %{z}.vec");
+    let at = f.iter().position(|l| *l == jump)?;
+    let label = f[at - 1].strip_suffix(':')?;
+    loops.iter().find_map(|q| {
+        // The scalar header is where `q.vec.exit` continues; its loop exits to `label`.
+        let (_, exit_last) = block(f, &format!("{}.vec.exit", q.prefix))?;
+        let header = f[exit_last].trim().strip_prefix("br label %")?;
+        let (_, header_last) = block(f, header)?;
+        let exits_to_label = f[header_last].trim().starts_with("br i1 ")
+            && f[header_last].trim().ends_with(&format!("label %{label}"));
+        exits_to_label.then(|| q.prefix.clone())
+    })
+}
+
+/// Fuses one vector loop into the one before it; false when no pair qualifies.
+fn fuse_once(f: &mut Vec<String>) -> bool {
+    let loops = find_loops(f);
+    let lengths = known_lengths(f);
+    for z in loops.iter().filter(|l| l.has_body) {
+        let Some(x) = predecessor(f, &loops, &z.prefix)
+            .and_then(|q| host_of(f, &loops, &q))
+            .and_then(|h| loops.iter().find(|l| l.prefix == h && l.has_body))
+        else {
+            continue;
+        };
+        if x.lanes != z.lanes || x.start != z.start || x.bound != z.bound {
+            continue;
+        }
+        if exact(x, &lengths).is_none() || exact(z, &lengths).is_none() {
+            continue;
+        }
+        let (x_reads, x_writes) = accessed(f, &x.prefix);
+        let (_, z_writes) = accessed(f, &z.prefix);
+        if z_writes.iter().any(|t| x_reads.contains(t) || x_writes.contains(t)) {
+            continue;
+        }
+        if fuse(f, &x.prefix, &z.prefix, &z.start).is_some() {
+            return true;
+        }
+    }
+    false
+}
+
+fn fuse(f: &mut Vec<String>, x: &str, z: &str, start: &str) -> Option<()> {
+    let (xpre, xpre_last) = block(f, &format!("{x}.vec"))?;
+    let (zpre, zpre_last) = block(f, &format!("{z}.vec"))?;
+    let (zbody, zbody_last) = block(f, &format!("{z}.vec.body"))?;
+    let (zexit, zexit_last) = block(f, &format!("{z}.vec.exit"))?;
+    if !(xpre < zpre && zpre < zbody && zbody < zexit) {
+        return None;
+    }
+    let phi = (zexit..=zexit_last).find(|&i| f[i].trim().starts_with(&format!("%{z}.vec.i = phi ")))?;
+
+    // `z`'s preheader only reads values defined before `x`'s, so it can move there.
+    let defined_before: HashSet<&str> = f[..xpre]
+        .iter()
+        .filter_map(|l| l.trim().split_once(" = ").map(|(d, _)| d))
+        .chain(f[0].split(['(', ',', ')']).filter_map(|p| p.split_whitespace().last()))
+        .collect();
+    let own = format!("%{z}.vec.");
+    let hoisted: Vec<String> = f[zpre + 1..zpre_last].to_vec();
+    let operands_ok = hoisted.iter().all(|l| {
+        l.split(|c: char| c.is_whitespace() || c == ',' || c == '(' || c == ')')
+            .filter(|t| t.starts_with('%') && !t.starts_with(&own))
+            .all(|t| defined_before.contains(t))
+    });
+    if !operands_ok {
+        return None;
+    }
+    let x_ok = f[xpre_last].trim().strip_prefix("br i1 ")?.split(',').next()?.to_string();
+    let z_ok = f[zpre_last].trim().strip_prefix("br i1 ")?.split(',').next()?.to_string();
+    let fused = format!("%{x}.vec.with.{z}");
+
+    // `z`'s lane operations, indexed by `x`'s position.
+    let z_at = format!("%{z}.vec.at");
+    let ops_from = (zbody..=zbody_last).find(|&i| f[i].trim().starts_with(&format!("{z_at} = ")))? + 1;
+    let ops_to = (zbody..=zbody_last).find(|&i| f[i].trim().starts_with(&format!("%{z}.vec.next = ")))?;
+    let ops: Vec<String> = f[ops_from..ops_to].iter().map(|l| rename(l, &z_at, &format!("%{x}.vec.at"))).collect();
+    let (xbody, xbody_last) = block(f, &format!("{x}.vec.body"))?;
+    let x_next = (xbody..=xbody_last).find(|&i| f[i].trim().starts_with(&format!("%{x}.vec.next = ")))?;
+
+    // Rewrite from the bottom up so earlier indices stay valid.
+    f[phi] = format!("  %{z}.vec.i = phi i32 [ %{z}.vec.from, %{z}.vec ]");
+    let blank = f.get(zbody_last + 1).is_some_and(|l| l.trim().is_empty());
+    f.drain(zbody..=zbody_last + usize::from(blank));
+    f.splice(
+        zpre + 1..=zpre_last,
+        [
+            format!("  %{z}.vec.from = select i1 {fused}, i32 %{x}.vec.end, i32 {start}"),
+            format!("  br label %{z}.vec.exit"),
+        ],
+    );
+    f.splice(x_next..x_next, ops);
+    let branch = f[xpre_last].replacen(&x_ok, &fused, 1);
+    f.splice(
+        xpre_last..=xpre_last,
+        hoisted.into_iter().chain([format!("  {fused} = and i1 {x_ok}, {z_ok}"), branch]),
+    );
+    Some(())
+}
+
+/// Attaches tiling hints to the vector loops with an exact trip count.
+fn tile(f: &mut [String], next_md: &mut u32, nodes: &mut Vec<String>) {
+    let lengths = known_lengths(f);
+    for l in find_loops(f).iter().filter(|l| l.has_body && l.lanes > 0) {
+        let Some((start, bound)) = exact(l, &lengths) else {
+            continue;
+        };
+        let vectors = (bound - start) as u64 / l.lanes;
+        if vectors == 0 {
+            continue;
+        }
+        let tile = (1..=MAX_TILE_VECTORS.min(vectors)).rev().find(|d| vectors.is_multiple_of(*d)).unwrap_or(1);
+        let backedge = format!("  br i1 %{p}.vec.more, label %{p}.vec.body, label %{p}.vec.exit", p = l.prefix);
+        let Some(line) = f.iter_mut().find(|line| line.starts_with(&backedge) && !line.contains("!llvm.loop")) else {
+            continue;
+        };
+
+        let id = *next_md;
+        let hints = [
+            "!{!\"llvm.loop.mustprogress\"}".to_string(),
+            format!("!{{!\"llvm.loop.unroll.count\", i32 {tile}}}"),
+            "!{!\"llvm.loop.vectorize.enable\", i1 false}".to_string(),
+            format!("!{{!\"aura.loop.tile\", i32 {}}}", tile * l.lanes),
+        ];
+        let refs: Vec<String> = (1..=hints.len() as u32).map(|k| format!("!{}", id + k)).collect();
+        nodes.push(format!("!{id} = distinct !{{!{id}, {}}}", refs.join(", ")));
+        for (k, hint) in hints.into_iter().enumerate() {
+            nodes.push(format!("!{} = {hint}", id + 1 + k as u32));
+        }
+        *next_md += 5;
+        *line = attach_metadata_before_comment(line, &format!("!llvm.loop !{id}"));
+    }
+}
@@ -0,0 +1,216 @@
+//! Function multi-versioning for tensor kernels on x86.
+//!
+//! A kernel is a function with a vectorized tensor loop (a `.vec.body` block, as the LLVM
+//! backend emits them). Each one is cloned per feature tier with matching
+//! `"target-features"`, and its name becomes a dispatch shim that picks a clone from
+//! `aura_cpu_features()` (see aura_stdlib.h). The binary therefore uses AVX-512 (or AMX)
+//! where the CPU has it and still runs elsewhere, whatever host it was built on.
+//!
+//! The shim is an ordinary function rather than an `ifunc`, which COFF and Mach-O lack; the
+//! runtime caches the feature word, so a call costs a load and a branch. Functions with
+//! debug info keep a single version, since their subprogram cannot be attached twice.
+
+/// Clone tiers, most capable first: suffix, bit in `aura_cpu_features()`, features.
+const TIERS: &[(&str, u32, &str)] = &[
+    ("amx", 2, "+avx512f,+avx512bw,+avx512vl,+avx512dq,+amx-tile,+amx-int8,+amx-bf16"),
+    ("avx512", 1, "+avx512f,+avx512bw,+avx512vl,+avx512dq"),
+];
+
+const FEATURES_FN: &str = "aura_cpu_features";
+
+/// Multi-versions the kernels of an x86 module; other modules are returned unchanged.
+pub fn multiversion_kernels(input_ll: &str) -> String {
+    if !is_x86_module(input_ll) {
+        return input_ll.to_string();
+    }
+    let lines: Vec<&str> = input_ll.lines().collect();
+    let mut out = String::with_capacity(input_ll.len() * 4);
+    let mut versioned = 0usize;
+
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let end = if line.starts_with("define ") {
+            lines[i..].iter().position(|l| *l == "}").map(|n| i + n)
+        } else {
+            None
+        };
+        let Some(end) = end else {
+            out.push_str(line);
+            out.push('\n');
+            i += 1;
+            continue;
+        };
+
+        let body = &lines[i + 1..end];
+        match parse_define(line).filter(|d| is_kernel(d, body)) {
+            Some(def) => {
+                emit_versions(&mut out, &def, body);
+                versioned += 1;
+            }
+            None => {
+                for l in &lines[i..=end] {
+                    out.push_str(l);
+                    out.push('\n');
+                }
+            }
+        }
+        i = end + 1;
+    }
+
+    let declared = lines
+        .iter()
+        .any(|l| (l.starts_with("declare ") || l.starts_with("define ")) && l.contains(&format!("@{FEATURES_FN}(")));
+    if versioned > 0 && !declared {
+        out.push_str(&format!("\ndeclare i32 @{FEATURES_FN}()\n"));
+    }
+    out
+}
+
+/// `define <ret> @<name>(<params>)<attrs> {`, split into its parts.
+struct Define<'a> {
+    /// Everything between `define ` and `@`: linkage, return attributes and type.
+    head: &'a str,
+    ret: &'a str,
+    name: &'a str,
+    params: &'a str,
+    /// Function attributes, without `"target-features"`.
+    attrs: String,
+    has_debug_info: bool,
+}
+
+fn parse_define(line: &str) -> Option<Define<'_>> {
+    let rest = line.strip_prefix("define ")?;
+    let at = rest.find(" @")?;
+    let head = &rest[..at];
+    let open = at + 2 + rest[at + 2..].find('(')?;
+    let name = &rest[at + 2..open];
+    let close = open + matching_paren(&rest[open..])?;
+    let params = &rest[open + 1..close];
+    let tail = rest[close + 1..].trim_end().strip_suffix('{')?.trim();
+
+    let mut attrs = tail.to_string();
+    if let Some(start) = attrs.find("\"target-features\"=\"") {
+        let value = start + "\"target-features\"=\"".len();
+        let stop = value + attrs[value..].find('"')? + 1;
+        attrs.replace_range(start..stop, "");
+    }
+    let attrs = attrs.split_whitespace().collect::<Vec<_>>().join(" ");
+    Some(Define {
+        head,
+        ret: head.split_whitespace().last()?,
+        name,
+        params,
+        has_debug_info: attrs.contains("!dbg"),
+        attrs,
+    })
+}
+
+/// Offset of the `)` closing the `(` `s` starts with.
+fn matching_paren(s: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn is_kernel(def: &Define<'_>, body: &[&str]) -> bool {
+    !def.has_debug_info
+        // Every parameter must be named for the shim to forward it; struct returns are
+        // left alone since the return type is read as the last token of `head`.
+        && def
+            .params
+            .split(',')
+            .all(|p| p.trim().is_empty() || p.split_whitespace().last().is_some_and(|n| n.starts_with('%')))
+        && !def.head.contains('}')
+        && body.iter().any(|l| l.trim_end().ends_with(".vec.body:"))
+}
+
+fn emit_versions(out: &mut String, def: &Define<'_>, body: &[&str]) {
+    let attrs = |features: Option<&str>| -> String {
+        let mut a = def.attrs.clone();
+        if let Some(f) = features {
+            if !a.is_empty() {
+                a.push(' ');
+            }
+            a.push_str(&format!("\"target-features\"=\"{f}\""));
+        }
+        if a.is_empty() { a } else { format!(" {a}") }
+    };
+    // Clones are internal to the module: only the shim keeps the kernel's linkage.
+    let clone_head = format!("internal {}", strip_linkage(def.head));
+
+    let tiers = TIERS.iter().map(|(s, _, f)| (*s, Some(*f))).chain([("default", None)]);
+    for (suffix, features) in tiers {
+        out.push_str(&format!(
+            "define {clone_head} @{}.{suffix}({}){} {{\n",
+            def.name,
+            def.params,
+            attrs(features)
+        ));
+        for l in body {
+            out.push_str(l);
+            out.push('\n');
+        }
+        out.push_str("}\n\n");
+    }
+
+    // Forwarded as declared: call arguments accept the same `<type> <attrs> %name` form.
+    let args = def.params;
+    out.push_str(&format!("define {} @{}({}){} {{\n", def.head, def.name, def.params, attrs(None)));
+    out.push_str("entry:\n");
+    out.push_str(&format!("  %mv.features = call i32 @{FEATURES_FN}()\n"));
+    for (k, (suffix, bit, _)) in TIERS.iter().enumerate() {
+        let next = match TIERS.get(k + 1) {
+            Some((s, _, _)) => format!("mv.check.{s}"),
+            None => "mv.use.default".to_string(),
+        };
+        out.push_str(&format!("  %mv.bit.{suffix} = and i32 %mv.features, {bit}\n"));
+        out.push_str(&format!("  %mv.has.{suffix} = icmp ne i32 %mv.bit.{suffix}, 0\n"));
+        out.push_str(&format!("  br i1 %mv.has.{suffix}, label %mv.use.{suffix}, label %{next}\n\n"));
+        out.push_str(&format!("mv.use.{suffix}:\n"));
+        emit_forward(out, def, suffix, args);
+        if let Some((s, _, _)) = TIERS.get(k + 1) {
+            out.push_str(&format!("mv.check.{s}:\n"));
+        }
+    }
+    out.push_str("mv.use.default:\n");
+    emit_forward(out, def, "default", args);
+    out.push_str("}\n");
+}
+
+fn emit_forward(out: &mut String, def: &Define<'_>, suffix: &str, args: &str) {
+    if def.ret == "void" {
+        out.push_str(&format!("  tail call void @{}.{suffix}({args})\n  ret void\n\n", def.name));
+    } else {
+        out.push_str(&format!(
+            "  %mv.r.{suffix} = tail call {} @{}.{suffix}({args})\n  ret {} %mv.r.{suffix}\n\n",
+            def.ret, def.name, def.ret
+        ));
+    }
+}
+
+fn strip_linkage(head: &str) -> String {
+    const LINKAGE: &[&str] = &["private", "internal", "external", "linkonce_odr", "weak_odr", "dso_local"];
+    head.split_whitespace()
+        .filter(|w| !LINKAGE.contains(w))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn is_x86_module(input_ll: &str) -> bool {
+    input_ll.lines().any(|l| {
+        l.starts_with("target triple")
+            && ["\"x86_64", "\"i386", "\"i486", "\"i586", "\"i686"].iter().any(|arch| l.contains(arch))
+    })
+}
@@ -0,0 +1,45 @@
+#![allow(dead_code)]
+
+use std::process::Command;
+
+use aura_backend_llvm::{emit_llvm_ir_for_target, NativeTarget, OptConfig, OptLevel};
+
+/// The IR the LLVM backend emits for `src` on x86-64 at -O2, where tensor loops are vectorized.
+pub fn emit(src: &str) -> String {
+    let program = aura_parse::parse_source(src).expect("parse");
+    aura_core::Checker::new().check_program(&program).expect("sema");
+    let module = aura_core::lower_program(&program).expect("lower");
+    let target = NativeTarget::parse("x86_64-unknown-linux-gnu").expect("target");
+    emit_llvm_ir_for_target(&module, None, &target, &OptConfig::new(OptLevel::O2))
+        .expect("emit")
+        .llvm_ir
+}
+
+/// Checks that `llvm-as` accepts `ll`; skipped when it is not installed. LLVM releases before
+/// 17 only read `ptr` with `-opaque-pointers`.
+pub fn assert_assembles(name: &str, ll: &str) {
+    if Command::new("llvm-as").arg("--version").output().is_err() {
+        eprintln!("skipping assembly check: no llvm-as in PATH");
+        return;
+    }
+    let dir = std::env::temp_dir();
+    let input = dir.join(format!("aura-ai-opt-{name}-{}.ll", std::process::id()));
+    let output = input.with_extension("bc");
+    std::fs::write(&input, ll).expect("write ll");
+    let run = |extra: &[&str]| {
+        Command::new("llvm-as")
+            .args(extra)
+            .arg(&input)
+            .arg("-o")
+            .arg(&output)
+            .output()
+            .expect("run llvm-as")
+    };
+    let mut out = run(&[]);
+    if !out.status.success() && String::from_utf8_lossy(&out.stderr).contains("-opaque-pointers") {
+        out = run(&["-opaque-pointers"]);
+    }
+    let _ = std::fs::remove_file(&input);
+    let _ = std::fs::remove_file(&output);
+    assert!(out.status.success(), "llvm-as rejected the module:\n{}\n{ll}", String::from_utf8_lossy(&out.stderr));
+}
@@ -0,0 +1,64 @@
+mod common;
+use common::{assert_assembles, emit};
+
+use aura_ai_opt::tile_and_fuse;
+
+/// Two element-wise loops over the same 64 elements of `a`, `x` and `y`.
+fn two_loops(first: &str, second: &str) -> String {
+    format!(
+        "import aura::tensor\n\ncell main() -> u32:\n    val n: u32 = 64\n    val mut a = tensor::new(n)\n    val mut x = tensor::new(n)\n    val mut y = tensor::new(n)\n    val mut i: u32 = 0\n    while i < n:\n        {first}\n        i = i + 1\n    val mut j: u32 = 0\n    while j < n:\n        {second}\n        j = j + 1\n    yield x.get(3) + y.get(3)\n"
+    )
+}
+
+#[test]
+fn adjacent_loops_over_the_same_range_are_fused_and_tiled() {
+    let ll = emit(&two_loops("x.set(i, a.get(i) * a.get(i))", "y.set(j, a.get(j) + a.get(j))"));
+    assert!(ll.contains("bb4.vec.body:"), "{ll}");
+    let out = tile_and_fuse(&ll);
+
+    // The second loop's vectors run in the first loop's body, at its position, once both
+    // guards pass; its own vector loop is gone.
+    assert!(!out.contains("bb4.vec.body:"), "{out}");
+    assert!(out.contains("%bb1.vec.with.bb4 = and i1 %bb1.vec.ok1, %bb4.vec.ok1"), "{out}");
+    assert!(out.contains("br i1 %bb1.vec.with.bb4, label %bb1.vec.body, label %bb1.vec.exit"), "{out}");
+    assert!(out.contains("ptr %bb4.vec.data1, i64 %bb1.vec.at"), "{out}");
+    // Its scalar loop starts where the fused loop stopped, or at the start if it did not run.
+    assert!(out.contains("%bb4.vec.from = select i1 %bb1.vec.with.bb4, i32 %bb1.vec.end, i32 0"), "{out}");
+    assert!(out.contains("%bb4.vec.i = phi i32 [ %bb4.vec.from, %bb4.vec ]"), "{out}");
+
+    // 64 elements are 16 vectors of 4: tiles of 8 vectors divide them exactly.
+    assert_eq!(out.matches("!llvm.loop !").count(), 1, "{out}");
+    assert!(out.contains("br i1 %bb1.vec.more, label %bb1.vec.body, label %bb1.vec.exit, !llvm.loop !0"), "{out}");
+    assert!(out.contains("!{!\"llvm.loop.unroll.count\", i32 8}"), "{out}");
+    assert!(out.contains("!{!\"aura.loop.tile\", i32 32}"), "{out}");
+    assert_assembles("fused", &out);
+}
+
+#[test]
+fn a_second_loop_writing_a_tensor_the_first_touches_is_not_fused() {
+    // It writes what the first loop reads, then what the first loop writes.
+    for (name, second) in [("reads", "a.set(j, x.get(j) + x.get(j))"), ("writes", "x.set(j, a.get(j) + a.get(j))")] {
+        let out = tile_and_fuse(&emit(&two_loops("x.set(i, a.get(i) * a.get(i))", second)));
+        assert!(!out.contains(".vec.with."), "{out}");
+        assert!(out.contains("bb4.vec.body:"), "{out}");
+        // Each loop is still tiled on its own.
+        assert_eq!(out.matches("!llvm.loop !").count(), 2, "{out}");
+        assert_assembles(name, &out);
+    }
+}
+
+#[test]
+fn a_loop_without_a_constant_bound_gets_no_tile_hint() {
+    let src = "import aura::tensor\n\ncell main(n: u32) -> u32:\n    val a = tensor::new(n)\n    val mut x = tensor::new(n)\n    val mut i: u32 = 0\n    while i < n:\n        x.set(i, a.get(i) * a.get(i))\n        i = i + 1\n    yield x.get(0)\n";
+    let ll = emit(src);
+    assert!(ll.contains("bb1.vec.body:"), "{ll}");
+    assert_eq!(tile_and_fuse(&ll), ll);
+}
+
+#[test]
+fn running_again_on_fused_output_changes_nothing() {
+    for second in ["y.set(j, a.get(j) + a.get(j))", "a.set(j, x.get(j) + x.get(j))"] {
+        let once = tile_and_fuse(&emit(&two_loops("x.set(i, a.get(i) * a.get(i))", second)));
+        assert_eq!(tile_and_fuse(&once), once);
+    }
+}
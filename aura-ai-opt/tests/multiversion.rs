@@ -0,0 +1,119 @@
+mod common;
+use common::{assert_assembles, emit};
+
+use std::collections::HashMap;
+
+use aura_ai_opt::{multiversion_kernels, rewrite_kernels};
+
+/// `scale` has a vectorized tensor loop; `main` has none.
+const SCALE: &str = "import aura::tensor\n\ncell scale(k: u32) -> u32:\n    val n: u32 = 64\n    val a = tensor::new(n)\n    val mut x = tensor::new(n)\n    val mut i: u32 = 0\n    while i < n:\n        x.set(i, a.get(i) * k)\n        i = i + 1\n    yield x.get(3)\n\ncell main() -> u32:\n    yield scale(3)\n";
+
+/// The `define` line and body of function `name`.
+fn function<'a>(ll: &'a str, name: &str) -> Vec<&'a str> {
+    let lines: Vec<&str> = ll.lines().collect();
+    let start = lines
+        .iter()
+        .position(|l| l.starts_with("define ") && l.contains(&format!(" @{name}(")))
+        .unwrap_or_else(|| panic!("no function @{name} in\n{ll}"));
+    let end = start + lines[start..].iter().position(|l| *l == "}").expect("function end");
+    lines[start..=end].to_vec()
+}
+
+/// The bit `aura_stdlib.h` reports feature `name` with.
+fn runtime_bit(name: &str) -> u32 {
+    let header = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/../aura-stdlib/include/aura_stdlib.h"))
+        .expect("read aura_stdlib.h");
+    let value = header
+        .lines()
+        .find_map(|l| l.strip_prefix(&format!("#define {name} ")))
+        .unwrap_or_else(|| panic!("aura_stdlib.h does not define {name}"));
+    value.trim().trim_end_matches('u').parse().expect("feature bit")
+}
+
+/// Follows the dispatch shim `shim` for a CPU whose `aura_cpu_features()` is `features`;
+/// returns the function it calls.
+fn dispatch(shim: &[&str], features: u32) -> String {
+    let mut ints: HashMap<&str, u32> = HashMap::new();
+    let mut bools: HashMap<&str, bool> = HashMap::new();
+    let mut at = 1;
+    loop {
+        let line = shim[at].trim();
+        at += 1;
+        if let Some((dst, _)) = line.split_once(" = call i32 @aura_cpu_features()") {
+            ints.insert(dst, features);
+        } else if let Some((dst, rest)) = line.split_once(" = and i32 ") {
+            let (src, mask) = rest.split_once(", ").expect("and operands");
+            ints.insert(dst, ints[src] & mask.parse::<u32>().expect("mask"));
+        } else if let Some((dst, rest)) = line.split_once(" = icmp ne i32 ") {
+            let src = rest.strip_suffix(", 0").expect("compared with 0");
+            bools.insert(dst, ints[src] != 0);
+        } else if let Some(rest) = line.strip_prefix("br i1 ") {
+            let [cond, then, otherwise] = rest.split(", ").collect::<Vec<_>>()[..] else {
+                panic!("unexpected branch {line}");
+            };
+            let target = if bools[cond] { then } else { otherwise };
+            let label = format!("{}:", target.strip_prefix("label %").expect("label"));
+            at = shim.iter().position(|l| *l == label).expect("branch target") + 1;
+        } else if let Some((_, callee)) = line.split_once(" @") {
+            return callee.split('(').next().expect("callee").to_string();
+        }
+    }
+}
+
+#[test]
+fn kernels_are_cloned_per_feature_tier() {
+    let ll = emit(SCALE);
+    let out = multiversion_kernels(&ll);
+
+    let clone = |suffix: &str| function(&out, &format!("scale.{suffix}"));
+    assert!(
+        clone("amx")[0].starts_with("define internal i32 @scale.amx(i32 %v0)")
+            && clone("amx")[0].contains(
+                "\"target-features\"=\"+avx512f,+avx512bw,+avx512vl,+avx512dq,+amx-tile,+amx-int8,+amx-bf16\""
+            ),
+        "{out}"
+    );
+    assert!(
+        clone("avx512")[0].contains("\"target-features\"=\"+avx512f,+avx512bw,+avx512vl,+avx512dq\""),
+        "{out}"
+    );
+    assert!(!clone("default")[0].contains("target-features"), "{out}");
+    // Every clone runs the original body; the name stays external, as the shim.
+    let body = &function(&ll, "scale")[1..];
+    for suffix in ["amx", "avx512", "default"] {
+        assert_eq!(&clone(suffix)[1..], body);
+    }
+    assert!(function(&out, "scale")[0].starts_with("define i32 @scale(i32 %v0)"), "{out}");
+
+    // Functions without a vector loop keep a single version.
+    assert_eq!(function(&out, "aura_main"), function(&ll, "aura_main"));
+    assert!(!out.contains("@aura_main."), "{out}");
+    assert_eq!(out.matches("declare i32 @aura_cpu_features()").count(), 1, "{out}");
+    assert_assembles("multiversion", &out);
+}
+
+#[test]
+fn the_dispatcher_calls_the_generic_version_without_the_features() {
+    let out = multiversion_kernels(&emit(SCALE));
+    let shim = function(&out, "scale");
+    let (avx512, amx) = (runtime_bit("AURA_CPU_AVX512"), runtime_bit("AURA_CPU_AMX"));
+
+    assert_eq!(dispatch(&shim, 0), "scale.default");
+    assert_eq!(dispatch(&shim, avx512), "scale.avx512");
+    assert_eq!(dispatch(&shim, avx512 | amx), "scale.amx");
+    // Arguments are forwarded as the kernel declares them.
+    assert!(shim.contains(&"  %mv.r.default = tail call i32 @scale.default(i32 %v0)"), "{out}");
+    assert!(shim.contains(&"  ret i32 %mv.r.default"), "{out}");
+}
+
+#[test]
+fn clones_are_tiled_and_the_module_still_parses() {
+    let out = rewrite_kernels(&emit(SCALE));
+    // One tiled vector loop per clone.
+    assert_eq!(out.matches("!llvm.loop !").count(), 3, "{out}");
+    assert_assembles("rewrite-kernels", &out);
+
+    // Other targets have no clones to pick from.
+    let arm = emit(SCALE).replace("x86_64-unknown-linux-gnu", "aarch64-unknown-linux-gnu");
+    assert_eq!(multiversion_kernels(&arm), arm);
+}
@@ -51,6 +51,8 @@ pub enum Stmt {
     CellDef(CellDef),
     ExternCell(ExternCell),
     UnsafeBlock(UnsafeBlock),
+    Scope(ScopeBlock),
+    Region(RegionBlock),
     Layout(LayoutBlock),
     Render(RenderBlock),
     Prop(PropStmt),
@@ -58,6 +60,11 @@ pub enum Stmt {
     If(IfStmt),
     Match(MatchStmt),
     While(WhileStmt),
+    For(ForStmt),
+    Break(BreakStmt),
+    Continue(ContinueStmt),
+    Return(ReturnStmt),
+    Defer(DeferStmt),
     Requires(RequiresStmt),
     Ensures(EnsuresStmt),
     Assert(AssertStmt),
@@ -67,6 +74,44 @@ pub enum Stmt {
     ExprStmt(Expr),
 }
 
+impl Stmt {
+    pub fn span(&self) -> Span {
+        match self {
+            Stmt::Import(s) => s.span,
+            Stmt::MacroDef(s) => s.span,
+            Stmt::TypeAlias(s) => s.span,
+            Stmt::TraitDef(s) => s.span,
+            Stmt::RecordDef(s) => s.span,
+            Stmt::EnumDef(s) => s.span,
+            Stmt::StrandDef(s) => s.span,
+            Stmt::CellDef(s) => s.span,
+            Stmt::ExternCell(s) => s.span,
+            Stmt::UnsafeBlock(s) => s.span,
+            Stmt::Scope(s) => s.span,
+            Stmt::Region(s) => s.span,
+            Stmt::Layout(s) => s.span,
+            Stmt::Render(s) => s.span,
+            Stmt::Prop(s) => s.span,
+            Stmt::Assign(s) => s.span,
+            Stmt::If(s) => s.span,
+            Stmt::Match(s) => s.span,
+            Stmt::While(s) => s.span,
+            Stmt::For(s) => s.span,
+            Stmt::Break(s) => s.span,
+            Stmt::Continue(s) => s.span,
+            Stmt::Return(s) => s.span,
+            Stmt::Defer(s) => s.span,
+            Stmt::Requires(s) => s.span,
+            Stmt::Ensures(s) => s.span,
+            Stmt::Assert(s) => s.span,
+            Stmt::Assume(s) => s.span,
+            Stmt::MacroCall(s) => s.span,
+            Stmt::FlowBlock(s) => s.span,
+            Stmt::ExprStmt(e) => e.span,
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct MacroDef {
     pub span: Span,
@@ -88,6 +133,23 @@ pub struct UnsafeBlock {
     pub body: Block,
 }
 
+/// `scope tok:`: every `~>` flow started in the body is joined before the block ends.
+/// `tok` names the body's cancellation token; `scope:` leaves it unnamed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScopeBlock {
+    pub span: Span,
+    pub token: Option<Ident>,
+    pub body: Block,
+}
+
+/// `region { ... }`: tensors and maps allocated in the body are freed when the block ends, after
+/// the flows it started are joined. The checker keeps them from escaping it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RegionBlock {
+    pub span: Span,
+    pub body: Block,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct RequiresStmt {
     pub span: Span,
@@ -131,12 +193,45 @@ pub enum Pattern {
     Wildcard { span: Span },
     IntLit { span: Span, value: u64 },
     StringLit { span: Span, value: String },
+    /// `Type::Variant(p, ...)`: matches the variant, then each payload field against `args`.
     Ctor {
         span: Span,
         ty: Ident,
         variant: Ident,
-        binders: Vec<Ident>,
+        args: Vec<Pattern>,
     },
+    /// `(x, _, 0)`: matches a tuple element-wise.
+    Tuple { span: Span, elems: Vec<Pattern> },
+    /// A plain name inside a tuple or constructor pattern; binds the element.
+    Bind { span: Span, name: Ident },
+    /// `p | q`: matches if any alternative does; every alternative binds the same names.
+    Or { span: Span, alts: Vec<Pattern> },
+}
+
+impl Pattern {
+    pub fn span(&self) -> Span {
+        match self {
+            Pattern::Wildcard { span }
+            | Pattern::IntLit { span, .. }
+            | Pattern::StringLit { span, .. }
+            | Pattern::Ctor { span, .. }
+            | Pattern::Tuple { span, .. }
+            | Pattern::Bind { span, .. }
+            | Pattern::Or { span, .. } => *span,
+        }
+    }
+
+    /// Names this pattern introduces, in source order.
+    pub fn binders(&self) -> Vec<&Ident> {
+        match self {
+            Pattern::Wildcard { .. } | Pattern::IntLit { .. } | Pattern::StringLit { .. } => Vec::new(),
+            Pattern::Ctor { args: elems, .. } | Pattern::Tuple { elems, .. } => {
+                elems.iter().flat_map(|p| p.binders()).collect()
+            }
+            Pattern::Bind { name, .. } => vec![name],
+            Pattern::Or { alts, .. } => alts.first().map(|p| p.binders()).unwrap_or_default(),
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -171,6 +266,8 @@ pub struct ExternCell {
     pub name: Ident,
     pub params: Vec<Param>,
     pub ret: TypeRef,
+    /// Text of the `##` doc comment directly above the definition.
+    pub doc: Option<String>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -200,6 +297,8 @@ pub struct RecordDef {
     pub name: Ident,
     pub params: Vec<TypeParam>,
     pub fields: Vec<RecordFieldDef>,
+    /// Text of the `##` doc comment directly above the definition.
+    pub doc: Option<String>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -216,6 +315,8 @@ pub struct EnumDef {
     pub name: Ident,
     pub params: Vec<TypeParam>,
     pub variants: Vec<EnumVariantDef>,
+    /// Text of the `##` doc comment directly above the definition.
+    pub doc: Option<String>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -237,18 +338,38 @@ pub struct StrandDef {
     pub span: Span,
     pub name: Ident,
     pub mutable: bool,
+    /// `const NAME: u32 = expr`: a top-level compile-time constant, folded before checking.
+    pub is_const: bool,
     pub ty: Option<TypeRef>,
     pub where_clause: Option<Expr>,
     pub expr: Expr,
+    /// Destructuring target: `val (x, y) = f()`. `name` is then `_` and binds nothing.
+    pub pattern: Option<Pattern>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct CellDef {
     pub span: Span,
     pub name: Ident,
+    /// Generic parameters: `cell map<T: Numeric>(...)`.
+    pub type_params: Vec<TypeParam>,
     pub params: Vec<Param>,
     pub flow: Option<FlowOp>,
+    /// Declared return type: `cell f(x: u32) -> u32:`. Inferred from the body when absent.
+    pub ret: Option<TypeRef>,
+    /// Declared effects: `cell f() uses [net, io]:`. `uses []` declares the cell pure; when absent
+    /// the effects are inferred from the body and not enforced.
+    pub effects: Option<Spanned<Vec<Ident>>>,
+    /// Termination measure: `cell f(n: u32) decreases n:`. Every recursive call must make it
+    /// smaller while keeping it non-negative.
+    pub decreases: Option<Expr>,
     pub body: Block,
+    /// Declared as `test cell`: discovered by tooling and run in the AVM.
+    pub is_test: bool,
+    /// Declared `pub`: callable from other modules.
+    pub is_pub: bool,
+    /// Text of the `##` doc comment directly above the definition.
+    pub doc: Option<String>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -271,9 +392,19 @@ pub struct Param {
     pub span: Span,
     pub name: Ident,
     pub mutable: bool,
+    /// `x: &T` or `x: &mut T`: the caller lends the value instead of moving it.
+    pub borrow: Option<BorrowKind>,
     pub ty: TypeRef,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BorrowKind {
+    /// `&x`: read-only; any number may be live at once.
+    Shared,
+    /// `&mut x`: may update the value in place; nothing else may touch it meanwhile.
+    Mut,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct AssignStmt {
     pub span: Span,
@@ -298,6 +429,44 @@ pub struct WhileStmt {
     pub body: Block,
 }
 
+/// `for var in start..end [invariant e]:` iterates `var` over the half-open range `[start, end)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ForStmt {
+    pub span: Span,
+    pub var: Ident,
+    pub start: Expr,
+    pub end: Expr,
+    pub invariant: Option<Expr>,
+    pub body: Block,
+}
+
+/// `break`: leaves the innermost enclosing `while` or `for` loop.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BreakStmt {
+    pub span: Span,
+}
+
+/// `continue`: skips to the next iteration of the innermost enclosing loop.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContinueStmt {
+    pub span: Span,
+}
+
+/// `return` / `return expr`: leaves the enclosing cell early with `expr` as its result.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReturnStmt {
+    pub span: Span,
+    pub value: Option<Expr>,
+}
+
+/// `defer { ... }`: runs `body` when the enclosing block exits, on every path out of it.
+/// Deferred blocks of one scope run in reverse order of registration.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeferStmt {
+    pub span: Span,
+    pub body: Block,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct QuantBinder {
     pub span: Span,
@@ -342,7 +511,10 @@ pub struct Expr {
 pub enum ExprKind {
     Ident(Ident),
     IntLit(u64),
+    FloatLit(f64),
     StringLit(String),
+    /// `"count = {n}"`: literal segments interleaved with embedded expressions.
+    InterpolatedString(Vec<InterpPart>),
     /// `Style { key: value, ... }`
     StyleLit {
         fields: Vec<(Ident, Expr)>,
@@ -365,6 +537,27 @@ pub enum ExprKind {
         base: Box<Expr>,
         member: Ident,
     },
+    /// `&x` or `&mut x`
+    Borrow {
+        kind: BorrowKind,
+        expr: Box<Expr>,
+    },
+    /// `(a, b, ...)`: two or more elements.
+    Tuple(Vec<Expr>),
+    /// `t.0`
+    TupleIndex {
+        base: Box<Expr>,
+        index: u32,
+    },
+    /// `[a, b, ...]`; may be empty when the element type comes from an annotation.
+    ListLit(Vec<Expr>),
+    /// `{ k: v, ... }`; `{}` is the empty map.
+    MapLit(Vec<(Expr, Expr)>),
+    /// `xs[i]` or `m[k]`
+    Index {
+        base: Box<Expr>,
+        index: Box<Expr>,
+    },
     Call {
         callee: Box<Expr>,
         args: Vec<CallArg>,
@@ -390,6 +583,12 @@ pub enum ExprKind {
     },
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub enum InterpPart {
+    Lit(String),
+    Expr(Expr),
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum CallArg {
     Positional(Expr),
@@ -418,5 +617,11 @@ pub enum BinOp {
 
     And,
     Or,
+
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
 }
 
@@ -32,11 +32,106 @@ pub fn emit_module(module: &ModuleIR, debug: Option<&DebugSource>) -> Result<CAr
         });
     }
 
+    if let Some(name) = first_function_using_tuples(&module) {
+        return Err(CBackendError {
+            message: format!("function '{name}' uses tuples, which the C backend does not support yet; use the LLVM backend"),
+        });
+    }
+
+    if let Some(name) = first_function_using_maps(&module) {
+        return Err(CBackendError {
+            message: format!("function '{name}' uses maps, which the C backend does not support yet; use the LLVM backend"),
+        });
+    }
+
+    if let Some(name) = first_function_using_records(&module) {
+        return Err(CBackendError {
+            message: format!("function '{name}' uses records, which the C backend does not support yet; use the LLVM backend"),
+        });
+    }
+
+    if let Some(name) = first_function_using_enums(&module) {
+        return Err(CBackendError {
+            message: format!("function '{name}' uses enums, which the C backend does not support yet; use the LLVM backend"),
+        });
+    }
+
+    if let Some(name) = first_function_using_closures(&module) {
+        return Err(CBackendError {
+            message: format!("function '{name}' uses closures, which the C backend does not support yet; use the LLVM backend"),
+        });
+    }
+
+    if let Some(name) = first_function_using_sized_ints(&module) {
+        return Err(CBackendError {
+            message: format!(
+                "function '{name}' uses u8/u16/u64/i32/i64 integers, which the C backend does not support yet; use the LLVM backend"
+            ),
+        });
+    }
+
     let runtime_h = emit_runtime_h();
     let module_c = emit_module_c(&module, debug);
     Ok(CArtifacts { runtime_h, module_c })
 }
 
+fn first_function_using_tuples(module: &ModuleIR) -> Option<&str> {
+    module.functions.values().find_map(|f| {
+        let in_sig = f.params.iter().any(|p| matches!(p.ty, Type::Tuple(_))) || matches!(f.ret, Type::Tuple(_));
+        let in_body = f.blocks.iter().flat_map(|b| &b.insts).any(|i| {
+            matches!(i.kind, InstKind::MakeTuple { .. } | InstKind::TupleGet { .. })
+        });
+        (in_sig || in_body).then_some(f.name.as_str())
+    })
+}
+
+fn first_function_using_maps(module: &ModuleIR) -> Option<&str> {
+    module.functions.values().find_map(|f| {
+        let in_body = f.blocks.iter().flat_map(|b| &b.insts).any(|i| {
+            matches!(&i.kind, InstKind::Call { callee, .. } if callee.starts_with("map."))
+        });
+        in_body.then_some(f.name.as_str())
+    })
+}
+
+fn first_function_using_records(module: &ModuleIR) -> Option<&str> {
+    module.functions.values().find_map(|f| {
+        let in_sig = f.params.iter().any(|p| matches!(p.ty, Type::Record { .. })) || matches!(f.ret, Type::Record { .. });
+        let in_body = f.blocks.iter().flat_map(|b| &b.insts).any(|i| {
+            matches!(i.kind, InstKind::MakeRecord { .. } | InstKind::RecordGet { .. })
+        });
+        (in_sig || in_body).then_some(f.name.as_str())
+    })
+}
+
+fn first_function_using_enums(module: &ModuleIR) -> Option<&str> {
+    module.functions.values().find_map(|f| {
+        let in_sig = f.params.iter().any(|p| matches!(p.ty, Type::Enum { .. })) || matches!(f.ret, Type::Enum { .. });
+        let in_body = f.blocks.iter().flat_map(|b| &b.insts).any(|i| {
+            matches!(i.kind, InstKind::MakeEnum { .. } | InstKind::EnumTag { .. } | InstKind::EnumField { .. })
+        });
+        (in_sig || in_body).then_some(f.name.as_str())
+    })
+}
+
+fn first_function_using_closures(module: &ModuleIR) -> Option<&str> {
+    module.functions.values().find_map(|f| {
+        let in_body = f.blocks.iter().flat_map(|b| &b.insts).any(|i| {
+            matches!(i.kind, InstKind::MakeClosure { .. } | InstKind::CallClosure { .. })
+        });
+        (f.is_closure || in_body).then_some(f.name.as_str())
+    })
+}
+
+fn first_function_using_sized_ints(module: &ModuleIR) -> Option<&str> {
+    let sized = |t: &Type| matches!(t, Type::U8 | Type::U16 | Type::U64 | Type::I32 | Type::I64);
+    module.functions.values().find_map(|f| {
+        let in_sig = f.params.iter().any(|p| sized(&p.ty)) || sized(&f.ret);
+        let in_body = f.blocks.iter().flat_map(|b| &b.insts).any(|i| matches!(i.kind, InstKind::IntCast { .. }));
+        (in_sig || in_body).then_some(f.name.as_str())
+    })
+}
+
 fn emit_runtime_h() -> String {
     // A tiny runtime that is:
     // - portable (falls back when C11 threads are unavailable)
@@ -49,7 +144,11 @@ fn emit_runtime_h() -> String {
     out.push_str("#include <stdlib.h>\n\n");
 
     // From aura-stdlib (linked by the CLI when running the C backend).
-    out.push_str("void aura_io_println(const char* s);\n\n");
+    out.push_str("void aura_io_println(const char* s);\n");
+    out.push_str("const char* aura_str_concat(const char* a, const char* b);\n");
+    out.push_str("const char* aura_str_from_u32(uint32_t v);\n");
+    out.push_str("const char* aura_str_from_bool(bool v);\n");
+    out.push_str("const char* aura_str_from_f64(double v);\n\n");
 
     out.push_str("// ---- Aura runtime (prototype) ----\n\n");
     out.push_str("typedef struct Tensor { uint32_t id; } Tensor;\n\n");
@@ -165,6 +264,7 @@ enum CType {
     Void,
     Bool,
     U32,
+    F64,
     CString,
     Tensor,
 }
@@ -184,6 +284,7 @@ fn emit_function(out: &mut String, debug: Option<&DebugSource>, f: &FunctionIR,
         CType::Void => "void ",
         CType::Tensor => "Tensor ",
         CType::U32 => "uint32_t ",
+        CType::F64 => "double ",
         CType::CString => "const char* ",
         CType::Bool => "bool ",
     });
@@ -242,6 +343,18 @@ fn emit_function(out: &mut String, debug: Option<&DebugSource>, f: &FunctionIR,
             match &inst.kind {
                 InstKind::AllocCapability { .. } => {}
 
+                // Rejected up front by `emit_module`.
+                InstKind::MakeTuple { .. }
+                | InstKind::TupleGet { .. }
+                | InstKind::MakeRecord { .. }
+                | InstKind::RecordGet { .. }
+                | InstKind::MakeEnum { .. }
+                | InstKind::EnumTag { .. }
+                | InstKind::EnumField { .. }
+                | InstKind::MakeClosure { .. }
+                | InstKind::CallClosure { .. }
+                | InstKind::IntCast { .. } => {}
+
                 InstKind::Phi { .. } => {
                     // Phi nodes are implemented by assignments in predecessors (see terminators).
                 }
@@ -277,6 +390,9 @@ fn emit_function(out: &mut String, debug: Option<&DebugSource>, f: &FunctionIR,
                         .map(|(_, n)| n.clone())
                         .unwrap_or_else(|| format!("v{}", operand.0));
                     let (ct, expr_s) = match op {
+                        UnaryOp::Neg if values.get(operand).map(|(t, _)| *t) == Some(CType::F64) => {
+                            (CType::F64, format!("-({operand_name})"))
+                        }
                         UnaryOp::Neg => (CType::U32, format!("-(int32_t)({operand_name})")),
                         UnaryOp::Not => (CType::Bool, format!("!({operand_name})")),
                     };
@@ -298,7 +414,11 @@ fn emit_function(out: &mut String, debug: Option<&DebugSource>, f: &FunctionIR,
                         .get(right)
                         .map(|(_, n)| n.clone())
                         .unwrap_or_else(|| format!("v{}", right.0));
-                    let (ct, expr_s) = emit_binop(*op, &ln, &rn);
+                    let (ct, expr_s) = if values.get(left).map(|(t, _)| *t) == Some(CType::F64) {
+                        emit_float_binop(*op, &ln, &rn)
+                    } else {
+                        emit_binop(*op, &ln, &rn)
+                    };
                     out.push_str("  ");
                     out.push_str(map_ctype_decl(ct));
                     out.push(' ');
@@ -402,6 +522,10 @@ fn emit_rvalue_decl(dest: ValueId, _name: &str, rv: &RValue) -> (CType, String)
             CType::U32,
             format!("const uint32_t {var} = {n}u;"),
         ),
+        RValue::ConstF64(v) => (
+            CType::F64,
+            format!("const double {var} = {v:?};"),
+        ),
         RValue::ConstBool(b) => (
             CType::Bool,
             format!("const bool {var} = {};", if *b { "true" } else { "false" }),
@@ -425,7 +549,14 @@ fn emit_call(
     values: &mut HashMap<ValueId, (CType, String)>,
     ret_map: &HashMap<String, CType>,
 ) {
-    let c_fn = map_callee(callee);
+    // `str.from` (string interpolation) dispatches on the operand's C type.
+    let c_fn = match (callee, args.first().and_then(|a| values.get(a)).map(|(t, _)| *t)) {
+        ("str.from", Some(CType::CString)) => "(const char*)".to_string(),
+        ("str.from", Some(CType::Bool)) => "aura_str_from_bool".to_string(),
+        ("str.from", Some(CType::F64)) => "aura_str_from_f64".to_string(),
+        ("str.from", _) => "aura_str_from_u32".to_string(),
+        _ => map_callee(callee),
+    };
     let ret = ret_map.get(callee).copied().unwrap_or_else(|| builtin_return_ctype(&c_fn));
 
     if let Some(d) = dest {
@@ -435,6 +566,7 @@ fn emit_call(
             let decl = match ret {
                 CType::Tensor => "Tensor",
                 CType::U32 => "uint32_t",
+                CType::F64 => "double",
                 CType::CString => "const char*",
                 CType::Bool => "bool",
                 CType::Void => "void",
@@ -516,9 +648,12 @@ fn map_type(ty: &Type) -> &'static str {
         Type::Unit => "void",
         Type::Bool => "bool",
         Type::U32 => "uint32_t",
+        Type::F64 => "double",
         Type::String => "const char*",
         Type::Tensor => "Tensor",
-        Type::Opaque(_) => "Tensor",
+        Type::Opaque(_) | Type::Tuple(_) | Type::Record { .. } | Type::Enum { .. } | Type::Closure => "Tensor",
+        // Rejected before emission.
+        Type::U8 | Type::U16 | Type::U64 | Type::I32 | Type::I64 => "uint32_t",
     }
 }
 
@@ -527,8 +662,10 @@ fn map_type_to_ctype(ty: &Type) -> CType {
         Type::Unit => CType::Void,
         Type::Bool => CType::Bool,
         Type::U32 => CType::U32,
+        Type::F64 => CType::F64,
         Type::String => CType::CString,
-        Type::Tensor | Type::Opaque(_) => CType::Tensor,
+        Type::Tensor | Type::Opaque(_) | Type::Tuple(_) | Type::Record { .. } | Type::Enum { .. } | Type::Closure => CType::Tensor,
+        Type::U8 | Type::U16 | Type::U64 | Type::I32 | Type::I64 => CType::U32,
     }
 }
 
@@ -541,6 +678,7 @@ fn map_ctype_decl(ct: CType) -> &'static str {
         CType::Void => "void",
         CType::Bool => "bool",
         CType::U32 => "uint32_t",
+        CType::F64 => "double",
         CType::CString => "const char*",
         CType::Tensor => "Tensor",
     }
@@ -568,6 +706,30 @@ fn emit_binop(op: BinOp, l: &str, r: &str) -> (CType, String) {
 
         BinOp::And => (CType::Bool, format!("({l}) && ({r})")),
         BinOp::Or => (CType::Bool, format!("({l}) || ({r})")),
+
+        BinOp::BitAnd => (CType::U32, format!("({l}) & ({r})")),
+        BinOp::BitOr => (CType::U32, format!("({l}) | ({r})")),
+        BinOp::BitXor => (CType::U32, format!("({l}) ^ ({r})")),
+        // Shifting a uint32_t by 32 or more is UB. Enforce a runtime trap.
+        BinOp::Shl => (
+            CType::U32,
+            format!("(AURA_RANGE_CHECK_U32((uint32_t)({r}), 0u, 31u, \"shift amount\"), ({l}) << ({r}))"),
+        ),
+        BinOp::Shr => (
+            CType::U32,
+            format!("(AURA_RANGE_CHECK_U32((uint32_t)({r}), 0u, 31u, \"shift amount\"), ({l}) >> ({r}))"),
+        ),
+    }
+}
+
+fn emit_float_binop(op: BinOp, l: &str, r: &str) -> (CType, String) {
+    match op {
+        BinOp::Add => (CType::F64, format!("({l}) + ({r})")),
+        BinOp::Sub => (CType::F64, format!("({l}) - ({r})")),
+        BinOp::Mul => (CType::F64, format!("({l}) * ({r})")),
+        // IEEE division: `x / 0.0` yields an infinity or NaN, no trap.
+        BinOp::Div => (CType::F64, format!("({l}) / ({r})")),
+        _ => emit_binop(op, l, r),
     }
 }
 
@@ -597,6 +759,9 @@ fn emit_phi_assigns(
 fn builtin_return_ctype(c_fn: &str) -> CType {
     match c_fn {
         "aura_io_println" => CType::Void,
+        "aura_str_concat" | "aura_str_from_u32" | "aura_str_from_bool" | "aura_str_from_f64" | "(const char*)" => {
+            CType::CString
+        }
         "io_load_tensor" => CType::Tensor,
         "io_display" => CType::Void,
         "compute_gradient" => CType::Tensor,
@@ -608,6 +773,7 @@ fn map_callee(name: &str) -> String {
     match name {
         // Match LLVM backend's builtin ABI mapping.
         "io.println" => "aura_io_println".to_string(),
+        "str.concat" => "aura_str_concat".to_string(),
         _ => {
             // Convert Aura module-style calls `io.load_tensor` -> `io_load_tensor`
             name.replace('.', "_")
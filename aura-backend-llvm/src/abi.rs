@@ -0,0 +1,389 @@
+//! C calling-convention lowering for records that cross an extern boundary.
+//!
+//! Aura cells hand records to each other as first-class LLVM aggregates. C compilers instead
+//! split small structs into registers and pass large ones through memory, with rules that
+//! differ per target, so calls to extern cells reshape record arguments and results to match
+//! what the C side was compiled for.
+
+use crate::codegen::LlvmTy;
+use crate::target::{NativeTarget, TargetArch, TargetOs};
+
+/// Size and alignment in bytes under the target's C struct layout rules.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Layout {
+    pub(crate) size: u64,
+    pub(crate) align: u64,
+}
+
+pub(crate) fn layout(ty: &LlvmTy, target: &NativeTarget) -> Layout {
+    match ty {
+        LlvmTy::I1 => Layout { size: 1, align: 1 },
+        LlvmTy::I8 => Layout { size: 1, align: 1 },
+        LlvmTy::I16 => Layout { size: 2, align: 2 },
+        LlvmTy::I32 => Layout { size: 4, align: 4 },
+        LlvmTy::I64 | LlvmTy::F64 => {
+            // i386 System V only aligns 8-byte scalars inside structs to 4 bytes.
+            let align = if target.arch() == TargetArch::X86 && target.os() != TargetOs::Windows {
+                4
+            } else {
+                8
+            };
+            Layout { size: 8, align }
+        }
+        LlvmTy::Ptr => {
+            let bytes = u64::from(target.pointer_bits() / 8);
+            Layout { size: bytes, align: bytes }
+        }
+        LlvmTy::Struct(fields) | LlvmTy::Record(_, fields) => {
+            let mut size = 0u64;
+            let mut align = 1;
+            for f in fields {
+                let l = layout(f, target);
+                size = size.next_multiple_of(l.align) + l.size;
+                align = align.max(l.align);
+            }
+            Layout {
+                size: size.next_multiple_of(align),
+                align,
+            }
+        }
+        LlvmTy::Enum(_, variants) => {
+            let payload = enum_payload(variants, target);
+            let align = payload.align.max(4);
+            Layout {
+                size: (4u64.next_multiple_of(payload.align) + payload.size).next_multiple_of(align),
+                align,
+            }
+        }
+    }
+}
+
+/// Union of an enum's variant payloads: large and aligned enough for any of them.
+pub(crate) fn enum_payload(variants: &[Vec<LlvmTy>], target: &NativeTarget) -> Layout {
+    variants
+        .iter()
+        .map(|fields| layout(&LlvmTy::Struct(fields.clone()), target))
+        .fold(Layout { size: 0, align: 1 }, |acc, l| Layout {
+            size: acc.size.max(l.size),
+            align: acc.align.max(l.align),
+        })
+}
+
+/// A scalar inside an aggregate: its byte offset, type and `extractvalue` index path.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Scalar {
+    pub(crate) offset: u64,
+    pub(crate) ty: LlvmTy,
+    pub(crate) path: Vec<u32>,
+}
+
+/// The scalars of `ty` in memory order, with nested aggregates flattened.
+pub(crate) fn scalars(ty: &LlvmTy, target: &NativeTarget) -> Vec<Scalar> {
+    fn walk(ty: &LlvmTy, target: &NativeTarget, base: u64, path: &mut Vec<u32>, out: &mut Vec<Scalar>) {
+        match ty {
+            LlvmTy::Struct(fields) | LlvmTy::Record(_, fields) => {
+                let mut offset = 0u64;
+                for (i, f) in fields.iter().enumerate() {
+                    let l = layout(f, target);
+                    offset = offset.next_multiple_of(l.align);
+                    path.push(i as u32);
+                    walk(f, target, base + offset, path, out);
+                    path.pop();
+                    offset += l.size;
+                }
+            }
+            scalar => out.push(Scalar {
+                offset: base,
+                ty: scalar.clone(),
+                path: path.clone(),
+            }),
+        }
+    }
+    let mut out = Vec::new();
+    walk(ty, target, 0, &mut Vec::new(), &mut out);
+    out
+}
+
+/// One register-sized piece of a record passed in registers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Part {
+    /// The bytes `offset..offset + bytes`, packed little-endian into an integer.
+    Int { offset: u64, bytes: u64 },
+    /// The double at `offset`.
+    F64 { offset: u64 },
+}
+
+impl Part {
+    pub(crate) fn llvm_ty(&self) -> String {
+        match self {
+            Part::Int { bytes, .. } => format!("i{}", bytes * 8),
+            Part::F64 { .. } => "double".to_string(),
+        }
+    }
+
+    pub(crate) fn contains(&self, offset: u64) -> bool {
+        match *self {
+            Part::Int { offset: start, bytes } => (start..start + bytes).contains(&offset),
+            Part::F64 { offset: start } => start == offset,
+        }
+    }
+}
+
+/// How a record argument or result crosses an extern boundary.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum PassMode {
+    /// In registers, as `parts`: separate arguments (a literal struct when returned), or a
+    /// single array when `array`, which AArch64 keeps in consecutive registers.
+    Direct { parts: Vec<Part>, array: bool },
+    /// Through memory: a pointer to a caller-owned copy, which `byval` places on the stack.
+    /// Results come back through a hidden `sret` pointer.
+    Indirect { byval: bool },
+}
+
+impl PassMode {
+    /// LLVM type of a directly passed value as one first-class value.
+    pub(crate) fn direct_ty(parts: &[Part], array: bool) -> String {
+        match parts {
+            [one] => one.llvm_ty(),
+            _ if array => format!("[{} x {}]", parts.len(), parts[0].llvm_ty()),
+            _ => format!("{{ {} }}", parts.iter().map(Part::llvm_ty).collect::<Vec<_>>().join(", ")),
+        }
+    }
+}
+
+/// Pass modes of an extern signature; `None` marks values passed unchanged.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ExternAbi {
+    pub(crate) params: Vec<Option<PassMode>>,
+    pub(crate) ret: Option<PassMode>,
+}
+
+impl ExternAbi {
+    /// Whether any record is reshaped, i.e. the signature differs from the plain one.
+    pub(crate) fn reshapes(&self) -> bool {
+        self.ret.is_some() || self.params.iter().any(Option::is_some)
+    }
+}
+
+/// Classifies the record params and result (`None` for void) of an extern cell.
+pub(crate) fn classify_extern(params: &[LlvmTy], ret: Option<&LlvmTy>, target: &NativeTarget) -> ExternAbi {
+    let is_record = |t: &&LlvmTy| matches!(t, LlvmTy::Record(..));
+    match (target.arch(), target.os()) {
+        (TargetArch::X86_64, TargetOs::Windows) => ExternAbi {
+            params: params.iter().map(|t| Some(t).filter(is_record).map(|t| win64(t, target))).collect(),
+            ret: ret.filter(is_record).map(|t| win64(t, target)),
+        },
+        (TargetArch::X86_64, _) => sysv_x86_64(params, ret, target),
+        (TargetArch::Aarch64, _) => ExternAbi {
+            params: params.iter().map(|t| Some(t).filter(is_record).map(|t| aapcs64(t, target))).collect(),
+            ret: ret.filter(is_record).map(|t| aapcs64(t, target)),
+        },
+        // i386 passes every struct on the stack; MSVC returns small ones in EDX:EAX.
+        (TargetArch::X86 | TargetArch::Other, os) => ExternAbi {
+            params: params
+                .iter()
+                .map(|t| Some(t).filter(is_record).map(|_| PassMode::Indirect { byval: true }))
+                .collect(),
+            ret: ret.filter(is_record).map(|t| {
+                let size = layout(t, target).size;
+                if os == TargetOs::Windows && target.arch() == TargetArch::X86 && matches!(size, 1 | 2 | 4 | 8) {
+                    int_word(size)
+                } else {
+                    PassMode::Indirect { byval: false }
+                }
+            }),
+        },
+    }
+}
+
+fn int_word(size: u64) -> PassMode {
+    PassMode::Direct {
+        parts: vec![Part::Int { offset: 0, bytes: size }],
+        array: false,
+    }
+}
+
+/// Windows x64: structs of 1, 2, 4 or 8 bytes travel as an integer, others by reference.
+fn win64(ty: &LlvmTy, target: &NativeTarget) -> PassMode {
+    let size = layout(ty, target).size;
+    if matches!(size, 1 | 2 | 4 | 8) {
+        int_word(size)
+    } else {
+        PassMode::Indirect { byval: false }
+    }
+}
+
+/// AAPCS64: up to four doubles form a homogeneous aggregate in FP registers, other structs
+/// of at most 16 bytes travel as 64-bit words and larger ones by reference.
+fn aapcs64(ty: &LlvmTy, target: &NativeTarget) -> PassMode {
+    let l = layout(ty, target);
+    let scalars = scalars(ty, target);
+    let parts: Vec<Part> = if (1..=4).contains(&scalars.len()) && scalars.iter().all(|s| s.ty == LlvmTy::F64) {
+        scalars.iter().map(|s| Part::F64 { offset: s.offset }).collect()
+    } else if l.size <= 16 {
+        (0..l.size.div_ceil(8))
+            .map(|k| Part::Int { offset: k * 8, bytes: 8 })
+            .collect()
+    } else {
+        return PassMode::Indirect { byval: false };
+    };
+    let array = parts.len() > 1;
+    PassMode::Direct { parts, array }
+}
+
+/// System V x86-64: structs of at most 16 bytes are split into eightbytes, each passed in an
+/// SSE register if it holds only doubles and in a general-purpose one otherwise. Larger
+/// structs, and arguments whose eightbytes no longer fit in the free registers, go on the
+/// stack.
+fn sysv_x86_64(params: &[LlvmTy], ret: Option<&LlvmTy>, target: &NativeTarget) -> ExternAbi {
+    let eightbytes = |ty: &LlvmTy| -> Option<Vec<Part>> {
+        let size = layout(ty, target).size;
+        if size > 16 {
+            return None;
+        }
+        let scalars = scalars(ty, target);
+        let mut parts = Vec::new();
+        for offset in (0..size).step_by(8) {
+            let mut in_word = scalars.iter().filter(|s| (offset..offset + 8).contains(&s.offset)).peekable();
+            if in_word.peek().is_none() {
+                continue;
+            }
+            if in_word.all(|s| s.ty == LlvmTy::F64) {
+                parts.push(Part::F64 { offset });
+            } else {
+                let bytes = (size - offset).min(8).next_power_of_two();
+                parts.push(Part::Int { offset, bytes });
+            }
+        }
+        Some(parts)
+    };
+
+    let mut free_int = 6usize;
+    let mut free_sse = 8usize;
+    let ret = ret.filter(|t| matches!(t, LlvmTy::Record(..))).map(|t| match eightbytes(t) {
+        Some(parts) => PassMode::Direct { parts, array: false },
+        None => {
+            // The `sret` pointer takes the first integer register.
+            free_int -= 1;
+            PassMode::Indirect { byval: false }
+        }
+    });
+
+    let params = params
+        .iter()
+        .map(|t| {
+            if !matches!(t, LlvmTy::Record(..)) {
+                for s in scalars(t, target) {
+                    if s.ty == LlvmTy::F64 {
+                        free_sse = free_sse.saturating_sub(1);
+                    } else {
+                        free_int = free_int.saturating_sub(1);
+                    }
+                }
+                return None;
+            }
+            let Some(parts) = eightbytes(t) else {
+                return Some(PassMode::Indirect { byval: true });
+            };
+            let sse = parts.iter().filter(|p| matches!(p, Part::F64 { .. })).count();
+            let int = parts.len() - sse;
+            if int > free_int || sse > free_sse {
+                return Some(PassMode::Indirect { byval: true });
+            }
+            free_int -= int;
+            free_sse -= sse;
+            Some(PassMode::Direct { parts, array: false })
+        })
+        .collect();
+    ExternAbi { params, ret }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rec(fields: Vec<LlvmTy>) -> LlvmTy {
+        LlvmTy::Record("R".to_string(), fields)
+    }
+
+    fn target(triple: &str) -> NativeTarget {
+        NativeTarget::parse(triple).unwrap()
+    }
+
+    #[test]
+    fn layout_follows_c_struct_rules() {
+        let linux = target("x86_64-unknown-linux-gnu");
+        let r = rec(vec![LlvmTy::I1, LlvmTy::F64, LlvmTy::I32]);
+        assert_eq!(layout(&r, &linux), Layout { size: 24, align: 8 });
+        let offsets: Vec<u64> = scalars(&r, &linux).iter().map(|s| s.offset).collect();
+        assert_eq!(offsets, vec![0, 8, 16]);
+
+        let i386 = target("i686-unknown-linux-gnu");
+        assert_eq!(layout(&r, &i386), Layout { size: 16, align: 4 });
+        assert_eq!(layout(&rec(vec![LlvmTy::Ptr, LlvmTy::I1]), &i386), Layout { size: 8, align: 4 });
+    }
+
+    #[test]
+    fn sysv_splits_small_records_into_eightbytes() {
+        let linux = target("x86_64-unknown-linux-gnu");
+        let pair = rec(vec![LlvmTy::I32, LlvmTy::I32, LlvmTy::F64]);
+        let abi = classify_extern(std::slice::from_ref(&pair), Some(&pair), &linux);
+        let direct = PassMode::Direct {
+            parts: vec![Part::Int { offset: 0, bytes: 8 }, Part::F64 { offset: 8 }],
+            array: false,
+        };
+        assert_eq!(abi.params, vec![Some(direct.clone())]);
+        assert_eq!(abi.ret, Some(direct));
+
+        let big = rec(vec![LlvmTy::F64, LlvmTy::F64, LlvmTy::I32]);
+        let abi = classify_extern(&[LlvmTy::I32, big.clone()], Some(&big), &linux);
+        assert_eq!(abi.params, vec![None, Some(PassMode::Indirect { byval: true })]);
+        assert_eq!(abi.ret, Some(PassMode::Indirect { byval: false }));
+    }
+
+    #[test]
+    fn sysv_spills_records_once_registers_run_out() {
+        let linux = target("x86_64-unknown-linux-gnu");
+        let two_words = rec(vec![LlvmTy::Ptr, LlvmTy::Ptr]);
+        let params = [LlvmTy::I32, LlvmTy::I32, LlvmTy::I32, two_words.clone(), two_words];
+        let abi = classify_extern(&params, None, &linux);
+        assert!(matches!(abi.params[3], Some(PassMode::Direct { .. })));
+        assert_eq!(abi.params[4], Some(PassMode::Indirect { byval: true }));
+    }
+
+    #[test]
+    fn windows_and_aarch64_pick_their_own_modes() {
+        let win = target("x86_64-pc-windows-msvc");
+        let eight = rec(vec![LlvmTy::I32, LlvmTy::I32]);
+        let twelve = rec(vec![LlvmTy::I32, LlvmTy::I32, LlvmTy::I32]);
+        let abi = classify_extern(&[eight.clone(), twelve.clone()], Some(&twelve), &win);
+        assert_eq!(
+            abi.params,
+            vec![
+                Some(PassMode::Direct {
+                    parts: vec![Part::Int { offset: 0, bytes: 8 }],
+                    array: false
+                }),
+                Some(PassMode::Indirect { byval: false }),
+            ]
+        );
+        assert_eq!(abi.ret, Some(PassMode::Indirect { byval: false }));
+
+        let mac = target("aarch64-apple-darwin");
+        let hfa = rec(vec![LlvmTy::F64, LlvmTy::F64, LlvmTy::F64]);
+        let abi = classify_extern(&[hfa, twelve], None, &mac);
+        let Some(PassMode::Direct { parts, array: true }) = &abi.params[0] else {
+            panic!("{abi:?}");
+        };
+        assert_eq!(PassMode::direct_ty(parts, true), "[3 x double]");
+        let Some(PassMode::Direct { parts, array: true }) = &abi.params[1] else {
+            panic!("{abi:?}");
+        };
+        assert_eq!(PassMode::direct_ty(parts, true), "[2 x i64]");
+
+        let i386 = target("i686-unknown-linux-gnu");
+        let abi = classify_extern(std::slice::from_ref(&eight), Some(&eight), &i386);
+        assert_eq!(abi.params, vec![Some(PassMode::Indirect { byval: true })]);
+        assert_eq!(abi.ret, Some(PassMode::Indirect { byval: false }));
+    }
+}
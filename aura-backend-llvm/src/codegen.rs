@@ -1,12 +1,25 @@
 #![forbid(unsafe_code)]
 
 #[cfg(feature = "llvm")]
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 use aura_ir::{DebugSource, ModuleIR};
 use miette::Diagnostic;
 use thiserror::Error;
 
+#[cfg(feature = "llvm")]
+use crate::abi::{self, ExternAbi, Part, PassMode};
+use crate::opt_level::OptConfig;
+#[cfg(feature = "llvm")]
+use crate::opt_level::SimdWidth;
+#[cfg(feature = "llvm")]
+use crate::pattern_lowering::{analyze_match, MatchArm, MatchLowering, PatternValue};
+use crate::target::NativeTarget;
+#[cfg(feature = "llvm")]
+use crate::target::TargetArch;
+#[cfg(feature = "llvm")]
+use crate::tensor_loops::{find_tensor_loops, LaneOp, Operand, TensorLoop};
+
 #[derive(Debug, Error, Diagnostic)]
 #[error("LLVM backend error: {message}")]
 #[diagnostic(code(aura::backend_llvm))]
@@ -20,6 +33,16 @@ pub struct LlvmArtifacts {
     pub llvm_ir: String,
 }
 
+/// A separately compiled slice of a module (see [`emit_llvm_units_for_target`]).
+pub struct LlvmUnit {
+    /// File-name-safe name: the LLVM symbol of the unit's function, or `aura_entry`.
+    pub name: String,
+    /// The Aura function the unit defines; `None` for the unit defining `aura_entry`.
+    pub function: Option<String>,
+    /// `None` when the caller did not ask for this unit to be emitted.
+    pub llvm_ir: Option<String>,
+}
+
 /// Emit LLVM IR from Aura-IR.
 ///
 /// This is intentionally a *skeleton* in Phase 3:
@@ -28,19 +51,22 @@ pub struct LlvmArtifacts {
 pub fn emit_llvm_ir(
     module: &ModuleIR,
     debug: Option<&DebugSource>,
+) -> Result<LlvmArtifacts, LlvmBackendError> {
+    emit_llvm_ir_for_target(module, debug, &NativeTarget::host()?, &OptConfig::default())
+}
+
+/// Emit LLVM IR from Aura-IR for an explicit native target and optimization configuration
+/// (see [`emit_llvm_ir`]).
+pub fn emit_llvm_ir_for_target(
+    module: &ModuleIR,
+    debug: Option<&DebugSource>,
+    target: &NativeTarget,
+    opt: &OptConfig,
 ) -> Result<LlvmArtifacts, LlvmBackendError> {
     #[cfg(feature = "llvm")]
     {
-        let mut m = module.clone();
-        aura_ir::optimize_module(&mut m);
-
-        if let Err(e) = aura_ir::validate_module(&m) {
-            return Err(LlvmBackendError {
-                message: format!("IR validation failed before LLVM emission: {e}"),
-            });
-        }
-
-        let llvm_ir = emit_module_llvm(&m, debug)?;
+        let m = prepare_module(module, opt)?;
+        let llvm_ir = emit_module_llvm(&m, debug, target, opt, UnitScope::Whole)?;
         Ok(LlvmArtifacts { llvm_ir })
     }
 
@@ -48,12 +74,97 @@ pub fn emit_llvm_ir(
     {
         let _ = module;
         let _ = debug;
+        let _ = target;
+        let _ = opt;
         Err(LlvmBackendError {
             message: "LLVM backend not enabled. Rebuild with `--features aura-backend-llvm/llvm`.".to_string(),
         })
     }
 }
 
+/// Emits `module` for separate compilation: one LLVM module per function plus one that
+/// defines `aura_entry`, each declaring whatever it uses from the others. Only the functions
+/// `wanted` accepts get their IR emitted (e.g. those without a cached object); the entry unit
+/// always does.
+pub fn emit_llvm_units_for_target(
+    module: &ModuleIR,
+    debug: Option<&DebugSource>,
+    target: &NativeTarget,
+    opt: &OptConfig,
+    mut wanted: impl FnMut(&str) -> bool,
+) -> Result<Vec<LlvmUnit>, LlvmBackendError> {
+    #[cfg(feature = "llvm")]
+    {
+        let m = prepare_module(module, opt)?;
+        let mut units = Vec::new();
+        for name in m.functions.keys() {
+            let llvm_ir = if wanted(name) {
+                Some(emit_module_llvm(&m, debug, target, opt, UnitScope::Function(name))?)
+            } else {
+                None
+            };
+            units.push(LlvmUnit {
+                name: function_symbol(name),
+                function: Some(name.clone()),
+                llvm_ir,
+            });
+        }
+        units.push(LlvmUnit {
+            name: "aura_entry".to_string(),
+            function: None,
+            llvm_ir: Some(emit_module_llvm(&m, debug, target, opt, UnitScope::Entry)?),
+        });
+        Ok(units)
+    }
+
+    #[cfg(not(feature = "llvm"))]
+    {
+        let _ = (module, debug, target, opt, &mut wanted);
+        Err(LlvmBackendError {
+            message: "LLVM backend not enabled. Rebuild with `--features aura-backend-llvm/llvm`.".to_string(),
+        })
+    }
+}
+
+/// Runs the IR passes `opt` asks for and validates the result.
+#[cfg(feature = "llvm")]
+fn prepare_module(module: &ModuleIR, opt: &OptConfig) -> Result<ModuleIR, LlvmBackendError> {
+    let mut m = module.clone();
+    if opt.runs_ir_passes() {
+        aura_ir::optimize_module(&mut m);
+    }
+
+    if let Err(e) = aura_ir::validate_module(&m) {
+        return Err(LlvmBackendError {
+            message: format!("IR validation failed before LLVM emission: {e}"),
+        });
+    }
+    Ok(m)
+}
+
+/// What one emitted LLVM module defines.
+#[cfg(feature = "llvm")]
+#[derive(Clone, Copy)]
+enum UnitScope<'a> {
+    /// Every function and `aura_entry`.
+    Whole,
+    /// One function; the others are only declared.
+    Function(&'a str),
+    /// Only `aura_entry`.
+    Entry,
+}
+
+#[cfg(feature = "llvm")]
+impl UnitScope<'_> {
+    fn defines(self, function: &str) -> bool {
+        match self {
+            UnitScope::Whole => true,
+            UnitScope::Function(name) => name == function,
+            UnitScope::Entry => false,
+        }
+    }
+}
+
 #[cfg(feature = "llvm")]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum ExternLlvmCallConv {
@@ -62,10 +173,11 @@ enum ExternLlvmCallConv {
 }
 
 #[cfg(feature = "llvm")]
-fn resolve_extern_symbol_and_cc(
-    aura_name: &str,
+fn resolve_extern_symbol_and_cc<'a>(
+    aura_name: &'a str,
     declared: aura_ir::CallConv,
-) -> (&str, ExternLlvmCallConv) {
+    target: &NativeTarget,
+) -> (&'a str, ExternLlvmCallConv) {
     // Name-based override for ergonomics without syntax changes.
     // Example: `extern cell __stdcall_MessageBoxA(...): u32`.
     let mut sym = aura_name.strip_prefix("__ffi_").unwrap_or(aura_name);
@@ -83,38 +195,153 @@ fn resolve_extern_symbol_and_cc(
         cc = ExternLlvmCallConv::C;
     }
 
+    // stdcall only exists on 32-bit Windows; the same declarations link as C elsewhere.
+    if !target.supports_stdcall() {
+        cc = ExternLlvmCallConv::C;
+    }
+
     (sym, cc)
 }
 
 #[cfg(feature = "llvm")]
-fn emit_module_llvm(module: &ModuleIR, debug: Option<&DebugSource>) -> Result<String, LlvmBackendError> {
-    let host = target_lexicon::HOST;
-    let triple = host.to_string();
+fn emit_module_llvm(
+    module: &ModuleIR,
+    debug: Option<&DebugSource>,
+    target: &NativeTarget,
+    opt: &OptConfig,
+    scope: UnitScope,
+) -> Result<String, LlvmBackendError> {
+    let triple = target.triple();
 
     let mut out = String::new();
     out.push_str("; ModuleID = 'aura'\n");
     out.push_str("; Proof-carrying: IR validated by aura_ir::validate_module\n");
+    out.push_str(&format!("; Optimization: {}\n", opt.summary()));
     out.push_str("source_filename = \"aura\"\n");
+    if let Some(layout) = target.data_layout() {
+        out.push_str(&format!("target datalayout = \"{layout}\"\n"));
+    }
     out.push_str(&format!("target triple = \"{}\"\n\n", triple));
 
-    let mut emitter = Emitter::new(module, debug);
+    let named = named_types(module);
+    for (name, ty) in &named {
+        out.push_str(&format!("%{} = type {}\n", llvm_ident(name), ty.definition(target)));
+    }
+    if !named.is_empty() {
+        out.push('\n');
+    }
+
+    let mut emitter = Emitter::new(module, debug, target, *opt);
+    emitter.strings = string_literals(module.functions.iter().filter(|(name, _)| scope.defines(name)).map(|(_, f)| f));
+    emitter.emit_string_literals(&mut out);
+    emitter.emit_cell_names(&mut out, scope);
 
     // Stdlib/runtime externs (prototype ABI).
     out.push_str("declare void @aura_io_println(ptr)\n");
+    out.push_str("declare ptr @aura_string_concat(ptr, ptr)\n");
+    out.push_str("declare ptr @aura_string_from_u32(i32)\n");
+    out.push_str("declare ptr @aura_string_from_u64(i64)\n");
+    out.push_str("declare ptr @aura_string_from_i64(i64)\n");
+    out.push_str("declare ptr @aura_string_from_bool(i1)\n");
+    out.push_str("declare ptr @aura_string_from_f64(double)\n");
+    out.push_str("declare ptr @aura_string_adopt(ptr)\n");
+    out.push_str("declare i32 @aura_string_len(ptr)\n");
+    out.push_str("declare ptr @aura_string_slice(ptr, i32, i32)\n");
+    out.push_str("declare i32 @aura_string_find(ptr, ptr)\n");
+    out.push_str("declare i32 @aura_string_split_count(ptr, ptr)\n");
+    out.push_str("declare ptr @aura_string_split_nth(ptr, ptr, i32)\n");
+    out.push_str("declare ptr @aura_string_trim(ptr)\n");
+    out.push_str("declare ptr @aura_string_pad_left(ptr, i32)\n");
+    out.push_str("declare ptr @aura_string_pad_right(ptr, i32)\n");
+    // std::math: u32 min/max and the f64 calls are LLVM intrinsics, which fold and vectorize.
+    out.push_str("declare i32 @aura_math_pow_u32(i32, i32)\n");
+    out.push_str("declare i32 @llvm.umin.i32(i32, i32)\n");
+    out.push_str("declare i32 @llvm.umax.i32(i32, i32)\n");
+    out.push_str("declare double @llvm.fabs.f64(double)\n");
+    out.push_str("declare double @llvm.sqrt.f64(double)\n");
+    out.push_str("declare double @llvm.sin.f64(double)\n");
+    out.push_str("declare double @llvm.cos.f64(double)\n");
+    out.push_str("declare double @llvm.pow.f64(double, double)\n");
     out.push_str("declare void @aura_range_check_u32(i32, i32, i32)\n");
+    out.push_str("declare void @aura_task_spawn(ptr, ptr)\n");
+    out.push_str("declare void @aura_task_join(ptr)\n");
+    out.push_str("declare i32 @aura_chan_bounded(i32)\n");
+    out.push_str("declare i32 @aura_chan_unbounded()\n");
+    out.push_str("declare void @aura_chan_send(i32, i32)\n");
+    out.push_str("declare i32 @aura_chan_recv(i32)\n");
+    out.push_str("declare void @aura_chan_close(i32)\n");
+    out.push_str("declare i32 @aura_chan_len(i32)\n");
+    out.push_str("declare i32 @aura_scope_open()\n");
+    out.push_str("declare void @aura_scope_cancel(i32)\n");
+    out.push_str("declare i1 @aura_scope_cancelled(i32)\n");
+    out.push_str("declare void @aura_scope_close(i32)\n");
+    out.push_str("declare i32 @aura_region_enter()\n");
+    out.push_str("declare void @aura_region_exit(i32)\n");
+    out.push_str("declare void @aura_region_detach()\n");
+    out.push_str("declare void @aura_region_reattach()\n");
+    out.push_str("declare i32 @aura_time_now_ms()\n");
+    out.push_str("declare void @aura_time_sleep_ms(i32)\n");
+    out.push_str("declare i32 @aura_time_unix_s()\n");
+    out.push_str("declare i32 @aura_time_add_ms(i32, i32)\n");
+    out.push_str("declare i32 @aura_time_elapsed_ms(i32, i32)\n");
+    out.push_str("declare ptr @aura_time_format_utc(i32)\n");
+    out.push_str("declare ptr @aura_time_format_ms(i32)\n");
+    out.push_str("declare i32 @aura_fs_open(ptr)\n");
+    out.push_str("declare i32 @aura_fs_create(ptr)\n");
+    out.push_str("declare i32 @aura_fs_append(ptr)\n");
+    out.push_str("declare ptr @aura_fs_read(i32)\n");
+    out.push_str("declare void @aura_fs_write(i32, ptr)\n");
+    out.push_str("declare void @aura_fs_close(i32)\n");
+    out.push_str("declare i32 @aura_net_connect(ptr, i32)\n");
+    out.push_str("declare void @aura_net_send(i32, ptr)\n");
+    out.push_str("declare ptr @aura_net_recv(i32)\n");
+    out.push_str("declare ptr @aura_net_recv_all(i32)\n");
+    out.push_str("declare void @aura_net_close(i32)\n");
     out.push_str("declare i32 @aura_tensor_new(i32)\n");
     out.push_str("declare i32 @aura_tensor_len(i32)\n");
     out.push_str("declare i32 @aura_tensor_get(i32, i32)\n");
     out.push_str("declare void @aura_tensor_set(i32, i32, i32)\n");
+    out.push_str("declare ptr @aura_tensor_data(i32)\n");
+    out.push_str("declare i32 @aura_map_new(i32)\n");
+    out.push_str("declare i32 @aura_map_len(i32)\n");
+    out.push_str("declare void @aura_map_insert(i32, i32, i32)\n");
+    out.push_str("declare void @aura_map_insert_str(i32, ptr, i32)\n");
+    out.push_str("declare i32 @aura_map_get(i32, i32)\n");
+    out.push_str("declare i32 @aura_map_get_str(i32, ptr)\n");
+    out.push_str("declare i1 @aura_map_contains(i32, i32)\n");
+    out.push_str("declare i1 @aura_map_contains_str(i32, ptr)\n");
     out.push_str("declare i32 @aura_ai_load_model(ptr)\n");
     out.push_str("declare i32 @aura_ai_infer(i32, i32)\n");
+    out.push_str("declare i32 @aura_ai_infer_batch(i32, i32, i32)\n");
     out.push_str("declare i32 @io_load_tensor(ptr)\n");
     out.push_str("declare void @io_display(i32)\n");
-    out.push_str("declare i32 @compute_gradient(i32, i32)\n\n");
+    out.push_str("declare i32 @compute_gradient(i32, i32)\n");
+    out.push_str(&format!("declare ptr @malloc(i{})\n", target.pointer_bits()));
+    // Stack overflow check of recursive cells.
+    out.push_str("declare void @aura_stack_overflow(ptr) noreturn\n");
+    out.push_str("declare ptr @llvm.frameaddress.p0(i32)\n");
+    out.push_str("declare i1 @llvm.expect.i1(i1, i1)\n");
+    out.push_str(&format!("@aura_stack_limit = external thread_local global i{}\n\n", target.pointer_bits()));
+
+    // MMIO accessors: the one place a register address becomes a pointer.
+    if uses_mmio(module.functions.iter().filter(|(name, _)| scope.defines(name)).map(|(_, f)| f)) {
+        out.push_str(MMIO_ACCESSORS);
+    }
 
     // User extern cells (including aura-bridge generated `__ffi_*`).
     for (name, sig) in &module.externs {
-        let (sym, cc) = resolve_extern_symbol_and_cc(name, sig.call_conv);
+        let (sym, cc) = resolve_extern_symbol_and_cc(name, sig.call_conv, target);
+        let cc_s = match cc {
+            ExternLlvmCallConv::C => "",
+            ExternLlvmCallConv::Stdcall => "x86_stdcallcc ",
+        };
+
+        let (param_tys, ret_ty, abi) = extern_abi(name, sig, target)?;
+        if abi.reshapes() {
+            let (ret_s, args_s) = reshaped_extern_sig(&param_tys, ret_ty.as_ref(), &abi, target);
+            out.push_str(&format!("declare {cc_s}{ret_s} @{sym}({args_s})\n"));
+            continue;
+        }
 
         let (ret_ty, _ret_is_void) = map_type_to_llvm(&sig.ret).ok_or_else(|| LlvmBackendError {
             message: format!("unsupported return type for extern '{name}': {:?}", sig.ret),
@@ -129,45 +356,55 @@ fn emit_module_llvm(module: &ModuleIR, debug: Option<&DebugSource>) -> Result<St
                 args_s.push_str(", ");
             }
             args_s.push_str(&aty);
+            args_s.push_str(c_ext_attr(pty).map(|a| format!(" {a}")).as_deref().unwrap_or(""));
         }
 
-        let cc_s = match cc {
-            ExternLlvmCallConv::C => "",
-            ExternLlvmCallConv::Stdcall => "x86_stdcallcc ",
-        };
-        out.push_str(&format!("declare {cc_s}{ret_ty} @{sym}({args_s})\n"));
+        let ret_attr = c_ext_attr(&sig.ret).map(|a| format!("{a} ")).unwrap_or_default();
+        out.push_str(&format!("declare {cc_s}{ret_attr}{ret_ty} @{sym}({args_s})\n"));
     }
 
     if !module.externs.is_empty() {
         out.push('\n');
     }
 
+    // Functions other units define.
+    let mut declared = false;
+    for (name, f) in module.functions.iter().filter(|(name, _)| !scope.defines(name)) {
+        out.push_str(&function_declaration(f, &function_symbol(name))?);
+        declared = true;
+    }
+    if declared {
+        out.push('\n');
+    }
+
     // Emit functions.
     for (name, f) in &module.functions {
-        if name == "main" {
+        if name == "main" || !scope.defines(name) {
             continue;
         }
         emitter.emit_function(&mut out, name, f)?;
         out.push('\n');
     }
 
-    if let Some(main_f) = module.functions.get("main") {
+    if let Some(main_f) = module.functions.get("main").filter(|_| scope.defines("main")) {
         emitter.emit_function(&mut out, "aura_main", main_f)?;
         out.push('\n');
     }
 
-    // Emit any string literal globals referenced by functions.
-    // (LLVM allows globals anywhere at top-level, so this can come after functions.)
-    emitter.emit_globals(&mut out);
+    emitter.emit_task_entries(&mut out)?;
 
     // Always provide a runtime entrypoint.
-    out.push_str("define i32 @aura_entry() {\n");
-    out.push_str("entry:\n");
-    if module.functions.contains_key("main") {
-        out.push_str("  call void @aura_main()\n");
+    if matches!(scope, UnitScope::Whole | UnitScope::Entry) {
+        out.push_str("define i32 @aura_entry() {\n");
+        out.push_str("entry:\n");
+        if module.functions.contains_key("main") {
+            out.push_str("  call void @aura_main()\n");
+        }
+        out.push_str("  ret i32 0\n");
+        out.push_str("}\n");
     }
-    out.push_str("  ret i32 0\n");
-    out.push_str("}\n");
+
+    emitter.emit_debug_metadata(&mut out);
 
     Ok(out)
 }
@@ -176,48 +413,324 @@ fn emit_module_llvm(module: &ModuleIR, debug: Option<&DebugSource>) -> Result<St
 struct Emitter<'m> {
     module: &'m ModuleIR,
     debug: Option<&'m DebugSource>,
+    target: &'m NativeTarget,
+    opt: OptConfig,
     /// Map Aura function name -> LLVM symbol name.
     fn_syms: BTreeMap<String, String>,
-    next_global: u32,
-    globals: Vec<(String, String, usize)>,
+    /// Interned string literals: contents -> N of its `.strN` global.
+    strings: BTreeMap<String, usize>,
+    /// Counter for temporaries that do not correspond to an IR value.
+    next_tmp: u32,
+    /// `alloca`s the current function needs, hoisted into its entry block.
+    allocas: String,
+    /// Values of the current function holding signed (`i32`/`i64`) integers.
+    signed: HashSet<aura_ir::ValueId>,
+    /// Cells started by `~>`, each needing a task entry point.
+    task_callees: BTreeSet<String>,
+    /// Cells that can reach themselves through calls; each checks the stack on entry.
+    recursive: BTreeSet<String>,
+    /// DWARF metadata nodes; `!N` is `metadata[N]`. Only populated with a `DebugSource`.
+    metadata: Vec<String>,
+    di_unit: Option<DiUnit>,
+    di_locations: HashMap<(u32, u32, usize), usize>,
+    di_base_types: HashMap<String, usize>,
 }
 
+/// Module-level debug metadata shared by every subprogram.
 #[cfg(feature = "llvm")]
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-enum LlvmTy {
+#[derive(Clone, Copy)]
+struct DiUnit {
+    file: usize,
+    cu: usize,
+    flags: [usize; 2],
+    subroutine_ty: usize,
+}
+
+/// Debug scopes of the function being emitted.
+#[cfg(feature = "llvm")]
+struct DiFunction {
+    subprogram: usize,
+    /// Metadata of each `FunctionDebug::scopes` entry.
+    scopes: Vec<usize>,
+}
+
+#[cfg(feature = "llvm")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum LlvmTy {
     I1,
+    I8,
+    I16,
     I32,
+    I64,
+    F64,
     Ptr,
+    /// Literal struct type, used for tuples: `{ i32, double }`.
+    Struct(Vec<LlvmTy>),
+    /// Named struct type of a record (`%Point`), defined once at the top of the module.
+    Record(String, Vec<LlvmTy>),
+    /// Named tagged union of an enum (`%Shape`): an `i32` tag followed by storage for the
+    /// largest variant payload. Each variant's fields are addressed through a literal struct.
+    Enum(String, Vec<Vec<LlvmTy>>),
 }
 
 #[cfg(feature = "llvm")]
 impl LlvmTy {
-    fn as_str(self) -> &'static str {
+    /// Width of an integer type (`i1` included).
+    fn int_bits(&self) -> Option<u32> {
         match self {
-            LlvmTy::I1 => "i1",
-            LlvmTy::I32 => "i32",
-            LlvmTy::Ptr => "ptr",
+            LlvmTy::I1 => Some(1),
+            LlvmTy::I8 => Some(8),
+            LlvmTy::I16 => Some(16),
+            LlvmTy::I32 => Some(32),
+            LlvmTy::I64 => Some(64),
+            _ => None,
+        }
+    }
+
+    fn render(&self) -> String {
+        match self {
+            LlvmTy::I1 => "i1".to_string(),
+            LlvmTy::I8 => "i8".to_string(),
+            LlvmTy::I16 => "i16".to_string(),
+            LlvmTy::I32 => "i32".to_string(),
+            LlvmTy::I64 => "i64".to_string(),
+            LlvmTy::F64 => "double".to_string(),
+            LlvmTy::Ptr => "ptr".to_string(),
+            LlvmTy::Struct(fields) => {
+                let fields_s = fields.iter().map(LlvmTy::render).collect::<Vec<_>>().join(", ");
+                format!("{{ {fields_s} }}")
+            }
+            LlvmTy::Record(name, _) | LlvmTy::Enum(name, _) => format!("%{}", llvm_ident(name)),
+        }
+    }
+
+    /// Body of the named type definition of a record or enum.
+    fn definition(&self, target: &NativeTarget) -> String {
+        match self {
+            LlvmTy::Enum(_, variants) => {
+                let payload = abi::enum_payload(variants, target);
+                if payload.size == 0 {
+                    return "{ i32 }".to_string();
+                }
+                let words = payload.size.div_ceil(payload.align);
+                format!("{{ i32, [{words} x i{}] }}", payload.align * 8)
+            }
+            LlvmTy::Record(_, fields) => LlvmTy::Struct(fields.clone()).render(),
+            other => other.render(),
         }
     }
 }
 
 #[cfg(feature = "llvm")]
 impl<'m> Emitter<'m> {
-    fn new(module: &'m ModuleIR, debug: Option<&'m DebugSource>) -> Self {
+    fn new(module: &'m ModuleIR, debug: Option<&'m DebugSource>, target: &'m NativeTarget, opt: OptConfig) -> Self {
         let mut fn_syms = BTreeMap::new();
         for name in module.functions.keys() {
-            if name == "main" {
-                fn_syms.insert(name.clone(), "aura_main".to_string());
-            } else {
-                fn_syms.insert(name.clone(), llvm_ident(name));
-            }
+            fn_syms.insert(name.clone(), function_symbol(name));
         }
-        Self {
+        let mut emitter = Self {
             module,
             debug,
+            target,
+            opt,
             fn_syms,
-            next_global: 0,
-            globals: Vec::new(),
+            strings: BTreeMap::new(),
+            next_tmp: 0,
+            allocas: String::new(),
+            signed: HashSet::new(),
+            task_callees: BTreeSet::new(),
+            recursive: recursive_cells(module),
+            metadata: Vec::new(),
+            di_unit: None,
+            di_locations: HashMap::new(),
+            di_base_types: HashMap::new(),
+        };
+        if let Some(src) = debug {
+            let path = std::path::Path::new(&src.file_name);
+            let file_name = path.file_name().map_or(src.file_name.clone(), |n| n.to_string_lossy().into_owned());
+            let dir = path
+                .parent()
+                .map(|d| d.to_string_lossy().into_owned())
+                .filter(|d| !d.is_empty())
+                .unwrap_or_else(|| ".".to_string());
+            let file = emitter.add_metadata(format!(
+                "!DIFile(filename: \"{}\", directory: \"{}\")",
+                metadata_escape(&file_name),
+                metadata_escape(&dir)
+            ));
+            let cu = emitter.add_metadata(format!(
+                "distinct !DICompileUnit(language: DW_LANG_C99, file: !{file}, producer: \"aura\", isOptimized: false, runtimeVersion: 0, emissionKind: FullDebug)"
+            ));
+            let flags = [
+                emitter.add_metadata("!{i32 7, !\"Dwarf Version\", i32 4}".to_string()),
+                emitter.add_metadata("!{i32 2, !\"Debug Info Version\", i32 3}".to_string()),
+            ];
+            let no_types = emitter.add_metadata("!{null}".to_string());
+            let subroutine_ty = emitter.add_metadata(format!("!DISubroutineType(types: !{no_types})"));
+            emitter.di_unit = Some(DiUnit {
+                file,
+                cu,
+                flags,
+                subroutine_ty,
+            });
+        }
+        emitter
+    }
+
+    fn add_metadata(&mut self, node: String) -> usize {
+        self.metadata.push(node);
+        self.metadata.len() - 1
+    }
+
+    /// Starts the subprogram for `f` and the lexical blocks of its nested scopes.
+    fn begin_debug_function(&mut self, f: &aura_ir::FunctionIR, llvm_name: &str) -> Option<DiFunction> {
+        let (src, unit) = (self.debug?, self.di_unit?);
+        let lc = src.line_col(f.span);
+        let subprogram = self.add_metadata(format!(
+            "distinct !DISubprogram(name: \"{}\", linkageName: \"{llvm_name}\", scope: !{file}, file: !{file}, line: {line}, type: !{ty}, scopeLine: {line}, spFlags: DISPFlagDefinition, unit: !{cu})",
+            metadata_escape(&f.name),
+            file = unit.file,
+            line = lc.line,
+            ty = unit.subroutine_ty,
+            cu = unit.cu,
+        ));
+        let mut scopes = Vec::with_capacity(f.debug.scopes.len());
+        for sc in &f.debug.scopes {
+            let parent = sc.parent.map_or(subprogram, |p| scopes[p]);
+            let lc = src.line_col(sc.span);
+            scopes.push(self.add_metadata(format!(
+                "distinct !DILexicalBlock(scope: !{parent}, file: !{}, line: {}, column: {})",
+                unit.file, lc.line, lc.col
+            )));
+        }
+        Some(DiFunction { subprogram, scopes })
+    }
+
+    /// Interned `DILocation` for `span`, scoped to the innermost lexical block containing it
+    /// (or to `scope` when given).
+    fn debug_location(
+        &mut self,
+        f: &aura_ir::FunctionIR,
+        di: &DiFunction,
+        span: aura_ast::Span,
+        scope: Option<Option<usize>>,
+    ) -> Option<usize> {
+        let src = self.debug?;
+        let lc = src.line_col(span);
+        let scope = scope
+            .unwrap_or_else(|| f.debug.scope_at(span))
+            .map_or(di.subprogram, |s| di.scopes[s]);
+        if let Some(&id) = self.di_locations.get(&(lc.line, lc.col, scope)) {
+            return Some(id);
+        }
+        let id = self.add_metadata(format!("!DILocation(line: {}, column: {}, scope: !{scope})", lc.line, lc.col));
+        self.di_locations.insert((lc.line, lc.col, scope), id);
+        Some(id)
+    }
+
+    /// DWARF type of a variable held in an LLVM value of type `ty`. Aggregates are not described.
+    fn debug_type(&mut self, ty: &LlvmTy) -> Option<usize> {
+        let key = ty.render();
+        if let Some(&id) = self.di_base_types.get(&key) {
+            return Some(id);
+        }
+        let node = match ty {
+            LlvmTy::I1 => "!DIBasicType(name: \"bool\", size: 8, encoding: DW_ATE_boolean)".to_string(),
+            LlvmTy::I8 => "!DIBasicType(name: \"u8\", size: 8, encoding: DW_ATE_unsigned)".to_string(),
+            LlvmTy::I16 => "!DIBasicType(name: \"u16\", size: 16, encoding: DW_ATE_unsigned)".to_string(),
+            LlvmTy::I32 => "!DIBasicType(name: \"u32\", size: 32, encoding: DW_ATE_unsigned)".to_string(),
+            LlvmTy::I64 => "!DIBasicType(name: \"u64\", size: 64, encoding: DW_ATE_unsigned)".to_string(),
+            LlvmTy::F64 => "!DIBasicType(name: \"f64\", size: 64, encoding: DW_ATE_float)".to_string(),
+            LlvmTy::Ptr => {
+                let char_ty =
+                    self.add_metadata("!DIBasicType(name: \"char\", size: 8, encoding: DW_ATE_signed_char)".to_string());
+                format!(
+                    "!DIDerivedType(tag: DW_TAG_pointer_type, baseType: !{char_ty}, size: {})",
+                    self.target.pointer_bits()
+                )
+            }
+            LlvmTy::Struct(_) | LlvmTy::Record(..) | LlvmTy::Enum(..) => return None,
+        };
+        let id = self.add_metadata(node);
+        self.di_base_types.insert(key, id);
+        Some(id)
+    }
+
+    /// Replaces the `llvm.dbg.value` placeholders emitted for `f` (from `start` on) with calls
+    /// describing its source variables, dropping values that were optimized away.
+    fn finish_debug_function(
+        &mut self,
+        out: &mut String,
+        start: usize,
+        f: &aura_ir::FunctionIR,
+        di: &DiFunction,
+        value_names: &HashMap<aura_ir::ValueId, String>,
+        value_types: &HashMap<aura_ir::ValueId, LlvmTy>,
+    ) {
+        let Some(unit) = self.di_unit else { return };
+        let Some(src) = self.debug else { return };
+
+        // Each variable is described once, typed by the first value it holds.
+        let mut vars: Vec<Option<(usize, LlvmTy, usize)>> = Vec::with_capacity(f.debug.vars.len());
+        for (idx, var) in f.debug.vars.iter().enumerate() {
+            let ty = f
+                .debug
+                .values
+                .iter()
+                .filter(|dv| dv.var == idx)
+                .find_map(|dv| value_types.get(&dv.value));
+            let Some(ty) = ty.cloned() else {
+                vars.push(None);
+                continue;
+            };
+            let Some(di_ty) = self.debug_type(&ty) else {
+                vars.push(None);
+                continue;
+            };
+            let scope = var.scope.map_or(di.subprogram, |s| di.scopes[s]);
+            let lc = src.line_col(var.span);
+            let arg = var.arg.map(|a| format!("arg: {a}, ")).unwrap_or_default();
+            let node = self.add_metadata(format!(
+                "!DILocalVariable(name: \"{}\", {arg}scope: !{scope}, file: !{}, line: {}, type: !{di_ty})",
+                metadata_escape(&var.name),
+                unit.file,
+                lc.line
+            ));
+            let Some(loc) = self.debug_location(f, di, var.span, Some(var.scope)) else {
+                vars.push(None);
+                continue;
+            };
+            vars.push(Some((node, ty, loc)));
+        }
+
+        let tail = out.split_off(start);
+        for line in tail.split_inclusive('\n') {
+            let Some(idx) = line.strip_prefix(DBG_VALUE_MARKER) else {
+                out.push_str(line);
+                continue;
+            };
+            let Some(dv) = idx.trim_end().parse::<usize>().ok().and_then(|i| f.debug.values.get(i)) else {
+                continue;
+            };
+            let Some((node, ty, loc)) = vars.get(dv.var).cloned().flatten() else { continue };
+            if value_types.get(&dv.value) != Some(&ty) {
+                continue;
+            }
+            let Some(vref) = value_names.get(&dv.value) else { continue };
+            out.push_str(&format!(
+                "  call void @llvm.dbg.value(metadata {} {vref}, metadata !{node}, metadata !DIExpression()), !dbg !{loc}\n",
+                ty.render()
+            ));
+        }
+    }
+
+    fn emit_debug_metadata(&self, out: &mut String) {
+        let Some(unit) = self.di_unit else { return };
+        out.push_str("\ndeclare void @llvm.dbg.value(metadata, metadata, metadata)\n\n");
+        out.push_str(&format!("!llvm.dbg.cu = !{{!{}}}\n", unit.cu));
+        out.push_str(&format!("!llvm.module.flags = !{{!{}, !{}}}\n\n", unit.flags[0], unit.flags[1]));
+        for (i, node) in self.metadata.iter().enumerate() {
+            out.push_str(&format!("!{i} = {node}\n"));
         }
     }
 
@@ -227,16 +740,40 @@ impl<'m> Emitter<'m> {
         out.push_str(&format!("  ; dbg {}:{}:{}\n", dbg.file_name, lc.line, lc.col));
     }
 
-    fn emit_globals(&mut self, out: &mut String) {
-        for (name, data, nbytes) in self.globals.drain(..) {
+    /// Emits the name each recursive cell defined here reports when it overflows the stack.
+    fn emit_cell_names(&self, out: &mut String, scope: UnitScope) {
+        let mut any = false;
+        for name in self.recursive.iter().filter(|name| scope.defines(name)) {
             out.push_str(&format!(
-                "@{name} = private unnamed_addr constant [{nbytes} x i8] c\"{data}\", align 1\n"
+                "@.cell.{} = private unnamed_addr constant [{} x i8] c\"{}\"\n",
+                function_symbol(name),
+                name.len() + 1,
+                escape_c_string(name)
             ));
+            any = true;
         }
-        if !out.ends_with("\n\n") {
+        if any {
+            out.push('\n');
+        }
+    }
+
+    /// Emits one constant per distinct string literal, laid out as the runtime's
+    /// `AuraStrHeader` (length, `AURA_STR_STATIC`) followed by the NUL-terminated bytes.
+    fn emit_string_literals(&self, out: &mut String) {
+        let mut globals: Vec<(usize, &String)> = self.strings.iter().map(|(s, n)| (*n, s)).collect();
+        globals.sort();
+        for (n, s) in &globals {
+            out.push_str(&format!(
+                "@.str{n} = private unnamed_addr constant {} {{ i32 {}, i32 1, [{} x i8] c\"{}\" }}, align 8\n",
+                string_literal_ty(s.len()),
+                s.len(),
+                s.len() + 1,
+                escape_c_string(s)
+            ));
+        }
+        if !globals.is_empty() {
             out.push('\n');
         }
-        out.push('\n');
     }
 
     fn emit_function(
@@ -249,18 +786,34 @@ impl<'m> Emitter<'m> {
         let (ret_ty, ret_is_void) = map_type_to_llvm(&f.ret)
             .ok_or_else(|| LlvmBackendError { message: format!("unsupported return type in function '{}': {:?}", f.name, f.ret) })?;
 
-        // Parameters
+        // Parameters. A closure body takes only its environment and loads the captured
+        // values from it on entry.
         let mut args_s = String::new();
+        let mut env_loads = String::new();
         let mut value_names: HashMap<aura_ir::ValueId, String> = HashMap::new();
         let mut value_types: HashMap<aura_ir::ValueId, LlvmTy> = HashMap::new();
+        let env_s = if f.is_closure {
+            args_s.push_str("ptr %env");
+            Some(closure_env_ty(f)?.render())
+        } else {
+            None
+        };
         for (i, p) in f.params.iter().enumerate() {
             let (pty, _is_void) = map_type_to_llvm(&p.ty)
                 .ok_or_else(|| LlvmBackendError { message: format!("unsupported param type in function '{}': {:?}", f.name, p.ty) })?;
-            if i > 0 {
-                args_s.push_str(", ");
-            }
             let vname = value_reg(p.value);
-            args_s.push_str(&format!("{pty} {vname}"));
+            if let Some(env_s) = &env_s {
+                let field = i + 1;
+                env_loads.push_str(&format!(
+                    "  %env.{field} = getelementptr inbounds {env_s}, ptr %env, i32 0, i32 {field}\n"
+                ));
+                env_loads.push_str(&format!("  {vname} = load {pty}, ptr %env.{field}\n"));
+            } else {
+                if i > 0 {
+                    args_s.push_str(", ");
+                }
+                args_s.push_str(&format!("{pty} {vname}"));
+            }
             value_names.insert(p.value, vname);
             let pty_enum = map_type_to_llvm_ty(&p.ty).ok_or_else(|| LlvmBackendError {
                 message: format!("unsupported param type in function '{}': {:?}", f.name, p.ty),
@@ -268,13 +821,37 @@ impl<'m> Emitter<'m> {
             value_types.insert(p.value, pty_enum);
         }
 
-        out.push_str(&format!("define {ret_ty} @{llvm_name}({args_s}) {{\n"));
+        self.allocas.clear();
+        let mut entry_at = None;
 
+        let di = self.begin_debug_function(f, &llvm_name);
+        let dbg_attach = di.as_ref().map(|d| format!(" !dbg !{}", d.subprogram)).unwrap_or_default();
         // Emit blocks.
         // Ensure entry is first for readability.
         let mut blocks: Vec<&aura_ir::BasicBlock> = f.blocks.iter().collect();
         blocks.sort_by_key(|b| if b.id == f.entry { 0 } else { 1 });
 
+        let tensor_loops = self.vector_tensor_loops(f, &blocks);
+        // Keeps `--no-inline` effective whichever toolchain compiles the module.
+        let mut attrs = if self.opt.inline { String::new() } else { " noinline".to_string() };
+        // AddressSanitizer only instruments functions that ask for it.
+        if self.opt.sanitize.address {
+            attrs.push_str(" sanitize_address");
+        }
+        if let Some(features) = self.simd_target_features().filter(|_| !tensor_loops.is_empty()) {
+            attrs.push_str(&format!(" \"target-features\"=\"{features}\""));
+        }
+        // Frames larger than a guard page probe each page so they cannot jump past it.
+        if let Some(probe) = self.target.stack_probe() {
+            attrs.push_str(&format!(" \"probe-stack\"=\"{probe}\""));
+        }
+        out.push_str(&format!("define {ret_ty} @{llvm_name}({args_s}){attrs}{dbg_attach} {{\n"));
+        let body_start = out.len();
+
+        if self.recursive.contains(&f.name) {
+            entry_at = Some(self.emit_stack_check(out, f, &llvm_name, &block_label(f.entry), di.as_ref()));
+        }
+
         // Track which blocks exist (for validation).
         let mut block_labels: HashMap<aura_ir::BlockId, String> = HashMap::new();
         for b in &f.blocks {
@@ -283,6 +860,7 @@ impl<'m> Emitter<'m> {
 
         // Infer value types (needed for correct `phi` emission, esp. for bool short-circuit lowering).
         infer_value_types(self.module, f, &mut value_types)?;
+        self.signed = signed_int_values(self.module, f);
 
         for b in blocks {
             let label = block_labels
@@ -290,6 +868,10 @@ impl<'m> Emitter<'m> {
                 .cloned()
                 .unwrap_or_else(|| block_label(b.id));
             out.push_str(&format!("{label}:\n"));
+            if b.id == f.entry {
+                entry_at.get_or_insert(out.len());
+                out.push_str(&env_loads);
+            }
 
             // Best-effort source mapping for reading/stepping through emitted IR.
             self.emit_dbg_comment(out, b.span);
@@ -298,13 +880,14 @@ impl<'m> Emitter<'m> {
             for inst in &b.insts {
                 if let aura_ir::InstKind::Phi { incomings } = &inst.kind {
                     self.emit_dbg_comment(out, inst.span);
+                    let inst_start = out.len();
                     let Some(dest) = inst.dest else {
                         return Err(LlvmBackendError { message: "phi instruction missing dest".to_string() });
                     };
 
                     let phi_ty = value_types
                         .get(&dest)
-                        .copied()
+                        .cloned()
                         .or_else(|| infer_phi_type_from_incomings(incomings, &value_types))
                         .ok_or_else(|| {
                             LlvmBackendError {
@@ -314,28 +897,76 @@ impl<'m> Emitter<'m> {
                                 ),
                             }
                         })?
-                        .as_str();
+                        .render();
                     let dest_name = value_reg(dest);
                     value_names.insert(dest, dest_name.clone());
 
+                    // A vectorized loop is entered from its vector loop, with the index it
+                    // reached.
+                    let vector_entry = tensor_loops.iter().find(|l| l.header == b.id).map(|l| l.preheader);
                     let mut incoming_s = String::new();
                     for (i, (pred, v)) in incomings.iter().enumerate() {
                         if i > 0 {
                             incoming_s.push_str(", ");
                         }
-                        let pred_label = block_labels
+                        let mut pred_label = block_labels
                             .get(pred)
                             .cloned()
                             .unwrap_or_else(|| block_label(*pred));
-                        let vref = value_ref(*v, &value_names);
+                        let mut vref = value_ref(*v, &value_names);
+                        if vector_entry == Some(*pred) {
+                            vref = format!("%{label}.vec.i");
+                            pred_label = format!("{label}.vec.exit");
+                        }
                         incoming_s.push_str(&format!("[ {vref}, %{pred_label} ]"));
                     }
                     out.push_str(&format!("  {dest_name} = phi {phi_ty} {incoming_s}\n"));
+                    if let Some(di) = &di {
+                        self.attach_debug_location(out, inst_start, f, di, inst.span);
+                    }
                 }
             }
 
-            for inst in &b.insts {
+            let mut last_span = b.span;
+            let mut tasks: Vec<PendingTask> = Vec::new();
+            for (idx, inst) in b.insts.iter().enumerate() {
+                // Wait for a task before its result is read. Copies only rename the result.
+                let reads = match &inst.kind {
+                    aura_ir::InstKind::BindStrand {
+                        expr: aura_ir::RValue::Local(src),
+                        ..
+                    } if tasks.iter().any(|t| t.values.contains(src)) => Vec::new(),
+                    kind => kind.operands(),
+                };
+                // `scope` and `region` blocks end by closing their handle, after every task
+                // they started.
+                let closes_scope = matches!(
+                    &inst.kind,
+                    aura_ir::InstKind::Call { callee, .. } if callee == "scope.close" || callee == "region.exit"
+                );
+                let (ready, still_running): (Vec<_>, Vec<_>) = tasks
+                    .into_iter()
+                    .partition(|t| closes_scope || t.values.iter().any(|v| reads.contains(v)));
+                tasks = still_running;
+                for task in ready {
+                    self.emit_task_join(out, task);
+                }
+
+                if di.is_some() {
+                    // A variable holding a running task's result is described once it is joined.
+                    for (i, dv) in f.debug.values.iter().enumerate() {
+                        if dv.block != b.id || dv.at != idx {
+                            continue;
+                        }
+                        match tasks.iter_mut().find(|t| t.values.contains(&dv.value)) {
+                            Some(task) => task.debug_values.push(i),
+                            None => out.push_str(&format!("{DBG_VALUE_MARKER}{i}\n")),
+                        }
+                    }
+                }
                 self.emit_dbg_comment(out, inst.span);
+                let inst_start = out.len();
+                last_span = inst.span;
                 match &inst.kind {
                     aura_ir::InstKind::Phi { .. } => {}
 
@@ -350,27 +981,60 @@ impl<'m> Emitter<'m> {
                         ));
                     }
 
+                    aura_ir::InstKind::IntCast { value, from, to } => {
+                        let Some(dest) = inst.dest else { continue };
+                        let vref = value_ref(*value, &value_names);
+                        let from_ty = value_types.get(value).cloned().unwrap_or(LlvmTy::I32);
+                        let to_ty = map_type_to_llvm_ty(to).ok_or_else(|| LlvmBackendError {
+                            message: format!("unsupported integer conversion in function '{}': {to:?}", f.name),
+                        })?;
+                        let (from_bits, to_bits) = (from_ty.int_bits().unwrap_or(32), to_ty.int_bits().unwrap_or(32));
+                        if from_bits == to_bits {
+                            // Same width: only the interpretation of the bits changes.
+                            value_names.insert(dest, vref);
+                        } else {
+                            // Sema only narrows values already proven to fit the target range.
+                            let op = if from_bits > to_bits {
+                                "trunc"
+                            } else if matches!(from, aura_ir::Type::I32 | aura_ir::Type::I64) {
+                                "sext"
+                            } else {
+                                "zext"
+                            };
+                            let dest_name = value_reg(dest);
+                            out.push_str(&format!(
+                                "  {dest_name} = {op} {} {vref} to {}\n",
+                                from_ty.render(),
+                                to_ty.render()
+                            ));
+                            value_names.insert(dest, dest_name);
+                        }
+                        value_types.insert(dest, to_ty);
+                    }
+
                     aura_ir::InstKind::BindStrand { expr, .. } => {
                         let Some(dest) = inst.dest else { continue };
                         match expr {
                             aura_ir::RValue::ConstU32(n) => {
                                 value_names.insert(dest, n.to_string());
-                                value_types.insert(dest, LlvmTy::I32);
+                                value_types.insert(dest, const_int_ty(*n));
+                            }
+                            aura_ir::RValue::ConstF64(v) => {
+                                // Hex form is exact for every double.
+                                value_names.insert(dest, format!("0x{:016X}", v.to_bits()));
+                                value_types.insert(dest, LlvmTy::F64);
                             }
                             aura_ir::RValue::ConstBool(b) => {
                                 value_names.insert(dest, if *b { "true".to_string() } else { "false".to_string() });
                                 value_types.insert(dest, LlvmTy::I1);
                             }
-                            aura_ir::RValue::ConstString(_) => {
-                                // Lower string literal to a private global and materialize a `ptr` via gep.
-                                let s = match expr {
-                                    aura_ir::RValue::ConstString(s) => s,
-                                    _ => unreachable!(),
-                                };
-                                let (gname, nbytes, _data) = self.intern_c_string(s);
+                            aura_ir::RValue::ConstString(s) => {
+                                // A literal points at the bytes of its interned global, past the header.
+                                let n = self.strings[s];
                                 let dest_name = value_reg(dest);
                                 out.push_str(&format!(
-                                    "  {dest_name} = getelementptr inbounds [{nbytes} x i8], ptr @{gname}, i64 0, i64 0\n"
+                                    "  {dest_name} = getelementptr inbounds {}, ptr @.str{n}, i32 0, i32 2\n",
+                                    string_literal_ty(s.len())
                                 ));
                                 value_names.insert(dest, dest_name);
                                 value_types.insert(dest, LlvmTy::Ptr);
@@ -378,9 +1042,12 @@ impl<'m> Emitter<'m> {
                             aura_ir::RValue::Local(src) => {
                                 let src_ref = value_ref(*src, &value_names);
                                 value_names.insert(dest, src_ref);
-                                if let Some(t) = value_types.get(src).copied() {
+                                if let Some(t) = value_types.get(src).cloned() {
                                     value_types.insert(dest, t);
                                 }
+                                if let Some(task) = tasks.iter_mut().find(|t| t.values.contains(src)) {
+                                    task.values.push(dest);
+                                }
                             }
                         }
                     }
@@ -390,9 +1057,14 @@ impl<'m> Emitter<'m> {
                         let dest_name = value_reg(dest);
                         let operand_ref = value_ref(*operand, &value_names);
                         match op {
+                            aura_ir::UnaryOp::Neg if value_types.get(operand) == Some(&LlvmTy::F64) => {
+                                out.push_str(&format!("  {dest_name} = fneg double {operand_ref}\n"));
+                                value_types.insert(dest, LlvmTy::F64);
+                            }
                             aura_ir::UnaryOp::Neg => {
-                                out.push_str(&format!("  {dest_name} = sub i32 0, {operand_ref}\n"));
-                                value_types.insert(dest, LlvmTy::I32);
+                                let ty = int_operand_ty(value_types.get(operand));
+                                out.push_str(&format!("  {dest_name} = sub {} 0, {operand_ref}\n", ty.render()));
+                                value_types.insert(dest, ty);
                             }
                             aura_ir::UnaryOp::Not => {
                                 // i1 logical not: xor with true
@@ -409,17 +1081,299 @@ impl<'m> Emitter<'m> {
                         let lref = value_ref(*left, &value_names);
                         let rref = value_ref(*right, &value_names);
 
-                        // In LLVM IR, `udiv` by 0 is UB. Enforce a runtime trap.
-                        if *op == aura_ir::BinOp::Div {
+                        let is_float = value_types.get(left) == Some(&LlvmTy::F64);
+                        // Sema converts both operands to one integer type; signed ones come from i32/i64.
+                        let int_ty = int_operand_ty(value_types.get(left));
+                        let is_signed = self.signed.contains(left) || self.signed.contains(right);
+
+                        if int_ty == LlvmTy::I32 {
+                            // In LLVM IR, `udiv` by 0 is UB. Enforce a runtime trap.
+                            if *op == aura_ir::BinOp::Div && !is_float {
+                                out.push_str(&format!(
+                                    "  call void @aura_range_check_u32(i32 {rref}, i32 1, i32 -1)\n"
+                                ));
+                            }
+                            // `shl`/`lshr` by 32 or more yields poison. Enforce a runtime trap.
+                            if matches!(op, aura_ir::BinOp::Shl | aura_ir::BinOp::Shr) {
+                                out.push_str(&format!(
+                                    "  call void @aura_range_check_u32(i32 {rref}, i32 0, i32 31)\n"
+                                ));
+                            }
+                        } else {
+                            let int_s = int_ty.render();
+                            let bits = int_ty.int_bits().unwrap_or(32);
+                            if *op == aura_ir::BinOp::Div {
+                                self.emit_trap_if(out, &format!("icmp eq {int_s} {rref}, 0"));
+                            }
+                            if matches!(op, aura_ir::BinOp::Shl | aura_ir::BinOp::Shr) {
+                                self.emit_trap_if(out, &format!("icmp uge {int_s} {rref}, {bits}"));
+                            }
+                        }
+                        // `sdiv` of the minimum by -1 overflows, which is UB as well.
+                        if *op == aura_ir::BinOp::Div && is_signed && !is_float {
+                            let int_s = int_ty.render();
+                            let min = -(1i128 << (int_ty.int_bits().unwrap_or(32) - 1));
+                            let is_min = self.fresh_tmp();
+                            let is_neg_one = self.fresh_tmp();
+                            out.push_str(&format!("  {is_min} = icmp eq {int_s} {lref}, {min}\n"));
+                            out.push_str(&format!("  {is_neg_one} = icmp eq {int_s} {rref}, -1\n"));
+                            self.emit_trap_if(out, &format!("and i1 {is_min}, {is_neg_one}"));
+                        }
+
+                        let (ty, instr) = if is_float {
+                            emit_float_binop_llvm(*op)
+                        } else {
+                            emit_binop_llvm(*op)
+                        };
+                        let instr = if is_signed { signed_instr(instr) } else { instr };
+                        let ty = if ty == "i32" { int_ty.render() } else { ty.to_string() };
+                        out.push_str(&format!("  {dest_name} = {instr} {ty} {lref}, {rref}\n"));
+                        value_names.insert(dest, dest_name);
+                        value_types.insert(dest, if ty == "i1" || binop_is_comparison(*op) {
+                            LlvmTy::I1
+                        } else if ty == "double" {
+                            LlvmTy::F64
+                        } else {
+                            int_ty
+                        });
+                    }
+
+                    aura_ir::InstKind::MakeTuple { elems } => {
+                        let Some(dest) = inst.dest else { continue };
+                        let fields = elems
+                            .iter()
+                            .map(|e| value_types.get(e).cloned())
+                            .collect::<Option<Vec<_>>>()
+                            .ok_or_else(|| LlvmBackendError {
+                                message: format!("cannot infer tuple element types for v{} in function '{}'", dest.0, f.name),
+                            })?;
+                        let struct_ty = LlvmTy::Struct(fields.clone());
+                        let struct_s = struct_ty.render();
+                        // Build the aggregate with a chain of `insertvalue`s starting from `undef`.
+                        let dest_name = value_reg(dest);
+                        let mut acc = "undef".to_string();
+                        for (i, (e, fty)) in elems.iter().zip(fields.iter()).enumerate() {
+                            let step = if i + 1 == elems.len() {
+                                dest_name.clone()
+                            } else {
+                                format!("{dest_name}.{i}")
+                            };
+                            let eref = value_ref(*e, &value_names);
                             out.push_str(&format!(
-                                "  call void @aura_range_check_u32(i32 {rref}, i32 1, i32 -1)\n"
+                                "  {step} = insertvalue {struct_s} {acc}, {} {eref}, {i}\n",
+                                fty.render()
                             ));
+                            acc = step;
                         }
+                        value_names.insert(dest, dest_name);
+                        value_types.insert(dest, struct_ty);
+                    }
 
-                        let (ty, instr) = emit_binop_llvm(*op);
-                        out.push_str(&format!("  {dest_name} = {instr} {ty} {lref}, {rref}\n"));
+                    aura_ir::InstKind::TupleGet { tuple, index } => {
+                        let Some(dest) = inst.dest else { continue };
+                        let Some(LlvmTy::Struct(fields)) = value_types.get(tuple).cloned() else {
+                            return Err(LlvmBackendError {
+                                message: format!("tuple access on non-tuple v{} in function '{}'", tuple.0, f.name),
+                            });
+                        };
+                        let field_ty = fields.get(*index as usize).cloned().ok_or_else(|| LlvmBackendError {
+                            message: format!("tuple index {index} out of bounds in function '{}'", f.name),
+                        })?;
+                        let dest_name = value_reg(dest);
+                        let tref = value_ref(*tuple, &value_names);
+                        out.push_str(&format!(
+                            "  {dest_name} = extractvalue {} {tref}, {index}\n",
+                            LlvmTy::Struct(fields).render()
+                        ));
+                        value_names.insert(dest, dest_name);
+                        value_types.insert(dest, field_ty);
+                    }
+
+                    aura_ir::InstKind::MakeRecord { ty, fields } => {
+                        let Some(dest) = inst.dest else { continue };
+                        let Some(LlvmTy::Record(rec_name, field_tys)) = map_type_to_llvm_ty(ty) else {
+                            return Err(LlvmBackendError {
+                                message: format!("unsupported record type in function '{}': {ty:?}", f.name),
+                            });
+                        };
+                        let rec_s = format!("%{}", llvm_ident(&rec_name));
+                        let dest_name = value_reg(dest);
+                        let mut acc = "undef".to_string();
+                        for (i, (v, fty)) in fields.iter().zip(&field_tys).enumerate() {
+                            let step = if i + 1 == fields.len() {
+                                dest_name.clone()
+                            } else {
+                                format!("{dest_name}.{i}")
+                            };
+                            out.push_str(&format!(
+                                "  {step} = insertvalue {rec_s} {acc}, {} {}, {i}\n",
+                                fty.render(),
+                                value_ref(*v, &value_names)
+                            ));
+                            acc = step;
+                        }
+                        value_names.insert(dest, if fields.is_empty() { "zeroinitializer".to_string() } else { dest_name });
+                        value_types.insert(dest, LlvmTy::Record(rec_name, field_tys));
+                    }
+
+                    aura_ir::InstKind::RecordGet { record, index } => {
+                        let Some(dest) = inst.dest else { continue };
+                        let Some(LlvmTy::Record(rec_name, field_tys)) = value_types.get(record).cloned() else {
+                            return Err(LlvmBackendError {
+                                message: format!("field access on non-record v{} in function '{}'", record.0, f.name),
+                            });
+                        };
+                        let field_ty = field_tys.get(*index as usize).cloned().ok_or_else(|| LlvmBackendError {
+                            message: format!("record field {index} out of bounds in function '{}'", f.name),
+                        })?;
+                        let dest_name = value_reg(dest);
+                        out.push_str(&format!(
+                            "  {dest_name} = extractvalue %{} {}, {index}\n",
+                            llvm_ident(&rec_name),
+                            value_ref(*record, &value_names)
+                        ));
+                        value_names.insert(dest, dest_name);
+                        value_types.insert(dest, field_ty);
+                    }
+
+                    aura_ir::InstKind::MakeEnum { ty, tag, fields } => {
+                        let Some(dest) = inst.dest else { continue };
+                        let Some(LlvmTy::Enum(enum_name, variants)) = map_type_to_llvm_ty(ty) else {
+                            return Err(LlvmBackendError {
+                                message: format!("unsupported enum type in function '{}': {ty:?}", f.name),
+                            });
+                        };
+                        let field_tys = variants.get(*tag as usize).cloned().unwrap_or_default();
+                        let payload = LlvmTy::Struct(field_tys.clone()).render();
+                        let enum_ty = LlvmTy::Enum(enum_name, variants);
+                        // Build the value in memory: only the tag and the active payload are written.
+                        let slot = self.alloca(&enum_ty);
+                        let enum_s = enum_ty.render();
+                        out.push_str(&format!("  store i32 {tag}, ptr {slot}\n"));
+                        if !fields.is_empty() {
+                            let payload_ptr = self.fresh_tmp();
+                            out.push_str(&format!(
+                                "  {payload_ptr} = getelementptr inbounds {enum_s}, ptr {slot}, i32 0, i32 1\n"
+                            ));
+                            for (i, (v, fty)) in fields.iter().zip(&field_tys).enumerate() {
+                                let field_ptr = self.fresh_tmp();
+                                out.push_str(&format!(
+                                    "  {field_ptr} = getelementptr inbounds {payload}, ptr {payload_ptr}, i32 0, i32 {i}\n"
+                                ));
+                                out.push_str(&format!(
+                                    "  store {} {}, ptr {field_ptr}\n",
+                                    fty.render(),
+                                    value_ref(*v, &value_names)
+                                ));
+                            }
+                        }
+                        let dest_name = value_reg(dest);
+                        out.push_str(&format!("  {dest_name} = load {enum_s}, ptr {slot}\n"));
+                        value_names.insert(dest, dest_name);
+                        value_types.insert(dest, enum_ty);
+                    }
+
+                    aura_ir::InstKind::EnumTag { value } => {
+                        let Some(dest) = inst.dest else { continue };
+                        let Some(enum_ty @ LlvmTy::Enum(..)) = value_types.get(value).cloned() else {
+                            return Err(LlvmBackendError {
+                                message: format!("tag read on non-enum v{} in function '{}'", value.0, f.name),
+                            });
+                        };
+                        let dest_name = value_reg(dest);
+                        out.push_str(&format!(
+                            "  {dest_name} = extractvalue {} {}, 0\n",
+                            enum_ty.render(),
+                            value_ref(*value, &value_names)
+                        ));
+                        value_names.insert(dest, dest_name);
+                        value_types.insert(dest, LlvmTy::I32);
+                    }
+
+                    aura_ir::InstKind::EnumField { value, tag, index } => {
+                        let Some(dest) = inst.dest else { continue };
+                        let Some(LlvmTy::Enum(enum_name, variants)) = value_types.get(value).cloned() else {
+                            return Err(LlvmBackendError {
+                                message: format!("payload read on non-enum v{} in function '{}'", value.0, f.name),
+                            });
+                        };
+                        let field_tys = variants.get(*tag as usize).cloned().unwrap_or_default();
+                        let enum_ty = LlvmTy::Enum(enum_name, variants);
+                        let field_ty = field_tys.get(*index as usize).cloned().ok_or_else(|| LlvmBackendError {
+                            message: format!("enum payload field {index} out of bounds in function '{}'", f.name),
+                        })?;
+                        // Reinterpret the payload storage as the variant's fields.
+                        let slot = self.alloca(&enum_ty);
+                        let enum_s = enum_ty.render();
+                        let payload_ptr = self.fresh_tmp();
+                        let field_ptr = self.fresh_tmp();
+                        out.push_str(&format!(
+                            "  store {enum_s} {}, ptr {slot}\n",
+                            value_ref(*value, &value_names)
+                        ));
+                        out.push_str(&format!(
+                            "  {payload_ptr} = getelementptr inbounds {enum_s}, ptr {slot}, i32 0, i32 1\n"
+                        ));
+                        out.push_str(&format!(
+                            "  {field_ptr} = getelementptr inbounds {}, ptr {payload_ptr}, i32 0, i32 {index}\n",
+                            LlvmTy::Struct(field_tys).render()
+                        ));
+                        let dest_name = value_reg(dest);
+                        out.push_str(&format!("  {dest_name} = load {}, ptr {field_ptr}\n", field_ty.render()));
+                        value_names.insert(dest, dest_name);
+                        value_types.insert(dest, field_ty);
+                    }
+
+                    aura_ir::InstKind::MakeClosure { func, captures } => {
+                        let Some(dest) = inst.dest else { continue };
+                        let body = self.module.functions.get(func).ok_or_else(|| LlvmBackendError {
+                            message: format!("closure over unknown function '{func}' in function '{}'", f.name),
+                        })?;
+                        let env_s = closure_env_ty(body)?.render();
+                        let sym = self.fn_syms.get(func).cloned().unwrap_or_else(|| llvm_ident(func));
+                        let isize_s = format!("i{}", self.target.pointer_bits());
+                        let dest_name = value_reg(dest);
+                        // Environments are never freed: closures may be copied freely and
+                        // outlive the frame that built them.
+                        out.push_str(&format!("  {dest_name}.end = getelementptr {env_s}, ptr null, i32 1\n"));
+                        out.push_str(&format!("  {dest_name}.size = ptrtoint ptr {dest_name}.end to {isize_s}\n"));
+                        out.push_str(&format!("  {dest_name} = call ptr @malloc({isize_s} {dest_name}.size)\n"));
+                        out.push_str(&format!("  store ptr @{sym}, ptr {dest_name}\n"));
+                        for (i, (c, p)) in captures.iter().zip(&body.params).enumerate() {
+                            let field = i + 1;
+                            let (cty, _) = map_type_to_llvm(&p.ty).ok_or_else(|| LlvmBackendError {
+                                message: format!("unsupported capture type in function '{}': {:?}", f.name, p.ty),
+                            })?;
+                            out.push_str(&format!(
+                                "  {dest_name}.{field} = getelementptr inbounds {env_s}, ptr {dest_name}, i32 0, i32 {field}\n"
+                            ));
+                            out.push_str(&format!(
+                                "  store {cty} {}, ptr {dest_name}.{field}\n",
+                                value_ref(*c, &value_names)
+                            ));
+                        }
                         value_names.insert(dest, dest_name);
-                        value_types.insert(dest, if ty == "i1" { LlvmTy::I1 } else { LlvmTy::I32 });
+                        value_types.insert(dest, LlvmTy::Ptr);
+                    }
+
+                    aura_ir::InstKind::CallClosure { closure, ret } => {
+                        let (ret_ty, ret_is_void) = map_type_to_llvm(ret).ok_or_else(|| LlvmBackendError {
+                            message: format!("unsupported closure return type in function '{}': {ret:?}", f.name),
+                        })?;
+                        let cref = value_ref(*closure, &value_names);
+                        let code = format!("%code.{}.{idx}", b.id.0);
+                        out.push_str(&format!("  {code} = load ptr, ptr {cref}\n"));
+                        match inst.dest {
+                            Some(dest) if !ret_is_void => {
+                                let dest_name = value_reg(dest);
+                                out.push_str(&format!("  {dest_name} = call {ret_ty} {code}(ptr {cref})\n"));
+                                value_names.insert(dest, dest_name);
+                                if let Some(t) = map_type_to_llvm_ty(ret) {
+                                    value_types.insert(dest, t);
+                                }
+                            }
+                            _ => out.push_str(&format!("  call {ret_ty} {code}(ptr {cref})\n")),
+                        }
                     }
 
                     aura_ir::InstKind::Call { callee, args } => {
@@ -434,11 +1388,17 @@ impl<'m> Emitter<'m> {
                         )?;
                     }
 
-                    aura_ir::InstKind::ComputeKernel { .. } => {
-                        // For now, treat compute kernels as plain calls (sync). The C backend may map these to async.
-                        let aura_ir::InstKind::ComputeKernel { callee, args } = &inst.kind else {
-                            unreachable!();
-                        };
+                    aura_ir::InstKind::ComputeKernel { callee, args } if self.module.functions.contains_key(callee) => {
+                        let task = self.emit_task_spawn(out, f, inst.dest, callee, args, &value_names)?;
+                        if let Some((reg, ty, _)) = &task.result {
+                            value_names.insert(task.values[0], reg.clone());
+                            value_types.insert(task.values[0], ty.clone());
+                        }
+                        tasks.push(task);
+                    }
+
+                    aura_ir::InstKind::ComputeKernel { callee, args } => {
+                        // Builtins and extern cells run synchronously.
                         self.emit_call_like(
                             out,
                             f,
@@ -450,9 +1410,20 @@ impl<'m> Emitter<'m> {
                         )?;
                     }
                 }
+                if let Some(di) = &di {
+                    self.attach_debug_location(out, inst_start, f, di, inst.span);
+                }
+            }
+            // Task frames live in this function's stack, so every task ends with its block.
+            for task in tasks {
+                self.emit_task_join(out, task);
+            }
+            if di.is_some() {
+                emit_dbg_value_markers(out, f, b.id, |at| at >= b.insts.len());
             }
 
             // Terminator
+            let term_start = out.len();
             match &b.term {
                 aura_ir::Terminator::Return(None) => {
                     if !ret_is_void {
@@ -476,7 +1447,10 @@ impl<'m> Emitter<'m> {
                         .get(tgt)
                         .cloned()
                         .unwrap_or_else(|| block_label(*tgt));
-                    out.push_str(&format!("  br label %{tgt_label}\n"));
+                    match tensor_loops.iter().find(|l| l.preheader == b.id) {
+                        Some(tl) => self.emit_vector_loop(out, tl, &tgt_label, &value_names),
+                        None => out.push_str(&format!("  br label %{tgt_label}\n")),
+                    }
                 }
                 aura_ir::Terminator::CondBr {
                     cond,
@@ -506,48 +1480,585 @@ impl<'m> Emitter<'m> {
                         .get(default_bb)
                         .cloned()
                         .unwrap_or_else(|| block_label(*default_bb));
-
-                    out.push_str(&format!("  switch i32 {sref}, label %{default_label} [\n"));
-                    for (k, bb2) in cases {
-                        let case_label = block_labels
-                            .get(bb2)
-                            .cloned()
-                            .unwrap_or_else(|| block_label(*bb2));
-                        out.push_str(&format!("    i32 {}, label %{case_label}\n", *k as u32));
+                    let case_labels = cases
+                        .iter()
+                        .map(|(_, bb2)| block_labels.get(bb2).cloned().unwrap_or_else(|| block_label(*bb2)))
+                        .collect::<Vec<_>>();
+
+                    match compare_chain(f, b.id, *scrut, cases, *default_bb) {
+                        Some(arms) => {
+                            // Few cases: compare one after the other in fresh blocks.
+                            let label = block_labels.get(&b.id).cloned().unwrap_or_else(|| block_label(b.id));
+                            for (i, (pattern, action)) in arms.iter().enumerate() {
+                                let key = pattern.key().unwrap_or_default() as u32;
+                                let next = if i + 1 == arms.len() {
+                                    default_label.clone()
+                                } else {
+                                    format!("{label}.case{}", i + 1)
+                                };
+                                let c = self.fresh_tmp();
+                                out.push_str(&format!("  {c} = icmp eq i32 {sref}, {key}\n"));
+                                out.push_str(&format!(
+                                    "  br i1 {c}, label %{}, label %{next}\n",
+                                    case_labels[*action as usize]
+                                ));
+                                if i + 1 < arms.len() {
+                                    out.push_str(&format!("\n{next}:\n"));
+                                }
+                            }
+                            if arms.is_empty() {
+                                out.push_str(&format!("  br label %{default_label}\n"));
+                            }
+                        }
+                        None => {
+                            out.push_str(&format!("  switch i32 {sref}, label %{default_label} [\n"));
+                            for ((k, _), case_label) in cases.iter().zip(&case_labels) {
+                                out.push_str(&format!("    i32 {}, label %{case_label}\n", *k as u32));
+                            }
+                            out.push_str("  ]\n");
+                        }
                     }
-                    out.push_str("  ]\n");
                 }
             }
+            if let Some(di) = &di {
+                self.attach_debug_location(out, term_start, f, di, last_span);
+            }
 
             out.push('\n');
         }
-
-        out.push_str("}\n");
-        Ok(())
+
+        out.push_str("}\n");
+        if let Some(at) = entry_at {
+            out.insert_str(at, &std::mem::take(&mut self.allocas));
+        }
+        if let Some(di) = &di {
+            self.finish_debug_function(out, body_start, f, di, &value_names, &value_types);
+        }
+        Ok(())
+    }
+
+    /// Emits the entry block of a recursive cell: it aborts with the cell's name once the
+    /// frame address drops below the thread's `aura_stack_limit`, and otherwise branches to
+    /// `body`. Returns where the function's `alloca`s go.
+    fn emit_stack_check(
+        &mut self,
+        out: &mut String,
+        f: &aura_ir::FunctionIR,
+        llvm_name: &str,
+        body: &str,
+        di: Option<&DiFunction>,
+    ) -> usize {
+        let bits = self.target.pointer_bits();
+        out.push_str("stack.check:\n");
+        let start = out.len();
+        out.push_str("  %stack.frame = call ptr @llvm.frameaddress.p0(i32 0)\n");
+        out.push_str(&format!("  %stack.sp = ptrtoint ptr %stack.frame to i{bits}\n"));
+        out.push_str(&format!("  %stack.limit = load i{bits}, ptr @aura_stack_limit\n"));
+        out.push_str(&format!("  %stack.low = icmp ult i{bits} %stack.sp, %stack.limit\n"));
+        out.push_str("  %stack.cold = call i1 @llvm.expect.i1(i1 %stack.low, i1 false)\n");
+        out.push_str(&format!("  br i1 %stack.cold, label %stack.overflow, label %{body}\n"));
+        out.push_str("stack.overflow:\n");
+        out.push_str(&format!("  call void @aura_stack_overflow(ptr @.cell.{llvm_name})\n"));
+        out.push_str("  unreachable\n");
+        if let Some(di) = di {
+            self.attach_debug_location(out, start, f, di, f.span);
+        }
+        out.push('\n');
+        start
+    }
+
+    /// Appends `!dbg` for `span` to every instruction line emitted since `start`.
+    fn attach_debug_location(
+        &mut self,
+        out: &mut String,
+        start: usize,
+        f: &aura_ir::FunctionIR,
+        di: &DiFunction,
+        span: aura_ast::Span,
+    ) {
+        if out.len() == start {
+            return;
+        }
+        let Some(loc) = self.debug_location(f, di, span, None) else { return };
+        let tail = out.split_off(start);
+        for line in tail.split_inclusive('\n') {
+            let body = line.strip_suffix('\n').unwrap_or(line);
+            // Skip comments, `switch` case rows and the opening `switch ... [` line.
+            let is_inst = body.starts_with("  ")
+                && !body.starts_with("   ")
+                && !body.starts_with("  ;")
+                && !body.ends_with('[');
+            if is_inst {
+                out.push_str(&format!("{body}, !dbg !{loc}\n"));
+            } else {
+                out.push_str(line);
+            }
+        }
+    }
+
+    /// Calls an extern cell whose record params or result the target's C ABI reshapes:
+    /// records split into registers are packed into their parts, those passed in memory are
+    /// copied to an entry-block `alloca`, and results are unpacked the same way.
+    #[allow(clippy::too_many_arguments)]
+    fn emit_reshaped_extern_call(
+        &mut self,
+        out: &mut String,
+        dest: Option<aura_ir::ValueId>,
+        sym: &str,
+        call_cc: Option<&'static str>,
+        (params, ret): (&[LlvmTy], Option<&LlvmTy>),
+        abi: &ExternAbi,
+        args: &[aura_ir::ValueId],
+        value_names: &HashMap<aura_ir::ValueId, String>,
+        value_types: &mut HashMap<aura_ir::ValueId, LlvmTy>,
+    ) -> Result<(), LlvmBackendError> {
+        let cc_s = call_cc.map(|c| format!("{c} ")).unwrap_or_default();
+        let mut args_s = Vec::new();
+
+        let mut sret = None;
+        if let (Some(PassMode::Indirect { .. }), Some(t)) = (&abi.ret, ret) {
+            let slot = self.alloca(t);
+            args_s.push(format!("ptr sret({}) align {} {slot}", t.render(), abi::layout(t, self.target).align));
+            sret = Some(slot);
+        }
+
+        for ((arg, t), mode) in args.iter().zip(params).zip(&abi.params) {
+            let vref = value_ref(*arg, value_names);
+            match mode {
+                None => args_s.push(format!("{} {vref}", t.render())),
+                Some(PassMode::Direct { parts, array }) => {
+                    for (ty, v) in self.pack_record(out, t, &vref, parts, *array) {
+                        args_s.push(format!("{ty} {v}"));
+                    }
+                }
+                Some(PassMode::Indirect { byval }) => {
+                    let slot = self.alloca(t);
+                    let rec_s = t.render();
+                    out.push_str(&format!("  store {rec_s} {vref}, ptr {slot}\n"));
+                    if *byval {
+                        args_s.push(format!("ptr byval({rec_s}) align {} {slot}", abi::layout(t, self.target).align));
+                    } else {
+                        args_s.push(format!("ptr {slot}"));
+                    }
+                }
+            }
+        }
+        let args_s = args_s.join(", ");
+
+        match (&abi.ret, ret) {
+            (Some(PassMode::Direct { parts, array }), Some(t)) => {
+                let coerced_ty = PassMode::direct_ty(parts, *array);
+                let coerced = self.fresh_tmp();
+                out.push_str(&format!("  {coerced} = call {cc_s}{coerced_ty} @{sym}({args_s})\n"));
+                if let Some(d) = dest {
+                    self.unpack_record(out, t, &coerced, parts, *array, &value_reg(d));
+                    value_types.insert(d, t.clone());
+                }
+            }
+            (Some(PassMode::Indirect { .. }), Some(t)) => {
+                out.push_str(&format!("  call {cc_s}void @{sym}({args_s})\n"));
+                if let (Some(d), Some(slot)) = (dest, sret) {
+                    out.push_str(&format!("  {} = load {}, ptr {slot}\n", value_reg(d), t.render()));
+                    value_types.insert(d, t.clone());
+                }
+            }
+            (_, Some(t)) => {
+                let ret_s = t.render();
+                match dest {
+                    Some(d) => {
+                        out.push_str(&format!("  {} = call {cc_s}{ret_s} @{sym}({args_s})\n", value_reg(d)));
+                        value_types.insert(d, t.clone());
+                    }
+                    None => out.push_str(&format!("  call {cc_s}{ret_s} @{sym}({args_s})\n")),
+                }
+            }
+            (_, None) => out.push_str(&format!("  call {cc_s}void @{sym}({args_s})\n")),
+        }
+        Ok(())
+    }
+
+    /// Packs the record `vref` into register parts; returns each LLVM argument as (type, value).
+    fn pack_record(&mut self, out: &mut String, rec: &LlvmTy, vref: &str, parts: &[Part], array: bool) -> Vec<(String, String)> {
+        let rec_s = rec.render();
+        let scalars = abi::scalars(rec, self.target);
+        let mut values = Vec::with_capacity(parts.len());
+        for part in parts {
+            let part_ty = part.llvm_ty();
+            let mut acc = match part {
+                Part::F64 { .. } => "undef".to_string(),
+                Part::Int { .. } => "0".to_string(),
+            };
+            for s in scalars.iter().filter(|s| part.contains(s.offset)) {
+                let path = s.path.iter().map(u32::to_string).collect::<Vec<_>>().join(", ");
+                let field = self.fresh_tmp();
+                out.push_str(&format!("  {field} = extractvalue {rec_s} {vref}, {path}\n"));
+                let Part::Int { offset, .. } = *part else {
+                    acc = field;
+                    continue;
+                };
+                let mut word = self.scalar_to_int(out, &s.ty, &field, &part_ty);
+                let shift = (s.offset - offset) * 8;
+                if shift > 0 {
+                    let shifted = self.fresh_tmp();
+                    out.push_str(&format!("  {shifted} = shl {part_ty} {word}, {shift}\n"));
+                    word = shifted;
+                }
+                if acc == "0" {
+                    acc = word;
+                } else {
+                    let merged = self.fresh_tmp();
+                    out.push_str(&format!("  {merged} = or {part_ty} {acc}, {word}\n"));
+                    acc = merged;
+                }
+            }
+            values.push((part_ty, acc));
+        }
+        if !array || values.len() < 2 {
+            return values;
+        }
+        let array_ty = PassMode::direct_ty(parts, true);
+        let mut acc = "undef".to_string();
+        for (i, (ty, v)) in values.iter().enumerate() {
+            let next = self.fresh_tmp();
+            out.push_str(&format!("  {next} = insertvalue {array_ty} {acc}, {ty} {v}, {i}\n"));
+            acc = next;
+        }
+        vec![(array_ty, acc)]
+    }
+
+    /// Rebuilds a record from the register parts in `coerced`, defining it as `dest_name`.
+    fn unpack_record(&mut self, out: &mut String, rec: &LlvmTy, coerced: &str, parts: &[Part], array: bool, dest_name: &str) {
+        let rec_s = rec.render();
+        let coerced_ty = PassMode::direct_ty(parts, array);
+        let part_values: Vec<String> = if parts.len() == 1 {
+            vec![coerced.to_string()]
+        } else {
+            (0..parts.len())
+                .map(|i| {
+                    let v = self.fresh_tmp();
+                    out.push_str(&format!("  {v} = extractvalue {coerced_ty} {coerced}, {i}\n"));
+                    v
+                })
+                .collect()
+        };
+
+        let scalars = abi::scalars(rec, self.target);
+        if scalars.is_empty() {
+            out.push_str(&format!("  {dest_name} = freeze {rec_s} zeroinitializer\n"));
+            return;
+        }
+        let mut acc = "undef".to_string();
+        for (i, s) in scalars.iter().enumerate() {
+            let Some(k) = parts.iter().position(|p| p.contains(s.offset)) else { continue };
+            let value = match parts[k] {
+                Part::F64 { .. } => part_values[k].clone(),
+                Part::Int { offset, .. } => {
+                    let part_ty = parts[k].llvm_ty();
+                    let mut word = part_values[k].clone();
+                    let shift = (s.offset - offset) * 8;
+                    if shift > 0 {
+                        let shifted = self.fresh_tmp();
+                        out.push_str(&format!("  {shifted} = lshr {part_ty} {word}, {shift}\n"));
+                        word = shifted;
+                    }
+                    self.int_to_scalar(out, &s.ty, &word, &part_ty)
+                }
+            };
+            let path = s.path.iter().map(u32::to_string).collect::<Vec<_>>().join(", ");
+            let next = if i + 1 == scalars.len() { dest_name.to_string() } else { self.fresh_tmp() };
+            out.push_str(&format!("  {next} = insertvalue {rec_s} {acc}, {} {value}, {path}\n", s.ty.render()));
+            acc = next;
+        }
+    }
+
+    /// Widens a scalar to the integer type `int_ty` (same or larger width).
+    fn scalar_to_int(&mut self, out: &mut String, ty: &LlvmTy, v: &str, int_ty: &str) -> String {
+        let (op, from) = match ty {
+            LlvmTy::Ptr => ("ptrtoint", "ptr"),
+            LlvmTy::F64 => ("bitcast", "double"),
+            _ if ty.render() == int_ty => return v.to_string(),
+            LlvmTy::I8 => ("zext", "i8"),
+            LlvmTy::I16 => ("zext", "i16"),
+            LlvmTy::I32 => ("zext", "i32"),
+            _ => ("zext", "i1"),
+        };
+        let r = self.fresh_tmp();
+        out.push_str(&format!("  {r} = {op} {from} {v} to {int_ty}\n"));
+        r
+    }
+
+    /// Narrows the low bits of an integer part back to a scalar of type `ty`.
+    fn int_to_scalar(&mut self, out: &mut String, ty: &LlvmTy, v: &str, int_ty: &str) -> String {
+        let (op, to) = match ty {
+            LlvmTy::Ptr => ("inttoptr", "ptr"),
+            LlvmTy::F64 => ("bitcast", "double"),
+            _ if ty.render() == int_ty => return v.to_string(),
+            LlvmTy::I8 => ("trunc", "i8"),
+            LlvmTy::I16 => ("trunc", "i16"),
+            LlvmTy::I32 => ("trunc", "i32"),
+            _ => ("trunc", "i1"),
+        };
+        let r = self.fresh_tmp();
+        out.push_str(&format!("  {r} = {op} {int_ty} {v} to {to}\n"));
+        r
+    }
+
+    /// Traps at runtime when the `i1` computed by `cond` (an instruction without its
+    /// destination) is true, through the same runtime check as the `u32` guards.
+    fn emit_trap_if(&mut self, out: &mut String, cond: &str) {
+        let bad = self.fresh_tmp();
+        let wide = self.fresh_tmp();
+        out.push_str(&format!("  {bad} = {cond}\n"));
+        out.push_str(&format!("  {wide} = zext i1 {bad} to i32\n"));
+        out.push_str(&format!("  call void @aura_range_check_u32(i32 {wide}, i32 0, i32 0)\n"));
+    }
+
+    /// Reserves a stack slot for `ty` in the entry block of the current function.
+    fn alloca(&mut self, ty: &LlvmTy) -> String {
+        let slot = self.fresh_tmp();
+        let align = abi::layout(ty, self.target).align;
+        self.allocas.push_str(&format!("  {slot} = alloca {}, align {align}\n", ty.render()));
+        slot
+    }
+
+    fn fresh_tmp(&mut self) -> String {
+        let t = format!("%abi{}", self.next_tmp);
+        self.next_tmp += 1;
+        t
+    }
+
+    /// The tensor loops of `f` to emit vector code for, given the block emission order. A loop
+    /// qualifies only if the values it reads before entering are emitted by the end of its
+    /// preheader.
+    fn vector_tensor_loops(&self, f: &aura_ir::FunctionIR, order: &[&aura_ir::BasicBlock]) -> Vec<TensorLoop> {
+        if self.opt.simd_lanes().is_none() {
+            return Vec::new();
+        }
+        let position: HashMap<aura_ir::BlockId, usize> = order.iter().enumerate().map(|(i, b)| (b.id, i)).collect();
+        let defined_at: HashMap<aura_ir::ValueId, usize> = order
+            .iter()
+            .enumerate()
+            .flat_map(|(i, b)| b.insts.iter().filter_map(move |inst| inst.dest.map(|d| (d, i))))
+            .collect();
+        find_tensor_loops(f)
+            .into_iter()
+            .filter(|l| {
+                let pre = position[&l.preheader];
+                l.invariants().iter().all(|v| defined_at.get(v).is_none_or(|at| *at <= pre))
+            })
+            .collect()
+    }
+
+    /// Features a function with vector loops needs beyond the target's baseline. Wider
+    /// widths than SSE2 are only configured for a host that has them.
+    fn simd_target_features(&self) -> Option<&'static str> {
+        if !matches!(self.target.arch(), TargetArch::X86 | TargetArch::X86_64) {
+            return None;
+        }
+        match self.opt.simd {
+            SimdWidth::V256 => Some("+avx2"),
+            SimdWidth::V512 => Some("+avx512f"),
+            SimdWidth::Scalar | SimdWidth::V128 => None,
+        }
+    }
+
+    /// Ends the preheader of `tl`: runs the iterations that fill whole vectors over contiguous
+    /// elements, then enters the scalar loop at `header` with the index reached. The vector
+    /// loop only runs when every tensor holds at least `bound` elements; otherwise the scalar
+    /// loop, whose accesses are bounds-checked, covers the whole range.
+    fn emit_vector_loop(
+        &mut self,
+        out: &mut String,
+        tl: &TensorLoop,
+        header: &str,
+        value_names: &HashMap<aura_ir::ValueId, String>,
+    ) {
+        let lanes = self.opt.simd_lanes().unwrap_or(1);
+        let vty = format!("<{lanes} x i32>");
+        let p = format!("{header}.vec");
+        let start = value_ref(tl.start, value_names);
+        let bound = value_ref(tl.bound, value_names);
+
+        out.push_str(&format!("  br label %{p}\n\n{p}:\n"));
+        out.push_str(&format!("  %{p}.span = sub i32 {bound}, {start}\n"));
+        out.push_str(&format!("  %{p}.ahead = icmp ult i32 {start}, {bound}\n"));
+        out.push_str(&format!("  %{p}.wide = icmp uge i32 %{p}.span, {lanes}\n"));
+        out.push_str(&format!("  %{p}.ok = and i1 %{p}.ahead, %{p}.wide\n"));
+        let mut ok = format!("%{p}.ok");
+        for (k, t) in tl.tensors.iter().enumerate() {
+            let t = value_ref(*t, value_names);
+            out.push_str(&format!("  %{p}.len{k} = call i32 @aura_tensor_len(i32 {t})\n"));
+            out.push_str(&format!("  %{p}.fits{k} = icmp ule i32 {bound}, %{p}.len{k}\n"));
+            out.push_str(&format!("  %{p}.ok{k} = and i1 {ok}, %{p}.fits{k}\n"));
+            out.push_str(&format!("  %{p}.data{k} = call ptr @aura_tensor_data(i32 {t})\n"));
+            ok = format!("%{p}.ok{k}");
+        }
+        for v in tl.splats() {
+            let s = value_ref(v, value_names);
+            let id = v.0;
+            out.push_str(&format!("  %{p}.s{id}.0 = insertelement {vty} poison, i32 {s}, i64 0\n"));
+            out.push_str(&format!(
+                "  %{p}.s{id} = shufflevector {vty} %{p}.s{id}.0, {vty} poison, {vty} zeroinitializer\n"
+            ));
+        }
+        out.push_str(&format!("  %{p}.whole = and i32 %{p}.span, {}\n", -(lanes as i32)));
+        out.push_str(&format!("  %{p}.end = add i32 {start}, %{p}.whole\n"));
+        out.push_str(&format!("  br i1 {ok}, label %{p}.body, label %{p}.exit\n\n{p}.body:\n"));
+        out.push_str(&format!("  %{p}.j = phi i32 [ {start}, %{p} ], [ %{p}.next, %{p}.body ]\n"));
+        out.push_str(&format!("  %{p}.at = zext i32 %{p}.j to i64\n"));
+
+        let operand = |o: &Operand| match o {
+            Operand::Lane(v) => format!("%{p}.v{}", v.0),
+            Operand::Splat(v) => format!("%{p}.s{}", v.0),
+            Operand::Const(c) => format!("<{}>", vec![format!("i32 {c}"); lanes as usize].join(", ")),
+        };
+        let data = |t: &aura_ir::ValueId| tl.tensors.iter().position(|x| x == t).unwrap_or_default();
+        for (n, op) in tl.ops.iter().enumerate() {
+            match op {
+                LaneOp::Load { dest, tensor } => {
+                    let k = data(tensor);
+                    out.push_str(&format!("  %{p}.in{n} = getelementptr inbounds i32, ptr %{p}.data{k}, i64 %{p}.at\n"));
+                    out.push_str(&format!("  %{p}.v{} = load {vty}, ptr %{p}.in{n}, align 4\n", dest.0));
+                }
+                LaneOp::Binary { dest, op, left, right } => {
+                    let (_, inst) = emit_binop_llvm(*op);
+                    out.push_str(&format!("  %{p}.v{} = {inst} {vty} {}, {}\n", dest.0, operand(left), operand(right)));
+                }
+                LaneOp::MulAdd { dest, a, b, c } => {
+                    // There is no integer `llvm.fma`; a `mul` feeding an `add` is what
+                    // instruction selection turns into a multiply-accumulate (NEON `mla`).
+                    let d = dest.0;
+                    out.push_str(&format!("  %{p}.m{d} = mul {vty} {}, {}\n", operand(a), operand(b)));
+                    out.push_str(&format!("  %{p}.v{d} = add {vty} %{p}.m{d}, {}\n", operand(c)));
+                }
+                LaneOp::Store { tensor, value } => {
+                    let k = data(tensor);
+                    out.push_str(&format!("  %{p}.out{n} = getelementptr inbounds i32, ptr %{p}.data{k}, i64 %{p}.at\n"));
+                    out.push_str(&format!("  store {vty} {}, ptr %{p}.out{n}, align 4\n", operand(value)));
+                }
+            }
+        }
+        out.push_str(&format!("  %{p}.next = add i32 %{p}.j, {lanes}\n"));
+        out.push_str(&format!("  %{p}.more = icmp ult i32 %{p}.next, %{p}.end\n"));
+        out.push_str(&format!("  br i1 %{p}.more, label %{p}.body, label %{p}.exit\n\n{p}.exit:\n"));
+        out.push_str(&format!("  %{p}.i = phi i32 [ {start}, %{p} ], [ %{p}.next, %{p}.body ]\n"));
+        out.push_str(&format!("  br label %{header}\n"));
+    }
+
+    /// Starts `~> callee(args)`: fills a task frame with the arguments and queues the callee's
+    /// task entry point on the runtime scheduler.
+    fn emit_task_spawn(
+        &mut self,
+        out: &mut String,
+        cur_fn: &aura_ir::FunctionIR,
+        dest: Option<aura_ir::ValueId>,
+        callee: &str,
+        args: &[aura_ir::ValueId],
+        value_names: &HashMap<aura_ir::ValueId, String>,
+    ) -> Result<PendingTask, LlvmBackendError> {
+        let target = &self.module.functions[callee];
+        if args.len() != target.params.len() {
+            return Err(LlvmBackendError {
+                message: format!(
+                    "arity mismatch in async flow to '{callee}' in function '{}': expected {}, got {}",
+                    cur_fn.name,
+                    target.params.len(),
+                    args.len()
+                ),
+            });
+        }
+        let (fields, result_ty) = task_frame_fields(target)?;
+        let frame_ty = LlvmTy::Struct(fields.clone());
+        let frame = self.alloca(&frame_ty);
+        let frame_s = frame_ty.render();
+        for (i, arg) in args.iter().enumerate() {
+            let field = TASK_HEADER_FIELDS + i;
+            let ptr = self.fresh_tmp();
+            out.push_str(&format!("  {ptr} = getelementptr inbounds {frame_s}, ptr {frame}, i32 0, i32 {field}\n"));
+            out.push_str(&format!(
+                "  store {} {}, ptr {ptr}\n",
+                fields[field].render(),
+                value_ref(*arg, value_names)
+            ));
+        }
+        let entry = format!("{}.task", self.fn_syms.get(callee).cloned().unwrap_or_else(|| llvm_ident(callee)));
+        out.push_str(&format!("  call void @aura_task_spawn(ptr {frame}, ptr @{entry})\n"));
+        self.task_callees.insert(callee.to_string());
+
+        let dest = dest.filter(|_| result_ty.is_some());
+        Ok(PendingTask {
+            values: dest.into_iter().collect(),
+            frame,
+            frame_s,
+            result: dest.zip(result_ty).map(|(d, ty)| (value_reg(d), ty, fields.len() - 1)),
+            debug_values: Vec::new(),
+        })
     }
 
-    fn intern_c_string(&mut self, s: &str) -> (String, usize, String) {
-        let name = format!(".str{}", self.next_global);
-        self.next_global += 1;
+    /// Waits for `task` and loads its result, if any, from its frame.
+    fn emit_task_join(&mut self, out: &mut String, task: PendingTask) {
+        out.push_str(&format!("  call void @aura_task_join(ptr {})\n", task.frame));
+        let Some((reg, ty, field)) = task.result else { return };
+        let ptr = self.fresh_tmp();
+        out.push_str(&format!(
+            "  {ptr} = getelementptr inbounds {}, ptr {}, i32 0, i32 {field}\n",
+            task.frame_s, task.frame
+        ));
+        out.push_str(&format!("  {reg} = load {}, ptr {ptr}\n", ty.render()));
+        for i in task.debug_values {
+            out.push_str(&format!("{DBG_VALUE_MARKER}{i}\n"));
+        }
+    }
 
-        // Emit as a C string (escape + add NUL).
-        let mut data = String::new();
-        for b in s.as_bytes() {
-            match *b {
-                b'\\' => data.push_str("\\5C"),
-                b'\n' => data.push_str("\\0A"),
-                b'\r' => data.push_str("\\0D"),
-                b'\t' => data.push_str("\\09"),
-                b'\"' => data.push_str("\\22"),
-                0x20..=0x7E => data.push(*b as char),
-                other => data.push_str(&format!("\\{:02X}", other)),
+    /// Emits `@<cell>.task` for every cell started by `~>`: the scheduler calls it with the task
+    /// frame, and it calls the cell with the arguments stored there and stores the result back.
+    fn emit_task_entries(&mut self, out: &mut String) -> Result<(), LlvmBackendError> {
+        // Tasks run outside any `region` of the thread running them, so that a region does not
+        // free what they make.
+        let uses_regions = self.module.functions.values().any(|f| {
+            f.blocks.iter().flat_map(|b| &b.insts).any(
+                |i| matches!(&i.kind, aura_ir::InstKind::Call { callee, .. } if callee == "region.enter"),
+            )
+        });
+        for callee in std::mem::take(&mut self.task_callees) {
+            let f = &self.module.functions[&callee];
+            let sym = self.fn_syms.get(&callee).cloned().unwrap_or_else(|| llvm_ident(&callee));
+            let (fields, result_ty) = task_frame_fields(f)?;
+            let frame_s = LlvmTy::Struct(fields.clone()).render();
+
+            out.push_str(&format!("define internal void @{sym}.task(ptr %task) {{\nentry:\n"));
+            let mut args = Vec::with_capacity(f.params.len());
+            for i in 0..f.params.len() {
+                let field = TASK_HEADER_FIELDS + i;
+                let ty = fields[field].render();
+                out.push_str(&format!(
+                    "  %arg{i}.ptr = getelementptr inbounds {frame_s}, ptr %task, i32 0, i32 {field}\n"
+                ));
+                out.push_str(&format!("  %arg{i} = load {ty}, ptr %arg{i}.ptr\n"));
+                args.push(format!("{ty} %arg{i}"));
+            }
+            let args_s = args.join(", ");
+            if uses_regions {
+                out.push_str("  call void @aura_region_detach()\n");
+            }
+            match &result_ty {
+                Some(ty) => {
+                    let ty = ty.render();
+                    out.push_str(&format!("  %result = call {ty} @{sym}({args_s})\n"));
+                    out.push_str(&format!(
+                        "  %result.ptr = getelementptr inbounds {frame_s}, ptr %task, i32 0, i32 {}\n",
+                        fields.len() - 1
+                    ));
+                    out.push_str(&format!("  store {ty} %result, ptr %result.ptr\n"));
+                }
+                None => out.push_str(&format!("  call void @{sym}({args_s})\n")),
+            }
+            if uses_regions {
+                out.push_str("  call void @aura_region_reattach()\n");
             }
+            out.push_str("  ret void\n}\n\n");
         }
-        data.push_str("\\00");
-        let nbytes = s.as_bytes().len() + 1;
-
-        self.globals.push((name.clone(), data.clone(), nbytes));
-        (name, nbytes, data)
+        Ok(())
     }
 
     fn emit_call_like(
@@ -604,7 +2115,25 @@ impl<'m> Emitter<'m> {
         }
 
         if let Some(ext) = self.module.externs.get(callee) {
-            let (sym, cc) = resolve_extern_symbol_and_cc(callee, ext.call_conv);
+            let (sym, cc) = resolve_extern_symbol_and_cc(callee, ext.call_conv, self.target);
+            let call_cc = match cc {
+                ExternLlvmCallConv::C => None,
+                ExternLlvmCallConv::Stdcall => Some("x86_stdcallcc"),
+            };
+            let (param_tys, ret_ty, abi) = extern_abi(callee, ext, self.target)?;
+            if abi.reshapes() && args.len() == param_tys.len() {
+                return self.emit_reshaped_extern_call(
+                    out,
+                    dest,
+                    sym,
+                    call_cc,
+                    (&param_tys, ret_ty.as_ref()),
+                    &abi,
+                    args,
+                    value_names,
+                    value_types,
+                );
+            }
             let (callee_ret_ty, callee_ret_is_void) = map_type_to_llvm(&ext.ret).ok_or_else(|| {
                 LlvmBackendError {
                     message: format!(
@@ -635,15 +2164,90 @@ impl<'m> Emitter<'m> {
                 if i > 0 {
                     args_s.push_str(", ");
                 }
-                args_s.push_str(&format!("{pty} {}", value_ref(*arg_id, value_names)));
+                let attr = c_ext_attr(param_ty).map(|a| format!(" {a}")).unwrap_or_default();
+                args_s.push_str(&format!("{pty}{attr} {}", value_ref(*arg_id, value_names)));
+            }
+            let callee_ret_ty = match c_ext_attr(&ext.ret) {
+                Some(attr) => format!("{attr} {callee_ret_ty}"),
+                None => callee_ret_ty,
+            };
+
+            // C hands back plain strings; give them a header before Aura code sees them.
+            if let (aura_ir::Type::String, Some(d)) = (&ext.ret, dest) {
+                let raw = self.fresh_tmp();
+                let cc_s = call_cc.map(|c| format!("{c} ")).unwrap_or_default();
+                out.push_str(&format!("  {raw} = call {cc_s}ptr @{sym}({args_s})\n"));
+                out.push_str(&format!("  {} = call ptr @aura_string_adopt(ptr {raw})\n", value_reg(d)));
+                value_types.insert(d, LlvmTy::Ptr);
+                return Ok(());
             }
 
-            return emit_call_site(out, dest, callee_ret_ty, callee_ret_is_void, sym, &args_s, value_types, map_type_to_llvm_ty(&ext.ret), match cc {
-                ExternLlvmCallConv::C => None,
-                ExternLlvmCallConv::Stdcall => Some("x86_stdcallcc"),
-            });
+            return emit_call_site(out, dest, callee_ret_ty, callee_ret_is_void, sym, &args_s, value_types, map_type_to_llvm_ty(&ext.ret), call_cc);
+        }
+
+        // `hw.*` addresses a register block of the `[hardware]` manifest.
+        if let Some(op) = callee.strip_prefix("hw.") {
+            return self.emit_hw_call(out, cur_fn, dest, op, args, value_names, value_types);
+        }
+
+        // `str.from` (string interpolation) dispatches on the operand's LLVM type.
+        if callee == "str.from" {
+            let ([arg], Some(d)) = (args, dest) else {
+                return Err(LlvmBackendError {
+                    message: "str.from expects 1 arg and a destination".to_string(),
+                });
+            };
+            let aref = value_ref(*arg, value_names);
+            let dest_name = value_reg(d);
+            match value_types.get(arg).cloned() {
+                Some(LlvmTy::Ptr) => {
+                    out.push_str(&format!("  {dest_name} = getelementptr inbounds i8, ptr {aref}, i64 0\n"));
+                }
+                Some(LlvmTy::I1) => {
+                    out.push_str(&format!("  {dest_name} = call ptr @aura_string_from_bool(i1 {aref})\n"));
+                }
+                Some(LlvmTy::F64) => {
+                    out.push_str(&format!("  {dest_name} = call ptr @aura_string_from_f64(double {aref})\n"));
+                }
+                Some(LlvmTy::I32) if self.signed.contains(arg) => {
+                    let wide = self.fresh_tmp();
+                    out.push_str(&format!("  {wide} = sext i32 {aref} to i64\n"));
+                    out.push_str(&format!("  {dest_name} = call ptr @aura_string_from_i64(i64 {wide})\n"));
+                }
+                Some(LlvmTy::I32) | None => {
+                    out.push_str(&format!("  {dest_name} = call ptr @aura_string_from_u32(i32 {aref})\n"));
+                }
+                Some(ty @ (LlvmTy::I8 | LlvmTy::I16)) => {
+                    let wide = self.fresh_tmp();
+                    out.push_str(&format!("  {wide} = zext {} {aref} to i32\n", ty.render()));
+                    out.push_str(&format!("  {dest_name} = call ptr @aura_string_from_u32(i32 {wide})\n"));
+                }
+                Some(LlvmTy::I64) => {
+                    let sym = if self.signed.contains(arg) { "aura_string_from_i64" } else { "aura_string_from_u64" };
+                    out.push_str(&format!("  {dest_name} = call ptr @{sym}(i64 {aref})\n"));
+                }
+                Some(LlvmTy::Struct(_) | LlvmTy::Record(..) | LlvmTy::Enum(..)) => {
+                    return Err(LlvmBackendError {
+                        message: "str.from does not support tuple, record or enum operands".to_string(),
+                    });
+                }
+            }
+            value_types.insert(d, LlvmTy::Ptr);
+            return Ok(());
         }
 
+        // Map calls take string keys through the `_str` runtime entry points.
+        let str_key_callee;
+        let callee = match callee {
+            "map.insert" | "map.get" | "map.contains"
+                if matches!(args.get(1).and_then(|k| value_types.get(k)), Some(LlvmTy::Ptr)) =>
+            {
+                str_key_callee = format!("{callee}_str");
+                str_key_callee.as_str()
+            }
+            _ => callee,
+        };
+
         // Builtin externs.
         let (sym, ret_ty, ret_kind, arg_tys) = builtin_abi(callee).ok_or_else(|| LlvmBackendError {
             message: format!("unknown callee '{callee}' in function '{}' (missing stdlib binding)", cur_fn.name),
@@ -659,8 +2263,29 @@ impl<'m> Emitter<'m> {
             });
         }
 
+        // Compiled channels hold 32-bit slots: u32 values and tensor, model, map or channel handles.
+        if callee == "chan.send" && !matches!(args.get(1).and_then(|v| value_types.get(v)), None | Some(LlvmTy::I32)) {
+            return Err(LlvmBackendError {
+                message: format!(
+                    "chan.send in function '{}' sends a value that does not fit a 32-bit channel slot",
+                    cur_fn.name
+                ),
+            });
+        }
+
         let mut args_s = String::new();
         for (i, (arg_id, aty)) in args.iter().zip(arg_tys.iter()).enumerate() {
+            if let Some(ty @ (LlvmTy::I8 | LlvmTy::I16 | LlvmTy::I64)) = value_types.get(arg_id)
+                && ty.render() != *aty
+            {
+                return Err(LlvmBackendError {
+                    message: format!(
+                        "'{callee}' in function '{}' cannot take a {} value yet; convert it to u32 first",
+                        cur_fn.name,
+                        ty.render()
+                    ),
+                });
+            }
             if i > 0 {
                 args_s.push_str(", ");
             }
@@ -677,18 +2302,121 @@ impl<'m> Emitter<'m> {
             value_types,
             match ret_kind {
                 LlvmRetKind::Void => None,
+                LlvmRetKind::I1 => Some(LlvmTy::I1),
                 LlvmRetKind::I32 => Some(LlvmTy::I32),
+                LlvmRetKind::F64 => Some(LlvmTy::F64),
+                LlvmRetKind::Ptr => Some(LlvmTy::Ptr),
             },
             None,
         )
     }
+
+    /// `hw.open` yields the block's base address as an `i32`; `hw.read_u32`/`hw.write_u32`
+    /// add the offset and go through the volatile MMIO accessors. Access modes, masks and
+    /// instance bounds are the verifier's job, so nothing is checked again here.
+    fn emit_hw_call(
+        &mut self,
+        out: &mut String,
+        cur_fn: &aura_ir::FunctionIR,
+        dest: Option<aura_ir::ValueId>,
+        op: &str,
+        args: &[aura_ir::ValueId],
+        value_names: &HashMap<aura_ir::ValueId, String>,
+        value_types: &mut HashMap<aura_ir::ValueId, LlvmTy>,
+    ) -> Result<(), LlvmBackendError> {
+        let err = |message: String| LlvmBackendError { message };
+        match (op, args) {
+            ("open", [name, index @ ..]) if index.len() <= 1 => {
+                let name = const_string(cur_fn, *name)
+                    .ok_or_else(|| err("hw.open expects a string literal register name".to_string()))?;
+                let (reg_name, region) = self
+                    .module
+                    .mmio
+                    .iter()
+                    .find(|(n, _)| n.eq_ignore_ascii_case(name))
+                    .ok_or_else(|| err(format!("hw.open: register '{name}' is not in the [hardware] manifest of this build")))?;
+                let end = region
+                    .instances
+                    .checked_sub(1)
+                    .and_then(|last| last.checked_mul(region.stride))
+                    .and_then(|off| off.checked_add(region.base))
+                    .and_then(|start| start.checked_add(region.size));
+                if end.is_none_or(|end| end > 1 << 32) {
+                    return Err(err(format!("hw.open: register '{reg_name}' lies outside the 32-bit address space")));
+                }
+                let Some(d) = dest else { return Ok(()) };
+                let dest_name = value_reg(d);
+                match index {
+                    [] if region.instances > 1 => {
+                        return Err(err(format!(
+                            "hw.open: register '{reg_name}' has {} instances; pass an index",
+                            region.instances
+                        )));
+                    }
+                    [] => out.push_str(&format!("  {dest_name} = add i32 {}, 0\n", region.base)),
+                    [index, ..] => {
+                        let t = self.fresh_tmp();
+                        let iref = value_ref(*index, value_names);
+                        out.push_str(&format!("  {t} = mul i32 {iref}, {}\n", region.stride));
+                        out.push_str(&format!("  {dest_name} = add i32 {t}, {}\n", region.base));
+                    }
+                }
+                value_types.insert(d, LlvmTy::I32);
+                Ok(())
+            }
+            ("read_u32", [cap, offset]) => {
+                let addr = self.fresh_tmp();
+                out.push_str(&format!(
+                    "  {addr} = add i32 {}, {}\n",
+                    value_ref(*cap, value_names),
+                    value_ref(*offset, value_names)
+                ));
+                match dest {
+                    Some(d) => {
+                        out.push_str(&format!("  {} = call i32 @aura_mmio_read32(i32 {addr})\n", value_reg(d)));
+                        value_types.insert(d, LlvmTy::I32);
+                    }
+                    None => out.push_str(&format!("  call i32 @aura_mmio_read32(i32 {addr})\n")),
+                }
+                Ok(())
+            }
+            ("write_u32", [cap, offset, value]) => {
+                let addr = self.fresh_tmp();
+                out.push_str(&format!(
+                    "  {addr} = add i32 {}, {}\n",
+                    value_ref(*cap, value_names),
+                    value_ref(*offset, value_names)
+                ));
+                out.push_str(&format!(
+                    "  call void @aura_mmio_write32(i32 {addr}, i32 {})\n",
+                    value_ref(*value, value_names)
+                ));
+                if let Some(d) = dest {
+                    out.push_str(&format!("  {} = add i32 0, 0\n", value_reg(d)));
+                    value_types.insert(d, LlvmTy::I32);
+                }
+                Ok(())
+            }
+            ("open" | "read_u32" | "write_u32", _) => Err(err(format!(
+                "arity mismatch calling 'hw.{op}' in function '{}'",
+                cur_fn.name
+            ))),
+            _ => Err(err(format!(
+                "'hw.{op}' in function '{}' is not supported in compiled code (only hw.open, hw.read_u32 and hw.write_u32 are)",
+                cur_fn.name
+            ))),
+        }
+    }
 }
 
 #[cfg(feature = "llvm")]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum LlvmRetKind {
     Void,
+    I1,
     I32,
+    F64,
+    Ptr,
 }
 
 #[cfg(feature = "llvm")]
@@ -737,12 +2465,134 @@ fn builtin_abi(name: &str) -> Option<(&'static str, &'static str, LlvmRetKind, V
         "tensor.len" => Some(("aura_tensor_len", "i32", LlvmRetKind::I32, vec!["i32"])),
         "tensor.get" => Some(("aura_tensor_get", "i32", LlvmRetKind::I32, vec!["i32", "i32"])),
         "tensor.set" => Some(("aura_tensor_set", "void", LlvmRetKind::Void, vec!["i32", "i32", "i32"])),
+        "map.new" => Some(("aura_map_new", "i32", LlvmRetKind::I32, vec!["i32"])),
+        "map.len" => Some(("aura_map_len", "i32", LlvmRetKind::I32, vec!["i32"])),
+        "map.insert" => Some(("aura_map_insert", "void", LlvmRetKind::Void, vec!["i32", "i32", "i32"])),
+        "map.insert_str" => Some(("aura_map_insert_str", "void", LlvmRetKind::Void, vec!["i32", "ptr", "i32"])),
+        "map.get" => Some(("aura_map_get", "i32", LlvmRetKind::I32, vec!["i32", "i32"])),
+        "map.get_str" => Some(("aura_map_get_str", "i32", LlvmRetKind::I32, vec!["i32", "ptr"])),
+        "map.contains" => Some(("aura_map_contains", "i1", LlvmRetKind::I1, vec!["i32", "i32"])),
+        "map.contains_str" => Some(("aura_map_contains_str", "i1", LlvmRetKind::I1, vec!["i32", "ptr"])),
         "ai.load_model" => Some(("aura_ai_load_model", "i32", LlvmRetKind::I32, vec!["ptr"])),
         "ai.infer" => Some(("aura_ai_infer", "i32", LlvmRetKind::I32, vec!["i32", "i32"])),
+        "ai.infer_batch" => Some(("aura_ai_infer_batch", "i32", LlvmRetKind::I32, vec!["i32", "i32", "i32"])),
+        "chan.bounded" => Some(("aura_chan_bounded", "i32", LlvmRetKind::I32, vec!["i32"])),
+        "chan.unbounded" => Some(("aura_chan_unbounded", "i32", LlvmRetKind::I32, vec![])),
+        "chan.send" => Some(("aura_chan_send", "void", LlvmRetKind::Void, vec!["i32", "i32"])),
+        "chan.recv" => Some(("aura_chan_recv", "i32", LlvmRetKind::I32, vec!["i32"])),
+        "chan.close" => Some(("aura_chan_close", "void", LlvmRetKind::Void, vec!["i32"])),
+        "chan.len" => Some(("aura_chan_len", "i32", LlvmRetKind::I32, vec!["i32"])),
+        "scope.open" => Some(("aura_scope_open", "i32", LlvmRetKind::I32, vec![])),
+        "scope.cancel" => Some(("aura_scope_cancel", "void", LlvmRetKind::Void, vec!["i32"])),
+        "scope.cancelled" => Some(("aura_scope_cancelled", "i1", LlvmRetKind::I1, vec!["i32"])),
+        "scope.close" => Some(("aura_scope_close", "void", LlvmRetKind::Void, vec!["i32"])),
+        "region.enter" => Some(("aura_region_enter", "i32", LlvmRetKind::I32, vec![])),
+        "region.exit" => Some(("aura_region_exit", "void", LlvmRetKind::Void, vec!["i32"])),
+        "time.now_ms" => Some(("aura_time_now_ms", "i32", LlvmRetKind::I32, vec![])),
+        "time.sleep_ms" => Some(("aura_time_sleep_ms", "void", LlvmRetKind::Void, vec!["i32"])),
+        "time.unix_s" => Some(("aura_time_unix_s", "i32", LlvmRetKind::I32, vec![])),
+        "time.add_ms" => Some(("aura_time_add_ms", "i32", LlvmRetKind::I32, vec!["i32", "i32"])),
+        "time.elapsed_ms" => Some(("aura_time_elapsed_ms", "i32", LlvmRetKind::I32, vec!["i32", "i32"])),
+        "time.format_utc" => Some(("aura_time_format_utc", "ptr", LlvmRetKind::Ptr, vec!["i32"])),
+        "time.format_ms" => Some(("aura_time_format_ms", "ptr", LlvmRetKind::Ptr, vec!["i32"])),
+        "fs.open" => Some(("aura_fs_open", "i32", LlvmRetKind::I32, vec!["ptr"])),
+        "fs.create" => Some(("aura_fs_create", "i32", LlvmRetKind::I32, vec!["ptr"])),
+        "fs.append" => Some(("aura_fs_append", "i32", LlvmRetKind::I32, vec!["ptr"])),
+        "fs.read" => Some(("aura_fs_read", "ptr", LlvmRetKind::Ptr, vec!["i32"])),
+        "fs.write" => Some(("aura_fs_write", "void", LlvmRetKind::Void, vec!["i32", "ptr"])),
+        "fs.close" => Some(("aura_fs_close", "void", LlvmRetKind::Void, vec!["i32"])),
+        "net.connect" => Some(("aura_net_connect", "i32", LlvmRetKind::I32, vec!["ptr", "i32"])),
+        "net.send" => Some(("aura_net_send", "void", LlvmRetKind::Void, vec!["i32", "ptr"])),
+        "net.recv" => Some(("aura_net_recv", "ptr", LlvmRetKind::Ptr, vec!["i32"])),
+        "net.recv_all" => Some(("aura_net_recv_all", "ptr", LlvmRetKind::Ptr, vec!["i32"])),
+        "net.close" => Some(("aura_net_close", "void", LlvmRetKind::Void, vec!["i32"])),
+        "str.concat" => Some(("aura_string_concat", "ptr", LlvmRetKind::Ptr, vec!["ptr", "ptr"])),
+        "str.len" => Some(("aura_string_len", "i32", LlvmRetKind::I32, vec!["ptr"])),
+        "str.slice" => Some(("aura_string_slice", "ptr", LlvmRetKind::Ptr, vec!["ptr", "i32", "i32"])),
+        "str.find" => Some(("aura_string_find", "i32", LlvmRetKind::I32, vec!["ptr", "ptr"])),
+        "str.split_count" => Some(("aura_string_split_count", "i32", LlvmRetKind::I32, vec!["ptr", "ptr"])),
+        "str.split_nth" => Some(("aura_string_split_nth", "ptr", LlvmRetKind::Ptr, vec!["ptr", "ptr", "i32"])),
+        "str.trim" => Some(("aura_string_trim", "ptr", LlvmRetKind::Ptr, vec!["ptr"])),
+        "str.pad_left" => Some(("aura_string_pad_left", "ptr", LlvmRetKind::Ptr, vec!["ptr", "i32"])),
+        "str.pad_right" => Some(("aura_string_pad_right", "ptr", LlvmRetKind::Ptr, vec!["ptr", "i32"])),
+        "math.min" => Some(("llvm.umin.i32", "i32", LlvmRetKind::I32, vec!["i32", "i32"])),
+        "math.max" => Some(("llvm.umax.i32", "i32", LlvmRetKind::I32, vec!["i32", "i32"])),
+        "math.pow" => Some(("aura_math_pow_u32", "i32", LlvmRetKind::I32, vec!["i32", "i32"])),
+        "math.abs" => Some(("llvm.fabs.f64", "double", LlvmRetKind::F64, vec!["double"])),
+        "math.sqrt" => Some(("llvm.sqrt.f64", "double", LlvmRetKind::F64, vec!["double"])),
+        "math.sin" => Some(("llvm.sin.f64", "double", LlvmRetKind::F64, vec!["double"])),
+        "math.cos" => Some(("llvm.cos.f64", "double", LlvmRetKind::F64, vec!["double"])),
+        "math.powf" => Some(("llvm.pow.f64", "double", LlvmRetKind::F64, vec!["double", "double"])),
         _ => None,
     }
 }
 
+/// LLVM symbol of a module function; the program's `main` becomes `aura_main`.
+#[cfg(feature = "llvm")]
+fn function_symbol(name: &str) -> String {
+    if name == "main" {
+        "aura_main".to_string()
+    } else {
+        llvm_ident(name)
+    }
+}
+
+/// Functions that can call themselves, directly or through other functions of `module`.
+/// Closures count through the cell that builds them, since that is where their body runs from.
+#[cfg(feature = "llvm")]
+fn recursive_cells(module: &ModuleIR) -> BTreeSet<String> {
+    let mut edges: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for (name, f) in &module.functions {
+        let callees = edges.entry(name.as_str()).or_default();
+        for inst in f.blocks.iter().flat_map(|b| &b.insts) {
+            let callee = match &inst.kind {
+                aura_ir::InstKind::Call { callee, .. } | aura_ir::InstKind::ComputeKernel { callee, .. } => callee,
+                aura_ir::InstKind::MakeClosure { func, .. } => func,
+                _ => continue,
+            };
+            if module.functions.contains_key(callee) {
+                callees.insert(callee.as_str());
+            }
+        }
+    }
+
+    let mut recursive = BTreeSet::new();
+    for &start in edges.keys() {
+        let mut seen = BTreeSet::new();
+        let mut stack: Vec<&str> = edges[start].iter().copied().collect();
+        while let Some(name) = stack.pop() {
+            if name == start {
+                recursive.insert(start.to_string());
+                break;
+            }
+            if seen.insert(name) {
+                stack.extend(edges[name].iter().copied());
+            }
+        }
+    }
+    recursive
+}
+
+/// `declare` line for a module function another unit defines.
+#[cfg(feature = "llvm")]
+fn function_declaration(f: &aura_ir::FunctionIR, sym: &str) -> Result<String, LlvmBackendError> {
+    let (ret_ty, _) = map_type_to_llvm(&f.ret)
+        .ok_or_else(|| LlvmBackendError { message: format!("unsupported return type in function '{}': {:?}", f.name, f.ret) })?;
+    let params = if f.is_closure {
+        vec!["ptr".to_string()]
+    } else {
+        f.params
+            .iter()
+            .map(|p| {
+                map_type_to_llvm(&p.ty).map(|(ty, _)| ty).ok_or_else(|| LlvmBackendError {
+                    message: format!("unsupported param type in function '{}': {:?}", f.name, p.ty),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?
+    };
+    Ok(format!("declare {ret_ty} @{sym}({})\n", params.join(", ")))
+}
+
 #[cfg(feature = "llvm")]
 fn llvm_ident(name: &str) -> String {
     let mut out = String::new();
@@ -760,6 +2610,34 @@ fn llvm_ident(name: &str) -> String {
     out
 }
 
+/// Prefix of the placeholder lines `finish_debug_function` turns into `llvm.dbg.value` calls.
+#[cfg(feature = "llvm")]
+const DBG_VALUE_MARKER: &str = "\u{1}dbg.value ";
+
+/// Emits a placeholder for each debug value of block `bb` whose position satisfies `here`.
+#[cfg(feature = "llvm")]
+fn emit_dbg_value_markers(out: &mut String, f: &aura_ir::FunctionIR, bb: aura_ir::BlockId, here: impl Fn(usize) -> bool) {
+    for (i, dv) in f.debug.values.iter().enumerate() {
+        if dv.block == bb && here(dv.at) {
+            out.push_str(&format!("{DBG_VALUE_MARKER}{i}\n"));
+        }
+    }
+}
+
+/// Escapes a string for a metadata string literal.
+#[cfg(feature = "llvm")]
+fn metadata_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if b == b'"' || b == b'\\' || !(0x20..0x7f).contains(&b) {
+            out.push_str(&format!("\\{b:02X}"));
+        } else {
+            out.push(b as char);
+        }
+    }
+    out
+}
+
 #[cfg(feature = "llvm")]
 fn block_label(id: aura_ir::BlockId) -> String {
     format!("bb{}", id.0)
@@ -783,10 +2661,19 @@ fn map_type_to_llvm(ty: &aura_ir::Type) -> Option<(String, bool)> {
     match ty {
         aura_ir::Type::Unit => Some(("void".to_string(), true)),
         aura_ir::Type::Bool => Some(("i1".to_string(), false)),
-        aura_ir::Type::U32 => Some(("i32".to_string(), false)),
+        aura_ir::Type::U8 => Some(("i8".to_string(), false)),
+        aura_ir::Type::U16 => Some(("i16".to_string(), false)),
+        aura_ir::Type::U32 | aura_ir::Type::I32 => Some(("i32".to_string(), false)),
+        aura_ir::Type::U64 | aura_ir::Type::I64 => Some(("i64".to_string(), false)),
+        aura_ir::Type::F64 => Some(("double".to_string(), false)),
         aura_ir::Type::String => Some(("ptr".to_string(), false)),
         aura_ir::Type::Tensor => Some(("i32".to_string(), false)),
         aura_ir::Type::Opaque(_) => Some(("i32".to_string(), false)),
+        aura_ir::Type::Tuple(_) => map_type_to_llvm_ty(ty).map(|t| (t.render(), false)),
+        aura_ir::Type::Closure => Some(("ptr".to_string(), false)),
+        aura_ir::Type::Record { .. } | aura_ir::Type::Enum { .. } => {
+            map_type_to_llvm_ty(ty).map(|t| (t.render(), false))
+        }
     }
 }
 
@@ -794,12 +2681,258 @@ fn map_type_to_llvm(ty: &aura_ir::Type) -> Option<(String, bool)> {
 fn map_type_to_llvm_ty(ty: &aura_ir::Type) -> Option<LlvmTy> {
     match ty {
         aura_ir::Type::Bool => Some(LlvmTy::I1),
-        aura_ir::Type::U32 => Some(LlvmTy::I32),
+        aura_ir::Type::U8 => Some(LlvmTy::I8),
+        aura_ir::Type::U16 => Some(LlvmTy::I16),
+        aura_ir::Type::U32 | aura_ir::Type::I32 => Some(LlvmTy::I32),
+        aura_ir::Type::U64 | aura_ir::Type::I64 => Some(LlvmTy::I64),
+        aura_ir::Type::F64 => Some(LlvmTy::F64),
         aura_ir::Type::String => Some(LlvmTy::Ptr),
         aura_ir::Type::Tensor => Some(LlvmTy::I32),
         aura_ir::Type::Unit => None,
         aura_ir::Type::Opaque(_) => Some(LlvmTy::I32),
+        aura_ir::Type::Tuple(elems) => elems
+            .iter()
+            .map(map_type_to_llvm_ty)
+            .collect::<Option<Vec<_>>>()
+            .map(LlvmTy::Struct),
+        aura_ir::Type::Closure => Some(LlvmTy::Ptr),
+        aura_ir::Type::Record { name, fields } => fields
+            .iter()
+            .map(map_type_to_llvm_ty)
+            .collect::<Option<Vec<_>>>()
+            .map(|fields| LlvmTy::Record(name.clone(), fields)),
+        aura_ir::Type::Enum { name, variants } => variants
+            .iter()
+            .map(|fields| fields.iter().map(map_type_to_llvm_ty).collect::<Option<Vec<_>>>())
+            .collect::<Option<Vec<_>>>()
+            .map(|variants| LlvmTy::Enum(name.clone(), variants)),
+    }
+}
+
+/// Every record and enum the module mentions, keyed by type name.
+/// Interns every string literal in `functions`: each distinct contents gets one `.strN`
+/// global, numbered in order of first appearance.
+#[cfg(feature = "llvm")]
+fn string_literals<'f>(functions: impl Iterator<Item = &'f aura_ir::FunctionIR>) -> BTreeMap<String, usize> {
+    let mut out = BTreeMap::new();
+    for f in functions {
+        for inst in f.blocks.iter().flat_map(|b| &b.insts) {
+            if let aura_ir::InstKind::BindStrand { expr: aura_ir::RValue::ConstString(s), .. } = &inst.kind {
+                let next = out.len();
+                out.entry(s.clone()).or_insert(next);
+            }
+        }
+    }
+    out
+}
+
+/// The literal `v` is bound to in `f`, looking through copies.
+#[cfg(feature = "llvm")]
+fn const_string(f: &aura_ir::FunctionIR, v: aura_ir::ValueId) -> Option<&str> {
+    let mut v = v;
+    loop {
+        let inst = f.blocks.iter().flat_map(|b| &b.insts).find(|i| i.dest == Some(v))?;
+        match &inst.kind {
+            aura_ir::InstKind::BindStrand { expr: aura_ir::RValue::ConstString(s), .. } => return Some(s),
+            aura_ir::InstKind::BindStrand { expr: aura_ir::RValue::Local(src), .. } => v = *src,
+            _ => return None,
+        }
+    }
+}
+
+/// Whether any of `functions` reads or writes a hardware register.
+#[cfg(feature = "llvm")]
+fn uses_mmio<'f>(mut functions: impl Iterator<Item = &'f aura_ir::FunctionIR>) -> bool {
+    functions.any(|f| {
+        f.blocks.iter().flat_map(|b| &b.insts).any(|inst| {
+            matches!(&inst.kind, aura_ir::InstKind::Call { callee, .. } if callee == "hw.read_u32" || callee == "hw.write_u32")
+        })
+    })
+}
+
+/// Volatile 32-bit register access at an absolute address. Internal to each unit so that
+/// no runtime support is needed on bare-metal targets.
+#[cfg(feature = "llvm")]
+const MMIO_ACCESSORS: &str = "\
+define internal i32 @aura_mmio_read32(i32 %addr) alwaysinline {
+entry:
+  %p = inttoptr i32 %addr to ptr
+  %v = load volatile i32, ptr %p, align 4
+  ret i32 %v
+}
+
+define internal void @aura_mmio_write32(i32 %addr, i32 %v) alwaysinline {
+entry:
+  %p = inttoptr i32 %addr to ptr
+  store volatile i32 %v, ptr %p, align 4
+  ret void
+}
+
+";
+
+/// The type of an interned literal of `len` bytes: header, then the bytes and a NUL.
+#[cfg(feature = "llvm")]
+fn string_literal_ty(len: usize) -> String {
+    format!("{{ i32, i32, [{} x i8] }}", len + 1)
+}
+
+#[cfg(feature = "llvm")]
+fn escape_c_string(s: &str) -> String {
+    let mut data = String::new();
+    for b in s.as_bytes() {
+        match *b {
+            b'\\' => data.push_str("\\5C"),
+            b'\n' => data.push_str("\\0A"),
+            b'\r' => data.push_str("\\0D"),
+            b'\t' => data.push_str("\\09"),
+            b'\"' => data.push_str("\\22"),
+            0x20..=0x7E => data.push(*b as char),
+            other => data.push_str(&format!("\\{:02X}", other)),
+        }
+    }
+    data.push_str("\\00");
+    data
+}
+
+#[cfg(feature = "llvm")]
+fn named_types(module: &ModuleIR) -> BTreeMap<String, LlvmTy> {
+    fn visit(ty: &aura_ir::Type, out: &mut BTreeMap<String, LlvmTy>) {
+        match ty {
+            aura_ir::Type::Record { name, fields } => {
+                fields.iter().for_each(|f| visit(f, out));
+                if let Some(llvm_ty) = map_type_to_llvm_ty(ty) {
+                    out.insert(name.clone(), llvm_ty);
+                }
+            }
+            aura_ir::Type::Enum { name, variants } => {
+                variants.iter().flatten().for_each(|f| visit(f, out));
+                if let Some(llvm_ty) = map_type_to_llvm_ty(ty) {
+                    out.insert(name.clone(), llvm_ty);
+                }
+            }
+            aura_ir::Type::Tuple(elems) => elems.iter().for_each(|e| visit(e, out)),
+            _ => {}
+        }
+    }
+
+    let mut out = BTreeMap::new();
+    for f in module.functions.values() {
+        f.params.iter().for_each(|p| visit(&p.ty, &mut out));
+        visit(&f.ret, &mut out);
+        for inst in f.blocks.iter().flat_map(|b| &b.insts) {
+            match &inst.kind {
+                aura_ir::InstKind::MakeRecord { ty, .. }
+                | aura_ir::InstKind::MakeEnum { ty, .. }
+                | aura_ir::InstKind::CallClosure { ret: ty, .. } => visit(ty, &mut out),
+                _ => {}
+            }
+        }
+    }
+    for sig in module.externs.values() {
+        sig.params.iter().for_each(|p| visit(p, &mut out));
+        visit(&sig.ret, &mut out);
+    }
+    out
+}
+
+/// LLVM types of an extern's params and result (`None` for void), and how the target's C ABI
+/// passes the records among them.
+#[cfg(feature = "llvm")]
+fn extern_abi(
+    name: &str,
+    sig: &aura_ir::ExternFnSig,
+    target: &NativeTarget,
+) -> Result<(Vec<LlvmTy>, Option<LlvmTy>, ExternAbi), LlvmBackendError> {
+    let params = sig
+        .params
+        .iter()
+        .map(|p| {
+            map_type_to_llvm_ty(p).ok_or_else(|| LlvmBackendError {
+                message: format!("unsupported param type for extern '{name}': {p:?}"),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let ret = map_type_to_llvm_ty(&sig.ret);
+    // C has no tagged unions, so there is no layout to agree on with the other side.
+    if let Some(LlvmTy::Enum(enum_name, _)) = params.iter().chain(&ret).find(|t| matches!(t, LlvmTy::Enum(..))) {
+        return Err(LlvmBackendError {
+            message: format!("extern '{name}' cannot take or return enum '{enum_name}'"),
+        });
+    }
+    let abi = abi::classify_extern(&params, ret.as_ref(), target);
+    Ok((params, ret, abi))
+}
+
+/// Result type and parameter list of an extern whose records the C ABI reshapes.
+#[cfg(feature = "llvm")]
+fn reshaped_extern_sig(params: &[LlvmTy], ret: Option<&LlvmTy>, abi: &ExternAbi, target: &NativeTarget) -> (String, String) {
+    let mut args = Vec::new();
+    let ret_s = match (&abi.ret, ret) {
+        (Some(PassMode::Direct { parts, array }), _) => PassMode::direct_ty(parts, *array),
+        (Some(PassMode::Indirect { .. }), Some(t)) => {
+            args.push(format!("ptr sret({}) align {}", t.render(), abi::layout(t, target).align));
+            "void".to_string()
+        }
+        (None, Some(t)) => t.render(),
+        (_, None) => "void".to_string(),
+    };
+    for (t, mode) in params.iter().zip(&abi.params) {
+        match mode {
+            None => args.push(t.render()),
+            Some(PassMode::Direct { parts, array: true }) => args.push(PassMode::direct_ty(parts, true)),
+            Some(PassMode::Direct { parts, array: false }) => args.extend(parts.iter().map(Part::llvm_ty)),
+            Some(PassMode::Indirect { byval: true }) => {
+                args.push(format!("ptr byval({}) align {}", t.render(), abi::layout(t, target).align))
+            }
+            Some(PassMode::Indirect { byval: false }) => args.push("ptr".to_string()),
+        }
+    }
+    (ret_s, args.join(", "))
+}
+
+/// Fields of the runtime's `AuraTask` header at the start of every task frame: its state,
+/// entry point and queue link.
+#[cfg(feature = "llvm")]
+const TASK_HEADER_FIELDS: usize = 3;
+
+/// A `~>` task started in the current block whose result has not been waited for yet.
+#[cfg(feature = "llvm")]
+struct PendingTask {
+    /// The task's result and the copies made of it.
+    values: Vec<aura_ir::ValueId>,
+    frame: String,
+    frame_s: String,
+    /// Register the result is loaded into, its type and its frame field.
+    result: Option<(String, LlvmTy, usize)>,
+    /// `FunctionDebug::values` entries that describe the result, emitted once it is loaded.
+    debug_values: Vec<usize>,
+}
+
+/// Fields of the task frame of an async call to `callee`: the `AuraTask` header, the arguments,
+/// then a slot for the result when there is one.
+#[cfg(feature = "llvm")]
+fn task_frame_fields(callee: &aura_ir::FunctionIR) -> Result<(Vec<LlvmTy>, Option<LlvmTy>), LlvmBackendError> {
+    let mut fields = vec![LlvmTy::I32, LlvmTy::Ptr, LlvmTy::Ptr];
+    for p in &callee.params {
+        fields.push(map_type_to_llvm_ty(&p.ty).ok_or_else(|| LlvmBackendError {
+            message: format!("unsupported param type for async flow to '{}': {:?}", callee.name, p.ty),
+        })?);
+    }
+    let result = map_type_to_llvm_ty(&callee.ret);
+    fields.extend(result.clone());
+    Ok((fields, result))
+}
+
+/// Layout of a closure environment: the body's code pointer followed by its captured values.
+#[cfg(feature = "llvm")]
+fn closure_env_ty(body: &aura_ir::FunctionIR) -> Result<LlvmTy, LlvmBackendError> {
+    let mut fields = vec![LlvmTy::Ptr];
+    for p in &body.params {
+        fields.push(map_type_to_llvm_ty(&p.ty).ok_or_else(|| LlvmBackendError {
+            message: format!("unsupported capture type in closure '{}': {:?}", body.name, p.ty),
+        })?);
     }
+    Ok(LlvmTy::Struct(fields))
 }
 
 #[cfg(feature = "llvm")]
@@ -821,6 +2954,161 @@ fn emit_binop_llvm(op: aura_ir::BinOp) -> (&'static str, &'static str) {
 
         BinOp::And => ("i1", "and"),
         BinOp::Or => ("i1", "or"),
+
+        BinOp::BitAnd => ("i32", "and"),
+        BinOp::BitOr => ("i32", "or"),
+        BinOp::BitXor => ("i32", "xor"),
+        BinOp::Shl => ("i32", "shl"),
+        BinOp::Shr => ("i32", "lshr"),
+    }
+}
+
+#[cfg(feature = "llvm")]
+fn emit_float_binop_llvm(op: aura_ir::BinOp) -> (&'static str, &'static str) {
+    use aura_ir::BinOp;
+
+    match op {
+        BinOp::Add => ("double", "fadd"),
+        BinOp::Sub => ("double", "fsub"),
+        BinOp::Mul => ("double", "fmul"),
+        BinOp::Div => ("double", "fdiv"),
+
+        // Ordered comparisons: any NaN operand compares false (except `!=`).
+        BinOp::Eq => ("double", "fcmp oeq"),
+        BinOp::Ne => ("double", "fcmp une"),
+        BinOp::Lt => ("double", "fcmp olt"),
+        BinOp::Gt => ("double", "fcmp ogt"),
+        BinOp::Le => ("double", "fcmp ole"),
+        BinOp::Ge => ("double", "fcmp oge"),
+
+        BinOp::And => ("i1", "and"),
+        BinOp::Or => ("i1", "or"),
+
+        // Sema only admits bitwise operators on u32.
+        BinOp::BitAnd | BinOp::BitOr | BinOp::BitXor | BinOp::Shl | BinOp::Shr => emit_binop_llvm(op),
+    }
+}
+
+#[cfg(feature = "llvm")]
+fn binop_is_comparison(op: aura_ir::BinOp) -> bool {
+    use aura_ir::BinOp;
+
+    matches!(op, BinOp::Eq | BinOp::Ne | BinOp::Lt | BinOp::Gt | BinOp::Le | BinOp::Ge)
+}
+
+/// C callers widen `u8`/`u16` arguments and results to a register; the attribute says how.
+#[cfg(feature = "llvm")]
+fn c_ext_attr(ty: &aura_ir::Type) -> Option<&'static str> {
+    matches!(ty, aura_ir::Type::U8 | aura_ir::Type::U16).then_some("zeroext")
+}
+
+/// The signed counterpart of an unsigned instruction from `emit_binop_llvm`.
+#[cfg(feature = "llvm")]
+fn signed_instr(instr: &'static str) -> &'static str {
+    match instr {
+        "udiv" => "sdiv",
+        "icmp ult" => "icmp slt",
+        "icmp ugt" => "icmp sgt",
+        "icmp ule" => "icmp sle",
+        "icmp uge" => "icmp sge",
+        "lshr" => "ashr",
+        other => other,
+    }
+}
+
+/// Integer type of an arithmetic operand; untyped values are `u32`.
+#[cfg(feature = "llvm")]
+fn int_operand_ty(ty: Option<&LlvmTy>) -> LlvmTy {
+    match ty {
+        Some(t @ (LlvmTy::I8 | LlvmTy::I16 | LlvmTy::I64)) => t.clone(),
+        _ => LlvmTy::I32,
+    }
+}
+
+/// Integer literals that don't fit 32 bits only occur as `u64`/`i64` values.
+#[cfg(feature = "llvm")]
+fn const_int_ty(n: u64) -> LlvmTy {
+    if n > u64::from(u32::MAX) { LlvmTy::I64 } else { LlvmTy::I32 }
+}
+
+/// Values of `f` holding `i32`/`i64` integers, which need signed division, comparisons and
+/// shifts. LLVM integer types carry no sign, so it is tracked from the IR types here.
+#[cfg(feature = "llvm")]
+fn signed_int_values(module: &ModuleIR, f: &aura_ir::FunctionIR) -> HashSet<aura_ir::ValueId> {
+    let is_signed = |t: &aura_ir::Type| matches!(t, aura_ir::Type::I32 | aura_ir::Type::I64);
+    let mut signed = f.params.iter().filter(|p| is_signed(&p.ty)).map(|p| p.value).collect::<HashSet<_>>();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for inst in f.blocks.iter().flat_map(|b| &b.insts) {
+            let Some(dest) = inst.dest else { continue };
+            if signed.contains(&dest) {
+                continue;
+            }
+            let found = match &inst.kind {
+                aura_ir::InstKind::IntCast { to, .. } => is_signed(to),
+                aura_ir::InstKind::BindStrand { expr: aura_ir::RValue::Local(src), .. } => signed.contains(src),
+                aura_ir::InstKind::Phi { incomings } => incomings.iter().any(|(_, v)| signed.contains(v)),
+                aura_ir::InstKind::Unary { op: aura_ir::UnaryOp::Neg, operand } => signed.contains(operand),
+                aura_ir::InstKind::Binary { op, left, right } => {
+                    !binop_is_comparison(*op) && (signed.contains(left) || signed.contains(right))
+                }
+                aura_ir::InstKind::Call { callee, .. } => module
+                    .functions
+                    .get(callee)
+                    .map(|t| &t.ret)
+                    .or_else(|| module.externs.get(callee).map(|e| &e.ret))
+                    .is_some_and(is_signed),
+                aura_ir::InstKind::CallClosure { ret, .. } => is_signed(ret),
+                _ => false,
+            };
+            if found {
+                signed.insert(dest);
+                changed = true;
+            }
+        }
+    }
+    signed
+}
+
+/// The compares to emit instead of an LLVM `switch` for a `Switch` terminator in block `from`,
+/// when the match analysis prefers them. Each arm's action is the index of its case; switches
+/// on an enum tag are analysed as variant patterns.
+#[cfg(feature = "llvm")]
+fn compare_chain(
+    f: &aura_ir::FunctionIR,
+    from: aura_ir::BlockId,
+    scrut: aura_ir::ValueId,
+    cases: &[(u64, aura_ir::BlockId)],
+    default_bb: aura_ir::BlockId,
+) -> Option<Vec<(PatternValue, u32)>> {
+    let insts = || f.blocks.iter().flat_map(|b| &b.insts);
+    let on_tag = insts().any(|i| i.dest == Some(scrut) && matches!(i.kind, aura_ir::InstKind::EnumTag { .. }));
+    let arms = cases
+        .iter()
+        .enumerate()
+        .map(|(action, (k, _))| MatchArm {
+            pattern: if on_tag {
+                PatternValue::Tag(*k as u32)
+            } else {
+                PatternValue::IntLiteral(*k as u32 as i64)
+            },
+            action_id: action as u32,
+        })
+        .collect::<Vec<_>>();
+    let lowering = analyze_match(&arms, cases.len() as u32);
+
+    // Compare chains add predecessors, so keep the `switch` when a successor's phi names `from`.
+    let targets = cases.iter().map(|(_, bb)| *bb).chain([default_bb]).collect::<Vec<_>>();
+    let phi_from_here = f
+        .blocks
+        .iter()
+        .filter(|b| targets.contains(&b.id))
+        .flat_map(|b| &b.insts)
+        .any(|i| matches!(&i.kind, aura_ir::InstKind::Phi { incomings } if incomings.iter().any(|(p, _)| *p == from)));
+    match lowering {
+        MatchLowering::CascadeComparisons { arms, .. } if !phi_from_here => Some(arms),
+        _ => None,
     }
 }
 
@@ -831,10 +3119,10 @@ fn infer_phi_type_from_incomings(
 ) -> Option<LlvmTy> {
     let mut ty: Option<LlvmTy> = None;
     for (_, v) in incomings {
-        let Some(vt) = value_types.get(v).copied() else { continue };
-        match ty {
+        let Some(vt) = value_types.get(v).cloned() else { continue };
+        match &ty {
             None => ty = Some(vt),
-            Some(t) if t == vt => {}
+            Some(t) if *t == vt => {}
             Some(_) => return None,
         }
     }
@@ -867,25 +3155,41 @@ fn infer_value_types(
                 let inferred: Option<LlvmTy> = match &inst.kind {
                     aura_ir::InstKind::AllocCapability { .. } => None,
                     aura_ir::InstKind::RangeCheckU32 { .. } => None,
-                    aura_ir::InstKind::ComputeKernel { .. } => None,
+                    aura_ir::InstKind::IntCast { to, .. } => map_type_to_llvm_ty(to),
 
                     aura_ir::InstKind::BindStrand { expr, .. } => match expr {
-                        aura_ir::RValue::ConstU32(_) => Some(LlvmTy::I32),
+                        aura_ir::RValue::ConstU32(n) => Some(const_int_ty(*n)),
+                        aura_ir::RValue::ConstF64(_) => Some(LlvmTy::F64),
                         aura_ir::RValue::ConstBool(_) => Some(LlvmTy::I1),
                         aura_ir::RValue::ConstString(_) => Some(LlvmTy::Ptr),
-                        aura_ir::RValue::Local(src) => value_types.get(src).copied(),
+                        aura_ir::RValue::Local(src) => value_types.get(src).cloned(),
                     },
 
-                    aura_ir::InstKind::Unary { op, .. } => match op {
-                        aura_ir::UnaryOp::Neg => Some(LlvmTy::I32),
+                    aura_ir::InstKind::Unary { op, operand } => match op {
+                        aura_ir::UnaryOp::Neg if value_types.get(operand) == Some(&LlvmTy::F64) => {
+                            Some(LlvmTy::F64)
+                        }
+                        aura_ir::UnaryOp::Neg => Some(int_operand_ty(value_types.get(operand))),
                         aura_ir::UnaryOp::Not => Some(LlvmTy::I1),
                     },
 
-                    aura_ir::InstKind::Binary { op, .. } => match op {
+                    aura_ir::InstKind::Binary { op, left, .. } => match op {
                         aura_ir::BinOp::Add
                         | aura_ir::BinOp::Sub
                         | aura_ir::BinOp::Mul
-                        | aura_ir::BinOp::Div => Some(LlvmTy::I32),
+                        | aura_ir::BinOp::Div => {
+                            if value_types.get(left) == Some(&LlvmTy::F64) {
+                                Some(LlvmTy::F64)
+                            } else {
+                                Some(int_operand_ty(value_types.get(left)))
+                            }
+                        }
+
+                        aura_ir::BinOp::BitAnd
+                        | aura_ir::BinOp::BitOr
+                        | aura_ir::BinOp::BitXor
+                        | aura_ir::BinOp::Shl
+                        | aura_ir::BinOp::Shr => Some(int_operand_ty(value_types.get(left))),
 
                         aura_ir::BinOp::Eq
                         | aura_ir::BinOp::Ne
@@ -899,15 +3203,50 @@ fn infer_value_types(
 
                     aura_ir::InstKind::Phi { incomings } => infer_phi_type_from_incomings(incomings, value_types),
 
-                    aura_ir::InstKind::Call { callee, .. } => {
+                    aura_ir::InstKind::MakeTuple { elems } => elems
+                        .iter()
+                        .map(|e| value_types.get(e).cloned())
+                        .collect::<Option<Vec<_>>>()
+                        .map(LlvmTy::Struct),
+
+                    aura_ir::InstKind::TupleGet { tuple, index } => match value_types.get(tuple) {
+                        Some(LlvmTy::Struct(fields)) => fields.get(*index as usize).cloned(),
+                        _ => None,
+                    },
+
+                    aura_ir::InstKind::MakeRecord { ty, .. } => map_type_to_llvm_ty(ty),
+                    aura_ir::InstKind::RecordGet { record, index } => match value_types.get(record) {
+                        Some(LlvmTy::Record(_, fields)) => fields.get(*index as usize).cloned(),
+                        _ => None,
+                    },
+                    aura_ir::InstKind::MakeEnum { ty, .. } => map_type_to_llvm_ty(ty),
+                    aura_ir::InstKind::EnumTag { .. } => Some(LlvmTy::I32),
+                    aura_ir::InstKind::EnumField { value, tag, index } => match value_types.get(value) {
+                        Some(LlvmTy::Enum(_, variants)) => variants
+                            .get(*tag as usize)
+                            .and_then(|fields| fields.get(*index as usize))
+                            .cloned(),
+                        _ => None,
+                    },
+                    aura_ir::InstKind::MakeClosure { .. } => Some(LlvmTy::Ptr),
+                    aura_ir::InstKind::CallClosure { ret, .. } => map_type_to_llvm_ty(ret),
+
+                    aura_ir::InstKind::Call { callee, .. } | aura_ir::InstKind::ComputeKernel { callee, .. } => {
                         if let Some(t) = module.functions.get(callee) {
                             map_type_to_llvm_ty(&t.ret)
                         } else if let Some(ext) = module.externs.get(callee) {
                             map_type_to_llvm_ty(&ext.ret)
+                        } else if callee == "str.from" {
+                            Some(LlvmTy::Ptr)
+                        } else if callee.starts_with("hw.") {
+                            Some(LlvmTy::I32)
                         } else if let Some((_sym, _ret_ty, ret_kind, _arg_tys)) = builtin_abi(callee) {
                             match ret_kind {
                                 LlvmRetKind::Void => None,
+                                LlvmRetKind::I1 => Some(LlvmTy::I1),
                                 LlvmRetKind::I32 => Some(LlvmTy::I32),
+                                LlvmRetKind::F64 => Some(LlvmTy::F64),
+                                LlvmRetKind::Ptr => Some(LlvmTy::Ptr),
                             }
                         } else {
                             None
@@ -1,9 +1,17 @@
 #![forbid(unsafe_code)]
 
+#[cfg(feature = "llvm")]
+mod abi;
 mod codegen;
 pub mod debugger;
+pub mod opt_level;
 pub mod pattern_lowering;
+pub mod target;
+#[cfg(feature = "llvm")]
+mod tensor_loops;
 
-pub use codegen::{emit_llvm_ir, LlvmBackendError, LlvmArtifacts};
+pub use codegen::{emit_llvm_ir, emit_llvm_ir_for_target, emit_llvm_units_for_target, LlvmArtifacts, LlvmBackendError, LlvmUnit};
+pub use opt_level::{OptConfig, OptLevel, Sanitizers, SimdWidth};
 pub use debugger::{DwarfDebugInfo, DebuggerIntegration, FunctionDebugInfo, SourceLocation};
 pub use pattern_lowering::{MatchLowering, JumpTable, analyze_match};
+pub use target::{NativeTarget, ObjectFormat, TargetArch, TargetOs};
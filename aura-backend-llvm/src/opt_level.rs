@@ -0,0 +1,277 @@
+use crate::LlvmBackendError;
+
+/// Optimization level of the native pipeline, mirroring clang's `-O0`..`-O3`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OptLevel {
+    O0,
+    O1,
+    O2,
+    O3,
+}
+
+impl OptLevel {
+    /// Parses `0`..`3`, optionally written `O2`/`-O2`. The older `none` and `full` spellings
+    /// map to `O0` and `O3`.
+    pub fn parse(s: &str) -> Result<Self, LlvmBackendError> {
+        let t = s.trim();
+        let t = t.strip_prefix('-').unwrap_or(t);
+        let t = t.strip_prefix('O').or_else(|| t.strip_prefix('o')).unwrap_or(t);
+        match t {
+            "0" | "none" => Ok(Self::O0),
+            "1" => Ok(Self::O1),
+            "2" => Ok(Self::O2),
+            "3" | "full" => Ok(Self::O3),
+            _ => Err(LlvmBackendError {
+                message: format!("unknown optimization level '{s}' (expected 0, 1, 2 or 3)"),
+            }),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::O0 => "O0",
+            Self::O1 => "O1",
+            Self::O2 => "O2",
+            Self::O3 => "O3",
+        }
+    }
+}
+
+/// Widest vector registers the emitter may use for recognized tensor loops.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SimdWidth {
+    /// Tensor loops stay scalar.
+    Scalar,
+    /// SSE2 / NEON: every supported 64-bit target has it.
+    V128,
+    /// AVX2.
+    V256,
+    /// AVX-512F.
+    V512,
+}
+
+impl SimdWidth {
+    /// The width for a machine whose vector registers are `bits` wide.
+    pub fn from_bits(bits: u32) -> Self {
+        match bits {
+            0..=127 => Self::Scalar,
+            128..=255 => Self::V128,
+            256..=511 => Self::V256,
+            _ => Self::V512,
+        }
+    }
+
+    /// Number of `u32` tensor elements per vector; `None` for scalar code.
+    pub fn lanes(self) -> Option<u32> {
+        match self {
+            Self::Scalar => None,
+            Self::V128 => Some(4),
+            Self::V256 => Some(8),
+            Self::V512 => Some(16),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Scalar => "scalar",
+            Self::V128 => "simd128",
+            Self::V256 => "simd256",
+            Self::V512 => "simd512",
+        }
+    }
+}
+
+/// Sanitizers instrumenting the emitted objects, the bundled runtime and bridge shims, so that
+/// unsafe extern bridges can be checked while developing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Sanitizers {
+    /// AddressSanitizer: out-of-bounds accesses, use after free and leaks.
+    pub address: bool,
+    /// UndefinedBehaviorSanitizer. It only instruments C sources; emitted Aura code already
+    /// traps on division by zero and oversized shifts.
+    pub undefined: bool,
+}
+
+impl Sanitizers {
+    /// Parses a comma-separated list of `address` (`asan`) and `undefined` (`ubsan`).
+    pub fn parse(s: &str) -> Result<Self, LlvmBackendError> {
+        let mut out = Self::default();
+        for name in s.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            match name {
+                "address" | "asan" => out.address = true,
+                "undefined" | "ubsan" => out.undefined = true,
+                _ => {
+                    return Err(LlvmBackendError {
+                        message: format!("unknown sanitizer '{name}' (expected address or undefined)"),
+                    });
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.address && !self.undefined
+    }
+
+    /// Flags for every clang invocation that compiles or links instrumented code.
+    pub fn clang_args(&self) -> Vec<String> {
+        let names: Vec<&str> = [(self.address, "address"), (self.undefined, "undefined")]
+            .into_iter()
+            .filter_map(|(on, name)| on.then_some(name))
+            .collect();
+        if names.is_empty() {
+            return Vec::new();
+        }
+        // Frame pointers keep the sanitizers' stack traces complete.
+        vec![format!("-fsanitize={}", names.join(",")), "-fno-omit-frame-pointer".to_string()]
+    }
+}
+
+/// Optimization settings for LLVM emission and the clang invocation that compiles the module.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OptConfig {
+    pub level: OptLevel,
+    /// Allow inlining of Aura cells (on from `O1`).
+    pub inline: bool,
+    /// Allow loop and SLP vectorization (on from `O2`).
+    pub vectorize: bool,
+    /// Vector width for tensor loops the emitter vectorizes itself (`V128` from `O2`). Wider
+    /// settings should only be chosen for a host whose features were detected.
+    pub simd: SimdWidth,
+    /// Sanitizer instrumentation; off by default.
+    pub sanitize: Sanitizers,
+}
+
+impl OptConfig {
+    pub fn new(level: OptLevel) -> Self {
+        Self {
+            level,
+            inline: level >= OptLevel::O1,
+            vectorize: level >= OptLevel::O2,
+            simd: if level >= OptLevel::O2 { SimdWidth::V128 } else { SimdWidth::Scalar },
+            sanitize: Sanitizers::default(),
+        }
+    }
+
+    /// Lanes of the vector code emitted for tensor loops, or `None` when they stay scalar
+    /// (also under `--no-vectorize`).
+    pub fn simd_lanes(&self) -> Option<u32> {
+        if self.vectorize {
+            self.simd.lanes()
+        } else {
+            None
+        }
+    }
+
+    /// Whether the Aura-IR passes (constant folding, DCE, unreachable-block removal) run
+    /// before emission. At `O0` every source variable stays visible to the debugger.
+    pub fn runs_ir_passes(&self) -> bool {
+        self.level > OptLevel::O0
+    }
+
+    /// Flags for the clang invocation that compiles the emitted module.
+    pub fn clang_args(&self) -> Vec<String> {
+        let mut args = vec![format!("-{}", self.level.as_str())];
+        args.extend(self.sanitize.clang_args());
+        if self.level == OptLevel::O0 {
+            return args;
+        }
+        if !self.inline {
+            args.push("-fno-inline".to_string());
+        }
+        if self.vectorize {
+            args.push("-fvectorize".to_string());
+            args.push("-fslp-vectorize".to_string());
+        } else {
+            args.push("-fno-vectorize".to_string());
+            args.push("-fno-slp-vectorize".to_string());
+        }
+        args
+    }
+
+    /// Stable one-line description (`O2 +inline +vectorize simd128`) for build metadata and
+    /// cache keys.
+    pub fn summary(&self) -> String {
+        let flag = |on: bool, name: &str| format!("{}{name}", if on { '+' } else { '-' });
+        let mut summary = format!(
+            "{} {} {} {}",
+            self.level.as_str(),
+            flag(self.inline, "inline"),
+            flag(self.vectorize, "vectorize"),
+            self.simd.as_str()
+        );
+        if self.sanitize.address {
+            summary.push_str(" +asan");
+        }
+        if self.sanitize.undefined {
+            summary.push_str(" +ubsan");
+        }
+        summary
+    }
+}
+
+impl Default for OptConfig {
+    fn default() -> Self {
+        Self::new(OptLevel::O2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_parse_in_every_accepted_spelling() {
+        assert_eq!(OptLevel::parse("2").unwrap(), OptLevel::O2);
+        assert_eq!(OptLevel::parse("O3").unwrap(), OptLevel::O3);
+        assert_eq!(OptLevel::parse("-O1").unwrap(), OptLevel::O1);
+        assert_eq!(OptLevel::parse("none").unwrap(), OptLevel::O0);
+        assert_eq!(OptLevel::parse("full").unwrap(), OptLevel::O3);
+        let err = OptLevel::parse("4").unwrap_err();
+        assert!(err.message.contains("unknown optimization level"), "{}", err.message);
+    }
+
+    #[test]
+    fn clang_args_follow_the_inline_and_vectorize_controls() {
+        assert_eq!(OptConfig::new(OptLevel::O0).clang_args(), vec!["-O0"]);
+
+        let o1 = OptConfig::new(OptLevel::O1);
+        assert_eq!(o1.clang_args(), vec!["-O1", "-fno-vectorize", "-fno-slp-vectorize"]);
+
+        let mut o3 = OptConfig::new(OptLevel::O3);
+        assert_eq!(o3.summary(), "O3 +inline +vectorize simd128");
+        o3.inline = false;
+        assert_eq!(o3.clang_args(), vec!["-O3", "-fno-inline", "-fvectorize", "-fslp-vectorize"]);
+        assert_eq!(o3.summary(), "O3 -inline +vectorize simd128");
+    }
+
+    #[test]
+    fn sanitizers_add_their_flags_at_every_level() {
+        let mut o0 = OptConfig::new(OptLevel::O0);
+        o0.sanitize = Sanitizers::parse("asan, undefined").unwrap();
+        assert_eq!(o0.clang_args(), vec!["-O0", "-fsanitize=address,undefined", "-fno-omit-frame-pointer"]);
+        assert_eq!(o0.summary(), "O0 -inline -vectorize scalar +asan +ubsan");
+
+        let mut o2 = OptConfig::new(OptLevel::O2);
+        o2.sanitize = Sanitizers::parse("address").unwrap();
+        assert_eq!(o2.clang_args()[..2], ["-O2", "-fsanitize=address"]);
+        assert!(Sanitizers::parse("").unwrap().is_empty());
+        let err = Sanitizers::parse("thread").unwrap_err();
+        assert!(err.message.contains("unknown sanitizer 'thread'"), "{}", err.message);
+    }
+
+    #[test]
+    fn tensor_loop_lanes_follow_the_simd_width() {
+        assert_eq!(OptConfig::new(OptLevel::O1).simd_lanes(), None);
+        let mut o2 = OptConfig::new(OptLevel::O2);
+        assert_eq!(o2.simd_lanes(), Some(4));
+        o2.simd = SimdWidth::from_bits(512);
+        assert_eq!(o2.simd_lanes(), Some(16));
+        assert_eq!(o2.summary(), "O2 +inline +vectorize simd512");
+        o2.vectorize = false;
+        assert_eq!(o2.simd_lanes(), None);
+        assert_eq!(SimdWidth::from_bits(256), SimdWidth::V256);
+        assert_eq!(SimdWidth::from_bits(0), SimdWidth::Scalar);
+    }
+}
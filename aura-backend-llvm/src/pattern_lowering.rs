@@ -1,11 +1,12 @@
-/// Jump-table lowering for dense match expressions.
-///
-/// When a match has many integer literal patterns, it's more efficient to
-/// generate a jump table (LLVM switch) than cascading if/else branches.
-///
-/// This module detects dense patterns and lowers them to jump tables.
+//! Jump-table lowering for dense match expressions.
+//!
+//! When a match has many integer literal patterns, it's more efficient to
+//! generate a jump table (LLVM switch) than cascading if/else branches.
+//!
+//! This module detects dense patterns and lowers them to jump tables. Enum matches dispatch
+//! the same way on the variant tag, which the scrutinee's tagged union stores first.
 
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::BTreeSet;
 
 /// Represents a match arm with a pattern and action.
 #[derive(Clone, Debug)]
@@ -20,6 +21,19 @@ pub enum PatternValue {
     IntLiteral(i64),
     StringLiteral(String),
     Variant(String),
+    /// An enum variant, identified by its tag.
+    Tag(u32),
+}
+
+impl PatternValue {
+    /// The integer a switch compares against, for patterns that have one.
+    pub fn key(&self) -> Option<i64> {
+        match self {
+            PatternValue::IntLiteral(v) => Some(*v),
+            PatternValue::Tag(tag) => Some(i64::from(*tag)),
+            PatternValue::StringLiteral(_) | PatternValue::Variant(_) => None,
+        }
+    }
 }
 
 /// Metrics for determining if a jump table is beneficial.
@@ -61,15 +75,10 @@ pub struct JumpTable {
 }
 
 impl JumpTable {
-    /// Create a jump table from a set of match arms with integer literals.
+    /// Create a jump table from a set of match arms with integer literals or variant tags.
     pub fn from_arms(arms: &[MatchArm], default_action: u32) -> Option<Self> {
-        // Extract only integer literals
-        let mut literals = BTreeSet::new();
-        for arm in arms {
-            if let PatternValue::IntLiteral(val) = arm.pattern {
-                literals.insert(val);
-            }
-        }
+        // Extract only integer keys
+        let literals: BTreeSet<i64> = arms.iter().filter_map(|arm| arm.pattern.key()).collect();
 
         if literals.is_empty() {
             return None;
@@ -88,7 +97,7 @@ impl JumpTable {
         let mut entries = vec![None; range];
 
         for arm in arms {
-            if let PatternValue::IntLiteral(val) = arm.pattern {
+            if let Some(val) = arm.pattern.key() {
                 let index = (val - min) as usize;
                 entries[index] = Some(arm.action_id);
             }
@@ -276,4 +285,28 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_variant_tags_dispatch_like_integers() {
+        let arms: Vec<MatchArm> = (0..4)
+            .map(|tag| MatchArm {
+                pattern: PatternValue::Tag(tag),
+                action_id: tag * 10,
+            })
+            .collect();
+
+        match analyze_match(&arms, 99) {
+            MatchLowering::JumpTable(table) => {
+                assert_eq!(table.lookup(2), 20);
+                assert_eq!(table.lookup(7), 99);
+            }
+            MatchLowering::CascadeComparisons { .. } => panic!("Expected jump table for every variant"),
+        }
+
+        // Two variants are cheaper to test one after the other.
+        assert!(matches!(
+            analyze_match(&arms[..2], 99),
+            MatchLowering::CascadeComparisons { .. }
+        ));
+    }
 }
@@ -0,0 +1,239 @@
+use std::str::FromStr;
+
+use target_lexicon::{Architecture, BinaryFormat, OperatingSystem, Triple};
+
+use crate::LlvmBackendError;
+
+/// Operating system families the native pipeline can emit and link for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TargetOs {
+    Windows,
+    Linux,
+    MacOs,
+}
+
+/// Instruction set families whose C calling conventions the emitter follows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TargetArch {
+    X86,
+    X86_64,
+    Aarch64,
+    Other,
+}
+
+/// Object file format produced for a target.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ObjectFormat {
+    Coff,
+    Elf,
+    MachO,
+}
+
+/// A native code generation target: the LLVM target triple plus the platform facts the
+/// emitter and the link driver depend on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NativeTarget {
+    triple: Triple,
+    os: TargetOs,
+}
+
+impl NativeTarget {
+    /// The machine the compiler runs on.
+    pub fn host() -> Result<Self, LlvmBackendError> {
+        Self::from_triple(Triple::host())
+    }
+
+    /// Parses a target triple such as `x86_64-unknown-linux-gnu`, `aarch64-apple-darwin` or
+    /// `x86_64-pc-windows-msvc`.
+    pub fn parse(triple: &str) -> Result<Self, LlvmBackendError> {
+        let parsed = Triple::from_str(triple.trim()).map_err(|e| LlvmBackendError {
+            message: format!("invalid target triple '{triple}': {e}"),
+        })?;
+        Self::from_triple(parsed)
+    }
+
+    fn from_triple(triple: Triple) -> Result<Self, LlvmBackendError> {
+        let os = match (triple.binary_format, &triple.operating_system) {
+            (BinaryFormat::Coff, _) => TargetOs::Windows,
+            (BinaryFormat::Macho, _) => TargetOs::MacOs,
+            (BinaryFormat::Elf, OperatingSystem::Linux) => TargetOs::Linux,
+            _ => {
+                return Err(LlvmBackendError {
+                    message: format!(
+                        "unsupported native target '{triple}' (supported: Windows, Linux and macOS)"
+                    ),
+                });
+            }
+        };
+        Ok(Self { triple, os })
+    }
+
+    /// The triple as written into `target triple = "..."` and passed to `clang --target=`.
+    pub fn triple(&self) -> String {
+        self.triple.to_string()
+    }
+
+    pub fn os(&self) -> TargetOs {
+        self.os
+    }
+
+    pub fn arch(&self) -> TargetArch {
+        match self.triple.architecture {
+            Architecture::X86_32(_) => TargetArch::X86,
+            Architecture::X86_64 | Architecture::X86_64h => TargetArch::X86_64,
+            Architecture::Aarch64(_) => TargetArch::Aarch64,
+            _ => TargetArch::Other,
+        }
+    }
+
+    pub fn object_format(&self) -> ObjectFormat {
+        match self.os {
+            TargetOs::Windows => ObjectFormat::Coff,
+            TargetOs::Linux => ObjectFormat::Elf,
+            TargetOs::MacOs => ObjectFormat::MachO,
+        }
+    }
+
+    pub fn is_host(&self) -> bool {
+        self.triple == Triple::host()
+    }
+
+    /// Suffix of linked executables (`.exe` on Windows, none elsewhere).
+    pub fn exe_suffix(&self) -> &'static str {
+        match self.os {
+            TargetOs::Windows => ".exe",
+            TargetOs::Linux | TargetOs::MacOs => "",
+        }
+    }
+
+    /// Extension of shared libraries loaded at run time, without the dot.
+    pub fn shared_lib_ext(&self) -> &'static str {
+        match self.os {
+            TargetOs::Windows => "dll",
+            TargetOs::Linux => "so",
+            TargetOs::MacOs => "dylib",
+        }
+    }
+
+    /// Extension of libraries handed to the linker (import or static), without the dot.
+    pub fn link_lib_ext(&self) -> &'static str {
+        match self.os {
+            TargetOs::Windows => "lib",
+            TargetOs::Linux | TargetOs::MacOs => "a",
+        }
+    }
+
+    /// The LLVM data layout written into `target datalayout = "..."`. It agrees with the sizes
+    /// and alignments the emitter assumes for records and enums; `None` for architectures
+    /// whose layout is left to the backend.
+    pub fn data_layout(&self) -> Option<&'static str> {
+        let layout = match (self.arch(), self.os) {
+            (TargetArch::X86_64, TargetOs::Linux) => {
+                "e-m:e-p270:32:32-p271:32:32-p272:64:64-i64:64-i128:128-f80:128-n8:16:32:64-S128"
+            }
+            (TargetArch::X86_64, TargetOs::MacOs) => {
+                "e-m:o-p270:32:32-p271:32:32-p272:64:64-i64:64-i128:128-f80:128-n8:16:32:64-S128"
+            }
+            (TargetArch::X86_64, TargetOs::Windows) => {
+                "e-m:w-p270:32:32-p271:32:32-p272:64:64-i64:64-i128:128-f80:128-n8:16:32:64-S128"
+            }
+            (TargetArch::X86, TargetOs::Linux) => {
+                "e-m:e-p:32:32-p270:32:32-p271:32:32-p272:64:64-i128:128-f64:32:64-f80:32-n8:16:32-S128"
+            }
+            (TargetArch::X86, TargetOs::MacOs) => {
+                "e-m:o-p:32:32-p270:32:32-p271:32:32-p272:64:64-i128:128-f64:32:64-f80:128-n8:16:32-S128"
+            }
+            (TargetArch::X86, TargetOs::Windows) => {
+                "e-m:x-p:32:32-p270:32:32-p271:32:32-p272:64:64-i64:64-i128:128-f80:32-n8:16:32-a:0:32-S32"
+            }
+            (TargetArch::Aarch64, TargetOs::Linux) => "e-m:e-i8:8:32-i16:16:32-i64:64-i128:128-n32:64-S128",
+            (TargetArch::Aarch64, TargetOs::MacOs) => "e-m:o-i64:64-i128:128-n32:64-S128",
+            (TargetArch::Aarch64, TargetOs::Windows) => "e-m:w-p:64:64-i32:32-i64:64-i128:128-n32:64-S128",
+            (TargetArch::Other, _) => return None,
+        };
+        Some(layout)
+    }
+
+    /// Width of a data pointer in bits.
+    pub fn pointer_bits(&self) -> u32 {
+        self.triple.pointer_width().map_or(64, |w| u32::from(w.bits()))
+    }
+
+    /// The `"probe-stack"` strategy for emitted functions, so that a frame larger than the
+    /// guard page faults on it instead of skipping past it. Windows already probes through
+    /// `__chkstk`; `None` there and for architectures without inline probing.
+    pub fn stack_probe(&self) -> Option<&'static str> {
+        match (self.os, self.arch()) {
+            (TargetOs::Windows, _) | (_, TargetArch::Other) => None,
+            (TargetOs::Linux | TargetOs::MacOs, TargetArch::X86 | TargetArch::X86_64 | TargetArch::Aarch64) => {
+                Some("inline-asm")
+            }
+        }
+    }
+
+    /// Whether `x86_stdcallcc` is meaningful; elsewhere stdcall externs use the C convention.
+    pub fn supports_stdcall(&self) -> bool {
+        self.os == TargetOs::Windows && matches!(self.triple.architecture, Architecture::X86_32(_))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linux_and_macos_triples_map_to_elf_and_macho() {
+        let linux = NativeTarget::parse("x86_64-unknown-linux-gnu").unwrap();
+        assert_eq!(linux.os(), TargetOs::Linux);
+        assert_eq!(linux.object_format(), ObjectFormat::Elf);
+        assert_eq!(linux.exe_suffix(), "");
+        assert_eq!(linux.shared_lib_ext(), "so");
+
+        let mac = NativeTarget::parse("aarch64-apple-darwin").unwrap();
+        assert_eq!(mac.os(), TargetOs::MacOs);
+        assert_eq!(mac.arch(), TargetArch::Aarch64);
+        assert_eq!(mac.object_format(), ObjectFormat::MachO);
+        assert_eq!(mac.shared_lib_ext(), "dylib");
+        assert!(!mac.supports_stdcall());
+
+        let win = NativeTarget::parse("i686-pc-windows-msvc").unwrap();
+        assert_eq!(win.arch(), TargetArch::X86);
+        assert_eq!(win.object_format(), ObjectFormat::Coff);
+        assert_eq!(win.exe_suffix(), ".exe");
+        assert!(win.supports_stdcall());
+    }
+
+    #[test]
+    fn data_layouts_follow_the_target_abi() {
+        let win = NativeTarget::parse("x86_64-pc-windows-msvc").unwrap();
+        assert!(win.data_layout().unwrap().starts_with("e-m:w-"));
+        // i386 System V aligns doubles to 4 bytes; 32-bit Windows keeps them at 8.
+        let i386 = NativeTarget::parse("i686-unknown-linux-gnu").unwrap();
+        assert!(i386.data_layout().unwrap().contains("-f64:32:64-"));
+        let win32 = NativeTarget::parse("i686-pc-windows-msvc").unwrap();
+        assert!(!win32.data_layout().unwrap().contains("f64:32"));
+        let mac = NativeTarget::parse("aarch64-apple-darwin").unwrap();
+        assert_eq!(mac.data_layout(), Some("e-m:o-i64:64-i128:128-n32:64-S128"));
+        let riscv = NativeTarget::parse("riscv64gc-unknown-linux-gnu").unwrap();
+        assert_eq!(riscv.data_layout(), None);
+    }
+
+    #[test]
+    fn stack_probes_are_inline_except_where_the_abi_already_probes() {
+        let linux = NativeTarget::parse("x86_64-unknown-linux-gnu").unwrap();
+        assert_eq!(linux.stack_probe(), Some("inline-asm"));
+        let mac = NativeTarget::parse("aarch64-apple-darwin").unwrap();
+        assert_eq!(mac.stack_probe(), Some("inline-asm"));
+        let win = NativeTarget::parse("x86_64-pc-windows-msvc").unwrap();
+        assert_eq!(win.stack_probe(), None);
+        let riscv = NativeTarget::parse("riscv64gc-unknown-linux-gnu").unwrap();
+        assert_eq!(riscv.stack_probe(), None);
+    }
+
+    #[test]
+    fn unsupported_targets_are_rejected() {
+        let err = NativeTarget::parse("wasm32-unknown-unknown").unwrap_err();
+        assert!(err.message.contains("unsupported native target"), "{}", err.message);
+        assert!(NativeTarget::parse("not-a-triple").is_err());
+    }
+}
@@ -0,0 +1,313 @@
+//! Recognition of element-wise tensor loops for vector code generation.
+//!
+//! A loop qualifies when it counts a `u32` index up to a loop-invariant bound
+//! (`while i < n: ... i = i + 1`) and its body only reads and writes element `i` of tensors
+//! (`t.get(i)`, `t.set(i, v)`) and combines those elements with lane-wise integer arithmetic.
+//! Each iteration then touches exactly one index of every tensor, so consecutive iterations
+//! can run together over contiguous elements. The emitter keeps the original loop as the
+//! scalar fallback: it finishes the iterations that do not fill a vector and runs the whole
+//! range when a tensor is shorter than the bound.
+
+use std::collections::{HashMap, HashSet};
+
+use aura_ir::{BinOp, BlockId, FunctionIR, InstKind, RValue, Terminator, ValueId};
+
+/// An operand of a lane-wise operation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Operand {
+    /// A vector produced earlier in the loop body.
+    Lane(ValueId),
+    /// A loop-invariant scalar, broadcast to every lane.
+    Splat(ValueId),
+    /// A constant, broadcast to every lane.
+    Const(u32),
+}
+
+/// One step of the vectorized loop body, in the order of the scalar body.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum LaneOp {
+    /// `dest = tensor[i .. i + lanes]`
+    Load { dest: ValueId, tensor: ValueId },
+    /// `dest = left op right`, lane by lane.
+    Binary { dest: ValueId, op: BinOp, left: Operand, right: Operand },
+    /// `dest = a * b + c`: a multiply whose only use is an add, fused into one
+    /// multiply-accumulate step.
+    MulAdd { dest: ValueId, a: Operand, b: Operand, c: Operand },
+    /// `tensor[i .. i + lanes] = value`
+    Store { tensor: ValueId, value: Operand },
+}
+
+/// A recognized element-wise tensor loop.
+#[derive(Clone, Debug)]
+pub(crate) struct TensorLoop {
+    /// The block that jumps into the loop header; the vector loop runs on this edge.
+    pub preheader: BlockId,
+    pub header: BlockId,
+    /// The index value on entry to the loop.
+    pub start: ValueId,
+    /// The exclusive, loop-invariant upper bound of the index.
+    pub bound: ValueId,
+    /// Tensor handles the body reads or writes, in first-use order.
+    pub tensors: Vec<ValueId>,
+    pub ops: Vec<LaneOp>,
+}
+
+impl TensorLoop {
+    /// Values defined outside the loop that the vector code reads.
+    pub fn invariants(&self) -> Vec<ValueId> {
+        let mut out = vec![self.start, self.bound];
+        out.extend(&self.tensors);
+        out.extend(self.splats());
+        out
+    }
+
+    /// Loop-invariant scalars broadcast to vectors, in first-use order.
+    pub fn splats(&self) -> Vec<ValueId> {
+        let mut out = Vec::new();
+        for op in &self.ops {
+            let operands = match op {
+                LaneOp::Load { .. } => vec![],
+                LaneOp::Binary { left, right, .. } => vec![*left, *right],
+                LaneOp::MulAdd { a, b, c, .. } => vec![*a, *b, *c],
+                LaneOp::Store { value, .. } => vec![*value],
+            };
+            for o in operands {
+                if let Operand::Splat(v) = o
+                    && !out.contains(&v)
+                {
+                    out.push(v);
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Finds the loops of `f` whose bodies can run as vector code.
+pub(crate) fn find_tensor_loops(f: &FunctionIR) -> Vec<TensorLoop> {
+    let mut preds: HashMap<BlockId, Vec<BlockId>> = HashMap::new();
+    for b in &f.blocks {
+        for succ in successors(&b.term) {
+            preds.entry(succ).or_default().push(b.id);
+        }
+    }
+    let mut defined_in = HashMap::new();
+    let mut consts = HashMap::new();
+    for b in &f.blocks {
+        for inst in &b.insts {
+            let Some(dest) = inst.dest else { continue };
+            defined_in.insert(dest, b.id);
+            if let InstKind::BindStrand { expr: RValue::ConstU32(c), .. } = &inst.kind
+                && let Ok(c) = u32::try_from(*c)
+            {
+                consts.insert(dest, c);
+            }
+        }
+    }
+    let facts = Facts { f, preds, defined_in, consts };
+    f.blocks.iter().filter_map(|h| facts.recognize(h.id)).collect()
+}
+
+struct Facts<'f> {
+    f: &'f FunctionIR,
+    preds: HashMap<BlockId, Vec<BlockId>>,
+    defined_in: HashMap<ValueId, BlockId>,
+    consts: HashMap<ValueId, u32>,
+}
+
+impl Facts<'_> {
+    fn block(&self, id: BlockId) -> Option<&aura_ir::BasicBlock> {
+        self.f.blocks.iter().find(|b| b.id == id)
+    }
+
+    fn preds(&self, id: BlockId) -> &[BlockId] {
+        self.preds.get(&id).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Matches `header: i = phi [start, pre], [next, body]; c = i < n; br c, body, exit`
+    /// with a single-block body that branches back to the header.
+    fn recognize(&self, header_id: BlockId) -> Option<TensorLoop> {
+        let header = self.block(header_id)?;
+        let [phi, cmp] = header.insts.as_slice() else { return None };
+        let (InstKind::Phi { incomings }, Some(index)) = (&phi.kind, phi.dest) else { return None };
+        let (InstKind::Binary { op: BinOp::Lt, left, right: bound }, Some(cond)) = (&cmp.kind, cmp.dest) else {
+            return None;
+        };
+        let Terminator::CondBr { cond: c, then_bb: body_id, .. } = &header.term else { return None };
+        if *left != index || *c != cond || *body_id == header_id {
+            return None;
+        }
+        let body = self.block(*body_id)?;
+        if !matches!(body.term, Terminator::Br(t) if t == header_id) || self.preds(body.id) != [header_id] {
+            return None;
+        }
+        let [(p0, v0), (p1, v1)] = incomings.as_slice() else { return None };
+        let ((preheader, start), next) = match (*p0 == body.id, *p1 == body.id) {
+            (false, true) => ((*p0, *v0), *v1),
+            (true, false) => ((*p1, *v1), *v0),
+            _ => return None,
+        };
+        let mut header_preds = self.preds(header_id).to_vec();
+        header_preds.sort();
+        let mut expected = vec![preheader, body.id];
+        expected.sort();
+        if header_preds != expected || !matches!(self.block(preheader)?.term, Terminator::Br(t) if t == header_id) {
+            return None;
+        }
+
+        let in_loop = |v: &ValueId| matches!(self.defined_in.get(v), Some(b) if *b == header_id || *b == body.id);
+        if in_loop(bound) || in_loop(&start) {
+            return None;
+        }
+
+        let mut aliases: HashMap<ValueId, Operand> = HashMap::new();
+        let mut lanes: HashSet<ValueId> = HashSet::new();
+        let operand = |v: ValueId, aliases: &HashMap<ValueId, Operand>, lanes: &HashSet<ValueId>| {
+            if let Some(o) = aliases.get(&v) {
+                Some(*o)
+            } else if lanes.contains(&v) {
+                Some(Operand::Lane(v))
+            } else if let Some(c) = self.consts.get(&v) {
+                Some(Operand::Const(*c))
+            } else if v == index || in_loop(&v) {
+                None
+            } else {
+                Some(Operand::Splat(v))
+            }
+        };
+
+        let mut tensors = Vec::new();
+        let mut ops = Vec::new();
+        let mut stepped = false;
+        for inst in &body.insts {
+            match (&inst.kind, inst.dest) {
+                (InstKind::Call { callee, args }, Some(dest))
+                    if callee == "tensor.get" && args.len() == 2 && args[1] == index && !in_loop(&args[0]) =>
+                {
+                    if !tensors.contains(&args[0]) {
+                        tensors.push(args[0]);
+                    }
+                    lanes.insert(dest);
+                    ops.push(LaneOp::Load { dest, tensor: args[0] });
+                }
+                (InstKind::Call { callee, args }, _)
+                    if callee == "tensor.set" && args.len() == 3 && args[1] == index && !in_loop(&args[0]) =>
+                {
+                    if !tensors.contains(&args[0]) {
+                        tensors.push(args[0]);
+                    }
+                    let value = operand(args[2], &aliases, &lanes)?;
+                    ops.push(LaneOp::Store { tensor: args[0], value });
+                }
+                (InstKind::Binary { op: BinOp::Add, left, right }, Some(dest))
+                    if dest == next && *left == index && self.consts.get(right) == Some(&1) =>
+                {
+                    stepped = true;
+                }
+                (InstKind::Binary { op, left, right }, Some(dest)) if is_lane_wise(*op) => {
+                    let left = operand(*left, &aliases, &lanes)?;
+                    let right = operand(*right, &aliases, &lanes)?;
+                    if !matches!(left, Operand::Lane(_)) && !matches!(right, Operand::Lane(_)) {
+                        return None;
+                    }
+                    lanes.insert(dest);
+                    ops.push(LaneOp::Binary { dest, op: *op, left, right });
+                }
+                (InstKind::BindStrand { expr: RValue::ConstU32(_), .. }, Some(dest)) if self.consts.contains_key(&dest) => {}
+                (InstKind::BindStrand { expr: RValue::Local(v), .. }, Some(dest)) => {
+                    let o = operand(*v, &aliases, &lanes)?;
+                    aliases.insert(dest, o);
+                }
+                _ => return None,
+            }
+        }
+        if !stepped || !ops.iter().any(|op| matches!(op, LaneOp::Store { .. })) {
+            return None;
+        }
+
+        // Values computed in the body only exist lane-wise, so nothing else may read them.
+        for b in &self.f.blocks {
+            if b.id == body.id {
+                continue;
+            }
+            for inst in &b.insts {
+                let allowed = b.id == header_id && matches!(inst.kind, InstKind::Phi { .. });
+                if inst
+                    .kind
+                    .operands()
+                    .iter()
+                    .any(|v| self.defined_in.get(v) == Some(&body.id) && !(allowed && *v == next))
+                {
+                    return None;
+                }
+            }
+            if term_operands(&b.term).iter().any(|v| self.defined_in.get(v) == Some(&body.id)) {
+                return None;
+            }
+        }
+
+        Some(TensorLoop { preheader, header: header_id, start, bound: *bound, tensors, ops: fuse_multiply_add(ops) })
+    }
+}
+
+/// Integer operators that act on each lane independently and wrap like the scalar ones.
+fn is_lane_wise(op: BinOp) -> bool {
+    matches!(op, BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::BitAnd | BinOp::BitOr | BinOp::BitXor)
+}
+
+/// Folds `m = a * b; d = m + c` (or `c + m`) into one `MulAdd` when `m` has no other use.
+fn fuse_multiply_add(mut ops: Vec<LaneOp>) -> Vec<LaneOp> {
+    let uses = |ops: &[LaneOp], v: ValueId| {
+        ops.iter()
+            .map(|op| match op {
+                LaneOp::Load { .. } => 0,
+                LaneOp::Binary { left, right, .. } => [left, right].iter().filter(|o| ***o == Operand::Lane(v)).count(),
+                LaneOp::MulAdd { a, b, c, .. } => [a, b, c].iter().filter(|o| ***o == Operand::Lane(v)).count(),
+                LaneOp::Store { value, .. } => usize::from(*value == Operand::Lane(v)),
+            })
+            .sum::<usize>()
+    };
+    let mut i = 0;
+    while i < ops.len() {
+        let LaneOp::Binary { dest, op: BinOp::Add, left, right } = ops[i] else {
+            i += 1;
+            continue;
+        };
+        let fused = [(left, right), (right, left)].into_iter().find_map(|(m, c)| {
+            let Operand::Lane(m) = m else { return None };
+            let at = ops[..i].iter().position(|op| matches!(op, LaneOp::Binary { dest, op: BinOp::Mul, .. } if *dest == m))?;
+            let LaneOp::Binary { left: a, right: b, .. } = ops[at] else { return None };
+            (uses(&ops, m) == 1).then_some((at, LaneOp::MulAdd { dest, a, b, c }))
+        });
+        match fused {
+            Some((at, mul_add)) => {
+                ops[i] = mul_add;
+                ops.remove(at);
+            }
+            None => i += 1,
+        }
+    }
+    ops
+}
+
+fn successors(term: &Terminator) -> Vec<BlockId> {
+    match term {
+        Terminator::Return(_) => vec![],
+        Terminator::Br(t) => vec![*t],
+        Terminator::CondBr { then_bb, else_bb, .. } => vec![*then_bb, *else_bb],
+        Terminator::Switch { default_bb, cases, .. } => {
+            let mut out = vec![*default_bb];
+            out.extend(cases.iter().map(|(_, b)| *b));
+            out
+        }
+    }
+}
+
+fn term_operands(term: &Terminator) -> Vec<ValueId> {
+    match term {
+        Terminator::Return(v) => v.iter().copied().collect(),
+        Terminator::Br(_) => vec![],
+        Terminator::CondBr { cond, .. } => vec![*cond],
+        Terminator::Switch { scrut, .. } => vec![*scrut],
+    }
+}
@@ -0,0 +1,131 @@
+#![cfg(feature = "llvm")]
+
+use aura_backend_llvm::{emit_llvm_ir_for_target, NativeTarget, OptConfig, OptLevel};
+
+fn emit(src: &str) -> String {
+    let program = aura_parse::parse_source(src).expect("parse");
+    aura_core::Checker::new().check_program(&program).expect("sema");
+    let module = aura_core::lower_program(&program).expect("lower");
+    let target = NativeTarget::parse("x86_64-unknown-linux-gnu").expect("target");
+    emit_llvm_ir_for_target(&module, None, &target, &OptConfig::new(OptLevel::O0))
+        .expect("emit")
+        .llvm_ir
+}
+
+fn body_of<'a>(ll: &'a str, define: &str) -> &'a str {
+    let start = ll.find(define).unwrap_or_else(|| panic!("missing {define}:\n{ll}"));
+    let body = &ll[start..];
+    &body[..body.find("\n}\n").expect("end of function")]
+}
+
+const FLOWS: &str = "cell scale(x: u32, k: u32) -> u32:\n    yield x * k\n\ncell main() -> u32:\n    val a: u32 = 3\n    val z: u32 = 4\n    val b: u32 = a ~> scale(10)\n    val c: u32 = z + 1\n    yield b + c\n";
+
+#[test]
+fn async_flows_spawn_a_task_frame_and_join_before_the_result_is_read() {
+    let ll = emit(FLOWS);
+    let main = body_of(&ll, "define i32 @aura_main");
+
+    // Header, the two arguments, then the result slot.
+    assert!(main.contains("= alloca { i32, ptr, ptr, i32, i32, i32 }"), "{main}");
+    assert!(main.contains("call void @aura_task_spawn(ptr %abi0, ptr @scale.task)"), "{main}");
+    let spawn = main.find("@aura_task_spawn").expect("spawn");
+    let join = main.find("call void @aura_task_join(ptr %abi0)").expect("join");
+    // Work that does not need the result runs while the task does.
+    let independent = main.find("= add i32 4, 1").expect("independent add");
+    assert!(spawn < independent && independent < join, "{main}");
+    assert!(main[join..].contains("= load i32, ptr %abi"), "{main}");
+}
+
+#[test]
+fn task_entries_unpack_arguments_and_store_the_result() {
+    let ll = emit(FLOWS);
+    let entry = body_of(&ll, "define internal void @scale.task(ptr %task)");
+    assert!(entry.contains("%arg1 = load i32, ptr %arg1.ptr"), "{entry}");
+    assert!(entry.contains("%result = call i32 @scale(i32 %arg0, i32 %arg1)"), "{entry}");
+    assert!(entry.contains("getelementptr inbounds { i32, ptr, ptr, i32, i32, i32 }, ptr %task, i32 0, i32 5"), "{entry}");
+    assert!(ll.contains("declare void @aura_task_spawn(ptr, ptr)"), "{ll}");
+}
+
+#[test]
+fn tasks_without_a_read_result_are_joined_at_the_end_of_their_block() {
+    let src = "cell log(n: u32):\n    io.println(\"tick\")\n\ncell main() -> u32:\n    val n: u32 = 1\n    n ~> log()\n    yield 2\n";
+    let ll = emit(src);
+    let main = body_of(&ll, "define i32 @aura_main");
+    assert!(main.contains("= alloca { i32, ptr, ptr, i32 }"), "{main}");
+    let join = main.find("@aura_task_join").expect("join");
+    assert!(join < main.find("ret i32").expect("ret"), "{main}");
+    let entry = body_of(&ll, "define internal void @log.task(ptr %task)");
+    assert!(entry.contains("call void @log(i32 %arg0)"), "{entry}");
+}
+
+#[test]
+fn channels_are_runtime_handles_shared_with_tasks() {
+    let src = "cell produce(ch: Channel<u32>, n: u32):\n    chan.send(ch, n)\n\ncell main() -> u32:\n    val ch: Channel<u32> = chan.bounded(4)\n    ch ~> produce(7)\n    val x: u32 = chan.recv(ch)\n    chan.close(ch)\n    yield x\n";
+    let ll = emit(src);
+    let main = body_of(&ll, "define i32 @aura_main");
+    assert!(main.contains("= call i32 @aura_chan_bounded(i32 4)"), "{main}");
+    let spawn = main.find("@aura_task_spawn").expect("spawn");
+    let recv = main.find("= call i32 @aura_chan_recv(i32 ").expect("recv");
+    assert!(spawn < recv && recv < main.find("call void @aura_chan_close(i32 ").expect("close"), "{main}");
+    let produce = body_of(&ll, "define void @produce");
+    assert!(produce.contains("call void @aura_chan_send(i32 %v0, i32 %v1)"), "{produce}");
+    assert!(ll.contains("declare i32 @aura_chan_recv(i32)"), "{ll}");
+}
+
+#[test]
+fn scopes_join_their_tasks_before_closing_the_token() {
+    let src = "cell work(tok: CancelToken, n: u32):\n    if scope.cancelled(tok):\n        return\n    io.println(\"work\")\n\ncell main():\n    scope tok:\n        tok ~> work(1)\n        scope.cancel(tok)\n    io.println(\"done\")\n";
+    let ll = emit(src);
+    let main = body_of(&ll, "define void @aura_main");
+    let open = main.find("= call i32 @aura_scope_open()").expect("open");
+    let spawn = main.find("@aura_task_spawn").expect("spawn");
+    let cancel = main.find("call void @aura_scope_cancel(i32 ").expect("cancel");
+    let join = main.find("@aura_task_join").expect("join");
+    let close = main.find("call void @aura_scope_close(i32 ").expect("close");
+    assert!(open < spawn && spawn < cancel && cancel < join && join < close, "{main}");
+    let work = body_of(&ll, "define void @work");
+    assert!(work.contains("call i1 @aura_scope_cancelled(i32 %v0)"), "{work}");
+}
+
+#[test]
+fn timers_call_the_runtime_clock() {
+    let src = "cell main() -> u32:\n    val start: u32 = time.now_ms()\n    time.sleep_ms(10)\n    yield time.now_ms() - start\n";
+    let ll = emit(src);
+    let main = body_of(&ll, "define i32 @aura_main");
+    let first = main.find("= call i32 @aura_time_now_ms()").expect("now");
+    let sleep = main.find("call void @aura_time_sleep_ms(i32 10)").expect("sleep");
+    assert!(first < sleep && main[sleep..].contains("= call i32 @aura_time_now_ms()"), "{main}");
+    assert!(ll.contains("declare void @aura_time_sleep_ms(i32)"), "{ll}");
+}
+
+#[test]
+fn wall_clock_durations_and_formatting_call_the_runtime() {
+    let src = "cell main():\n    val start: u32 = time.now_ms()\n    val d: u32 = time.add_ms(time.elapsed_ms(start, time.now_ms()), 5)\n    io.println(time.format_ms(d))\n    io.println(time.format_utc(time.unix_s()))\n";
+    let ll = emit(src);
+    let main = body_of(&ll, "define void @aura_main");
+    assert!(main.contains("= call i32 @aura_time_elapsed_ms(i32 %v"), "{main}");
+    assert!(main.contains("= call i32 @aura_time_add_ms(i32 %v"), "{main}");
+    assert!(main.contains("= call ptr @aura_time_format_ms(i32 %v"), "{main}");
+    assert!(main.contains("= call i32 @aura_time_unix_s()"), "{main}");
+    assert!(main.contains("= call ptr @aura_time_format_utc(i32 %v"), "{main}");
+}
+
+#[test]
+fn regions_exit_after_their_tasks_and_tasks_run_detached_from_them() {
+    let src = "cell work(n: u32):\n    io.println(\"work\")\n\ncell main(n: u32):\n    region {\n        val mut t = tensor.new(n)\n        n ~> work()\n        tensor.set(t, 0, 1)\n    }\n    io.println(\"done\")\n";
+    let ll = emit(src);
+    let main = body_of(&ll, "define void @aura_main");
+    let enter = main.find("= call i32 @aura_region_enter()").expect("enter");
+    let spawn = main.find("@aura_task_spawn").expect("spawn");
+    let join = main.find("@aura_task_join").expect("join");
+    let exit = main.find("call void @aura_region_exit(i32 ").expect("exit");
+    assert!(enter < spawn && spawn < join && join < exit, "{main}");
+    let entry = body_of(&ll, "define internal void @work.task");
+    let detach = entry.find("call void @aura_region_detach()").expect("detach");
+    let reattach = entry.find("call void @aura_region_reattach()").expect("reattach");
+    let call = entry.find("call void @work(").expect("call");
+    assert!(detach < call && call < reattach, "{entry}");
+
+    // Modules without regions keep their task entries as they were.
+    assert!(!emit(FLOWS).contains("call void @aura_region_detach()"));
+}
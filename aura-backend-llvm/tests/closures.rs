@@ -0,0 +1,36 @@
+#![cfg(feature = "llvm")]
+
+fn emit(src: &str) -> String {
+    let program = aura_parse::parse_source(src).expect("parse");
+    aura_core::Checker::new().check_program(&program).expect("sema");
+    let module = aura_core::lower_program(&program).expect("lower");
+    aura_backend_llvm::emit_llvm_ir(&module, None).expect("emit").llvm_ir
+}
+
+#[test]
+fn lambdas_compile_to_heap_environments_and_indirect_calls() {
+    let src = "cell apply<T>(f: T) -> u32:\n    yield f() + 1\n\ncell main() -> u32:\n    val n: u32 = 3\n    val s: String = \"hi\"\n    val f = -> { yield n * 2 }\n    val g = ~> { io.println(s) }\n    g()\n    yield apply(f)\n";
+    let ll = emit(src);
+
+    assert!(ll.contains("declare ptr @malloc(i64)"), "{ll}");
+    // Bodies take their environment and load each capture from it.
+    assert!(ll.contains("define i32 @main_lambda0(ptr %env)"), "{ll}");
+    assert!(ll.contains("define void @main_lambda1(ptr %env)"), "{ll}");
+    assert!(ll.contains("getelementptr inbounds { ptr, i32 }, ptr %env, i32 0, i32 1"), "{ll}");
+    assert!(ll.contains("getelementptr inbounds { ptr, ptr }, ptr %env, i32 0, i32 1"), "{ll}");
+
+    // The environment starts with the code pointer.
+    assert!(ll.contains("getelementptr { ptr, i32 }, ptr null, i32 1"), "{ll}");
+    assert!(ll.contains("store ptr @main_lambda0, ptr %v"), "{ll}");
+    assert!(ll.contains("store i32 3, ptr %v"), "{ll}");
+
+    // Closures passed as callbacks are called through the loaded code pointer.
+    let apply = ll
+        .split("define i32 @apply__Closure_u32_(ptr ")
+        .nth(1)
+        .and_then(|s| s.split("\n}").next())
+        .expect("apply instance");
+    assert!(apply.contains("= load ptr, ptr %v"), "{apply}");
+    assert!(apply.contains("= call i32 %code."), "{apply}");
+    assert!(ll.contains("call void %code."), "{ll}");
+}
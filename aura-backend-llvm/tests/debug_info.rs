@@ -0,0 +1,67 @@
+#![cfg(feature = "llvm")]
+
+use aura_ir::DebugSource;
+
+fn emit_with_debug(src: &str) -> String {
+    let program = aura_parse::parse_source(src).expect("parse");
+    let module = aura_core::lower_program(&program).expect("lower");
+    let debug = DebugSource::new("src/dbg.aura".to_string(), src);
+    aura_backend_llvm::emit_llvm_ir(&module, Some(&debug)).expect("emit").llvm_ir
+}
+
+#[test]
+fn params_strands_and_block_scopes_get_dwarf_variables() {
+    let src = "cell sum(n: u32) -> u32:\n    val mut acc: u32 = 0\n    val mut i: u32 = 0\n    while i < n:\n        val step: u32 = i * 2\n        acc = acc + step\n        i = i + 1\n    yield acc\n";
+    let ll = emit_with_debug(src);
+
+    assert!(ll.contains("!llvm.dbg.cu"), "{ll}");
+    assert!(ll.contains("!DIFile(filename: \"dbg.aura\", directory: \"src\")"), "{ll}");
+    assert!(ll.contains("define i32 @sum(i32 %v0) \"probe-stack\"=\"inline-asm\" !dbg !"), "{ll}");
+    assert!(ll.contains("distinct !DISubprogram(name: \"sum\""), "{ll}");
+    assert!(ll.contains("!DILocalVariable(name: \"n\", arg: 1,"), "{ll}");
+    for name in ["acc", "i"] {
+        assert!(ll.contains(&format!("!DILocalVariable(name: \"{name}\", scope: ")), "{name}: {ll}");
+    }
+
+    // `step` is declared in the loop body, a lexical block of its own on line 5.
+    let step = ll
+        .lines()
+        .find(|l| l.contains("!DILocalVariable(name: \"step\""))
+        .expect("step variable");
+    assert!(step.contains("line: 5"), "{step}");
+    let scope = step.split("scope: !").nth(1).and_then(|s| s.split(',').next()).expect("scope");
+    let block = ll
+        .lines()
+        .find(|l| l.starts_with(&format!("!{scope} = ")))
+        .expect("scope node");
+    assert!(block.contains("distinct !DILexicalBlock("), "{block}");
+
+    assert!(ll.contains("call void @llvm.dbg.value(metadata i32 %v0, metadata !"), "{ll}");
+    assert!(ll.contains("declare void @llvm.dbg.value(metadata, metadata, metadata)"), "{ll}");
+    let ret = ll.lines().find(|l| l.trim_start().starts_with("ret i32")).expect("ret");
+    assert!(ret.contains(", !dbg !"), "{ret}");
+}
+
+#[test]
+fn every_call_in_a_function_with_debug_info_has_a_location() {
+    // LLVM rejects inlinable calls without `!dbg` inside a subprogram.
+    let src = "cell twice(x: u32) -> u32:\n    yield x * 2\n\ncell main() -> u32:\n    val a: u32 = twice(3)\n    if a > 4:\n        val b: u32 = twice(a)\n        yield b\n    yield a\n";
+    let ll = emit_with_debug(src);
+    let calls: Vec<&str> = ll
+        .lines()
+        .filter(|l| l.contains("call ") && l.contains("@twice("))
+        .collect();
+    assert_eq!(calls.len(), 2, "{ll}");
+    for call in calls {
+        assert!(call.contains(", !dbg !"), "{call}");
+    }
+}
+
+#[test]
+fn without_a_debug_source_no_metadata_is_emitted() {
+    let program = aura_parse::parse_source("cell f(x: u32) -> u32:\n    val y: u32 = x + 1\n    yield y\n").expect("parse");
+    let module = aura_core::lower_program(&program).expect("lower");
+    let ll = aura_backend_llvm::emit_llvm_ir(&module, None).expect("emit").llvm_ir;
+    assert!(!ll.contains("!dbg"), "{ll}");
+    assert!(!ll.contains("llvm.dbg.value"), "{ll}");
+}
@@ -0,0 +1,59 @@
+#![cfg(feature = "llvm")]
+
+use aura_backend_llvm::{emit_llvm_ir_for_target, NativeTarget, OptConfig, OptLevel};
+
+fn emit_for(src: &str, triple: &str) -> Result<String, String> {
+    let program = aura_parse::parse_source(src).expect("parse");
+    aura_core::Checker::new().check_program(&program).expect("sema");
+    let module = aura_core::lower_program(&program).expect("lower");
+    let target = NativeTarget::parse(triple).expect("target");
+    emit_llvm_ir_for_target(&module, None, &target, &OptConfig::new(OptLevel::O0))
+        .map(|a| a.llvm_ir)
+        .map_err(|e| e.message)
+}
+
+const SHAPES: &str = "type Shape = enum { Circle(r: f64), Rect(w: u32, h: u32), Line(len: u32), Dot }\n\ncell weight(s: Shape) -> u32:\n    val mut out: u32 = 0\n    match s:\n        Shape::Rect(w, 0):\n            out = w\n        Shape::Rect(w, h):\n            out = w * h\n        Shape::Circle(_):\n            out = 3\n        Shape::Line(len):\n            out = len\n        Shape::Dot:\n            out = 1\n    yield out\n\ncell main() -> u32:\n    yield weight(Shape::Rect(2, 5)) + weight(Shape::Dot())\n";
+
+#[test]
+fn enums_are_tagged_unions_sized_for_their_largest_payload() {
+    let ll = emit_for(SHAPES, "x86_64-unknown-linux-gnu").expect("emit");
+
+    assert!(ll.contains("%Shape = type { i32, [1 x i64] }"), "{ll}");
+    assert!(ll.contains("define i32 @weight(%Shape %v0)"), "{ll}");
+    // The tag is stored first, then each field through the variant's view of the payload.
+    assert!(ll.contains("store i32 1, ptr %abi"), "{ll}");
+    assert!(ll.contains("= getelementptr inbounds { i32, i32 }, ptr %abi"), "{ll}");
+    assert!(ll.contains("= extractvalue %Shape %v0, 0"), "{ll}");
+    assert!(ll.contains("= load i32, ptr %abi"), "{ll}");
+
+    // i386 System V aligns doubles to 4 bytes, so the payload is two 32-bit words.
+    let ll = emit_for(SHAPES, "i686-unknown-linux-gnu").expect("emit");
+    assert!(ll.contains("%Shape = type { i32, [2 x i32] }"), "{ll}");
+}
+
+#[test]
+fn matches_on_many_variants_dispatch_through_a_switch() {
+    let ll = emit_for(SHAPES, "x86_64-unknown-linux-gnu").expect("emit");
+    assert!(ll.contains("switch i32"), "{ll}");
+    assert!(ll.contains("    i32 1, label %"), "{ll}");
+    assert!(ll.contains("    i32 2, label %"), "{ll}");
+}
+
+#[test]
+fn matches_on_two_variants_compare_the_tag_directly() {
+    let src = "type Opt = enum { Some(x: u32), None }\n\ncell get(o: Opt) -> u32:\n    val mut out: u32 = 0\n    match o:\n        Opt::Some(x):\n            out = x\n        Opt::None:\n            out = 7\n    yield out\n\ncell main() -> u32:\n    yield get(Opt::Some(3)) + get(Opt::None())\n";
+    let ll = emit_for(src, "x86_64-unknown-linux-gnu").expect("emit");
+    let get = &ll[ll.find("define i32 @get").expect("get")..];
+    let get = &get[..get.find("\n}\n").expect("end of get")];
+
+    assert!(!get.contains("switch"), "{get}");
+    assert!(get.contains("= icmp eq i32 %v"), "{get}");
+    assert!(ll.contains("%Opt = type { i32, [1 x i32] }"), "{ll}");
+}
+
+#[test]
+fn enums_cannot_cross_an_extern_boundary() {
+    let src = "type Opt = enum { Some(x: u32), None }\n\ntrusted extern cell peek(o: Opt): u32\n\ncell main() -> u32:\n    yield peek(Opt::Some(1))\n";
+    let err = emit_for(src, "x86_64-unknown-linux-gnu").expect_err("enum at extern");
+    assert!(err.contains("extern 'peek' cannot take or return enum 'Opt'"), "{err}");
+}
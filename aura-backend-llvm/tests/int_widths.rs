@@ -0,0 +1,51 @@
+#![cfg(feature = "llvm")]
+
+use aura_backend_llvm::{emit_llvm_ir_for_target, NativeTarget, OptConfig, OptLevel};
+
+fn emit(src: &str) -> String {
+    let program = aura_parse::parse_source(src).expect("parse");
+    aura_core::Checker::new().check_program(&program).expect("sema");
+    let module = aura_core::lower_program(&program).expect("lower");
+    let target = NativeTarget::parse("x86_64-unknown-linux-gnu").expect("target");
+    emit_llvm_ir_for_target(&module, None, &target, &OptConfig::new(OptLevel::O0))
+        .expect("emit")
+        .llvm_ir
+}
+
+fn body_of<'a>(ll: &'a str, define: &str) -> &'a str {
+    let start = ll.find(define).unwrap_or_else(|| panic!("missing {define}:\n{ll}"));
+    let body = &ll[start..];
+    &body[..body.find("\n}\n").expect("end of function")]
+}
+
+#[test]
+fn sized_integers_map_to_their_llvm_widths_and_widen_with_zext() {
+    let ll = emit("cell widen(b: u8, h: u16) -> u64:\n    val s: u32 = b + h\n    return s\n");
+    let f = body_of(&ll, "define i64 @widen(i8 %v");
+    assert!(f.contains("i16 %v"), "{f}");
+    assert!(f.contains("= zext i8 %v"), "{f}");
+    assert!(f.contains("= add i16"), "{f}");
+    assert!(f.contains("to i64"), "{f}");
+}
+
+#[test]
+fn signed_integers_use_signed_division_comparison_and_shifts() {
+    let ll = emit(
+        "cell f(a: i32, b: i32) -> i32:\n    if a < b:\n        return a / b\n    return a >> 1\n",
+    );
+    let f = body_of(&ll, "define i32 @f(");
+    assert!(f.contains("icmp slt i32"), "{f}");
+    assert!(f.contains("sdiv i32"), "{f}");
+    assert!(f.contains("ashr i32"), "{f}");
+    // Dividing the minimum by -1 traps instead of being undefined.
+    assert!(f.contains("icmp eq i32 %v0, -2147483648"), "{f}");
+}
+
+#[test]
+fn wide_values_check_division_by_zero_at_their_own_width() {
+    let ll = emit("cell f(a: u64, b: u64) -> u64:\n    return a / b\n");
+    let f = body_of(&ll, "define i64 @f(");
+    assert!(f.contains("icmp eq i64 %v1, 0"), "{f}");
+    assert!(f.contains("udiv i64"), "{f}");
+    assert!(ll.contains("declare ptr @aura_string_from_u64(i64)"));
+}
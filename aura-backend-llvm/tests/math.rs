@@ -0,0 +1,36 @@
+#![cfg(feature = "llvm")]
+
+use aura_backend_llvm::{emit_llvm_ir_for_target, NativeTarget, OptConfig, OptLevel};
+
+fn emit(src: &str) -> String {
+    let program = aura_parse::parse_source(src).expect("parse");
+    aura_core::Checker::new().check_program(&program).expect("sema");
+    let module = aura_core::lower_program(&program).expect("lower");
+    let target = NativeTarget::parse("x86_64-unknown-linux-gnu").expect("target");
+    emit_llvm_ir_for_target(&module, None, &target, &OptConfig::new(OptLevel::O0))
+        .expect("emit")
+        .llvm_ir
+}
+
+#[test]
+fn math_calls_lower_to_llvm_intrinsics() {
+    let src = "cell clamp(x: u32, lo: u32, hi: u32) -> u32:\n    return math.min(math.max(x, lo), hi)\n\ncell norm(x: f64, y: f64) -> f64:\n    return math.sqrt(math.abs(x * x + y * y))\n\ncell wave(t: f64) -> f64:\n    return math.sin(t) + math.cos(math.powf(t, 2.0))\n";
+    let ll = emit(src);
+
+    assert!(ll.contains("= call i32 @llvm.umax.i32(i32 "), "{ll}");
+    assert!(ll.contains("= call i32 @llvm.umin.i32(i32 "), "{ll}");
+    assert!(ll.contains("= call double @llvm.fabs.f64(double "), "{ll}");
+    assert!(ll.contains("= call double @llvm.sqrt.f64(double "), "{ll}");
+    assert!(ll.contains("= call double @llvm.sin.f64(double "), "{ll}");
+    assert!(ll.contains("= call double @llvm.pow.f64(double %v"), "{ll}");
+    assert!(ll.contains("= call double @llvm.cos.f64(double "), "{ll}");
+}
+
+#[test]
+fn u32_pow_calls_the_overflow_checked_runtime() {
+    let src = "cell main():\n    val n: u32 = 3\n    io.println(\"{math.pow(2, n)}\")\n";
+    let ll = emit(src);
+
+    assert!(ll.contains("declare i32 @aura_math_pow_u32(i32, i32)"), "{ll}");
+    assert!(ll.contains("= call i32 @aura_math_pow_u32(i32 "), "{ll}");
+}
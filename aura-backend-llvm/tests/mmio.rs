@@ -0,0 +1,79 @@
+#![cfg(feature = "llvm")]
+
+use aura_backend_llvm::{emit_llvm_ir_for_target, NativeTarget, OptConfig, OptLevel};
+use aura_ir::{MmioRegion, ModuleIR};
+
+fn lower(src: &str) -> ModuleIR {
+    let program = aura_parse::parse_source(src).expect("parse");
+    aura_core::Checker::new().check_program(&program).expect("sema");
+    let mut module = aura_core::lower_program(&program).expect("lower");
+    module.mmio.insert(
+        "GPIO".to_string(),
+        MmioRegion { base: 0x4000_0000, size: 8, instances: 1, stride: 8 },
+    );
+    module.mmio.insert(
+        "UART".to_string(),
+        MmioRegion { base: 0x4001_0000, size: 16, instances: 4, stride: 0x100 },
+    );
+    module
+}
+
+fn emit(module: &ModuleIR) -> Result<String, String> {
+    let target = NativeTarget::parse("x86_64-unknown-linux-gnu").expect("target");
+    emit_llvm_ir_for_target(module, None, &target, &OptConfig::new(OptLevel::O0))
+        .map(|a| a.llvm_ir)
+        .map_err(|e| e.message)
+}
+
+fn body_of<'a>(ll: &'a str, define: &str) -> &'a str {
+    let start = ll.find(define).unwrap_or_else(|| panic!("missing {define}:\n{ll}"));
+    let body = &ll[start..];
+    &body[..body.find("\n}\n").expect("end of function")]
+}
+
+#[test]
+fn register_access_compiles_to_volatile_loads_and_stores_at_the_manifest_address() {
+    let src = "cell main() ->:\n    val cap = hw.open(\"GPIO\")\n    val _ok = hw.write_u32(cap, 4, 1)\n    val _v = hw.read_u32(cap, 0)\n";
+    let ll = emit(&lower(src)).expect("emit");
+    let main = body_of(&ll, "define void @aura_main");
+
+    assert!(main.contains(&format!("= add i32 {}, 0", 0x4000_0000u32)), "{main}");
+    assert!(main.contains("call void @aura_mmio_write32(i32 %abi"), "{main}");
+    assert!(main.contains("= call i32 @aura_mmio_read32(i32 %abi"), "{main}");
+
+    let write = body_of(&ll, "define internal void @aura_mmio_write32(i32 %addr, i32 %v)");
+    assert!(write.contains("inttoptr i32 %addr to ptr"), "{write}");
+    assert!(write.contains("store volatile i32 %v, ptr %p, align 4"), "{write}");
+    let read = body_of(&ll, "define internal i32 @aura_mmio_read32(i32 %addr)");
+    assert!(read.contains("load volatile i32, ptr %p, align 4"), "{read}");
+}
+
+#[test]
+fn indexed_instances_are_addressed_by_stride() {
+    let src = "cell main() ->:\n    val cap = hw.open(\"UART\", 2)\n    val _v = hw.read_u32(cap, 4)\n";
+    let ll = emit(&lower(src)).expect("emit");
+    let main = body_of(&ll, "define void @aura_main");
+    assert!(main.contains(&format!("= mul i32 2, {}", 0x100)), "{main}");
+    assert!(main.contains(&format!(", {}\n", 0x4001_0000u32)), "{main}");
+}
+
+#[test]
+fn modules_without_register_access_get_no_accessors() {
+    let ll = emit(&lower("cell main() -> u32:\n    yield 1\n")).expect("emit");
+    assert!(!ll.contains("aura_mmio_"), "{ll}");
+}
+
+#[test]
+fn unknown_registers_and_unsupported_calls_are_rejected() {
+    let err = emit(&lower("cell main() ->:\n    val cap = hw.open(\"TIMER\")\n    val _v = hw.read_u32(cap, 0)\n"))
+        .expect_err("TIMER is not mapped");
+    assert!(err.contains("'TIMER' is not in the [hardware] manifest"), "{err}");
+
+    let err = emit(&lower("cell main() ->:\n    val cap = hw.open(\"UART\")\n    val _v = hw.read_u32(cap, 0)\n"))
+        .expect_err("UART needs an index");
+    assert!(err.contains("has 4 instances"), "{err}");
+
+    let err = emit(&lower("cell main() ->:\n    val cap = hw.open(\"GPIO\")\n    val _v = hw.read_field(cap, 0, \"EN\")\n"))
+        .expect_err("fields are not compiled");
+    assert!(err.contains("'hw.read_field'"), "{err}");
+}
@@ -0,0 +1,64 @@
+#![cfg(feature = "llvm")]
+
+use aura_backend_llvm::{emit_llvm_ir_for_target, NativeTarget, OptConfig, OptLevel};
+
+fn emit_for(src: &str, triple: &str) -> String {
+    let program = aura_parse::parse_source(src).expect("parse");
+    aura_core::Checker::new().check_program(&program).expect("sema");
+    let module = aura_core::lower_program(&program).expect("lower");
+    let target = NativeTarget::parse(triple).expect("target");
+    emit_llvm_ir_for_target(&module, None, &target, &OptConfig::new(OptLevel::O0))
+        .expect("emit")
+        .llvm_ir
+}
+
+const EXTERNS: &str = "type Pair = record { a: u32, b: u32 }\ntype Vec3 = record { x: f64, y: f64, z: f64 }\ntype Mixed = record { n: u32, w: f64 }\n\ntrusted extern cell pair_sum(p: Pair): u32\ntrusted extern cell vec_scale(v: Vec3, k: f64): Vec3\ntrusted extern cell mixed_make(n: u32): Mixed\n\ncell main() -> u32:\n    val s: u32 = pair_sum(Pair { a: 1, b: 2 })\n    val v: Vec3 = vec_scale(Vec3 { x: 1.0, y: 2.0, z: 3.0 }, 2.0)\n    val m: Mixed = mixed_make(s)\n    yield m.n\n";
+
+#[test]
+fn records_are_named_structs_built_with_insertvalue() {
+    let src = "type Point = record { x: u32, y: u32 }\n\ncell shift(p: Point, d: u32) -> Point:\n    yield Point { x: p.x + d, y: p.y }\n\ncell main() -> u32:\n    val p: Point = shift(Point { x: 1, y: 2 }, 3)\n    yield p.x + p.y\n";
+    let ll = emit_for(src, "x86_64-unknown-linux-gnu");
+
+    assert!(ll.contains("%Point = type { i32, i32 }"), "{ll}");
+    // Between Aura cells records travel as first-class aggregates.
+    assert!(ll.contains("define %Point @shift(%Point %v0, i32 %v1)"), "{ll}");
+    assert!(ll.contains("= insertvalue %Point undef, i32 1, 0"), "{ll}");
+    assert!(ll.contains("= extractvalue %Point %v0, 0"), "{ll}");
+    assert!(ll.contains("= call %Point @shift(%Point"), "{ll}");
+}
+
+#[test]
+fn sysv_x86_64_splits_small_records_into_eightbytes() {
+    let ll = emit_for(EXTERNS, "x86_64-unknown-linux-gnu");
+    assert!(ll.contains("declare i32 @pair_sum(i64)"), "{ll}");
+    assert!(ll.contains("declare void @vec_scale(ptr sret(%Vec3) align 8, ptr byval(%Vec3) align 8, double)"), "{ll}");
+    assert!(ll.contains("declare { i64, double } @mixed_make(i32)"), "{ll}");
+    // The two u32 fields are packed into one register.
+    assert!(ll.contains("= shl i64 %abi3, 32"), "{ll}");
+}
+
+#[test]
+fn win64_passes_records_as_integers_or_by_reference() {
+    let ll = emit_for(EXTERNS, "x86_64-pc-windows-msvc");
+    assert!(ll.contains("target datalayout = \"e-m:w-"), "{ll}");
+    assert!(ll.contains("target triple = \"x86_64-pc-windows-msvc\""), "{ll}");
+    assert!(ll.contains("declare i32 @pair_sum(i64)"), "{ll}");
+    assert!(ll.contains("declare void @vec_scale(ptr sret(%Vec3) align 8, ptr, double)"), "{ll}");
+    assert!(ll.contains("declare void @mixed_make(ptr sret(%Mixed) align 8, i32)"), "{ll}");
+}
+
+#[test]
+fn aapcs64_uses_homogeneous_float_aggregates() {
+    let ll = emit_for(EXTERNS, "aarch64-apple-darwin");
+    assert!(ll.contains("declare [3 x double] @vec_scale([3 x double], double)"), "{ll}");
+    assert!(ll.contains("declare [2 x i64] @mixed_make(i32)"), "{ll}");
+    assert!(ll.contains("= bitcast i64 %abi20 to double"), "{ll}");
+}
+
+#[test]
+fn i386_passes_records_on_the_stack() {
+    let ll = emit_for(EXTERNS, "i686-pc-windows-msvc");
+    assert!(ll.contains("declare i32 @pair_sum(ptr byval(%Pair) align 4)"), "{ll}");
+    assert!(ll.contains("declare void @mixed_make(ptr sret(%Mixed) align 8, i32)"), "{ll}");
+    assert!(ll.contains("store %Pair %v3, ptr %abi0"), "{ll}");
+}
@@ -0,0 +1,26 @@
+#![cfg(feature = "llvm")]
+
+use aura_backend_llvm::{emit_llvm_ir_for_target, NativeTarget, OptConfig, OptLevel, Sanitizers};
+
+fn emit_with(src: &str, opt: OptConfig) -> String {
+    let program = aura_parse::parse_source(src).expect("parse");
+    aura_core::Checker::new().check_program(&program).expect("sema");
+    let module = aura_core::lower_program(&program).expect("lower");
+    let target = NativeTarget::parse("x86_64-unknown-linux-gnu").expect("target");
+    emit_llvm_ir_for_target(&module, None, &target, &opt).expect("emit").llvm_ir
+}
+
+const SRC: &str = "import aura::tensor\n\ncell at(t: Tensor, i: u32) -> u32:\n    yield t.get(i)\n\ncell main() -> u32:\n    val t = tensor::new(4)\n    yield at(t, 2)\n";
+
+#[test]
+fn address_sanitizer_marks_every_emitted_function() {
+    let mut opt = OptConfig::new(OptLevel::O0);
+    opt.sanitize = Sanitizers::parse("address").unwrap();
+    let ll = emit_with(SRC, opt);
+    assert!(ll.contains("define i32 @at(i32 %v0, i32 %v1) noinline sanitize_address \"probe-stack\"=\"inline-asm\" {"), "{ll}");
+    assert!(ll.contains("define i32 @aura_main() noinline sanitize_address \"probe-stack\"=\"inline-asm\" {"), "{ll}");
+    assert!(ll.contains("; Optimization: O0 -inline -vectorize scalar +asan"), "{ll}");
+
+    let ll = emit_with(SRC, OptConfig::new(OptLevel::O0));
+    assert!(!ll.contains("sanitize_address"), "{ll}");
+}
@@ -0,0 +1,44 @@
+#![cfg(feature = "llvm")]
+
+use aura_backend_llvm::{emit_llvm_ir_for_target, NativeTarget, OptConfig, OptLevel};
+
+fn emit_for(src: &str, triple: &str) -> String {
+    let program = aura_parse::parse_source(src).expect("parse");
+    aura_core::Checker::new().check_program(&program).expect("sema");
+    let module = aura_core::lower_program(&program).expect("lower");
+    let target = NativeTarget::parse(triple).expect("target");
+    emit_llvm_ir_for_target(&module, None, &target, &OptConfig::new(OptLevel::O0)).expect("emit").llvm_ir
+}
+
+const SRC: &str = "cell depth(n: u32) -> u32:\n    val mut r: u32 = 0\n    if n > 0:\n        r = depth(n - 1) + 1\n    yield r\n\ncell ping(n: u32) -> u32:\n    val mut r: u32 = 0\n    if n > 0:\n        r = pong(n - 1)\n    yield r\n\ncell pong(n: u32) -> u32:\n    yield ping(n)\n\ncell twice(n: u32) -> u32:\n    yield n * 2\n\ncell main() -> u32:\n    yield depth(twice(3)) + ping(4)\n";
+
+#[test]
+fn recursive_cells_check_the_stack_limit_on_entry() {
+    let ll = emit_for(SRC, "x86_64-unknown-linux-gnu");
+    assert!(ll.contains("@aura_stack_limit = external thread_local global i64"), "{ll}");
+    assert!(ll.contains("@.cell.depth = private unnamed_addr constant [6 x i8] c\"depth\\00\""), "{ll}");
+
+    // Direct and mutual recursion both get the check, ahead of the cell's own entry block.
+    for cell in ["depth", "ping", "pong"] {
+        let body = ll.split(&format!("define i32 @{cell}(")).nth(1).expect(cell);
+        let body = &body[..body.find("\n}\n").unwrap()];
+        assert!(body.contains("\nstack.check:\n"), "{body}");
+        assert!(body.contains("%stack.low = icmp ult i64 %stack.sp, %stack.limit"), "{body}");
+        assert!(body.contains(&format!("call void @aura_stack_overflow(ptr @.cell.{cell})")), "{body}");
+        assert!(body.find("stack.check:").unwrap() < body.find("\nbb").unwrap(), "{body}");
+    }
+    // Leaf and non-recursive callers pay nothing.
+    assert!(!ll.contains("@.cell.twice"), "{ll}");
+    assert!(!ll.contains("@.cell.aura_main"), "{ll}");
+}
+
+#[test]
+fn stack_probes_follow_the_target() {
+    let ll = emit_for(SRC, "x86_64-unknown-linux-gnu");
+    assert!(ll.contains("noinline \"probe-stack\"=\"inline-asm\" {"), "{ll}");
+
+    // Windows already probes large frames through `__chkstk`.
+    let ll = emit_for(SRC, "x86_64-pc-windows-msvc");
+    assert!(!ll.contains("probe-stack"), "{ll}");
+    assert!(ll.contains("call void @aura_stack_overflow(ptr @.cell.depth)"), "{ll}");
+}
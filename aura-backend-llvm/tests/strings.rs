@@ -0,0 +1,85 @@
+#![cfg(feature = "llvm")]
+
+use aura_backend_llvm::{emit_llvm_ir_for_target, NativeTarget, OptConfig, OptLevel};
+
+fn emit(src: &str) -> String {
+    let program = aura_parse::parse_source(src).expect("parse");
+    aura_core::Checker::new().check_program(&program).expect("sema");
+    let module = aura_core::lower_program(&program).expect("lower");
+    let target = NativeTarget::parse("x86_64-unknown-linux-gnu").expect("target");
+    emit_llvm_ir_for_target(&module, None, &target, &OptConfig::new(OptLevel::O0))
+        .expect("emit")
+        .llvm_ir
+}
+
+#[test]
+fn identical_literals_share_one_length_prefixed_global() {
+    let src = "import aura::io\n\ncell greet():\n    io.println(\"hello\")\n\ncell main():\n    greet()\n    io.println(\"hello\")\n    io.println(\"bye\")\n";
+    let ll = emit(src);
+
+    assert_eq!(ll.matches("c\"hello\\00\"").count(), 1, "{ll}");
+    assert!(
+        ll.contains("@.str0 = private unnamed_addr constant { i32, i32, [6 x i8] } { i32 5, i32 1, [6 x i8] c\"hello\\00\" }, align 8"),
+        "{ll}"
+    );
+    assert!(ll.contains("c\"bye\\00\""), "{ll}");
+    // Both cells point past the header at the same bytes.
+    assert_eq!(ll.matches("getelementptr inbounds { i32, i32, [6 x i8] }, ptr @.str0, i32 0, i32 2").count(), 2, "{ll}");
+}
+
+#[test]
+fn interpolation_builds_strings_with_the_length_prefixed_runtime() {
+    let src = "import aura::io\n\ncell main():\n    val n: u32 = 42\n    io.println(\"n = {n}, half = {2.5}\")\n";
+    let ll = emit(src);
+
+    assert!(ll.contains("= call ptr @aura_string_from_u32(i32 42)"), "{ll}");
+    assert!(ll.contains("= call ptr @aura_string_from_f64(double 0x4004000000000000)"), "{ll}");
+    assert!(ll.contains("= call ptr @aura_string_concat(ptr %v"), "{ll}");
+    assert!(!ll.contains("@aura_str_"), "{ll}");
+}
+
+#[test]
+fn strings_returned_by_externs_are_adopted() {
+    let src = "import aura::io\n\ntrusted extern cell getenv(name: String): String\n\ncell main():\n    val home: String = getenv(\"HOME\")\n    io.println(home)\n";
+    let ll = emit(src);
+
+    assert!(ll.contains("declare ptr @getenv(ptr)"), "{ll}");
+    assert!(ll.contains("%abi0 = call ptr @getenv(ptr %v"), "{ll}");
+    assert!(ll.contains("= call ptr @aura_string_adopt(ptr %abi0)"), "{ll}");
+}
+
+#[test]
+fn files_are_stdlib_handles_that_read_and_write_runtime_strings() {
+    let src = "cell main():\n    val f = fs.open(\"in.txt\")\n    val text = fs.read(f)\n    fs.close(f)\n    val mut out = fs.append(\"out.txt\")\n    fs.write(out, text)\n    fs.close(out)\n";
+    let ll = emit(src);
+
+    assert!(ll.contains("= call i32 @aura_fs_open(ptr "), "{ll}");
+    assert!(ll.contains("= call ptr @aura_fs_read(i32 %v"), "{ll}");
+    assert!(ll.contains("= call i32 @aura_fs_append(ptr "), "{ll}");
+    assert!(ll.contains("call void @aura_fs_write(i32 %v"), "{ll}");
+    assert_eq!(ll.matches("call void @aura_fs_close(i32 %v").count(), 2, "{ll}");
+}
+
+#[test]
+fn sockets_are_stdlib_handles_that_send_and_receive_runtime_strings() {
+    let src = "cell main():\n    val mut s = net.connect(\"example.com\", 80)\n    net.send(s, \"GET / HTTP/1.0\\r\\n\\r\\n\")\n    val reply = net.recv(s)\n    net.close(s)\n    io.println(reply)\n";
+    let ll = emit(src);
+
+    assert!(ll.contains("= call i32 @aura_net_connect(ptr "), "{ll}");
+    assert!(ll.contains("call void @aura_net_send(i32 %v"), "{ll}");
+    assert!(ll.contains("= call ptr @aura_net_recv(i32 %v"), "{ll}");
+    assert!(ll.contains("call void @aura_net_close(i32 %v"), "{ll}");
+}
+
+#[test]
+fn str_calls_lower_to_the_stdlib_string_functions() {
+    let src = "cell main():\n    val line = \" key = value \"\n    val eq = str.find(line, \"=\")\n    val key = str.trim(str.slice(line, 0, eq))\n    val value = str.split_nth(line, \"=\", 1)\n    io.println(str.pad_left(key, str.len(value)))\n";
+    let ll = emit(src);
+
+    assert!(ll.contains("= call i32 @aura_string_find(ptr "), "{ll}");
+    assert!(ll.contains("= call ptr @aura_string_slice(ptr "), "{ll}");
+    assert!(ll.contains("= call ptr @aura_string_trim(ptr %v"), "{ll}");
+    assert!(ll.contains("= call ptr @aura_string_split_nth(ptr "), "{ll}");
+    assert!(ll.contains("= call i32 @aura_string_len(ptr %v"), "{ll}");
+    assert!(ll.contains("= call ptr @aura_string_pad_left(ptr %v"), "{ll}");
+}
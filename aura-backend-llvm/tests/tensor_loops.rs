@@ -0,0 +1,63 @@
+#![cfg(feature = "llvm")]
+
+use aura_backend_llvm::{emit_llvm_ir_for_target, NativeTarget, OptConfig, OptLevel, SimdWidth};
+
+fn emit_with(src: &str, triple: &str, opt: OptConfig) -> String {
+    let program = aura_parse::parse_source(src).expect("parse");
+    aura_core::Checker::new().check_program(&program).expect("sema");
+    let module = aura_core::lower_program(&program).expect("lower");
+    let target = NativeTarget::parse(triple).expect("target");
+    emit_llvm_ir_for_target(&module, None, &target, &opt).expect("emit").llvm_ir
+}
+
+const AXPY: &str = "import aura::tensor\n\ncell main() -> u32:\n    val n: u32 = 64\n    val a = tensor::new(n)\n    val s: u32 = a.len()\n    val x = tensor::new(n)\n    val mut y = tensor::new(n)\n    val mut i: u32 = 0\n    while i < n:\n        y.set(i, a.get(i) * x.get(i) + y.get(i) * s)\n        i = i + 1\n    yield y.get(3)\n";
+
+#[test]
+fn elementwise_tensor_loops_get_a_vector_loop_before_the_scalar_one() {
+    let ll = emit_with(AXPY, "x86_64-unknown-linux-gnu", OptConfig::new(OptLevel::O2));
+
+    assert!(ll.contains("declare ptr @aura_tensor_data(i32)"), "{ll}");
+    // The vector loop only runs when every tensor is long enough for the bound.
+    assert!(ll.contains("= call i32 @aura_tensor_len(i32 %v"), "{ll}");
+    assert!(ll.contains("= icmp ule i32 64, %bb1.vec.len2"), "{ll}");
+    assert!(ll.contains("= load <4 x i32>, ptr %bb1.vec.in0, align 4"), "{ll}");
+    assert!(ll.contains("store <4 x i32> %bb1.vec.v"), "{ll}");
+    // `a * x` feeds the add directly; `s` is broadcast once before the loop.
+    assert!(ll.contains("<4 x i32> poison, <4 x i32> zeroinitializer"), "{ll}");
+    assert!(ll.contains("= mul <4 x i32> %bb1.vec.v"), "{ll}");
+    assert!(ll.contains("%bb1.vec.m"), "{ll}");
+    assert!(ll.contains("%bb1.vec.next = add i32 %bb1.vec.j, 4"), "{ll}");
+    // The scalar loop finishes the remainder, starting where the vector loop stopped.
+    assert!(ll.contains("phi i32 [ %bb1.vec.i, %bb1.vec.exit ]"), "{ll}");
+    assert!(ll.contains("call void @aura_tensor_set("), "{ll}");
+}
+
+#[test]
+fn vector_width_follows_the_configured_simd_width() {
+    let mut opt = OptConfig::new(OptLevel::O3);
+    opt.simd = SimdWidth::V512;
+    let ll = emit_with(AXPY, "x86_64-unknown-linux-gnu", opt);
+    assert!(ll.contains("load <16 x i32>"), "{ll}");
+    assert!(ll.contains("\"target-features\"=\"+avx512f\""), "{ll}");
+
+    // NEON has no wider registers to ask for.
+    let ll = emit_with(AXPY, "aarch64-apple-darwin", OptConfig::new(OptLevel::O2));
+    assert!(ll.contains("load <4 x i32>"), "{ll}");
+    assert!(!ll.contains("target-features"), "{ll}");
+}
+
+#[test]
+fn scalar_configurations_and_unsupported_bodies_keep_the_scalar_loop() {
+    let ll = emit_with(AXPY, "x86_64-unknown-linux-gnu", OptConfig::new(OptLevel::O1));
+    assert!(!ll.contains(".vec"), "{ll}");
+
+    let mut opt = OptConfig::new(OptLevel::O2);
+    opt.vectorize = false;
+    let ll = emit_with(AXPY, "x86_64-unknown-linux-gnu", opt);
+    assert!(!ll.contains(".vec"), "{ll}");
+
+    // The index itself is not a tensor element.
+    let src = "import aura::tensor\n\ncell main() -> u32:\n    val n: u32 = 64\n    val mut y = tensor::new(n)\n    val mut i: u32 = 0\n    while i < n:\n        y.set(i, i * 2)\n        i = i + 1\n    yield y.get(3)\n";
+    let ll = emit_with(src, "x86_64-unknown-linux-gnu", OptConfig::new(OptLevel::O2));
+    assert!(!ll.contains(".vec"), "{ll}");
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "llvm")]
+
+use aura_backend_llvm::{emit_llvm_units_for_target, LlvmUnit, NativeTarget, OptConfig, OptLevel};
+
+const PROGRAM: &str = "import aura::io\n\ncell twice(x: u32) -> u32:\n    yield x * 2\n\ncell main():\n    val n: u32 = 4\n    val f = -> { yield twice(n) }\n    io.println(\"{f()}\")\n";
+
+fn units(wanted: impl FnMut(&str) -> bool) -> Vec<LlvmUnit> {
+    let program = aura_parse::parse_source(PROGRAM).expect("parse");
+    aura_core::Checker::new().check_program(&program).expect("sema");
+    let module = aura_core::lower_program(&program).expect("lower");
+    let target = NativeTarget::parse("x86_64-unknown-linux-gnu").expect("target");
+    emit_llvm_units_for_target(&module, None, &target, &OptConfig::new(OptLevel::O0), wanted).expect("emit")
+}
+
+fn unit<'u>(units: &'u [LlvmUnit], name: &str) -> &'u str {
+    let unit = units.iter().find(|u| u.name == name).unwrap_or_else(|| panic!("missing unit {name}"));
+    unit.llvm_ir.as_deref().expect("emitted")
+}
+
+#[test]
+fn each_function_is_a_unit_declaring_the_others() {
+    let units = units(|_| true);
+    let names: Vec<&str> = units.iter().map(|u| u.name.as_str()).collect();
+    assert_eq!(names, ["aura_main", "main_lambda0", "twice", "aura_entry"]);
+    assert_eq!(units[0].function.as_deref(), Some("main"));
+
+    let twice = unit(&units, "twice");
+    assert!(twice.contains("define i32 @twice(i32 %v0)"), "{twice}");
+    assert!(twice.contains("declare void @aura_main()"), "{twice}");
+    assert!(!twice.contains("@aura_entry"), "{twice}");
+
+    let lambda = unit(&units, "main_lambda0");
+    assert!(lambda.contains("define i32 @main_lambda0(ptr %env)"), "{lambda}");
+    assert!(lambda.contains("declare i32 @twice(i32)"), "{lambda}");
+
+    // Each unit interns only its own literals.
+    let main = unit(&units, "aura_main");
+    assert!(main.contains("declare i32 @main_lambda0(ptr)"), "{main}");
+    assert!(!twice.contains("@.str"), "{twice}");
+
+    let entry = unit(&units, "aura_entry");
+    assert!(entry.contains("define i32 @aura_entry()"), "{entry}");
+    assert!(entry.contains("declare void @aura_main()"), "{entry}");
+}
+
+#[test]
+fn units_the_caller_does_not_want_are_listed_without_ir() {
+    let units = units(|f| f != "twice");
+    let twice = units.iter().find(|u| u.name == "twice").expect("twice");
+    assert!(twice.llvm_ir.is_none());
+    assert!(units.iter().filter(|u| u.name != "twice").all(|u| u.llvm_ir.is_some()));
+}
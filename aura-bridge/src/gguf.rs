@@ -0,0 +1,211 @@
+#![forbid(unsafe_code)]
+
+//! Tensor metadata of GGUF files (llama.cpp's format, versions 2 and 3): the `GGUF` magic,
+//! a little-endian header of key/value metadata, then one info record per tensor.
+
+use crate::model::WeightTensor;
+use crate::onnx::OnnxElemType;
+
+/// Tensor data starts on this boundary unless `general.alignment` says otherwise.
+const DEFAULT_ALIGNMENT: u64 = 32;
+
+/// The tensors a GGUF file declares, in file order. Shapes are outermost dimension first,
+/// as in safetensors: GGUF lists dimensions innermost first, so they are reversed.
+pub fn parse_gguf(bytes: &[u8]) -> miette::Result<Vec<WeightTensor>> {
+    let mut r = Reader { bytes, pos: 0 };
+    if r.take(4)? != b"GGUF" {
+        return Err(miette::miette!("GGUF file missing its magic"));
+    }
+    let version = r.u32()?;
+    if !(2..=3).contains(&version) {
+        return Err(miette::miette!("unsupported GGUF version {version}"));
+    }
+    let tensor_count = r.u64()?;
+    let kv_count = r.u64()?;
+
+    let mut alignment = DEFAULT_ALIGNMENT;
+    for _ in 0..kv_count {
+        let key = r.string()?;
+        let ty = r.u32()?;
+        if key == "general.alignment" && ty == 4 {
+            alignment = u64::from(r.u32()?).max(1);
+        } else {
+            r.skip_value(ty)?;
+        }
+    }
+
+    let mut tensors = Vec::new();
+    let mut offsets = Vec::new();
+    for _ in 0..tensor_count {
+        let name = r.string()?.to_string();
+        let n_dims = r.u32()?;
+        let mut shape = (0..n_dims).map(|_| r.u64()).collect::<miette::Result<Vec<_>>>()?;
+        shape.reverse();
+        let ty = r.u32()?;
+        offsets.push((r.u64()?, name.clone()));
+        tensors.push(WeightTensor {
+            dtype: ggml_type_name(ty),
+            elem: ggml_type_elem(ty),
+            name,
+            shape,
+        });
+    }
+
+    let data_start = (r.pos as u64).div_ceil(alignment) * alignment;
+    for (offset, name) in offsets {
+        if data_start.checked_add(offset).is_none_or(|start| start > bytes.len() as u64) {
+            return Err(miette::miette!("GGUF tensor '{name}' has data outside the file"));
+        }
+    }
+    Ok(tensors)
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: u64) -> miette::Result<&'a [u8]> {
+        let end = usize::try_from(n)
+            .ok()
+            .and_then(|n| self.pos.checked_add(n))
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| miette::miette!("GGUF header runs past the end of the file"))?;
+        let out = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(out)
+    }
+
+    fn u32(&mut self) -> miette::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().expect("4 bytes")))
+    }
+
+    fn u64(&mut self) -> miette::Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().expect("8 bytes")))
+    }
+
+    fn string(&mut self) -> miette::Result<&'a str> {
+        let len = self.u64()?;
+        std::str::from_utf8(self.take(len)?).map_err(|_| miette::miette!("GGUF string is not UTF-8"))
+    }
+
+    /// Skips a metadata value of GGUF value type `ty`.
+    fn skip_value(&mut self, ty: u32) -> miette::Result<()> {
+        match ty {
+            // u8, i8, bool
+            0 | 1 | 7 => self.take(1).map(drop),
+            // u16, i16
+            2 | 3 => self.take(2).map(drop),
+            // u32, i32, f32
+            4..=6 => self.take(4).map(drop),
+            // u64, i64, f64
+            10..=12 => self.take(8).map(drop),
+            8 => self.string().map(drop),
+            9 => {
+                let elem = self.u32()?;
+                let len = self.u64()?;
+                for _ in 0..len {
+                    self.skip_value(elem)?;
+                }
+                Ok(())
+            }
+            _ => Err(miette::miette!("unknown GGUF metadata value type {ty}")),
+        }
+    }
+}
+
+/// ggml tensor types by code. Block-quantized types have no Aura element type.
+const GGML_TYPES: &[(u32, &str, Option<&str>)] = &[
+    (0, "F32", Some("f32")),
+    (1, "F16", Some("f16")),
+    (2, "Q4_0", None),
+    (3, "Q4_1", None),
+    (6, "Q5_0", None),
+    (7, "Q5_1", None),
+    (8, "Q8_0", None),
+    (9, "Q8_1", None),
+    (10, "Q2_K", None),
+    (11, "Q3_K", None),
+    (12, "Q4_K", None),
+    (13, "Q5_K", None),
+    (14, "Q6_K", None),
+    (15, "Q8_K", None),
+    (16, "IQ2_XXS", None),
+    (17, "IQ2_XS", None),
+    (18, "IQ3_XXS", None),
+    (19, "IQ1_S", None),
+    (20, "IQ4_NL", None),
+    (21, "IQ3_S", None),
+    (22, "IQ2_S", None),
+    (23, "IQ4_XS", None),
+    (24, "I8", Some("i8")),
+    (25, "I16", Some("i16")),
+    (26, "I32", Some("i32")),
+    (27, "I64", Some("i64")),
+    (28, "F64", Some("f64")),
+    (29, "IQ1_M", None),
+    (30, "BF16", Some("bf16")),
+];
+
+fn ggml_type_name(ty: u32) -> String {
+    match GGML_TYPES.iter().find(|(code, _, _)| *code == ty) {
+        Some((_, name, _)) => name.to_string(),
+        None => format!("ggml type {ty}"),
+    }
+}
+
+fn ggml_type_elem(ty: u32) -> Option<OnnxElemType> {
+    let (_, _, aura) = GGML_TYPES.iter().find(|(code, _, _)| *code == ty)?;
+    OnnxElemType::from_aura_name((*aura)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(out: &mut Vec<u8>, s: &str) {
+        out.extend((s.len() as u64).to_le_bytes());
+        out.extend(s.as_bytes());
+    }
+
+    #[test]
+    fn reads_tensor_infos_after_metadata() {
+        let mut f = b"GGUF".to_vec();
+        f.extend(3u32.to_le_bytes());
+        f.extend(2u64.to_le_bytes());
+        f.extend(2u64.to_le_bytes());
+        // general.architecture = "llama"; tokenizer.ggml.tokens = ["a", "b"]
+        string(&mut f, "general.architecture");
+        f.extend(8u32.to_le_bytes());
+        string(&mut f, "llama");
+        string(&mut f, "tokenizer.ggml.tokens");
+        f.extend(9u32.to_le_bytes());
+        f.extend(8u32.to_le_bytes());
+        f.extend(2u64.to_le_bytes());
+        string(&mut f, "a");
+        string(&mut f, "b");
+        // token_embd.weight: ne = [4, 32000], Q4_K; output_norm.weight: ne = [4], F32
+        string(&mut f, "token_embd.weight");
+        f.extend(2u32.to_le_bytes());
+        f.extend(4u64.to_le_bytes());
+        f.extend(32000u64.to_le_bytes());
+        f.extend(12u32.to_le_bytes());
+        f.extend(0u64.to_le_bytes());
+        string(&mut f, "output_norm.weight");
+        f.extend(1u32.to_le_bytes());
+        f.extend(4u64.to_le_bytes());
+        f.extend(0u32.to_le_bytes());
+        f.extend(64u64.to_le_bytes());
+        f.resize(f.len().div_ceil(32) * 32 + 80, 0);
+
+        let tensors = parse_gguf(&f).expect("parse header");
+        assert_eq!(tensors[0].name, "token_embd.weight");
+        assert_eq!(tensors[0].shape, vec![32000, 4]);
+        assert_eq!((tensors[0].dtype.as_str(), tensors[0].elem), ("Q4_K", None));
+        assert_eq!(tensors[1].elem.and_then(OnnxElemType::aura_name), Some("f32"));
+
+        assert!(parse_gguf(&f[..f.len() - 80]).is_err());
+        assert!(parse_gguf(b"GGML").is_err());
+    }
+}
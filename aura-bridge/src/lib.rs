@@ -9,7 +9,10 @@ use miette::{Diagnostic, IntoDiagnostic};
 use regex::Regex;
 use thiserror::Error;
 
+pub mod gguf;
+pub mod model;
 pub mod onnx;
+pub mod safetensors;
 
 #[derive(Debug, Error, Diagnostic)]
 #[error("bridge error: {message}")]
@@ -28,9 +31,8 @@ pub struct BridgeConfig {
 
     /// Best-effort refined type mapping for generated Aura shims.
     ///
-    /// When enabled, the shim may use:
-    /// - range constraints like `u32[0..255]` for small unsigned integer C types
-    /// - `Option<u32>` for pointer-typed parameters/returns (nullable by default)
+    /// When enabled, the shim uses `Option<u32>` for pointer-typed parameters/returns
+    /// (nullable by default). Fixed-width C integers always map to the matching Aura width.
     pub refine_types: bool,
 }
 
@@ -41,10 +43,88 @@ pub struct LinkInputs {
     /// Extra C/C++ sources to compile into the final executable.
     /// This is used for lightweight shims/wrappers (e.g. Raylib ABI adapters).
     pub c_sources: Vec<PathBuf>,
-    /// DLLs to copy next to the final executable at run time.
+    /// Directories the `c_sources` find headers in (e.g. the project's `include/`, where
+    /// packages unpack theirs).
+    pub include_dirs: Vec<PathBuf>,
+    /// Shared libraries (`.dll`, `.so`, `.dylib`) to copy next to the final executable at run
+    /// time.
     pub runtime_dlls: Vec<PathBuf>,
 }
 
+impl LinkInputs {
+    /// Records a library file found on disk by its kind:
+    /// - `.lib` (import/static, Windows) and `.a` (static, Linux/macOS) are linked
+    /// - `.dll` is only needed at run time
+    /// - `.so`/`.dylib` are linked and needed at run time; versioned sonames such as
+    ///   `libfoo.so.1` are only needed at run time
+    pub fn add_library_file(&mut self, path: &Path) {
+        let Some(name) = path.file_name().and_then(|x| x.to_str()) else { return };
+        let lower = name.to_ascii_lowercase();
+        let ext = path.extension().and_then(|x| x.to_str()).map(str::to_ascii_lowercase);
+        let (link, runtime) = match ext.as_deref() {
+            Some("lib" | "a") => (true, false),
+            Some("dll") => (false, true),
+            Some("so" | "dylib") => (true, true),
+            _ if lower.contains(".so.") => (false, true),
+            _ => (false, false),
+        };
+        if link && !self.libs.iter().any(|l| l.eq_ignore_ascii_case(name)) {
+            self.libs.push(name.to_string());
+        }
+        if runtime && !self.runtime_dlls.iter().any(|d| d == path) {
+            self.runtime_dlls.push(path.to_path_buf());
+        }
+    }
+
+    /// Shared libraries this platform can load at run time, e.g. for an interpreter to call
+    /// the bridged functions: the collected runtime libraries, plus each of `libs` found as a
+    /// shared library in `lib_dirs`.
+    pub fn runtime_libraries(&self) -> Vec<PathBuf> {
+        let mut out: Vec<PathBuf> = self.runtime_dlls.iter().filter(|p| is_loadable_here(p)).cloned().collect();
+        for lib in &self.libs {
+            let found = self
+                .lib_dirs
+                .iter()
+                .flat_map(|dir| shared_library_names(lib).into_iter().map(move |n| dir.join(n)))
+                .find(|p| p.is_file());
+            if let Some(p) = found
+                && !out.contains(&p)
+            {
+                out.push(p);
+            }
+        }
+        out
+    }
+}
+
+fn is_loadable_here(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|x| x.to_str()) else { return false };
+    let lower = name.to_ascii_lowercase();
+    if cfg!(windows) {
+        lower.ends_with(".dll")
+    } else if cfg!(target_os = "macos") {
+        lower.ends_with(".dylib")
+    } else {
+        lower.ends_with(".so") || lower.contains(".so.")
+    }
+}
+
+/// File names the shared build of `lib` (`foo`, `libfoo.a`, `foo.lib`, ...) has here.
+fn shared_library_names(lib: &str) -> Vec<String> {
+    let base = ["lib", "a", "so", "dylib", "dll"]
+        .iter()
+        .find_map(|ext| lib.strip_suffix(&format!(".{ext}")))
+        .unwrap_or(lib);
+    let base = base.strip_prefix("lib").filter(|b| !b.is_empty()).unwrap_or(base);
+    if cfg!(windows) {
+        vec![format!("{base}.dll"), format!("lib{base}.dll")]
+    } else if cfg!(target_os = "macos") {
+        vec![format!("lib{base}.dylib")]
+    } else {
+        vec![format!("lib{base}.so")]
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct BridgeOutputs {
     pub aura_shim_path: PathBuf,
@@ -57,6 +137,8 @@ pub struct DiscoveredFn {
     pub name: String,
     pub params: Vec<(String, String)>,
     pub ret: String,
+    /// `///` or `/** */` comment directly above the declaration, emitted as `##` in the shim.
+    pub doc: Option<String>,
 }
 
 /// Very small “universal bridge” v0:
@@ -82,6 +164,7 @@ pub fn run_bridge(config: &BridgeConfig, out_dir: &Path) -> miette::Result<Bridg
     let mut link = LinkInputs::default();
     link.lib_dirs.extend(config.lib_dirs.iter().cloned());
     link.libs.extend(config.libs.iter().cloned());
+    link.include_dirs.extend(config.include_dirs.iter().cloned());
 
     // Bootstrap discovery: look for import libs / DLLs next to the bridged headers.
     discover_artifacts_near_headers(&config.headers, &mut link)?;
@@ -150,26 +233,12 @@ fn discover_artifacts_near_headers(headers: &[PathBuf], link: &mut LinkInputs) -
             continue;
         }
 
-        // Bootstrap discovery: look for import libs / DLLs next to the bridged headers.
+        // Bootstrap discovery: look for libraries next to the bridged headers.
         let Ok(entries) = fs::read_dir(&dir) else { continue };
-        for e in entries.flatten() {
-            let p = e.path();
-            let Some(ext) = p.extension().and_then(|x| x.to_str()) else { continue };
-            match ext.to_ascii_lowercase().as_str() {
-                "lib" => {
-                    if let Some(name) = p.file_name().and_then(|x| x.to_str()) {
-                        if !link.libs.iter().any(|l| l.eq_ignore_ascii_case(name)) {
-                            link.libs.push(name.to_string());
-                        }
-                    }
-                }
-                "dll" => {
-                    if !link.runtime_dlls.iter().any(|d| d == &p) {
-                        link.runtime_dlls.push(p);
-                    }
-                }
-                _ => {}
-            }
+        let mut files: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+        files.sort();
+        for p in files {
+            link.add_library_file(&p);
         }
     }
 
@@ -192,12 +261,51 @@ fn parse_header_functions(header_text: &str) -> Vec<DiscoveredFn> {
         let name = caps.name("name").unwrap().as_str().to_string();
         let args = caps.name("args").unwrap().as_str();
         let params = parse_params(args);
+        let doc = leading_doc_comment(&header_text[..caps.name("ret").unwrap().start()]);
 
-        out.push(DiscoveredFn { name, params, ret });
+        out.push(DiscoveredFn { name, params, ret, doc });
     }
     out
 }
 
+fn leading_doc_comment(before: &str) -> Option<String> {
+    // Only whole lines above the declaration count; drop its own indentation.
+    let before = &before[..before.rfind('\n')?];
+    let mut lines: Vec<String> = Vec::new();
+    let mut in_block = false;
+    for line in before.lines().rev() {
+        let t = line.trim();
+        if in_block {
+            let done = t.starts_with("/*");
+            let body = t.trim_start_matches("/**").trim_start_matches("/*").trim_start_matches('*');
+            if !(done && body.trim().is_empty()) {
+                lines.push(body.trim().to_string());
+            }
+            if done {
+                break;
+            }
+        } else if let Some(doc) = t.strip_prefix("///") {
+            lines.push(doc.trim().to_string());
+        } else if lines.is_empty() && t.ends_with("*/") {
+            let body = t.trim_end_matches("*/");
+            if let Some(one_line) = body.strip_prefix("/**").or_else(|| body.strip_prefix("/*")) {
+                lines.push(one_line.trim().to_string());
+                break;
+            }
+            let body = body.trim_start_matches('*').trim();
+            if !body.is_empty() {
+                lines.push(body.to_string());
+            }
+            in_block = true;
+        } else {
+            break;
+        }
+    }
+    lines.reverse();
+    let doc = lines.join("\n").trim().to_string();
+    (!doc.is_empty()).then_some(doc)
+}
+
 fn parse_params(args: &str) -> Vec<(String, String)> {
     let args = args.trim();
     if args.is_empty() || args == "void" {
@@ -267,24 +375,16 @@ fn map_c_type_to_aura(ty: &str, refine_types: bool) -> String {
     match t {
         "void" => "Unit".to_string(),
 
-        // Common integer types
-        "int" | "unsigned int" | "uint32_t" | "size_t" => "u32".to_string(),
+        // Common integer types. Plain `int` stays `u32` so existing callers keep passing
+        // `u32` values; the exact-width types say what they hold.
+        "int" | "unsigned int" | "uint32_t" => "u32".to_string(),
+        "int32_t" => "i32".to_string(),
 
-        // Small unsigned types: emit range refinement when enabled.
-        "uint8_t" | "unsigned char" => {
-            if refine_types {
-                "u32[0..255]".to_string()
-            } else {
-                "u32".to_string()
-            }
-        }
-        "uint16_t" | "unsigned short" => {
-            if refine_types {
-                "u32[0..65535]".to_string()
-            } else {
-                "u32".to_string()
-            }
-        }
+        // Sized types carry their range in the type (`u8` is `u8[0..255]`).
+        "uint8_t" | "unsigned char" => "u8".to_string(),
+        "uint16_t" | "unsigned short" => "u16".to_string(),
+        "uint64_t" | "unsigned long long" | "size_t" => "u64".to_string(),
+        "int64_t" | "long long" => "i64".to_string(),
 
         // Fallback: treat unknowns as opaque handle.
         _ => "u32".to_string(),
@@ -308,6 +408,16 @@ fn generate_aura_shim(funcs: &[DiscoveredFn], refine_types: bool) -> String {
 
         let ret_aura = map_c_type_to_aura(&f.ret, refine_types);
 
+        if let Some(doc) = &f.doc {
+            for line in doc.lines() {
+                if line.is_empty() {
+                    out.push_str("##\n");
+                } else {
+                    out.push_str(&format!("## {line}\n"));
+                }
+            }
+        }
+
         // Emit a direct extern declaration matching the C symbol name.
         // We intentionally do NOT generate a same-named Aura wrapper `cell`,
         // because that would produce an LLVM `define` and collide with the C shim.
@@ -325,10 +435,12 @@ mod tests {
     use super::*;
 
     #[test]
-    fn refined_mapping_emits_ranges_and_option_for_pointers() {
-        assert_eq!(map_c_type_to_aura("uint8_t", false), "u32");
-        assert_eq!(map_c_type_to_aura("uint8_t", true), "u32[0..255]");
-        assert_eq!(map_c_type_to_aura("uint16_t", true), "u32[0..65535]");
+    fn refined_mapping_emits_sized_ints_and_option_for_pointers() {
+        assert_eq!(map_c_type_to_aura("uint8_t", false), "u8");
+        assert_eq!(map_c_type_to_aura("uint16_t", true), "u16");
+        assert_eq!(map_c_type_to_aura("uint64_t", false), "u64");
+        assert_eq!(map_c_type_to_aura("int64_t", false), "i64");
+        assert_eq!(map_c_type_to_aura("int", false), "u32");
         assert_eq!(map_c_type_to_aura("int*", false), "u32");
         assert_eq!(map_c_type_to_aura("int*", true), "Option<u32>");
         assert_eq!(map_c_type_to_aura("const char *", true), "String");
@@ -340,13 +452,52 @@ mod tests {
             name: "foo".to_string(),
             params: vec![("p".to_string(), "int*".to_string()), ("n".to_string(), "uint8_t".to_string())],
             ret: "void".to_string(),
+            doc: None,
         }];
 
         let shim_plain = generate_aura_shim(&funcs, false);
-        assert!(shim_plain.contains("extern cell foo(p: u32, n: u32): Unit"));
+        assert!(shim_plain.contains("extern cell foo(p: u32, n: u8): Unit"));
 
         let shim_refined = generate_aura_shim(&funcs, true);
-        assert!(shim_refined.contains("extern cell foo(p: Option<u32>, n: u32[0..255]): Unit"));
+        assert!(shim_refined.contains("extern cell foo(p: Option<u32>, n: u8): Unit"));
+    }
+
+    #[test]
+    fn library_files_are_classified_per_platform() {
+        let mut link = LinkInputs::default();
+        for f in ["opencv.lib", "opencv.dll", "libz.a", "libfoo.so", "libfoo.so.1", "libbar.dylib", "notes.txt"] {
+            link.add_library_file(Path::new("deps").join(f).as_path());
+        }
+        assert_eq!(link.libs, vec!["opencv.lib", "libz.a", "libfoo.so", "libbar.dylib"]);
+        let runtime: Vec<String> = link.runtime_dlls.iter().map(|p| p.file_name().unwrap().to_string_lossy().into_owned()).collect();
+        assert_eq!(runtime, vec!["opencv.dll", "libfoo.so", "libfoo.so.1", "libbar.dylib"]);
     }
-}
 
+    #[test]
+    fn runtime_libraries_resolve_link_names_in_lib_dirs() {
+        let dir = std::env::temp_dir().join(format!("aura-bridge-runtime-libs-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let shared = dir.join(shared_library_names("foo").remove(0));
+        fs::write(&shared, b"").unwrap();
+
+        let link = LinkInputs {
+            lib_dirs: vec![dir.clone()],
+            libs: vec!["foo".to_string(), "missing".to_string()],
+            ..LinkInputs::default()
+        };
+        assert_eq!(link.runtime_libraries(), vec![shared]);
+        assert_eq!(shared_library_names("libfoo.a"), shared_library_names("foo.lib"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn header_doc_comments_carry_into_shim() {
+        let header = "/// Opens the device.\n/// Returns a handle.\nint dev_open(int id);\n\n/**\n * Closes it.\n */\nvoid dev_close(int h);\nint undocumented(void);\n";
+        let funcs = parse_header_functions(header);
+        let docs: Vec<Option<&str>> = funcs.iter().map(|f| f.doc.as_deref()).collect();
+        assert_eq!(docs, vec![Some("Opens the device.\nReturns a handle."), Some("Closes it."), None]);
+
+        let shim = generate_aura_shim(&funcs, false);
+        assert!(shim.contains("## Opens the device.\n## Returns a handle.\nextern cell dev_open"), "{shim}");
+    }
+}
@@ -0,0 +1,155 @@
+#![forbid(unsafe_code)]
+
+//! Model contracts across file formats.
+//!
+//! ONNX models declare their inputs and outputs. Weight files (safetensors, GGUF) only list
+//! tensors, so their contract is the one of a causal language model: a sequence of token
+//! ids in, one row of logits per token out, over the vocabulary the output head (or the
+//! token embedding it is tied to) is sized for.
+
+use std::{fs, path::Path};
+
+use miette::IntoDiagnostic;
+
+use crate::onnx::{OnnxDim, OnnxElemType, OnnxIoShapes};
+
+/// A tensor a weight file declares.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WeightTensor {
+    pub name: String,
+    /// Outermost dimension first.
+    pub shape: Vec<u64>,
+    /// The format's own name for the element type (`BF16`, `Q4_K`, ...).
+    pub dtype: String,
+    /// The element type, when Aura can spell it.
+    pub elem: Option<OnnxElemType>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModelFormat {
+    Onnx,
+    Safetensors,
+    Gguf,
+}
+
+impl ModelFormat {
+    /// The format of a model file: by its magic, else by its extension, else ONNX (whose
+    /// protobuf encoding has no magic).
+    pub fn detect(path: &Path, bytes: &[u8]) -> Self {
+        if bytes.starts_with(b"GGUF") {
+            return Self::Gguf;
+        }
+        // safetensors: a little-endian header length, then the JSON header.
+        if let Some(len) = bytes.get(..8).map(|b| u64::from_le_bytes(b.try_into().expect("8 bytes")))
+            && len.checked_add(8).is_some_and(|end| end <= bytes.len() as u64)
+            && bytes.get(8) == Some(&b'{')
+        {
+            return Self::Safetensors;
+        }
+        let ext = path.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase);
+        match ext.as_deref() {
+            Some("safetensors") => Self::Safetensors,
+            Some("gguf") => Self::Gguf,
+            _ => Self::Onnx,
+        }
+    }
+}
+
+/// Output heads, then the token embeddings a tied head reuses, by tensor name suffix.
+const VOCAB_TENSORS: &[&str] = &[
+    "lm_head.weight",
+    "output.weight",
+    "embed_tokens.weight",
+    "token_embd.weight",
+    "wte.weight",
+    "word_embeddings.weight",
+];
+
+/// The dimension token sequences are open in; bounded by `[ai.dims]` like any other.
+pub const SEQUENCE_DIM: &str = "sequence";
+
+pub fn read_model_io_shapes(path: &Path) -> miette::Result<OnnxIoShapes> {
+    let bytes = fs::read(path).into_diagnostic()?;
+    parse_model_io_shapes(path, &bytes)
+}
+
+/// The contract of the model in `bytes`, read from `path`, with the parser its format needs.
+pub fn parse_model_io_shapes(path: &Path, bytes: &[u8]) -> miette::Result<OnnxIoShapes> {
+    match ModelFormat::detect(path, bytes) {
+        ModelFormat::Onnx => crate::onnx::parse_onnx_io_shapes(bytes),
+        ModelFormat::Safetensors => language_model_io_shapes(&crate::safetensors::parse_safetensors(bytes)?),
+        ModelFormat::Gguf => language_model_io_shapes(&crate::gguf::parse_gguf(bytes)?),
+    }
+}
+
+/// `[sequence]` u32 token ids to `[sequence, vocab]` f32 logits, where `vocab` is the leading
+/// dimension of the first `VOCAB_TENSORS` match.
+pub fn language_model_io_shapes(tensors: &[WeightTensor]) -> miette::Result<OnnxIoShapes> {
+    let vocab_tensor = VOCAB_TENSORS
+        .iter()
+        .find_map(|suffix| {
+            tensors
+                .iter()
+                .find(|t| t.name == *suffix || t.name.ends_with(&format!(".{suffix}")))
+        })
+        .ok_or_else(|| miette::miette!("weights have no output head or token embedding to size the vocabulary"))?;
+    let vocab = match vocab_tensor.shape.as_slice() {
+        [vocab, _] => i64::try_from(*vocab).into_diagnostic()?,
+        shape => {
+            return Err(miette::miette!(
+                "'{}' should be a [vocab, hidden] matrix but has shape {shape:?}",
+                vocab_tensor.name
+            ));
+        }
+    };
+
+    let sequence = || OnnxDim::Symbolic(SEQUENCE_DIM.to_string());
+    Ok(OnnxIoShapes {
+        input: vec![sequence()],
+        output: vec![sequence(), OnnxDim::Known(vocab)],
+        input_elem: OnnxElemType::from_aura_name("u32"),
+        output_elem: OnnxElemType::from_aura_name("f32"),
+        input_quant: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tensor(name: &str, shape: &[u64]) -> WeightTensor {
+        WeightTensor {
+            name: name.to_string(),
+            shape: shape.to_vec(),
+            dtype: "F16".to_string(),
+            elem: OnnxElemType::from_aura_name("f16"),
+        }
+    }
+
+    #[test]
+    fn detects_formats_by_magic_then_extension() {
+        let mut st = 2u64.to_le_bytes().to_vec();
+        st.extend(b"{}");
+        assert_eq!(ModelFormat::detect(Path::new("m.bin"), &st), ModelFormat::Safetensors);
+        assert_eq!(ModelFormat::detect(Path::new("m.bin"), b"GGUF\x03\0\0\0"), ModelFormat::Gguf);
+        assert_eq!(ModelFormat::detect(Path::new("m.gguf"), b""), ModelFormat::Gguf);
+        assert_eq!(ModelFormat::detect(Path::new("m.onnx"), &[0x08, 0x07]), ModelFormat::Onnx);
+    }
+
+    #[test]
+    fn language_models_are_sized_by_their_output_head() {
+        let tensors = [
+            tensor("model.embed_tokens.weight", &[32000, 64]),
+            tensor("lm_head.weight", &[32064, 64]),
+        ];
+        let io = language_model_io_shapes(&tensors).expect("contract");
+        assert_eq!(io.input, vec![OnnxDim::Symbolic("sequence".into())]);
+        assert_eq!(io.output, vec![OnnxDim::Symbolic("sequence".into()), OnnxDim::Known(32064)]);
+        assert_eq!(io.input_elem.and_then(OnnxElemType::aura_name), Some("u32"));
+
+        // Tied embeddings size the vocabulary when there is no head.
+        let io = language_model_io_shapes(&tensors[..1]).expect("contract");
+        assert_eq!(io.output[1], OnnxDim::Known(32000));
+        assert!(language_model_io_shapes(&[tensor("norm.weight", &[64])]).is_err());
+    }
+}
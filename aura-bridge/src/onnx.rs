@@ -4,10 +4,22 @@ use std::{fs, path::Path};
 
 use miette::IntoDiagnostic;
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct OnnxIoShapes {
     pub input: Vec<OnnxDim>,
     pub output: Vec<OnnxDim>,
+    /// Element types, when the model declares them.
+    pub input_elem: Option<OnnxElemType>,
+    pub output_elem: Option<OnnxElemType>,
+    /// Set when the input is quantized: it feeds a `DequantizeLinear` node.
+    pub input_quant: Option<OnnxQuantParams>,
+}
+
+/// Per-tensor quantization: a stored value `q` stands for `(q - zero_point) * scale`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OnnxQuantParams {
+    pub scale: f32,
+    pub zero_point: i64,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -16,9 +28,73 @@ pub enum OnnxDim {
     Symbolic(String),
 }
 
+/// Tensor element type, as an ONNX `TensorProto.DataType` code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct OnnxElemType(pub i32);
+
+impl OnnxElemType {
+    /// ONNX codes paired with the Aura element type names that spell them.
+    const NAMES: &'static [(i32, &'static str)] = &[
+        (1, "f32"),
+        (2, "u8"),
+        (3, "i8"),
+        (4, "u16"),
+        (5, "i16"),
+        (6, "i32"),
+        (7, "i64"),
+        (8, "String"),
+        (9, "bool"),
+        (10, "f16"),
+        (11, "f64"),
+        (12, "u32"),
+        (13, "u64"),
+        (16, "bf16"),
+    ];
+
+    /// Every element type with an Aura name.
+    pub fn all() -> impl Iterator<Item = Self> {
+        Self::NAMES.iter().map(|(code, _)| Self(*code))
+    }
+
+    /// The element type of `Tensor<name, ...>`.
+    pub fn from_aura_name(name: &str) -> Option<Self> {
+        Self::NAMES.iter().find(|(_, n)| *n == name).map(|(code, _)| Self(*code))
+    }
+
+    pub fn aura_name(self) -> Option<&'static str> {
+        Self::NAMES.iter().find(|(code, _)| *code == self.0).map(|(_, n)| *n)
+    }
+
+    /// The values an integer element type holds; `None` for other types.
+    pub fn int_range(self) -> Option<(i64, i64)> {
+        match self.aura_name()? {
+            "u8" => Some((0, u8::MAX.into())),
+            "i8" => Some((i8::MIN.into(), i8::MAX.into())),
+            "u16" => Some((0, u16::MAX.into())),
+            "i16" => Some((i16::MIN.into(), i16::MAX.into())),
+            "u32" => Some((0, u32::MAX.into())),
+            "i32" => Some((i32::MIN.into(), i32::MAX.into())),
+            _ => None,
+        }
+    }
+
+    /// The Aura name, or the raw ONNX code for types Aura cannot spell.
+    pub fn describe(self) -> String {
+        match self.aura_name() {
+            Some(n) => n.to_string(),
+            None => format!("ONNX element type {}", self.0),
+        }
+    }
+}
+
 pub fn read_onnx_io_shapes(path: &Path) -> miette::Result<OnnxIoShapes> {
     let bytes = fs::read(path).into_diagnostic()?;
-    let graph = find_len_delimited_field(&bytes, 7)
+    parse_onnx_io_shapes(&bytes)
+}
+
+/// The first input and output of a serialized ONNX `ModelProto`.
+pub fn parse_onnx_io_shapes(bytes: &[u8]) -> miette::Result<OnnxIoShapes> {
+    let graph = find_len_delimited_field(bytes, 7)
         .ok_or_else(|| miette::miette!("ONNX model missing graph field"))?;
 
     let input_vi = find_first_repeated_len_field(graph, 11)
@@ -31,7 +107,137 @@ pub fn read_onnx_io_shapes(path: &Path) -> miette::Result<OnnxIoShapes> {
     let output = extract_value_info_shape(output_vi)
         .ok_or_else(|| miette::miette!("ONNX output type missing tensor shape"))?;
 
-    Ok(OnnxIoShapes { input, output })
+    let input_quant = find_len_delimited_field(input_vi, 1)
+        .and_then(|name| std::str::from_utf8(name).ok())
+        .and_then(|name| extract_input_quant(graph, name));
+
+    Ok(OnnxIoShapes {
+        input,
+        output,
+        input_elem: extract_value_info_elem(input_vi),
+        output_elem: extract_value_info_elem(output_vi),
+        input_quant,
+    })
+}
+
+/// Quantization of graph input `input`: the scale and zero point of the `DequantizeLinear`
+/// node that reads it, which are initializers (a missing zero point is 0).
+fn extract_input_quant(graph: &[u8], input: &str) -> Option<OnnxQuantParams> {
+    // GraphProto.node = repeated field 1 (NodeProto: input = 1, op_type = 4).
+    let mut cursor = graph;
+    while let Some((field, wire, node, rest)) = next_field(cursor) {
+        cursor = rest;
+        if field != 1 || wire != WireType::Len {
+            continue;
+        }
+        let op_type = find_len_delimited_field(node, 4);
+        let inputs = repeated_strings(node, 1);
+        if op_type != Some(b"DequantizeLinear".as_slice()) || inputs.first().copied() != Some(input) {
+            continue;
+        }
+        let scale = find_initializer(graph, inputs.get(1)?).and_then(scalar_f32)?;
+        let zero_point = match inputs.get(2).filter(|n| !n.is_empty()) {
+            Some(name) => scalar_int(find_initializer(graph, name)?)?,
+            None => 0,
+        };
+        return Some(OnnxQuantParams { scale, zero_point });
+    }
+    None
+}
+
+fn repeated_strings(msg: &[u8], field_num: u32) -> Vec<&str> {
+    let mut out = Vec::new();
+    let mut cursor = msg;
+    while let Some((field, wire, val, rest)) = next_field(cursor) {
+        cursor = rest;
+        if field == field_num
+            && wire == WireType::Len
+            && let Ok(s) = std::str::from_utf8(val)
+        {
+            out.push(s);
+        }
+    }
+    out
+}
+
+/// The initializer (GraphProto field 5, a TensorProto named by field 8) called `name`.
+fn find_initializer<'a>(graph: &'a [u8], name: &str) -> Option<&'a [u8]> {
+    let mut cursor = graph;
+    while let Some((field, wire, tensor, rest)) = next_field(cursor) {
+        cursor = rest;
+        if field == 5 && wire == WireType::Len && find_len_delimited_field(tensor, 8) == Some(name.as_bytes()) {
+            return Some(tensor);
+        }
+    }
+    None
+}
+
+/// The first element of a float TensorProto: `float_data` (field 4) or `raw_data` (field 9).
+fn scalar_f32(tensor: &[u8]) -> Option<f32> {
+    let mut cursor = tensor;
+    while let Some((field, wire, val, rest)) = next_field(cursor) {
+        cursor = rest;
+        match (field, wire) {
+            (4, WireType::Len | WireType::Other(5)) | (9, WireType::Len) => {
+                return Some(f32::from_le_bytes(val.get(..4)?.try_into().ok()?));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The first element of an integer TensorProto, read as its `data_type` (field 2):
+/// `int32_data` (field 5) or `raw_data` (field 9).
+fn scalar_int(tensor: &[u8]) -> Option<i64> {
+    let elem = find_varint_field(tensor, 2).and_then(|c| i32::try_from(c).ok()).map(OnnxElemType);
+    let mut cursor = tensor;
+    while let Some((field, wire, val, rest)) = next_field(cursor) {
+        cursor = rest;
+        match (field, wire) {
+            (5, WireType::Varint) => return Some(decode_varint(val)? as i32 as i64),
+            (5, WireType::Len) => return Some(read_varint(val)?.0 as i32 as i64),
+            (9, WireType::Len) => {
+                return match elem?.aura_name()? {
+                    "u8" => Some((*val.first()?).into()),
+                    "i8" => Some((*val.first()? as i8).into()),
+                    "u16" => Some(u16::from_le_bytes(val.get(..2)?.try_into().ok()?).into()),
+                    "i16" => Some(i16::from_le_bytes(val.get(..2)?.try_into().ok()?).into()),
+                    "i32" => Some(i32::from_le_bytes(val.get(..4)?.try_into().ok()?).into()),
+                    _ => None,
+                };
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn find_varint_field(msg: &[u8], field_num: u32) -> Option<u64> {
+    let mut cursor = msg;
+    while let Some((field, wire, val, rest)) = next_field(cursor) {
+        cursor = rest;
+        if field == field_num && wire == WireType::Varint {
+            return decode_varint(val);
+        }
+    }
+    None
+}
+
+fn extract_value_info_elem(value_info: &[u8]) -> Option<OnnxElemType> {
+    let type_proto = find_len_delimited_field(value_info, 2)?;
+    let tensor_type = find_len_delimited_field(type_proto, 1)?;
+
+    // TensorTypeAndShapeProto.elem_type = field 1 (int32); 0 is UNDEFINED.
+    let mut cursor = tensor_type;
+    while let Some((field, wire, val, rest)) = next_field(cursor) {
+        cursor = rest;
+        if field == 1 && wire == WireType::Varint {
+            let code = i32::try_from(decode_varint(val)?).ok()?;
+            return (code != 0).then_some(OnnxElemType(code));
+        }
+    }
+    None
 }
 
 fn extract_value_info_shape(value_info: &[u8]) -> Option<Vec<OnnxDim>> {
@@ -120,7 +326,9 @@ fn next_field(input: &[u8]) -> Option<(u32, WireType, &[u8], &[u8])> {
         other => {
             let wt = WireType::Other(other);
             let rest2 = skip_unknown(rest, other)?;
-            Some((field, wt, &[], rest2))
+            // Fixed-width values (wire types 1 and 5) are returned as their bytes.
+            let val: &[u8] = if matches!(other, 1 | 5) { &rest[..rest.len() - rest2.len()] } else { &[] };
+            Some((field, wt, val, rest2))
         }
     }
 }
@@ -205,3 +413,86 @@ fn decode_varint(bytes: &[u8]) -> Option<u64> {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A length-delimited protobuf field.
+    fn len_field(field: u8, body: &[u8]) -> Vec<u8> {
+        let mut out = vec![field << 3 | 2, body.len() as u8];
+        out.extend_from_slice(body);
+        out
+    }
+
+    /// A ValueInfoProto with element type `elem` and one dimension of size 4.
+    fn value_info(elem: u8) -> Vec<u8> {
+        let dim = len_field(1, &[1 << 3, 4]);
+        let mut tensor_type = vec![1 << 3, elem];
+        tensor_type.extend(len_field(2, &dim));
+        len_field(2, &len_field(1, &tensor_type))
+    }
+
+    #[test]
+    fn reads_shapes_and_element_types() {
+        let mut graph = len_field(11, &value_info(2));
+        graph.extend(len_field(12, &value_info(1)));
+        let dir = std::env::temp_dir().join(format!("aura-onnx-elem-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let model = dir.join("u8_to_f32.onnx");
+        fs::write(&model, len_field(7, &graph)).unwrap();
+
+        let io = read_onnx_io_shapes(&model).expect("read model");
+        assert_eq!(io.input, vec![OnnxDim::Known(4)]);
+        assert_eq!(io.input_elem.and_then(OnnxElemType::aura_name), Some("u8"));
+        assert_eq!(io.output_elem.and_then(OnnxElemType::aura_name), Some("f32"));
+    }
+
+    #[test]
+    fn models_without_element_types_leave_them_open() {
+        let model = Path::new(env!("CARGO_MANIFEST_DIR")).join("../examples/aura-vision-safe/identity_u32_2x2x3.onnx");
+        let io = read_onnx_io_shapes(&model).expect("read model");
+        assert_eq!(io.input, vec![OnnxDim::Known(2), OnnxDim::Known(2), OnnxDim::Known(3)]);
+        assert_eq!((io.input_elem, io.output_elem), (None, None));
+    }
+
+    #[test]
+    fn reads_quantization_of_dequantized_inputs() {
+        // x (u8) -> DequantizeLinear(x, x_scale, x_zero_point); scale 0.5 as raw f32, zero point 128.
+        let mut vi = len_field(1, b"x");
+        vi.extend(value_info(2));
+        let mut node = len_field(1, b"x");
+        node.extend(len_field(1, b"x_scale"));
+        node.extend(len_field(1, b"x_zero_point"));
+        node.extend(len_field(4, b"DequantizeLinear"));
+        let mut scale = vec![2 << 3, 1];
+        scale.extend(len_field(8, b"x_scale"));
+        scale.extend(len_field(9, &0.5f32.to_le_bytes()));
+        let mut zero_point = vec![2 << 3, 2];
+        zero_point.extend(len_field(8, b"x_zero_point"));
+        zero_point.extend(len_field(9, &[128]));
+
+        let mut graph = len_field(1, &node);
+        graph.extend(len_field(5, &scale));
+        graph.extend(len_field(5, &zero_point));
+        graph.extend(len_field(11, &vi));
+        graph.extend(len_field(12, &value_info(1)));
+        let io = parse_onnx_io_shapes(&len_field(7, &graph)).expect("parse model");
+        assert_eq!(
+            io.input_quant,
+            Some(OnnxQuantParams {
+                scale: 0.5,
+                zero_point: 128
+            })
+        );
+        assert_eq!(io.input_elem.and_then(OnnxElemType::int_range), Some((0, 255)));
+    }
+
+    #[test]
+    fn element_type_names_round_trip() {
+        for t in OnnxElemType::all() {
+            assert_eq!(OnnxElemType::from_aura_name(t.aura_name().unwrap()), Some(t));
+        }
+        assert_eq!(OnnxElemType(14).describe(), "ONNX element type 14");
+    }
+}
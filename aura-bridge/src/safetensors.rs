@@ -0,0 +1,128 @@
+#![forbid(unsafe_code)]
+
+//! Tensor metadata of safetensors files: an 8-byte little-endian header length, a JSON
+//! header mapping each tensor name to its dtype, shape and byte range, then the data.
+
+use crate::model::WeightTensor;
+use crate::onnx::OnnxElemType;
+
+/// The tensors a safetensors file declares, by name.
+pub fn parse_safetensors(bytes: &[u8]) -> miette::Result<Vec<WeightTensor>> {
+    let len_bytes: [u8; 8] = bytes
+        .get(..8)
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| miette::miette!("safetensors file shorter than its header length"))?;
+    let header_len = usize::try_from(u64::from_le_bytes(len_bytes))
+        .ok()
+        .filter(|n| n.checked_add(8).is_some_and(|end| end <= bytes.len()))
+        .ok_or_else(|| miette::miette!("safetensors header runs past the end of the file"))?;
+    let data_len = (bytes.len() - 8 - header_len) as u64;
+
+    let header: serde_json::Map<String, serde_json::Value> = serde_json::from_slice(&bytes[8..8 + header_len])
+        .map_err(|e| miette::miette!("safetensors header is not a JSON object: {e}"))?;
+
+    let mut tensors = Vec::new();
+    for (name, info) in header {
+        // Free-form string metadata, not a tensor.
+        if name == "__metadata__" {
+            continue;
+        }
+        let bad = |what: &str| miette::miette!("safetensors tensor '{name}' has {what}");
+        let dtype = info
+            .get("dtype")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| bad("no dtype"))?
+            .to_string();
+        let shape = info
+            .get("shape")
+            .and_then(|v| v.as_array())
+            .and_then(|dims| dims.iter().map(|d| d.as_u64()).collect::<Option<Vec<_>>>())
+            .ok_or_else(|| bad("no shape"))?;
+        let offsets = info
+            .get("data_offsets")
+            .and_then(|v| v.as_array())
+            .and_then(|o| Some((o.first()?.as_u64()?, o.get(1)?.as_u64()?)))
+            .ok_or_else(|| bad("no data offsets"))?;
+        if offsets.0 > offsets.1 || offsets.1 > data_len {
+            return Err(bad("data outside the file"));
+        }
+        if let Some(size) = dtype_size(&dtype) {
+            let elems = shape.iter().try_fold(1u64, |n, d| n.checked_mul(*d));
+            if elems.and_then(|n| n.checked_mul(size)) != Some(offsets.1 - offsets.0) {
+                return Err(bad("a byte range that does not match its shape"));
+            }
+        }
+        tensors.push(WeightTensor {
+            elem: dtype_elem(&dtype),
+            name,
+            shape,
+            dtype,
+        });
+    }
+    Ok(tensors)
+}
+
+/// Bytes per element; `None` for dtypes this reader does not know.
+fn dtype_size(dtype: &str) -> Option<u64> {
+    match dtype {
+        "BOOL" | "U8" | "I8" | "F8_E4M3" | "F8_E5M2" => Some(1),
+        "U16" | "I16" | "F16" | "BF16" => Some(2),
+        "U32" | "I32" | "F32" => Some(4),
+        "U64" | "I64" | "F64" => Some(8),
+        _ => None,
+    }
+}
+
+fn dtype_elem(dtype: &str) -> Option<OnnxElemType> {
+    let name = match dtype {
+        "BOOL" => "bool",
+        "U8" => "u8",
+        "I8" => "i8",
+        "U16" => "u16",
+        "I16" => "i16",
+        "F16" => "f16",
+        "BF16" => "bf16",
+        "U32" => "u32",
+        "I32" => "i32",
+        "F32" => "f32",
+        "U64" => "u64",
+        "I64" => "i64",
+        "F64" => "f64",
+        _ => return None,
+    };
+    OnnxElemType::from_aura_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(header: &str, data_len: usize) -> Vec<u8> {
+        let mut out = (header.len() as u64).to_le_bytes().to_vec();
+        out.extend_from_slice(header.as_bytes());
+        out.resize(out.len() + data_len, 0);
+        out
+    }
+
+    #[test]
+    fn reads_tensor_names_shapes_and_dtypes() {
+        let header = r#"{"__metadata__":{"format":"pt"},
+            "embed.weight":{"dtype":"BF16","shape":[8,4],"data_offsets":[0,64]},
+            "norm.weight":{"dtype":"F8_E4M3","shape":[4],"data_offsets":[64,68]}}"#;
+        let tensors = parse_safetensors(&file(header, 68)).expect("parse header");
+        assert_eq!(tensors.len(), 2);
+        let embed = tensors.iter().find(|t| t.name == "embed.weight").unwrap();
+        assert_eq!(embed.shape, vec![8, 4]);
+        assert_eq!(embed.elem.and_then(OnnxElemType::aura_name), Some("bf16"));
+        let norm = tensors.iter().find(|t| t.name == "norm.weight").unwrap();
+        assert_eq!((norm.dtype.as_str(), norm.elem), ("F8_E4M3", None));
+    }
+
+    #[test]
+    fn rejects_byte_ranges_that_do_not_fit() {
+        let header = r#"{"w":{"dtype":"F32","shape":[2,2],"data_offsets":[0,8]}}"#;
+        assert!(parse_safetensors(&file(header, 8)).is_err());
+        let header = r#"{"w":{"dtype":"F32","shape":[2],"data_offsets":[0,8]}}"#;
+        assert!(parse_safetensors(&file(header, 4)).is_err());
+    }
+}
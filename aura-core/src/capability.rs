@@ -31,7 +31,7 @@ pub struct CapabilityNode {
     pub consumed_at: Option<Span>,
 }
 
-#[derive(Default, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct CapabilityGraph {
     pub nodes: HashMap<CapabilityId, CapabilityNode>,
     pub edges: Vec<CapabilityEdge>,
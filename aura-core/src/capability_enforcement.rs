@@ -1,12 +1,13 @@
 #![forbid(unsafe_code)]
 
-/// Linear Capability Enforcement for Sockets and Tensors
+/// Linear Capability Enforcement for Sockets, Files and Tensors
 /// 
 /// This module enforces capability-based resource management for hardware-bound
 /// resources like Sockets (network) and Tensors (compute). Capabilities represent
 /// exclusive access rights to resources and must follow strict ordering rules:
 /// 
 /// **Socket Lifecycle**: Created → Connected/Listening → Reading/Writing → Closed
+/// **File Lifecycle**: Opened → Reading/Writing → Closed
 /// **Tensor Lifecycle**: Created → Computed → Available for IO → Released
 /// 
 /// A capability is "consumed" when it transitions to a restricted state.
@@ -19,6 +20,8 @@ use std::collections::{HashMap, HashSet};
 pub enum CapabilityKind {
     /// Network socket capability (exclusive access to network I/O)
     Socket,
+    /// Open file capability (exclusive access to a file opened by `fs.*`)
+    File,
     /// Tensor compute capability (exclusive access to tensor operations)
     Tensor,
     /// Memory region capability (exclusive access to memory region)
@@ -31,6 +34,7 @@ impl CapabilityKind {
     pub fn display(&self) -> &'static str {
         match self {
             CapabilityKind::Socket => "socket",
+            CapabilityKind::File => "file",
             CapabilityKind::Tensor => "tensor",
             CapabilityKind::Region => "region",
             CapabilityKind::Concurrent => "concurrent",
@@ -4,9 +4,84 @@
 /// capabilities during semantic analysis. It wraps CapabilityContext and provides
 /// convenient APIs for sema.rs to use capability enforcement.
 
+use std::collections::{HashMap, HashSet};
+
+use aura_ast::{
+    span_between, Block, BorrowKind, CallArg, CellDef, Expr, ExprKind, InterpPart, Param, Pattern, Program, Span, Stmt,
+    TypeArg, TypeRef,
+};
+
 use crate::capability_enforcement::{CapabilityContext, CapabilityKind, CapabilityState};
+use crate::sema::{call_arg_value, expr_to_callee_name};
 use crate::types::Type;
 
+/// How much of a capability a cell uses through a parameter, weakest first
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ParamAccess {
+    /// Never used
+    Unused,
+    /// Only read, so `x: &T` is enough
+    Read,
+    /// Updated in place, so `x: &mut T` is enough
+    Write,
+    /// Moved or consumed, so it must be taken by value
+    Consume,
+}
+
+impl ParamAccess {
+    /// What a parameter declares: a borrow grants reading or writing, a value everything
+    pub fn declared(param: &Param) -> Self {
+        match param.borrow {
+            Some(BorrowKind::Shared) => ParamAccess::Read,
+            Some(BorrowKind::Mut) => ParamAccess::Write,
+            None => ParamAccess::Consume,
+        }
+    }
+
+    pub fn display(&self) -> &'static str {
+        match self {
+            ParamAccess::Unused => "unused",
+            ParamAccess::Read => "read",
+            ParamAccess::Write => "write",
+            ParamAccess::Consume => "consume",
+        }
+    }
+
+    /// The parameter prefix granting this access: `&`, `&mut ` or nothing
+    fn prefix(&self) -> &'static str {
+        match self {
+            ParamAccess::Unused | ParamAccess::Read => "&",
+            ParamAccess::Write => "&mut ",
+            ParamAccess::Consume => "",
+        }
+    }
+}
+
+/// A source replacement an editor can apply as-is
+#[derive(Clone, Debug, PartialEq)]
+pub struct SuggestedEdit {
+    pub span: Span,
+    pub replacement: String,
+}
+
+/// A capability parameter that declares more than its cell uses
+#[derive(Clone, Debug, PartialEq)]
+pub struct CapabilitySuggestion {
+    pub cell: String,
+    pub param: String,
+    pub kind: CapabilityKind,
+    pub declared: ParamAccess,
+    pub needed: ParamAccess,
+    /// The parameter declaration
+    pub span: Span,
+    pub message: String,
+    /// The cell's signature with every capability parameter narrowed to what it needs
+    pub signature: String,
+    /// Rewrites the parameter and the arguments of every call to the cell. Empty when a caller
+    /// passes something that cannot be borrowed, or when nothing weaker can be declared.
+    pub edits: Vec<SuggestedEdit>,
+}
+
 /// Validator for capabilities in type-checking context
 pub struct CapabilityValidator {
     /// Context tracking all capabilities
@@ -56,6 +131,7 @@ impl CapabilityValidator {
                     n if n.contains("Tensor") => Some(CapabilityKind::Tensor),
                     n if n.contains("Region") => Some(CapabilityKind::Region),
                     n if n.contains("Concurrent") => Some(CapabilityKind::Concurrent),
+                    "File" => Some(CapabilityKind::File),
                     _ => None,
                 }
             }
@@ -140,6 +216,462 @@ impl CapabilityValidator {
     pub fn get_state(&self, name: &str) -> Option<CapabilityState> {
         self.context.get_state(name)
     }
+
+    /// What a builtin does with a capability passed to it; `None` for builtins that consume it
+    pub fn builtin_access(name: &str) -> Option<ParamAccess> {
+        match name {
+            "tensor.len"
+            | "tensor.get"
+            | "tensor.matmul"
+            | "tensor.reshape"
+            | "tensor.concat"
+            | "collections.vector_len"
+            | "collections.vector_get"
+            | "fs.read"
+            | "net.recv"
+            | "net.recv_all" => Some(ParamAccess::Read),
+            "tensor.set" | "collections.vector_set" | "fs.write" | "net.send" => Some(ParamAccess::Write),
+            _ => None,
+        }
+    }
+
+    /// Infer the capability each cell needs from its capability-typed parameters and suggest
+    /// the narrower signature where one declares more than that, including parameters never used.
+    pub fn infer_capabilities(program: &Program) -> Vec<CapabilitySuggestion> {
+        let cells: HashMap<&str, &CellDef> = program
+            .stmts
+            .iter()
+            .filter_map(|s| match s {
+                Stmt::CellDef(c) => Some((c.name.node.as_str(), c)),
+                _ => None,
+            })
+            .collect();
+        let calls = collect_calls(program, &cells);
+
+        let mut out = Vec::new();
+        for stmt in &program.stmts {
+            let Stmt::CellDef(cell) = stmt else { continue };
+            if !cell.type_params.is_empty() {
+                continue;
+            }
+            let mut binders = HashSet::new();
+            collect_binders(&cell.body, &mut binders);
+
+            let mut needed: Vec<Option<(CapabilityKind, ParamAccess)>> = Vec::new();
+            for param in &cell.params {
+                let kind = Self::infer_capability_kind(&Type::Named(param.ty.name.node.clone()));
+                // A shadowed or reassigned name hides which uses are the parameter's.
+                needed.push(match kind {
+                    Some(kind) if !binders.contains(&param.name.node) => {
+                        Some((kind, param_access(&cell.body, &param.name.node, &cells)))
+                    }
+                    _ => None,
+                });
+            }
+
+            let signature = inferred_signature(cell, &needed);
+            for (i, param) in cell.params.iter().enumerate() {
+                let Some((kind, needed)) = needed[i] else { continue };
+                let declared = ParamAccess::declared(param);
+                if needed >= declared && needed != ParamAccess::Unused {
+                    continue;
+                }
+                let ty = type_ref_text(&param.ty);
+                let message = match needed {
+                    ParamAccess::Unused => format!(
+                        "cell '{}' never uses {} capability '{}'",
+                        cell.name.node,
+                        kind.display(),
+                        param.name.node
+                    ),
+                    _ => format!(
+                        "cell '{}' only needs {} access to '{}'; declare it `{}: {}{}`",
+                        cell.name.node,
+                        needed.display(),
+                        param.name.node,
+                        param.name.node,
+                        needed.prefix(),
+                        ty
+                    ),
+                };
+                let edits = if needed.prefix() == declared.prefix() {
+                    Vec::new()
+                } else {
+                    narrowing_edits(cell, i, needed, calls.get(cell.name.node.as_str()))
+                };
+                out.push(CapabilitySuggestion {
+                    cell: cell.name.node.clone(),
+                    param: param.name.node.clone(),
+                    kind,
+                    declared,
+                    needed,
+                    span: param.span,
+                    message,
+                    signature: signature.clone(),
+                    edits,
+                });
+            }
+        }
+        out
+    }
+}
+
+/// Every call to a cell of the program, as the argument bound to each parameter. A call whose
+/// arguments cannot be matched up, like the right side of a flow, is recorded as `None`.
+fn collect_calls<'a>(
+    program: &'a Program,
+    cells: &HashMap<&str, &CellDef>,
+) -> HashMap<String, Vec<Option<Vec<Option<&'a Expr>>>>> {
+    let mut calls: HashMap<String, Vec<Option<Vec<Option<&'a Expr>>>>> = HashMap::new();
+    let mut visit = |e: &'a Expr| match &e.kind {
+        ExprKind::Call { callee, args, .. } => {
+            let name = expr_to_callee_name(callee);
+            if let Some(cell) = cells.get(name.as_str()) {
+                let bound = cell.params.iter().enumerate().map(|(i, p)| bound_arg(args, i, p)).collect();
+                calls.entry(name).or_default().push(Some(bound));
+            }
+        }
+        ExprKind::Flow { right, .. } => {
+            if let ExprKind::Call { callee, .. } = &right.kind {
+                calls.entry(expr_to_callee_name(callee)).or_default().push(None);
+            }
+        }
+        _ => {}
+    };
+    for stmt in &program.stmts {
+        visit_stmt(stmt, &mut visit);
+    }
+    calls
+}
+
+/// The argument bound to parameter `i`, by name or by position.
+fn bound_arg<'a>(args: &'a [CallArg], i: usize, param: &Param) -> Option<&'a Expr> {
+    args.iter()
+        .find_map(|a| match a {
+            CallArg::Named { name, value } if name.node == param.name.node => Some(value),
+            _ => None,
+        })
+        .or_else(|| match args.get(i) {
+            Some(CallArg::Positional(e)) => Some(e),
+            _ => None,
+        })
+}
+
+/// The strongest access `body` makes to `name`. Any use not known to borrow it consumes it.
+fn param_access(body: &Block, name: &str, cells: &HashMap<&str, &CellDef>) -> ParamAccess {
+    let mut access = ParamAccess::Unused;
+    let mut lent: HashSet<usize> = HashSet::new();
+    let is_name = |e: &Expr| matches!(&e.kind, ExprKind::Ident(id) if id.node == name);
+
+    let mut visit = |e: &Expr| match &e.kind {
+        ExprKind::Call { callee, args, .. } => {
+            if let ExprKind::Member { base, member } = &callee.kind
+                && is_name(base)
+                && matches!(member.node.as_str(), "len" | "get" | "set")
+            {
+                let used = CapabilityValidator::builtin_access(&format!("tensor.{}", member.node));
+                access = access.max(used.unwrap_or(ParamAccess::Consume));
+                lent.insert(base.span.offset());
+            }
+            let callee_name = expr_to_callee_name(callee);
+            for (i, arg) in args.iter().enumerate() {
+                let value = call_arg_value(arg);
+                if !is_name(value) {
+                    continue;
+                }
+                let used = match cells.get(callee_name.as_str()) {
+                    Some(cell) => {
+                        let param = match arg {
+                            CallArg::Named { name, .. } => cell.params.iter().find(|p| p.name.node == name.node),
+                            CallArg::Positional(_) => cell.params.get(i),
+                        };
+                        param.map(ParamAccess::declared).unwrap_or(ParamAccess::Consume)
+                    }
+                    None => CapabilityValidator::builtin_access(&callee_name).unwrap_or(ParamAccess::Consume),
+                };
+                access = access.max(used);
+                lent.insert(value.span.offset());
+            }
+        }
+        ExprKind::Borrow { kind, expr } if is_name(expr) => {
+            let used = match kind {
+                BorrowKind::Shared => ParamAccess::Read,
+                BorrowKind::Mut => ParamAccess::Write,
+            };
+            access = access.max(used);
+            lent.insert(expr.span.offset());
+        }
+        ExprKind::Ident(id) if id.node == name && !lent.contains(&e.span.offset()) => {
+            access = ParamAccess::Consume;
+        }
+        _ => {}
+    };
+    visit_block(body, &mut visit);
+    access
+}
+
+/// Edits declaring parameter `i` of `cell` as `needed` and passing it that way at every call.
+fn narrowing_edits(
+    cell: &CellDef,
+    i: usize,
+    needed: ParamAccess,
+    calls: Option<&Vec<Option<Vec<Option<&Expr>>>>>,
+) -> Vec<SuggestedEdit> {
+    let param = &cell.params[i];
+    let mut edits = vec![SuggestedEdit {
+        span: span_between(param.span.offset(), param.ty.span.offset()),
+        replacement: format!("{}: {}", param.name.node, needed.prefix()),
+    }];
+    for call in calls.into_iter().flatten() {
+        let arg = call.as_ref().and_then(|bound| bound[i]);
+        let value = match arg.map(|a| &a.kind) {
+            Some(ExprKind::Ident(id)) => id,
+            Some(ExprKind::Borrow { expr, .. }) => match &expr.kind {
+                ExprKind::Ident(id) => id,
+                _ => return Vec::new(),
+            },
+            _ => return Vec::new(),
+        };
+        edits.push(SuggestedEdit {
+            span: arg.expect("bound argument").span,
+            replacement: format!("{}{}", needed.prefix(), value.node),
+        });
+    }
+    edits
+}
+
+/// `cell name(params) -> ret` with each inferred capability parameter narrowed.
+fn inferred_signature(cell: &CellDef, needed: &[Option<(CapabilityKind, ParamAccess)>]) -> String {
+    let params: Vec<String> = cell
+        .params
+        .iter()
+        .zip(needed)
+        .map(|(p, n)| {
+            let declared = ParamAccess::declared(p);
+            let prefix = match n {
+                Some((_, needed)) if *needed < declared => needed.prefix(),
+                _ if p.mutable => "mut ",
+                _ => declared.prefix(),
+            };
+            format!("{}: {}{}", p.name.node, prefix, type_ref_text(&p.ty))
+        })
+        .collect();
+    let ret = cell.ret.as_ref().map(|r| format!(" -> {}", type_ref_text(r))).unwrap_or_default();
+    format!("cell {}({}){}", cell.name.node, params.join(", "), ret)
+}
+
+fn type_ref_text(t: &TypeRef) -> String {
+    let args: Vec<String> = t
+        .args
+        .iter()
+        .map(|a| match a {
+            TypeArg::Type(inner) => type_ref_text(inner),
+            TypeArg::Shape(dims) => {
+                format!("[{}]", dims.iter().map(|d| d.to_string()).collect::<Vec<_>>().join(", "))
+            }
+        })
+        .collect();
+    if t.name.node == "Tuple" && args.len() >= 2 {
+        format!("({})", args.join(", "))
+    } else if args.is_empty() {
+        t.name.node.clone()
+    } else {
+        format!("{}<{}>", t.name.node, args.join(", "))
+    }
+}
+
+/// Names bound or assigned anywhere in `block`.
+fn collect_binders(block: &Block, out: &mut HashSet<String>) {
+    fn pattern(p: &Pattern, out: &mut HashSet<String>) {
+        out.extend(p.binders().into_iter().map(|b| b.node.clone()));
+    }
+    for stmt in &block.stmts {
+        match stmt {
+            Stmt::StrandDef(s) => {
+                out.insert(s.name.node.clone());
+                if let Some(p) = &s.pattern {
+                    pattern(p, out);
+                }
+            }
+            Stmt::Assign(a) => {
+                out.insert(a.target.node.clone());
+            }
+            Stmt::For(f) => {
+                out.insert(f.var.node.clone());
+                collect_binders(&f.body, out);
+            }
+            Stmt::Match(m) => {
+                for arm in &m.arms {
+                    pattern(&arm.pat, out);
+                    collect_binders(&arm.body, out);
+                }
+            }
+            Stmt::Scope(s) => {
+                out.extend(s.token.iter().map(|t| t.node.clone()));
+                collect_binders(&s.body, out);
+            }
+            Stmt::If(i) => {
+                collect_binders(&i.then_block, out);
+                if let Some(e) = &i.else_block {
+                    collect_binders(e, out);
+                }
+            }
+            Stmt::While(w) => collect_binders(&w.body, out),
+            Stmt::Defer(d) => collect_binders(&d.body, out),
+            Stmt::UnsafeBlock(u) => collect_binders(&u.body, out),
+            Stmt::Region(r) => collect_binders(&r.body, out),
+            Stmt::FlowBlock(f) => collect_binders(&f.body, out),
+            _ => {}
+        }
+    }
+    let mut visit = |e: &Expr| match &e.kind {
+        ExprKind::ForAll { binders, .. } | ExprKind::Exists { binders, .. } => {
+            out.extend(binders.iter().map(|b| b.name.node.clone()));
+        }
+        ExprKind::Lambda { body, .. } => {
+            let mut inner = HashSet::new();
+            collect_binders(body, &mut inner);
+            out.extend(inner);
+        }
+        _ => {}
+    };
+    for stmt in &block.stmts {
+        visit_stmt(stmt, &mut visit);
+    }
+}
+
+/// Calls `f` on every expression under `stmt`, outer expressions first.
+fn visit_stmt<'a>(stmt: &'a Stmt, f: &mut impl FnMut(&'a Expr)) {
+    match stmt {
+        Stmt::CellDef(c) => visit_block(&c.body, f),
+        Stmt::StrandDef(s) => {
+            if let Some(w) = &s.where_clause {
+                visit_expr(w, f);
+            }
+            visit_expr(&s.expr, f);
+        }
+        Stmt::Assign(a) => visit_expr(&a.expr, f),
+        Stmt::Prop(p) => visit_expr(&p.expr, f),
+        Stmt::If(i) => {
+            visit_expr(&i.cond, f);
+            visit_block(&i.then_block, f);
+            if let Some(e) = &i.else_block {
+                visit_block(e, f);
+            }
+        }
+        Stmt::Match(m) => {
+            visit_expr(&m.scrutinee, f);
+            for arm in &m.arms {
+                visit_block(&arm.body, f);
+            }
+        }
+        Stmt::While(w) => {
+            visit_expr(&w.cond, f);
+            for e in w.invariant.iter().chain(&w.decreases) {
+                visit_expr(e, f);
+            }
+            visit_block(&w.body, f);
+        }
+        Stmt::For(l) => {
+            visit_expr(&l.start, f);
+            visit_expr(&l.end, f);
+            if let Some(e) = &l.invariant {
+                visit_expr(e, f);
+            }
+            visit_block(&l.body, f);
+        }
+        Stmt::Return(r) => {
+            if let Some(e) = &r.value {
+                visit_expr(e, f);
+            }
+        }
+        Stmt::Requires(s) => visit_expr(&s.expr, f),
+        Stmt::Ensures(s) => visit_expr(&s.expr, f),
+        Stmt::Assert(s) => visit_expr(&s.expr, f),
+        Stmt::Assume(s) => visit_expr(&s.expr, f),
+        Stmt::MacroCall(m) => {
+            for e in &m.args {
+                visit_expr(e, f);
+            }
+        }
+        Stmt::ExprStmt(e) => visit_expr(e, f),
+        Stmt::Defer(d) => visit_block(&d.body, f),
+        Stmt::UnsafeBlock(u) => visit_block(&u.body, f),
+        Stmt::Scope(s) => visit_block(&s.body, f),
+        Stmt::Region(r) => visit_block(&r.body, f),
+        Stmt::Layout(l) => visit_block(&l.body, f),
+        Stmt::Render(r) => visit_block(&r.body, f),
+        Stmt::FlowBlock(b) => visit_block(&b.body, f),
+        Stmt::Import(_)
+        | Stmt::MacroDef(_)
+        | Stmt::TypeAlias(_)
+        | Stmt::TraitDef(_)
+        | Stmt::RecordDef(_)
+        | Stmt::EnumDef(_)
+        | Stmt::ExternCell(_)
+        | Stmt::Break(_)
+        | Stmt::Continue(_) => {}
+    }
+}
+
+pub(crate) fn visit_block<'a>(block: &'a Block, f: &mut impl FnMut(&'a Expr)) {
+    for stmt in &block.stmts {
+        visit_stmt(stmt, f);
+    }
+    if let Some(y) = &block.yield_expr {
+        visit_expr(y, f);
+    }
+}
+
+pub(crate) fn visit_expr<'a>(expr: &'a Expr, f: &mut impl FnMut(&'a Expr)) {
+    f(expr);
+    match &expr.kind {
+        ExprKind::Ident(_) | ExprKind::IntLit(_) | ExprKind::FloatLit(_) | ExprKind::StringLit(_) => {}
+        ExprKind::InterpolatedString(parts) => {
+            for part in parts {
+                if let InterpPart::Expr(e) = part {
+                    visit_expr(e, f);
+                }
+            }
+        }
+        ExprKind::StyleLit { fields } | ExprKind::RecordLit { fields, .. } => {
+            for (_, v) in fields {
+                visit_expr(v, f);
+            }
+        }
+        ExprKind::Unary { expr, .. } | ExprKind::Borrow { expr, .. } => visit_expr(expr, f),
+        ExprKind::Member { base, .. } | ExprKind::TupleIndex { base, .. } => visit_expr(base, f),
+        ExprKind::Binary { left, right, .. } | ExprKind::Flow { left, right, .. } => {
+            visit_expr(left, f);
+            visit_expr(right, f);
+        }
+        ExprKind::Index { base, index } => {
+            visit_expr(base, f);
+            visit_expr(index, f);
+        }
+        ExprKind::Tuple(elems) | ExprKind::ListLit(elems) => {
+            for e in elems {
+                visit_expr(e, f);
+            }
+        }
+        ExprKind::MapLit(entries) => {
+            for (k, v) in entries {
+                visit_expr(k, f);
+                visit_expr(v, f);
+            }
+        }
+        ExprKind::Call { callee, args, trailing } => {
+            visit_expr(callee, f);
+            for a in args {
+                visit_expr(call_arg_value(a), f);
+            }
+            if let Some(b) = trailing {
+                visit_block(b, f);
+            }
+        }
+        ExprKind::Lambda { body, .. } => visit_block(body, f),
+        ExprKind::ForAll { body, .. } | ExprKind::Exists { body, .. } => visit_expr(body, f),
+    }
 }
 
 #[cfg(test)]
@@ -221,6 +753,8 @@ mod tests {
         let region = Type::Named("RegionAlloc".to_string());
         assert_eq!(CapabilityValidator::infer_capability_kind(&region), Some(CapabilityKind::Region));
         
+        assert_eq!(CapabilityValidator::infer_capability_kind(&Type::file()), Some(CapabilityKind::File));
+        
         let u32_type = Type::U32;
         assert_eq!(CapabilityValidator::infer_capability_kind(&u32_type), None);
     }
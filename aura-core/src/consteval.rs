@@ -0,0 +1,563 @@
+#![forbid(unsafe_code)]
+
+//! Compile-time constants.
+//!
+//! `const MAX: u32 = 4096` declares a top-level `u32` constant. Before checking, every use of a
+//! constant is replaced by its value and arithmetic over constants is folded. Range bounds are
+//! evaluated in full, so `u32[0..MAX * 2]` and `u32[0..64 - 1]` reach sema and the verifier as
+//! plain literals, and a bound that isn't a compile-time constant is an error.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use aura_ast::{
+    BinOp, Block, CallArg, Expr, ExprKind, InterpPart, Pattern, Program, QuantBinder, Span, Stmt,
+    TypeArg, TypeRef,
+};
+
+use crate::SemanticError;
+
+/// Values of the top-level `const` declarations of a program.
+#[derive(Clone, Debug, Default)]
+pub struct ConstEnv {
+    values: HashMap<String, u64>,
+}
+
+impl ConstEnv {
+    /// Evaluates the program's `const` declarations in order; a constant may only use the ones
+    /// declared before it.
+    pub fn collect(program: &Program) -> Result<Self, SemanticError> {
+        let mut env = ConstEnv::default();
+        for stmt in &program.stmts {
+            let Stmt::StrandDef(sd) = stmt else { continue };
+            if !sd.is_const {
+                continue;
+            }
+            if env.values.contains_key(&sd.name.node) {
+                return Err(SemanticError {
+                    message: format!("duplicate const '{}'", sd.name.node),
+                    span: sd.name.span,
+                });
+            }
+            if let Some(ty) = &sd.ty
+                && (ty.name.node != "u32" || !ty.args.is_empty() || ty.range.is_some())
+            {
+                return Err(SemanticError {
+                    message: "const declarations must have type u32".to_string(),
+                    span: ty.span,
+                });
+            }
+            let value = env.eval(&sd.expr)?;
+            env.values.insert(sd.name.node.clone(), value);
+        }
+        Ok(env)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn get(&self, name: &str) -> Option<u64> {
+        self.values.get(name).copied()
+    }
+
+    /// Evaluates a constant `u32` expression: literals, known constants, and arithmetic or
+    /// bitwise operators over them.
+    pub fn eval(&self, expr: &Expr) -> Result<u64, SemanticError> {
+        self.eval_as(expr, "const initializer")
+    }
+
+    /// [`ConstEnv::eval`], naming what must be constant (`range upper-bound`) in errors.
+    fn eval_as(&self, expr: &Expr, what: &str) -> Result<u64, SemanticError> {
+        match &expr.kind {
+            ExprKind::IntLit(n) => Ok(*n),
+            ExprKind::Ident(id) => self.get(&id.node).ok_or_else(|| SemanticError {
+                message: format!("{what} must be a constant u32 expression; '{}' is not a constant", id.node),
+                span: id.span,
+            }),
+            ExprKind::Binary { left, op, right } => {
+                let l = self.eval_as(left, what)?;
+                let r = self.eval_as(right, what)?;
+                fold_binop(*op, l, r, expr.span)?.ok_or_else(|| SemanticError {
+                    message: "operator is not allowed in a constant expression".to_string(),
+                    span: expr.span,
+                })
+            }
+            _ => Err(SemanticError {
+                message: format!("{what} must be a constant u32 expression"),
+                span: expr.span,
+            }),
+        }
+    }
+}
+
+/// Replaces every use of a top-level constant with its value and folds range bounds to
+/// literals. Programs with nothing to fold are returned as-is.
+pub fn fold_consts(program: &Program) -> Result<Cow<'_, Program>, SemanticError> {
+    check_nested_consts(program)?;
+    let env = ConstEnv::collect(program)?;
+    if env.is_empty() && !has_computed_bounds(program) {
+        return Ok(Cow::Borrowed(program));
+    }
+
+    let mut out = program.clone();
+    let mut folder = Folder { env: &env, changed: false };
+    for stmt in &mut out.stmts {
+        folder.stmt(stmt)?;
+    }
+    if !folder.changed {
+        return Ok(Cow::Borrowed(program));
+    }
+    Ok(Cow::Owned(out))
+}
+
+fn check_nested_consts(program: &Program) -> Result<(), SemanticError> {
+    fn block(b: &Block) -> Result<(), SemanticError> {
+        b.stmts.iter().try_for_each(nested)
+    }
+    fn nested(stmt: &Stmt) -> Result<(), SemanticError> {
+        match stmt {
+            Stmt::StrandDef(sd) if sd.is_const => Err(SemanticError {
+                message: "const declarations are only allowed at the top level".to_string(),
+                span: sd.span,
+            }),
+            _ => top(stmt),
+        }
+    }
+    fn top(stmt: &Stmt) -> Result<(), SemanticError> {
+        match stmt {
+            Stmt::CellDef(c) => block(&c.body),
+            Stmt::FlowBlock(f) => block(&f.body),
+            Stmt::UnsafeBlock(u) => block(&u.body),
+            Stmt::Scope(sc) => block(&sc.body),
+            Stmt::Region(r) => block(&r.body),
+            Stmt::Defer(d) => block(&d.body),
+            Stmt::Layout(l) => block(&l.body),
+            Stmt::Render(r) => block(&r.body),
+            Stmt::If(i) => {
+                block(&i.then_block)?;
+                i.else_block.as_ref().map_or(Ok(()), block)
+            }
+            Stmt::Match(m) => m.arms.iter().try_for_each(|a| block(&a.body)),
+            Stmt::While(w) => block(&w.body),
+            Stmt::For(f) => block(&f.body),
+            _ => Ok(()),
+        }
+    }
+    program.stmts.iter().try_for_each(top)
+}
+
+/// Whether any range bound in `program` is something other than an integer literal.
+fn has_computed_bounds(program: &Program) -> bool {
+    fn block(b: &Block) -> bool {
+        b.stmts.iter().any(stmt) || b.yield_expr.as_ref().is_some_and(expr)
+    }
+    fn ty(t: &TypeRef) -> bool {
+        let computed = |e: &Expr| !matches!(e.kind, ExprKind::IntLit(_));
+        t.range.as_ref().is_some_and(|r| computed(&r.lo) || computed(&r.hi))
+            || t.args.iter().any(|a| matches!(a, TypeArg::Type(inner) if ty(inner)))
+    }
+    fn stmt(s: &Stmt) -> bool {
+        match s {
+            Stmt::StrandDef(sd) => {
+                sd.ty.as_ref().is_some_and(ty) || sd.where_clause.as_ref().is_some_and(expr) || expr(&sd.expr)
+            }
+            Stmt::TypeAlias(ta) => ty(&ta.target),
+            Stmt::RecordDef(rd) => rd.fields.iter().any(|f| ty(&f.ty) || f.default.as_ref().is_some_and(expr)),
+            Stmt::EnumDef(ed) => ed.variants.iter().any(|v| v.fields.iter().any(|f| ty(&f.ty))),
+            Stmt::CellDef(c) => c.params.iter().any(|p| ty(&p.ty)) || c.ret.as_ref().is_some_and(ty) || block(&c.body),
+            Stmt::ExternCell(ext) => ext.params.iter().any(|p| ty(&p.ty)) || ty(&ext.ret),
+            Stmt::UnsafeBlock(u) => block(&u.body),
+            Stmt::Scope(sc) => block(&sc.body),
+            Stmt::Region(r) => block(&r.body),
+            Stmt::Defer(d) => block(&d.body),
+            Stmt::Layout(l) => block(&l.body),
+            Stmt::Render(r) => block(&r.body),
+            Stmt::FlowBlock(f) => block(&f.body),
+            Stmt::Prop(p) => expr(&p.expr),
+            Stmt::Assign(a) => expr(&a.expr),
+            Stmt::If(i) => expr(&i.cond) || block(&i.then_block) || i.else_block.as_ref().is_some_and(block),
+            Stmt::Match(m) => expr(&m.scrutinee) || m.arms.iter().any(|a| block(&a.body)),
+            Stmt::While(w) => expr(&w.cond) || block(&w.body),
+            Stmt::For(f) => expr(&f.start) || expr(&f.end) || block(&f.body),
+            Stmt::Return(r) => r.value.as_ref().is_some_and(expr),
+            Stmt::Requires(r) => expr(&r.expr),
+            Stmt::Ensures(e) => expr(&e.expr),
+            Stmt::Assert(a) => expr(&a.expr),
+            Stmt::Assume(a) => expr(&a.expr),
+            Stmt::MacroCall(m) => m.args.iter().any(expr),
+            Stmt::ExprStmt(e) => expr(e),
+            Stmt::Import(_) | Stmt::MacroDef(_) | Stmt::TraitDef(_) | Stmt::Break(_) | Stmt::Continue(_) => false,
+        }
+    }
+    // Only blocks and quantifier binders inside an expression can carry a type.
+    fn expr(e: &Expr) -> bool {
+        match &e.kind {
+            ExprKind::Lambda { body, .. } => block(body),
+            ExprKind::ForAll { binders, body } | ExprKind::Exists { binders, body } => {
+                binders.iter().any(|b| b.ty.as_ref().is_some_and(ty)) || expr(body)
+            }
+            ExprKind::Call { callee, args, trailing } => {
+                expr(callee)
+                    || args.iter().any(|a| match a {
+                        CallArg::Positional(v) | CallArg::Named { value: v, .. } => expr(v),
+                    })
+                    || trailing.as_deref().is_some_and(block)
+            }
+            ExprKind::Unary { expr: inner, .. } | ExprKind::Borrow { expr: inner, .. } => expr(inner),
+            ExprKind::Binary { left, right, .. } | ExprKind::Flow { left, right, .. } => expr(left) || expr(right),
+            ExprKind::Member { base, .. } | ExprKind::TupleIndex { base, .. } => expr(base),
+            ExprKind::Index { base, index } => expr(base) || expr(index),
+            ExprKind::Tuple(elems) | ExprKind::ListLit(elems) => elems.iter().any(expr),
+            ExprKind::MapLit(entries) => entries.iter().any(|(k, v)| expr(k) || expr(v)),
+            ExprKind::StyleLit { fields } | ExprKind::RecordLit { fields, .. } => fields.iter().any(|(_, v)| expr(v)),
+            ExprKind::InterpolatedString(parts) => {
+                parts.iter().any(|p| matches!(p, InterpPart::Expr(inner) if expr(inner)))
+            }
+            ExprKind::Ident(_)
+            | ExprKind::IntLit(_)
+            | ExprKind::FloatLit(_)
+            | ExprKind::StringLit(_) => false,
+        }
+    }
+    program.stmts.iter().any(stmt)
+}
+
+/// Folds `l op r`; `Ok(None)` for operators that do not produce a `u32`.
+fn fold_binop(op: BinOp, l: u64, r: u64, span: Span) -> Result<Option<u64>, SemanticError> {
+    let out = match op {
+        BinOp::Add => l.checked_add(r),
+        BinOp::Sub => l.checked_sub(r),
+        BinOp::Mul => l.checked_mul(r),
+        BinOp::Div => {
+            if r == 0 {
+                return Err(SemanticError {
+                    message: "division by zero in constant expression".to_string(),
+                    span,
+                });
+            }
+            Some(l / r)
+        }
+        BinOp::BitAnd => Some(l & r),
+        BinOp::BitOr => Some(l | r),
+        BinOp::BitXor => Some(l ^ r),
+        BinOp::Shl | BinOp::Shr => {
+            if r >= 32 {
+                return Err(SemanticError {
+                    message: "shift amount may be 32 or more".to_string(),
+                    span,
+                });
+            }
+            if op == BinOp::Shl {
+                Some((l << r) & u64::from(u32::MAX))
+            } else {
+                Some(l >> r)
+            }
+        }
+        _ => return Ok(None),
+    };
+    match out {
+        Some(v) if v <= u64::from(u32::MAX) => Ok(Some(v)),
+        _ => Err(SemanticError {
+            message: "constant expression overflows u32".to_string(),
+            span,
+        }),
+    }
+}
+
+struct Folder<'e> {
+    env: &'e ConstEnv,
+    /// Whether anything was rewritten
+    changed: bool,
+}
+
+impl Folder<'_> {
+    fn bind(&self, name: &aura_ast::Ident) -> Result<(), SemanticError> {
+        if self.env.get(&name.node).is_some() {
+            return Err(SemanticError {
+                message: format!("'{}' is a constant and cannot be rebound", name.node),
+                span: name.span,
+            });
+        }
+        Ok(())
+    }
+
+    fn stmt(&mut self, stmt: &mut Stmt) -> Result<(), SemanticError> {
+        match stmt {
+            Stmt::StrandDef(sd) if sd.is_const => {
+                let value = self.env.get(&sd.name.node).unwrap_or_default();
+                sd.expr = Expr { span: sd.expr.span, kind: ExprKind::IntLit(value) };
+                self.changed = true;
+            }
+            Stmt::StrandDef(sd) => {
+                self.bind(&sd.name)?;
+                if let Some(p) = &sd.pattern {
+                    self.pattern(p)?;
+                }
+                if let Some(t) = &mut sd.ty {
+                    self.type_ref(t)?;
+                }
+                if let Some(e) = &mut sd.where_clause {
+                    self.expr(e)?;
+                }
+                self.expr(&mut sd.expr)?;
+            }
+            Stmt::TypeAlias(ta) => self.type_ref(&mut ta.target)?,
+            Stmt::RecordDef(rd) => {
+                for f in &mut rd.fields {
+                    self.type_ref(&mut f.ty)?;
+                    if let Some(e) = &mut f.default {
+                        self.expr(e)?;
+                    }
+                }
+            }
+            Stmt::EnumDef(ed) => {
+                for v in &mut ed.variants {
+                    for f in &mut v.fields {
+                        self.type_ref(&mut f.ty)?;
+                    }
+                }
+            }
+            Stmt::CellDef(c) => {
+                for p in &mut c.params {
+                    self.bind(&p.name)?;
+                    self.type_ref(&mut p.ty)?;
+                }
+                if let Some(t) = &mut c.ret {
+                    self.type_ref(t)?;
+                }
+                if let Some(e) = &mut c.decreases {
+                    self.expr(e)?;
+                }
+                self.block(&mut c.body)?;
+            }
+            Stmt::ExternCell(ext) => {
+                for p in &mut ext.params {
+                    self.type_ref(&mut p.ty)?;
+                }
+                self.type_ref(&mut ext.ret)?;
+            }
+            Stmt::UnsafeBlock(u) => self.block(&mut u.body)?,
+            Stmt::Scope(sc) => self.block(&mut sc.body)?,
+            Stmt::Region(r) => self.block(&mut r.body)?,
+            Stmt::Defer(d) => self.block(&mut d.body)?,
+            Stmt::Layout(l) => self.block(&mut l.body)?,
+            Stmt::Render(r) => self.block(&mut r.body)?,
+            Stmt::FlowBlock(f) => self.block(&mut f.body)?,
+            Stmt::Prop(p) => {
+                self.expr(&mut p.expr)?;
+            }
+            Stmt::Assign(a) => {
+                if self.env.get(&a.target.node).is_some() {
+                    return Err(SemanticError {
+                        message: format!("cannot assign to constant '{}'", a.target.node),
+                        span: a.target.span,
+                    });
+                }
+                self.expr(&mut a.expr)?;
+            }
+            Stmt::If(i) => {
+                self.expr(&mut i.cond)?;
+                self.block(&mut i.then_block)?;
+                if let Some(b) = &mut i.else_block {
+                    self.block(b)?;
+                }
+            }
+            Stmt::Match(m) => {
+                self.expr(&mut m.scrutinee)?;
+                for arm in &mut m.arms {
+                    self.pattern(&arm.pat)?;
+                    self.block(&mut arm.body)?;
+                }
+            }
+            Stmt::While(w) => {
+                self.expr(&mut w.cond)?;
+                if let Some(e) = &mut w.invariant {
+                    self.expr(e)?;
+                }
+                if let Some(e) = &mut w.decreases {
+                    self.expr(e)?;
+                }
+                self.block(&mut w.body)?;
+            }
+            Stmt::For(f) => {
+                self.bind(&f.var)?;
+                self.expr(&mut f.start)?;
+                self.expr(&mut f.end)?;
+                if let Some(e) = &mut f.invariant {
+                    self.expr(e)?;
+                }
+                self.block(&mut f.body)?;
+            }
+            Stmt::Return(r) => {
+                if let Some(e) = &mut r.value {
+                    self.expr(e)?;
+                }
+            }
+            Stmt::Requires(r) => {
+                self.expr(&mut r.expr)?;
+            }
+            Stmt::Ensures(e) => {
+                self.expr(&mut e.expr)?;
+            }
+            Stmt::Assert(a) => {
+                self.expr(&mut a.expr)?;
+            }
+            Stmt::Assume(a) => {
+                self.expr(&mut a.expr)?;
+            }
+            Stmt::MacroCall(m) => {
+                for e in &mut m.args {
+                    self.expr(e)?;
+                }
+            }
+            Stmt::ExprStmt(e) => {
+                self.expr(e)?;
+            }
+            Stmt::Import(_)
+            | Stmt::MacroDef(_)
+            | Stmt::TraitDef(_)
+            | Stmt::Break(_)
+            | Stmt::Continue(_) => {}
+        }
+        Ok(())
+    }
+
+    fn block(&mut self, b: &mut Block) -> Result<(), SemanticError> {
+        for s in &mut b.stmts {
+            self.stmt(s)?;
+        }
+        if let Some(e) = &mut b.yield_expr {
+            self.expr(e)?;
+        }
+        Ok(())
+    }
+
+    fn pattern(&self, p: &Pattern) -> Result<(), SemanticError> {
+        match p {
+            Pattern::Bind { name, .. } => self.bind(name),
+            Pattern::Ctor { args, .. } => args.iter().try_for_each(|a| self.pattern(a)),
+            Pattern::Tuple { elems, .. } => elems.iter().try_for_each(|e| self.pattern(e)),
+            Pattern::Or { alts, .. } => alts.iter().try_for_each(|a| self.pattern(a)),
+            Pattern::Wildcard { .. } | Pattern::IntLit { .. } | Pattern::StringLit { .. } => Ok(()),
+        }
+    }
+
+    fn binders(&mut self, binders: &mut [QuantBinder]) -> Result<(), SemanticError> {
+        for b in binders {
+            self.bind(&b.name)?;
+            if let Some(t) = &mut b.ty {
+                self.type_ref(t)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn type_ref(&mut self, t: &mut TypeRef) -> Result<(), SemanticError> {
+        for arg in &mut t.args {
+            if let TypeArg::Type(inner) = arg {
+                self.type_ref(inner)?;
+            }
+        }
+        if let Some(r) = &mut t.range {
+            self.bound(&mut r.lo, "range lower-bound")?;
+            self.bound(&mut r.hi, "range upper-bound")?;
+        }
+        Ok(())
+    }
+
+    /// Evaluates a range bound in full; unlike other expressions it must be constant.
+    fn bound(&mut self, e: &mut Expr, what: &str) -> Result<(), SemanticError> {
+        if matches!(e.kind, ExprKind::IntLit(_)) {
+            return Ok(());
+        }
+        e.kind = ExprKind::IntLit(self.env.eval_as(e, what)?);
+        self.changed = true;
+        Ok(())
+    }
+
+    /// Substitutes constants in `e` and folds the arithmetic they take part in. Returns whether
+    /// `e` is now a literal derived from a constant.
+    fn expr(&mut self, e: &mut Expr) -> Result<bool, SemanticError> {
+        match &mut e.kind {
+            ExprKind::Ident(id) => {
+                if let Some(v) = self.env.get(&id.node) {
+                    e.kind = ExprKind::IntLit(v);
+                    self.changed = true;
+                    return Ok(true);
+                }
+            }
+            ExprKind::IntLit(_) | ExprKind::FloatLit(_) | ExprKind::StringLit(_) => {}
+            ExprKind::InterpolatedString(parts) => {
+                for part in parts {
+                    if let InterpPart::Expr(inner) = part {
+                        self.expr(inner)?;
+                    }
+                }
+            }
+            ExprKind::StyleLit { fields } | ExprKind::RecordLit { fields, .. } => {
+                for (_, v) in fields {
+                    self.expr(v)?;
+                }
+            }
+            ExprKind::Unary { expr, .. } | ExprKind::Borrow { expr, .. } => {
+                self.expr(expr)?;
+            }
+            ExprKind::Binary { left, op, right } => {
+                let l_const = self.expr(left)?;
+                let r_const = self.expr(right)?;
+                let folds = l_const || r_const;
+                if let (true, ExprKind::IntLit(l), ExprKind::IntLit(r)) = (folds, &left.kind, &right.kind)
+                    && let Some(v) = fold_binop(*op, *l, *r, e.span)?
+                {
+                    e.kind = ExprKind::IntLit(v);
+                    return Ok(true);
+                }
+            }
+            ExprKind::Flow { left, right, .. } => {
+                self.expr(left)?;
+                self.expr(right)?;
+            }
+            ExprKind::Member { base, .. } | ExprKind::TupleIndex { base, .. } => {
+                self.expr(base)?;
+            }
+            ExprKind::Tuple(elems) | ExprKind::ListLit(elems) => {
+                for x in elems {
+                    self.expr(x)?;
+                }
+            }
+            ExprKind::MapLit(entries) => {
+                for (k, v) in entries {
+                    self.expr(k)?;
+                    self.expr(v)?;
+                }
+            }
+            ExprKind::Index { base, index } => {
+                self.expr(base)?;
+                self.expr(index)?;
+            }
+            ExprKind::Call { callee, args, trailing } => {
+                // A callee names a cell, never a constant.
+                if !matches!(callee.kind, ExprKind::Ident(_)) {
+                    self.expr(callee)?;
+                }
+                for arg in args {
+                    match arg {
+                        CallArg::Positional(v) | CallArg::Named { value: v, .. } => {
+                            self.expr(v)?;
+                        }
+                    }
+                }
+                if let Some(b) = trailing {
+                    self.block(b)?;
+                }
+            }
+            ExprKind::Lambda { body, .. } => self.block(body)?,
+            ExprKind::ForAll { binders, body } | ExprKind::Exists { binders, body } => {
+                self.binders(binders)?;
+                self.expr(body)?;
+            }
+        }
+        Ok(false)
+    }
+}
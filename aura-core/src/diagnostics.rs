@@ -452,6 +452,76 @@ impl DiagnosticFactory {
             var_name
         ))
     }
+    
+    /// Create warning for a val or parameter that is never read
+    pub fn unused_binding(file: String, line: u32, col: u32, name: &str, is_param: bool) -> LinearTypeDiagnostic {
+        let what = if is_param { "parameter" } else { "val" };
+        let diag = LinearTypeDiagnostic::new(
+            Location::new(file, line, col),
+            ViolationKind::UnusedBinding,
+            format!("unused {} '{}'", what, name),
+        )
+        .with_severity(Severity::Warning);
+        
+        if is_param {
+            diag.with_suggestion(format!("Rename it to '_{}' if the cell has to take it", name))
+        } else {
+            diag.with_suggestion(format!(
+                "Remove it, or rename it to '_{}' if it is only computed for its effects",
+                name
+            ))
+        }
+    }
+    
+    /// Create warning for an import nothing refers to
+    pub fn unused_import(file: String, line: u32, col: u32, path: &str) -> LinearTypeDiagnostic {
+        LinearTypeDiagnostic::new(
+            Location::new(file, line, col),
+            ViolationKind::UnusedImport,
+            format!("unused import '{}'", path),
+        )
+        .with_severity(Severity::Warning)
+        .with_suggestion("Remove the import".to_string())
+    }
+    
+    /// Create warning for statements after one that always leaves the block
+    pub fn unreachable_code(file: String, line: u32, col: u32) -> LinearTypeDiagnostic {
+        LinearTypeDiagnostic::new(
+            Location::new(file, line, col),
+            ViolationKind::UnreachableCode,
+            "unreachable code".to_string(),
+        )
+        .with_severity(Severity::Warning)
+        .with_details(
+            "Every branch of the statement before it returns or leaves the loop, so this code never runs."
+                .to_string(),
+        )
+        .with_suggestion("Remove it, or move it before the statement that leaves the block".to_string())
+    }
+
+    /// Create warning for a match arm the arms before it already cover
+    pub fn unreachable_arm(file: String, line: u32, col: u32) -> LinearTypeDiagnostic {
+        LinearTypeDiagnostic::new(
+            Location::new(file, line, col),
+            ViolationKind::UnreachableCode,
+            "unreachable match arm".to_string(),
+        )
+        .with_severity(Severity::Warning)
+        .with_details("The arms before it already match every value this one does, so it never runs.".to_string())
+        .with_suggestion("Remove the arm, or move it before the arm that covers it".to_string())
+    }
+
+    /// Create warning for a shared strand that concurrent flows access without synchronization
+    pub fn data_race(file: String, line: u32, col: u32, message: String, suggestion: String) -> LinearTypeDiagnostic {
+        LinearTypeDiagnostic::new(Location::new(file, line, col), ViolationKind::DataRace, message)
+            .with_severity(Severity::Warning)
+            .with_details(
+                "Flows started with `~>` run at the same time as the cell that started them and as each other, \
+                 so their accesses can interleave."
+                    .to_string(),
+            )
+            .with_suggestion(suggestion)
+    }
 }
 
 /// Diagnostic collector/reporter
@@ -530,7 +600,7 @@ mod tests {
             ViolationKind::UseAfterMove,
             "test error".to_string(),
         )
-        .with_suggestion("fix this".to_string())
+        .suggestion("fix this".to_string())
         .build();
         
         assert_eq!(diag.severity, Severity::Error);
@@ -551,6 +621,19 @@ mod tests {
         assert!(!diag.related.is_empty());
     }
 
+    #[test]
+    fn test_lint_diagnostics_are_warnings() {
+        let diag = DiagnosticFactory::unused_binding("test.aura".to_string(), 3, 9, "n", true);
+        assert_eq!(diag.severity, Severity::Warning);
+        assert_eq!(diag.error_kind, ViolationKind::UnusedBinding);
+        assert_eq!(diag.message, "unused parameter 'n'");
+
+        let mut reporter = DiagnosticReporter::new();
+        reporter.add(diag);
+        reporter.add(DiagnosticFactory::unreachable_code("test.aura".to_string(), 5, 5));
+        assert!(!reporter.has_errors());
+    }
+
     #[test]
     fn test_code_snippet() {
         let lines = vec![
@@ -0,0 +1,253 @@
+//! Effect inference: which kinds of I/O a cell performs.
+//!
+//! A cell's effects come from the built-ins it calls (`io.*`, `fs.*`, `net.*`, `hw.*`, plus the
+//! clock, channel and UI calls, which count as `io`), from extern cells, which are foreign
+//! code and count as `io`, and from every cell it calls. A cell declaring `uses [..]` may only
+//! perform the effects it lists; `uses []` declares it pure. Cells without a list are not
+//! checked.
+
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use aura_ast::{CellDef, ExprKind, Program, Span, Stmt};
+
+use crate::capability_validator::{visit_block, SuggestedEdit};
+use crate::sema::expr_to_callee_name;
+
+/// A kind of side effect, in the order `uses` lists print them
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Effect {
+    /// Console, clock, channels, UI and foreign code
+    Io,
+    /// Files
+    Fs,
+    /// Sockets
+    Net,
+    /// Device registers, interrupts and DMA
+    Hw,
+}
+
+impl Effect {
+    pub const ALL: [Effect; 4] = [Effect::Io, Effect::Fs, Effect::Net, Effect::Hw];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Effect::Io => "io",
+            Effect::Fs => "fs",
+            Effect::Net => "net",
+            Effect::Hw => "hw",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Effect::ALL.into_iter().find(|e| e.name() == name)
+    }
+
+    /// The effect of calling the built-in `name`, if it has one
+    pub fn of_intrinsic(name: &str) -> Option<Self> {
+        let (module, call) = name.split_once('.')?;
+        match module {
+            "io" | "time" | "chan" | "ui" | "audio" | "shop" => Some(Effect::Io),
+            "fs" => Some(Effect::Fs),
+            "ai" if call == "load_model" => Some(Effect::Fs),
+            "net" => Some(Effect::Net),
+            "hw" => Some(Effect::Hw),
+            _ => None,
+        }
+    }
+}
+
+/// `uses [fs, net]`, or `uses []` for no effects
+pub fn format_uses(effects: impl IntoIterator<Item = Effect>) -> String {
+    let names: Vec<&str> = effects.into_iter().map(Effect::name).collect();
+    format!("uses [{}]", names.join(", "))
+}
+
+/// The call through which a cell gets an effect
+#[derive(Clone, Debug, PartialEq)]
+pub struct EffectSource {
+    pub effect: Effect,
+    /// A built-in, an extern cell, or a cell that has the effect itself
+    pub via: String,
+    /// The call
+    pub span: Span,
+}
+
+/// Effects a cell performs, directly or through the cells it calls
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CellEffects {
+    pub effects: BTreeSet<Effect>,
+    /// One source per effect, in the order of `effects`
+    pub sources: Vec<EffectSource>,
+}
+
+impl CellEffects {
+    pub fn is_pure(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    /// The `uses` list documenting these effects
+    pub fn signature(&self) -> String {
+        format_uses(self.effects.iter().copied())
+    }
+
+    pub fn source(&self, effect: Effect) -> Option<&EffectSource> {
+        self.sources.iter().find(|s| s.effect == effect)
+    }
+}
+
+/// A cell whose body performs an effect its `uses` list leaves out, or a list naming an
+/// unknown effect
+#[derive(Clone, Debug, PartialEq)]
+pub struct EffectViolation {
+    pub cell: String,
+    /// The call performing the effect, or the unknown name
+    pub span: Span,
+    pub message: String,
+    /// Rewrites the `uses` list to every effect the cell performs
+    pub edit: Option<SuggestedEdit>,
+}
+
+/// Effects of every top-level cell, keyed by its (qualified) name. Effects flow from callees
+/// to callers until nothing changes, so recursion is handled.
+pub fn infer_effects(program: &Program) -> BTreeMap<String, CellEffects> {
+    let cells: Vec<&CellDef> = program
+        .stmts
+        .iter()
+        .filter_map(|s| match s {
+            Stmt::CellDef(c) => Some(c),
+            _ => None,
+        })
+        .collect();
+    let cell_names: HashSet<&str> = cells.iter().map(|c| c.name.node.as_str()).collect();
+    let externs: HashSet<&str> = program
+        .stmts
+        .iter()
+        .filter_map(|s| match s {
+            Stmt::ExternCell(e) => Some(e.name.node.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    let mut found: BTreeMap<String, BTreeMap<Effect, EffectSource>> = BTreeMap::new();
+    // Calls to other cells: (caller, callee, call span), in body order.
+    let mut edges: Vec<(String, String, Span)> = Vec::new();
+    for cell in &cells {
+        let direct = found.entry(cell.name.node.clone()).or_default();
+        visit_block(&cell.body, &mut |e| {
+            let ExprKind::Call { callee, .. } = &e.kind else {
+                return;
+            };
+            let name = expr_to_callee_name(callee);
+            let effect = if let Some(callee) = resolve_cell_call(&cell.name.node, &name, &cell_names) {
+                edges.push((cell.name.node.clone(), callee, e.span));
+                return;
+            } else if externs.contains(name.as_str()) {
+                Effect::Io
+            } else if let Some(effect) = Effect::of_intrinsic(&name) {
+                effect
+            } else {
+                return;
+            };
+            direct.entry(effect).or_insert(EffectSource {
+                effect,
+                via: name,
+                span: e.span,
+            });
+        });
+    }
+
+    loop {
+        let mut changed = false;
+        for (caller, callee, span) in &edges {
+            let inherited: Vec<Effect> = found[callee].keys().copied().collect();
+            let into = found.get_mut(caller).expect("caller");
+            for effect in inherited {
+                if let Entry::Vacant(slot) = into.entry(effect) {
+                    slot.insert(EffectSource {
+                        effect,
+                        via: callee.clone(),
+                        span: *span,
+                    });
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    found
+        .into_iter()
+        .map(|(name, sources)| {
+            let effects = sources.keys().copied().collect();
+            let sources = sources.into_values().collect();
+            (name, CellEffects { effects, sources })
+        })
+        .collect()
+}
+
+/// The top-level cell a call to `name` inside `caller` reaches, if any. A module's cells are
+/// named `http.get` and may call each other unqualified.
+pub(crate) fn resolve_cell_call(caller: &str, name: &str, cells: &HashSet<&str>) -> Option<String> {
+    if let Some((ns, _)) = caller.rsplit_once('.')
+        && !name.contains('.')
+    {
+        let qualified = format!("{ns}.{name}");
+        if cells.contains(qualified.as_str()) {
+            return Some(qualified);
+        }
+    }
+    cells.contains(name).then(|| name.to_string())
+}
+
+/// Checks every cell declaring `uses [..]` against what it performs.
+pub fn check_declared_effects(program: &Program) -> Vec<EffectViolation> {
+    let inferred = infer_effects(program);
+    let mut out = Vec::new();
+    for stmt in &program.stmts {
+        let Stmt::CellDef(cell) = stmt else { continue };
+        let Some(list) = &cell.effects else { continue };
+        let name = &cell.name.node;
+
+        let mut declared: BTreeSet<Effect> = BTreeSet::new();
+        for id in &list.node {
+            match Effect::from_name(&id.node) {
+                Some(effect) => {
+                    declared.insert(effect);
+                }
+                None => out.push(EffectViolation {
+                    cell: name.clone(),
+                    span: id.span,
+                    message: format!("unknown effect '{}'; the effects are io, fs, net and hw", id.node),
+                    edit: None,
+                }),
+            }
+        }
+
+        let Some(performed) = inferred.get(name) else { continue };
+        let needed: BTreeSet<Effect> = declared.union(&performed.effects).copied().collect();
+        for source in &performed.sources {
+            if declared.contains(&source.effect) {
+                continue;
+            }
+            let declared_list = format_uses(declared.iter().copied());
+            out.push(EffectViolation {
+                cell: name.clone(),
+                span: source.span,
+                message: format!(
+                    "cell '{name}' performs {} effects through `{}` but declares `{declared_list}`; declare `{}`",
+                    source.effect.name(),
+                    source.via,
+                    format_uses(needed.iter().copied()),
+                ),
+                edit: Some(SuggestedEdit {
+                    span: list.span,
+                    replacement: format_uses(needed.iter().copied()),
+                }),
+            });
+        }
+    }
+    out
+}
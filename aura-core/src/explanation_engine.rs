@@ -10,6 +10,10 @@
 
 use std::collections::HashMap;
 
+use aura_ast::Span;
+
+use crate::ownership_enforcement::ViolationKind;
+
 /// Proof step in the derivation
 #[derive(Clone, Debug)]
 pub struct ProofStep {
@@ -245,6 +249,24 @@ impl Explanation {
     }
 }
 
+/// A linear value used after it moved, as the type checker found it
+#[derive(Clone, Debug, PartialEq)]
+pub struct MoveViolation {
+    /// `UseAfterMove` or `DoubleMove`
+    pub kind: ViolationKind,
+    /// The binding used again
+    pub name: String,
+    /// Its type, as diagnostics print it
+    pub ty: String,
+    /// The use the checker rejected
+    pub used_at: Span,
+    /// Where the value moved out, when known
+    pub moved_at: Option<Span>,
+    /// The cell and parameter it moved into, when that was a call to a cell of the program,
+    /// which could borrow it instead
+    pub moved_into: Option<(String, String)>,
+}
+
 /// Explanation generator from UNSAT cores
 pub struct ExplanationEngine {
     /// Proof steps by location
@@ -355,6 +377,87 @@ impl ExplanationEngine {
         explanation.add_suggestion("Use this example to debug your code".to_string());
         explanation
     }
+
+    /// Step-by-step explanation of a use-after-move or double consume: where the value moved,
+    /// why its type moves rather than copies, where it was used again, and how to fix it.
+    pub fn explain_ownership(&self, v: &MoveViolation, source: &str) -> Explanation {
+        let name = &v.name;
+        let ty = &v.ty;
+        let second = v.kind == ViolationKind::DoubleMove;
+        let message = if second {
+            format!("value '{name}' is consumed twice")
+        } else {
+            format!("value '{name}' used after move")
+        };
+        let mut explanation = Explanation::new(message);
+
+        let moved_line = v.moved_at.map(|span| {
+            let (location, line) = source_location(source, span);
+            let derives = match &v.moved_into {
+                Some((cell, param)) => format!("'{name}' moves into '{cell}' as '{param}'"),
+                None => format!("'{name}' moves out here"),
+            };
+            explanation.add_step(ProofStep::new(
+                location,
+                line,
+                derives,
+                format!(
+                    "{ty} is linear: it is never copied, so passing or binding it by value hands \
+                     the one value over and leaves '{name}' empty"
+                ),
+            ));
+            location.0
+        });
+
+        let (location, line) = source_location(source, v.used_at);
+        let (derives, reason) = if second {
+            (
+                format!("'{name}' is moved a second time"),
+                "a linear value can be consumed only once, and the first move already took it",
+            )
+        } else {
+            (
+                format!("'{name}' is used, but it no longer holds a value"),
+                "a binding cannot be read once its value has moved out",
+            )
+        };
+        explanation.add_step(ProofStep::new(location, line, derives, reason.to_string()));
+
+        explanation.add_suggestion(match &v.moved_into {
+            Some((cell, param)) => format!(
+                "Borrow instead of moving: declare '{param}' in '{cell}' as `&{ty}` (or `&mut {ty}` to \
+                 update it in place) and pass `&{name}`, so '{name}' stays with the caller"
+            ),
+            None => format!(
+                "Borrow instead of moving where a use only needs to look at '{name}': a cell \
+                 parameter `&{ty}` takes `&{name}` and leaves it with the caller"
+            ),
+        });
+        let earlier = moved_line.map(|l| format!(" before line {l}")).unwrap_or_default();
+        explanation.add_suggestion(match (&v.moved_into, second) {
+            (Some((cell, _)), _) => format!(
+                "Have '{cell}' return the value when it is done with it, bind the result to a new \
+                 name and use that instead of '{name}'"
+            ),
+            (None, true) => format!("Consume '{name}' once, and do everything else with it{earlier}"),
+            (None, false) => format!("Move the use of '{name}'{earlier}, while it still owns the value"),
+        });
+        explanation.add_suggestion(format!(
+            "If both uses need a value of their own, build a separate {ty} for the second one"
+        ));
+
+        explanation
+    }
+}
+
+/// 1-based (line, column) of `span` in `source`, and that line's text.
+fn source_location(source: &str, span: Span) -> ((u32, u32), String) {
+    let offset = span.offset().min(source.len());
+    let start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let end = source[offset..].find('\n').map_or(source.len(), |i| offset + i);
+    let line = source[..start].matches('\n').count() + 1;
+    let column = source[start..offset].chars().count() + 1;
+    ((line as u32, column as u32), source[start..end].trim().to_string())
 }
 
 impl Default for ExplanationEngine {
@@ -0,0 +1,473 @@
+//! Data races between `~>` flows on shared strands.
+//!
+//! A top-level `val mut` strand is visible to every cell, so flows spawned with
+//! `x ~> cell(..)` may read and write it while the cell that spawned them, or another flow,
+//! does the same. A spawned flow runs until its handle is read, or until the `scope` or
+//! `region` block it started in ends; a spawn inside a loop stands for several flows at once.
+//! Each flow's accesses, including those of the cells it calls, are recorded as
+//! [`MemoryAccess`]es on their own thread and checked by the [`RaceDetector`].
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use aura_ast::{Block, CellDef, Expr, ExprKind, FlowOp, Program, Span, Stmt};
+
+use crate::capability_validator::{visit_expr, CapabilityValidator, ParamAccess};
+use crate::diagnostics::{DiagnosticFactory, Location};
+use crate::effects::resolve_cell_call;
+use crate::lint::{lint_at, Lint};
+use crate::race_detector::{AccessType, MemoryAccess, RaceDetector, RaceViolation};
+use crate::sema::{call_arg_value, expr_to_callee_name};
+
+/// One side of a [`FlowRace`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlowAccess {
+    pub span: Span,
+    pub write: bool,
+    /// The cell whose body reads or writes the strand
+    pub cell: String,
+    /// The `~>` that started the flow, or `None` for the cell that spawned the others
+    pub spawn: Option<Span>,
+}
+
+impl FlowAccess {
+    fn describe(&self) -> String {
+        let verb = if self.write { "written" } else { "read" };
+        match self.spawn {
+            Some(_) => format!("{verb} here by the flow into '{}'", self.cell),
+            None => format!("{verb} here by '{}' while its flows run", self.cell),
+        }
+    }
+}
+
+/// A shared strand two flows running at the same time access without synchronization, at
+/// least one of them writing
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlowRace {
+    pub strand: String,
+    pub first: FlowAccess,
+    /// Where the race is reported
+    pub second: FlowAccess,
+}
+
+impl FlowRace {
+    pub fn message(&self) -> String {
+        let how = if self.first.write && self.second.write { "written" } else { "read and written" };
+        format!("data race on '{}': {how} by concurrent flows without synchronization", self.strand)
+    }
+
+    /// Moves the updates onto a channel, so a single flow owns the strand.
+    pub fn suggestion(&self) -> String {
+        format!(
+            "Give '{s}' one owner: create `val ch = chan.bounded(1)` before spawning, have each flow \
+             `chan.send(ch, ..)` its update instead of touching '{s}', and apply the updates where \
+             '{s}' is owned with `chan.recv(ch)`",
+            s = self.strand
+        )
+    }
+
+    /// Other sites worth showing alongside the report: the first access and the spawns.
+    pub fn related(&self) -> Vec<(Span, String)> {
+        let mut out = vec![(self.first.span, self.first.describe())];
+        for access in [&self.first, &self.second] {
+            if let Some(spawn) = access.spawn
+                && !out.iter().any(|(span, _)| *span == spawn)
+            {
+                out.push((spawn, format!("flow into '{}' started here", access.cell)));
+            }
+        }
+        out
+    }
+
+    /// The race as a warning at the second access, or `None` when that lies outside `source`.
+    pub fn lint(&self, file: &str, source: &str) -> Option<Lint> {
+        lint_at(file, source, self.second.span, |file, line, col| {
+            let mut diag = DiagnosticFactory::data_race(file.clone(), line, col, self.message(), self.suggestion());
+            for (span, message) in self.related() {
+                if span.offset() + span.len() <= source.len() {
+                    let (line, col) = line_col(source, span.offset());
+                    diag = diag.with_related(Location::new(file.clone(), line, col), message);
+                }
+            }
+            diag
+        })
+    }
+}
+
+/// Races on the top-level `val mut` strands of `program`, in source order. `source` is the
+/// text `program` was parsed from.
+pub fn detect_flow_races(program: &Program, source: &str) -> Vec<FlowRace> {
+    let shared: HashSet<&str> = program
+        .stmts
+        .iter()
+        .filter_map(|s| match s {
+            Stmt::StrandDef(s) if s.mutable && !s.is_const && s.pattern.is_none() => Some(s.name.node.as_str()),
+            _ => None,
+        })
+        .collect();
+    if shared.is_empty() {
+        return Vec::new();
+    }
+    let cells: Vec<&CellDef> = program
+        .stmts
+        .iter()
+        .filter_map(|s| match s {
+            Stmt::CellDef(c) => Some(c),
+            _ => None,
+        })
+        .collect();
+    let cell_names: HashSet<&str> = cells.iter().map(|c| c.name.node.as_str()).collect();
+
+    let events: Vec<(&str, Vec<Event>)> = cells
+        .iter()
+        .map(|cell| {
+            let mut collector = Collector {
+                shared: &shared,
+                cells: &cell_names,
+                caller: &cell.name.node,
+                locals: vec![cell.params.iter().map(|p| p.name.node.clone()).collect()],
+                loops: 0,
+                events: Vec::new(),
+            };
+            collector.block(&cell.body);
+            (cell.name.node.as_str(), collector.events)
+        })
+        .collect();
+    let summaries = summarize(&events);
+
+    let mut races: Vec<FlowRace> = Vec::new();
+    for (_, events) in &events {
+        for race in replay(events, &summaries, source) {
+            if !races.contains(&race) {
+                races.push(race);
+            }
+        }
+    }
+    races.sort_by_key(|r| (r.second.span.offset(), r.first.span.offset()));
+    races
+}
+
+/// What a cell body does that matters for races, in evaluation order
+enum Event {
+    Access(String, FlowAccess),
+    /// A synchronous call to another cell
+    Call(String),
+    /// `x ~> cell(..)`, bound to `handle` when the statement names it
+    Spawn {
+        callee: String,
+        span: Span,
+        handle: Option<String>,
+        looped: bool,
+    },
+    /// A read of a local, which awaits the flow it holds
+    Use(String),
+    /// A `scope` or `region` block, which joins the flows started in it when it ends
+    JoinStart,
+    JoinEnd,
+}
+
+struct Collector<'a> {
+    shared: &'a HashSet<&'a str>,
+    cells: &'a HashSet<&'a str>,
+    caller: &'a str,
+    /// Names bound in each enclosing block, innermost last; they shadow strands of the same
+    /// name until their block ends
+    locals: Vec<HashSet<String>>,
+    loops: usize,
+    events: Vec<Event>,
+}
+
+impl Collector<'_> {
+    fn block(&mut self, block: &Block) {
+        self.scoped_block(block, Vec::new());
+    }
+
+    /// Walks `block` in a scope of its own that starts out binding `names`.
+    fn scoped_block(&mut self, block: &Block, names: Vec<String>) {
+        self.locals.push(names.into_iter().collect());
+        for stmt in &block.stmts {
+            self.stmt(stmt);
+        }
+        if let Some(y) = &block.yield_expr {
+            self.expr(y);
+        }
+        self.locals.pop();
+    }
+
+    fn bind(&mut self, names: impl IntoIterator<Item = String>) {
+        self.locals.last_mut().expect("a block scope").extend(names);
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::StrandDef(s) => {
+                if let Some(w) = &s.where_clause {
+                    self.expr(w);
+                }
+                self.spawn_or_expr(&s.expr, Some(&s.name.node));
+                self.bind([s.name.node.clone()]);
+                if let Some(p) = &s.pattern {
+                    self.bind(p.binders().into_iter().map(|b| b.node.clone()));
+                }
+            }
+            Stmt::Assign(a) => {
+                self.spawn_or_expr(&a.expr, Some(&a.target.node));
+                self.access(&a.target.node, a.target.span, true);
+            }
+            Stmt::ExprStmt(e) => self.spawn_or_expr(e, None),
+            Stmt::Prop(p) => self.expr(&p.expr),
+            Stmt::Requires(s) => self.expr(&s.expr),
+            Stmt::Ensures(s) => self.expr(&s.expr),
+            Stmt::Assert(s) => self.expr(&s.expr),
+            Stmt::Assume(s) => self.expr(&s.expr),
+            Stmt::Return(r) => {
+                if let Some(e) = &r.value {
+                    self.expr(e);
+                }
+            }
+            Stmt::MacroCall(m) => {
+                for e in &m.args {
+                    self.expr(e);
+                }
+            }
+            Stmt::If(i) => {
+                self.expr(&i.cond);
+                self.block(&i.then_block);
+                if let Some(e) = &i.else_block {
+                    self.block(e);
+                }
+            }
+            Stmt::Match(m) => {
+                self.expr(&m.scrutinee);
+                for arm in &m.arms {
+                    let binders = arm.pat.binders().into_iter().map(|b| b.node.clone()).collect();
+                    self.scoped_block(&arm.body, binders);
+                }
+            }
+            Stmt::While(w) => {
+                self.expr(&w.cond);
+                self.loops += 1;
+                self.block(&w.body);
+                self.loops -= 1;
+            }
+            Stmt::For(f) => {
+                self.expr(&f.start);
+                self.expr(&f.end);
+                self.loops += 1;
+                self.scoped_block(&f.body, vec![f.var.node.clone()]);
+                self.loops -= 1;
+            }
+            Stmt::Scope(s) => {
+                self.events.push(Event::JoinStart);
+                self.scoped_block(&s.body, s.token.iter().map(|t| t.node.clone()).collect());
+                self.events.push(Event::JoinEnd);
+            }
+            Stmt::Region(r) => {
+                self.events.push(Event::JoinStart);
+                self.block(&r.body);
+                self.events.push(Event::JoinEnd);
+            }
+            Stmt::Defer(d) => self.block(&d.body),
+            Stmt::UnsafeBlock(u) => self.block(&u.body),
+            Stmt::FlowBlock(f) => self.block(&f.body),
+            _ => {}
+        }
+    }
+
+    /// Spawns the flow `expr` starts, after evaluating its input and arguments here.
+    fn spawn_or_expr(&mut self, expr: &Expr, handle: Option<&str>) {
+        if let ExprKind::Flow { left, op: FlowOp::Async, right } = &expr.kind
+            && let ExprKind::Call { callee, args, .. } = &right.kind
+            && matches!(callee.kind, ExprKind::Ident(_))
+            && let Some(callee) = resolve_cell_call(self.caller, &expr_to_callee_name(callee), self.cells)
+        {
+            self.expr(left);
+            for arg in args {
+                self.expr(call_arg_value(arg));
+            }
+            self.events.push(Event::Spawn {
+                callee,
+                span: expr.span,
+                handle: handle.map(str::to_string),
+                looped: self.loops > 0,
+            });
+            return;
+        }
+        self.expr(expr);
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        let mut found = Vec::new();
+        visit_expr(expr, &mut |e| found.push(e));
+        for e in found {
+            match &e.kind {
+                ExprKind::Ident(id) if self.is_shared(&id.node) => self.access(&id.node, id.span, false),
+                ExprKind::Ident(id) => self.events.push(Event::Use(id.node.clone())),
+                ExprKind::Call { callee, args, .. } => {
+                    let name = expr_to_callee_name(callee);
+                    if let Some(callee) = resolve_cell_call(self.caller, &name, self.cells) {
+                        self.events.push(Event::Call(callee));
+                    } else if CapabilityValidator::builtin_access(&name) == Some(ParamAccess::Write)
+                        && let Some(ExprKind::Ident(id)) = args.first().map(|a| &call_arg_value(a).kind)
+                    {
+                        self.access(&id.node, id.span, true);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Whether `name` refers to a shared strand here.
+    fn is_shared(&self, name: &str) -> bool {
+        self.shared.contains(name) && !self.locals.iter().any(|scope| scope.contains(name))
+    }
+
+    /// Records an access to `name` if it is a shared strand here.
+    fn access(&mut self, name: &str, span: Span, write: bool) {
+        if !self.is_shared(name) {
+            return;
+        }
+        let access = FlowAccess {
+            span,
+            write,
+            cell: self.caller.to_string(),
+            spawn: None,
+        };
+        self.events.push(Event::Access(name.to_string(), access));
+    }
+}
+
+/// Every access a call to each cell performs, including through the cells it calls.
+fn summarize(events: &[(&str, Vec<Event>)]) -> HashMap<String, Vec<(String, FlowAccess)>> {
+    let mut out: HashMap<String, Vec<(String, FlowAccess)>> = HashMap::new();
+    let mut calls: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for (cell, events) in events {
+        let direct = out.entry(cell.to_string()).or_default();
+        for event in events {
+            match event {
+                Event::Access(strand, access) => direct.push((strand.clone(), access.clone())),
+                Event::Call(callee) => calls.entry(*cell).or_default().push(callee),
+                _ => {}
+            }
+        }
+    }
+    loop {
+        let mut changed = false;
+        for (caller, callees) in &calls {
+            for callee in callees {
+                let inherited = out.get(*callee).cloned().unwrap_or_default();
+                let into = out.get_mut(*caller).expect("caller");
+                for access in inherited {
+                    if !into.contains(&access) {
+                        into.push(access);
+                        changed = true;
+                    }
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    out
+}
+
+/// A flow spawned by the cell being replayed
+struct Live {
+    handle: Option<String>,
+    /// How many `scope`/`region` blocks were open when it started
+    depth: usize,
+}
+
+/// Replays a cell's events, checking each stretch during which its flows run.
+fn replay(events: &[Event], summaries: &HashMap<String, Vec<(String, FlowAccess)>>, source: &str) -> Vec<FlowRace> {
+    let mut window = Window::default();
+    let mut races = Vec::new();
+    let mut live: Vec<Live> = Vec::new();
+    let mut depth = 0;
+    let mut next_thread = 1;
+    for event in events {
+        match event {
+            Event::Access(strand, access) if !live.is_empty() => window.record(strand, access, 0, source),
+            Event::Call(callee) if !live.is_empty() => {
+                for (strand, access) in summaries.get(callee).into_iter().flatten() {
+                    window.record(strand, access, 0, source);
+                }
+            }
+            Event::Access(..) | Event::Call(_) => {}
+            Event::Spawn { callee, span, handle, looped } => {
+                // Two flows from one loop run at the same time as each other.
+                for _ in 0..if *looped { 2 } else { 1 } {
+                    for (strand, access) in summaries.get(callee).into_iter().flatten() {
+                        let access = FlowAccess {
+                            spawn: Some(*span),
+                            ..access.clone()
+                        };
+                        window.record(strand, &access, next_thread, source);
+                    }
+                    next_thread += 1;
+                    live.push(Live {
+                        handle: handle.clone(),
+                        depth,
+                    });
+                }
+            }
+            Event::Use(name) => live.retain(|l| l.handle.as_deref() != Some(name.as_str())),
+            Event::JoinStart => depth += 1,
+            Event::JoinEnd => {
+                depth -= 1;
+                live.retain(|l| l.depth <= depth);
+            }
+        }
+        if live.is_empty() {
+            races.extend(std::mem::take(&mut window).finish());
+        }
+    }
+    races.extend(window.finish());
+    races
+}
+
+/// Accesses recorded while some flows run
+#[derive(Default)]
+struct Window {
+    detector: RaceDetector,
+    /// (thread, line, column) of each recorded access
+    sites: HashMap<(u32, u32, u32), FlowAccess>,
+}
+
+impl Window {
+    fn record(&mut self, strand: &str, access: &FlowAccess, thread: u32, source: &str) {
+        let (line, col) = line_col(source, access.span.offset());
+        let kind = if access.write { AccessType::Write } else { AccessType::Read };
+        self.detector.record_access(MemoryAccess::new(strand.to_string(), kind, thread, line, col));
+        let site = self.sites.entry((thread, line, col)).or_insert_with(|| access.clone());
+        site.write |= access.write;
+    }
+
+    fn finish(mut self) -> Vec<FlowRace> {
+        if self.sites.is_empty() {
+            return Vec::new();
+        }
+        self.detector.detect_data_races();
+        self.detector
+            .violations()
+            .iter()
+            .filter_map(|v| match v {
+                RaceViolation::DataRace { var_name, thread1, access1, thread2, access2 } => Some(FlowRace {
+                    strand: var_name.clone(),
+                    first: self.sites.get(&(*thread1, access1.0, access1.1))?.clone(),
+                    second: self.sites.get(&(*thread2, access2.0, access2.1))?.clone(),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// 1-based (line, column) of byte `offset` in `source`.
+fn line_col(source: &str, offset: usize) -> (u32, u32) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() as u32 + 1;
+    let col = (before.len() - before.rfind('\n').map_or(0, |i| i + 1)) as u32 + 1;
+    (line, col)
+}
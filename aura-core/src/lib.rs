@@ -2,7 +2,9 @@
 
 mod error;
 mod capability;
+mod consteval;
 mod lower;
+mod models;
 mod sema;
 mod types;
 mod verifier;
@@ -17,9 +19,15 @@ pub mod capability_validator;
 pub mod capability_diagnostics;
 pub mod race_detector;
 pub mod explanation_engine;
+pub mod lint;
+pub mod effects;
+pub mod symbols;
+pub mod termination;
+pub mod flow_races;
 
 pub use error::SemanticError;
 pub use capability::CapabilityGraph;
+pub use consteval::{fold_consts, ConstEnv};
 pub use lower::lower_program;
 pub use sema::Checker;
 pub use types::Type;
@@ -31,7 +39,12 @@ pub use control_flow::{ControlFlowGraph, ControlFlowPath, OwnershipFlowAnalyzer}
 pub use function_signature::{LinearFunctionSignature, LinearParam, LinearReturn, ParamMode, ReturnMode, SignatureValidator, SignatureContext};
 pub use diagnostics::{LinearTypeDiagnostic, DiagnosticFactory, DiagnosticReporter, Severity, Location, CodeSnippet, DiagnosticBuilder};
 pub use capability_enforcement::{CapabilityKind, CapabilityState, CapabilityViolation, CapabilityBinding, CapabilityContext};
-pub use capability_validator::CapabilityValidator;
+pub use capability_validator::{CapabilityValidator, CapabilitySuggestion, ParamAccess, SuggestedEdit};
 pub use capability_diagnostics::{CapabilityDiagnostic, CapabilitySeverity, CapabilityLocation, CapabilityDiagnosticFactory, CapabilityDiagnosticReporter, CodeSnippet as CapabilityCodeSnippet};
-pub use race_detector::{RaceDetector, RaceViolation, MemoryAccess, AccessType, SynchronizationInfo, LockInfo};
-pub use explanation_engine::{ExplanationEngine, Explanation, ProofStep, Counterexample, VariableBinding};
+pub use race_detector::{RaceDetector, RaceViolation, MemoryAccess, AccessType, SynchronizationInfo, LockInfo, LockOrderEdge};
+pub use explanation_engine::{ExplanationEngine, Explanation, ProofStep, Counterexample, VariableBinding, MoveViolation};
+pub use lint::{lint_program, Lint};
+pub use effects::{check_declared_effects, infer_effects, CellEffects, Effect, EffectSource, EffectViolation};
+pub use symbols::{DuplicateDefinition, Symbol, SymbolKind, SymbolTable};
+pub use termination::{check_termination, Measure};
+pub use flow_races::{detect_flow_races, FlowAccess, FlowRace};
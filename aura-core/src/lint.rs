@@ -0,0 +1,386 @@
+//! Lint pass: warnings for code that type-checks but has no effect.
+//!
+//! Reports `val`s and parameters that are never read, imports nothing refers to, and
+//! statements after one whose every branch returns or leaves the loop. Names starting with
+//! `_` are never reported. Capability parameters are left to capability inference, which
+//! already reports the unused ones.
+
+use std::collections::HashSet;
+
+use aura_ast::{span_between, Block, CellDef, Expr, ExprKind, InterpPart, Program, Span, Stmt, StrandDef};
+
+use crate::capability_validator::CapabilityValidator;
+use crate::diagnostics::{DiagnosticFactory, LinearTypeDiagnostic};
+use crate::sema::call_arg_value;
+use crate::types::Type;
+
+/// A lint warning and the source it covers
+#[derive(Clone, Debug)]
+pub struct Lint {
+    pub span: Span,
+    pub diagnostic: LinearTypeDiagnostic,
+}
+
+/// Lints `program`. Only code inside `source` (the first `source.len()` bytes of the
+/// program's span space) is reported; `file` names it in the diagnostics.
+pub fn lint_program(program: &Program, file: &str, source: &str) -> Vec<Lint> {
+    let mut linter = Linter {
+        file,
+        source,
+        scopes: vec![Vec::new()],
+        names: HashSet::new(),
+        called: HashSet::new(),
+        lints: Vec::new(),
+    };
+
+    // Top-level vals are visible to every cell, wherever they are declared.
+    for stmt in &program.stmts {
+        if let Stmt::StrandDef(sd) = stmt {
+            linter.bind_strand(sd);
+        }
+    }
+    linter.stmts(&program.stmts, None, true);
+    linter.pop_scope();
+
+    // A cell defined outside `source` and called by its bare name comes from an import,
+    // though not one we can tell apart: keep every import then.
+    let foreign_call = program.stmts.iter().any(|s| {
+        let name = match s {
+            Stmt::CellDef(c) => &c.name,
+            Stmt::ExternCell(e) => &e.name,
+            _ => return false,
+        };
+        name.span.offset() >= source.len() && linter.called.contains(&name.node)
+    });
+    if !foreign_call {
+        for stmt in &program.stmts {
+            let Stmt::Import(import) = stmt else { continue };
+            let Some(last) = import.path.last() else { continue };
+            if names_namespace(program, &import.path) && !linter.names.contains(&last.node) {
+                let path: Vec<&str> = import.path.iter().map(|p| p.node.as_str()).collect();
+                linter.report(import.span, |file, line, col| {
+                    DiagnosticFactory::unused_import(file, line, col, &path.join("::"))
+                });
+            }
+        }
+    }
+
+    linter.lints.sort_by_key(|l| l.span.offset());
+    linter.lints
+}
+
+/// A warning at `span`, located in `source` for its diagnostic; none if `span` lies outside it.
+pub(crate) fn lint_at(
+    file: &str,
+    source: &str,
+    span: Span,
+    diagnostic: impl FnOnce(String, u32, u32) -> LinearTypeDiagnostic,
+) -> Option<Lint> {
+    if span.offset() + span.len() > source.len() {
+        return None;
+    }
+    let before = &source[..span.offset()];
+    let line = before.matches('\n').count() as u32 + 1;
+    let col = (before.len() - before.rfind('\n').map_or(0, |i| i + 1)) as u32 + 1;
+    Some(Lint {
+        span,
+        diagnostic: diagnostic(file.to_string(), line, col),
+    })
+}
+
+struct Binding {
+    name: String,
+    span: Span,
+    param: bool,
+    // Pattern and loop variables are bound for shadowing but never reported.
+    reported: bool,
+    used: bool,
+}
+
+struct Linter<'a> {
+    file: &'a str,
+    source: &'a str,
+    scopes: Vec<Vec<Binding>>,
+    // Names read that no binding holds: module namespaces, cells and the like.
+    names: HashSet<String>,
+    // Names called directly, as in `f(x)`.
+    called: HashSet<String>,
+    lints: Vec<Lint>,
+}
+
+impl Linter<'_> {
+    fn report(&mut self, span: Span, diagnostic: impl FnOnce(String, u32, u32) -> LinearTypeDiagnostic) {
+        self.lints.extend(lint_at(self.file, self.source, span, diagnostic));
+    }
+
+    fn push_scope(&mut self) {
+        self.scopes.push(Vec::new());
+    }
+
+    fn pop_scope(&mut self) {
+        let Some(scope) = self.scopes.pop() else { return };
+        for b in scope {
+            if b.reported && !b.used && !b.name.starts_with('_') {
+                self.report(b.span, |file, line, col| {
+                    DiagnosticFactory::unused_binding(file, line, col, &b.name, b.param)
+                });
+            }
+        }
+    }
+
+    fn bind(&mut self, name: &aura_ast::Ident, param: bool, reported: bool) {
+        let scope = self.scopes.last_mut().expect("scope stack");
+        scope.push(Binding {
+            name: name.node.clone(),
+            span: name.span,
+            param,
+            reported,
+            used: false,
+        });
+    }
+
+    fn bind_strand(&mut self, sd: &StrandDef) {
+        match &sd.pattern {
+            Some(pat) => {
+                for name in pat.binders() {
+                    self.bind(name, false, true);
+                }
+            }
+            None => self.bind(&sd.name, false, true),
+        }
+    }
+
+    fn read(&mut self, name: &str) {
+        let binding = self.scopes.iter_mut().rev().find_map(|s| s.iter_mut().rev().find(|b| b.name == name));
+        match binding {
+            Some(b) => b.used = true,
+            None => {
+                self.names.insert(name.to_string());
+            }
+        }
+    }
+
+    fn cell(&mut self, cell: &CellDef) {
+        self.push_scope();
+        for p in &cell.params {
+            let capability = CapabilityValidator::is_capability_type(&Type::Named(p.ty.name.node.clone()));
+            self.bind(&p.name, true, !capability);
+        }
+        if let Some(dec) = &cell.decreases {
+            self.expr(dec);
+        }
+        self.block(&cell.body);
+        self.pop_scope();
+    }
+
+    fn block(&mut self, block: &Block) {
+        self.push_scope();
+        self.stmts(&block.stmts, block.yield_expr.as_ref(), false);
+        if let Some(y) = &block.yield_expr {
+            self.expr(y);
+        }
+        self.pop_scope();
+    }
+
+    /// Walks the statements of one block; top-level vals are already bound.
+    fn stmts(&mut self, stmts: &[Stmt], yield_expr: Option<&Expr>, top_level: bool) {
+        if let Some(i) = stmts.iter().position(diverges) {
+            let rest: Vec<Span> = stmts[i + 1..].iter().map(Stmt::span).chain(yield_expr.map(|y| y.span)).collect();
+            if let (Some(first), Some(last)) = (rest.first(), rest.last()) {
+                let span = span_between(first.offset(), last.offset() + last.len());
+                self.report(span, DiagnosticFactory::unreachable_code);
+            }
+        }
+        for stmt in stmts {
+            match stmt {
+                Stmt::StrandDef(sd) if top_level => self.strand_exprs(sd),
+                Stmt::StrandDef(sd) => {
+                    self.strand_exprs(sd);
+                    self.bind_strand(sd);
+                }
+                other => self.stmt(other),
+            }
+        }
+    }
+
+    fn strand_exprs(&mut self, sd: &StrandDef) {
+        self.expr(&sd.expr);
+        if let Some(w) = &sd.where_clause {
+            self.expr(w);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::CellDef(c) => self.cell(c),
+            Stmt::StrandDef(sd) => {
+                self.strand_exprs(sd);
+                self.bind_strand(sd);
+            }
+            // Assigning to a binding does not read it.
+            Stmt::Assign(a) => self.expr(&a.expr),
+            Stmt::Prop(p) => self.expr(&p.expr),
+            Stmt::If(i) => {
+                self.expr(&i.cond);
+                self.block(&i.then_block);
+                if let Some(e) = &i.else_block {
+                    self.block(e);
+                }
+            }
+            Stmt::Match(m) => {
+                self.expr(&m.scrutinee);
+                for arm in &m.arms {
+                    self.push_scope();
+                    for name in arm.pat.binders() {
+                        self.bind(name, false, false);
+                    }
+                    self.block(&arm.body);
+                    self.pop_scope();
+                }
+            }
+            Stmt::While(w) => {
+                self.expr(&w.cond);
+                for e in w.invariant.iter().chain(&w.decreases) {
+                    self.expr(e);
+                }
+                self.block(&w.body);
+            }
+            Stmt::For(l) => {
+                self.expr(&l.start);
+                self.expr(&l.end);
+                self.push_scope();
+                self.bind(&l.var, false, false);
+                if let Some(e) = &l.invariant {
+                    self.expr(e);
+                }
+                self.block(&l.body);
+                self.pop_scope();
+            }
+            Stmt::Return(r) => {
+                if let Some(e) = &r.value {
+                    self.expr(e);
+                }
+            }
+            Stmt::Requires(s) => self.expr(&s.expr),
+            Stmt::Ensures(s) => self.expr(&s.expr),
+            Stmt::Assert(s) => self.expr(&s.expr),
+            Stmt::Assume(s) => self.expr(&s.expr),
+            Stmt::MacroCall(m) => {
+                for e in &m.args {
+                    self.expr(e);
+                }
+            }
+            Stmt::ExprStmt(e) => self.expr(e),
+            Stmt::Defer(d) => self.block(&d.body),
+            Stmt::UnsafeBlock(u) => self.block(&u.body),
+            Stmt::Scope(s) => self.block(&s.body),
+            Stmt::Region(r) => self.block(&r.body),
+            Stmt::Layout(l) => self.block(&l.body),
+            Stmt::Render(r) => self.block(&r.body),
+            Stmt::FlowBlock(b) => self.block(&b.body),
+            Stmt::Import(_)
+            | Stmt::MacroDef(_)
+            | Stmt::TypeAlias(_)
+            | Stmt::TraitDef(_)
+            | Stmt::RecordDef(_)
+            | Stmt::EnumDef(_)
+            | Stmt::ExternCell(_)
+            | Stmt::Break(_)
+            | Stmt::Continue(_) => {}
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Ident(id) => self.read(&id.node),
+            ExprKind::IntLit(_) | ExprKind::FloatLit(_) | ExprKind::StringLit(_) => {}
+            ExprKind::InterpolatedString(parts) => {
+                for part in parts {
+                    if let InterpPart::Expr(e) = part {
+                        self.expr(e);
+                    }
+                }
+            }
+            ExprKind::StyleLit { fields } | ExprKind::RecordLit { fields, .. } => {
+                for (_, v) in fields {
+                    self.expr(v);
+                }
+            }
+            ExprKind::Unary { expr, .. } | ExprKind::Borrow { expr, .. } => self.expr(expr),
+            ExprKind::Binary { left, right, .. } | ExprKind::Flow { left, right, .. } => {
+                self.expr(left);
+                self.expr(right);
+            }
+            ExprKind::Member { base, .. } | ExprKind::TupleIndex { base, .. } => self.expr(base),
+            ExprKind::Tuple(elems) | ExprKind::ListLit(elems) => {
+                for e in elems {
+                    self.expr(e);
+                }
+            }
+            ExprKind::MapLit(entries) => {
+                for (k, v) in entries {
+                    self.expr(k);
+                    self.expr(v);
+                }
+            }
+            ExprKind::Index { base, index } => {
+                self.expr(base);
+                self.expr(index);
+            }
+            ExprKind::Call { callee, args, trailing } => {
+                if let ExprKind::Ident(id) = &callee.kind {
+                    self.called.insert(id.node.clone());
+                }
+                self.expr(callee);
+                for a in args {
+                    self.expr(call_arg_value(a));
+                }
+                if let Some(b) = trailing {
+                    self.block(b);
+                }
+            }
+            ExprKind::Lambda { body, .. } => self.block(body),
+            ExprKind::ForAll { binders, body } | ExprKind::Exists { binders, body } => {
+                self.push_scope();
+                for b in binders {
+                    self.bind(&b.name, false, false);
+                }
+                self.expr(body);
+                self.pop_scope();
+            }
+        }
+    }
+}
+
+/// Whether an import only brings a namespace into scope: a stdlib module, or a module whose
+/// cells are qualified with its name. Plugin imports switch their Nexus plugin on, and other
+/// imports may only bring in types, so those are never reported.
+fn names_namespace(program: &Program, path: &[aura_ast::Ident]) -> bool {
+    let segments: Vec<&str> = path.iter().map(|p| p.node.as_str()).collect();
+    match segments.as_slice() {
+        ["aura", "ai" | "iot" | "lumina"] => false,
+        ["aura" | "std", _, ..] => true,
+        [.., last] => {
+            let prefix = format!("{last}.");
+            program
+                .stmts
+                .iter()
+                .any(|s| matches!(s, Stmt::CellDef(c) if c.name.node.starts_with(&prefix)))
+        }
+        [] => false,
+    }
+}
+
+/// Whether every path through `stmt` returns or leaves the enclosing loop.
+fn diverges(stmt: &Stmt) -> bool {
+    let block_diverges = |b: &Block| b.stmts.iter().any(diverges);
+    match stmt {
+        Stmt::Return(_) | Stmt::Break(_) | Stmt::Continue(_) => true,
+        Stmt::If(i) => block_diverges(&i.then_block) && i.else_block.as_ref().is_some_and(block_diverges),
+        Stmt::Match(m) => !m.arms.is_empty() && m.arms.iter().all(|arm| block_diverges(&arm.body)),
+        Stmt::UnsafeBlock(u) => block_diverges(&u.body),
+        Stmt::Scope(s) => block_diverges(&s.body),
+        Stmt::Region(r) => block_diverges(&r.body),
+        _ => false,
+    }
+}
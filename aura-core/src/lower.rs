@@ -1,36 +1,63 @@
 #![forbid(unsafe_code)]
 
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use aura_ast::{
-    AssignStmt, BinOp as AstBinOp, Block, CellDef, Expr, ExprKind, FlowBlock, FlowOp, IfStmt,
-    Ident, LayoutBlock, MatchStmt, Pattern, Program, RenderBlock, Stmt, StrandDef, TypeRef,
+    AssignStmt, BinOp as AstBinOp, Block, CellDef, DeferStmt, Expr, ExprKind, FlowBlock, FlowOp, ForStmt, IfStmt,
+    Ident, InterpPart, LayoutBlock, MatchStmt, Pattern, Program, RegionBlock, RenderBlock, ScopeBlock, Stmt, StrandDef,
     UnaryOp as AstUnaryOp, UnsafeBlock, WhileStmt,
 };
 use aura_ir::{
-    BasicBlock, BinOp, BlockId, CallConv, ExecutionHint, ExternFnSig, FlowKind, FunctionIR, IdGen,
-    Inst, InstKind, ModuleIR, Param, RValue, Terminator, Type, UnaryOp, ValueId,
+    BasicBlock, BinOp, BlockId, CallConv, DebugScope, DebugValue, DebugVarDecl, ExecutionHint,
+    ExternFnSig, FlowKind, FunctionDebug, FunctionIR, IdGen, Inst, InstKind, ModuleIR, Param, RValue,
+    Terminator, Type, UnaryOp, ValueId,
 };
 
+use crate::consteval::fold_consts;
+use crate::sema::GenericInstance;
 use crate::{Checker, SemanticError};
 
 fn lower_sema_type_to_ir(ty: &crate::types::Type) -> Type {
     match ty {
         crate::types::Type::Unit => Type::Unit,
         crate::types::Type::Bool => Type::Bool,
+        crate::types::Type::U8 => Type::U8,
+        crate::types::Type::U16 => Type::U16,
         crate::types::Type::U32 => Type::U32,
+        crate::types::Type::U64 => Type::U64,
+        crate::types::Type::I32 => Type::I32,
+        crate::types::Type::I64 => Type::I64,
+        crate::types::Type::F64 => Type::F64,
         crate::types::Type::String => Type::String,
         crate::types::Type::Style => Type::Opaque("Style".to_string()),
         crate::types::Type::Model => Type::Opaque("Model".to_string()),
-        crate::types::Type::Tensor { .. } => Type::Tensor,
+        crate::types::Type::Tensor { .. } | crate::types::Type::List(_) => Type::Tensor,
         crate::types::Type::ConstrainedRange { base, .. } => lower_sema_type_to_ir(base),
         crate::types::Type::Named(n) => Type::Opaque(n.clone()),
         crate::types::Type::Applied { name, .. } => Type::Opaque(name.clone()),
+        crate::types::Type::Tuple(elems) => Type::Tuple(elems.iter().map(lower_sema_type_to_ir).collect()),
+        crate::types::Type::Map(..) => Type::Opaque("Map".to_string()),
+        crate::types::Type::Closure(_) => Type::Closure,
         crate::types::Type::Unknown => Type::U32,
     }
 }
 
+/// Payload patterns of one enum match alternative, the block it selects, and its span.
+type CtorAlt<'p> = (&'p [Pattern], BlockId, aura_ast::Span);
+
+/// Whether `ty` refers to the handle `Opaque(name)` anywhere inside it.
+fn type_mentions_opaque(ty: &Type, name: &str) -> bool {
+    match ty {
+        Type::Opaque(n) => n == name,
+        Type::Tuple(elems) | Type::Record { fields: elems, .. } => elems.iter().any(|t| type_mentions_opaque(t, name)),
+        Type::Enum { variants, .. } => variants.iter().flatten().any(|t| type_mentions_opaque(t, name)),
+        _ => false,
+    }
+}
+
 pub fn lower_program(program: &Program) -> Result<ModuleIR, SemanticError> {
+    let folded = fold_consts(program)?;
+    let program = &*folded;
     let mut checker = Checker::new();
     // Prototype: range safety proofs are handled by `aura-verify` (Z3).
     checker.set_defer_range_proofs(true);
@@ -43,10 +70,25 @@ pub fn lower_program(program: &Program) -> Result<ModuleIR, SemanticError> {
         locals: HashMap::new(),
         blocks: Vec::new(),
         current: None,
+        instance: None,
+        namespace: None,
+        loops: Vec::new(),
+        ret_ty: Type::Unit,
+        bare_returns: BTreeSet::new(),
+        defers: Vec::new(),
+        debug: FunctionDebug::default(),
+        debug_scope: None,
+        debug_decls: HashMap::new(),
+        fn_name: String::new(),
+        captured: BTreeSet::new(),
+        closures: Vec::new(),
+        next_lambda: 0,
     };
 
     for stmt in &program.stmts {
         match stmt {
+            // Generic cells are emitted only as their monomorphized instances.
+            Stmt::CellDef(cell) if !cell.type_params.is_empty() => {}
             Stmt::CellDef(cell) => {
                 let f = lower.lower_cell(cell)?;
                 module.functions.insert(f.name.clone(), f);
@@ -62,8 +104,11 @@ pub fn lower_program(program: &Program) -> Result<ModuleIR, SemanticError> {
                     CallConv::C
                 };
                 let sig = ExternFnSig {
-                    params: ext.params.iter().map(|p| lower_type(&p.ty)).collect(),
-                    ret: lower_type(&ext.ret),
+                    params: lower.sig_param_types(&ext.name.node),
+                    ret: checker
+                        .function_ret_type(&ext.name.node)
+                        .map(|t| lower.ir_type(t))
+                        .unwrap_or(Type::Unit),
                     call_conv,
                 };
                 module.externs.insert(ext.name.node.clone(), sig);
@@ -72,6 +117,15 @@ pub fn lower_program(program: &Program) -> Result<ModuleIR, SemanticError> {
         }
     }
 
+    for inst in checker.generic_instances() {
+        let f = lower.lower_generic_instance(inst)?;
+        module.functions.insert(f.name.clone(), f);
+    }
+
+    for f in lower.closures.drain(..) {
+        module.functions.insert(f.name.clone(), f);
+    }
+
     Ok(module)
 }
 
@@ -82,12 +136,178 @@ struct Lowerer<'c> {
 
     blocks: Vec<BasicBlock>,
     current: Option<usize>,
+    // Mangled name of the generic instance being lowered, if any.
+    instance: Option<String>,
+    // Module namespace of the cell being lowered (`net` for `cell net::get`).
+    namespace: Option<String>,
+    // Enclosing loops, innermost last.
+    loops: Vec<LoopCtx>,
+    // Result type of the function being lowered.
+    ret_ty: Type,
+    // Blocks ended by a valueless `return`, whose terminator looks like an unset one.
+    bare_returns: BTreeSet<BlockId>,
+    // `defer` bodies (and `scope` and `region` ends) registered in each enclosing block, innermost last.
+    defers: Vec<Vec<Deferred>>,
+    // Source variables and scopes of the function being lowered, for debug info.
+    debug: FunctionDebug,
+    // Innermost lexical scope (`None` is the function body itself).
+    debug_scope: Option<usize>,
+    // Visible source variables, by name, as indices into `debug.vars`.
+    debug_decls: HashMap<String, usize>,
+    // Name of the function being lowered; its lambdas are named after it.
+    fn_name: String,
+    // Params of a closure body holding captured values, which it may not assign.
+    captured: BTreeSet<ValueId>,
+    // Closure bodies lowered so far, added to the module at the end.
+    closures: Vec<FunctionIR>,
+    next_lambda: u32,
+}
+
+/// What runs when control leaves a block.
+#[derive(Clone)]
+enum Deferred {
+    /// A `defer` body.
+    Body(Block),
+    /// The end of a `scope` or `region`: `scope.close` of its token or `region.exit` of its
+    /// mark. Codegen joins the block's tasks before the call.
+    Close {
+        callee: &'static str,
+        handle: ValueId,
+        span: aura_ast::Span,
+    },
+}
+
+/// Per-function lowering state, set aside while a lambda body is lowered.
+struct FunctionState {
+    fn_name: String,
+    locals: HashMap<String, ValueId>,
+    blocks: Vec<BasicBlock>,
+    current: Option<usize>,
+    loops: Vec<LoopCtx>,
+    ret_ty: Type,
+    bare_returns: BTreeSet<BlockId>,
+    defers: Vec<Vec<Deferred>>,
+    debug: FunctionDebug,
+    debug_scope: Option<usize>,
+    debug_decls: HashMap<String, usize>,
+    captured: BTreeSet<ValueId>,
+}
+
+/// Jump targets of a loop being lowered, plus the locals at each `break`/`continue`
+/// so the targets can merge them with phis.
+struct LoopCtx {
+    continue_bb: BlockId,
+    exit_bb: BlockId,
+    // Number of `defer` scopes outside the loop; jumps run the ones inside it.
+    defer_depth: usize,
+    continues: Vec<(BlockId, HashMap<String, ValueId>)>,
+    breaks: Vec<(BlockId, HashMap<String, ValueId>)>,
 }
 
 impl<'c> Lowerer<'c> {
     fn lower_cell(&mut self, cell: &CellDef) -> Result<FunctionIR, SemanticError> {
+        let params = self.sig_param_types(&cell.name.node);
+        self.lower_cell_as(cell, &cell.name.node, params)
+    }
+
+    /// IR parameter types of a cell or extern cell, as sema resolved them.
+    fn sig_param_types(&self, name: &str) -> Vec<Type> {
+        self.checker
+            .function_param_types(name)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|t| self.ir_type(t))
+            .collect()
+    }
+
+    /// Lowers a checked type, expanding records and enums into their field types.
+    fn ir_type(&self, ty: &crate::types::Type) -> Type {
+        self.ir_type_in(ty, &mut Vec::new())
+    }
+
+    fn ir_type_in(&self, ty: &crate::types::Type, expanding: &mut Vec<String>) -> Type {
+        use crate::types::Type as T;
+        match ty {
+            T::ConstrainedRange { base, .. } => self.ir_type_in(base, expanding),
+            T::Tuple(elems) => Type::Tuple(elems.iter().map(|t| self.ir_type_in(t, expanding)).collect()),
+            T::Named(_) | T::Applied { .. } => {
+                let record = self.checker.record_fields(ty);
+                let variants = self.checker.enum_variants(ty);
+                if record.is_none() && variants.is_none() {
+                    return lower_sema_type_to_ir(ty);
+                }
+                let name = ty.display();
+                // Nothing can hold itself by value; leave the cycle as a handle.
+                if expanding.contains(&name) {
+                    return Type::Opaque(name);
+                }
+                expanding.push(name.clone());
+                let out = match (record, variants) {
+                    (Some(fields), _) => Type::Record {
+                        name,
+                        fields: fields.iter().map(|(_, t)| self.ir_type_in(t, expanding)).collect(),
+                    },
+                    (None, variants) => Type::Enum {
+                        name,
+                        variants: variants
+                            .unwrap_or_default()
+                            .iter()
+                            .map(|(_, fields)| fields.iter().map(|t| self.ir_type_in(t, expanding)).collect())
+                            .collect(),
+                    },
+                };
+                expanding.pop();
+                out
+            }
+            other => lower_sema_type_to_ir(other),
+        }
+    }
+
+    /// IR type of the enum built by the constructor call at `span`. Enums that contain
+    /// themselves have no by-value layout, so they are rejected here.
+    fn enum_ctor_ir_type(&self, span: aura_ast::Span) -> Result<Type, SemanticError> {
+        let ty = self
+            .checker
+            .enum_ctor_type(self.instance.as_deref(), span)
+            .ok_or_else(|| SemanticError {
+                message: "lowering: enum constructor was not type-checked".to_string(),
+                span,
+            })?;
+        let ir = self.ir_type(ty);
+        let Type::Enum { name, variants } = &ir else {
+            return Err(SemanticError {
+                message: format!("lowering: '{}' is not an enum", ty.display()),
+                span,
+            });
+        };
+        if variants.iter().flatten().any(|t| type_mentions_opaque(t, name)) {
+            return Err(SemanticError {
+                message: format!("lowering: recursive enum '{name}' cannot be stored by value"),
+                span,
+            });
+        }
+        Ok(ir)
+    }
+
+    fn lower_generic_instance(&mut self, inst: &GenericInstance) -> Result<FunctionIR, SemanticError> {
+        self.instance = Some(inst.name.clone());
+        let params = inst.params.iter().map(|t| self.ir_type(t)).collect();
+        let f = self.lower_cell_as(&inst.cell, &inst.name, params);
+        self.instance = None;
+        f
+    }
+
+    fn lower_cell_as(
+        &mut self,
+        cell: &CellDef,
+        name: &str,
+        param_tys: Vec<Type>,
+    ) -> Result<FunctionIR, SemanticError> {
+        self.namespace = cell.name.node.rsplit_once('.').map(|(ns, _)| ns.to_string());
+        self.fn_name = name.to_string();
         self.locals.clear();
         self.blocks.clear();
+        self.reset_debug();
         let entry = self.id.fresh_block();
         let hint = match cell.flow {
             Some(FlowOp::Async) => ExecutionHint::Parallel,
@@ -96,39 +316,45 @@ impl<'c> Lowerer<'c> {
         self.push_block(entry, cell.span, hint);
 
         let mut params = Vec::new();
-        for p in &cell.params {
+        for (i, (p, ty)) in cell.params.iter().zip(param_tys).enumerate() {
             let v = self.id.fresh_value();
             params.push(Param {
                 name: p.name.node.clone(),
-                ty: lower_type(&p.ty),
+                ty,
                 span: p.span,
                 value: v,
             });
-            self.locals.insert(p.name.node.clone(), v);
+            self.declare_local(&p.name.node, v, p.span, Some(i as u32 + 1));
         }
 
-        self.lower_block(&cell.body)?;
         let ret = self
             .checker
-            .function_ret_type(&cell.name.node)
-            .map(lower_sema_type_to_ir)
+            .function_ret_type(name)
+            .map(|t| self.ir_type(t))
             .unwrap_or(Type::Unit);
+        self.ret_ty = ret.clone();
+        self.lower_block_body(&cell.body)?;
 
         let blocks = std::mem::take(&mut self.blocks);
 
         Ok(FunctionIR {
-            name: cell.name.node.clone(),
+            name: name.to_string(),
             span: cell.span,
             params,
             ret,
             blocks,
             entry,
+            debug: std::mem::take(&mut self.debug),
+            is_closure: false,
         })
     }
 
     fn lower_flow_block(&mut self, fb: &FlowBlock) -> Result<FunctionIR, SemanticError> {
+        self.namespace = None;
+        self.fn_name = fb.name.node.clone();
         self.locals.clear();
         self.blocks.clear();
+        self.reset_debug();
         let entry = self.id.fresh_block();
         let hint = match fb.flow {
             FlowOp::Async => ExecutionHint::Parallel,
@@ -136,7 +362,7 @@ impl<'c> Lowerer<'c> {
         };
         self.push_block(entry, fb.span, hint);
 
-        self.lower_block(&fb.body)?;
+        self.lower_block_body(&fb.body)?;
         let blocks = std::mem::take(&mut self.blocks);
         Ok(FunctionIR {
             name: fb.name.node.clone(),
@@ -145,16 +371,239 @@ impl<'c> Lowerer<'c> {
             ret: self
                 .checker
                 .function_ret_type(&fb.name.node)
-                .map(lower_sema_type_to_ir)
+                .map(|t| self.ir_type(t))
                 .unwrap_or(Type::Unit),
             blocks,
             entry,
+            debug: std::mem::take(&mut self.debug),
+            is_closure: false,
         })
     }
 
+    /// Closure-converts a lambda: its body becomes a function whose params are the captured
+    /// values, and the lambda itself a `MakeClosure` over the values they have here.
+    fn lower_lambda(&mut self, span: aura_ast::Span, op: FlowOp, body: &Block) -> Result<ValueId, SemanticError> {
+        let info = self
+            .checker
+            .lambda_info(self.instance.as_deref(), span)
+            .cloned()
+            .ok_or_else(|| SemanticError {
+                message: "lowering: lambda was not type-checked".to_string(),
+                span,
+            })?;
+        let mut captures = Vec::with_capacity(info.captures.len());
+        for (name, _) in &info.captures {
+            let v = self.locals.get(name).copied().ok_or_else(|| SemanticError {
+                message: format!("lowering: lambda captures unknown local '{name}'"),
+                span,
+            })?;
+            captures.push(v);
+        }
+
+        let name = format!("{}$lambda{}", self.fn_name, self.next_lambda);
+        self.next_lambda += 1;
+        let outer = self.take_function_state(&name);
+
+        let entry = self.id.fresh_block();
+        let hint = match op {
+            FlowOp::Async => ExecutionHint::Parallel,
+            FlowOp::Sync => ExecutionHint::Sequential,
+        };
+        self.push_block(entry, body.span, hint);
+        let mut params = Vec::with_capacity(info.captures.len());
+        for (cap_name, ty) in &info.captures {
+            let v = self.id.fresh_value();
+            params.push(Param {
+                name: cap_name.clone(),
+                ty: self.ir_type(ty),
+                span,
+                value: v,
+            });
+            self.declare_local(cap_name, v, span, None);
+            self.captured.insert(v);
+        }
+        let ret = self.ir_type(&info.ret);
+        self.ret_ty = ret.clone();
+        let res = self.lower_block_body(body);
+        let blocks = std::mem::take(&mut self.blocks);
+        let debug = std::mem::take(&mut self.debug);
+        self.restore_function_state(outer);
+        res?;
+
+        self.closures.push(FunctionIR {
+            name: name.clone(),
+            span,
+            params,
+            ret,
+            blocks,
+            entry,
+            debug,
+            is_closure: true,
+        });
+        let v = self.id.fresh_value();
+        self.push_inst(Inst {
+            span,
+            dest: Some(v),
+            kind: InstKind::MakeClosure { func: name, captures },
+        });
+        Ok(v)
+    }
+
+    /// Builds a record from a literal. Fields are evaluated in source order, then any
+    /// defaults, and stored in declaration order.
+    fn lower_record_lit(
+        &mut self,
+        span: aura_ast::Span,
+        name: &Ident,
+        fields: &[(Ident, Expr)],
+    ) -> Result<ValueId, SemanticError> {
+        let checker = self.checker;
+        let (Some(ty), Some(def)) = (
+            checker.record_literal_type(self.instance.as_deref(), span),
+            checker.record_def(&name.node),
+        ) else {
+            return Err(SemanticError {
+                message: format!("lowering: record literal '{}' was not type-checked", name.node),
+                span,
+            });
+        };
+
+        let mut given = HashMap::new();
+        for (k, e) in fields {
+            given.insert(k.node.as_str(), self.lower_expr(e)?);
+        }
+        let mut values = Vec::with_capacity(def.fields.len());
+        for f in &def.fields {
+            let v = match (given.get(f.name.node.as_str()), &f.default) {
+                (Some(v), _) => *v,
+                (None, Some(default)) => self.lower_expr(default)?,
+                (None, None) => {
+                    return Err(SemanticError {
+                        message: format!("lowering: missing field '{}' for record '{}'", f.name.node, name.node),
+                        span,
+                    });
+                }
+            };
+            values.push(v);
+        }
+
+        let v = self.id.fresh_value();
+        self.push_inst(Inst {
+            span,
+            dest: Some(v),
+            kind: InstKind::MakeRecord {
+                ty: self.ir_type(ty),
+                fields: values,
+            },
+        });
+        Ok(v)
+    }
+
+    /// Sets aside the state of the function being lowered and starts `name` from scratch.
+    fn take_function_state(&mut self, name: &str) -> FunctionState {
+        FunctionState {
+            fn_name: std::mem::replace(&mut self.fn_name, name.to_string()),
+            locals: std::mem::take(&mut self.locals),
+            blocks: std::mem::take(&mut self.blocks),
+            current: self.current.take(),
+            loops: std::mem::take(&mut self.loops),
+            ret_ty: std::mem::replace(&mut self.ret_ty, Type::Unit),
+            bare_returns: std::mem::take(&mut self.bare_returns),
+            defers: std::mem::take(&mut self.defers),
+            debug: std::mem::take(&mut self.debug),
+            debug_scope: self.debug_scope.take(),
+            debug_decls: std::mem::take(&mut self.debug_decls),
+            captured: std::mem::take(&mut self.captured),
+        }
+    }
+
+    fn restore_function_state(&mut self, state: FunctionState) {
+        self.fn_name = state.fn_name;
+        self.locals = state.locals;
+        self.blocks = state.blocks;
+        self.current = state.current;
+        self.loops = state.loops;
+        self.ret_ty = state.ret_ty;
+        self.bare_returns = state.bare_returns;
+        self.defers = state.defers;
+        self.debug = state.debug;
+        self.debug_scope = state.debug_scope;
+        self.debug_decls = state.debug_decls;
+        self.captured = state.captured;
+    }
+
+    fn reset_debug(&mut self) {
+        self.debug = FunctionDebug::default();
+        self.debug_scope = None;
+        self.debug_decls.clear();
+    }
+
+    /// Opens a lexical scope for debug info; returns what `leave_debug_scope` restores.
+    fn enter_debug_scope(&mut self, span: aura_ast::Span) -> (Option<usize>, HashMap<String, usize>) {
+        let saved = (self.debug_scope, self.debug_decls.clone());
+        self.debug.scopes.push(DebugScope {
+            span,
+            parent: self.debug_scope,
+        });
+        self.debug_scope = Some(self.debug.scopes.len() - 1);
+        saved
+    }
+
+    fn leave_debug_scope(&mut self, saved: (Option<usize>, HashMap<String, usize>)) {
+        (self.debug_scope, self.debug_decls) = saved;
+    }
+
+    /// Binds a newly declared source variable (parameter, strand or pattern binder).
+    fn declare_local(&mut self, name: &str, v: ValueId, span: aura_ast::Span, arg: Option<u32>) {
+        self.locals.insert(name.to_string(), v);
+        let var = self.debug.vars.len();
+        self.debug.vars.push(DebugVarDecl {
+            name: name.to_string(),
+            scope: self.debug_scope,
+            span,
+            arg,
+        });
+        self.debug_decls.insert(name.to_string(), var);
+        self.record_debug_value(var, v);
+    }
+
+    /// Moves an existing local to a new SSA value (an assignment or a merge phi).
+    fn rebind_local(&mut self, name: &str, v: ValueId) {
+        let prev = self.locals.insert(name.to_string(), v);
+        if prev != Some(v)
+            && let Some(&var) = self.debug_decls.get(name)
+        {
+            self.record_debug_value(var, v);
+        }
+    }
+
+    fn record_debug_value(&mut self, var: usize, value: ValueId) {
+        if let Some(block) = self.current_block_id() {
+            let at = self.current_block_mut().insts.len();
+            self.debug.values.push(DebugValue { var, value, block, at });
+        }
+    }
+
+    /// Lowers a nested block in a lexical scope of its own.
     fn lower_block(&mut self, block_ast: &Block) -> Result<(), SemanticError> {
+        let saved = self.enter_debug_scope(block_ast.span);
+        let res = self.lower_block_body(block_ast);
+        self.leave_debug_scope(saved);
+        res
+    }
+
+    /// Lowers a block's statements and yield in the current scope (a function body uses
+    /// the function's own scope).
+    fn lower_block_body(&mut self, block_ast: &Block) -> Result<(), SemanticError> {
+        self.defers.push(Vec::new());
         for stmt in &block_ast.stmts {
             self.lower_stmt_in_place(stmt, block_ast.span)?;
+            // Everything after a `break`/`continue` (or an `if` whose branches all jump) is dead.
+            // Jumps run the deferred bodies themselves.
+            if self.has_terminator() {
+                self.defers.pop();
+                return Ok(());
+            }
         }
 
         let ret = if let Some(y) = &block_ast.yield_expr {
@@ -162,10 +611,52 @@ impl<'c> Lowerer<'c> {
         } else {
             None
         };
+        // The result is computed before the block's deferred bodies run.
+        self.lower_deferred(self.defers.len() - 1)?;
+        self.defers.pop();
         self.set_terminator(Terminator::Return(ret));
         Ok(())
     }
 
+    /// Emits, in place, the deferred bodies of every scope from `depth` inward: innermost
+    /// scope first, and within a scope the last registered first.
+    fn lower_deferred(&mut self, depth: usize) -> Result<(), SemanticError> {
+        let pending: Vec<Deferred> = self.defers[depth..]
+            .iter()
+            .rev()
+            .flat_map(|scope| scope.iter().rev().cloned())
+            .collect();
+        for deferred in &pending {
+            let body = match deferred {
+                Deferred::Body(body) => body,
+                Deferred::Close { callee, handle, span } => {
+                    self.push_inst(Inst {
+                        span: *span,
+                        dest: None,
+                        kind: InstKind::Call {
+                            callee: callee.to_string(),
+                            args: vec![*handle],
+                        },
+                    });
+                    continue;
+                }
+            };
+            // A deferred body is a scope of its own, so its nested `defer`s run at its end.
+            let saved = self.enter_debug_scope(body.span);
+            self.defers.push(Vec::new());
+            for stmt in &body.stmts {
+                self.lower_stmt_in_place(stmt, body.span)?;
+            }
+            if let Some(y) = &body.yield_expr {
+                let _ = self.lower_expr(y)?;
+            }
+            self.lower_deferred(self.defers.len() - 1)?;
+            self.defers.pop();
+            self.leave_debug_scope(saved);
+        }
+        Ok(())
+    }
+
     fn lower_stmt_in_place(&mut self, stmt: &Stmt, span: aura_ast::Span) -> Result<(), SemanticError> {
         match stmt {
             Stmt::Import(_) | Stmt::TypeAlias(_) | Stmt::MacroDef(_) | Stmt::MacroCall(_) => Ok(()),
@@ -178,6 +669,31 @@ impl<'c> Lowerer<'c> {
             Stmt::If(i) => self.lower_if(i),
             Stmt::Match(m) => self.lower_match(m),
             Stmt::While(w) => self.lower_while(w),
+            Stmt::For(f) => self.lower_for(f),
+            Stmt::Break(b) => self.lower_loop_jump(true, b.span),
+            Stmt::Continue(c) => self.lower_loop_jump(false, c.span),
+            Stmt::Defer(d) => {
+                if let Some(scope) = self.defers.last_mut() {
+                    scope.push(Deferred::Body(d.body.clone()));
+                }
+                Ok(())
+            }
+            Stmt::Return(r) => {
+                let v = match &r.value {
+                    Some(e) => Some(self.lower_expr(e)?),
+                    None => None,
+                };
+                // A unit-typed value is evaluated for its effects only.
+                let v = v.filter(|_| self.ret_ty != Type::Unit);
+                self.lower_deferred(0)?;
+                self.set_terminator(Terminator::Return(v));
+                if v.is_none()
+                    && let Some(bb) = self.current_block_id()
+                {
+                    self.bare_returns.insert(bb);
+                }
+                Ok(())
+            }
             Stmt::Requires(_) | Stmt::Ensures(_) | Stmt::Assert(_) | Stmt::Assume(_) => Ok(()),
             Stmt::ExprStmt(expr) => {
                 let _ = self.lower_expr(expr)?;
@@ -185,6 +701,10 @@ impl<'c> Lowerer<'c> {
             }
             Stmt::Layout(lb) => self.lower_layout_block(lb, span),
             Stmt::Render(rb) => self.lower_render_block(rb, span),
+            Stmt::Scope(sc) => {
+                self.lower_closed_block("scope.open", "scope.close", sc.token.as_ref(), &sc.body, sc.span)
+            }
+            Stmt::Region(r) => self.lower_closed_block("region.enter", "region.exit", None, &r.body, r.span),
             other => Err(SemanticError {
                 message: format!("lowering: unsupported statement: {other:?}"),
                 span,
@@ -192,6 +712,53 @@ impl<'c> Lowerer<'c> {
         }
     }
 
+    /// Lowers `scope tok:` or `region:` in place: `open` makes the block's handle (bound to
+    /// `name`, if any), and `close` of it runs after the body's deferred bodies on every path
+    /// out of it.
+    fn lower_closed_block(
+        &mut self,
+        open: &str,
+        close: &'static str,
+        name: Option<&Ident>,
+        body: &Block,
+        span: aura_ast::Span,
+    ) -> Result<(), SemanticError> {
+        let handle = self.id.fresh_value();
+        self.push_inst(Inst {
+            span,
+            dest: Some(handle),
+            kind: InstKind::Call {
+                callee: open.to_string(),
+                args: Vec::new(),
+            },
+        });
+        let saved = self.enter_debug_scope(body.span);
+        if let Some(name) = name {
+            self.declare_local(&name.node, handle, name.span, None);
+        }
+        self.defers.push(vec![Deferred::Close {
+            callee: close,
+            handle,
+            span,
+        }]);
+        for stmt in &body.stmts {
+            self.lower_stmt_in_place(stmt, body.span)?;
+            // A jump out of the body already closed the block.
+            if self.has_terminator() {
+                self.defers.pop();
+                self.leave_debug_scope(saved);
+                return Ok(());
+            }
+        }
+        if let Some(y) = &body.yield_expr {
+            let _ = self.lower_expr(y)?;
+        }
+        self.lower_deferred(self.defers.len() - 1)?;
+        self.defers.pop();
+        self.leave_debug_scope(saved);
+        Ok(())
+    }
+
     fn lower_layout_block(&mut self, lb: &LayoutBlock, span: aura_ast::Span) -> Result<(), SemanticError> {
         for s in &lb.body.stmts {
             self.lower_stmt_in_place(s, span)?;
@@ -213,6 +780,11 @@ impl<'c> Lowerer<'c> {
     }
 
     fn lower_strand(&mut self, sd: &StrandDef) -> Result<(), SemanticError> {
+        if let Some(pat) = &sd.pattern {
+            let tuple_v = self.lower_expr(&sd.expr)?;
+            self.bind_pattern(pat, tuple_v, sd.span)?;
+            return Ok(());
+        }
         let v = self.id.fresh_value();
         let expr = self.lower_rvalue(&sd.expr)?;
         self.push_inst(Inst {
@@ -223,19 +795,203 @@ impl<'c> Lowerer<'c> {
                 expr,
             },
         });
-        self.locals.insert(sd.name.node.clone(), v);
+        self.declare_local(&sd.name.node, v, sd.span, None);
+        Ok(())
+    }
+
+    /// Binds every name in a pattern to the matching part of `v`: tuple elements via
+    /// `TupleGet`, enum payload fields via `EnumField`. Or-patterns bind through their
+    /// first alternative, so callers expand the ones that bind names first.
+    fn bind_pattern(&mut self, pat: &Pattern, v: ValueId, span: aura_ast::Span) -> Result<(), SemanticError> {
+        match pat {
+            Pattern::Bind { name, .. } => {
+                self.declare_local(&name.node, v, name.span, None);
+            }
+            Pattern::Tuple { elems, .. } => {
+                for (i, p) in elems.iter().enumerate() {
+                    if !p.binders().is_empty() {
+                        let elem_v = self.lower_tuple_get(v, i as u32, span);
+                        self.bind_pattern(p, elem_v, span)?;
+                    }
+                }
+            }
+            Pattern::Ctor { ty, variant, args, .. } => {
+                let tag = self.variant_tag(ty, variant, span)?;
+                for (i, p) in args.iter().enumerate() {
+                    if !p.binders().is_empty() {
+                        let field_v = self.lower_enum_field(v, tag, i as u32, span);
+                        self.bind_pattern(p, field_v, span)?;
+                    }
+                }
+            }
+            Pattern::Or { alts, .. } => {
+                if let Some(first) = alts.first() {
+                    self.bind_pattern(first, v, span)?;
+                }
+            }
+            Pattern::Wildcard { .. } | Pattern::IntLit { .. } | Pattern::StringLit { .. } => {}
+        }
         Ok(())
     }
 
+    /// Branches to `then_bb` if `v` matches `pat` and to `else_bb` otherwise. Tests
+    /// short-circuit, so an enum's payload slots are only read once its tag matched.
+    fn lower_pattern_branch(
+        &mut self,
+        pat: &Pattern,
+        v: ValueId,
+        then_bb: BlockId,
+        else_bb: BlockId,
+        span: aura_ast::Span,
+    ) -> Result<(), SemanticError> {
+        match pat {
+            Pattern::Wildcard { .. } | Pattern::Bind { .. } => {
+                self.set_terminator(Terminator::Br(then_bb));
+            }
+            Pattern::IntLit { value, .. } => {
+                let lit_v = self.lower_const_u32(*value, span);
+                let cond = self.lower_binary(BinOp::Eq, v, lit_v, span);
+                self.set_terminator(Terminator::CondBr { cond, then_bb, else_bb });
+            }
+            Pattern::StringLit { value, .. } => {
+                let lit_v = self.lower_const_string(value.clone(), span);
+                let cond = self.lower_binary(BinOp::Eq, v, lit_v, span);
+                self.set_terminator(Terminator::CondBr { cond, then_bb, else_bb });
+            }
+            Pattern::Tuple { elems, .. } => {
+                for (i, p) in elems.iter().enumerate() {
+                    if !pattern_is_refutable(p) {
+                        continue;
+                    }
+                    let elem_v = self.lower_tuple_get(v, i as u32, span);
+                    let next_bb = self.id.fresh_block();
+                    self.lower_pattern_branch(p, elem_v, next_bb, else_bb, span)?;
+                    self.push_block(next_bb, span, ExecutionHint::Sequential);
+                }
+                self.set_terminator(Terminator::Br(then_bb));
+            }
+            Pattern::Ctor { ty, variant, args, .. } => {
+                let tag = self.variant_tag(ty, variant, span)?;
+                let tag_v = self.lower_enum_tag(v, span);
+                let lit_v = self.lower_const_u32(tag as u64, span);
+                let cond = self.lower_binary(BinOp::Eq, tag_v, lit_v, span);
+                let fields_bb = self.id.fresh_block();
+                self.set_terminator(Terminator::CondBr {
+                    cond,
+                    then_bb: fields_bb,
+                    else_bb,
+                });
+                self.push_block(fields_bb, span, ExecutionHint::Sequential);
+                self.lower_payload_branch(args, v, tag, then_bb, else_bb, span)?;
+            }
+            Pattern::Or { alts, .. } => {
+                for (i, alt) in alts.iter().enumerate() {
+                    if i + 1 == alts.len() {
+                        self.lower_pattern_branch(alt, v, then_bb, else_bb, span)?;
+                    } else {
+                        let next_bb = self.id.fresh_block();
+                        self.lower_pattern_branch(alt, v, then_bb, next_bb, span)?;
+                        self.push_block(next_bb, span, ExecutionHint::Sequential);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Branches to `then_bb` if the payload of `v`, known to hold variant `tag`, matches
+    /// `args` and to `else_bb` otherwise. Only fields with refutable patterns are read.
+    fn lower_payload_branch(
+        &mut self,
+        args: &[Pattern],
+        v: ValueId,
+        tag: u32,
+        then_bb: BlockId,
+        else_bb: BlockId,
+        span: aura_ast::Span,
+    ) -> Result<(), SemanticError> {
+        for (i, p) in args.iter().enumerate() {
+            if !pattern_is_refutable(p) {
+                continue;
+            }
+            let field_v = self.lower_enum_field(v, tag, i as u32, span);
+            let next_bb = self.id.fresh_block();
+            self.lower_pattern_branch(p, field_v, next_bb, else_bb, span)?;
+            self.push_block(next_bb, span, ExecutionHint::Sequential);
+        }
+        self.set_terminator(Terminator::Br(then_bb));
+        Ok(())
+    }
+
+    fn variant_tag(&self, ty: &Ident, variant: &Ident, span: aura_ast::Span) -> Result<u32, SemanticError> {
+        let (tag, _arity) = self
+            .checker
+            .enum_variant_info(&ty.node, &variant.node)
+            .ok_or_else(|| SemanticError {
+                message: format!("lowering: unknown enum variant '{}::{}' in match pattern", ty.node, variant.node),
+                span,
+            })?;
+        Ok(tag)
+    }
+
+    fn lower_enum_tag(&mut self, value: ValueId, span: aura_ast::Span) -> ValueId {
+        let out = self.id.fresh_value();
+        self.push_inst(Inst {
+            span,
+            dest: Some(out),
+            kind: InstKind::EnumTag { value },
+        });
+        out
+    }
+
+    fn lower_enum_field(&mut self, value: ValueId, tag: u32, index: u32, span: aura_ast::Span) -> ValueId {
+        let out = self.id.fresh_value();
+        self.push_inst(Inst {
+            span,
+            dest: Some(out),
+            kind: InstKind::EnumField { value, tag, index },
+        });
+        out
+    }
+
+    fn lower_tuple_get(&mut self, tuple_v: ValueId, index: u32, span: aura_ast::Span) -> ValueId {
+        let out = self.id.fresh_value();
+        self.push_inst(Inst {
+            span,
+            dest: Some(out),
+            kind: InstKind::TupleGet { tuple: tuple_v, index },
+        });
+        out
+    }
+
+    fn lower_binary(&mut self, op: BinOp, left: ValueId, right: ValueId, span: aura_ast::Span) -> ValueId {
+        let out = self.id.fresh_value();
+        self.push_inst(Inst {
+            span,
+            dest: Some(out),
+            kind: InstKind::Binary { op, left, right },
+        });
+        out
+    }
+
     fn lower_assign(&mut self, a: &AssignStmt) -> Result<(), SemanticError> {
-        if !self.locals.contains_key(&a.target.node) {
+        let Some(prev) = self.locals.get(&a.target.node) else {
             return Err(SemanticError {
                 message: format!("lowering: assignment to unknown local '{}'", a.target.node),
                 span: a.target.span,
             });
+        };
+        if self.captured.contains(prev) {
+            return Err(SemanticError {
+                message: format!(
+                    "lowering: lambda cannot assign captured variable '{}' (closures capture by value)",
+                    a.target.node
+                ),
+                span: a.target.span,
+            });
         }
         let v = self.lower_expr(&a.expr)?;
-        self.locals.insert(a.target.node.clone(), v);
+        self.rebind_local(&a.target.node, v);
         Ok(())
     }
 
@@ -256,10 +1012,10 @@ impl<'c> Lowerer<'c> {
         // then
         self.push_block(then_bb, i.then_block.span, ExecutionHint::Sequential);
         self.lower_block(&i.then_block)?;
-        if !self.has_terminator() {
+        let then_end = (!self.has_terminator()).then(|| {
             self.set_terminator(Terminator::Br(join_bb));
-        }
-        let then_locals = self.locals.clone();
+            (self.current_block_id().unwrap_or(then_bb), self.locals.clone())
+        });
 
         // else
         self.locals = saved_locals.clone();
@@ -267,15 +1023,24 @@ impl<'c> Lowerer<'c> {
         if let Some(else_block) = &i.else_block {
             self.lower_block(else_block)?;
         }
-        if !self.has_terminator() {
+        let else_end = (!self.has_terminator()).then(|| {
             self.set_terminator(Terminator::Br(join_bb));
-        }
-        let else_locals = self.locals.clone();
+            (self.current_block_id().unwrap_or(else_bb), self.locals.clone())
+        });
 
-        // join
+        // join (skipped when neither branch falls through, e.g. both `break`)
         self.locals = saved_locals;
-        self.push_block(join_bb, i.span, ExecutionHint::Sequential);
-        self.merge_locals_with_phi(&then_locals, then_bb, &else_locals, else_bb, i.span);
+        match (then_end, else_end) {
+            (Some((then_end, then_locals)), Some((else_end, else_locals))) => {
+                self.push_block(join_bb, i.span, ExecutionHint::Sequential);
+                self.merge_locals_with_phi(&then_locals, then_end, &else_locals, else_end, i.span);
+            }
+            (Some((_, locals)), None) | (None, Some((_, locals))) => {
+                self.push_block(join_bb, i.span, ExecutionHint::Sequential);
+                self.locals = locals;
+            }
+            (None, None) => {}
+        }
 
         Ok(())
     }
@@ -289,103 +1054,132 @@ impl<'c> Lowerer<'c> {
             body_bbs.push(self.id.fresh_block());
         }
 
-        // Dispatch into the appropriate arm.
-        // Sema enforces that the final arm is a wildcard, so dispatch always terminates.
-        let mut wildcard_arm: Option<(usize, BlockId)> = None;
-        let mut int_arms: Vec<(u64, BlockId, aura_ast::Span)> = Vec::new();
-        let mut string_arms: Vec<(String, BlockId, aura_ast::Span)> = Vec::new();
-        let mut ctor_arms: Vec<(u64, BlockId, aura_ast::Span)> = Vec::new();
-
+        // Each alternative of an arm is dispatched separately. Alternatives of an arm that
+        // binds names get their own block, which binds from that alternative and then jumps
+        // to the shared body; nested or-patterns are expanded for them first.
+        let mut arm_alts: Vec<Vec<(Pattern, BlockId)>> = Vec::with_capacity(m.arms.len());
         for (idx, arm) in m.arms.iter().enumerate() {
-            let body_bb = body_bbs[idx];
-            match &arm.pat {
-                Pattern::Wildcard { .. } => wildcard_arm = Some((idx, body_bb)),
-                Pattern::IntLit { value, .. } => int_arms.push((*value, body_bb, arm.span)),
-                Pattern::StringLit { value, .. } => string_arms.push((value.clone(), body_bb, arm.span)),
-                Pattern::Ctor { ty, variant, .. } => {
-                    let Some((tag, _arity)) =
-                        self.checker.enum_variant_info(&ty.node, &variant.node)
-                    else {
-                        return Err(SemanticError {
-                            message: format!(
-                                "lowering: unknown enum variant '{}::{}' in match pattern",
-                                ty.node, variant.node
-                            ),
-                            span: arm.span,
-                        });
-                    };
-                    ctor_arms.push((tag as u64, body_bb, arm.span));
-                }
+            if let Pattern::Bind { .. } = &arm.pat {
+                return Err(SemanticError {
+                    message: "lowering: bare name pattern outside a tuple".to_string(),
+                    span: arm.span,
+                });
             }
+            let alts: Vec<Pattern> = if arm.pat.binders().is_empty() {
+                match &arm.pat {
+                    Pattern::Or { alts, .. } => alts.clone(),
+                    p => vec![p.clone()],
+                }
+            } else {
+                or_free_alternatives(&arm.pat)
+            };
+            let own_blocks = alts.len() > 1 && !arm.pat.binders().is_empty();
+            arm_alts.push(
+                alts.into_iter()
+                    .map(|p| {
+                        let target = if own_blocks { self.id.fresh_block() } else { body_bbs[idx] };
+                        (p, target)
+                    })
+                    .collect(),
+            );
         }
 
-        let default_bb = wildcard_arm
-            .map(|(_, bb)| bb)
-            .ok_or_else(|| SemanticError {
-                message: "lowering: match without trailing wildcard".to_string(),
-                span: m.span,
-            })?;
+        // Dispatch into the appropriate arm. Refutable alternatives are tested in order; the
+        // first irrefutable one (a wildcard arm) is the default. Sema only accepts a match
+        // without one when its arms are exhaustive, so then the last alternative is the default.
+        let mut refutable: Vec<(&Pattern, BlockId, aura_ast::Span)> = Vec::new();
+        let mut default_bb: Option<BlockId> = None;
+        'arms: for (arm, alts) in m.arms.iter().zip(arm_alts.iter()) {
+            for (p, target) in alts {
+                if !pattern_is_refutable(p) {
+                    default_bb = Some(*target);
+                    break 'arms;
+                }
+                refutable.push((p, *target, arm.span));
+            }
+        }
+        let default_bb = match default_bb {
+            Some(bb) => bb,
+            None => {
+                let (_, bb, _) = refutable.pop().ok_or_else(|| SemanticError {
+                    message: "lowering: match without arms".to_string(),
+                    span: m.span,
+                })?;
+                bb
+            }
+        };
 
-        // If this is an integer-literal match, emit a balanced decision tree (or switch) instead of a linear chain.
-        // This is a small but meaningful step toward "advanced pattern matching compilation".
-        if !int_arms.is_empty() && string_arms.is_empty() && ctor_arms.is_empty() {
-            int_arms.sort_by_key(|(v, _, _)| *v);
-            let items: Vec<(u64, BlockId)> = int_arms.iter().map(|(v, bb, _)| (*v, *bb)).collect();
+        let all_ints = refutable.iter().all(|(p, _, _)| matches!(p, Pattern::IntLit { .. }));
+        let all_ctors = refutable.iter().all(|(p, _, _)| matches!(p, Pattern::Ctor { .. }));
+
+        if refutable.is_empty() {
+            self.set_terminator(Terminator::Br(default_bb));
+        } else if all_ints {
+            // If this is an integer-literal match, emit a balanced decision tree (or switch) instead of a linear chain.
+            // This is a small but meaningful step toward "advanced pattern matching compilation".
+            let mut items: Vec<(u64, BlockId)> = refutable
+                .iter()
+                .filter_map(|(p, bb, _)| match p {
+                    Pattern::IntLit { value, .. } => Some((*value, *bb)),
+                    _ => None,
+                })
+                .collect();
+            items.sort_by_key(|(v, _)| *v);
+            items.dedup_by_key(|(v, _)| *v);
             self.lower_match_int_switch_or_tree(scrut_v, &items, default_bb, m.span);
-        } else if int_arms.is_empty() && !string_arms.is_empty() && ctor_arms.is_empty() {
-            // Keep the simple linear chain for string matches.
-            for (idx, arm) in m.arms.iter().enumerate() {
-                let body_bb = body_bbs[idx];
-                match &arm.pat {
-                    Pattern::Wildcard { .. } => {
-                        self.set_terminator(Terminator::Br(body_bb));
+        } else if all_ctors {
+            // Enum match: switch on the tag, then test the payload patterns of that variant's
+            // alternatives in order. Alternatives of other variants cannot match, so once
+            // they all fail the match falls through to the default.
+            let mut by_tag: BTreeMap<u32, Vec<CtorAlt<'_>>> = BTreeMap::new();
+            for (p, bb, span) in &refutable {
+                let Pattern::Ctor { ty, variant, args, .. } = p else {
+                    continue;
+                };
+                let tag = self.variant_tag(ty, variant, *span)?;
+                by_tag.entry(tag).or_default().push((args.as_slice(), *bb, *span));
+            }
+            let is_flat = |args: &[Pattern]| args.iter().all(|a| !pattern_is_refutable(a));
+            let mut cases: Vec<(u64, BlockId)> = Vec::with_capacity(by_tag.len());
+            let mut payload_tests = Vec::new();
+            for (tag, alts) in by_tag {
+                if is_flat(alts[0].0) {
+                    cases.push((tag as u64, alts[0].1));
+                } else {
+                    let test_bb = self.id.fresh_block();
+                    cases.push((tag as u64, test_bb));
+                    payload_tests.push((test_bb, tag, alts));
+                }
+            }
+            let tag_v = self.lower_enum_tag(scrut_v, m.span);
+            self.set_terminator(Terminator::Switch {
+                scrut: tag_v,
+                default_bb,
+                cases,
+            });
+            for (test_bb, tag, alts) in payload_tests {
+                self.push_block(test_bb, m.span, ExecutionHint::Sequential);
+                for (args, target, span) in alts {
+                    if is_flat(args) {
+                        self.set_terminator(Terminator::Br(target));
                         break;
                     }
-                    Pattern::StringLit { value, .. } => {
-                        let lit_v = self.lower_const_string(value.clone(), arm.span);
-                        let cond_v = self.id.fresh_value();
-                        self.push_inst(Inst {
-                            span: arm.span,
-                            dest: Some(cond_v),
-                            kind: InstKind::Binary {
-                                op: BinOp::Eq,
-                                left: scrut_v,
-                                right: lit_v,
-                            },
-                        });
-
-                        let else_bb = self.id.fresh_block();
-                        self.set_terminator(Terminator::CondBr {
-                            cond: cond_v,
-                            then_bb: body_bb,
-                            else_bb,
-                        });
-                        self.push_block(else_bb, arm.span, ExecutionHint::Sequential);
-                    }
-                    Pattern::IntLit { .. } | Pattern::Ctor { .. } => {
-                        return Err(SemanticError {
-                            message: "lowering: mixed literal patterns are not supported in IR".to_string(),
-                            span: arm.span,
-                        });
-                    }
+                    let else_bb = self.id.fresh_block();
+                    self.lower_payload_branch(args, scrut_v, tag, target, else_bb, span)?;
+                    self.push_block(else_bb, span, ExecutionHint::Sequential);
+                }
+                if !self.has_terminator() {
+                    self.set_terminator(Terminator::Br(default_bb));
                 }
             }
-        } else if int_arms.is_empty() && string_arms.is_empty() && !ctor_arms.is_empty() {
-            // Enum/ctor match: dispatch on the enum tag stored in slot 0.
-            ctor_arms.sort_by_key(|(v, _, _)| *v);
-            let tag_v = self.lower_tensor_get(scrut_v, 0, m.span);
-            let items: Vec<(u64, BlockId)> = ctor_arms.iter().map(|(v, bb, _)| (*v, *bb)).collect();
-            self.lower_match_int_switch_or_tree(tag_v, &items, default_bb, m.span);
         } else {
-            // Either only wildcard exists, or the match contains a mixed set of literal kinds.
-            if int_arms.is_empty() && string_arms.is_empty() {
-                self.set_terminator(Terminator::Br(default_bb));
-            } else {
-                return Err(SemanticError {
-                    message: "lowering: mixed literal patterns are not supported in IR".to_string(),
-                    span: m.span,
-                });
+            // Linear chain: each alternative tests its literals, tags and nested fields in order.
+            for (p, target, span) in &refutable {
+                let else_bb = self.id.fresh_block();
+                self.lower_pattern_branch(p, scrut_v, *target, else_bb, *span)?;
+                self.push_block(else_bb, *span, ExecutionHint::Sequential);
             }
+            self.set_terminator(Terminator::Br(default_bb));
         }
 
         let saved_locals = self.locals.clone();
@@ -393,59 +1187,41 @@ impl<'c> Lowerer<'c> {
 
         for (idx, arm) in m.arms.iter().enumerate() {
             let bb = body_bbs[idx];
-            self.locals = saved_locals.clone();
-            self.push_block(bb, arm.body.span, ExecutionHint::Sequential);
-
-            // Pattern binders for enum ctor arms are scoped to the arm; do not escape the match.
-            if let Pattern::Ctor {
-                ty,
-                variant,
-                binders,
-                ..
-            } = &arm.pat
-            {
-                let Some((_tag, arity)) =
-                    self.checker.enum_variant_info(&ty.node, &variant.node)
-                else {
-                    return Err(SemanticError {
-                        message: format!(
-                            "lowering: unknown enum variant '{}::{}' in match arm",
-                            ty.node, variant.node
-                        ),
-                        span: arm.span,
-                    });
-                };
-
-                if binders.len() != arity {
-                    return Err(SemanticError {
-                        message: format!(
-                            "lowering: wrong number of binders for pattern '{}::{}': expected {}, got {}",
-                            ty.node,
-                            variant.node,
-                            arity,
-                            binders.len()
-                        ),
-                        span: arm.span,
-                    });
-                }
-
-                for (i, b) in binders.iter().enumerate() {
-                    let field_v = self.lower_tensor_get(scrut_v, (1 + i) as u64, arm.span);
-                    self.locals.insert(b.node.clone(), field_v);
+            let alts = &arm_alts[idx];
+            let saved_scope = self.enter_debug_scope(arm.span);
+            // Pattern binders are scoped to the arm; do not escape the match.
+            if alts.iter().any(|(_, target)| *target != bb) {
+                let mut bound: Vec<(BlockId, HashMap<String, ValueId>)> = Vec::with_capacity(alts.len());
+                for (p, target) in alts {
+                    self.locals = saved_locals.clone();
+                    self.push_block(*target, arm.span, ExecutionHint::Sequential);
+                    self.bind_pattern(p, scrut_v, arm.span)?;
+                    self.set_terminator(Terminator::Br(bb));
+                    bound.push((*target, self.locals.clone()));
                 }
+                self.locals = bound[0].1.clone();
+                self.push_block(bb, arm.body.span, ExecutionHint::Sequential);
+                self.merge_locals_with_phi_multi(&bound, arm.span);
+            } else {
+                self.locals = saved_locals.clone();
+                self.push_block(bb, arm.body.span, ExecutionHint::Sequential);
+                self.bind_pattern(&arm.pat, scrut_v, arm.span)?;
             }
 
             self.lower_block(&arm.body)?;
+            self.leave_debug_scope(saved_scope);
             if !self.has_terminator() {
                 self.set_terminator(Terminator::Br(join_bb));
+                arm_locals.push((self.current_block_id().unwrap_or(bb), self.locals.clone()));
             }
-            arm_locals.push((bb, self.locals.clone()));
         }
 
-        // join
+        // join (skipped when no arm falls through)
         self.locals = saved_locals;
-        self.push_block(join_bb, m.span, ExecutionHint::Sequential);
-        self.merge_locals_with_phi_multi(&arm_locals, m.span);
+        if !arm_locals.is_empty() {
+            self.push_block(join_bb, m.span, ExecutionHint::Sequential);
+            self.merge_locals_with_phi_multi(&arm_locals, m.span);
+        }
         Ok(())
     }
 
@@ -539,20 +1315,6 @@ impl<'c> Lowerer<'c> {
         self.lower_match_int_tree(scrut_v, items, default_bb, span);
     }
 
-    fn lower_tensor_get(&mut self, tensor_v: ValueId, index: u64, span: aura_ast::Span) -> ValueId {
-        let idx_v = self.lower_const_u32(index, span);
-        let out = self.id.fresh_value();
-        self.push_inst(Inst {
-            span,
-            dest: Some(out),
-            kind: InstKind::Call {
-                callee: "tensor.get".to_string(),
-                args: vec![tensor_v, idx_v],
-            },
-        });
-        out
-    }
-
     fn lower_const_u32(&mut self, n: u64, span: aura_ast::Span) -> ValueId {
         let v = self.id.fresh_value();
         self.push_inst(Inst {
@@ -609,7 +1371,7 @@ impl<'c> Lowerer<'c> {
 
             let Some(v0) = first else { continue };
             if all_same {
-                self.locals.insert(name, v0);
+                self.rebind_local(&name, v0);
                 continue;
             }
 
@@ -619,13 +1381,20 @@ impl<'c> Lowerer<'c> {
                 dest: Some(out),
                 kind: InstKind::Phi { incomings },
             });
-            self.locals.insert(name, out);
+            self.rebind_local(&name, out);
         }
     }
 
     fn lower_while(&mut self, w: &WhileStmt) -> Result<(), SemanticError> {
+        self.lower_loop(w, None)
+    }
+
+    /// Lowers a loop; `step` runs at the end of every iteration, including ones cut
+    /// short by `continue` (the increment of a desugared `for`).
+    fn lower_loop(&mut self, w: &WhileStmt, step: Option<&Stmt>) -> Result<(), SemanticError> {
         let cond_bb = self.id.fresh_block();
         let body_bb = self.id.fresh_block();
+        let latch_bb = step.map(|_| self.id.fresh_block());
         let exit_bb = self.id.fresh_block();
 
         let preheader_bb = self.current_block_id().ok_or_else(|| SemanticError {
@@ -643,12 +1412,15 @@ impl<'c> Lowerer<'c> {
         // creates invalid self-referential phi incoming edges in nested loops.
         let mut mutated: BTreeSet<String> = BTreeSet::new();
         collect_assigned_names(&w.body, &mut mutated);
+        if let Some(Stmt::Assign(a)) = step {
+            mutated.insert(a.target.node.clone());
+        }
 
         // cond
         self.push_block(cond_bb, w.cond.span, ExecutionHint::Sequential);
 
         // Loop-carried SSA: introduce phis for locals that may change in the loop body.
-        // Incoming edges: preheader -> cond, body -> cond.
+        // Incoming edges: preheader -> cond, plus every back edge (patched below).
         let cond_block_idx = self.current.expect("current block");
         let mut phi_fixups: Vec<(usize, String, ValueId)> = Vec::new();
         for (name, &pre_v) in &saved_locals {
@@ -661,15 +1433,16 @@ impl<'c> Lowerer<'c> {
                 span: w.span,
                 dest: Some(out),
                 kind: InstKind::Phi {
-                    incomings: vec![(preheader_bb, pre_v), (body_bb, pre_v)],
+                    incomings: vec![(preheader_bb, pre_v)],
                 },
             });
-            self.locals.insert(name.clone(), out);
+            self.rebind_local(name, out);
             phi_fixups.push((inst_idx, name.clone(), pre_v));
         }
 
         let cond_v = self.lower_expr(&w.cond)?;
         let cond_locals = self.locals.clone();
+        let cond_end_bb = self.current_block_id().unwrap_or(cond_bb);
         self.set_terminator(Terminator::CondBr {
             cond: cond_v,
             then_bb: body_bb,
@@ -679,37 +1452,168 @@ impl<'c> Lowerer<'c> {
         // body
         self.push_block(body_bb, w.body.span, ExecutionHint::Sequential);
         self.locals = cond_locals.clone();
-        self.lower_block(&w.body)?;
+        self.loops.push(LoopCtx {
+            continue_bb: latch_bb.unwrap_or(cond_bb),
+            exit_bb,
+            defer_depth: self.defers.len(),
+            continues: Vec::new(),
+            breaks: Vec::new(),
+        });
+        let body = self.lower_block(&w.body);
+        let ctx = self.loops.pop().expect("loop context");
+        body?;
+
+        // Predecessors of the loop's continue point: the end of the body, if it falls
+        // through, and every `continue`.
+        let mut continues = ctx.continues;
         if !self.has_terminator() {
-            self.set_terminator(Terminator::Br(cond_bb));
+            self.set_terminator(Terminator::Br(ctx.continue_bb));
+            let end_bb = self.current_block_id().unwrap_or(body_bb);
+            continues.push((end_bb, self.locals.clone()));
         }
-        let backedge_bb = self.current_block_id().unwrap_or(body_bb);
-        let body_locals = self.locals.clone();
 
-        // Patch phi incoming values from the body.
+        let backedges = match (step, latch_bb) {
+            (Some(step), Some(latch_bb)) if !continues.is_empty() => {
+                self.locals = cond_locals.clone();
+                self.push_block(latch_bb, w.span, ExecutionHint::Sequential);
+                self.merge_locals_with_phi_multi(&continues, w.span);
+                self.lower_stmt_in_place(step, w.span)?;
+                self.set_terminator(Terminator::Br(cond_bb));
+                let end_bb = self.current_block_id().unwrap_or(latch_bb);
+                vec![(end_bb, self.locals.clone())]
+            }
+            (Some(_), _) => Vec::new(),
+            (None, _) => continues,
+        };
+
+        // Patch phi incoming values from the back edges.
         // Note: only names present at loop entry get phis; new names in the body don't escape.
         for (inst_idx, name, pre_v) in phi_fixups {
-            let body_v = body_locals.get(&name).copied().unwrap_or(pre_v);
             let inst = &mut self.blocks[cond_block_idx].insts[inst_idx];
             if let InstKind::Phi { incomings } = &mut inst.kind {
-                if incomings.len() >= 2 {
-                    incomings[1].0 = backedge_bb;
-                    incomings[1].1 = body_v;
+                for (bb, locals) in &backedges {
+                    incomings.push((*bb, locals.get(&name).copied().unwrap_or(pre_v)));
                 }
             }
         }
 
         // exit
-        // Exit is reached only from the condition block, so values are the loop-header SSA values.
-        self.locals = cond_locals;
+        // Without `break`, exit is reached only from the condition block, so values are the
+        // loop-header SSA values.
+        self.locals = cond_locals.clone();
         self.push_block(exit_bb, w.span, ExecutionHint::Sequential);
+        if !ctx.breaks.is_empty() {
+            let mut preds = vec![(cond_end_bb, cond_locals)];
+            preds.extend(ctx.breaks);
+            self.merge_locals_with_phi_multi(&preds, w.span);
+        }
+
+        Ok(())
+    }
+
+    fn lower_loop_jump(&mut self, is_break: bool, span: aura_ast::Span) -> Result<(), SemanticError> {
+        let Some(depth) = self.loops.last().map(|ctx| ctx.defer_depth) else {
+            let kw = if is_break { "break" } else { "continue" };
+            return Err(SemanticError {
+                message: format!("lowering: '{kw}' outside of a loop"),
+                span,
+            });
+        };
+        self.lower_deferred(depth)?;
+        let bb = self.current_block_id().ok_or_else(|| SemanticError {
+            message: "lowering: loop jump without an active block".to_string(),
+            span,
+        })?;
+        let locals = self.locals.clone();
+        let ctx = self.loops.last_mut().expect("loop context");
+        let target = if is_break {
+            ctx.breaks.push((bb, locals));
+            ctx.exit_bb
+        } else {
+            ctx.continues.push((bb, locals));
+            ctx.continue_bb
+        };
+        self.set_terminator(Terminator::Br(target));
+        Ok(())
+    }
 
+    fn lower_for(&mut self, f: &ForStmt) -> Result<(), SemanticError> {
+        // Desugar onto the `while` lowering so loop-carried phis are shared:
+        //   var = start; while var < $end: body; var = var + 1
+        // The increment runs in a latch block so `continue` still advances `var`.
+        // The end bound is evaluated once, before entering the loop.
+        let shadowed = self.locals.get(&f.var.node).copied();
+        let shadowed_decl = self.debug_decls.get(&f.var.node).copied();
+        let start_v = self.lower_expr(&f.start)?;
+        let end_v = self.lower_expr(&f.end)?;
+        let end_name = format!("$for_end{end_v:?}");
+        self.locals.insert(end_name.clone(), end_v);
+        self.declare_local(&f.var.node, start_v, f.var.span, None);
+
+        let ident = |name: &str, span: aura_ast::Span| Expr {
+            span,
+            kind: ExprKind::Ident(Ident {
+                span,
+                node: name.to_string(),
+            }),
+        };
+        let cond = Expr {
+            span: f.end.span,
+            kind: ExprKind::Binary {
+                left: Box::new(ident(&f.var.node, f.var.span)),
+                op: AstBinOp::Lt,
+                right: Box::new(ident(&end_name, f.end.span)),
+            },
+        };
+        let step = Stmt::Assign(AssignStmt {
+            span: f.var.span,
+            target: f.var.clone(),
+            expr: Expr {
+                span: f.var.span,
+                kind: ExprKind::Binary {
+                    left: Box::new(ident(&f.var.node, f.var.span)),
+                    op: AstBinOp::Add,
+                    right: Box::new(Expr {
+                        span: f.var.span,
+                        kind: ExprKind::IntLit(1),
+                    }),
+                },
+            },
+        });
+        self.lower_loop(
+            &WhileStmt {
+                span: f.span,
+                cond,
+                invariant: None,
+                decreases: None,
+                body: f.body.clone(),
+            },
+            Some(&step),
+        )?;
+
+        // The loop variable and hidden bound are scoped to the loop.
+        self.locals.remove(&end_name);
+        match shadowed_decl {
+            Some(var) => self.debug_decls.insert(f.var.node.clone(), var),
+            None => self.debug_decls.remove(&f.var.node),
+        };
+        match shadowed {
+            Some(v) => {
+                self.locals.insert(f.var.node.clone(), v);
+            }
+            None => {
+                self.locals.remove(&f.var.node);
+            }
+        }
         Ok(())
     }
 
     fn lower_rvalue(&mut self, expr: &Expr) -> Result<RValue, SemanticError> {
         match &expr.kind {
-            ExprKind::IntLit(n) => Ok(RValue::ConstU32(*n)),
+            ExprKind::IntLit(n) if self.checker.int_cast(self.instance.as_deref(), expr.span).is_none() => {
+                Ok(RValue::ConstU32(*n))
+            }
+            ExprKind::FloatLit(v) => Ok(RValue::ConstF64(*v)),
             ExprKind::StringLit(s) => Ok(RValue::ConstString(s.clone())),
             ExprKind::StyleLit { fields } => Ok(RValue::ConstString(format_style_lit(fields))),
             _ => Ok(RValue::Local(self.lower_expr(expr)?)),
@@ -717,6 +1621,32 @@ impl<'c> Lowerer<'c> {
     }
 
     fn lower_expr(&mut self, expr: &Expr) -> Result<ValueId, SemanticError> {
+        let mut v = self.lower_expr_value(expr)?;
+        // A use of an `Option` narrowed to `Some` reads its payload.
+        if self.checker.option_unwrap(self.instance.as_deref(), expr.span)
+            && let Some((tag, _)) = self.checker.enum_variant_info("Option", "Some")
+        {
+            v = self.lower_enum_field(v, tag, 0, expr.span);
+        }
+        // Sema converts integers to the type their use needs, e.g. a `u8` passed as a `u32`.
+        let Some((from, to)) = self.checker.int_cast(self.instance.as_deref(), expr.span) else {
+            return Ok(v);
+        };
+        let kind = InstKind::IntCast {
+            value: v,
+            from: lower_sema_type_to_ir(from),
+            to: lower_sema_type_to_ir(to),
+        };
+        let cast = self.id.fresh_value();
+        self.push_inst(Inst {
+            span: expr.span,
+            dest: Some(cast),
+            kind,
+        });
+        Ok(cast)
+    }
+
+    fn lower_expr_value(&mut self, expr: &Expr) -> Result<ValueId, SemanticError> {
         match &expr.kind {
             ExprKind::Ident(id) => self
                 .locals
@@ -740,6 +1670,19 @@ impl<'c> Lowerer<'c> {
                 Ok(v)
             }
 
+            ExprKind::FloatLit(f) => {
+                let v = self.id.fresh_value();
+                self.push_inst(Inst {
+                    span: expr.span,
+                    dest: Some(v),
+                    kind: InstKind::BindStrand {
+                        name: format!("$lit{v:?}"),
+                        expr: RValue::ConstF64(*f),
+                    },
+                });
+                Ok(v)
+            }
+
             ExprKind::StringLit(s) => {
                 let v = self.id.fresh_value();
                 self.push_inst(Inst {
@@ -753,6 +1696,57 @@ impl<'c> Lowerer<'c> {
                 Ok(v)
             }
 
+            ExprKind::InterpolatedString(parts) => {
+                // Lower to a left fold of `str.concat` over the segments. Embedded values go
+                // through `str.from`, which backends specialize on the operand's type.
+                let mut acc: Option<ValueId> = None;
+                for part in parts {
+                    let seg = match part {
+                        InterpPart::Lit(l) => {
+                            let v = self.id.fresh_value();
+                            self.push_inst(Inst {
+                                span: expr.span,
+                                dest: Some(v),
+                                kind: InstKind::BindStrand {
+                                    name: format!("$str{v:?}"),
+                                    expr: RValue::ConstString(l.clone()),
+                                },
+                            });
+                            v
+                        }
+                        InterpPart::Expr(e) => {
+                            let inner = self.lower_expr(e)?;
+                            let v = self.id.fresh_value();
+                            self.push_inst(Inst {
+                                span: e.span,
+                                dest: Some(v),
+                                kind: InstKind::Call {
+                                    callee: "str.from".to_string(),
+                                    args: vec![inner],
+                                },
+                            });
+                            v
+                        }
+                    };
+                    acc = Some(match acc {
+                        None => seg,
+                        Some(prev) => {
+                            let v = self.id.fresh_value();
+                            self.push_inst(Inst {
+                                span: expr.span,
+                                dest: Some(v),
+                                kind: InstKind::Call {
+                                    callee: "str.concat".to_string(),
+                                    args: vec![prev, seg],
+                                },
+                            });
+                            v
+                        }
+                    });
+                }
+                Ok(acc.expect("interpolated strings have at least one segment"))
+            }
+
             ExprKind::ForAll { .. } | ExprKind::Exists { .. } => Err(SemanticError {
                 message: "quantifiers are verification-only and cannot be lowered".to_string(),
                 span: expr.span,
@@ -771,21 +1765,10 @@ impl<'c> Lowerer<'c> {
                 Ok(v)
             }
 
-            ExprKind::Lambda { .. } => {
-                // Closures/callbacks are not lowered into IR yet.
-                // Keep it as an opaque placeholder value.
-                let v = self.id.fresh_value();
-                self.push_inst(Inst {
-                    span: expr.span,
-                    dest: Some(v),
-                    kind: InstKind::BindStrand {
-                        name: format!("$lambda{v:?}"),
-                        expr: RValue::ConstU32(0),
-                    },
-                });
-                Ok(v)
-            }
+            ExprKind::Lambda { op, body } => self.lower_lambda(expr.span, *op, body),
 
+            // A borrow passes the value's handle along; sema has ruled out conflicting uses.
+            ExprKind::Borrow { expr: inner, .. } => self.lower_expr(inner),
             ExprKind::Unary { op, expr: inner } => {
                 let operand = self.lower_expr(inner)?;
                 let v = self.id.fresh_value();
@@ -823,6 +1806,116 @@ impl<'c> Lowerer<'c> {
                 Ok(v)
             }
 
+            ExprKind::Tuple(elems) => {
+                let elems = elems
+                    .iter()
+                    .map(|e| self.lower_expr(e))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let v = self.id.fresh_value();
+                self.push_inst(Inst {
+                    span: expr.span,
+                    dest: Some(v),
+                    kind: InstKind::MakeTuple { elems },
+                });
+                Ok(v)
+            }
+
+            ExprKind::TupleIndex { base, index } => {
+                let base_v = self.lower_expr(base)?;
+                Ok(self.lower_tuple_get(base_v, *index, expr.span))
+            }
+
+            // Lists share the tensor runtime: `[a, b]` becomes `tensor.new(2)` plus one
+            // `tensor.set` per element, and `xs[i]` becomes `tensor.get(xs, i)`. Like enum
+            // payloads, elements occupy u32-sized slots.
+            ExprKind::ListLit(elems) => {
+                let len_v = self.lower_const_u32(elems.len() as u64, expr.span);
+                let list_v = self.id.fresh_value();
+                self.push_inst(Inst {
+                    span: expr.span,
+                    dest: Some(list_v),
+                    kind: InstKind::Call {
+                        callee: "tensor.new".to_string(),
+                        args: vec![len_v],
+                    },
+                });
+                for (i, e) in elems.iter().enumerate() {
+                    let elem_v = self.lower_expr(e)?;
+                    let idx_v = self.lower_const_u32(i as u64, e.span);
+                    self.push_inst(Inst {
+                        span: e.span,
+                        dest: None,
+                        kind: InstKind::Call {
+                            callee: "tensor.set".to_string(),
+                            args: vec![list_v, idx_v, elem_v],
+                        },
+                    });
+                }
+                Ok(list_v)
+            }
+
+            // Maps are opaque runtime handles: `{k: v}` becomes `map.new(n)` plus one
+            // `map.insert` per entry, and `m[k]` becomes `map.get(m, k)`. Values occupy
+            // u32-sized slots like list elements.
+            ExprKind::MapLit(entries) => {
+                let len_v = self.lower_const_u32(entries.len() as u64, expr.span);
+                let map_v = self.id.fresh_value();
+                self.push_inst(Inst {
+                    span: expr.span,
+                    dest: Some(map_v),
+                    kind: InstKind::Call {
+                        callee: "map.new".to_string(),
+                        args: vec![len_v],
+                    },
+                });
+                for (k, v) in entries {
+                    let key_v = self.lower_expr(k)?;
+                    let val_v = self.lower_expr(v)?;
+                    self.push_inst(Inst {
+                        span: k.span,
+                        dest: None,
+                        kind: InstKind::Call {
+                            callee: "map.insert".to_string(),
+                            args: vec![map_v, key_v, val_v],
+                        },
+                    });
+                }
+                Ok(map_v)
+            }
+
+            ExprKind::Index { base, index } => {
+                let base_v = self.lower_expr(base)?;
+                let idx_v = self.lower_expr(index)?;
+                let callee = if self.checker.is_map_access(self.instance.as_deref(), expr.span) {
+                    "map.get"
+                } else {
+                    "tensor.get"
+                };
+                let v = self.id.fresh_value();
+                self.push_inst(Inst {
+                    span: expr.span,
+                    dest: Some(v),
+                    kind: InstKind::Call {
+                        callee: callee.to_string(),
+                        args: vec![base_v, idx_v],
+                    },
+                });
+                Ok(v)
+            }
+
+            ExprKind::Member { base, .. }
+                if let Some(index) = self.checker.record_field_index(self.instance.as_deref(), expr.span) =>
+            {
+                let record = self.lower_expr(base)?;
+                let v = self.id.fresh_value();
+                self.push_inst(Inst {
+                    span: expr.span,
+                    dest: Some(v),
+                    kind: InstKind::RecordGet { record, index },
+                });
+                Ok(v)
+            }
+
             ExprKind::Member { base, member } => {
                 // We lower member access by turning it into a callee string when used as callee.
                 // If evaluated as a value, it's an opaque handle.
@@ -840,10 +1933,30 @@ impl<'c> Lowerer<'c> {
             }
 
             ExprKind::Call { callee, args, trailing } => {
-                // Enum constructor lowering: `Type::Variant(a, b, ...)`.
-                // We encode enum values as `Tensor` handles where:
-                // - slot 0 = tag (variant index)
-                // - slots 1.. = field payloads (u32-ish handles)
+                // `is_some(o)` and `is_none(o)` compare the tag of `o` with `Some`'s.
+                if let Some((some, arg)) = self.checker.option_test(expr)
+                    && let Some((tag, _)) = self.checker.enum_variant_info("Option", "Some")
+                {
+                    let value = self.lower_expr(arg)?;
+                    let tag_v = self.lower_enum_tag(value, expr.span);
+                    let lit_v = self.lower_const_u32(tag as u64, expr.span);
+                    let op = if some { BinOp::Eq } else { BinOp::Ne };
+                    return Ok(self.lower_binary(op, tag_v, lit_v, expr.span));
+                }
+                // Calls through a closure value (sema only admits them without arguments).
+                if let Some(ret) = self.checker.closure_call_ret(self.instance.as_deref(), expr.span) {
+                    let ret = self.ir_type(ret);
+                    let closure = self.lower_expr(callee)?;
+                    let v = self.id.fresh_value();
+                    self.push_inst(Inst {
+                        span: expr.span,
+                        dest: (ret != Type::Unit).then_some(v),
+                        kind: InstKind::CallClosure { closure, ret },
+                    });
+                    return Ok(v);
+                }
+
+                // Enum constructor lowering: `Type::Variant(a, b, ...)` builds the tagged union.
                 if let ExprKind::Member { base, member } = &callee.kind {
                     if let ExprKind::Ident(ty_id) = &base.kind {
                         if let Some((tag, arity)) =
@@ -861,53 +1974,21 @@ impl<'c> Lowerer<'c> {
                                     span: expr.span,
                                 });
                             }
-
-                            let len_v = self.lower_const_u32((1 + arity) as u64, expr.span);
+                            let ty = self.enum_ctor_ir_type(expr.span)?;
+                            let mut fields = Vec::with_capacity(args.len());
+                            for a in args {
+                                fields.push(self.lower_expr(Self::call_arg_value(a))?);
+                            }
                             let enum_v = self.id.fresh_value();
                             self.push_inst(Inst {
                                 span: expr.span,
                                 dest: Some(enum_v),
-                                kind: InstKind::Call {
-                                    callee: "tensor.new".to_string(),
-                                    args: vec![len_v],
-                                },
-                            });
-
-                            let idx0 = self.lower_const_u32(0, expr.span);
-                            let tag_v = self.lower_const_u32(tag as u64, expr.span);
-                            self.push_inst(Inst {
-                                span: expr.span,
-                                dest: None,
-                                kind: InstKind::Call {
-                                    callee: "tensor.set".to_string(),
-                                    args: vec![enum_v, idx0, tag_v],
+                                kind: InstKind::MakeEnum {
+                                    ty,
+                                    tag,
+                                    fields,
                                 },
                             });
-
-                            for (i, a) in args.iter().enumerate() {
-                                let field_v = self.lower_expr(Self::call_arg_value(a))?;
-                                let idx_v = self.lower_const_u32((1 + i) as u64, expr.span);
-                                self.push_inst(Inst {
-                                    span: expr.span,
-                                    dest: None,
-                                    kind: InstKind::Call {
-                                        callee: "tensor.set".to_string(),
-                                        args: vec![enum_v, idx_v, field_v],
-                                    },
-                                });
-                            }
-
-                            // Trailing blocks are not supported on enum constructors (enforced in sema),
-                            // but ignore defensively.
-                            if let Some(tb) = trailing {
-                                for s in &tb.stmts {
-                                    self.lower_stmt_in_place(s, tb.span)?;
-                                }
-                                if let Some(y) = &tb.yield_expr {
-                                    let _ = self.lower_expr(y)?;
-                                }
-                            }
-
                             return Ok(enum_v);
                         }
                     }
@@ -916,9 +1997,21 @@ impl<'c> Lowerer<'c> {
                 // Prototype method lowering: treat tensor instance methods `.len/.get/.set`
                 // as `tensor.*(receiver, ...)`.
                 let (callee_name, lowered_args) = match &callee.kind {
+                    ExprKind::Member { base, member }
+                        if self.checker.is_map_access(self.instance.as_deref(), expr.span) =>
+                    {
+                        let recv = self.lower_expr(base)?;
+                        let mut v = Vec::with_capacity(args.len() + 1);
+                        v.push(recv);
+                        for a in args {
+                            v.push(self.lower_expr(Self::call_arg_value(a))?);
+                        }
+                        (format!("map.{}", member.node), v)
+                    }
                     ExprKind::Member { base, member }
                         if matches!(member.node.as_str(), "len" | "get" | "set")
-                            && !matches!(&base.kind, aura_ast::ExprKind::Ident(id) if id.node == "tensor") =>
+                            && !matches!(&base.kind, aura_ast::ExprKind::Ident(id) if id.node == "tensor")
+                            && !self.checker.is_known_cell(&expr_to_callee_name(callee)) =>
                     {
                         let recv = self.lower_expr(base)?;
                         let mut v = Vec::with_capacity(args.len() + 1);
@@ -930,7 +2023,8 @@ impl<'c> Lowerer<'c> {
                     }
                     ExprKind::Member { base, member }
                         if member.node == "infer"
-                            && !matches!(&base.kind, aura_ast::ExprKind::Ident(id) if id.node == "ai") =>
+                            && !matches!(&base.kind, aura_ast::ExprKind::Ident(id) if id.node == "ai")
+                            && !self.checker.is_known_cell(&expr_to_callee_name(callee)) =>
                     {
                         let recv = self.lower_expr(base)?;
                         let mut v = Vec::with_capacity(args.len() + 1);
@@ -941,7 +2035,12 @@ impl<'c> Lowerer<'c> {
                         ("ai.infer".to_string(), v)
                     }
                     _ => {
-                        let name = expr_to_callee_name(callee);
+                        let name = match self.checker.generic_call_target(self.instance.as_deref(), expr.span) {
+                            Some(target) => target.to_string(),
+                            None => self
+                                .checker
+                                .resolve_callee_in(self.namespace.as_deref(), &expr_to_callee_name(callee)),
+                        };
                         let mut v = Vec::with_capacity(args.len());
                         for a in args {
                             v.push(self.lower_expr(Self::call_arg_value(a))?);
@@ -996,7 +2095,8 @@ impl<'c> Lowerer<'c> {
                         let (callee_name, lowered_args) = match &callee.kind {
                             ExprKind::Member { base, member }
                                 if matches!(member.node.as_str(), "len" | "get" | "set")
-                                    && !matches!(&base.kind, aura_ast::ExprKind::Ident(id) if id.node == "tensor") =>
+                                    && !matches!(&base.kind, aura_ast::ExprKind::Ident(id) if id.node == "tensor")
+                                    && !self.checker.is_known_cell(&expr_to_callee_name(callee)) =>
                             {
                                 let recv = self.lower_expr(base)?;
                                 let mut v = Vec::with_capacity(args.len() + 2);
@@ -1009,7 +2109,8 @@ impl<'c> Lowerer<'c> {
                             }
                             ExprKind::Member { base, member }
                                 if member.node == "infer"
-                                    && !matches!(&base.kind, aura_ast::ExprKind::Ident(id) if id.node == "ai") =>
+                                    && !matches!(&base.kind, aura_ast::ExprKind::Ident(id) if id.node == "ai")
+                                    && !self.checker.is_known_cell(&expr_to_callee_name(callee)) =>
                             {
                                 let recv = self.lower_expr(base)?;
                                 let mut v = Vec::with_capacity(args.len() + 2);
@@ -1021,7 +2122,9 @@ impl<'c> Lowerer<'c> {
                                 ("ai.infer".to_string(), v)
                             }
                             _ => {
-                                let name = expr_to_callee_name(callee);
+                                let name = self
+                                    .checker
+                                    .resolve_callee_in(self.namespace.as_deref(), &expr_to_callee_name(callee));
                                 let mut v = Vec::with_capacity(args.len() + 1);
                                 v.push(left_v);
                                 for a in args {
@@ -1076,10 +2179,7 @@ impl<'c> Lowerer<'c> {
                 }
             }
 
-            ExprKind::RecordLit { .. } => Err(SemanticError {
-                message: "lowering: record literals are not supported in IR yet".to_string(),
-                span: expr.span,
-            }),
+            ExprKind::RecordLit { name, fields } => self.lower_record_lit(expr.span, name, fields),
         }
     }
 
@@ -1168,7 +2268,7 @@ fn call_arg_value(arg: &aura_ast::CallArg) -> &Expr {
         for (name, &a_v) in a {
             if let Some(&b_v) = b.get(name) {
                 if a_v == b_v {
-                    self.locals.insert(name.clone(), a_v);
+                    self.rebind_local(name, a_v);
                     continue;
                 }
                 let out = self.id.fresh_value();
@@ -1179,10 +2279,10 @@ fn call_arg_value(arg: &aura_ast::CallArg) -> &Expr {
                         incomings: vec![(a_bb, a_v), (b_bb, b_v)],
                     },
                 });
-                self.locals.insert(name.clone(), out);
+                self.rebind_local(name, out);
             } else {
                 // If missing from b, keep a.
-                self.locals.insert(name.clone(), a_v);
+                self.rebind_local(name, a_v);
             }
         }
         // Include names present only in b.
@@ -1221,11 +2321,66 @@ fn call_arg_value(arg: &aura_ast::CallArg) -> &Expr {
 
     fn has_terminator(&self) -> bool {
         let idx = self.current.expect("current block");
-        !matches!(self.blocks[idx].term, Terminator::Return(None))
+        !matches!(self.blocks[idx].term, Terminator::Return(None)) || self.bare_returns.contains(&self.blocks[idx].id)
+    }
+}
+
+/// Whether matching `pat` needs a runtime test.
+fn pattern_is_refutable(pat: &Pattern) -> bool {
+    match pat {
+        Pattern::Wildcard { .. } | Pattern::Bind { .. } => false,
+        Pattern::Tuple { elems, .. } => elems.iter().any(pattern_is_refutable),
+        Pattern::Or { alts, .. } => alts.iter().all(pattern_is_refutable),
+        Pattern::IntLit { .. } | Pattern::StringLit { .. } | Pattern::Ctor { .. } => true,
     }
 }
 
-fn collect_assigned_names(block: &Block, out: &mut BTreeSet<String>) {
+/// Expands nested or-patterns into the equivalent list of or-free alternatives, so each
+/// one binds its names from a fixed position.
+fn or_free_alternatives(pat: &Pattern) -> Vec<Pattern> {
+    fn products(pats: &[Pattern]) -> Vec<Vec<Pattern>> {
+        let mut out: Vec<Vec<Pattern>> = vec![Vec::new()];
+        for p in pats {
+            let alts = or_free_alternatives(p);
+            out = out
+                .into_iter()
+                .flat_map(|prefix| {
+                    alts.iter().map(move |a| {
+                        let mut row = prefix.clone();
+                        row.push(a.clone());
+                        row
+                    })
+                })
+                .collect();
+        }
+        out
+    }
+
+    match pat {
+        Pattern::Or { alts, .. } => alts.iter().flat_map(or_free_alternatives).collect(),
+        Pattern::Tuple { span, elems } => products(elems)
+            .into_iter()
+            .map(|elems| Pattern::Tuple { span: *span, elems })
+            .collect(),
+        Pattern::Ctor {
+            span,
+            ty,
+            variant,
+            args,
+        } => products(args)
+            .into_iter()
+            .map(|args| Pattern::Ctor {
+                span: *span,
+                ty: ty.clone(),
+                variant: variant.clone(),
+                args,
+            })
+            .collect(),
+        p => vec![p.clone()],
+    }
+}
+
+pub(crate) fn collect_assigned_names(block: &Block, out: &mut BTreeSet<String>) {
     for s in &block.stmts {
         match s {
             Stmt::Assign(AssignStmt { target, .. }) => {
@@ -1251,13 +2406,21 @@ fn collect_assigned_names(block: &Block, out: &mut BTreeSet<String>) {
             Stmt::While(WhileStmt { body, .. }) => {
                 collect_assigned_names(body, out);
             }
+            Stmt::For(ForStmt { body, .. }) => {
+                collect_assigned_names(body, out);
+            }
             Stmt::FlowBlock(FlowBlock { body, .. }) => {
                 collect_assigned_names(body, out);
             }
-            Stmt::UnsafeBlock(UnsafeBlock { body, .. }) => {
+            Stmt::UnsafeBlock(UnsafeBlock { body, .. })
+            | Stmt::Scope(ScopeBlock { body, .. })
+            | Stmt::Region(RegionBlock { body, .. }) => {
+                collect_assigned_names(body, out);
+            }
+            Stmt::Defer(DeferStmt { body, .. }) => {
                 collect_assigned_names(body, out);
             }
-            Stmt::Prop(_) => {}
+            Stmt::Prop(_) | Stmt::Break(_) | Stmt::Continue(_) | Stmt::Return(_) => {}
             Stmt::Requires(_) | Stmt::Ensures(_) | Stmt::Assert(_) | Stmt::Assume(_) => {}
             Stmt::CellDef(_)
             | Stmt::ExternCell(_)
@@ -1288,18 +2451,11 @@ fn map_binop(op: AstBinOp) -> BinOp {
         AstBinOp::Ge => BinOp::Ge,
         AstBinOp::And => BinOp::And,
         AstBinOp::Or => BinOp::Or,
-    }
-}
-
-fn lower_type(tr: &TypeRef) -> Type {
-    match tr.name.node.as_str() {
-        "bool" => Type::Bool,
-        "u32" => Type::U32,
-        "Tensor" => Type::Tensor,
-        "String" => Type::String,
-        "Unit" => Type::Unit,
-        "Style" => Type::Opaque("Style".to_string()),
-        other => Type::Opaque(other.to_string()),
+        AstBinOp::BitAnd => BinOp::BitAnd,
+        AstBinOp::BitOr => BinOp::BitOr,
+        AstBinOp::BitXor => BinOp::BitXor,
+        AstBinOp::Shl => BinOp::Shl,
+        AstBinOp::Shr => BinOp::Shr,
     }
 }
 
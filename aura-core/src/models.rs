@@ -0,0 +1,57 @@
+#![forbid(unsafe_code)]
+
+//! Output shapes of the models `ai.load_model` reads, for the types of `ai.infer` results.
+//!
+//! Only the shape is taken: a model that cannot be read, or whose contract is wrong, is
+//! left to verification to report, and `infer` results keep an unshaped tensor type.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+use aura_bridge::onnx::OnnxDim;
+
+/// A model's output dims, `None` where a dim is open (symbolic or unsized).
+pub(crate) type OutputDims = Vec<Option<u64>>;
+
+/// Output dims with the size and modification time of the file they were read from.
+type Stamped = (u64, SystemTime, OutputDims);
+
+/// Parsed shapes by path, reused while the file's size and modification time are unchanged:
+/// the language server checks every edit.
+static SHAPES: OnceLock<Mutex<HashMap<PathBuf, Stamped>>> = OnceLock::new();
+
+/// Output dims of the model at `path`, resolved against the working directory as
+/// verification and the AVM resolve it.
+pub(crate) fn model_output_dims(path: &str) -> Option<OutputDims> {
+    let mut path = PathBuf::from(path);
+    if path.is_relative() {
+        path = std::env::current_dir().ok()?.join(path);
+    }
+    let meta = fs::metadata(&path).ok()?;
+    let stamp = (meta.len(), meta.modified().ok()?);
+
+    let cache = SHAPES.get_or_init(Default::default);
+    if let Some((len, modified, dims)) = cache.lock().unwrap_or_else(|e| e.into_inner()).get(&path)
+        && (*len, *modified) == stamp
+    {
+        return Some(dims.clone());
+    }
+
+    let shapes = aura_bridge::model::read_model_io_shapes(&path).ok()?;
+    let dims: OutputDims = shapes
+        .output
+        .iter()
+        .map(|d| match d {
+            OnnxDim::Known(v) if *v > 0 => Some(*v as u64),
+            _ => None,
+        })
+        .collect();
+    cache
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(path, (stamp.0, stamp.1, dims.clone()));
+    Some(dims)
+}
@@ -25,11 +25,15 @@ pub fn classify_type(ty: &Type) -> LinearTypeKind {
         Type::Unknown => LinearTypeKind::Copyable,
         Type::Unit => LinearTypeKind::Copyable,
         Type::Bool => LinearTypeKind::Copyable,
-        Type::U32 => LinearTypeKind::Copyable,
+        Type::U8 | Type::U16 | Type::U32 | Type::U64 | Type::I32 | Type::I64 => LinearTypeKind::Copyable,
+        Type::F64 => LinearTypeKind::Copyable,
         Type::String => LinearTypeKind::Copyable,
+        // A closure's environment is never written after it is built, so copies may share it.
+        Type::Closure(_) => LinearTypeKind::Copyable,
         
         // Linear resource types
         Type::Tensor { .. } => LinearTypeKind::Linear,
+        Type::List(_) | Type::Map(..) => LinearTypeKind::Linear,
         Type::Model => LinearTypeKind::Linear,
         Type::Style => LinearTypeKind::Linear,
         
@@ -59,6 +63,15 @@ pub fn classify_type(ty: &Type) -> LinearTypeKind {
         }
         
         Type::ConstrainedRange { base, .. } => classify_type(base),
+
+        // A tuple is linear if any element is.
+        Type::Tuple(elems) => {
+            if elems.iter().any(|t| classify_type(t) == LinearTypeKind::Linear) {
+                LinearTypeKind::Linear
+            } else {
+                LinearTypeKind::Copyable
+            }
+        }
     }
 }
 
@@ -67,6 +67,7 @@ struct ThreadLockPattern {
 /// Network Safety Verifier
 /// 
 /// Performs formal verification of network code properties using constraint solving.
+#[derive(Debug)]
 pub struct NetworkVerifier {
     /// Track lock acquisition order across threads
     lock_graph: HashMap<String, Lock>,
@@ -228,10 +229,12 @@ impl NetworkVerifier {
             (SocketState::Open, SocketState::Closed) => true,
             // Accepting can go to reading
             (SocketState::Accepting, SocketState::Reading) => true,
-            // Reading/Writing can stay in same state or close
+            // Reading/Writing can stay in same state, alternate (request/response) or close
             (SocketState::Reading, SocketState::Reading) => true,
+            (SocketState::Reading, SocketState::Writing) => true,
             (SocketState::Reading, SocketState::Closed) => true,
             (SocketState::Writing, SocketState::Writing) => true,
+            (SocketState::Writing, SocketState::Reading) => true,
             (SocketState::Writing, SocketState::Closed) => true,
             // Can't transition from closed or other invalid states
             _ => false,
@@ -411,6 +414,20 @@ mod tests {
         assert!(verifier.transition_socket("sock", SocketState::Closed));
     }
 
+    #[test]
+    fn socket_lifecycle_allows_request_response() {
+        let mut verifier = NetworkVerifier::new();
+        verifier.init_socket("sock");
+
+        assert!(verifier.transition_socket("sock", SocketState::Open));
+        assert!(verifier.transition_socket("sock", SocketState::Writing));
+        assert!(verifier.transition_socket("sock", SocketState::Reading));
+        assert!(verifier.transition_socket("sock", SocketState::Writing));
+        assert!(verifier.transition_socket("sock", SocketState::Closed));
+        assert!(!verifier.transition_socket("sock", SocketState::Writing));
+        assert_eq!(verifier.violations(), ["Invalid socket transition Closed -> Writing"]);
+    }
+
     #[test]
     fn socket_lifecycle_rejects_invalid_transition() {
         let mut verifier = NetworkVerifier::new();
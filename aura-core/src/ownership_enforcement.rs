@@ -154,6 +154,14 @@ pub enum ViolationKind {
     UseNotMoved,
     /// Invalid operation for state: e.g., mutate borrowed-immut value
     InvalidOperation,
+    /// Unused binding: a val or parameter that is never read (a lint warning)
+    UnusedBinding,
+    /// Unused import: nothing refers to the imported module (a lint warning)
+    UnusedImport,
+    /// Unreachable code: no path reaches the statement (a lint warning)
+    UnreachableCode,
+    /// Data race: concurrent flows access a shared strand, at least one writing (a warning)
+    DataRace,
 }
 
 /// The ownership enforcement engine for a function scope.
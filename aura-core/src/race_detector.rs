@@ -10,7 +10,7 @@
 /// 
 /// Uses happens-before relationship analysis + lock dependency graphs.
 
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{HashMap, HashSet};
 
 /// Types of concurrent access
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -66,9 +66,9 @@ pub enum RaceViolation {
         thread2: u32,
         access2: (u32, u32),
     },
-    /// Circular lock dependency detected
+    /// Threads acquire locks in orders that form a cycle, so each may wait on the next
     Deadlock {
-        cycle: Vec<(String, String)>, // lock dependencies forming cycle
+        cycle: Vec<LockOrderEdge>, // one edge per thread, each acquiring the next edge's held lock
     },
     /// Variable accessed after it's been freed
     UseAfterFree {
@@ -93,11 +93,16 @@ impl RaceViolation {
                 )
             }
             RaceViolation::Deadlock { cycle } => {
-                let cycle_str = cycle.iter()
-                    .map(|(a, b)| format!("{} → {}", a, b))
+                let mut order: Vec<&str> = cycle.iter().map(|e| e.held.as_str()).collect();
+                order.extend(cycle.first().map(|e| e.held.as_str()));
+                let sites = cycle.iter()
+                    .map(|e| format!(
+                        "thread {} acquires '{}' at {}:{} while holding '{}'",
+                        e.thread_id, e.lock, e.acquired_at.0, e.acquired_at.1, e.held
+                    ))
                     .collect::<Vec<_>>()
-                    .join(" → ");
-                format!("deadlock detected: {}", cycle_str)
+                    .join(", ");
+                format!("potential deadlock: lock order cycle {}: {}", order.join(" → "), sites)
             }
             RaceViolation::UseAfterFree { var_name, freed_at, used_at } => {
                 format!(
@@ -113,6 +118,51 @@ impl RaceViolation {
             }
         }
     }
+
+    /// Where the violation is reported: for a deadlock, the first thread's acquisition.
+    pub fn location(&self) -> (u32, u32) {
+        match self {
+            RaceViolation::DataRace { access2, .. } => *access2,
+            RaceViolation::Deadlock { cycle } => cycle.first().map(|e| e.acquired_at).unwrap_or((0, 0)),
+            RaceViolation::UseAfterFree { used_at, .. } => *used_at,
+            RaceViolation::LockLeak { acquired_at, .. } => *acquired_at,
+        }
+    }
+
+    /// Other sites worth showing alongside the report, such as each acquisition in a deadlock cycle.
+    pub fn related(&self) -> Vec<((u32, u32), String)> {
+        match self {
+            RaceViolation::DataRace { var_name, thread1, access1, .. } => {
+                vec![(*access1, format!("thread {} accesses '{}' here", thread1, var_name))]
+            }
+            RaceViolation::Deadlock { cycle } => cycle
+                .iter()
+                .map(|e| {
+                    let msg = format!(
+                        "thread {} acquires '{}' here while holding '{}' (acquired at {}:{})",
+                        e.thread_id, e.lock, e.held, e.held_at.0, e.held_at.1
+                    );
+                    (e.acquired_at, msg)
+                })
+                .collect(),
+            RaceViolation::UseAfterFree { var_name, freed_at, .. } => {
+                vec![(*freed_at, format!("'{}' freed here", var_name))]
+            }
+            RaceViolation::LockLeak { .. } => Vec::new(),
+        }
+    }
+}
+
+/// One edge of the lock-order graph: a thread acquired `lock` while it held `held`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LockOrderEdge {
+    pub held: String,
+    pub lock: String,
+    pub thread_id: u32,
+    /// Where the thread acquired `held`
+    pub held_at: (u32, u32),
+    /// Where the thread acquired `lock`
+    pub acquired_at: (u32, u32),
 }
 
 /// Lock information for deadlock detection
@@ -184,6 +234,8 @@ pub struct RaceDetector {
     accesses: Vec<MemoryAccess>,
     /// Lock information for deadlock detection
     locks: HashMap<String, LockInfo>,
+    /// Lock-order graph, first edge seen per (held, lock, thread)
+    lock_edges: Vec<LockOrderEdge>,
     /// Variables freed in each thread
     freed_vars: HashMap<u32, Vec<(String, u32, u32)>>, // thread -> (var, line, col)
     /// Synchronization information
@@ -197,6 +249,7 @@ impl RaceDetector {
         RaceDetector {
             accesses: Vec::new(),
             locks: HashMap::new(),
+            lock_edges: Vec::new(),
             freed_vars: HashMap::new(),
             sync_info: SynchronizationInfo::new(),
             violations: Vec::new(),
@@ -209,7 +262,35 @@ impl RaceDetector {
     }
 
     /// Record a lock operation
+    ///
+    /// Acquiring a lock while the thread holds others adds an edge from each held lock to it.
     pub fn record_lock(&mut self, lock_name: String, acquired: bool, thread_id: u32, line: u32, col: u32) {
+        if acquired {
+            let mut held: Vec<(String, (u32, u32))> = self.locks
+                .values()
+                .filter(|l| l.name != lock_name)
+                .filter_map(|l| l.acquired_by.get(&thread_id).map(|at| (l.name.clone(), *at)))
+                .collect();
+            held.sort();
+            for (held_name, held_at) in held {
+                if let Some(info) = self.locks.get_mut(&held_name) {
+                    info.dependencies.insert(lock_name.clone());
+                }
+                let seen = self.lock_edges.iter()
+                    .any(|e| e.held == held_name && e.lock == lock_name && e.thread_id == thread_id);
+                if !seen {
+                    self.lock_edges.push(LockOrderEdge {
+                        held: held_name,
+                        lock: lock_name.clone(),
+                        thread_id,
+                        held_at,
+                        acquired_at: (line, col),
+                    });
+                }
+            }
+            self.sync_info.lock_order.entry(thread_id).or_default().push(lock_name.clone());
+        }
+
         let lock = self.locks
             .entry(lock_name.clone())
             .or_insert_with(|| LockInfo::new(lock_name.clone()));
@@ -245,59 +326,49 @@ impl RaceDetector {
                 .push((access.thread_id, access.access_type, access.line, access.col));
         }
 
-        for (var_name, accesses) in var_accesses {
-            let mut threads = HashSet::new();
-            let mut has_write = false;
-            
-            for (thread_id, access_type, _line, _col) in &accesses {
-                threads.insert(*thread_id);
-                if *access_type == AccessType::Write {
-                    has_write = true;
-                }
+        let mut names: Vec<&String> = var_accesses.keys().collect();
+        names.sort();
+        for var_name in names {
+            if self.sync_info.is_protected(var_name) {
+                continue;
             }
-
-            // Race condition: multiple threads, at least one write, no synchronization
-            if threads.len() > 1 && has_write && !self.sync_info.is_protected(&var_name) {
-                let accesses_sorted: Vec<_> = accesses.iter().collect();
-                if accesses_sorted.len() >= 2 {
-                    let access1 = accesses_sorted[0];
-                    let access2 = accesses_sorted[1];
-                    
-                    self.violations.push(RaceViolation::DataRace {
-                        var_name,
-                        thread1: access1.0,
-                        access1: (access1.2, access1.3),
-                        thread2: access2.0,
-                        access2: (access2.2, access2.3),
-                    });
-                }
+            // Race condition: the first two accesses from different threads, at least one a write
+            let accesses = &var_accesses[var_name];
+            let conflict = accesses.iter().enumerate().find_map(|(i, a)| {
+                accesses[i + 1..]
+                    .iter()
+                    .find(|b| b.0 != a.0 && (a.1 == AccessType::Write || b.1 == AccessType::Write))
+                    .map(|b| (a, b))
+            });
+            if let Some((access1, access2)) = conflict {
+                self.violations.push(RaceViolation::DataRace {
+                    var_name: var_name.clone(),
+                    thread1: access1.0,
+                    access1: (access1.2, access1.3),
+                    thread2: access2.0,
+                    access2: (access2.2, access2.3),
+                });
             }
         }
     }
 
-    /// Detect potential deadlocks using cycle detection
+    /// Detect potential deadlocks: cycles in the lock-order graph whose edges come from
+    /// different threads, since one thread cannot wait on itself at two locks at once.
     pub fn detect_deadlocks(&mut self) {
-        // Build dependency graph: lock A -> lock B if B acquired after A in same thread
-        let mut graph: HashMap<String, HashSet<String>> = HashMap::new();
-        
-        for (_thread_id, lock_order) in &self.sync_info.lock_order {
-            for i in 0..lock_order.len() {
-                for j in (i + 1)..lock_order.len() {
-                    graph
-                        .entry(lock_order[i].clone())
-                        .or_insert_with(HashSet::new)
-                        .insert(lock_order[j].clone());
+        let mut names: Vec<&str> = self.lock_edges.iter().map(|e| e.held.as_str()).collect();
+        names.sort();
+        names.dedup();
+
+        let mut reported: HashSet<Vec<String>> = HashSet::new();
+        for start in names {
+            let mut path = Vec::new();
+            if let Some(cycle) = self.find_cycle(start, start, &mut path) {
+                let locks: Vec<String> = cycle.iter().map(|e| e.held.clone()).collect();
+                if reported.insert(locks) {
+                    self.violations.push(RaceViolation::Deadlock { cycle });
                 }
             }
         }
-
-        // Detect cycles using DFS
-        let all_locks: Vec<_> = graph.keys().cloned().collect();
-        for lock in all_locks {
-            if let Some(cycle) = self.find_cycle(&graph, &lock) {
-                self.violations.push(RaceViolation::Deadlock { cycle });
-            }
-        }
     }
 
     /// Detect use-after-free violations
@@ -348,46 +419,33 @@ impl RaceDetector {
         !self.violations.is_empty()
     }
 
-    /// Find cycle in lock dependency graph using DFS
-    fn find_cycle(&self, graph: &HashMap<String, HashSet<String>>, start: &str) -> Option<Vec<(String, String)>> {
-        let mut visited = HashSet::new();
-        let mut path = VecDeque::new();
-        
-        if self.dfs_cycle(&graph, start, &mut visited, &mut path) {
-            let mut cycle = Vec::new();
-            for i in 0..path.len() - 1 {
-                cycle.push((path[i].clone(), path[i + 1].clone()));
-            }
-            return Some(cycle);
-        }
-        None
-    }
-
-    fn dfs_cycle(
-        &self,
-        graph: &HashMap<String, HashSet<String>>,
+    /// Find a cycle back to `start` through locks that sort after it, taking edges from
+    /// threads not yet on `path`.
+    fn find_cycle<'a>(
+        &'a self,
+        start: &str,
         node: &str,
-        visited: &mut HashSet<String>,
-        path: &mut VecDeque<String>,
-    ) -> bool {
-        if visited.contains(node) {
-            // Found cycle if node is in current path
-            return path.contains(&node.to_string());
-        }
-
-        visited.insert(node.to_string());
-        path.push_back(node.to_string());
-
-        if let Some(neighbors) = graph.get(node) {
-            for neighbor in neighbors {
-                if self.dfs_cycle(graph, neighbor, visited, path) {
-                    return true;
-                }
+        path: &mut Vec<&'a LockOrderEdge>,
+    ) -> Option<Vec<LockOrderEdge>> {
+        for edge in self.lock_edges.iter().filter(|e| e.held == node) {
+            if path.iter().any(|e| e.thread_id == edge.thread_id) {
+                continue;
             }
+            if edge.lock == start {
+                let mut cycle: Vec<LockOrderEdge> = path.iter().map(|e| (*e).clone()).collect();
+                cycle.push(edge.clone());
+                return Some(cycle);
+            }
+            if edge.lock.as_str() < start || path.iter().any(|e| e.held == edge.lock) {
+                continue;
+            }
+            path.push(edge);
+            if let Some(cycle) = self.find_cycle(start, &edge.lock, path) {
+                return Some(cycle);
+            }
+            path.pop();
         }
-
-        path.pop_back();
-        false
+        None
     }
 }
 
@@ -417,6 +475,22 @@ mod tests {
         assert!(detector.violations().iter().any(|v| matches!(v, RaceViolation::DataRace { .. })));
     }
 
+    #[test]
+    fn test_data_race_reports_conflicting_threads() {
+        let mut detector = RaceDetector::new();
+
+        // Thread 0's two reads don't conflict with each other; thread 1's write does
+        detector.record_access(MemoryAccess::new("x".to_string(), AccessType::Read, 0, 2, 1));
+        detector.record_access(MemoryAccess::new("x".to_string(), AccessType::Read, 0, 3, 1));
+        detector.record_access(MemoryAccess::new("x".to_string(), AccessType::Write, 1, 7, 5));
+
+        detector.detect_data_races();
+
+        assert_eq!(detector.violations().len(), 1);
+        assert_eq!(detector.violations()[0].location(), (7, 5));
+        assert_eq!(detector.violations()[0].related()[0].0, (2, 1));
+    }
+
     #[test]
     fn test_protected_variable_no_race() {
         let mut detector = RaceDetector::new();
@@ -457,6 +531,55 @@ mod tests {
         assert!(detector.violations().iter().any(|v| matches!(v, RaceViolation::LockLeak { .. })));
     }
 
+    #[test]
+    fn test_opposite_lock_orders_deadlock() {
+        let mut detector = RaceDetector::new();
+
+        // Thread 0 takes a then b, thread 1 takes b then a
+        detector.record_lock("a".to_string(), true, 0, 3, 5);
+        detector.record_lock("b".to_string(), true, 0, 4, 5);
+        detector.record_lock("b".to_string(), false, 0, 5, 5);
+        detector.record_lock("a".to_string(), false, 0, 6, 5);
+        detector.record_lock("b".to_string(), true, 1, 10, 5);
+        detector.record_lock("a".to_string(), true, 1, 11, 5);
+        detector.record_lock("a".to_string(), false, 1, 12, 5);
+        detector.record_lock("b".to_string(), false, 1, 13, 5);
+
+        detector.detect_all();
+
+        let deadlocks: Vec<_> = detector.violations().iter()
+            .filter(|v| matches!(v, RaceViolation::Deadlock { .. }))
+            .collect();
+        assert_eq!(deadlocks.len(), 1);
+        assert_eq!(deadlocks[0].location(), (4, 5));
+        let related: Vec<_> = deadlocks[0].related().into_iter().map(|(at, _)| at).collect();
+        assert_eq!(related, vec![(4, 5), (11, 5)]);
+        assert!(deadlocks[0].message().contains("cycle a → b → a"));
+    }
+
+    #[test]
+    fn test_consistent_lock_order_no_deadlock() {
+        let mut detector = RaceDetector::new();
+
+        for thread in 0..2 {
+            detector.record_lock("a".to_string(), true, thread, 3, 5);
+            detector.record_lock("b".to_string(), true, thread, 4, 5);
+            detector.record_lock("b".to_string(), false, thread, 5, 5);
+            detector.record_lock("a".to_string(), false, thread, 6, 5);
+        }
+        detector.detect_deadlocks();
+        assert!(!detector.has_violations());
+
+        // A single thread taking the locks in both orders cannot deadlock with itself
+        let mut detector = RaceDetector::new();
+        detector.record_lock("a".to_string(), true, 0, 3, 5);
+        detector.record_lock("b".to_string(), true, 0, 4, 5);
+        detector.record_lock("a".to_string(), false, 0, 5, 5);
+        detector.record_lock("a".to_string(), true, 0, 6, 5);
+        detector.detect_deadlocks();
+        assert!(!detector.has_violations());
+    }
+
     #[test]
     fn test_memory_access_new() {
         let access = MemoryAccess::new("x".to_string(), AccessType::Write, 1, 5, 10);
@@ -1,15 +1,24 @@
 #![forbid(unsafe_code)]
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use aura_ast::{
-    AssignStmt, BinOp, Block, CallArg, CellDef, EnumDef, Expr, ExprKind, ExternCell, FlowBlock,
-    Ident, IfStmt, MatchStmt, Pattern, Program, RecordDef, Span, Stmt, StrandDef, TraitDef,
+    AssignStmt, BinOp, Block, BorrowKind, CallArg, CellDef, EnumDef, Expr, ExprKind, ExternCell, FlowBlock,
+    ForStmt, Ident, IfStmt, InterpPart, MatchStmt, Pattern, Program, RecordDef, Span, Stmt, StrandDef, TraitDef,
     TypeArg, TypeRef, UnaryOp, WhileStmt,
 };
 
 use crate::error::SemanticError;
 use crate::capability::CapabilityGraph;
+use crate::capability_validator::{CapabilityValidator, ParamAccess};
+use crate::consteval::fold_consts;
+use crate::diagnostics::DiagnosticFactory;
+use crate::explanation_engine::MoveViolation;
+use crate::lint::{lint_at, Lint};
+use crate::ownership_enforcement::ViolationKind;
+use crate::lower::collect_assigned_names;
+use crate::models::{model_output_dims, OutputDims};
+use crate::symbols::{SymbolKind, SymbolTable};
 use crate::types::{is_subset_range, Type};
 use crate::verifier::{DummySolver, Verifier};
 
@@ -27,7 +36,7 @@ const U32_MAX: u64 = 0xFFFF_FFFF;
 /// ai.infer(model, data);        // ERROR: Consumed already
 /// ```
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-#[allow(dead_code)]  // Borrowed and Returned variants used in future phases
+#[allow(dead_code)]  // Returned variant used in future phases
 enum OwnershipState {
     /// Value is owned and can be moved, borrowed, or used
     Owned,
@@ -36,8 +45,8 @@ enum OwnershipState {
     /// Subsequent uses are not permitted (unless type is Copy).
     Consumed,
     
-    /// Value is borrowed (immutable reference exists).
-    /// May be used for reads, but not moved.
+    /// The binding holds a borrow (`x: &T`, `x: &mut T` or `val r = &x`).
+    /// May be used for reads (and in-place updates for `&mut`), but not moved or reassigned.
     Borrowed,
     
     /// Value has been returned or transferred to caller.
@@ -45,6 +54,24 @@ enum OwnershipState {
     Returned,
 }
 
+/// A live `&x` or `&mut x`. Until it ends, `target` cannot be moved or assigned, and a
+/// mutable loan also keeps every other use of it out.
+#[derive(Clone, Debug)]
+struct Loan {
+    target: String,
+    // Scope depth `target` is bound at, so a binding that shadows it is not affected.
+    depth: usize,
+    kind: BorrowKind,
+}
+
+/// Where a value last moved, kept to explain a later use of it.
+#[derive(Clone, Debug)]
+struct MoveSite {
+    span: Span,
+    // The cell and parameter it was passed to, if a cell of the program took it.
+    into: Option<(String, String)>,
+}
+
 fn base_type(ty: &Type) -> &Type {
     match ty {
         Type::ConstrainedRange { base, .. } => base_type(base),
@@ -52,6 +79,83 @@ fn base_type(ty: &Type) -> &Type {
     }
 }
 
+/// Whether `ty` still has a part inference could not determine.
+fn mentions_unknown(ty: &Type) -> bool {
+    match ty {
+        Type::Unknown => true,
+        Type::Applied { args, .. } | Type::Tuple(args) => args.iter().any(mentions_unknown),
+        Type::Tensor { elem, .. } | Type::List(elem) | Type::Closure(elem) => mentions_unknown(elem),
+        Type::ConstrainedRange { base, .. } => mentions_unknown(base),
+        Type::Map(k, v) => mentions_unknown(k) || mentions_unknown(v),
+        _ => false,
+    }
+}
+
+/// `ty` with each part inference could not determine taken from `evidence` where their shapes
+/// agree. The unknown parts of an unannotated binding's type act as type variables, and this
+/// is how a use of the binding solves them.
+fn fill_unknowns(ty: &Type, evidence: &Type) -> Type {
+    let fill_all = |tys: &[Type], ev: &[Type]| tys.iter().zip(ev).map(|(t, e)| fill_unknowns(t, e)).collect();
+    match (ty, evidence) {
+        (Type::Unknown, e) => e.clone(),
+        (Type::Applied { name, args }, Type::Applied { name: ev_name, args: ev_args })
+            if name == ev_name && args.len() == ev_args.len() =>
+        {
+            Type::Applied {
+                name: name.clone(),
+                args: fill_all(args, ev_args),
+            }
+        }
+        (Type::Tuple(elems), Type::Tuple(ev)) if elems.len() == ev.len() => Type::Tuple(fill_all(elems, ev)),
+        (Type::List(elem), Type::List(ev)) => Type::List(Box::new(fill_unknowns(elem, ev))),
+        (Type::Closure(ret), Type::Closure(ev)) => Type::Closure(Box::new(fill_unknowns(ret, ev))),
+        (Type::Map(k, v), Type::Map(ek, ev)) => {
+            Type::Map(Box::new(fill_unknowns(k, ek)), Box::new(fill_unknowns(v, ev)))
+        }
+        (Type::Tensor { elem, shape }, Type::Tensor { elem: ev, .. }) => Type::Tensor {
+            elem: Box::new(fill_unknowns(elem, ev)),
+            shape: shape.clone(),
+        },
+        _ => ty.clone(),
+    }
+}
+
+/// `ty` with every range widened to its base type: what a value shows a binding holds,
+/// not all it may hold.
+fn widen_ranges(ty: &Type) -> Type {
+    match ty {
+        Type::ConstrainedRange { base, .. } => widen_ranges(base),
+        Type::Applied { name, args } => Type::Applied {
+            name: name.clone(),
+            args: args.iter().map(widen_ranges).collect(),
+        },
+        Type::Tuple(elems) => Type::Tuple(elems.iter().map(widen_ranges).collect()),
+        Type::List(elem) => Type::List(Box::new(widen_ranges(elem))),
+        Type::Map(k, v) => Type::Map(Box::new(widen_ranges(k)), Box::new(widen_ranges(v))),
+        other => other.clone(),
+    }
+}
+
+/// Whether a value of type `ty` carries a `scope`'s cancellation token.
+fn mentions_cancel_token(ty: &Type) -> bool {
+    match ty {
+        Type::Named(n) => n == "CancelToken",
+        Type::Applied { args, .. } | Type::Tuple(args) => args.iter().any(mentions_cancel_token),
+        Type::Tensor { elem, .. } | Type::List(elem) | Type::Closure(elem) => mentions_cancel_token(elem),
+        Type::Map(k, v) => mentions_cancel_token(k) || mentions_cancel_token(v),
+        _ => false,
+    }
+}
+
+/// Whether a value of type `ty` lives in memory a `region` block frees when it ends.
+fn holds_region_memory(ty: &Type) -> bool {
+    match ty {
+        Type::Tensor { .. } | Type::List(_) | Type::Map(..) => true,
+        Type::Applied { args, .. } | Type::Tuple(args) => args.iter().any(holds_region_memory),
+        _ => false,
+    }
+}
+
 fn applied_name_and_args(ty: &Type) -> Option<(&str, &[Type])> {
     match ty {
         Type::Applied { name, args } => Some((name.as_str(), args.as_slice())),
@@ -60,10 +164,124 @@ fn applied_name_and_args(ty: &Type) -> Option<(&str, &[Type])> {
     }
 }
 
+/// The alternatives of a top-level or-pattern, or the pattern itself.
+fn top_level_alts(pat: &Pattern) -> Vec<&Pattern> {
+    match pat {
+        Pattern::Or { alts, .. } => alts.iter().collect(),
+        p => vec![p],
+    }
+}
+
+/// `pat` written out, when it matches exactly one value: no wildcards, bindings or
+/// alternatives anywhere in it.
+fn literal_pattern(pat: &Pattern) -> Option<String> {
+    let list = |pats: &[Pattern]| pats.iter().map(literal_pattern).collect::<Option<Vec<_>>>().map(|v| v.join(", "));
+    match pat {
+        Pattern::IntLit { value, .. } => Some(value.to_string()),
+        Pattern::StringLit { value, .. } => Some(format!("{value:?}")),
+        Pattern::Ctor { ty, variant, args, .. } if args.is_empty() => Some(format!("{}::{}", ty.node, variant.node)),
+        Pattern::Ctor { ty, variant, args, .. } => Some(format!("{}::{}({})", ty.node, variant.node, list(args)?)),
+        Pattern::Tuple { elems, .. } => Some(format!("({})", list(elems)?)),
+        Pattern::Wildcard { .. } | Pattern::Bind { .. } | Pattern::Or { .. } => None,
+    }
+}
+
+/// Whether every path through `block` ends in `return`.
+fn block_always_returns(block: &Block) -> bool {
+    match block.stmts.last() {
+        Some(Stmt::Return(_)) => true,
+        Some(Stmt::If(i)) => {
+            block_always_returns(&i.then_block) && i.else_block.as_ref().is_some_and(block_always_returns)
+        }
+        Some(Stmt::Match(m)) => m.arms.iter().all(|arm| block_always_returns(&arm.body)),
+        Some(Stmt::UnsafeBlock(u)) => block_always_returns(&u.body),
+        Some(Stmt::Scope(sc)) => block_always_returns(&sc.body),
+        Some(Stmt::Region(r)) => block_always_returns(&r.body),
+        _ => false,
+    }
+}
+
+/// The type two result sites of an undeclared cell agree on: u32 ranges widen to cover both.
+fn join_return_types(a: &Type, b: &Type) -> Option<Type> {
+    match (a, b) {
+        _ if a == b => Some(a.clone()),
+        (Type::Unknown, t) | (t, Type::Unknown) => Some(t.clone()),
+        _ => match (u32_bounds(a), u32_bounds(b)) {
+            (Some((alo, ahi)), Some((blo, bhi))) => {
+                let (lo, hi) = (alo.min(blo), ahi.max(bhi));
+                Some(if (lo, hi) == (0, U32_MAX) { Type::U32 } else { mk_u32_range(lo, hi) })
+            }
+            _ => None,
+        },
+    }
+}
+
 fn is_u32_like(ty: &Type) -> bool {
     matches!(base_type(ty), Type::U32)
 }
 
+/// `n` as the singleton range of the narrowest of `u32` and `u64` that holds it.
+fn int_literal_type(n: u64) -> Type {
+    Type::ConstrainedRange {
+        base: Box::new(if n > U32_MAX { Type::U64 } else { Type::U32 }),
+        lo: n,
+        hi: n,
+    }
+}
+
+fn is_int_like(ty: &Type) -> bool {
+    base_type(ty).int_bounds().is_some()
+}
+
+/// The values an integer-typed expression can take: its range if refined, else its type's.
+fn int_value_bounds(ty: &Type) -> Option<(i128, i128)> {
+    match ty {
+        Type::ConstrainedRange { base, lo, hi } if base.int_bounds().is_some() => Some((*lo as i128, *hi as i128)),
+        other => other.int_bounds(),
+    }
+}
+
+/// The type integer operands of different types meet at: the narrowest one holding the values
+/// of both, so `u8 + 1` stays a `u8` and `i32 + u32` is an `i64`.
+fn common_int_type(lt: &Type, rt: &Type) -> Option<Type> {
+    if base_type(lt) == base_type(rt) {
+        return is_int_like(lt).then(|| base_type(lt).clone());
+    }
+    let (l_lo, l_hi) = int_value_bounds(lt)?;
+    let (r_lo, r_hi) = int_value_bounds(rt)?;
+    let (lo, hi) = (l_lo.min(r_lo), l_hi.max(r_hi));
+    [Type::U8, Type::U16, Type::U32, Type::I32, Type::U64, Type::I64]
+        .into_iter()
+        .find(|t| t.int_bounds().is_some_and(|(min, max)| min <= lo && hi <= max))
+}
+
+fn is_map_key_type(ty: &Type) -> bool {
+    matches!(base_type(ty), Type::U32 | Type::String)
+}
+
+// Map literal entries must agree on one key type and one value type.
+fn unify_map_part(slot: &mut Option<Type>, ty: Type, what: &str, span: Span) -> Result<(), SemanticError> {
+    match slot {
+        None => *slot = Some(ty),
+        Some(first) if *first == ty => {}
+        Some(first) => {
+            return Err(SemanticError {
+                message: format!(
+                    "map {what} must share one type: expected {}, got {}",
+                    first.display(),
+                    ty.display()
+                ),
+                span,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn is_f64(ty: &Type) -> bool {
+    matches!(base_type(ty), Type::F64)
+}
+
 fn u32_bounds(ty: &Type) -> Option<(u64, u64)> {
     match ty {
         Type::U32 => Some((0, U32_MAX)),
@@ -116,10 +334,35 @@ fn infer_u32_range_binop(op: &BinOp, lt: &Type, rt: &Type) -> Type {
             mk_u32_range(lo, hi)
         }
 
+        BinOp::BitAnd => mk_u32_range(0, l_hi.min(r_hi)),
+
+        BinOp::BitOr => mk_u32_range(l_lo.max(r_lo), low_bits_mask(l_hi.max(r_hi))),
+
+        BinOp::BitXor => mk_u32_range(0, low_bits_mask(l_hi.max(r_hi))),
+
+        BinOp::Shl => {
+            // Bits shifted past bit 31 are dropped, so only a shift that cannot overflow keeps bounds.
+            if r_hi >= 32 || (l_hi << r_hi) > U32_MAX {
+                return Type::U32;
+            }
+            mk_u32_range(l_lo << r_lo, l_hi << r_hi)
+        }
+
+        BinOp::Shr => {
+            let lo = if r_hi >= 32 { 0 } else { l_lo >> r_hi };
+            let hi = if r_lo >= 32 { 0 } else { l_hi >> r_lo };
+            mk_u32_range(lo, hi)
+        }
+
         _ => Type::U32,
     }
 }
 
+/// Smallest all-ones value (`2^k - 1`) that is at least `n`.
+fn low_bits_mask(n: u64) -> u64 {
+    if n == 0 { 0 } else { u64::MAX >> n.leading_zeros() }
+}
+
 #[derive(Clone, Debug)]
 struct FnParam {
     name: String,
@@ -132,6 +375,92 @@ struct FnSig {
     ret: Type,
 }
 
+/// Return-type context of the cell body being checked.
+#[derive(Clone, Debug)]
+struct CellReturn {
+    /// The `-> T` annotation, if any.
+    declared: Option<Type>,
+    /// Types of the `return` sites seen so far, for inferring an undeclared type.
+    sites: Vec<(Type, Span)>,
+}
+
+/// A monomorphized copy of a generic cell, produced at its first call with a
+/// given set of type arguments.
+#[derive(Clone, Debug)]
+pub(crate) struct GenericInstance {
+    pub(crate) name: String,
+    pub(crate) cell: CellDef,
+    pub(crate) params: Vec<Type>,
+}
+
+/// Substitutes the instance's type arguments into every `val` annotation of `b`.
+fn subst_block_types(b: &mut Block, args: &HashMap<String, TypeRef>) {
+    for stmt in &mut b.stmts {
+        match stmt {
+            Stmt::StrandDef(sd) => {
+                if let Some(t) = &mut sd.ty {
+                    *t = subst_type_ref(t, args);
+                }
+            }
+            Stmt::If(i) => {
+                subst_block_types(&mut i.then_block, args);
+                if let Some(e) = &mut i.else_block {
+                    subst_block_types(e, args);
+                }
+            }
+            Stmt::Match(m) => m.arms.iter_mut().for_each(|a| subst_block_types(&mut a.body, args)),
+            Stmt::While(w) => subst_block_types(&mut w.body, args),
+            Stmt::For(f) => subst_block_types(&mut f.body, args),
+            Stmt::Scope(sc) => subst_block_types(&mut sc.body, args),
+            Stmt::Region(r) => subst_block_types(&mut r.body, args),
+            Stmt::Defer(d) => subst_block_types(&mut d.body, args),
+            Stmt::UnsafeBlock(u) => subst_block_types(&mut u.body, args),
+            _ => {}
+        }
+    }
+}
+
+/// The type annotation that names `ty`.
+fn type_ref_of(ty: &Type, span: Span) -> TypeRef {
+    let named = |name: &str, args: Vec<TypeArg>| TypeRef {
+        span,
+        name: Ident { node: name.to_string(), span },
+        args,
+        range: None,
+    };
+    let arg = |t: &Type| TypeArg::Type(Box::new(type_ref_of(t, span)));
+    match ty {
+        Type::ConstrainedRange { base, lo, hi } => {
+            let bound = |n: u64| Expr { span, kind: ExprKind::IntLit(n) };
+            TypeRef {
+                range: Some(aura_ast::RangeConstraint { span, lo: bound(*lo), hi: bound(*hi) }),
+                ..type_ref_of(base, span)
+            }
+        }
+        Type::Applied { name, args } => named(name, args.iter().map(arg).collect()),
+        Type::List(elem) => named("List", vec![arg(elem)]),
+        Type::Map(k, v) => named("Map", vec![arg(k), arg(v)]),
+        Type::Tensor { elem, shape } => {
+            let mut args = vec![arg(elem)];
+            args.extend(shape.clone().map(TypeArg::Shape));
+            named("Tensor", args)
+        }
+        other => named(&other.display(), Vec::new()),
+    }
+}
+
+/// What lowering needs to closure-convert a lambda.
+#[derive(Clone, Debug)]
+pub(crate) struct LambdaInfo {
+    /// Outer values the body uses (or assigns), in first-use order; they become its environment.
+    pub(crate) captures: Vec<(String, Type)>,
+    /// Type the body yields.
+    pub(crate) ret: Type,
+}
+
+/// Nested instantiations deeper than this are rejected (polymorphic recursion).
+const MAX_GENERIC_DEPTH: u32 = 32;
+
 #[derive(Clone, Debug)]
 struct TypeAliasDef {
     params: Vec<TypeParamDef>,
@@ -157,12 +486,70 @@ pub struct Checker {
     enum_defs: HashMap<String, EnumDef>,
     functions: HashMap<String, FnSig>,
     extern_cells: HashMap<String, bool>,
+    // Generic cells are only checked per instantiation. Each call site records the
+    // mangled instance it resolved to, keyed by (enclosing instance, call offset).
+    generic_cells: HashMap<String, CellDef>,
+    generic_instances: Vec<GenericInstance>,
+    generic_call_targets: HashMap<(Option<String>, usize), String>,
+    // `m[k]`, `m.len()` and `m.contains(k)` on maps lower to `map.*` runtime calls rather
+    // than the tensor ones lists use; keyed by (enclosing instance, offset, length).
+    map_accesses: HashSet<(Option<String>, usize, usize)>,
+    // Lambdas and the calls made through closure values, keyed by (enclosing instance, offset).
+    lambdas: HashMap<(Option<String>, usize), LambdaInfo>,
+    closure_calls: HashMap<(Option<String>, usize), Type>,
+    // Type of each record literal, keyed by (enclosing instance, offset), and the field
+    // index of each record field read, keyed by (enclosing instance, offset, length) since
+    // `a.b.c` and `a.b` start at the same offset.
+    record_lits: HashMap<(Option<String>, usize), Type>,
+    field_reads: HashMap<(Option<String>, usize, usize), u32>,
+    // Enum type built by each constructor call, keyed by (enclosing instance, offset). A
+    // constructor that leaves type arguments unknown (`Option::None`) takes them from the
+    // type it is checked against.
+    enum_ctors: HashMap<(Option<String>, usize), Type>,
+    // Implicit integer conversions as (from, to), keyed like `field_reads` since an operand
+    // and the expression it starts share an offset.
+    int_casts: HashMap<(Option<String>, usize, usize), (Type, Type)>,
+    // Uses of an `Option` narrowed to `Some` that read its payload, keyed like `int_casts`.
+    option_unwraps: HashSet<(Option<String>, usize, usize)>,
+    current_instance: Option<String>,
+    generic_depth: u32,
+    // Type parameters of the generic body being checked opaquely, with their trait bound;
+    // each stands for `Type::Named(param)`.
+    opaque_params: HashMap<String, Option<String>>,
+    // Module namespaces: `import net::http` makes `http` a module; cells named
+    // `http.f` without `pub` may only be called from inside `http`.
+    imported_modules: HashSet<String>,
+    private_cells: HashSet<String>,
+    current_namespace: Option<String>,
+    // Definitions of every file when the program came from the module resolver; callee
+    // visibility is then decided by the symbol that defines the cell.
+    symbols: Option<SymbolTable>,
     // value scopes
     scopes: Vec<HashMap<String, Type>>,
     mut_scopes: Vec<HashSet<String>>,
+    // Output dims of the model each `Model` binding holds, per scope, when its contract
+    // could be read; `ai.infer` results take their shape from them.
+    model_outputs: Vec<HashMap<String, OutputDims>>,
     
     // Linear type enforcement: track ownership state of each variable
     ownership_states: Vec<HashMap<String, OwnershipState>>,
+    // Borrows held by `val r = &x` bindings, per scope: they end with the scope of `r`.
+    // Borrows passed as call arguments end with the call and sit in `call_loans`.
+    loans: Vec<Vec<Loan>>,
+    // `Option` values known to hold `Some` in each scope, from `is_some`/`is_none` checks
+    // and `Option::Some` match arms. Assigning to a value forgets it.
+    narrowed: Vec<HashSet<String>>,
+    call_loans: Vec<Loan>,
+    // Offsets of the `&x` expressions in a position that can hold a borrow: a call
+    // argument or a `val` initializer.
+    borrow_sites: HashSet<usize>,
+    // How each cell of the program takes each parameter; builtins and extern cells have no entry.
+    param_borrows: HashMap<String, Vec<Option<BorrowKind>>>,
+    // Match arms the arms before them already cover, reported as warnings.
+    unreachable_arms: Vec<Span>,
+    // Where each value last moved, and the use-after-move the last error was about.
+    move_sites: HashMap<String, MoveSite>,
+    move_violation: Option<MoveViolation>,
 
     // If true, accept assignments into constrained ranges without proving them here.
     // This is intended for the LLVM pipeline where `aura-verify` (Z3) is a hard gate.
@@ -177,10 +564,29 @@ pub struct Checker {
 
     // Unsafe context depth for explicit FFI/trust boundaries.
     unsafe_depth: u32,
+    // Number of enclosing `while`/`for` loops; `break`/`continue` need one.
+    loop_depth: u32,
+    // Number of enclosing `defer` bodies; control flow may not leave one.
+    defer_depth: u32,
+    // Values consumed by registered `defer` blocks that have not run yet; they may still be
+    // read, but not moved, before their scope exits.
+    deferred_moves: Vec<(String, Span)>,
+    // Values moved while checking a `defer` body, innermost body last.
+    defer_consumed: Vec<(String, Span)>,
+    // Set while checking a cell body; `return` needs one.
+    cell_return: Option<CellReturn>,
+    // Scope depth at which each enclosing `scope` block starts, innermost last. Bindings
+    // below it outlive the block, so its tasks and token may not be stored in them.
+    scope_bases: Vec<usize>,
+    // Scope depth at which each enclosing `region` block starts, outermost first.
+    region_bases: Vec<usize>,
     // If non-empty, we're inside an async lambda; the value is the scope depth
     // at which the lambda started. Any mutable binding resolved from an outer
     // scope is an invalid capture.
     async_lambda_bases: Vec<usize>,
+    // Lambdas being checked, innermost last: the scope depth the body starts at and the
+    // outer values it uses so far.
+    lambda_frames: Vec<(usize, Vec<(String, Type)>)>,
 }
 
 impl Checker {
@@ -192,16 +598,51 @@ impl Checker {
             enum_defs: HashMap::new(),
             functions: HashMap::new(),
             extern_cells: HashMap::new(),
+            generic_cells: HashMap::new(),
+            generic_instances: Vec::new(),
+            generic_call_targets: HashMap::new(),
+            map_accesses: HashSet::new(),
+            lambdas: HashMap::new(),
+            closure_calls: HashMap::new(),
+            record_lits: HashMap::new(),
+            field_reads: HashMap::new(),
+            enum_ctors: HashMap::new(),
+            int_casts: HashMap::new(),
+            option_unwraps: HashSet::new(),
+            current_instance: None,
+            generic_depth: 0,
+            opaque_params: HashMap::new(),
+            imported_modules: HashSet::new(),
+            private_cells: HashSet::new(),
+            current_namespace: None,
+            symbols: None,
             scopes: vec![HashMap::new()],
             mut_scopes: vec![HashSet::new()],
+            model_outputs: vec![HashMap::new()],
             ownership_states: vec![HashMap::new()],
+            loans: vec![Vec::new()],
+            narrowed: vec![HashSet::new()],
+            call_loans: Vec::new(),
+            borrow_sites: HashSet::new(),
+            param_borrows: HashMap::new(),
+            unreachable_arms: Vec::new(),
+            move_sites: HashMap::new(),
+            move_violation: None,
             defer_range_proofs: false,
 
             cap: CapabilityGraph::default(),
             cap_next: 0,
             verifier: Verifier::new(DummySolver),
             unsafe_depth: 0,
+            loop_depth: 0,
+            defer_depth: 0,
+            deferred_moves: Vec::new(),
+            defer_consumed: Vec::new(),
+            cell_return: None,
+            scope_bases: Vec::new(),
+            region_bases: Vec::new(),
             async_lambda_bases: Vec::new(),
+            lambda_frames: Vec::new(),
         };
 
         // Builtins (minimal; extend later)
@@ -659,6 +1100,58 @@ impl Checker {
                 ret: Type::Unit,
             },
         );
+        checker.functions.insert(
+            "tensor.matmul".to_string(),
+            FnSig {
+                params: vec![
+                    FnParam {
+                        name: "a".to_string(),
+                        ty: Type::tensor_unknown(),
+                    },
+                    FnParam {
+                        name: "b".to_string(),
+                        ty: Type::tensor_unknown(),
+                    },
+                ],
+                ret: Type::tensor_unknown(),
+            },
+        );
+        checker.functions.insert(
+            "tensor.reshape".to_string(),
+            FnSig {
+                params: vec![
+                    FnParam {
+                        name: "t".to_string(),
+                        ty: Type::tensor_unknown(),
+                    },
+                    FnParam {
+                        name: "shape".to_string(),
+                        ty: Type::List(Box::new(Type::U32)),
+                    },
+                ],
+                ret: Type::tensor_unknown(),
+            },
+        );
+        checker.functions.insert(
+            "tensor.concat".to_string(),
+            FnSig {
+                params: vec![
+                    FnParam {
+                        name: "a".to_string(),
+                        ty: Type::tensor_unknown(),
+                    },
+                    FnParam {
+                        name: "b".to_string(),
+                        ty: Type::tensor_unknown(),
+                    },
+                    FnParam {
+                        name: "axis".to_string(),
+                        ty: Type::U32,
+                    },
+                ],
+                ret: Type::tensor_unknown(),
+            },
+        );
 
         // --- std::collections (prototype vector API; currently backed by tensor ops) ---
         checker.functions.insert(
@@ -745,50 +1238,808 @@ impl Checker {
                 ret: Type::tensor_unknown(),
             },
         );
+        checker.functions.insert(
+            "ai.infer_batch".to_string(),
+            FnSig {
+                params: vec![
+                    FnParam {
+                        name: "model".to_string(),
+                        ty: Type::Model,
+                    },
+                    FnParam {
+                        name: "input".to_string(),
+                        ty: Type::tensor_unknown(),
+                    },
+                    FnParam {
+                        name: "batch".to_string(),
+                        ty: Type::U32,
+                    },
+                ],
+                ret: Type::tensor_unknown(),
+            },
+        );
 
-        // --- demo compat ---
+        // --- chan ---
+        // `chan.send`/`chan.recv` values take the channel's element type (see `check_chan_call`).
         checker.functions.insert(
-            "compute_gradient".to_string(),
+            "chan.bounded".to_string(),
             FnSig {
                 params: vec![FnParam {
-                    name: "x".to_string(),
+                    name: "capacity".to_string(),
                     ty: Type::U32,
                 }],
-                ret: Type::tensor_unknown(),
+                ret: Type::channel(Type::Unknown),
+            },
+        );
+        checker.functions.insert(
+            "chan.unbounded".to_string(),
+            FnSig {
+                params: vec![],
+                ret: Type::channel(Type::Unknown),
+            },
+        );
+        checker.functions.insert(
+            "chan.send".to_string(),
+            FnSig {
+                params: vec![
+                    FnParam {
+                        name: "ch".to_string(),
+                        ty: Type::channel(Type::Unknown),
+                    },
+                    FnParam {
+                        name: "value".to_string(),
+                        ty: Type::Unknown,
+                    },
+                ],
+                ret: Type::Unit,
+            },
+        );
+        checker.functions.insert(
+            "chan.recv".to_string(),
+            FnSig {
+                params: vec![FnParam {
+                    name: "ch".to_string(),
+                    ty: Type::channel(Type::Unknown),
+                }],
+                ret: Type::Unknown,
+            },
+        );
+        checker.functions.insert(
+            "chan.close".to_string(),
+            FnSig {
+                params: vec![FnParam {
+                    name: "ch".to_string(),
+                    ty: Type::channel(Type::Unknown),
+                }],
+                ret: Type::Unit,
+            },
+        );
+        checker.functions.insert(
+            "chan.len".to_string(),
+            FnSig {
+                params: vec![FnParam {
+                    name: "ch".to_string(),
+                    ty: Type::channel(Type::Unknown),
+                }],
+                ret: Type::U32,
             },
         );
 
-        checker
-    }
-
-    pub(crate) fn is_void_function(&self, name: &str) -> bool {
-        self.functions
-            .get(name)
-            .is_some_and(|sig| matches!(base_type(&sig.ret), Type::Unit))
-    }
-
-    pub(crate) fn function_ret_type(&self, name: &str) -> Option<&Type> {
-        self.functions.get(name).map(|sig| &sig.ret)
-    }
-
-    pub fn function_param_names(&self, name: &str) -> Option<Vec<String>> {
-        self.functions.get(name).map(|sig| {
-            sig.params
-                .iter()
-                .map(|p| p.name.clone())
-                .collect::<Vec<_>>()
-        })
-    }
-
-    pub fn set_defer_range_proofs(&mut self, defer: bool) {
-        self.defer_range_proofs = defer;
-    }
+        // --- scope ---
+        // Tokens are bound by `scope tok:` blocks; flows poll them to stop early.
+        checker.functions.insert(
+            "scope.cancel".to_string(),
+            FnSig {
+                params: vec![FnParam {
+                    name: "token".to_string(),
+                    ty: Type::cancel_token(),
+                }],
+                ret: Type::Unit,
+            },
+        );
+        checker.functions.insert(
+            "scope.cancelled".to_string(),
+            FnSig {
+                params: vec![FnParam {
+                    name: "token".to_string(),
+                    ty: Type::cancel_token(),
+                }],
+                ret: Type::Bool,
+            },
+        );
 
-    pub fn check_program(&mut self, program: &Program) -> Result<(), SemanticError> {
-        // First pass: register type aliases and cell headers.
-        for stmt in &program.stmts {
-            match stmt {
-                Stmt::Import(i) => {
+        // --- time ---
+        // Milliseconds on a monotonic clock that starts with the program (wrapping at 2^32).
+        checker.functions.insert(
+            "time.now_ms".to_string(),
+            FnSig {
+                params: vec![],
+                ret: Type::U32,
+            },
+        );
+        checker.functions.insert(
+            "time.sleep_ms".to_string(),
+            FnSig {
+                params: vec![FnParam {
+                    name: "ms".to_string(),
+                    ty: Type::U32,
+                }],
+                ret: Type::Unit,
+            },
+        );
+        // Wall-clock seconds since the Unix epoch, and UTC / duration formatting of them.
+        checker.functions.insert(
+            "time.unix_s".to_string(),
+            FnSig {
+                params: vec![],
+                ret: Type::U32,
+            },
+        );
+        // `time.add_ms` traps on overflow, which the verifier rules out at each call;
+        // `time.elapsed_ms` is the difference of two `time.now_ms` readings across a wrap.
+        for (name, a, b) in [("time.add_ms", "t", "ms"), ("time.elapsed_ms", "start", "end")] {
+            checker.functions.insert(
+                name.to_string(),
+                FnSig {
+                    params: vec![
+                        FnParam {
+                            name: a.to_string(),
+                            ty: Type::U32,
+                        },
+                        FnParam {
+                            name: b.to_string(),
+                            ty: Type::U32,
+                        },
+                    ],
+                    ret: Type::U32,
+                },
+            );
+        }
+        for (name, param) in [("time.format_utc", "unix_s"), ("time.format_ms", "ms")] {
+            checker.functions.insert(
+                name.to_string(),
+                FnSig {
+                    params: vec![FnParam {
+                        name: param.to_string(),
+                        ty: Type::U32,
+                    }],
+                    ret: Type::String,
+                },
+            );
+        }
+
+        // --- fs ---
+        // Files are linear: `fs.read` and `fs.write` lend them, `fs.close` consumes them.
+        // Paths are checked against the `[fs]` policy of aura.toml when the program runs.
+        for name in ["fs.open", "fs.create", "fs.append"] {
+            checker.functions.insert(
+                name.to_string(),
+                FnSig {
+                    params: vec![FnParam {
+                        name: "path".to_string(),
+                        ty: Type::String,
+                    }],
+                    ret: Type::file(),
+                },
+            );
+        }
+        checker.functions.insert(
+            "fs.read".to_string(),
+            FnSig {
+                params: vec![FnParam {
+                    name: "file".to_string(),
+                    ty: Type::file(),
+                }],
+                ret: Type::String,
+            },
+        );
+        checker.functions.insert(
+            "fs.write".to_string(),
+            FnSig {
+                params: vec![
+                    FnParam {
+                        name: "file".to_string(),
+                        ty: Type::file(),
+                    },
+                    FnParam {
+                        name: "text".to_string(),
+                        ty: Type::String,
+                    },
+                ],
+                ret: Type::Unit,
+            },
+        );
+        checker.functions.insert(
+            "fs.close".to_string(),
+            FnSig {
+                params: vec![FnParam {
+                    name: "file".to_string(),
+                    ty: Type::file(),
+                }],
+                ret: Type::Unit,
+            },
+        );
+
+        // --- net ---
+        // Sockets are linear like files: `net.recv*` and `net.send` lend them, `net.close`
+        // consumes them. The runtime also walks each socket through `NetworkVerifier`'s state machine.
+        checker.functions.insert(
+            "net.connect".to_string(),
+            FnSig {
+                params: vec![
+                    FnParam {
+                        name: "host".to_string(),
+                        ty: Type::String,
+                    },
+                    FnParam {
+                        name: "port".to_string(),
+                        ty: Type::U32,
+                    },
+                ],
+                ret: Type::socket(),
+            },
+        );
+        checker.functions.insert(
+            "net.send".to_string(),
+            FnSig {
+                params: vec![
+                    FnParam {
+                        name: "socket".to_string(),
+                        ty: Type::socket(),
+                    },
+                    FnParam {
+                        name: "data".to_string(),
+                        ty: Type::String,
+                    },
+                ],
+                ret: Type::Unit,
+            },
+        );
+        checker.functions.insert(
+            "net.recv".to_string(),
+            FnSig {
+                params: vec![FnParam {
+                    name: "socket".to_string(),
+                    ty: Type::socket(),
+                }],
+                ret: Type::String,
+            },
+        );
+        checker.functions.insert(
+            "net.recv_all".to_string(),
+            FnSig {
+                params: vec![FnParam {
+                    name: "socket".to_string(),
+                    ty: Type::socket(),
+                }],
+                ret: Type::String,
+            },
+        );
+        checker.functions.insert(
+            "net.close".to_string(),
+            FnSig {
+                params: vec![FnParam {
+                    name: "socket".to_string(),
+                    ty: Type::socket(),
+                }],
+                ret: Type::Unit,
+            },
+        );
+
+        // --- str ---
+        // Lengths and offsets count bytes. The verifier knows each result's length in terms of
+        // the arguments', and proves the bounds `str.slice` and `str.split_nth` need.
+        checker.functions.insert(
+            "str.len".to_string(),
+            FnSig {
+                params: vec![FnParam {
+                    name: "s".to_string(),
+                    ty: Type::String,
+                }],
+                ret: Type::U32,
+            },
+        );
+        checker.functions.insert(
+            "str.concat".to_string(),
+            FnSig {
+                params: vec![
+                    FnParam {
+                        name: "a".to_string(),
+                        ty: Type::String,
+                    },
+                    FnParam {
+                        name: "b".to_string(),
+                        ty: Type::String,
+                    },
+                ],
+                ret: Type::String,
+            },
+        );
+        checker.functions.insert(
+            "str.slice".to_string(),
+            FnSig {
+                params: vec![
+                    FnParam {
+                        name: "s".to_string(),
+                        ty: Type::String,
+                    },
+                    FnParam {
+                        name: "start".to_string(),
+                        ty: Type::U32,
+                    },
+                    FnParam {
+                        name: "end".to_string(),
+                        ty: Type::U32,
+                    },
+                ],
+                ret: Type::String,
+            },
+        );
+        checker.functions.insert(
+            "str.find".to_string(),
+            FnSig {
+                params: vec![
+                    FnParam {
+                        name: "s".to_string(),
+                        ty: Type::String,
+                    },
+                    FnParam {
+                        name: "needle".to_string(),
+                        ty: Type::String,
+                    },
+                ],
+                ret: Type::U32,
+            },
+        );
+        checker.functions.insert(
+            "str.split_count".to_string(),
+            FnSig {
+                params: vec![
+                    FnParam {
+                        name: "s".to_string(),
+                        ty: Type::String,
+                    },
+                    FnParam {
+                        name: "sep".to_string(),
+                        ty: Type::String,
+                    },
+                ],
+                ret: Type::U32,
+            },
+        );
+        checker.functions.insert(
+            "str.split_nth".to_string(),
+            FnSig {
+                params: vec![
+                    FnParam {
+                        name: "s".to_string(),
+                        ty: Type::String,
+                    },
+                    FnParam {
+                        name: "sep".to_string(),
+                        ty: Type::String,
+                    },
+                    FnParam {
+                        name: "index".to_string(),
+                        ty: Type::U32,
+                    },
+                ],
+                ret: Type::String,
+            },
+        );
+        checker.functions.insert(
+            "str.trim".to_string(),
+            FnSig {
+                params: vec![FnParam {
+                    name: "s".to_string(),
+                    ty: Type::String,
+                }],
+                ret: Type::String,
+            },
+        );
+        for name in ["str.pad_left", "str.pad_right"] {
+            checker.functions.insert(
+                name.to_string(),
+                FnSig {
+                    params: vec![
+                        FnParam {
+                            name: "s".to_string(),
+                            ty: Type::String,
+                        },
+                        FnParam {
+                            name: "width".to_string(),
+                            ty: Type::U32,
+                        },
+                    ],
+                    ret: Type::String,
+                },
+            );
+        }
+
+        // --- math ---
+        // `math.pow` traps when the result does not fit a u32. The f64 calls follow IEEE 754:
+        // the verifier asks for `x >= 0.0` before `math.sqrt(x)` rather than allow a NaN.
+        for (name, a, b) in [("math.min", "a", "b"), ("math.max", "a", "b"), ("math.pow", "base", "exp")] {
+            checker.functions.insert(
+                name.to_string(),
+                FnSig {
+                    params: vec![
+                        FnParam {
+                            name: a.to_string(),
+                            ty: Type::U32,
+                        },
+                        FnParam {
+                            name: b.to_string(),
+                            ty: Type::U32,
+                        },
+                    ],
+                    ret: Type::U32,
+                },
+            );
+        }
+        for name in ["math.abs", "math.sqrt", "math.sin", "math.cos"] {
+            checker.functions.insert(
+                name.to_string(),
+                FnSig {
+                    params: vec![FnParam {
+                        name: "x".to_string(),
+                        ty: Type::F64,
+                    }],
+                    ret: Type::F64,
+                },
+            );
+        }
+        checker.functions.insert(
+            "math.powf".to_string(),
+            FnSig {
+                params: vec![
+                    FnParam {
+                        name: "x".to_string(),
+                        ty: Type::F64,
+                    },
+                    FnParam {
+                        name: "y".to_string(),
+                        ty: Type::F64,
+                    },
+                ],
+                ret: Type::F64,
+            },
+        );
+
+        // --- demo compat ---
+        checker.functions.insert(
+            "compute_gradient".to_string(),
+            FnSig {
+                params: vec![FnParam {
+                    name: "x".to_string(),
+                    ty: Type::U32,
+                }],
+                ret: Type::tensor_unknown(),
+            },
+        );
+
+        checker
+    }
+
+    pub(crate) fn is_void_function(&self, name: &str) -> bool {
+        self.functions
+            .get(name)
+            .is_some_and(|sig| matches!(base_type(&sig.ret), Type::Unit))
+    }
+
+    pub(crate) fn function_ret_type(&self, name: &str) -> Option<&Type> {
+        self.functions.get(name).map(|sig| &sig.ret)
+    }
+
+    /// A user cell (plain or generic) with this exact name; such calls are never method sugar.
+    pub(crate) fn is_known_cell(&self, name: &str) -> bool {
+        self.functions.contains_key(name) || self.generic_cells.contains_key(name)
+    }
+
+    /// Unqualified calls inside `cell ns::f` resolve to `ns::g` before a global `g`.
+    pub(crate) fn resolve_callee_in(&self, namespace: Option<&str>, name: &str) -> String {
+        if let Some(ns) = namespace
+            && !name.contains('.')
+        {
+            let qualified = format!("{ns}.{name}");
+            if self.is_known_cell(&qualified) {
+                return qualified;
+            }
+        }
+        name.to_string()
+    }
+
+    pub(crate) fn generic_instances(&self) -> &[GenericInstance] {
+        &self.generic_instances
+    }
+
+    /// Every generic instance the last `check_program` created, as a plain cell named after
+    /// the instance (`twice__u32`) with its type parameters replaced by their arguments in the
+    /// parameter, result and `val` types.
+    pub fn instance_cells(&self) -> Vec<CellDef> {
+        self.generic_instances
+            .iter()
+            .map(|inst| {
+                let mut cell = inst.cell.clone();
+                // Each parameter's declared type, with its instantiated one.
+                let mut args = HashMap::new();
+                for (p, ty) in cell.params.iter().zip(&inst.params) {
+                    if cell.type_params.iter().any(|tp| tp.name.node == p.ty.name.node) {
+                        args.insert(p.ty.name.node.clone(), type_ref_of(ty, p.ty.span));
+                    }
+                }
+                for p in &mut cell.params {
+                    p.ty = subst_type_ref(&p.ty, &args);
+                }
+                if let Some(t) = &mut cell.ret {
+                    *t = subst_type_ref(t, &args);
+                }
+                subst_block_types(&mut cell.body, &args);
+                cell.name.node = inst.name.clone();
+                cell.type_params.clear();
+                cell
+            })
+            .collect()
+    }
+
+    /// Mangled instance a generic call at `span` resolved to, if any.
+    pub(crate) fn generic_call_target(&self, instance: Option<&str>, span: Span) -> Option<&str> {
+        self.generic_call_targets
+            .get(&(instance.map(str::to_string), span.offset()))
+            .map(String::as_str)
+    }
+
+    /// Whether the index or method call at `span` operates on a map.
+    pub(crate) fn is_map_access(&self, instance: Option<&str>, span: Span) -> bool {
+        self.map_accesses
+            .contains(&(instance.map(str::to_string), span.offset(), span.len()))
+    }
+
+    /// Captures and result type of the lambda at `span`.
+    pub(crate) fn lambda_info(&self, instance: Option<&str>, span: Span) -> Option<&LambdaInfo> {
+        self.lambdas.get(&(instance.map(str::to_string), span.offset()))
+    }
+
+    /// Result type of the call at `span` if it invokes a closure value rather than a cell.
+    pub(crate) fn closure_call_ret(&self, instance: Option<&str>, span: Span) -> Option<&Type> {
+        self.closure_calls.get(&(instance.map(str::to_string), span.offset()))
+    }
+
+    /// The integer conversion `(from, to)` applied to the value of the expression at `span`.
+    pub(crate) fn int_cast(&self, instance: Option<&str>, span: Span) -> Option<&(Type, Type)> {
+        self.int_casts.get(&(instance.map(str::to_string), span.offset(), span.len()))
+    }
+
+    /// Whether the expression at `span` reads the payload of an `Option` narrowed to `Some`.
+    pub(crate) fn option_unwrap(&self, instance: Option<&str>, span: Span) -> bool {
+        self.option_unwraps.contains(&(instance.map(str::to_string), span.offset(), span.len()))
+    }
+
+    /// `is_some(x)` or `is_none(x)`, unless a cell takes that name: whether it tests for
+    /// `Some`, and `x`.
+    pub(crate) fn option_test<'e>(&self, expr: &'e Expr) -> Option<(bool, &'e Expr)> {
+        let ExprKind::Call { callee, args, trailing: None } = &expr.kind else {
+            return None;
+        };
+        let ExprKind::Ident(id) = &callee.kind else {
+            return None;
+        };
+        let some = match id.node.as_str() {
+            "is_some" => true,
+            "is_none" => false,
+            _ => return None,
+        };
+        match args.as_slice() {
+            [CallArg::Positional(arg)] if !self.is_known_cell(&id.node) => Some((some, arg)),
+            _ => None,
+        }
+    }
+
+    /// Payload type of an `Option<T>`: an enum named `Option` with `Some(T)` and `None`.
+    pub(crate) fn option_payload(&self, ty: &Type) -> Option<Type> {
+        let Some(("Option", [payload])) = applied_name_and_args(base_type(ty)) else {
+            return None;
+        };
+        let def = self.enum_defs.get("Option")?;
+        let shape = |name: &str, arity: usize| {
+            def.variants.iter().any(|v| v.name.node == name && v.fields.len() == arity)
+        };
+        (shape("Some", 1) && shape("None", 0)).then(|| payload.clone())
+    }
+
+    /// Type of the record literal at `span`.
+    pub(crate) fn record_literal_type(&self, instance: Option<&str>, span: Span) -> Option<&Type> {
+        self.record_lits.get(&(instance.map(str::to_string), span.offset()))
+    }
+
+    /// Declaration-order index of the record field read by the member expression at `span`.
+    pub(crate) fn record_field_index(&self, instance: Option<&str>, span: Span) -> Option<u32> {
+        self.field_reads
+            .get(&(instance.map(str::to_string), span.offset(), span.len()))
+            .copied()
+    }
+
+    /// Enum type built by the constructor call at `span`.
+    pub(crate) fn enum_ctor_type(&self, instance: Option<&str>, span: Span) -> Option<&Type> {
+        self.enum_ctors.get(&(instance.map(str::to_string), span.offset()))
+    }
+
+    pub(crate) fn record_def(&self, name: &str) -> Option<&RecordDef> {
+        self.record_defs.get(name)
+    }
+
+    /// Fields of the record type `ty`, in declaration order, with its type arguments applied.
+    pub(crate) fn record_fields(&self, ty: &Type) -> Option<Vec<(String, Type)>> {
+        let (name, args) = applied_name_and_args(base_type(ty))?;
+        let def = self.record_defs.get(name)?;
+        let subst: HashMap<String, Type> = def
+            .params
+            .iter()
+            .zip(args.iter())
+            .map(|(p, a)| (p.name.node.clone(), a.clone()))
+            .collect();
+        def.fields
+            .iter()
+            .map(|f| {
+                let ty = if def.params.is_empty() {
+                    self.resolve_type_ref(&f.ty)
+                } else {
+                    self.resolve_type_ref_with_type_params(&f.ty, &subst)
+                };
+                ty.ok().map(|t| (f.name.node.clone(), t))
+            })
+            .collect()
+    }
+
+    /// Variant names and payload types of enum type `ty`, if it is one, instantiated at
+    /// its type arguments.
+    pub(crate) fn enum_variants(&self, ty: &Type) -> Option<Vec<(String, Vec<Type>)>> {
+        let (name, args) = applied_name_and_args(base_type(ty))?;
+        let def = self.enum_defs.get(name)?;
+        let subst: HashMap<String, Type> = def
+            .params
+            .iter()
+            .zip(args.iter())
+            .map(|(p, a)| (p.name.node.clone(), a.clone()))
+            .collect();
+        def.variants
+            .iter()
+            .map(|v| {
+                let fields = v
+                    .fields
+                    .iter()
+                    .map(|f| {
+                        if def.params.is_empty() {
+                            self.resolve_type_ref(&f.ty).ok()
+                        } else {
+                            self.resolve_type_ref_with_type_params(&f.ty, &subst).ok()
+                        }
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                Some((v.name.node.clone(), fields))
+            })
+            .collect()
+    }
+
+    /// Gives an enum constructor that left type arguments unknown the type it is checked
+    /// against, so it is laid out like the values it flows into.
+    fn refine_enum_ctor(&mut self, expected: &Type, rhs: &Expr) {
+        if !matches!(rhs.kind, ExprKind::Call { .. }) {
+            return;
+        }
+        let key = (self.current_instance.clone(), rhs.span.offset());
+        let expected = base_type(expected);
+        let Some(Type::Applied { name, .. }) = self.enum_ctors.get(&key).filter(|t| mentions_unknown(t)) else {
+            return;
+        };
+        if matches!(expected, Type::Applied { name: exp, .. } if exp == name) && !mentions_unknown(expected) {
+            self.enum_ctors.insert(key, expected.clone());
+        }
+    }
+
+    /// Solves the unknown parts of the type of the binding `expr` names or borrows, or of the channel a
+    /// `chan.recv(ch)` reads, from `evidence`, the type a use of it needs. Returns whether
+    /// anything was solved.
+    fn refine_expr(&mut self, expr: &Expr, evidence: &Type) -> bool {
+        match &expr.kind {
+            ExprKind::Ident(id) => self.refine_binding(&id.node, evidence),
+            ExprKind::Borrow { expr, .. } => self.refine_expr(expr, evidence),
+            ExprKind::Call { callee, args, .. } if expr_to_callee_name(callee) == "chan.recv" && args.len() == 1 => {
+                self.refine_expr(call_arg_value(&args[0]), &Type::channel(evidence.clone()))
+            }
+            _ => false,
+        }
+    }
+
+    fn refine_binding(&mut self, name: &str, evidence: &Type) -> bool {
+        let Some(idx) = self.lookup_scope_index(name) else {
+            return false;
+        };
+        let old = &self.scopes[idx][name];
+        if !mentions_unknown(old) {
+            return false;
+        }
+        let solved = fill_unknowns(old, evidence);
+        if solved == *old {
+            return false;
+        }
+        // Lambdas being checked that already captured it see the solved type too.
+        for (base, captures) in &mut self.lambda_frames {
+            if idx < *base {
+                for (_, ty) in captures.iter_mut().filter(|(n, _)| n == name) {
+                    *ty = solved.clone();
+                }
+            }
+        }
+        self.scopes[idx].insert(name.to_string(), solved);
+        true
+    }
+
+    /// Parameter types of a cell or extern cell.
+    pub(crate) fn function_param_types(&self, name: &str) -> Option<Vec<&Type>> {
+        self.functions.get(name).map(|sig| sig.params.iter().map(|p| &p.ty).collect())
+    }
+
+    fn record_map_access(&mut self, span: Span) {
+        self.map_accesses
+            .insert((self.current_instance.clone(), span.offset(), span.len()));
+    }
+
+    pub fn function_param_names(&self, name: &str) -> Option<Vec<String>> {
+        self.functions.get(name).map(|sig| {
+            sig.params
+                .iter()
+                .map(|p| p.name.clone())
+                .collect::<Vec<_>>()
+        })
+    }
+
+    pub fn set_defer_range_proofs(&mut self, defer: bool) {
+        self.defer_range_proofs = defer;
+    }
+
+    /// Resolves module cells through `symbols`, the table the module resolver built for the
+    /// program, rather than through the qualified names it gave them.
+    pub fn set_symbols(&mut self, symbols: SymbolTable) {
+        self.symbols = Some(symbols);
+    }
+
+    /// Type of the top-level `val name` left by the last `check_program`, refinements included.
+    pub fn top_level_val_type(&self, name: &str) -> Option<Type> {
+        self.scopes.first().and_then(|scope| scope.get(name)).cloned()
+    }
+
+    /// Infers `expr` as if it followed the program last passed to `check_program`, the way a
+    /// REPL types an input line against the definitions entered before it.
+    pub fn infer_top_level_expr(&mut self, expr: &Expr) -> Result<Type, SemanticError> {
+        self.infer_expr(expr)
+    }
+
+    /// The use-after-move `err` reports, with what an explanation of it needs, when `err` came
+    /// from the last `check_program` and is one.
+    pub fn move_violation(&self, err: &SemanticError) -> Option<&MoveViolation> {
+        self.move_violation.as_ref().filter(|v| v.used_at == err.span)
+    }
+
+    /// Warnings for the match arms the last `check_program` found unreachable, within the
+    /// first `source.len()` bytes of the program; `file` names the source in them.
+    pub fn match_lints(&self, file: &str, source: &str) -> Vec<Lint> {
+        self.unreachable_arms
+            .iter()
+            .filter_map(|&span| lint_at(file, source, span, DiagnosticFactory::unreachable_arm))
+            .collect()
+    }
+
+    pub fn check_program(&mut self, program: &Program) -> Result<(), SemanticError> {
+        self.move_violation = None;
+        self.unreachable_arms.clear();
+        // Constants are substituted first so range bounds and shapes see plain literals.
+        let folded = fold_consts(program)?;
+        let program = &*folded;
+        // First pass: register type aliases and cell headers.
+        for stmt in &program.stmts {
+            match stmt {
+                Stmt::Import(i) => {
                     self.handle_import(i)?;
                 }
                 Stmt::MacroDef(_) | Stmt::MacroCall(_) => {}
@@ -824,7 +2075,27 @@ impl Checker {
                     self.enum_defs.insert(e.name.node.clone(), e.clone());
                     self.define_type_placeholder(&e.name)?;
                 }
+                Stmt::CellDef(cell) if !cell.type_params.is_empty() => {
+                    for p in &cell.type_params {
+                        if let Some(bound) = &p.bound
+                            && !self.traits.contains(&bound.node)
+                        {
+                            return Err(SemanticError {
+                                message: format!(
+                                    "unknown trait '{}' in type parameter constraint",
+                                    bound.node
+                                ),
+                                span: bound.span,
+                            });
+                        }
+                    }
+                    self.note_cell_visibility(cell);
+                    self.note_param_borrows(&cell.name.node, &cell.params);
+                    self.generic_cells.insert(cell.name.node.clone(), cell.clone());
+                }
                 Stmt::CellDef(cell) => {
+                    self.note_cell_visibility(cell);
+                    self.note_param_borrows(&cell.name.node, &cell.params);
                     let sig = self.signature_from_cell(cell)?;
                     self.functions.insert(cell.name.node.clone(), sig);
                 }
@@ -848,6 +2119,10 @@ impl Checker {
                 Stmt::MacroDef(_) | Stmt::MacroCall(_) => {}
                 Stmt::TypeAlias(_) => {}
                 Stmt::TraitDef(_) | Stmt::RecordDef(_) | Stmt::EnumDef(_) => {}
+                // Checked once here with opaque type parameters, then per instantiation.
+                Stmt::CellDef(cell) if !cell.type_params.is_empty() => {
+                    self.check_generic_body(cell)?;
+                }
                 Stmt::CellDef(cell) => {
                     self.check_cell(cell)?;
                 }
@@ -857,6 +2132,8 @@ impl Checker {
                     let _ = self.check_block(&s.body)?;
                     self.unsafe_depth -= 1;
                 }
+                Stmt::Scope(s) => self.check_scope_block(s)?,
+                Stmt::Region(r) => self.check_region_block(r)?,
                 Stmt::Layout(lb) => {
                     self.check_layout_block(lb)?;
                 }
@@ -878,11 +2155,38 @@ impl Checker {
                 Stmt::If(i) => {
                     self.check_if(i)?;
                 }
-                Stmt::Match(m) => {
-                    self.check_match(m)?;
+                Stmt::Match(m) => {
+                    self.check_match(m)?;
+                }
+                Stmt::While(w) => {
+                    self.check_while(w)?;
+                }
+                Stmt::For(f) => {
+                    self.check_for(f)?;
+                }
+                Stmt::Break(b) => {
+                    return Err(SemanticError {
+                        message: "'break' outside of a loop".to_string(),
+                        span: b.span,
+                    });
+                }
+                Stmt::Continue(c) => {
+                    return Err(SemanticError {
+                        message: "'continue' outside of a loop".to_string(),
+                        span: c.span,
+                    });
+                }
+                Stmt::Return(r) => {
+                    return Err(SemanticError {
+                        message: "'return' outside of a cell".to_string(),
+                        span: r.span,
+                    });
                 }
-                Stmt::While(w) => {
-                    self.check_while(w)?;
+                Stmt::Defer(d) => {
+                    return Err(SemanticError {
+                        message: "'defer' outside of a cell".to_string(),
+                        span: d.span,
+                    });
                 }
                 Stmt::Requires(r) => {
                     let ty = self.infer_expr(&r.expr)?;
@@ -926,9 +2230,65 @@ impl Checker {
             }
         }
 
+        // Cells declaring `uses [..]` may not perform anything else, directly or through calls.
+        if let Some(v) = crate::effects::check_declared_effects(program).into_iter().next() {
+            return Err(SemanticError {
+                message: v.message,
+                span: v.span,
+            });
+        }
+
+        // Cells calling each other in a cycle need a measure that shrinks on every call.
+        crate::termination::check_termination(program)?;
+
+        Ok(())
+    }
+
+    fn note_cell_visibility(&mut self, cell: &CellDef) {
+        if !cell.is_pub && cell.name.node.contains('.') {
+            self.private_cells.insert(cell.name.node.clone());
+        }
+    }
+
+    fn check_callee_visible(&self, name: &str, span: Span) -> Result<(), SemanticError> {
+        if let Some(symbols) = &self.symbols {
+            let Some(sym) = symbols.resolve(name).filter(|s| s.kind == SymbolKind::Cell) else {
+                return Ok(());
+            };
+            // Stdlib cells are injected unqualified and callable from anywhere.
+            if !sym.is_pub
+                && !sym.from_std
+                && let Some(namespace) = sym.module.last()
+                && self.current_namespace.as_ref() != Some(namespace)
+            {
+                return Err(SemanticError {
+                    message: format!("cell '{}' is private to module '{}'", sym.qualified, sym.module.join("::")),
+                    span,
+                });
+            }
+            return Ok(());
+        }
+        let Some((module, item)) = name.rsplit_once('.') else {
+            return Ok(());
+        };
+        if self.private_cells.contains(name)
+            && self.imported_modules.contains(module)
+            && self.current_namespace.as_deref() != Some(module)
+        {
+            return Err(SemanticError {
+                message: format!("cell '{item}' is private to module '{module}'"),
+                span,
+            });
+        }
         Ok(())
     }
 
+    /// Records how `cell` takes each parameter when it borrows any of them.
+    fn note_param_borrows(&mut self, cell: &str, params: &[aura_ast::Param]) {
+        self.param_borrows
+            .insert(cell.to_string(), params.iter().map(|p| p.borrow).collect());
+    }
+
     fn signature_from_cell(&mut self, cell: &CellDef) -> Result<FnSig, SemanticError> {
         let mut params = Vec::new();
         for p in &cell.params {
@@ -937,15 +2297,26 @@ impl Checker {
                 ty: self.resolve_type_ref(&p.ty)?,
             });
         }
-        Ok(FnSig {
-            params,
-            ret: Type::Unknown,
-        })
+        // A declared return type is known before the body is checked (e.g. for recursion).
+        let ret = match &cell.ret {
+            Some(t) => self.resolve_type_ref(t)?,
+            None => Type::Unknown,
+        };
+        Ok(FnSig { params, ret })
     }
 
     fn signature_from_extern_cell(&mut self, ext: &ExternCell) -> Result<FnSig, SemanticError> {
         let mut params = Vec::new();
         for p in &ext.params {
+            if p.borrow.is_some() {
+                return Err(SemanticError {
+                    message: format!(
+                        "extern cell '{}' cannot borrow parameter '{}'; native code takes values",
+                        ext.name.node, p.name.node
+                    ),
+                    span: p.span,
+                });
+            }
             params.push(FnParam {
                 name: p.name.node.clone(),
                 ty: self.resolve_type_ref(&p.ty)?,
@@ -956,13 +2327,31 @@ impl Checker {
     }
 
     fn check_cell(&mut self, cell: &CellDef) -> Result<(), SemanticError> {
+        if cell.is_test && !cell.params.is_empty() {
+            return Err(SemanticError {
+                message: format!("test cell '{}' must not take parameters", cell.name.node),
+                span: cell.params[0].span,
+            });
+        }
+
+        let saved_namespace = std::mem::replace(&mut self.current_namespace, cell_namespace(cell));
         self.push_scope();
         for p in &cell.params {
             let ty = self.resolve_type_ref(&p.ty)?;
-            self.define_val(&p.name, ty, p.mutable)?;
+            self.define_param(p, ty)?;
+        }
+        if let Some(dec) = &cell.decreases {
+            let dec_ty = self.infer_expr(dec)?;
+            if !is_u32_like(&dec_ty) {
+                return Err(SemanticError {
+                    message: format!("decreases expects integer, got {}", dec_ty.display()),
+                    span: dec.span,
+                });
+            }
         }
-        let ret_ty = self.check_block(&cell.body)?;
+        let ret_ty = self.check_cell_body(cell)?;
         self.pop_scope();
+        self.current_namespace = saved_namespace;
 
         // Update function return type.
         if let Some(sig) = self.functions.get_mut(&cell.name.node) {
@@ -972,6 +2361,411 @@ impl Checker {
         Ok(())
     }
 
+    /// Infers type arguments for a call to a generic cell, checks their bounds and
+    /// returns the name of the monomorphized instance (checking its body on first use).
+    fn instantiate_generic_call(
+        &mut self,
+        name: &str,
+        call_span: Span,
+        args: &[CallArg],
+    ) -> Result<String, SemanticError> {
+        let cell = self.generic_cells.get(name).cloned().expect("generic cell exists");
+
+        // Only the parameter names matter for matching named arguments.
+        let shape = FnSig {
+            params: cell
+                .params
+                .iter()
+                .map(|p| FnParam {
+                    name: p.name.node.clone(),
+                    ty: Type::Unknown,
+                })
+                .collect(),
+            ret: Type::Unknown,
+        };
+        let ordered = self.resolve_call_args_against_sig(call_span, args, &shape)?;
+
+        let mut subst: HashMap<String, Type> = HashMap::new();
+        for (i, (p, arg)) in cell.params.iter().zip(ordered).enumerate() {
+            let actual = self.infer_expr(arg)?;
+            self.unify_type_params_in_typeref(&p.ty, base_type(&actual), &cell.type_params, &mut subst)
+                .map_err(|mut e| {
+                    e.message = format!("arg {i}: {}", e.message);
+                    e.span = arg.span;
+                    e
+                })?;
+        }
+
+        let mut type_args = Vec::with_capacity(cell.type_params.len());
+        for tp in &cell.type_params {
+            let ty = match subst.get(&tp.name.node) {
+                Some(t) if *t != Type::Unknown => t.clone(),
+                _ => {
+                    return Err(SemanticError {
+                        message: format!(
+                            "cannot infer type parameter '{}' of generic cell '{name}'",
+                            tp.name.node
+                        ),
+                        span: call_span,
+                    });
+                }
+            };
+            if let Some(bound) = &tp.bound
+                && !self.satisfies_trait(&ty, &bound.node)
+            {
+                return Err(SemanticError {
+                    message: format!(
+                        "type argument {} for '{}' does not satisfy trait bound '{}'",
+                        ty.display(),
+                        tp.name.node,
+                        bound.node
+                    ),
+                    span: call_span,
+                });
+            }
+            type_args.push(ty);
+        }
+
+        let mangled = mangle_instance_name(name, &type_args);
+        self.generic_call_targets
+            .insert((self.current_instance.clone(), call_span.offset()), mangled.clone());
+        if self.functions.contains_key(&mangled) {
+            return Ok(mangled);
+        }
+        if self.generic_depth >= MAX_GENERIC_DEPTH {
+            return Err(SemanticError {
+                message: format!("generic instantiation of '{name}' is nested too deeply"),
+                span: call_span,
+            });
+        }
+
+        let mut params = Vec::with_capacity(cell.params.len());
+        for p in &cell.params {
+            params.push(FnParam {
+                name: p.name.node.clone(),
+                ty: self.resolve_type_ref_with_type_params(&p.ty, &subst)?,
+            });
+        }
+        if let Some(modes) = self.param_borrows.get(name).cloned() {
+            self.param_borrows.insert(mangled.clone(), modes);
+        }
+        // Registered before the body is checked so recursive calls resolve.
+        self.functions.insert(
+            mangled.clone(),
+            FnSig {
+                params: params.clone(),
+                ret: Type::Unknown,
+            },
+        );
+
+        let ret = self
+            .check_generic_instance(&cell, &mangled, &subst, &params)
+            .map_err(|mut e| {
+                e.message = format!("in instantiation '{mangled}': {}", e.message);
+                e
+            })?;
+        if let Some(sig) = self.functions.get_mut(&mangled) {
+            sig.ret = ret;
+        }
+        self.generic_instances.push(GenericInstance {
+            name: mangled.clone(),
+            cell,
+            params: params.into_iter().map(|p| p.ty).collect(),
+        });
+        Ok(mangled)
+    }
+
+    /// Checks a generic body with its type parameters bound, isolated from the
+    /// caller's locals, capabilities and unsafe/async context.
+    fn check_generic_instance(
+        &mut self,
+        cell: &CellDef,
+        mangled: &str,
+        subst: &HashMap<String, Type>,
+        params: &[FnParam],
+    ) -> Result<Type, SemanticError> {
+        let globals = (
+            self.scopes[0].clone(),
+            self.mut_scopes[0].clone(),
+            self.ownership_states[0].clone(),
+        );
+        let saved_scopes = std::mem::replace(&mut self.scopes, vec![globals.0]);
+        let saved_mut = std::mem::replace(&mut self.mut_scopes, vec![globals.1]);
+        let saved_own = std::mem::replace(&mut self.ownership_states, vec![globals.2]);
+        let global_loans = self.loans[0].clone();
+        let saved_loans = std::mem::replace(&mut self.loans, vec![global_loans]);
+        let saved_call_loans = std::mem::take(&mut self.call_loans);
+        let global_models = self.model_outputs[0].clone();
+        let saved_models = std::mem::replace(&mut self.model_outputs, vec![global_models]);
+        let saved_cap = self.cap.clone();
+        let saved_async = std::mem::take(&mut self.async_lambda_bases);
+        let saved_lambdas = std::mem::take(&mut self.lambda_frames);
+        let saved_unsafe = std::mem::replace(&mut self.unsafe_depth, 0);
+        let saved_loop = std::mem::replace(&mut self.loop_depth, 0);
+        let saved_scope_bases = std::mem::take(&mut self.scope_bases);
+        let saved_region_bases = std::mem::take(&mut self.region_bases);
+        let saved_instance = self.current_instance.replace(mangled.to_string());
+        let saved_namespace = std::mem::replace(&mut self.current_namespace, cell_namespace(cell));
+        let saved_aliases: Vec<(String, Option<AliasEntry>)> = subst
+            .iter()
+            .map(|(k, t)| {
+                let prev = self.type_aliases.insert(k.clone(), AliasEntry::Mono(t.clone()));
+                (k.clone(), prev)
+            })
+            .collect();
+        self.generic_depth += 1;
+
+        let result: Result<Type, SemanticError> = (|| {
+            self.push_scope();
+            for (p, fp) in cell.params.iter().zip(params) {
+                self.define_param(p, fp.ty.clone())?;
+            }
+            let ret = self.check_cell_body(cell)?;
+            self.pop_scope();
+            Ok(ret)
+        })();
+
+        self.generic_depth -= 1;
+        for (k, prev) in saved_aliases {
+            match prev {
+                Some(entry) => {
+                    self.type_aliases.insert(k, entry);
+                }
+                None => {
+                    self.type_aliases.remove(&k);
+                }
+            }
+        }
+        self.current_namespace = saved_namespace;
+        self.current_instance = saved_instance;
+        self.unsafe_depth = saved_unsafe;
+        self.loop_depth = saved_loop;
+        self.scope_bases = saved_scope_bases;
+        self.region_bases = saved_region_bases;
+        self.async_lambda_bases = saved_async;
+        self.lambda_frames = saved_lambdas;
+        self.cap = saved_cap;
+        self.ownership_states = saved_own;
+        self.call_loans = saved_call_loans;
+        self.loans = saved_loans;
+        self.model_outputs = saved_models;
+        self.mut_scopes = saved_mut;
+        self.scopes = saved_scopes;
+        result
+    }
+
+    /// Checks a generic body once with each type parameter opaque: a `T` only meets another
+    /// `T` and supports just what its bound allows. A cell that is never called is checked too.
+    /// Instances this body would create are discarded; each call site makes its own.
+    fn check_generic_body(&mut self, cell: &CellDef) -> Result<(), SemanticError> {
+        let subst: HashMap<String, Type> = cell
+            .type_params
+            .iter()
+            .map(|tp| (tp.name.node.clone(), Type::Named(tp.name.node.clone())))
+            .collect();
+        let opaque = cell
+            .type_params
+            .iter()
+            .map(|tp| (tp.name.node.clone(), tp.bound.as_ref().map(|b| b.node.clone())))
+            .collect();
+        let saved_opaque = std::mem::replace(&mut self.opaque_params, opaque);
+        let saved_functions = self.functions.clone();
+        let saved_instances = self.generic_instances.len();
+        let saved_targets = self.generic_call_targets.clone();
+
+        let result = (|| {
+            let mut params = Vec::with_capacity(cell.params.len());
+            for p in &cell.params {
+                params.push(FnParam {
+                    name: p.name.node.clone(),
+                    ty: self.resolve_type_ref_with_type_params(&p.ty, &subst)?,
+                });
+            }
+            self.check_generic_instance(cell, &cell.name.node, &subst, &params)
+        })();
+
+        self.generic_call_targets = saved_targets;
+        self.generic_instances.truncate(saved_instances);
+        self.functions = saved_functions;
+        self.opaque_params = saved_opaque;
+        result.map(|_| ())
+    }
+
+    /// The bound of an opaque type parameter `ty` stands for, if it is one.
+    fn opaque_param<'a>(&'a self, ty: &Type) -> Option<(&'a str, Option<&'a str>)> {
+        match base_type(ty) {
+            Type::Named(name) => self
+                .opaque_params
+                .get_key_value(name)
+                .map(|(name, bound)| (name.as_str(), bound.as_deref())),
+            _ => None,
+        }
+    }
+
+    /// [`type_satisfies_trait`], where an opaque type parameter satisfies its own bound.
+    fn satisfies_trait(&self, ty: &Type, tr: &str) -> bool {
+        match self.opaque_param(ty) {
+            Some((_, bound)) => bound == Some(tr),
+            None => type_satisfies_trait(ty, tr),
+        }
+    }
+
+    /// Types `left op right` when either side is an opaque type parameter: both sides must be
+    /// the same parameter, and its bound must provide the operator.
+    fn opaque_binop(&self, op: &BinOp, lt: &Type, rt: &Type, span: Span) -> Result<Option<Type>, SemanticError> {
+        let (l, r) = (self.opaque_param(lt), self.opaque_param(rt));
+        let Some((name, bound)) = l.or(r) else {
+            // What an opaque value yields (`f()` for `f: T`) is unknown until instantiation.
+            if !self.opaque_params.is_empty() && (*lt == Type::Unknown || *rt == Type::Unknown) {
+                return Ok(Some(match op {
+                    BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div => {
+                        if *lt == Type::Unknown { widen_ranges(rt) } else { widen_ranges(lt) }
+                    }
+                    BinOp::BitAnd | BinOp::BitOr | BinOp::BitXor | BinOp::Shl | BinOp::Shr => Type::U32,
+                    _ => Type::Bool,
+                }));
+            }
+            return Ok(None);
+        };
+        if l != r {
+            return Err(SemanticError {
+                message: format!(
+                    "operands must both be type parameter '{name}'; got {},{}",
+                    lt.display(),
+                    rt.display()
+                ),
+                span,
+            });
+        }
+        let numeric = bound == Some("Numeric");
+        let result = match op {
+            BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div if numeric => Type::Named(name.to_string()),
+            BinOp::Lt | BinOp::Gt | BinOp::Le | BinOp::Ge if numeric => Type::Bool,
+            BinOp::Eq | BinOp::Ne if bound.is_some() => Type::Bool,
+            _ => {
+                let needs = match op {
+                    BinOp::Eq | BinOp::Ne => "a trait bound such as 'Eq'",
+                    _ => "the 'Numeric' bound",
+                };
+                return Err(SemanticError {
+                    message: format!("this operator needs {needs} on type parameter '{name}'"),
+                    span,
+                });
+            }
+        };
+        Ok(Some(result))
+    }
+
+    /// Checks a cell body and returns the cell's result type: the declared `-> T`, or the
+    /// type that the final yield and every `return` agree on.
+    fn check_cell_body(&mut self, cell: &CellDef) -> Result<Type, SemanticError> {
+        let declared = match &cell.ret {
+            Some(t) => Some(self.resolve_type_ref(t)?),
+            None => None,
+        };
+        let saved = self.cell_return.replace(CellReturn {
+            declared: declared.clone(),
+            sites: Vec::new(),
+        });
+        let body = self.check_block(&cell.body);
+        let ctx = std::mem::replace(&mut self.cell_return, saved).expect("cell return context");
+        let body_ty = body?;
+        let falls_through = !block_always_returns(&cell.body);
+        if declared.as_ref().is_some_and(mentions_cancel_token) || (falls_through && mentions_cancel_token(&body_ty)) {
+            return Err(SemanticError {
+                message: format!(
+                    "cell '{}' cannot return a scope's cancellation token: it would outlive the scope",
+                    cell.name.node
+                ),
+                span: cell.name.span,
+            });
+        }
+
+        if let Some(ty) = declared {
+            if falls_through {
+                match &cell.body.yield_expr {
+                    Some(y) => self.check_assignable(&ty, &body_ty, y)?,
+                    None if ty != Type::Unit => {
+                        return Err(SemanticError {
+                            message: format!(
+                                "cell '{}' must yield or return a value of type {}",
+                                cell.name.node,
+                                ty.display()
+                            ),
+                            span: cell.name.span,
+                        });
+                    }
+                    None => {}
+                }
+            }
+            return Ok(ty);
+        }
+
+        let mut ret = falls_through.then_some(body_ty);
+        for (ty, span) in ctx.sites {
+            ret = Some(match ret {
+                None => ty,
+                Some(prev) => join_return_types(&prev, &ty).ok_or_else(|| SemanticError {
+                    message: format!(
+                        "mismatched return types: cell '{}' returns {} here but {} elsewhere",
+                        cell.name.node,
+                        ty.display(),
+                        prev.display()
+                    ),
+                    span,
+                })?,
+            });
+        }
+        Ok(ret.unwrap_or(Type::Unit))
+    }
+
+    fn check_return(&mut self, r: &aura_ast::ReturnStmt) -> Result<(), SemanticError> {
+        let ty = match &r.value {
+            Some(e) => self.infer_expr(e)?,
+            None => Type::Unit,
+        };
+        if mentions_cancel_token(&ty) {
+            return Err(SemanticError {
+                message: "a scope's cancellation token cannot be returned: it would outlive the scope".to_string(),
+                span: r.span,
+            });
+        }
+        if let Some(e) = &r.value {
+            self.check_not_lent_out(e, &ty, "return")?;
+        }
+        if let Some(e) = &r.value
+            && self.escapes_region(e, &ty, 0)
+        {
+            return Err(SemanticError {
+                message: "a value allocated in a region cannot be returned from it: the region frees it at its end"
+                    .to_string(),
+                span: e.span,
+            });
+        }
+        let Some(ctx) = &self.cell_return else {
+            return Err(SemanticError {
+                message: "'return' outside of a cell".to_string(),
+                span: r.span,
+            });
+        };
+        if let Some(declared) = ctx.declared.clone() {
+            match &r.value {
+                Some(e) => self.check_assignable(&declared, &ty, e)?,
+                None if declared != Type::Unit => {
+                    return Err(SemanticError {
+                        message: format!("'return' needs a value of type {}", declared.display()),
+                        span: r.span,
+                    });
+                }
+                None => {}
+            }
+        }
+        if let Some(ctx) = &mut self.cell_return {
+            ctx.sites.push((ty, r.span));
+        }
+        Ok(())
+    }
+
     fn check_flow_block(&mut self, fb: &FlowBlock) -> Result<(), SemanticError> {
         self.push_scope();
         let _ret = self.check_block(&fb.body)?;
@@ -981,7 +2775,15 @@ impl Checker {
 
     fn check_block(&mut self, block: &Block) -> Result<Type, SemanticError> {
         self.push_scope();
+        let deferred_base = self.deferred_moves.len();
+        let mut jumped: Option<(&str, aura_ast::Span)> = None;
         for stmt in &block.stmts {
+            if let Some((kw, span)) = jumped {
+                return Err(SemanticError {
+                    message: format!("unreachable code after '{kw}'"),
+                    span,
+                });
+            }
             match stmt {
                 Stmt::Import(i) => self.handle_import(i)?,
                 Stmt::MacroDef(m) => {
@@ -1010,6 +2812,26 @@ impl Checker {
                 Stmt::If(i) => self.check_if(i)?,
                 Stmt::Match(m) => self.check_match(m)?,
                 Stmt::While(w) => self.check_while(w)?,
+                Stmt::For(f) => self.check_for(f)?,
+                Stmt::Break(b) => {
+                    self.check_loop_jump("break", b.span)?;
+                    jumped = Some(("break", b.span));
+                }
+                Stmt::Continue(c) => {
+                    self.check_loop_jump("continue", c.span)?;
+                    jumped = Some(("continue", c.span));
+                }
+                Stmt::Return(r) => {
+                    if self.defer_depth > 0 {
+                        return Err(SemanticError {
+                            message: "'return' cannot leave a defer block".to_string(),
+                            span: r.span,
+                        });
+                    }
+                    self.check_return(r)?;
+                    jumped = Some(("return", r.span));
+                }
+                Stmt::Defer(d) => self.check_defer(d)?,
                 Stmt::Requires(r) => {
                     let ty = self.infer_expr(&r.expr)?;
                     if ty != Type::Bool {
@@ -1093,17 +2915,140 @@ impl Checker {
                     let _ = self.check_block(&s.body)?;
                     self.unsafe_depth -= 1;
                 }
+                Stmt::Scope(s) => self.check_scope_block(s)?,
+                Stmt::Region(r) => self.check_region_block(r)?,
             }
         }
 
+        if let (Some((kw, span)), Some(_)) = (jumped, &block.yield_expr) {
+            return Err(SemanticError {
+                message: format!("unreachable code after '{kw}'"),
+                span,
+            });
+        }
         let ret = match &block.yield_expr {
-            Some(expr) => self.infer_expr(expr)?,
+            Some(expr) => {
+                let ty = self.infer_expr(expr)?;
+                self.check_not_lent_out(expr, &ty, "yield")?;
+                ty
+            }
             None => Type::Unit,
         };
+        let released = self.deferred_moves.split_off(deferred_base);
         self.pop_scope();
+        // The block's deferred bodies have run now, so what they consumed is gone.
+        for (name, span) in released {
+            if self.lookup_val(&name).is_some() {
+                self.mark_consumed(&name, span)?;
+            }
+        }
         Ok(ret)
     }
 
+    fn check_loop_jump(&self, kw: &str, span: Span) -> Result<(), SemanticError> {
+        if self.loop_depth > 0 {
+            return Ok(());
+        }
+        let message = if self.defer_depth > 0 {
+            format!("'{kw}' cannot leave a defer block")
+        } else {
+            format!("'{kw}' outside of a loop")
+        };
+        Err(SemanticError { message, span })
+    }
+
+    /// A deferred body runs at scope exit, not where it is written. It is checked here against
+    /// a snapshot of the ownership state; the values it consumes stay readable until the scope
+    /// exits but can no longer be moved elsewhere.
+    fn check_defer(&mut self, d: &aura_ast::DeferStmt) -> Result<(), SemanticError> {
+        let saved_own = self.ownership_states.clone();
+        let saved_cap = self.cap.clone();
+        let saved_loop = std::mem::replace(&mut self.loop_depth, 0);
+        let base = self.defer_consumed.len();
+        self.defer_depth += 1;
+        let result = self.check_block(&d.body);
+        self.defer_depth -= 1;
+        self.loop_depth = saved_loop;
+        self.cap = saved_cap;
+        self.ownership_states = saved_own;
+        let moved = self.defer_consumed.split_off(base);
+        result?;
+
+        // Locals of the body are out of scope again; only outer values stay pending.
+        for (name, _) in moved {
+            if self.lookup_val(&name).is_some() {
+                self.deferred_moves.push((name, d.span));
+            }
+        }
+        Ok(())
+    }
+
+    /// A `scope` body is checked with its token bound. The tasks it starts and the token stay
+    /// inside it: neither may be stored in a binding declared outside the block, and the
+    /// token may not be returned or sent over a channel either.
+    fn check_scope_block(&mut self, s: &aura_ast::ScopeBlock) -> Result<(), SemanticError> {
+        self.push_scope();
+        if let Some(token) = &s.token {
+            self.define_val(token, Type::cancel_token(), false)?;
+        }
+        self.scope_bases.push(self.scopes.len() - 1);
+        let _ = self.check_block(&s.body)?;
+        self.scope_bases.pop();
+        self.pop_scope();
+        Ok(())
+    }
+
+    /// Rejects assigning a task or token of the innermost `scope` to a binding declared
+    /// outside it.
+    fn check_scope_escape(&self, assign: &AssignStmt, rhs_ty: &Type) -> Result<(), SemanticError> {
+        let Some(&base) = self.scope_bases.last() else {
+            return Ok(());
+        };
+        let target = &assign.target;
+        if self.lookup_scope_index(&target.node).is_none_or(|idx| idx >= base) {
+            return Ok(());
+        }
+        let spawns = matches!(
+            &assign.expr.kind,
+            ExprKind::Flow { op: aura_ast::FlowOp::Async, right, .. } if matches!(right.kind, ExprKind::Call { .. })
+        );
+        let what = if spawns {
+            "a task started in a scope"
+        } else if mentions_cancel_token(rhs_ty) {
+            "a scope's cancellation token"
+        } else {
+            return Ok(());
+        };
+        Err(SemanticError {
+            message: format!("{what} cannot escape it through '{}', which is declared outside the scope", target.node),
+            span: assign.span,
+        })
+    }
+
+    /// A `region` body is checked in a scope of its own. Tensors, lists and maps made in it
+    /// are freed when it ends, so none may be stored in a binding declared outside it,
+    /// returned, or sent over a channel that outlives it. Bindings from outside may still be
+    /// read and assigned among themselves.
+    fn check_region_block(&mut self, r: &aura_ast::RegionBlock) -> Result<(), SemanticError> {
+        self.push_scope();
+        self.region_bases.push(self.scopes.len() - 1);
+        let _ = self.check_block(&r.body)?;
+        self.region_bases.pop();
+        self.pop_scope();
+        Ok(())
+    }
+
+    /// Whether storing `expr` (of type `ty`) somewhere that lives at scope depth `depth`
+    /// would let it outlive a `region`: it holds region memory and is not a binding declared
+    /// outside every region that ends before that depth does.
+    fn escapes_region(&self, expr: &Expr, ty: &Type, depth: usize) -> bool {
+        let Some(&base) = self.region_bases.iter().find(|&&b| b > depth) else {
+            return false;
+        };
+        let outer = |id: &Ident| self.lookup_scope_index(&id.node).is_some_and(|idx| idx < base);
+        holds_region_memory(ty) && !matches!(&expr.kind, ExprKind::Ident(id) if outer(id))
+    }
+
     fn check_layout_block(&mut self, lb: &aura_ast::LayoutBlock) -> Result<(), SemanticError> {
         let _ = self.check_block(&lb.body)?;
         Ok(())
@@ -1122,6 +3067,7 @@ impl Checker {
         let Some(last) = import.path.last() else {
             return Ok(());
         };
+        self.imported_modules.insert(last.node.clone());
         self.define_module_placeholder(last)?;
         Ok(())
     }
@@ -1162,7 +3108,27 @@ impl Checker {
             });
         }
 
-        let expr_ty = self.infer_expr(&sd.expr)?;
+        let borrow = match &sd.expr.kind {
+            ExprKind::Borrow { kind, .. } => Some(*kind),
+            _ => None,
+        };
+        if let Some(kind) = borrow {
+            if kind == BorrowKind::Mut {
+                return Err(SemanticError {
+                    message: "a `&mut` borrow can only be passed to a cell; a `val` can hold `&x`".to_string(),
+                    span: sd.expr.span,
+                });
+            }
+            if sd.mutable || sd.pattern.is_some() {
+                return Err(SemanticError {
+                    message: "a borrow can only be held by a plain `val` binding".to_string(),
+                    span: sd.name.span,
+                });
+            }
+            self.borrow_sites.insert(sd.expr.span.offset());
+        }
+
+        let expr_ty = self.infer_expr(&sd.expr).inspect_err(|_| self.note_second_move(&sd.expr))?;
 
         let final_ty = if let Some(annot) = &sd.ty {
             let expected = self.resolve_type_ref(annot)?;
@@ -1170,7 +3136,13 @@ impl Checker {
             // If there is a `where` clause, we treat it as a refinement for type tracking,
             // but we don't require proving it here (the verifier is the hard gate).
             // We still check base assignability.
-            self.check_assignable(&base_type(&expected).clone(), &expr_ty, &sd.expr)?;
+            match &expected {
+                // A range of a sized integer narrows any integer; the verifier proves the range.
+                Type::ConstrainedRange { base, .. } if **base != Type::U32 && is_int_like(&expr_ty) => {
+                    self.note_int_cast(&sd.expr, &expr_ty, base);
+                }
+                _ => self.check_assignable(&base_type(&expected).clone(), &expr_ty, &sd.expr)?,
+            }
 
             if let Some(w) = &sd.where_clause {
                 if let Some((lo, hi)) = refinement_u32_range_from_where(&sd.name.node, w) {
@@ -1196,7 +3168,212 @@ impl Checker {
                 self.consume_move_from_value(&src.node, src.span)?;
             }
         }
-        self.define_val(&sd.name, final_ty, sd.mutable)
+        if let Some(pat) = &sd.pattern {
+            return self.bind_tuple_pattern(pat, &final_ty, sd.mutable);
+        }
+        let model_output = match final_ty {
+            Type::Model => self.model_output_of(&sd.expr),
+            _ => None,
+        };
+        // Looked up before the new binding, which may shadow the target.
+        let loan = match &sd.expr.kind {
+            ExprKind::Borrow { kind, expr } => match &expr.kind {
+                ExprKind::Ident(target) => self.lookup_scope_index(&target.node).map(|depth| Loan {
+                    target: target.node.clone(),
+                    depth,
+                    kind: *kind,
+                }),
+                _ => None,
+            },
+            _ => None,
+        };
+        self.define_val(&sd.name, final_ty, sd.mutable)?;
+        if let Some(loan) = loan {
+            self.set_ownership(&sd.name.node, OwnershipState::Borrowed);
+            self.loans.last_mut().expect("loan scope stack").push(loan);
+        }
+        if let Some(dims) = model_output {
+            let scope = self.model_outputs.last_mut().expect("model scope stack");
+            scope.insert(sd.name.node.clone(), dims);
+        }
+        Ok(())
+    }
+
+    /// Output dims of the model `expr` evaluates to: a model loaded from a literal path, or
+    /// a binding that holds one.
+    fn model_output_of(&self, expr: &Expr) -> Option<OutputDims> {
+        match &expr.kind {
+            ExprKind::Ident(id) => {
+                let idx = self.lookup_scope_index(&id.node)?;
+                self.model_outputs.get(idx)?.get(&id.node).cloned()
+            }
+            ExprKind::Call { callee, args, .. } if expr_to_callee_name(callee) == "ai.load_model" => {
+                let [arg] = args.as_slice() else { return None };
+                let ExprKind::StringLit(path) = &call_arg_value(arg).kind else {
+                    return None;
+                };
+                model_output_dims(path)
+            }
+            _ => None,
+        }
+    }
+
+    /// The type of an `ai.infer`/`ai.infer_batch` call (`args` as the signature orders them):
+    /// a tensor shaped like the model's output, when every dim is fixed. A batched result's
+    /// leading dim is the batch, which must be a literal.
+    fn infer_result_type(&self, name: &str, args: &[&Expr]) -> Option<Type> {
+        let mut dims = self.model_output_of(args.first()?)?;
+        match name {
+            "ai.infer" => {}
+            "ai.infer_batch" => {
+                let ExprKind::IntLit(batch) = args.get(2)?.kind else {
+                    return None;
+                };
+                *dims.first_mut()? = Some(batch);
+            }
+            _ => return None,
+        }
+        Some(Type::Tensor {
+            // Element types are proven by the aura-ai plugin, which sees refined annotations.
+            elem: Box::new(Type::Unknown),
+            shape: Some(dims.into_iter().collect::<Option<Vec<u64>>>()?),
+        })
+    }
+
+    /// Rejects a literal `tensor.get`/`tensor.set` index past the end of a statically shaped
+    /// tensor; other indices are proven by aura-verify.
+    fn check_literal_tensor_index(&mut self, name: &str, args: &[&Expr]) -> Result<(), SemanticError> {
+        if !matches!(name, "tensor.get" | "tensor.set") {
+            return Ok(());
+        }
+        let (Some(tensor), Some(index)) = (args.first(), args.get(1)) else {
+            return Ok(());
+        };
+        let ExprKind::IntLit(i) = index.kind else {
+            return Ok(());
+        };
+        let ty = self.infer_expr(tensor)?;
+        let Type::Tensor { shape: Some(dims), .. } = base_type(&ty) else {
+            return Ok(());
+        };
+        let len = dims.iter().try_fold(1u64, |n, d| n.checked_mul(*d));
+        match len {
+            Some(len) if i >= len => Err(SemanticError {
+                message: format!(
+                    "tensor index {i} is out of bounds for {} (length {len})",
+                    ty.display()
+                ),
+                span: index.span,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// The signature of a `chan.*` call on `args` (as the signature orders them): the value
+    /// `chan.send` takes and `chan.recv` returns has the channel's element type, or any type
+    /// when that is not known. A literal `chan.bounded` capacity must be at least 1; other
+    /// capacities are proven by aura-verify.
+    fn check_chan_call(&mut self, name: &str, args: &[&Expr], mut sig: FnSig) -> Result<FnSig, SemanticError> {
+        match (name, args) {
+            ("chan.bounded", [capacity]) => {
+                if let ExprKind::IntLit(0) = capacity.kind {
+                    return Err(SemanticError {
+                        message: "channel capacity must be at least 1".to_string(),
+                        span: capacity.span,
+                    });
+                }
+            }
+            ("chan.send" | "chan.recv", [ch, rest @ ..]) => {
+                let ch_ty = self.infer_expr(ch)?;
+                let elem = ch_ty.channel_elem().cloned().unwrap_or(Type::Unknown);
+                if name == "chan.recv" {
+                    sig.ret = elem;
+                } else if let (Some(param), [value]) = (sig.params.get_mut(1), rest) {
+                    let value_ty = self.infer_expr(value)?;
+                    if mentions_cancel_token(&value_ty) {
+                        return Err(SemanticError {
+                            message: "a scope's cancellation token cannot be sent over a channel: it would outlive the scope"
+                                .to_string(),
+                            span: value.span,
+                        });
+                    }
+                    let ch_depth = match &ch.kind {
+                        ExprKind::Ident(id) => self.lookup_scope_index(&id.node).unwrap_or(0),
+                        _ => 0,
+                    };
+                    if self.escapes_region(value, &value_ty, ch_depth) {
+                        return Err(SemanticError {
+                            message: "a value allocated in a region cannot be sent over a channel that outlives the region"
+                                .to_string(),
+                            span: value.span,
+                        });
+                    }
+                    param.ty = match elem {
+                        Type::Unknown => {
+                            // The first value sent fixes what the channel carries.
+                            self.refine_expr(ch, &Type::channel(widen_ranges(&value_ty)));
+                            value_ty
+                        }
+                        elem => elem,
+                    };
+                }
+            }
+            _ => {}
+        }
+        Ok(sig)
+    }
+
+    /// Binds the names in a `val (x, y) = ...` tuple pattern against `ty`; only names,
+    /// `_` and nested tuples are allowed.
+    fn bind_tuple_pattern(
+        &mut self,
+        pat: &Pattern,
+        ty: &Type,
+        mutable: bool,
+    ) -> Result<(), SemanticError> {
+        match pat {
+            Pattern::Wildcard { .. } => Ok(()),
+            Pattern::Bind { name, .. } => self.define_val(name, ty.clone(), mutable),
+            Pattern::Tuple { span, elems } => {
+                let Type::Tuple(elem_tys) = base_type(ty) else {
+                    return Err(SemanticError {
+                        message: format!(
+                            "tuple pattern is not compatible with type {}",
+                            ty.display()
+                        ),
+                        span: *span,
+                    });
+                };
+                if elem_tys.len() != elems.len() {
+                    return Err(SemanticError {
+                        message: format!(
+                            "tuple pattern has {} elements, but {} has {}",
+                            elems.len(),
+                            ty.display(),
+                            elem_tys.len()
+                        ),
+                        span: *span,
+                    });
+                }
+                let elem_tys = elem_tys.clone();
+                for (p, t) in elems.iter().zip(elem_tys.iter()) {
+                    self.bind_tuple_pattern(p, t, mutable)?;
+                }
+                Ok(())
+            }
+            Pattern::IntLit { span, .. } | Pattern::StringLit { span, .. } => Err(SemanticError {
+                message: "literal patterns are not allowed in a val binding".to_string(),
+                span: *span,
+            }),
+            Pattern::Ctor { span, .. } => Err(SemanticError {
+                message: "constructor patterns are not allowed in a val binding".to_string(),
+                span: *span,
+            }),
+            Pattern::Or { span, .. } => Err(SemanticError {
+                message: "or-patterns are not allowed in a val binding".to_string(),
+                span: *span,
+            }),
+        }
     }
 
     fn check_assign(&mut self, assign: &AssignStmt) -> Result<(), SemanticError> {
@@ -1210,6 +3387,18 @@ impl Checker {
         let _ = self
             .cap
             .ensure_alive(&assign.target.node, assign.target.span)?;
+        self.note_capture(&assign.target.node, &target_ty);
+
+        if self.is_reference(&assign.target.node) {
+            return Err(SemanticError {
+                message: format!(
+                    "cannot assign to '{}': it holds a borrow, which can only be read or updated in place",
+                    assign.target.node
+                ),
+                span: assign.target.span,
+            });
+        }
+        self.check_not_borrowed(&assign.target.node, "assign to", assign.target.span)?;
 
         if !self.is_mutable(&assign.target.node) {
             return Err(SemanticError {
@@ -1219,12 +3408,32 @@ impl Checker {
         }
 
         let rhs_ty = self.infer_expr(&assign.expr)?;
+        self.forget_narrowing(&assign.target.node);
+        self.check_scope_escape(assign, &rhs_ty)?;
+        if let Some(depth) = self.lookup_scope_index(&assign.target.node)
+            && self.escapes_region(&assign.expr, &rhs_ty, depth)
+        {
+            return Err(SemanticError {
+                message: format!(
+                    "a value allocated in a region cannot escape it through '{}', which is declared outside the region",
+                    assign.target.node
+                ),
+                span: assign.span,
+            });
+        }
         // Mutation aliasing rule (MVP): assigning from a resource identifier moves it.
         if let ExprKind::Ident(src) = &assign.expr.kind {
             if src.node != assign.target.node && self.is_non_copy_type(&rhs_ty) {
                 self.consume_move_from_value(&src.node, src.span)?;
             }
         }
+        let target_ty = if mentions_unknown(&target_ty)
+            && self.refine_binding(&assign.target.node, &widen_ranges(&rhs_ty))
+        {
+            fill_unknowns(&target_ty, &widen_ranges(&rhs_ty))
+        } else {
+            target_ty
+        };
         self.check_assignable(&target_ty, &rhs_ty, &assign.expr)
     }
 
@@ -1236,13 +3445,42 @@ impl Checker {
                 span: if_stmt.cond.span,
             });
         }
-        let _ = self.check_block(&if_stmt.then_block)?;
+        let test = self.option_condition(&if_stmt.cond);
+        let narrowed_in = |some| test.as_ref().filter(|(_, s)| *s == some).map(|(name, _)| name.as_str());
+        self.check_block_narrowed(&if_stmt.then_block, narrowed_in(true))?;
         if let Some(else_block) = &if_stmt.else_block {
-            let _ = self.check_block(else_block)?;
+            self.check_block_narrowed(else_block, narrowed_in(false))?;
+        }
+
+        // A branch that returns when the value is None narrows the rest of the block, as long
+        // as the other branch leaves the value alone.
+        if let Some((name, some)) = test {
+            let (none_branch, some_branch) = if some {
+                (if_stmt.else_block.as_ref(), Some(&if_stmt.then_block))
+            } else {
+                (Some(&if_stmt.then_block), if_stmt.else_block.as_ref())
+            };
+            let mut assigned = BTreeSet::new();
+            if let Some(b) = some_branch {
+                collect_assigned_names(b, &mut assigned);
+            }
+            if none_branch.is_some_and(block_always_returns) && !assigned.contains(&name) {
+                self.narrow_to_some(&name);
+            }
         }
         Ok(())
     }
 
+    /// Checks a branch in which the `Option` binding `name` is known to hold `Some`.
+    fn check_block_narrowed(&mut self, block: &Block, name: Option<&str>) -> Result<(), SemanticError> {
+        let added = name.is_some_and(|n| self.narrowed.last_mut().expect("scope stack").insert(n.to_string()));
+        let checked = self.check_block(block);
+        if let Some(n) = name.filter(|_| added) {
+            self.narrowed.last_mut().expect("scope stack").remove(n);
+        }
+        checked.map(|_| ())
+    }
+
     fn check_match(&mut self, m: &MatchStmt) -> Result<(), SemanticError> {
         if m.arms.is_empty() {
             return Err(SemanticError {
@@ -1253,166 +3491,549 @@ impl Checker {
 
         let scrut_ty = self.infer_expr(&m.scrutinee)?;
 
-        // First-pass exhaustiveness: require a trailing wildcard arm.
-        // Keep semantics obvious: if a wildcard is present, it must be last.
+        // Keep semantics obvious: if a catch-all arm is present, it must be last.
         let mut wildcard_idx: Option<usize> = None;
         for (i, arm) in m.arms.iter().enumerate() {
-            if matches!(arm.pat, Pattern::Wildcard { .. }) {
+            if top_level_alts(&arm.pat)
+                .iter()
+                .any(|p| matches!(p, Pattern::Wildcard { .. }))
+            {
                 wildcard_idx = Some(i);
                 break;
             }
         }
-
-        match wildcard_idx {
-            None => {
-                return Err(SemanticError {
-                    message: "non-exhaustive match; add a final '_' arm".to_string(),
-                    span: m.span,
-                });
-            }
-            Some(i) if i != m.arms.len() - 1 => {
-                return Err(SemanticError {
-                    message: "wildcard '_' arm must be last".to_string(),
-                    span: m.arms[i].span,
-                });
-            }
-            Some(_) => {}
+        if let Some(i) = wildcard_idx.filter(|i| *i != m.arms.len() - 1) {
+            return Err(SemanticError {
+                message: "wildcard '_' arm must be last".to_string(),
+                span: m.arms[i].span,
+            });
         }
 
         let mut seen_ints: HashSet<u64> = HashSet::new();
         let mut seen_strings: HashSet<String> = HashSet::new();
+        let mut seen_literals: HashSet<String> = HashSet::new();
 
         for arm in &m.arms {
-            match &arm.pat {
-                Pattern::Wildcard { .. } => {}
-                Pattern::IntLit { span, value } => {
-                    if !is_u32_like(&scrut_ty) {
+            for alt in top_level_alts(&arm.pat) {
+                match alt {
+                    Pattern::IntLit { span, value }
+                        if is_u32_like(&scrut_ty) && !seen_ints.insert(*value) =>
+                    {
                         return Err(SemanticError {
-                            message: format!(
-                                "int pattern is not compatible with scrutinee type {}",
-                                scrut_ty.display()
-                            ),
+                            message: format!("duplicate match arm for literal {value}"),
                             span: *span,
                         });
                     }
-                    if !seen_ints.insert(*value) {
+                    Pattern::StringLit { span, value }
+                        if base_type(&scrut_ty) == &Type::String && !seen_strings.insert(value.clone()) =>
+                    {
                         return Err(SemanticError {
-                            message: format!("duplicate match arm for literal {value}"),
+                            message: "duplicate match arm for string literal".to_string(),
                             span: *span,
                         });
                     }
-                }
-                Pattern::StringLit { span, value } => {
-                    if base_type(&scrut_ty) != &Type::String {
+                    Pattern::Bind { span, .. } => {
                         return Err(SemanticError {
-                            message: format!(
-                                "string pattern is not compatible with scrutinee type {}",
-                                scrut_ty.display()
-                            ),
+                            message:
+                                "a bare name is only a valid pattern inside a tuple or constructor pattern"
+                                    .to_string(),
                             span: *span,
                         });
                     }
-                    if !seen_strings.insert(value.clone()) {
+                    _ => {}
+                }
+            }
+
+            let mut bindings: Vec<(Ident, Type)> = Vec::new();
+            self.check_match_pattern(&arm.pat, &scrut_ty, "scrutinee", &mut bindings)?;
+            for alt in top_level_alts(&arm.pat) {
+                if let Pattern::Ctor { .. } | Pattern::Tuple { .. } = alt
+                    && let Some(text) = literal_pattern(alt)
+                    && !seen_literals.insert(text.clone())
+                {
+                    return Err(SemanticError {
+                        message: format!("duplicate match arm for {text}"),
+                        span: alt.span(),
+                    });
+                }
+            }
+
+            // Bind pattern names for the arm body only; they do not escape the match.
+            self.push_scope();
+            for (name, ty) in bindings {
+                self.define_val(&name, ty, false)?;
+            }
+            if let ExprKind::Ident(scrut) = &m.scrutinee.kind
+                && self.option_payload(&scrut_ty).is_some()
+                && top_level_alts(&arm.pat)
+                    .iter()
+                    .all(|p| matches!(p, Pattern::Ctor { variant, .. } if variant.node == "Some"))
+            {
+                self.narrow_to_some(&scrut.node);
+            }
+            let _ = self.check_block(&arm.body)?;
+            self.pop_scope();
+        }
+
+        // An arm that the arms before it already cover never runs.
+        for (i, arm) in m.arms.iter().enumerate().skip(1) {
+            let rows: Vec<Vec<Option<&Pattern>>> = m.arms[..i].iter().map(|a| vec![Some(&a.pat)]).collect();
+            if !self.is_useful(rows, vec![Some(&arm.pat)], std::slice::from_ref(&scrut_ty), m.span)?
+                && !self.unreachable_arms.contains(&arm.span)
+            {
+                self.unreachable_arms.push(arm.span);
+            }
+        }
+
+        if wildcard_idx.is_none() {
+            let rows: Vec<Vec<Option<&Pattern>>> = m.arms.iter().map(|a| vec![Some(&a.pat)]).collect();
+            if let Some(witness) = self.uncovered_case(rows, std::slice::from_ref(&scrut_ty), m.span)? {
+                return Err(SemanticError {
+                    message: format!(
+                        "non-exhaustive match; {} is not covered, add an arm for it or a final '_' arm",
+                        witness.join(", ")
+                    ),
+                    span: m.span,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Type-checks a match pattern against `ty` and collects the names it binds, in
+    /// source order. `site` names what is being matched, for error messages.
+    fn check_match_pattern(
+        &mut self,
+        pat: &Pattern,
+        ty: &Type,
+        site: &str,
+        out: &mut Vec<(Ident, Type)>,
+    ) -> Result<(), SemanticError> {
+        match pat {
+            Pattern::Wildcard { .. } => Ok(()),
+            Pattern::Bind { name, .. } => {
+                if out.iter().any(|(n, _)| n.node == name.node) {
+                    return Err(SemanticError {
+                        message: format!("'{}' is bound more than once in this pattern", name.node),
+                        span: name.span,
+                    });
+                }
+                out.push((name.clone(), ty.clone()));
+                Ok(())
+            }
+            Pattern::IntLit { span, .. } => {
+                if !is_u32_like(ty) {
+                    return Err(SemanticError {
+                        message: format!(
+                            "int pattern is not compatible with {site} type {}",
+                            ty.display()
+                        ),
+                        span: *span,
+                    });
+                }
+                Ok(())
+            }
+            Pattern::StringLit { span, .. } => {
+                if base_type(ty) != &Type::String {
+                    return Err(SemanticError {
+                        message: format!(
+                            "string pattern is not compatible with {site} type {}",
+                            ty.display()
+                        ),
+                        span: *span,
+                    });
+                }
+                Ok(())
+            }
+            Pattern::Tuple { span, elems } => {
+                let Type::Tuple(elem_tys) = base_type(ty) else {
+                    return Err(SemanticError {
+                        message: format!(
+                            "tuple pattern is not compatible with type {}",
+                            ty.display()
+                        ),
+                        span: *span,
+                    });
+                };
+                if elem_tys.len() != elems.len() {
+                    return Err(SemanticError {
+                        message: format!(
+                            "tuple pattern has {} elements, but {} has {}",
+                            elems.len(),
+                            ty.display(),
+                            elem_tys.len()
+                        ),
+                        span: *span,
+                    });
+                }
+                let elem_tys = elem_tys.clone();
+                for (p, t) in elems.iter().zip(elem_tys.iter()) {
+                    self.check_match_pattern(p, t, "element", out)?;
+                }
+                Ok(())
+            }
+            Pattern::Ctor {
+                span,
+                ty: ty_name,
+                variant,
+                args,
+            } => {
+                let Some((scrut_name, scrut_args)) = applied_name_and_args(base_type(ty)) else {
+                    return Err(SemanticError {
+                        message: format!(
+                            "ctor pattern is not compatible with {site} type {}",
+                            ty.display()
+                        ),
+                        span: *span,
+                    });
+                };
+                if scrut_name != ty_name.node.as_str() {
+                    return Err(SemanticError {
+                        message: format!(
+                            "ctor pattern expects '{}', but {site} is '{}'",
+                            ty_name.node, scrut_name
+                        ),
+                        span: *span,
+                    });
+                }
+                let scrut_args = scrut_args.to_vec();
+                let variants = self.enum_variant_field_types(ty_name, &scrut_args)?;
+                let Some((_, field_tys)) = variants.iter().find(|(v, _)| *v == variant.node) else {
+                    return Err(SemanticError {
+                        message: format!(
+                            "unknown variant '{}' for enum '{}'",
+                            variant.node, ty_name.node
+                        ),
+                        span: variant.span,
+                    });
+                };
+                if args.len() != field_tys.len() {
+                    return Err(SemanticError {
+                        message: format!(
+                            "wrong number of binders for '{}::{}': expected {}, got {}",
+                            ty_name.node,
+                            variant.node,
+                            field_tys.len(),
+                            args.len()
+                        ),
+                        span: *span,
+                    });
+                }
+                for (p, t) in args.iter().zip(field_tys.iter()) {
+                    self.check_match_pattern(p, t, "field", out)?;
+                }
+                Ok(())
+            }
+            Pattern::Or { alts, .. } => {
+                let mut first: Option<Vec<(Ident, Type)>> = None;
+                for alt in alts {
+                    let mut names: Vec<(Ident, Type)> = Vec::new();
+                    self.check_match_pattern(alt, ty, site, &mut names)?;
+                    let Some(expected) = &first else {
+                        first = Some(names);
+                        continue;
+                    };
+                    let mismatch = names.len() != expected.len()
+                        || names
+                            .iter()
+                            .any(|(n, _)| !expected.iter().any(|(e, _)| e.node == n.node));
+                    if mismatch {
                         return Err(SemanticError {
-                            message: "duplicate match arm for string literal".to_string(),
-                            span: *span,
+                            message: "or-pattern alternatives must bind the same names".to_string(),
+                            span: alt.span(),
                         });
                     }
+                    for (n, t) in &names {
+                        let (_, et) = expected
+                            .iter()
+                            .find(|(e, _)| e.node == n.node)
+                            .expect("checked above");
+                        if et != t {
+                            return Err(SemanticError {
+                                message: format!(
+                                    "'{}' has type {} in one alternative and {} in another",
+                                    n.node,
+                                    et.display(),
+                                    t.display()
+                                ),
+                                span: n.span,
+                            });
+                        }
+                    }
                 }
-                Pattern::Ctor {
-                    span,
-                    ty,
-                    variant,
-                    binders,
-                } => {
-                    let scrut_base = base_type(&scrut_ty);
-                    let Some((scrut_name, scrut_args)) = applied_name_and_args(scrut_base) else {
+                for (n, t) in first.unwrap_or_default() {
+                    if out.iter().any(|(o, _)| o.node == n.node) {
                         return Err(SemanticError {
-                            message: format!(
-                                "ctor pattern is not compatible with scrutinee type {}",
-                                scrut_ty.display()
-                            ),
-                            span: *span,
+                            message: format!("'{}' is bound more than once in this pattern", n.node),
+                            span: n.span,
                         });
-                    };
+                    }
+                    out.push((n, t));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Variants of enum `name`, in declaration order, with payload types instantiated
+    /// at the enum's type arguments.
+    fn enum_variant_field_types(
+        &mut self,
+        name: &Ident,
+        type_args: &[Type],
+    ) -> Result<Vec<(String, Vec<Type>)>, SemanticError> {
+        // Grab variant field TypeRefs up-front so we can mutate `self` freely after.
+        let (variants, def_params): (Vec<(String, Vec<TypeRef>)>, Vec<aura_ast::TypeParam>) = {
+            let Some(def) = self.enum_defs.get(&name.node) else {
+                return Err(SemanticError {
+                    message: format!("unknown enum type '{}'", name.node),
+                    span: name.span,
+                });
+            };
+            (
+                def.variants
+                    .iter()
+                    .map(|v| (v.name.node.clone(), v.fields.iter().map(|f| f.ty.clone()).collect()))
+                    .collect(),
+                def.params.clone(),
+            )
+        };
+
+        let mut subst: HashMap<String, Type> = HashMap::new();
+        for (p, a) in def_params.iter().zip(type_args.iter()) {
+            subst.insert(p.name.node.clone(), a.clone());
+        }
+        let mut out = Vec::new();
+        for (variant, fields) in variants {
+            let mut tys = Vec::new();
+            for tr in &fields {
+                tys.push(if def_params.is_empty() {
+                    self.resolve_type_ref(tr)?
+                } else {
+                    self.resolve_type_ref_with_type_params(tr, &subst)?
+                });
+            }
+            out.push((variant, tys));
+        }
+        Ok(out)
+    }
 
-                    if scrut_name != ty.node.as_str() {
-                        return Err(SemanticError {
-                            message: format!(
-                                "ctor pattern expects '{}', but scrutinee is '{}'",
-                                ty.node, scrut_name
-                            ),
-                            span: *span,
-                        });
+    /// Exhaustiveness check over a pattern matrix: each row is one arm's patterns for the
+    /// columns typed by `tys` (`None` matches anything). Returns an example of a value no
+    /// row matches, one rendered pattern per column, or `None` if the rows cover every value.
+    fn uncovered_case(
+        &mut self,
+        rows: Vec<Vec<Option<&Pattern>>>,
+        tys: &[Type],
+        span: Span,
+    ) -> Result<Option<Vec<String>>, SemanticError> {
+        let Some((ty, rest_tys)) = tys.split_first() else {
+            return Ok(if rows.is_empty() { Some(Vec::new()) } else { None });
+        };
+
+        // Or-alternatives in the first column each become their own row.
+        let mut expanded: Vec<Vec<Option<&Pattern>>> = Vec::new();
+        let mut pending = rows;
+        while let Some(row) = pending.pop() {
+            match row[0] {
+                Some(Pattern::Or { alts, .. }) => {
+                    for alt in alts {
+                        let mut r = row.clone();
+                        r[0] = Some(alt);
+                        pending.push(r);
                     }
+                }
+                _ => expanded.push(row),
+            }
+        }
+        let is_catch_all = |p: Option<&Pattern>| {
+            matches!(p, None | Some(Pattern::Wildcard { .. }) | Some(Pattern::Bind { .. }))
+        };
 
-                    // Grab variant field TypeRefs up-front so we can mutate `self` freely after.
-                    let (expected_fields, def_params): (Vec<TypeRef>, Vec<aura_ast::TypeParam>) = {
-                        let Some(def) = self.enum_defs.get(&ty.node) else {
-                            return Err(SemanticError {
-                                message: format!("unknown enum type '{}'", ty.node),
-                                span: ty.span,
-                            });
-                        };
+        let enum_ty = applied_name_and_args(base_type(ty))
+            .filter(|(name, _)| self.enum_defs.contains_key(*name))
+            .map(|(name, args)| (name.to_string(), args.to_vec()));
+        if let Some((enum_name, type_args)) = enum_ty {
+            let name = aura_ast::Spanned::new(span, enum_name.clone());
+            for (variant, field_tys) in self.enum_variant_field_types(&name, &type_args)? {
+                let arity = field_tys.len();
+                let mut specialized: Vec<Vec<Option<&Pattern>>> = Vec::new();
+                for row in &expanded {
+                    let mut r: Vec<Option<&Pattern>> = match row[0] {
+                        Some(Pattern::Ctor { variant: v, args, .. }) if v.node == variant => {
+                            args.iter().map(Some).collect()
+                        }
+                        p if is_catch_all(p) => vec![None; arity],
+                        _ => continue,
+                    };
+                    r.extend_from_slice(&row[1..]);
+                    specialized.push(r);
+                }
+                let mut col_tys = field_tys;
+                col_tys.extend_from_slice(rest_tys);
+                if let Some(mut witness) = self.uncovered_case(specialized, &col_tys, span)? {
+                    let rest = witness.split_off(arity);
+                    let head = if arity == 0 {
+                        format!("{enum_name}::{variant}")
+                    } else {
+                        format!("{enum_name}::{variant}({})", witness.join(", "))
+                    };
+                    let mut out = vec![head];
+                    out.extend(rest);
+                    return Ok(Some(out));
+                }
+            }
+            return Ok(None);
+        }
 
-                        let Some(var_def) =
-                            def.variants.iter().find(|v| v.name.node == variant.node)
-                        else {
-                            return Err(SemanticError {
-                                message: format!(
-                                    "unknown variant '{}' for enum '{}'",
-                                    variant.node, ty.node
-                                ),
-                                span: variant.span,
-                            });
-                        };
+        if let Type::Tuple(elem_tys) = base_type(ty) {
+            let arity = elem_tys.len();
+            let mut specialized: Vec<Vec<Option<&Pattern>>> = Vec::new();
+            for row in &expanded {
+                let mut r: Vec<Option<&Pattern>> = match row[0] {
+                    Some(Pattern::Tuple { elems, .. }) => elems.iter().map(Some).collect(),
+                    p if is_catch_all(p) => vec![None; arity],
+                    _ => continue,
+                };
+                r.extend_from_slice(&row[1..]);
+                specialized.push(r);
+            }
+            let mut col_tys = elem_tys.clone();
+            col_tys.extend_from_slice(rest_tys);
+            return Ok(self.uncovered_case(specialized, &col_tys, span)?.map(|mut witness| {
+                let rest = witness.split_off(arity);
+                let mut out = vec![format!("({})", witness.join(", "))];
+                out.extend(rest);
+                out
+            }));
+        }
 
-                        (
-                            var_def.fields.iter().map(|f| f.ty.clone()).collect(),
-                            def.params.clone(),
-                        )
-                    };
+        // Literal domains are open-ended: only catch-all rows can cover this column.
+        let defaults: Vec<Vec<Option<&Pattern>>> = expanded
+            .iter()
+            .filter(|row| is_catch_all(row[0]))
+            .map(|row| row[1..].to_vec())
+            .collect();
+        Ok(self.uncovered_case(defaults, rest_tys, span)?.map(|mut witness| {
+            witness.insert(0, "_".to_string());
+            witness
+        }))
+    }
 
-                    if binders.len() != expected_fields.len() {
-                        return Err(SemanticError {
-                            message: format!(
-                                "wrong number of binders for '{}::{}': expected {}, got {}",
-                                ty.node,
-                                variant.node,
-                                expected_fields.len(),
-                                binders.len()
-                            ),
-                            span: *span,
-                        });
+    /// Whether some value of `tys` matches the row `case` but none of `rows`, where a `None`
+    /// column matches anything. Literal domains are open-ended; enums range over their variants.
+    fn is_useful<'p>(
+        &mut self,
+        rows: Vec<Vec<Option<&'p Pattern>>>,
+        case: Vec<Option<&'p Pattern>>,
+        tys: &[Type],
+        span: Span,
+    ) -> Result<bool, SemanticError> {
+        let Some((ty, rest_tys)) = tys.split_first() else {
+            return Ok(rows.is_empty());
+        };
+
+        if let Some(Pattern::Or { alts, .. }) = case[0] {
+            for alt in alts {
+                let mut c = case.clone();
+                c[0] = Some(alt);
+                if self.is_useful(rows.clone(), c, tys, span)? {
+                    return Ok(true);
+                }
+            }
+            return Ok(false);
+        }
+        let mut expanded: Vec<Vec<Option<&Pattern>>> = Vec::new();
+        let mut pending = rows;
+        while let Some(row) = pending.pop() {
+            match row[0] {
+                Some(Pattern::Or { alts, .. }) => {
+                    for alt in alts {
+                        let mut r = row.clone();
+                        r[0] = Some(alt);
+                        pending.push(r);
                     }
+                }
+                _ => expanded.push(row),
+            }
+        }
+        let is_catch_all = |p: Option<&Pattern>| {
+            matches!(p, None | Some(Pattern::Wildcard { .. }) | Some(Pattern::Bind { .. }))
+        };
+        // A row whose first column opens into `fields` continues with them, then its other columns.
+        fn specialize<'p>(
+            row: &[Option<&'p Pattern>],
+            fields: Option<Vec<Option<&'p Pattern>>>,
+        ) -> Option<Vec<Option<&'p Pattern>>> {
+            let mut r = fields?;
+            r.extend_from_slice(&row[1..]);
+            Some(r)
+        }
 
-                    // Bind ctor fields for the arm body.
-                    self.push_scope();
-                    for (b, tr) in binders.iter().zip(expected_fields.iter()) {
-                        let field_ty = if def_params.is_empty() {
-                            self.resolve_type_ref(tr)?
-                        } else {
-                            let mut subst: HashMap<String, Type> = HashMap::new();
-                            for (p, a) in def_params.iter().zip(scrut_args.iter()) {
-                                subst.insert(p.name.node.clone(), a.clone());
-                            }
-                            self.resolve_type_ref_with_type_params(tr, &subst)?
-                        };
-                        self.define_val(b, field_ty, false)?;
+        let enum_ty = applied_name_and_args(base_type(ty))
+            .filter(|(name, _)| self.enum_defs.contains_key(*name))
+            .map(|(name, args)| (name.to_string(), args.to_vec()));
+        if let Some((enum_name, type_args)) = enum_ty {
+            let name = aura_ast::Spanned::new(span, enum_name);
+            for (variant, field_tys) in self.enum_variant_field_types(&name, &type_args)? {
+                let fields = |p: Option<&'p Pattern>| -> Option<Vec<Option<&'p Pattern>>> {
+                    match p {
+                        Some(Pattern::Ctor { variant: v, args, .. }) if v.node == variant => {
+                            Some(args.iter().map(Some).collect())
+                        }
+                        p if is_catch_all(p) => Some(vec![None; field_tys.len()]),
+                        _ => None,
                     }
-                    let _ = self.check_block(&arm.body)?;
-                    self.pop_scope();
-                    continue;
+                };
+                let Some(c) = specialize(&case, fields(case[0])) else { continue };
+                let specialized = expanded.iter().filter_map(|r| specialize(r, fields(r[0]))).collect();
+                let mut col_tys = field_tys.clone();
+                col_tys.extend_from_slice(rest_tys);
+                if self.is_useful(specialized, c, &col_tys, span)? {
+                    return Ok(true);
                 }
             }
+            return Ok(false);
+        }
 
-            let _ = self.check_block(&arm.body)?;
+        if let Type::Tuple(elem_tys) = base_type(ty) {
+            let fields = |p: Option<&'p Pattern>| -> Option<Vec<Option<&'p Pattern>>> {
+                match p {
+                    Some(Pattern::Tuple { elems, .. }) => Some(elems.iter().map(Some).collect()),
+                    p if is_catch_all(p) => Some(vec![None; elem_tys.len()]),
+                    _ => None,
+                }
+            };
+            let Some(c) = specialize(&case, fields(case[0])) else { return Ok(false) };
+            let specialized = expanded.iter().filter_map(|r| specialize(r, fields(r[0]))).collect();
+            let mut col_tys = elem_tys.clone();
+            col_tys.extend_from_slice(rest_tys);
+            return self.is_useful(specialized, c, &col_tys, span);
         }
 
-        Ok(())
+        // A literal is covered by the same literal or a catch-all; a catch-all only by another.
+        let literal = case[0].and_then(literal_pattern);
+        let covers = |p: Option<&Pattern>| {
+            is_catch_all(p) || (literal.is_some() && p.and_then(literal_pattern) == literal)
+        };
+        let rest: Vec<Vec<Option<&Pattern>>> =
+            expanded.iter().filter(|row| covers(row[0])).map(|row| row[1..].to_vec()).collect();
+        self.is_useful(rest, case[1..].to_vec(), rest_tys, span)
+    }
+
+    /// A loop may run its assignments before any use, so what was narrowed before it
+    /// no longer holds for what the loop assigns.
+    fn forget_loop_narrowing(&mut self, body: &Block) {
+        let mut assigned = BTreeSet::new();
+        collect_assigned_names(body, &mut assigned);
+        for name in assigned {
+            self.forget_narrowing(&name);
+        }
     }
 
     fn check_while(&mut self, while_stmt: &WhileStmt) -> Result<(), SemanticError> {
+        self.forget_loop_narrowing(&while_stmt.body);
         let cond_ty = self.infer_expr(&while_stmt.cond)?;
         if cond_ty != Type::Bool {
             return Err(SemanticError {
@@ -1438,21 +4059,123 @@ impl Checker {
                 });
             }
         }
-        let _ = self.check_block(&while_stmt.body)?;
+        self.loop_depth += 1;
+        let body = self.check_block(&while_stmt.body);
+        self.loop_depth -= 1;
+        body?;
+        Ok(())
+    }
+
+    fn check_for(&mut self, for_stmt: &ForStmt) -> Result<(), SemanticError> {
+        self.forget_loop_narrowing(&for_stmt.body);
+        let start_ty = self.infer_expr(&for_stmt.start)?;
+        let end_ty = self.infer_expr(&for_stmt.end)?;
+        for (ty, e) in [(&start_ty, &for_stmt.start), (&end_ty, &for_stmt.end)] {
+            if !is_u32_like(ty) {
+                return Err(SemanticError {
+                    message: format!("for range bound must be an integer, got {}", ty.display()),
+                    span: e.span,
+                });
+            }
+        }
+
+        // Refine the loop variable to [start.lo, end.hi) from the bound types.
+        let var_ty = match (u32_bounds(&start_ty), u32_bounds(&end_ty)) {
+            (Some((lo, _)), Some((_, hi))) if hi > lo => mk_u32_range(lo, hi - 1),
+            _ => Type::U32,
+        };
+
+        self.push_scope();
+        self.define_val(&for_stmt.var, var_ty, false)?;
+        if let Some(inv) = &for_stmt.invariant {
+            let inv_ty = self.infer_expr(inv)?;
+            if inv_ty != Type::Bool {
+                return Err(SemanticError {
+                    message: format!("for invariant must be bool, got {}", inv_ty.display()),
+                    span: inv.span,
+                });
+            }
+        }
+        self.loop_depth += 1;
+        let body = self.check_block(&for_stmt.body);
+        self.loop_depth -= 1;
+        body?;
+        self.pop_scope();
         Ok(())
     }
 
-    fn check_assignable(&self, expected: &Type, actual: &Type, rhs: &Expr) -> Result<(), SemanticError> {
+    /// Converts integer operands of different types to the type they meet at, which is the
+    /// result of the operator; anything else is the `expects` error.
+    fn unify_int_operands(
+        &mut self,
+        (left, lt): (&Expr, &Type),
+        (right, rt): (&Expr, &Type),
+        span: Span,
+        expects: &str,
+    ) -> Result<Type, SemanticError> {
+        if let Some(common) = common_int_type(lt, rt) {
+            self.note_int_cast(left, lt, &common);
+            self.note_int_cast(right, rt, &common);
+            return Ok(common);
+        }
+        let message = if is_int_like(lt) && is_int_like(rt) {
+            format!("no integer type holds both {} and {} values", lt.display(), rt.display())
+        } else {
+            format!("{expects}; got {},{}", lt.display(), rt.display())
+        };
+        Err(SemanticError { message, span })
+    }
+
+    /// Records that the value of `expr`, of type `from`, converts to the integer type `to`.
+    fn note_int_cast(&mut self, expr: &Expr, from: &Type, to: &Type) {
+        let (from, to) = (base_type(from), base_type(to));
+        if from != to && from.int_bounds().is_some() && to.int_bounds().is_some() {
+            self.int_casts.insert(
+                (self.current_instance.clone(), expr.span.offset(), expr.span.len()),
+                (from.clone(), to.clone()),
+            );
+        }
+    }
+
+    fn check_assignable(&mut self, expected: &Type, actual: &Type, rhs: &Expr) -> Result<(), SemanticError> {
+        // An `Option` where its payload is expected is read as that payload.
+        if let Some(payload) = self.option_payload(actual)
+            && self.option_payload(expected).is_none()
+            && (is_int_like(expected)
+                || matches!(base_type(expected), Type::Bool | Type::F64 | Type::String)
+                || base_type(expected) == base_type(&payload))
+        {
+            let payload = self.deref_option(rhs, actual)?;
+            return self.check_assignable(expected, &payload, rhs);
+        }
+        self.refine_enum_ctor(expected, rhs);
+        let solved;
+        let actual = if mentions_unknown(actual) && self.refine_expr(rhs, expected) {
+            solved = fill_unknowns(actual, expected);
+            &solved
+        } else {
+            actual
+        };
+        // Tuple and list literals widen their elements; an integer literal fits by its value.
+        let literal;
+        let actual = match rhs.kind {
+            ExprKind::IntLit(n) if is_int_like(actual) => {
+                literal = int_literal_type(n);
+                &literal
+            }
+            _ => actual,
+        };
         match (expected, actual, &rhs.kind) {
             // Range proof via literal.
             (
                 Type::ConstrainedRange { base, lo, hi },
                 _,
                 _,
-            ) if **base == Type::U32 => {
+            ) if base.int_bounds().is_some() => {
                 if self.defer_range_proofs {
-                    // Only allow u32-like values; proof is deferred to Z3.
-                    if is_u32_like(actual) {
+                    // Only allow integers; proof is deferred to Z3.
+                    if is_int_like(actual) {
+                        self.note_int_cast(rhs, actual, base);
                         Ok(())
                     } else {
                         Err(SemanticError {
@@ -1465,7 +4188,9 @@ impl Checker {
                         })
                     }
                 } else {
-                    self.verifier.prove_u32_in_range(rhs, *lo, *hi)
+                    self.verifier.prove_u32_in_range(rhs, *lo, *hi)?;
+                    self.note_int_cast(rhs, actual, base);
+                    Ok(())
                 }
             }
 
@@ -1515,15 +4240,20 @@ impl Checker {
                             let name = match &callee.kind {
                                 ExprKind::Member { base, member }
                                     if member.node == "infer"
-                                        && !matches!(&base.kind, ExprKind::Ident(id) if id.node == "ai") =>
+                                        && !matches!(&base.kind, ExprKind::Ident(id) if id.node == "ai")
+                                        && !self.is_known_cell(&expr_to_callee_name(callee)) =>
                                 {
                                     "ai.infer".to_string()
                                 }
                                 _ => expr_to_callee_name(callee),
                             };
 
-                            // ai.infer returns an unshaped tensor in sema; the verifier refines it.
-                            if name == "ai.infer" {
+                            // ai.infer and the tensor algebra intrinsics return an unshaped
+                            // tensor in sema; the verifier refines it.
+                            if matches!(
+                                name.as_str(),
+                                "ai.infer" | "ai.infer_batch" | "tensor.matmul" | "tensor.reshape" | "tensor.concat"
+                            ) {
                                 ok = true;
                             }
 
@@ -1610,11 +4340,106 @@ impl Checker {
                 Ok(())
             }
 
+            // Tuples: element-wise, using the literal's own elements when available so
+            // per-element range proofs still see constants.
+            (Type::Tuple(exp_elems), Type::Tuple(act_elems), _) if exp_elems.len() == act_elems.len() => {
+                for (i, (e, a)) in exp_elems.iter().zip(act_elems.iter()).enumerate() {
+                    let elem_rhs = match &rhs.kind {
+                        ExprKind::Tuple(items) => &items[i],
+                        _ => rhs,
+                    };
+                    let mismatch = || SemanticError {
+                        message: format!(
+                            "type mismatch: expected {}, got {}",
+                            expected.display(),
+                            actual.display()
+                        ),
+                        span: rhs.span,
+                    };
+                    // Only a literal's elements can be converted one by one.
+                    if is_int_like(a) && base_type(a) != base_type(e) && !matches!(rhs.kind, ExprKind::Tuple(_)) {
+                        return Err(mismatch());
+                    }
+                    self.check_assignable(e, a, elem_rhs).map_err(|_| mismatch())?;
+                }
+                Ok(())
+            }
+
+            // Lists: `[]` fits any list; literals are checked per element, other values
+            // need a matching element type.
+            (Type::List(exp_elem), Type::List(act_elem), _) => {
+                if **act_elem == Type::Unknown {
+                    return Ok(());
+                }
+                let mismatch = || SemanticError {
+                    message: format!(
+                        "type mismatch: expected {}, got {}",
+                        expected.display(),
+                        actual.display()
+                    ),
+                    span: rhs.span,
+                };
+                match &rhs.kind {
+                    ExprKind::ListLit(items) => {
+                        for item in items {
+                            self.check_assignable(exp_elem, act_elem, item).map_err(|_| mismatch())?;
+                        }
+                        Ok(())
+                    }
+                    _ if exp_elem == act_elem => Ok(()),
+                    _ => Err(mismatch()),
+                }
+            }
+
+            // Maps follow the list rules for both keys and values.
+            (Type::Map(exp_key, exp_val), Type::Map(act_key, act_val), _) => {
+                if **act_key == Type::Unknown {
+                    return Ok(());
+                }
+                let mismatch = || SemanticError {
+                    message: format!(
+                        "type mismatch: expected {}, got {}",
+                        expected.display(),
+                        actual.display()
+                    ),
+                    span: rhs.span,
+                };
+                match &rhs.kind {
+                    ExprKind::MapLit(entries) => {
+                        for (k, v) in entries {
+                            self.check_assignable(exp_key, act_key, k).map_err(|_| mismatch())?;
+                            self.check_assignable(exp_val, act_val, v).map_err(|_| mismatch())?;
+                        }
+                        Ok(())
+                    }
+                    _ if exp_key == act_key && exp_val == act_val => Ok(()),
+                    _ => Err(mismatch()),
+                }
+            }
+
             // Base equality (very minimal today).
             (a, b, _) if a == b => Ok(()),
 
-            // Allow constrained-range values to be used where the base type is expected.
-            (Type::U32, Type::ConstrainedRange { base, .. }, _) if **base == Type::U32 => Ok(()),
+            // Integers convert implicitly to any integer type that holds every value they can
+            // take: `u8` widens to `u32` or `i32`, while `u32` only narrows to `u8` once its range
+            // fits.
+            (_, _, _) if expected.int_bounds().is_some() && is_int_like(actual) => {
+                let (lo, hi) = int_value_bounds(actual).expect("integer type");
+                let (min, max) = expected.int_bounds().expect("integer type");
+                if min <= lo && hi <= max {
+                    self.note_int_cast(rhs, actual, expected);
+                    Ok(())
+                } else {
+                    Err(SemanticError {
+                        message: format!(
+                            "type mismatch: expected {}, got {} (values outside {min}..{max} don't fit)",
+                            expected.display(),
+                            actual.display()
+                        ),
+                        span: rhs.span,
+                    })
+                }
+            }
 
             // Assigning unconstrained u32 into constrained range requires proof.
             (Type::ConstrainedRange { .. }, Type::U32, _) if !self.defer_range_proofs => Err(SemanticError {
@@ -1635,31 +4460,36 @@ impl Checker {
 
     fn infer_expr(&mut self, expr: &Expr) -> Result<Type, SemanticError> {
         match &expr.kind {
-            ExprKind::IntLit(n) => {
-                if *n > U32_MAX {
-                    return Err(SemanticError {
-                        message: format!(
-                            "integer literal out of range for u32 (max={})",
-                            U32_MAX
-                        ),
-                        span: expr.span,
-                    });
+            ExprKind::IntLit(n) => Ok(int_literal_type(*n)),
+            ExprKind::FloatLit(_) => Ok(Type::F64),
+            ExprKind::StringLit(_) => Ok(Type::String),
+            ExprKind::InterpolatedString(parts) => {
+                for part in parts {
+                    let InterpPart::Expr(e) = part else { continue };
+                    let t = self.infer_expr(e)?;
+                    let t = self.deref_option(e, &t)?;
+                    let ok = is_int_like(&t)
+                        || matches!(base_type(&t), Type::F64 | Type::Bool | Type::String | Type::Unknown);
+                    if !ok {
+                        return Err(SemanticError {
+                            message: format!(
+                                "interpolated values must be integers, f64, bool, or String, got {}",
+                                t.display()
+                            ),
+                            span: e.span,
+                        });
+                    }
                 }
-                Ok(Type::ConstrainedRange {
-                    base: Box::new(Type::U32),
-                    lo: *n,
-                    hi: *n,
-                })
+                Ok(Type::String)
             }
-            ExprKind::StringLit(_) => Ok(Type::String),
             ExprKind::StyleLit { fields } => {
                 for (_k, v) in fields {
                     let t = self.infer_expr(v)?;
-                    let ok = matches!(base_type(&t), Type::U32 | Type::Bool | Type::String | Type::Style);
+                    let ok = matches!(base_type(&t), Type::U32 | Type::F64 | Type::Bool | Type::String | Type::Style);
                     if !ok {
                         return Err(SemanticError {
                             message: format!(
-                                "style field values must be scalar (u32/bool/String/Style), got {}",
+                                "style field values must be scalar (u32/f64/bool/String/Style), got {}",
                                 t.display()
                             ),
                             span: v.span,
@@ -1732,19 +4562,22 @@ impl Checker {
                     }
                 }
 
-                if def.params.is_empty() {
-                    Ok(Type::Named(name.node.clone()))
+                let ty = if def.params.is_empty() {
+                    Type::Named(name.node.clone())
                 } else {
                     let args = def
                         .params
                         .iter()
                         .map(|p| param_subst.get(&p.name.node).cloned().unwrap_or(Type::Unknown))
                         .collect::<Vec<_>>();
-                    Ok(Type::Applied {
+                    Type::Applied {
                         name: name.node.clone(),
                         args,
-                    })
-                }
+                    }
+                };
+                self.record_lits
+                    .insert((self.current_instance.clone(), expr.span.offset()), ty.clone());
+                Ok(ty)
             }
             ExprKind::Ident(id) => {
                 self.check_async_capture(&id.node, id.span)?;
@@ -1754,22 +4587,58 @@ impl Checker {
                     message: format!("unknown identifier '{}'", id.node),
                     span: id.span,
                 })?;
+                self.note_capture(&id.node, &ty);
                 
                 // Enforce linear type ownership constraints
                 self.enforce_linear_use(&id.node, &ty, id.span)?;
+                if self.live_loan(&id.node).is_some_and(|l| l.kind == BorrowKind::Mut) {
+                    return Err(SemanticError {
+                        message: format!("cannot use '{}' while it is mutably borrowed", id.node),
+                        span: id.span,
+                    });
+                }
                 
                 // Global liveness check for the Flow/linear capability model.
                 let _ = self.cap.ensure_alive(&id.node, id.span)?;
                 
                 Ok(ty)
             }
+            ExprKind::Borrow { kind, expr: inner } => {
+                if !self.borrow_sites.contains(&expr.span.offset()) {
+                    return Err(SemanticError {
+                        message: "a borrow can only be passed to a cell that takes `&`/`&mut`, or held by a `val`"
+                            .to_string(),
+                        span: expr.span,
+                    });
+                }
+                Ok(self.check_borrow(*kind, inner)?.0)
+            }
             ExprKind::Unary { op, expr: inner } => {
                 let t = self.infer_expr(inner)?;
+                let t = self.deref_option(inner, &t)?;
                 match op {
                     UnaryOp::Neg => {
+                        if is_f64(&t) {
+                            return Ok(Type::F64);
+                        }
+                        // A negated literal is signed: `-5` is an `i32`.
+                        if let ExprKind::IntLit(n) = inner.kind {
+                            if n > 1 << 63 {
+                                return Err(SemanticError {
+                                    message: format!("integer literal -{n} is out of range for i64"),
+                                    span: expr.span,
+                                });
+                            }
+                            let signed = if n <= 1 << 31 { Type::I32 } else { Type::I64 };
+                            self.note_int_cast(inner, &t, &signed);
+                            return Ok(signed);
+                        }
+                        if matches!(base_type(&t), Type::I32 | Type::I64) {
+                            return Ok(base_type(&t).clone());
+                        }
                         if !is_u32_like(&t) {
                             return Err(SemanticError {
-                                message: format!("unary '-' expects u32, got {}", t.display()),
+                                message: format!("unary '-' expects u32, a signed integer or f64, got {}", t.display()),
                                 span: inner.span,
                             });
                         }
@@ -1788,18 +4657,26 @@ impl Checker {
             }
             ExprKind::Binary { left, op, right } => {
                 let lt = self.infer_expr(left)?;
+                let mut lt = self.deref_option(left, &lt)?;
                 let rt = self.infer_expr(right)?;
+                let mut rt = self.deref_option(right, &rt)?;
+                if let Some(t) = self.opaque_binop(op, &lt, &rt, expr.span)? {
+                    return Ok(t);
+                }
+                // An operand of unknown type takes the other's: `chan.recv(ch) + 1` is a u32.
+                if lt == Type::Unknown && rt != Type::Unknown && self.refine_expr(left, &widen_ranges(&rt)) {
+                    lt = widen_ranges(&rt);
+                } else if rt == Type::Unknown && lt != Type::Unknown && self.refine_expr(right, &widen_ranges(&lt)) {
+                    rt = widen_ranges(&lt);
+                }
                 match op {
                     BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div => {
+                        if is_f64(&lt) && is_f64(&rt) {
+                            return Ok(Type::F64);
+                        }
                         if !is_u32_like(&lt) || !is_u32_like(&rt) {
-                            return Err(SemanticError {
-                                message: format!(
-                                    "arithmetic op expects u32,u32; got {},{}",
-                                    lt.display(),
-                                    rt.display()
-                                ),
-                                span: expr.span,
-                            });
+                            let expects = "arithmetic op expects u32,u32 or f64,f64";
+                            return self.unify_int_operands((left, &lt), (right, &rt), expr.span, expects);
                         }
 
                         // Range inference (prototype): keep u32 range information when possible.
@@ -1807,18 +4684,22 @@ impl Checker {
                         Ok(inferred)
                     }
                     BinOp::Eq | BinOp::Ne | BinOp::Lt | BinOp::Gt | BinOp::Le | BinOp::Ge => {
+                        if is_f64(&lt) && is_f64(&rt) {
+                            return Ok(Type::Bool);
+                        }
                         if !is_u32_like(&lt) || !is_u32_like(&rt) {
-                            return Err(SemanticError {
-                                message: format!(
-                                    "comparison op expects u32,u32; got {},{}",
-                                    lt.display(),
-                                    rt.display()
-                                ),
-                                span: expr.span,
-                            });
+                            let expects = "comparison op expects u32,u32 or f64,f64";
+                            self.unify_int_operands((left, &lt), (right, &rt), expr.span, expects)?;
                         }
                         Ok(Type::Bool)
                     }
+                    BinOp::BitAnd | BinOp::BitOr | BinOp::BitXor | BinOp::Shl | BinOp::Shr => {
+                        if !is_u32_like(&lt) || !is_u32_like(&rt) {
+                            let expects = "bitwise op expects u32,u32";
+                            return self.unify_int_operands((left, &lt), (right, &rt), expr.span, expects);
+                        }
+                        Ok(infer_u32_range_binop(op, &lt, &rt))
+                    }
                     BinOp::And | BinOp::Or => {
                         if lt != Type::Bool || rt != Type::Bool {
                             return Err(SemanticError {
@@ -1834,12 +4715,154 @@ impl Checker {
                     }
                 }
             }
+            ExprKind::Tuple(elems) => {
+                let mut tys = Vec::with_capacity(elems.len());
+                for e in elems {
+                    let t = self.infer_expr(e)?;
+                    // Building a tuple from a resource identifier moves it into the tuple.
+                    if let ExprKind::Ident(src) = &e.kind
+                        && self.is_non_copy_type(&t)
+                    {
+                        self.consume_move_from_value(&src.node, src.span)?;
+                    }
+                    // Literal ranges are widened so `(1, 2)` has type `(u32, u32)`.
+                    tys.push(base_type(&t).clone());
+                }
+                Ok(Type::Tuple(tys))
+            }
+            ExprKind::TupleIndex { base, index } => {
+                let base_ty = self.infer_expr(base)?;
+                let Type::Tuple(elems) = base_type(&base_ty) else {
+                    return Err(SemanticError {
+                        message: format!("cannot index non-tuple type {} with .{index}", base_ty.display()),
+                        span: expr.span,
+                    });
+                };
+                elems.get(*index as usize).cloned().ok_or_else(|| SemanticError {
+                    message: format!(
+                        "tuple index {index} out of bounds for {}",
+                        base_ty.display()
+                    ),
+                    span: expr.span,
+                })
+            }
+            ExprKind::ListLit(elems) => {
+                let mut elem_ty: Option<Type> = None;
+                for e in elems {
+                    let t = self.infer_expr(e)?;
+                    // Like tuples, a list literal takes ownership of resource identifiers.
+                    if let ExprKind::Ident(src) = &e.kind
+                        && self.is_non_copy_type(&t)
+                    {
+                        self.consume_move_from_value(&src.node, src.span)?;
+                    }
+                    let t = base_type(&t).clone();
+                    match &elem_ty {
+                        None => elem_ty = Some(t),
+                        Some(first) if *first == t => {}
+                        Some(first) => {
+                            return Err(SemanticError {
+                                message: format!(
+                                    "list elements must share one type: expected {}, got {}",
+                                    first.display(),
+                                    t.display()
+                                ),
+                                span: e.span,
+                            });
+                        }
+                    }
+                }
+                Ok(Type::List(Box::new(elem_ty.unwrap_or(Type::Unknown))))
+            }
+            ExprKind::MapLit(entries) => {
+                let mut key_ty: Option<Type> = None;
+                let mut val_ty: Option<Type> = None;
+                let mut seen_keys: HashSet<String> = HashSet::new();
+                for (k, v) in entries {
+                    let kt = base_type(&self.infer_expr(k)?).clone();
+                    if !is_map_key_type(&kt) {
+                        return Err(SemanticError {
+                            message: format!("map keys must be u32 or String, got {}", kt.display()),
+                            span: k.span,
+                        });
+                    }
+                    let literal_key = match &k.kind {
+                        ExprKind::IntLit(n) => Some(n.to_string()),
+                        ExprKind::StringLit(text) => Some(format!("{text:?}")),
+                        _ => None,
+                    };
+                    if let Some(text) = literal_key
+                        && !seen_keys.insert(text.clone())
+                    {
+                        return Err(SemanticError {
+                            message: format!("duplicate map key {text}"),
+                            span: k.span,
+                        });
+                    }
+                    let vt = self.infer_expr(v)?;
+                    // Like lists, a map literal takes ownership of resource identifiers.
+                    if let ExprKind::Ident(src) = &v.kind
+                        && self.is_non_copy_type(&vt)
+                    {
+                        self.consume_move_from_value(&src.node, src.span)?;
+                    }
+                    let vt = base_type(&vt).clone();
+                    unify_map_part(&mut key_ty, kt, "keys", k.span)?;
+                    unify_map_part(&mut val_ty, vt, "values", v.span)?;
+                }
+                Ok(Type::Map(
+                    Box::new(key_ty.unwrap_or(Type::Unknown)),
+                    Box::new(val_ty.unwrap_or(Type::Unknown)),
+                ))
+            }
+            ExprKind::Index { base, index } => {
+                let base_ty = self.infer_expr(base)?;
+                if let Type::Map(key, value) = base_type(&base_ty) {
+                    let (key, value) = ((**key).clone(), (**value).clone());
+                    if key == Type::Unknown {
+                        return Err(SemanticError {
+                            message: "cannot index an empty map of unknown key type".to_string(),
+                            span: expr.span,
+                        });
+                    }
+                    let key_ty = self.infer_expr(index)?;
+                    self.check_assignable(&key, &key_ty, index).map_err(|_| SemanticError {
+                        message: format!("map key must be {}, got {}", key.display(), key_ty.display()),
+                        span: index.span,
+                    })?;
+                    // Key presence is proven by aura-verify, mirroring `VerifiedHashMap::get`.
+                    self.record_map_access(expr.span);
+                    return Ok(value);
+                }
+                let Type::List(elem) = base_type(&base_ty) else {
+                    return Err(SemanticError {
+                        message: format!("cannot index non-list type {}", base_ty.display()),
+                        span: base.span,
+                    });
+                };
+                let elem = (**elem).clone();
+                let idx_ty = self.infer_expr(index)?;
+                if !is_u32_like(&idx_ty) {
+                    return Err(SemanticError {
+                        message: format!("list index must be u32, got {}", idx_ty.display()),
+                        span: index.span,
+                    });
+                }
+                if elem == Type::Unknown {
+                    return Err(SemanticError {
+                        message: "cannot index an empty list of unknown element type".to_string(),
+                        span: expr.span,
+                    });
+                }
+                // Bounds are proven by aura-verify against the list's length.
+                Ok(elem)
+            }
             ExprKind::Member { base, member } => {
                 let base_ty = self.infer_expr(base)?;
 
                 if let Some((rec_name, args)) = applied_name_and_args(base_type(&base_ty)) {
                     if let Some(def) = self.record_defs.get(rec_name) {
-                        let Some(field) = def.fields.iter().find(|f| f.name.node == member.node) else {
+                        let Some(index) = def.fields.iter().position(|f| f.name.node == member.node) else {
                             return Err(SemanticError {
                                 message: format!(
                                     "unknown field '{}' for record '{}'",
@@ -1848,6 +4871,7 @@ impl Checker {
                                 span: member.span,
                             });
                         };
+                        let field = &def.fields[index];
 
                         let ty = if def.params.is_empty() {
                             self.resolve_type_ref(&field.ty)?
@@ -1858,6 +4882,10 @@ impl Checker {
                             }
                             self.resolve_type_ref_with_type_params(&field.ty, &subst)?
                         };
+                        self.field_reads.insert(
+                            (self.current_instance.clone(), expr.span.offset(), expr.span.len()),
+                            index as u32,
+                        );
                         return Ok(ty);
                     }
                 }
@@ -1866,6 +4894,17 @@ impl Checker {
                 Ok(Type::Named(member.node.clone()))
             }
             ExprKind::Call { callee, args, trailing } => {
+                if let Some((some, arg)) = self.option_test(expr) {
+                    let t = self.infer_expr(arg)?;
+                    if t != Type::Unknown && self.option_payload(&t).is_none() {
+                        let name = if some { "is_some" } else { "is_none" };
+                        return Err(SemanticError {
+                            message: format!("{name} expects an Option, got {}", t.display()),
+                            span: arg.span,
+                        });
+                    }
+                    return Ok(Type::Bool);
+                }
                 // Enum constructor calls: `Type::Variant(...)`.
                 if let ExprKind::Member { base, member } = &callee.kind {
                     if let ExprKind::Ident(ty_id) = &base.kind {
@@ -1965,26 +5004,86 @@ impl Checker {
                                 });
                             }
 
-                            if def.params.is_empty() {
-                                return Ok(Type::Named(ty_id.node.clone()));
-                            }
-
-                            let args = def
-                                .params
-                                .iter()
-                                .map(|p| {
-                                    param_subst
-                                        .get(&p.name.node)
-                                        .cloned()
-                                        .unwrap_or(Type::Unknown)
-                                })
-                                .collect::<Vec<_>>();
+                            let ty = if def.params.is_empty() {
+                                Type::Named(ty_id.node.clone())
+                            } else {
+                                let args = def
+                                    .params
+                                    .iter()
+                                    .map(|p| {
+                                        param_subst
+                                            .get(&p.name.node)
+                                            .cloned()
+                                            .unwrap_or(Type::Unknown)
+                                    })
+                                    .collect::<Vec<_>>();
+                                Type::Applied {
+                                    name: ty_id.node.clone(),
+                                    args,
+                                }
+                            };
+                            self.enum_ctors
+                                .insert((self.current_instance.clone(), expr.span.offset()), ty.clone());
+                            return Ok(ty);
+                        }
+                    }
+                }
 
-                            return Ok(Type::Applied {
-                                name: ty_id.node.clone(),
-                                args,
+                // Map methods: `m.len()` and `m.contains(k)` lower to `map.*` runtime calls.
+                if let ExprKind::Member { base, member } = &callee.kind
+                    && let ExprKind::Ident(recv) = &base.kind
+                    && let Some(Type::Map(key, _)) = self.lookup_val(&recv.node).map(|t| base_type(&t).clone())
+                {
+                    let _ = self.infer_expr(base)?;
+                    let ty = match (member.node.as_str(), args.as_slice()) {
+                        ("len", []) => Type::U32,
+                        ("contains", [arg]) => {
+                            let arg = call_arg_value(arg);
+                            let arg_ty = self.infer_expr(arg)?;
+                            self.check_assignable(&key, &arg_ty, arg).map_err(|_| SemanticError {
+                                message: format!(
+                                    "map key must be {}, got {}",
+                                    key.display(),
+                                    arg_ty.display()
+                                ),
+                                span: arg.span,
+                            })?;
+                            Type::Bool
+                        }
+                        _ => {
+                            return Err(SemanticError {
+                                message: format!(
+                                    "unknown map method '{}'; maps support `.len()`, `.contains(k)` and `m[k]`",
+                                    member.node
+                                ),
+                                span: member.span,
                             });
                         }
+                    };
+                    self.record_map_access(expr.span);
+                    return Ok(ty);
+                }
+
+                // Calls through a closure value: `val f = -> { ... }` then `f()`.
+                if let ExprKind::Ident(id) = &callee.kind
+                    && !self.is_known_cell(&self.resolve_callee_in(self.current_namespace.as_deref(), &id.node))
+                    && let Some(Type::Closure(ret)) = self.lookup_val(&id.node)
+                {
+                    if !args.is_empty() || trailing.is_some() {
+                        return Err(SemanticError {
+                            message: format!("closure '{}' takes no arguments", id.node),
+                            span: expr.span,
+                        });
+                    }
+                    let _ = self.infer_expr(callee)?;
+                    self.closure_calls
+                        .insert((self.current_instance.clone(), expr.span.offset()), (*ret).clone());
+                    return Ok(*ret);
+                }
+
+                for a in args {
+                    if let ExprKind::Borrow { .. } = call_arg_value(a).kind {
+                        self.borrow_sites.insert(call_arg_value(a).span.offset());
                     }
                 }
 
@@ -1993,10 +5092,24 @@ impl Checker {
                 let (name, all_args): (String, Vec<&Expr>) = match &callee.kind {
                     ExprKind::Member { base, member }
                         if matches!(member.node.as_str(), "len" | "get" | "set")
-                            && !matches!(&base.kind, ExprKind::Ident(id) if id.node == "tensor") =>
+                            && !matches!(&base.kind, ExprKind::Ident(id) if id.node == "tensor")
+                            && !self.is_known_cell(&expr_to_callee_name(callee)) =>
                     {
                         // Ensure receiver is typed.
-                        let _recv_ty = self.infer_expr(base)?;
+                        let recv_ty = self.infer_expr(base)?;
+                        // Lists share the tensor runtime but only expose `.len()`.
+                        if let Type::List(_) = base_type(&recv_ty) {
+                            if member.node != "len" || !args.is_empty() {
+                                return Err(SemanticError {
+                                    message: format!(
+                                        "unknown list method '{}'; lists support `.len()` and `xs[i]`",
+                                        member.node
+                                    ),
+                                    span: member.span,
+                                });
+                            }
+                            return Ok(Type::U32);
+                        }
                         let mut v = Vec::with_capacity(args.len() + 1);
                         v.push(base.as_ref());
                         for a in args {
@@ -2006,7 +5119,8 @@ impl Checker {
                     }
                     ExprKind::Member { base, member }
                         if member.node == "infer"
-                            && !matches!(&base.kind, ExprKind::Ident(id) if id.node == "ai") =>
+                            && !matches!(&base.kind, ExprKind::Ident(id) if id.node == "ai")
+                            && !self.is_known_cell(&expr_to_callee_name(callee)) =>
                     {
                         let _recv_ty = self.infer_expr(base)?;
                         let mut v = Vec::with_capacity(args.len() + 1);
@@ -2017,7 +5131,12 @@ impl Checker {
                         ("ai.infer".to_string(), v)
                     }
                     _ => {
-                        let name = expr_to_callee_name(callee);
+                        let mut name = self
+                            .resolve_callee_in(self.current_namespace.as_deref(), &expr_to_callee_name(callee));
+                        self.check_callee_visible(&name, callee.span)?;
+                        if self.generic_cells.contains_key(&name) {
+                            name = self.instantiate_generic_call(&name, expr.span, args)?;
+                        }
                         if let Some(sig) = self.functions.get(&name) {
                             (name, self.resolve_call_args_against_sig(expr.span, args, sig)?)
                         } else {
@@ -2049,8 +5168,18 @@ impl Checker {
                     }
                 }
 
-                for a in &all_args {
-                    let _ = self.infer_expr(a)?;
+                for (i, a) in all_args.iter().enumerate() {
+                    if let Err(e) = self.infer_expr(a) {
+                        // By value into a cell's parameter, or into a consuming builtin.
+                        let moves = match self.param_borrows.get(&name) {
+                            Some(modes) => modes.get(i).is_some_and(|m| m.is_none()),
+                            None => sig.is_some() && CapabilityValidator::builtin_access(&name).is_none(),
+                        };
+                        if moves {
+                            self.note_second_move(a);
+                        }
+                        return Err(e);
+                    }
                 }
 
                 if let Some(sig) = sig {
@@ -2066,6 +5195,9 @@ impl Checker {
                             span: expr.span,
                         });
                     }
+                    let sig = self.check_chan_call(&name, &all_args, sig)?;
+                    let modes = self.param_borrows.get(&name).cloned().unwrap_or_default();
+                    let call_loans = self.call_loans.len();
 
                     // Minimal type checking (range constraints may appear on expected params later).
                     for (i, (expected, arg)) in sig.params.iter().zip(all_args.iter()).enumerate() {
@@ -2075,30 +5207,51 @@ impl Checker {
                             e
                         })?;
 
+                        // A borrowed parameter is lent the value for the call, so it is not moved.
+                        let mode = modes.get(i).copied().flatten();
+                        if self.check_borrow_arg(&name, &expected.name, mode, i, arg)? {
+                            continue;
+                        }
+
                         // Linear capability rule for calls:
                         // - Some builtins borrow (do not consume) non-copy values.
                         // - Otherwise, passing a non-copy identifier consumes (moves) it.
                         if let ExprKind::Ident(src) = &arg.kind {
                             if self.is_non_copy_type(&actual) {
-                                match name.as_str() {
+                                match CapabilityValidator::builtin_access(&name) {
                                     // Read-only borrows.
-                                    "tensor.len" | "tensor.get" | "collections.vector_len" | "collections.vector_get" => {
+                                    Some(ParamAccess::Read) => {
                                         let from = self.cap.ensure_alive(&src.node, src.span)?;
                                         let to = self.fresh_cap(arg.span);
                                         self.cap.lend_read(from, to, arg.span);
                                     }
 
                                     // Write borrow (must originate from a mutable binding).
-                                    "tensor.set" | "collections.vector_set" => {
+                                    Some(_) => {
+                                        if self.is_reference(&src.node) && !self.is_mutable(&src.node) {
+                                            return Err(SemanticError {
+                                                message: format!(
+                                                    "cannot mutate '{}' through a shared borrow",
+                                                    src.node
+                                                ),
+                                                span: src.span,
+                                            });
+                                        }
                                         if !self.is_mutable(&src.node) {
+                                            let what = match name.as_str() {
+                                                "fs.write" => "file",
+                                                "net.send" => "socket",
+                                                _ => "tensor",
+                                            };
                                             return Err(SemanticError {
                                                 message: format!(
-                                                    "cannot pass immutable val '{}' as mutable tensor receiver",
+                                                    "cannot pass immutable val '{}' as mutable {what} receiver",
                                                     src.node
                                                 ),
                                                 span: src.span,
                                             });
                                         }
+                                        self.check_not_borrowed(&src.node, "mutate", src.span)?;
                                         let from = self.cap.ensure_alive(&src.node, src.span)?;
                                         let to = self.fresh_cap(arg.span);
                                         self.cap.lend_write(from, to, arg.span);
@@ -2107,13 +5260,21 @@ impl Checker {
                                     // Default: move/consume.
                                     _ => {
                                         self.consume_move_from_value(&src.node, src.span)?;
+                                        if self.param_borrows.contains_key(&name)
+                                            && let Some(site) = self.move_sites.get_mut(&src.node)
+                                        {
+                                            site.into = Some((name.clone(), expected.name.clone()));
+                                        }
                                     }
                                 }
                             }
                         }
                     }
 
-                    Ok(sig.ret)
+                    self.call_loans.truncate(call_loans);
+
+                    self.check_literal_tensor_index(&name, &all_args)?;
+                    Ok(self.infer_result_type(&name, &all_args).unwrap_or(sig.ret))
                 } else {
                     // Unknown call: allow but type becomes unknown.
                     Ok(Type::Unknown)
@@ -2140,14 +5301,30 @@ impl Checker {
                 Ok(Type::Bool)
             }
             ExprKind::Lambda { op, body } => {
-                if *op == aura_ast::FlowOp::Async {
+                // A lambda body cannot jump out of a loop or return from the cell around it.
+                let saved_loop = std::mem::replace(&mut self.loop_depth, 0);
+                let saved_return = self.cell_return.take();
+                self.lambda_frames.push((self.scopes.len(), Vec::new()));
+                let result = if *op == aura_ast::FlowOp::Async {
                     self.async_lambda_bases.push(self.scopes.len());
-                    let _ = self.check_block(body)?;
+                    let r = self.check_block(body);
                     self.async_lambda_bases.pop();
+                    r
                 } else {
-                    let _ = self.check_block(body)?;
-                }
-                Ok(Type::Unknown)
+                    self.check_block(body)
+                };
+                let (_, captures) = self.lambda_frames.pop().expect("lambda frame");
+                self.loop_depth = saved_loop;
+                self.cell_return = saved_return;
+                let ret = result?;
+                self.lambdas.insert(
+                    (self.current_instance.clone(), expr.span.offset()),
+                    LambdaInfo {
+                        captures,
+                        ret: ret.clone(),
+                    },
+                );
+                Ok(Type::Closure(Box::new(ret)))
             }
             ExprKind::Flow { left, op: _, right } => {
                 // Capability rules:
@@ -2168,7 +5345,8 @@ impl Checker {
                     let (name, base_args): (String, Vec<&Expr>) = match &callee.kind {
                         ExprKind::Member { base, member }
                             if matches!(member.node.as_str(), "len" | "get" | "set")
-                                && !matches!(&base.kind, ExprKind::Ident(id) if id.node == "tensor") =>
+                                && !matches!(&base.kind, ExprKind::Ident(id) if id.node == "tensor")
+                                && !self.is_known_cell(&expr_to_callee_name(callee)) =>
                         {
                             let mut v = Vec::with_capacity(args.len() + 1);
                             v.push(base.as_ref());
@@ -2179,7 +5357,8 @@ impl Checker {
                         }
                         ExprKind::Member { base, member }
                             if member.node == "infer"
-                                && !matches!(&base.kind, ExprKind::Ident(id) if id.node == "ai") =>
+                                && !matches!(&base.kind, ExprKind::Ident(id) if id.node == "ai")
+                                && !self.is_known_cell(&expr_to_callee_name(callee)) =>
                         {
                             let mut v = Vec::with_capacity(args.len() + 1);
                             v.push(base.as_ref());
@@ -2193,10 +5372,24 @@ impl Checker {
                             for a in args {
                                 v.push(call_arg_value(a));
                             }
-                            (expr_to_callee_name(callee), v)
+                            let name = self.resolve_callee_in(
+                                self.current_namespace.as_deref(),
+                                &expr_to_callee_name(callee),
+                            );
+                            self.check_callee_visible(&name, callee.span)?;
+                            (name, v)
                         }
                     };
 
+                    if self.generic_cells.contains_key(&name) {
+                        return Err(SemanticError {
+                            message: format!(
+                                "generic cell '{name}' must be called directly, not as a flow target"
+                            ),
+                            span: right.span,
+                        });
+                    }
+
                     let sig = self.functions.get(&name).cloned();
                     if let Some(sig) = sig {
                         // Flow-injected call: expected args are [left] + base_args.
@@ -2212,6 +5405,9 @@ impl Checker {
                             });
                         }
 
+                        let all_args: Vec<&Expr> = std::iter::once(left.as_ref()).chain(base_args.iter().copied()).collect();
+                        let sig = self.check_chan_call(&name, &all_args, sig)?;
+
                         // Arg0: left
                         self.check_assignable(&sig.params[0].ty, &left_ty, left).map_err(|mut e| {
                             e.message = format!("arg 0: {}", e.message);
@@ -2231,7 +5427,8 @@ impl Checker {
                             })?;
                         }
 
-                        Ok(sig.ret)
+                        self.check_literal_tensor_index(&name, &all_args)?;
+                        Ok(self.infer_result_type(&name, &all_args).unwrap_or(sig.ret))
                     } else {
                         // Unknown call through flow: allow, but type becomes unknown.
                         Ok(Type::Unknown)
@@ -2283,6 +5480,14 @@ impl Checker {
                 });
             }
 
+            // Channels and cancellation tokens are shared between flows, not handed over.
+            if self
+                .lookup_val(&id.node)
+                .is_some_and(|ty| ty.channel_elem().is_some() || ty == Type::cancel_token())
+            {
+                return Ok(());
+            }
+
             let from = self.cap.ensure_alive(&id.node, id.span)?;
             let to = self.fresh_cap(span);
             self.cap.consume_move(from, to, span);
@@ -2298,8 +5503,14 @@ impl Checker {
 
     fn resolve_type_ref(&self, tr: &TypeRef) -> Result<Type, SemanticError> {
         let base = match tr.name.node.as_str() {
+            "u8" => Type::U8,
+            "u16" => Type::U16,
             "u32" => Type::U32,
+            "u64" => Type::U64,
+            "i32" => Type::I32,
+            "i64" => Type::I64,
             "Int" => Type::U32,
+            "f64" => Type::F64,
             "bool" => Type::Bool,
             "Tensor" => {
                 // `Tensor<Elem, [d0, d1, ...]>` (shape optional)
@@ -2350,6 +5561,54 @@ impl Checker {
             "Style" => Type::Style,
             "Unit" => Type::Unit,
             "Model" => Type::Model,
+            "Tuple" if tr.args.len() >= 2 => Type::Tuple(
+                tr.args
+                    .iter()
+                    .map(|a| match a {
+                        TypeArg::Type(t) => self.resolve_type_ref(t),
+                        TypeArg::Shape(_) => Err(SemanticError {
+                            message: "tuple elements must be types".to_string(),
+                            span: tr.span,
+                        }),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            "Channel" => match tr.args.as_slice() {
+                [TypeArg::Type(t)] => Type::channel(self.resolve_type_ref(t)?),
+                _ => {
+                    return Err(SemanticError {
+                        message: "Channel expects exactly one element type: `Channel<T>`".to_string(),
+                        span: tr.span,
+                    });
+                }
+            },
+            "List" => match tr.args.as_slice() {
+                [TypeArg::Type(t)] => Type::List(Box::new(self.resolve_type_ref(t)?)),
+                _ => {
+                    return Err(SemanticError {
+                        message: "List expects exactly one element type: `List<T>`".to_string(),
+                        span: tr.span,
+                    });
+                }
+            },
+            "Map" => match tr.args.as_slice() {
+                [TypeArg::Type(k), TypeArg::Type(v)] => {
+                    let key = self.resolve_type_ref(k)?;
+                    if !is_map_key_type(&key) {
+                        return Err(SemanticError {
+                            message: format!("map keys must be u32 or String, got {}", key.display()),
+                            span: k.span,
+                        });
+                    }
+                    Type::Map(Box::new(key), Box::new(self.resolve_type_ref(v)?))
+                }
+                _ => {
+                    return Err(SemanticError {
+                        message: "Map expects a key and a value type: `Map<K, V>`".to_string(),
+                        span: tr.span,
+                    });
+                }
+            },
             other => {
                 if let Some(def) = self.record_defs.get(other) {
                     return self.resolve_nominal_type_ref(other, &def.params, &tr.args, tr.span);
@@ -2395,6 +5654,14 @@ impl Checker {
                     span: range.span,
                 });
             }
+            if let Some((_, max)) = base.int_bounds()
+                && i128::from(hi) > max
+            {
+                return Err(SemanticError {
+                    message: format!("range [{lo}..{hi}] does not fit {}", base.display()),
+                    span: range.span,
+                });
+            }
 
             Ok(Type::ConstrainedRange {
                 base: Box::new(base),
@@ -2496,7 +5763,13 @@ impl Checker {
         }
 
         let base = match tr.name.node.as_str() {
+            "u8" => Type::U8,
+            "u16" => Type::U16,
             "u32" | "Int" => Type::U32,
+            "u64" => Type::U64,
+            "i32" => Type::I32,
+            "i64" => Type::I64,
+            "f64" => Type::F64,
             "bool" => Type::Bool,
             "String" => Type::String,
             "Style" => Type::Style,
@@ -2545,6 +5818,48 @@ impl Checker {
                     shape,
                 }
             }
+            "Tuple" if tr.args.len() >= 2 => Type::Tuple(
+                tr.args
+                    .iter()
+                    .map(|a| match a {
+                        TypeArg::Type(t) => self.resolve_type_ref_with_type_params(t, type_params),
+                        TypeArg::Shape(_) => Err(SemanticError {
+                            message: "tuple elements must be types".to_string(),
+                            span: tr.span,
+                        }),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            "Channel" => match tr.args.as_slice() {
+                [TypeArg::Type(t)] => Type::channel(self.resolve_type_ref_with_type_params(t, type_params)?),
+                _ => {
+                    return Err(SemanticError {
+                        message: "Channel expects exactly one element type: `Channel<T>`".to_string(),
+                        span: tr.span,
+                    });
+                }
+            },
+            "List" => match tr.args.as_slice() {
+                [TypeArg::Type(t)] => Type::List(Box::new(self.resolve_type_ref_with_type_params(t, type_params)?)),
+                _ => {
+                    return Err(SemanticError {
+                        message: "List expects exactly one element type: `List<T>`".to_string(),
+                        span: tr.span,
+                    });
+                }
+            },
+            "Map" => match tr.args.as_slice() {
+                [TypeArg::Type(k), TypeArg::Type(v)] => Type::Map(
+                    Box::new(self.resolve_type_ref_with_type_params(k, type_params)?),
+                    Box::new(self.resolve_type_ref_with_type_params(v, type_params)?),
+                ),
+                _ => {
+                    return Err(SemanticError {
+                        message: "Map expects a key and a value type: `Map<K, V>`".to_string(),
+                        span: tr.span,
+                    });
+                }
+            },
             other => {
                 if tr.args.is_empty() {
                     return self.resolve_type_ref(tr);
@@ -2584,6 +5899,14 @@ impl Checker {
                     span: range.span,
                 });
             }
+            if let Some((_, max)) = base.int_bounds()
+                && i128::from(hi) > max
+            {
+                return Err(SemanticError {
+                    message: format!("range [{lo}..{hi}] does not fit {}", base.display()),
+                    span: range.span,
+                });
+            }
 
             Ok(Type::ConstrainedRange {
                 base: Box::new(base),
@@ -2704,6 +6027,31 @@ impl Checker {
                 }
                 _ => Ok(()),
             }
+        } else if expected.name.node == "Tuple" {
+            let Type::Tuple(act_elems) = base_type(actual) else {
+                return Ok(());
+            };
+            for (a, t) in expected.args.iter().zip(act_elems.iter()) {
+                if let TypeArg::Type(e) = a {
+                    self.unify_type_params_in_typeref(e, t, params, subst)?;
+                }
+            }
+            Ok(())
+        } else if expected.name.node == "List" {
+            match (expected.args.as_slice(), base_type(actual)) {
+                ([TypeArg::Type(e)], Type::List(act_elem)) => {
+                    self.unify_type_params_in_typeref(e, act_elem, params, subst)
+                }
+                _ => Ok(()),
+            }
+        } else if expected.name.node == "Map" {
+            match (expected.args.as_slice(), base_type(actual)) {
+                ([TypeArg::Type(k), TypeArg::Type(v)], Type::Map(act_key, act_val)) => {
+                    self.unify_type_params_in_typeref(k, act_key, params, subst)?;
+                    self.unify_type_params_in_typeref(v, act_val, params, subst)
+                }
+                _ => Ok(()),
+            }
         } else {
             Ok(())
         }
@@ -2718,6 +6066,7 @@ impl Checker {
             });
         }
         scope.insert(name.node.clone(), ty.clone());
+        self.narrowed.last_mut().expect("scope stack").remove(&name.node);
 
         if mutable {
             let m = self.mut_scopes.last_mut().expect("mut scope stack");
@@ -2762,6 +6111,21 @@ impl Checker {
         None
     }
 
+    /// Records `name` as captured by every lambda being checked that it is defined outside of.
+    fn note_capture(&mut self, name: &str, ty: &Type) {
+        if self.lambda_frames.is_empty() || matches!(ty, Type::Named(n) if n.starts_with("<module:")) {
+            return;
+        }
+        let Some(def_idx) = self.lookup_scope_index(name) else {
+            return;
+        };
+        for (base, captures) in &mut self.lambda_frames {
+            if def_idx < *base && !captures.iter().any(|(n, _)| n == name) {
+                captures.push((name.to_string(), ty.clone()));
+            }
+        }
+    }
+
     fn check_async_capture(&self, name: &str, span: Span) -> Result<(), SemanticError> {
         let Some(&base) = self.async_lambda_bases.last() else {
             return Ok(());
@@ -2804,9 +6168,10 @@ impl Checker {
 
         match base_type(ty) {
             Type::Named(n) if n.starts_with("<module:") => false,
-            Type::Tensor { .. } | Type::Model | Type::Style => true,
+            Type::Tensor { .. } | Type::List(_) | Type::Map(..) | Type::Model | Type::Style => true,
             Type::Named(n) => is_linear_nominal_name(n.as_str()),
             Type::Applied { name, .. } => is_linear_nominal_name(name.as_str()),
+            Type::Tuple(elems) => elems.iter().any(|t| self.is_non_copy_type(t)),
             _ => false,
         }
     }
@@ -2818,6 +6183,7 @@ impl Checker {
             
             // Only mark as consumed if non-copy type
             if self.is_non_copy_type(&ty) {
+                self.check_not_borrowed(value_name, "move", span)?;
                 self.mark_consumed(value_name, span)?;
             }
         }
@@ -2832,13 +6198,73 @@ impl Checker {
     fn push_scope(&mut self) {
         self.scopes.push(HashMap::new());
         self.mut_scopes.push(HashSet::new());
+        self.model_outputs.push(HashMap::new());
         self.ownership_states.push(HashMap::new());
+        self.loans.push(Vec::new());
+        self.narrowed.push(HashSet::new());
     }
 
     fn pop_scope(&mut self) {
         let _ = self.scopes.pop();
         let _ = self.mut_scopes.pop();
+        let _ = self.model_outputs.pop();
         let _ = self.ownership_states.pop();
+        let _ = self.loans.pop();
+        let _ = self.narrowed.pop();
+    }
+
+    /// The `Option` binding a condition tests, and whether the condition holds when it is
+    /// `Some`: `is_some(o)`, `is_none(o)` and their negations.
+    fn option_condition(&self, cond: &Expr) -> Option<(String, bool)> {
+        if let ExprKind::Unary { op: UnaryOp::Not, expr } = &cond.kind {
+            return self.option_condition(expr).map(|(name, some)| (name, !some));
+        }
+        let (some, arg) = self.option_test(cond)?;
+        match &arg.kind {
+            ExprKind::Ident(id) => Some((id.node.clone(), some)),
+            _ => None,
+        }
+    }
+
+    fn narrow_to_some(&mut self, name: &str) {
+        self.narrowed.last_mut().expect("scope stack").insert(name.to_string());
+    }
+
+    /// Whether the binding `name` refers to is known to hold `Some`.
+    fn is_narrowed(&self, name: &str) -> bool {
+        let Some(idx) = self.lookup_scope_index(name) else {
+            return false;
+        };
+        self.narrowed[idx..].iter().any(|s| s.contains(name))
+    }
+
+    fn forget_narrowing(&mut self, name: &str) {
+        for scope in &mut self.narrowed {
+            scope.remove(name);
+        }
+    }
+
+    /// Type of reading the payload of `expr`, an `Option` of type `ty`: only a binding
+    /// narrowed to `Some` can be read that way.
+    fn deref_option(&mut self, expr: &Expr, ty: &Type) -> Result<Type, SemanticError> {
+        let Some(payload) = self.option_payload(ty) else {
+            return Ok(ty.clone());
+        };
+        let message = match &expr.kind {
+            ExprKind::Ident(id) if self.is_narrowed(&id.node) => {
+                self.option_unwraps
+                    .insert((self.current_instance.clone(), expr.span.offset(), expr.span.len()));
+                return Ok(payload);
+            }
+            ExprKind::Ident(id) => format!(
+                "'{0}' may be None here; check it first with `if is_some({0}):` or match on `Option::Some`",
+                id.node
+            ),
+            _ => "this value may be None; bind it with `val` and check it with `is_some` first, or match on \
+                  `Option::Some`"
+                .to_string(),
+        };
+        Err(SemanticError { message, span: expr.span })
     }
 
     fn instantiate_type_alias(
@@ -2930,10 +6356,23 @@ impl Checker {
     /// Mark a variable as consumed (moved) and check it wasn't already consumed.
     /// Only enforces for non-copy types.
     fn mark_consumed(&mut self, name: &str, span: Span) -> Result<(), SemanticError> {
+        if self.deferred_moves.iter().any(|(n, _)| n == name) {
+            return Err(SemanticError {
+                message: format!("value '{name}' is consumed by a pending defer and cannot be moved before its scope exits"),
+                span,
+            });
+        }
         let current_state = self.get_ownership(name);
+        if current_state == OwnershipState::Borrowed {
+            return Err(SemanticError {
+                message: format!("cannot move out of borrowed '{name}'; it is only lent to this scope"),
+                span,
+            });
+        }
         
         // Consumed values cannot be used again (unless type is copy)
         if current_state == OwnershipState::Consumed {
+            self.note_move_violation(ViolationKind::DoubleMove, name, span);
             return Err(SemanticError {
                 message: format!("value '{}' used after move (was consumed by previous use)", name),
                 span,
@@ -2941,15 +6380,173 @@ impl Checker {
         }
 
         self.set_ownership(name, OwnershipState::Consumed);
+        self.move_sites.insert(name.to_string(), MoveSite { span, into: None });
+        if self.defer_depth > 0 {
+            self.defer_consumed.push((name.to_string(), span));
+        }
         Ok(())
     }
 
+    /// Binds a cell parameter. A borrowed one holds the caller's value: `&mut` ones may be
+    /// updated in place, but neither kind may be moved or reassigned.
+    fn define_param(&mut self, p: &aura_ast::Param, ty: Type) -> Result<(), SemanticError> {
+        match p.borrow {
+            None => self.define_val(&p.name, ty, p.mutable),
+            Some(kind) => {
+                self.define_val(&p.name, ty, kind == BorrowKind::Mut)?;
+                self.set_ownership(&p.name.node, OwnershipState::Borrowed);
+                Ok(())
+            }
+        }
+    }
+
+    /// Whether `name` is a binding that holds a borrow rather than an owned value.
+    fn is_reference(&self, name: &str) -> bool {
+        self.get_ownership(name) == OwnershipState::Borrowed
+    }
+
+    /// Rejects handing out (`action`: returning or yielding) a non-copy value a binding only
+    /// borrows: its owner still holds it.
+    fn check_not_lent_out(&self, value: &Expr, ty: &Type, action: &str) -> Result<(), SemanticError> {
+        match &value.kind {
+            ExprKind::Ident(id) if self.is_reference(&id.node) && self.is_non_copy_type(ty) => Err(SemanticError {
+                message: format!("cannot {action} borrowed '{}': it is only lent to this scope", id.node),
+                span: id.span,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// The live loan on `name` (a mutable one, if there is one).
+    fn live_loan(&self, name: &str) -> Option<&Loan> {
+        let depth = self.lookup_scope_index(name)?;
+        let mut loans = self
+            .loans
+            .iter()
+            .flatten()
+            .chain(&self.call_loans)
+            .filter(|l| l.target == name && l.depth == depth);
+        let first = loans.next()?;
+        Some(loans.find(|l| l.kind == BorrowKind::Mut).unwrap_or(first))
+    }
+
+    /// Rejects `action` on `name` while it is borrowed.
+    fn check_not_borrowed(&self, name: &str, action: &str, span: Span) -> Result<(), SemanticError> {
+        match self.live_loan(name) {
+            Some(loan) => Err(SemanticError {
+                message: format!(
+                    "cannot {action} '{name}' while it is {}",
+                    if loan.kind == BorrowKind::Mut { "mutably borrowed" } else { "borrowed" }
+                ),
+                span,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks `&x`/`&mut x` (or a binding that already holds a borrow being lent on) and
+    /// returns the loan it takes; the caller decides how long the loan lives.
+    fn check_borrow(&mut self, kind: BorrowKind, operand: &Expr) -> Result<(Type, Loan), SemanticError> {
+        let ExprKind::Ident(id) = &operand.kind else {
+            return Err(SemanticError {
+                message: "only a named value can be borrowed".to_string(),
+                span: operand.span,
+            });
+        };
+        let name = id.node.as_str();
+        if let Some(loan) = self.live_loan(name) {
+            let message = match (kind, loan.kind) {
+                (BorrowKind::Mut, BorrowKind::Mut) => {
+                    format!("cannot borrow '{name}' as mutable more than once at a time")
+                }
+                (BorrowKind::Mut, BorrowKind::Shared) => {
+                    format!("cannot borrow '{name}' as mutable because it is also borrowed as immutable")
+                }
+                (BorrowKind::Shared, BorrowKind::Mut) => {
+                    format!("cannot borrow '{name}' as immutable because it is also borrowed as mutable")
+                }
+                (BorrowKind::Shared, BorrowKind::Shared) => String::new(),
+            };
+            if !message.is_empty() {
+                return Err(SemanticError { message, span: id.span });
+            }
+        }
+        let ty = self.infer_expr(operand)?;
+        if kind == BorrowKind::Mut && !self.is_mutable(name) {
+            let message = if self.is_reference(name) {
+                format!("cannot borrow '{name}' as mutable: it is itself a shared borrow")
+            } else {
+                format!("cannot borrow immutable val '{name}' as mutable")
+            };
+            return Err(SemanticError { message, span: id.span });
+        }
+        let depth = self.lookup_scope_index(name).expect("borrowed value is bound");
+        Ok((
+            ty,
+            Loan {
+                target: name.to_string(),
+                depth,
+                kind,
+            },
+        ))
+    }
+
+    /// Checks argument `i` (`arg`) of a call to `cell`, which takes parameter `param` as
+    /// `mode`: a borrowed parameter needs `&x`/`&mut x` or a binding holding such a borrow,
+    /// and an owned one needs a value. Returns whether the argument is lent, in which case
+    /// the loan lasts until the call has been checked.
+    fn check_borrow_arg(
+        &mut self,
+        cell: &str,
+        param: &str,
+        mode: Option<BorrowKind>,
+        i: usize,
+        arg: &Expr,
+    ) -> Result<bool, SemanticError> {
+        let written = |kind| if kind == BorrowKind::Mut { "&mut " } else { "&" };
+        let loan = match (&arg.kind, mode) {
+            (ExprKind::Borrow { kind, expr }, Some(mode)) if *kind == mode => self.check_borrow(mode, expr)?.1,
+            (ExprKind::Borrow { .. }, Some(mode)) => {
+                return Err(SemanticError {
+                    message: format!(
+                        "arg {i}: '{cell}' borrows '{param}' as `{}`",
+                        written(mode).trim_end()
+                    ),
+                    span: arg.span,
+                });
+            }
+            (ExprKind::Borrow { expr, .. }, None) => {
+                return Err(SemanticError {
+                    message: format!(
+                        "arg {i}: '{cell}' takes '{param}' by value; pass it without `&`"
+                    ),
+                    span: expr.span,
+                });
+            }
+            (ExprKind::Ident(id), Some(mode)) if self.is_reference(&id.node) => self.check_borrow(mode, arg)?.1,
+            (_, Some(mode)) => {
+                let pass = match &arg.kind {
+                    ExprKind::Ident(id) => format!("pass `{}{}`", written(mode), id.node),
+                    _ => format!("pass `{}x` for a named value `x`", written(mode)),
+                };
+                return Err(SemanticError {
+                    message: format!("arg {i}: '{cell}' borrows '{param}'; {pass}"),
+                    span: arg.span,
+                });
+            }
+            (_, None) => return Ok(false),
+        };
+        self.call_loans.push(loan);
+        Ok(true)
+    }
+
     /// Check that a non-copy value hasn't been consumed yet.
     /// Called before reading/using a value.
-    fn check_not_consumed(&self, name: &str, span: Span) -> Result<(), SemanticError> {
+    fn check_not_consumed(&mut self, name: &str, span: Span) -> Result<(), SemanticError> {
         let current_state = self.get_ownership(name);
         
         if current_state == OwnershipState::Consumed {
+            self.note_move_violation(ViolationKind::UseAfterMove, name, span);
             return Err(SemanticError {
                 message: format!("value '{}' used after move", name),
                 span,
@@ -2966,6 +6563,36 @@ impl Checker {
         Ok(())
     }
 
+    /// Records the use of moved-out `name` at `span` for `move_violation`.
+    fn note_move_violation(&mut self, kind: ViolationKind, name: &str, span: Span) {
+        let site = self.move_sites.get(name).cloned();
+        self.move_violation = Some(MoveViolation {
+            kind,
+            name: name.to_string(),
+            ty: self
+                .lookup_val(name)
+                .map(|t| match t.display() {
+                    // `Tensor<<unknown>>` reads better as just `Tensor`.
+                    shown if mentions_unknown(&t) => shown.split('<').next().unwrap_or_default().to_string(),
+                    shown => shown,
+                })
+                .unwrap_or_default(),
+            used_at: span,
+            moved_at: site.as_ref().map(|s| s.span),
+            moved_into: site.and_then(|s| s.into),
+        });
+    }
+
+    /// A use-after-move found while inferring `expr` is a second move when `expr` is itself
+    /// being moved, as a bare identifier passed or bound by value.
+    fn note_second_move(&mut self, expr: &Expr) {
+        if let (ExprKind::Ident(_), Some(v)) = (&expr.kind, self.move_violation.as_mut())
+            && v.used_at == expr.span
+        {
+            v.kind = ViolationKind::DoubleMove;
+        }
+    }
+
     /// Enforce linear type rules when using an identifier.
     /// 
     /// For non-copy types, track the use and potentially mark as consumed.
@@ -3077,8 +6704,8 @@ fn refinement_u32_range_from_where(var: &str, expr: &Expr) -> Option<(u64, u64)>
 fn type_satisfies_trait(ty: &Type, tr: &str) -> bool {
     // MVP built-in trait satisfaction table.
     match tr {
-        "Numeric" => matches!(base_type(ty), Type::U32),
-        "Scalar" => matches!(base_type(ty), Type::U32 | Type::Bool | Type::String | Type::Style),
+        "Numeric" => matches!(base_type(ty), Type::U32 | Type::F64),
+        "Scalar" => matches!(base_type(ty), Type::U32 | Type::F64 | Type::Bool | Type::String | Type::Style),
         "Eq" => matches!(base_type(ty), Type::U32 | Type::Bool | Type::String),
         _ => false,
     }
@@ -3148,6 +6775,25 @@ impl Checker {
     }
 }
 
+/// `cell net::get` lives in namespace `net`.
+fn cell_namespace(cell: &CellDef) -> Option<String> {
+    cell.name.node.rsplit_once('.').map(|(ns, _)| ns.to_string())
+}
+
+/// `map` instantiated at `u32` becomes `map__u32`; symbols stay C-identifier safe.
+fn mangle_instance_name(name: &str, type_args: &[Type]) -> String {
+    let mut out = name.to_string();
+    for t in type_args {
+        out.push_str("__");
+        out.extend(
+            t.display()
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }),
+        );
+    }
+    out
+}
+
 fn const_u64(expr: &Expr) -> Option<u64> {
     match expr.kind {
         ExprKind::IntLit(n) => Some(n),
@@ -3155,7 +6801,7 @@ fn const_u64(expr: &Expr) -> Option<u64> {
     }
 }
 
-fn expr_to_callee_name(expr: &Expr) -> String {
+pub(crate) fn expr_to_callee_name(expr: &Expr) -> String {
     match &expr.kind {
         ExprKind::Ident(id) => id.node.clone(),
         ExprKind::Member { base, member } => format!("{}.{}", expr_to_callee_name(base), member.node),
@@ -3171,12 +6817,33 @@ fn collect_value_idents(expr: &Expr, out: &mut Vec<Ident>) {
                 collect_value_idents(v, out);
             }
         }
-        ExprKind::Unary { expr, .. } => collect_value_idents(expr, out),
+        ExprKind::Unary { expr, .. } | ExprKind::Borrow { expr, .. } => collect_value_idents(expr, out),
         ExprKind::Binary { left, right, .. } => {
             collect_value_idents(left, out);
             collect_value_idents(right, out);
         }
         ExprKind::Member { base, .. } => collect_value_idents(base, out),
+        ExprKind::Tuple(elems) => {
+            for e in elems {
+                collect_value_idents(e, out);
+            }
+        }
+        ExprKind::TupleIndex { base, .. } => collect_value_idents(base, out),
+        ExprKind::ListLit(elems) => {
+            for e in elems {
+                collect_value_idents(e, out);
+            }
+        }
+        ExprKind::MapLit(entries) => {
+            for (k, v) in entries {
+                collect_value_idents(k, out);
+                collect_value_idents(v, out);
+            }
+        }
+        ExprKind::Index { base, index } => {
+            collect_value_idents(base, out);
+            collect_value_idents(index, out);
+        }
         ExprKind::Call { args, trailing, .. } => {
             for a in args {
                 collect_value_idents(call_arg_value(a), out);
@@ -3220,11 +6887,18 @@ fn collect_value_idents(expr: &Expr, out: &mut Vec<Ident>) {
                 .collect();
             out.extend(tmp.into_iter().filter(|id| !bound.contains(&id.node)));
         }
-        ExprKind::IntLit(_) | ExprKind::StringLit(_) => {}
+        ExprKind::InterpolatedString(parts) => {
+            for part in parts {
+                if let InterpPart::Expr(e) = part {
+                    collect_value_idents(e, out);
+                }
+            }
+        }
+        ExprKind::IntLit(_) | ExprKind::FloatLit(_) | ExprKind::StringLit(_) => {}
     }
 }
 
-fn call_arg_value(arg: &CallArg) -> &Expr {
+pub(crate) fn call_arg_value(arg: &CallArg) -> &Expr {
     match arg {
         CallArg::Positional(e) => e,
         CallArg::Named { value, .. } => value,
@@ -0,0 +1,313 @@
+//! Program-wide symbol table: every top-level definition of every file, under a stable
+//! fully-qualified name.
+//!
+//! Files are added one at a time, each with its module path and the offset it was parsed at,
+//! so a symbol knows its file and local span without reconciling text offsets. A module's
+//! cells are qualified with its path (`net::http::get`) and sema calls them by the last
+//! segment (`http.get`). Types, traits and extern cells share one global namespace, so
+//! defining one twice in any two files is a conflict. Stdlib modules are injected side by
+//! side and may repeat each other, but user code may not redefine what they define.
+//!
+//! Handed to [`crate::Checker::set_symbols`], the table decides who may call a module cell:
+//! sema looks the callee up by its resolved name and checks the defining symbol's module and
+//! `pub`, reporting it by its qualified name.
+
+use std::collections::HashMap;
+
+use aura_ast::{span, Span, Stmt};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SymbolKind {
+    Cell,
+    ExternCell,
+    Record,
+    Enum,
+    TypeAlias,
+    Trait,
+}
+
+impl SymbolKind {
+    pub fn display(&self) -> &'static str {
+        match self {
+            SymbolKind::Cell => "cell",
+            SymbolKind::ExternCell => "extern cell",
+            SymbolKind::Record => "record",
+            SymbolKind::Enum => "enum",
+            SymbolKind::TypeAlias => "type",
+            SymbolKind::Trait => "trait",
+        }
+    }
+
+    /// Kinds that can't share a name: callables, types and traits each have their own.
+    fn namespace(&self) -> u8 {
+        match self {
+            SymbolKind::Cell | SymbolKind::ExternCell => 0,
+            SymbolKind::Record | SymbolKind::Enum | SymbolKind::TypeAlias => 1,
+            SymbolKind::Trait => 2,
+        }
+    }
+}
+
+/// A top-level definition
+#[derive(Clone, Debug, PartialEq)]
+pub struct Symbol {
+    pub kind: SymbolKind,
+    /// Module path and name, `net::http::get`; the bare name in the entry file
+    pub qualified: String,
+    /// Name sema and lowering know it by: `http.get` for a module cell, the bare name otherwise
+    pub resolved: String,
+    /// Module path (`["net", "http"]`); empty for the entry file
+    pub module: Vec<String>,
+    /// Index of the defining file, in the order the caller numbers them
+    pub file: usize,
+    /// The defined name, in program-wide offsets
+    pub span: Span,
+    /// The whole definition, in program-wide offsets
+    pub def_span: Span,
+    /// The defined name, in offsets local to its file
+    pub local_span: Span,
+    pub is_pub: bool,
+    /// Injected from the stdlib rather than written by the user
+    pub from_std: bool,
+}
+
+/// Two definitions of one name
+#[derive(Clone, Debug, PartialEq)]
+pub struct DuplicateDefinition {
+    pub first: Symbol,
+    pub second: Symbol,
+    pub message: String,
+}
+
+/// Every top-level definition of a program, by fully-qualified and resolved name
+#[derive(Clone, Debug, Default)]
+pub struct SymbolTable {
+    symbols: Vec<Symbol>,
+    by_qualified: HashMap<String, usize>,
+    /// (namespace, resolved name) -> symbol
+    by_resolved: HashMap<(u8, String), usize>,
+}
+
+impl SymbolTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the definitions of a user file: `module` is its path (empty for the entry file)
+    /// and `base` the offset of its first byte in the program-wide span space.
+    pub fn add_file(
+        &mut self,
+        file: usize,
+        module: &[String],
+        base: usize,
+        stmts: &[Stmt],
+    ) -> Result<(), Box<DuplicateDefinition>> {
+        self.add(file, module, base, stmts, false)
+    }
+
+    /// Adds the definitions of an injected stdlib module (`["aura", "time"]`) living in `file`.
+    pub fn add_stdlib(
+        &mut self,
+        file: usize,
+        module: &[String],
+        base: usize,
+        stmts: &[Stmt],
+    ) -> Result<(), Box<DuplicateDefinition>> {
+        self.add(file, module, base, stmts, true)
+    }
+
+    fn add(
+        &mut self,
+        file: usize,
+        module: &[String],
+        base: usize,
+        stmts: &[Stmt],
+        from_std: bool,
+    ) -> Result<(), Box<DuplicateDefinition>> {
+        for stmt in stmts {
+            let (kind, name, def_span, is_pub) = match stmt {
+                Stmt::CellDef(c) => (SymbolKind::Cell, &c.name, c.span, c.is_pub),
+                Stmt::ExternCell(e) => (SymbolKind::ExternCell, &e.name, e.span, true),
+                Stmt::RecordDef(r) => (SymbolKind::Record, &r.name, r.span, true),
+                Stmt::EnumDef(e) => (SymbolKind::Enum, &e.name, e.span, true),
+                Stmt::TypeAlias(t) => (SymbolKind::TypeAlias, &t.name, t.span, true),
+                Stmt::TraitDef(t) => (SymbolKind::Trait, &t.name, t.span, true),
+                _ => continue,
+            };
+            // Stdlib cells are injected unqualified, like the entry file's.
+            let qualifies = kind == SymbolKind::Cell && !from_std && !module.is_empty();
+            let resolved = match module.last() {
+                Some(ns) if qualifies => format!("{ns}.{}", name.node),
+                _ => name.node.clone(),
+            };
+            let qualified = module
+                .iter()
+                .map(String::as_str)
+                .chain([name.node.as_str()])
+                .collect::<Vec<_>>()
+                .join("::");
+            let symbol = Symbol {
+                kind,
+                qualified,
+                resolved,
+                module: module.to_vec(),
+                file,
+                span: name.span,
+                def_span,
+                local_span: span(name.span.offset().saturating_sub(base), name.span.len()),
+                is_pub,
+                from_std,
+            };
+
+            let key = (kind.namespace(), symbol.resolved.clone());
+            if let Some(&i) = self.by_resolved.get(&key) {
+                let first = &self.symbols[i];
+                if first.from_std && from_std {
+                    continue;
+                }
+                let message = duplicate_message(first, &symbol);
+                return Err(Box::new(DuplicateDefinition {
+                    first: first.clone(),
+                    second: symbol,
+                    message,
+                }));
+            }
+            self.by_resolved.insert(key, self.symbols.len());
+            self.by_qualified.insert(symbol.qualified.clone(), self.symbols.len());
+            self.symbols.push(symbol);
+        }
+        Ok(())
+    }
+
+    /// The symbol with a fully-qualified name (`net::http::get`).
+    pub fn get(&self, qualified: &str) -> Option<&Symbol> {
+        self.by_qualified.get(qualified).map(|&i| &self.symbols[i])
+    }
+
+    /// The callable, type or trait sema knows as `resolved` (`http.get`), in that order.
+    pub fn resolve(&self, resolved: &str) -> Option<&Symbol> {
+        (0..3).find_map(|ns| self.by_resolved.get(&(ns, resolved.to_string())).map(|&i| &self.symbols[i]))
+    }
+
+    /// The definition containing a program-wide offset.
+    pub fn at(&self, offset: usize) -> Option<&Symbol> {
+        self.symbols
+            .iter()
+            .find(|s| s.def_span.offset() <= offset && offset < s.def_span.offset() + s.def_span.len())
+    }
+
+    /// Definitions of one module, in source order.
+    pub fn module<'a>(&'a self, module: &'a [String]) -> impl Iterator<Item = &'a Symbol> + 'a {
+        self.symbols.iter().filter(move |s| s.module == module)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Symbol> {
+        self.symbols.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+}
+
+fn duplicate_message(first: &Symbol, second: &Symbol) -> String {
+    let describe = |s: &Symbol| format!("{} '{}'", s.kind.display(), s.qualified);
+    if first.from_std || second.from_std {
+        let (user, std) = if first.from_std { (second, first) } else { (first, second) };
+        format!("{} redefines {} from the standard library", describe(user), describe(std))
+    } else if first.module != second.module {
+        format!(
+            "{} conflicts with {}; types, traits and extern cells share one namespace across modules",
+            describe(second),
+            describe(first)
+        )
+    } else if first.kind == second.kind {
+        format!("{} is defined twice", describe(second))
+    } else {
+        format!("{} is already defined as {}", describe(second), describe(first))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stmts(src: &str, base: usize) -> Vec<Stmt> {
+        aura_parse::parse_source_at(src, base, &aura_parse::ParseConfig::default())
+            .expect("parse")
+            .stmts
+    }
+
+    fn path(module: &str) -> Vec<String> {
+        module.split("::").map(str::to_string).collect()
+    }
+
+    #[test]
+    fn module_definitions_get_qualified_and_resolved_names() {
+        let http = "pub cell get(x: u32) -> u32:\n    return x\n\ntype Req = record { id: u32 }\n";
+        let mut table = SymbolTable::new();
+        table.add_file(0, &[], 0, &stmts("cell main():\n    val a = 1\n", 0)).unwrap();
+        table.add_file(1, &path("net::http"), 100, &stmts(http, 100)).unwrap();
+
+        let get = table.get("net::http::get").expect("get");
+        assert_eq!((get.kind, get.resolved.as_str(), get.file), (SymbolKind::Cell, "http.get", 1));
+        assert!(get.is_pub);
+        assert_eq!(&http[get.local_span.offset()..get.local_span.offset() + get.local_span.len()], "get");
+        assert_eq!(get.span.offset(), get.local_span.offset() + 100);
+        assert_eq!(table.resolve("http.get"), Some(get));
+
+        // Types keep their bare name; the entry file's definitions are unqualified.
+        assert_eq!(table.resolve("Req").map(|s| s.qualified.as_str()), Some("net::http::Req"));
+        assert_eq!(table.get("main").map(|s| s.resolved.as_str()), Some("main"));
+        let http_path = path("net::http");
+        let names: Vec<&str> = table.module(&http_path).map(|s| s.qualified.as_str()).collect();
+        assert_eq!(names, vec!["net::http::get", "net::http::Req"]);
+
+        assert_eq!(table.at(get.def_span.offset() + 4).map(|s| s.qualified.as_str()), Some("net::http::get"));
+        assert_eq!(table.at(99), None);
+    }
+
+    #[test]
+    fn duplicates_are_reported_per_namespace() {
+        let mut table = SymbolTable::new();
+        let dup = table
+            .add_file(0, &[], 0, &stmts("cell f():\n    val a = 1\n\ncell f():\n    val b = 2\n", 0))
+            .expect_err("f twice");
+        assert_eq!(dup.message, "cell 'f' is defined twice");
+        assert_eq!(dup.first.span.offset(), 5);
+
+        // Cells of two modules are qualified apart; types share one namespace.
+        let mut table = SymbolTable::new();
+        let a = "pub cell f():\n    val a = 1\n\ntype P = record { x: u32 }\n";
+        table.add_file(1, &path("a"), 0, &stmts(a, 0)).unwrap();
+        table.add_file(2, &path("b"), 100, &stmts("pub cell f():\n    val a = 1\n", 100)).unwrap();
+        let dup = table.add_file(3, &path("c"), 200, &stmts("type P = enum { Origin }\n", 200)).expect_err("two Ps");
+        assert_eq!(
+            dup.message,
+            "enum 'c::P' conflicts with record 'a::P'; types, traits and extern cells share one namespace across modules"
+        );
+
+        // A record and a cell of one name live in different namespaces.
+        let mut table = SymbolTable::new();
+        table.add_file(0, &[], 0, &stmts("type f = record { x: u32 }\n\ncell f():\n    val a = 1\n", 0)).unwrap();
+        assert_eq!(table.len(), 2);
+    }
+
+    #[test]
+    fn stdlib_modules_may_repeat_each_other_but_not_be_redefined() {
+        let log = "cell log(msg: String):\n    val m = msg\n";
+        let mut table = SymbolTable::new();
+        table.add_stdlib(0, &path("aura::one"), 0, &stmts(log, 0)).unwrap();
+        table.add_stdlib(0, &path("aura::two"), 100, &stmts(log, 100)).unwrap();
+        let first = table.resolve("log").expect("log");
+        assert_eq!((first.qualified.as_str(), first.from_std), ("aura::one::log", true));
+        assert_eq!(table.len(), 1);
+
+        let dup = table.add_file(0, &[], 200, &stmts(log, 200)).expect_err("user log");
+        assert_eq!(dup.message, "cell 'log' redefines cell 'aura::one::log' from the standard library");
+    }
+}
@@ -0,0 +1,288 @@
+//! Termination of recursive cells.
+//!
+//! Cells that call each other in a cycle must say why the recursion ends. Each cell in the
+//! cycle declares a `decreases` measure, which the verifier proves non-negative and smaller at
+//! every call back into the cycle. Without declarations, a measure is inferred when one
+//! unsigned parameter position never grows around the cycle and shrinks by a literal
+//! (`n - 1`) at least once on every loop through it; otherwise the cycle is an error naming
+//! its path.
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use aura_ast::{BinOp, CallArg, CellDef, Expr, ExprKind, Program, Stmt};
+
+use crate::capability_validator::visit_block;
+use crate::effects::resolve_cell_call;
+use crate::sema::expr_to_callee_name;
+use crate::types::Type;
+use crate::SemanticError;
+
+/// Why a recursive cell terminates
+#[derive(Clone, Debug, PartialEq)]
+pub struct Measure {
+    /// The declared `decreases` expression, or the inferred parameter
+    pub expr: Expr,
+    /// Inferred from a parameter that shrinks around the cycle, so nothing is left to prove
+    pub inferred: bool,
+    /// Cells with the same group call each other in a cycle
+    pub group: usize,
+}
+
+/// A call from one top-level cell to another
+struct CallEdge<'a> {
+    callee: usize,
+    args: &'a [CallArg],
+}
+
+/// Measures of every cell that takes part in a call cycle, keyed by its (qualified) name.
+/// Fails on the first cycle with neither declared nor inferable measures.
+pub fn check_termination(program: &Program) -> Result<BTreeMap<String, Measure>, SemanticError> {
+    let cells: Vec<&CellDef> = program
+        .stmts
+        .iter()
+        .filter_map(|s| match s {
+            Stmt::CellDef(c) => Some(c),
+            _ => None,
+        })
+        .collect();
+    let index: HashMap<&str, usize> = cells.iter().enumerate().map(|(i, c)| (c.name.node.as_str(), i)).collect();
+    let names: HashSet<&str> = index.keys().copied().collect();
+
+    let mut edges: Vec<Vec<CallEdge>> = Vec::with_capacity(cells.len());
+    for cell in &cells {
+        let mut out = Vec::new();
+        visit_block(&cell.body, &mut |e| {
+            let ExprKind::Call { callee, args, .. } = &e.kind else {
+                return;
+            };
+            let name = expr_to_callee_name(callee);
+            if let Some(callee) = resolve_cell_call(&cell.name.node, &name, &names) {
+                out.push(CallEdge {
+                    callee: index[callee.as_str()],
+                    args,
+                });
+            }
+        });
+        edges.push(out);
+    }
+
+    let mut measures = BTreeMap::new();
+    for (group, members) in cycles(&edges).into_iter().enumerate() {
+        let in_group: HashSet<usize> = members.iter().copied().collect();
+        let undeclared: Vec<usize> = members.iter().copied().filter(|&i| cells[i].decreases.is_none()).collect();
+
+        if undeclared.is_empty() {
+            for &i in &members {
+                let expr = cells[i].decreases.clone().expect("declared");
+                measures.insert(cells[i].name.node.clone(), Measure { expr, inferred: false, group });
+            }
+            continue;
+        }
+
+        let first = undeclared[0];
+        let path = cycle_path(first, &edges, &in_group)
+            .into_iter()
+            .map(|i| cells[i].name.node.as_str())
+            .collect::<Vec<_>>()
+            .join(" -> ");
+        let name = &cells[first].name.node;
+        if let Some(declared) = members.iter().find(|&&i| cells[i].decreases.is_some()) {
+            return Err(SemanticError {
+                message: format!(
+                    "cell '{name}' is recursive ({path}) but declares no `decreases` measure while '{}' does; \
+                     every cell in the cycle needs one",
+                    cells[*declared].name.node
+                ),
+                span: cells[first].name.span,
+            });
+        }
+
+        let Some(position) = shrinking_param(&cells, &members, &edges, &in_group) else {
+            return Err(SemanticError {
+                message: format!(
+                    "cell '{name}' is recursive ({path}) but no parameter shrinks around the cycle; \
+                     add `decreases <expr>` to each cell in the cycle"
+                ),
+                span: cells[first].name.span,
+            });
+        };
+        for &i in &members {
+            let param = &cells[i].params[position].name;
+            let expr = Expr {
+                span: param.span,
+                kind: ExprKind::Ident(param.clone()),
+            };
+            measures.insert(cells[i].name.node.clone(), Measure { expr, inferred: true, group });
+        }
+    }
+    Ok(measures)
+}
+
+/// Strongly connected components that contain a cycle, each in source order (Tarjan).
+fn cycles(edges: &[Vec<CallEdge>]) -> Vec<Vec<usize>> {
+    struct Tarjan<'e, 'a> {
+        edges: &'e [Vec<CallEdge<'a>>],
+        next: usize,
+        order: Vec<Option<usize>>,
+        low: Vec<usize>,
+        stack: Vec<usize>,
+        on_stack: Vec<bool>,
+        out: Vec<Vec<usize>>,
+    }
+
+    impl Tarjan<'_, '_> {
+        fn visit(&mut self, v: usize) {
+            self.order[v] = Some(self.next);
+            self.low[v] = self.next;
+            self.next += 1;
+            self.stack.push(v);
+            self.on_stack[v] = true;
+
+            let edges = self.edges;
+            for e in &edges[v] {
+                let w = e.callee;
+                match self.order[w] {
+                    None => {
+                        self.visit(w);
+                        self.low[v] = self.low[v].min(self.low[w]);
+                    }
+                    Some(o) if self.on_stack[w] => self.low[v] = self.low[v].min(o),
+                    Some(_) => {}
+                }
+            }
+
+            if Some(self.low[v]) == self.order[v] {
+                let mut scc = Vec::new();
+                while let Some(w) = self.stack.pop() {
+                    self.on_stack[w] = false;
+                    scc.push(w);
+                    if w == v {
+                        break;
+                    }
+                }
+                let recursive = scc.len() > 1 || self.edges[v].iter().any(|e| e.callee == v);
+                if recursive {
+                    scc.sort_unstable();
+                    self.out.push(scc);
+                }
+            }
+        }
+    }
+
+    let n = edges.len();
+    let mut t = Tarjan {
+        edges,
+        next: 0,
+        order: vec![None; n],
+        low: vec![0; n],
+        stack: Vec::new(),
+        on_stack: vec![false; n],
+        out: Vec::new(),
+    };
+    for v in 0..n {
+        if t.order[v].is_none() {
+            t.visit(v);
+        }
+    }
+    t.out.sort_unstable();
+    t.out
+}
+
+/// The shortest cycle from `start` back to itself, both ends included.
+fn cycle_path(start: usize, edges: &[Vec<CallEdge>], in_group: &HashSet<usize>) -> Vec<usize> {
+    let mut prev: HashMap<usize, usize> = HashMap::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(v) = queue.pop_front() {
+        for e in &edges[v] {
+            let w = e.callee;
+            if !in_group.contains(&w) || prev.contains_key(&w) {
+                continue;
+            }
+            prev.insert(w, v);
+            if w == start {
+                let mut path = vec![start];
+                let mut at = v;
+                while at != start {
+                    path.push(at);
+                    at = prev[&at];
+                }
+                path.push(start);
+                path.reverse();
+                return path;
+            }
+            queue.push_back(w);
+        }
+    }
+    vec![start, start]
+}
+
+/// A parameter position every cell of the cycle has as an unsigned integer, which no call
+/// around the cycle grows (it passes the caller's own parameter, or that minus a positive
+/// literal) and which shrinks at least once on every cycle.
+fn shrinking_param(
+    cells: &[&CellDef],
+    members: &[usize],
+    edges: &[Vec<CallEdge>],
+    in_group: &HashSet<usize>,
+) -> Option<usize> {
+    let arity = members.iter().map(|&i| cells[i].params.len()).min()?;
+    (0..arity).find(|&position| {
+        let unsigned = members.iter().all(|&i| {
+            Type::int_from_name(&cells[i].params[position].ty.name.node)
+                .and_then(|t| t.int_bounds())
+                .is_some_and(|(lo, _)| lo == 0)
+        });
+        if !unsigned {
+            return false;
+        }
+        // Calls passing the parameter on unchanged; these alone must not form a cycle.
+        let mut unchanged: Vec<Vec<CallEdge>> = edges.iter().map(|_| Vec::new()).collect();
+        for &caller in members {
+            let own = &cells[caller].params[position].name.node;
+            for e in edges[caller].iter().filter(|e| in_group.contains(&e.callee)) {
+                match arg_for(e.args, cells[e.callee], position).map(|arg| change(arg, own)) {
+                    Some(Change::Shrinks) => {}
+                    Some(Change::Same) => unchanged[caller].push(CallEdge { callee: e.callee, args: e.args }),
+                    Some(Change::Other) | None => return false,
+                }
+            }
+        }
+        cycles(&unchanged).is_empty()
+    })
+}
+
+/// The argument a call passes for the callee's parameter at `position`.
+fn arg_for<'a>(args: &'a [CallArg], callee: &CellDef, position: usize) -> Option<&'a Expr> {
+    let param = &callee.params[position].name.node;
+    args.iter()
+        .find_map(|a| match a {
+            CallArg::Named { name, value } if name.node == *param => Some(value),
+            _ => None,
+        })
+        .or_else(|| match args.get(position) {
+            Some(CallArg::Positional(v)) => Some(v),
+            _ => None,
+        })
+}
+
+enum Change {
+    /// `param - k` with a literal `k > 0`
+    Shrinks,
+    /// `param` itself
+    Same,
+    Other,
+}
+
+/// How an argument relates to the caller's parameter `param`.
+fn change(arg: &Expr, param: &str) -> Change {
+    let is_param = |e: &Expr| matches!(&e.kind, ExprKind::Ident(id) if id.node == param);
+    match &arg.kind {
+        _ if is_param(arg) => Change::Same,
+        ExprKind::Binary { left, op: BinOp::Sub, right }
+            if is_param(left) && matches!(right.kind, ExprKind::IntLit(k) if k > 0) =>
+        {
+            Change::Shrinks
+        }
+        _ => Change::Other,
+    }
+}
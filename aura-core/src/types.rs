@@ -5,7 +5,13 @@ pub enum Type {
     Unknown,
     Unit,
     Bool,
+    U8,
+    U16,
     U32,
+    U64,
+    I32,
+    I64,
+    F64,
     String,
     Style,
     Model,
@@ -28,6 +34,18 @@ pub enum Type {
         lo: u64,
         hi: u64,
     },
+
+    // Structural product type `(A, B, ...)`; always two or more elements.
+    Tuple(Vec<Type>),
+
+    // Growable indexed collection `List<T>`; `[]` starts out as `List<Unknown>`.
+    List(Box<Type>),
+
+    // Key/value collection `Map<K, V>`; `{}` starts out as `Map<Unknown, Unknown>`.
+    Map(Box<Type>, Box<Type>),
+
+    // Lambda value (`-> { ... }`); the payload is the type its body yields.
+    Closure(Box<Type>),
 }
 
 impl Type {
@@ -36,7 +54,13 @@ impl Type {
             Type::Unknown => "<unknown>".to_string(),
             Type::Unit => "Unit".to_string(),
             Type::Bool => "bool".to_string(),
+            Type::U8 => "u8".to_string(),
+            Type::U16 => "u16".to_string(),
             Type::U32 => "u32".to_string(),
+            Type::U64 => "u64".to_string(),
+            Type::I32 => "i32".to_string(),
+            Type::I64 => "i64".to_string(),
+            Type::F64 => "f64".to_string(),
             Type::String => "String".to_string(),
             Type::Style => "Style".to_string(),
             Type::Model => "Model".to_string(),
@@ -69,6 +93,17 @@ impl Type {
             Type::ConstrainedRange { base, lo, hi } => {
                 format!("{}[{}..{}]", base.display(), lo, hi)
             }
+            Type::Tuple(elems) => {
+                let elems_s = elems
+                    .iter()
+                    .map(|t| t.display())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("({elems_s})")
+            }
+            Type::List(elem) => format!("List<{}>", elem.display()),
+            Type::Map(key, value) => format!("Map<{}, {}>", key.display(), value.display()),
+            Type::Closure(ret) => format!("Closure<{}>", ret.display()),
         }
     }
 }
@@ -80,6 +115,63 @@ impl Type {
             shape: None,
         }
     }
+
+    /// `Channel<elem>`: a handle to a channel between flows carrying `elem` values.
+    pub fn channel(elem: Type) -> Self {
+        Type::Applied {
+            name: "Channel".to_string(),
+            args: vec![elem],
+        }
+    }
+
+    /// `CancelToken`: the handle a `scope` block's flows poll for cancellation.
+    pub fn cancel_token() -> Self {
+        Type::Named("CancelToken".to_string())
+    }
+
+    /// `File`: a linear handle to a file opened by `fs.open`, `fs.create` or `fs.append`.
+    pub fn file() -> Self {
+        Type::Named("File".to_string())
+    }
+
+    /// `Socket`: a linear TCP connection made by `net.connect`.
+    pub fn socket() -> Self {
+        Type::Named("Socket".to_string())
+    }
+
+    /// The sized integer type spelled `name` (`u8`, `i64`, ...); `Int` is `u32`.
+    pub fn int_from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "u8" => Type::U8,
+            "u16" => Type::U16,
+            "u32" | "Int" => Type::U32,
+            "u64" => Type::U64,
+            "i32" => Type::I32,
+            "i64" => Type::I64,
+            _ => return None,
+        })
+    }
+
+    /// The values an integer type holds, `u8` holding `0..=255`; `None` for other types.
+    pub fn int_bounds(&self) -> Option<(i128, i128)> {
+        Some(match self {
+            Type::U8 => (0, u8::MAX.into()),
+            Type::U16 => (0, u16::MAX.into()),
+            Type::U32 => (0, u32::MAX.into()),
+            Type::U64 => (0, u64::MAX.into()),
+            Type::I32 => (i32::MIN.into(), i32::MAX.into()),
+            Type::I64 => (i64::MIN.into(), i64::MAX.into()),
+            _ => return None,
+        })
+    }
+
+    /// The element type of a `Channel<T>`.
+    pub fn channel_elem(&self) -> Option<&Type> {
+        match self {
+            Type::Applied { name, args } if name == "Channel" => args.first(),
+            _ => None,
+        }
+    }
 }
 
 pub fn is_subset_range(a_lo: u64, a_hi: u64, b_lo: u64, b_hi: u64) -> bool {
@@ -0,0 +1,39 @@
+use aura_core::Checker;
+
+mod common;
+use common::check_err;
+
+#[test]
+fn bitwise_operators_require_integer_operands() {
+    let msg = check_err("cell f(b: bool) -> u32:\n    yield b & 1\n");
+    assert!(msg.contains("bitwise op expects u32,u32"), "{msg}");
+}
+
+#[test]
+fn masks_and_shifts_narrow_the_result_range() {
+    let program = aura_parse::parse_source(
+        "cell low(x: u32) -> u32[0..255]:\n    yield x & 0xFF\n\ncell nibble(x: u32[0..255]) -> u32[0..15]:\n    yield x >> 4\n",
+    )
+    .expect("parse");
+    Checker::new().check_program(&program).expect("masked values fit the declared ranges");
+}
+
+#[test]
+fn bitwise_expressions_lower_to_ir_binops() {
+    let src = "cell f(x: u32) -> u32:\n    yield (x << 2) | (x ^ 1)\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+
+    let module = aura_core::lower_program(&program).expect("lower");
+    let f = module.functions.get("f").expect("f");
+    let ops: Vec<aura_ir::BinOp> = f
+        .blocks
+        .iter()
+        .flat_map(|b| b.insts.iter())
+        .filter_map(|i| match &i.kind {
+            aura_ir::InstKind::Binary { op, .. } => Some(*op),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(ops, vec![aura_ir::BinOp::Shl, aura_ir::BinOp::BitXor, aura_ir::BinOp::BitOr]);
+}
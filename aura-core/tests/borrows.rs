@@ -0,0 +1,120 @@
+mod common;
+use common::check;
+
+const HELPERS: &str = r#"
+import aura::tensor
+
+cell total(xs: &List<u32>) -> u32:
+    return xs.len()
+
+cell fill(t: &mut Tensor, v: u32):
+    tensor::set(t, 0, v)
+
+cell consume(t: Tensor):
+    val z: u32 = 0
+"#;
+
+fn with_helpers(main: &str) -> String {
+    format!("{HELPERS}\ncell main(c: bool):\n{main}")
+}
+
+#[test]
+fn shared_borrows_leave_the_value_with_its_owner() {
+    let src = with_helpers(
+        "    val xs = [1, 2, 3]\n    val a = total(&xs)\n    val b = total(&xs)\n    val r = &xs\n    val n = total(r) + xs.len()\n",
+    );
+    check(&src).expect("sema");
+}
+
+#[test]
+fn mutable_borrows_update_in_place_one_call_at_a_time() {
+    let src = with_helpers(
+        "    val mut t: Tensor = tensor::new(2)\n    fill(&mut t, 1)\n    fill(&mut t, 2)\n    val n: u32 = tensor::len(t)\n    consume(t)\n",
+    );
+    check(&src).expect("sema");
+}
+
+#[test]
+fn borrowed_parameters_can_be_lent_on() {
+    let src = format!(
+        "{HELPERS}\ncell refill(t: &mut Tensor):\n    fill(t, 1)\n    fill(&mut t, 2)\n    val n: u32 = tensor::len(t)\n"
+    );
+    check(&src).expect("sema");
+}
+
+#[test]
+fn a_loan_ends_with_the_scope_of_its_binding() {
+    let src = with_helpers("    val mut x: u32 = 1\n    if c:\n        val r = &x\n        val y = r + 1\n    x = 2\n");
+    check(&src).expect("sema");
+}
+
+#[test]
+fn conflicting_borrows_are_rejected() {
+    let cases = [
+        ("    val xs = [1]\n    val n = total(xs)\n", "'total' borrows 'xs'; pass `&xs`"),
+        ("    val mut t: Tensor = tensor::new(1)\n    consume(&t)\n", "'consume' takes 't' by value"),
+        ("    val mut t: Tensor = tensor::new(1)\n    fill(&t, 1)\n", "'fill' borrows 't' as `&mut`"),
+        ("    val t: Tensor = tensor::new(1)\n    fill(&mut t, 1)\n", "cannot borrow immutable val 't' as mutable"),
+        (
+            "    val mut t: Tensor = tensor::new(1)\n    fill(&mut t, tensor::len(t))\n",
+            "cannot use 't' while it is mutably borrowed",
+        ),
+        (
+            "    val mut t: Tensor = tensor::new(1)\n    val r = &t\n    fill(&mut t, 1)\n",
+            "cannot borrow 't' as mutable because it is also borrowed as immutable",
+        ),
+        (
+            "    val mut t: Tensor = tensor::new(1)\n    val r = &t\n    consume(t)\n",
+            "cannot move 't' while it is borrowed",
+        ),
+        (
+            "    val mut x: u32 = 1\n    val r = &x\n    x = 2\n",
+            "cannot assign to 'x' while it is borrowed",
+        ),
+        (
+            "    val mut t: Tensor = tensor::new(1)\n    val r = &t\n    tensor::set(t, 0, 1)\n",
+            "cannot mutate 't' while it is borrowed",
+        ),
+    ];
+    for (main, expected) in cases {
+        let err = check(&with_helpers(main)).expect_err(main);
+        assert!(err.contains(expected), "{main}: expected '{expected}', got: {err}");
+    }
+}
+
+#[test]
+fn two_mutable_borrows_in_one_call_are_rejected() {
+    let src = format!(
+        "{HELPERS}\ncell swap(a: &mut Tensor, b: &mut Tensor):\n    val z: u32 = 0\n\ncell main():\n    val mut t: Tensor = tensor::new(1)\n    swap(&mut t, &mut t)\n"
+    );
+    let err = check(&src).expect_err("sema");
+    assert!(err.contains("cannot borrow 't' as mutable more than once at a time"), "{err}");
+}
+
+#[test]
+fn a_borrow_cannot_be_moved_out_of_or_replaced() {
+    let cases = [
+        ("cell f(t: &Tensor) -> Tensor:\n    return t\n", "cannot return borrowed 't'"),
+        ("cell f(t: &Tensor):\n    consume(t)\n", "cannot move out of borrowed 't'"),
+        ("cell f(t: &Tensor):\n    tensor::set(t, 0, 1)\n", "cannot mutate 't' through a shared borrow"),
+        ("cell f(t: &Tensor):\n    fill(t, 1)\n", "cannot borrow 't' as mutable: it is itself a shared borrow"),
+        ("cell f(n: &mut u32):\n    n = 1\n", "cannot assign to 'n': it holds a borrow"),
+    ];
+    for (cell, expected) in cases {
+        let err = check(&format!("{HELPERS}\n{cell}")).expect_err(cell);
+        assert!(err.contains(expected), "{cell}: expected '{expected}', got: {err}");
+    }
+}
+
+#[test]
+fn borrows_only_appear_where_they_can_be_held() {
+    let cases = [
+        ("    val x: u32 = 1\n    val n = &x + 1\n", "a borrow can only be passed to a cell"),
+        ("    val mut x: u32 = 1\n    val r = &mut x\n", "a `&mut` borrow can only be passed to a cell"),
+        ("    val xs = [1]\n    val n = total(&xs[0])\n", "only a named value can be borrowed"),
+    ];
+    for (main, expected) in cases {
+        let err = check(&with_helpers(main)).expect_err(main);
+        assert!(err.contains(expected), "{main}: expected '{expected}', got: {err}");
+    }
+}
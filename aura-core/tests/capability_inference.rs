@@ -0,0 +1,57 @@
+use aura_core::{CapabilityValidator, Checker, ParamAccess};
+
+fn infer(src: &str) -> Vec<aura_core::CapabilitySuggestion> {
+    let program = aura_parse::parse_source(src).expect("parse");
+    CapabilityValidator::infer_capabilities(&program)
+}
+
+#[test]
+fn read_only_tensor_taken_by_value_is_narrowed_to_a_shared_borrow() {
+    let src = "import aura::tensor\n\ncell total(t: Tensor) -> u32:\n    return tensor.get(t, 0) + t.len()\n\ncell main():\n    val t: Tensor = tensor.new(2)\n    val n = total(t)\n";
+    let found = infer(src);
+    assert_eq!(found.len(), 1, "{found:?}");
+    let s = &found[0];
+    assert_eq!((s.declared, s.needed), (ParamAccess::Consume, ParamAccess::Read));
+    assert_eq!(s.signature, "cell total(t: &Tensor) -> u32");
+    assert!(s.message.contains("declare it `t: &Tensor`"), "{}", s.message);
+
+    // The edits rewrite the parameter and the call, and the result still checks.
+    let mut fixed = src.to_string();
+    let mut edits = s.edits.clone();
+    edits.sort_by_key(|e| std::cmp::Reverse(e.span.offset()));
+    for e in &edits {
+        fixed.replace_range(e.span.offset()..e.span.offset() + e.span.len(), &e.replacement);
+    }
+    assert!(fixed.contains("cell total(t: &Tensor)") && fixed.contains("total(&t)"), "{fixed}");
+    let program = aura_parse::parse_source(&fixed).expect("parse");
+    Checker::new().check_program(&program).expect("narrowed program checks");
+}
+
+#[test]
+fn in_place_updates_need_a_mutable_borrow_and_closing_needs_the_value() {
+    let src = "cell log(f: mut File, line: String):\n    fs.write(f, line)\n\ncell finish(f: File):\n    fs.close(f)\n";
+    let found = infer(src);
+    assert_eq!(found.len(), 1, "{found:?}");
+    assert_eq!((found[0].param.as_str(), found[0].needed), ("f", ParamAccess::Write));
+    assert_eq!(found[0].signature, "cell log(f: &mut File, line: String)");
+}
+
+#[test]
+fn unused_capabilities_are_reported() {
+    let src = "cell ping(s: &Socket, t: &mut Tensor) -> u32:\n    return 1\n";
+    let found = infer(src);
+    assert_eq!(found.len(), 2, "{found:?}");
+    assert!(found[0].message.contains("never uses socket capability 's'"), "{}", found[0].message);
+    // `&T` is already the least a parameter can declare.
+    assert!(found[0].edits.is_empty());
+    assert_eq!(found[1].signature, "cell ping(s: &Socket, t: &Tensor) -> u32");
+}
+
+#[test]
+fn callers_passing_temporaries_get_no_edits() {
+    let src = "cell total(t: Tensor) -> u32:\n    return t.len()\n\ncell main():\n    val n = total(tensor.new(2))\n";
+    let found = infer(src);
+    assert_eq!(found.len(), 1, "{found:?}");
+    assert_eq!(found[0].needed, ParamAccess::Read);
+    assert!(found[0].edits.is_empty());
+}
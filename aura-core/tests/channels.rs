@@ -0,0 +1,38 @@
+use aura_core::Checker;
+use aura_ir::InstKind;
+
+#[test]
+fn channel_values_take_the_element_type_and_lower_to_chan_calls() {
+    let src = "cell produce(ch: Channel<u32>, n: u32) -> u32:\n    chan.send(ch, n)\n    return 0\n\ncell main():\n    val ch: Channel<u32> = chan.bounded(4)\n    ch ~> produce(7)\n    val x: u32 = chan.recv(ch) + 1\n    chan.close(ch)\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+
+    let module = aura_core::lower_program(&program).expect("lower");
+    let main = module.functions.get("main").expect("main");
+    let callees: Vec<&str> = main
+        .blocks
+        .iter()
+        .flat_map(|b| b.insts.iter())
+        .filter_map(|i| match &i.kind {
+            InstKind::Call { callee, .. } if callee.starts_with("chan.") => Some(callee.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(callees, vec!["chan.bounded", "chan.recv", "chan.close"]);
+}
+
+#[test]
+fn channel_element_and_capacity_errors_are_reported() {
+    let cases = [
+        ("val ch: Channel<u32> = chan.unbounded()\n    chan.send(ch, \"hi\")", "expected u32, got String"),
+        ("val ch: Channel<String> = chan.unbounded()\n    val n: u32 = chan.recv(ch)", "expected u32, got String"),
+        ("val ch = chan.bounded(0)", "channel capacity must be at least 1"),
+        ("val ch: Channel<u32, bool> = chan.unbounded()", "Channel expects exactly one element type"),
+    ];
+    for (body, expected) in cases {
+        let src = format!("cell main():\n    {body}\n");
+        let program = aura_parse::parse_source(&src).expect("parse");
+        let err = Checker::new().check_program(&program).expect_err(body);
+        assert!(err.message.contains(expected), "{body}: {}", err.message);
+    }
+}
@@ -0,0 +1,53 @@
+use aura_core::Checker;
+use aura_ir::{run_oracle_entry, InstKind, OracleConfig, OracleValue, Type};
+
+fn run_main(src: &str) -> OracleValue {
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+    let module = aura_core::lower_program(&program).expect("lower");
+    let out = run_oracle_entry(&module, "main", &[], OracleConfig::default()).expect("oracle");
+    assert!(out.ok, "{out:?}");
+    out.return_value.expect("main yields a value")
+}
+
+#[test]
+fn lambdas_lower_to_closure_bodies_over_their_captures() {
+    let src = "cell main() -> u32:\n    val n: u32 = 3\n    val base: u32 = 10\n    val add = -> { yield n + base }\n    yield add()\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+    let module = aura_core::lower_program(&program).expect("lower");
+
+    let body = module.functions.get("main$lambda0").expect("closure body");
+    assert!(body.is_closure);
+    assert_eq!(body.ret, Type::U32);
+    let params: Vec<&str> = body.params.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(params, vec!["n", "base"]);
+
+    let main = module.functions.get("main").expect("main");
+    let insts: Vec<&InstKind> = main.blocks.iter().flat_map(|b| b.insts.iter()).map(|i| &i.kind).collect();
+    assert!(insts
+        .iter()
+        .any(|k| matches!(k, InstKind::MakeClosure { func, captures } if func == "main$lambda0" && captures.len() == 2)));
+    assert!(insts.iter().any(|k| matches!(k, InstKind::CallClosure { ret: Type::U32, .. })));
+    assert_eq!(run_main(src), OracleValue::U32(13));
+}
+
+#[test]
+fn closures_capture_by_value_and_nest() {
+    let src = "cell main() -> u32:\n    val mut x: u32 = 1\n    val f = -> { yield x }\n    x = 5\n    val k: u32 = 7\n    val g = -> { yield f() * 2 + k }\n    yield g() + x\n";
+    assert_eq!(run_main(src), OracleValue::U32(14));
+}
+
+#[test]
+fn closures_are_called_without_arguments_and_cannot_assign_captures() {
+    let src = "cell main():\n    val f = -> { yield 1 }\n    val y = f(2)\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    let err = Checker::new().check_program(&program).expect_err("closure call with arguments");
+    assert!(err.message.contains("closure 'f' takes no arguments"), "{}", err.message);
+
+    let src = "cell main():\n    val mut x: u32 = 1\n    val f = -> { x = 2 }\n    f()\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+    let err = aura_core::lower_program(&program).expect_err("assigning a capture");
+    assert!(err.message.contains("cannot assign captured variable 'x'"), "{}", err.message);
+}
@@ -0,0 +1,14 @@
+#![allow(dead_code)]
+
+use aura_core::Checker;
+
+/// Parses and checks `src`, returning the checker's error message if there is one.
+pub fn check(src: &str) -> Result<(), String> {
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).map(|_| ()).map_err(|e| e.message)
+}
+
+/// Parses and checks `src`, which must fail, returning the error message.
+pub fn check_err(src: &str) -> String {
+    check(src).expect_err("expected a semantic error")
+}
@@ -0,0 +1,87 @@
+mod common;
+use common::check;
+
+#[test]
+fn consts_fold_into_range_bounds() {
+    let src = "const MAX: u32 = 4096\nconst LAST: u32 = MAX - 1\n\ncell clamp(x: u32[0..LAST]) -> u32[0..MAX - 1]:\n    yield x\n";
+    check(src).expect("folded bounds match");
+
+    let program = aura_parse::parse_source(src).expect("parse");
+    let folded = aura_core::fold_consts(&program).expect("fold");
+    let aura_ast::Stmt::CellDef(cell) = &folded.stmts[2] else { panic!("expected cell") };
+    let range = cell.ret.as_ref().and_then(|t| t.range.as_ref()).expect("range");
+    assert_eq!(range.hi.kind, aura_ast::ExprKind::IntLit(4095));
+}
+
+#[test]
+fn range_bounds_are_evaluated_without_consts() {
+    check("cell f(x: u32[0..64 * 2]) -> u32[0..(1 << 8) - 1]:\n    yield x\n").expect("literal arithmetic");
+
+    let err = check("cell f(n: u32, x: u32[0..n]) -> u32:\n    yield x\n").expect_err("param bound");
+    assert!(err.contains("range upper-bound must be a constant u32 expression; 'n' is not a constant"), "{err}");
+
+    let err = check("const MAX: u32 = 8\n\ncell f(x: u32[len(MAX)..MAX]) -> u32:\n    yield x\n")
+        .expect_err("call bound");
+    assert!(err.contains("range lower-bound must be a constant u32 expression"), "{err}");
+
+    let err = check("val x: u32[0..65536 * 65536] = 1\n").expect_err("overflow");
+    assert!(err.contains("overflows u32"), "{err}");
+}
+
+#[test]
+fn programs_with_nothing_to_fold_are_not_copied() {
+    let program = aura_parse::parse_source("cell f(x: u32[0..63]) -> u32:\n    yield x\n").expect("parse");
+    let folded = aura_core::fold_consts(&program).expect("fold");
+    assert!(matches!(folded, std::borrow::Cow::Borrowed(_)));
+
+    // A bound inside a nested block still has to be folded.
+    let program =
+        aura_parse::parse_source("cell f() -> u32:\n    val y: u32[0..8 * 8] = 1\n    yield y\n").expect("parse");
+    let folded = aura_core::fold_consts(&program).expect("fold");
+    assert!(matches!(folded, std::borrow::Cow::Owned(_)));
+}
+
+#[test]
+fn folded_consts_reach_lowering_as_literals() {
+    let src = "const SIZE: u32 = 1 << 10\n\ncell f() -> u32:\n    yield SIZE * 2 + 1\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    let folded = aura_core::fold_consts(&program).expect("fold");
+    let aura_ast::Stmt::CellDef(cell) = &folded.stmts[1] else { panic!("expected cell") };
+    let yielded = cell.body.yield_expr.as_ref().expect("yield");
+    assert_eq!(yielded.kind, aura_ast::ExprKind::IntLit(2049));
+
+    aura_core::lower_program(&program).expect("lower");
+}
+
+#[test]
+fn const_initializers_must_be_constant_u32_expressions() {
+    let err = check("const N: u32 = 1 << 40\n").expect_err("shift too wide");
+    assert!(err.contains("shift amount"), "{err}");
+
+    let err = check("const N: u32 = 65536 * 65536\n").expect_err("overflow");
+    assert!(err.contains("overflows u32"), "{err}");
+
+    let err = check("const N: u32 = f()\n\ncell f() -> u32:\n    yield 1\n").expect_err("call");
+    assert!(err.contains("constant u32 expression"), "{err}");
+
+    let err = check("const A: u32 = B\nconst B: u32 = 1\n").expect_err("used before declaration");
+    assert!(err.contains("'B' is not a constant"), "{err}");
+
+    let err = check("const F: f64 = 1\n").expect_err("not u32");
+    assert!(err.contains("must have type u32"), "{err}");
+}
+
+#[test]
+fn consts_cannot_be_nested_rebound_or_assigned() {
+    let err = check("cell f() -> u32:\n    const N: u32 = 1\n    yield N\n").expect_err("nested");
+    assert!(err.contains("only allowed at the top level"), "{err}");
+
+    let err = check("const N: u32 = 1\nconst N: u32 = 2\n").expect_err("duplicate");
+    assert!(err.contains("duplicate const 'N'"), "{err}");
+
+    let err = check("const N: u32 = 1\n\ncell f(N: u32) -> u32:\n    yield N\n").expect_err("param");
+    assert!(err.contains("cannot be rebound"), "{err}");
+
+    let err = check("const N: u32 = 1\n\ncell f() -> u32:\n    N = 2\n    yield 0\n").expect_err("assign");
+    assert!(err.contains("cannot assign to constant 'N'"), "{err}");
+}
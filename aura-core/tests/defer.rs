@@ -0,0 +1,73 @@
+use aura_core::Checker;
+use aura_ir::{run_oracle_entry, InstKind, OracleConfig, OracleValue};
+
+mod common;
+use common::check_err;
+
+fn run_main(src: &str) -> OracleValue {
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+    let module = aura_core::lower_program(&program).expect("lower");
+    let out = run_oracle_entry(&module, "main", &[], OracleConfig::default()).expect("oracle");
+    assert!(out.ok, "{out:?}");
+    out.return_value.expect("main yields a value")
+}
+
+#[test]
+fn jumps_cannot_leave_a_defer_block() {
+    let msg = check_err("cell f() -> u32:\n    defer {\n        return 1\n    }\n    yield 0\n");
+    assert!(msg.contains("'return' cannot leave a defer block"), "{msg}");
+
+    let msg = check_err(
+        "cell f():\n    val mut i: u32 = 0\n    while i < 3:\n        defer {\n            break\n        }\n        i = i + 1\n",
+    );
+    assert!(msg.contains("'break' cannot leave a defer block"), "{msg}");
+
+    let msg = check_err("defer {\n    val x: u32 = 1\n}\n");
+    assert!(msg.contains("'defer' outside of a cell"), "{msg}");
+}
+
+#[test]
+fn values_consumed_by_a_defer_stay_usable_until_scope_exit() {
+    let src = "cell main():\n    val t: Tensor = tensor::new(4)\n    defer {\n        val gone: Tensor = t\n    }\n    val n: u32 = tensor::len(t)\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("reads before the deferred move are fine");
+
+    let msg = check_err(
+        "cell main():\n    val t: Tensor = tensor::new(4)\n    defer {\n        val gone: Tensor = t\n    }\n    val u: Tensor = t\n",
+    );
+    assert!(msg.contains("consumed by a pending defer"), "{msg}");
+
+    let msg = check_err(
+        "cell main(c: bool):\n    val t: Tensor = tensor::new(4)\n    if c:\n        defer {\n            val gone: Tensor = t\n        }\n    val n: u32 = tensor::len(t)\n",
+    );
+    assert!(msg.contains("used after move"), "{msg}");
+}
+
+#[test]
+fn deferred_bodies_run_in_reverse_at_the_end_of_their_block() {
+    let src = "cell main() -> u32:\n    val mut log: u32 = 0\n    if log == 0:\n        defer {\n            log = log * 10 + 1\n        }\n        defer {\n            log = log * 10 + 2\n        }\n        log = log * 10 + 3\n    yield log\n";
+    assert_eq!(run_main(src), OracleValue::U32(321));
+}
+
+#[test]
+fn early_return_runs_the_pending_defers() {
+    let src = "cell note(v: u32) -> u32:\n    yield v\n\ncell f(x: u32) -> u32:\n    defer {\n        val n: u32 = note(x)\n    }\n    if x > 1:\n        return x\n    yield 0\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+    let module = aura_core::lower_program(&program).expect("lower");
+    let f = module.functions.get("f").expect("f");
+    let calls = f
+        .blocks
+        .iter()
+        .flat_map(|b| &b.insts)
+        .filter(|i| matches!(&i.kind, InstKind::Call { callee, .. } if callee == "note"))
+        .count();
+    assert_eq!(calls, 2, "expected the deferred call on the return and the fallthrough path");
+}
+
+#[test]
+fn loop_jumps_run_the_defers_of_the_scopes_they_leave() {
+    let src = "cell main() -> u32:\n    val mut log: u32 = 0\n    val mut i: u32 = 0\n    while i < 5:\n        i = i + 1\n        defer {\n            log = log * 10 + i\n        }\n        if i == 2:\n            continue\n        if i == 4:\n            break\n        log = log * 10 + 9\n    yield log\n";
+    assert_eq!(run_main(src), OracleValue::U32(912_934));
+}
@@ -0,0 +1,51 @@
+use aura_core::Checker;
+use aura_ir::Terminator;
+
+mod common;
+use common::check_err;
+
+#[test]
+fn return_value_must_match_the_declared_type() {
+    let msg = check_err("cell f(x: u32) -> u32:\n    if x > 1:\n        return \"big\"\n    yield x\n");
+    assert!(msg.contains("type mismatch"), "{msg}");
+}
+
+#[test]
+fn declared_cell_must_yield_or_return_on_every_path() {
+    let msg = check_err("cell f(x: u32) -> u32:\n    if x > 1:\n        return x\n");
+    assert!(msg.contains("must yield or return a value of type u32"), "{msg}");
+
+    let program = aura_parse::parse_source(
+        "cell f(x: u32) -> u32:\n    if x > 1:\n        return x\n    else:\n        return 0\n",
+    )
+    .expect("parse");
+    Checker::new().check_program(&program).expect("both branches return");
+}
+
+#[test]
+fn undeclared_return_sites_must_agree() {
+    let msg = check_err("cell f(x: u32):\n    if x > 1:\n        return \"big\"\n    yield x\n");
+    assert!(msg.contains("mismatched return types"), "{msg}");
+}
+
+#[test]
+fn return_outside_a_cell_is_rejected() {
+    let msg = check_err("return 1\n");
+    assert!(msg.contains("'return' outside of a cell"), "{msg}");
+}
+
+#[test]
+fn early_return_lowers_to_a_return_in_a_branch() {
+    let src = "cell find(n: u32) -> u32:\n    val mut i: u32 = 0\n    while i < n:\n        if i * i > 50:\n            return i\n        i = i + 1\n    yield n\n\ncell main():\n    val r: u32 = find(20)\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+
+    let module = aura_core::lower_program(&program).expect("lower");
+    let f = module.functions.get("find").expect("find");
+    let returns = f
+        .blocks
+        .iter()
+        .filter(|b| matches!(b.term, Terminator::Return(Some(_))))
+        .count();
+    assert_eq!(returns, 2, "expected the early return and the final yield");
+}
@@ -0,0 +1,57 @@
+use aura_core::{infer_effects, Effect};
+
+mod common;
+use common::check;
+
+#[test]
+fn effects_come_from_intrinsics_and_flow_through_calls() {
+    let src = "cell send(msg: String):\n    val mut s = net.connect(\"example.org\", 80)\n    net.send(s, msg)\n    net.close(s)\n\ncell log(line: String):\n    io.println(line)\n\ncell report(msg: String):\n    log(msg)\n    send(msg)\n\ncell add(a: u32, b: u32) -> u32:\n    return a + b\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    let found = infer_effects(&program);
+
+    assert_eq!(found["send"].effects.iter().copied().collect::<Vec<_>>(), vec![Effect::Net]);
+    let report = &found["report"];
+    assert_eq!(report.signature(), "uses [io, net]");
+    let via_send = report.source(Effect::Net).expect("net source");
+    assert_eq!(via_send.via, "send");
+    assert!(found["add"].is_pure());
+}
+
+#[test]
+fn recursion_reaches_a_fixpoint() {
+    let src = "cell ping(n: u32):\n    if n > 0:\n        pong(n - 1)\n\ncell pong(n: u32):\n    hw.enable_irq(n)\n    ping(n)\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    let found = infer_effects(&program);
+    assert_eq!(found["ping"].signature(), "uses [hw]");
+    assert_eq!(found["pong"].signature(), "uses [hw]");
+}
+
+#[test]
+fn declared_effects_must_cover_what_the_cell_performs() {
+    check("cell save(path: String, text: String) uses [fs]:\n    val mut f = fs.create(path)\n    fs.write(f, text)\n    fs.close(f)\n")
+        .expect("declared fs covers fs.*");
+    check("cell add(a: u32, b: u32) -> u32 uses []:\n    return a + b\n").expect("a pure cell");
+
+    let src = "cell log(line: String):\n    io.println(line)\n\ncell add(a: u32, b: u32) -> u32 uses []:\n    log(\"adding\")\n    return a + b\n";
+    let msg = check(src).unwrap_err();
+    assert!(
+        msg.contains("cell 'add' performs io effects through `log` but declares `uses []`; declare `uses [io]`"),
+        "{msg}"
+    );
+
+    let msg = check("cell f() uses [disk]:\n    io.println(\"x\")\n").unwrap_err();
+    assert!(msg.contains("unknown effect 'disk'"), "{msg}");
+}
+
+#[test]
+fn violations_carry_an_edit_that_fixes_the_uses_list() {
+    let src = "cell fetch(host: String) -> String uses [io]:\n    val mut s = net.connect(host, 80)\n    val reply = net.recv_all(s)\n    net.close(s)\n    io.println(reply)\n    return reply\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    let found = aura_core::check_declared_effects(&program);
+    assert_eq!(found.len(), 1, "{found:?}");
+    let edit = found[0].edit.as_ref().expect("edit");
+    let mut fixed = src.to_string();
+    fixed.replace_range(edit.span.offset()..edit.span.offset() + edit.span.len(), &edit.replacement);
+    assert!(fixed.contains("-> String uses [io, net]:"), "{fixed}");
+    check(&fixed).expect("fixed program checks");
+}
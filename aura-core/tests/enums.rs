@@ -0,0 +1,66 @@
+use aura_core::Checker;
+use aura_ir::{run_oracle_entry, InstKind, OracleConfig, OracleValue, Type};
+
+fn lower(src: &str) -> aura_ir::ModuleIR {
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+    aura_core::lower_program(&program).expect("lower")
+}
+
+fn run_main(src: &str) -> OracleValue {
+    let module = lower(src);
+    let out = run_oracle_entry(&module, "main", &[], OracleConfig::default()).expect("oracle");
+    assert!(out.ok, "{out:?}");
+    out.return_value.expect("main yields a value")
+}
+
+const SHAPES: &str = "type Point = enum { At(x: u32, y: u32) }\ntype Shape = enum { Circle(c: Point, r: u32), Square(side: u32), Dot }\n\ncell area(s: Shape) -> u32:\n    val mut out: u32 = 0\n    match s:\n        Shape::Circle(Point::At(x, 0), r):\n            out = x + r\n        Shape::Circle(Point::At(_, 1), r):\n            out = r * 100\n        Shape::Circle(_, r) | Shape::Square(r):\n            out = r\n        Shape::Dot:\n            out = 1\n    yield out\n\n";
+
+#[test]
+fn nested_payload_tests_fall_through_within_their_variant() {
+    let cases = [
+        ("Shape::Circle(Point::At(4, 0), 2)", 6),
+        ("Shape::Circle(Point::At(4, 1), 2)", 200),
+        ("Shape::Circle(Point::At(4, 9), 2)", 2),
+        ("Shape::Square(5)", 5),
+        ("Shape::Dot()", 1),
+    ];
+    for (shape, want) in cases {
+        let src = format!("{SHAPES}cell main() -> u32:\n    yield area({shape})\n");
+        assert_eq!(run_main(&src), OracleValue::U32(want), "{shape}");
+    }
+}
+
+#[test]
+fn enums_lower_to_tagged_unions_with_instantiated_payloads() {
+    let src = "type Option<T> = enum { Some(x: T), None }\n\ncell first(a: Option<String>, b: Option<String>) -> Option<String>:\n    val mut out: Option<String> = b\n    match a:\n        Option::Some(_):\n            out = a\n        _:\n            out = b\n    yield out\n\ncell main() -> u32:\n    val none: Option<String> = Option::None()\n    val mut n: u32 = 0\n    match first(none, Option::Some(\"b\")):\n        Option::Some(s):\n            n = 1\n        Option::None:\n            n = 2\n    yield n\n";
+    let module = lower(src);
+    let first = module.functions.get("first").expect("first");
+    let option = Type::Enum {
+        name: "Option<String>".to_string(),
+        variants: vec![vec![Type::String], vec![]],
+    };
+    assert_eq!(first.ret, option);
+    let main = module.functions.get("main").expect("main");
+    let built: Vec<&Type> = main
+        .blocks
+        .iter()
+        .flat_map(|b| b.insts.iter())
+        .filter_map(|i| match &i.kind {
+            InstKind::MakeEnum { ty, .. } => Some(ty),
+            _ => None,
+        })
+        .collect();
+    // `Option::None` takes its type argument from the binding it initializes.
+    assert_eq!(built, vec![&option, &option]);
+    assert_eq!(run_main(src), OracleValue::U32(1));
+}
+
+#[test]
+fn recursive_enums_are_rejected_by_lowering() {
+    let src = "type Chain = enum { Link(head: u32, tail: Chain), End }\n\ncell main() -> u32:\n    val c: Chain = Chain::Link(1, Chain::End())\n    yield 0\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+    let err = aura_core::lower_program(&program).expect_err("no by-value layout");
+    assert!(err.message.contains("recursive enum 'Chain' cannot be stored by value"), "{}", err.message);
+}
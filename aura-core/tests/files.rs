@@ -0,0 +1,48 @@
+use aura_core::Checker;
+use aura_ir::InstKind;
+
+#[test]
+fn files_are_opened_used_and_closed_through_fs_calls() {
+    let src = "cell copy(src: String, dst: String):\n    val f = fs.open(src)\n    val text = fs.read(f)\n    fs.close(f)\n    val mut out = fs.create(dst)\n    fs.write(out, text)\n    fs.close(out)\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+
+    let module = aura_core::lower_program(&program).expect("lower");
+    let copy = module.functions.get("copy").expect("copy");
+    let callees: Vec<&str> = copy
+        .blocks
+        .iter()
+        .flat_map(|b| b.insts.iter())
+        .filter_map(|i| match &i.kind {
+            InstKind::Call { callee, .. } if callee.starts_with("fs.") => Some(callee.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        callees,
+        vec!["fs.open", "fs.read", "fs.close", "fs.create", "fs.write", "fs.close"]
+    );
+}
+
+#[test]
+fn file_handles_are_linear() {
+    let cases = [
+        (
+            "cell main():\n    val f = fs.open(\"a.txt\")\n    fs.close(f)\n    val _t = fs.read(f)\n",
+            "used after move",
+        ),
+        (
+            "cell main():\n    val f = fs.append(\"log.txt\")\n    fs.close(f)\n    fs.close(f)\n",
+            "used after move",
+        ),
+        (
+            "cell main():\n    val f = fs.create(\"out.txt\")\n    fs.write(f, \"x\")\n",
+            "cannot pass immutable val 'f' as mutable file receiver",
+        ),
+    ];
+    for (src, want) in cases {
+        let program = aura_parse::parse_source(src).expect("parse");
+        let err = Checker::new().check_program(&program).expect_err(src);
+        assert!(err.message.contains(want), "{src}: {}", err.message);
+    }
+}
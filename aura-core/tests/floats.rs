@@ -0,0 +1,31 @@
+use aura_core::Checker;
+use aura_ir::{InstKind, RValue};
+
+#[test]
+fn f64_arithmetic_checks_and_lowers_to_const_f64() {
+    let src = "cell main():\n    val x: f64 = 1.5e2\n    val y = -x * 0.25 + 2.0\n    val big = y > 10.0\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+
+    let module = aura_core::lower_program(&program).expect("lower");
+    let f = module.functions.get("main").expect("main");
+
+    let consts: Vec<f64> = f
+        .blocks
+        .iter()
+        .flat_map(|b| b.insts.iter())
+        .filter_map(|i| match &i.kind {
+            InstKind::BindStrand { expr: RValue::ConstF64(v), .. } => Some(*v),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(consts, vec![150.0, 0.25, 2.0, 10.0]);
+}
+
+#[test]
+fn mixing_u32_and_f64_is_rejected() {
+    let src = "cell main():\n    val x: f64 = 1.5\n    val n: u32 = 2\n    val bad = x + n\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    let err = Checker::new().check_program(&program).expect_err("u32 + f64 must not type-check");
+    assert!(err.message.contains("expects u32,u32 or f64,f64"), "{}", err.message);
+}
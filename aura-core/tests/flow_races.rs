@@ -0,0 +1,86 @@
+use aura_core::{detect_flow_races, Checker, FlowRace, ViolationKind};
+
+fn races(src: &str) -> Vec<FlowRace> {
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+    detect_flow_races(&program, src)
+}
+
+fn text(src: &str, span: aura_ast::Span) -> &str {
+    &src[span.offset()..span.offset() + span.len()]
+}
+
+#[test]
+fn two_spawned_flows_writing_a_shared_strand_race() {
+    let src = "val mut counter: u32 = 0\n\ncell bump(seed: u32) -> u32:\n    counter = counter + 1\n    return 0\n\ncell main():\n    val a = 1 ~> bump()\n    val b = 2 ~> bump()\n";
+    let found = races(src);
+    assert_eq!(found.len(), 1, "{found:?}");
+    let race = &found[0];
+    assert_eq!(race.strand, "counter");
+    assert_eq!(race.first.cell, "bump");
+    assert!(race.second.write);
+    assert_eq!(text(src, race.first.spawn.expect("spawned")), "1 ~> bump()");
+    assert_eq!(text(src, race.second.spawn.expect("spawned")), "2 ~> bump()");
+    assert_eq!(race.message(), "data race on 'counter': read and written by concurrent flows without synchronization");
+    assert!(race.suggestion().contains("chan.bounded"), "{}", race.suggestion());
+
+    let lint = race.lint("main.aura", src).expect("in source");
+    assert_eq!(lint.diagnostic.error_kind, ViolationKind::DataRace);
+    assert_eq!(lint.diagnostic.location.line, 4);
+    let related: Vec<u32> = lint.diagnostic.related.iter().map(|r| r.location.line).collect();
+    assert_eq!(related, vec![4, 8, 9]);
+}
+
+#[test]
+fn the_spawning_cell_races_with_its_flows_until_they_are_joined() {
+    let src = "val mut total: u32 = 0\n\ncell add(seed: u32, n: u32) -> u32:\n    total = total + n\n    return n\n\ncell main():\n    val t = 1 ~> add(2)\n    total = 5\n    val r = t\n    total = 6\n";
+    let found = races(src);
+    assert_eq!(found.len(), 1, "{found:?}");
+    assert_eq!(found[0].second.cell, "main");
+    assert_eq!(found[0].second.spawn, None);
+    assert_eq!(text(src, found[0].second.span), "total");
+    assert_eq!(src[..found[0].second.span.offset()].lines().count(), 9);
+
+    // Reading the handle first joins the flow.
+    let src = "val mut total: u32 = 0\n\ncell add(seed: u32, n: u32) -> u32:\n    total = total + n\n    return n\n\ncell main():\n    val t = 1 ~> add(2)\n    val r = t\n    total = 5\n";
+    assert!(races(src).is_empty());
+}
+
+#[test]
+fn scopes_join_their_flows_and_loops_spawn_several() {
+    let src = "val mut hits: u32 = 0\n\ncell hit(seed: u32) -> u32:\n    hits = hits + 1\n    return 0\n\ncell main():\n    scope:\n        val a = 1 ~> hit()\n    scope:\n        val b = 2 ~> hit()\n";
+    assert!(races(src).is_empty());
+
+    let src = "val mut hits: u32 = 0\n\ncell hit(seed: u32) -> u32:\n    hits = hits + 1\n    return 0\n\ncell main():\n    scope:\n        for i in 0..4:\n            val a = 1 ~> hit()\n";
+    let found = races(src);
+    assert_eq!(found.len(), 1, "{found:?}");
+    assert_eq!(found[0].first.spawn, found[0].second.spawn);
+}
+
+#[test]
+fn callees_locals_and_read_only_strands() {
+    // The write happens in a cell the flow calls.
+    let src = "val mut level: u32 = 0\n\ncell set_level(n: u32):\n    level = n\n\ncell worker(seed: u32, n: u32) -> u32:\n    set_level(n)\n    return n\n\ncell main():\n    val a = 1 ~> worker(1)\n    val b = level\n";
+    let found = races(src);
+    assert_eq!(found.len(), 1, "{found:?}");
+    assert_eq!(found[0].first.cell, "set_level");
+    assert_eq!(found[0].second.cell, "main");
+
+    // A parameter of the same name is not the strand, and flows that only read cannot race.
+    let src = "val mut level: u32 = 0\n\ncell worker(seed: u32, level: mut u32) -> u32:\n    level = level + 1\n    return level\n\ncell reader(seed: u32, n: u32) -> u32:\n    return level + n\n\ncell main():\n    val a = 1 ~> worker(1)\n    val b = 2 ~> reader(2)\n    val c = 3 ~> reader(3)\n";
+    assert!(races(src).is_empty());
+}
+
+#[test]
+fn a_shadowing_binding_ends_with_its_block() {
+    // `counter` names the loop variable only inside the loop; the write after it is the strand.
+    let src = "val mut counter: u32 = 0\n\ncell bump(seed: u32) -> u32:\n    for counter in 0..3:\n        val x = counter + 1\n    counter = counter + 1\n    return 0\n\ncell main():\n    val a = 1 ~> bump()\n    val b = 2 ~> bump()\n";
+    let found = races(src);
+    assert_eq!(found.len(), 1, "{found:?}");
+    assert_eq!(found[0].strand, "counter");
+    assert_eq!(src[..found[0].first.span.offset()].lines().count(), 6);
+
+    // Inside the loop it is still the local.
+    let src = "val mut counter: u32 = 0\n\ncell bump(seed: u32) -> u32:\n    for counter in 0..3:\n        val x = counter + 1\n    return 0\n\ncell main():\n    val a = 1 ~> bump()\n    val b = 2 ~> bump()\n";
+    assert!(races(src).is_empty());
+}
@@ -0,0 +1,20 @@
+use aura_core::Checker;
+
+#[test]
+fn for_loop_checks_and_lowers_to_a_loop() {
+    let src = "cell main():\n    val mut total: u32 = 0\n    for i in 0..10:\n        total = total + i\n    val done: u32 = total\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+
+    let module = aura_core::lower_program(&program).expect("lower");
+    let f = module.functions.get("main").expect("main");
+    assert!(f.blocks.len() > 1, "for-loop should lower to a multi-block loop");
+}
+
+#[test]
+fn for_loop_variable_is_immutable() {
+    let src = "cell main():\n    for i in 0..4:\n        i = i + 1\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    let err = Checker::new().check_program(&program).expect_err("assigning to the loop variable must fail");
+    assert!(format!("{err:?}").contains("cannot assign to immutable val 'i'"));
+}
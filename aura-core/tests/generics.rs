@@ -13,3 +13,73 @@ fn generic_enum_ctor_and_match_binder_types() {
     let program = aura_parse::parse_source(src).expect("parse");
     Checker::new().check_program(&program).expect("sema");
 }
+
+#[test]
+fn generic_cell_monomorphizes_per_type_argument() {
+    let src = "trait Numeric\n\ncell twice<T: Numeric>(x: T):\n    val y: T = x + x\n    yield y\n\ncell main():\n    val a: u32 = twice(2)\n    val b: f64 = twice(x: 1.5)\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    let module = aura_core::lower_program(&program).expect("lower");
+
+    assert!(!module.functions.contains_key("twice"));
+    let u = module.functions.get("twice__u32").expect("u32 instance");
+    assert_eq!(u.params[0].ty, aura_ir::Type::U32);
+    assert_eq!(u.ret, aura_ir::Type::U32);
+    let f = module.functions.get("twice__f64").expect("f64 instance");
+    assert_eq!(f.params[0].ty, aura_ir::Type::F64);
+
+    let callees: Vec<&str> = module.functions["main"]
+        .blocks
+        .iter()
+        .flat_map(|b| b.insts.iter())
+        .filter_map(|i| match &i.kind {
+            aura_ir::InstKind::Call { callee, .. } => Some(callee.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(callees, vec!["twice__u32", "twice__f64"]);
+}
+
+#[test]
+fn generic_cell_rejects_unsatisfied_bound_and_conflicting_inference() {
+    let src = "trait Numeric\n\ncell twice<T: Numeric>(x: T):\n    x\n\ncell main():\n    val s = twice(\"hi\")\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    let err = Checker::new().check_program(&program).expect_err("String is not Numeric");
+    assert!(err.message.contains("does not satisfy trait bound 'Numeric'"), "{}", err.message);
+
+    let src = "cell pick<T>(a: T, b: T):\n    a\n\ncell main():\n    val x = pick(1, 2.5)\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    let err = Checker::new().check_program(&program).expect_err("T cannot be both u32 and f64");
+    assert!(err.message.contains("conflicting type argument inference for 'T'"), "{}", err.message);
+}
+
+#[test]
+fn generic_bodies_are_checked_once_with_opaque_type_parameters() {
+    let check = |src: &str| {
+        let program = aura_parse::parse_source(src).expect("parse");
+        Checker::new().check_program(&program).map_err(|e| e.message)
+    };
+
+    // Never called, so no instance would ever have checked it.
+    let err = check("cell f<T>(x: T):\n    val y: u32 = nope\n    yield x\n").expect_err("unknown name");
+    assert!(err.contains("nope"), "{err}");
+
+    // `f(1)` would be fine, but `T` is not `u32` for every caller.
+    let err = check("trait Numeric\n\ncell f<T: Numeric>(x: T) -> T:\n    return x + 1\n").expect_err("T + u32");
+    assert!(err.contains("operands must both be type parameter 'T'; got T,"), "{err}");
+
+    let err = check("cell f<T>(x: T):\n    val y = x + x\n    yield y\n").expect_err("no bound");
+    assert!(err.contains("this operator needs the 'Numeric' bound on type parameter 'T'"), "{err}");
+
+    check("trait Numeric\n\ncell f<T: Numeric>(x: T) -> bool:\n    val y: T = x * x\n    return y > x\n")
+        .expect("bounded ops");
+}
+
+#[test]
+fn the_opaque_check_leaves_no_instances_behind() {
+    let src = "trait Numeric\n\ncell twice<T: Numeric>(x: T) -> T:\n    return x + x\n\ncell quad<T: Numeric>(x: T) -> T:\n    return twice(twice(x))\n\ncell main():\n    val a: u32 = quad(2)\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    let module = aura_core::lower_program(&program).expect("lower");
+    let mut names: Vec<&str> = module.functions.keys().map(String::as_str).collect();
+    names.sort();
+    assert_eq!(names, vec!["main", "quad__u32", "twice__u32"]);
+}
@@ -0,0 +1,47 @@
+use aura_core::Checker;
+
+fn infer(src: &str, name: &str) -> Result<String, String> {
+    let program = aura_parse::parse_source(src).expect("parse");
+    let mut checker = Checker::new();
+    checker.check_program(&program).map_err(|e| e.message)?;
+    Ok(checker.top_level_val_type(name).expect("top-level val").display())
+}
+
+#[test]
+fn unannotated_channels_take_their_element_type_from_the_first_use() {
+    let cases = [
+        ("val ch = chan.unbounded()\nchan.send(ch, 5)\nval x = chan.recv(ch)\n", "u32"),
+        ("val ch = chan.bounded(2)\nval x = chan.recv(ch) + 1\n", "u32[1..4294967295]"),
+        ("val ch = chan.unbounded()\nval x = chan.recv(ch)\nval s: String = x\n", "String"),
+        ("cell f(ch: Channel<u32[0..9]>):\n    chan.send(ch, 1)\n\nval ch = chan.unbounded()\nf(ch)\nval x = chan.recv(ch)\n", "u32[0..9]"),
+    ];
+    for (src, expected) in cases {
+        assert_eq!(infer(src, "x").as_deref(), Ok(expected), "{src}");
+    }
+    assert_eq!(infer("val ch = chan.unbounded()\nchan.send(ch, 1)\n", "ch").as_deref(), Ok("Channel<u32>"));
+}
+
+#[test]
+fn uses_inside_lambdas_and_assignments_solve_bindings_too() {
+    let src = "val ch = chan.unbounded()\nval f = -> { chan.send(ch, \"hi\") }\nval x = chan.recv(ch)\n";
+    assert_eq!(infer(src, "x").as_deref(), Ok("String"));
+
+    let src = "val mut xs = []\nxs = [1, 2]\nval x = xs\n";
+    assert_eq!(infer(src, "x").as_deref(), Ok("List<u32>"));
+}
+
+#[test]
+fn later_uses_are_checked_against_the_inferred_type() {
+    let cases = [
+        ("val ch = chan.unbounded()\nchan.send(ch, 5)\nchan.send(ch, \"a\")\n", "expected u32, got String"),
+        ("val ch = chan.unbounded()\nval x = chan.recv(ch) + 1\nchan.send(ch, \"a\")\n", "expected u32, got String"),
+        (
+            "cell count(xs: &List<String>) -> u32:\n    return xs.len()\n\nval xs = []\nval c = count(&xs)\nval n: u32 = xs[0]\n",
+            "expected u32, got String",
+        ),
+    ];
+    for (src, expected) in cases {
+        let err = infer(src, "ch").expect_err(src);
+        assert!(err.contains(expected), "{src}: {err}");
+    }
+}
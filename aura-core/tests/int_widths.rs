@@ -0,0 +1,61 @@
+use aura_core::Checker;
+
+mod common;
+use common::check;
+
+#[test]
+fn narrower_integers_widen_implicitly() {
+    check("val a: u8 = 200\nval b: u32 = a\nval c: i64 = b\n").expect("widening conversions");
+    check("cell f(data: u8, seed: u16) -> u64:\n    val sum = data + seed\n    return sum\n")
+        .expect("mixed widths meet at the wider type");
+    check("val d: i32 = -1\nval w: u32 = 3\nval x = d + w\n").expect("i32 and u32 meet at i64");
+}
+
+#[test]
+fn narrowing_needs_a_range_or_bounded_value() {
+    let msg = check("cell low(x: u32) -> u8:\n    val b: u8 = x\n    return b\n").unwrap_err();
+    assert!(msg.contains("expected u8, got u32 (values outside 0..255 don't fit)"), "{msg}");
+
+    // Masked values and range types say how many bits the value needs.
+    check("cell low(x: u32) -> u8:\n    return x & 255\n").expect("a mask fits u8");
+    check("cell low(x: u32) -> u32:\n    val b: u8[0..255] = x\n    return b\n")
+        .expect("the verifier proves the range");
+}
+
+#[test]
+fn literals_and_ranges_must_fit_the_width() {
+    let msg = check("val a: u8 = 300\n").unwrap_err();
+    assert!(msg.contains("u8"), "{msg}");
+    let msg = check("val a: u8[0..300] = 5\n").unwrap_err();
+    assert!(msg.contains("range [0..300] does not fit u8"), "{msg}");
+    let msg = check("val a: u32 = -1\n").unwrap_err();
+    assert!(msg.contains("expected u32, got i32"), "{msg}");
+    check("val a: i32 = -2147483648\nval b: i64 = 5000000000\n").expect("literals at the edges of the width");
+}
+
+#[test]
+fn conversions_lower_to_int_casts() {
+    let src = "cell f(b: u8, n: i32) -> i64:\n    val w: u32 = b\n    return n + w\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+
+    let module = aura_core::lower_program(&program).expect("lower");
+    let f = module.functions.get("f").expect("f");
+    let casts: Vec<(aura_ir::Type, aura_ir::Type)> = f
+        .blocks
+        .iter()
+        .flat_map(|b| b.insts.iter())
+        .filter_map(|i| match &i.kind {
+            aura_ir::InstKind::IntCast { from, to, .. } => Some((from.clone(), to.clone())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        casts,
+        vec![
+            (aura_ir::Type::U8, aura_ir::Type::U32),
+            (aura_ir::Type::I32, aura_ir::Type::I64),
+            (aura_ir::Type::U32, aura_ir::Type::I64),
+        ]
+    );
+}
@@ -0,0 +1,61 @@
+use aura_core::{lint_program, Severity, ViolationKind};
+
+fn lint(src: &str) -> Vec<(ViolationKind, String, &str)> {
+    let program = aura_parse::parse_source(src).expect("parse");
+    aura_core::Checker::new().check_program(&program).expect("sema");
+    lint_program(&program, "main.aura", src)
+        .into_iter()
+        .map(|l| {
+            assert_eq!(l.diagnostic.severity, Severity::Warning);
+            let text = &src[l.span.offset()..l.span.offset() + l.span.len()];
+            (l.diagnostic.error_kind, l.diagnostic.message, text)
+        })
+        .collect()
+}
+
+#[test]
+fn unused_vals_and_params_are_reported_unless_underscored() {
+    let src = "cell f(a: u32, b: u32, _c: u32) -> u32:\n    val x = a + 1\n    val y = 2\n    val _z = 3\n    return x\n";
+    assert_eq!(
+        lint(src),
+        vec![
+            (ViolationKind::UnusedBinding, "unused parameter 'b'".to_string(), "b"),
+            (ViolationKind::UnusedBinding, "unused val 'y'".to_string(), "y"),
+        ]
+    );
+
+    // Assigning is not reading.
+    let src = "cell f() -> u32:\n    val mut n: u32 = 1\n    n = 2\n    return 0\n";
+    let found: Vec<String> = lint(src).into_iter().map(|(_, msg, _)| msg).collect();
+    assert_eq!(found, vec!["unused val 'n'"]);
+}
+
+#[test]
+fn capability_params_and_pattern_bindings_are_left_alone() {
+    let src = "cell f(t: Tensor, pair: (u32, u32)) -> u32:\n    val (a, b) = pair\n    for i in 0..3:\n        val _ = a\n    return b\n";
+    let found = lint(src);
+    assert!(found.is_empty(), "{found:?}");
+}
+
+#[test]
+fn imports_nothing_refers_to_are_reported() {
+    let src = "import aura::io\nimport aura::tensor\nimport aura::lumina\n\ncell main():\n    io.println(\"hi\")\n";
+    assert_eq!(
+        lint(src),
+        vec![(ViolationKind::UnusedImport, "unused import 'aura::tensor'".to_string(), "import aura::tensor")]
+    );
+}
+
+#[test]
+fn statements_after_a_statement_that_always_leaves_are_unreachable() {
+    let src = "cell f(n: u32) -> u32:\n    if n > 2:\n        return 1\n    else:\n        return 2\n    val z = n\n    return z\n";
+    let found = lint(src);
+    assert_eq!(found.len(), 1, "{found:?}");
+    assert_eq!(found[0].0, ViolationKind::UnreachableCode);
+    assert_eq!(found[0].2, "val z = n\n    return z");
+
+    let src = "cell f() -> u32:\n    while 1 < 2:\n        if 1 < 2:\n            break\n        else:\n            continue\n        val k = 1\n    return 0\n";
+    let found = lint(src);
+    assert_eq!(found.len(), 2, "{found:?}");
+    assert_eq!(found[0].0, ViolationKind::UnreachableCode);
+}
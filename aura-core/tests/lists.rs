@@ -0,0 +1,50 @@
+use aura_core::Checker;
+use aura_ir::InstKind;
+
+#[test]
+fn list_literals_index_and_lower_to_tensor_calls() {
+    let src = "cell main():\n    val xs: List<u32> = [10, 20, 30]\n    val n = xs.len()\n    val y: u32 = xs[1] + n\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+
+    let module = aura_core::lower_program(&program).expect("lower");
+    let main = module.functions.get("main").expect("main");
+    let callees: Vec<&str> = main
+        .blocks
+        .iter()
+        .flat_map(|b| b.insts.iter())
+        .filter_map(|i| match &i.kind {
+            InstKind::Call { callee, .. } => Some(callee.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        callees,
+        vec!["tensor.new", "tensor.set", "tensor.set", "tensor.set", "tensor.len", "tensor.get"]
+    );
+}
+
+#[test]
+fn list_element_and_index_type_errors_are_reported() {
+    let src = "cell main():\n    val xs = [1, 2 > 1]\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    let err = Checker::new().check_program(&program).expect_err("mixed elements");
+    assert!(err.message.contains("list elements must share one type"), "{}", err.message);
+
+    let src = "cell main():\n    val xs = [1, 2]\n    val y = xs[2 > 1]\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    let err = Checker::new().check_program(&program).expect_err("bool index");
+    assert!(err.message.contains("list index must be u32"), "{}", err.message);
+
+    let src = "cell main():\n    val xs: List<bool> = [1]\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    let err = Checker::new().check_program(&program).expect_err("element mismatch");
+    assert!(err.message.contains("expected List<bool>, got List<u32>"), "{}", err.message);
+}
+
+#[test]
+fn empty_list_takes_its_element_type_from_the_annotation() {
+    let src = "cell main():\n    val xs: List<String> = []\n    val n: u32 = xs.len()\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+}
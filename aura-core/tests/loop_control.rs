@@ -0,0 +1,73 @@
+use std::collections::{BTreeSet, HashMap};
+
+use aura_core::Checker;
+use aura_ir::{BlockId, FunctionIR, InstKind, Terminator};
+
+mod common;
+use common::check_err;
+
+/// Every phi must list exactly the predecessors of its block.
+fn assert_phis_match_predecessors(f: &FunctionIR) {
+    let mut preds: HashMap<BlockId, BTreeSet<BlockId>> = HashMap::new();
+    for bb in &f.blocks {
+        let succs = match &bb.term {
+            Terminator::Return(_) => vec![],
+            Terminator::Br(t) => vec![*t],
+            Terminator::CondBr { then_bb, else_bb, .. } => vec![*then_bb, *else_bb],
+            Terminator::Switch { default_bb, cases, .. } => {
+                let mut v: Vec<BlockId> = cases.iter().map(|(_, b)| *b).collect();
+                v.push(*default_bb);
+                v
+            }
+        };
+        for s in succs {
+            preds.entry(s).or_default().insert(bb.id);
+        }
+    }
+    for bb in &f.blocks {
+        for inst in &bb.insts {
+            if let InstKind::Phi { incomings } = &inst.kind {
+                let got: BTreeSet<BlockId> = incomings.iter().map(|(b, _)| *b).collect();
+                let want = preds.get(&bb.id).cloned().unwrap_or_default();
+                assert_eq!(got, want, "phi in {:?} does not match its predecessors", bb.id);
+            }
+        }
+    }
+}
+
+#[test]
+fn break_and_continue_outside_a_loop_are_rejected() {
+    let msg = check_err("cell main():\n    break\n");
+    assert!(msg.contains("'break' outside of a loop"), "{msg}");
+
+    let msg = check_err("cell main(x: u32):\n    if x > 1:\n        continue\n");
+    assert!(msg.contains("'continue' outside of a loop"), "{msg}");
+}
+
+#[test]
+fn code_after_break_is_rejected() {
+    let msg = check_err("cell main():\n    val mut i: u32 = 0\n    while i < 3:\n        break\n        i = i + 1\n");
+    assert!(msg.contains("unreachable code after 'break'"), "{msg}");
+}
+
+#[test]
+fn break_and_continue_lower_with_consistent_phis() {
+    let src = "cell main(n: u32):\n    val mut i: u32 = 0\n    val mut s: u32 = 0\n    while i < n:\n        i = i + 1\n        if i == 3:\n            continue\n        if s > 100:\n            break\n        s = s + i\n    for j in 0..n:\n        if j == 2:\n            continue\n        else:\n            s = s + 1\n        if j > 5:\n            break\n        s = s + j\n    yield s\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+
+    let module = aura_core::lower_program(&program).expect("lower");
+    let f = module.functions.get("main").expect("main");
+    assert_phis_match_predecessors(f);
+}
+
+#[test]
+fn if_whose_branches_both_jump_leaves_no_dead_join() {
+    let src = "cell main(n: u32):\n    val mut i: u32 = 0\n    while i < n:\n        i = i + 1\n        if i > 4:\n            break\n        else:\n            continue\n    yield i\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+
+    let module = aura_core::lower_program(&program).expect("lower");
+    let f = module.functions.get("main").expect("main");
+    assert_phis_match_predecessors(f);
+}
@@ -0,0 +1,50 @@
+use aura_core::Checker;
+use aura_ir::InstKind;
+
+#[test]
+fn map_literals_index_and_lower_to_map_calls() {
+    let src = "cell main():\n    val ages: Map<String, u32> = {\"ann\": 31, \"bob\": 42}\n    val n = ages.len()\n    if ages.contains(\"ann\"):\n        val a: u32 = ages[\"ann\"] + n\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+
+    let module = aura_core::lower_program(&program).expect("lower");
+    let main = module.functions.get("main").expect("main");
+    let callees: Vec<&str> = main
+        .blocks
+        .iter()
+        .flat_map(|b| b.insts.iter())
+        .filter_map(|i| match &i.kind {
+            InstKind::Call { callee, .. } => Some(callee.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        callees,
+        vec!["map.new", "map.insert", "map.insert", "map.len", "map.contains", "map.get"]
+    );
+}
+
+#[test]
+fn map_key_value_and_method_errors_are_reported() {
+    let cases = [
+        ("val m = {1: 2, 3: 2 > 1}", "map values must share one type"),
+        ("val m = {\"a\": 1, \"a\": 2}", "duplicate map key \"a\""),
+        ("val m = {2 > 1: 1}", "map keys must be u32 or String"),
+        ("val m = {1: 2}\n    val v = m[\"a\"]", "map key must be u32, got String"),
+        ("val m: Map<u32, bool> = {1: 2}", "expected Map<u32, bool>, got Map<u32, u32>"),
+        ("val m = {1: 2}\n    val v = m.keys()", "unknown map method 'keys'"),
+    ];
+    for (body, expected) in cases {
+        let src = format!("cell main():\n    {body}\n");
+        let program = aura_parse::parse_source(&src).expect("parse");
+        let err = Checker::new().check_program(&program).expect_err(body);
+        assert!(err.message.contains(expected), "{body}: {}", err.message);
+    }
+}
+
+#[test]
+fn empty_map_takes_its_types_from_the_annotation() {
+    let src = "cell main():\n    val m: Map<u32, String> = {}\n    val n: u32 = m.len()\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+}
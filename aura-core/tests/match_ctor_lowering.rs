@@ -1,7 +1,7 @@
-use aura_ir::InstKind;
+use aura_ir::{InstKind, Terminator, Type};
 
 #[test]
-fn lowers_enum_ctor_and_match_patterns_via_tensor_encoding() {
+fn lowers_enum_ctor_and_match_patterns_to_tagged_unions() {
     let src = r#"
 import aura::io
 
@@ -24,25 +24,14 @@ cell main() ->:
     let module = aura_core::lower_program(&program).expect("lower");
 
     let f = module.functions.get("main").expect("main");
-
-    let mut saw_tensor_new = false;
-    let mut saw_tensor_set = false;
-    let mut saw_tensor_get = false;
-
-    for bb in &f.blocks {
-        for inst in &bb.insts {
-            if let InstKind::Call { callee, .. } = &inst.kind {
-                match callee.as_str() {
-                    "tensor.new" => saw_tensor_new = true,
-                    "tensor.set" => saw_tensor_set = true,
-                    "tensor.get" => saw_tensor_get = true,
-                    _ => {}
-                }
-            }
-        }
-    }
-
-    assert!(saw_tensor_new, "expected enum ctor lowering to use tensor.new");
-    assert!(saw_tensor_set, "expected enum ctor lowering to use tensor.set");
-    assert!(saw_tensor_get, "expected enum match lowering to use tensor.get");
+    let insts: Vec<&InstKind> = f.blocks.iter().flat_map(|b| b.insts.iter()).map(|i| &i.kind).collect();
+
+    assert!(insts.iter().any(|k| matches!(
+        k,
+        InstKind::MakeEnum { ty: Type::Enum { name, variants }, tag: 1, fields }
+            if name == "Opt" && variants == &vec![vec![], vec![Type::U32]] && fields.len() == 1
+    )));
+    assert!(insts.iter().any(|k| matches!(k, InstKind::EnumTag { .. })));
+    assert!(insts.iter().any(|k| matches!(k, InstKind::EnumField { tag: 1, index: 0, .. })));
+    assert!(f.blocks.iter().any(|b| matches!(b.term, Terminator::Switch { .. })));
 }
@@ -35,3 +35,54 @@ fn match_rejects_duplicate_literals() {
         err.message
     );
 }
+
+const SHAPES: &str = "type Point = enum { At(x: u32, y: u32) }\ntype Shape = enum { Circle(c: Point, r: u32), Square(side: u32), Dot }\n";
+
+/// Source text of each arm the checker warns is unreachable.
+fn unreachable_arms(body: &str) -> Vec<String> {
+    let src = format!("{SHAPES}{body}");
+    let program = aura_parse::parse_source(&src).expect("parse");
+    let mut checker = Checker::new();
+    checker.check_program(&program).expect("sema");
+    checker
+        .match_lints("main.aura", &src)
+        .into_iter()
+        .map(|l| {
+            assert_eq!(l.diagnostic.message, "unreachable match arm");
+            let text = &src[l.span.offset()..l.span.offset() + l.span.len()];
+            text.lines().next().unwrap_or_default().trim().to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn match_rejects_duplicate_variant_and_tuple_arms() {
+    let src = format!("{SHAPES}cell f(s: Shape) -> u32:\n    val mut out: u32 = 0\n    match s:\n        Shape::Dot:\n            out = 1\n        Shape::Square(2) | Shape::Dot:\n            out = 2\n        _:\n            out = 3\n    return out\n");
+    let program = aura_parse::parse_source(&src).expect("parse");
+    let err = Checker::new().check_program(&program).expect_err("expected sema error");
+    assert_eq!(err.message, "duplicate match arm for Shape::Dot");
+
+    let src = "cell f(p: (u32, u32)) -> u32:\n    val mut out: u32 = 0\n    match p:\n        (1, 2):\n            out = 1\n        (1, 2):\n            out = 2\n        _:\n            out = 3\n    return out\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    let err = Checker::new().check_program(&program).expect_err("expected sema error");
+    assert_eq!(err.message, "duplicate match arm for (1, 2)");
+}
+
+#[test]
+fn arms_covered_by_earlier_wildcards_are_reported_unreachable() {
+    let arms = unreachable_arms(
+        "cell area(s: Shape) -> u32:\n    val mut out: u32 = 0\n    match s:\n        Shape::Circle(_, r):\n            out = r\n        Shape::Circle(Point::At(x, 0), r):\n            out = x\n        Shape::Square(_) | Shape::Dot:\n            out = 1\n    return out\n",
+    );
+    assert_eq!(arms, vec!["Shape::Circle(Point::At(x, 0), r):"]);
+
+    // Only the two arms together cover `(0, 1)`.
+    let arms = unreachable_arms(
+        "cell f(p: (u32, u32)) -> u32:\n    val mut out: u32 = 0\n    match p:\n        (0, _):\n            out = 1\n        (_, 1):\n            out = 2\n        (0, 1):\n            out = 3\n        (2, 2):\n            out = 4\n        _:\n            out = 5\n    return out\n",
+    );
+    assert_eq!(arms, vec!["(0, 1):"]);
+
+    let arms = unreachable_arms(
+        "cell area(s: Shape) -> u32:\n    val mut out: u32 = 0\n    match s:\n        Shape::Circle(Point::At(x, 0), r):\n            out = x + r\n        Shape::Circle(_, r) | Shape::Square(r):\n            out = r\n        Shape::Dot:\n            out = 1\n    return out\n",
+    );
+    assert!(arms.is_empty(), "{arms:?}");
+}
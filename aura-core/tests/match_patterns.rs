@@ -0,0 +1,68 @@
+use aura_core::Checker;
+use aura_ir::{InstKind, Terminator};
+
+mod common;
+use common::check;
+
+const SHAPES: &str = "type Point = enum { At(x: u32, y: u32) }\ntype Shape = enum { Circle(c: Point, r: u32), Square(side: u32), Dot }\n";
+
+#[test]
+fn or_and_nested_patterns_cover_every_variant_without_a_wildcard() {
+    check(&format!(
+        "{SHAPES}cell area(s: Shape):\n    val mut out: u32 = 0\n    match s:\n        Shape::Circle(Point::At(x, 0), r):\n            out = x + r\n        Shape::Circle(_, r) | Shape::Square(r):\n            out = r\n        Shape::Dot:\n            out = 1\n    yield out\n"
+    ))
+    .expect("arms are exhaustive");
+}
+
+#[test]
+fn missing_variant_is_reported_with_an_example() {
+    let err = check(&format!(
+        "{SHAPES}cell area(s: Shape):\n    val mut out: u32 = 0\n    match s:\n        Shape::Circle(Point::At(_, 0), r):\n            out = r\n        Shape::Square(_) | Shape::Dot:\n            out = 1\n    yield out\n"
+    ))
+    .expect_err("circles off the x axis are not covered");
+    assert!(err.contains("non-exhaustive match"), "{err}");
+    assert!(err.contains("Shape::Circle(Point::At(_, _), _)"), "{err}");
+}
+
+#[test]
+fn or_alternatives_must_bind_the_same_names() {
+    let err = check(&format!(
+        "{SHAPES}cell area(s: Shape):\n    val mut out: u32 = 0\n    match s:\n        Shape::Circle(_, r) | Shape::Square(side):\n            out = 1\n        _:\n            out = 0\n    yield out\n"
+    ))
+    .expect_err("r and side differ");
+    assert!(err.contains("or-pattern alternatives must bind the same names"), "{err}");
+
+    let err = check(&format!(
+        "{SHAPES}cell area(s: Shape):\n    val mut out: u32 = 0\n    match s:\n        Shape::Circle(c, _) | Shape::Square(c):\n            out = 1\n        _:\n            out = 0\n    yield out\n"
+    ))
+    .expect_err("c has two types");
+    assert!(err.contains("'c' has type Point in one alternative and u32 in another"), "{err}");
+}
+
+#[test]
+fn or_arm_binders_meet_in_a_phi_and_nested_fields_are_tested() {
+    let src = format!(
+        "{SHAPES}cell area(s: Shape):\n    val mut out: u32 = 0\n    match s:\n        Shape::Circle(Point::At(x, 0), r):\n            out = x + r\n        Shape::Circle(_, r) | Shape::Square(r):\n            out = r\n        Shape::Dot:\n            out = 1\n    yield out\n"
+    );
+    let program = aura_parse::parse_source(&src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+
+    let module = aura_core::lower_program(&program).expect("lower");
+    let f = module.functions.get("area").expect("area");
+    // Circles dispatch through the tag switch, then test the nested `Point::At(x, 0)`.
+    assert!(f.blocks.iter().any(|b| matches!(b.term, Terminator::Switch { .. })));
+    let cond_brs = f
+        .blocks
+        .iter()
+        .filter(|b| matches!(b.term, Terminator::CondBr { .. }))
+        .count();
+    assert!(cond_brs >= 2, "expected the nested payload to be tested, got {cond_brs}");
+    // `r` comes from a different payload slot per alternative.
+    let phis = f
+        .blocks
+        .iter()
+        .flat_map(|b| b.insts.iter())
+        .filter(|i| matches!(i.kind, InstKind::Phi { .. }))
+        .count();
+    assert!(phis >= 1, "expected a phi joining the or-pattern binders");
+}
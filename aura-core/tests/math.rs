@@ -0,0 +1,21 @@
+use aura_core::Checker;
+
+#[test]
+fn math_calls_take_u32_or_f64() {
+    let src = "cell main(x: u32, t: f64):\n    val c: u32 = math.min(math.max(x, 1), 10)\n    val p: u32 = math.pow(c, 2)\n    val r: f64 = math.sqrt(math.abs(t)) + math.sin(t) * math.cos(t)\n    val q: f64 = math.powf(r, 0.5)\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+}
+
+#[test]
+fn math_calls_do_not_mix_u32_and_f64() {
+    let cases = [
+        "cell main(t: f64):\n    val m = math.min(t, 1)\n",
+        "cell main(x: u32):\n    val r = math.sqrt(x)\n",
+        "cell main(t: f64):\n    val n: u32 = math.abs(t)\n",
+    ];
+    for src in cases {
+        let program = aura_parse::parse_source(src).expect("parse");
+        Checker::new().check_program(&program).expect_err(src);
+    }
+}
@@ -0,0 +1,38 @@
+use aura_core::{Checker, Type};
+
+fn program(body: &str) -> aura_ast::Program {
+    let model = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../examples/aura-vision-safe/identity_u32_2x2x3.onnx");
+    let src = format!(
+        "val model = ai.load_model({:?})\nval input: Tensor<u32, [2, 2, 3]> = tensor.new(12)\n{body}",
+        model.to_string_lossy()
+    );
+    aura_parse::parse_source(&src).expect("parse")
+}
+
+#[test]
+fn infer_results_take_the_model_output_shape() {
+    let mut checker = Checker::new();
+    checker.check_program(&program("val out = model.infer(input)\n")).expect("sema");
+    assert_eq!(
+        checker.top_level_val_type("out"),
+        Some(Type::Tensor {
+            elem: Box::new(Type::Unknown),
+            shape: Some(vec![2, 2, 3]),
+        })
+    );
+
+    let err = Checker::new()
+        .check_program(&program("val out: Tensor<u32, [12]> = model.infer(input)\n"))
+        .expect_err("wrong shape");
+    assert!(err.message.contains("type mismatch"), "{}", err.message);
+}
+
+#[test]
+fn literal_indices_are_checked_against_the_inferred_shape() {
+    let ok = program("val out = model.infer(input)\nval last = tensor.get(out, 11)\n");
+    Checker::new().check_program(&ok).expect("in bounds");
+
+    let past = program("val out = model.infer(input)\nval x = out.get(12)\n");
+    let err = Checker::new().check_program(&past).expect_err("out of bounds");
+    assert!(err.message.contains("tensor index 12 is out of bounds"), "{}", err.message);
+}
@@ -0,0 +1,31 @@
+use aura_core::Checker;
+use aura_ir::InstKind;
+
+// Shapes the module resolver produces: module cells carry qualified names.
+const HTTP: &str = "cell http::helper(x: u32):\n    yield x + 1\n\npub cell http::get(x: u32):\n    yield helper(x)\n\n";
+
+#[test]
+fn module_cells_resolve_siblings_unqualified() {
+    let src = format!("import http\n\n{HTTP}cell main():\n    val r: u32 = http::get(1)\n");
+    let program = aura_parse::parse_source(&src).expect("parse");
+    let module = aura_core::lower_program(&program).expect("lower");
+
+    let callees: Vec<&str> = module.functions["http.get"]
+        .blocks
+        .iter()
+        .flat_map(|b| b.insts.iter())
+        .filter_map(|i| match &i.kind {
+            InstKind::Call { callee, .. } => Some(callee.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(callees, vec!["http.helper"]);
+}
+
+#[test]
+fn private_module_cells_are_not_callable_from_outside() {
+    let src = format!("import http\n\n{HTTP}cell main():\n    val r: u32 = http::helper(1)\n");
+    let program = aura_parse::parse_source(&src).expect("parse");
+    let err = Checker::new().check_program(&program).expect_err("helper is private");
+    assert!(err.message.contains("cell 'helper' is private to module 'http'"), "{}", err.message);
+}
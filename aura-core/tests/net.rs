@@ -0,0 +1,31 @@
+use aura_core::Checker;
+
+#[test]
+fn sockets_connect_send_receive_and_close_through_net_calls() {
+    let src = "cell fetch(host: String, request: String) -> String:\n    val mut s = net.connect(host, 80)\n    net.send(s, request)\n    val reply = net.recv(s)\n    net.close(s)\n    return reply\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+}
+
+#[test]
+fn sockets_are_linear() {
+    let cases = [
+        (
+            "cell main():\n    val mut s = net.connect(\"localhost\", 80)\n    net.close(s)\n    net.send(s, \"x\")\n",
+            "used after move",
+        ),
+        (
+            "cell main():\n    val s = net.connect(\"localhost\", 80)\n    net.close(s)\n    net.close(s)\n",
+            "used after move",
+        ),
+        (
+            "cell main():\n    val s = net.connect(\"localhost\", 80)\n    net.send(s, \"x\")\n",
+            "cannot pass immutable val 's' as mutable socket receiver",
+        ),
+    ];
+    for (src, want) in cases {
+        let program = aura_parse::parse_source(src).expect("parse");
+        let err = Checker::new().check_program(&program).expect_err(src);
+        assert!(err.message.contains(want), "{src}: {}", err.message);
+    }
+}
@@ -0,0 +1,90 @@
+use aura_core::Checker;
+
+mod common;
+use common::check;
+
+const OPTION: &str = "type Option<T> = enum { Some(x: T), None }\n\n";
+
+#[test]
+fn possibly_none_uses_are_rejected_with_a_fix_it() {
+    let msg = check(&format!("{OPTION}cell f(o: Option<u32>) -> u32:\n    return o + 1\n")).unwrap_err();
+    assert!(msg.contains("'o' may be None here; check it first with `if is_some(o):`"), "{msg}");
+    let msg = check(&format!(
+        "{OPTION}cell f(o: Option<u32>) -> u32:\n    val y: u32 = o\n    return y\n"
+    ))
+    .unwrap_err();
+    assert!(msg.contains("'o' may be None here"), "{msg}");
+    let msg = check(&format!(
+        "{OPTION}cell f(n: u32) -> u32:\n    if is_some(n):\n        return n\n    return 0\n"
+    ))
+    .unwrap_err();
+    assert!(msg.contains("is_some expects an Option, got u32"), "{msg}");
+}
+
+#[test]
+fn checks_and_some_arms_narrow_their_branch() {
+    check(&format!("{OPTION}cell f(o: Option<u32>) -> u32:\n    if is_some(o):\n        return o + 1\n    return 0\n"))
+        .expect("narrowed by is_some");
+    check(&format!(
+        "{OPTION}cell f(o: Option<u32>) -> String:\n    if is_none(o):\n        return \"none\"\n    else:\n        return \"{{o}}\"\n"
+    ))
+    .expect("narrowed in the else of is_none");
+    check(&format!(
+        "{OPTION}cell f(o: Option<u32>) -> u32:\n    match o:\n        Option::Some(x):\n            return o * x\n        _:\n            return 0\n"
+    ))
+    .expect("narrowed in the Some arm");
+
+    // Narrowing ends with the branch.
+    let msg = check(&format!(
+        "{OPTION}cell f(o: Option<u32>) -> u32:\n    if is_some(o):\n        val y = o + 1\n    return o\n"
+    ))
+    .unwrap_err();
+    assert!(msg.contains("may be None"), "{msg}");
+}
+
+#[test]
+fn an_early_return_on_none_narrows_the_rest_of_the_block() {
+    check(&format!("{OPTION}cell f(o: Option<u32>) -> u32:\n    if is_none(o):\n        return 0\n    return o + 1\n"))
+        .expect("guarded by an early return");
+    let msg = check(&format!(
+        "{OPTION}cell f(o: Option<u32>) -> u32:\n    if is_none(o):\n        val z = 0\n    return o + 1\n"
+    ))
+    .unwrap_err();
+    assert!(msg.contains("may be None"), "{msg}");
+}
+
+#[test]
+fn assignments_forget_the_narrowing() {
+    let msg = check(&format!(
+        "{OPTION}cell f(o: Option<u32>) -> u32:\n    val mut p = o\n    if is_some(p):\n        p = Option::None()\n        return p\n    return 0\n"
+    ))
+    .unwrap_err();
+    assert!(msg.contains("'p' may be None here"), "{msg}");
+
+    // A loop may assign before the use on an earlier iteration.
+    let msg = check(&format!(
+        "{OPTION}cell f(o: Option<u32>):\n    val mut p = o\n    if is_some(p):\n        while 1 < 2:\n            val y = p + 1\n            p = Option::None()\n"
+    ))
+    .unwrap_err();
+    assert!(msg.contains("'p' may be None here"), "{msg}");
+}
+
+#[test]
+fn narrowed_uses_lower_to_a_tag_check_and_a_payload_read() {
+    let src = format!("{OPTION}cell f(o: Option<u32>) -> u32:\n    if is_none(o):\n        return 0\n    return o + 1\n");
+    let program = aura_parse::parse_source(&src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+
+    let module = aura_core::lower_program(&program).expect("lower");
+    let f = module.functions.get("f").expect("f");
+    let kinds: Vec<&aura_ir::InstKind> = f.blocks.iter().flat_map(|b| b.insts.iter()).map(|i| &i.kind).collect();
+    assert!(kinds.iter().any(|k| matches!(k, aura_ir::InstKind::EnumTag { .. })), "{kinds:?}");
+    assert!(
+        kinds.iter().any(|k| matches!(k, aura_ir::InstKind::Binary { op: aura_ir::BinOp::Ne, .. })),
+        "{kinds:?}"
+    );
+    assert!(
+        kinds.iter().any(|k| matches!(k, aura_ir::InstKind::EnumField { tag: 0, index: 0, .. })),
+        "{kinds:?}"
+    );
+}
@@ -0,0 +1,54 @@
+use aura_core::{Checker, ExplanationEngine, MoveViolation, ViolationKind};
+
+fn violation(src: &str) -> MoveViolation {
+    let program = aura_parse::parse_source(src).expect("parse");
+    let mut checker = Checker::new();
+    let err = checker.check_program(&program).expect_err("expected sema error");
+    checker.move_violation(&err).cloned().unwrap_or_else(|| panic!("no move violation for: {}", err.message))
+}
+
+#[test]
+fn a_second_move_into_a_cell_explains_the_first_and_suggests_borrowing() {
+    let src = "cell consume(t: Tensor) -> u32:\n    return tensor.len(t)\n\ncell main():\n    val t = tensor.new(4)\n    val a = consume(t)\n    val b = consume(t)\n";
+    let v = violation(src);
+    assert_eq!(v.kind, ViolationKind::DoubleMove);
+    assert_eq!(v.name, "t");
+    assert_eq!(v.moved_into, Some(("consume".to_string(), "t".to_string())));
+    let moved_at = v.moved_at.expect("move site");
+    assert_eq!(&src[moved_at.offset()..moved_at.offset() + moved_at.len()], "t");
+    assert!(moved_at.offset() < v.used_at.offset());
+
+    let exp = ExplanationEngine::new().explain_ownership(&v, src);
+    assert_eq!(exp.message, "value 't' is consumed twice");
+    assert_eq!(exp.proof_steps.len(), 2);
+    assert_eq!(exp.proof_steps[0].location, (6, 21));
+    assert_eq!(exp.proof_steps[0].source_line, "val a = consume(t)");
+    assert_eq!(exp.proof_steps[0].derives, "'t' moves into 'consume' as 't'");
+    assert!(exp.proof_steps[0].reason.starts_with("Tensor is linear"), "{}", exp.proof_steps[0].reason);
+    assert_eq!(exp.proof_steps[1].location.0, 7);
+    assert_eq!(exp.suggestions.len(), 3);
+    assert!(exp.suggestions[0].contains("declare 't' in 'consume' as `&Tensor`"), "{}", exp.suggestions[0]);
+    assert!(exp.full_explanation().contains("## How to Fix"));
+}
+
+#[test]
+fn reading_a_moved_value_is_a_use_after_move() {
+    let src = "cell main():\n    val t = tensor.new(4)\n    val u = t\n    val n = tensor.len(t)\n";
+    let v = violation(src);
+    assert_eq!(v.kind, ViolationKind::UseAfterMove);
+    assert_eq!(v.moved_into, None);
+
+    let exp = ExplanationEngine::new().explain_ownership(&v, src);
+    assert_eq!(exp.message, "value 't' used after move");
+    assert_eq!(exp.proof_steps[0].source_line, "val u = t");
+    assert_eq!(exp.proof_steps[1].derives, "'t' is used, but it no longer holds a value");
+    assert!(exp.suggestions[1].contains("before line 3"), "{}", exp.suggestions[1]);
+}
+
+#[test]
+fn other_errors_carry_no_move_violation() {
+    let program = aura_parse::parse_source("cell main():\n    val x: u32 = true\n").expect("parse");
+    let mut checker = Checker::new();
+    let err = checker.check_program(&program).expect_err("expected sema error");
+    assert!(checker.move_violation(&err).is_none(), "{}", err.message);
+}
@@ -0,0 +1,62 @@
+use aura_core::Checker;
+use aura_ir::{run_oracle_entry, InstKind, OracleConfig, OracleValue, Type};
+
+fn lower(src: &str) -> aura_ir::ModuleIR {
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+    aura_core::lower_program(&program).expect("lower")
+}
+
+fn run_main(src: &str) -> OracleValue {
+    let module = lower(src);
+    let out = run_oracle_entry(&module, "main", &[], OracleConfig::default()).expect("oracle");
+    assert!(out.ok, "{out:?}");
+    out.return_value.expect("main yields a value")
+}
+
+#[test]
+fn record_literals_and_field_reads_lower_to_aggregates() {
+    let src = "type Point = record { x: u32, y: u32 }\n\ncell main() -> u32:\n    val p: Point = Point { y: 2, x: 40 }\n    yield p.x + p.y\n";
+    let module = lower(src);
+    let main = module.functions.get("main").expect("main");
+    let insts: Vec<&InstKind> = main.blocks.iter().flat_map(|b| b.insts.iter()).map(|i| &i.kind).collect();
+
+    let point = Type::Record {
+        name: "Point".to_string(),
+        fields: vec![Type::U32, Type::U32],
+    };
+    assert!(insts
+        .iter()
+        .any(|k| matches!(k, InstKind::MakeRecord { ty, fields } if *ty == point && fields.len() == 2)));
+    assert!(insts.iter().any(|k| matches!(k, InstKind::RecordGet { index: 0, .. })));
+    assert!(insts.iter().any(|k| matches!(k, InstKind::RecordGet { index: 1, .. })));
+    assert_eq!(run_main(src), OracleValue::U32(42));
+}
+
+#[test]
+fn records_pass_through_cells_with_defaults_and_nesting() {
+    let src = "type Inner = record { a: u32, b: u32 = 2 }\ntype Outer = record { inner: Inner, scale: u32 = 3 }\n\ncell make(a: u32) -> Outer:\n    yield Outer { inner: Inner { a: a } }\n\ncell weigh(o: Outer) -> u32:\n    yield o.inner.a * o.scale + o.inner.b\n\ncell main() -> u32:\n    yield weigh(make(5))\n";
+    let module = lower(src);
+    let make = module.functions.get("make").expect("make");
+    assert!(matches!(&make.ret, Type::Record { name, fields } if name == "Outer" && fields.len() == 2));
+    assert_eq!(run_main(src), OracleValue::U32(17));
+}
+
+#[test]
+fn generic_records_lower_per_instantiation() {
+    let src = "type Box<T> = record { v: T }\n\ncell main() -> u32:\n    val a: Box<u32> = Box { v: 7 }\n    val b: Box<bool> = Box { v: 2 < 1 }\n    if b.v:\n        yield 0\n    yield a.v\n";
+    let module = lower(src);
+    let main = module.functions.get("main").expect("main");
+    let tys: Vec<&Type> = main
+        .blocks
+        .iter()
+        .flat_map(|b| b.insts.iter())
+        .filter_map(|i| match &i.kind {
+            InstKind::MakeRecord { ty, .. } => Some(ty),
+            _ => None,
+        })
+        .collect();
+    assert!(matches!(tys[0], Type::Record { fields, .. } if fields == &vec![Type::U32]));
+    assert!(matches!(tys[1], Type::Record { fields, .. } if fields == &vec![Type::Bool]));
+    assert_eq!(run_main(src), OracleValue::U32(7));
+}
@@ -0,0 +1,52 @@
+use aura_core::Checker;
+use aura_ir::InstKind;
+
+#[test]
+fn region_blocks_enter_and_exit_around_the_body() {
+    let src = "cell total(n: u32) -> u32:\n    val mut sum = 0\n    region {\n        val mut t = tensor.new(n)\n        tensor.set(t, 0, 2)\n        sum = sum + tensor.get(t, 0)\n    }\n    return sum\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+
+    let module = aura_core::lower_program(&program).expect("lower");
+    let total = module.functions.get("total").expect("total");
+    let callees: Vec<&str> = total
+        .blocks
+        .iter()
+        .flat_map(|b| b.insts.iter())
+        .filter_map(|i| match &i.kind {
+            InstKind::Call { callee, .. } if callee.starts_with("region.") || callee == "tensor.new" => {
+                Some(callee.as_str())
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(callees, vec!["region.enter", "tensor.new", "region.exit"]);
+}
+
+#[test]
+fn region_allocations_cannot_escape_the_region() {
+    let cases = [
+        (
+            "cell main(n: u32):\n    val mut t = tensor.new(1)\n    region {\n        t = tensor.new(n)\n    }\n",
+            "cannot escape it through 't', which is declared outside the region",
+        ),
+        (
+            "cell leak(n: u32) -> Tensor:\n    region {\n        val t = tensor.new(n)\n        return t\n    }\n",
+            "cannot be returned from it",
+        ),
+        (
+            "cell main(n: u32):\n    val ch: Channel<Tensor> = chan.unbounded()\n    region {\n        chan.send(ch, tensor.new(n))\n    }\n",
+            "cannot be sent over a channel that outlives the region",
+        ),
+    ];
+    for (src, expected) in cases {
+        let program = aura_parse::parse_source(src).expect("parse");
+        let err = Checker::new().check_program(&program).expect_err(src);
+        assert!(err.message.contains(expected), "{src}: {}", err.message);
+    }
+
+    // Values from outside the region may still move between outer bindings.
+    let ok = "cell main(n: u32):\n    val a = tensor.new(n)\n    val mut b = tensor.new(n)\n    region {\n        b = a\n    }\n";
+    let program = aura_parse::parse_source(ok).expect("parse");
+    Checker::new().check_program(&program).expect("outer values stay assignable");
+}
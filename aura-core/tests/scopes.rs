@@ -0,0 +1,47 @@
+use aura_core::Checker;
+use aura_ir::InstKind;
+
+#[test]
+fn scope_blocks_open_a_token_and_close_it_after_the_body() {
+    let src = "cell work(tok: CancelToken, n: u32) -> u32:\n    if scope.cancelled(tok):\n        return 0\n    return n\n\ncell main():\n    scope tok:\n        tok ~> work(1)\n        scope.cancel(tok)\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+
+    let module = aura_core::lower_program(&program).expect("lower");
+    let main = module.functions.get("main").expect("main");
+    let callees: Vec<&str> = main
+        .blocks
+        .iter()
+        .flat_map(|b| b.insts.iter())
+        .filter_map(|i| match &i.kind {
+            InstKind::Call { callee, .. } if callee.starts_with("scope.") => Some(callee.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(callees, vec!["scope.open", "scope.cancel", "scope.close"]);
+}
+
+#[test]
+fn flows_and_tokens_cannot_escape_their_scope() {
+    let cases = [
+        (
+            "cell main():\n    val mut t = 0\n    scope:\n        t = 1 ~> work()\n",
+            "a task started in a scope cannot escape it through 't'",
+        ),
+        (
+            "cell main():\n    val mut t = 0\n    scope tok:\n        t = tok\n",
+            "cannot escape it through 't'",
+        ),
+        ("cell leak() -> CancelToken:\n    scope tok:\n        return tok\n", "cancellation token"),
+        (
+            "cell main():\n    val ch: Channel<u32> = chan.unbounded()\n    scope tok:\n        chan.send(ch, tok)\n",
+            "cancellation token",
+        ),
+    ];
+    for (body, expected) in cases {
+        let src = format!("cell work(n: u32) -> u32:\n    return n\n\n{body}");
+        let program = aura_parse::parse_source(&src).expect("parse");
+        let err = Checker::new().check_program(&program).expect_err(body);
+        assert!(err.message.contains(expected), "{body}: {}", err.message);
+    }
+}
@@ -0,0 +1,33 @@
+use aura_core::Checker;
+use aura_ir::InstKind;
+
+#[test]
+fn interpolated_string_lowers_to_concat_calls() {
+    let src = "import aura::io\n\ncell main():\n    val n: u32 = 3\n    val ok: bool = n > 1\n    io.println(\"count = {n + 1}, ok = {ok}\")\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+
+    let module = aura_core::lower_program(&program).expect("lower");
+    let f = module.functions.get("main").expect("main");
+
+    let callees: Vec<&str> = f
+        .blocks
+        .iter()
+        .flat_map(|b| b.insts.iter())
+        .filter_map(|i| match &i.kind {
+            InstKind::Call { callee, .. } => Some(callee.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(callees.iter().filter(|c| **c == "str.from").count(), 2);
+    assert_eq!(callees.iter().filter(|c| **c == "str.concat").count(), 3);
+    assert_eq!(callees.last(), Some(&"io.println"));
+}
+
+#[test]
+fn interpolated_values_must_be_displayable() {
+    let src = "cell main():\n    val s = Style { color: 1 }\n    val msg = \"style = {s}\"\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    let err = Checker::new().check_program(&program).expect_err("Style is not displayable");
+    assert!(err.message.contains("interpolated values must be integers, f64, bool, or String"));
+}
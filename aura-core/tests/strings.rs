@@ -0,0 +1,21 @@
+use aura_core::Checker;
+
+#[test]
+fn str_calls_measure_slice_split_and_pad_strings() {
+    let src = "cell column(line: String, width: u32) -> String:\n    val eq = str.find(line, \"=\")\n    val key = str.trim(str.slice(line, 0, eq))\n    val n: u32 = str.split_count(line, \"=\")\n    val value = str.split_nth(line, \"=\", n - 1)\n    val padded = str.concat(str.pad_right(key, width), value)\n    return str.pad_left(padded, str.len(line))\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+}
+
+#[test]
+fn str_calls_check_their_argument_types() {
+    let cases = [
+        "cell main():\n    val n = str.len(3)\n",
+        "cell main(s: String):\n    val t = str.slice(s, \"0\", 1)\n",
+        "cell main(s: String):\n    val t: u32 = str.trim(s)\n",
+    ];
+    for src in cases {
+        let program = aura_parse::parse_source(src).expect("parse");
+        Checker::new().check_program(&program).expect_err(src);
+    }
+}
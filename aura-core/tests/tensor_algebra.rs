@@ -0,0 +1,16 @@
+use aura_core::Checker;
+
+#[test]
+fn tensor_algebra_results_flow_into_shaped_tensors() {
+    let src = "cell main():\n    val a: Tensor<u32, [2, 3]> = tensor.new(6)\n    val b: Tensor<u32, [3, 4]> = tensor.new(12)\n    val c: Tensor<u32, [2, 4]> = tensor.matmul(a, b)\n    val d: Tensor<u32, [4, 2]> = tensor.reshape(c, [4, 2])\n    val e: Tensor<u32, [8, 2]> = tensor.concat(d, d, 0)\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+}
+
+#[test]
+fn tensor_reshape_takes_a_list_of_dims() {
+    let src = "cell main():\n    val a: Tensor<u32, [2, 3]> = tensor.new(6)\n    val b = tensor.reshape(a, 6)\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    let err = Checker::new().check_program(&program).expect_err("scalar shape");
+    assert!(err.message.contains("type mismatch"), "{}", err.message);
+}
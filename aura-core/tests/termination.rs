@@ -0,0 +1,48 @@
+use aura_core::check_termination;
+
+mod common;
+use common::check;
+
+#[test]
+fn a_parameter_shrinking_on_every_call_is_inferred_as_the_measure() {
+    let src = "cell fib(n: u32) -> u32:\n    if n < 2:\n        return n\n    return fib(n - 1) + fib(n - 2)\n\ncell ping(n: u32):\n    if n > 0:\n        pong(n - 1)\n\ncell pong(n: u32):\n    ping(n)\n";
+    check(src).expect("inferred measures");
+
+    let program = aura_parse::parse_source(src).expect("parse");
+    let measures = check_termination(&program).expect("measures");
+    assert!(measures["fib"].inferred);
+    assert_eq!(measures["ping"].group, measures["pong"].group);
+    assert_ne!(measures["fib"].group, measures["ping"].group);
+}
+
+#[test]
+fn cycles_without_a_measure_report_their_path() {
+    let src = "cell a(n: u32) -> u32:\n    return b(n)\n\ncell b(n: u32) -> u32:\n    return c(n + 1)\n\ncell c(n: u32) -> u32:\n    if n > 10:\n        return n\n    return a(n)\n";
+    let msg = check(src).unwrap_err();
+    assert!(
+        msg.contains("cell 'a' is recursive (a -> b -> c -> a) but no parameter shrinks around the cycle"),
+        "{msg}"
+    );
+
+    // Passing `n` on unchanged is fine only while every loop shrinks it somewhere.
+    let msg = check("cell a(n: u32):\n    if n > 0:\n        a(n - 1)\n        b(n)\n\ncell b(n: u32):\n    a(n)\n").unwrap_err();
+    assert!(msg.contains("(a -> a)"), "{msg}");
+
+    // A signed parameter can shrink forever.
+    let msg = check("cell down(n: i32) -> i32:\n    return down(n - 1)\n").unwrap_err();
+    assert!(msg.contains("(down -> down)"), "{msg}");
+}
+
+#[test]
+fn every_cell_in_a_cycle_declares_its_measure() {
+    let src = "cell ping(n: u32) decreases n:\n    if n > 0:\n        pong(n)\n\ncell pong(n: u32):\n    if n > 0:\n        ping(n - 1)\n";
+    let msg = check(src).unwrap_err();
+    assert!(msg.contains("cell 'pong' is recursive (pong -> ping -> pong)"), "{msg}");
+    assert!(msg.contains("while 'ping' does"), "{msg}");
+
+    let src = "cell ping(n: u32) decreases 2 * n + 1:\n    if n > 0:\n        pong(n)\n\ncell pong(n: u32) decreases 2 * n:\n    if n > 0:\n        ping(n - 1)\n";
+    check(src).expect("declared measures");
+
+    let msg = check("cell f(s: String) decreases s:\n    f(s)\n").unwrap_err();
+    assert!(msg.contains("decreases expects integer"), "{msg}");
+}
@@ -0,0 +1,34 @@
+use aura_core::{Checker, Type};
+
+fn checked(src: &str) -> Checker {
+    let program = aura_parse::parse_source(src).expect("parse");
+    let mut checker = Checker::new();
+    checker.check_program(&program).expect("sema");
+    checker
+}
+
+fn expr(src: &str) -> aura_ast::Expr {
+    let program = aura_parse::parse_source(src).expect("parse");
+    let Some(aura_ast::Stmt::ExprStmt(e)) = program.stmts.into_iter().next() else { panic!("expected expression") };
+    e
+}
+
+#[test]
+fn top_level_vals_keep_their_refined_types() {
+    let checker = checked("type Small = u32[0..10]\n\nval a: Small = 3\nval b = 4\n\ncell f(x: u32) -> u32:\n    val inner = x\n    yield inner\n");
+    assert_eq!(checker.top_level_val_type("a").map(|t| t.display()).as_deref(), Some("u32[0..10]"));
+    // Literals are refined to their value.
+    assert_eq!(checker.top_level_val_type("b").map(|t| t.display()).as_deref(), Some("u32[4..4]"));
+    // Cell locals are gone once their body is checked.
+    assert_eq!(checker.top_level_val_type("inner"), None);
+}
+
+#[test]
+fn expressions_infer_against_the_checked_definitions() {
+    let mut checker = checked("val a = 4\n\ncell twice(x: u32) -> u32:\n    yield x * 2\n");
+    assert_eq!(checker.infer_top_level_expr(&expr("twice(a) + 1\n")).ok().map(|t| t.display()).as_deref(), Some("u32[1..4294967295]"));
+    assert_eq!(checker.infer_top_level_expr(&expr("a > 2\n")).ok(), Some(Type::Bool));
+
+    let err = checker.infer_top_level_expr(&expr("missing + 1\n")).unwrap_err();
+    assert!(err.message.contains("missing"), "{}", err.message);
+}
@@ -0,0 +1,51 @@
+use aura_core::Checker;
+use aura_ir::{InstKind, Type};
+
+#[test]
+fn tuples_check_destructure_and_lower_to_tuple_insts() {
+    let src = "cell swap(a: u32, b: f64):\n    yield (b, a)\n\ncell main():\n    val (x, y) = swap(1, 2.5)\n    val p: (u32, bool) = (y + 1, x > 1.0)\n    val first = p.0\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+
+    let module = aura_core::lower_program(&program).expect("lower");
+    let swap = module.functions.get("swap").expect("swap");
+    assert_eq!(swap.ret, Type::Tuple(vec![Type::F64, Type::U32]));
+
+    let main = module.functions.get("main").expect("main");
+    let insts: Vec<&InstKind> = main.blocks.iter().flat_map(|b| b.insts.iter()).map(|i| &i.kind).collect();
+    assert!(insts.iter().any(|k| matches!(k, InstKind::MakeTuple { elems } if elems.len() == 2)));
+    let gets: Vec<u32> = insts
+        .iter()
+        .filter_map(|k| match k {
+            InstKind::TupleGet { index, .. } => Some(*index),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(gets, vec![0, 1, 0]);
+}
+
+#[test]
+fn tuple_arity_and_index_errors_are_reported() {
+    let src = "cell main():\n    val (a, b, c) = (1, 2)\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    let err = Checker::new().check_program(&program).expect_err("arity mismatch");
+    assert!(err.message.contains("tuple pattern has 3 elements"), "{}", err.message);
+
+    let src = "cell main():\n    val t = (1, \"two\")\n    val bad = t.2\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    let err = Checker::new().check_program(&program).expect_err("index out of bounds");
+    assert!(err.message.contains("tuple index 2 out of bounds for (u32, String)"), "{}", err.message);
+}
+
+#[test]
+fn match_on_tuple_patterns_binds_elements() {
+    let src = "cell main():\n    val mut out: u32 = 0\n    val t = (3, \"go\")\n    match t:\n        (0, _):\n            out = 1\n        (n, \"go\"):\n            out = n\n        _:\n            out = 2\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    Checker::new().check_program(&program).expect("sema");
+    aura_core::lower_program(&program).expect("lower");
+
+    let src = "cell main():\n    val t = (3, 4)\n    match t:\n        (0, \"x\"):\n            val a = 1\n        _:\n            val b = 2\n";
+    let program = aura_parse::parse_source(src).expect("parse");
+    let err = Checker::new().check_program(&program).expect_err("string vs u32 element");
+    assert!(err.message.contains("string pattern is not compatible with element type u32"), "{}", err.message);
+}
@@ -0,0 +1,443 @@
+//! Statement and branch coverage of AVM runs.
+//!
+//! With `AvmConfig::coverage` set, each run registers every statement, branch point and cell
+//! of the program it executes (nested blocks and cell bodies included, so code that never ran
+//! shows up with zero hits) and counts what executes: statements, the arms taken at `if`,
+//! `while` and `match`, and cell calls. `requires`/`ensures`/`assert`/`assume` statements are
+//! marked as contracts. Runs over the same source accumulate; `Coverage::merge` combines
+//! separate AVMs (e.g. one per test cell). The result exports as lcov or as a JSON report.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::ops::Range;
+
+use aura_ast::{Block, Program, Span, Stmt};
+use serde::Serialize;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct StmtHits {
+    contract: bool,
+    hits: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct CellCalls {
+    offset: usize,
+    calls: u64,
+}
+
+/// Coverage collected from AVM runs over one source text. Offsets are byte offsets into it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Coverage {
+    source: String,
+    /// Keyed by the statement's start offset.
+    statements: BTreeMap<usize, StmtHits>,
+    /// Hits per arm, keyed by the branching statement's start offset. `if` and `while` have
+    /// two arms: taken (then / another iteration) and not taken (else / exit).
+    branches: BTreeMap<usize, Vec<u64>>,
+    cells: BTreeMap<String, CellCalls>,
+}
+
+impl Coverage {
+    /// Registers the statements, branches and cells of `program`, parsed from `source`.
+    /// Coverage of a different source is discarded first.
+    pub(crate) fn register(&mut self, source: &str, program: &Program) {
+        if self.source != source {
+            *self = Coverage {
+                source: source.to_string(),
+                ..Coverage::default()
+            };
+        }
+        self.register_stmts(&program.stmts);
+    }
+
+    fn register_stmts(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            self.register_stmt(stmt);
+        }
+    }
+
+    fn register_block(&mut self, b: &Block) {
+        self.register_stmts(&b.stmts);
+    }
+
+    fn register_stmt(&mut self, stmt: &Stmt) {
+        let span = stmt.span();
+        let contract = matches!(
+            stmt,
+            Stmt::Requires(_) | Stmt::Ensures(_) | Stmt::Assert(_) | Stmt::Assume(_)
+        );
+        let arms = match stmt {
+            // Declarations are not executed.
+            Stmt::Import(_)
+            | Stmt::MacroDef(_)
+            | Stmt::MacroCall(_)
+            | Stmt::TypeAlias(_)
+            | Stmt::TraitDef(_)
+            | Stmt::RecordDef(_)
+            | Stmt::EnumDef(_)
+            | Stmt::ExternCell(_) => return,
+            Stmt::CellDef(c) => {
+                self.cells.entry(c.name.node.clone()).or_insert(CellCalls {
+                    offset: c.span.offset(),
+                    calls: 0,
+                });
+                self.register_block(&c.body);
+                return;
+            }
+            Stmt::If(i) => {
+                self.register_block(&i.then_block);
+                if let Some(b) = &i.else_block {
+                    self.register_block(b);
+                }
+                2
+            }
+            Stmt::While(w) => {
+                self.register_block(&w.body);
+                2
+            }
+            Stmt::Match(m) => {
+                for arm in &m.arms {
+                    self.register_block(&arm.body);
+                }
+                m.arms.len()
+            }
+            Stmt::For(f) => {
+                self.register_block(&f.body);
+                0
+            }
+            Stmt::UnsafeBlock(u) => {
+                self.register_block(&u.body);
+                0
+            }
+            Stmt::Scope(sc) => {
+                self.register_block(&sc.body);
+                0
+            }
+            Stmt::Region(r) => {
+                self.register_block(&r.body);
+                0
+            }
+            Stmt::Defer(d) => {
+                self.register_block(&d.body);
+                0
+            }
+            _ => 0,
+        };
+        self.statements.entry(span.offset()).or_insert(StmtHits { contract, hits: 0 });
+        if arms > 0 {
+            self.branches.entry(span.offset()).or_insert_with(|| vec![0; arms]);
+        }
+    }
+
+    /// Counts an execution of the statement at `span`. Unregistered statements are ignored.
+    pub(crate) fn hit_stmt(&mut self, span: Span) {
+        if let Some(s) = self.statements.get_mut(&span.offset()) {
+            s.hits += 1;
+        }
+    }
+
+    /// Counts arm `arm` taken at the branching statement at `span`.
+    pub(crate) fn hit_branch(&mut self, span: Span, arm: usize) {
+        if let Some(arms) = self.branches.get_mut(&span.offset())
+            && let Some(n) = arms.get_mut(arm)
+        {
+            *n += 1;
+        }
+    }
+
+    pub(crate) fn hit_cell(&mut self, name: &str) {
+        if let Some(c) = self.cells.get_mut(name) {
+            c.calls += 1;
+        }
+    }
+
+    /// The same registrations with every count at zero, for a worker AVM to fill in.
+    pub(crate) fn zeroed(&self) -> Coverage {
+        let mut out = self.clone();
+        out.statements.values_mut().for_each(|s| s.hits = 0);
+        out.branches.values_mut().for_each(|arms| arms.fill(0));
+        out.cells.values_mut().for_each(|c| c.calls = 0);
+        out
+    }
+
+    /// Adds the counts of `other`, which must cover the same source (otherwise it is ignored).
+    pub fn merge(&mut self, other: &Coverage) {
+        if self.source.is_empty() && self.statements.is_empty() {
+            *self = other.clone();
+            return;
+        }
+        if self.source != other.source {
+            return;
+        }
+        for (off, s) in &other.statements {
+            self.statements.entry(*off).or_insert(StmtHits { hits: 0, ..s.clone() }).hits += s.hits;
+        }
+        for (off, arms) in &other.branches {
+            let mine = self.branches.entry(*off).or_insert_with(|| vec![0; arms.len()]);
+            for (m, n) in mine.iter_mut().zip(arms) {
+                *m += n;
+            }
+        }
+        for (name, c) in &other.cells {
+            self.cells.entry(name.clone()).or_insert(CellCalls { calls: 0, ..c.clone() }).calls += c.calls;
+        }
+    }
+
+    /// Only the coverage of `range` of the source, rebased so offsets and lines count from its
+    /// start. Hosts that run a document with code around it (a prelude, appended std modules)
+    /// use this to report on the document alone.
+    pub fn restrict(&self, range: Range<usize>) -> Coverage {
+        let range = range.start.min(self.source.len())..range.end.min(self.source.len());
+        let inside = |off: &usize| range.contains(off);
+        Coverage {
+            source: self.source.get(range.clone()).unwrap_or_default().to_string(),
+            statements: self
+                .statements
+                .iter()
+                .filter(|(off, _)| inside(off))
+                .map(|(off, s)| (off - range.start, s.clone()))
+                .collect(),
+            branches: self
+                .branches
+                .iter()
+                .filter(|(off, _)| inside(off))
+                .map(|(off, arms)| (off - range.start, arms.clone()))
+                .collect(),
+            cells: self
+                .cells
+                .iter()
+                .filter(|(_, c)| inside(&c.offset))
+                .map(|(name, c)| {
+                    (
+                        name.clone(),
+                        CellCalls {
+                            offset: c.offset - range.start,
+                            calls: c.calls,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    /// A line/column report of every registered statement, branch point and cell.
+    pub fn report(&self) -> CoverageReport {
+        let lines = LineIndex::new(&self.source);
+        let statements: Vec<StatementCoverage> = self
+            .statements
+            .iter()
+            .map(|(off, s)| {
+                let (line, column) = lines.line_col(*off);
+                StatementCoverage {
+                    line,
+                    column,
+                    hits: s.hits,
+                    contract: s.contract,
+                }
+            })
+            .collect();
+        let branches: Vec<BranchCoverage> = self
+            .branches
+            .iter()
+            .map(|(off, arms)| {
+                let (line, column) = lines.line_col(*off);
+                BranchCoverage {
+                    line,
+                    column,
+                    arms: arms.clone(),
+                }
+            })
+            .collect();
+        let mut cells: Vec<CellCoverage> = self
+            .cells
+            .iter()
+            .map(|(name, c)| CellCoverage {
+                name: name.clone(),
+                line: lines.line_col(c.offset).0,
+                calls: c.calls,
+            })
+            .collect();
+        cells.sort_by_key(|c| c.line);
+
+        let contracts = statements.iter().filter(|s| s.contract);
+        let summary = CoverageSummary {
+            statements: Ratio::of(statements.iter().map(|s| s.hits)),
+            branches: Ratio::of(branches.iter().flat_map(|b| b.arms.iter().copied())),
+            contracts: Ratio::of(contracts.map(|s| s.hits)),
+            cells: Ratio::of(cells.iter().map(|c| c.calls)),
+        };
+        CoverageReport {
+            summary,
+            statements,
+            branches,
+            cells,
+        }
+    }
+
+    /// The coverage as an lcov tracefile with one record for `file`.
+    pub fn to_lcov(&self, file: &str) -> String {
+        let report = self.report();
+        let mut out = String::new();
+        let _ = writeln!(out, "TN:");
+        let _ = writeln!(out, "SF:{file}");
+        for c in &report.cells {
+            let _ = writeln!(out, "FN:{},{}", c.line, c.name);
+        }
+        for c in &report.cells {
+            let _ = writeln!(out, "FNDA:{},{}", c.calls, c.name);
+        }
+        let _ = writeln!(out, "FNF:{}", report.summary.cells.total);
+        let _ = writeln!(out, "FNH:{}", report.summary.cells.covered);
+
+        for (block, b) in report.branches.iter().enumerate() {
+            // `-` marks arms of a branch point that was never reached.
+            let reached = b.arms.iter().any(|n| *n > 0);
+            for (arm, n) in b.arms.iter().enumerate() {
+                let taken = if reached { n.to_string() } else { "-".to_string() };
+                let _ = writeln!(out, "BRDA:{},{block},{arm},{taken}", b.line);
+            }
+        }
+        let _ = writeln!(out, "BRF:{}", report.summary.branches.total);
+        let _ = writeln!(out, "BRH:{}", report.summary.branches.covered);
+
+        // lcov counts lines: a line's count is the most any statement starting on it ran.
+        let mut lines: BTreeMap<u32, u64> = BTreeMap::new();
+        for s in &report.statements {
+            let n = lines.entry(s.line).or_insert(0);
+            *n = (*n).max(s.hits);
+        }
+        for (line, n) in &lines {
+            let _ = writeln!(out, "DA:{line},{n}");
+        }
+        let _ = writeln!(out, "LF:{}", lines.len());
+        let _ = writeln!(out, "LH:{}", lines.values().filter(|n| **n > 0).count());
+        let _ = writeln!(out, "end_of_record");
+        out
+    }
+}
+
+/// Covered out of total, for a coverage summary.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Ratio {
+    pub covered: usize,
+    pub total: usize,
+}
+
+impl Ratio {
+    fn of(counts: impl Iterator<Item = u64>) -> Ratio {
+        counts.fold(Ratio::default(), |r, n| Ratio {
+            covered: r.covered + usize::from(n > 0),
+            total: r.total + 1,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct CoverageSummary {
+    pub statements: Ratio,
+    /// Arms of `if`, `while` and `match`.
+    pub branches: Ratio,
+    /// `requires`, `ensures`, `assert` and `assume` statements.
+    pub contracts: Ratio,
+    pub cells: Ratio,
+}
+
+/// Lines and columns count from 1.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct StatementCoverage {
+    pub line: u32,
+    pub column: u32,
+    pub hits: u64,
+    pub contract: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct BranchCoverage {
+    pub line: u32,
+    pub column: u32,
+    /// Hits per arm, in source order; for `if` and `while`, taken then not taken.
+    pub arms: Vec<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct CellCoverage {
+    pub name: String,
+    pub line: u32,
+    pub calls: u64,
+}
+
+/// What `Coverage::report` returns; serializes to the JSON export.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct CoverageReport {
+    pub summary: CoverageSummary,
+    pub statements: Vec<StatementCoverage>,
+    pub branches: Vec<BranchCoverage>,
+    pub cells: Vec<CellCoverage>,
+}
+
+struct LineIndex {
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    fn new(text: &str) -> Self {
+        let mut starts = vec![0];
+        starts.extend(text.bytes().enumerate().filter(|(_, b)| *b == b'\n').map(|(i, _)| i + 1));
+        Self { starts }
+    }
+
+    fn line_col(&self, off: usize) -> (u32, u32) {
+        let line = self.starts.partition_point(|s| *s <= off).saturating_sub(1);
+        ((line + 1) as u32, (off - self.starts[line] + 1) as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SRC: &str = "cell f(x: u32) -> u32:\n    if x > 1:\n        return 1\n    return 0\n\nval a = f(2)\n";
+
+    fn registered() -> Coverage {
+        let program = aura_parse::parse_source(SRC).expect("parse");
+        let mut cov = Coverage::default();
+        cov.register(SRC, &program);
+        cov
+    }
+
+    #[test]
+    fn registration_covers_nested_statements_and_cells() {
+        let report = registered().report();
+        let lines: Vec<u32> = report.statements.iter().map(|s| s.line).collect();
+        assert_eq!(lines, [2, 3, 4, 6]);
+        assert_eq!(report.branches.len(), 1);
+        assert_eq!(report.branches[0].arms, [0, 0]);
+        assert_eq!(report.cells[0].name, "f");
+        assert_eq!(report.summary.statements, Ratio { covered: 0, total: 4 });
+    }
+
+    #[test]
+    fn restrict_rebases_lines() {
+        let mut cov = registered();
+        cov.hit_stmt(Span::from((SRC.find("val a").unwrap(), 1)));
+        let tail = SRC.find("\nval a").unwrap() + 1;
+        let report = cov.restrict(tail..SRC.len()).report();
+        assert_eq!(report.statements.len(), 1);
+        assert_eq!((report.statements[0].line, report.statements[0].hits), (1, 1));
+        assert!(report.cells.is_empty());
+    }
+
+    #[test]
+    fn merge_adds_counts() {
+        let mut a = registered();
+        a.hit_cell("f");
+        let mut b = a.zeroed();
+        b.hit_cell("f");
+        b.hit_branch(Span::from((SRC.find("if").unwrap(), 1)), 1);
+        a.merge(&b);
+        let report = a.report();
+        assert_eq!(report.cells[0].calls, 2);
+        assert_eq!(report.branches[0].arms, [0, 1]);
+    }
+}
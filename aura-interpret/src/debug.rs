@@ -1,7 +1,8 @@
 #![forbid(unsafe_code)]
 
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
 
 use aura_ast::{Expr, Span};
 use serde::{Deserialize, Serialize};
@@ -9,8 +10,16 @@ use serde::{Deserialize, Serialize};
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DebugBreakpoint {
     pub line: u32,
+    /// Only reached when this expression, evaluated in the current AVM scope, is true.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub condition: Option<String>,
+    /// Filters on how often the breakpoint has been reached: `3` (the third hit), `>= 3`,
+    /// `> 3`, `<= 3`, `< 3`, or `% 3` (every third hit).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hit_condition: Option<String>,
+    /// Remove the breakpoint the first time it stops.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub one_shot: bool,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -26,14 +35,102 @@ pub enum DebugCommand {
         start_paused: bool,
         #[serde(default)]
         perf: bool,
+        /// With `perf`, profile only every Nth frame of live UI loops.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        perf_frame_interval: Option<u32>,
     },
     Pause,
     Continue,
+    /// Same as `StepIn`.
     Step,
+    /// Stop before the next statement, inside the cell, callback or trailing block the
+    /// current statement enters if it enters one.
+    StepIn,
+    /// Stop before the next statement of the current frame (or of a caller, once it returns),
+    /// running through the cells, callbacks and trailing blocks it enters.
+    StepOver,
+    /// Run until the current frame returns, then stop before the next statement.
+    StepOut,
     /// Request termination of any running program (native child or Dev-VM).
     Terminate,
     SetBreakpoints { breakpoints: Vec<DebugBreakpoint> },
     SetWatches { watches: Vec<DebugWatch> },
+    /// Evaluate an expression in the paused frame; answered with `DebugEvent::Evaluated`.
+    Evaluate { expr: String },
+    /// List children `start..start + count` of a variable reported while paused; answered with
+    /// `DebugEvent::Expanded`. Without `count`, one page of `DebugVariable::PAGE` children.
+    Expand {
+        reference: u64,
+        #[serde(default)]
+        start: usize,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        count: Option<usize>,
+    },
+}
+
+/// A runtime value as reported to debugger clients.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum DebugValue {
+    Int { value: i64 },
+    Float { value: f64 },
+    Bool { value: bool },
+    Str { value: String },
+    /// Records and style literals.
+    Record { fields: BTreeMap<String, DebugValue> },
+    Ui { kind: String },
+    Tuple { elems: Vec<DebugValue> },
+    List { elems: Vec<DebugValue> },
+    Map { entries: Vec<(DebugValue, DebugValue)> },
+    Unit,
+    /// A `~>` task that has not been awaited yet.
+    Task { id: u64 },
+}
+
+/// One node of an expandable variable tree: a binding of the paused frame, or a field,
+/// element or entry of one.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DebugVariable {
+    /// Binding or field name, tuple position (`0`), list index (`[0]`) or map key.
+    pub name: String,
+    /// One-line rendering; aggregates are summarized, e.g. `[1000 items]`.
+    pub value: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+    /// Pass to `DebugCommand::Expand` to list the children; 0 when there are none. Valid
+    /// until the program resumes.
+    pub reference: u64,
+    /// How many children `reference` has.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub children: Option<usize>,
+}
+
+impl DebugVariable {
+    /// Children listed per `expand` request that gives no `count`.
+    pub const PAGE: usize = 100;
+}
+
+/// What a paused run answers debugger requests with.
+pub trait DebugFrame {
+    /// Evaluates an expression in the paused frame.
+    fn evaluate(&mut self, expr: &Expr) -> Result<DebugValue, String>;
+
+    /// Children `start..start + count` of the variable `reference`, and how many it has.
+    fn expand(&mut self, _reference: u64, _start: usize, _count: usize) -> Result<(usize, Vec<DebugVariable>), String> {
+        Err("this frame has no variables to expand".to_string())
+    }
+}
+
+/// A frame that can only evaluate, through a closure.
+struct EvalOnly<F>(F);
+
+impl<F> DebugFrame for EvalOnly<F>
+where
+    F: FnMut(&Expr) -> Result<DebugValue, String>,
+{
+    fn evaluate(&mut self, expr: &Expr) -> Result<DebugValue, String> {
+        (self.0)(expr)
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -58,6 +155,22 @@ pub struct PerfReport {
     pub timeline: Vec<PerfTimelineEvent>,
     pub flame_folded: String,
     pub memory: HashMap<String, u64>,
+    /// Call counts and times of every cell called, by name.
+    #[serde(default)]
+    pub cells: Vec<PerfCell>,
+    /// Self time per stack of cell calls, in collapsed-stack format (`program;main;draw 1200`).
+    #[serde(default)]
+    pub cell_stacks: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PerfCell {
+    pub name: String,
+    pub calls: u64,
+    /// Time in the cell's own statements, not counting the cells it called.
+    pub self_ns: u64,
+    /// Time from entering the cell to leaving it; recursive calls count once.
+    pub total_ns: u64,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -74,8 +187,48 @@ pub enum DebugEvent {
         col: u32,
         env: HashMap<String, String>,
         watches: Vec<DebugWatchValue>,
+        /// Statements reached so far in this run; replaying with `pause_at_step` set to a
+        /// smaller count steps back.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        step: Option<u64>,
+        /// The `~>` task the run stopped in, if not the main flow.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        task: Option<u64>,
+        /// The bindings of `env`, sorted by name, as expandable variables.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        variables: Vec<DebugVariable>,
+    },
+    /// A `~>` task changed state, e.g. the scheduler switched to or away from it.
+    Task {
+        task: u64,
+        cell: String,
+        state: DebugTaskState,
     },
     PerfReport { report: PerfReport },
+    /// The program's file was hot-reloaded between UI frames. `units` lists the top-level
+    /// units the edit changed: cells and flows by name, top-level layouts and renders as
+    /// `layout #n` / `render #n`. On `error` the running version stays in place.
+    Reloaded {
+        units: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    Evaluated {
+        expr: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        value: Option<DebugValue>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    Expanded {
+        reference: u64,
+        start: usize,
+        /// Children `reference` has in all; page with further `expand` requests.
+        total: usize,
+        children: Vec<DebugVariable>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
     NativeLaunch { exe: String },
     NativeExit {
         #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -87,6 +240,16 @@ pub enum DebugEvent {
     },
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DebugTaskState {
+    Spawned,
+    /// The scheduler switched to the task (cooperative scheduling only).
+    Started,
+    Finished,
+    Failed,
+}
+
 pub type DebugEmitFn = Arc<dyn Fn(DebugEvent) + Send + Sync + 'static>;
 
 #[derive(Clone)]
@@ -168,12 +331,17 @@ impl DebugSession {
     pub fn apply_command(&self, cmd: DebugCommand) {
         let mut st = self.inner.state.lock().expect("debug state poisoned");
         match cmd {
-            DebugCommand::Enable { start_paused, perf } => {
+            DebugCommand::Enable {
+                start_paused,
+                perf,
+                perf_frame_interval,
+            } => {
                 st.enabled = true;
                 if start_paused {
                     st.paused = true;
                 }
                 st.perf_enabled = perf;
+                st.perf_frame_interval = perf_frame_interval;
             }
             DebugCommand::Pause => {
                 st.enabled = true;
@@ -182,15 +350,17 @@ impl DebugSession {
             DebugCommand::Continue => {
                 st.enabled = true;
                 st.paused = false;
-                st.step_mode = false;
-                st.step_budget = 0;
+                st.step = None;
                 self.inner.cv.notify_all();
             }
-            DebugCommand::Step => {
+            DebugCommand::Step | DebugCommand::StepIn | DebugCommand::StepOver | DebugCommand::StepOut => {
                 st.enabled = true;
                 st.paused = false;
-                st.step_mode = true;
-                st.step_budget = 1;
+                st.step = Some(match cmd {
+                    DebugCommand::StepOver => StepMode::Over(st.depth),
+                    DebugCommand::StepOut => StepMode::Out(st.depth),
+                    _ => StepMode::In,
+                });
                 self.inner.cv.notify_all();
             }
             DebugCommand::Terminate => {
@@ -212,6 +382,24 @@ impl DebugSession {
                     .map(|w| InternalWatch::from_wire(w))
                     .collect();
             }
+            DebugCommand::Evaluate { expr } => {
+                drop(st);
+                self.emit(DebugEvent::Evaluated {
+                    expr,
+                    value: None,
+                    error: Some("expressions can only be evaluated while paused".to_string()),
+                });
+            }
+            DebugCommand::Expand { reference, start, .. } => {
+                drop(st);
+                self.emit(DebugEvent::Expanded {
+                    reference,
+                    start,
+                    total: 0,
+                    children: Vec::new(),
+                    error: Some("variables can only be expanded while paused".to_string()),
+                });
+            }
         }
     }
 
@@ -243,67 +431,148 @@ impl DebugSession {
             return None;
         }
 
-        // Step mode: after we have executed one statement, we stop before the next.
-        if st.step_mode && st.step_budget == 0 && !st.paused {
+        // Stepping stops before the next statement at a depth the step mode allows.
+        if !st.paused
+            && let Some(step) = st.step
+            && step.stops_at(st.depth)
+        {
             st.paused = true;
+            st.step = None;
             return Some("step".to_string());
         }
 
         if st.paused {
+            st.step = None;
             return Some("pause".to_string());
         }
 
-        // Breakpoints.
-        for bp in &mut st.breakpoints {
+        // Breakpoints. A condition that fails to parse or evaluate stops so the error is seen.
+        let mut hit = None;
+        for (i, bp) in st.breakpoints.iter_mut().enumerate() {
             if bp.line != line {
                 continue;
             }
 
-            match &bp.condition {
-                None => {
-                    st.paused = true;
-                    return Some("breakpoint".to_string());
-                }
-                Some(cond) => match cond {
-                    ParsedExpr::Ok(e) => match eval_bool(e) {
-                        Ok(true) => {
-                            st.paused = true;
-                            return Some("breakpoint".to_string());
-                        }
-                        Ok(false) => continue,
-                        Err(err) => {
-                            bp.last_error = Some(err);
-                            st.paused = true;
-                            return Some("breakpoint".to_string());
-                        }
-                    },
-                    ParsedExpr::Err(_e) => {
-                        st.paused = true;
-                        return Some("breakpoint".to_string());
+            let reached = match &bp.condition {
+                None | Some(ParsedExpr::Err(_)) => true,
+                Some(ParsedExpr::Ok(e)) => match eval_bool(e) {
+                    Ok(b) => b,
+                    Err(err) => {
+                        bp.last_error = Some(err);
+                        true
                     }
                 },
+            };
+            if !reached {
+                continue;
+            }
+
+            bp.hits += 1;
+            let stops = match &bp.hit_filter {
+                None => true,
+                Some(Ok(filter)) => filter.matches(bp.hits),
+                Some(Err(err)) => {
+                    bp.last_error = Some(err.clone());
+                    true
+                }
+            };
+            if stops {
+                hit = Some(i);
+                break;
             }
         }
 
-        None
+        let i = hit?;
+        if st.breakpoints[i].one_shot {
+            st.breakpoints.remove(i);
+        }
+        st.paused = true;
+        st.step = None;
+        Some("breakpoint".to_string())
     }
 
-    pub fn note_stmt_executed(&self) {
+    /// The run entered a cell, callback or trailing block; step-over runs through it and
+    /// step-out runs until it returns.
+    pub fn enter_frame(&self) {
         let mut st = self.inner.state.lock().expect("debug state poisoned");
-        if st.step_mode && st.step_budget > 0 {
-            st.step_budget = st.step_budget.saturating_sub(1);
-        }
+        st.depth += 1;
     }
 
-    pub fn wait_while_paused(&self) {
+    pub fn exit_frame(&self) {
         let mut st = self.inner.state.lock().expect("debug state poisoned");
-        while st.paused && !st.terminate_requested {
-            st = self.inner.cv.wait(st).expect("debug state poisoned");
-        }
+        st.depth = st.depth.saturating_sub(1);
+    }
+
+    /// Blocks while paused, with no frame to evaluate `Evaluate` requests against.
+    pub fn wait_while_paused(&self) {
+        self.wait_while_paused_with(|_| Err("no AVM frame is paused".to_string()));
+    }
+
+    /// Blocks while paused, applying commands as they arrive and answering `Evaluate`
+    /// with `evaluate`, which runs against the paused frame.
+    pub fn wait_while_paused_with<F>(&self, evaluate: F)
+    where
+        F: FnMut(&Expr) -> Result<DebugValue, String>,
+    {
+        self.wait_while_paused_in(&mut EvalOnly(evaluate));
+    }
+
+    /// Blocks while paused, applying commands as they arrive and answering `Evaluate` and
+    /// `Expand` from `frame`.
+    pub fn wait_while_paused_in(&self, frame: &mut dyn DebugFrame) {
+        loop {
+            for c in self.take_pending_commands() {
+                match c {
+                    DebugCommand::Evaluate { expr } => {
+                        let result = aura_parse::parse_expr(&expr)
+                            .map_err(|e| e.to_string())
+                            .and_then(|e| frame.evaluate(&e));
+                        let (value, error) = match result {
+                            Ok(v) => (Some(v), None),
+                            Err(e) => (None, Some(e)),
+                        };
+                        self.emit(DebugEvent::Evaluated { expr, value, error });
+                    }
+                    DebugCommand::Expand { reference, start, count } => {
+                        let count = count.unwrap_or(DebugVariable::PAGE);
+                        let ev = match frame.expand(reference, start, count) {
+                            Ok((total, children)) => DebugEvent::Expanded {
+                                reference,
+                                start,
+                                total,
+                                children,
+                                error: None,
+                            },
+                            Err(e) => DebugEvent::Expanded {
+                                reference,
+                                start,
+                                total: 0,
+                                children: Vec::new(),
+                                error: Some(e),
+                            },
+                        };
+                        self.emit(ev);
+                    }
+                    other => self.apply_command(other),
+                }
+            }
 
-        // If termination was requested while paused, unpause so the VM can unwind.
-        if st.terminate_requested {
-            st.paused = false;
+            let mut st = self.inner.state.lock().expect("debug state poisoned");
+            if st.terminate_requested {
+                // Unpause so the VM can unwind.
+                st.paused = false;
+                return;
+            }
+            if !st.paused {
+                return;
+            }
+            // `send` does not take the state lock, so a wakeup can slip in between draining the
+            // queue and waiting; the timeout bounds how long such a command waits.
+            let _ = self
+                .inner
+                .cv
+                .wait_timeout(st, Duration::from_millis(50))
+                .expect("debug state poisoned");
         }
     }
 
@@ -317,6 +586,11 @@ impl DebugSession {
         st.perf_enabled
     }
 
+    pub fn perf_frame_interval(&self) -> Option<u32> {
+        let st = self.inner.state.lock().expect("debug state poisoned");
+        st.perf_frame_interval
+    }
+
     pub fn perf_push(&self, ev: PerfTimelineEvent) {
         let mut st = self.inner.state.lock().expect("debug state poisoned");
         if st.perf_enabled {
@@ -331,6 +605,12 @@ impl DebugSession {
         out
     }
 
+    /// How often each breakpoint has been reached (with its condition true), by line.
+    pub fn breakpoint_hits(&self) -> Vec<(u32, u64)> {
+        let st = self.inner.state.lock().expect("debug state poisoned");
+        st.breakpoints.iter().map(|b| (b.line, b.hits)).collect()
+    }
+
     pub fn breakpoint_errors(&self) -> Vec<(u32, String)> {
         let st = self.inner.state.lock().expect("debug state poisoned");
         st.breakpoints
@@ -387,6 +667,9 @@ impl InternalWatch {
 struct InternalBreakpoint {
     line: u32,
     condition: Option<ParsedExpr>,
+    hit_filter: Option<Result<HitFilter, String>>,
+    one_shot: bool,
+    hits: u64,
     last_error: Option<String>,
 }
 
@@ -399,25 +682,92 @@ impl InternalBreakpoint {
         Self {
             line: b.line,
             condition,
+            hit_filter: b.hit_condition.as_deref().map(HitFilter::parse),
+            one_shot: b.one_shot,
+            hits: 0,
             last_error: None,
         }
     }
 }
 
+/// Parsed `DebugBreakpoint::hit_condition`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum HitFilter {
+    Eq(u64),
+    Ge(u64),
+    Gt(u64),
+    Le(u64),
+    Lt(u64),
+    Every(u64),
+}
+
+impl HitFilter {
+    fn parse(src: &str) -> Result<Self, String> {
+        let src = src.trim();
+        let (op, count) = [">=", "<=", "==", ">", "<", "%", "="]
+            .into_iter()
+            .find_map(|op| src.strip_prefix(op).map(|rest| (op, rest)))
+            .unwrap_or(("==", src));
+        let n = count.trim().parse::<u64>().map_err(|_| {
+            format!("invalid hit condition '{src}': expected a count like '3', '>= 3' or '% 3'")
+        })?;
+        Ok(match op {
+            ">=" => HitFilter::Ge(n),
+            "<=" => HitFilter::Le(n),
+            ">" => HitFilter::Gt(n),
+            "<" => HitFilter::Lt(n),
+            "%" if n == 0 => return Err(format!("invalid hit condition '{src}': '% 0' never matches")),
+            "%" => HitFilter::Every(n),
+            _ => HitFilter::Eq(n),
+        })
+    }
+
+    fn matches(self, hits: u64) -> bool {
+        match self {
+            HitFilter::Eq(n) => hits == n,
+            HitFilter::Ge(n) => hits >= n,
+            HitFilter::Gt(n) => hits > n,
+            HitFilter::Le(n) => hits <= n,
+            HitFilter::Lt(n) => hits < n,
+            HitFilter::Every(n) => hits.is_multiple_of(n),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub enum ParsedExpr {
     Ok(Expr),
     Err(String),
 }
 
+/// Where a step in progress stops, with the frame depth it started at.
+#[derive(Clone, Copy, Debug)]
+enum StepMode {
+    In,
+    Over(usize),
+    Out(usize),
+}
+
+impl StepMode {
+    fn stops_at(self, depth: usize) -> bool {
+        match self {
+            StepMode::In => true,
+            StepMode::Over(from) => depth <= from,
+            StepMode::Out(from) => depth < from,
+        }
+    }
+}
+
 #[derive(Default)]
 struct DebugState {
     enabled: bool,
     paused: bool,
-    step_mode: bool,
-    step_budget: u32,
+    step: Option<StepMode>,
+    /// Frames entered and not yet returned from.
+    depth: usize,
     terminate_requested: bool,
     perf_enabled: bool,
+    perf_frame_interval: Option<u32>,
     file: String,
     line_starts: Vec<usize>,
     breakpoints: Vec<InternalBreakpoint>,
@@ -449,4 +799,124 @@ mod tests {
         assert!(s.contains("\"event\":\"hello\""));
         assert!(s.contains("\"protocol\""));
     }
+
+    fn session_with(breakpoints: Vec<DebugBreakpoint>) -> DebugSession {
+        let (sess, _handle) = DebugSession::new(Arc::new(|_| {}));
+        sess.set_source("<test>".to_string(), "val a = 1\nval b = 2\n");
+        sess.apply_command(DebugCommand::SetBreakpoints { breakpoints });
+        sess
+    }
+
+    /// Reaches line 2 once and reports whether it stopped, resuming afterwards.
+    fn reach_line_2(sess: &DebugSession, cond: bool) -> bool {
+        let stopped = sess.should_stop_before_stmt(Span::from((10, 9)), |_| Ok(cond)).is_some();
+        sess.apply_command(DebugCommand::Continue);
+        stopped
+    }
+
+    #[test]
+    fn breakpoint_wire_fields_default_when_absent() {
+        let cmd: DebugCommand = serde_json::from_str(
+            r#"{"cmd":"setBreakpoints","breakpoints":[{"line":2},{"line":3,"hit_condition":">= 2","one_shot":true}]}"#,
+        )
+        .expect("parse DebugCommand");
+        let DebugCommand::SetBreakpoints { breakpoints } = cmd else {
+            panic!("expected SetBreakpoints");
+        };
+        assert_eq!(breakpoints[0].hit_condition, None);
+        assert!(!breakpoints[0].one_shot);
+        assert_eq!(breakpoints[1].hit_condition.as_deref(), Some(">= 2"));
+        assert!(breakpoints[1].one_shot);
+
+        let s = serde_json::to_string(&breakpoints[0]).expect("serialize DebugBreakpoint");
+        assert_eq!(s, r#"{"line":2}"#);
+    }
+
+    #[test]
+    fn hit_conditions_count_only_hits_whose_condition_holds() {
+        let sess = session_with(vec![DebugBreakpoint {
+            line: 2,
+            condition: Some("b > 1".to_string()),
+            hit_condition: Some("% 2".to_string()),
+            one_shot: false,
+        }]);
+        assert!(!reach_line_2(&sess, true));
+        assert!(!reach_line_2(&sess, false));
+        assert!(reach_line_2(&sess, true));
+        assert!(!reach_line_2(&sess, true));
+        assert!(reach_line_2(&sess, true));
+        assert_eq!(sess.breakpoint_hits(), vec![(2, 4)]);
+    }
+
+    #[test]
+    fn one_shot_breakpoints_stop_once() {
+        let sess = session_with(vec![DebugBreakpoint {
+            line: 2,
+            condition: None,
+            hit_condition: Some(">= 2".to_string()),
+            one_shot: true,
+        }]);
+        assert!(!reach_line_2(&sess, true));
+        assert!(reach_line_2(&sess, true));
+        assert!(!reach_line_2(&sess, true));
+        assert!(sess.breakpoint_hits().is_empty());
+    }
+
+    #[test]
+    fn invalid_hit_conditions_stop_and_report_an_error() {
+        assert_eq!(HitFilter::parse("3"), Ok(HitFilter::Eq(3)));
+        assert_eq!(HitFilter::parse(" > 1 "), Ok(HitFilter::Gt(1)));
+        assert!(HitFilter::parse("% 0").is_err());
+
+        let sess = session_with(vec![DebugBreakpoint {
+            line: 2,
+            condition: None,
+            hit_condition: Some("often".to_string()),
+            one_shot: false,
+        }]);
+        assert!(reach_line_2(&sess, true));
+        let errors = sess.breakpoint_errors();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].1.contains("invalid hit condition 'often'"), "{errors:?}");
+    }
+
+    fn recording_session() -> (DebugSession, DebugHandle, Arc<Mutex<Vec<DebugEvent>>>) {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        let (sess, handle) = DebugSession::new(Arc::new(move |ev| sink.lock().unwrap().push(ev)));
+        (sess, handle, events)
+    }
+
+    #[test]
+    fn evaluate_requests_are_answered_while_paused() {
+        let (sess, handle, events) = recording_session();
+        sess.apply_command(DebugCommand::Pause);
+        handle.send(DebugCommand::Evaluate { expr: "n + 1".to_string() });
+        handle.send(DebugCommand::Evaluate { expr: "n +".to_string() });
+        handle.send(DebugCommand::Continue);
+
+        sess.wait_while_paused_with(|_| Ok(DebugValue::Int { value: 42 }));
+
+        let events = events.lock().unwrap();
+        match &events[..] {
+            [
+                DebugEvent::Evaluated { expr, value: Some(DebugValue::Int { value: 42 }), error: None },
+                DebugEvent::Evaluated { value: None, error: Some(_), .. },
+            ] => assert_eq!(expr, "n + 1"),
+            other => panic!("unexpected events: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn evaluate_outside_a_pause_reports_an_error() {
+        let (sess, _handle, events) = recording_session();
+        sess.apply_command(DebugCommand::Evaluate { expr: "1".to_string() });
+        let json = serde_json::to_string(&events.lock().unwrap()[0]).expect("serialize DebugEvent");
+        assert!(json.contains(r#""event":"evaluated""#), "{json}");
+        assert!(json.contains("only be evaluated while paused"), "{json}");
+
+        let v = DebugValue::List { elems: vec![DebugValue::Unit, DebugValue::Bool { value: true }] };
+        let json = serde_json::to_string(&v).expect("serialize DebugValue");
+        assert_eq!(json, r#"{"type":"list","elems":[{"type":"unit"},{"type":"bool","value":true}]}"#);
+    }
 }
@@ -0,0 +1,289 @@
+//! Calls `extern cell`s into native libraries at interpretation time (`ffi` feature).
+//!
+//! The libraries come from `AvmConfig::native_libs` (typically the shared libraries
+//! `aura_bridge::LinkInputs::runtime_libraries` collects) and are loaded on the first extern
+//! call. Each call is marshalled from the extern's declared signature with libffi. With
+//! `ort`, this is the only module of the crate that may use `unsafe`: the declaration is
+//! trusted to match the C symbol, exactly as it is when the program is compiled and linked.
+#![allow(unsafe_code)]
+
+use std::collections::HashMap;
+use std::ffi::{c_char, CStr, CString};
+use std::path::PathBuf;
+
+use aura_ast::{ExternCell, TypeRef};
+use libffi::middle::{Arg, Cif, CodePtr, Type};
+use libloading::Library;
+
+use crate::vm::AvmValue;
+
+type RawFn = unsafe extern "C" fn();
+
+/// C representation of an Aura type at the boundary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CType {
+    Void,
+    U8,
+    U16,
+    U32,
+    U64,
+    I32,
+    I64,
+    F64,
+    /// `bool`, passed as a C `bool` (one byte).
+    Bool,
+    /// `String`, passed as a NUL-terminated `const char*`.
+    Str,
+}
+
+impl CType {
+    fn of(ty: &TypeRef) -> Option<CType> {
+        if !ty.args.is_empty() {
+            return None;
+        }
+        // Range refinements (`u32[0..255]`) don't change the representation.
+        Some(match ty.name.node.as_str() {
+            "Unit" => CType::Void,
+            "u8" => CType::U8,
+            "u16" => CType::U16,
+            "u32" | "Int" => CType::U32,
+            "u64" => CType::U64,
+            "i32" => CType::I32,
+            "i64" => CType::I64,
+            "f64" => CType::F64,
+            "bool" => CType::Bool,
+            "String" => CType::Str,
+            _ => return None,
+        })
+    }
+
+    fn ffi_type(self) -> Type {
+        match self {
+            CType::Void => Type::void(),
+            CType::Bool | CType::U8 => Type::u8(),
+            CType::U16 => Type::u16(),
+            CType::U32 => Type::u32(),
+            CType::U64 => Type::u64(),
+            CType::I32 => Type::i32(),
+            CType::I64 => Type::i64(),
+            CType::F64 => Type::f64(),
+            CType::Str => Type::pointer(),
+        }
+    }
+}
+
+/// An argument converted to its C representation; its address is what libffi passes.
+enum Slot {
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    I32(i32),
+    I64(i64),
+    F64(f64),
+    Ptr(*const c_char),
+}
+
+impl Slot {
+    fn arg(&self) -> Arg {
+        match self {
+            Slot::U8(v) => Arg::new(v),
+            Slot::U16(v) => Arg::new(v),
+            Slot::U32(v) => Arg::new(v),
+            Slot::U64(v) => Arg::new(v),
+            Slot::I32(v) => Arg::new(v),
+            Slot::I64(v) => Arg::new(v),
+            Slot::F64(v) => Arg::new(v),
+            Slot::Ptr(v) => Arg::new(v),
+        }
+    }
+}
+
+/// The loaded native libraries and the symbols resolved from them so far.
+pub(crate) struct NativeLibs {
+    libs: Vec<(PathBuf, Library)>,
+    symbols: HashMap<String, RawFn>,
+}
+
+impl std::fmt::Debug for NativeLibs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NativeLibs")
+            .field("libs", &self.libs.iter().map(|(p, _)| p).collect::<Vec<_>>())
+            .field("symbols", &self.symbols.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl NativeLibs {
+    pub(crate) fn load(paths: &[PathBuf]) -> miette::Result<Self> {
+        let mut libs = Vec::with_capacity(paths.len());
+        for path in paths {
+            // SAFETY: loading runs the library's initialisers. The libraries are the ones the
+            // program links against when compiled, so they run there as well.
+            let lib = unsafe { Library::new(path) }
+                .map_err(|e| miette::miette!("AVM: failed to load native library {}: {e}", path.display()))?;
+            libs.push((path.clone(), lib));
+        }
+        Ok(Self {
+            libs,
+            symbols: HashMap::new(),
+        })
+    }
+
+    fn symbol(&mut self, name: &str) -> miette::Result<RawFn> {
+        if let Some(f) = self.symbols.get(name) {
+            return Ok(*f);
+        }
+        for (_, lib) in &self.libs {
+            // SAFETY: the symbol is only ever called through a libffi call interface built
+            // from its extern declaration, never as the placeholder type it is fetched as.
+            if let Ok(sym) = unsafe { lib.get::<RawFn>(name.as_bytes()) } {
+                let f = *sym;
+                self.symbols.insert(name.to_string(), f);
+                return Ok(f);
+            }
+        }
+        if self.libs.is_empty() {
+            return Err(miette::miette!(
+                "AVM: extern cell '{name}' has no native library to call into (no native libraries are configured)"
+            ));
+        }
+        let searched: Vec<String> = self.libs.iter().map(|(p, _)| p.display().to_string()).collect();
+        Err(miette::miette!(
+            "AVM: symbol '{name}' not found in native libraries: {}",
+            searched.join(", ")
+        ))
+    }
+
+    /// Calls the native symbol `ext` declares with already evaluated arguments.
+    pub(crate) fn call(&mut self, ext: &ExternCell, args: &[AvmValue]) -> miette::Result<AvmValue> {
+        let name = ext.name.node.as_str();
+        let unsupported = |ty: &TypeRef| {
+            miette::miette!(
+                "AVM: extern cell '{name}' uses type '{}', which cannot cross the FFI boundary in the AVM",
+                ty.name.node
+            )
+        };
+        let mut params = Vec::with_capacity(ext.params.len());
+        for p in &ext.params {
+            match CType::of(&p.ty) {
+                Some(CType::Void) | None => return Err(unsupported(&p.ty)),
+                Some(c) => params.push(c),
+            }
+        }
+        let ret = CType::of(&ext.ret).ok_or_else(|| unsupported(&ext.ret))?;
+
+        // Owned C strings must outlive the call.
+        let mut strings = Vec::new();
+        let mut slots = Vec::with_capacity(args.len());
+        for (c, v) in params.iter().zip(args) {
+            slots.push(to_slot(name, *c, v, &mut strings)?);
+        }
+        let f = self.symbol(name)?;
+
+        let cif = Cif::new(params.iter().map(|c| c.ffi_type()), ret.ffi_type());
+        let ffi_args: Vec<Arg> = slots.iter().map(Slot::arg).collect();
+        let code = CodePtr::from_fun(f);
+        // SAFETY: the call interface is built from the extern declaration, which the program
+        // asserts matches the C signature; every argument slot and C string stays alive until
+        // the call returns. Integer results narrower than a register are read back widened,
+        // as libffi writes them.
+        let value = unsafe {
+            match ret {
+                CType::Void => {
+                    cif.call::<u64>(code, &ffi_args);
+                    AvmValue::Unit
+                }
+                CType::Bool => AvmValue::Bool(cif.call::<u64>(code, &ffi_args) as u8 != 0),
+                CType::U8 => AvmValue::Int(i64::from(cif.call::<u64>(code, &ffi_args) as u8)),
+                CType::U16 => AvmValue::Int(i64::from(cif.call::<u64>(code, &ffi_args) as u16)),
+                CType::U32 => AvmValue::Int(i64::from(cif.call::<u64>(code, &ffi_args) as u32)),
+                CType::U64 => {
+                    let v = cif.call::<u64>(code, &ffi_args);
+                    AvmValue::Int(i64::try_from(v).map_err(|_| {
+                        miette::miette!("AVM: extern cell '{name}' returned {v}, which the AVM cannot represent")
+                    })?)
+                }
+                CType::I32 => AvmValue::Int(i64::from(cif.call::<u64>(code, &ffi_args) as u32 as i32)),
+                CType::I64 => AvmValue::Int(cif.call::<i64>(code, &ffi_args)),
+                CType::F64 => AvmValue::Float(cif.call::<f64>(code, &ffi_args)),
+                CType::Str => {
+                    let p = cif.call::<*const c_char>(code, &ffi_args);
+                    if p.is_null() {
+                        return Err(miette::miette!("AVM: extern cell '{name}' returned a null string"));
+                    }
+                    // The string is copied and never freed: who owns it is up to the library.
+                    AvmValue::Str(CStr::from_ptr(p).to_string_lossy().into_owned())
+                }
+            }
+        };
+        drop(strings);
+        Ok(value)
+    }
+}
+
+fn to_slot(name: &str, c: CType, v: &AvmValue, strings: &mut Vec<CString>) -> miette::Result<Slot> {
+    let out_of_range = |i: i64| {
+        miette::miette!("AVM: argument {i} to extern cell '{name}' is out of range for its C type")
+    };
+    Ok(match (c, v) {
+        (CType::U8, AvmValue::Int(i)) => Slot::U8(u8::try_from(*i).map_err(|_| out_of_range(*i))?),
+        (CType::U16, AvmValue::Int(i)) => Slot::U16(u16::try_from(*i).map_err(|_| out_of_range(*i))?),
+        (CType::U32, AvmValue::Int(i)) => Slot::U32(u32::try_from(*i).map_err(|_| out_of_range(*i))?),
+        (CType::U64, AvmValue::Int(i)) => Slot::U64(u64::try_from(*i).map_err(|_| out_of_range(*i))?),
+        (CType::I32, AvmValue::Int(i)) => Slot::I32(i32::try_from(*i).map_err(|_| out_of_range(*i))?),
+        (CType::I64, AvmValue::Int(i)) => Slot::I64(*i),
+        (CType::F64, AvmValue::Float(f)) => Slot::F64(*f),
+        (CType::Bool, AvmValue::Bool(b)) => Slot::U8(u8::from(*b)),
+        (CType::Str, AvmValue::Str(s)) => {
+            let s = CString::new(s.as_str())
+                .map_err(|_| miette::miette!("AVM: string passed to extern cell '{name}' contains a NUL byte"))?;
+            let p = s.as_ptr();
+            strings.push(s);
+            Slot::Ptr(p)
+        }
+        (_, v) => {
+            return Err(miette::miette!(
+                "AVM: extern cell '{name}' cannot take {} for a {c:?} parameter",
+                v.display()
+            ));
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ty(src: &str) -> TypeRef {
+        let program = aura_parse::parse_source(&format!("extern cell f(x: {src}): Unit\n")).expect("parse");
+        match &program.stmts[0] {
+            aura_ast::Stmt::ExternCell(e) => e.params[0].ty.clone(),
+            other => panic!("expected an extern cell, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn refined_and_bridged_types_map_to_c_types() {
+        assert_eq!(CType::of(&ty("u32")), Some(CType::U32));
+        assert_eq!(CType::of(&ty("u32[0..255]")), Some(CType::U32));
+        assert_eq!(CType::of(&ty("String")), Some(CType::Str));
+        assert_eq!(CType::of(&ty("Int")), Some(CType::U32));
+        assert_eq!(CType::of(&ty("f64")), Some(CType::F64));
+        assert_eq!(CType::of(&ty("u8")), Some(CType::U8));
+        assert_eq!(CType::of(&ty("i64")), Some(CType::I64));
+        assert_eq!(CType::of(&ty("Option<u32>")), None);
+    }
+
+    #[test]
+    fn arguments_are_range_checked() {
+        let mut strings = Vec::new();
+        assert!(to_slot("f", CType::U32, &AvmValue::Int(-1), &mut strings).is_err());
+        assert!(to_slot("f", CType::U32, &AvmValue::Int(1 << 32), &mut strings).is_err());
+        assert!(to_slot("f", CType::U8, &AvmValue::Int(256), &mut strings).is_err());
+        assert!(to_slot("f", CType::I32, &AvmValue::Int(-1), &mut strings).is_ok());
+        assert!(to_slot("f", CType::Str, &AvmValue::Int(1), &mut strings).is_err());
+        assert!(to_slot("f", CType::Str, &AvmValue::Str("hi".into()), &mut strings).is_ok());
+        assert_eq!(strings.len(), 1);
+    }
+}
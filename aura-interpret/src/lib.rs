@@ -1,9 +1,30 @@
-#![forbid(unsafe_code)]
+// Only the `ffi` and `ort` modules may use `unsafe`, so the crate forbids it unless they are built.
+#![cfg_attr(not(feature = "ffi"), forbid(unsafe_code))]
+#![cfg_attr(feature = "ffi", deny(unsafe_code))]
 
+mod coverage;
 mod debug;
+#[cfg(feature = "ffi")]
+mod ffi;
+#[cfg(feature = "ffi")]
+mod ort;
+mod repl;
+mod trace;
 mod vm;
 
+pub use coverage::{
+	BranchCoverage, CellCoverage, Coverage, CoverageReport, CoverageSummary, Ratio, StatementCoverage,
+};
 pub use debug::{
-	DebugBreakpoint, DebugCommand, DebugEvent, DebugHandle, DebugSession, DebugWatch, PerfReport,
+	DebugBreakpoint, DebugCommand, DebugEvent, DebugFrame, DebugHandle, DebugSession, DebugTaskState, DebugValue,
+	DebugVariable, DebugWatch, PerfCell, PerfReport,
+};
+pub use repl::{Repl, ReplBinding, ReplReply};
+pub use trace::TraceMode;
+pub use vm::{
+	Avm, AvmConfig, AvmEvent, AvmResource, AvmTerminated, AvmValue, ExecOutcome, FlowScheduling,
+	HotReload, HwEmulation, HwScript, HwScriptedReads, HwStore, HwWriteHook, InferenceRuntime, PrepareSourceFn,
+	Profile, RuntimeTensor,
 };
-pub use vm::{Avm, AvmConfig, AvmEvent, AvmTerminated, AvmValue, ExecOutcome};
+#[cfg(feature = "ffi")]
+pub use ort::OnnxRuntime;
@@ -1,12 +1,12 @@
 #![forbid(unsafe_code)]
 
-use std::io::{self, BufRead, Read};
+use std::io::{self, BufRead, Read, Write};
 
 use clap::{Parser, Subcommand};
 use serde::{Deserialize, Serialize};
 use miette::IntoDiagnostic;
 
-use aura_interpret::{Avm, AvmConfig, AvmValue};
+use aura_interpret::{Avm, AvmConfig, FlowScheduling, HotReload, HwEmulation, HwScript, Repl, ReplReply, TraceMode};
 
 #[derive(Parser, Debug)]
 #[command(name = "aura-interpret", version, about = "Aura Virtual Machine (AVM) interpreter")]
@@ -30,13 +30,81 @@ enum Cmd {
         /// Print machine-readable JSON
         #[arg(long, default_value_t = false)]
         json: bool,
+
+        /// Record UI events, stdin and file reads to this trace file (JSON lines)
+        #[arg(long, conflicts_with = "replay")]
+        record: Option<std::path::PathBuf>,
+
+        /// Replay a trace written by `--record` instead of reading live inputs
+        #[arg(long)]
+        replay: Option<std::path::PathBuf>,
+
+        /// Stop after this many statements and cell calls
+        #[arg(long)]
+        fuel: Option<u64>,
+
+        /// Stop once bindings and output are estimated to hold more than this many bytes
+        #[arg(long)]
+        max_memory: Option<u64>,
+
+        /// Shared library to call extern cells in (repeatable; needs the `ffi` feature)
+        #[arg(long = "native-lib")]
+        native_libs: Vec<std::path::PathBuf>,
+
+        /// Run `~>` flows in parallel on worker threads instead of one at a time
+        #[arg(long, default_value_t = false)]
+        flow_threads: bool,
+
+        /// With `--flow-threads`, merge the output of unawaited flows in spawn order
+        #[arg(long, default_value_t = false, requires = "flow_threads")]
+        deterministic_flows: bool,
+
+        /// Write statement and branch coverage to this file (JSON if it ends in `.json`, lcov otherwise)
+        #[arg(long)]
+        coverage: Option<std::path::PathBuf>,
+
+        /// Swap edits of `--file` into running UI loops between frames
+        #[arg(long, default_value_t = false, requires = "file")]
+        hot_reload: bool,
+
+        /// Write per-cell-stack times to this file in collapsed-stack format, for flamegraph tools
+        #[arg(long)]
+        profile: Option<std::path::PathBuf>,
+
+        /// With `--profile`, profile only every Nth frame of live UI loops
+        #[arg(long, requires = "profile")]
+        profile_frame_interval: Option<u32>,
+
+        /// Run `hw.*` calls against registers emulated from the nearest aura.toml's `[hardware]`
+        #[arg(long, default_value_t = false)]
+        emulate_hw: bool,
+
+        /// With `--emulate-hw`, script peripheral behaviour from this JSON file
+        #[arg(long, requires = "emulate_hw")]
+        hw_script: Option<std::path::PathBuf>,
     },
 
-    /// Start a REPL over stdin/stdout using JSON lines
+    /// Start an interactive session that keeps definitions and values across inputs
     Repl {
         /// Disable Z3 safety gate
         #[arg(long, default_value_t = false)]
         no_z3: bool,
+
+        /// Speak JSON lines (`{"source": ...}` in, one result object out) for editor integration
+        #[arg(long, default_value_t = false)]
+        json: bool,
+
+        /// Stop each input after this many statements and cell calls
+        #[arg(long)]
+        fuel: Option<u64>,
+
+        /// Stop an input once bindings and output are estimated to hold more than this many bytes
+        #[arg(long)]
+        max_memory: Option<u64>,
+
+        /// Shared library to call extern cells in (repeatable; needs the `ffi` feature)
+        #[arg(long = "native-lib")]
+        native_libs: Vec<std::path::PathBuf>,
     },
 }
 
@@ -45,13 +113,49 @@ struct JsonOut {
     verified: bool,
     value: String,
     stdout: String,
+    /// Refined type of an evaluated REPL expression.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ty: Option<String>,
 }
 
 fn main() -> miette::Result<()> {
     let cli = Cli::parse();
 
     match cli.cmd {
-        Cmd::Run { file, no_z3, json } => {
+        Cmd::Run {
+            file,
+            no_z3,
+            json,
+            record,
+            replay,
+            fuel,
+            max_memory,
+            native_libs,
+            flow_threads,
+            deterministic_flows,
+            coverage,
+            hot_reload,
+            profile,
+            profile_frame_interval,
+            emulate_hw,
+            hw_script,
+        } => {
+            let src_name = file
+                .as_ref()
+                .map_or_else(|| "<stdin>".to_string(), |p| p.display().to_string());
+            let hot_reload = file.clone().filter(|_| hot_reload).map(HotReload::new);
+            let hw = if emulate_hw {
+                let start = file.clone().unwrap_or_else(|| std::path::PathBuf::from("."));
+                let mut emu = HwEmulation::load(&start)?;
+                if let Some(path) = &hw_script {
+                    emu = emu.with_script(HwScript::load(path)?);
+                }
+                Some(emu)
+            } else {
+                None
+            };
+            let policy_start = file.clone().unwrap_or_else(|| std::path::PathBuf::from("."));
+            let fs_policy = aura_sdk::fs_policy::load_fs_policy(&policy_start)?;
             let src = if let Some(p) = file {
                 std::fs::read_to_string(p).into_diagnostic()?
             } else {
@@ -60,19 +164,58 @@ fn main() -> miette::Result<()> {
                 buf
             };
 
+            let trace = match (record, replay) {
+                (Some(path), _) => Some(TraceMode::Record(path)),
+                (None, Some(path)) => Some(TraceMode::Replay {
+                    path,
+                    pause_at_step: None,
+                }),
+                (None, None) => None,
+            };
             let mut avm = Avm::new(AvmConfig {
                 enable_z3_gate: !no_z3,
+                trace,
+                fuel,
+                max_memory_bytes: max_memory,
+                native_libs,
+                flows: if flow_threads {
+                    FlowScheduling::Threads {
+                        deterministic: deterministic_flows,
+                    }
+                } else {
+                    FlowScheduling::Cooperative
+                },
+                coverage: coverage.is_some(),
+                hot_reload,
+                profile: profile.is_some(),
+                profile_frame_interval,
+                hw,
+                fs_policy,
                 ..Default::default()
             });
 
-            let out = avm.exec_source(&src)?;
+            let out = avm.exec_source(&src);
+            // Coverage and profiles of a failed run are still worth having.
+            if let (Some(path), Some(cov)) = (&coverage, avm.coverage()) {
+                let text = if path.extension().is_some_and(|e| e == "json") {
+                    serde_json::to_string_pretty(&cov.report()).into_diagnostic()?
+                } else {
+                    cov.to_lcov(&src_name)
+                };
+                std::fs::write(path, text).into_diagnostic()?;
+            }
+            if let (Some(path), Some(prof)) = (&profile, avm.profile()) {
+                std::fs::write(path, prof.to_collapsed()).into_diagnostic()?;
+            }
+            let out = out?;
             if json {
                 println!(
                     "{}",
                     serde_json::to_string(&JsonOut {
                         verified: out.verified,
-                        value: format_value(&out.value),
+                        value: out.value.display(),
                         stdout: out.stdout,
+                        ty: None,
                     })
                     .into_diagnostic()?
                 );
@@ -80,12 +223,31 @@ fn main() -> miette::Result<()> {
                 if !out.stdout.is_empty() {
                     print!("{}", out.stdout);
                 }
-                println!("{}", format_value(&out.value));
+                println!("{}", out.value.display());
             }
 
             Ok(())
         }
-        Cmd::Repl { no_z3 } => repl_main(!no_z3),
+        Cmd::Repl {
+            no_z3,
+            json,
+            fuel,
+            max_memory,
+            native_libs,
+        } => {
+            let repl = Repl::new(AvmConfig {
+                enable_z3_gate: !no_z3,
+                fuel,
+                max_memory_bytes: max_memory,
+                native_libs,
+                ..Default::default()
+            });
+            if json {
+                repl_json(repl)
+            } else {
+                repl_interactive(repl)
+            }
+        }
     }
 }
 
@@ -94,12 +256,47 @@ struct ReplIn {
     source: String,
 }
 
-fn repl_main(enable_z3: bool) -> miette::Result<()> {
-    let mut avm = Avm::new(AvmConfig {
-        enable_z3_gate: enable_z3,
-        ..Default::default()
-    });
+fn repl_interactive(mut repl: Repl) -> miette::Result<()> {
+    let stdin = io::stdin();
+    let mut stdin = stdin.lock();
+    let mut input = String::new();
+    loop {
+        print!("{}", if input.is_empty() { "aura> " } else { "....> " });
+        io::stdout().flush().into_diagnostic()?;
+
+        let mut line = String::new();
+        if stdin.read_line(&mut line).into_diagnostic()? == 0 {
+            println!();
+            break;
+        }
+        if input.is_empty() && line.trim().is_empty() {
+            continue;
+        }
+        if input.is_empty() && matches!(line.trim(), ":quit" | ":q") {
+            break;
+        }
+        input.push_str(&line);
+        if Repl::is_incomplete(&input) {
+            continue;
+        }
+
+        match repl.eval(&input) {
+            Ok(reply) => {
+                print!("{}", reply.stdout());
+                let shown = reply.render();
+                if !shown.is_empty() {
+                    println!("{shown}");
+                }
+            }
+            Err(e) => eprintln!("error: {e}"),
+        }
+        input.clear();
+    }
+
+    Ok(())
+}
 
+fn repl_json(mut repl: Repl) -> miette::Result<()> {
     let stdin = io::stdin();
     let mut stdin = stdin.lock();
     let mut line = String::new();
@@ -121,52 +318,29 @@ fn repl_main(enable_z3: bool) -> miette::Result<()> {
             }
         };
 
-        match avm.exec_source(&inp.source) {
-            Ok(out) => {
-                let msg = JsonOut {
-                    verified: out.verified,
-                    value: format_value(&out.value),
-                    stdout: out.stdout,
-                };
-                println!("{}", serde_json::to_string(&msg).into_diagnostic()?);
-            }
-            Err(e) => {
-                let msg = JsonOut {
-                    verified: false,
-                    value: "<error>".to_string(),
-                    stdout: e.to_string(),
-                };
-                println!("{}", serde_json::to_string(&msg).into_diagnostic()?);
-            }
-        }
+        let msg = match repl.eval(&inp.source) {
+            Ok(reply) => JsonOut {
+                verified: reply.verified(),
+                value: match &reply {
+                    ReplReply::Value { value, .. } => value.display(),
+                    other => other.render(),
+                },
+                stdout: reply.stdout().to_string(),
+                ty: reply.ty().map(|t| t.display()),
+            },
+            Err(e) => JsonOut {
+                verified: false,
+                value: "<error>".to_string(),
+                stdout: e.to_string(),
+                ty: None,
+            },
+        };
+        println!("{}", serde_json::to_string(&msg).into_diagnostic()?);
     }
 
     Ok(())
 }
 
-fn format_value(v: &AvmValue) -> String {
-    match v {
-        AvmValue::Int(i) => i.to_string(),
-        AvmValue::Bool(b) => b.to_string(),
-        AvmValue::Str(s) => s.clone(),
-        AvmValue::Style(map) => {
-            let mut out = String::from("Style{");
-            for (i, (k, vv)) in map.iter().enumerate() {
-                if i > 0 {
-                    out.push(',');
-                }
-                out.push_str(k);
-                out.push(':');
-                out.push_str(&format_value(vv));
-            }
-            out.push('}');
-            out
-        }
-        AvmValue::Ui(node) => aura_nexus::format_ui_tree(node),
-        AvmValue::Unit => "()".to_string(),
-    }
-}
-
 fn escape_json(s: &str) -> String {
     s.replace('\\', "\\\\").replace('"', "\\\"")
 }
@@ -0,0 +1,466 @@
+//! Runs `ai.infer` models with the ONNX Runtime shared library (`ffi` feature).
+//!
+//! The library is loaded from `AvmConfig::native_libs` (the `onnxruntime` package `aura pkg`
+//! installs) and driven through its C API, the function table `OrtGetApiBase` hands out.
+//! Only the table entries of API version 1 are used, so any ONNX Runtime release works.
+#![allow(unsafe_code)]
+
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::path::Path;
+use std::ptr;
+use std::sync::Mutex;
+
+use aura_bridge::onnx::OnnxElemType;
+use libloading::Library;
+
+use crate::vm::{InferenceRuntime, RuntimeTensor};
+
+type Status = *mut c_void;
+
+/// `OrtApiBase`: the library's single export.
+#[repr(C)]
+struct ApiBase {
+    get_api: unsafe extern "C" fn(version: u32) -> *const Api,
+    get_version_string: unsafe extern "C" fn() -> *const c_char,
+}
+
+/// The leading entries of `OrtApi`, by position; none of them moves between releases.
+#[repr(C)]
+struct Api {
+    fns: [*const c_void; 101],
+}
+
+const GET_ERROR_MESSAGE: usize = 2;
+const CREATE_ENV: usize = 3;
+const CREATE_SESSION: usize = 7;
+const RUN: usize = 9;
+const CREATE_SESSION_OPTIONS: usize = 10;
+const SESSION_GET_INPUT_TYPE_INFO: usize = 33;
+const SESSION_GET_INPUT_NAME: usize = 36;
+const SESSION_GET_OUTPUT_NAME: usize = 37;
+const CREATE_TENSOR_WITH_DATA: usize = 49;
+const GET_TENSOR_MUTABLE_DATA: usize = 51;
+const CAST_TYPE_INFO_TO_TENSOR_INFO: usize = 55;
+const GET_TENSOR_ELEMENT_TYPE: usize = 60;
+const GET_DIMENSIONS_COUNT: usize = 61;
+const GET_DIMENSIONS: usize = 62;
+const GET_TENSOR_TYPE_AND_SHAPE: usize = 65;
+const CREATE_CPU_MEMORY_INFO: usize = 69;
+const ALLOCATOR_FREE: usize = 76;
+const GET_ALLOCATOR_WITH_DEFAULT_OPTIONS: usize = 78;
+const RELEASE_ENV: usize = 92;
+const RELEASE_STATUS: usize = 93;
+const RELEASE_MEMORY_INFO: usize = 94;
+const RELEASE_SESSION: usize = 95;
+const RELEASE_VALUE: usize = 96;
+const RELEASE_TYPE_INFO: usize = 98;
+const RELEASE_TENSOR_TYPE_AND_SHAPE_INFO: usize = 99;
+const RELEASE_SESSION_OPTIONS: usize = 100;
+
+/// `ORT_LOGGING_LEVEL_WARNING`.
+const LOG_WARNING: i32 = 2;
+/// `OrtArenaAllocator` and `OrtMemTypeDefault`.
+const ARENA_ALLOCATOR: i32 = 1;
+const MEM_TYPE_DEFAULT: i32 = 0;
+
+/// Whether `path` names the ONNX Runtime library (not one of its provider plugins).
+pub(crate) fn is_onnxruntime_library(path: &Path) -> bool {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("").to_ascii_lowercase();
+    name.contains("onnxruntime") && !name.contains("providers") && !name.ends_with(".lib") && !name.ends_with(".a")
+}
+
+/// Byte width of an element type in a tensor slot; wider types do not fit the 32-bit slots.
+fn slot_width(elem: i32) -> Option<usize> {
+    match OnnxElemType(elem).aura_name()? {
+        "u8" | "i8" | "bool" => Some(1),
+        "u16" | "i16" => Some(2),
+        "f32" | "u32" | "i32" => Some(4),
+        _ => None,
+    }
+}
+
+/// The elements as the model reads them: the low bytes of each slot, in native order.
+fn to_bytes(elem: i32, data: &[u32]) -> Vec<u8> {
+    let width = slot_width(elem).unwrap_or(4);
+    let mut out = Vec::with_capacity(data.len() * width);
+    for v in data {
+        match width {
+            1 => out.push(*v as u8),
+            2 => out.extend_from_slice(&(*v as u16).to_ne_bytes()),
+            _ => out.extend_from_slice(&v.to_ne_bytes()),
+        }
+    }
+    out
+}
+
+fn from_bytes(elem: i32, bytes: &[u8]) -> Vec<u32> {
+    let signed = matches!(OnnxElemType(elem).aura_name(), Some("i8" | "i16"));
+    let width = slot_width(elem).unwrap_or(4);
+    bytes
+        .chunks_exact(width)
+        .map(|c| match (width, signed) {
+            (1, false) => u32::from(c[0]),
+            (1, true) => c[0] as i8 as i32 as u32,
+            (2, false) => u32::from(u16::from_ne_bytes([c[0], c[1]])),
+            (2, true) => i16::from_ne_bytes([c[0], c[1]]) as i32 as u32,
+            _ => u32::from_ne_bytes([c[0], c[1], c[2], c[3]]),
+        })
+        .collect()
+}
+
+struct Session {
+    ptr: *mut c_void,
+    input_name: CString,
+    output_name: CString,
+    input_elem: i32,
+}
+
+struct State {
+    env: *mut c_void,
+    sessions: Vec<Session>,
+}
+
+/// ONNX Runtime, loaded from its shared library.
+pub struct OnnxRuntime {
+    api: *const Api,
+    version: String,
+    state: Mutex<State>,
+    // Keeps the library mapped while the function table is in use.
+    _lib: Library,
+}
+
+// SAFETY: the ONNX Runtime C API is thread-safe for environments and sessions, and every
+// call goes through `state`'s lock.
+unsafe impl Send for OnnxRuntime {}
+unsafe impl Sync for OnnxRuntime {}
+
+impl std::fmt::Debug for OnnxRuntime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OnnxRuntime").field("version", &self.version).finish()
+    }
+}
+
+impl OnnxRuntime {
+    /// Loads ONNX Runtime from the shared library at `path`.
+    pub fn load(path: &Path) -> miette::Result<Self> {
+        let fail = |why: String| miette::miette!("AVM: failed to load ONNX Runtime from {}: {why}", path.display());
+        // SAFETY: loading runs the library's initialisers, as linking it into a compiled
+        // program does.
+        let lib = unsafe { Library::new(path) }.map_err(|e| fail(e.to_string()))?;
+        // SAFETY: `OrtGetApiBase` takes no arguments and returns a pointer to a static table.
+        let base = unsafe {
+            let get_base = lib
+                .get::<unsafe extern "C" fn() -> *const ApiBase>(b"OrtGetApiBase")
+                .map_err(|e| fail(e.to_string()))?;
+            get_base()
+        };
+        if base.is_null() {
+            return Err(fail("OrtGetApiBase returned null".to_string()));
+        }
+        // SAFETY: `base` points to the library's static `OrtApiBase`.
+        let (api, version) = unsafe {
+            let api = ((*base).get_api)(1);
+            let v = ((*base).get_version_string)();
+            let version = if v.is_null() {
+                String::new()
+            } else {
+                CStr::from_ptr(v).to_string_lossy().into_owned()
+            };
+            (api, version)
+        };
+        if api.is_null() {
+            return Err(fail("the library does not provide ONNX Runtime API version 1".to_string()));
+        }
+        let rt = Self {
+            api,
+            version,
+            state: Mutex::new(State {
+                env: ptr::null_mut(),
+                sessions: Vec::new(),
+            }),
+            _lib: lib,
+        };
+        let mut env = ptr::null_mut();
+        let logid = c"aura-avm";
+        // SAFETY: `CreateEnv(OrtLoggingLevel, const char*, OrtEnv**)`.
+        rt.check(unsafe {
+            rt.f::<unsafe extern "C" fn(i32, *const c_char, *mut *mut c_void) -> Status>(CREATE_ENV)(
+                LOG_WARNING,
+                logid.as_ptr(),
+                &mut env,
+            )
+        })
+        .map_err(fail)?;
+        rt.state.lock().expect("fresh lock").env = env;
+        Ok(rt)
+    }
+
+    /// The table entry at `index`, as the function type the C API declares for it.
+    ///
+    /// # Safety
+    /// `F` must be the entry's exact C signature.
+    unsafe fn f<F: Copy>(&self, index: usize) -> F {
+        // SAFETY: `api` is the table of version 1, which has every entry this module indexes;
+        // the caller guarantees the type.
+        unsafe { std::mem::transmute_copy(&(*self.api).fns[index]) }
+    }
+
+    /// Turns a returned `OrtStatus*` into a result, releasing it.
+    fn check(&self, status: Status) -> Result<(), String> {
+        if status.is_null() {
+            return Ok(());
+        }
+        // SAFETY: `GetErrorMessage(const OrtStatus*)` and `ReleaseStatus(OrtStatus*)` on a
+        // status the API returned.
+        unsafe {
+            let msg = self.f::<unsafe extern "C" fn(Status) -> *const c_char>(GET_ERROR_MESSAGE)(status);
+            let msg = CStr::from_ptr(msg).to_string_lossy().into_owned();
+            self.f::<unsafe extern "C" fn(Status)>(RELEASE_STATUS)(status);
+            Err(msg)
+        }
+    }
+
+    fn allocator(&self) -> Result<*mut c_void, String> {
+        let mut alloc = ptr::null_mut();
+        // SAFETY: `GetAllocatorWithDefaultOptions(OrtAllocator**)`.
+        self.check(unsafe {
+            self.f::<unsafe extern "C" fn(*mut *mut c_void) -> Status>(GET_ALLOCATOR_WITH_DEFAULT_OPTIONS)(&mut alloc)
+        })?;
+        Ok(alloc)
+    }
+
+    /// The name of a session's first input (`output == false`) or output.
+    fn io_name(&self, session: *mut c_void, output: bool) -> Result<CString, String> {
+        let alloc = self.allocator()?;
+        let mut name: *mut c_char = ptr::null_mut();
+        let index = if output { SESSION_GET_OUTPUT_NAME } else { SESSION_GET_INPUT_NAME };
+        // SAFETY: `SessionGet{Input,Output}Name(const OrtSession*, size_t, OrtAllocator*,
+        // char**)`; the name is copied, then freed with the allocator that made it.
+        unsafe {
+            self.check(self
+                .f::<unsafe extern "C" fn(*mut c_void, usize, *mut c_void, *mut *mut c_char) -> Status>(index)(
+                    session, 0, alloc, &mut name,
+                ))?;
+            let owned = CStr::from_ptr(name).to_owned();
+            self.check(self.f::<unsafe extern "C" fn(*mut c_void, *mut c_void) -> Status>(ALLOCATOR_FREE)(
+                alloc,
+                name.cast(),
+            ))?;
+            Ok(owned)
+        }
+    }
+
+    /// The element type of a session's first input.
+    fn input_elem(&self, session: *mut c_void) -> Result<i32, String> {
+        let mut info = ptr::null_mut();
+        let mut tensor_info: *const c_void = ptr::null();
+        let mut elem = 0;
+        // SAFETY: `SessionGetInputTypeInfo(const OrtSession*, size_t, OrtTypeInfo**)`,
+        // `CastTypeInfoToTensorInfo(const OrtTypeInfo*, const OrtTensorTypeAndShapeInfo**)`
+        // (borrowed from the type info) and `GetTensorElementType(const
+        // OrtTensorTypeAndShapeInfo*, ONNXTensorElementDataType*)`.
+        unsafe {
+            self.check(self.f::<unsafe extern "C" fn(*mut c_void, usize, *mut *mut c_void) -> Status>(
+                SESSION_GET_INPUT_TYPE_INFO,
+            )(session, 0, &mut info))?;
+            let res = self
+                .check(self.f::<unsafe extern "C" fn(*mut c_void, *mut *const c_void) -> Status>(
+                    CAST_TYPE_INFO_TO_TENSOR_INFO,
+                )(info, &mut tensor_info))
+                .and_then(|()| {
+                    if tensor_info.is_null() {
+                        return Err("the model's first input is not a tensor".to_string());
+                    }
+                    self.check(self.f::<unsafe extern "C" fn(*const c_void, *mut i32) -> Status>(
+                        GET_TENSOR_ELEMENT_TYPE,
+                    )(tensor_info, &mut elem))
+                });
+            self.f::<unsafe extern "C" fn(*mut c_void)>(RELEASE_TYPE_INFO)(info);
+            res?;
+        }
+        Ok(elem)
+    }
+
+    /// Shape, element type and data of a tensor value.
+    fn read_tensor(&self, value: *mut c_void) -> Result<RuntimeTensor, String> {
+        let mut info = ptr::null_mut();
+        let (mut rank, mut elem) = (0usize, 0i32);
+        // SAFETY: `GetTensorTypeAndShape(const OrtValue*, OrtTensorTypeAndShapeInfo**)`,
+        // `GetTensorElementType`, `GetDimensionsCount(const OrtTensorTypeAndShapeInfo*,
+        // size_t*)`, `GetDimensions(const OrtTensorTypeAndShapeInfo*, int64_t*, size_t)` and
+        // `GetTensorMutableData(OrtValue*, void**)`; the data holds the product of the
+        // dimensions in elements of the reported type.
+        unsafe {
+            self.check(self.f::<unsafe extern "C" fn(*mut c_void, *mut *mut c_void) -> Status>(
+                GET_TENSOR_TYPE_AND_SHAPE,
+            )(value, &mut info))?;
+            let mut dims = Vec::new();
+            let res = self
+                .check(self.f::<unsafe extern "C" fn(*mut c_void, *mut i32) -> Status>(GET_TENSOR_ELEMENT_TYPE)(
+                    info, &mut elem,
+                ))
+                .and_then(|()| {
+                    self.check(self.f::<unsafe extern "C" fn(*mut c_void, *mut usize) -> Status>(
+                        GET_DIMENSIONS_COUNT,
+                    )(info, &mut rank))
+                })
+                .and_then(|()| {
+                    dims = vec![0i64; rank];
+                    self.check(self.f::<unsafe extern "C" fn(*mut c_void, *mut i64, usize) -> Status>(
+                        GET_DIMENSIONS,
+                    )(info, dims.as_mut_ptr(), rank))
+                });
+            self.f::<unsafe extern "C" fn(*mut c_void)>(RELEASE_TENSOR_TYPE_AND_SHAPE_INFO)(info);
+            res?;
+
+            let width = slot_width(elem)
+                .ok_or_else(|| format!("output elements of type {} do not fit 32-bit tensor slots", OnnxElemType(elem).describe()))?;
+            let shape: Vec<u64> = dims.iter().map(|d| u64::try_from(*d).unwrap_or(0)).collect();
+            let count = shape.iter().product::<u64>() as usize;
+            let mut data: *mut c_void = ptr::null_mut();
+            self.check(self.f::<unsafe extern "C" fn(*mut c_void, *mut *mut c_void) -> Status>(
+                GET_TENSOR_MUTABLE_DATA,
+            )(value, &mut data))?;
+            let bytes = if count == 0 {
+                &[][..]
+            } else {
+                std::slice::from_raw_parts(data.cast::<u8>(), count * width)
+            };
+            Ok(RuntimeTensor {
+                shape,
+                elem: Some(OnnxElemType(elem)),
+                data: from_bytes(elem, bytes),
+            })
+        }
+    }
+}
+
+impl Drop for OnnxRuntime {
+    fn drop(&mut self) {
+        let state = self.state.get_mut().unwrap_or_else(|e| e.into_inner());
+        let (env, sessions) = (state.env, std::mem::take(&mut state.sessions));
+        // SAFETY: `ReleaseSession(OrtSession*)` and `ReleaseEnv(OrtEnv*)` on objects this
+        // runtime created and nothing else holds.
+        unsafe {
+            for s in sessions {
+                self.f::<unsafe extern "C" fn(*mut c_void)>(RELEASE_SESSION)(s.ptr);
+            }
+            if !env.is_null() {
+                self.f::<unsafe extern "C" fn(*mut c_void)>(RELEASE_ENV)(env);
+            }
+        }
+    }
+}
+
+impl InferenceRuntime for OnnxRuntime {
+    fn load(&self, path: &Path) -> Result<u64, String> {
+        let mut state = self.state.lock().map_err(|_| "ONNX Runtime state is poisoned".to_string())?;
+        #[cfg(windows)]
+        let model_path: Vec<u16> = {
+            use std::os::windows::ffi::OsStrExt;
+            path.as_os_str().encode_wide().chain(Some(0)).collect()
+        };
+        #[cfg(not(windows))]
+        let model_path = CString::new(path.as_os_str().as_encoded_bytes()).map_err(|e| e.to_string())?;
+
+        let mut options = ptr::null_mut();
+        let mut session = ptr::null_mut();
+        // SAFETY: `CreateSessionOptions(OrtSessionOptions**)`, `CreateSession(const OrtEnv*,
+        // const ORTCHAR_T*, const OrtSessionOptions*, OrtSession**)` with a NUL-terminated
+        // path in the platform's `ORTCHAR_T`, and `ReleaseSessionOptions`.
+        unsafe {
+            self.check(self.f::<unsafe extern "C" fn(*mut *mut c_void) -> Status>(CREATE_SESSION_OPTIONS)(
+                &mut options,
+            ))?;
+            let res = self.check(self
+                .f::<unsafe extern "C" fn(*mut c_void, *const c_void, *mut c_void, *mut *mut c_void) -> Status>(
+                    CREATE_SESSION,
+                )(state.env, model_path.as_ptr().cast(), options, &mut session));
+            self.f::<unsafe extern "C" fn(*mut c_void)>(RELEASE_SESSION_OPTIONS)(options);
+            res?;
+        }
+        let input_elem = self.input_elem(session)?;
+        if slot_width(input_elem).is_none() {
+            return Err(format!(
+                "input elements of type {} do not fit 32-bit tensor slots",
+                OnnxElemType(input_elem).describe()
+            ));
+        }
+        state.sessions.push(Session {
+            ptr: session,
+            input_name: self.io_name(session, false)?,
+            output_name: self.io_name(session, true)?,
+            input_elem,
+        });
+        Ok(state.sessions.len() as u64 - 1)
+    }
+
+    fn run(&self, model: u64, input: &RuntimeTensor) -> Result<RuntimeTensor, String> {
+        let state = self.state.lock().map_err(|_| "ONNX Runtime state is poisoned".to_string())?;
+        let session = state
+            .sessions
+            .get(model as usize)
+            .ok_or_else(|| format!("no model with id {model}"))?;
+        let mut bytes = to_bytes(session.input_elem, &input.data);
+        let shape: Vec<i64> = input.shape.iter().map(|d| *d as i64).collect();
+
+        let mut mem = ptr::null_mut();
+        let mut value = ptr::null_mut();
+        let mut output = ptr::null_mut();
+        // SAFETY: `CreateCpuMemoryInfo(OrtAllocatorType, OrtMemType, OrtMemoryInfo**)`,
+        // `CreateTensorWithDataAsOrtValue(const OrtMemoryInfo*, void*, size_t, const int64_t*,
+        // size_t, ONNXTensorElementDataType, OrtValue**)` over `bytes`, which outlives the run,
+        // and `Run(OrtSession*, const OrtRunOptions*, const char* const*, const OrtValue* const*,
+        // size_t, const char* const*, size_t, OrtValue**)` with one input and one output.
+        unsafe {
+            self.check(self.f::<unsafe extern "C" fn(i32, i32, *mut *mut c_void) -> Status>(CREATE_CPU_MEMORY_INFO)(
+                ARENA_ALLOCATOR,
+                MEM_TYPE_DEFAULT,
+                &mut mem,
+            ))?;
+            let res = self.check(self
+                .f::<unsafe extern "C" fn(*mut c_void, *mut c_void, usize, *const i64, usize, i32, *mut *mut c_void) -> Status>(
+                    CREATE_TENSOR_WITH_DATA,
+                )(
+                    mem,
+                    bytes.as_mut_ptr().cast(),
+                    bytes.len(),
+                    shape.as_ptr(),
+                    shape.len(),
+                    session.input_elem,
+                    &mut value,
+                ));
+            self.f::<unsafe extern "C" fn(*mut c_void)>(RELEASE_MEMORY_INFO)(mem);
+            res?;
+
+            let input_names = [session.input_name.as_ptr()];
+            let output_names = [session.output_name.as_ptr()];
+            let inputs = [value.cast_const()];
+            let res = self.check(self
+                .f::<unsafe extern "C" fn(
+                    *mut c_void,
+                    *const c_void,
+                    *const *const c_char,
+                    *const *const c_void,
+                    usize,
+                    *const *const c_char,
+                    usize,
+                    *mut *mut c_void,
+                ) -> Status>(RUN)(
+                    session.ptr,
+                    ptr::null(),
+                    input_names.as_ptr(),
+                    inputs.as_ptr(),
+                    1,
+                    output_names.as_ptr(),
+                    1,
+                    &mut output,
+                ));
+            self.f::<unsafe extern "C" fn(*mut c_void)>(RELEASE_VALUE)(value);
+            res?;
+            let out = self.read_tensor(output);
+            self.f::<unsafe extern "C" fn(*mut c_void)>(RELEASE_VALUE)(output);
+            out
+        }
+    }
+}
@@ -0,0 +1,275 @@
+//! Interactive sessions over one long-lived AVM.
+//!
+//! Each input is checked as the tail of everything defined so far, runs in the same `Avm`
+//! (so vals and cells stay live), and has its definitions appended to the session program.
+
+use aura_ast::{Pattern, Stmt};
+use aura_core::{Checker, Type};
+
+use crate::vm::{normalize_source_for_plugin_imports, Avm, AvmConfig, AvmValue};
+
+const HELP: &str = "\
+Enter Aura statements or expressions; a line ending in `:` continues until a blank line.
+  :verify   run the prover on every definition entered so far
+  :defs     show the accumulated program
+  :reset    forget all definitions and values
+  :help     show this help";
+
+/// Something an input bound at the top level.
+#[derive(Clone, Debug)]
+pub struct ReplBinding {
+    /// `val`, `cell`, `type` or `import`.
+    pub kind: &'static str,
+    pub name: String,
+    /// Refined type of a `val`.
+    pub ty: Option<Type>,
+    /// Value of a `val` after the input ran.
+    pub value: Option<AvmValue>,
+}
+
+/// Outcome of one REPL input.
+#[derive(Clone, Debug)]
+pub enum ReplReply {
+    /// An expression was evaluated.
+    Value { value: AvmValue, ty: Type, stdout: String },
+    /// The input defined vals, cells or types.
+    Defined { bindings: Vec<ReplBinding>, stdout: String },
+    /// The input ran without defining anything or producing a value.
+    Ran { stdout: String },
+    /// The Z3 gate rejected the input; nothing ran.
+    Rejected { error: String },
+    /// Result of `:verify`.
+    Verified { verified: bool, error: Option<String> },
+    /// Answer to `:defs` or `:help`.
+    Info(String),
+}
+
+impl ReplReply {
+    /// Whether the input passed the Z3 gate (or `:verify` succeeded).
+    pub fn verified(&self) -> bool {
+        !matches!(self, ReplReply::Rejected { .. } | ReplReply::Verified { verified: false, .. })
+    }
+
+    pub fn stdout(&self) -> &str {
+        match self {
+            ReplReply::Value { stdout, .. } | ReplReply::Defined { stdout, .. } | ReplReply::Ran { stdout } => stdout,
+            _ => "",
+        }
+    }
+
+    /// Refined type of an evaluated expression.
+    pub fn ty(&self) -> Option<&Type> {
+        match self {
+            ReplReply::Value { ty, .. } => Some(ty),
+            _ => None,
+        }
+    }
+
+    /// The reply as printed at the prompt, without the captured stdout.
+    pub fn render(&self) -> String {
+        match self {
+            // Statements like `io.println(..)` have nothing worth echoing.
+            ReplReply::Value { value: AvmValue::Unit, .. } => String::new(),
+            ReplReply::Value { value, ty, .. } => format!("{} : {}", value.display(), ty.display()),
+            ReplReply::Defined { bindings, .. } => bindings
+                .iter()
+                .map(|b| match (&b.ty, &b.value) {
+                    (Some(ty), Some(v)) => format!("val {}: {} = {}", b.name, ty.display(), v.display()),
+                    (Some(ty), None) => format!("val {}: {}", b.name, ty.display()),
+                    _ => format!("{} {}", b.kind, b.name),
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            ReplReply::Ran { .. } => String::new(),
+            ReplReply::Rejected { error } => format!("rejected by the Z3 gate: {error}"),
+            ReplReply::Verified { verified: true, .. } => "verified".to_string(),
+            ReplReply::Verified { verified: false, error } => {
+                format!("not verified: {}", error.as_deref().unwrap_or("the prover rejected the program"))
+            }
+            ReplReply::Info(text) => text.clone(),
+        }
+    }
+}
+
+/// A definition entered at the prompt, kept as source so later inputs can be checked and
+/// verified together with it.
+#[derive(Clone, Debug)]
+struct ReplDef {
+    /// `(kind, name)` pairs it binds; a later definition of any of them replaces it.
+    names: Vec<(&'static str, String)>,
+    source: String,
+}
+
+/// A REPL session: one `Avm` kept alive across inputs plus the program defined so far.
+#[derive(Debug)]
+pub struct Repl {
+    cfg: AvmConfig,
+    avm: Avm,
+    defs: Vec<ReplDef>,
+}
+
+impl Repl {
+    /// Starts a session. With `cfg.enable_z3_gate`, every input must verify as part of the
+    /// accumulated program before it runs. The session owns stdin, so the AVM never reads it.
+    pub fn new(cfg: AvmConfig) -> Self {
+        let cfg = AvmConfig { read_stdin: false, ..cfg };
+        Self {
+            avm: Self::session_avm(&cfg),
+            cfg,
+            defs: Vec::new(),
+        }
+    }
+
+    // Inputs are fragments that only check together with earlier definitions, so the
+    // session runs the gate itself on the accumulated program.
+    fn session_avm(cfg: &AvmConfig) -> Avm {
+        Avm::new(AvmConfig {
+            enable_z3_gate: false,
+            ..cfg.clone()
+        })
+    }
+
+    /// Whether `input` opened a block (a line ending in `:`) that no blank line has closed
+    /// yet, so a terminal should keep reading.
+    pub fn is_incomplete(input: &str) -> bool {
+        let opens_block = input
+            .lines()
+            .any(|l| l.trim_end().ends_with(':') && !l.trim_start().starts_with(':'));
+        opens_block && !input.lines().last().is_some_and(|l| l.trim().is_empty())
+    }
+
+    /// Every definition entered so far, as one program.
+    pub fn program_source(&self) -> String {
+        self.defs.iter().map(|d| d.source.as_str()).collect::<Vec<_>>().join("\n")
+    }
+
+    /// Runs one input: a `:command`, or Aura source checked against the definitions before it.
+    pub fn eval(&mut self, input: &str) -> miette::Result<ReplReply> {
+        if let Some(cmd) = input.trim().strip_prefix(':') {
+            return self.command(cmd.trim());
+        }
+
+        let source = normalize_source_for_plugin_imports(input);
+        let program = aura_parse::parse_source(&source)?;
+        let defined: Vec<ReplDef> = program.stmts.iter().filter_map(|s| definition(s, &source)).collect();
+
+        // Redefining a name replaces its earlier definition.
+        let kept: Vec<ReplDef> = self
+            .defs
+            .iter()
+            .filter(|d| !d.names.iter().any(|n| defined.iter().any(|new| new.names.contains(n))))
+            .cloned()
+            .collect();
+        let prelude = kept.iter().map(|d| format!("{}\n", d.source)).collect::<String>();
+        let candidate = format!("{prelude}{source}");
+
+        let mut checker = Checker::new();
+        let expr_ty = if let [Stmt::ExprStmt(e)] = program.stmts.as_slice() {
+            checker.check_program(&aura_parse::parse_source(&prelude)?).map_err(miette::Report::new)?;
+            Some(checker.infer_top_level_expr(e).map_err(miette::Report::new)?)
+        } else {
+            checker.check_program(&aura_parse::parse_source(&candidate)?).map_err(miette::Report::new)?;
+            None
+        };
+
+        if self.cfg.enable_z3_gate {
+            let gate = self.avm.verify_source(&candidate)?;
+            if !gate.verified {
+                return Ok(ReplReply::Rejected {
+                    error: gate.gate_error.unwrap_or_else(|| "verification failed".to_string()),
+                });
+            }
+        }
+
+        let out = self.avm.exec_source(&source)?;
+        self.defs = kept;
+        self.defs.extend(defined.iter().cloned());
+
+        if let Some(ty) = expr_ty {
+            return Ok(ReplReply::Value { value: out.value, ty, stdout: out.stdout });
+        }
+        if defined.is_empty() {
+            return Ok(ReplReply::Ran { stdout: out.stdout });
+        }
+        let bindings = defined
+            .iter()
+            .flat_map(|d| &d.names)
+            .map(|&(kind, ref name)| {
+                let is_val = kind == "val";
+                ReplBinding {
+                    kind,
+                    name: name.clone(),
+                    ty: checker.top_level_val_type(name).filter(|_| is_val),
+                    value: self.avm.binding(name).cloned().filter(|_| is_val),
+                }
+            })
+            .collect();
+        Ok(ReplReply::Defined { bindings, stdout: out.stdout })
+    }
+
+    fn command(&mut self, cmd: &str) -> miette::Result<ReplReply> {
+        match cmd {
+            "verify" => {
+                let out = self.avm.verify_source(&self.program_source())?;
+                Ok(ReplReply::Verified { verified: out.verified, error: out.gate_error })
+            }
+            "defs" => {
+                let program = self.program_source();
+                Ok(ReplReply::Info(if program.is_empty() { "(no definitions)".to_string() } else { program }))
+            }
+            "reset" => {
+                self.avm = Self::session_avm(&self.cfg);
+                self.defs.clear();
+                Ok(ReplReply::Info("session reset".to_string()))
+            }
+            "help" => Ok(ReplReply::Info(HELP.to_string())),
+            other => Err(miette::miette!("unknown REPL command ':{other}' (try :help)")),
+        }
+    }
+}
+
+/// The source and names of `stmt` if it is a definition later inputs can refer to.
+fn definition(stmt: &Stmt, source: &str) -> Option<ReplDef> {
+    let names = match stmt {
+        Stmt::StrandDef(sd) => match &sd.pattern {
+            Some(p) => {
+                let mut names = Vec::new();
+                pattern_names(p, &mut names);
+                names.into_iter().map(|n| ("val", n)).collect()
+            }
+            None => vec![("val", sd.name.node.clone())],
+        },
+        Stmt::CellDef(c) => vec![("cell", c.name.node.clone())],
+        Stmt::ExternCell(c) => vec![("cell", c.name.node.clone())],
+        Stmt::TypeAlias(t) => vec![("type", t.name.node.clone())],
+        Stmt::RecordDef(r) => vec![("type", r.name.node.clone())],
+        Stmt::EnumDef(e) => vec![("type", e.name.node.clone())],
+        Stmt::TraitDef(t) => vec![("type", t.name.node.clone())],
+        Stmt::MacroDef(m) => vec![("macro", m.name.node.clone())],
+        Stmt::Import(i) => {
+            let path = i.path.iter().map(|p| p.node.as_str()).collect::<Vec<_>>().join("::");
+            vec![("import", path)]
+        }
+        _ => return None,
+    };
+    let span = stmt.span();
+    let text = source.get(span.offset()..span.offset() + span.len())?;
+    Some(ReplDef {
+        names,
+        source: text.trim_end().to_string(),
+    })
+}
+
+fn pattern_names(p: &Pattern, out: &mut Vec<String>) {
+    match p {
+        Pattern::Bind { name, .. } => out.push(name.node.clone()),
+        Pattern::Tuple { elems, .. } => elems.iter().for_each(|e| pattern_names(e, out)),
+        Pattern::Ctor { args, .. } => args.iter().for_each(|a| pattern_names(a, out)),
+        Pattern::Or { alts, .. } => {
+            if let Some(first) = alts.first() {
+                pattern_names(first, out);
+            }
+        }
+        Pattern::Wildcard { .. } | Pattern::IntLit { .. } | Pattern::StringLit { .. } => {}
+    }
+}
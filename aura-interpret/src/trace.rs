@@ -0,0 +1,201 @@
+//! Record/replay of the inputs that make an AVM run nondeterministic.
+//!
+//! A recording logs, as JSON lines, every input the program observes from outside: UI
+//! feedback and polled stdin for each frame of a live UI loop, the results of the
+//! `io.read_line` / `io.read_text` externs and of `fs.read` and `net.recv*` calls, and every
+//! `time.now_ms` reading. Replaying feeds those inputs back in the same order, so the run
+//! takes the same path and reaches the same statements; a debugger can "step back" by
+//! replaying up to an earlier statement count.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{LineWriter, Write};
+use std::path::PathBuf;
+
+use aura_nexus::{UiRuntimeFeedback, UiTextInputEvent};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug)]
+pub enum TraceMode {
+    /// Log every nondeterministic input to this file as the run goes.
+    Record(PathBuf),
+    /// Feed the inputs logged in this file back instead of reading them live. With
+    /// `pause_at_step`, an attached debugger pauses before that statement (counted from 1).
+    Replay { path: PathBuf, pause_at_step: Option<u64> },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub(crate) enum TraceEvent {
+    /// One frame of a live UI loop and what the window reported (`None` when no UI plugin
+    /// rendered it, which ends the loop).
+    UiFrame { feedback: Option<TraceFeedback> },
+    /// Lines the background stdin reader had delivered when a UI frame polled it; polls
+    /// that found nothing are not logged.
+    Stdin { lines: Vec<String> },
+    ReadLine { line: Result<String, String> },
+    ReadText { path: String, text: Result<String, String> },
+    /// A `time.now_ms` reading.
+    Clock { ms: u32 },
+    /// A `time.unix_s` reading.
+    WallClock { s: u32 },
+}
+
+impl TraceEvent {
+    pub(crate) const UI_FRAME: &'static str = "a UI frame";
+    pub(crate) const READ_LINE: &'static str = "io.read_line";
+    pub(crate) const READ_TEXT: &'static str = "io.read_text";
+    pub(crate) const CLOCK: &'static str = "time.now_ms";
+    pub(crate) const WALL_CLOCK: &'static str = "time.unix_s";
+    const STDIN: &'static str = "stdin";
+
+    fn kind(&self) -> &'static str {
+        match self {
+            TraceEvent::UiFrame { .. } => Self::UI_FRAME,
+            TraceEvent::Stdin { .. } => Self::STDIN,
+            TraceEvent::ReadLine { .. } => Self::READ_LINE,
+            TraceEvent::ReadText { .. } => Self::READ_TEXT,
+            TraceEvent::Clock { .. } => Self::CLOCK,
+            TraceEvent::WallClock { .. } => Self::WALL_CLOCK,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct TraceFeedback {
+    close_requested: bool,
+    clicked_callback_id: Option<u64>,
+    text_input: Vec<TraceTextInput>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct TraceTextInput {
+    callback_id: u64,
+    text: String,
+    submitted: bool,
+}
+
+impl From<&UiRuntimeFeedback> for TraceFeedback {
+    fn from(fb: &UiRuntimeFeedback) -> Self {
+        Self {
+            close_requested: fb.close_requested,
+            clicked_callback_id: fb.clicked_callback_id,
+            text_input: fb
+                .text_input_events
+                .iter()
+                .map(|ev| TraceTextInput {
+                    callback_id: ev.callback_id,
+                    text: ev.text.clone(),
+                    submitted: ev.submitted,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<TraceFeedback> for UiRuntimeFeedback {
+    fn from(fb: TraceFeedback) -> Self {
+        Self {
+            close_requested: fb.close_requested,
+            clicked_callback_id: fb.clicked_callback_id,
+            text_input_events: fb
+                .text_input
+                .into_iter()
+                .map(|ev| UiTextInputEvent {
+                    callback_id: ev.callback_id,
+                    text: ev.text,
+                    submitted: ev.submitted,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum Trace {
+    /// Events are flushed line by line, so a run that aborts still leaves its trace.
+    Recording(LineWriter<File>),
+    Replaying {
+        events: VecDeque<TraceEvent>,
+        /// Index of the next event, for divergence errors.
+        next: usize,
+    },
+}
+
+impl Trace {
+    pub(crate) fn open(mode: &TraceMode) -> miette::Result<Self> {
+        match mode {
+            TraceMode::Record(path) => {
+                let file = File::create(path)
+                    .map_err(|e| miette::miette!("failed to create trace {}: {e}", path.display()))?;
+                Ok(Trace::Recording(LineWriter::new(file)))
+            }
+            TraceMode::Replay { path, .. } => {
+                let text = std::fs::read_to_string(path)
+                    .map_err(|e| miette::miette!("failed to read trace {}: {e}", path.display()))?;
+                let events = text
+                    .lines()
+                    .enumerate()
+                    .filter(|(_, l)| !l.trim().is_empty())
+                    .map(|(i, l)| {
+                        serde_json::from_str(l).map_err(|e| {
+                            miette::miette!("invalid trace {} line {}: {e}", path.display(), i + 1)
+                        })
+                    })
+                    .collect::<miette::Result<_>>()?;
+                Ok(Trace::Replaying { events, next: 0 })
+            }
+        }
+    }
+
+    pub(crate) fn is_replaying(&self) -> bool {
+        matches!(self, Trace::Replaying { .. })
+    }
+
+    /// Appends `ev` to a recording; replays ignore it.
+    pub(crate) fn record(&mut self, ev: &TraceEvent) -> miette::Result<()> {
+        let Trace::Recording(out) = self else {
+            return Ok(());
+        };
+        let line = serde_json::to_string(ev).map_err(|e| miette::miette!("failed to encode trace event: {e}"))?;
+        writeln!(out, "{line}").map_err(|e| miette::miette!("failed to write trace: {e}"))
+    }
+
+    /// The next recorded event, which must be of the `wanted` kind (see `TraceEvent::kind`).
+    pub(crate) fn replay(&mut self, wanted: &'static str) -> miette::Result<TraceEvent> {
+        let Trace::Replaying { events, next } = self else {
+            unreachable!("replay called while recording");
+        };
+        let at = *next;
+        match events.pop_front() {
+            Some(ev) if ev.kind() == wanted => {
+                *next += 1;
+                Ok(ev)
+            }
+            Some(ev) => Err(miette::miette!(
+                "AVM: replay diverged at trace event {}: the program asked for {wanted} but the trace has {}",
+                at + 1,
+                ev.kind()
+            )),
+            None => Err(miette::miette!(
+                "AVM: replay trace ended after {at} event(s) but the program asked for {wanted}"
+            )),
+        }
+    }
+
+    /// Stdin lines recorded for the poll about to happen, if any. A polled `Stdin` event
+    /// always comes right before the poll's frame, so it can only be at the front now.
+    pub(crate) fn replay_stdin(&mut self) -> Vec<String> {
+        let Trace::Replaying { events, next } = self else {
+            unreachable!("replay called while recording");
+        };
+        if !matches!(events.front(), Some(TraceEvent::Stdin { .. })) {
+            return Vec::new();
+        }
+        *next += 1;
+        match events.pop_front() {
+            Some(TraceEvent::Stdin { lines }) => lines,
+            _ => unreachable!("front is a Stdin event"),
+        }
+    }
+}
@@ -1,31 +1,166 @@
-use std::collections::{BTreeMap, HashMap};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::PathBuf;
 use std::{fs, io};
-use std::sync::mpsc;
+use std::sync::{mpsc, Arc, Mutex};
 use std::time::Instant;
 
 use std::io::{BufReader, Cursor};
 
-use aura_ast::{BinOp, CallArg, Expr, ExprKind, MatchStmt, Pattern, Program, Span, Stmt, UnaryOp};
-use aura_nexus::{take_ui_feedback, NexusContext, UiNode, UiPluginDispatch};
+use aura_ast::{BinOp, CallArg, Expr, ExprKind, ExternCell, InterpPart, MatchStmt, Pattern, Program, Span, Stmt};
+use aura_core::NetworkVerifier;
+use aura_nexus::{take_ui_feedback, NexusContext, UiNode, UiPluginDispatch, UiRuntimeFeedback};
+use aura_rt_native::{ChannelTable, Clock, FileTable, ScopeTable, SocketTable};
+use aura_sdk::fs_policy::FsPolicy;
 
 use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink};
 
+use crate::coverage::Coverage;
 use crate::debug::{
-    DebugEvent, DebugSession, DebugWatchValue, PerfReport, PerfTimelineEvent, ParsedExpr,
+    DebugCommand, DebugEvent, DebugSession, DebugValue, DebugWatchValue, PerfReport, PerfTimelineEvent, ParsedExpr,
 };
+use crate::trace::{Trace, TraceEvent, TraceMode};
+
+mod ai;
+mod bytecode;
+mod chan;
+mod file;
+mod flow;
+mod hw;
+mod inspect;
+mod math;
+mod net;
+mod profile;
+mod region;
+mod reload;
+mod scope;
+mod string;
+mod time;
+
+use ai::AiSession;
+pub use ai::{InferenceRuntime, RuntimeTensor};
+use bytecode::{eval_unary, Chunk};
+pub use flow::FlowScheduling;
+pub use hw::{HwEmulation, HwScript, HwScriptedReads, HwStore, HwWriteHook};
+use hw::HwDevice;
+use flow::Tasks;
+use inspect::{Inspector, PausedFrame};
+pub use profile::Profile;
+pub use reload::{HotReload, PrepareSourceFn};
+use reload::Reloader;
+
+/// Why a run stopped early. The `Avm` keeps the state it reached (bindings via
+/// `env_snapshot`, output via `stdout`) for inspection.
+#[derive(Clone, Debug, thiserror::Error, miette::Diagnostic)]
+pub enum AvmTerminated {
+    /// A debugger asked the run to stop.
+    #[error("avm: terminated")]
+    #[diagnostic(code(aura::avm::terminated))]
+    Cancelled,
+    /// The run used up a budget set in `AvmConfig`.
+    #[error("avm: {resource} limit of {limit} exceeded ({used} used)")]
+    #[diagnostic(code(aura::avm::resource_limit))]
+    ResourceLimit { resource: AvmResource, limit: u64, used: u64 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AvmResource {
+    /// Statements executed plus cells called (`AvmConfig::fuel`).
+    Fuel,
+    /// Estimated bytes held by bindings and captured output (`AvmConfig::max_memory_bytes`).
+    Memory,
+}
 
-#[derive(Debug, thiserror::Error, miette::Diagnostic)]
-#[error("avm: terminated")]
-#[diagnostic(code(aura::avm::terminated))]
-pub struct AvmTerminated;
+impl std::fmt::Display for AvmResource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            AvmResource::Fuel => "fuel",
+            AvmResource::Memory => "memory",
+        })
+    }
+}
 #[derive(Clone, Debug, PartialEq)]
 pub enum AvmValue {
     Int(i64),
+    Float(f64),
     Bool(bool),
     Str(String),
     Style(BTreeMap<String, AvmValue>),
     Ui(UiNode),
+    Tuple(Vec<AvmValue>),
+    List(Vec<AvmValue>),
+    // Insertion-ordered entries; keys are unique.
+    Map(Vec<(AvmValue, AvmValue)>),
     Unit,
+    /// A spawned `~>` flow nobody has awaited yet; reading a binding that holds one awaits it.
+    Task(u64),
+}
+
+impl AvmValue {
+    /// Renders the value the way the interpreter prints results.
+    pub fn display(&self) -> String {
+        match self {
+            AvmValue::Int(i) => i.to_string(),
+            AvmValue::Float(f) => f.to_string(),
+            AvmValue::Bool(b) => b.to_string(),
+            AvmValue::Str(s) => s.clone(),
+            AvmValue::Style(map) => {
+                let mut out = String::from("Style{");
+                for (i, (k, vv)) in map.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    out.push_str(k);
+                    out.push(':');
+                    out.push_str(&vv.display());
+                }
+                out.push('}');
+                out
+            }
+            AvmValue::Ui(node) => aura_nexus::format_ui_tree(node),
+            AvmValue::Tuple(elems) => format!(
+                "({})",
+                elems.iter().map(AvmValue::display).collect::<Vec<_>>().join(", ")
+            ),
+            AvmValue::List(elems) => format!(
+                "[{}]",
+                elems.iter().map(AvmValue::display).collect::<Vec<_>>().join(", ")
+            ),
+            AvmValue::Map(entries) => format!(
+                "{{{}}}",
+                entries
+                    .iter()
+                    .map(|(k, v)| format!("{}: {}", k.display(), v.display()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            AvmValue::Unit => "()".to_string(),
+            AvmValue::Task(id) => format!("<task {id}>"),
+        }
+    }
+}
+
+impl From<&AvmValue> for DebugValue {
+    fn from(v: &AvmValue) -> Self {
+        let all = |vals: &[AvmValue]| vals.iter().map(DebugValue::from).collect();
+        match v {
+            AvmValue::Int(i) => DebugValue::Int { value: *i },
+            AvmValue::Float(f) => DebugValue::Float { value: *f },
+            AvmValue::Bool(b) => DebugValue::Bool { value: *b },
+            AvmValue::Str(s) => DebugValue::Str { value: s.clone() },
+            AvmValue::Style(map) => DebugValue::Record {
+                fields: map.iter().map(|(k, v)| (k.clone(), v.into())).collect(),
+            },
+            AvmValue::Ui(node) => DebugValue::Ui { kind: node.kind.clone() },
+            AvmValue::Tuple(elems) => DebugValue::Tuple { elems: all(elems) },
+            AvmValue::List(elems) => DebugValue::List { elems: all(elems) },
+            AvmValue::Map(entries) => DebugValue::Map {
+                entries: entries.iter().map(|(k, v)| (k.into(), v.into())).collect(),
+            },
+            AvmValue::Unit => DebugValue::Unit,
+            AvmValue::Task(id) => DebugValue::Task { id: *id },
+        }
+    }
 }
 
 fn stmt_kind_name(stmt: &Stmt) -> &'static str {
@@ -40,6 +175,8 @@ fn stmt_kind_name(stmt: &Stmt) -> &'static str {
         Stmt::CellDef(_) => "CellDef",
         Stmt::ExternCell(_) => "ExternCell",
         Stmt::UnsafeBlock(_) => "UnsafeBlock",
+        Stmt::Scope(_) => "Scope",
+        Stmt::Region(_) => "Region",
         Stmt::Layout(_) => "Layout",
         Stmt::Render(_) => "Render",
         Stmt::Prop(_) => "Prop",
@@ -47,6 +184,11 @@ fn stmt_kind_name(stmt: &Stmt) -> &'static str {
         Stmt::If(_) => "If",
         Stmt::Match(_) => "Match",
         Stmt::While(_) => "While",
+        Stmt::For(_) => "For",
+        Stmt::Break(_) => "Break",
+        Stmt::Continue(_) => "Continue",
+        Stmt::Return(_) => "Return",
+        Stmt::Defer(_) => "Defer",
         Stmt::Requires(_) => "Requires",
         Stmt::Ensures(_) => "Ensures",
         Stmt::Assert(_) => "Assert",
@@ -60,6 +202,7 @@ fn stmt_kind_name(stmt: &Stmt) -> &'static str {
 fn estimate_value_bytes(v: &AvmValue) -> u64 {
     match v {
         AvmValue::Int(_) => 8,
+        AvmValue::Float(_) => 8,
         AvmValue::Bool(_) => 1,
         AvmValue::Str(s) => s.len() as u64,
         AvmValue::Style(m) => m
@@ -67,7 +210,13 @@ fn estimate_value_bytes(v: &AvmValue) -> u64 {
             .map(|(k, vv)| k.len() as u64 + estimate_value_bytes(vv))
             .sum(),
         AvmValue::Ui(_) => 0,
+        AvmValue::Tuple(elems) | AvmValue::List(elems) => elems.iter().map(estimate_value_bytes).sum(),
+        AvmValue::Map(entries) => entries
+            .iter()
+            .map(|(k, vv)| estimate_value_bytes(k) + estimate_value_bytes(vv))
+            .sum(),
         AvmValue::Unit => 0,
+        AvmValue::Task(_) => 8,
     }
 }
 
@@ -75,6 +224,7 @@ fn estimate_value_bytes(v: &AvmValue) -> u64 {
 struct LiveMemStats {
     values_total: u64,
     ints: u64,
+    floats: u64,
     bools: u64,
     strs: u64,
     styles: u64,
@@ -87,6 +237,7 @@ fn collect_live_mem(stats: &mut LiveMemStats, v: &AvmValue) {
     stats.values_total += 1;
     match v {
         AvmValue::Int(_) => stats.ints += 1,
+        AvmValue::Float(_) => stats.floats += 1,
         AvmValue::Bool(_) => stats.bools += 1,
         AvmValue::Str(s) => {
             stats.strs += 1;
@@ -101,7 +252,19 @@ fn collect_live_mem(stats: &mut LiveMemStats, v: &AvmValue) {
             }
         }
         AvmValue::Ui(_) => {}
+        AvmValue::Tuple(elems) | AvmValue::List(elems) => {
+            for vv in elems {
+                collect_live_mem(stats, vv);
+            }
+        }
+        AvmValue::Map(entries) => {
+            for (k, vv) in entries {
+                collect_live_mem(stats, k);
+                collect_live_mem(stats, vv);
+            }
+        }
         AvmValue::Unit => {}
+        AvmValue::Task(_) => {}
     }
 }
 
@@ -120,6 +283,44 @@ pub struct AvmConfig {
     pub hot_threshold: u64,
     pub smt_profile: aura_verify::SmtProfile,
     pub debug: Option<DebugSession>,
+    /// Spawn a background stdin reader for interactive demos. Hosts that own stdin
+    /// (e.g. the language server) must disable this.
+    pub read_stdin: bool,
+    /// Record the run's nondeterministic inputs, or replay a recorded run.
+    pub trace: Option<TraceMode>,
+    /// Window input for the frames of live UI loops, in place of a real window: each frame
+    /// acts on the next entry, and a loop ends once the script runs out. UI plugins still
+    /// render the frames (e.g. the headless renderer), but their feedback is ignored.
+    pub ui_script: Option<Vec<UiRuntimeFeedback>>,
+    /// Fuel for one run: each statement executed and each cell called burns one unit.
+    pub fuel: Option<u64>,
+    /// Cap on the estimated bytes held by bindings and captured output during a run.
+    pub max_memory_bytes: Option<u64>,
+    /// Shared libraries that calls to `extern cell`s go to (e.g.
+    /// `aura_bridge::LinkInputs::runtime_libraries`). Needs the `ffi` feature.
+    pub native_libs: Vec<PathBuf>,
+    /// How spawned `~>` flows are scheduled.
+    pub flows: FlowScheduling,
+    /// Collect statement and branch coverage (see `Avm::coverage`).
+    pub coverage: bool,
+    /// Re-read the program's file between frames of live UI loops and swap in what changed.
+    pub hot_reload: Option<HotReload>,
+    /// Time cell calls (see `Avm::profile`). Runs under a debugger with perf enabled are
+    /// profiled either way.
+    pub profile: bool,
+    /// Profile only every Nth frame of live UI loops; the others are left out of the profile.
+    pub profile_frame_interval: Option<u32>,
+    /// Run cell and UI loop bodies from bytecode where they compile. When off, everything
+    /// runs on the tree-walker, which the bytecode has to agree with.
+    pub bytecode: bool,
+    /// Run `hw.*` calls against emulated hardware instead of rejecting them.
+    pub hw: Option<HwEmulation>,
+    /// Runs the models of `ai.*` calls. When unset, the ONNX Runtime library among
+    /// `native_libs` is used (needs the `ffi` feature).
+    pub inference: Option<Arc<dyn InferenceRuntime>>,
+    /// Paths `fs.*` calls may open, from the `[fs]` table of aura.toml (see
+    /// `aura_sdk::fs_policy::load_fs_policy`). `None` allows every path.
+    pub fs_policy: Option<FsPolicy>,
 }
 
 impl Default for AvmConfig {
@@ -129,6 +330,21 @@ impl Default for AvmConfig {
             hot_threshold: 50,
             smt_profile: aura_verify::SmtProfile::Ci,
             debug: None,
+            read_stdin: true,
+            trace: None,
+            ui_script: None,
+            fuel: None,
+            max_memory_bytes: None,
+            native_libs: Vec::new(),
+            flows: FlowScheduling::default(),
+            coverage: false,
+            hot_reload: None,
+            profile: false,
+            profile_frame_interval: None,
+            bytecode: true,
+            hw: None,
+            inference: None,
+            fs_policy: None,
         }
     }
 }
@@ -144,6 +360,15 @@ pub struct Avm {
     // State
     env: HashMap<String, AvmValue>,
 
+    // User cells defined so far, callable by name.
+    cells: HashMap<String, Arc<UserCell>>,
+    // Extern cells declared so far, called into `cfg.native_libs`.
+    externs: HashMap<String, Arc<ExternCell>>,
+    // Lexical `unsafe` blocks around the running statement; untrusted externs need one.
+    unsafe_depth: u32,
+    #[cfg(feature = "ffi")]
+    native: Option<crate::ffi::NativeLibs>,
+
     // Frame-local callback registry used by UI runtimes.
     next_callback_id: u64,
     callbacks: HashMap<u64, aura_ast::Block>,
@@ -171,6 +396,70 @@ pub struct Avm {
     stdin_rx: Option<mpsc::Receiver<String>>,
 
     debug: Option<DebugSession>,
+
+    // Pending `break`/`continue`/`return`, unwinding blocks up to the loop or cell it leaves.
+    jump: Option<Jump>,
+
+    // Opened by the first run when `cfg.trace` is set.
+    trace: Option<Trace>,
+
+    // Statements reached under the debugger, reported with each stop.
+    steps: u64,
+
+    // Scripted window input still to deliver, and the frames rendered under the script.
+    ui_script: Option<VecDeque<UiRuntimeFeedback>>,
+    ui_frames: Vec<UiNode>,
+
+    // Fuel burned by the current run.
+    fuel_used: u64,
+
+    // `~>` tasks spawned by the current run.
+    tasks: Tasks,
+
+    // Present when `cfg.coverage` is set; accumulates over runs of the same source.
+    coverage: Option<Coverage>,
+
+    // Present when `cfg.hot_reload` is set; watches the file of the running program.
+    reload: Option<Reloader>,
+
+    // Started on first use once profiling is on; covers the current or latest run.
+    profile: Option<Profile>,
+
+    // Emulated hardware, set up by the first `hw.*` call when `cfg.hw` is set.
+    hw: Option<HwDevice>,
+
+    // Tensors and models created by `tensor.*` and `ai.*` calls.
+    ai: AiSession,
+
+    // Channels created by `chan.*` calls, shared with the workers running this run's flows.
+    channels: Arc<ChannelTable<AvmValue>>,
+
+    // Cancellation tokens of `scope` blocks, shared like `channels`.
+    scopes: Arc<ScopeTable>,
+
+    // The clock `time.*` calls read, shared like `channels`.
+    clock: Clock,
+
+    // Files opened by `fs.*` calls, shared like `channels`.
+    files: Arc<FileTable>,
+
+    // Connections made by `net.*` calls and the state each is in, shared like `channels`.
+    sockets: Arc<SocketTable>,
+    socket_states: Arc<Mutex<NetworkVerifier>>,
+}
+
+/// A user cell, with its body compiled to bytecode when the body allows it.
+#[derive(Debug)]
+struct UserCell {
+    def: aura_ast::CellDef,
+    chunk: Option<Chunk>,
+}
+
+#[derive(Clone, Debug)]
+enum Jump {
+    Break,
+    Continue,
+    Return(AvmValue),
 }
 
 struct AudioState {
@@ -656,27 +945,38 @@ impl Avm {
 impl Avm {
     pub fn new(cfg: AvmConfig) -> Self {
         let debug = cfg.debug.clone();
-
-        let (tx, rx) = mpsc::channel::<String>();
-        std::thread::spawn(move || {
-            let stdin = io::stdin();
-            loop {
-                let mut line = String::new();
-                if stdin.read_line(&mut line).is_err() {
-                    break;
-                }
-                while line.ends_with(['\n', '\r']) {
-                    line.pop();
-                }
-                if tx.send(line).is_err() {
-                    break;
+        let ui_script = cfg.ui_script.clone().map(VecDeque::from);
+
+        // A replay takes its stdin lines from the trace.
+        let replaying = matches!(cfg.trace, Some(TraceMode::Replay { .. }));
+        let stdin_rx = (cfg.read_stdin && !replaying).then(|| {
+            let (tx, rx) = mpsc::channel::<String>();
+            std::thread::spawn(move || {
+                let stdin = io::stdin();
+                loop {
+                    let mut line = String::new();
+                    if !matches!(stdin.read_line(&mut line), Ok(n) if n > 0) {
+                        break;
+                    }
+                    while line.ends_with(['\n', '\r']) {
+                        line.pop();
+                    }
+                    if tx.send(line).is_err() {
+                        break;
+                    }
                 }
-            }
+            });
+            rx
         });
 
         Self {
             cfg,
             env: HashMap::new(),
+            cells: HashMap::new(),
+            externs: HashMap::new(),
+            unsafe_depth: 0,
+            #[cfg(feature = "ffi")]
+            native: None,
             next_callback_id: 1,
             callbacks: HashMap::new(),
             verify_cache: HashMap::new(),
@@ -686,23 +986,163 @@ impl Avm {
             ui_event_text: String::new(),
             ui_text_state: HashMap::new(),
             audio: None,
-            stdin_rx: Some(rx),
+            stdin_rx,
             debug,
+            jump: None,
+            trace: None,
+            steps: 0,
+            ui_script,
+            ui_frames: Vec::new(),
+            fuel_used: 0,
+            tasks: Tasks::default(),
+            coverage: None,
+            reload: None,
+            profile: None,
+            hw: None,
+            ai: AiSession::default(),
+            channels: Arc::default(),
+            scopes: Arc::default(),
+            clock: Clock::new(),
+            files: Arc::default(),
+            sockets: Arc::default(),
+            socket_states: Arc::default(),
+        }
+    }
+
+    /// Output captured so far, including that of a run that failed or hit a limit.
+    pub fn stdout(&self) -> &str {
+        &self.stdout
+    }
+
+    /// Fuel burned by the latest run.
+    pub fn fuel_used(&self) -> u64 {
+        self.fuel_used
+    }
+
+    /// Coverage of the runs so far, if `AvmConfig::coverage` is set.
+    pub fn coverage(&self) -> Option<&Coverage> {
+        self.coverage.as_ref()
+    }
+
+    /// Cell-level profile of the latest run, if it was profiled.
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+
+    /// The profile of the current run, started on first use once profiling is on.
+    fn profiler(&mut self) -> Option<&mut Profile> {
+        if self.profile.is_none() {
+            let debug_interval = match &self.debug {
+                Some(sess) if sess.perf_enabled() => Some(sess.perf_frame_interval()),
+                _ => None,
+            };
+            if debug_interval.is_none() && !self.cfg.profile {
+                return None;
+            }
+            let interval = debug_interval.flatten().or(self.cfg.profile_frame_interval);
+            self.profile = Some(Profile::new(interval));
+        }
+        self.profile.as_mut()
+    }
+
+    /// Burns one unit of fuel and, every so often, checks the memory cap.
+    fn burn_fuel(&mut self) -> miette::Result<()> {
+        self.fuel_used += 1;
+        if let Some(limit) = self.cfg.fuel
+            && self.fuel_used > limit
+        {
+            return Err(miette::Report::new(AvmTerminated::ResourceLimit {
+                resource: AvmResource::Fuel,
+                limit,
+                used: self.fuel_used,
+            }));
+        }
+        if self.fuel_used.is_multiple_of(64) {
+            self.check_memory(None)?;
+        }
+        Ok(())
+    }
+
+    /// Checks the memory cap against everything the run holds, or just against `stored`
+    /// when a value is about to be bound (which catches one huge value between the
+    /// periodic full checks).
+    fn check_memory(&self, stored: Option<&AvmValue>) -> miette::Result<()> {
+        let Some(limit) = self.cfg.max_memory_bytes else {
+            return Ok(());
+        };
+        let used = match stored {
+            Some(v) => estimate_value_bytes(v),
+            None => {
+                let env: u64 = self
+                    .env
+                    .iter()
+                    .map(|(k, v)| k.len() as u64 + estimate_value_bytes(v))
+                    .sum();
+                env + self.stdout.len() as u64
+            }
+        };
+        if used > limit {
+            return Err(miette::Report::new(AvmTerminated::ResourceLimit {
+                resource: AvmResource::Memory,
+                limit,
+                used,
+            }));
+        }
+        Ok(())
+    }
+
+    /// Queues window input for upcoming UI frames, as with `AvmConfig::ui_script`.
+    pub fn script_ui_frames(&mut self, frames: impl IntoIterator<Item = UiRuntimeFeedback>) {
+        self.ui_script.get_or_insert_with(VecDeque::new).extend(frames);
+    }
+
+    /// UI trees of the frames rendered while a UI script was active, oldest first.
+    pub fn scripted_frames(&self) -> &[UiNode] {
+        &self.ui_frames
+    }
+
+    fn open_trace(&mut self) -> miette::Result<()> {
+        if let Some(mode) = &self.cfg.trace
+            && self.trace.is_none()
+        {
+            self.trace = Some(Trace::open(mode)?);
+        }
+        Ok(())
+    }
+
+    fn record(&mut self, ev: TraceEvent) -> miette::Result<()> {
+        match &mut self.trace {
+            Some(trace) => trace.record(&ev),
+            None => Ok(()),
         }
     }
 
-    fn poll_shop_stdin(&mut self) {
+    fn poll_shop_stdin(&mut self) -> miette::Result<()> {
+        let lines = match &mut self.trace {
+            Some(trace) if trace.is_replaying() => trace.replay_stdin(),
+            _ => self.drain_stdin(),
+        };
+        if lines.is_empty() {
+            return Ok(());
+        }
+        self.record(TraceEvent::Stdin { lines: lines.clone() })?;
+        for line in lines {
+            // Only consume lines when we're in an add/edit flow.
+            if self.shop.is_pending() {
+                self.shop.consume_input_line(&line);
+            }
+        }
+        Ok(())
+    }
+
+    fn drain_stdin(&mut self) -> Vec<String> {
+        let mut lines = Vec::new();
         let Some(rx) = &self.stdin_rx else {
-            return;
+            return lines;
         };
         loop {
             match rx.try_recv() {
-                Ok(line) => {
-                    // Only consume lines when we're in an add/edit flow.
-                    if self.shop.is_pending() {
-                        self.shop.consume_input_line(&line);
-                    }
-                }
+                Ok(line) => lines.push(line),
                 Err(mpsc::TryRecvError::Empty) => break,
                 Err(mpsc::TryRecvError::Disconnected) => {
                     self.stdin_rx = None;
@@ -710,6 +1150,7 @@ impl Avm {
                 }
             }
         }
+        lines
     }
 
     fn builtin_io_read_line(&mut self, prompt: &str) -> miette::Result<AvmValue> {
@@ -720,21 +1161,120 @@ impl Avm {
             }
         }
 
-        // Note: blocks the render loop while waiting.
-        let mut line = String::new();
-        io::stdin()
-            .read_line(&mut line)
-            .map_err(|e| miette::miette!("failed to read stdin: {e}"))?;
-        while line.ends_with(['\n', '\r']) {
-            line.pop();
+        let line = if let Some(trace) = &mut self.trace
+            && trace.is_replaying()
+        {
+            let TraceEvent::ReadLine { line } = trace.replay(TraceEvent::READ_LINE)? else {
+                unreachable!("replay returns the requested kind");
+            };
+            line
+        } else if let Some(rx) = &self.stdin_rx {
+            // The stdin thread owns the input; take its next line so the two never race.
+            // Note: blocks the render loop while waiting.
+            let read = Ok(rx.recv().unwrap_or_default());
+            self.record(TraceEvent::ReadLine { line: read.clone() })?;
+            read
+        } else {
+            // Note: blocks the render loop while waiting.
+            let mut line = String::new();
+            let read = io::stdin()
+                .read_line(&mut line)
+                .map(|_| {
+                    while line.ends_with(['\n', '\r']) {
+                        line.pop();
+                    }
+                    line
+                })
+                .map_err(|e| format!("failed to read stdin: {e}"));
+            self.record(TraceEvent::ReadLine { line: read.clone() })?;
+            read
+        };
+        line.map(AvmValue::Str).map_err(|e| miette::miette!("{e}"))
+    }
+
+    fn builtin_io_read_text(&mut self, path: &str) -> miette::Result<AvmValue> {
+        let text = if let Some(trace) = &mut self.trace
+            && trace.is_replaying()
+        {
+            let TraceEvent::ReadText { text, .. } = trace.replay(TraceEvent::READ_TEXT)? else {
+                unreachable!("replay returns the requested kind");
+            };
+            text
+        } else {
+            let read = fs::read_to_string(path).map_err(|e| format!("failed to read {path}: {e}"));
+            self.record(TraceEvent::ReadText {
+                path: path.to_string(),
+                text: read.clone(),
+            })?;
+            read
+        };
+        text.map(AvmValue::Str).map_err(|e| miette::miette!("{e}"))
+    }
+
+    /// Renders one frame of a live UI loop and returns what the window reported, or `None`
+    /// if no UI plugin rendered it. Replays and UI scripts still render when they can, but
+    /// act on the recorded or scripted feedback.
+    fn ui_frame(
+        &mut self,
+        node: &UiNode,
+        ui_plugins: &dyn UiPluginDispatch,
+        nexus: &mut NexusContext,
+    ) -> miette::Result<Option<UiRuntimeFeedback>> {
+        let live = match ui_plugins.try_ui_render(node, nexus) {
+            Some(res) => {
+                res.map_err(|d| error_at(d.span, d.message))?;
+                Some(take_ui_feedback(nexus))
+            }
+            None => None,
+        };
+        if let Some(trace) = &mut self.trace
+            && trace.is_replaying()
+        {
+            let TraceEvent::UiFrame { feedback } = trace.replay(TraceEvent::UI_FRAME)? else {
+                unreachable!("replay returns the requested kind");
+            };
+            return Ok(feedback.map(UiRuntimeFeedback::from));
         }
-        Ok(AvmValue::Str(line))
+        let feedback = match &mut self.ui_script {
+            Some(script) => {
+                self.ui_frames.push(node.clone());
+                script.pop_front()
+            }
+            None => live,
+        };
+        self.record(TraceEvent::UiFrame {
+            feedback: feedback.as_ref().map(Into::into),
+        })?;
+        Ok(feedback)
     }
 
-    fn builtin_io_read_text(&self, path: &str) -> miette::Result<AvmValue> {
-        let s = fs::read_to_string(path)
-            .map_err(|e| miette::miette!("failed to read {path}: {e}"))?;
-        Ok(AvmValue::Str(s))
+    /// Applies an edit of the program's file between frames of a live UI loop, returning the
+    /// new span and body of the running block (at `running`) if the edit changed them.
+    fn hot_reload(&mut self, running: Span) -> Option<(Span, aura_ast::Block)> {
+        let reload = match self.reload.as_mut()?.poll(running) {
+            Ok(reload) => reload?,
+            Err(error) => {
+                match &self.debug {
+                    Some(sess) => sess.emit(DebugEvent::Reloaded {
+                        units: Vec::new(),
+                        error: Some(error),
+                    }),
+                    None => eprintln!("hot reload: {error}"),
+                }
+                return None;
+            }
+        };
+        for &idx in &reload.cells {
+            self.define_cell(&reload.program.stmts[idx]);
+        }
+        if let Some(sess) = &self.debug {
+            sess.set_source("<reload>".to_string(), &reload.text);
+            sess.emit(DebugEvent::Reloaded {
+                units: reload.changed,
+                error: None,
+            });
+        }
+        reload.running
     }
 
     fn builtin_io_write_text(&self, path: &str, text: &str) -> miette::Result<AvmValue> {
@@ -1160,6 +1700,8 @@ impl Avm {
             Stmt::CellDef(x) => x.span,
             Stmt::ExternCell(x) => x.span,
             Stmt::UnsafeBlock(x) => x.span,
+            Stmt::Scope(x) => x.span,
+            Stmt::Region(x) => x.span,
             Stmt::Layout(x) => x.span,
             Stmt::Render(x) => x.span,
             Stmt::Prop(x) => x.span,
@@ -1167,6 +1709,11 @@ impl Avm {
             Stmt::If(x) => x.span,
             Stmt::Match(x) => x.span,
             Stmt::While(x) => x.span,
+            Stmt::For(x) => x.span,
+            Stmt::Break(x) => x.span,
+            Stmt::Continue(x) => x.span,
+            Stmt::Return(x) => x.span,
+            Stmt::Defer(x) => x.span,
             Stmt::Requires(x) => x.span,
             Stmt::Ensures(x) => x.span,
             Stmt::Assert(x) => x.span,
@@ -1177,17 +1724,35 @@ impl Avm {
         }
     }
 
+    /// Runs `f` as a frame of its own for the debugger: a cell body, a UI callback or a
+    /// trailing block, which step-over runs through and step-out leaves.
+    fn debug_frame<T>(&mut self, f: impl FnOnce(&mut Self) -> miette::Result<T>) -> miette::Result<T> {
+        let Some(sess) = self.debug.clone() else {
+            return f(self);
+        };
+        sess.enter_frame();
+        let out = f(self);
+        sess.exit_frame();
+        out
+    }
+
     fn debug_stop_if_needed(&mut self, span: Span) -> miette::Result<()> {
         let Some(sess) = self.debug.clone() else {
             return Ok(());
         };
+        self.steps += 1;
+        if let Some(TraceMode::Replay { pause_at_step: Some(at), .. }) = &self.cfg.trace
+            && *at == self.steps
+        {
+            sess.apply_command(DebugCommand::Pause);
+        }
 
         // Terminate is handled as a cooperative cancel: stop at statement boundaries.
         if sess.take_terminate_requested() {
             sess.emit(DebugEvent::Terminated {
                 target: "devvm".to_string(),
             });
-            return Err(miette::Report::new(AvmTerminated));
+            return Err(miette::Report::new(AvmTerminated::Cancelled));
         }
 
         let stop_reason = sess.should_stop_before_stmt(span, |expr| {
@@ -1209,6 +1774,13 @@ impl Avm {
         for (k, v) in &self.env {
             env.insert(k.clone(), format!("{v:?}"));
         }
+        let mut inspector = Inspector::default();
+        let mut names: Vec<&String> = self.env.keys().collect();
+        names.sort();
+        let variables = names
+            .into_iter()
+            .map(|k| inspector.variable(k.clone(), &self.env[k]))
+            .collect();
 
         let watches = sess
             .watches()
@@ -1241,16 +1813,19 @@ impl Avm {
             col,
             env,
             watches,
+            step: Some(self.steps),
+            task: self.tasks.current(),
+            variables,
         });
 
-        sess.wait_while_paused();
+        sess.wait_while_paused_in(&mut PausedFrame { avm: self, inspector });
 
         // If a terminate came in while paused, unwind now.
         if sess.take_terminate_requested() {
             sess.emit(DebugEvent::Terminated {
                 target: "devvm".to_string(),
             });
-            return Err(miette::Report::new(AvmTerminated));
+            return Err(miette::Report::new(AvmTerminated::Cancelled));
         }
         Ok(())
     }
@@ -1271,63 +1846,20 @@ impl Avm {
         }
     }
 
+    /// Evaluates a debugger `Evaluate` request in the paused frame. The session is detached
+    /// meanwhile so the evaluation cannot stop at a breakpoint of its own.
+    fn eval_paused(&mut self, expr: &Expr) -> Result<DebugValue, String> {
+        let sess = self.debug.take();
+        let out = self.eval_expr_pure(expr);
+        self.debug = sess;
+        out.map(|v| DebugValue::from(&v)).map_err(|e| e.to_string())
+    }
+
     fn reset_frame_callbacks(&mut self) {
         self.next_callback_id = 1;
         self.callbacks.clear();
     }
 
-    fn interpolate_string(&self, s: &str) -> String {
-        // Minimal interpolation: replaces `{ident}` with the current value from `env`.
-        // Unknown identifiers are left unchanged.
-        use std::fmt::Write;
-
-        let mut out = String::with_capacity(s.len());
-        let mut rest = s;
-
-        while let Some(open) = rest.find('{') {
-            // Copy the chunk before `{`.
-            out.push_str(&rest[..open]);
-
-            // Try to parse `{ident}`.
-            let after_open = &rest[open + 1..];
-            if let Some(close_rel) = after_open.find('}') {
-                let key = &after_open[..close_rel];
-                if let Some(v) = self.env.get(key) {
-                    match v {
-                        AvmValue::Int(n) => {
-                            let _ = write!(&mut out, "{n}");
-                        }
-                        AvmValue::Bool(b) => {
-                            let _ = write!(&mut out, "{b}");
-                        }
-                        AvmValue::Str(st) => out.push_str(st),
-                        AvmValue::Style(_) => out.push_str("<style>"),
-                        AvmValue::Ui(_) => out.push_str("<ui>"),
-                        AvmValue::Unit => out.push_str("()"),
-                    }
-
-                    // Advance past the closing `}`.
-                    rest = &after_open[close_rel + 1..];
-                    continue;
-                }
-
-                // Unknown key: preserve the original placeholder.
-                out.push('{');
-                out.push_str(key);
-                out.push('}');
-                rest = &after_open[close_rel + 1..];
-                continue;
-            }
-
-            // No closing `}`: preserve the '{' and continue.
-            out.push('{');
-            rest = after_open;
-        }
-
-        out.push_str(rest);
-        out
-    }
-
     fn register_callback(&mut self, body: &aura_ast::Block) -> u64 {
         let id = self.next_callback_id;
         self.next_callback_id = self.next_callback_id.saturating_add(1);
@@ -1339,6 +1871,57 @@ impl Avm {
         self.env.clone()
     }
 
+    /// Current value of the top-level binding `name`.
+    pub fn binding(&self, name: &str) -> Option<&AvmValue> {
+        self.env.get(name)
+    }
+
+    /// Runs the Z3 gate over `source` without executing it, whether or not the gate is
+    /// enabled for execution.
+    pub fn verify_source(&mut self, source: &str) -> miette::Result<ExecOutcome> {
+        let normalized = normalize_source_for_plugin_imports(source);
+        let (verified, gate_error, gate_span) = self.verify_normalized(&normalized)?;
+        Ok(ExecOutcome {
+            verified,
+            gate_error,
+            gate_span,
+            value: AvmValue::Unit,
+            stdout: String::new(),
+        })
+    }
+
+    fn verify_normalized(&mut self, normalized: &str) -> miette::Result<(bool, Option<String>, Option<Span>)> {
+        let key = stable_hash(normalized);
+        if let Some(v) = self.verify_cache.get(&key).copied() {
+            return Ok((v, None, None));
+        }
+        let (ok, err, span) = verify_source_z3(normalized, self.cfg.smt_profile)?;
+        self.verify_cache.insert(key, ok);
+        Ok((ok, err, span))
+    }
+
+    /// Makes the cells and extern cells `program` defines callable, including from statements
+    /// before them.
+    fn define_cells(&mut self, program: &Program) {
+        for stmt in &program.stmts {
+            self.define_cell(stmt);
+        }
+    }
+
+    fn define_cell(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::CellDef(cell) => {
+                let chunk = Chunk::compile(&cell.body);
+                self.cells
+                    .insert(cell.name.node.clone(), Arc::new(UserCell { def: cell.clone(), chunk }));
+            }
+            Stmt::ExternCell(ext) => {
+                self.externs.insert(ext.name.node.clone(), Arc::new(ext.clone()));
+            }
+            _ => {}
+        }
+    }
+
     pub fn exec_source(&mut self, source: &str) -> miette::Result<ExecOutcome> {
         let mut nexus = NexusContext::default();
         self.exec_source_with_ui_plugins(source, &(), &mut nexus)
@@ -1351,6 +1934,10 @@ impl Avm {
         nexus: &mut NexusContext,
     ) -> miette::Result<ExecOutcome> {
         self.stdout.clear();
+        self.fuel_used = 0;
+        self.profile = None;
+        self.reset_flows();
+        self.open_trace()?;
 
         // Allow REPL-friendly plugin imports like `import aura-ai` even though `-` isn't a valid
         // identifier in the lexer. We normalize these imports to `aura_ai/aura_iot`.
@@ -1360,15 +1947,10 @@ impl Avm {
             sess.set_source("<repl>".to_string(), &normalized);
         }
 
-        let key = stable_hash(&normalized);
-        let (verified, gate_error, gate_span) = if !self.cfg.enable_z3_gate {
-            (true, None, None)
-        } else if let Some(v) = self.verify_cache.get(&key).copied() {
-            (v, None, None)
+        let (verified, gate_error, gate_span) = if self.cfg.enable_z3_gate {
+            self.verify_normalized(&normalized)?
         } else {
-            let (ok, err, span) = verify_source_z3(&normalized, self.cfg.smt_profile)?;
-            self.verify_cache.insert(key, ok);
-            (ok, err, span)
+            (true, None, None)
         };
 
         if !verified {
@@ -1382,16 +1964,28 @@ impl Avm {
         }
 
         let program = aura_parse::parse_source(&normalized)?;
+        self.define_cells(&program);
+        if self.cfg.coverage {
+            self.coverage.get_or_insert_default().register(&normalized, &program);
+        }
+        if let Some(hot) = &self.cfg.hot_reload {
+            self.reload = Some(Reloader::new(hot.clone(), &normalized, &program));
+        }
 
         // Execute program statements in order.
+        let at_source = |e| with_run_source(e, "<repl>", &normalized);
         let mut last = AvmValue::Unit;
         for (idx, stmt) in program.stmts.iter().enumerate() {
-            last = self.exec_stmt(stmt, ui_plugins, nexus)?;
+            last = self.exec_stmt(stmt, ui_plugins, nexus).map_err(at_source)?;
 
             let cell_id = idx as u64;
             let c = self.hot.entry(cell_id).or_insert(0);
             *c += 1;
         }
+        self.join_flows().map_err(at_source)?;
+        if let Some(profile) = &mut self.profile {
+            profile.finish();
+        }
 
         self.debug_finish_run();
 
@@ -1417,6 +2011,10 @@ impl Avm {
         nexus: &mut NexusContext,
     ) -> miette::Result<ExecOutcome> {
         self.stdout.clear();
+        self.fuel_used = 0;
+        self.profile = None;
+        self.reset_flows();
+        self.open_trace()?;
 
         // Keep behavior consistent with `exec_source`.
         let normalized = normalize_source_for_plugin_imports(source);
@@ -1425,15 +2023,10 @@ impl Avm {
             sess.set_source("<entry>".to_string(), &normalized);
         }
 
-        let key = stable_hash(&normalized);
-        let (verified, gate_error, gate_span) = if !self.cfg.enable_z3_gate {
-            (true, None, None)
-        } else if let Some(v) = self.verify_cache.get(&key).copied() {
-            (v, None, None)
+        let (verified, gate_error, gate_span) = if self.cfg.enable_z3_gate {
+            self.verify_normalized(&normalized)?
         } else {
-            let (ok, err, span) = verify_source_z3(&normalized, self.cfg.smt_profile)?;
-            self.verify_cache.insert(key, ok);
-            (ok, err, span)
+            (true, None, None)
         };
 
         if !verified {
@@ -1447,8 +2040,16 @@ impl Avm {
         }
 
         let program = aura_parse::parse_source(&normalized)?;
+        self.define_cells(&program);
+        if self.cfg.coverage {
+            self.coverage.get_or_insert_default().register(&normalized, &program);
+        }
+        if let Some(hot) = &self.cfg.hot_reload {
+            self.reload = Some(Reloader::new(hot.clone(), &normalized, &program));
+        }
 
         // Allow top-level initialization statements.
+        let at_source = |e| with_run_source(e, "<entry>", &normalized);
         let mut last = AvmValue::Unit;
         for stmt in &program.stmts {
             match stmt {
@@ -1458,7 +2059,7 @@ impl Avm {
                 | Stmt::Import(_)
                 | Stmt::TypeAlias(_) => {}
                 other => {
-                    last = self.exec_stmt(other, ui_plugins, nexus)?;
+                    last = self.exec_stmt(other, ui_plugins, nexus).map_err(at_source)?;
                 }
             }
         }
@@ -1476,7 +2077,27 @@ impl Avm {
                     entry
                 ));
             }
-            last = self.exec_block(&cell.body, ui_plugins, nexus)?;
+            if let Some(cov) = &mut self.coverage {
+                cov.hit_cell(entry);
+            }
+            let compiled = self.cells.get(entry).cloned();
+            let chunk = compiled.as_ref().and_then(|c| c.chunk.as_ref());
+            if let Some(profile) = self.profiler() {
+                profile.enter(entry);
+            }
+            last = self
+                .debug_frame(|vm| vm.exec_compiled(chunk, &cell.body, ui_plugins, nexus))
+                .map_err(at_source)?;
+            if let Some(profile) = &mut self.profile {
+                profile.exit();
+            }
+            if let Some(Jump::Return(v)) = self.jump.take() {
+                last = v;
+            }
+        }
+        self.join_flows().map_err(at_source)?;
+        if let Some(profile) = &mut self.profile {
+            profile.finish();
         }
 
         self.debug_finish_run();
@@ -1529,6 +2150,7 @@ impl Avm {
         memory.insert("env_keys_bytes".to_string(), mem.env_keys_bytes);
         memory.insert("values_total".to_string(), mem.values_total);
         memory.insert("values_int".to_string(), mem.ints);
+        memory.insert("values_float".to_string(), mem.floats);
         memory.insert("values_bool".to_string(), mem.bools);
         memory.insert("values_str".to_string(), mem.strs);
         memory.insert("values_style".to_string(), mem.styles);
@@ -1544,6 +2166,8 @@ impl Avm {
                 timeline,
                 flame_folded,
                 memory,
+                cells: self.profile.as_ref().map(Profile::cells).unwrap_or_default(),
+                cell_stacks: self.profile.as_ref().map(Profile::to_collapsed).unwrap_or_default(),
             },
         });
     }
@@ -1556,6 +2180,10 @@ impl Avm {
     ) -> miette::Result<AvmValue> {
         let span = Self::stmt_span(stmt);
         self.debug_stop_if_needed(span)?;
+        self.burn_fuel()?;
+        if let Some(cov) = &mut self.coverage {
+            cov.hit_stmt(span);
+        }
 
         let t0 = Instant::now();
         let out = match stmt {
@@ -1565,10 +2193,31 @@ impl Avm {
             // Macro nodes should not survive parsing (they are expanded there), but ignore defensively.
             Stmt::MacroDef(_) | Stmt::MacroCall(_) => Ok(AvmValue::Unit),
 
-            Stmt::UnsafeBlock(s) => self.exec_block(&s.body, ui_plugins, nexus),
+            Stmt::UnsafeBlock(s) => {
+                self.unsafe_depth += 1;
+                let out = self.exec_block(&s.body, ui_plugins, nexus);
+                self.unsafe_depth -= 1;
+                out
+            }
+
+            Stmt::Scope(s) => self.exec_scope(s, ui_plugins, nexus),
+
+            Stmt::Region(r) => self.exec_region(r, ui_plugins, nexus),
 
             Stmt::StrandDef(sd) => {
-                let v = self.eval_expr(&sd.expr)?;
+                let v = if sd.pattern.is_some() {
+                    self.eval_expr(&sd.expr)?
+                } else {
+                    self.eval_spawning(&sd.expr)?
+                };
+                if let Some(pat) = &sd.pattern {
+                    if !pat_matches_value(pat, &v) {
+                        return Err(miette::miette!("AVM: value does not match destructuring pattern"));
+                    }
+                    self.bind_pattern(pat, &v);
+                    return Ok(AvmValue::Unit);
+                }
+                self.check_memory(Some(&v))?;
                 self.env.insert(sd.name.node.clone(), v.clone());
                 Ok(AvmValue::Unit)
             }
@@ -1576,7 +2225,8 @@ impl Avm {
                 // Layout blocks can yield a UI tree (e.g. `VStack { ... }`).
                 // If a UI plugin is active, tick a live loop so the window stays open.
                 self.reset_frame_callbacks();
-                let v = self.exec_block(&lb.body, ui_plugins, nexus)?;
+                let mut chunk = Chunk::compile(&lb.body);
+                let v = self.exec_compiled(chunk.as_ref(), &lb.body, ui_plugins, nexus)?;
                 let AvmValue::Ui(_) = v else {
                     return Ok(AvmValue::Unit);
                 };
@@ -1587,10 +2237,18 @@ impl Avm {
                     .ok()
                     .and_then(|s| s.parse::<u32>().ok());
                 let mut frames: u32 = 0;
+                let (mut span, mut body) = (lb.span, Cow::Borrowed(&lb.body));
                 loop {
-                    self.poll_shop_stdin();
+                    self.poll_shop_stdin()?;
+                    if let Some((new_span, new_body)) = self.hot_reload(span) {
+                        chunk = Chunk::compile(&new_body);
+                        (span, body) = (new_span, Cow::Owned(new_body));
+                    }
+                    if let Some(profile) = self.profiler() {
+                        profile.next_frame();
+                    }
                     self.reset_frame_callbacks();
-                    let v = self.exec_block(&lb.body, ui_plugins, nexus)?;
+                    let v = self.exec_compiled(chunk.as_ref(), &body, ui_plugins, nexus)?;
                     let AvmValue::Ui(node) = v else {
                         if debug_ui {
                             eprintln!("AURA_UI_DEBUG: layout loop ended (non-UI value)");
@@ -1598,17 +2256,12 @@ impl Avm {
                         break;
                     };
 
-                    let Some(res) = ui_plugins.try_ui_render(&node, nexus) else {
+                    let Some(fb) = self.ui_frame(&node, ui_plugins, nexus)? else {
                         if debug_ui {
                             eprintln!("AURA_UI_DEBUG: layout loop ended (no UI plugin)");
                         }
                         break;
                     };
-                    res.map_err(|d| {
-                        miette::miette!("{}", d.message).with_source_code(source_note(d.span))
-                    })?;
-
-                    let fb = take_ui_feedback(nexus);
                     if debug_ui && frames < 5 {
                         eprintln!(
                             "AURA_UI_DEBUG: frame={} close={} clicked={:?}",
@@ -1636,24 +2289,28 @@ impl Avm {
 
                     if let Some(cb) = fb.clicked_callback_id {
                         if let Some(body) = self.callbacks.get(&cb).cloned() {
-                            let _ = self.exec_block(&body, ui_plugins, nexus)?;
+                            let _ = self.debug_frame(|vm| vm.exec_block(&body, ui_plugins, nexus))?;
                         }
                     }
 
                     for ev in fb.text_input_events {
                         self.ui_set_event_text(ev.text);
                         if let Some(body) = self.callbacks.get(&ev.callback_id).cloned() {
-                            let _ = self.exec_block(&body, ui_plugins, nexus)?;
+                            let _ = self.debug_frame(|vm| vm.exec_block(&body, ui_plugins, nexus))?;
                         }
                     }
                 }
+                if let Some(profile) = &mut self.profile {
+                    profile.end_frames();
+                }
                 Ok(AvmValue::Unit)
             }
             Stmt::Render(rb) => {
                 // Live UI loop: rebuild UI, render a frame, handle click callbacks, repeat.
                 // If no UI plugin is active, execute once and return.
                 self.reset_frame_callbacks();
-                let first = self.exec_block(&rb.body, ui_plugins, nexus)?;
+                let mut chunk = Chunk::compile(&rb.body);
+                let first = self.exec_compiled(chunk.as_ref(), &rb.body, ui_plugins, nexus)?;
                 let AvmValue::Ui(_) = first else {
                     return Ok(AvmValue::Unit);
                 };
@@ -1663,10 +2320,18 @@ impl Avm {
                     .ok()
                     .and_then(|s| s.parse::<u32>().ok());
                 let mut frames: u32 = 0;
+                let (mut span, mut body) = (rb.span, Cow::Borrowed(&rb.body));
                 loop {
-                    self.poll_shop_stdin();
+                    self.poll_shop_stdin()?;
+                    if let Some((new_span, new_body)) = self.hot_reload(span) {
+                        chunk = Chunk::compile(&new_body);
+                        (span, body) = (new_span, Cow::Owned(new_body));
+                    }
+                    if let Some(profile) = self.profiler() {
+                        profile.next_frame();
+                    }
                     self.reset_frame_callbacks();
-                    let v = self.exec_block(&rb.body, ui_plugins, nexus)?;
+                    let v = self.exec_compiled(chunk.as_ref(), &body, ui_plugins, nexus)?;
                     let AvmValue::Ui(node) = v else {
                         if debug_ui {
                             eprintln!("AURA_UI_DEBUG: render loop ended (non-UI value)");
@@ -1674,17 +2339,12 @@ impl Avm {
                         break;
                     };
 
-                    let Some(res) = ui_plugins.try_ui_render(&node, nexus) else {
+                    let Some(fb) = self.ui_frame(&node, ui_plugins, nexus)? else {
                         if debug_ui {
                             eprintln!("AURA_UI_DEBUG: render loop ended (no UI plugin)");
                         }
                         break;
                     };
-                    res.map_err(|d| {
-                        miette::miette!("{}", d.message).with_source_code(source_note(d.span))
-                    })?;
-
-                    let fb = take_ui_feedback(nexus);
                     if debug_ui && frames < 5 {
                         eprintln!(
                             "AURA_UI_DEBUG: frame={} close={} clicked={:?}",
@@ -1712,21 +2372,25 @@ impl Avm {
 
                     if let Some(cb) = fb.clicked_callback_id {
                         if let Some(body) = self.callbacks.get(&cb).cloned() {
-                            let _ = self.exec_block(&body, ui_plugins, nexus)?;
+                            let _ = self.debug_frame(|vm| vm.exec_block(&body, ui_plugins, nexus))?;
                         }
                     }
 
                     for ev in fb.text_input_events {
                         self.ui_set_event_text(ev.text);
                         if let Some(body) = self.callbacks.get(&ev.callback_id).cloned() {
-                            let _ = self.exec_block(&body, ui_plugins, nexus)?;
+                            let _ = self.debug_frame(|vm| vm.exec_block(&body, ui_plugins, nexus))?;
                         }
                     }
                 }
+                if let Some(profile) = &mut self.profile {
+                    profile.end_frames();
+                }
                 Ok(AvmValue::Unit)
             }
             Stmt::Assign(a) => {
-                let v = self.eval_expr(&a.expr)?;
+                let v = self.eval_spawning(&a.expr)?;
+                self.check_memory(Some(&v))?;
                 self.env.insert(a.target.node.clone(), v);
                 Ok(AvmValue::Unit)
             }
@@ -1751,11 +2415,14 @@ impl Avm {
                 if matches!(v, AvmValue::Bool(true)) {
                     Ok(AvmValue::Unit)
                 } else {
-                    Err(miette::miette!("assertion failed")
-                        .with_source_code(source_note(stmt_span(stmt))))
+                    Err(error_at(stmt_span(stmt), "assertion failed"))
                 }
             }
-            Stmt::ExprStmt(e) => self.eval_expr(e),
+            Stmt::ExprStmt(e) => match self.spawn_flow(e)? {
+                // Runs in the background; joined by an await or at the end of the run.
+                Some(_) => Ok(AvmValue::Unit),
+                None => self.eval_expr(e),
+            },
             // DX-focused REPL: ignore declarations not used in scripts.
             Stmt::Import(_)
             | Stmt::TypeAlias(_)
@@ -1767,6 +2434,9 @@ impl Avm {
             Stmt::If(i) => {
                 let c = self.eval_expr(&i.cond)?;
                 let take_then = matches!(c, AvmValue::Bool(true));
+                if let Some(cov) = &mut self.coverage {
+                    cov.hit_branch(span, usize::from(!take_then));
+                }
                 if take_then {
                     self.exec_block(&i.then_block, ui_plugins, nexus)
                 } else if let Some(b) = &i.else_block {
@@ -1782,26 +2452,75 @@ impl Avm {
                 loop {
                     guard += 1;
                     if guard > 5_000_000 {
-                        return Err(
-                            miette::miette!("AVM: loop guard tripped (possible infinite loop)")
-                                .with_source_code(source_note(stmt_span(stmt))),
-                        );
+                        return Err(error_at(stmt_span(stmt), "AVM: loop guard tripped (possible infinite loop)"));
                     }
 
                     let c = self.eval_expr(&w.cond)?;
-                    if !matches!(c, AvmValue::Bool(true)) {
+                    let again = matches!(c, AvmValue::Bool(true));
+                    if let Some(cov) = &mut self.coverage {
+                        cov.hit_branch(span, usize::from(!again));
+                    }
+                    if !again {
                         break;
                     }
                     let _ = self.exec_block(&w.body, ui_plugins, nexus)?;
+                    if self.leave_loop() {
+                        break;
+                    }
                 }
                 Ok(AvmValue::Unit)
             }
+            Stmt::For(f) => {
+                let bound = |v: AvmValue, span: Span| match v {
+                    AvmValue::Int(n) => Ok(n),
+                    _ => Err(error_at(span, "AVM: for range bounds must be integers")),
+                };
+                let start = bound(self.eval_expr(&f.start)?, f.start.span)?;
+                let end = bound(self.eval_expr(&f.end)?, f.end.span)?;
+
+                // The loop variable is scoped to the loop; restore any shadowed binding.
+                let shadowed = self.env.get(&f.var.node).cloned();
+                for i in start..end {
+                    self.env.insert(f.var.node.clone(), AvmValue::Int(i));
+                    let _ = self.exec_block(&f.body, ui_plugins, nexus)?;
+                    if self.leave_loop() {
+                        break;
+                    }
+                }
+                match shadowed {
+                    Some(v) => {
+                        self.env.insert(f.var.node.clone(), v);
+                    }
+                    None => {
+                        self.env.remove(&f.var.node);
+                    }
+                }
+                Ok(AvmValue::Unit)
+            }
+            Stmt::Break(_) => {
+                self.jump = Some(Jump::Break);
+                Ok(AvmValue::Unit)
+            }
+            Stmt::Continue(_) => {
+                self.jump = Some(Jump::Continue);
+                Ok(AvmValue::Unit)
+            }
+            Stmt::Return(r) => {
+                let v = match &r.value {
+                    Some(e) => self.eval_expr(e)?,
+                    None => AvmValue::Unit,
+                };
+                self.jump = Some(Jump::Return(v));
+                Ok(AvmValue::Unit)
+            }
+            // Registered and run by the enclosing `exec_block`.
+            Stmt::Defer(_) => Ok(AvmValue::Unit),
             // All statement variants are handled above.
         };
 
         let dur = t0.elapsed();
         if let Some(sess) = &self.debug {
-            if sess.perf_enabled() {
+            if sess.perf_enabled() && self.profile.as_ref().is_none_or(Profile::sampling) {
                 let (line, col, _file) = sess.line_col(span);
                 sess.perf_push(PerfTimelineEvent {
                     line,
@@ -1810,7 +2529,6 @@ impl Avm {
                     dur_ns: dur.as_nanos() as u64,
                 });
             }
-            sess.note_stmt_executed();
         }
 
         out
@@ -1823,8 +2541,12 @@ impl Avm {
         nexus: &mut NexusContext,
     ) -> miette::Result<AvmValue> {
         let v = self.eval_expr(&m.scrutinee)?;
-        for arm in &m.arms {
+        for (i, arm) in m.arms.iter().enumerate() {
             if pat_matches_value(&arm.pat, &v) {
+                if let Some(cov) = &mut self.coverage {
+                    cov.hit_branch(m.span, i);
+                }
+                self.bind_pattern(&arm.pat, &v);
                 let _ = self.exec_block(&arm.body, ui_plugins, nexus)?;
                 break;
             }
@@ -1832,6 +2554,39 @@ impl Avm {
         Ok(AvmValue::Unit)
     }
 
+    /// Binds the names of a (tuple) pattern that already matched `v`.
+    fn bind_pattern(&mut self, p: &Pattern, v: &AvmValue) {
+        match (p, v) {
+            (Pattern::Bind { name, .. }, _) => {
+                self.env.insert(name.node.clone(), v.clone());
+            }
+            (Pattern::Tuple { elems, .. }, AvmValue::Tuple(vals)) => {
+                for (pp, vv) in elems.iter().zip(vals.iter()) {
+                    self.bind_pattern(pp, vv);
+                }
+            }
+            (Pattern::Or { alts, .. }, _) => {
+                if let Some(alt) = alts.iter().find(|a| pat_matches_value(a, v)) {
+                    self.bind_pattern(alt, v);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Consumes a pending `break`/`continue` at the end of a loop iteration and reports
+    /// whether the loop must stop; a pending `return` stays set and stops it too.
+    fn leave_loop(&mut self) -> bool {
+        match self.jump.take() {
+            None | Some(Jump::Continue) => false,
+            Some(Jump::Break) => true,
+            Some(ret @ Jump::Return(_)) => {
+                self.jump = Some(ret);
+                true
+            }
+        }
+    }
+
     fn exec_block(
         &mut self,
         b: &aura_ast::Block,
@@ -1839,19 +2594,115 @@ impl Avm {
         nexus: &mut NexusContext,
     ) -> miette::Result<AvmValue> {
         let mut last = AvmValue::Unit;
+        let mut deferred: Vec<&aura_ast::Block> = Vec::new();
         for s in &b.stmts {
+            if let Stmt::Defer(d) = s {
+                deferred.push(&d.body);
+                continue;
+            }
             last = self.exec_stmt(s, ui_plugins, nexus)?;
+            if self.jump.is_some() {
+                self.run_deferred(&deferred, ui_plugins, nexus)?;
+                return Ok(AvmValue::Unit);
+            }
         }
         if let Some(e) = &b.yield_expr {
             last = self.eval_expr(e)?;
         }
+        self.run_deferred(&deferred, ui_plugins, nexus)?;
         Ok(last)
     }
 
+    /// Runs `body` from its bytecode when it has some and `cfg.bytecode` is on. Perf timelines
+    /// time each statement, which only the tree-walker records, so profiled runs stay on it.
+    fn exec_compiled(
+        &mut self,
+        chunk: Option<&Chunk>,
+        body: &aura_ast::Block,
+        ui_plugins: &dyn UiPluginDispatch,
+        nexus: &mut NexusContext,
+    ) -> miette::Result<AvmValue> {
+        let profiling = self.debug.as_ref().is_some_and(|s| s.perf_enabled());
+        match chunk {
+            Some(chunk) if self.cfg.bytecode && !profiling => self.run_chunk(chunk, ui_plugins, nexus),
+            _ => self.exec_block(body, ui_plugins, nexus),
+        }
+    }
+
+    /// Runs a block's `defer` bodies, last registered first. A pending jump is held aside
+    /// while they run and restored afterwards.
+    fn run_deferred(
+        &mut self,
+        deferred: &[&aura_ast::Block],
+        ui_plugins: &dyn UiPluginDispatch,
+        nexus: &mut NexusContext,
+    ) -> miette::Result<()> {
+        let pending = self.jump.take();
+        for body in deferred.iter().rev() {
+            let _ = self.exec_block(body, ui_plugins, nexus)?;
+        }
+        self.jump = pending;
+        Ok(())
+    }
+
+    /// The value of `name`, awaiting the task it holds if it is bound to a pending flow.
+    fn read_var(&mut self, name: &str) -> miette::Result<AvmValue> {
+        match self.load_var(name)? {
+            AvmValue::Task(id) => {
+                let v = self.await_task(id)?;
+                self.env.insert(name.to_string(), v.clone());
+                Ok(v)
+            }
+            v => Ok(v),
+        }
+    }
+
+    fn load_var(&self, name: &str) -> miette::Result<AvmValue> {
+        self.env
+            .get(name)
+            .cloned()
+            .or_else(|| match name {
+                // Minimal: allow module-style calls like `io.println(...)` and `shop.count()`
+                // without requiring `val io = "io"` pre-bindings.
+                "io" => Some(AvmValue::Str("io".to_string())),
+                "shop" => Some(AvmValue::Str("shop".to_string())),
+                "ui" => Some(AvmValue::Str("ui".to_string())),
+                "hw" => Some(AvmValue::Str("hw".to_string())),
+                "ai" => Some(AvmValue::Str("ai".to_string())),
+                "tensor" => Some(AvmValue::Str("tensor".to_string())),
+                "chan" => Some(AvmValue::Str("chan".to_string())),
+                "scope" => Some(AvmValue::Str("scope".to_string())),
+                "time" => Some(AvmValue::Str("time".to_string())),
+                "fs" => Some(AvmValue::Str("fs".to_string())),
+                "net" => Some(AvmValue::Str("net".to_string())),
+                "str" => Some(AvmValue::Str("str".to_string())),
+                "math" => Some(AvmValue::Str("math".to_string())),
+                _ => None,
+            })
+            .ok_or_else(|| miette::miette!("AVM: unknown identifier '{}'", name))
+    }
+
     fn eval_expr(&mut self, expr: &Expr) -> miette::Result<AvmValue> {
         match &expr.kind {
+            // What can be updated in place (tensors, files, sockets) is a handle, so a copy
+            // shares it; sema keeps the owner off it while it is borrowed.
+            ExprKind::Borrow { expr: inner, .. } => self.eval_expr(inner),
             ExprKind::IntLit(n) => Ok(AvmValue::Int(*n as i64)),
-            ExprKind::StringLit(s) => Ok(AvmValue::Str(self.interpolate_string(s))),
+            ExprKind::FloatLit(v) => Ok(AvmValue::Float(*v)),
+            ExprKind::StringLit(s) => Ok(AvmValue::Str(s.clone())),
+            ExprKind::InterpolatedString(parts) => {
+                let mut out = String::new();
+                for part in parts {
+                    match part {
+                        InterpPart::Lit(l) => out.push_str(l),
+                        InterpPart::Expr(e) => {
+                            let v = self.eval_expr(e)?;
+                            out.push_str(&avm_value_to_prop_string(&v));
+                        }
+                    }
+                }
+                Ok(AvmValue::Str(out))
+            }
             ExprKind::StyleLit { fields } => {
                 let mut map: BTreeMap<String, AvmValue> = BTreeMap::new();
                 for (k, v) in fields {
@@ -1869,26 +2720,10 @@ impl Avm {
                 // MVP runtime representation: treat records like maps.
                 Ok(AvmValue::Style(map))
             }
-            ExprKind::Ident(id) => self
-                .env
-                .get(&id.node)
-                .cloned()
-                .or_else(|| match id.node.as_str() {
-                    // Minimal: allow module-style calls like `io.println(...)` and `shop.count()`
-                    // without requiring `val io = "io"` pre-bindings.
-                    "io" => Some(AvmValue::Str("io".to_string())),
-                    "shop" => Some(AvmValue::Str("shop".to_string())),
-                    "ui" => Some(AvmValue::Str("ui".to_string())),
-                    _ => None,
-                })
-                .ok_or_else(|| miette::miette!("AVM: unknown identifier '{}'", id.node)),
+            ExprKind::Ident(id) => self.read_var(&id.node),
             ExprKind::Unary { op, expr } => {
                 let v = self.eval_expr(expr)?;
-                match (op, v) {
-                    (UnaryOp::Neg, AvmValue::Int(i)) => Ok(AvmValue::Int(-i)),
-                    (UnaryOp::Not, AvmValue::Bool(b)) => Ok(AvmValue::Bool(!b)),
-                    _ => Err(miette::miette!("AVM: unsupported unary op")),
-                }
+                eval_unary(*op, v)
             }
             ExprKind::Binary { left, op, right } => {
                 let l = self.eval_expr(left)?;
@@ -1904,15 +2739,109 @@ impl Avm {
                 match b {
                     AvmValue::Str(ns) => Ok(AvmValue::Str(format!("{ns}.{}", member.node))),
                     AvmValue::Unit => Ok(AvmValue::Str(member.node.clone())),
-                    AvmValue::Int(_) | AvmValue::Bool(_) => Err(miette::miette!("AVM: member access unsupported")),
+                    AvmValue::Int(_) | AvmValue::Float(_) | AvmValue::Bool(_) => {
+                        Err(miette::miette!("AVM: member access unsupported"))
+                    }
                     AvmValue::Style(map) => map
                         .get(&member.node)
                         .cloned()
                         .ok_or_else(|| miette::miette!("AVM: unknown field '{}'", member.node)),
-                    AvmValue::Ui(_) => Err(miette::miette!("AVM: member access unsupported")),
+                    AvmValue::Ui(_)
+                    | AvmValue::Tuple(_)
+                    | AvmValue::List(_)
+                    | AvmValue::Map(_)
+                    | AvmValue::Task(_) => Err(miette::miette!("AVM: member access unsupported")),
+                }
+            }
+            ExprKind::Tuple(elems) => Ok(AvmValue::Tuple(
+                elems
+                    .iter()
+                    .map(|e| self.eval_expr(e))
+                    .collect::<miette::Result<Vec<_>>>()?,
+            )),
+            ExprKind::TupleIndex { base, index } => match self.eval_expr(base)? {
+                AvmValue::Tuple(mut vals) if (*index as usize) < vals.len() => {
+                    Ok(vals.swap_remove(*index as usize))
+                }
+                other => Err(miette::miette!("AVM: cannot take .{index} of {other:?}")),
+            },
+            ExprKind::ListLit(elems) => Ok(AvmValue::List(
+                elems
+                    .iter()
+                    .map(|e| self.eval_expr(e))
+                    .collect::<miette::Result<Vec<_>>>()?,
+            )),
+            ExprKind::MapLit(entries) => {
+                let mut out: Vec<(AvmValue, AvmValue)> = Vec::with_capacity(entries.len());
+                for (k, v) in entries {
+                    let k = self.eval_expr(k)?;
+                    let v = self.eval_expr(v)?;
+                    match out.iter_mut().find(|(existing, _)| *existing == k) {
+                        Some(slot) => slot.1 = v,
+                        None => out.push((k, v)),
+                    }
+                }
+                Ok(AvmValue::Map(out))
+            }
+            ExprKind::Index { base, index } => {
+                let mut vals = match self.eval_expr(base)? {
+                    AvmValue::List(vals) => vals,
+                    AvmValue::Map(entries) => {
+                        // Same contract the verifier proves statically: the key must be present.
+                        let key = self.eval_expr(index)?;
+                        return entries
+                            .into_iter()
+                            .find(|(k, _)| *k == key)
+                            .map(|(_, v)| v)
+                            .ok_or_else(|| {
+                                miette::miette!("AVM: map has no key {}", avm_value_to_prop_string(&key))
+                            });
+                    }
+                    _ => return Err(miette::miette!("AVM: indexing requires a list or map")),
+                };
+                let i = match self.eval_expr(index)? {
+                    AvmValue::Int(i) => i,
+                    other => return Err(miette::miette!("AVM: list index must be an int, got {other:?}")),
+                };
+                // Same contract the verifier proves statically; re-checked here for unverified runs.
+                let contract = aura_verify::BoundsContract::new(vals.len());
+                match usize::try_from(i) {
+                    Ok(i) if contract.is_valid_index(i) => Ok(vals.swap_remove(i)),
+                    _ => Err(miette::miette!(
+                        "AVM: {}",
+                        contract.bounds_error(usize::try_from(i).unwrap_or(usize::MAX))
+                    )),
                 }
             }
             ExprKind::Call { callee, args, trailing } => {
+                // `xs.len()` on lists; `m.len()` and `m.contains(k)` on maps.
+                let collection_result = match &callee.kind {
+                    ExprKind::Member { base, member } if matches!(member.node.as_str(), "len" | "contains") => {
+                        match (self.eval_expr(base), member.node.as_str(), args.as_slice()) {
+                            (Ok(AvmValue::List(vals)), "len", []) => Some(AvmValue::Int(vals.len() as i64)),
+                            (Ok(AvmValue::Map(entries)), "len", []) => Some(AvmValue::Int(entries.len() as i64)),
+                            (Ok(AvmValue::Map(entries)), "contains", [arg]) => {
+                                let key = self.eval_expr(call_arg_value(arg))?;
+                                Some(AvmValue::Bool(entries.iter().any(|(k, _)| *k == key)))
+                            }
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                };
+                if let Some(v) = collection_result {
+                    return Ok(v);
+                }
+
+                // `model.infer(input)` is `ai.infer(model, input)`.
+                if let (ExprKind::Member { base, member }, [input]) = (&callee.kind, args.as_slice())
+                    && member.node == "infer"
+                    && matches!(base.kind, ExprKind::Ident(_))
+                    && let AvmValue::Int(model) = self.eval_expr(base)?
+                {
+                    return self.builtin_ai_infer(model, input, None);
+                }
+
                 // Support extern calls by name.
                 // Special-case `Ident` callee so UI constructors like `Button(...)` don't require
                 // pre-binding a namespace value.
@@ -1986,6 +2915,26 @@ impl Avm {
                     self.builtin_ui_dispatch(&name, args)
                 } else if name.starts_with("audio.") {
                     self.builtin_audio_dispatch(&name, args)
+                } else if name.starts_with("hw.") {
+                    self.builtin_hw_dispatch(&name, args)
+                } else if name.starts_with("ai.") {
+                    self.builtin_ai_dispatch(&name, args)
+                } else if name.starts_with("tensor.") {
+                    self.builtin_tensor_dispatch(&name, args)
+                } else if name.starts_with("chan.") {
+                    self.builtin_chan_dispatch(&name, args)
+                } else if name.starts_with("scope.") {
+                    self.builtin_scope_dispatch(&name, args)
+                } else if name.starts_with("time.") {
+                    self.builtin_time_dispatch(&name, args)
+                } else if name.starts_with("fs.") {
+                    self.builtin_fs_dispatch(&name, args)
+                } else if name.starts_with("net.") {
+                    self.builtin_net_dispatch(&name, args)
+                } else if name.starts_with("str.") {
+                    self.builtin_str_dispatch(&name, args)
+                } else if name.starts_with("math.") {
+                    self.builtin_math_dispatch(&name, args)
                 } else if is_ui_call(&name, trailing.is_some()) {
                     let mut node = UiNode::new(name);
 
@@ -2023,10 +2972,14 @@ impl Avm {
                     }
 
                     if let Some(tb) = trailing {
-                        self.eval_ui_trailing_block(&mut node, tb)?;
+                        self.debug_frame(|vm| vm.eval_ui_trailing_block(&mut node, tb))?;
                     }
 
                     Ok(AvmValue::Ui(node))
+                } else if let Some(cell) = self.cells.get(&name).cloned() {
+                    self.call_cell(&cell, args)
+                } else if let Some(ext) = self.externs.get(&name).cloned() {
+                    self.call_extern(&ext, args)
                 } else {
                     Err(miette::miette!("AVM: unknown call '{name}'"))
                 }?;
@@ -2035,6 +2988,9 @@ impl Avm {
             }
             ExprKind::Lambda { .. } => Ok(AvmValue::Unit),
             ExprKind::Flow { left, op: _op, right } => {
+                if let Some(id) = self.spawn_flow(expr)? {
+                    return self.await_task(id);
+                }
                 // Minimal: execute `left`, then `right`.
                 let _ = self.eval_expr(left)?;
                 self.eval_expr(right)
@@ -2042,6 +2998,95 @@ impl Avm {
         }
     }
 
+    /// Runs a user cell with its parameters bound over the caller's bindings, which are
+    /// restored afterwards.
+    fn call_cell(&mut self, cell: &UserCell, args: &[CallArg]) -> miette::Result<AvmValue> {
+        if args.len() != cell.def.params.len() {
+            return Err(miette::miette!(
+                "AVM: cell '{}' expects {} argument(s), got {}",
+                cell.def.name.node,
+                cell.def.params.len(),
+                args.len()
+            ));
+        }
+        let mut values = Vec::with_capacity(args.len());
+        for a in args {
+            values.push(self.eval_expr(call_arg_value(a))?);
+        }
+        self.call_cell_values(cell, values)
+    }
+
+    /// Runs a user cell on already evaluated arguments (one per parameter).
+    fn call_cell_values(&mut self, cell: &UserCell, values: Vec<AvmValue>) -> miette::Result<AvmValue> {
+        self.burn_fuel()?;
+        let (chunk, cell) = (cell.chunk.as_ref(), &cell.def);
+        if let Some(cov) = &mut self.coverage {
+            cov.hit_cell(&cell.name.node);
+        }
+        let saved = self.env.clone();
+        for (p, v) in cell.params.iter().zip(values) {
+            self.env.insert(p.name.node.clone(), v);
+        }
+        if let Some(profile) = self.profiler() {
+            profile.enter(&cell.name.node);
+        }
+        // `unsafe` is lexical: the caller's block does not cover the cell body.
+        let unsafe_depth = std::mem::take(&mut self.unsafe_depth);
+        let out = self.debug_frame(|vm| vm.exec_compiled(chunk, &cell.body, &(), &mut NexusContext::default()));
+        self.unsafe_depth = unsafe_depth;
+        if let Some(profile) = &mut self.profile {
+            profile.exit();
+        }
+        self.env = saved;
+        let value = out?;
+        match self.jump.take() {
+            Some(Jump::Return(v)) => Ok(v),
+            _ => Ok(value),
+        }
+    }
+
+    /// Calls an extern cell the AVM has no builtin for in `cfg.native_libs`. As in compiled
+    /// code, an untrusted extern may only be called inside an `unsafe` block.
+    fn call_extern(&mut self, ext: &ExternCell, args: &[CallArg]) -> miette::Result<AvmValue> {
+        self.burn_fuel()?;
+        if !ext.trusted && self.unsafe_depth == 0 {
+            return Err(miette::miette!(
+                "AVM: calling extern cell '{}' requires an unsafe block",
+                ext.name.node
+            ));
+        }
+        if args.len() != ext.params.len() {
+            return Err(miette::miette!(
+                "AVM: extern cell '{}' expects {} argument(s), got {}",
+                ext.name.node,
+                ext.params.len(),
+                args.len()
+            ));
+        }
+        let mut values = Vec::with_capacity(args.len());
+        for a in args {
+            values.push(self.eval_expr(call_arg_value(a))?);
+        }
+        self.call_native(ext, &values)
+    }
+
+    #[cfg(feature = "ffi")]
+    fn call_native(&mut self, ext: &ExternCell, values: &[AvmValue]) -> miette::Result<AvmValue> {
+        let native = match &mut self.native {
+            Some(native) => native,
+            None => self.native.insert(crate::ffi::NativeLibs::load(&self.cfg.native_libs)?),
+        };
+        native.call(ext, values)
+    }
+
+    #[cfg(not(feature = "ffi"))]
+    fn call_native(&mut self, ext: &ExternCell, _values: &[AvmValue]) -> miette::Result<AvmValue> {
+        Err(miette::miette!(
+            "AVM: extern cell '{}' can only be called when aura-interpret is built with the `ffi` feature",
+            ext.name.node
+        ))
+    }
+
     fn eval_ui_trailing_block(&mut self, node: &mut UiNode, b: &aura_ast::Block) -> miette::Result<()> {
         for s in &b.stmts {
             match s {
@@ -2104,6 +3149,11 @@ impl Avm {
             (BinOp::Mul, AvmValue::Int(a), AvmValue::Int(b)) => Ok(AvmValue::Int(a * b)),
             (BinOp::Div, AvmValue::Int(a), AvmValue::Int(b)) => Ok(AvmValue::Int(a / b)),
 
+            (BinOp::Add, AvmValue::Float(a), AvmValue::Float(b)) => Ok(AvmValue::Float(a + b)),
+            (BinOp::Sub, AvmValue::Float(a), AvmValue::Float(b)) => Ok(AvmValue::Float(a - b)),
+            (BinOp::Mul, AvmValue::Float(a), AvmValue::Float(b)) => Ok(AvmValue::Float(a * b)),
+            (BinOp::Div, AvmValue::Float(a), AvmValue::Float(b)) => Ok(AvmValue::Float(a / b)),
+
             (BinOp::Eq, a, b) => Ok(AvmValue::Bool(a == b)),
             (BinOp::Ne, a, b) => Ok(AvmValue::Bool(a != b)),
 
@@ -2112,9 +3162,24 @@ impl Avm {
             (BinOp::Le, AvmValue::Int(a), AvmValue::Int(b)) => Ok(AvmValue::Bool(a <= b)),
             (BinOp::Ge, AvmValue::Int(a), AvmValue::Int(b)) => Ok(AvmValue::Bool(a >= b)),
 
+            (BinOp::Lt, AvmValue::Float(a), AvmValue::Float(b)) => Ok(AvmValue::Bool(a < b)),
+            (BinOp::Gt, AvmValue::Float(a), AvmValue::Float(b)) => Ok(AvmValue::Bool(a > b)),
+            (BinOp::Le, AvmValue::Float(a), AvmValue::Float(b)) => Ok(AvmValue::Bool(a <= b)),
+            (BinOp::Ge, AvmValue::Float(a), AvmValue::Float(b)) => Ok(AvmValue::Bool(a >= b)),
+
             (BinOp::And, AvmValue::Bool(a), AvmValue::Bool(b)) => Ok(AvmValue::Bool(a && b)),
             (BinOp::Or, AvmValue::Bool(a), AvmValue::Bool(b)) => Ok(AvmValue::Bool(a || b)),
 
+            // Bitwise operators act on the u32 representation.
+            (BinOp::BitAnd, AvmValue::Int(a), AvmValue::Int(b)) => Ok(AvmValue::Int(i64::from(a as u32 & b as u32))),
+            (BinOp::BitOr, AvmValue::Int(a), AvmValue::Int(b)) => Ok(AvmValue::Int(i64::from(a as u32 | b as u32))),
+            (BinOp::BitXor, AvmValue::Int(a), AvmValue::Int(b)) => Ok(AvmValue::Int(i64::from(a as u32 ^ b as u32))),
+            (BinOp::Shl | BinOp::Shr, AvmValue::Int(_), AvmValue::Int(b)) if !(0..32).contains(&b) => {
+                Err(miette::miette!("AVM: shift amount {b} is out of range for u32"))
+            }
+            (BinOp::Shl, AvmValue::Int(a), AvmValue::Int(b)) => Ok(AvmValue::Int(i64::from((a as u32) << b))),
+            (BinOp::Shr, AvmValue::Int(a), AvmValue::Int(b)) => Ok(AvmValue::Int(i64::from((a as u32) >> b))),
+
             _ => Err(miette::miette!("AVM: unsupported binary op")),
         }
     }
@@ -2190,7 +3255,7 @@ fn verify_source_z3(
     Ok((ok, err, span))
 }
 
-fn normalize_source_for_plugin_imports(source: &str) -> String {
+pub(crate) fn normalize_source_for_plugin_imports(source: &str) -> String {
     // Minimal text-level normalization just for the REPL/interpreter.
     // We rewrite `import aura-ai` to `import aura_ai` (same for iot), so the lexer can parse it.
     // Keep it conservative: only rewrite the `import ...` token sequence.
@@ -2263,10 +3328,17 @@ fn stmt_span(stmt: &Stmt) -> Span {
         Stmt::CellDef(s) => s.span,
         Stmt::ExternCell(s) => s.span,
         Stmt::UnsafeBlock(s) => s.span,
+        Stmt::Scope(s) => s.span,
+        Stmt::Region(s) => s.span,
         Stmt::Assign(s) => s.span,
         Stmt::If(s) => s.span,
         Stmt::Match(s) => s.span,
         Stmt::While(s) => s.span,
+        Stmt::For(s) => s.span,
+        Stmt::Break(s) => s.span,
+        Stmt::Continue(s) => s.span,
+        Stmt::Return(s) => s.span,
+        Stmt::Defer(s) => s.span,
         Stmt::Requires(s) => s.span,
         Stmt::Ensures(s) => s.span,
         Stmt::Assert(s) => s.span,
@@ -2286,6 +3358,11 @@ fn pat_matches_value(p: &Pattern, v: &AvmValue) -> bool {
         (Pattern::StringLit { value, .. }, AvmValue::Str(s)) => s == value,
         // MVP: the VM does not have a structured enum representation yet.
         (Pattern::Ctor { .. }, _) => false,
+        (Pattern::Bind { .. }, _) => true,
+        (Pattern::Tuple { elems, .. }, AvmValue::Tuple(vals)) => {
+            elems.len() == vals.len() && elems.iter().zip(vals.iter()).all(|(p, v)| pat_matches_value(p, v))
+        }
+        (Pattern::Or { alts, .. }, _) => alts.iter().any(|a| pat_matches_value(a, v)),
         _ => false,
     }
 }
@@ -2297,10 +3374,19 @@ fn call_arg_value(arg: &CallArg) -> &Expr {
     }
 }
 
-fn source_note(_span: Span) -> miette::NamedSource<String> {
-    // Placeholder: we don't have stable source attachment here yet.
-    // Keep errors readable.
-    miette::NamedSource::new("avm".to_string(), "".to_string())
+/// An error pointing at `span` in the running program. The program's text is attached as
+/// the error leaves the run (see `with_run_source`).
+fn error_at(span: Span, message: impl std::fmt::Display) -> miette::Report {
+    miette::miette!(labels = vec![miette::LabeledSpan::underline(span)], "{message}")
+}
+
+/// Attaches the running program's text to an error that points into it but has no source yet.
+fn with_run_source(err: miette::Report, name: &str, source: &str) -> miette::Report {
+    if err.labels().is_some() && err.source_code().is_none() {
+        err.with_source_code(miette::NamedSource::new(name, source.to_string()))
+    } else {
+        err
+    }
 }
 
 fn is_ui_call(name: &str, has_trailing: bool) -> bool {
@@ -2327,12 +3413,30 @@ fn is_ui_call(name: &str, has_trailing: bool) -> bool {
 fn avm_value_to_prop_string(v: &AvmValue) -> String {
     match v {
         AvmValue::Int(i) => i.to_string(),
+        AvmValue::Float(f) => f.to_string(),
         AvmValue::Bool(b) => b.to_string(),
         // UI runtimes typically expect raw string payloads (e.g. Color names, labels).
         AvmValue::Str(s) => s.clone(),
         AvmValue::Style(_) => "<style>".to_string(),
         AvmValue::Ui(n) => format!("<{}>", n.kind),
+        AvmValue::Tuple(elems) => format!(
+            "({})",
+            elems.iter().map(avm_value_to_prop_string).collect::<Vec<_>>().join(", ")
+        ),
+        AvmValue::List(elems) => format!(
+            "[{}]",
+            elems.iter().map(avm_value_to_prop_string).collect::<Vec<_>>().join(", ")
+        ),
+        AvmValue::Map(entries) => format!(
+            "{{{}}}",
+            entries
+                .iter()
+                .map(|(k, v)| format!("{}: {}", avm_value_to_prop_string(k), avm_value_to_prop_string(v)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
         AvmValue::Unit => "Unit".to_string(),
+        AvmValue::Task(id) => format!("<task {id}>"),
     }
 }
 
@@ -0,0 +1,559 @@
+//! Model inference for `ai.*` calls, and the `tensor.*` buffers models run on.
+//!
+//! Verification proves `ai.infer` inputs fit the model's ONNX contract; the AVM runs the
+//! model with an `InferenceRuntime` and re-checks both sides of every call against that
+//! contract, so a model file that changed since verification, or a tensor whose shape the
+//! verifier never saw, fails the call instead of running with the wrong shape.
+//!
+//! As in compiled code, tensors are handles to 32-bit slots: integer elements are stored
+//! widened (signed ones as two's complement) and `f32` elements as their bits.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use aura_ast::CallArg;
+use aura_bridge::onnx::{OnnxDim, OnnxElemType, OnnxQuantParams};
+use aura_plugin_ai::{AiConfig, DimBounds};
+
+use super::{call_arg_value, Avm, AvmValue};
+
+/// A tensor crossing the inference boundary: row-major elements in 32-bit slots.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RuntimeTensor {
+    pub shape: Vec<u64>,
+    /// The element type, when known.
+    pub elem: Option<OnnxElemType>,
+    pub data: Vec<u32>,
+}
+
+/// Runs ONNX models for `ai.infer` (set `AvmConfig::inference`). With the `ffi` feature,
+/// `OnnxRuntime` runs them with the ONNX Runtime library among `AvmConfig::native_libs`.
+pub trait InferenceRuntime: fmt::Debug + Send + Sync {
+    /// Loads the model at `path`, returning the id `run` takes.
+    fn load(&self, path: &Path) -> Result<u64, String>;
+
+    /// Runs a loaded model on `input`, which is shaped as the model's first input, and
+    /// returns its first output.
+    fn run(&self, model: u64, input: &RuntimeTensor) -> Result<RuntimeTensor, String>;
+}
+
+/// One dimension of a model's contract.
+#[derive(Clone, Debug)]
+enum ContractDim {
+    Fixed(u64),
+    /// A dimension the model leaves open, sized per call within its bounds.
+    Open { name: Option<String>, bounds: DimBounds },
+}
+
+impl fmt::Display for ContractDim {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContractDim::Fixed(d) => write!(f, "{d}"),
+            ContractDim::Open { name: Some(n), .. } => f.write_str(n),
+            ContractDim::Open { name: None, .. } => f.write_str("?"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct LoadedModel {
+    path: String,
+    id: u64,
+    input: Vec<ContractDim>,
+    output: Vec<ContractDim>,
+    input_elem: Option<OnnxElemType>,
+    output_elem: Option<OnnxElemType>,
+    input_quant: Option<OnnxQuantParams>,
+}
+
+/// Tensors and models of a run.
+#[derive(Debug, Default)]
+pub(super) struct AiSession {
+    /// Handle `n` is `tensors[n - 1]`.
+    tensors: Vec<Vec<u32>>,
+    /// Indices into `tensors` made in each open `region`, innermost last.
+    regions: Vec<Vec<usize>>,
+    /// Handle `n` is `models[n - 1]`.
+    models: Vec<LoadedModel>,
+    runtime: Option<Arc<dyn InferenceRuntime>>,
+    /// `[ai]` from aura.toml, read on first use.
+    config: Option<AiConfig>,
+}
+
+impl AiSession {
+    pub(super) fn enter_region(&mut self) {
+        self.regions.push(Vec::new());
+    }
+
+    /// Frees the tensors made in the innermost region. Their handles read as empty tensors,
+    /// and those at the end of the table are reused.
+    pub(super) fn exit_region(&mut self) {
+        let Some(made) = self.regions.pop() else {
+            return;
+        };
+        for &i in &made {
+            self.tensors[i] = Vec::new();
+        }
+        for &i in made.iter().rev() {
+            if i + 1 != self.tensors.len() {
+                break;
+            }
+            self.tensors.pop();
+        }
+    }
+
+    /// Sets the open regions aside while a task runs: what tasks make belongs to no region.
+    pub(super) fn detach_regions(&mut self) -> Vec<Vec<usize>> {
+        std::mem::take(&mut self.regions)
+    }
+
+    pub(super) fn reattach_regions(&mut self, regions: Vec<Vec<usize>>) {
+        self.regions = regions;
+    }
+}
+
+fn describe_shape<T: fmt::Display>(dims: &[T]) -> String {
+    let dims: Vec<String> = dims.iter().map(|d| d.to_string()).collect();
+    format!("[{}]", dims.join(", "))
+}
+
+/// `[1, 3] f32`, or just the shape when the element type is unknown.
+fn describe_tensor<T: fmt::Display>(dims: &[T], elem: Option<OnnxElemType>) -> String {
+    match elem {
+        Some(elem) => format!("{} {}", describe_shape(dims), elem.describe()),
+        None => describe_shape(dims),
+    }
+}
+
+impl LoadedModel {
+    /// This model with its leading input and output dimension fixed to `batch`, along with
+    /// every dimension that shares its name.
+    fn batched(&self, batch: u64) -> Result<LoadedModel, String> {
+        let (Some(first), Some(out_first)) = (self.input.first(), self.output.first()) else {
+            return Err(format!(
+                "model '{}' has no leading batch dimension in its input and output",
+                self.path
+            ));
+        };
+        let fits = |d: &ContractDim| match d {
+            ContractDim::Fixed(d) => *d == batch,
+            ContractDim::Open { bounds, .. } => batch >= bounds.min && bounds.max.is_none_or(|max| batch <= max),
+        };
+        if !fits(first) || !fits(out_first) {
+            return Err(format!(
+                "a batch of {batch} does not fit the batch dimension of model '{}' (input {}, output {})",
+                self.path,
+                describe_shape(&self.input),
+                describe_shape(&self.output)
+            ));
+        }
+        let name = match first {
+            ContractDim::Open { name: Some(n), .. } => Some(n.clone()),
+            _ => None,
+        };
+        let fix = |dims: &[ContractDim]| -> Vec<ContractDim> {
+            dims.iter()
+                .enumerate()
+                .map(|(i, d)| match d {
+                    _ if i == 0 => ContractDim::Fixed(batch),
+                    ContractDim::Open { name: Some(n), .. } if name.as_ref() == Some(n) => ContractDim::Fixed(batch),
+                    d => d.clone(),
+                })
+                .collect()
+        };
+        Ok(LoadedModel {
+            input: fix(&self.input),
+            output: fix(&self.output),
+            ..self.clone()
+        })
+    }
+
+    /// The input shape for a tensor of `len` elements: open dimensions take the size left
+    /// over by the fixed ones, which must be within their bounds, and dimensions sharing a
+    /// name take the same size. Returns the sizes of the named dimensions as well.
+    fn input_shape(&self, len: u64) -> Result<(Vec<u64>, BTreeMap<String, u64>), String> {
+        let mismatch = || {
+            format!(
+                "a tensor of {len} element(s) does not fit input {} of model '{}'",
+                describe_shape(&self.input),
+                self.path
+            )
+        };
+        let fixed: u64 = self
+            .input
+            .iter()
+            .map(|d| match d {
+                ContractDim::Fixed(d) => *d,
+                ContractDim::Open { .. } => 1,
+            })
+            .product();
+        if fixed == 0 || !len.is_multiple_of(fixed) {
+            return Err(mismatch());
+        }
+        let rest = len / fixed;
+
+        // Open positions grouped by name; each unnamed one is a group of its own.
+        let mut groups: Vec<(Option<&str>, u32, DimBounds)> = Vec::new();
+        for d in &self.input {
+            let ContractDim::Open { name, bounds } = d else { continue };
+            match groups.iter_mut().find(|(n, _, _)| n.is_some() && *n == name.as_deref()) {
+                Some(group) => group.1 += 1,
+                None => groups.push((name.as_deref(), 1, *bounds)),
+            }
+        }
+        let size = match groups.as_slice() {
+            [] if rest == 1 => None,
+            [] => return Err(mismatch()),
+            [(_, 1, _)] => Some(rest),
+            // A name repeated `count` times: the size is the `count`-th root of what is left.
+            [(_, count, _)] => {
+                let size = (1..=rest)
+                    .find(|s| s.checked_pow(*count).is_none_or(|p| p >= rest))
+                    .filter(|s| s.checked_pow(*count) == Some(rest))
+                    .ok_or_else(mismatch)?;
+                Some(size)
+            }
+            _ => {
+                return Err(format!(
+                    "the sizes of the open dimensions of input {} of model '{}' cannot be told apart at run time",
+                    describe_shape(&self.input),
+                    self.path
+                ));
+            }
+        };
+
+        let mut named = BTreeMap::new();
+        if let (Some(size), [(name, _, bounds)]) = (size, groups.as_slice()) {
+            if size < bounds.min || bounds.max.is_some_and(|max| size > max) {
+                return Err(format!(
+                    "input dimension {} of model '{}' would be {size}, outside {} allowed for it",
+                    name.unwrap_or("?"),
+                    self.path,
+                    bounds.describe()
+                ));
+            }
+            if let Some(name) = name {
+                named.insert(name.to_string(), size);
+            }
+        }
+        let shape = self
+            .input
+            .iter()
+            .map(|d| match d {
+                ContractDim::Fixed(d) => *d,
+                ContractDim::Open { .. } => size.unwrap_or(1),
+            })
+            .collect();
+        Ok((shape, named))
+    }
+
+    /// For a quantized model, checks every input value is in the domain of its stored
+    /// integer type (slots of signed types hold two's complement).
+    fn check_quantized_input(&self, data: &[u32]) -> Result<(), String> {
+        let (Some(quant), Some(elem)) = (self.input_quant, self.input_elem) else {
+            return Ok(());
+        };
+        let Some((lo, hi)) = elem.int_range() else {
+            return Ok(());
+        };
+        let signed = lo < 0;
+        for (i, slot) in data.iter().enumerate() {
+            let v = if signed { i64::from(*slot as i32) } else { i64::from(*slot) };
+            if v < lo || v > hi {
+                return Err(format!(
+                    "input element {i} is {v}, outside the quantized {} domain [{lo}..{hi}] of model '{}' (scale {}, zero point {})",
+                    elem.describe(),
+                    self.path,
+                    quant.scale,
+                    quant.zero_point
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks a result against the output contract: fixed dimensions match, named ones take
+    /// the size the input gave them (or one within their bounds), and the element type and
+    /// count agree.
+    fn check_output(&self, out: &RuntimeTensor, named: &BTreeMap<String, u64>) -> Result<(), String> {
+        let breaks = || {
+            format!(
+                "model '{}' returned {}, which breaks its output contract {}",
+                self.path,
+                describe_tensor(&out.shape, out.elem),
+                describe_tensor(&self.output, self.output_elem)
+            )
+        };
+        if out.shape.len() != self.output.len() {
+            return Err(breaks());
+        }
+        for (d, size) in self.output.iter().zip(&out.shape) {
+            let ok = match d {
+                ContractDim::Fixed(d) => d == size,
+                ContractDim::Open { name, bounds } => match name.as_ref().and_then(|n| named.get(n)) {
+                    Some(expected) => expected == size,
+                    None => *size >= bounds.min && bounds.max.is_none_or(|max| *size <= max),
+                },
+            };
+            if !ok {
+                return Err(breaks());
+            }
+        }
+        if let (Some(declared), Some(actual)) = (self.output_elem, out.elem)
+            && declared != actual
+        {
+            return Err(breaks());
+        }
+        let count: u64 = out.shape.iter().product();
+        if count != out.data.len() as u64 {
+            return Err(format!(
+                "model '{}' returned {} element(s) for output shape {}",
+                self.path,
+                out.data.len(),
+                describe_shape(&out.shape)
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Avm {
+    /// The elements of the tensor with handle `handle`, if any `tensor.new` created it.
+    pub fn tensor_data(&self, handle: i64) -> Option<&[u32]> {
+        let i = usize::try_from(handle).ok()?.checked_sub(1)?;
+        self.ai.tensors.get(i).map(Vec::as_slice)
+    }
+
+    fn tensor(&self, handle: i64) -> miette::Result<&[u32]> {
+        self.tensor_data(handle)
+            .ok_or_else(|| miette::miette!("AVM: {handle} is not a tensor handle"))
+    }
+
+    fn new_tensor(&mut self, data: Vec<u32>) -> AvmValue {
+        if let Some(region) = self.ai.regions.last_mut() {
+            region.push(self.ai.tensors.len());
+        }
+        self.ai.tensors.push(data);
+        AvmValue::Int(self.ai.tensors.len() as i64)
+    }
+
+    fn inference_runtime(&mut self) -> miette::Result<Arc<dyn InferenceRuntime>> {
+        if let Some(rt) = &self.ai.runtime {
+            return Ok(rt.clone());
+        }
+        let rt = match &self.cfg.inference {
+            Some(rt) => rt.clone(),
+            None => self.native_inference_runtime()?,
+        };
+        Ok(self.ai.runtime.insert(rt).clone())
+    }
+
+    #[cfg(feature = "ffi")]
+    fn native_inference_runtime(&self) -> miette::Result<Arc<dyn InferenceRuntime>> {
+        let lib = self.cfg.native_libs.iter().find(|p| crate::ort::is_onnxruntime_library(p)).ok_or_else(|| {
+            miette::miette!(
+                "AVM: ai.* calls need an inference runtime (AvmConfig::inference, or the onnxruntime library among AvmConfig::native_libs)"
+            )
+        })?;
+        Ok(Arc::new(crate::ort::OnnxRuntime::load(lib)?))
+    }
+
+    #[cfg(not(feature = "ffi"))]
+    fn native_inference_runtime(&self) -> miette::Result<Arc<dyn InferenceRuntime>> {
+        Err(miette::miette!(
+            "AVM: ai.* calls need an inference runtime (AvmConfig::inference, or build aura-interpret with the `ffi` feature to use onnxruntime)"
+        ))
+    }
+
+    pub(super) fn builtin_tensor_dispatch(&mut self, name: &str, args: &[CallArg]) -> miette::Result<AvmValue> {
+        let arity = match name {
+            "tensor.new" | "tensor.len" => 1,
+            "tensor.get" => 2,
+            "tensor.set" => 3,
+            _ => return Err(miette::miette!("AVM: unknown tensor builtin '{name}'")),
+        };
+        if args.len() != arity {
+            return Err(miette::miette!("AVM: {name} expects {arity} argument(s), got {}", args.len()));
+        }
+        let mut vals = Vec::with_capacity(args.len());
+        for a in args {
+            match self.eval_expr(call_arg_value(a))? {
+                AvmValue::Int(i) => vals.push(i),
+                _ => return Err(miette::miette!("AVM: {name} expects int arguments")),
+            }
+        }
+        let index = |t: &[u32], i: i64| -> miette::Result<usize> {
+            let contract = aura_verify::BoundsContract::new(t.len());
+            match usize::try_from(i) {
+                Ok(i) if contract.is_valid_index(i) => Ok(i),
+                _ => Err(miette::miette!(
+                    "AVM: {}",
+                    contract.bounds_error(usize::try_from(i).unwrap_or(usize::MAX))
+                )),
+            }
+        };
+        match name {
+            "tensor.new" => {
+                let len = usize::try_from(vals[0])
+                    .map_err(|_| miette::miette!("AVM: tensor.new length must not be negative"))?;
+                Ok(self.new_tensor(vec![0; len]))
+            }
+            "tensor.len" => Ok(AvmValue::Int(self.tensor(vals[0])?.len() as i64)),
+            "tensor.get" => {
+                let t = self.tensor(vals[0])?;
+                Ok(AvmValue::Int(i64::from(t[index(t, vals[1])?])))
+            }
+            _ => {
+                let value = u32::try_from(vals[2])
+                    .map_err(|_| miette::miette!("AVM: tensor.set value {} does not fit 32 bits", vals[2]))?;
+                let i = index(self.tensor(vals[0])?, vals[1])?;
+                self.ai.tensors[vals[0] as usize - 1][i] = value;
+                Ok(AvmValue::Unit)
+            }
+        }
+    }
+
+    pub(super) fn builtin_ai_dispatch(&mut self, name: &str, args: &[CallArg]) -> miette::Result<AvmValue> {
+        match (name, args) {
+            ("ai.load_model", [path]) => {
+                let AvmValue::Str(path) = self.eval_expr(call_arg_value(path))? else {
+                    return Err(miette::miette!("AVM: ai.load_model expects a string path"));
+                };
+                self.builtin_ai_load_model(&path)
+            }
+            ("ai.infer", [model, input]) => {
+                let AvmValue::Int(model) = self.eval_expr(call_arg_value(model))? else {
+                    return Err(miette::miette!("AVM: ai.infer expects a model handle"));
+                };
+                self.builtin_ai_infer(model, input, None)
+            }
+            ("ai.infer_batch", [model, input, batch]) => {
+                let AvmValue::Int(model) = self.eval_expr(call_arg_value(model))? else {
+                    return Err(miette::miette!("AVM: ai.infer_batch expects a model handle"));
+                };
+                self.builtin_ai_infer(model, input, Some(batch))
+            }
+            ("ai.load_model", _) => Err(miette::miette!("AVM: ai.load_model expects 1 argument")),
+            ("ai.infer", _) => Err(miette::miette!("AVM: ai.infer expects 2 arguments")),
+            ("ai.infer_batch", _) => Err(miette::miette!("AVM: ai.infer_batch expects 3 arguments")),
+            _ => Err(miette::miette!("AVM: unknown ai builtin '{name}'")),
+        }
+    }
+
+    fn builtin_ai_load_model(&mut self, path_s: &str) -> miette::Result<AvmValue> {
+        // Resolved as verification resolves it, so both read the same contract.
+        let mut path = PathBuf::from(path_s);
+        if path.is_relative()
+            && let Ok(cwd) = std::env::current_dir()
+        {
+            path = cwd.join(path);
+        }
+        let shapes = aura_bridge::model::read_model_io_shapes(&path)
+            .map_err(|e| miette::miette!("AVM: failed to read model contract from '{path_s}': {e}"))?;
+        let bounds = &self.ai_config()?.dims;
+        let contract = |dims: Vec<OnnxDim>| -> Vec<ContractDim> {
+            dims.into_iter()
+                .map(|d| match d {
+                    OnnxDim::Known(v) if v > 0 => ContractDim::Fixed(v as u64),
+                    OnnxDim::Known(_) => ContractDim::Open {
+                        name: None,
+                        bounds: DimBounds::default(),
+                    },
+                    OnnxDim::Symbolic(name) => ContractDim::Open {
+                        bounds: bounds.get(&name).copied().unwrap_or_default(),
+                        name: Some(name),
+                    },
+                })
+                .collect()
+        };
+        let (input, output) = (contract(shapes.input), contract(shapes.output));
+
+        let id = self
+            .inference_runtime()?
+            .load(&path)
+            .map_err(|e| miette::miette!("AVM: failed to load model '{path_s}': {e}"))?;
+        self.ai.models.push(LoadedModel {
+            path: path_s.to_string(),
+            id,
+            input,
+            output,
+            input_elem: shapes.input_elem,
+            output_elem: shapes.output_elem,
+            input_quant: shapes.input_quant,
+        });
+        Ok(AvmValue::Int(self.ai.models.len() as i64))
+    }
+
+    fn ai_config(&mut self) -> miette::Result<&AiConfig> {
+        if self.ai.config.is_none() {
+            let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+            self.ai.config = Some(aura_plugin_ai::load_ai_config(&cwd)?);
+        }
+        Ok(self.ai.config.as_ref().expect("loaded"))
+    }
+
+    /// `ai.infer(model, input)`, also written `model.infer(input)`, or with a `batch`,
+    /// `ai.infer_batch(model, input, batch)`.
+    pub(super) fn builtin_ai_infer(
+        &mut self,
+        model: i64,
+        input: &CallArg,
+        batch: Option<&CallArg>,
+    ) -> miette::Result<AvmValue> {
+        let op = if batch.is_some() { "ai.infer_batch" } else { "ai.infer" };
+        let AvmValue::Int(input) = self.eval_expr(call_arg_value(input))? else {
+            return Err(miette::miette!("AVM: {op} expects a tensor"));
+        };
+        let batch = match batch {
+            Some(b) => {
+                let AvmValue::Int(b) = self.eval_expr(call_arg_value(b))? else {
+                    return Err(miette::miette!("AVM: ai.infer_batch expects an int batch size"));
+                };
+                // `[ai] max_batch` bounds the batch as `[ai.dims]` bounds a named dimension.
+                let allowed = DimBounds {
+                    min: 1,
+                    max: self.ai_config()?.max_batch,
+                };
+                match u64::try_from(b) {
+                    Ok(b) if b >= allowed.min && allowed.max.is_none_or(|max| b <= max) => Some(b),
+                    _ => {
+                        return Err(miette::miette!(
+                            "AVM: ai.infer_batch: batch size {b} is outside {} allowed for it",
+                            allowed.describe()
+                        ));
+                    }
+                }
+            }
+            None => None,
+        };
+        let m = usize::try_from(model)
+            .ok()
+            .and_then(|m| m.checked_sub(1))
+            .and_then(|m| self.ai.models.get(m))
+            .ok_or_else(|| miette::miette!("AVM: {model} is not a model handle"))?;
+        let m = match batch {
+            Some(b) => m.batched(b).map_err(|e| miette::miette!("AVM: {op}: {e}"))?,
+            None => m.clone(),
+        };
+        let data = self.tensor(input)?.to_vec();
+        m.check_quantized_input(&data)
+            .map_err(|e| miette::miette!("AVM: {op}: {e}"))?;
+        let (shape, named) = m
+            .input_shape(data.len() as u64)
+            .map_err(|e| miette::miette!("AVM: {op}: {e}"))?;
+        let input = RuntimeTensor {
+            shape,
+            elem: m.input_elem,
+            data,
+        };
+        let out = self
+            .inference_runtime()?
+            .run(m.id, &input)
+            .map_err(|e| miette::miette!("AVM: {op}: model '{}' failed: {e}", m.path))?;
+        m.check_output(&out, &named)
+            .map_err(|e| miette::miette!("AVM: {op}: {e}"))?;
+        Ok(self.new_tensor(out.data))
+    }
+}
@@ -0,0 +1,574 @@
+//! Bytecode for AVM blocks that run over and over.
+//!
+//! UI frame bodies and cell bodies are compiled once into a flat list of `Op`s and run by a
+//! dispatch loop instead of re-walking the AST every frame or call. Bindings, arithmetic and
+//! control flow are native ops; everything else (calls, `match`, nested UI blocks, `defer`)
+//! stays as AST and is handed to the tree-walker, so both paths share one set of semantics.
+//!
+//! Every op records the span it was compiled from. `Step` ops use it to stop the debugger
+//! before the same statements the tree-walker stops at, and failing ops point their error
+//! at it.
+
+use aura_ast::{AssignStmt, BinOp, Block, Expr, ExprKind, InterpPart, Span, Stmt, StrandDef, UnaryOp};
+use aura_nexus::{NexusContext, UiPluginDispatch};
+
+use super::flow::is_spawn;
+use super::{avm_value_to_prop_string, error_at, Avm, AvmValue, Jump};
+
+#[derive(Clone, Copy, Debug)]
+enum Op {
+    /// Start of a statement: the debugger may stop here.
+    Step,
+    Const(u32),
+    Unit,
+    Load(u32),
+    /// Pops a value into the named binding.
+    Store(u32),
+    Pop,
+    Unary(UnaryOp),
+    Binary(BinOp),
+    Tuple(u32),
+    List(u32),
+    /// Pops `n` interpolation parts and pushes their concatenation.
+    Interp(u32),
+    Jmp(u32),
+    /// Pops a condition and jumps unless it is `true`.
+    JmpUnlessTrue(u32),
+    /// Pops an asserted condition and fails unless it is `true`.
+    Assert,
+    /// Fails unless the value on top is an integer `for` bound.
+    CheckInt,
+    WhileEnter(u32),
+    /// Counts one `while` iteration against the runaway-loop guard.
+    WhileTick(u32),
+    /// Pops the `for` range and saves the binding the loop variable shadows.
+    ForEnter(u32),
+    /// Binds the next loop value, or jumps to `exit` once the range is done.
+    ForNext { slot: u32, exit: u32 },
+    /// Restores the binding the loop variable shadowed.
+    ForExit(u32),
+    Break(u32),
+    Continue(u32),
+    /// Pops the returned value and leaves the chunk.
+    Return,
+    /// Evaluates an expression with the tree-walker.
+    EvalExpr(u32),
+    /// Executes a statement with the tree-walker. A `break`/`continue` it leaves pending
+    /// resumes `in_loop`, the innermost compiled loop around it.
+    ExecStmt { stmt: u32, in_loop: Option<u32> },
+}
+
+impl Op {
+    fn stack_effect(self) -> i64 {
+        match self {
+            Op::Const(_) | Op::Unit | Op::Load(_) | Op::EvalExpr(_) | Op::ExecStmt { .. } => 1,
+            Op::Store(_) | Op::Pop | Op::Binary(_) | Op::JmpUnlessTrue(_) | Op::Assert | Op::Return => -1,
+            Op::Tuple(n) | Op::List(n) | Op::Interp(n) => 1 - i64::from(n),
+            Op::ForEnter(_) => -2,
+            Op::Step
+            | Op::Unary(_)
+            | Op::Jmp(_)
+            | Op::CheckInt
+            | Op::WhileEnter(_)
+            | Op::WhileTick(_)
+            | Op::ForNext { .. }
+            | Op::ForExit(_)
+            | Op::Break(_)
+            | Op::Continue(_) => 0,
+        }
+    }
+}
+
+/// A compiled `while` or `for` loop.
+#[derive(Clone, Debug, Default)]
+struct LoopInfo {
+    /// Stack depth outside the loop; `break` and `continue` drop back to it.
+    depth: u32,
+    /// The `for` variable, if this is a `for` loop.
+    var: Option<u32>,
+    continue_at: u32,
+    break_at: u32,
+}
+
+/// Per-run state of one loop.
+#[derive(Clone, Debug, Default)]
+struct LoopState {
+    guard: u64,
+    next: i64,
+    end: i64,
+    shadowed: Option<AvmValue>,
+    active: bool,
+}
+
+/// A block compiled to bytecode.
+#[derive(Debug, Default)]
+pub(super) struct Chunk {
+    code: Vec<Op>,
+    /// Source span of each op in `code`.
+    spans: Vec<Span>,
+    consts: Vec<AvmValue>,
+    names: Vec<String>,
+    exprs: Vec<Expr>,
+    stmts: Vec<Stmt>,
+    loops: Vec<LoopInfo>,
+}
+
+impl Chunk {
+    /// Compiles `body`, or returns `None` if the block itself registers `defer`s and so has
+    /// to run on the tree-walker.
+    pub(super) fn compile(body: &Block) -> Option<Chunk> {
+        let mut c = Compiler::default();
+        c.block(body).ok()?;
+        Some(c.chunk)
+    }
+}
+
+/// The construct has no bytecode form; its statement falls back to the tree-walker.
+struct Unsupported;
+
+#[derive(Default)]
+struct Compiler {
+    chunk: Chunk,
+    depth: u32,
+    /// Compiled loops enclosing the current position, innermost last.
+    open_loops: Vec<u32>,
+}
+
+impl Compiler {
+    fn pc(&self) -> u32 {
+        self.chunk.code.len() as u32
+    }
+
+    fn emit(&mut self, op: Op, span: Span) -> u32 {
+        let at = self.pc();
+        self.depth = (i64::from(self.depth) + op.stack_effect()) as u32;
+        self.chunk.code.push(op);
+        self.chunk.spans.push(span);
+        at
+    }
+
+    fn patch(&mut self, at: u32, target: u32) {
+        match &mut self.chunk.code[at as usize] {
+            Op::Jmp(t) | Op::JmpUnlessTrue(t) | Op::ForNext { exit: t, .. } => *t = target,
+            op => unreachable!("cannot patch {op:?}"),
+        }
+    }
+
+    fn name(&mut self, name: &str) -> u32 {
+        match self.chunk.names.iter().position(|n| n == name) {
+            Some(i) => i as u32,
+            None => {
+                self.chunk.names.push(name.to_string());
+                self.chunk.names.len() as u32 - 1
+            }
+        }
+    }
+
+    fn constant(&mut self, v: AvmValue, span: Span) {
+        self.chunk.consts.push(v);
+        self.emit(Op::Const(self.chunk.consts.len() as u32 - 1), span);
+    }
+
+    /// Leaves the block's value on the stack.
+    fn block(&mut self, b: &Block) -> Result<(), Unsupported> {
+        if b.stmts.iter().any(|s| matches!(s, Stmt::Defer(_))) {
+            return Err(Unsupported);
+        }
+        self.emit(Op::Unit, b.span);
+        for s in &b.stmts {
+            self.emit(Op::Pop, s.span());
+            self.stmt(s);
+        }
+        if let Some(e) = &b.yield_expr {
+            self.emit(Op::Pop, e.span);
+            self.expr(e);
+        }
+        Ok(())
+    }
+
+    /// Leaves the statement's value on the stack.
+    fn stmt(&mut self, s: &Stmt) {
+        let (len, depth, open) = (self.chunk.code.len(), self.depth, self.open_loops.len());
+        if self.native_stmt(s).is_ok() {
+            return;
+        }
+        self.chunk.code.truncate(len);
+        self.chunk.spans.truncate(len);
+        self.depth = depth;
+        self.open_loops.truncate(open);
+
+        self.chunk.stmts.push(s.clone());
+        let op = Op::ExecStmt {
+            stmt: self.chunk.stmts.len() as u32 - 1,
+            in_loop: self.open_loops.last().copied(),
+        };
+        self.emit(op, s.span());
+    }
+
+    fn native_stmt(&mut self, s: &Stmt) -> Result<(), Unsupported> {
+        let span = s.span();
+        let depth = self.depth;
+        // A spawned `~>` task is bound or left running rather than awaited.
+        if let Stmt::StrandDef(StrandDef { expr, .. })
+        | Stmt::Assign(AssignStmt { expr, .. })
+        | Stmt::ExprStmt(expr) = s
+            && is_spawn(expr)
+        {
+            return Err(Unsupported);
+        }
+        self.emit(Op::Step, span);
+        match s {
+            Stmt::StrandDef(sd) if sd.pattern.is_none() => {
+                self.expr(&sd.expr);
+                let name = self.name(&sd.name.node);
+                self.emit(Op::Store(name), span);
+                self.emit(Op::Unit, span);
+            }
+            Stmt::Assign(a) => {
+                self.expr(&a.expr);
+                let name = self.name(&a.target.node);
+                self.emit(Op::Store(name), span);
+                self.emit(Op::Unit, span);
+            }
+            Stmt::ExprStmt(e) => self.expr(e),
+            Stmt::Prop(p) => self.discard(&p.expr, span),
+            Stmt::Requires(r) => self.discard(&r.expr, span),
+            Stmt::Ensures(e) => self.discard(&e.expr, span),
+            Stmt::Assume(a) => self.discard(&a.expr, span),
+            Stmt::Assert(a) => {
+                self.expr(&a.expr);
+                self.emit(Op::Assert, span);
+                self.emit(Op::Unit, span);
+            }
+            Stmt::If(i) => {
+                self.expr(&i.cond);
+                let to_else = self.emit(Op::JmpUnlessTrue(0), span);
+                self.block(&i.then_block)?;
+                let to_end = self.emit(Op::Jmp(0), span);
+                self.depth -= 1;
+                let else_at = self.pc();
+                self.patch(to_else, else_at);
+                match &i.else_block {
+                    Some(b) => self.block(b)?,
+                    None => {
+                        self.emit(Op::Unit, span);
+                    }
+                }
+                let end = self.pc();
+                self.patch(to_end, end);
+            }
+            Stmt::While(w) => {
+                let slot = self.open_loop(None);
+                self.emit(Op::WhileEnter(slot), span);
+                let top = self.emit(Op::WhileTick(slot), span);
+                self.expr(&w.cond);
+                let to_exit = self.emit(Op::JmpUnlessTrue(0), span);
+                self.block(&w.body)?;
+                self.emit(Op::Pop, span);
+                self.emit(Op::Jmp(top), span);
+                let exit = self.pc();
+                self.patch(to_exit, exit);
+                self.close_loop(slot, top, exit);
+                self.emit(Op::Unit, span);
+            }
+            Stmt::For(f) => {
+                self.expr(&f.start);
+                self.emit(Op::CheckInt, f.start.span);
+                self.expr(&f.end);
+                self.emit(Op::CheckInt, f.end.span);
+                let var = self.name(&f.var.node);
+                let slot = self.open_loop(Some(var));
+                self.emit(Op::ForEnter(slot), span);
+                let next = self.emit(Op::ForNext { slot, exit: 0 }, span);
+                self.block(&f.body)?;
+                self.emit(Op::Pop, span);
+                self.emit(Op::Jmp(next), span);
+                let exit = self.emit(Op::ForExit(slot), span);
+                self.patch(next, exit);
+                self.close_loop(slot, next, exit);
+                self.emit(Op::Unit, span);
+            }
+            Stmt::Break(_) | Stmt::Continue(_) => {
+                let slot = *self.open_loops.last().ok_or(Unsupported)?;
+                let op = if matches!(s, Stmt::Break(_)) { Op::Break(slot) } else { Op::Continue(slot) };
+                self.emit(op, span);
+                // Never falls through; keep the block's stack discipline for what follows.
+                self.depth = depth + 1;
+                return Ok(());
+            }
+            Stmt::Return(r) => {
+                match &r.value {
+                    Some(e) => self.expr(e),
+                    None => {
+                        self.emit(Op::Unit, span);
+                    }
+                }
+                self.emit(Op::Return, span);
+                self.depth = depth + 1;
+                return Ok(());
+            }
+            _ => return Err(Unsupported),
+        }
+        Ok(())
+    }
+
+    fn discard(&mut self, e: &Expr, span: Span) {
+        self.expr(e);
+        self.emit(Op::Pop, span);
+        self.emit(Op::Unit, span);
+    }
+
+    fn open_loop(&mut self, var: Option<u32>) -> u32 {
+        let slot = self.chunk.loops.len() as u32;
+        self.chunk.loops.push(LoopInfo {
+            var,
+            ..LoopInfo::default()
+        });
+        self.open_loops.push(slot);
+        slot
+    }
+
+    /// `depth` is taken here, once the loop's own operands are off the stack.
+    fn close_loop(&mut self, slot: u32, continue_at: u32, break_at: u32) {
+        self.open_loops.pop();
+        let depth = self.depth;
+        let info = &mut self.chunk.loops[slot as usize];
+        info.depth = depth;
+        info.continue_at = continue_at;
+        info.break_at = break_at;
+    }
+
+    /// Leaves the expression's value on the stack.
+    fn expr(&mut self, e: &Expr) {
+        let span = e.span;
+        match &e.kind {
+            ExprKind::IntLit(n) => self.constant(AvmValue::Int(*n as i64), span),
+            ExprKind::FloatLit(v) => self.constant(AvmValue::Float(*v), span),
+            ExprKind::StringLit(s) => self.constant(AvmValue::Str(s.clone()), span),
+            ExprKind::Ident(id) => {
+                let name = self.name(&id.node);
+                self.emit(Op::Load(name), span);
+            }
+            ExprKind::Unary { op, expr } => {
+                self.expr(expr);
+                self.emit(Op::Unary(*op), span);
+            }
+            ExprKind::Binary { left, op, right } => {
+                self.expr(left);
+                self.expr(right);
+                self.emit(Op::Binary(*op), span);
+            }
+            ExprKind::Tuple(elems) => {
+                elems.iter().for_each(|e| self.expr(e));
+                self.emit(Op::Tuple(elems.len() as u32), span);
+            }
+            ExprKind::ListLit(elems) => {
+                elems.iter().for_each(|e| self.expr(e));
+                self.emit(Op::List(elems.len() as u32), span);
+            }
+            ExprKind::InterpolatedString(parts) => {
+                for part in parts {
+                    match part {
+                        InterpPart::Lit(l) => self.constant(AvmValue::Str(l.clone()), span),
+                        InterpPart::Expr(e) => self.expr(e),
+                    }
+                }
+                self.emit(Op::Interp(parts.len() as u32), span);
+            }
+            _ => {
+                self.chunk.exprs.push(e.clone());
+                self.emit(Op::EvalExpr(self.chunk.exprs.len() as u32 - 1), span);
+            }
+        }
+    }
+}
+
+fn pop(stack: &mut Vec<AvmValue>) -> AvmValue {
+    stack.pop().expect("bytecode stack underflow")
+}
+
+fn pop_n(stack: &mut Vec<AvmValue>, n: u32) -> Vec<AvmValue> {
+    stack.split_off(stack.len() - n as usize)
+}
+
+impl Avm {
+    /// Runs a compiled block and returns its value, leaving `self.jump` set exactly as
+    /// `exec_block` would.
+    pub(super) fn run_chunk(
+        &mut self,
+        chunk: &Chunk,
+        ui_plugins: &dyn UiPluginDispatch,
+        nexus: &mut NexusContext,
+    ) -> miette::Result<AvmValue> {
+        let mut stack: Vec<AvmValue> = Vec::with_capacity(16);
+        let mut loops = vec![LoopState::default(); chunk.loops.len()];
+        let mut pc = 0usize;
+
+        while let Some(&op) = chunk.code.get(pc) {
+            let span = chunk.spans[pc];
+            pc += 1;
+            match op {
+                Op::Step => {
+                    if self.debug.is_some() {
+                        self.debug_stop_if_needed(span)?;
+                    }
+                    self.burn_fuel()?;
+                    if let Some(cov) = &mut self.coverage {
+                        cov.hit_stmt(span);
+                    }
+                }
+                Op::Const(i) => stack.push(chunk.consts[i as usize].clone()),
+                Op::Unit => stack.push(AvmValue::Unit),
+                Op::Load(n) => stack.push(self.read_var(&chunk.names[n as usize])?),
+                Op::Store(n) => {
+                    let v = pop(&mut stack);
+                    self.check_memory(Some(&v))?;
+                    self.env.insert(chunk.names[n as usize].clone(), v);
+                }
+                Op::Pop => {
+                    pop(&mut stack);
+                }
+                Op::Unary(op) => {
+                    let v = pop(&mut stack);
+                    stack.push(eval_unary(op, v)?);
+                }
+                Op::Binary(op) => {
+                    let r = pop(&mut stack);
+                    let l = pop(&mut stack);
+                    stack.push(self.eval_binop(op, l, r)?);
+                }
+                Op::Tuple(n) => {
+                    let elems = pop_n(&mut stack, n);
+                    stack.push(AvmValue::Tuple(elems));
+                }
+                Op::List(n) => {
+                    let elems = pop_n(&mut stack, n);
+                    stack.push(AvmValue::List(elems));
+                }
+                Op::Interp(n) => {
+                    let out = pop_n(&mut stack, n).iter().map(avm_value_to_prop_string).collect();
+                    stack.push(AvmValue::Str(out));
+                }
+                Op::Jmp(t) => pc = t as usize,
+                Op::JmpUnlessTrue(t) => {
+                    // Only `if` and `while` compile to conditional jumps; the span is theirs.
+                    let taken = matches!(pop(&mut stack), AvmValue::Bool(true));
+                    if let Some(cov) = &mut self.coverage {
+                        cov.hit_branch(span, usize::from(!taken));
+                    }
+                    if !taken {
+                        pc = t as usize;
+                    }
+                }
+                Op::Assert => {
+                    if !matches!(pop(&mut stack), AvmValue::Bool(true)) {
+                        return Err(error_at(span, "assertion failed"));
+                    }
+                }
+                Op::CheckInt => {
+                    if !matches!(stack.last(), Some(AvmValue::Int(_))) {
+                        return Err(error_at(span, "AVM: for range bounds must be integers"));
+                    }
+                }
+                Op::WhileEnter(slot) => loops[slot as usize].guard = 0,
+                Op::WhileTick(slot) => {
+                    let state = &mut loops[slot as usize];
+                    state.guard += 1;
+                    if state.guard > 5_000_000 {
+                        return Err(error_at(span, "AVM: loop guard tripped (possible infinite loop)"));
+                    }
+                }
+                Op::ForEnter(slot) => {
+                    let (AvmValue::Int(end), AvmValue::Int(start)) = (pop(&mut stack), pop(&mut stack)) else {
+                        unreachable!("for bounds are checked by CheckInt");
+                    };
+                    let var = &chunk.names[chunk.loops[slot as usize].var.expect("for loop") as usize];
+                    loops[slot as usize] = LoopState {
+                        guard: 0,
+                        next: start,
+                        end,
+                        shadowed: self.env.get(var).cloned(),
+                        active: true,
+                    };
+                }
+                Op::ForNext { slot, exit } => {
+                    let state = &mut loops[slot as usize];
+                    if state.next < state.end {
+                        let var = &chunk.names[chunk.loops[slot as usize].var.expect("for loop") as usize];
+                        self.env.insert(var.clone(), AvmValue::Int(state.next));
+                        state.next += 1;
+                    } else {
+                        pc = exit as usize;
+                    }
+                }
+                Op::ForExit(slot) => self.exit_for(chunk, &mut loops, slot),
+                Op::Break(slot) => pc = resume_loop(chunk, &mut stack, slot, false),
+                Op::Continue(slot) => pc = resume_loop(chunk, &mut stack, slot, true),
+                Op::Return => {
+                    let v = pop(&mut stack);
+                    self.jump = Some(Jump::Return(v));
+                    return Ok(self.leave_chunk(chunk, &mut loops));
+                }
+                Op::EvalExpr(i) => {
+                    let v = self.eval_expr(&chunk.exprs[i as usize])?;
+                    stack.push(v);
+                }
+                Op::ExecStmt { stmt, in_loop } => {
+                    let v = self.exec_stmt(&chunk.stmts[stmt as usize], ui_plugins, nexus)?;
+                    match (self.jump.take(), in_loop) {
+                        (None, _) => stack.push(v),
+                        (Some(Jump::Break), Some(slot)) => pc = resume_loop(chunk, &mut stack, slot, false),
+                        (Some(Jump::Continue), Some(slot)) => pc = resume_loop(chunk, &mut stack, slot, true),
+                        (pending, _) => {
+                            self.jump = pending;
+                            return Ok(self.leave_chunk(chunk, &mut loops));
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(stack.pop().unwrap_or(AvmValue::Unit))
+    }
+
+    fn exit_for(&mut self, chunk: &Chunk, loops: &mut [LoopState], slot: u32) {
+        let state = &mut loops[slot as usize];
+        state.active = false;
+        let var = &chunk.names[chunk.loops[slot as usize].var.expect("for loop") as usize];
+        match state.shadowed.take() {
+            Some(v) => {
+                self.env.insert(var.clone(), v);
+            }
+            None => {
+                self.env.remove(var);
+            }
+        }
+    }
+
+    /// Unwinds the `for` loops a pending jump leaves early; the chunk's value is then `Unit`,
+    /// as with `exec_block`.
+    fn leave_chunk(&mut self, chunk: &Chunk, loops: &mut [LoopState]) -> AvmValue {
+        for slot in (0..loops.len()).rev() {
+            if loops[slot].active {
+                self.exit_for(chunk, loops, slot as u32);
+            }
+        }
+        AvmValue::Unit
+    }
+}
+
+/// Drops the values of an abandoned iteration and returns where the loop resumes.
+fn resume_loop(chunk: &Chunk, stack: &mut Vec<AvmValue>, slot: u32, is_continue: bool) -> usize {
+    let info = &chunk.loops[slot as usize];
+    stack.truncate(info.depth as usize);
+    if is_continue { info.continue_at as usize } else { info.break_at as usize }
+}
+
+pub(super) fn eval_unary(op: UnaryOp, v: AvmValue) -> miette::Result<AvmValue> {
+    match (op, v) {
+        (UnaryOp::Neg, AvmValue::Int(i)) => Ok(AvmValue::Int(-i)),
+        (UnaryOp::Neg, AvmValue::Float(f)) => Ok(AvmValue::Float(-f)),
+        (UnaryOp::Not, AvmValue::Bool(b)) => Ok(AvmValue::Bool(!b)),
+        _ => Err(miette::miette!("AVM: unsupported unary op")),
+    }
+}
@@ -0,0 +1,96 @@
+//! `chan.*` calls: channels between `~>` flows, on aura-rt-native's `ChannelTable`.
+//!
+//! Channels are `Int` handles into a table every worker of the run shares. Under
+//! `FlowScheduling::Threads` a receive on an empty channel (or a send to a full one) waits
+//! for another task. Cooperative tasks only run when something waits for them, so there
+//! the AVM runs queued tasks, oldest first, until the channel is ready, and fails the call
+//! if none is left to make it so. A started cooperative task runs to completion: it cannot
+//! wait for the task that started it to make room in a channel.
+
+use aura_ast::CallArg;
+use aura_rt_native::ChanError;
+
+use super::{call_arg_value, Avm, AvmValue, FlowScheduling};
+
+impl Avm {
+    pub(super) fn builtin_chan_dispatch(&mut self, name: &str, args: &[CallArg]) -> miette::Result<AvmValue> {
+        let arity = match name {
+            "chan.unbounded" => 0,
+            "chan.bounded" | "chan.recv" | "chan.close" | "chan.len" => 1,
+            "chan.send" => 2,
+            _ => return Err(miette::miette!("AVM: unknown chan builtin '{name}'")),
+        };
+        if args.len() != arity {
+            return Err(miette::miette!("AVM: {name} expects {arity} argument(s), got {}", args.len()));
+        }
+        let mut vals = Vec::with_capacity(args.len());
+        for a in args {
+            vals.push(self.eval_expr(call_arg_value(a))?);
+        }
+        let handle = |v: &AvmValue| -> miette::Result<u32> {
+            match v {
+                AvmValue::Int(i) => u32::try_from(*i).map_err(|_| miette::miette!("AVM: {name}: unknown channel {i}")),
+                _ => Err(miette::miette!("AVM: {name} expects a channel, got {v:?}")),
+            }
+        };
+        let failed = |e: ChanError| miette::miette!("AVM: {name}: {e}");
+
+        match name {
+            "chan.unbounded" => Ok(AvmValue::Int(i64::from(self.channels.unbounded()))),
+            "chan.bounded" => {
+                let capacity = match vals[0] {
+                    AvmValue::Int(i) => u32::try_from(i)
+                        .map_err(|_| miette::miette!("AVM: chan.bounded capacity {i} does not fit 32 bits"))?,
+                    ref v => return Err(miette::miette!("AVM: chan.bounded expects an int capacity, got {v:?}")),
+                };
+                // Same contract the verifier proves statically; re-checked here for unverified runs.
+                Ok(AvmValue::Int(i64::from(self.channels.bounded(capacity).map_err(failed)?)))
+            }
+            "chan.len" => Ok(AvmValue::Int(self.channels.len(handle(&vals[0])?).map_err(failed)? as i64)),
+            "chan.close" => {
+                self.channels.close(handle(&vals[0])?).map_err(failed)?;
+                Ok(AvmValue::Unit)
+            }
+            "chan.send" => {
+                let ch = handle(&vals[0])?;
+                let value = vals.pop().expect("two arguments");
+                if matches!(self.cfg.flows, FlowScheduling::Threads { .. }) {
+                    self.channels.send(ch, value).map_err(failed)?;
+                    return Ok(AvmValue::Unit);
+                }
+                loop {
+                    match self.channels.try_send(ch, value.clone()) {
+                        Ok(()) => return Ok(AvmValue::Unit),
+                        Err(ChanError::Full) => {
+                            if !self.run_next_task()? {
+                                return Err(miette::miette!(
+                                    "AVM: chan.send: channel {ch} is full and no task is left to receive from it"
+                                ));
+                            }
+                        }
+                        Err(e) => return Err(failed(e)),
+                    }
+                }
+            }
+            _ => {
+                let ch = handle(&vals[0])?;
+                if matches!(self.cfg.flows, FlowScheduling::Threads { .. }) {
+                    return self.channels.recv(ch).map_err(failed);
+                }
+                loop {
+                    match self.channels.try_recv(ch) {
+                        Ok(value) => return Ok(value),
+                        Err(ChanError::Empty) => {
+                            if !self.run_next_task()? {
+                                return Err(miette::miette!(
+                                    "AVM: chan.recv: channel {ch} is empty and no task is left to send to it"
+                                ));
+                            }
+                        }
+                        Err(e) => return Err(failed(e)),
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! `fs.*` calls, on aura-rt-native's `FileTable`.
+//!
+//! Files are `Int` handles into a table shared with the workers running the program's flows,
+//! so a flow can be handed one. Opening a path checks it against `AvmConfig::fs_policy` first.
+//! What `fs.read` returns is recorded in traces, like `io.read_text` results, and replayed
+//! from them; a replay still opens its files but does not read them.
+
+use std::path::Path;
+
+use aura_ast::CallArg;
+use aura_rt_native::{FileError, FileMode};
+use aura_sdk::fs_policy::FsAccess;
+
+use super::{call_arg_value, Avm, AvmValue};
+use crate::trace::TraceEvent;
+
+impl Avm {
+    pub(super) fn builtin_fs_dispatch(&mut self, name: &str, args: &[CallArg]) -> miette::Result<AvmValue> {
+        let arity = match name {
+            "fs.open" | "fs.create" | "fs.append" | "fs.read" | "fs.close" => 1,
+            "fs.write" => 2,
+            _ => return Err(miette::miette!("AVM: unknown fs builtin '{name}'")),
+        };
+        if args.len() != arity {
+            return Err(miette::miette!("AVM: {name} expects {arity} argument(s), got {}", args.len()));
+        }
+        let failed = |e: FileError| miette::miette!("AVM: {name}: {e}");
+
+        match name {
+            "fs.open" | "fs.create" | "fs.append" => {
+                let path = self.fs_string(name, &args[0])?;
+                let (mode, access) = match name {
+                    "fs.open" => (FileMode::Read, FsAccess::Read),
+                    "fs.create" => (FileMode::Create, FsAccess::Write),
+                    _ => (FileMode::Append, FsAccess::Write),
+                };
+                if let Some(policy) = &self.cfg.fs_policy
+                    && !policy.allows(Path::new(&path), access)
+                {
+                    return Err(miette::miette!(
+                        "AVM: {name}: the [fs] policy of aura.toml does not allow {} '{path}'",
+                        access.display()
+                    ));
+                }
+                let h = self.files.open(Path::new(&path), mode).map_err(failed)?;
+                Ok(AvmValue::Int(i64::from(h)))
+            }
+            "fs.read" => {
+                let h = self.fs_handle(name, &args[0])?;
+                let text = if let Some(trace) = &mut self.trace
+                    && trace.is_replaying()
+                {
+                    let TraceEvent::ReadText { text, .. } = trace.replay(TraceEvent::READ_TEXT)? else {
+                        unreachable!("replay returns the requested kind");
+                    };
+                    text
+                } else {
+                    let path = self.files.path(h).map_err(failed)?;
+                    let read = self.files.read_to_string(h).map_err(|e| e.to_string());
+                    self.record(TraceEvent::ReadText {
+                        path: path.display().to_string(),
+                        text: read.clone(),
+                    })?;
+                    read
+                };
+                text.map(AvmValue::Str).map_err(|e| miette::miette!("AVM: fs.read: {e}"))
+            }
+            "fs.write" => {
+                let h = self.fs_handle(name, &args[0])?;
+                let text = self.fs_string(name, &args[1])?;
+                self.files.write(h, &text).map_err(failed)?;
+                Ok(AvmValue::Unit)
+            }
+            _ => {
+                let h = self.fs_handle(name, &args[0])?;
+                self.files.close(h).map_err(failed)?;
+                Ok(AvmValue::Unit)
+            }
+        }
+    }
+
+    fn fs_handle(&mut self, name: &str, arg: &CallArg) -> miette::Result<u32> {
+        match self.eval_expr(call_arg_value(arg))? {
+            AvmValue::Int(i) => u32::try_from(i).map_err(|_| miette::miette!("AVM: {name}: unknown file {i}")),
+            v => Err(miette::miette!("AVM: {name} expects a file, got {v:?}")),
+        }
+    }
+
+    fn fs_string(&mut self, name: &str, arg: &CallArg) -> miette::Result<String> {
+        match self.eval_expr(call_arg_value(arg))? {
+            AvmValue::Str(s) => Ok(s),
+            v => Err(miette::miette!("AVM: {name} expects a string, got {v:?}")),
+        }
+    }
+}
@@ -0,0 +1,343 @@
+//! Scheduling for `~>` flows.
+//!
+//! `x ~> f(a)` with a user cell `f` spawns a task that calls `f(x, a)`. Bound with `val` or
+//! assigned, the binding holds the pending task and reading it awaits the task; as a bare
+//! statement the task runs in the background; anywhere else it is awaited on the spot. Every
+//! task still pending when a run ends is joined before the run returns, and a task that
+//! failed fails the run.
+//!
+//! Under `FlowScheduling::Cooperative` tasks queue on the AVM's thread and run one at a time,
+//! oldest first, whenever a task is awaited. Under `FlowScheduling::Threads` they start at
+//! once on aura-rt-native workers, each in a child `Avm` over the bindings at the spawn (the
+//! checker only lets `~>` move immutable values, so the snapshot is what the task may see).
+//! Channels, `scope` cancellation tokens and the clock are the only state the children share
+//! with their parent.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Arc;
+
+use aura_ast::{Expr, ExprKind, FlowOp};
+use aura_rt_native::JoinHandle;
+
+use super::{call_arg_value, Avm, AvmConfig, AvmValue, UserCell};
+use crate::coverage::Coverage;
+use crate::debug::{DebugEvent, DebugTaskState};
+
+/// How spawned `~>` flows run.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FlowScheduling {
+    /// Tasks queue on the AVM's thread and run one at a time in spawn order when one is
+    /// awaited, so runs are reproducible and the debugger can stop inside tasks.
+    #[default]
+    Cooperative,
+    /// Tasks run in parallel on aura-rt-native worker threads. The output of tasks nobody
+    /// awaited is merged as they finish, or in spawn order when `deterministic`.
+    Threads { deterministic: bool },
+}
+
+/// What a worker thread hands back: the task's value, the output it printed and the coverage
+/// it collected.
+type WorkerResult = (miette::Result<AvmValue>, String, Option<Coverage>);
+
+enum TaskState {
+    Queued(Vec<AvmValue>),
+    Running,
+    Spawned(JoinHandle<WorkerResult>),
+    Done(AvmValue),
+    /// Failed; the error was reported to whoever awaited it first.
+    Failed,
+}
+
+struct FlowTask {
+    cell: Arc<UserCell>,
+    state: TaskState,
+}
+
+/// The tasks of the current run.
+#[derive(Default)]
+pub(super) struct Tasks {
+    next_id: u64,
+    tasks: BTreeMap<u64, FlowTask>,
+    /// Cooperative tasks waiting to run, oldest first.
+    queue: VecDeque<u64>,
+    /// The cooperative task running now, innermost last.
+    current: Vec<u64>,
+}
+
+impl std::fmt::Debug for Tasks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tasks")
+            .field("pending", &self.tasks.iter().filter(|(_, t)| !t.is_settled()).count())
+            .field("current", &self.current)
+            .finish()
+    }
+}
+
+impl FlowTask {
+    fn is_settled(&self) -> bool {
+        matches!(self.state, TaskState::Done(_) | TaskState::Failed)
+    }
+}
+
+impl Tasks {
+    /// The cooperative task the AVM is running, if any.
+    pub(super) fn current(&self) -> Option<u64> {
+        self.current.last().copied()
+    }
+}
+
+/// Whether `expr` is an async flow into a call, which may spawn a task.
+pub(super) fn is_spawn(expr: &Expr) -> bool {
+    matches!(
+        &expr.kind,
+        ExprKind::Flow { op: FlowOp::Async, right, .. }
+            if matches!(&right.kind, ExprKind::Call { callee, .. } if matches!(callee.kind, ExprKind::Ident(_)))
+    )
+}
+
+impl Avm {
+    /// Evaluates the right-hand side of a binding: an async flow into a user cell becomes a
+    /// pending task instead of being awaited.
+    pub(super) fn eval_spawning(&mut self, expr: &Expr) -> miette::Result<AvmValue> {
+        match self.spawn_flow(expr)? {
+            Some(id) => Ok(AvmValue::Task(id)),
+            None => self.eval_expr(expr),
+        }
+    }
+
+    /// Spawns `left ~> cell(args)` as `cell(left, args)`, or returns `None` if `expr` does not
+    /// flow into a user cell.
+    pub(super) fn spawn_flow(&mut self, expr: &Expr) -> miette::Result<Option<u64>> {
+        if !is_spawn(expr) {
+            return Ok(None);
+        }
+        let ExprKind::Flow { left, right, .. } = &expr.kind else { unreachable!("checked by is_spawn") };
+        let ExprKind::Call { callee, args, .. } = &right.kind else { unreachable!("checked by is_spawn") };
+        let ExprKind::Ident(name) = &callee.kind else { unreachable!("checked by is_spawn") };
+        let Some(cell) = self.cells.get(&name.node).cloned() else {
+            return Ok(None);
+        };
+
+        let mut values = Vec::with_capacity(args.len() + 1);
+        values.push(self.eval_expr(left)?);
+        for a in args {
+            values.push(self.eval_expr(call_arg_value(a))?);
+        }
+        let params = cell.def.params.len();
+        if values.len() != params {
+            return Err(miette::miette!(
+                "AVM: cell '{}' expects {params} argument(s), got {} (the flowed value counts as the first)",
+                name.node,
+                values.len()
+            ));
+        }
+
+        self.tasks.next_id += 1;
+        let id = self.tasks.next_id;
+        let state = match self.cfg.flows {
+            FlowScheduling::Cooperative => {
+                self.tasks.queue.push_back(id);
+                TaskState::Queued(values)
+            }
+            FlowScheduling::Threads { .. } => TaskState::Spawned(self.spawn_worker(Arc::clone(&cell), values)),
+        };
+        self.tasks.tasks.insert(id, FlowTask { cell, state });
+        self.emit_task(id, DebugTaskState::Spawned);
+        Ok(Some(id))
+    }
+
+    fn spawn_worker(&self, cell: Arc<UserCell>, args: Vec<AvmValue>) -> JoinHandle<WorkerResult> {
+        // Workers have no debugger, trace or window; they share the program, not the state.
+        let cfg = AvmConfig {
+            enable_z3_gate: false,
+            debug: None,
+            read_stdin: false,
+            trace: None,
+            ui_script: None,
+            ..self.cfg.clone()
+        };
+        let env = self.env.clone();
+        let cells = self.cells.clone();
+        let externs = self.externs.clone();
+        let channels = Arc::clone(&self.channels);
+        let scopes = Arc::clone(&self.scopes);
+        let clock = self.clock;
+        let files = Arc::clone(&self.files);
+        let sockets = Arc::clone(&self.sockets);
+        let socket_states = Arc::clone(&self.socket_states);
+        let coverage = self.coverage.as_ref().map(Coverage::zeroed);
+        aura_rt_native::spawn(move || {
+            let mut avm = Avm::new(cfg);
+            avm.env = env;
+            avm.cells = cells;
+            avm.externs = externs;
+            avm.channels = channels;
+            avm.scopes = scopes;
+            avm.clock = clock;
+            avm.files = files;
+            avm.sockets = sockets;
+            avm.socket_states = socket_states;
+            avm.coverage = coverage;
+            let out = avm.call_cell_values(&cell, args).and_then(|v| avm.join_flows().map(|()| v));
+            (out, avm.stdout, avm.coverage)
+        })
+    }
+
+    /// Waits for task `id` and returns its value. Cooperative tasks spawned before it run first.
+    pub(super) fn await_task(&mut self, id: u64) -> miette::Result<AvmValue> {
+        loop {
+            let Some(task) = self.tasks.tasks.get_mut(&id) else {
+                return Err(miette::miette!("AVM: unknown task {id}"));
+            };
+            match &task.state {
+                TaskState::Done(v) => return Ok(v.clone()),
+                TaskState::Failed => {
+                    return Err(miette::miette!("AVM: task {id} ('{}') failed", task.cell.def.name.node));
+                }
+                TaskState::Running => {
+                    return Err(miette::miette!(
+                        "AVM: task {id} ('{}') awaits itself",
+                        task.cell.def.name.node
+                    ));
+                }
+                TaskState::Spawned(_) => {
+                    let TaskState::Spawned(handle) = std::mem::replace(&mut task.state, TaskState::Running) else {
+                        unreachable!("matched Spawned")
+                    };
+                    return self.settle_worker(id, handle.join());
+                }
+                TaskState::Queued(_) => {
+                    let next = self.tasks.queue.pop_front().expect("a queued task is in the queue");
+                    self.run_task(next)?;
+                }
+            }
+        }
+    }
+
+    /// The id of the task spawned last, 0 before the first.
+    pub(super) fn last_task_id(&self) -> u64 {
+        self.tasks.next_id
+    }
+
+    /// Waits for every task spawned after task `after` that has not settled, oldest first,
+    /// including those spawned meanwhile. At the first failure `on_failure` runs and the
+    /// remaining tasks are still waited for; the first failure is returned.
+    pub(super) fn join_tasks_after(&mut self, after: u64, mut on_failure: impl FnMut()) -> miette::Result<()> {
+        let mut failure = None;
+        let mut id = after;
+        while id < self.tasks.next_id {
+            id += 1;
+            if self.tasks.tasks.get(&id).is_none_or(FlowTask::is_settled) {
+                continue;
+            }
+            if let Err(e) = self.await_task(id)
+                && failure.is_none()
+            {
+                on_failure();
+                failure = Some(e);
+            }
+        }
+        failure.map_or(Ok(()), Err)
+    }
+
+    /// Runs the oldest queued cooperative task, if there is one; `false` when none is queued.
+    pub(super) fn run_next_task(&mut self) -> miette::Result<bool> {
+        match self.tasks.queue.pop_front() {
+            Some(id) => self.run_task(id).map(|()| true),
+            None => Ok(false),
+        }
+    }
+
+    /// Runs the queued cooperative task `id` to completion on this thread.
+    fn run_task(&mut self, id: u64) -> miette::Result<()> {
+        let task = self.tasks.tasks.get_mut(&id).expect("queued task exists");
+        let TaskState::Queued(args) = std::mem::replace(&mut task.state, TaskState::Running) else {
+            unreachable!("only queued tasks are in the queue")
+        };
+        let cell = Arc::clone(&task.cell);
+
+        self.tasks.current.push(id);
+        self.emit_task(id, DebugTaskState::Started);
+        let regions = self.ai.detach_regions();
+        let out = self.call_cell_values(&cell, args);
+        self.ai.reattach_regions(regions);
+        self.tasks.current.pop();
+        self.settle(id, out)
+    }
+
+    fn settle_worker(&mut self, id: u64, (out, stdout, coverage): WorkerResult) -> miette::Result<AvmValue> {
+        self.stdout.push_str(&stdout);
+        if let (Some(mine), Some(theirs)) = (&mut self.coverage, &coverage) {
+            mine.merge(theirs);
+        }
+        self.settle(id, out)?;
+        self.await_task(id)
+    }
+
+    fn settle(&mut self, id: u64, out: miette::Result<AvmValue>) -> miette::Result<()> {
+        let task = self.tasks.tasks.get_mut(&id).expect("settled task exists");
+        match out {
+            Ok(v) => {
+                task.state = TaskState::Done(v);
+                self.emit_task(id, DebugTaskState::Finished);
+                Ok(())
+            }
+            Err(e) => {
+                task.state = TaskState::Failed;
+                self.emit_task(id, DebugTaskState::Failed);
+                Err(e)
+            }
+        }
+    }
+
+    /// Waits for every task still pending. The first failure fails the join.
+    pub(super) fn join_flows(&mut self) -> miette::Result<()> {
+        while let Some(id) = self.tasks.queue.pop_front() {
+            self.run_task(id)?;
+        }
+        let deterministic = matches!(self.cfg.flows, FlowScheduling::Threads { deterministic: true });
+        loop {
+            let spawned: Vec<u64> = self
+                .tasks
+                .tasks
+                .iter()
+                .filter(|(_, t)| matches!(t.state, TaskState::Spawned(_)))
+                .map(|(id, _)| *id)
+                .collect();
+            let Some(&first) = spawned.first() else {
+                return Ok(());
+            };
+            if deterministic {
+                self.await_task(first)?;
+                continue;
+            }
+            // Take whichever worker finishes first.
+            let finished = spawned.iter().find_map(|id| {
+                let TaskState::Spawned(handle) = &self.tasks.tasks[id].state else { unreachable!("filtered") };
+                handle.try_join().map(|out| (*id, out))
+            });
+            match finished {
+                Some((id, out)) => {
+                    self.tasks.tasks.get_mut(&id).expect("task exists").state = TaskState::Running;
+                    self.settle_worker(id, out)?;
+                }
+                None => std::thread::sleep(std::time::Duration::from_millis(1)),
+            }
+        }
+    }
+
+    /// Forgets the tasks of the previous run.
+    pub(super) fn reset_flows(&mut self) {
+        self.tasks = Tasks::default();
+    }
+
+    fn emit_task(&self, id: u64, state: DebugTaskState) {
+        if let Some(sess) = &self.debug {
+            sess.emit(DebugEvent::Task {
+                task: id,
+                cell: self.tasks.tasks[&id].cell.def.name.node.clone(),
+                state,
+            });
+        }
+    }
+}
@@ -0,0 +1,458 @@
+//! Emulated hardware for `hw.*` calls.
+//!
+//! Verification proves `hw.*` calls safe against the `[hardware]` manifest of `aura.toml`;
+//! with `AvmConfig::hw` set the AVM also runs them, against a register file laid out by the
+//! same manifest. Stores keep only the bits in a register's mask, reads of write-only and
+//! writes of read-only registers fail as they would on the bus, and an `HwScript` stands in
+//! for the peripherals: values reads return in turn, and stores that writes trigger.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::Path;
+
+use aura_ast::CallArg;
+use aura_plugin_iot::{HardwareManifest, HwRegister};
+use miette::IntoDiagnostic;
+use serde::Deserialize;
+
+use super::{call_arg_value, Avm, AvmValue};
+
+/// Hardware the AVM emulates for `hw.*` calls.
+#[derive(Clone, Debug, Default)]
+pub struct HwEmulation {
+    pub manifest: HardwareManifest,
+    pub script: HwScript,
+}
+
+impl HwEmulation {
+    pub fn new(manifest: HardwareManifest) -> Self {
+        Self {
+            manifest,
+            script: HwScript::default(),
+        }
+    }
+
+    /// Emulates the hardware declared by the `aura.toml` nearest to `start`.
+    pub fn load(start: &Path) -> miette::Result<Self> {
+        Ok(Self::new(HardwareManifest::load_from(start)?))
+    }
+
+    pub fn with_script(mut self, script: HwScript) -> Self {
+        self.script = script;
+        self
+    }
+}
+
+/// Scripted peripheral behaviour. Registers are named as in the manifest, with an instance
+/// index for repeated blocks (`UART[2]`).
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct HwScript {
+    pub reads: Vec<HwScriptedReads>,
+    pub on_write: Vec<HwWriteHook>,
+}
+
+impl HwScript {
+    /// Reads a script from a JSON file.
+    pub fn load(path: &Path) -> miette::Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| miette::miette!("failed to read hardware script {}: {e}", path.display()))?;
+        serde_json::from_str(&text).into_diagnostic()
+    }
+}
+
+/// Values that reads of a register word return in turn, before it reads back its contents.
+#[derive(Clone, Debug, Deserialize)]
+pub struct HwScriptedReads {
+    pub register: String,
+    #[serde(default)]
+    pub offset: u64,
+    pub values: Vec<u32>,
+}
+
+/// A store that every write of a register word triggers, e.g. a status flag a peripheral
+/// raises once data is written.
+#[derive(Clone, Debug, Deserialize)]
+pub struct HwWriteHook {
+    pub register: String,
+    #[serde(default)]
+    pub offset: u64,
+    pub set: HwStore,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct HwStore {
+    pub register: String,
+    #[serde(default)]
+    pub offset: u64,
+    pub value: u32,
+}
+
+/// The emulated device of a run, kept across runs like a real one.
+#[derive(Debug)]
+pub(super) struct HwDevice {
+    manifest: HardwareManifest,
+    /// Register blocks opened by `hw.open`; handle `n` is `caps[n - 1]`.
+    caps: Vec<HwRegister>,
+    /// Register words by address; words never written read as 0.
+    words: BTreeMap<u64, u32>,
+    reads: HashMap<u64, VecDeque<u32>>,
+    /// `(written address, stored address, mask of the stored register, value)`.
+    hooks: Vec<(u64, u64, u64, u32)>,
+    /// Enabled interrupts and their priorities.
+    irqs: BTreeMap<String, u64>,
+    /// Current power state, as an index into the manifest's states.
+    power: Option<usize>,
+}
+
+impl HwDevice {
+    fn new(emu: &HwEmulation) -> miette::Result<Self> {
+        let mut dev = Self {
+            manifest: emu.manifest.clone(),
+            caps: Vec::new(),
+            words: BTreeMap::new(),
+            reads: HashMap::new(),
+            hooks: Vec::new(),
+            irqs: BTreeMap::new(),
+            power: emu.manifest.power.as_ref().map(|p| p.index(&p.initial).unwrap_or(0)),
+        };
+        for r in &emu.script.reads {
+            let (addr, _) = dev.word(&r.register, r.offset)?;
+            dev.reads.entry(addr).or_default().extend(&r.values);
+        }
+        for h in &emu.script.on_write {
+            let (from, _) = dev.word(&h.register, h.offset)?;
+            let (to, reg) = dev.word(&h.set.register, h.set.offset)?;
+            dev.hooks.push((from, to, reg.mask.unwrap_or(0xFFFF_FFFF), h.set.value));
+        }
+        Ok(dev)
+    }
+
+    /// The register block named `name`, or instance `i` of one for `name[i]`.
+    fn register(&self, name: &str) -> miette::Result<HwRegister> {
+        let (base, index) = match name.strip_suffix(']').and_then(|n| n.split_once('[')) {
+            Some((base, i)) => {
+                let i = i
+                    .parse::<u64>()
+                    .map_err(|_| miette::miette!("AVM: invalid register instance '{name}'"))?;
+                (base, Some(i))
+            }
+            None => (name, None),
+        };
+        let reg = self
+            .manifest
+            .get(base)
+            .ok_or_else(|| miette::miette!("AVM: unknown hardware register '{base}'"))?;
+        match index {
+            Some(i) if i >= reg.instances => Err(miette::miette!(
+                "AVM: instance {i} of '{}' is out of range ({} instance(s))",
+                reg.name,
+                reg.instances
+            )),
+            Some(i) => Ok(reg.instance(i)),
+            None if reg.instances > 1 => Err(miette::miette!(
+                "AVM: hardware register '{}' has {} instances; name one, e.g. '{}[0]'",
+                reg.name,
+                reg.instances,
+                reg.name
+            )),
+            None => Ok(reg.clone()),
+        }
+    }
+
+    /// Address of the word at `offset` in register block `name`.
+    fn word(&self, name: &str, offset: u64) -> miette::Result<(u64, HwRegister)> {
+        let reg = self.register(name)?;
+        let addr = word_address(&reg, offset)?;
+        Ok((addr, reg))
+    }
+
+    fn cap(&self, handle: i64) -> miette::Result<HwRegister> {
+        usize::try_from(handle)
+            .ok()
+            .and_then(|h| h.checked_sub(1))
+            .and_then(|i| self.caps.get(i))
+            .cloned()
+            .ok_or_else(|| miette::miette!("AVM: {handle} is not a capability returned by hw.open"))
+    }
+
+    fn check_power(&self, reg: &HwRegister) -> miette::Result<()> {
+        let (Some(power), Some(state), Some(allowed)) = (&self.manifest.power, self.power, &reg.states) else {
+            return Ok(());
+        };
+        let current = &power.states[state];
+        if allowed.iter().any(|a| a.eq_ignore_ascii_case(current)) {
+            return Ok(());
+        }
+        Err(miette::miette!(
+            "AVM: hardware register '{}' is accessed in power state '{current}'; it is accessible only in {}",
+            reg.name,
+            allowed.join(", ")
+        ))
+    }
+
+    /// Stores `value` at `addr`, keeping the bits in `mask`, then runs the write hooks of `addr`.
+    fn store(&mut self, addr: u64, mask: u64, value: u32) {
+        self.words.insert(addr, value & mask as u32);
+        let hooks: Vec<_> = self.hooks.iter().filter(|h| h.0 == addr).copied().collect();
+        for (_, to, to_mask, v) in hooks {
+            self.words.insert(to, v & to_mask as u32);
+        }
+    }
+
+    /// The next scripted value for `addr`, or its contents.
+    fn load(&mut self, addr: u64) -> u32 {
+        match self.reads.get_mut(&addr).and_then(VecDeque::pop_front) {
+            Some(v) => v,
+            None => self.words.get(&addr).copied().unwrap_or(0),
+        }
+    }
+}
+
+fn word_address(reg: &HwRegister, offset: u64) -> miette::Result<u64> {
+    if reg.size < 4 || offset > reg.size - 4 {
+        return Err(miette::miette!(
+            "AVM: offset {offset} is out of bounds for hardware register '{}' (size {})",
+            reg.name,
+            reg.size
+        ));
+    }
+    if !offset.is_multiple_of(4) {
+        return Err(miette::miette!(
+            "AVM: offset {offset} of hardware register '{}' is not 4-byte aligned",
+            reg.name
+        ));
+    }
+    Ok(reg.base + offset)
+}
+
+fn check_access(reg: &HwRegister, write: bool) -> miette::Result<()> {
+    if reg.access.allows(write) {
+        return Ok(());
+    }
+    let what = if write { "write to" } else { "read of" };
+    Err(miette::miette!(
+        "AVM: {what} hardware register '{}' is not allowed: it is {}",
+        reg.name,
+        reg.access.describe()
+    ))
+}
+
+impl Avm {
+    fn hw_device(&mut self) -> miette::Result<&mut HwDevice> {
+        if self.hw.is_none() {
+            let emu = self.cfg.hw.as_ref().ok_or_else(|| {
+                miette::miette!("AVM: hw.* calls need hardware emulation (AvmConfig::hw, or `--emulate-hw`)")
+            })?;
+            self.hw = Some(HwDevice::new(emu)?);
+        }
+        Ok(self.hw.as_mut().expect("just initialized"))
+    }
+
+    /// The emulated contents of the word at `offset` in register block `register` (`UART[2]`
+    /// for an instance), if any `hw.*` call ran.
+    pub fn hw_word(&self, register: &str, offset: u64) -> Option<u32> {
+        let dev = self.hw.as_ref()?;
+        let (addr, _) = dev.word(register, offset).ok()?;
+        Some(dev.words.get(&addr).copied().unwrap_or(0))
+    }
+
+    /// The priority an enabled interrupt was given by `hw.enable_irq`.
+    pub fn hw_irq_priority(&self, interrupt: &str) -> Option<u64> {
+        let dev = self.hw.as_ref()?;
+        dev.irqs.iter().find(|(n, _)| n.eq_ignore_ascii_case(interrupt)).map(|(_, p)| *p)
+    }
+
+    /// The power state the emulated device is in, if the manifest declares any.
+    pub fn hw_power_state(&self) -> Option<&str> {
+        let dev = self.hw.as_ref()?;
+        Some(dev.manifest.power.as_ref()?.states[dev.power?].as_str())
+    }
+
+    pub(super) fn builtin_hw_dispatch(&mut self, name: &str, args: &[CallArg]) -> miette::Result<AvmValue> {
+        let arity = match name {
+            "hw.open" => 1..=2,
+            "hw.set_power" => 1..=1,
+            "hw.read_u32" | "hw.enable_irq" => 2..=2,
+            "hw.write_u32" | "hw.read_field" | "hw.dma_start" => 3..=3,
+            "hw.write_field" => 4..=4,
+            _ => return Err(miette::miette!("AVM: unknown hw builtin '{name}'")),
+        };
+        if !arity.contains(&args.len()) {
+            return Err(miette::miette!(
+                "AVM: {name} expects {} argument(s), got {}",
+                arity.end(),
+                args.len()
+            ));
+        }
+        let mut vals = Vec::with_capacity(args.len());
+        for a in args {
+            vals.push(self.eval_expr(call_arg_value(a))?);
+        }
+        let int = |idx: usize| -> miette::Result<i64> {
+            match vals[idx] {
+                AvmValue::Int(i) => Ok(i),
+                _ => Err(miette::miette!("AVM: {name} expects an int as argument {}", idx + 1)),
+            }
+        };
+        let string = |idx: usize| -> miette::Result<&str> {
+            match &vals[idx] {
+                AvmValue::Str(s) => Ok(s.as_str()),
+                _ => Err(miette::miette!("AVM: {name} expects a string as argument {}", idx + 1)),
+            }
+        };
+        let offset = |idx: usize| -> miette::Result<u64> {
+            u64::try_from(int(idx)?).map_err(|_| miette::miette!("AVM: {name} offset must not be negative"))
+        };
+
+        let dev = self.hw_device()?;
+        match name {
+            "hw.open" => {
+                let reg_name = string(0)?;
+                let reg = match vals.get(1) {
+                    Some(_) => {
+                        let index = int(1)?;
+                        let block = dev
+                            .manifest
+                            .get(reg_name)
+                            .ok_or_else(|| miette::miette!("AVM: unknown hardware register '{reg_name}'"))?;
+                        match u64::try_from(index) {
+                            Ok(i) if i < block.instances => block.instance(i),
+                            _ => {
+                                return Err(miette::miette!(
+                                    "AVM: instance {index} of '{}' is out of range ({} instance(s))",
+                                    block.name,
+                                    block.instances
+                                ));
+                            }
+                        }
+                    }
+                    None => dev.register(reg_name)?,
+                };
+                dev.caps.push(reg);
+                Ok(AvmValue::Int(dev.caps.len() as i64))
+            }
+            "hw.read_u32" => {
+                let reg = dev.cap(int(0)?)?;
+                check_access(&reg, false)?;
+                dev.check_power(&reg)?;
+                let addr = word_address(&reg, offset(1)?)?;
+                Ok(AvmValue::Int(i64::from(dev.load(addr))))
+            }
+            "hw.write_u32" => {
+                let reg = dev.cap(int(0)?)?;
+                check_access(&reg, true)?;
+                dev.check_power(&reg)?;
+                let addr = word_address(&reg, offset(1)?)?;
+                let value = u32::try_from(int(2)?)
+                    .map_err(|_| miette::miette!("AVM: hw.write_u32 value {} does not fit in 32 bits", vals[2].display()))?;
+                dev.store(addr, reg.mask.unwrap_or(0xFFFF_FFFF), value);
+                Ok(AvmValue::Int(0))
+            }
+            "hw.read_field" => {
+                let reg = dev.cap(int(0)?)?;
+                check_access(&reg, false)?;
+                dev.check_power(&reg)?;
+                let addr = word_address(&reg, offset(1)?)?;
+                let field = reg
+                    .field(string(2)?)
+                    .ok_or_else(|| miette::miette!("AVM: hardware register '{}' has no field '{}'", reg.name, vals[2].display()))?;
+                let word = u64::from(dev.load(addr));
+                Ok(AvmValue::Int(((word & field.mask()) >> field.offset) as i64))
+            }
+            "hw.write_field" => {
+                let reg = dev.cap(int(0)?)?;
+                check_access(&reg, true)?;
+                dev.check_power(&reg)?;
+                let addr = word_address(&reg, offset(1)?)?;
+                let field = reg
+                    .field(string(2)?)
+                    .ok_or_else(|| miette::miette!("AVM: hardware register '{}' has no field '{}'", reg.name, vals[2].display()))?;
+                let value = match &vals[3] {
+                    AvmValue::Str(n) => field.value(n).ok_or_else(|| {
+                        miette::miette!("AVM: field '{}' of register '{}' has no value '{n}'", field.name, reg.name)
+                    })?,
+                    AvmValue::Int(i) if (0..=field.max_value() as i64).contains(i) => *i as u64,
+                    v => {
+                        return Err(miette::miette!(
+                            "AVM: value {} does not fit field '{}' ({} bits)",
+                            v.display(),
+                            field.name,
+                            field.width
+                        ));
+                    }
+                };
+                if !field.values.is_empty() && !field.values.iter().any(|(_, v)| *v == value) {
+                    return Err(miette::miette!(
+                        "AVM: value {value} is not one of the named values of field '{}'",
+                        field.name
+                    ));
+                }
+                let old = u64::from(dev.words.get(&addr).copied().unwrap_or(0));
+                let word = (old & !field.mask()) | (value << field.offset);
+                dev.store(addr, reg.mask.unwrap_or(0xFFFF_FFFF), word as u32);
+                Ok(AvmValue::Int(0))
+            }
+            "hw.enable_irq" => {
+                let irq = dev
+                    .manifest
+                    .interrupt(string(0)?)
+                    .cloned()
+                    .ok_or_else(|| miette::miette!("AVM: unknown interrupt '{}'", vals[0].display()))?;
+                let priority = match u64::try_from(int(1)?) {
+                    Ok(p) if p <= irq.max_priority => p,
+                    _ => {
+                        return Err(miette::miette!(
+                            "AVM: IRQ priority {} is outside [0..{}] for interrupt '{}'",
+                            vals[1].display(),
+                            irq.max_priority,
+                            irq.name
+                        ));
+                    }
+                };
+                dev.irqs.insert(irq.name, priority);
+                Ok(AvmValue::Int(0))
+            }
+            "hw.dma_start" => {
+                let ch = dev
+                    .manifest
+                    .dma_channel(string(0)?)
+                    .cloned()
+                    .ok_or_else(|| miette::miette!("AVM: unknown DMA channel '{}'", vals[0].display()))?;
+                let (buffer, len) = (int(1)?, int(2)?);
+                if len < 0 || len > buffer {
+                    return Err(miette::miette!("AVM: DMA length {len} exceeds the buffer size {buffer}"));
+                }
+                if let Some(max) = ch.max_len
+                    && len as u64 > max
+                {
+                    return Err(miette::miette!(
+                        "AVM: DMA length {len} exceeds the {max}-byte limit of channel '{}'",
+                        ch.name
+                    ));
+                }
+                Ok(AvmValue::Int(0))
+            }
+            "hw.set_power" => {
+                let power = dev
+                    .manifest
+                    .power
+                    .as_ref()
+                    .ok_or_else(|| miette::miette!("AVM: hw.set_power needs a [hardware.power] section in the manifest"))?;
+                let to = power
+                    .index(string(0)?)
+                    .ok_or_else(|| miette::miette!("AVM: unknown power state '{}'", vals[0].display()))?;
+                let from = dev.power.unwrap_or(to);
+                if from != to && !power.sources(&power.states[to]).contains(&from) {
+                    return Err(miette::miette!(
+                        "AVM: power transition {} -> {} is not declared in [hardware.power]",
+                        power.states[from],
+                        power.states[to]
+                    ));
+                }
+                dev.power = Some(to);
+                Ok(AvmValue::Int(0))
+            }
+            _ => unreachable!("arity checked"),
+        }
+    }
+}
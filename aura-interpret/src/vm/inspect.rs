@@ -0,0 +1,172 @@
+//! Expandable views of AVM values for the debugger.
+//!
+//! While a run is paused, every aggregate it reports gets a reference and is kept until the
+//! run resumes, so a client expands records, tuples, lists, maps and UI trees one level (and,
+//! for large ones, one page) at a time instead of receiving them whole.
+
+use aura_ast::Expr;
+
+use super::{Avm, AvmValue};
+use crate::debug::{DebugFrame, DebugValue, DebugVariable};
+
+/// The aggregates reported while paused; reference `n` is `values[n - 1]`.
+#[derive(Debug, Default)]
+pub(super) struct Inspector {
+    values: Vec<AvmValue>,
+}
+
+impl Inspector {
+    /// Describes `v` under `name`, handing out a reference if it has children.
+    pub(super) fn variable(&mut self, name: String, v: &AvmValue) -> DebugVariable {
+        let children = child_count(v);
+        let reference = if children > 0 {
+            self.values.push(v.clone());
+            self.values.len() as u64
+        } else {
+            0
+        };
+        DebugVariable {
+            name,
+            value: summary(v),
+            ty: type_name(v).to_string(),
+            reference,
+            children: (children > 0).then_some(children),
+        }
+    }
+
+    fn expand(&mut self, reference: u64, start: usize, count: usize) -> Result<(usize, Vec<DebugVariable>), String> {
+        let v = reference
+            .checked_sub(1)
+            .and_then(|i| self.values.get(i as usize))
+            .ok_or_else(|| format!("unknown variable reference {reference}"))?;
+        let total = child_count(v);
+        let window = children(v, start, count);
+        let vars = window.into_iter().map(|(name, c)| self.variable(name, &c)).collect();
+        Ok((total, vars))
+    }
+}
+
+/// A paused run as the debugger sees it.
+pub(super) struct PausedFrame<'a> {
+    pub(super) avm: &'a mut Avm,
+    pub(super) inspector: Inspector,
+}
+
+impl DebugFrame for PausedFrame<'_> {
+    fn evaluate(&mut self, expr: &Expr) -> Result<DebugValue, String> {
+        self.avm.eval_paused(expr)
+    }
+
+    fn expand(&mut self, reference: u64, start: usize, count: usize) -> Result<(usize, Vec<DebugVariable>), String> {
+        self.inspector.expand(reference, start, count)
+    }
+}
+
+fn child_count(v: &AvmValue) -> usize {
+    match v {
+        AvmValue::Style(map) => map.len(),
+        AvmValue::Tuple(elems) | AvmValue::List(elems) => elems.len(),
+        AvmValue::Map(entries) => entries.len(),
+        AvmValue::Ui(node) => node.props.len() + node.children.len(),
+        AvmValue::Int(_)
+        | AvmValue::Float(_)
+        | AvmValue::Bool(_)
+        | AvmValue::Str(_)
+        | AvmValue::Unit
+        | AvmValue::Task(_) => 0,
+    }
+}
+
+/// Children `start..start + count` of `v` with their names. UI nodes list their props, then
+/// their child nodes.
+fn children(v: &AvmValue, start: usize, count: usize) -> Vec<(String, AvmValue)> {
+    fn page(it: impl Iterator<Item = (String, AvmValue)>, start: usize, count: usize) -> Vec<(String, AvmValue)> {
+        it.skip(start).take(count).collect()
+    }
+    match v {
+        AvmValue::Style(map) => page(map.iter().map(|(k, vv)| (k.clone(), vv.clone())), start, count),
+        AvmValue::Tuple(elems) => page(elems.iter().enumerate().map(|(i, vv)| (i.to_string(), vv.clone())), start, count),
+        AvmValue::List(elems) => page(
+            elems.iter().enumerate().map(|(i, vv)| (format!("[{i}]"), vv.clone())),
+            start,
+            count,
+        ),
+        AvmValue::Map(entries) => page(entries.iter().map(|(k, vv)| (k.display(), vv.clone())), start, count),
+        AvmValue::Ui(node) => {
+            let props = node.props.iter().map(|(k, vv)| (k.clone(), AvmValue::Str(vv.clone())));
+            let nodes = node.children.iter().enumerate().map(|(i, c)| (format!("[{i}]"), AvmValue::Ui(c.clone())));
+            page(props.chain(nodes), start, count)
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// One line for `v`: scalars in full, tuples inline, other aggregates by size.
+fn summary(v: &AvmValue) -> String {
+    let plural = |n: usize, one: &str, many: &str| format!("{n} {}", if n == 1 { one } else { many });
+    match v {
+        AvmValue::Str(s) => format!("{s:?}"),
+        AvmValue::Style(map) => format!("{{{}}}", plural(map.len(), "field", "fields")),
+        AvmValue::Ui(node) => format!("<{}>", node.kind),
+        AvmValue::Tuple(elems) => format!("({})", elems.iter().map(summary).collect::<Vec<_>>().join(", ")),
+        AvmValue::List(elems) => format!("[{}]", plural(elems.len(), "item", "items")),
+        AvmValue::Map(entries) => format!("{{{}}}", plural(entries.len(), "entry", "entries")),
+        AvmValue::Int(_) | AvmValue::Float(_) | AvmValue::Bool(_) | AvmValue::Unit | AvmValue::Task(_) => v.display(),
+    }
+}
+
+/// The `type` tag `DebugValue` uses for `v`.
+fn type_name(v: &AvmValue) -> &'static str {
+    match v {
+        AvmValue::Int(_) => "int",
+        AvmValue::Float(_) => "float",
+        AvmValue::Bool(_) => "bool",
+        AvmValue::Str(_) => "str",
+        AvmValue::Style(_) => "record",
+        AvmValue::Ui(_) => "ui",
+        AvmValue::Tuple(_) => "tuple",
+        AvmValue::List(_) => "list",
+        AvmValue::Map(_) => "map",
+        AvmValue::Unit => "unit",
+        AvmValue::Task(_) => "task",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn large_lists_expand_a_page_at_a_time() {
+        let mut insp = Inspector::default();
+        let list = AvmValue::List((0..250).map(AvmValue::Int).collect());
+        let var = insp.variable("xs".to_string(), &list);
+        assert_eq!((var.value.as_str(), var.children), ("[250 items]", Some(250)));
+
+        let (total, page) = insp.expand(var.reference, 200, DebugVariable::PAGE).expect("expand");
+        assert_eq!((total, page.len()), (250, 50));
+        assert_eq!((page[0].name.as_str(), page[0].value.as_str()), ("[200]", "200"));
+        assert!(insp.expand(99, 0, 1).is_err());
+    }
+
+    #[test]
+    fn nested_aggregates_get_their_own_references() {
+        let mut insp = Inspector::default();
+        let rec = AvmValue::Style(
+            [
+                ("name".to_string(), AvmValue::Str("ada".to_string())),
+                ("pos".to_string(), AvmValue::Tuple(vec![AvmValue::Int(1), AvmValue::Int(2)])),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let var = insp.variable("r".to_string(), &rec);
+        assert_eq!(var.value, "{2 fields}");
+
+        let (_, fields) = insp.expand(var.reference, 0, 10).expect("expand");
+        assert_eq!((fields[0].name.as_str(), fields[0].value.as_str(), fields[0].reference), ("name", "\"ada\"", 0));
+        assert_eq!((fields[1].value.as_str(), fields[1].ty.as_str()), ("(1, 2)", "tuple"));
+        let (_, elems) = insp.expand(fields[1].reference, 0, 10).expect("expand");
+        assert_eq!(elems.iter().map(|e| e.name.as_str()).collect::<Vec<_>>(), ["0", "1"]);
+    }
+}
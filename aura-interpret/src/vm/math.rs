@@ -0,0 +1,40 @@
+//! `math.*` calls. The u32 calls fail where compiled code would trap (`math.pow` past
+//! u32::MAX); the f64 calls are Rust's, which match the LLVM intrinsics they lower to.
+
+use aura_ast::CallArg;
+
+use super::{call_arg_value, Avm, AvmValue};
+
+impl Avm {
+    pub(super) fn builtin_math_dispatch(&mut self, name: &str, args: &[CallArg]) -> miette::Result<AvmValue> {
+        let arity = match name {
+            "math.abs" | "math.sqrt" | "math.sin" | "math.cos" => 1,
+            "math.min" | "math.max" | "math.pow" | "math.powf" => 2,
+            _ => return Err(miette::miette!("AVM: unknown math builtin '{name}'")),
+        };
+        if args.len() != arity {
+            return Err(miette::miette!("AVM: {name} expects {arity} argument(s), got {}", args.len()));
+        }
+        let mut vals = Vec::with_capacity(args.len());
+        for a in args {
+            vals.push(self.eval_expr(call_arg_value(a))?);
+        }
+
+        match (name, vals.as_slice()) {
+            ("math.min", [AvmValue::Int(a), AvmValue::Int(b)]) => Ok(AvmValue::Int(*a.min(b))),
+            ("math.max", [AvmValue::Int(a), AvmValue::Int(b)]) => Ok(AvmValue::Int(*a.max(b))),
+            ("math.pow", [AvmValue::Int(base), AvmValue::Int(exp)]) => u32::try_from(*base)
+                .ok()
+                .zip(u32::try_from(*exp).ok())
+                .and_then(|(b, e)| b.checked_pow(e))
+                .map(|v| AvmValue::Int(i64::from(v)))
+                .ok_or_else(|| miette::miette!("AVM: math.pow: {base}^{exp} does not fit a u32")),
+            ("math.abs", [AvmValue::Float(x)]) => Ok(AvmValue::Float(x.abs())),
+            ("math.sqrt", [AvmValue::Float(x)]) => Ok(AvmValue::Float(x.sqrt())),
+            ("math.sin", [AvmValue::Float(x)]) => Ok(AvmValue::Float(x.sin())),
+            ("math.cos", [AvmValue::Float(x)]) => Ok(AvmValue::Float(x.cos())),
+            ("math.powf", [AvmValue::Float(x), AvmValue::Float(y)]) => Ok(AvmValue::Float(x.powf(*y))),
+            _ => Err(miette::miette!("AVM: {name}: unexpected arguments {vals:?}")),
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//! `net.*` calls: TCP client connections, on aura-rt-native's `SocketTable`.
+//!
+//! Sockets are `Int` handles into a table shared with the workers running the program's flows.
+//! Every call first moves its socket through aura-core's `NetworkVerifier` state machine
+//! (connected, then sending and receiving, then closed once) and fails when the verifier
+//! rejects the step. What `net.recv` and `net.recv_all` return is recorded in traces like
+//! `fs.read` results and replayed from them; a replay still connects and sends but does not
+//! receive.
+
+use aura_ast::CallArg;
+use aura_core::SocketState;
+use aura_rt_native::SocketError;
+
+use super::{call_arg_value, Avm, AvmValue};
+use crate::trace::TraceEvent;
+
+impl Avm {
+    pub(super) fn builtin_net_dispatch(&mut self, name: &str, args: &[CallArg]) -> miette::Result<AvmValue> {
+        let arity = match name {
+            "net.recv" | "net.recv_all" | "net.close" => 1,
+            "net.connect" | "net.send" => 2,
+            _ => return Err(miette::miette!("AVM: unknown net builtin '{name}'")),
+        };
+        if args.len() != arity {
+            return Err(miette::miette!("AVM: {name} expects {arity} argument(s), got {}", args.len()));
+        }
+        let failed = |e: SocketError| miette::miette!("AVM: {name}: {e}");
+
+        match name {
+            "net.connect" => {
+                let host = self.net_string(name, &args[0])?;
+                let port = match self.eval_expr(call_arg_value(&args[1]))? {
+                    AvmValue::Int(p) => {
+                        u16::try_from(p).map_err(|_| miette::miette!("AVM: net.connect: port {p} is out of range"))?
+                    }
+                    v => return Err(miette::miette!("AVM: net.connect expects an int port, got {v:?}")),
+                };
+                let h = self.sockets.connect(&host, port).map_err(failed)?;
+                let mut states = self.socket_states.lock().unwrap_or_else(|e| e.into_inner());
+                states.init_socket(&socket_name(h));
+                states.transition_socket(&socket_name(h), SocketState::Open);
+                Ok(AvmValue::Int(i64::from(h)))
+            }
+            "net.send" => {
+                let h = self.net_handle(name, &args[0])?;
+                let data = self.net_string(name, &args[1])?;
+                self.socket_step(name, h, SocketState::Writing)?;
+                self.sockets.send(h, &data).map_err(failed)?;
+                Ok(AvmValue::Unit)
+            }
+            "net.recv" | "net.recv_all" => {
+                let h = self.net_handle(name, &args[0])?;
+                self.socket_step(name, h, SocketState::Reading)?;
+                let text = if let Some(trace) = &mut self.trace
+                    && trace.is_replaying()
+                {
+                    let TraceEvent::ReadText { text, .. } = trace.replay(TraceEvent::READ_TEXT)? else {
+                        unreachable!("replay returns the requested kind");
+                    };
+                    text
+                } else {
+                    let peer = self.sockets.peer(h).map_err(failed)?;
+                    let read = if name == "net.recv" { self.sockets.recv(h) } else { self.sockets.recv_all(h) };
+                    let read = read.map_err(|e| e.to_string());
+                    self.record(TraceEvent::ReadText { path: peer, text: read.clone() })?;
+                    read
+                };
+                text.map(AvmValue::Str).map_err(|e| miette::miette!("AVM: {name}: {e}"))
+            }
+            _ => {
+                let h = self.net_handle(name, &args[0])?;
+                self.socket_step(name, h, SocketState::Closed)?;
+                self.sockets.close(h).map_err(failed)?;
+                Ok(AvmValue::Unit)
+            }
+        }
+    }
+
+    /// Moves socket `h` to `to`, failing with the verifier's reason when the step is invalid.
+    fn socket_step(&mut self, name: &str, h: u32, to: SocketState) -> miette::Result<()> {
+        let mut states = self.socket_states.lock().unwrap_or_else(|e| e.into_inner());
+        if states.transition_socket(&socket_name(h), to) {
+            return Ok(());
+        }
+        let why = states.violations().last().cloned().unwrap_or_default();
+        Err(miette::miette!("AVM: {name}: socket {h}: {why}"))
+    }
+
+    fn net_handle(&mut self, name: &str, arg: &CallArg) -> miette::Result<u32> {
+        match self.eval_expr(call_arg_value(arg))? {
+            AvmValue::Int(i) => u32::try_from(i).map_err(|_| miette::miette!("AVM: {name}: unknown socket {i}")),
+            v => Err(miette::miette!("AVM: {name} expects a socket, got {v:?}")),
+        }
+    }
+
+    fn net_string(&mut self, name: &str, arg: &CallArg) -> miette::Result<String> {
+        match self.eval_expr(call_arg_value(arg))? {
+            AvmValue::Str(s) => Ok(s),
+            v => Err(miette::miette!("AVM: {name} expects a string, got {v:?}")),
+        }
+    }
+}
+
+fn socket_name(h: u32) -> String {
+    format!("socket {h}")
+}
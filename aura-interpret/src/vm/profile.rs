@@ -0,0 +1,219 @@
+//! Cell-level profiling of AVM runs.
+//!
+//! Every call of a user cell is timed while profiling is on (`AvmConfig::profile`, or a
+//! debugger with perf enabled). The profile keeps per-cell call counts with self and total
+//! time, and the self time of every distinct call stack, which it exports in the collapsed
+//! format flamegraph tools read (`program;main;draw 1200`, times in nanoseconds). Stacks start
+//! at `program`, whose self time is the run's time outside any cell.
+//!
+//! Live UI loops can be sampled: with a frame interval of N only the first frame and every
+//! Nth after it are profiled, and the time spent in the others is left out of every figure.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use crate::debug::PerfCell;
+
+/// The root of every collapsed stack.
+const ROOT: &str = "program";
+
+#[derive(Debug)]
+struct OpenCall {
+    name: String,
+    start: Instant,
+    /// `skipped` when the call started.
+    skipped_at_start: Duration,
+    /// Total time of the calls it made.
+    children: Duration,
+}
+
+#[derive(Debug, Default)]
+struct CellTimes {
+    calls: u64,
+    self_time: Duration,
+    total: Duration,
+}
+
+/// The profile of one run.
+#[derive(Debug)]
+pub struct Profile {
+    started: Instant,
+    frame_interval: u32,
+    /// UI frames started so far.
+    frames: u64,
+    /// When the current frame started, if it is not sampled.
+    unsampled_since: Option<Instant>,
+    /// Time spent in frames that were not sampled.
+    skipped: Duration,
+    /// Open calls, innermost last; `None` for calls made in frames that are not sampled.
+    open: Vec<Option<OpenCall>>,
+    /// Total time of the calls made outside any cell.
+    top_level_calls: Duration,
+    cells: BTreeMap<String, CellTimes>,
+    stacks: BTreeMap<String, Duration>,
+    finished: Option<Duration>,
+}
+
+impl Profile {
+    pub(super) fn new(frame_interval: Option<u32>) -> Self {
+        Self {
+            started: Instant::now(),
+            frame_interval: frame_interval.unwrap_or(1).max(1),
+            frames: 0,
+            unsampled_since: None,
+            skipped: Duration::ZERO,
+            open: Vec::new(),
+            top_level_calls: Duration::ZERO,
+            cells: BTreeMap::new(),
+            stacks: BTreeMap::new(),
+            finished: None,
+        }
+    }
+
+    /// Whether the running code is being profiled, i.e. not in a frame left out by sampling.
+    pub(super) fn sampling(&self) -> bool {
+        self.unsampled_since.is_none()
+    }
+
+    /// Starts the next frame of a live UI loop, ending the one before it.
+    pub(super) fn next_frame(&mut self) {
+        self.end_frames();
+        if !self.frames.is_multiple_of(u64::from(self.frame_interval)) {
+            self.unsampled_since = Some(Instant::now());
+        }
+        self.frames += 1;
+    }
+
+    /// Ends the last frame of a live UI loop.
+    pub(super) fn end_frames(&mut self) {
+        if let Some(since) = self.unsampled_since.take() {
+            self.skipped += since.elapsed();
+        }
+    }
+
+    pub(super) fn enter(&mut self, name: &str) {
+        let call = self.sampling().then(|| OpenCall {
+            name: name.to_string(),
+            start: Instant::now(),
+            skipped_at_start: self.skipped,
+            children: Duration::ZERO,
+        });
+        self.open.push(call);
+    }
+
+    pub(super) fn exit(&mut self) {
+        let Some(Some(call)) = self.open.pop() else {
+            return;
+        };
+        let total = call
+            .start
+            .elapsed()
+            .saturating_sub(self.skipped.saturating_sub(call.skipped_at_start));
+        let self_time = total.saturating_sub(call.children);
+
+        let mut path = String::from(ROOT);
+        for outer in self.open.iter().flatten() {
+            path.push(';');
+            path.push_str(&outer.name);
+        }
+        // A recursive call's time is already part of the outer call of the same cell.
+        let recursive = self.open.iter().flatten().any(|outer| outer.name == call.name);
+        path.push(';');
+        path.push_str(&call.name);
+        *self.stacks.entry(path).or_default() += self_time;
+
+        let times = self.cells.entry(call.name).or_default();
+        times.calls += 1;
+        times.self_time += self_time;
+        if !recursive {
+            times.total += total;
+        }
+        match self.open.iter_mut().rev().flatten().next() {
+            Some(outer) => outer.children += total,
+            None => self.top_level_calls += total,
+        }
+    }
+
+    /// Stops the clock at the end of the run.
+    pub(super) fn finish(&mut self) {
+        self.end_frames();
+        self.finished = Some(self.started.elapsed().saturating_sub(self.skipped));
+    }
+
+    /// Profiled time of the whole run so far.
+    pub fn total_ns(&self) -> u64 {
+        let total = self.finished.unwrap_or_else(|| self.started.elapsed().saturating_sub(self.skipped));
+        total.as_nanos() as u64
+    }
+
+    /// UI frames rendered during the run, sampled or not.
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Call counts and times of every cell called, by name.
+    pub fn cells(&self) -> Vec<PerfCell> {
+        self.cells
+            .iter()
+            .map(|(name, t)| PerfCell {
+                name: name.clone(),
+                calls: t.calls,
+                self_ns: t.self_time.as_nanos() as u64,
+                total_ns: t.total.as_nanos() as u64,
+            })
+            .collect()
+    }
+
+    /// Self time per call stack in collapsed-stack format, one `frame;frame;... ns` line each.
+    pub fn to_collapsed(&self) -> String {
+        let root = Duration::from_nanos(self.total_ns()).saturating_sub(self.top_level_calls);
+        let mut out = format!("{ROOT} {}\n", root.as_nanos());
+        for (stack, time) in &self.stacks {
+            out.push_str(&format!("{stack} {}\n", time.as_nanos()));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_and_recursive_calls_split_self_and_total_time() {
+        let mut p = Profile::new(None);
+        p.enter("main");
+        p.enter("fib");
+        p.enter("fib");
+        std::thread::sleep(Duration::from_millis(2));
+        p.exit();
+        p.exit();
+        p.exit();
+        p.finish();
+
+        let cells = p.cells();
+        let fib = cells.iter().find(|c| c.name == "fib").expect("fib");
+        let main = cells.iter().find(|c| c.name == "main").expect("main");
+        assert_eq!((fib.calls, main.calls), (2, 1));
+        // The inner call's time counts once towards `fib`'s total.
+        assert!(fib.total_ns <= main.total_ns, "{cells:?}");
+        assert!(fib.total_ns >= 2_000_000);
+
+        let collapsed = p.to_collapsed();
+        let stacks: Vec<&str> = collapsed.lines().map(|l| l.rsplit_once(' ').expect("weight").0).collect();
+        assert_eq!(stacks, ["program", "program;main", "program;main;fib", "program;main;fib;fib"]);
+    }
+
+    #[test]
+    fn unsampled_frames_are_left_out() {
+        let mut p = Profile::new(Some(2));
+        for _ in 0..4 {
+            p.next_frame();
+            p.enter("draw");
+            p.exit();
+        }
+        p.end_frames();
+        assert_eq!(p.frames(), 4);
+        assert_eq!(p.cells()[0].calls, 2);
+    }
+}
@@ -0,0 +1,32 @@
+//! `region` blocks: the tensors made in one are freed when it ends.
+//!
+//! Like a `scope`, a region first waits for every task spawned since it started. Tasks run
+//! outside of any region (see `run_task`), so what they make outlives it, as it does in
+//! compiled programs. The checker keeps the region's own tensors from escaping it.
+
+use aura_ast::RegionBlock;
+use aura_nexus::{NexusContext, UiPluginDispatch};
+
+use super::{Avm, AvmValue};
+
+impl Avm {
+    pub(super) fn exec_region(
+        &mut self,
+        r: &RegionBlock,
+        ui_plugins: &dyn UiPluginDispatch,
+        nexus: &mut NexusContext,
+    ) -> miette::Result<AvmValue> {
+        self.ai.enter_region();
+        let before = self.last_task_id();
+        let body = self.exec_block(&r.body, ui_plugins, nexus);
+
+        // A `return` or `break` leaves the region only once its tasks are done.
+        let pending = self.jump.take();
+        let joined = self.join_tasks_after(before, || {});
+        self.jump = pending;
+        self.ai.exit_region();
+        let v = body?;
+        joined?;
+        Ok(v)
+    }
+}
@@ -0,0 +1,240 @@
+//! Hot reload of a program while one of its live UI loops runs.
+//!
+//! With `AvmConfig::hot_reload` set, live UI loops check the program's file between frames.
+//! Once it changed, the AVM re-parses it and compares the merkle hash of every top-level unit
+//! (cell, flow, layout or render; see `aura_sdk::merkle`) with the running version's. Changed
+//! and new cells are swapped in, and a loop whose own unit changed continues with the new body
+//! of its layout or render block. Nothing is re-run, so bindings and strands keep their state;
+//! a file that no longer parses leaves the running version in place.
+
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use aura_ast::{Block, Program, Span, Stmt};
+use aura_sdk::merkle::{sha256_hex, stmt_merkle_hashes};
+
+use super::normalize_source_for_plugin_imports;
+
+/// Turns the text of a reloaded file into the source the run executes.
+pub type PrepareSourceFn = Arc<dyn Fn(&str) -> miette::Result<String> + Send + Sync>;
+
+/// Where a hot-reloading run re-reads its program.
+#[derive(Clone)]
+pub struct HotReload {
+    /// The file the program was read from.
+    pub path: PathBuf,
+    /// Applied to the file's text before it is parsed, for hosts that run more than the file
+    /// (e.g. with a prelude added).
+    pub prepare: Option<PrepareSourceFn>,
+}
+
+impl HotReload {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            prepare: None,
+        }
+    }
+
+    pub fn with_prepare(mut self, prepare: impl Fn(&str) -> miette::Result<String> + Send + Sync + 'static) -> Self {
+        self.prepare = Some(Arc::new(prepare));
+        self
+    }
+}
+
+impl fmt::Debug for HotReload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HotReload")
+            .field("path", &self.path)
+            .field("prepare", &self.prepare.is_some())
+            .finish()
+    }
+}
+
+/// A top-level statement with a merkle hash.
+#[derive(Debug)]
+struct Unit {
+    key: String,
+    index: usize,
+    span: Span,
+    hash: String,
+    /// The layout and render blocks in the unit, itself included, in source order.
+    ui: Vec<Span>,
+}
+
+/// An edit of the program's file, ready to apply.
+pub(super) struct Reload {
+    pub(super) text: String,
+    pub(super) program: Program,
+    /// Units whose hash changed, and new ones.
+    pub(super) changed: Vec<String>,
+    /// Cells to (re)define: the changed ones and those the edit moved.
+    pub(super) cells: Vec<usize>,
+    /// The new span and body of the running layout or render block, if its unit changed.
+    pub(super) running: Option<(Span, Block)>,
+}
+
+/// Watches the file of the running program.
+#[derive(Debug)]
+pub(super) struct Reloader {
+    cfg: HotReload,
+    stamp: Option<(SystemTime, u64)>,
+    units: Vec<Unit>,
+}
+
+impl Reloader {
+    /// Starts watching for edits of `cfg.path` after `program` (parsed from `text`) started.
+    pub(super) fn new(cfg: HotReload, text: &str, program: &Program) -> Self {
+        Self {
+            stamp: stamp(&cfg),
+            cfg,
+            units: units(program, text),
+        }
+    }
+
+    /// Re-parses the file if it changed since the last poll. `running` is the span of the
+    /// layout or render block whose loop is polling.
+    pub(super) fn poll(&mut self, running: Span) -> Result<Option<Reload>, String> {
+        let now = stamp(&self.cfg);
+        if now == self.stamp {
+            return Ok(None);
+        }
+        self.stamp = now;
+
+        let path = self.cfg.path.display();
+        let text = fs::read_to_string(&self.cfg.path).map_err(|e| format!("failed to read {path}: {e}"))?;
+        let text = match &self.cfg.prepare {
+            Some(prepare) => prepare(&text).map_err(|e| format!("{e}"))?,
+            None => text,
+        };
+        let text = normalize_source_for_plugin_imports(&text);
+        let program = aura_parse::parse_source(&text).map_err(|e| format!("{path}: {e}"))?;
+        let units = units(&program, &text);
+
+        let mut changed = Vec::new();
+        let mut cells = Vec::new();
+        for unit in &units {
+            let old = self.units.iter().find(|o| o.key == unit.key);
+            let edited = old.is_none_or(|o| o.hash != unit.hash);
+            if edited {
+                changed.push(unit.key.clone());
+            }
+            // Moved cells are redefined too, so what the debugger and coverage see stays in
+            // step with the text.
+            if matches!(program.stmts[unit.index], Stmt::CellDef(_)) && (edited || old.is_some_and(|o| o.span != unit.span)) {
+                cells.push(unit.index);
+            }
+        }
+
+        let running = self.units.iter().find_map(|old| {
+            let nth = old.ui.iter().position(|s| *s == running)?;
+            let unit = units.iter().find(|u| u.key == old.key)?;
+            if unit.hash == old.hash && unit.ui == old.ui {
+                return None;
+            }
+            let mut blocks = Vec::new();
+            ui_blocks(&program.stmts[unit.index], &mut blocks);
+            blocks.get(nth).map(|(span, body)| (*span, (*body).clone()))
+        });
+
+        self.units = units;
+        Ok(Some(Reload {
+            text,
+            program,
+            changed,
+            cells,
+            running,
+        }))
+    }
+}
+
+fn stamp(cfg: &HotReload) -> Option<(SystemTime, u64)> {
+    let meta = fs::metadata(&cfg.path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+/// The hashed top-level statements of `program`. Cells and flows are keyed by name, top-level
+/// layouts and renders by their position among their kind.
+fn units(program: &Program, text: &str) -> Vec<Unit> {
+    let slice = |span: Span| text.get(span.offset()..span.offset() + span.len()).unwrap_or("");
+
+    // Imports, types and top-level bindings can change any unit's meaning.
+    let mut shared = String::new();
+    for stmt in &program.stmts {
+        if !matches!(stmt, Stmt::CellDef(_) | Stmt::FlowBlock(_) | Stmt::Layout(_) | Stmt::Render(_)) {
+            shared.push_str(slice(stmt.span()));
+            shared.push('\n');
+        }
+    }
+    let hashes = stmt_merkle_hashes(program, text, &sha256_hex(&shared));
+
+    let (mut layouts, mut renders) = (0, 0);
+    let mut out = Vec::new();
+    for (index, (stmt, hash)) in program.stmts.iter().zip(hashes).enumerate() {
+        let Some(hash) = hash else { continue };
+        let key = match stmt {
+            Stmt::CellDef(cell) => cell.name.node.clone(),
+            Stmt::FlowBlock(flow) => flow.name.node.clone(),
+            Stmt::Layout(_) => {
+                layouts += 1;
+                format!("layout #{}", layouts - 1)
+            }
+            Stmt::Render(_) => {
+                renders += 1;
+                format!("render #{}", renders - 1)
+            }
+            _ => continue,
+        };
+        let mut blocks = Vec::new();
+        ui_blocks(stmt, &mut blocks);
+        out.push(Unit {
+            key,
+            index,
+            span: stmt.span(),
+            hash: hash.hash,
+            ui: blocks.into_iter().map(|(span, _)| span).collect(),
+        });
+    }
+    out
+}
+
+/// The layout and render blocks reachable through the statements of `stmt`, in source order.
+fn ui_blocks<'a>(stmt: &'a Stmt, out: &mut Vec<(Span, &'a Block)>) {
+    fn block<'a>(b: &'a Block, out: &mut Vec<(Span, &'a Block)>) {
+        for s in &b.stmts {
+            ui_blocks(s, out);
+        }
+    }
+    match stmt {
+        Stmt::Layout(lb) => {
+            out.push((lb.span, &lb.body));
+            block(&lb.body, out);
+        }
+        Stmt::Render(rb) => {
+            out.push((rb.span, &rb.body));
+            block(&rb.body, out);
+        }
+        Stmt::CellDef(cell) => block(&cell.body, out),
+        Stmt::FlowBlock(flow) => block(&flow.body, out),
+        Stmt::UnsafeBlock(ub) => block(&ub.body, out),
+        Stmt::Scope(sc) => block(&sc.body, out),
+        Stmt::Region(r) => block(&r.body, out),
+        Stmt::If(i) => {
+            block(&i.then_block, out);
+            if let Some(b) = &i.else_block {
+                block(b, out);
+            }
+        }
+        Stmt::Match(m) => {
+            for arm in &m.arms {
+                block(&arm.body, out);
+            }
+        }
+        Stmt::While(w) => block(&w.body, out),
+        Stmt::For(f) => block(&f.body, out),
+        _ => {}
+    }
+}
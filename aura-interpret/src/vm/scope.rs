@@ -0,0 +1,67 @@
+//! `scope` blocks and their `scope.*` calls, on aura-rt-native's `ScopeTable`.
+//!
+//! A scope binds a fresh cancellation token (an `Int` handle) to its name, runs its body and
+//! then waits for every task spawned since it started, including those nobody awaited and
+//! those the tasks spawned in turn, before the block ends. The first task to fail cancels the
+//! token; the others are still waited for, and the failure then fails the scope.
+
+use std::sync::Arc;
+
+use aura_ast::{CallArg, ScopeBlock};
+use aura_nexus::{NexusContext, UiPluginDispatch};
+
+use super::{call_arg_value, Avm, AvmValue};
+
+impl Avm {
+    pub(super) fn exec_scope(
+        &mut self,
+        s: &ScopeBlock,
+        ui_plugins: &dyn UiPluginDispatch,
+        nexus: &mut NexusContext,
+    ) -> miette::Result<AvmValue> {
+        let tok = self.scopes.open();
+        if let Some(name) = &s.token {
+            self.env.insert(name.node.clone(), AvmValue::Int(i64::from(tok)));
+        }
+        let before = self.last_task_id();
+        let body = self.exec_block(&s.body, ui_plugins, nexus);
+        if body.is_err() {
+            // Whatever is still running should stop; the body's error is the one reported.
+            let _ = self.scopes.cancel(tok);
+        }
+
+        // A `return` or `break` leaves the scope only once its tasks are done.
+        let pending = self.jump.take();
+        let scopes = Arc::clone(&self.scopes);
+        let joined = self.join_tasks_after(before, || {
+            let _ = scopes.cancel(tok);
+        });
+        self.jump = pending;
+        let _ = self.scopes.close(tok);
+        let v = body?;
+        joined?;
+        Ok(v)
+    }
+
+    pub(super) fn builtin_scope_dispatch(&mut self, name: &str, args: &[CallArg]) -> miette::Result<AvmValue> {
+        if !matches!(name, "scope.cancel" | "scope.cancelled") {
+            return Err(miette::miette!("AVM: unknown scope builtin '{name}'"));
+        }
+        let [arg] = args else {
+            return Err(miette::miette!("AVM: {name} expects 1 argument(s), got {}", args.len()));
+        };
+        let tok = match self.eval_expr(call_arg_value(arg))? {
+            AvmValue::Int(i) => {
+                u32::try_from(i).map_err(|_| miette::miette!("AVM: {name}: unknown cancellation token {i}"))?
+            }
+            v => return Err(miette::miette!("AVM: {name} expects a cancellation token, got {v:?}")),
+        };
+        let failed = |e| miette::miette!("AVM: {name}: {e}");
+        if name == "scope.cancel" {
+            self.scopes.cancel(tok).map_err(failed)?;
+            Ok(AvmValue::Unit)
+        } else {
+            Ok(AvmValue::Bool(self.scopes.is_cancelled(tok).map_err(failed)?))
+        }
+    }
+}
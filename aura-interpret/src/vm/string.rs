@@ -0,0 +1,80 @@
+//! `str.*` calls, with the byte semantics of the `aura_string_*` functions of the stdlib.
+//!
+//! Lengths and offsets count bytes, so results match compiled programs exactly. A slice is
+//! clamped to the string like `aura_string_slice`, but an offset that falls inside a UTF-8
+//! character is an error here, since AVM strings are always valid UTF-8.
+
+use aura_ast::CallArg;
+
+use super::{call_arg_value, Avm, AvmValue};
+
+impl Avm {
+    pub(super) fn builtin_str_dispatch(&mut self, name: &str, args: &[CallArg]) -> miette::Result<AvmValue> {
+        let arity = match name {
+            "str.len" | "str.trim" => 1,
+            "str.concat" | "str.find" | "str.split_count" | "str.pad_left" | "str.pad_right" => 2,
+            "str.slice" | "str.split_nth" => 3,
+            _ => return Err(miette::miette!("AVM: unknown str builtin '{name}'")),
+        };
+        if args.len() != arity {
+            return Err(miette::miette!("AVM: {name} expects {arity} argument(s), got {}", args.len()));
+        }
+        let mut vals = Vec::with_capacity(args.len());
+        for a in args {
+            vals.push(self.eval_expr(call_arg_value(a))?);
+        }
+        let text = |v: &AvmValue| -> miette::Result<String> {
+            match v {
+                AvmValue::Str(s) => Ok(s.clone()),
+                _ => Err(miette::miette!("AVM: {name} expects a string, got {v:?}")),
+            }
+        };
+        let count = |v: &AvmValue| -> miette::Result<usize> {
+            match v {
+                AvmValue::Int(i) => u32::try_from(*i)
+                    .map(|n| n as usize)
+                    .map_err(|_| miette::miette!("AVM: {name}: {i} does not fit 32 bits")),
+                _ => Err(miette::miette!("AVM: {name} expects an int, got {v:?}")),
+            }
+        };
+        let len = |n: usize| AvmValue::Int(n as i64);
+
+        let s = text(&vals[0])?;
+        match name {
+            "str.len" => Ok(len(s.len())),
+            "str.concat" => Ok(AvmValue::Str(s + &text(&vals[1])?)),
+            "str.slice" => {
+                let end = count(&vals[2])?.min(s.len());
+                let start = count(&vals[1])?.min(end);
+                s.get(start..end)
+                    .map(|part| AvmValue::Str(part.to_string()))
+                    .ok_or_else(|| miette::miette!("AVM: str.slice: bytes {start}..{end} split a character"))
+            }
+            "str.find" => Ok(len(s.find(text(&vals[1])?.as_str()).unwrap_or(s.len()))),
+            "str.split_count" => {
+                let sep = text(&vals[1])?;
+                Ok(len(if sep.is_empty() { 1 } else { s.split(sep.as_str()).count() }))
+            }
+            "str.split_nth" => {
+                let sep = text(&vals[1])?;
+                let index = count(&vals[2])?;
+                let field = if sep.is_empty() {
+                    (index == 0).then_some(s.as_str())
+                } else {
+                    s.split(sep.as_str()).nth(index)
+                };
+                field
+                    .map(|f| AvmValue::Str(f.to_string()))
+                    .ok_or_else(|| miette::miette!("AVM: str.split_nth: there is no field {index}"))
+            }
+            "str.trim" => Ok(AvmValue::Str(
+                s.trim_matches(|c: char| c == ' ' || ('\t'..='\r').contains(&c)).to_string(),
+            )),
+            _ => {
+                let width = count(&vals[1])?;
+                let fill = " ".repeat(width.saturating_sub(s.len()));
+                Ok(AvmValue::Str(if name == "str.pad_left" { fill + &s } else { s + &fill }))
+            }
+        }
+    }
+}
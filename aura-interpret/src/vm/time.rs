@@ -0,0 +1,126 @@
+//! `time.*` calls, on aura-rt-native's `Clock`.
+//!
+//! The clock starts with the `Avm` and is shared with the workers running its flows.
+//! `time.now_ms` and `time.unix_s` readings are recorded in traces and replayed from them.
+//! `time.sleep_ms` first runs every queued cooperative task, as waiting on a task would, then
+//! blocks the thread for what is left of the delay; worker threads just block. Replays do not
+//! wait, since every reading the program takes is replayed anyway. The arithmetic and
+//! formatting calls match `aura_time_*` in the C runtime byte for byte.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use aura_ast::CallArg;
+
+use super::{call_arg_value, Avm, AvmValue, FlowScheduling};
+use crate::trace::TraceEvent;
+
+impl Avm {
+    pub(super) fn builtin_time_dispatch(&mut self, name: &str, args: &[CallArg]) -> miette::Result<AvmValue> {
+        let arity = match name {
+            "time.now_ms" | "time.unix_s" => 0,
+            "time.sleep_ms" | "time.format_utc" | "time.format_ms" => 1,
+            "time.add_ms" | "time.elapsed_ms" => 2,
+            _ => return Err(miette::miette!("AVM: unknown time builtin '{name}'")),
+        };
+        if args.len() != arity {
+            return Err(miette::miette!("AVM: {name} expects {arity} argument(s), got {}", args.len()));
+        }
+        match (name, args) {
+            ("time.now_ms", []) => {
+                let ms = if let Some(trace) = &mut self.trace
+                    && trace.is_replaying()
+                {
+                    let TraceEvent::Clock { ms } = trace.replay(TraceEvent::CLOCK)? else {
+                        unreachable!("replay returns the requested kind");
+                    };
+                    ms
+                } else {
+                    // Compiled programs read the clock as a u32, so the AVM wraps it the same way.
+                    let ms = self.clock.now_ms() as u32;
+                    self.record(TraceEvent::Clock { ms })?;
+                    ms
+                };
+                Ok(AvmValue::Int(i64::from(ms)))
+            }
+            ("time.sleep_ms", [ms]) => {
+                let ms = match self.eval_expr(call_arg_value(ms))? {
+                    AvmValue::Int(i) => u32::try_from(i)
+                        .map_err(|_| miette::miette!("AVM: time.sleep_ms: {i} ms does not fit 32 bits"))?,
+                    v => return Err(miette::miette!("AVM: time.sleep_ms expects an int delay, got {v:?}")),
+                };
+                let deadline = self.clock.deadline_after(u64::from(ms));
+                if self.cfg.flows == FlowScheduling::Cooperative {
+                    while self.run_next_task()? {}
+                }
+                if !self.trace.as_ref().is_some_and(|t| t.is_replaying()) {
+                    self.clock.sleep_until(deadline);
+                }
+                Ok(AvmValue::Unit)
+            }
+            ("time.unix_s", []) => {
+                let s = if let Some(trace) = &mut self.trace
+                    && trace.is_replaying()
+                {
+                    let TraceEvent::WallClock { s } = trace.replay(TraceEvent::WALL_CLOCK)? else {
+                        unreachable!("replay returns the requested kind");
+                    };
+                    s
+                } else {
+                    let since_epoch = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+                    let s = since_epoch.as_secs() as u32;
+                    self.record(TraceEvent::WallClock { s })?;
+                    s
+                };
+                Ok(AvmValue::Int(i64::from(s)))
+            }
+            ("time.add_ms", [t, ms]) => {
+                let t = self.time_u32(name, t)?;
+                let ms = self.time_u32(name, ms)?;
+                t.checked_add(ms)
+                    .map(|sum| AvmValue::Int(i64::from(sum)))
+                    .ok_or_else(|| miette::miette!("AVM: time.add_ms: {t} + {ms} ms does not fit a u32"))
+            }
+            ("time.elapsed_ms", [start, end]) => {
+                let start = self.time_u32(name, start)?;
+                let end = self.time_u32(name, end)?;
+                Ok(AvmValue::Int(i64::from(end.wrapping_sub(start))))
+            }
+            ("time.format_utc", [s]) => Ok(AvmValue::Str(format_utc(self.time_u32(name, s)?))),
+            ("time.format_ms", [ms]) => Ok(AvmValue::Str(format_ms(self.time_u32(name, ms)?))),
+            _ => unreachable!("arity checked above"),
+        }
+    }
+
+    fn time_u32(&mut self, name: &str, arg: &CallArg) -> miette::Result<u32> {
+        match self.eval_expr(call_arg_value(arg))? {
+            AvmValue::Int(i) => u32::try_from(i).map_err(|_| miette::miette!("AVM: {name}: {i} does not fit 32 bits")),
+            v => Err(miette::miette!("AVM: {name} expects an int, got {v:?}")),
+        }
+    }
+}
+
+/// ISO 8601 UTC, like `aura_time_format_utc`: "2024-05-01T12:00:00Z".
+fn format_utc(unix_s: u32) -> String {
+    // Civil date of a day count, in 400-year eras of 146097 days starting on 0000-03-01.
+    let (days, secs) = (unix_s / 86_400, unix_s % 86_400);
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u32::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+/// A duration as "HH:MM:SS.mmm", like `aura_time_format_ms`.
+fn format_ms(ms: u32) -> String {
+    format!("{:02}:{:02}:{:02}.{:03}", ms / 3_600_000, ms / 60_000 % 60, ms / 1000 % 60, ms % 1000)
+}
@@ -0,0 +1,15 @@
+use aura_interpret::{Avm, AvmConfig};
+
+fn avm() -> Avm {
+    Avm::new(AvmConfig {
+        enable_z3_gate: false,
+        read_stdin: false,
+        ..AvmConfig::default()
+    })
+}
+
+#[test]
+fn borrowed_arguments_stay_with_the_caller_and_see_in_place_updates() {
+    let src = "cell fill(t: &mut Tensor, v: u32):\n    tensor.set(t, 0, v)\n    tensor.set(t, 1, v + 1)\n\ncell sum(t: &Tensor) -> u32:\n    return tensor.get(t, 0) + tensor.get(t, 1)\n\nval mut t: Tensor = tensor.new(2)\nfill(&mut t, 20)\nval a = sum(&t)\nfill(&mut t, 1)\nval out = \"{a} {sum(&t)} {tensor.len(t)}\"\nout\n";
+    assert_eq!(avm().exec_source(src).expect("run").value.display(), "41 3 2");
+}
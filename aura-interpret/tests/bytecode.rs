@@ -0,0 +1,154 @@
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use aura_interpret::{Avm, AvmConfig, DebugBreakpoint, DebugCommand, DebugEvent, DebugSession};
+
+/// What a run left behind: its value and output, or its error and the source it points at.
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Done { value: String, stdout: String },
+    Failed { message: String, at: Vec<String> },
+}
+
+fn run(src: &str, bytecode: bool) -> Outcome {
+    let mut avm = Avm::new(AvmConfig {
+        enable_z3_gate: false,
+        read_stdin: false,
+        bytecode,
+        ..AvmConfig::default()
+    });
+    match avm.exec_source(src) {
+        Ok(out) => Outcome::Done {
+            value: out.value.display(),
+            stdout: out.stdout,
+        },
+        Err(e) => Outcome::Failed {
+            message: e.to_string(),
+            at: e
+                .labels()
+                .into_iter()
+                .flatten()
+                .map(|l| src[l.offset()..l.offset() + l.len()].to_string())
+                .collect(),
+        },
+    }
+}
+
+/// Runs `src` from bytecode and on the tree-walker alone; both have to agree.
+fn run_both(src: &str) -> Outcome {
+    let compiled = run(src, true);
+    assert_eq!(compiled, run(src, false), "bytecode and tree-walker disagree on:\n{src}");
+    compiled
+}
+
+fn done(value: &str) -> Outcome {
+    Outcome::Done {
+        value: value.to_string(),
+        stdout: String::new(),
+    }
+}
+
+#[test]
+fn break_and_continue_leave_while_loops_the_same_way() {
+    let src = "cell sum(n: u32) -> u32:\n    val mut i: u32 = 0\n    val mut s: u32 = 0\n    while i < n:\n        i = i + 1\n        if i == 3:\n            continue\n        if s > 20:\n            break\n        s = s + i\n    yield s * 100 + i\n\nsum(10)\n";
+    assert_eq!(run_both(src), done("2508"));
+
+    // A `break` in an inner loop only leaves that loop.
+    let src = "cell grid() -> u32:\n    val mut i: u32 = 0\n    val mut hits: u32 = 0\n    while i < 4:\n        i = i + 1\n        val mut j: u32 = 0\n        while j < 10:\n            j = j + 1\n            if j > i:\n                break\n            hits = hits + 1\n    yield hits\n\ngrid()\n";
+    assert_eq!(run_both(src), done("10"));
+}
+
+#[test]
+fn statements_handed_to_the_tree_walker_resume_the_enclosing_loop() {
+    // `match` has no bytecode form; its `break` and `continue` are left pending for the
+    // compiled `while` around it.
+    let src = "cell walk() -> u32:\n    val mut i: u32 = 0\n    val mut s: u32 = 0\n    while i < 10:\n        i = i + 1\n        match i:\n            2:\n                continue\n            6:\n                break\n            _:\n                s = s + i\n    yield s * 100 + i\n\nwalk()\n";
+    assert_eq!(run_both(src), done("1306"));
+
+    let src = "cell walk() -> u32:\n    val mut s: u32 = 0\n    for i in 0..10:\n        match i:\n            2:\n                continue\n            6:\n                break\n            _:\n                s = s + i\n    yield s\n\nwalk()\n";
+    assert_eq!(run_both(src), done("13"));
+}
+
+#[test]
+fn a_failing_assert_points_at_the_assert() {
+    let src = "cell check(n: u32) -> u32:\n    val mut i: u32 = 0\n    while i < n:\n        i = i + 1\n        assert i < 3\n    yield i\n\ncheck(5)\n";
+    assert_eq!(
+        run_both(src),
+        Outcome::Failed {
+            message: "assertion failed".to_string(),
+            at: vec!["assert i < 3".to_string()],
+        }
+    );
+}
+
+#[test]
+fn blocks_with_defer_fall_back_to_the_tree_walker() {
+    // The cell body defers, so none of it compiles.
+    let src = "cell f() -> u32:\n    val mut log: u32 = 0\n    defer {\n        io.println(\"done\")\n    }\n    log = log * 10 + 1\n    yield log\n\nf()\n";
+    assert_eq!(
+        run_both(src),
+        Outcome::Done {
+            value: "1".to_string(),
+            stdout: "done\n".to_string(),
+        }
+    );
+
+    // Only the loop whose body defers is handed over; its `break` and `continue` still run
+    // the deferred bodies.
+    let src = "cell g() -> u32:\n    val mut log: u32 = 0\n    val mut i: u32 = 0\n    while i < 5:\n        i = i + 1\n        defer {\n            log = log * 10 + i\n        }\n        if i == 2:\n            continue\n        if i == 4:\n            break\n        log = log * 10 + 9\n    yield log\n\ng()\n";
+    assert_eq!(run_both(src), done("912934"));
+}
+
+/// Runs `source` under the debugger, stopping at `line`, then sends each command after the next
+/// stop and returns the line of every stop.
+fn stops(source: &'static str, line: u32, bytecode: bool, commands: &[DebugCommand]) -> Vec<u32> {
+    let (tx, rx) = mpsc::channel();
+    let (sess, handle) = DebugSession::new(Arc::new(move |ev| {
+        let _ = tx.send(ev);
+    }));
+    sess.apply_command(DebugCommand::SetBreakpoints {
+        breakpoints: vec![DebugBreakpoint {
+            line,
+            condition: None,
+            hit_condition: None,
+            one_shot: true,
+        }],
+    });
+    let run = std::thread::spawn(move || {
+        let mut avm = Avm::new(AvmConfig {
+            enable_z3_gate: false,
+            read_stdin: false,
+            debug: Some(sess),
+            bytecode,
+            ..AvmConfig::default()
+        });
+        avm.exec_source(source).map(|_| ())
+    });
+
+    let mut lines = Vec::new();
+    for cmd in commands {
+        loop {
+            let ev = rx.recv_timeout(Duration::from_secs(10)).expect("debug event");
+            if let DebugEvent::Stopped { line, .. } = ev {
+                lines.push(line);
+                break;
+            }
+        }
+        handle.send(cmd.clone());
+    }
+    run.join().expect("run thread").expect("run");
+    lines
+}
+
+const LOOPS: &str = "cell count(n: u32) -> u32:\n    val mut i: u32 = 0\n    while i < n:\n        i = i + 1\n        if i == 2:\n            continue\n        match i:\n            3:\n                break\n            _:\n                i = i + 0\n    return i\n\nval a = count(5)\nval b = a + 1\n";
+
+#[test]
+fn stepping_stops_at_the_same_statements_with_and_without_bytecode() {
+    let mut commands = vec![DebugCommand::StepIn; 14];
+    commands.push(DebugCommand::Continue);
+    let compiled = stops(LOOPS, 14, true, &commands);
+    assert_eq!(compiled, stops(LOOPS, 14, false, &commands));
+    // Each iteration stops at the body's statements; the loop header only on entry.
+    assert_eq!(compiled, [14, 2, 3, 4, 5, 7, 11, 4, 5, 6, 4, 5, 7, 9, 12]);
+}
@@ -0,0 +1,63 @@
+use aura_interpret::{Avm, AvmConfig, Ratio};
+
+const PROGRAM: &str = "cell clamp(x: u32) -> u32:\n    requires x < 100\n    if x > 10:\n        return 10\n    return x\n\ncell unused() -> u32:\n    return 0\n\nval mut i: u32 = 0\nwhile i < 3:\n    i = i + 1\nval a = clamp(4)\n";
+
+fn covering() -> Avm {
+    Avm::new(AvmConfig {
+        enable_z3_gate: false,
+        read_stdin: false,
+        coverage: true,
+        ..AvmConfig::default()
+    })
+}
+
+#[test]
+fn statements_branches_contracts_and_cells_are_counted() {
+    let mut avm = covering();
+    avm.exec_source(PROGRAM).expect("run");
+    let report = avm.coverage().expect("coverage").report();
+
+    let hits: Vec<(u32, u64)> = report.statements.iter().map(|s| (s.line, s.hits)).collect();
+    assert_eq!(hits, [(2, 1), (3, 1), (4, 0), (5, 1), (8, 0), (10, 1), (11, 1), (12, 3), (13, 1)]);
+
+    let arms: Vec<(u32, Vec<u64>)> = report.branches.iter().map(|b| (b.line, b.arms.clone())).collect();
+    assert_eq!(arms, [(3, vec![0, 1]), (11, vec![3, 1])]);
+
+    let calls: Vec<(&str, u64)> = report.cells.iter().map(|c| (c.name.as_str(), c.calls)).collect();
+    assert_eq!(calls, [("clamp", 1), ("unused", 0)]);
+    assert_eq!(report.summary.contracts, Ratio { covered: 1, total: 1 });
+    assert_eq!(report.summary.branches, Ratio { covered: 3, total: 4 });
+}
+
+#[test]
+fn runs_over_the_same_source_accumulate() {
+    let mut avm = covering();
+    avm.exec_source(PROGRAM).expect("run");
+    avm.exec_source(PROGRAM).expect("run");
+    let report = avm.coverage().expect("coverage").report();
+    assert_eq!(report.cells[0].calls, 2);
+}
+
+#[test]
+fn lcov_export_lists_lines_functions_and_branches() {
+    let mut avm = covering();
+    avm.exec_source(PROGRAM).expect("run");
+    let lcov = avm.coverage().expect("coverage").to_lcov("main.aura");
+
+    assert!(lcov.starts_with("TN:\nSF:main.aura\n"), "{lcov}");
+    for line in ["FN:1,clamp", "FNDA:0,unused", "FNH:1", "BRDA:3,0,0,0", "BRDA:11,1,0,3", "DA:4,0", "DA:12,3", "LH:7"] {
+        assert!(lcov.lines().any(|l| l == line), "missing {line} in\n{lcov}");
+    }
+    assert!(lcov.ends_with("end_of_record\n"));
+}
+
+#[test]
+fn coverage_is_off_by_default() {
+    let mut avm = Avm::new(AvmConfig {
+        enable_z3_gate: false,
+        read_stdin: false,
+        ..AvmConfig::default()
+    });
+    avm.exec_source(PROGRAM).expect("run");
+    assert!(avm.coverage().is_none());
+}
@@ -0,0 +1,37 @@
+use aura_interpret::{Avm, AvmConfig};
+
+fn run(src: &str) -> miette::Result<aura_interpret::ExecOutcome> {
+    let mut avm = Avm::new(AvmConfig {
+        enable_z3_gate: false,
+        read_stdin: false,
+        ..AvmConfig::default()
+    });
+    avm.exec_source(src)
+}
+
+#[test]
+fn untrusted_externs_need_an_unsafe_block() {
+    let err = run("extern cell add(a: u32, b: u32): u32\nval x: u32 = add(1, 2)\n").expect_err("outside unsafe");
+    assert!(err.to_string().contains("requires an unsafe block"), "{err}");
+}
+
+#[test]
+fn unsafe_does_not_cover_cells_called_from_the_block() {
+    let src = "extern cell add(a: u32, b: u32): u32\ncell f() -> u32:\n    add(1, 2)\nunsafe:\n    f()\n";
+    let err = run(src).expect_err("cell body is outside unsafe");
+    assert!(err.to_string().contains("requires an unsafe block"), "{err}");
+}
+
+#[cfg(not(feature = "ffi"))]
+#[test]
+fn extern_calls_need_the_ffi_feature() {
+    let err = run("trusted extern cell add(a: u32, b: u32): u32\nval x: u32 = add(1, 2)\n").expect_err("no ffi");
+    assert!(err.to_string().contains("`ffi` feature"), "{err}");
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn extern_calls_without_native_libraries_fail_cleanly() {
+    let err = run("trusted extern cell add(a: u32, b: u32): u32\nval x: u32 = add(1, 2)\n").expect_err("no libs");
+    assert!(err.to_string().contains("no native libraries"), "{err}");
+}
@@ -0,0 +1,61 @@
+use std::path::PathBuf;
+
+use aura_interpret::{Avm, AvmConfig};
+use aura_sdk::fs_policy::{load_fs_policy, FsPolicy};
+
+fn project(name: &str, manifest: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("aura-files-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("data")).expect("create dir");
+    std::fs::write(dir.join("aura.toml"), manifest).expect("write manifest");
+    dir
+}
+
+fn avm(fs_policy: Option<FsPolicy>) -> Avm {
+    Avm::new(AvmConfig {
+        enable_z3_gate: false,
+        read_stdin: false,
+        fs_policy,
+        ..AvmConfig::default()
+    })
+}
+
+// Aura string literals take `/` on every platform.
+fn slashed(dir: &std::path::Path) -> String {
+    dir.display().to_string().replace('\\', "/")
+}
+
+#[test]
+fn files_are_created_appended_to_and_read_back() {
+    let dir = project("round-trip", "[package]\nname = \"files\"\n");
+    let d = slashed(&dir);
+    let src = format!(
+        "val mut out = fs.create(\"{d}/notes.txt\")\nfs.write(out, \"hello \")\nfs.close(out)\nval mut log = fs.append(\"{d}/notes.txt\")\nfs.write(log, \"world\")\nfs.close(log)\nval f = fs.open(\"{d}/notes.txt\")\nval text = fs.read(f)\nfs.close(f)\ntext\n"
+    );
+    let out = avm(None).exec_source(&src).expect("run");
+    assert_eq!(out.value.display(), "hello world");
+    assert_eq!(std::fs::read_to_string(dir.join("notes.txt")).expect("written"), "hello world");
+}
+
+#[test]
+fn the_fs_policy_of_aura_toml_limits_what_can_be_opened() {
+    let dir = project("policy", "[fs]\nread = [\"data\"]\nwrite = [\"out\"]\n");
+    std::fs::write(dir.join("data").join("in.txt"), "42").expect("input");
+    std::fs::write(dir.join("secret.txt"), "no").expect("secret");
+    let policy = load_fs_policy(&dir).expect("load").expect("[fs] table");
+    let d = slashed(&dir);
+
+    let src = format!("val f = fs.open(\"{d}/data/in.txt\")\nval text = fs.read(f)\nfs.close(f)\ntext\n");
+    let out = avm(Some(policy.clone())).exec_source(&src).expect("allowed read");
+    assert_eq!(out.value.display(), "42");
+
+    for (src, err) in [
+        (format!("val f = fs.open(\"{d}/secret.txt\")\n"), "does not allow reading"),
+        (format!("val f = fs.open(\"{d}/data/../secret.txt\")\n"), "does not allow reading"),
+        (format!("val f = fs.create(\"{d}/data/in.txt\")\n"), "does not allow writing"),
+    ] {
+        let e = avm(Some(policy.clone())).exec_source(&src).expect_err(&src);
+        assert!(format!("{e:?}").contains(err), "{src}: {e:?}");
+    }
+    assert_eq!(std::fs::read_to_string(dir.join("data").join("in.txt")).expect("input"), "42");
+}
@@ -0,0 +1,145 @@
+use std::sync::{Arc, Mutex};
+
+use aura_interpret::{Avm, AvmConfig, AvmValue, DebugEvent, DebugSession, DebugTaskState, FlowScheduling};
+
+const PROGRAM: &str = "cell work(x: u32, tag: String) -> u32:\n    io.println(\"work {tag}\")\n    return x * 2\n\nval a = 1 ~> work(\"a\")\nval b = 2 ~> work(\"b\")\nio.println(\"spawned\")\nval three = 3\nthree ~> work(\"bg\")\nval c = a + b\nio.println(\"sum {c}\")\n";
+
+fn avm(flows: FlowScheduling) -> Avm {
+    Avm::new(AvmConfig {
+        enable_z3_gate: false,
+        read_stdin: false,
+        flows,
+        ..AvmConfig::default()
+    })
+}
+
+#[test]
+fn cooperative_tasks_run_in_spawn_order_when_awaited() {
+    let mut avm = avm(FlowScheduling::Cooperative);
+    avm.exec_source(PROGRAM).expect("run");
+    // Reading `a` runs the queue up to `a`; `b` then runs, and the bare `bg` task is joined at the end.
+    assert_eq!(avm.stdout(), "spawned\nwork a\nwork b\nsum 6\nwork bg\n");
+}
+
+#[test]
+fn awaiting_an_unbound_flow_returns_its_value() {
+    let mut avm = avm(FlowScheduling::Cooperative);
+    let out = avm
+        .exec_source("cell double(x: u32) -> u32:\n    return x * 2\n\nval r = (4 ~> double()) + 1\nr\n")
+        .expect("run");
+    assert_eq!(out.value, AvmValue::Int(9));
+}
+
+#[test]
+fn threaded_tasks_merge_output_in_spawn_order_when_deterministic() {
+    let mut avm = avm(FlowScheduling::Threads { deterministic: true });
+    avm.exec_source(PROGRAM).expect("run");
+    let out = avm.stdout();
+    // Workers start at once, so only the awaited order of their output is fixed.
+    assert!(out.starts_with("spawned\n"), "{out}");
+    assert!(out.ends_with("work a\nwork b\nsum 6\nwork bg\n"), "{out}");
+}
+
+#[test]
+fn a_failing_task_fails_the_run() {
+    let mut avm = avm(FlowScheduling::Cooperative);
+    let err = avm
+        .exec_source("cell bad(x: u32) -> u32:\n    return missing(x)\n\nval one = 1\none ~> bad()\nio.println(\"after\")\n")
+        .expect_err("task fails");
+    assert!(format!("{err:?}").contains("missing"), "{err:?}");
+    assert_eq!(avm.stdout(), "after\n");
+}
+
+#[test]
+fn the_debugger_sees_task_lifecycle_events() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&events);
+    let (sess, _handle) = DebugSession::new(Arc::new(move |ev| {
+        if let DebugEvent::Task { task, cell, state } = ev {
+            sink.lock().unwrap().push((task, cell, state));
+        }
+    }));
+    let mut avm = Avm::new(AvmConfig {
+        enable_z3_gate: false,
+        read_stdin: false,
+        debug: Some(sess),
+        ..AvmConfig::default()
+    });
+    avm.exec_source("cell work(x: u32) -> u32:\n    return x\n\nval a = 1 ~> work()\na\n")
+        .expect("run");
+
+    let work = || "work".to_string();
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            (1, work(), DebugTaskState::Spawned),
+            (1, work(), DebugTaskState::Started),
+            (1, work(), DebugTaskState::Finished),
+        ]
+    );
+}
+
+fn channels_program(capacity: u32) -> String {
+    format!(
+        "cell produce(ch: Channel<u32>, from: u32) -> u32:\n    chan.send(ch, from)\n    chan.send(ch, from + 1)\n    chan.send(ch, from + 2)\n    return 0\n\nval ch: Channel<u32> = chan.bounded({capacity})\nch ~> produce(10)\nval a = chan.recv(ch)\nval b = chan.recv(ch)\nval c = chan.recv(ch)\nval total = a + b + c\ntotal\n"
+    )
+}
+
+#[test]
+fn flows_communicate_over_bounded_channels() {
+    // A cooperative task runs to completion once started, so its sends must fit the channel;
+    // a worker thread waits for room instead.
+    for (flows, capacity) in [(FlowScheduling::Cooperative, 3), (FlowScheduling::Threads { deterministic: false }, 1)] {
+        let mut avm = avm(flows);
+        let out = avm.exec_source(&channels_program(capacity)).expect("run");
+        assert_eq!(out.value, AvmValue::Int(33), "{flows:?}");
+    }
+}
+
+#[test]
+fn cooperative_channel_waits_fail_when_no_task_can_finish_them() {
+    let mut full = avm(FlowScheduling::Cooperative);
+    let err = full
+        .exec_source("val ch: Channel<u32> = chan.bounded(1)\nchan.send(ch, 1)\nchan.send(ch, 2)\n")
+        .expect_err("full");
+    assert!(format!("{err:?}").contains("is full and no task is left"), "{err:?}");
+
+    let mut avm = avm(FlowScheduling::Cooperative);
+    let out = avm
+        .exec_source("val ch: Channel<u32> = chan.unbounded()\nchan.send(ch, 7)\nchan.close(ch)\nval n = chan.len(ch)\nval x = chan.recv(ch)\nn + x\n")
+        .expect("drain a closed channel");
+    assert_eq!(out.value, AvmValue::Int(8));
+    let err = avm.exec_source("val y = chan.recv(ch)\n").expect_err("closed");
+    assert!(format!("{err:?}").contains("channel is closed"), "{err:?}");
+}
+
+const CANCELLABLE: &str = "cell work(tok: CancelToken, x: u32) -> u32:\n    if scope.cancelled(tok):\n        io.println(\"cancelled {x}\")\n        return 0\n    io.println(\"ran {x}\")\n    return x\n\ncell bad(x: u32) -> u32:\n    return missing(x)\n\n";
+
+#[test]
+fn scopes_join_every_task_started_in_them() {
+    for flows in [FlowScheduling::Cooperative, FlowScheduling::Threads { deterministic: true }] {
+        let mut avm = avm(flows);
+        avm.exec_source(&format!("{CANCELLABLE}scope tok:\n    tok ~> work(1)\n    tok ~> work(2)\nio.println(\"after\")\n"))
+            .expect("run");
+        assert_eq!(avm.stdout(), "ran 1\nran 2\nafter\n", "{flows:?}");
+    }
+}
+
+#[test]
+fn scope_tokens_cancel_cooperatively() {
+    let mut cancelled = avm(FlowScheduling::Cooperative);
+    cancelled
+        .exec_source(&format!("{CANCELLABLE}scope tok:\n    tok ~> work(1)\n    scope.cancel(tok)\nio.println(\"after\")\n"))
+        .expect("run");
+    assert_eq!(cancelled.stdout(), "cancelled 1\nafter\n");
+
+    // The first failing task cancels the rest, which still run, and then fails the scope.
+    let mut avm = avm(FlowScheduling::Cooperative);
+    let err = avm
+        .exec_source(&format!(
+            "{CANCELLABLE}scope tok:\n    val one = 1\n    one ~> bad()\n    tok ~> work(2)\nio.println(\"after\")\n"
+        ))
+        .expect_err("task fails");
+    assert!(format!("{err:?}").contains("missing"), "{err:?}");
+    assert_eq!(avm.stdout(), "cancelled 2\n");
+}
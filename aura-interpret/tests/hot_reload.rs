@@ -0,0 +1,81 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use aura_interpret::{Avm, AvmConfig, DebugEvent, DebugSession, HotReload};
+use aura_nexus::{UiNode, UiRuntimeFeedback};
+
+/// A counter whose button also copies `edit{clicks}.aura` over the running program, standing
+/// in for someone saving an edit between frames.
+fn program(dir: &Path, label_body: &str, text: &str) -> String {
+    let main = dir.join("main.aura");
+    let edit = dir.join("edit");
+    format!(
+        "val mut clicks: u32 = 0\ncell label(n: u32) -> String:\n    return {label_body}\n\nlayout:\n    VStack(spacing: 4) {{\n        render: Text(text: {text})\n        render: Button(label: \"inc\") {{\n            on_click: ~> {{\n                clicks = clicks + 1\n                io.write_text(\"{}\", io.read_text(\"{}{{clicks}}.aura\"))\n            }}\n        }}\n    }}\n",
+        main.display(),
+        edit.display()
+    )
+}
+
+fn workdir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("aura-hot-reload-{name}-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("create dir");
+    dir
+}
+
+fn run(dir: &Path, edits: &[String], clicks: usize, debug: Option<DebugSession>) -> Vec<String> {
+    for (i, edit) in edits.iter().enumerate() {
+        std::fs::write(dir.join(format!("edit{}.aura", i + 1)), edit).expect("write edit");
+    }
+    let main = dir.join("main.aura");
+    let src = program(dir, "\"v1 {n}\"", "label(clicks)");
+    std::fs::write(&main, &src).expect("write program");
+
+    let mut avm = Avm::new(AvmConfig {
+        enable_z3_gate: false,
+        read_stdin: false,
+        debug,
+        hot_reload: Some(HotReload::new(&main)),
+        ..AvmConfig::default()
+    });
+    avm.script_ui_frames((0..clicks).map(|_| UiRuntimeFeedback {
+        clicked_callback_id: Some(1),
+        ..UiRuntimeFeedback::default()
+    }));
+    avm.exec_source(&src).expect("run");
+    avm.scripted_frames().iter().map(label).collect()
+}
+
+fn label(frame: &UiNode) -> String {
+    let text = &frame.children[0];
+    text.props.iter().find(|(k, _)| k == "text").map(|(_, v)| v.clone()).expect("text prop")
+}
+
+#[test]
+fn edited_cells_are_swapped_in_and_state_is_kept() {
+    let dir = workdir("cells");
+    let edit = program(&dir, "\"version 2: {n}\"", "label(clicks)");
+    assert_eq!(run(&dir, &[edit], 1, None), ["v1 0", "version 2: 1"]);
+}
+
+#[test]
+fn the_running_layout_continues_with_its_edited_body() {
+    let dir = workdir("layout");
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&events);
+    let (sess, _handle) = DebugSession::new(Arc::new(move |ev| {
+        if let DebugEvent::Reloaded { units, error } = ev {
+            sink.lock().unwrap().push((units, error.is_some()));
+        }
+    }));
+
+    let broken = "layout:\n    VStack(\n".to_string();
+    let edit = program(&dir, "\"v1 {n}\"", "\"count {clicks}\"");
+    let frames = run(&dir, &[broken, edit], 2, Some(sess));
+
+    // The file that doesn't parse leaves the running version in place.
+    assert_eq!(frames, ["v1 0", "v1 1", "count 2"]);
+    assert_eq!(
+        *events.lock().unwrap(),
+        [(vec![], true), (vec!["layout #0".to_string()], false)]
+    );
+}
@@ -0,0 +1,115 @@
+use std::path::{Path, PathBuf};
+
+use aura_interpret::{Avm, AvmConfig, HwEmulation, HwScript};
+
+const MANIFEST: &str = r#"[hardware]
+registers = [
+  { name = "CTRL", base = "0x40000000", size = 4, mask = "0x3FF", fields = [
+    { name = "EN", offset = 0, width = 1 },
+    { name = "MODE", offset = 1, width = 2, values = { IDLE = 0, TX = 1, RX = 2 } },
+  ] },
+  { name = "STATUS", base = "0x40000004", size = 4, access = "ro" },
+  { name = "TX", base = "0x40000008", size = 4, mask = "0xFF", access = "wo" },
+  { name = "UART", base = "0x40010000", size = 8, instances = 3, stride = "0x100" },
+  { name = "RADIO", base = "0x40020000", size = 4, states = ["RUN"] },
+]
+interrupts = [
+  { name = "UART_RX", line = 5, max_priority = 7 },
+]
+
+[hardware.power]
+states = ["OFF", "RUN", "SLEEP"]
+transitions = [
+  { from = "OFF", to = "RUN" },
+  { from = "RUN", to = "SLEEP" },
+  { from = "SLEEP", to = "RUN" },
+  { from = ["RUN", "SLEEP"], to = "OFF" },
+]
+"#;
+
+fn device(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("aura-hw-{name}-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("create dir");
+    std::fs::write(dir.join("aura.toml"), MANIFEST).expect("write manifest");
+    dir
+}
+
+fn avm(dir: &Path, script: &str) -> Avm {
+    let script: HwScript = serde_json::from_str(script).expect("script");
+    Avm::new(AvmConfig {
+        enable_z3_gate: false,
+        read_stdin: false,
+        hw: Some(HwEmulation::load(dir).expect("manifest").with_script(script)),
+        ..AvmConfig::default()
+    })
+}
+
+#[test]
+fn registers_keep_masked_bits_and_fields() {
+    let dir = device("fields");
+    let mut avm = avm(&dir, "{}");
+    let out = avm
+        .exec_source(
+            "val ctrl = hw.open(\"CTRL\")\nval _a = hw.write_u32(ctrl, 0, 65535)\nval _b = hw.write_field(ctrl, 0, \"MODE\", \"RX\")\nval mode = hw.read_field(ctrl, 0, \"MODE\")\nval uart = hw.open(\"UART\", 2)\nval _c = hw.write_u32(uart, 4, 7)\nval _d = hw.enable_irq(\"UART_RX\", 3)\nmode\n",
+        )
+        .expect("run");
+    assert_eq!(out.value.display(), "2");
+    assert_eq!(avm.hw_word("CTRL", 0), Some(0x3FD));
+    assert_eq!(avm.hw_word("UART[2]", 4), Some(7));
+    assert_eq!(avm.hw_word("UART[1]", 4), Some(0));
+    assert_eq!(avm.hw_irq_priority("UART_RX"), Some(3));
+}
+
+#[test]
+fn access_modes_and_bounds_are_enforced() {
+    let dir = device("access");
+    for (src, err) in [
+        ("val s = hw.open(\"STATUS\")\nval _ = hw.write_u32(s, 0, 1)\n", "read-only"),
+        ("val t = hw.open(\"TX\")\nval _ = hw.read_u32(t, 0)\n", "write-only"),
+        ("val c = hw.open(\"CTRL\")\nval _ = hw.read_u32(c, 4)\n", "out of bounds"),
+        ("val u = hw.open(\"UART\", 3)\n", "out of range"),
+        ("val c = hw.open(\"CTRL\")\nval _ = hw.write_field(c, 0, \"MODE\", 3)\n", "named values"),
+    ] {
+        let e = avm(&dir, "{}").exec_source(src).expect_err(src);
+        assert!(format!("{e}").contains(err), "{src}: {e}");
+    }
+}
+
+#[test]
+fn scripted_peripherals_answer_reads_and_writes() {
+    let dir = device("script");
+    let script = r#"{
+        "reads": [{ "register": "STATUS", "values": [0, 0, 4] }],
+        "on_write": [{ "register": "TX", "set": { "register": "STATUS", "value": 1 } }]
+    }"#;
+    let mut avm = avm(&dir, script);
+    let out = avm
+        .exec_source(
+            "val status = hw.open(\"STATUS\")\nval tx = hw.open(\"TX\")\nval mut polls: u32 = 1\nwhile hw.read_u32(status, 0) == 0:\n    polls = polls + 1\nval _ = hw.write_u32(tx, 0, 65)\nval after = hw.read_u32(status, 0)\npolls * 10 + after\n",
+        )
+        .expect("run");
+    // Three scripted reads, then the flag the write to TX raised.
+    assert_eq!(out.value.display(), "31");
+    assert_eq!(avm.hw_word("TX", 0), Some(65));
+}
+
+#[test]
+fn power_states_gate_register_access_and_transitions() {
+    let dir = device("power");
+    let mut vm = avm(&dir, "{}");
+    vm.exec_source(
+        "val _on = hw.set_power(\"RUN\")\nval radio = hw.open(\"RADIO\")\nval _w = hw.write_u32(radio, 0, 1)\nval _s = hw.set_power(\"SLEEP\")\n",
+    )
+    .expect("run");
+    assert_eq!(vm.hw_power_state(), Some("SLEEP"));
+    assert_eq!(vm.hw_word("RADIO", 0), Some(1));
+
+    for (src, err) in [
+        ("val r = hw.open(\"RADIO\")\nval _ = hw.read_u32(r, 0)\n", "power state 'OFF'"),
+        ("val _ = hw.set_power(\"SLEEP\")\n", "OFF -> SLEEP"),
+        ("val _ = hw.set_power(\"HIBERNATE\")\n", "unknown power state"),
+    ] {
+        let e = avm(&dir, "{}").exec_source(src).expect_err(src);
+        assert!(format!("{e}").contains(err), "{src}: {e}");
+    }
+}
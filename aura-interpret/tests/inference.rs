@@ -0,0 +1,217 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use aura_interpret::{Avm, AvmConfig, InferenceRuntime, RuntimeTensor};
+
+/// A length-delimited protobuf field.
+fn len_field(field: u8, body: &[u8]) -> Vec<u8> {
+    let mut out = vec![field << 3 | 2, body.len() as u8];
+    out.extend_from_slice(body);
+    out
+}
+
+/// A tensor ValueInfoProto of u32 elements; a dimension is a size or a `dim_param` name.
+fn value_info(dims: &[&str]) -> Vec<u8> {
+    typed_value_info(12, dims)
+}
+
+fn typed_value_info(elem: u8, dims: &[&str]) -> Vec<u8> {
+    let mut shape = Vec::new();
+    for d in dims {
+        let dim = match d.parse::<u8>() {
+            Ok(v) => vec![1 << 3, v],
+            Err(_) => len_field(2, d.as_bytes()),
+        };
+        shape.extend(len_field(1, &dim));
+    }
+    let mut tensor_type = vec![1 << 3, elem];
+    tensor_type.extend(len_field(2, &shape));
+    len_field(2, &len_field(1, &tensor_type))
+}
+
+/// Writes a model whose graph has one input and one output shaped as given.
+fn model(name: &str, input: &[&str], output: &[&str]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("aura-infer-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("create dir");
+    let mut graph = len_field(11, &value_info(input));
+    graph.extend(len_field(12, &value_info(output)));
+    let path = dir.join(format!("{name}.onnx"));
+    std::fs::write(&path, len_field(7, &graph)).expect("write model");
+    path
+}
+
+/// Writes a model whose u8 input `x` feeds `DequantizeLinear` (scale 0.5, zero point 128).
+fn quantized_model(name: &str, dims: &[&str]) -> PathBuf {
+    let mut node = len_field(1, b"x");
+    node.extend(len_field(1, b"x_scale"));
+    node.extend(len_field(1, b"x_zero_point"));
+    node.extend(len_field(4, b"DequantizeLinear"));
+    let mut scale = vec![2 << 3, 1];
+    scale.extend(len_field(8, b"x_scale"));
+    scale.extend(len_field(9, &0.5f32.to_le_bytes()));
+    let mut zero_point = vec![2 << 3, 2];
+    zero_point.extend(len_field(8, b"x_zero_point"));
+    zero_point.extend(len_field(9, &[128]));
+    let mut input = len_field(1, b"x");
+    input.extend(typed_value_info(2, dims));
+
+    let mut graph = len_field(1, &node);
+    graph.extend(len_field(5, &scale));
+    graph.extend(len_field(5, &zero_point));
+    graph.extend(len_field(11, &input));
+    graph.extend(len_field(12, &typed_value_info(2, dims)));
+    let path = model(name, dims, dims);
+    std::fs::write(&path, len_field(7, &graph)).expect("write model");
+    path
+}
+
+/// Doubles every element; `drop_last` returns one row fewer than it was given.
+#[derive(Debug, Default)]
+struct Doubler {
+    drop_last: bool,
+    seen: Mutex<Vec<Vec<u64>>>,
+}
+
+impl InferenceRuntime for Doubler {
+    fn load(&self, _path: &Path) -> Result<u64, String> {
+        Ok(7)
+    }
+
+    fn run(&self, model: u64, input: &RuntimeTensor) -> Result<RuntimeTensor, String> {
+        assert_eq!(model, 7);
+        self.seen.lock().unwrap().push(input.shape.clone());
+        let mut out = RuntimeTensor {
+            shape: input.shape.clone(),
+            elem: input.elem,
+            data: input.data.iter().map(|v| v * 2).collect(),
+        };
+        if self.drop_last {
+            out.shape[0] -= 1;
+            out.data.truncate(out.data.len() - out.shape[1..].iter().product::<u64>() as usize);
+        }
+        Ok(out)
+    }
+}
+
+fn avm(rt: Arc<Doubler>) -> Avm {
+    Avm::new(AvmConfig {
+        enable_z3_gate: false,
+        read_stdin: false,
+        inference: Some(rt),
+        ..AvmConfig::default()
+    })
+}
+
+fn program(model: &Path, len: usize) -> String {
+    format!(
+        "val model = ai.load_model(\"{}\")\nval input = tensor.new({len})\nval _a = tensor.set(input, 1, 21)\nval out = model.infer(input)\nval _b = tensor.len(out)\ntensor.get(out, 1)\n",
+        model.display().to_string().replace('\\', "/")
+    )
+}
+
+#[test]
+fn infer_runs_the_model_on_the_contract_shape() {
+    let path = model("fixed", &["2", "3"], &["2", "3"]);
+    let rt = Arc::new(Doubler::default());
+    let out = avm(rt.clone()).exec_source(&program(&path, 6)).expect("run");
+    assert_eq!(out.value.display(), "42");
+    assert_eq!(*rt.seen.lock().unwrap(), vec![vec![2, 3]]);
+}
+
+#[test]
+fn open_dimensions_are_sized_from_the_tensor() {
+    let path = model("batch", &["batch", "3"], &["batch", "3"]);
+    let rt = Arc::new(Doubler::default());
+    let out = avm(rt.clone()).exec_source(&program(&path, 12)).expect("run");
+    assert_eq!(out.value.display(), "42");
+    assert_eq!(*rt.seen.lock().unwrap(), vec![vec![4, 3]]);
+}
+
+#[test]
+fn tensors_that_do_not_fit_the_contract_are_rejected_before_running() {
+    let path = model("mismatch", &["batch", "3"], &["batch", "3"]);
+    let rt = Arc::new(Doubler::default());
+    let e = avm(rt.clone()).exec_source(&program(&path, 7)).expect_err("7 elements");
+    assert!(format!("{e}").contains("does not fit input [batch, 3]"), "{e}");
+    assert!(rt.seen.lock().unwrap().is_empty());
+}
+
+#[test]
+fn outputs_that_break_the_contract_fail_the_call() {
+    let path = model("broken", &["batch", "3"], &["batch", "3"]);
+    let rt = Arc::new(Doubler {
+        drop_last: true,
+        ..Doubler::default()
+    });
+    let e = avm(rt).exec_source(&program(&path, 6)).expect_err("broken output");
+    assert!(
+        format!("{e}").contains("returned [1, 3] u32, which breaks its output contract [batch, 3] u32"),
+        "{e}"
+    );
+}
+
+#[test]
+fn models_need_an_inference_runtime() {
+    let path = model("no-runtime", &["3"], &["3"]);
+    let mut avm = Avm::new(AvmConfig {
+        enable_z3_gate: false,
+        read_stdin: false,
+        ..AvmConfig::default()
+    });
+    let e = avm.exec_source(&program(&path, 3)).expect_err("no runtime");
+    assert!(format!("{e}").contains("need an inference runtime"), "{e}");
+}
+
+fn batch_program(model: &Path, len: usize, batch: u32) -> String {
+    format!(
+        "val model = ai.load_model(\"{}\")\nval input = tensor.new({len})\nval _a = tensor.set(input, 1, 21)\nval out = ai.infer_batch(model, input, {batch})\ntensor.get(out, 1)\n",
+        model.display().to_string().replace('\\', "/")
+    )
+}
+
+#[test]
+fn infer_batch_sizes_the_leading_dimension_from_the_batch() {
+    let path = model("batched", &["batch", "3"], &["batch", "2"]);
+    let rt = Arc::new(Doubler::default());
+    let e = avm(rt.clone()).exec_source(&batch_program(&path, 12, 4)).expect_err("output is [4, 3]");
+    assert_eq!(*rt.seen.lock().unwrap(), vec![vec![4, 3]]);
+    assert!(format!("{e}").contains("breaks its output contract [4, 2]"), "{e}");
+
+    let path = model("batched-same", &["batch", "3"], &["batch", "3"]);
+    let out = avm(Arc::new(Doubler::default()))
+        .exec_source(&batch_program(&path, 12, 4))
+        .expect("run");
+    assert_eq!(out.value.display(), "42");
+}
+
+#[test]
+fn batches_that_do_not_fit_the_model_are_rejected_before_running() {
+    let path = model("fixed-batch", &["2", "3"], &["2", "3"]);
+    let rt = Arc::new(Doubler::default());
+    let e = avm(rt.clone()).exec_source(&batch_program(&path, 12, 4)).expect_err("batch of 4");
+    assert!(format!("{e}").contains("a batch of 4 does not fit the batch dimension"), "{e}");
+
+    let path = model("open-batch", &["batch", "3"], &["batch", "3"]);
+    let e = avm(rt.clone()).exec_source(&batch_program(&path, 12, 3)).expect_err("9 elements");
+    assert!(format!("{e}").contains("does not fit input [3, 3]"), "{e}");
+
+    let e = avm(rt.clone()).exec_source(&batch_program(&path, 12, 0)).expect_err("empty batch");
+    assert!(format!("{e}").contains("batch size 0 is outside >= 1"), "{e}");
+    assert!(rt.seen.lock().unwrap().is_empty());
+}
+
+#[test]
+fn quantized_models_reject_values_outside_their_domain() {
+    let path = quantized_model("quantized", &["2", "3"]);
+    let rt = Arc::new(Doubler::default());
+    let out = avm(rt.clone()).exec_source(&program(&path, 6)).expect("run");
+    assert_eq!(out.value.display(), "42");
+
+    let src = program(&path, 6).replace("tensor.set(input, 1, 21)", "tensor.set(input, 1, 300)");
+    let e = avm(rt.clone()).exec_source(&src).expect_err("300 is not a u8");
+    assert!(
+        format!("{e}").contains("input element 1 is 300, outside the quantized u8 domain [0..255]"),
+        "{e}"
+    );
+    assert_eq!(rt.seen.lock().unwrap().len(), 1);
+}
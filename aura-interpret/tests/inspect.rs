@@ -0,0 +1,52 @@
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use aura_interpret::{Avm, AvmConfig, DebugBreakpoint, DebugCommand, DebugEvent, DebugSession};
+
+#[test]
+fn paused_bindings_expand_into_children() {
+    let (tx, rx) = mpsc::channel();
+    let (sess, handle) = DebugSession::new(Arc::new(move |ev| {
+        let _ = tx.send(ev);
+    }));
+    sess.apply_command(DebugCommand::SetBreakpoints {
+        breakpoints: vec![DebugBreakpoint {
+            line: 3,
+            condition: None,
+            hit_condition: None,
+            one_shot: false,
+        }],
+    });
+    let run = std::thread::spawn(move || {
+        let mut avm = Avm::new(AvmConfig {
+            enable_z3_gate: false,
+            read_stdin: false,
+            debug: Some(sess),
+            ..AvmConfig::default()
+        });
+        avm.exec_source("val xs = [10, 20, 30, 40]\nval n = 1\nval m = 2\n").map(|_| ())
+    });
+
+    let next = || rx.recv_timeout(Duration::from_secs(10)).expect("debug event");
+    let variables = loop {
+        if let DebugEvent::Stopped { variables, .. } = next() {
+            break variables;
+        }
+    };
+    let shown: Vec<(&str, &str, u64)> = variables.iter().map(|v| (v.name.as_str(), v.value.as_str(), v.reference)).collect();
+    assert_eq!(shown, [("n", "1", 0), ("xs", "[4 items]", 1)]);
+
+    handle.send(DebugCommand::Expand { reference: 1, start: 1, count: Some(2) });
+    let (total, children, error) = loop {
+        if let DebugEvent::Expanded { total, children, error, .. } = next() {
+            break (total, children, error);
+        }
+    };
+    handle.send(DebugCommand::Continue);
+    run.join().expect("run thread").expect("run");
+
+    assert_eq!((total, error), (4, None));
+    let shown: Vec<(&str, &str)> = children.iter().map(|c| (c.name.as_str(), c.value.as_str())).collect();
+    assert_eq!(shown, [("[1]", "20"), ("[2]", "30")]);
+}
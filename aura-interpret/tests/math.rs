@@ -0,0 +1,21 @@
+use aura_interpret::{Avm, AvmConfig};
+
+fn avm() -> Avm {
+    Avm::new(AvmConfig {
+        enable_z3_gate: false,
+        read_stdin: false,
+        ..AvmConfig::default()
+    })
+}
+
+#[test]
+fn math_calls_compute_like_the_compiled_intrinsics() {
+    let src = "val c = math.min(math.max(12, 1), 10)\nval p = math.pow(2, 31)\nval h = math.sqrt(math.abs(-3.0 * 3.0 - 16.0))\nval s = math.sin(0.0) + math.cos(0.0)\nval out = \"{c} {p} {h} {s} {math.powf(2.0, 10.0)}\"\nout\n";
+    assert_eq!(avm().exec_source(src).expect("run").value.display(), "10 2147483648 5 1 1024");
+}
+
+#[test]
+fn u32_pow_fails_past_u32_max() {
+    let e = avm().exec_source("val p = math.pow(2, 32)\n").expect_err("overflow");
+    assert!(format!("{e:?}").contains("math.pow: 2^32 does not fit a u32"), "{e:?}");
+}
@@ -0,0 +1,49 @@
+use std::io::{Read, Write};
+use std::net::TcpListener;
+
+use aura_interpret::{Avm, AvmConfig};
+
+fn avm() -> Avm {
+    Avm::new(AvmConfig {
+        enable_z3_gate: false,
+        read_stdin: false,
+        ..AvmConfig::default()
+    })
+}
+
+// A one-connection server that answers each request with `reply` and then hangs up.
+fn serve_once(reply: &'static str) -> (u16, std::thread::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+    let port = listener.local_addr().expect("addr").port();
+    let server = std::thread::spawn(move || {
+        let (mut conn, _) = listener.accept().expect("accept");
+        let mut request = [0u8; 1024];
+        let n = conn.read(&mut request).expect("read request");
+        conn.write_all(reply.as_bytes()).expect("reply");
+        String::from_utf8_lossy(&request[..n]).into_owned()
+    });
+    (port, server)
+}
+
+#[test]
+fn sockets_send_requests_and_receive_until_the_peer_hangs_up() {
+    let (port, server) = serve_once("pong");
+    let src = format!(
+        "val mut s = net.connect(\"127.0.0.1\", {port})\nnet.send(s, \"ping\")\nval first = net.recv(s)\nval rest = net.recv(s)\nnet.close(s)\nval both = \"{{first}}|{{rest}}\"\nboth\n"
+    );
+    let out = avm().exec_source(&src).expect("run");
+    assert_eq!(out.value.display(), "pong|");
+    assert_eq!(server.join().expect("server"), "ping");
+}
+
+#[test]
+fn the_network_verifier_rejects_using_a_closed_socket() {
+    let (port, server) = serve_once("");
+    // Unchecked source: the compiler would reject `1` as a socket, the runtime has to catch it.
+    let src = format!(
+        "val mut s = net.connect(\"127.0.0.1\", {port})\nnet.send(s, \"bye\")\nnet.close(s)\nnet.send(1, \"again\")\n"
+    );
+    let e = avm().exec_source(&src).expect_err("closed socket");
+    assert!(format!("{e:?}").contains("Invalid socket transition Closed -> Writing"), "{e:?}");
+    server.join().expect("server");
+}
@@ -0,0 +1,76 @@
+use std::sync::{Arc, Mutex};
+
+use aura_interpret::{Avm, AvmConfig, DebugCommand, DebugEvent, DebugSession, PerfReport};
+use aura_nexus::UiRuntimeFeedback;
+
+const PROGRAM: &str = "cell square(x: u32) -> u32:\n    return x * x\n\ncell sum_squares(n: u32) -> u32:\n    val mut i: u32 = 0\n    val mut acc: u32 = 0\n    while i < n:\n        acc = acc + square(i)\n        i = i + 1\n    return acc\n\nval total = sum_squares(4)\n";
+
+fn profiled(cfg: AvmConfig) -> Avm {
+    Avm::new(AvmConfig {
+        enable_z3_gate: false,
+        read_stdin: false,
+        profile: true,
+        ..cfg
+    })
+}
+
+#[test]
+fn cells_get_call_counts_and_collapsed_stacks() {
+    let mut avm = profiled(AvmConfig::default());
+    avm.exec_source(PROGRAM).expect("run");
+    let profile = avm.profile().expect("profile");
+
+    let cells = profile.cells();
+    let calls: Vec<(&str, u64)> = cells.iter().map(|c| (c.name.as_str(), c.calls)).collect();
+    assert_eq!(calls, [("square", 4), ("sum_squares", 1)]);
+    let outer = &cells[1];
+    assert!(outer.self_ns <= outer.total_ns);
+
+    let collapsed = profile.to_collapsed();
+    let stacks: Vec<&str> = collapsed.lines().map(|l| l.rsplit_once(' ').expect("weight").0).collect();
+    assert_eq!(stacks, ["program", "program;sum_squares", "program;sum_squares;square"]);
+    assert!(collapsed.lines().all(|l| l.rsplit_once(' ').unwrap().1.parse::<u64>().is_ok()));
+}
+
+#[test]
+fn ui_loops_can_be_sampled_every_nth_frame() {
+    let mut avm = profiled(AvmConfig {
+        profile_frame_interval: Some(2),
+        ui_script: Some(vec![UiRuntimeFeedback::default(); 5]),
+        ..AvmConfig::default()
+    });
+    avm.exec_source("cell caption(n: u32) -> String:\n    return \"n = {n}\"\n\nlayout:\n    VStack(spacing: 4) {\n        render: Text(text: caption(1))\n    }\n")
+        .expect("run");
+    let profile = avm.profile().expect("profile");
+    assert_eq!(profile.frames(), 6);
+    // One call builds the tree before the loop starts, then frames 0, 2 and 4 are sampled.
+    assert_eq!(profile.cells()[0].calls, 4);
+}
+
+#[test]
+fn perf_reports_carry_the_cell_profile() {
+    let reports = Arc::new(Mutex::new(Vec::<PerfReport>::new()));
+    let sink = Arc::clone(&reports);
+    let (sess, handle) = DebugSession::new(Arc::new(move |ev| {
+        if let DebugEvent::PerfReport { report } = ev {
+            sink.lock().unwrap().push(report);
+        }
+    }));
+    handle.send(DebugCommand::Enable {
+        start_paused: false,
+        perf: true,
+        perf_frame_interval: None,
+    });
+    let mut avm = Avm::new(AvmConfig {
+        enable_z3_gate: false,
+        read_stdin: false,
+        debug: Some(sess),
+        ..AvmConfig::default()
+    });
+    avm.exec_source(PROGRAM).expect("run");
+
+    let reports = reports.lock().unwrap();
+    let report = reports.first().expect("perf report");
+    assert_eq!(report.cells.iter().map(|c| c.calls).sum::<u64>(), 5);
+    assert!(report.cell_stacks.contains("program;sum_squares;square "), "{}", report.cell_stacks);
+}
@@ -0,0 +1,31 @@
+use aura_interpret::{Avm, AvmConfig, AvmValue};
+
+fn avm() -> Avm {
+    Avm::new(AvmConfig {
+        enable_z3_gate: false,
+        read_stdin: false,
+        ..AvmConfig::default()
+    })
+}
+
+#[test]
+fn region_tensors_are_freed_and_their_handles_reused() {
+    let mut avm = avm();
+    let out = avm
+        .exec_source("val keep = tensor.new(2)\nval mut got = 0\nregion {\n    val mut t = tensor.new(3)\n    tensor.set(t, 0, 7)\n    got = tensor.get(t, 0)\n}\nval after = tensor.new(4)\nafter\n")
+        .expect("run");
+    assert_eq!(avm.exec_source("got\n").expect("got").value, AvmValue::Int(7));
+    // The region's tensor was the last one made, so its handle is the next one handed out.
+    assert_eq!(out.value, AvmValue::Int(2));
+    assert_eq!(avm.tensor_data(1).map(<[u32]>::len), Some(2));
+    assert_eq!(avm.tensor_data(2).map(<[u32]>::len), Some(4));
+}
+
+#[test]
+fn tensors_made_by_tasks_outlive_the_region_they_ran_in() {
+    let mut avm = avm();
+    let out = avm
+        .exec_source("cell make(n: u32) -> Tensor:\n    return tensor.new(n)\n\nval five = 5\nval t = five ~> make()\nregion {\n    val mut scratch = tensor.new(1)\n    time.sleep_ms(0)\n}\ntensor.len(t)\n")
+        .expect("run");
+    assert_eq!(out.value, AvmValue::Int(5));
+}
@@ -0,0 +1,56 @@
+use aura_interpret::{Avm, AvmConfig, AvmResource, AvmTerminated};
+
+fn limited(fuel: Option<u64>, max_memory_bytes: Option<u64>) -> Avm {
+    Avm::new(AvmConfig {
+        enable_z3_gate: false,
+        read_stdin: false,
+        fuel,
+        max_memory_bytes,
+        ..AvmConfig::default()
+    })
+}
+
+fn limit_hit(err: &miette::Report) -> AvmResource {
+    match err.downcast_ref::<AvmTerminated>() {
+        Some(AvmTerminated::ResourceLimit { resource, .. }) => *resource,
+        other => panic!("expected a resource limit, got {other:?}: {err}"),
+    }
+}
+
+const COUNTDOWN: &str = "val mut n: u32 = 0\nwhile 0 == 0:\n    io.println(\"tick {n}\")\n    n = n + 1\n";
+
+#[test]
+fn fuel_stops_a_runaway_loop_and_keeps_partial_state() {
+    let mut avm = limited(Some(40), None);
+    let err = avm.exec_source(COUNTDOWN).expect_err("fuel runs out");
+    assert_eq!(limit_hit(&err), AvmResource::Fuel);
+    assert_eq!(avm.fuel_used(), 41);
+    assert!(avm.stdout().starts_with("tick 0\ntick 1\n"));
+    assert!(matches!(avm.binding("n"), Some(v) if v.display() != "0"));
+}
+
+#[test]
+fn fuel_is_per_run() {
+    let mut avm = limited(Some(10), None);
+    avm.exec_source("val a: u32 = 1\nval b: u32 = a + 1\n").expect("first run");
+    avm.exec_source("val c: u32 = 2\nval d: u32 = c + 1\n").expect("second run");
+    assert_eq!(avm.fuel_used(), 2);
+}
+
+#[test]
+fn memory_cap_stops_a_growing_binding() {
+    let mut avm = limited(None, Some(4096));
+    let err = avm
+        .exec_source("val mut s: String = \"x\"\nwhile 0 == 0:\n    s = \"{s}{s}\"\n")
+        .expect_err("memory runs out");
+    assert_eq!(limit_hit(&err), AvmResource::Memory);
+    assert!(avm.binding("s").is_some());
+}
+
+#[test]
+fn no_limits_by_default() {
+    let mut avm = limited(None, None);
+    let out = avm.exec_source("val mut i: u32 = 0\nwhile i < 1000:\n    i = i + 1\n").expect("run");
+    assert!(out.stdout.is_empty());
+    assert_eq!(avm.fuel_used(), 1002);
+}
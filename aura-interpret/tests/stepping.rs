@@ -0,0 +1,97 @@
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use aura_interpret::{Avm, AvmConfig, DebugBreakpoint, DebugCommand, DebugEvent, DebugSession};
+use aura_nexus::UiRuntimeFeedback;
+
+/// Runs `source` on a thread, stopping at `line`, then sends each command after the next stop
+/// and returns the line of every stop.
+fn stops(source: &'static str, line: u32, script: Option<Vec<UiRuntimeFeedback>>, commands: &[DebugCommand]) -> Vec<u32> {
+    let (tx, rx) = mpsc::channel();
+    let (sess, handle) = DebugSession::new(Arc::new(move |ev| {
+        let _ = tx.send(ev);
+    }));
+    sess.apply_command(DebugCommand::SetBreakpoints {
+        breakpoints: vec![DebugBreakpoint {
+            line,
+            condition: None,
+            hit_condition: None,
+            one_shot: true,
+        }],
+    });
+    let run = std::thread::spawn(move || {
+        let mut avm = Avm::new(AvmConfig {
+            enable_z3_gate: false,
+            read_stdin: false,
+            debug: Some(sess),
+            ui_script: script,
+            ..AvmConfig::default()
+        });
+        avm.exec_source(source).map(|_| ())
+    });
+
+    let mut lines = Vec::new();
+    for cmd in commands {
+        loop {
+            let ev = rx.recv_timeout(Duration::from_secs(10)).expect("debug event");
+            if let DebugEvent::Stopped { line, .. } = ev {
+                lines.push(line);
+                break;
+            }
+        }
+        handle.send(cmd.clone());
+    }
+    run.join().expect("run thread").expect("run");
+    lines
+}
+
+const CELLS: &str = "cell inc(x: u32) -> u32:\n    val y = x + 1\n    return y\n\nval a = inc(1)\nval b = inc(a)\nval c = b + 1\n";
+
+#[test]
+fn step_in_enters_cells_and_step_out_and_over_return_past_them() {
+    let lines = stops(
+        CELLS,
+        5,
+        None,
+        &[DebugCommand::StepIn, DebugCommand::StepOut, DebugCommand::StepOver, DebugCommand::Continue],
+    );
+    assert_eq!(lines, [5, 2, 6, 7]);
+}
+
+#[test]
+fn step_over_stays_in_the_cell_until_it_returns() {
+    let lines = stops(
+        CELLS,
+        2,
+        None,
+        &[DebugCommand::StepOver, DebugCommand::StepOver, DebugCommand::StepOver, DebugCommand::Continue],
+    );
+    // Line 3 returns to the caller, whose next statement is line 6; the second call runs through.
+    assert_eq!(lines, [2, 3, 6, 7]);
+}
+
+const CALLBACK: &str = "val mut clicks: u32 = 0\nlayout:\n    VStack(spacing: 4) {\n        render:\n            val shown = clicks * 2\n            Text(text: \"{shown}\")\n        render: Button(label: \"inc\") {\n            on_click: ~> {\n                clicks = clicks + 1\n                clicks = clicks + 1\n            }\n        }\n    }\n";
+
+#[test]
+fn callbacks_and_trailing_blocks_are_frames_of_their_own() {
+    let click = UiRuntimeFeedback {
+        clicked_callback_id: Some(1),
+        ..UiRuntimeFeedback::default()
+    };
+    let lines = stops(
+        CALLBACK,
+        9,
+        Some(vec![click, UiRuntimeFeedback::default()]),
+        &[
+            DebugCommand::StepOver,
+            DebugCommand::StepOut,
+            DebugCommand::StepOver,
+            DebugCommand::StepIn,
+            DebugCommand::Continue,
+        ],
+    );
+    // Out of the callback into the next frame's `VStack`, over its trailing block to the
+    // frame after, then into the block's first `render`.
+    assert_eq!(lines, [9, 10, 3, 3, 5]);
+}
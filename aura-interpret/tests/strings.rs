@@ -0,0 +1,23 @@
+use aura_interpret::{Avm, AvmConfig};
+
+fn avm() -> Avm {
+    Avm::new(AvmConfig {
+        enable_z3_gate: false,
+        read_stdin: false,
+        ..AvmConfig::default()
+    })
+}
+
+#[test]
+fn str_calls_count_bytes_like_the_compiled_runtime() {
+    let src = "val line = \" name = Ada \"\nval eq = str.find(line, \"=\")\nval key = str.trim(str.slice(line, 0, eq))\nval value = str.trim(str.split_nth(line, \"=\", 1))\nval n = str.split_count(\"a,b,,c\", \",\")\nval missing = str.find(line, \"#\")\nval accent = \"é\"\nval out = \"[{str.pad_right(key, 6)}|{str.pad_left(value, 5)}|{n}|{missing}|{str.len(accent)}]\"\nout\n";
+    assert_eq!(avm().exec_source(src).expect("run").value.display(), "[name  |  Ada|4|12|2]");
+}
+
+#[test]
+fn str_calls_reject_fields_and_offsets_that_do_not_exist() {
+    let e = avm().exec_source("val f = str.split_nth(\"a,b\", \",\", 2)\n").expect_err("no field 2");
+    assert!(format!("{e:?}").contains("there is no field 2"), "{e:?}");
+    let e = avm().exec_source("val s = str.slice(\"é\", 0, 1)\n").expect_err("splits a character");
+    assert!(format!("{e:?}").contains("split a character"), "{e:?}");
+}
@@ -0,0 +1,71 @@
+use aura_interpret::{Avm, AvmConfig, AvmValue, FlowScheduling, TraceMode};
+
+fn avm(flows: FlowScheduling, trace: Option<TraceMode>) -> Avm {
+    Avm::new(AvmConfig {
+        enable_z3_gate: false,
+        read_stdin: false,
+        flows,
+        trace,
+        ..AvmConfig::default()
+    })
+}
+
+#[test]
+fn sleeping_waits_on_the_monotonic_clock_and_runs_queued_tasks() {
+    for flows in [FlowScheduling::Cooperative, FlowScheduling::Threads { deterministic: true }] {
+        let mut avm = avm(flows, None);
+        let out = avm
+            .exec_source("cell work(x: u32) -> u32:\n    io.println(\"work\")\n    return x\n\nval start = time.now_ms()\nval one = 1\none ~> work()\ntime.sleep_ms(20)\nio.println(\"woke\")\nval waited = time.now_ms() - start\nwaited\n")
+            .expect("run");
+        let AvmValue::Int(waited) = out.value else {
+            panic!("expected an int, got {:?}", out.value);
+        };
+        assert!(waited >= 20, "{flows:?}: {waited}");
+        if flows == FlowScheduling::Cooperative {
+            assert_eq!(avm.stdout(), "work\nwoke\n");
+        }
+    }
+}
+
+#[test]
+fn clock_readings_are_recorded_and_replayed() {
+    let dir = std::env::temp_dir().join(format!("aura-timers-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("temp dir");
+    let path = dir.join("clock.jsonl");
+    let src = "time.sleep_ms(5)\nval t = time.now_ms()\nval wall = time.unix_s()\nval both = \"{t} {wall}\"\nboth\n";
+
+    let mut recording = avm(FlowScheduling::Cooperative, Some(TraceMode::Record(path.clone())));
+    let recorded = recording.exec_source(src).expect("record").value;
+    drop(recording);
+
+    let mut replaying = avm(
+        FlowScheduling::Cooperative,
+        Some(TraceMode::Replay {
+            path: path.clone(),
+            pause_at_step: None,
+        }),
+    );
+    assert_eq!(replaying.exec_source(src).expect("replay").value, recorded);
+
+    let err = replaying.exec_source("val u = time.now_ms()\n").expect_err("trace ran out");
+    assert!(format!("{err:?}").contains("trace ended"), "{err:?}");
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn durations_add_without_overflow_and_format_like_the_runtime() {
+    let src = "val d = time.add_ms(3723000, 4)\nval since = time.elapsed_ms(4294967000, 704)\nval at = time.format_utc(1709208000)\nval out = \"{time.format_ms(d)} {since} {at} {time.format_utc(4294967295)}\"\nout\n";
+    let out = avm(FlowScheduling::Cooperative, None).exec_source(src).expect("run");
+    assert_eq!(out.value.display(), "01:02:03.004 1000 2024-02-29T12:00:00Z 2106-02-07T06:28:15Z");
+
+    let now = avm(FlowScheduling::Cooperative, None).exec_source("val s = time.unix_s()\ns\n").expect("run");
+    let AvmValue::Int(s) = now.value else {
+        panic!("expected an int, got {:?}", now.value);
+    };
+    assert!(s > 1_700_000_000, "{s}");
+
+    let err = avm(FlowScheduling::Cooperative, None)
+        .exec_source("val t = time.add_ms(4294967295, 1)\n")
+        .expect_err("overflow");
+    assert!(format!("{err:?}").contains("time.add_ms: 4294967295 + 1 ms does not fit a u32"), "{err:?}");
+}
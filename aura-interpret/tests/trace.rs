@@ -0,0 +1,145 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use aura_interpret::{Avm, AvmConfig, DebugCommand, DebugEvent, DebugSession, TraceMode};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("aura-trace-{name}-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("temp dir");
+    dir
+}
+
+fn replay(path: &Path, pause_at_step: Option<u64>) -> Option<TraceMode> {
+    Some(TraceMode::Replay {
+        path: path.to_path_buf(),
+        pause_at_step,
+    })
+}
+
+/// Runs `file` with the CLI, feeding `stdin` to it, and returns its JSON output.
+fn run_cli(file: &Path, trace_flag: &str, trace: &Path, stdin: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_aura-interpret"))
+        .args(["run", "--file"])
+        .arg(file)
+        .args(["--no-z3", "--json", trace_flag])
+        .arg(trace)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn aura-interpret");
+    // A replay may finish without reading its stdin.
+    let _ = child.stdin.take().unwrap().write_all(stdin.as_bytes());
+    let out = child.wait_with_output().expect("aura-interpret output");
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    String::from_utf8(out.stdout).unwrap()
+}
+
+#[test]
+fn a_replay_ignores_live_input_and_repeats_the_recorded_run() {
+    let dir = temp_dir("cli");
+    let file = dir.join("main.aura");
+    let trace = dir.join("run.jsonl");
+    std::fs::write(
+        &file,
+        "val name = io.read_line(\"name?\")\ntime.sleep_ms(3)\nval t = time.now_ms()\nio.println(\"hi {name} at {t}\")\n",
+    )
+    .unwrap();
+
+    let recorded = run_cli(&file, "--record", &trace, "alice\n");
+    assert!(recorded.contains("hi alice at "), "{recorded}");
+    let events = std::fs::read_to_string(&trace).unwrap();
+    assert_eq!(events.lines().count(), 2, "{events}");
+
+    // Different stdin and a later clock: the replay still reads what the recording did.
+    std::thread::sleep(Duration::from_millis(20));
+    assert_eq!(run_cli(&file, "--replay", &trace, "bob\n"), recorded);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn a_replay_fails_when_the_trace_runs_out_or_diverges() {
+    let dir = temp_dir("short");
+    let path = dir.join("short.jsonl");
+    std::fs::write(&path, "{\"kind\":\"readLine\",\"line\":{\"Ok\":\"alice\"}}\n").unwrap();
+    let config = || AvmConfig {
+        enable_z3_gate: false,
+        read_stdin: false,
+        trace: replay(&path, None),
+        ..AvmConfig::default()
+    };
+
+    let err = Avm::new(config())
+        .exec_source("val a = io.read_line(\"\")\nval b = io.read_line(\"\")\n")
+        .expect_err("trace ran out");
+    assert!(
+        err.to_string().contains("AVM: replay trace ended after 1 event(s) but the program asked for io.read_line"),
+        "{err:?}"
+    );
+
+    let err = Avm::new(config()).exec_source("val t = time.now_ms()\n").expect_err("diverged");
+    assert!(
+        err.to_string().contains("AVM: replay diverged at trace event 1: the program asked for time.now_ms but the trace has io.read_line"),
+        "{err:?}"
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// Replays `source` under the debugger, paused before statement `step`, and returns where it
+/// stopped and the value bound to `name` there.
+fn pause_at(source: &'static str, path: &Path, step: u64, name: &'static str) -> (u32, Option<u64>, String) {
+    let (tx, rx) = mpsc::channel();
+    let (sess, handle) = DebugSession::new(Arc::new(move |ev| {
+        let _ = tx.send(ev);
+    }));
+    let trace = replay(path, Some(step));
+    let run = std::thread::spawn(move || {
+        let mut avm = Avm::new(AvmConfig {
+            enable_z3_gate: false,
+            read_stdin: false,
+            debug: Some(sess),
+            trace,
+            ..AvmConfig::default()
+        });
+        avm.exec_source(source).map(|out| out.value.display())
+    });
+
+    let stop = loop {
+        let ev = rx.recv_timeout(Duration::from_secs(10)).expect("debug event");
+        if let DebugEvent::Stopped { line, step, env, .. } = ev {
+            break (line, step, env.get(name).cloned().unwrap_or_default());
+        }
+    };
+    handle.send(DebugCommand::Continue);
+    run.join().expect("run thread").expect("replay");
+    stop
+}
+
+#[test]
+fn replaying_to_an_earlier_step_steps_back() {
+    const SRC: &str = "time.sleep_ms(2)\nval a = time.now_ms()\nval b = a + 1\nval c = b + 1\nc\n";
+    let dir = temp_dir("back");
+    let path = dir.join("back.jsonl");
+    let recorded = Avm::new(AvmConfig {
+        enable_z3_gate: false,
+        read_stdin: false,
+        trace: Some(TraceMode::Record(path.clone())),
+        ..AvmConfig::default()
+    })
+    .exec_source(SRC)
+    .expect("record")
+    .value
+    .display();
+    let a: u64 = recorded.parse::<u64>().unwrap() - 2;
+
+    let (line, step, b) = pause_at(SRC, &path, 4, "b");
+    assert_eq!((line, step, b), (4, Some(4), format!("Int({})", a + 1)));
+    // One statement back, `a` holds the recorded reading again.
+    let (line, step, a_then) = pause_at(SRC, &path, 3, "a");
+    assert_eq!((line, step, a_then), (3, Some(3), format!("Int({a})")));
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,73 @@
+use aura_interpret::{Avm, AvmConfig};
+use aura_nexus::{UiNode, UiRuntimeFeedback, UiTextInputEvent};
+
+const COUNTER: &str = "val mut clicks: u32 = 0\nval mut name: String = \"\"\nlayout:\n    VStack(spacing: 4) {\n        render: Text(text: \"clicks {clicks} name {name}\")\n        render: Button(label: \"inc\") {\n            on_click: ~> {\n                clicks = clicks + 1\n            }\n        }\n        render: TextInput(value: name) {\n            on_change: ~> {\n                name = ui.event_text()\n            }\n        }\n    }\n";
+
+fn headless() -> Avm {
+    Avm::new(AvmConfig {
+        enable_z3_gate: false,
+        read_stdin: false,
+        ..AvmConfig::default()
+    })
+}
+
+fn click(id: u64) -> UiRuntimeFeedback {
+    UiRuntimeFeedback {
+        clicked_callback_id: Some(id),
+        ..UiRuntimeFeedback::default()
+    }
+}
+
+fn label(frame: &UiNode) -> &str {
+    let text = &frame.children[0];
+    text.props.iter().find(|(k, _)| k == "text").map(|(_, v)| v.as_str()).expect("text prop")
+}
+
+#[test]
+fn scripted_clicks_and_text_input_drive_the_ui_loop() {
+    let mut avm = headless();
+    avm.script_ui_frames([
+        click(1),
+        click(1),
+        UiRuntimeFeedback {
+            text_input_events: vec![UiTextInputEvent {
+                callback_id: 2,
+                text: "ada".to_string(),
+                submitted: false,
+            }],
+            ..UiRuntimeFeedback::default()
+        },
+        UiRuntimeFeedback {
+            close_requested: true,
+            ..UiRuntimeFeedback::default()
+        },
+    ]);
+    avm.exec_source(COUNTER).expect("run");
+
+    let labels: Vec<&str> = avm.scripted_frames().iter().map(label).collect();
+    assert_eq!(
+        labels,
+        ["clicks 0 name ", "clicks 1 name ", "clicks 2 name ", "clicks 2 name ada"]
+    );
+}
+
+#[test]
+fn the_ui_loop_ends_when_the_script_runs_out() {
+    let mut avm = Avm::new(AvmConfig {
+        enable_z3_gate: false,
+        read_stdin: false,
+        ui_script: Some(vec![click(1)]),
+        ..AvmConfig::default()
+    });
+    avm.exec_source(COUNTER).expect("run");
+
+    let labels: Vec<&str> = avm.scripted_frames().iter().map(label).collect();
+    assert_eq!(labels, ["clicks 0 name ", "clicks 1 name "]);
+}
+
+#[test]
+fn without_a_script_or_window_the_layout_runs_once() {
+    let mut avm = headless();
+    avm.exec_source(COUNTER).expect("run");
+    assert!(avm.scripted_frames().is_empty());
+}
@@ -2,6 +2,8 @@
 
 use aura_ast::Span;
 
+use crate::ir::{BlockId, ValueId};
+
 #[derive(Clone, Debug)]
 pub struct DebugSource {
     pub file_name: String,
@@ -47,3 +49,56 @@ impl DebugSource {
         }
     }
 }
+
+/// Source-level variable and scope information recorded while lowering a function, consumed by
+/// backends that emit debug info (e.g. DWARF `DILocalVariable`/`DILexicalBlock`).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FunctionDebug {
+    /// Lexical scopes nested inside the function body; the body itself is the implicit root.
+    pub scopes: Vec<DebugScope>,
+    pub vars: Vec<DebugVarDecl>,
+    /// SSA values a variable takes, in program order.
+    pub values: Vec<DebugValue>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DebugScope {
+    pub span: Span,
+    /// Enclosing scope, or `None` for scopes directly inside the function body.
+    pub parent: Option<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DebugVarDecl {
+    pub name: String,
+    /// Declaring scope (index into `FunctionDebug::scopes`), `None` for the function body.
+    pub scope: Option<usize>,
+    pub span: Span,
+    /// 1-based parameter index for cell parameters.
+    pub arg: Option<u32>,
+}
+
+/// `var` holds `value` in `block` from instruction index `at` onwards.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DebugValue {
+    pub var: usize,
+    pub value: ValueId,
+    pub block: BlockId,
+    pub at: usize,
+}
+
+impl FunctionDebug {
+    /// The innermost scope whose span contains `span`, if any.
+    pub fn scope_at(&self, span: Span) -> Option<usize> {
+        let start = span.offset();
+        let mut best: Option<(usize, usize)> = None;
+        for (i, sc) in self.scopes.iter().enumerate() {
+            let s = sc.span.offset();
+            let len = sc.span.len();
+            if start >= s && start < s + len.max(1) && best.is_none_or(|(_, l)| len <= l) {
+                best = Some((i, len));
+            }
+        }
+        best.map(|(i, _)| i)
+    }
+}
@@ -33,10 +33,26 @@ pub enum FlowKind {
 pub enum Type {
     Unit,
     Bool,
+    U8,
+    U16,
     U32,
+    U64,
+    I32,
+    I64,
+    F64,
     String,
     Tensor,
     Opaque(String),
+    /// Structural product of two or more element types.
+    Tuple(Vec<Type>),
+    /// Closure built by `MakeClosure`: a closure body plus the values it captured.
+    Closure,
+    /// Nominal record: its declared field types, in declaration order. Backends lay it out
+    /// like the equivalent C struct.
+    Record { name: String, fields: Vec<Type> },
+    /// Tagged union: the payload types of each variant, in declaration order. A variant's
+    /// index is its tag.
+    Enum { name: String, variants: Vec<Vec<Type>> },
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -61,12 +77,21 @@ pub enum BinOp {
 
     And,
     Or,
+
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
 }
 
 #[derive(Clone, Debug)]
 pub struct ModuleIR {
     pub functions: BTreeMap<String, FunctionIR>,
     pub externs: BTreeMap<String, ExternFnSig>,
+    /// Memory-mapped register blocks reachable through `hw.open`, keyed by register name.
+    /// Filled from the `[hardware]` manifest by the build; empty for hosted programs.
+    pub mmio: BTreeMap<String, MmioRegion>,
 }
 
 impl ModuleIR {
@@ -74,10 +99,21 @@ impl ModuleIR {
         Self {
             functions: BTreeMap::new(),
             externs: BTreeMap::new(),
+            mmio: BTreeMap::new(),
         }
     }
 }
 
+/// Physical placement of a register block: `instances` copies of `size` bytes,
+/// the `i`-th starting at `base + i * stride`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MmioRegion {
+    pub base: u64,
+    pub size: u64,
+    pub instances: u64,
+    pub stride: u64,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ExternFnSig {
     pub params: Vec<Type>,
@@ -99,6 +135,10 @@ pub struct FunctionIR {
     pub ret: Type,
     pub blocks: Vec<BasicBlock>,
     pub entry: BlockId,
+    pub debug: crate::debug::FunctionDebug,
+    /// Body of a lambda: `params` are its captured values, which callers pass inside the
+    /// closure's environment rather than as arguments.
+    pub is_closure: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -139,7 +179,8 @@ pub enum InstKind {
         args: Vec<ValueId>,
     },
 
-    /// A compute kernel / async flow node.
+    /// A compute kernel / async flow node (`x ~> f()`): a call that may run concurrently with
+    /// the rest of its block. Its result is ready by the time it is read.
     ComputeKernel {
         callee: String,
         args: Vec<ValueId>,
@@ -148,6 +189,10 @@ pub enum InstKind {
     /// Range/bounds check inserted by verifier.
     RangeCheckU32 { value: ValueId, lo: u64, hi: u64 },
 
+    /// Convert an integer of type `from` to type `to`: widening zero- or sign-extends by the
+    /// signedness of `from`, narrowing keeps the low bits (sema only narrows values that fit).
+    IntCast { value: ValueId, from: Type, to: Type },
+
     /// Unary operator.
     Unary { op: UnaryOp, operand: ValueId },
 
@@ -162,6 +207,58 @@ pub enum InstKind {
     Phi {
         incomings: Vec<(BlockId, ValueId)>,
     },
+
+    /// Build a tuple value from its elements.
+    MakeTuple { elems: Vec<ValueId> },
+
+    /// Read element `index` of a tuple value.
+    TupleGet { tuple: ValueId, index: u32 },
+
+    /// Build a value of the record type `ty` from its fields, in declaration order.
+    MakeRecord { ty: Type, fields: Vec<ValueId> },
+
+    /// Read field `index` (in declaration order) of a record value.
+    RecordGet { record: ValueId, index: u32 },
+
+    /// Build variant `tag` of the enum type `ty` from its payload fields.
+    MakeEnum { ty: Type, tag: u32, fields: Vec<ValueId> },
+
+    /// Read the tag of an enum value, as a `u32`.
+    EnumTag { value: ValueId },
+
+    /// Read payload field `index` of an enum value known to hold variant `tag`.
+    EnumField { value: ValueId, tag: u32, index: u32 },
+
+    /// Build a closure over the closure body `func`, capturing `captures` (one per param).
+    MakeClosure { func: String, captures: Vec<ValueId> },
+
+    /// Call a closure value; its body returns `ret`.
+    CallClosure { closure: ValueId, ret: Type },
+}
+
+impl InstKind {
+    /// The values this instruction reads, in operand order.
+    pub fn operands(&self) -> Vec<ValueId> {
+        match self {
+            InstKind::AllocCapability { .. } => vec![],
+            InstKind::BindStrand { expr, .. } => match expr {
+                RValue::Local(v) => vec![*v],
+                _ => vec![],
+            },
+            InstKind::Call { args, .. } | InstKind::ComputeKernel { args, .. } => args.clone(),
+            InstKind::RangeCheckU32 { value, .. } | InstKind::IntCast { value, .. } => vec![*value],
+            InstKind::Unary { operand, .. } => vec![*operand],
+            InstKind::Binary { left, right, .. } => vec![*left, *right],
+            InstKind::Phi { incomings } => incomings.iter().map(|(_, v)| *v).collect(),
+            InstKind::MakeTuple { elems } => elems.clone(),
+            InstKind::TupleGet { tuple, .. } => vec![*tuple],
+            InstKind::MakeRecord { fields, .. } | InstKind::MakeEnum { fields, .. } => fields.clone(),
+            InstKind::RecordGet { record, .. } => vec![*record],
+            InstKind::EnumTag { value } | InstKind::EnumField { value, .. } => vec![*value],
+            InstKind::MakeClosure { captures, .. } => captures.clone(),
+            InstKind::CallClosure { closure, .. } => vec![*closure],
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -174,6 +271,7 @@ pub struct Inst {
 #[derive(Clone, Debug)]
 pub enum RValue {
     ConstU32(u64),
+    ConstF64(f64),
     ConstBool(bool),
     ConstString(String),
     Local(ValueId),
@@ -7,6 +7,8 @@ use crate::{BinOp, BlockId, FunctionIR, InstKind, ModuleIR, RValue, Terminator,
 #[derive(Clone, Debug, PartialEq, Eq)]
 enum ConstVal {
     U32(u64),
+    /// Stored as raw bits so constants stay `Eq`; folded through `f64::from_bits`.
+    F64(u64),
     Bool(bool),
     String(String),
 }
@@ -147,6 +149,7 @@ fn const_fold_and_simplify_cfg(f: &mut FunctionIR) -> bool {
 fn const_from_rvalue(rv: &RValue, consts: &BTreeMap<ValueId, ConstVal>) -> Option<ConstVal> {
     match rv {
         RValue::ConstU32(n) => Some(ConstVal::U32(*n)),
+        RValue::ConstF64(v) => Some(ConstVal::F64(v.to_bits())),
         RValue::ConstBool(b) => Some(ConstVal::Bool(*b)),
         RValue::ConstString(s) => Some(ConstVal::String(s.clone())),
         RValue::Local(v) => consts.get(v).cloned(),
@@ -156,6 +159,7 @@ fn const_from_rvalue(rv: &RValue, consts: &BTreeMap<ValueId, ConstVal>) -> Optio
 fn rvalue_from_const(c: ConstVal) -> RValue {
     match c {
         ConstVal::U32(n) => RValue::ConstU32(n),
+        ConstVal::F64(bits) => RValue::ConstF64(f64::from_bits(bits)),
         ConstVal::Bool(b) => RValue::ConstBool(b),
         ConstVal::String(s) => RValue::ConstString(s),
     }
@@ -164,6 +168,7 @@ fn rvalue_from_const(c: ConstVal) -> RValue {
 fn fold_unary(op: UnaryOp, v: ConstVal) -> Option<ConstVal> {
     match (op, v) {
         (UnaryOp::Neg, ConstVal::U32(n)) => Some(ConstVal::U32((0u64).wrapping_sub(n))),
+        (UnaryOp::Neg, ConstVal::F64(bits)) => Some(ConstVal::F64((-f64::from_bits(bits)).to_bits())),
         (UnaryOp::Not, ConstVal::Bool(b)) => Some(ConstVal::Bool(!b)),
         _ => None,
     }
@@ -176,6 +181,14 @@ fn fold_binary(op: BinOp, l: ConstVal, r: ConstVal) -> Option<ConstVal> {
         (BinOp::Mul, ConstVal::U32(a), ConstVal::U32(b)) => Some(ConstVal::U32(a.wrapping_mul(b))),
         (BinOp::Div, ConstVal::U32(a), ConstVal::U32(b)) if b != 0 => Some(ConstVal::U32(a / b)),
 
+        (BinOp::BitAnd, ConstVal::U32(a), ConstVal::U32(b)) => Some(ConstVal::U32(a & b)),
+        (BinOp::BitOr, ConstVal::U32(a), ConstVal::U32(b)) => Some(ConstVal::U32(a | b)),
+        (BinOp::BitXor, ConstVal::U32(a), ConstVal::U32(b)) => Some(ConstVal::U32(a ^ b)),
+        (BinOp::Shl, ConstVal::U32(a), ConstVal::U32(b)) if b < 32 => Some(ConstVal::U32((a << b) & 0xFFFF_FFFF)),
+        (BinOp::Shr, ConstVal::U32(a), ConstVal::U32(b)) if b < 32 => Some(ConstVal::U32(a >> b)),
+
+        (op, ConstVal::F64(a), ConstVal::F64(b)) => fold_f64(op, f64::from_bits(a), f64::from_bits(b)),
+
         (BinOp::Eq, a, b) => Some(ConstVal::Bool(a == b)),
         (BinOp::Ne, a, b) => Some(ConstVal::Bool(a != b)),
 
@@ -191,6 +204,23 @@ fn fold_binary(op: BinOp, l: ConstVal, r: ConstVal) -> Option<ConstVal> {
     }
 }
 
+fn fold_f64(op: BinOp, a: f64, b: f64) -> Option<ConstVal> {
+    let f = |v: f64| Some(ConstVal::F64(v.to_bits()));
+    match op {
+        BinOp::Add => f(a + b),
+        BinOp::Sub => f(a - b),
+        BinOp::Mul => f(a * b),
+        BinOp::Div => f(a / b),
+        BinOp::Eq => Some(ConstVal::Bool(a == b)),
+        BinOp::Ne => Some(ConstVal::Bool(a != b)),
+        BinOp::Lt => Some(ConstVal::Bool(a < b)),
+        BinOp::Gt => Some(ConstVal::Bool(a > b)),
+        BinOp::Le => Some(ConstVal::Bool(a <= b)),
+        BinOp::Ge => Some(ConstVal::Bool(a >= b)),
+        BinOp::And | BinOp::Or | BinOp::BitAnd | BinOp::BitOr | BinOp::BitXor | BinOp::Shl | BinOp::Shr => None,
+    }
+}
+
 fn dce(f: &mut FunctionIR) -> bool {
     let mut changed = false;
 
@@ -223,18 +253,37 @@ fn dce(f: &mut FunctionIR) -> bool {
         seed_value_uses_from_inst(&inst.kind, &mut needed_values, &mut work);
     }
 
+    // Removed copies (`val x = y` strands) forward their variable's debug info to the source.
+    let mut copies: BTreeMap<ValueId, ValueId> = BTreeMap::new();
     for b in &mut f.blocks {
-        let before = b.insts.len();
-        b.insts.retain(|inst| {
-            let Some(dest) = inst.dest else { return true };
-            if needed_values.contains(&dest) {
-                return true;
+        let keep: Vec<bool> = b
+            .insts
+            .iter()
+            .map(|inst| {
+                let Some(dest) = inst.dest else { return true };
+                // If it produces a value we don't need, only keep it if it has side effects.
+                needed_values.contains(&dest) || is_side_effecting(&inst.kind)
+            })
+            .collect();
+        if keep.iter().all(|k| *k) {
+            continue;
+        }
+        for (inst, _) in b.insts.iter().zip(&keep).filter(|(_, k)| !**k) {
+            if let (Some(dest), InstKind::BindStrand { expr: RValue::Local(src), .. }) = (inst.dest, &inst.kind) {
+                copies.insert(dest, *src);
             }
-            // If it produces a value we don't need, only keep it if it has side effects.
-            is_side_effecting(&inst.kind)
-        });
-        if b.insts.len() != before {
-            changed = true;
+        }
+        // Debug values are placed by instruction index; shift them past the removed ones.
+        for dv in f.debug.values.iter_mut().filter(|dv| dv.block == b.id) {
+            dv.at = keep[..dv.at.min(keep.len())].iter().filter(|k| **k).count();
+        }
+        let mut keep = keep.into_iter();
+        b.insts.retain(|_| keep.next().unwrap_or(true));
+        changed = true;
+    }
+    for dv in &mut f.debug.values {
+        while let Some(src) = copies.get(&dv.value) {
+            dv.value = *src;
         }
     }
 
@@ -242,7 +291,13 @@ fn dce(f: &mut FunctionIR) -> bool {
 }
 
 fn is_side_effecting(k: &InstKind) -> bool {
-    matches!(k, InstKind::Call { .. } | InstKind::ComputeKernel { .. } | InstKind::RangeCheckU32 { .. })
+    matches!(
+        k,
+        InstKind::Call { .. }
+            | InstKind::ComputeKernel { .. }
+            | InstKind::CallClosure { .. }
+            | InstKind::RangeCheckU32 { .. }
+    )
 }
 
 fn seed_value_uses_from_term(term: &Terminator, needed: &mut BTreeSet<ValueId>, work: &mut VecDeque<ValueId>) {
@@ -286,7 +341,7 @@ fn seed_value_uses_from_inst(k: &InstKind, needed: &mut BTreeSet<ValueId>, work:
                 use_v(*v);
             }
         }
-        InstKind::RangeCheckU32 { value, .. } => use_v(*value),
+        InstKind::RangeCheckU32 { value, .. } | InstKind::IntCast { value, .. } => use_v(*value),
         InstKind::Unary { operand, .. } => use_v(*operand),
         InstKind::Binary { left, right, .. } => {
             use_v(*left);
@@ -297,6 +352,30 @@ fn seed_value_uses_from_inst(k: &InstKind, needed: &mut BTreeSet<ValueId>, work:
                 use_v(*v);
             }
         }
+        InstKind::MakeTuple { elems } => {
+            for v in elems {
+                use_v(*v);
+            }
+        }
+        InstKind::TupleGet { tuple, .. } => use_v(*tuple),
+        InstKind::MakeRecord { fields, .. } => {
+            for v in fields {
+                use_v(*v);
+            }
+        }
+        InstKind::RecordGet { record, .. } => use_v(*record),
+        InstKind::MakeEnum { fields, .. } => {
+            for v in fields {
+                use_v(*v);
+            }
+        }
+        InstKind::EnumTag { value } | InstKind::EnumField { value, .. } => use_v(*value),
+        InstKind::MakeClosure { captures, .. } => {
+            for v in captures {
+                use_v(*v);
+            }
+        }
+        InstKind::CallClosure { closure, .. } => use_v(*closure),
     }
 }
 
@@ -356,6 +435,7 @@ fn remove_unreachable_blocks(f: &mut FunctionIR) -> bool {
 
     // Drop dead blocks.
     f.blocks.retain(|b| reachable.contains(&b.id));
+    f.debug.values.retain(|dv| reachable.contains(&dv.block));
 
     // Clean up phi incomings from dead predecessors; if it collapses, leave it to DCE.
     for b in &mut f.blocks {
@@ -445,6 +525,8 @@ mod tests {
                 term: Terminator::Return(Some(v(2))),
             }],
             entry: b(0),
+            debug: Default::default(),
+            is_closure: false,
         };
 
         optimize_function(&mut f);
@@ -453,4 +535,74 @@ mod tests {
         assert!(insts.iter().any(|i| matches!(&i.kind, InstKind::BindStrand{ expr: RValue::ConstU32(3), .. })), "expected folded z = 3");
         assert!(!insts.iter().any(|i| i.dest == Some(v(3))), "expected unused w to be DCE'd");
     }
+
+    #[test]
+    fn dce_keeps_debug_values_pointing_at_live_instructions() {
+        // t = call g()   (kept: side effects)
+        // x = t          (unused copy, removed; `x` is now described by t)
+        // y = 5          (unused)
+        // call h(t)
+        let call = |dest: Option<ValueId>, callee: &str, args: Vec<ValueId>| Inst {
+            span: span(0, 1),
+            dest,
+            kind: InstKind::Call {
+                callee: callee.to_string(),
+                args,
+            },
+        };
+        let mut f = FunctionIR {
+            name: "f".to_string(),
+            span: span(0, 0),
+            params: vec![],
+            ret: Type::Unit,
+            blocks: vec![BasicBlock {
+                id: b(0),
+                span: span(0, 0),
+                hint: ExecutionHint::Sequential,
+                insts: vec![
+                    call(Some(v(0)), "g", vec![]),
+                    Inst {
+                        span: span(2, 1),
+                        dest: Some(v(1)),
+                        kind: InstKind::BindStrand {
+                            name: "x".to_string(),
+                            expr: RValue::Local(v(0)),
+                        },
+                    },
+                    Inst {
+                        span: span(4, 1),
+                        dest: Some(v(2)),
+                        kind: InstKind::BindStrand {
+                            name: "y".to_string(),
+                            expr: RValue::ConstU32(5),
+                        },
+                    },
+                    call(None, "h", vec![v(0)]),
+                ],
+                term: Terminator::Return(None),
+            }],
+            entry: b(0),
+            debug: Default::default(),
+            is_closure: false,
+        };
+        f.debug.values.push(crate::DebugValue {
+            var: 0,
+            value: v(1),
+            block: b(0),
+            at: 2,
+        });
+        f.debug.values.push(crate::DebugValue {
+            var: 1,
+            value: v(2),
+            block: b(0),
+            at: 3,
+        });
+
+        optimize_function(&mut f);
+
+        assert_eq!(f.blocks[0].insts.len(), 2);
+        let dv = &f.debug.values;
+        assert_eq!((dv[0].value, dv[0].at), (v(0), 1), "copy forwards to its source");
+        assert_eq!(dv[1].at, 1, "positions shift past removed instructions");
+    }
 }
@@ -6,7 +6,7 @@ use crate::{
     BinOp, BlockId, FunctionIR, InstKind, ModuleIR, RValue, Terminator, Type, UnaryOp, ValueId,
 };
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct OracleOutput {
     pub ok: bool,
     pub stdout: String,
@@ -14,14 +14,22 @@ pub struct OracleOutput {
     pub return_value: Option<OracleValue>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum OracleValue {
     Unit,
     Bool(bool),
     U32(u32),
+    F64(f64),
     String(String),
     Tensor(u32),
     Opaque(String),
+    Tuple(Vec<OracleValue>),
+    /// A record value and its fields, in declaration order.
+    Record { name: String, fields: Vec<OracleValue> },
+    /// An enum value of type `ty`: its variant's tag and payload fields.
+    Enum { ty: Type, tag: u32, fields: Vec<OracleValue> },
+    /// A closure body and the values it captured.
+    Closure { func: String, env: Vec<OracleValue> },
 }
 
 #[derive(Debug, Clone)]
@@ -247,6 +255,30 @@ fn run_function(
                         return Ok((None, false));
                     }
                 }
+                InstKind::IntCast { value, to, .. } => {
+                    let v = env.get(value).ok_or_else(|| OracleError {
+                        message: format!("oracle: missing value {:?} for integer conversion", value),
+                    })?;
+                    // Only the unsigned types up to 32 bits are modeled, all as `U32`.
+                    let mask = match to {
+                        Type::U8 => 0xFF,
+                        Type::U16 => 0xFFFF,
+                        Type::U32 => u32::MAX,
+                        other => {
+                            return Err(OracleError {
+                                message: format!("oracle: {other:?} values are not modeled"),
+                            });
+                        }
+                    };
+                    let OracleValue::U32(u) = v else {
+                        return Err(OracleError {
+                            message: "oracle: IntCast expects U32".to_string(),
+                        });
+                    };
+                    if let Some(dest) = inst.dest {
+                        env.insert(dest, OracleValue::U32(u & mask));
+                    }
+                }
                 InstKind::Unary { op, operand } => {
                     let v = env.get(operand).ok_or_else(|| OracleError {
                         message: format!("oracle: missing operand {:?}", operand),
@@ -291,6 +323,153 @@ fn run_function(
                         env.insert(dest, v.clone());
                     }
                 }
+                InstKind::MakeTuple { elems } => {
+                    let vals = elems
+                        .iter()
+                        .map(|id| {
+                            env.get(id).cloned().ok_or_else(|| OracleError {
+                                message: format!("oracle: missing tuple element {:?}", id),
+                            })
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    if let Some(dest) = inst.dest {
+                        env.insert(dest, OracleValue::Tuple(vals));
+                    }
+                }
+                InstKind::TupleGet { tuple, index } => {
+                    let Some(OracleValue::Tuple(vals)) = env.get(tuple) else {
+                        return Err(OracleError {
+                            message: "oracle: TupleGet expects Tuple".to_string(),
+                        });
+                    };
+                    let v = vals.get(*index as usize).cloned().ok_or_else(|| OracleError {
+                        message: format!("oracle: tuple index {index} out of bounds"),
+                    })?;
+                    if let Some(dest) = inst.dest {
+                        env.insert(dest, v);
+                    }
+                }
+                InstKind::MakeRecord { ty, fields } => {
+                    let Type::Record { name, .. } = ty else {
+                        return Err(OracleError {
+                            message: format!("oracle: MakeRecord of non-record type {ty:?}"),
+                        });
+                    };
+                    let vals = fields
+                        .iter()
+                        .map(|id| {
+                            env.get(id).cloned().ok_or_else(|| OracleError {
+                                message: format!("oracle: missing record field {:?}", id),
+                            })
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    if let Some(dest) = inst.dest {
+                        env.insert(
+                            dest,
+                            OracleValue::Record {
+                                name: name.clone(),
+                                fields: vals,
+                            },
+                        );
+                    }
+                }
+                InstKind::RecordGet { record, index } => {
+                    let Some(OracleValue::Record { fields, .. }) = env.get(record) else {
+                        return Err(OracleError {
+                            message: "oracle: RecordGet expects Record".to_string(),
+                        });
+                    };
+                    let v = fields.get(*index as usize).cloned().ok_or_else(|| OracleError {
+                        message: format!("oracle: record field {index} out of bounds"),
+                    })?;
+                    if let Some(dest) = inst.dest {
+                        env.insert(dest, v);
+                    }
+                }
+                InstKind::MakeEnum { ty, tag, fields } => {
+                    let vals = fields
+                        .iter()
+                        .map(|id| {
+                            env.get(id).cloned().ok_or_else(|| OracleError {
+                                message: format!("oracle: missing enum payload {:?}", id),
+                            })
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    if let Some(dest) = inst.dest {
+                        env.insert(
+                            dest,
+                            OracleValue::Enum {
+                                ty: ty.clone(),
+                                tag: *tag,
+                                fields: vals,
+                            },
+                        );
+                    }
+                }
+                InstKind::EnumTag { value } => {
+                    let Some(OracleValue::Enum { tag, .. }) = env.get(value) else {
+                        return Err(OracleError {
+                            message: "oracle: EnumTag expects Enum".to_string(),
+                        });
+                    };
+                    if let Some(dest) = inst.dest {
+                        env.insert(dest, OracleValue::U32(*tag));
+                    }
+                }
+                InstKind::EnumField { value, tag, index } => {
+                    let Some(OracleValue::Enum { tag: actual, fields, .. }) = env.get(value) else {
+                        return Err(OracleError {
+                            message: "oracle: EnumField expects Enum".to_string(),
+                        });
+                    };
+                    if actual != tag {
+                        return Err(OracleError {
+                            message: format!("oracle: payload of variant {tag} read from variant {actual}"),
+                        });
+                    }
+                    let v = fields.get(*index as usize).cloned().ok_or_else(|| OracleError {
+                        message: format!("oracle: enum payload field {index} out of bounds"),
+                    })?;
+                    if let Some(dest) = inst.dest {
+                        env.insert(dest, v);
+                    }
+                }
+                InstKind::MakeClosure { func, captures } => {
+                    let vals = captures
+                        .iter()
+                        .map(|id| {
+                            env.get(id).cloned().ok_or_else(|| OracleError {
+                                message: format!("oracle: missing capture {:?}", id),
+                            })
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    if let Some(dest) = inst.dest {
+                        env.insert(
+                            dest,
+                            OracleValue::Closure {
+                                func: func.clone(),
+                                env: vals,
+                            },
+                        );
+                    }
+                }
+                InstKind::CallClosure { closure, .. } => {
+                    let Some(OracleValue::Closure { func, env: captured }) = env.get(closure).cloned() else {
+                        return Err(OracleError {
+                            message: "oracle: CallClosure expects Closure".to_string(),
+                        });
+                    };
+                    let f = module.functions.get(&func).ok_or_else(|| OracleError {
+                        message: format!("oracle: unknown closure body '{func}'"),
+                    })?;
+                    let (rv, ok) = run_function(module, f, &captured, stdout, stderr, cfg.clone())?;
+                    if !ok {
+                        return Ok((None, false));
+                    }
+                    if let Some(dest) = inst.dest {
+                        env.insert(dest, rv.unwrap_or(OracleValue::Unit));
+                    }
+                }
                 InstKind::Call { callee, args } | InstKind::ComputeKernel { callee, args } => {
                     let call_args = args
                         .iter()
@@ -395,6 +574,7 @@ fn run_function(
 fn eval_rvalue(rv: &RValue, env: &HashMap<ValueId, OracleValue>) -> Result<OracleValue, OracleError> {
     Ok(match rv {
         RValue::ConstU32(u) => OracleValue::U32(*u as u32),
+        RValue::ConstF64(v) => OracleValue::F64(*v),
         RValue::ConstBool(b) => OracleValue::Bool(*b),
         RValue::ConstString(s) => OracleValue::String(s.clone()),
         RValue::Local(id) => env.get(id).cloned().ok_or_else(|| OracleError {
@@ -406,6 +586,7 @@ fn eval_rvalue(rv: &RValue, env: &HashMap<ValueId, OracleValue>) -> Result<Oracl
 fn eval_unary(op: UnaryOp, v: &OracleValue) -> Result<OracleValue, OracleError> {
     match (op, v) {
         (UnaryOp::Neg, OracleValue::U32(x)) => Ok(OracleValue::U32(x.wrapping_neg())),
+        (UnaryOp::Neg, OracleValue::F64(x)) => Ok(OracleValue::F64(-x)),
         (UnaryOp::Not, OracleValue::Bool(b)) => Ok(OracleValue::Bool(!b)),
         _ => Err(OracleError {
             message: format!("oracle: unsupported unary op {:?} for value {:?}", op, v),
@@ -428,6 +609,19 @@ fn eval_binary(op: BinOp, l: &OracleValue, r: &OracleValue) -> Result<OracleValu
             }
             Ok(OracleValue::U32(a / b))
         }
+        (BitAnd, OracleValue::U32(a), OracleValue::U32(b)) => Ok(OracleValue::U32(a & b)),
+        (BitOr, OracleValue::U32(a), OracleValue::U32(b)) => Ok(OracleValue::U32(a | b)),
+        (BitXor, OracleValue::U32(a), OracleValue::U32(b)) => Ok(OracleValue::U32(a ^ b)),
+        (Shl | Shr, OracleValue::U32(_), OracleValue::U32(b)) if *b >= 32 => Err(OracleError {
+            message: format!("oracle: shift amount {b} is out of range for u32"),
+        }),
+        (Shl, OracleValue::U32(a), OracleValue::U32(b)) => Ok(OracleValue::U32(a << b)),
+        (Shr, OracleValue::U32(a), OracleValue::U32(b)) => Ok(OracleValue::U32(a >> b)),
+
+        (Add, OracleValue::F64(a), OracleValue::F64(b)) => Ok(OracleValue::F64(a + b)),
+        (Sub, OracleValue::F64(a), OracleValue::F64(b)) => Ok(OracleValue::F64(a - b)),
+        (Mul, OracleValue::F64(a), OracleValue::F64(b)) => Ok(OracleValue::F64(a * b)),
+        (Div, OracleValue::F64(a), OracleValue::F64(b)) => Ok(OracleValue::F64(a / b)),
 
         (Eq, OracleValue::U32(a), OracleValue::U32(b)) => Ok(OracleValue::Bool(a == b)),
         (Ne, OracleValue::U32(a), OracleValue::U32(b)) => Ok(OracleValue::Bool(a != b)),
@@ -436,6 +630,13 @@ fn eval_binary(op: BinOp, l: &OracleValue, r: &OracleValue) -> Result<OracleValu
         (Le, OracleValue::U32(a), OracleValue::U32(b)) => Ok(OracleValue::Bool(a <= b)),
         (Ge, OracleValue::U32(a), OracleValue::U32(b)) => Ok(OracleValue::Bool(a >= b)),
 
+        (Eq, OracleValue::F64(a), OracleValue::F64(b)) => Ok(OracleValue::Bool(a == b)),
+        (Ne, OracleValue::F64(a), OracleValue::F64(b)) => Ok(OracleValue::Bool(a != b)),
+        (Lt, OracleValue::F64(a), OracleValue::F64(b)) => Ok(OracleValue::Bool(a < b)),
+        (Gt, OracleValue::F64(a), OracleValue::F64(b)) => Ok(OracleValue::Bool(a > b)),
+        (Le, OracleValue::F64(a), OracleValue::F64(b)) => Ok(OracleValue::Bool(a <= b)),
+        (Ge, OracleValue::F64(a), OracleValue::F64(b)) => Ok(OracleValue::Bool(a >= b)),
+
         (Eq, OracleValue::Bool(a), OracleValue::Bool(b)) => Ok(OracleValue::Bool(a == b)),
         (Ne, OracleValue::Bool(a), OracleValue::Bool(b)) => Ok(OracleValue::Bool(a != b)),
 
@@ -477,8 +678,16 @@ pub fn oracle_type_of(v: &OracleValue) -> Type {
         OracleValue::Unit => Type::Unit,
         OracleValue::Bool(_) => Type::Bool,
         OracleValue::U32(_) => Type::U32,
+        OracleValue::F64(_) => Type::F64,
         OracleValue::String(_) => Type::String,
         OracleValue::Tensor(_) => Type::Tensor,
         OracleValue::Opaque(s) => Type::Opaque(s.clone()),
+        OracleValue::Tuple(vals) => Type::Tuple(vals.iter().map(oracle_type_of).collect()),
+        OracleValue::Record { name, fields } => Type::Record {
+            name: name.clone(),
+            fields: fields.iter().map(oracle_type_of).collect(),
+        },
+        OracleValue::Enum { ty, .. } => ty.clone(),
+        OracleValue::Closure { .. } => Type::Closure,
     }
 }
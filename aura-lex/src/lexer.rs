@@ -6,7 +6,7 @@ use logos::Logos;
 use miette::Diagnostic;
 use thiserror::Error;
 
-use crate::token::{Token, TokenKind};
+use crate::token::{DocComment, StrPart, Token, TokenKind, Trivia};
 
 #[derive(Debug, Error, Diagnostic)]
 #[error("lex error: {message}")]
@@ -25,6 +25,8 @@ enum RawToken {
     KwImport,
     #[token("val")]
     KwVal,
+    #[token("const")]
+    KwConst,
     #[token("extern")]
     KwExtern,
     #[token("macro")]
@@ -53,6 +55,18 @@ enum RawToken {
     KwMatch,
     #[token("while")]
     KwWhile,
+    #[token("for")]
+    KwFor,
+    #[token("in")]
+    KwIn,
+    #[token("break")]
+    KwBreak,
+    #[token("continue")]
+    KwContinue,
+    #[token("return")]
+    KwReturn,
+    #[token("defer")]
+    KwDefer,
     #[token("invariant")]
     KwInvariant,
     #[token("decreases")]
@@ -95,6 +109,8 @@ enum RawToken {
     Le,
     #[token(">=")]
     Ge,
+    #[token("<<")]
+    Shl,
     #[token("<")]
     Lt,
     #[token(">")]
@@ -104,6 +120,12 @@ enum RawToken {
     AndAnd,
     #[token("||")]
     OrOr,
+    #[token("|")]
+    Pipe,
+    #[token("&")]
+    Amp,
+    #[token("^")]
+    Caret,
     #[token("!")]
     Bang,
 
@@ -147,10 +169,17 @@ enum RawToken {
     #[regex(r"[0-9][0-9_]*", |lex| parse_int_decimal(lex.slice()))]
     Int(Option<u64>),
 
+    // Float literals: `1.5`, `2.0e-3`, `1e9`. A fraction needs digits on both sides of `.`
+    // so ranges like `0..n` still lex as Int DotDot.
+    #[regex(r"[0-9][0-9_]*\.[0-9][0-9_]*([eE][+-]?[0-9][0-9_]*)?", |lex| parse_float(lex.slice()))]
+    #[regex(r"[0-9][0-9_]*[eE][+-]?[0-9][0-9_]*", |lex| parse_float(lex.slice()))]
+    Float(Option<f64>),
+
     // String literals: "..." with a limited, strict set of escapes.
     // Supported: \n, \t, \r, \", \\, and \u{HEX} (1-6 hex digits)
+    // `{expr}` embeds an expression; `{{` and `}}` are literal braces.
     #[regex(r#"\"([^\"\\]|\\.)*\""#, parse_string)]
-    String(Option<String>),
+    String(Option<Vec<RawStrPart>>),
 
     #[regex(r"[a-zA-Z_][a-zA-Z0-9_]*", |lex| lex.slice().to_string())]
     Ident(String),
@@ -167,6 +196,22 @@ fn parse_int_prefixed(s: &str, radix: u32, prefix_len: usize) -> Option<u64> {
     u64::from_str_radix(&digits, radix).ok()
 }
 
+fn parse_float(s: &str) -> Option<f64> {
+    // Underscores may only separate digits.
+    let bytes = s.as_bytes();
+    for (i, b) in bytes.iter().enumerate() {
+        if *b == b'_' {
+            let prev = i.checked_sub(1).map(|j| bytes[j]);
+            let next = bytes.get(i + 1).copied();
+            if !prev.is_some_and(|c| c.is_ascii_digit()) || !next.is_some_and(|c| c.is_ascii_digit()) {
+                return None;
+            }
+        }
+    }
+    let v = s.replace('_', "").parse::<f64>().ok()?;
+    v.is_finite().then_some(v)
+}
+
 fn strip_underscores(s: &str) -> Option<String> {
     if s.is_empty() {
         return None;
@@ -177,21 +222,73 @@ fn strip_underscores(s: &str) -> Option<String> {
     Some(s.replace('_', ""))
 }
 
-fn parse_string(lex: &mut logos::Lexer<RawToken>) -> Option<String> {
+#[derive(Debug, Clone, PartialEq)]
+enum RawStrPart {
+    Lit(String),
+    /// Source of an embedded expression and its byte offset within the literal token.
+    Expr { src: String, offset: usize },
+}
+
+fn parse_string(lex: &mut logos::Lexer<RawToken>) -> Option<Vec<RawStrPart>> {
     let s = lex.slice();
     let inner = &s[1..s.len().saturating_sub(1)];
+    let mut parts = Vec::new();
     let mut out = String::with_capacity(inner.len());
-    let mut chars = inner.chars().peekable();
+    let mut chars = inner.char_indices().peekable();
+
+    while let Some((pos, c)) = chars.next() {
+        if c == '{' {
+            if chars.peek().map(|(_, ch)| *ch) == Some('{') {
+                chars.next();
+                out.push('{');
+                continue;
+            }
 
-    while let Some(c) = chars.next() {
+            // Embedded expression: scan to the matching `}` (record literals may nest braces).
+            let mut depth = 1usize;
+            let mut end = None;
+            for (p, ch) in chars.by_ref() {
+                match ch {
+                    '{' => depth += 1,
+                    '}' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            end = Some(p);
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            let end = end?;
+            let src = &inner[pos + 1..end];
+            if src.trim().is_empty() {
+                return None;
+            }
+            if !out.is_empty() {
+                parts.push(RawStrPart::Lit(std::mem::take(&mut out)));
+            }
+            // +1 for the opening quote, +1 for `{`.
+            parts.push(RawStrPart::Expr {
+                src: src.to_string(),
+                offset: pos + 2,
+            });
+            continue;
+        }
+        if c == '}' {
+            if chars.peek().map(|(_, ch)| *ch) == Some('}') {
+                chars.next();
+                out.push('}');
+                continue;
+            }
+            return None;
+        }
         if c != '\\' {
             out.push(c);
             continue;
         }
 
-        let Some(esc) = chars.next() else {
-            return None;
-        };
+        let (_, esc) = chars.next()?;
 
         match esc {
             'n' => out.push('\n'),
@@ -201,11 +298,11 @@ fn parse_string(lex: &mut logos::Lexer<RawToken>) -> Option<String> {
             '\\' => out.push('\\'),
             'u' => {
                 // Expect: \u{HEX}
-                if chars.next() != Some('{') {
+                if chars.next().map(|(_, ch)| ch) != Some('{') {
                     return None;
                 }
                 let mut hex = String::new();
-                while let Some(&ch) = chars.peek() {
+                while let Some(&(_, ch)) = chars.peek() {
                     if ch == '}' {
                         break;
                     }
@@ -215,7 +312,7 @@ fn parse_string(lex: &mut logos::Lexer<RawToken>) -> Option<String> {
                         return None;
                     }
                 }
-                if chars.next() != Some('}') {
+                if chars.next().map(|(_, ch)| ch) != Some('}') {
                     return None;
                 }
                 if hex.is_empty() {
@@ -229,7 +326,10 @@ fn parse_string(lex: &mut logos::Lexer<RawToken>) -> Option<String> {
         }
     }
 
-    Some(out)
+    if !out.is_empty() || parts.is_empty() {
+        parts.push(RawStrPart::Lit(out));
+    }
+    Some(parts)
 }
 
 pub struct Lexer<'a> {
@@ -284,8 +384,47 @@ impl<'a> Lexer<'a> {
         None
     }
 
+    fn string_token(parts: Vec<RawStrPart>, abs_start: usize) -> Result<TokenKind, LexError> {
+        if let [RawStrPart::Lit(lit)] = parts.as_slice() {
+            return Ok(TokenKind::String(lit.clone()));
+        }
+
+        let mut out = Vec::with_capacity(parts.len());
+        for part in parts {
+            match part {
+                RawStrPart::Lit(lit) => out.push(StrPart::Lit(lit)),
+                RawStrPart::Expr { src, offset } => {
+                    // Embedded expressions are single-line; lex them on their own and shift
+                    // spans back into the enclosing source.
+                    let base = abs_start + offset + (src.len() - src.trim_start().len());
+                    let tokens = Lexer::new(src.trim())
+                        .lex()?
+                        .into_iter()
+                        .filter(|t| !matches!(t.kind, TokenKind::Newline))
+                        .map(|t| Token {
+                            kind: t.kind,
+                            span: span_between(base + t.span.offset(), base + t.span.offset() + t.span.len()),
+                        })
+                        .collect();
+                    out.push(StrPart::Expr(tokens));
+                }
+            }
+        }
+        Ok(TokenKind::InterpString(out))
+    }
+
     pub fn lex(&self) -> Result<Vec<Token>, LexError> {
+        self.lex_with_trivia().map(|(tokens, _)| tokens)
+    }
+
+    /// Like [`Lexer::lex`], but also returns `##` doc comments.
+    ///
+    /// A doc comment attaches to the next code line only when nothing else (blank lines or
+    /// ordinary comments) separates them.
+    pub fn lex_with_trivia(&self) -> Result<(Vec<Token>, Trivia), LexError> {
         let mut tokens = Vec::new();
+        let mut trivia = Trivia::default();
+        let mut pending_doc: Option<(usize, usize, Vec<String>)> = None;
         let mut indent_stack: Vec<usize> = vec![0];
 
         // Track absolute byte offsets.
@@ -304,6 +443,7 @@ impl<'a> Lexer<'a> {
 
             // Skip completely empty/whitespace-only lines (but still advance line_start).
             if content.trim().is_empty() {
+                pending_doc = None;
                 line_start = line_end;
                 continue;
             }
@@ -351,7 +491,20 @@ impl<'a> Lexer<'a> {
             // Comment stripping: ignore comment markers inside string literals.
             let mut code = &content[leading_spaces..];
             if let Some(idx) = Self::find_comment_start(code) {
+                let comment = &code[idx..];
                 code = &code[..idx];
+                if code.trim().is_empty() {
+                    if let Some(doc) = comment.strip_prefix("##") {
+                        let doc = doc.strip_prefix(' ').unwrap_or(doc).trim_end();
+                        let start = line_start + leading_spaces + idx;
+                        let end = line_start + content.len();
+                        let entry = pending_doc.get_or_insert((start, end, Vec::new()));
+                        entry.1 = end;
+                        entry.2.push(doc.to_string());
+                    } else {
+                        pending_doc = None;
+                    }
+                }
             }
             if code.trim().is_empty() {
                 // Line was only comment.
@@ -359,15 +512,46 @@ impl<'a> Lexer<'a> {
                 continue;
             }
 
+            if let Some((start, end, lines)) = pending_doc.take() {
+                trivia.doc_comments.push(DocComment {
+                    span: span_between(start, end),
+                    text: lines.join("\n"),
+                    target: line_start + leading_spaces,
+                });
+            }
+
             let mut lex = RawToken::lexer(code);
             while let Some(raw) = lex.next() {
                 let span_in_line = lex.span();
                 let abs_start = line_start + leading_spaces + span_in_line.start;
                 let abs_end = line_start + leading_spaces + span_in_line.end;
 
+                // `t.0.1`: after a `.`, `0.1` is two tuple indices rather than a float.
+                if matches!(raw, Ok(RawToken::Float(_)))
+                    && matches!(tokens.last(), Some(Token { kind: TokenKind::Dot, .. }))
+                    && let Some((outer, inner)) = lex.slice().split_once('.')
+                    && let (Ok(a), Ok(b)) = (outer.parse::<u64>(), inner.parse::<u64>())
+                {
+                    let dot = abs_start + outer.len();
+                    tokens.push(Token {
+                        kind: TokenKind::Int(a),
+                        span: span_between(abs_start, dot),
+                    });
+                    tokens.push(Token {
+                        kind: TokenKind::Dot,
+                        span: span_between(dot, dot + 1),
+                    });
+                    tokens.push(Token {
+                        kind: TokenKind::Int(b),
+                        span: span_between(dot + 1, abs_end),
+                    });
+                    continue;
+                }
+
                 let kind = match raw {
                     Ok(RawToken::KwImport) => TokenKind::KwImport,
                     Ok(RawToken::KwVal) => TokenKind::KwVal,
+                    Ok(RawToken::KwConst) => TokenKind::KwConst,
                     Ok(RawToken::KwExtern) => TokenKind::KwExtern,
                     Ok(RawToken::KwMacro) => TokenKind::KwMacro,
                     Ok(RawToken::KwCell) => TokenKind::KwCell,
@@ -382,6 +566,12 @@ impl<'a> Lexer<'a> {
                     Ok(RawToken::KwElse) => TokenKind::KwElse,
                     Ok(RawToken::KwMatch) => TokenKind::KwMatch,
                     Ok(RawToken::KwWhile) => TokenKind::KwWhile,
+                    Ok(RawToken::KwFor) => TokenKind::KwFor,
+                    Ok(RawToken::KwIn) => TokenKind::KwIn,
+                    Ok(RawToken::KwBreak) => TokenKind::KwBreak,
+                    Ok(RawToken::KwContinue) => TokenKind::KwContinue,
+                    Ok(RawToken::KwReturn) => TokenKind::KwReturn,
+                    Ok(RawToken::KwDefer) => TokenKind::KwDefer,
                     Ok(RawToken::KwInvariant) => TokenKind::KwInvariant,
                     Ok(RawToken::KwDecreases) => TokenKind::KwDecreases,
                     Ok(RawToken::KwRequires) => TokenKind::KwRequires,
@@ -404,11 +594,15 @@ impl<'a> Lexer<'a> {
                     Ok(RawToken::Neq) => TokenKind::Neq,
                     Ok(RawToken::Le) => TokenKind::Le,
                     Ok(RawToken::Ge) => TokenKind::Ge,
+                    Ok(RawToken::Shl) => TokenKind::Shl,
                     Ok(RawToken::Lt) => TokenKind::Lt,
                     Ok(RawToken::Gt) => TokenKind::Gt,
 
                     Ok(RawToken::AndAnd) => TokenKind::AndAnd,
                     Ok(RawToken::OrOr) => TokenKind::OrOr,
+                    Ok(RawToken::Pipe) => TokenKind::Pipe,
+                    Ok(RawToken::Amp) => TokenKind::Amp,
+                    Ok(RawToken::Caret) => TokenKind::Caret,
                     Ok(RawToken::Bang) => TokenKind::Bang,
 
                     Ok(RawToken::Plus) => TokenKind::Plus,
@@ -432,13 +626,30 @@ impl<'a> Lexer<'a> {
 
                     Ok(RawToken::Ident(s)) => TokenKind::Ident(s),
                     Ok(RawToken::Int(Some(n))) => TokenKind::Int(n),
+                    Ok(RawToken::Float(Some(v))) => TokenKind::Float(v),
+                    Ok(RawToken::Float(None)) => {
+                        return Err(LexError {
+                            message: "invalid float literal".to_string(),
+                            span: span_between(abs_start, abs_end),
+                        });
+                    }
                     Ok(RawToken::Int(None)) => {
                         return Err(LexError {
                             message: "invalid integer literal".to_string(),
                             span: span_between(abs_start, abs_end),
                         });
                     }
-                    Ok(RawToken::String(Some(s))) => TokenKind::String(s),
+                    Ok(RawToken::String(Some(parts))) => {
+                        match Self::string_token(parts, abs_start) {
+                            Ok(kind) => kind,
+                            Err(_) => {
+                                return Err(LexError {
+                                    message: "invalid interpolation in string literal".to_string(),
+                                    span: span_between(abs_start, abs_end),
+                                });
+                            }
+                        }
+                    }
                     Ok(RawToken::String(None)) => {
                         return Err(LexError {
                             message: "invalid string literal".to_string(),
@@ -483,6 +694,6 @@ impl<'a> Lexer<'a> {
             span: span_between(self.src.len(), self.src.len()),
         });
 
-        Ok(tokens)
+        Ok((tokens, trivia))
     }
 }
@@ -4,7 +4,7 @@ mod lexer;
 mod token;
 
 pub use lexer::{LexError, Lexer};
-pub use token::{Token, TokenKind};
+pub use token::{DocComment, StrPart, Token, TokenKind, Trivia};
 
 #[cfg(test)]
 mod tests {
@@ -61,4 +61,63 @@ mod tests {
 		let err = Lexer::new("val s = \"\\q\"\n").lex().unwrap_err();
 		assert!(err.message.contains("invalid string literal"));
 	}
+
+	#[test]
+	fn lex_interpolated_string_parts() {
+		let src = "val s = \"n = {n + 1}, {{ok}}\"\n";
+		let tokens = Lexer::new(src).lex().unwrap();
+		let parts = tokens
+			.iter()
+			.find_map(|t| match &t.kind {
+				TokenKind::InterpString(parts) => Some(parts.clone()),
+				_ => None,
+			})
+			.unwrap();
+		assert_eq!(parts.len(), 3);
+		assert_eq!(parts[0], StrPart::Lit("n = ".to_string()));
+		let StrPart::Expr(expr) = &parts[1] else {
+			panic!("expected embedded expression");
+		};
+		assert_eq!(expr[0].kind, TokenKind::Ident("n".to_string()));
+		assert_eq!(&src[expr[0].span.offset()..expr[0].span.offset() + 1], "n");
+		assert_eq!(parts[2], StrPart::Lit(", {ok}".to_string()));
+	}
+
+	#[test]
+	fn lex_rejects_empty_interpolation() {
+		let err = Lexer::new("val s = \"{ }\"\n").lex().unwrap_err();
+		assert!(err.message.contains("invalid string literal"));
+	}
+
+	#[test]
+	fn lex_float_literals_and_ranges() {
+		let src = "val a = 1.5\nval b = 2.0e-3\nval c = 1_000.25\nval d = 3E2\nval r = 0..10\n";
+		let tokens = Lexer::new(src).lex().unwrap();
+		let floats: Vec<f64> = tokens
+			.iter()
+			.filter_map(|t| match &t.kind {
+				TokenKind::Float(v) => Some(*v),
+				_ => None,
+			})
+			.collect();
+		assert_eq!(floats, vec![1.5, 2.0e-3, 1000.25, 300.0]);
+		assert!(tokens.iter().any(|t| t.kind == TokenKind::DotDot));
+	}
+
+	#[test]
+	fn lex_rejects_bad_float_underscore_placement() {
+		let err = Lexer::new("val x = 1_.5\n").lex().unwrap_err();
+		assert!(err.message.contains("invalid float literal"));
+	}
+
+	#[test]
+	fn lex_collects_doc_comments_for_the_next_line() {
+		let src = "## Adds one.\n##   Keeps indent.\ncell inc(x: u32):\n    x + 1\n## detached\n\n# plain\ncell other():\n    1\n";
+		let (tokens, trivia) = Lexer::new(src).lex_with_trivia().unwrap();
+		assert_eq!(trivia.doc_comments.len(), 1);
+		let doc = &trivia.doc_comments[0];
+		assert_eq!(doc.text, "Adds one.\n  Keeps indent.");
+		assert_eq!(tokens[0].kind, TokenKind::KwCell);
+		assert_eq!(doc.target, tokens[0].span.offset());
+	}
 }
@@ -13,6 +13,7 @@ pub enum TokenKind {
     // Keywords
     KwImport,
     KwVal,
+    KwConst,
     KwCell,
     KwExtern,
     KwMacro,
@@ -27,6 +28,12 @@ pub enum TokenKind {
     KwElse,
     KwMatch,
     KwWhile,
+    KwFor,
+    KwIn,
+    KwBreak,
+    KwContinue,
+    KwReturn,
+    KwDefer,
     KwInvariant,
     KwDecreases,
     KwRequires,
@@ -52,6 +59,9 @@ pub enum TokenKind {
     Gt,
     Le,
     Ge,
+    /// `<<`. There is no `>>` token: two adjacent `>` close nested generics like `List<List<u32>>`,
+    /// and the parser reads them as a right shift in expressions.
+    Shl,
 
     Plus,
     Minus,
@@ -60,6 +70,10 @@ pub enum TokenKind {
 
     AndAnd,
     OrOr,
+    /// `|`: or-pattern alternatives and bitwise or.
+    Pipe,
+    Amp,
+    Caret,
     Bang,
     Dot,
     DotDot,
@@ -80,5 +94,32 @@ pub enum TokenKind {
     // Literals / identifiers
     Ident(String),
     Int(u64),
+    Float(f64),
     String(String),
+    /// A string literal containing `{expr}` segments.
+    InterpString(Vec<StrPart>),
+}
+
+/// A segment of an interpolated string literal.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StrPart {
+    Lit(String),
+    /// Tokens of an embedded `{expr}`, terminated by `Eof`.
+    Expr(Vec<Token>),
+}
+
+/// A run of consecutive `##` lines, attached to the token that starts the next code line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DocComment {
+    pub span: Span,
+    /// Comment text with the `##` marker (and one following space) removed; lines joined by `\n`.
+    pub text: String,
+    /// Byte offset of the first token of the documented line.
+    pub target: usize,
+}
+
+/// Source information the token stream drops but tooling still wants.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Trivia {
+    pub doc_comments: Vec<DocComment>,
 }
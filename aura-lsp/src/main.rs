@@ -63,6 +63,8 @@ struct AuraClientCaps {
     protocol_version: Option<u64>,
     phases: Option<Vec<String>>,
     telemetry: AuraClientTelemetryCaps,
+    // Standard LSP `window.workDoneProgress` support (not part of the Aura block).
+    work_done_progress: bool,
 }
 
 fn parse_aura_client_caps(params: &InitializeParams) -> AuraClientCaps {
@@ -105,6 +107,7 @@ fn parse_aura_client_caps(params: &InitializeParams) -> AuraClientCaps {
             protocol_version,
             phases,
             telemetry,
+            work_done_progress: false,
         }
     }
 
@@ -125,8 +128,6 @@ fn parse_aura_client_caps(params: &InitializeParams) -> AuraClientCaps {
     AuraClientCaps::default()
 }
 
-use sha2::{Digest, Sha256};
-
 const PROOF_CACHE_STORE_VERSION: u32 = 1;
 const PROOF_CACHE_FILE_NAME: &str = "proof-cache-v1.json";
 
@@ -227,21 +228,12 @@ fn resolve_import_paths(root: &Path, imports: &[Vec<String>]) -> Vec<PathBuf> {
             continue;
         }
         // Skip stdlib imports; the verifier already augments source with default std.
-        if segs.first().map(|s| s.as_str()) == Some("aura") {
+        if matches!(segs.first().map(|s| s.as_str()), Some("aura") | Some("std")) {
             continue;
         }
 
-        let joined = segs.join(std::path::MAIN_SEPARATOR_STR);
-        let cand1 = root.join(format!("{joined}.aura"));
-        if cand1.exists() {
-            out.push(cand1);
-            continue;
-        }
-        if let Some(last) = segs.last() {
-            let cand2 = root.join(format!("{last}.aura"));
-            if cand2.exists() {
-                out.push(cand2);
-            }
+        if let Some(path) = aura_sdk::modules::find_module_file(root, segs) {
+            out.push(path);
         }
     }
     out.sort();
@@ -266,248 +258,8 @@ fn dep_hash_from_imports(root: Option<&Path>, text: &str) -> String {
     sha256_hex(&input)
 }
 
-#[derive(Clone, Debug)]
-struct StmtHashInfo {
-    hash: String,
-    is_ui: bool,
-}
-
-fn compute_checkable_stmt_merkle_hashes(
-    program: &aura_ast::Program,
-    text: &str,
-    dep_hash: &str,
-) -> Vec<Option<StmtHashInfo>> {
-    use aura_ast::{Expr, ExprKind, Stmt};
-
-    fn walk_expr_call_names(out: &mut std::collections::BTreeSet<String>, expr: &Expr) {
-        match &expr.kind {
-            ExprKind::Call { callee, args, trailing } => {
-                if let Some(name) = expr_callee_name(callee) {
-                    out.insert(name);
-                }
-                walk_expr_call_names(out, callee);
-                for a in args {
-                    match a {
-                        aura_ast::CallArg::Positional(e) => walk_expr_call_names(out, e),
-                        aura_ast::CallArg::Named { value, .. } => walk_expr_call_names(out, value),
-                    }
-                }
-                if let Some(b) = trailing {
-                    walk_block_call_names(out, b);
-                }
-            }
-            ExprKind::Unary { expr: inner, .. } => walk_expr_call_names(out, inner),
-            ExprKind::Binary { left, right, .. } => {
-                walk_expr_call_names(out, left);
-                walk_expr_call_names(out, right);
-            }
-            ExprKind::Member { base, .. } => walk_expr_call_names(out, base),
-            ExprKind::Lambda { body, .. } => walk_block_call_names(out, body),
-            ExprKind::Flow { left, right, .. } => {
-                walk_expr_call_names(out, left);
-                walk_expr_call_names(out, right);
-            }
-            ExprKind::StyleLit { fields } => {
-                for (_, v) in fields {
-                    walk_expr_call_names(out, v);
-                }
-            }
-            ExprKind::RecordLit { fields, .. } => {
-                for (_, v) in fields {
-                    walk_expr_call_names(out, v);
-                }
-            }
-            ExprKind::ForAll { binders: _, body } | ExprKind::Exists { binders: _, body } => {
-                walk_expr_call_names(out, body);
-            }
-            ExprKind::Ident(_) | ExprKind::IntLit(_) | ExprKind::StringLit(_) => {}
-        }
-    }
-
-    fn walk_stmt_call_names(out: &mut std::collections::BTreeSet<String>, stmt: &Stmt) {
-        match stmt {
-            Stmt::StrandDef(sd) => walk_expr_call_names(out, &sd.expr),
-            Stmt::Assign(a) => walk_expr_call_names(out, &a.expr),
-            Stmt::Prop(p) => walk_expr_call_names(out, &p.expr),
-            Stmt::ExprStmt(e) => walk_expr_call_names(out, e),
-            Stmt::Requires(r) => walk_expr_call_names(out, &r.expr),
-            Stmt::Ensures(e) => walk_expr_call_names(out, &e.expr),
-            Stmt::Assert(a) => walk_expr_call_names(out, &a.expr),
-            Stmt::Assume(a) => walk_expr_call_names(out, &a.expr),
-            Stmt::If(i) => {
-                walk_expr_call_names(out, &i.cond);
-                walk_block_call_names(out, &i.then_block);
-                if let Some(b) = &i.else_block {
-                    walk_block_call_names(out, b);
-                }
-            }
-            Stmt::Match(m) => {
-                walk_expr_call_names(out, &m.scrutinee);
-                for arm in &m.arms {
-                    walk_block_call_names(out, &arm.body);
-                }
-            }
-            Stmt::While(w) => {
-                walk_expr_call_names(out, &w.cond);
-                if let Some(inv) = &w.invariant {
-                    walk_expr_call_names(out, inv);
-                }
-                if let Some(dec) = &w.decreases {
-                    walk_expr_call_names(out, dec);
-                }
-                walk_block_call_names(out, &w.body);
-            }
-            Stmt::CellDef(c) => walk_block_call_names(out, &c.body),
-            Stmt::FlowBlock(f) => walk_block_call_names(out, &f.body),
-            Stmt::Layout(l) => walk_block_call_names(out, &l.body),
-            Stmt::Render(r) => walk_block_call_names(out, &r.body),
-            Stmt::MacroCall(m) => {
-                for a in &m.args {
-                    walk_expr_call_names(out, a);
-                }
-            }
-            Stmt::MacroDef(m) => walk_block_call_names(out, &m.body),
-            Stmt::UnsafeBlock(ub) => walk_block_call_names(out, &ub.body),
-            Stmt::Import(_) | Stmt::ExternCell(_) | Stmt::TypeAlias(_) | Stmt::TraitDef(_) | Stmt::RecordDef(_) | Stmt::EnumDef(_) => {}
-        }
-    }
-
-    fn walk_block_call_names(out: &mut std::collections::BTreeSet<String>, block: &aura_ast::Block) {
-        for s in &block.stmts {
-            walk_stmt_call_names(out, s);
-        }
-        if let Some(y) = &block.yield_expr {
-            walk_expr_call_names(out, y);
-        }
-    }
-
-    let n = program.stmts.len();
-    let mut out: Vec<Option<StmtHashInfo>> = vec![None; n];
-
-    // Map top-level callable defs (cells/flows) to statement indices.
-    let mut top_level_callables: HashMap<String, usize> = HashMap::new();
-    for (i, stmt) in program.stmts.iter().enumerate() {
-        match stmt {
-            Stmt::CellDef(c) => {
-                top_level_callables.insert(c.name.node.clone(), i);
-            }
-            Stmt::FlowBlock(f) => {
-                top_level_callables.insert(f.name.node.clone(), i);
-            }
-            _ => {}
-        }
-    }
-
-    // Content hashes for checkable top-level units.
-    let mut content_hash_by_idx: HashMap<usize, String> = HashMap::new();
-    let mut kind_by_idx: HashMap<usize, &'static str> = HashMap::new();
-    let mut ui_by_idx: HashMap<usize, bool> = HashMap::new();
-
-    for (i, stmt) in program.stmts.iter().enumerate() {
-        let (is_checkable, is_ui) = match stmt {
-            Stmt::CellDef(_) => (true, true),
-            Stmt::FlowBlock(_) => (true, false),
-            Stmt::Layout(_) => (true, true),
-            Stmt::Render(_) => (true, true),
-            _ => (false, false),
-        };
-        if !is_checkable {
-            continue;
-        }
-
-        let (kind, sp) = match stmt {
-            Stmt::CellDef(s) => ("cell", s.span),
-            Stmt::FlowBlock(s) => ("flow", s.span),
-            Stmt::Layout(s) => ("layout", s.span),
-            Stmt::Render(s) => ("render", s.span),
-            _ => continue,
-        };
-        let start = sp.offset();
-        let end = start.saturating_add(sp.len());
-        let slice = text.get(start..end).unwrap_or("");
-        let content_hash = sha256_hex(&format!("stmt-content-v1\nkind={kind}\n{slice}"));
-
-        content_hash_by_idx.insert(i, content_hash);
-        kind_by_idx.insert(i, kind);
-        ui_by_idx.insert(i, is_ui);
-    }
-
-    // Direct deps via call edges.
-    let mut deps: Vec<Vec<usize>> = vec![Vec::new(); n];
-    for (i, stmt) in program.stmts.iter().enumerate() {
-        if !content_hash_by_idx.contains_key(&i) {
-            continue;
-        }
-        let mut names: std::collections::BTreeSet<String> = std::collections::BTreeSet::new();
-        walk_stmt_call_names(&mut names, stmt);
-
-        let mut edges: Vec<usize> = Vec::new();
-        for name in names {
-            if let Some(&j) = top_level_callables.get(&name) {
-                if j != i {
-                    edges.push(j);
-                }
-            }
-        }
-        edges.sort();
-        edges.dedup();
-        deps[i] = edges;
-    }
-
-    // Transitive closure of deps (by index), restricted to checkable nodes.
-    for i in 0..n {
-        if !content_hash_by_idx.contains_key(&i) {
-            continue;
-        }
-        let mut stack: Vec<usize> = deps[i].clone();
-        let mut seen: std::collections::BTreeSet<usize> = std::collections::BTreeSet::new();
-        while let Some(j) = stack.pop() {
-            if j == i {
-                continue;
-            }
-            if !content_hash_by_idx.contains_key(&j) {
-                continue;
-            }
-            if !seen.insert(j) {
-                continue;
-            }
-            for &k in &deps[j] {
-                stack.push(k);
-            }
-        }
-
-        let kind = kind_by_idx.get(&i).copied().unwrap_or("stmt");
-        let content_hash = content_hash_by_idx.get(&i).cloned().unwrap_or_else(|| sha256_hex(""));
-
-        let mut input = String::new();
-        input.push_str("stmt-merkle-v1\n");
-        input.push_str(&format!("kind={kind}\n"));
-        input.push_str(&format!("content={content_hash}\n"));
-        input.push_str(&format!("imports={dep_hash}\n"));
-        for j in seen {
-            // Encode deps deterministically by name if we have one, else by index.
-            let dep_name = top_level_callables
-                .iter()
-                .find_map(|(name, idx)| if *idx == j { Some(name.clone()) } else { None })
-                .unwrap_or_else(|| format!("#{j}"));
-            let dep_content = content_hash_by_idx
-                .get(&j)
-                .cloned()
-                .unwrap_or_else(|| sha256_hex(""));
-            input.push_str(&format!("dep={dep_name}:{dep_content}\n"));
-        }
-
-        let hash = sha256_hex(&input);
-        out[i] = Some(StmtHashInfo {
-            hash,
-            is_ui: ui_by_idx.get(&i).copied().unwrap_or(false),
-        });
-    }
-
-    out
-}
-
 use aura_nexus::{NexusFileContext, PluginManifest};
+use aura_sdk::merkle::{expr_callee_name, sha256_hex, stmt_merkle_hashes};
 
 // Stable, documented diagnostic codes for Aura (versioned).
 // See docs/diagnostic-codes.md.
@@ -700,12 +452,6 @@ fn collect_identifiers(text: &str) -> Vec<String> {
     out
 }
 
-fn sha256_hex(s: &str) -> String {
-    let mut hasher = Sha256::new();
-    hasher.update(s.as_bytes());
-    hex::encode(hasher.finalize())
-}
-
 #[derive(Debug, Clone, serde::Deserialize)]
 struct ProofsParams {
     uri: Url,
@@ -738,6 +484,37 @@ struct ProofsStreamCancelParams {
     id: u64,
 }
 
+#[derive(Debug, Clone, serde::Deserialize)]
+struct TestsParams {
+    uri: Url,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+struct TestItem {
+    name: String,
+    range: Range,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+struct TestsResponse {
+    uri: Url,
+    tests: Vec<TestItem>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+struct TestResult {
+    name: String,
+    range: Range,
+    passed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+    stdout: String,
+}
+
+/// `workspace/executeCommand` id that runs `test cell`s in the AVM.
+/// Arguments: `[uri]` to run every test in the file, or `[uri, name]` for a single test.
+const RUN_TESTS_COMMAND: &str = "aura.runTests";
+
 #[derive(Debug, Clone, serde::Deserialize, Default)]
 struct ProofCacheClearParams {
     // Reserved for future: file-scoped clearing.
@@ -763,6 +540,25 @@ struct ProofsStreamEvent {
     telemetry: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    partial: Option<ProofsStreamPartial>,
+}
+
+/// Progress payload for `state = "partial"` events: one per verified top-level unit.
+/// Clients can replace diagnostics for `stmtHash` as they arrive; `done` stays authoritative.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct ProofsStreamPartial {
+    #[serde(rename = "stmtHash")]
+    stmt_hash: String,
+    done: u64,
+    total: u64,
+    cached: bool,
+}
+
+type PartialProofsTx = mpsc::UnboundedSender<(ProofsStreamPartial, Vec<Diagnostic>)>;
+
+fn proofs_progress_token(id: u64) -> NumberOrString {
+    NumberOrString::String(format!("aura/proofs/{id}"))
 }
 
 enum AuraProofsStreamNotification {}
@@ -787,8 +583,94 @@ fn diagnostic_from_span(text: &str, span: SourceSpan, code: &str, message: Strin
     }
 }
 
+/// Code of the capability-inference reports; their `data` carries the narrowed signature and
+/// the edits that apply it.
+const CAPABILITY_CODE: &str = "aura::capability";
+
+fn diagnostic_from_capability_suggestion(
+    text: &str,
+    source_len: usize,
+    s: aura_core::CapabilitySuggestion,
+) -> Option<Diagnostic> {
+    // Only the open document's cells are reported, and only edits inside it are offered.
+    let in_source = |span: SourceSpan| span.offset() + span.len() <= source_len;
+    if !in_source(s.span) {
+        return None;
+    }
+    let unused = s.needed == aura_core::ParamAccess::Unused;
+    let edits: Vec<TextEdit> = if s.edits.iter().all(|e| in_source(e.span)) {
+        s.edits
+            .iter()
+            .map(|e| TextEdit {
+                range: range_from_source_span(text, e.span),
+                new_text: e.replacement.clone(),
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let mut diag = diagnostic_from_span(text, s.span, CAPABILITY_CODE, s.message);
+    diag.severity = Some(if unused {
+        DiagnosticSeverity::WARNING
+    } else {
+        DiagnosticSeverity::INFORMATION
+    });
+    diag.tags = unused.then(|| vec![DiagnosticTag::UNNECESSARY]);
+    diag.data = Some(json!({ "signature": s.signature, "edits": edits }));
+    Some(diag)
+}
+
+/// Code of the lint warnings: unused bindings and imports, and unreachable code.
+const LINT_CODE: &str = "aura::lint";
+
+fn diagnostic_from_lint(text: &str, lint: aura_core::Lint) -> Diagnostic {
+    let mut diag = diagnostic_from_span(text, lint.span, LINT_CODE, lint.diagnostic.message);
+    diag.severity = Some(match lint.diagnostic.severity {
+        aura_core::Severity::Error => DiagnosticSeverity::ERROR,
+        aura_core::Severity::Warning => DiagnosticSeverity::WARNING,
+        aura_core::Severity::Info => DiagnosticSeverity::INFORMATION,
+    });
+    // Everything the lints report can be deleted; editors fade it out.
+    diag.tags = Some(vec![DiagnosticTag::UNNECESSARY]);
+    diag
+}
+
+/// Code of the data races on strands shared by `~>` flows; their `data` carries the channel-based
+/// fix.
+const FLOW_RACE_CODE: &str = "aura::race";
+
+fn diagnostic_from_flow_race(
+    uri: &Url,
+    text: &str,
+    source_len: usize,
+    race: aura_core::FlowRace,
+) -> Option<Diagnostic> {
+    let in_source = |span: SourceSpan| span.offset() + span.len() <= source_len;
+    if !in_source(race.second.span) {
+        return None;
+    }
+    let mut diag = diagnostic_from_span(text, race.second.span, FLOW_RACE_CODE, race.message());
+    diag.severity = Some(DiagnosticSeverity::WARNING);
+    let related = race
+        .related()
+        .into_iter()
+        .filter(|(span, _)| in_source(*span))
+        .map(|(span, message)| DiagnosticRelatedInformation {
+            location: Location {
+                uri: uri.clone(),
+                range: range_from_source_span(text, span),
+            },
+            message,
+        })
+        .collect();
+    diag.related_information = Some(related);
+    diag.data = Some(json!({ "strand": race.strand, "suggestion": race.suggestion() }));
+    Some(diag)
+}
+
 fn compute_diagnostics(uri: &Url, text: &str, prover: &mut aura_verify::Z3Prover) -> Vec<Diagnostic> {
     let mut diags: Vec<Diagnostic> = Vec::new();
+    let source_len = text.len();
 
     // Heuristic: quantifiers require `--smt-profile thorough`.
     // Keep Fast as the default for responsiveness.
@@ -805,7 +687,13 @@ fn compute_diagnostics(uri: &Url, text: &str, prover: &mut aura_verify::Z3Prover
 
     let source_path = uri.to_file_path().ok();
 
-    let program = match aura_parse::parse_source(&text) {
+    // Files on disk resolve their `import`ed modules; unsaved buffers parse standalone.
+    let parsed = match &source_path {
+        Some(path) => aura_sdk::modules::resolve_modules(path, &text, &aura_parse::ParseConfig::default())
+            .map(|g| (g.program.clone(), Some(g))),
+        None => aura_parse::parse_source(&text).map(|p| (p, None)),
+    };
+    let (program, graph) = match parsed {
         Ok(p) => p,
         Err(e) => {
             diags.push(diagnostic_from_miette(uri, &text, e));
@@ -815,11 +703,44 @@ fn compute_diagnostics(uri: &Url, text: &str, prover: &mut aura_verify::Z3Prover
 
     // Semantic checks (best effort). If sema fails, surface as a diagnostic.
     let mut checker = aura_core::Checker::new();
-    if let Err(e) = checker.check_program(&program) {
-        diags.push(diagnostic_from_miette(uri, &text, e.into()));
+    if let Some(graph) = &graph {
+        checker.set_symbols(graph.symbols.clone());
+    }
+    if let Err(mut e) = checker.check_program(&program) {
+        let mut moved = checker.move_violation(&e).cloned();
+        // Errors inside an imported module are reported on the import that pulled it in.
+        if let Some(graph) = &graph {
+            if let Some((file, _)) = graph
+                .locate(e.span.offset())
+                .filter(|(f, _)| f.imported_at.is_some())
+            {
+                e.message = match graph.symbols.at(e.span.offset()) {
+                    Some(sym) => format!("{}: in {}: {}", file.path.display(), sym.qualified, e.message),
+                    None => format!("{}: {}", file.path.display(), e.message),
+                };
+                moved = None;
+            }
+            e.span = graph.entry_span(e.span);
+        }
+        let mut diag = diagnostic_from_miette(uri, &text, e.into());
+        if let Some(v) = moved {
+            attach_move_explanation(&mut diag, uri, &text, &v);
+        }
+        diags.push(diag);
         return diags;
     }
 
+    for s in aura_core::CapabilityValidator::infer_capabilities(&program) {
+        diags.extend(diagnostic_from_capability_suggestion(&text, source_len, s));
+    }
+    let lints = aura_core::lint_program(&program, uri.as_str(), &text[..source_len]);
+    for lint in lints.into_iter().chain(checker.match_lints(uri.as_str(), &text[..source_len])) {
+        diags.push(diagnostic_from_lint(&text, lint));
+    }
+    for race in aura_core::detect_flow_races(&program, &text) {
+        diags.extend(diagnostic_from_flow_race(uri, &text, source_len, race));
+    }
+
     // Z3 verification diagnostics.
     {
         let manifest = find_aura_toml_for_uri(uri);
@@ -833,6 +754,7 @@ fn compute_diagnostics(uri: &Url, text: &str, prover: &mut aura_verify::Z3Prover
             source_path,
             manifest_path: manifest.clone(),
         });
+        nexus.insert(model_contract_cache());
         match verify_with_manifest_plugins(&program, prover, &mut nexus, &manifest_plugins, smt_profile) {
             Ok(report) => {
                 for p in report.proofs {
@@ -940,6 +862,80 @@ fn requested_plugins_from_manifest(
     Ok(requested)
 }
 
+fn collect_test_cells(text: &str) -> Vec<TestItem> {
+    let Ok(program) = aura_parse::parse_source(text) else { return vec![] };
+    program
+        .stmts
+        .iter()
+        .filter_map(|s| match s {
+            aura_ast::Stmt::CellDef(c) if c.is_test => Some(TestItem {
+                name: c.name.node.clone(),
+                range: range_from_source_span(text, c.span),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Fuel for one test cell, so a test that never terminates fails instead of hanging the server.
+const TEST_CELL_FUEL: u64 = 50_000_000;
+/// Memory cap for one test cell.
+const TEST_CELL_MAX_MEMORY_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Run each test cell in a fresh AVM. The Z3 gate is disabled here because the editor
+/// already reports verification results through diagnostics. Also returns the coverage of
+/// the document (without the bridge shim or injected std modules) over all the tests.
+fn run_test_cells(
+    text: &str,
+    tests: Vec<TestItem>,
+    bridge: &NativeBridge,
+) -> (Vec<TestResult>, aura_interpret::Coverage) {
+    let doc_len = text.len();
+    let (text, doc_start) = if bridge.shim.is_empty() {
+        (text.to_string(), 0)
+    } else {
+        (format!("{}\n\n{text}", bridge.shim), bridge.shim.len() + 2)
+    };
+    let source = aura_sdk::augment_source_with_default_std(&text).unwrap_or_else(|_| text.clone());
+    let mut coverage = aura_interpret::Coverage::default();
+    let results = tests
+        .into_iter()
+        .map(|t| {
+            // stdin carries the LSP transport, so the AVM must not read from it.
+            let mut avm = aura_interpret::Avm::new(aura_interpret::AvmConfig {
+                enable_z3_gate: false,
+                read_stdin: false,
+                fuel: Some(TEST_CELL_FUEL),
+                max_memory_bytes: Some(TEST_CELL_MAX_MEMORY_BYTES),
+                native_libs: bridge.libs.clone(),
+                coverage: true,
+                ..aura_interpret::AvmConfig::default()
+            });
+            let out = avm.exec_entry_cell(&source, &t.name);
+            if let Some(cov) = avm.coverage() {
+                coverage.merge(cov);
+            }
+            match out {
+                Ok(out) => TestResult {
+                    name: t.name,
+                    range: t.range,
+                    passed: true,
+                    message: None,
+                    stdout: out.stdout,
+                },
+                Err(e) => TestResult {
+                    name: t.name,
+                    range: t.range,
+                    passed: false,
+                    message: Some(e.to_string()),
+                    stdout: avm.stdout().to_string(),
+                },
+            }
+        })
+        .collect();
+    (results, coverage.restrict(doc_start..doc_start + doc_len))
+}
+
 fn diagnostic_from_proof_note(
     uri: &Url,
     text: &str,
@@ -1061,6 +1057,12 @@ fn diagnostic_from_proof_note(
     }
 }
 
+/// Model contracts, shared by every verification pass so edits do not re-read models.
+fn model_contract_cache() -> aura_plugin_ai::ModelContractCache {
+    static CACHE: std::sync::OnceLock<aura_plugin_ai::ModelContractCache> = std::sync::OnceLock::new();
+    CACHE.get_or_init(Default::default).clone()
+}
+
 fn find_aura_toml_for_uri(uri: &Url) -> Option<PathBuf> {
     let p = uri.to_file_path().ok()?;
     find_manifest(&p)
@@ -1086,6 +1088,75 @@ fn find_manifest(start: &Path) -> Option<PathBuf> {
 struct AuraToml {
     #[serde(default)]
     plugins: Vec<PluginManifest>,
+    #[serde(default)]
+    bridge: Option<BridgeToml>,
+    // `[linker] paths = [...]` and `[linking] lib_dirs = [...]` name the same thing.
+    #[serde(default)]
+    linker: Option<LinkToml>,
+    #[serde(default)]
+    linking: Option<LinkToml>,
+}
+
+#[derive(Default, serde::Deserialize)]
+struct BridgeToml {
+    #[serde(default)]
+    headers: Vec<String>,
+}
+
+#[derive(Default, serde::Deserialize)]
+struct LinkToml {
+    #[serde(default, alias = "paths")]
+    lib_dirs: Vec<String>,
+    #[serde(default)]
+    libs: Vec<String>,
+}
+
+/// Native code the manifest bridges in: the generated `extern cell` shim, prepended to test
+/// runs like the compiler prepends it to builds, and the shared libraries the AVM calls the
+/// externs in (with the `ffi` feature).
+#[derive(Default)]
+struct NativeBridge {
+    shim: String,
+    libs: Vec<PathBuf>,
+}
+
+fn load_native_bridge(manifest_path: &Path) -> NativeBridge {
+    let Some(parsed) = fs::read_to_string(manifest_path)
+        .ok()
+        .and_then(|raw| toml::from_str::<AuraToml>(&raw).ok())
+    else {
+        return NativeBridge::default();
+    };
+    let dir = manifest_path.parent().unwrap_or(Path::new("."));
+
+    let mut link = aura_bridge::LinkInputs::default();
+    for l in parsed.linker.into_iter().chain(parsed.linking) {
+        link.lib_dirs.extend(l.lib_dirs.iter().map(|p| dir.join(p)));
+        link.libs.extend(l.libs);
+    }
+    let headers: Vec<PathBuf> = parsed
+        .bridge
+        .map(|b| b.headers.iter().map(|h| dir.join(h)).collect())
+        .unwrap_or_default();
+
+    let mut shim = String::new();
+    if !headers.is_empty() {
+        let config = aura_bridge::BridgeConfig {
+            headers,
+            include_dirs: vec![],
+            lib_dirs: link.lib_dirs.clone(),
+            libs: link.libs.clone(),
+            refine_types: false,
+        };
+        if let Ok(out) = aura_bridge::run_bridge(&config, &dir.join("build").join("lsp").join("bridge")) {
+            shim = fs::read_to_string(&out.aura_shim_path).unwrap_or_default();
+            link = out.link;
+        }
+    }
+    NativeBridge {
+        shim,
+        libs: link.runtime_libraries(),
+    }
 }
 
 fn load_manifest_plugins(path: &Path) -> Option<Vec<PluginManifest>> {
@@ -1191,6 +1262,151 @@ fn find_plugin_entry(
     })
 }
 
+/// Extract the plugin name from a "Nexus plugin 'x' is not trusted" verifier message.
+fn untrusted_plugin_from_message(message: &str) -> Option<&str> {
+    let rest = message.strip_prefix("Nexus plugin '")?;
+    let end = rest.find("' is not trusted")?;
+    Some(&rest[..end])
+}
+
+/// The binding named by a "'x' may be None here" diagnostic.
+fn maybe_none_binding_from_message(message: &str) -> Option<&str> {
+    let rest = message.strip_prefix('\'')?;
+    let end = rest.find("' may be None here")?;
+    Some(&rest[..end])
+}
+
+/// The `uses` list rewrite for the undeclared-effect diagnostic at `range`, and the new list.
+fn declare_effects_edit(text: &str, range: Range) -> Option<(String, TextEdit)> {
+    let (program, _) = aura_parse::parse_source_with_recovery(text).ok()?;
+    aura_core::check_declared_effects(&program).into_iter().find_map(|v| {
+        let edit = v.edit.filter(|_| range_from_source_span(text, v.span) == range)?;
+        Some((
+            edit.replacement.clone(),
+            TextEdit {
+                range: range_from_source_span(text, edit.span),
+                new_text: edit.replacement,
+            },
+        ))
+    })
+}
+
+/// Build an edit that puts the statement on `line` under `if is_some(name):`.
+fn guard_with_is_some_edit(text: &str, line: u32, name: &str) -> Option<TextEdit> {
+    let src = text.lines().nth(line as usize)?;
+    let indent = &src[..src.len() - src.trim_start().len()];
+    let start = Position { line, character: 0 };
+    Some(TextEdit {
+        range: Range { start, end: start },
+        new_text: format!("{indent}if is_some({name}):\n    "),
+    })
+}
+
+/// Byte range of the `key = value` pair in an inline table's text, from its `{` up to the `}`.
+/// Only the exact key matches, not `untrusted` or a quoted value containing it; commas inside
+/// strings don't split pairs.
+fn inline_table_pair(table: &str, key: &str) -> Option<(usize, usize)> {
+    let mut start = 1;
+    let mut in_string = false;
+    for (i, c) in table.char_indices().skip(1).chain(std::iter::once((table.len(), ','))) {
+        match c {
+            '"' => in_string = !in_string,
+            ',' if !in_string => {
+                let pair = &table[start..i];
+                let lead = pair.len() - pair.trim_start().len();
+                if let Some(rest) = pair.trim_start().strip_prefix(key)
+                    && rest.trim_start().starts_with('=')
+                {
+                    return Some((start + lead, start + pair.trim_end().len()));
+                }
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Build an edit that marks the aura.toml entry for `plugin_name` as `trusted = true`.
+///
+/// Best-effort: reuses `find_plugin_entry` to locate the `name = "..."` assignment, then
+/// either rewrites an existing `trusted = ...` key in the same entry or inserts one.
+fn trust_plugin_edit(
+    manifest_path: &Path,
+    plugin_name: &str,
+    manifest_plugins: &[PluginManifest],
+) -> Option<TextEdit> {
+    let entry = find_plugin_entry(manifest_path, plugin_name, manifest_plugins)?;
+    let raw = fs::read_to_string(manifest_path).ok()?;
+    let lines: Vec<&str> = raw.lines().collect();
+    let name_line = entry.location.range.start.line as usize;
+    let line_text = *lines.get(name_line)?;
+
+    let line_range = |line: usize, start: usize, end: usize| Range {
+        start: Position {
+            line: line as u32,
+            character: lines[line][..start].chars().count() as u32,
+        },
+        end: Position {
+            line: line as u32,
+            character: lines[line][..end].chars().count() as u32,
+        },
+    };
+
+    // Inline table form: `{ name = "x", trusted = false }` on a single line.
+    let name_col = line_text
+        .char_indices()
+        .nth(entry.location.range.start.character as usize)
+        .map(|(b, _)| b)
+        .unwrap_or(line_text.len());
+    let open = line_text[..name_col].rfind('{');
+    if let Some(open) = open {
+        let close = open + line_text[open..].find('}')?;
+        let table = &line_text[open..close];
+        if let Some((start, end)) = inline_table_pair(table, "trusted") {
+            return Some(TextEdit {
+                range: line_range(name_line, open + start, open + end),
+                new_text: "trusted = true".to_string(),
+            });
+        }
+        let insert_at = line_text[..close].trim_end().len();
+        return Some(TextEdit {
+            range: line_range(name_line, insert_at, insert_at),
+            new_text: ", trusted = true".to_string(),
+        });
+    }
+
+    // `[[plugins]]` table-array form: scan the entry's block for an existing key.
+    let block_start = (0..=name_line)
+        .rev()
+        .find(|&i| lines[i].trim_start().starts_with('['))
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    let block_end = (name_line + 1..lines.len())
+        .find(|&i| lines[i].trim_start().starts_with('['))
+        .unwrap_or(lines.len());
+    for i in block_start..block_end {
+        let trimmed = lines[i].trim_start();
+        let Some(after_key) = trimmed.strip_prefix("trusted") else { continue };
+        if !after_key.trim_start().starts_with('=') {
+            continue;
+        }
+        let indent = lines[i].len() - trimmed.len();
+        let end = lines[i].find('#').unwrap_or(lines[i].len());
+        let end = lines[i][..end].trim_end().len();
+        return Some(TextEdit {
+            range: line_range(i, indent, end),
+            new_text: "trusted = true".to_string(),
+        });
+    }
+
+    let indent = line_text.len() - line_text.trim_start().len();
+    Some(TextEdit {
+        range: line_range(name_line, line_text.len(), line_text.len()),
+        new_text: format!("\n{}trusted = true", &line_text[..indent]),
+    })
+}
+
 fn range_from_source_span(text: &str, span: SourceSpan) -> Range {
     let start = span.offset();
     let len = span.len();
@@ -1534,6 +1750,41 @@ fn diagnostic_from_verify_error(uri: &Url, text: &str, err: VerifyError) -> Diag
     }
 }
 
+/// Adds the step-by-step explanation of a use-after-move to its diagnostic: the steps and
+/// fixes under `data.explanation`, and the move site as related information.
+fn attach_move_explanation(diag: &mut Diagnostic, uri: &Url, text: &str, v: &aura_core::MoveViolation) {
+    let exp = aura_core::ExplanationEngine::new().explain_ownership(v, text);
+    if let Some(span) = v.moved_at {
+        diag.related_information.get_or_insert_with(Vec::new).push(DiagnosticRelatedInformation {
+            location: Location {
+                uri: uri.clone(),
+                range: range_from_source_span(text, span),
+            },
+            message: format!("'{}' moved here", v.name),
+        });
+    }
+    if let Some(serde_json::Value::Object(data)) = diag.data.as_mut() {
+        data.insert(
+            "explanation".to_string(),
+            json!({
+                "kind": format!("{:?}", v.kind),
+                "name": v.name,
+                "type": v.ty,
+                "message": exp.message,
+                "steps": exp.proof_steps.iter().map(|s| json!({
+                    "line": s.location.0,
+                    "column": s.location.1,
+                    "source": s.source_line,
+                    "derives": s.derives,
+                    "reason": s.reason,
+                })).collect::<Vec<_>>(),
+                "suggestions": exp.suggestions,
+                "markdown": exp.full_explanation(),
+            }),
+        );
+    }
+}
+
 fn diagnostic_from_miette(uri: &Url, text: &str, report: miette::Report) -> Diagnostic {
     if let Some(e) = report.downcast_ref::<aura_parse::ParseError>() {
         return diagnostic_from_span(
@@ -1725,24 +1976,39 @@ struct RefInfo {
     range: Range,
 }
 
-fn expr_callee_name(expr: &aura_ast::Expr) -> Option<String> {
-    use aura_ast::ExprKind;
-    match &expr.kind {
-        ExprKind::Ident(id) => Some(id.node.clone()),
-        ExprKind::Member { base, member } => {
-            if let ExprKind::Ident(b) = &base.kind {
-                Some(format!("{}.{}", b.node, member.node))
-            } else {
-                None
+/// Hover markdown for a documented top-level definition (`##` doc comments).
+fn def_doc_markdown(text: &str, name: &str, kind: &str) -> Option<String> {
+    let (program, _) = aura_parse::parse_source_with_recovery(text).ok()?;
+    program.stmts.iter().find_map(|stmt| {
+        let (header, doc) = match stmt {
+            // Cells always hover with their inferred effects, documented or not.
+            aura_ast::Stmt::CellDef(c) if kind == "cell" && c.name.node == name => {
+                let effects = aura_core::infer_effects(&program).remove(name).unwrap_or_default();
+                let header = format!("cell {name} {}", effects.signature());
+                let Some(doc) = &c.doc else {
+                    return Some(format!("```aura\n{header}\n```"));
+                };
+                (header, doc)
             }
-        }
-        _ => None,
-    }
+            aura_ast::Stmt::ExternCell(c) if kind == "extern_cell" && c.name.node == name => {
+                (format!("extern cell {name}"), c.doc.as_ref()?)
+            }
+            aura_ast::Stmt::RecordDef(r) if kind == "record" && r.name.node == name => {
+                (format!("type {name} = record"), r.doc.as_ref()?)
+            }
+            aura_ast::Stmt::EnumDef(e) if kind == "enum" && e.name.node == name => {
+                (format!("type {name} = enum"), e.doc.as_ref()?)
+            }
+            _ => return None,
+        };
+        Some(format!("```aura\n{header}\n```\n\n{doc}"))
+    })
 }
 
 fn collect_file_symbols(uri: &Url, text: &str) -> (Vec<DefInfo>, Vec<RefInfo>) {
-    let program = match aura_parse::parse_source(text) {
-        Ok(p) => p,
+    // Best-effort parse: a half-typed statement must not hide the rest of the file's symbols.
+    let program = match aura_parse::parse_source_with_recovery(text) {
+        Ok((p, _)) => p,
         Err(_) => return (vec![], vec![]),
     };
 
@@ -1893,7 +2159,9 @@ fn collect_file_symbols(uri: &Url, text: &str) -> (Vec<DefInfo>, Vec<RefInfo>) {
                     });
                 }
             }
-            ExprKind::Unary { expr: inner, .. } => walk_expr(refs, scopes, globals, uri, text, inner),
+            ExprKind::Unary { expr: inner, .. } | ExprKind::Borrow { expr: inner, .. } => {
+                walk_expr(refs, scopes, globals, uri, text, inner)
+            }
             ExprKind::Binary { left, right, .. } => {
                 walk_expr(refs, scopes, globals, uri, text, left);
                 walk_expr(refs, scopes, globals, uri, text, right);
@@ -1901,6 +2169,29 @@ fn collect_file_symbols(uri: &Url, text: &str) -> (Vec<DefInfo>, Vec<RefInfo>) {
             ExprKind::Member { base, .. } => {
                 walk_expr(refs, scopes, globals, uri, text, base);
             }
+            ExprKind::Tuple(elems) => {
+                for e in elems {
+                    walk_expr(refs, scopes, globals, uri, text, e);
+                }
+            }
+            ExprKind::TupleIndex { base, .. } => {
+                walk_expr(refs, scopes, globals, uri, text, base);
+            }
+            ExprKind::ListLit(elems) => {
+                for e in elems {
+                    walk_expr(refs, scopes, globals, uri, text, e);
+                }
+            }
+            ExprKind::MapLit(entries) => {
+                for (k, v) in entries {
+                    walk_expr(refs, scopes, globals, uri, text, k);
+                    walk_expr(refs, scopes, globals, uri, text, v);
+                }
+            }
+            ExprKind::Index { base, index } => {
+                walk_expr(refs, scopes, globals, uri, text, base);
+                walk_expr(refs, scopes, globals, uri, text, index);
+            }
             ExprKind::Call { callee, args, trailing } => {
                 walk_expr(refs, scopes, globals, uri, text, callee);
                 for a in args {
@@ -1953,7 +2244,14 @@ fn collect_file_symbols(uri: &Url, text: &str) -> (Vec<DefInfo>, Vec<RefInfo>) {
                 scopes2.push(qscope);
                 walk_expr(refs, &scopes2, globals, uri, text, body);
             }
-            ExprKind::IntLit(_) | ExprKind::StringLit(_) => {}
+            ExprKind::InterpolatedString(parts) => {
+                for part in parts {
+                    if let aura_ast::InterpPart::Expr(e) = part {
+                        walk_expr(refs, scopes, globals, uri, text, e);
+                    }
+                }
+            }
+            ExprKind::IntLit(_) | ExprKind::FloatLit(_) | ExprKind::StringLit(_) => {}
         }
     }
 
@@ -2012,8 +2310,15 @@ fn collect_file_symbols(uri: &Url, text: &str) -> (Vec<DefInfo>, Vec<RefInfo>) {
             aura_ast::Stmt::StrandDef(sd) => {
                 // RHS references resolve against current scope.
                 walk_expr(refs, scopes, globals, uri, text, &sd.expr);
-                // Then define name for subsequent statements.
-                add_def(defs, scopes, uri, text, &sd.name, "val");
+                // Then define name(s) for subsequent statements.
+                match &sd.pattern {
+                    Some(pat) => {
+                        for name in pat.binders() {
+                            add_def(defs, scopes, uri, text, name, "val");
+                        }
+                    }
+                    None => add_def(defs, scopes, uri, text, &sd.name, "val"),
+                }
             }
             aura_ast::Stmt::Assign(a) => {
                 // Assign target is a reference.
@@ -2029,6 +2334,11 @@ fn collect_file_symbols(uri: &Url, text: &str) -> (Vec<DefInfo>, Vec<RefInfo>) {
                 walk_expr(refs, scopes, globals, uri, text, &p.expr);
             }
             aura_ast::Stmt::ExprStmt(e) => walk_expr(refs, scopes, globals, uri, text, e),
+            aura_ast::Stmt::Return(r) => {
+                if let Some(v) = &r.value {
+                    walk_expr(refs, scopes, globals, uri, text, v);
+                }
+            }
             aura_ast::Stmt::Requires(r) => walk_expr(refs, scopes, globals, uri, text, &r.expr),
             aura_ast::Stmt::Ensures(e) => walk_expr(refs, scopes, globals, uri, text, &e.expr),
             aura_ast::Stmt::Assert(a) => walk_expr(refs, scopes, globals, uri, text, &a.expr),
@@ -2056,6 +2366,18 @@ fn collect_file_symbols(uri: &Url, text: &str) -> (Vec<DefInfo>, Vec<RefInfo>) {
                 }
                 walk_block(refs, defs, scopes, globals, uri, text, &w.body);
             }
+            aura_ast::Stmt::For(f) => {
+                walk_expr(refs, scopes, globals, uri, text, &f.start);
+                walk_expr(refs, scopes, globals, uri, text, &f.end);
+                // The loop variable is scoped to the invariant and body.
+                scopes.push(HashMap::new());
+                add_def(defs, scopes, uri, text, &f.var, "val");
+                if let Some(inv) = &f.invariant {
+                    walk_expr(refs, scopes, globals, uri, text, inv);
+                }
+                walk_block(refs, defs, scopes, globals, uri, text, &f.body);
+                scopes.pop();
+            }
             aura_ast::Stmt::CellDef(c) => {
                 // Function name def already added as global; treat it as a def occurrence too.
                 let key = DefKey {
@@ -2073,6 +2395,9 @@ fn collect_file_symbols(uri: &Url, text: &str) -> (Vec<DefInfo>, Vec<RefInfo>) {
                 for p in &c.params {
                     add_def(defs, scopes, uri, text, &p.name, "param");
                 }
+                if let Some(dec) = &c.decreases {
+                    walk_expr(refs, scopes, globals, uri, text, dec);
+                }
                 walk_block(refs, defs, scopes, globals, uri, text, &c.body);
                 scopes.pop();
             }
@@ -2111,9 +2436,24 @@ fn collect_file_symbols(uri: &Url, text: &str) -> (Vec<DefInfo>, Vec<RefInfo>) {
             aura_ast::Stmt::UnsafeBlock(ub) => {
                 walk_block(refs, defs, scopes, globals, uri, text, &ub.body);
             }
+            aura_ast::Stmt::Scope(sc) => {
+                // The token is scoped to the body.
+                scopes.push(HashMap::new());
+                if let Some(token) = &sc.token {
+                    add_def(defs, scopes, uri, text, token, "val");
+                }
+                walk_block(refs, defs, scopes, globals, uri, text, &sc.body);
+                scopes.pop();
+            }
+            aura_ast::Stmt::Region(r) => {
+                scopes.push(HashMap::new());
+                walk_block(refs, defs, scopes, globals, uri, text, &r.body);
+                scopes.pop();
+            }
+            aura_ast::Stmt::Defer(d) => walk_block(refs, defs, scopes, globals, uri, text, &d.body),
             aura_ast::Stmt::Layout(lb) => walk_block(refs, defs, scopes, globals, uri, text, &lb.body),
             aura_ast::Stmt::Render(rb) => walk_block(refs, defs, scopes, globals, uri, text, &rb.body),
-            aura_ast::Stmt::Import(_) => {}
+            aura_ast::Stmt::Import(_) | aura_ast::Stmt::Break(_) | aura_ast::Stmt::Continue(_) => {}
         }
     }
 
@@ -2153,6 +2493,7 @@ enum SolverJob {
         scope_is_affected: bool,
         affected_offsets: Vec<(usize, usize)>,
         cache_snapshot: ProofCacheEntry,
+        partial_tx: Option<PartialProofsTx>,
         resp: oneshot::Sender<SolverThreadResult<(Vec<Diagnostic>, ProofCacheEntry, String, String, u64, u64, Option<bool>)>>,
     },
 }
@@ -2200,6 +2541,7 @@ impl SolverWorker {
                             scope_is_affected,
                             affected_offsets,
                             cache_snapshot,
+                            partial_tx,
                             resp,
                         } => {
                             let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
@@ -2218,6 +2560,7 @@ impl SolverWorker {
                                     scope_is_affected,
                                     &affected_offsets,
                                     cache_snapshot,
+                                    partial_tx.as_ref(),
                                     &mut prover,
                                 )
                             }))
@@ -2272,6 +2615,7 @@ impl SolverWorker {
         scope_is_affected: bool,
         affected_offsets: Vec<(usize, usize)>,
         cache_snapshot: ProofCacheEntry,
+        partial_tx: Option<PartialProofsTx>,
     ) -> SolverThreadResult<(Vec<Diagnostic>, ProofCacheEntry, String, String, u64, u64, Option<bool>)> {
         let (resp_tx, resp_rx) = oneshot::channel();
         self.tx
@@ -2290,6 +2634,7 @@ impl SolverWorker {
                 scope_is_affected,
                 affected_offsets,
                 cache_snapshot,
+                partial_tx,
                 resp: resp_tx,
             })
             .map_err(|_| "aura-z3-worker channel closed".to_string())?;
@@ -2337,6 +2682,7 @@ fn run_incremental_verify_stage(
     scope_is_affected: bool,
     affected_offsets: &[(usize, usize)],
     cache_snapshot: ProofCacheEntry,
+    partial_tx: Option<&PartialProofsTx>,
     prover: &mut aura_verify::Z3Prover,
 ) -> (Vec<Diagnostic>, ProofCacheEntry, String, String, u64, u64, Option<bool>) {
     // Incremental: verify per top-level unit and reuse cached unit results.
@@ -2354,6 +2700,7 @@ fn run_incremental_verify_stage(
         source_path,
         manifest_path: manifest.map(|p| p.to_path_buf()),
     });
+    nexus.insert(model_contract_cache());
 
     // Always run the fast non-Z3 verifier pass (range alias checks).
     if let Err(err) = aura_verify::verify_program(program, prover) {
@@ -2369,11 +2716,16 @@ fn run_incremental_verify_stage(
         );
     }
 
+    // Units are verified one at a time, so substitute constants up front; a bad `const` is
+    // reported by sema.
+    let folded = aura_core::fold_consts(program);
+    let program = folded.as_deref().unwrap_or(program);
+
     let mut live_stmt_hashes: Vec<String> = Vec::new();
     let mut ui_hash_input = String::new();
 
     // Compute stable statement hashes that incorporate intra-file call dependencies.
-    let stmt_hashes = compute_checkable_stmt_merkle_hashes(program, text, dep_hash_for_stmt);
+    let stmt_hashes = stmt_merkle_hashes(program, text, dep_hash_for_stmt);
 
     let overlaps_affected = |stmt_start: usize, stmt_end: usize| -> bool {
         if affected_offsets.is_empty() {
@@ -2384,6 +2736,35 @@ fn run_incremental_verify_stage(
             .any(|(a, b)| stmt_start < *b && *a < stmt_end)
     };
 
+    // Count the units this run visits so partial results can report progress.
+    let total_units = program
+        .stmts
+        .iter()
+        .filter_map(|stmt| match stmt {
+            aura_ast::Stmt::CellDef(s) => Some(s.span),
+            aura_ast::Stmt::FlowBlock(s) => Some(s.span),
+            aura_ast::Stmt::Layout(s) => Some(s.span),
+            aura_ast::Stmt::Render(s) => Some(s.span),
+            _ => None,
+        })
+        .filter(|sp| !scope_is_affected || overlaps_affected(sp.offset(), sp.offset().saturating_add(sp.len())))
+        .count() as u64;
+    let mut done_units: u64 = 0;
+    let mut emit_partial = |stmt_hash: &str, stmt_diags: &[Diagnostic], cached: bool| {
+        done_units += 1;
+        if let Some(tx) = partial_tx {
+            let _ = tx.send((
+                ProofsStreamPartial {
+                    stmt_hash: stmt_hash.to_string(),
+                    done: done_units,
+                    total: total_units,
+                    cached,
+                },
+                stmt_diags.to_vec(),
+            ));
+        }
+    };
+
     for (stmt_idx, stmt) in program.stmts.iter().enumerate() {
         let (is_checkable, is_ui) = match stmt {
             aura_ast::Stmt::CellDef(_) => (true, true),
@@ -2434,6 +2815,7 @@ fn run_incremental_verify_stage(
         if cache_enabled {
             if let Some(cached) = cache_entry.stmt_diags.get(&stmt_hash).cloned() {
                 local_stmt_cache_hits += 1;
+                emit_partial(&stmt_hash, &cached, true);
                 diags.extend(cached);
                 continue;
             }
@@ -2526,6 +2908,7 @@ fn run_incremental_verify_stage(
                 .stmt_diags
                 .insert(stmt_hash.clone(), stmt_diags.clone());
         }
+        emit_partial(&stmt_hash, &stmt_diags, false);
         diags.extend(stmt_diags);
     }
 
@@ -2790,6 +3173,7 @@ impl Backend {
             diagnostics: None,
             telemetry: None,
             error: None,
+            partial: None,
         })
         .await;
 
@@ -2857,6 +3241,7 @@ impl Backend {
                         diagnostics: Some(diags),
                         telemetry: None,
                         error: None,
+                        partial: None,
                     })
                     .await;
                     let mut map = tasks.lock().await;
@@ -2912,6 +3297,7 @@ impl Backend {
                         diagnostics: Some(diags),
                         telemetry,
                         error: None,
+                        partial: None,
                     })
                     .await;
                     let mut map = tasks.lock().await;
@@ -2936,6 +3322,7 @@ impl Backend {
                         diagnostics: None,
                         telemetry: None,
                         error: None,
+                        partial: None,
                     })
                     .await;
                 }
@@ -2961,6 +3348,7 @@ impl Backend {
                         diagnostics: Some(diags),
                         telemetry: None,
                         error: None,
+                        partial: None,
                     })
                     .await;
                     let mut map = tasks.lock().await;
@@ -2976,6 +3364,7 @@ impl Backend {
                         diagnostics: None,
                         telemetry: None,
                         error: Some(format!("parse task failed: {e}")),
+                        partial: None,
                     })
                     .await;
                     let mut map = tasks.lock().await;
@@ -2998,6 +3387,7 @@ impl Backend {
                         diagnostics: None,
                         telemetry: None,
                         error: None,
+                        partial: None,
                     })
                     .await;
                 }
@@ -3026,6 +3416,7 @@ impl Backend {
                         diagnostics: Some(diags),
                         telemetry: None,
                         error: None,
+                        partial: None,
                     })
                     .await;
                     let mut map = tasks.lock().await;
@@ -3041,6 +3432,7 @@ impl Backend {
                         diagnostics: None,
                         telemetry: None,
                         error: Some(format!("sema task failed: {e}")),
+                        partial: None,
                     })
                     .await;
                     let mut map = tasks.lock().await;
@@ -3063,6 +3455,7 @@ impl Backend {
                         diagnostics: None,
                         telemetry: None,
                         error: None,
+                        partial: None,
                     })
                     .await;
                 }
@@ -3088,6 +3481,7 @@ impl Backend {
                         diagnostics: Some(diags),
                         telemetry: None,
                         error: None,
+                        partial: None,
                     })
                     .await;
                     let mut map = tasks.lock().await;
@@ -3103,6 +3497,7 @@ impl Backend {
                         diagnostics: None,
                         telemetry: None,
                         error: Some(format!("normalize task failed: {e}")),
+                        partial: None,
                     })
                     .await;
                     let mut map = tasks.lock().await;
@@ -3125,6 +3520,7 @@ impl Backend {
                         diagnostics: None,
                         telemetry: None,
                         error: None,
+                        partial: None,
                     })
                     .await;
                 }
@@ -3137,7 +3533,75 @@ impl Backend {
                 ProofCacheEntry::default()
             };
 
-            let verify_res = solver.verify_incremental(
+            // Forward per-unit results as `partial` events (plus work-done progress) while
+            // the solver thread is still working through the rest of the file.
+            let (partial_tx, mut partial_rx): (PartialProofsTx, _) = mpsc::unbounded_channel();
+            let mut progress_token = aura_caps.work_done_progress.then(|| proofs_progress_token(id));
+            if let Some(token) = progress_token.clone() {
+                let created = client2
+                    .send_request::<tower_lsp::lsp_types::request::WorkDoneProgressCreate>(
+                        WorkDoneProgressCreateParams { token: token.clone() },
+                    )
+                    .await;
+                // A client that refused the token must not get progress reports for it.
+                if created.is_err() {
+                    progress_token = None;
+                } else {
+                    client2
+                        .send_notification::<tower_lsp::lsp_types::notification::Progress>(ProgressParams {
+                            token,
+                            value: ProgressParamsValue::WorkDone(WorkDoneProgress::Begin(
+                                WorkDoneProgressBegin {
+                                    title: "Aura: verifying proofs".to_string(),
+                                    cancellable: Some(false),
+                                    message: None,
+                                    percentage: Some(0),
+                                },
+                            )),
+                        })
+                        .await;
+                }
+            }
+            let forward_partials = {
+                let client = client2.clone();
+                let uri = uri2.clone();
+                let progress_token = progress_token.clone();
+                async move {
+                    while let Some((partial, diags)) = partial_rx.recv().await {
+                        if let Some(token) = progress_token.clone() {
+                            let percentage = (partial.done * 100).checked_div(partial.total).unwrap_or(100);
+                            client
+                                .send_notification::<tower_lsp::lsp_types::notification::Progress>(ProgressParams {
+                                    token,
+                                    value: ProgressParamsValue::WorkDone(WorkDoneProgress::Report(
+                                        WorkDoneProgressReport {
+                                            cancellable: Some(false),
+                                            message: Some(format!("{}/{} units", partial.done, partial.total)),
+                                            percentage: Some(percentage as u32),
+                                        },
+                                    )),
+                                })
+                                .await;
+                        }
+                        client
+                            .send_notification::<AuraProofsStreamNotification>(ProofsStreamEvent {
+                                id,
+                                uri: uri.clone(),
+                                state: "partial".to_string(),
+                                phase: None,
+                                diagnostics: Some(diags),
+                                telemetry: None,
+                                error: None,
+                                partial: Some(partial),
+                            })
+                            .await;
+                    }
+                }
+            };
+
+            // Polled in this task (not spawned) so cancelling the stream also stops forwarding.
+            let (verify_res, ()) = tokio::join!(
+                solver.verify_incremental(
                     uri2.clone(),
                     augmented.clone(),
                     program,
@@ -3152,10 +3616,23 @@ impl Backend {
                     scope_is_affected,
                     affected_offsets_for_verify,
                     cache_snapshot,
-                )
-                .await;
+                    Some(partial_tx),
+                ),
+                // Ends once the worker drops its sender, so every partial precedes `done`.
+                forward_partials,
+            );
 
             t_z3_ms = Some(t_start.elapsed().as_millis());
+            if let Some(token) = progress_token {
+                client2
+                    .send_notification::<tower_lsp::lsp_types::notification::Progress>(ProgressParams {
+                        token,
+                        value: ProgressParamsValue::WorkDone(WorkDoneProgress::End(WorkDoneProgressEnd {
+                            message: None,
+                        })),
+                    })
+                    .await;
+            }
 
             match verify_res {
                 Ok((diags, mut cache_entry, base_key, file_hash, stmt_hits, stmt_misses, ui_hit)) => {
@@ -3223,6 +3700,7 @@ impl Backend {
                         diagnostics: Some(diags),
                         telemetry,
                         error: None,
+                        partial: None,
                     })
                     .await;
                 }
@@ -3235,6 +3713,7 @@ impl Backend {
                         diagnostics: None,
                         telemetry: None,
                         error: Some(format!("z3 task failed: {e}")),
+                        partial: None,
                     })
                     .await;
                 }
@@ -3261,6 +3740,16 @@ impl Backend {
 
         let cancelled_uri = if let Some((u, t)) = task {
             t.abort();
+            if self.aura_caps_snapshot().await.work_done_progress {
+                self.client
+                    .send_notification::<tower_lsp::lsp_types::notification::Progress>(ProgressParams {
+                        token: proofs_progress_token(id),
+                        value: ProgressParamsValue::WorkDone(WorkDoneProgress::End(WorkDoneProgressEnd {
+                            message: Some("cancelled".to_string()),
+                        })),
+                    })
+                    .await;
+            }
             u
         } else {
             Url::parse("untitled:///cancelled.aura").unwrap_or_else(|_| Url::parse("untitled:///cancelled").unwrap())
@@ -3274,12 +3763,21 @@ impl Backend {
             diagnostics: None,
             telemetry: None,
             error: None,
+            partial: None,
         })
         .await;
 
         Ok(())
     }
 
+    async fn tests(&self, params: TestsParams) -> Result<TestsResponse> {
+        let text = self.get_text_for_uri(&params.uri).await;
+        Ok(TestsResponse {
+            uri: params.uri,
+            tests: collect_test_cells(&text),
+        })
+    }
+
     async fn proof_cache_clear(&self, _params: ProofCacheClearParams) -> Result<ProofCacheClearResponse> {
         {
             let mut cache = self.proof_cache.write().await;
@@ -3349,6 +3847,12 @@ impl LanguageServer for Backend {
         {
             let mut caps = self.aura_client_caps.write().await;
             *caps = parse_aura_client_caps(&params);
+            caps.work_done_progress = params
+                .capabilities
+                .window
+                .as_ref()
+                .and_then(|w| w.work_done_progress)
+                .unwrap_or(false);
         }
 
         let legend = SemanticTokensLegend {
@@ -3378,6 +3882,7 @@ impl LanguageServer for Backend {
                     ..CompletionOptions::default()
                 }),
                 definition_provider: Some(OneOf::Left(true)),
+                hover_provider: Some(HoverProviderCapability::Simple(true)),
                 references_provider: Some(OneOf::Left(true)),
                 rename_provider: Some(OneOf::Right(RenameOptions {
                     prepare_provider: Some(true),
@@ -3386,6 +3891,19 @@ impl LanguageServer for Backend {
                     },
                 })),
                 inlay_hint_provider: Some(OneOf::Left(true)),
+                execute_command_provider: Some(ExecuteCommandOptions {
+                    commands: vec![RUN_TESTS_COMMAND.to_string()],
+                    work_done_progress_options: WorkDoneProgressOptions {
+                        work_done_progress: Some(false),
+                    },
+                }),
+                code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+                    code_action_kinds: Some(vec![CodeActionKind::QUICKFIX]),
+                    work_done_progress_options: WorkDoneProgressOptions {
+                        work_done_progress: Some(false),
+                    },
+                    resolve_provider: Some(false),
+                })),
                 semantic_tokens_provider: Some(
                     SemanticTokensServerCapabilities::SemanticTokensOptions(SemanticTokensOptions {
                         work_done_progress_options: WorkDoneProgressOptions {
@@ -3585,6 +4103,30 @@ impl LanguageServer for Backend {
         Ok(None)
     }
 
+    async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
+        let uri = params.text_document_position_params.text_document.uri;
+        let pos = params.text_document_position_params.position;
+        let text = self.get_text_for_uri(&uri).await;
+
+        let (defs, refs) = collect_file_symbols(&uri, &text);
+        let chosen = refs
+            .iter()
+            .map(|r| (r.range, &r.key))
+            .chain(defs.iter().map(|d| (d.range, &d.key)))
+            .find(|(range, _)| range_contains_position(*range, pos));
+        let Some((range, key)) = chosen else {
+            return Ok(None);
+        };
+
+        Ok(def_doc_markdown(&text, &key.name, key.kind).map(|value| Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }),
+            range: Some(range),
+        }))
+    }
+
     async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
         let uri = params.text_document_position.text_document.uri;
         let pos = params.text_document_position.position;
@@ -3748,6 +4290,156 @@ impl LanguageServer for Backend {
         }))
     }
 
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let uri = params.text_document.uri;
+        let mut actions: Vec<CodeActionOrCommand> = Vec::new();
+
+        // Capability inference: apply the narrowed signature it computed.
+        for diag in &params.context.diagnostics {
+            if diag.code != Some(NumberOrString::String(CAPABILITY_CODE.to_string())) {
+                continue;
+            }
+            let Some(data) = &diag.data else { continue };
+            let edits: Vec<TextEdit> = serde_json::from_value(data["edits"].clone()).unwrap_or_default();
+            let Some(signature) = data["signature"].as_str() else { continue };
+            if edits.is_empty() {
+                continue;
+            }
+            let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+            changes.insert(uri.clone(), edits);
+            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: format!("Use the inferred signature `{signature}`"),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diag.clone()]),
+                edit: Some(WorkspaceEdit {
+                    changes: Some(changes),
+                    document_changes: None,
+                    change_annotations: None,
+                }),
+                command: None,
+                is_preferred: Some(true),
+                disabled: None,
+                data: None,
+            }));
+        }
+
+        // Possibly-None uses: guard the statement with an `is_some` check.
+        let text = self.get_text_for_uri(&uri).await;
+        for diag in &params.context.diagnostics {
+            let Some(name) = maybe_none_binding_from_message(&diag.message) else { continue };
+            let Some(edit) = guard_with_is_some_edit(&text, diag.range.start.line, name) else { continue };
+
+            let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+            changes.insert(uri.clone(), vec![edit]);
+            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: format!("Guard with `if is_some({name}):`"),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diag.clone()]),
+                edit: Some(WorkspaceEdit {
+                    changes: Some(changes),
+                    document_changes: None,
+                    change_annotations: None,
+                }),
+                command: None,
+                is_preferred: Some(true),
+                disabled: None,
+                data: None,
+            }));
+        }
+
+        // Undeclared effects: extend the cell's `uses` list.
+        for diag in &params.context.diagnostics {
+            if !diag.message.contains("` but declares `uses [") {
+                continue;
+            }
+            let Some((uses, edit)) = declare_effects_edit(&text, diag.range) else { continue };
+
+            let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+            changes.insert(uri.clone(), vec![edit]);
+            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: format!("Declare `{uses}`"),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diag.clone()]),
+                edit: Some(WorkspaceEdit {
+                    changes: Some(changes),
+                    document_changes: None,
+                    change_annotations: None,
+                }),
+                command: None,
+                is_preferred: Some(true),
+                disabled: None,
+                data: None,
+            }));
+        }
+
+        let manifest = find_aura_toml_for_uri(&uri);
+        let manifest_plugins = manifest.as_deref().and_then(load_manifest_plugins).unwrap_or_default();
+        let manifest_uri = manifest.as_deref().and_then(|m| Url::from_file_path(m).ok());
+        for diag in &params.context.diagnostics {
+            let (Some(manifest), Some(manifest_uri)) = (&manifest, &manifest_uri) else { break };
+            let Some(plugin) = untrusted_plugin_from_message(&diag.message) else { continue };
+            let Some(edit) = trust_plugin_edit(manifest, plugin, &manifest_plugins) else { continue };
+
+            let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+            changes.insert(manifest_uri.clone(), vec![edit]);
+            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: format!("Trust Nexus plugin '{plugin}' (set `trusted = true` in aura.toml)"),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diag.clone()]),
+                edit: Some(WorkspaceEdit {
+                    changes: Some(changes),
+                    document_changes: None,
+                    change_annotations: None,
+                }),
+                command: None,
+                is_preferred: Some(false),
+                disabled: None,
+                data: None,
+            }));
+        }
+
+        if actions.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(actions))
+    }
+
+    async fn execute_command(&self, params: ExecuteCommandParams) -> Result<Option<serde_json::Value>> {
+        if params.command != RUN_TESTS_COMMAND {
+            return Err(tower_lsp::jsonrpc::Error::method_not_found());
+        }
+
+        let uri = params
+            .arguments
+            .first()
+            .and_then(|v| serde_json::from_value::<Url>(v.clone()).ok())
+            .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("expected a document uri"))?;
+        let only = params.arguments.get(1).and_then(|v| v.as_str()).map(str::to_string);
+
+        let text = self.get_text_for_uri(&uri).await;
+        let tests: Vec<TestItem> = collect_test_cells(&text)
+            .into_iter()
+            .filter(|t| only.as_deref().is_none_or(|n| n == t.name))
+            .collect();
+
+        // The AVM (and the bridge generator) is synchronous; keep it off the async runtime.
+        let manifest = find_aura_toml_for_uri(&uri);
+        let (results, coverage) = tokio::task::spawn_blocking(move || {
+            let bridge = manifest.as_deref().map(load_native_bridge).unwrap_or_default();
+            run_test_cells(&text, tests, &bridge)
+        })
+            .await
+            .map_err(|_| tower_lsp::jsonrpc::Error::internal_error())?;
+
+        Ok(Some(json!({
+            "uri": uri,
+            "passed": results.iter().filter(|r| r.passed).count(),
+            "failed": results.iter().filter(|r| !r.passed).count(),
+            "results": results,
+            "coverage": coverage.report(),
+        })))
+    }
+
     async fn inlay_hint(&self, params: InlayHintParams) -> Result<Option<Vec<InlayHint>>> {
         let uri = params.text_document.uri;
         let text = self.get_text_for_uri(&uri).await;
@@ -3803,12 +4495,35 @@ impl LanguageServer for Backend {
                         }
                     }
                 }
-                ExprKind::Unary { expr: inner, .. } => walk_expr_for_hints(hints, checker, text, inner),
+                ExprKind::Unary { expr: inner, .. } | ExprKind::Borrow { expr: inner, .. } => {
+                    walk_expr_for_hints(hints, checker, text, inner)
+                }
                 ExprKind::Binary { left, right, .. } => {
                     walk_expr_for_hints(hints, checker, text, left);
                     walk_expr_for_hints(hints, checker, text, right);
                 }
                 ExprKind::Member { base, .. } => walk_expr_for_hints(hints, checker, text, base),
+                ExprKind::Tuple(elems) => {
+                    for e in elems {
+                        walk_expr_for_hints(hints, checker, text, e);
+                    }
+                }
+                ExprKind::TupleIndex { base, .. } => walk_expr_for_hints(hints, checker, text, base),
+                ExprKind::ListLit(elems) => {
+                    for e in elems {
+                        walk_expr_for_hints(hints, checker, text, e);
+                    }
+                }
+                ExprKind::MapLit(entries) => {
+                    for (k, v) in entries {
+                        walk_expr_for_hints(hints, checker, text, k);
+                        walk_expr_for_hints(hints, checker, text, v);
+                    }
+                }
+                ExprKind::Index { base, index } => {
+                    walk_expr_for_hints(hints, checker, text, base);
+                    walk_expr_for_hints(hints, checker, text, index);
+                }
                 ExprKind::Flow { left, right, .. } => {
                     walk_expr_for_hints(hints, checker, text, left);
                     walk_expr_for_hints(hints, checker, text, right);
@@ -3834,7 +4549,14 @@ impl LanguageServer for Backend {
                 ExprKind::ForAll { body, .. } | ExprKind::Exists { body, .. } => {
                     walk_expr_for_hints(hints, checker, text, body)
                 }
-                ExprKind::Ident(_) | ExprKind::IntLit(_) | ExprKind::StringLit(_) => {}
+                ExprKind::InterpolatedString(parts) => {
+                    for part in parts {
+                        if let aura_ast::InterpPart::Expr(e) = part {
+                            walk_expr_for_hints(hints, checker, text, e);
+                        }
+                    }
+                }
+                ExprKind::Ident(_) | ExprKind::IntLit(_) | ExprKind::FloatLit(_) | ExprKind::StringLit(_) => {}
             }
         }
 
@@ -3863,7 +4585,10 @@ impl LanguageServer for Backend {
                     if sd.ty.is_none() {
                         let ty = match &sd.expr.kind {
                             aura_ast::ExprKind::IntLit(_) => Some("u32"),
-                            aura_ast::ExprKind::StringLit(_) => Some("String"),
+                            aura_ast::ExprKind::FloatLit(_) => Some("f64"),
+                            aura_ast::ExprKind::StringLit(_) | aura_ast::ExprKind::InterpolatedString(_) => {
+                                Some("String")
+                            }
                             aura_ast::ExprKind::Call { .. } => None,
                             _ => None,
                         };
@@ -3886,6 +4611,11 @@ impl LanguageServer for Backend {
                 aura_ast::Stmt::Assign(a) => walk_expr_for_hints(hints, checker, text, &a.expr),
                 aura_ast::Stmt::Prop(p) => walk_expr_for_hints(hints, checker, text, &p.expr),
                 aura_ast::Stmt::ExprStmt(e) => walk_expr_for_hints(hints, checker, text, e),
+                aura_ast::Stmt::Return(r) => {
+                    if let Some(v) = &r.value {
+                        walk_expr_for_hints(hints, checker, text, v);
+                    }
+                }
                 aura_ast::Stmt::Requires(r) => walk_expr_for_hints(hints, checker, text, &r.expr),
                 aura_ast::Stmt::Ensures(e) => walk_expr_for_hints(hints, checker, text, &e.expr),
                 aura_ast::Stmt::Assert(a) => walk_expr_for_hints(hints, checker, text, &a.expr),
@@ -3933,6 +4663,19 @@ impl LanguageServer for Backend {
                         walk_expr_for_hints(hints, checker, text, y);
                     }
                 }
+                aura_ast::Stmt::For(f) => {
+                    walk_expr_for_hints(hints, checker, text, &f.start);
+                    walk_expr_for_hints(hints, checker, text, &f.end);
+                    if let Some(inv) = &f.invariant {
+                        walk_expr_for_hints(hints, checker, text, inv);
+                    }
+                    for s in &f.body.stmts {
+                        walk_stmt_for_hints(hints, checker, text, s);
+                    }
+                    if let Some(y) = &f.body.yield_expr {
+                        walk_expr_for_hints(hints, checker, text, y);
+                    }
+                }
                 aura_ast::Stmt::CellDef(c) => {
                     for s in &c.body.stmts {
                         walk_stmt_for_hints(hints, checker, text, s);
@@ -3973,12 +4716,31 @@ impl LanguageServer for Backend {
                         walk_expr_for_hints(hints, checker, text, y);
                     }
                 }
+                aura_ast::Stmt::Scope(aura_ast::ScopeBlock { body, .. })
+                | aura_ast::Stmt::Region(aura_ast::RegionBlock { body, .. }) => {
+                    for s in &body.stmts {
+                        walk_stmt_for_hints(hints, checker, text, s);
+                    }
+                    if let Some(y) = &body.yield_expr {
+                        walk_expr_for_hints(hints, checker, text, y);
+                    }
+                }
+                aura_ast::Stmt::Defer(d) => {
+                    for s in &d.body.stmts {
+                        walk_stmt_for_hints(hints, checker, text, s);
+                    }
+                    if let Some(y) = &d.body.yield_expr {
+                        walk_expr_for_hints(hints, checker, text, y);
+                    }
+                }
                 aura_ast::Stmt::Import(_)
                 | aura_ast::Stmt::TypeAlias(_)
                 | aura_ast::Stmt::TraitDef(_)
                 | aura_ast::Stmt::RecordDef(_)
                 | aura_ast::Stmt::EnumDef(_)
-                | aura_ast::Stmt::ExternCell(_) => {}
+                | aura_ast::Stmt::ExternCell(_)
+                | aura_ast::Stmt::Break(_)
+                | aura_ast::Stmt::Continue(_) => {}
             }
         }
 
@@ -4044,11 +4806,12 @@ fn semantic_tokens_for_source(text: &str) -> Vec<SemanticToken> {
         }
 
         let tok_type = match &t.kind {
-            aura_lex::TokenKind::String(_) => SemanticTokenType::STRING,
-            aura_lex::TokenKind::Int(_) => SemanticTokenType::NUMBER,
+            aura_lex::TokenKind::String(_) | aura_lex::TokenKind::InterpString(_) => SemanticTokenType::STRING,
+            aura_lex::TokenKind::Int(_) | aura_lex::TokenKind::Float(_) => SemanticTokenType::NUMBER,
             aura_lex::TokenKind::Ident(_) => SemanticTokenType::VARIABLE,
             aura_lex::TokenKind::KwImport
             | aura_lex::TokenKind::KwVal
+            | aura_lex::TokenKind::KwConst
             | aura_lex::TokenKind::KwCell
             | aura_lex::TokenKind::KwExtern
             | aura_lex::TokenKind::KwType
@@ -4058,6 +4821,10 @@ fn semantic_tokens_for_source(text: &str) -> Vec<SemanticToken> {
             | aura_lex::TokenKind::KwElse
             | aura_lex::TokenKind::KwMatch
             | aura_lex::TokenKind::KwWhile
+            | aura_lex::TokenKind::KwBreak
+            | aura_lex::TokenKind::KwContinue
+            | aura_lex::TokenKind::KwReturn
+            | aura_lex::TokenKind::KwDefer
             | aura_lex::TokenKind::KwInvariant
             | aura_lex::TokenKind::KwLayout
             | aura_lex::TokenKind::KwRender => SemanticTokenType::KEYWORD,
@@ -4072,12 +4839,16 @@ fn semantic_tokens_for_source(text: &str) -> Vec<SemanticToken> {
             | aura_lex::TokenKind::Gt
             | aura_lex::TokenKind::Le
             | aura_lex::TokenKind::Ge
+            | aura_lex::TokenKind::Shl
             | aura_lex::TokenKind::Plus
             | aura_lex::TokenKind::Minus
             | aura_lex::TokenKind::Star
             | aura_lex::TokenKind::Slash
             | aura_lex::TokenKind::AndAnd
             | aura_lex::TokenKind::OrOr
+            | aura_lex::TokenKind::Pipe
+            | aura_lex::TokenKind::Amp
+            | aura_lex::TokenKind::Caret
             | aura_lex::TokenKind::Bang
             | aura_lex::TokenKind::Dot
             | aura_lex::TokenKind::DotDot
@@ -4138,7 +4909,7 @@ mod tests {
 
     fn hash_for_cell_named(text: &str, cell_name: &str, dep_hash: &str) -> String {
         let program = aura_parse::parse_source(text).expect("parse");
-        let hashes = compute_checkable_stmt_merkle_hashes(&program, text, dep_hash);
+        let hashes = stmt_merkle_hashes(&program, text, dep_hash);
         for (i, stmt) in program.stmts.iter().enumerate() {
             let slice = stmt_slice_for_test(text, stmt);
             if slice.trim_start().starts_with(&format!("cell {cell_name}")) {
@@ -4151,6 +4922,18 @@ mod tests {
         panic!("cell not found: {cell_name}");
     }
 
+    #[test]
+    fn inline_table_pair_matches_the_exact_key() {
+        let table = r#"{ name = "trusted-sensor", untrusted = 1, trusted = false "#;
+        let (start, end) = inline_table_pair(table, "trusted").expect("trusted key");
+        assert_eq!(&table[start..end], "trusted = false");
+
+        assert_eq!(inline_table_pair(r#"{ name = "a, trusted = x", untrusted = true"#, "trusted"), None);
+        let table = r#"{ trusted=true"#;
+        let (start, end) = inline_table_pair(table, "trusted").expect("trusted key");
+        assert_eq!(&table[start..end], "trusted=true");
+    }
+
     #[test]
     fn proof_cache_store_roundtrips() {
         let diag = Diagnostic {
@@ -4265,6 +5048,19 @@ cell a() ->:
         let h2 = hash_for_cell_named(t2, "a", "deps0");
         assert_eq!(h1, h2, "caller stmt hash should not change when unrelated stmt changes");
     }
+
+    #[test]
+    fn file_symbols_survive_a_half_typed_statement() {
+        let text = "cell a():\n    val x: u32 = 1 +\n    val y: u32 = 2\n\ncell b():\n    val z: u32 = 3\n";
+        let uri = Url::parse("file:///tmp/half_typed.aura").unwrap();
+        let (defs, _) = collect_file_symbols(&uri, text);
+        let cells: Vec<&str> = defs
+            .iter()
+            .filter(|d| d.key.kind == "cell")
+            .map(|d| d.key.name.as_str())
+            .collect();
+        assert_eq!(cells, vec!["a", "b"]);
+    }
 }
 
 #[tokio::main]
@@ -4277,6 +5073,7 @@ async fn main() {
         .custom_method("aura/proofsStreamStart", Backend::proofs_stream_start)
         .custom_method("aura/proofsStreamCancel", Backend::proofs_stream_cancel)
         .custom_method("aura/proofCacheClear", Backend::proof_cache_clear)
+        .custom_method("aura/tests", Backend::tests)
         .finish();
     Server::new(stdin, stdout, socket).serve(service).await;
 }
@@ -614,6 +614,450 @@ fn aura_lsp_proofs_stream_phases_include_normalize() {
     }
 }
 
+/// Every `aura/proofsStream` event of one run, up to and including `done`. Answers the
+/// server's `window/workDoneProgress/create` requests with an error and collects any
+/// `$/progress` it still sends.
+fn collect_proofs_stream(
+    rx: &mpsc::Receiver<serde_json::Value>,
+    stdin: &mut impl Write,
+    stream_id: i64,
+    timeout: Duration,
+) -> (Vec<serde_json::Value>, Vec<serde_json::Value>) {
+    let deadline = std::time::Instant::now() + timeout;
+    let mut events = Vec::new();
+    let mut progress = Vec::new();
+    loop {
+        if std::time::Instant::now() >= deadline {
+            panic!("timed out waiting for proofs stream done id={stream_id}; events={events:?}");
+        }
+        let msg = rx.recv_timeout(Duration::from_millis(250)).unwrap();
+        match msg.get("method").and_then(|m| m.as_str()) {
+            Some("window/workDoneProgress/create") => {
+                write_lsp(
+                    stdin,
+                    &serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": msg["id"],
+                        "error": {"code": -32601, "message": "progress is not supported"}
+                    }),
+                );
+            }
+            Some("$/progress") => progress.push(msg["params"].clone()),
+            Some("aura/proofsStream") if msg["params"]["id"].as_i64() == Some(stream_id) => {
+                let params = msg["params"].clone();
+                let state = params["state"].as_str().unwrap_or_default().to_string();
+                events.push(params);
+                match state.as_str() {
+                    "done" => return (events, progress),
+                    "error" => panic!("proofs stream error: {events:?}"),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[test]
+fn aura_lsp_proofs_stream_reports_each_statement_and_reuses_the_cache() {
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::write(tmp.path().join("aura.toml"), "[project]\nname = \"Test\"\n").unwrap();
+
+    let file_path = tmp.path().join("main.aura");
+    let src = r#"cell add(a: u32[0..10], b: u32[0..10]) -> u32[0..20]:
+    yield a + b
+
+cell double(x: u32[0..10]) -> u32[0..20]:
+    yield x + x
+
+cell main():
+    val mut counter: u32 = 0
+    while counter < 3 invariant counter < 1000:
+        counter = counter + 1
+"#;
+    std::fs::write(&file_path, src).unwrap();
+    let uri = file_uri_from_windows_path(&file_path);
+
+    let exe = env!("CARGO_BIN_EXE_aura-lsp");
+    let mut child = Command::new(exe)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stdin = child.stdin.take().unwrap();
+    let stdout = child.stdout.take().unwrap();
+    let mut stderr = child.stderr.take().unwrap();
+
+    let (tx, rx) = mpsc::channel::<serde_json::Value>();
+    std::thread::spawn(move || {
+        let mut r = BufReader::new(stdout);
+        while let Some(msg) = read_lsp(&mut r) {
+            let _ = tx.send(msg);
+        }
+    });
+
+    // The client offers work-done progress, then refuses the token the server asks for.
+    write_lsp(
+        &mut stdin,
+        &serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {
+                "processId": null,
+                "rootUri": null,
+                "capabilities": {"window": {"workDoneProgress": true}}
+            }
+        }),
+    );
+    let _ = wait_for_response(&rx, 1, Duration::from_secs(5));
+    write_lsp(&mut stdin, &serde_json::json!({"jsonrpc": "2.0", "method": "initialized", "params": {}}));
+    write_lsp(
+        &mut stdin,
+        &serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": {
+                "textDocument": {"uri": uri, "languageId": "aura", "version": 1, "text": src}
+            }
+        }),
+    );
+
+    let mut run = |request_id: i64| {
+        write_lsp(
+            &mut stdin,
+            &serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": request_id,
+                "method": "aura/proofsStreamStart",
+                "params": {"uri": uri}
+            }),
+        );
+        let resp = wait_for_response(&rx, request_id, Duration::from_secs(10));
+        let stream_id = resp["result"]["id"].as_i64().expect("proofsStreamStart result id");
+        collect_proofs_stream(&rx, &mut stdin, stream_id, Duration::from_secs(30))
+    };
+
+    let (events, progress) = run(2);
+    let partials: Vec<&serde_json::Value> = events
+        .iter()
+        .filter(|e| e["state"] == "partial")
+        .map(|e| &e["partial"])
+        .collect();
+    assert!(partials.len() >= 3, "expected one partial per cell: {events:?}");
+    assert!(partials.iter().all(|p| p["stmtHash"].as_str().is_some_and(|h| !h.is_empty())));
+    let done: Vec<u64> = partials.iter().map(|p| p["done"].as_u64().unwrap()).collect();
+    let total = partials[0]["total"].as_u64().unwrap();
+    assert!(done.windows(2).all(|w| w[0] < w[1]), "done must increase: {done:?}");
+    assert_eq!(done.last(), Some(&total));
+    // Every partial arrives before `done`, which is last.
+    let last_partial = events.iter().rposition(|e| e["state"] == "partial").unwrap();
+    let done_at = events.iter().position(|e| e["state"] == "done").unwrap();
+    assert!(last_partial < done_at);
+    assert_eq!(done_at, events.len() - 1);
+    assert!(partials.iter().all(|p| p["cached"] == false));
+    // The refused token gets no progress reports.
+    assert!(progress.is_empty(), "progress sent for a refused token: {progress:?}");
+
+    // Nothing changed, so the second run answers every statement from the cache.
+    let (events, _) = run(3);
+    let partials: Vec<&serde_json::Value> = events
+        .iter()
+        .filter(|e| e["state"] == "partial")
+        .map(|e| &e["partial"])
+        .collect();
+    assert_eq!(partials.len() as u64, total);
+    assert!(partials.iter().all(|p| p["cached"] == true), "{partials:?}");
+
+    write_lsp(
+        &mut stdin,
+        &serde_json::json!({"jsonrpc": "2.0", "id": 4, "method": "shutdown", "params": null}),
+    );
+    let _ = wait_for_response(&rx, 4, Duration::from_secs(5));
+    drop(stdin);
+
+    let status = child.wait_timeout(Duration::from_secs(5)).unwrap();
+    if !status.success() {
+        let mut err = String::new();
+        let _ = stderr.read_to_string(&mut err);
+        panic!("aura-lsp exited non-zero: {status:?}\n--- stderr ---\n{err}");
+    }
+}
+
+#[test]
+fn aura_lsp_offers_quick_fix_to_trust_plugin() {
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::write(
+        tmp.path().join("aura.toml"),
+        "[project]\nname = \"Test\"\n\n[[plugins]]\nname = \"aura-iot\"\ntrusted = false\n",
+    )
+    .unwrap();
+
+    let file_path = tmp.path().join("main.aura");
+    let src = "cell main():\n    val x: u32 = 1\n";
+    std::fs::write(&file_path, src).unwrap();
+    let uri = file_uri_from_windows_path(&file_path);
+
+    let exe = env!("CARGO_BIN_EXE_aura-lsp");
+    let mut child = Command::new(exe)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stdin = child.stdin.take().unwrap();
+    let stdout = child.stdout.take().unwrap();
+    let mut stderr = child.stderr.take().unwrap();
+
+    let (tx, rx) = mpsc::channel::<serde_json::Value>();
+    std::thread::spawn(move || {
+        let mut r = BufReader::new(stdout);
+        while let Some(msg) = read_lsp(&mut r) {
+            let _ = tx.send(msg);
+        }
+    });
+
+    write_lsp(
+        &mut stdin,
+        &serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {
+                "processId": null,
+                "rootUri": null,
+                "capabilities": {}
+            }
+        }),
+    );
+    let init = wait_for_response(&rx, 1, Duration::from_secs(5));
+    assert!(
+        init.pointer("/result/capabilities/codeActionProvider").is_some(),
+        "server advertises code actions"
+    );
+
+    write_lsp(
+        &mut stdin,
+        &serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "initialized",
+            "params": {}
+        }),
+    );
+
+    // Request code actions for the verifier's "not trusted" diagnostic.
+    write_lsp(
+        &mut stdin,
+        &serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "textDocument/codeAction",
+            "params": {
+                "textDocument": {"uri": uri},
+                "range": {
+                    "start": {"line": 0, "character": 0},
+                    "end": {"line": 0, "character": 0}
+                },
+                "context": {
+                    "diagnostics": [{
+                        "range": {
+                            "start": {"line": 0, "character": 0},
+                            "end": {"line": 0, "character": 0}
+                        },
+                        "message": "Nexus plugin 'aura-iot' is not trusted. Set `trusted = true` in aura.toml to enable it."
+                    }]
+                }
+            }
+        }),
+    );
+    let resp = wait_for_response(&rx, 2, Duration::from_secs(5));
+    let actions = resp
+        .get("result")
+        .and_then(|r| r.as_array())
+        .expect("codeAction returns actions");
+    assert_eq!(actions.len(), 1, "one quick fix for one untrusted plugin");
+    let title = actions[0].get("title").and_then(|t| t.as_str()).unwrap_or("");
+    assert!(title.contains("aura-iot"), "title names the plugin: {title}");
+
+    let changes = actions[0]
+        .pointer("/edit/changes")
+        .and_then(|c| c.as_object())
+        .expect("quick fix carries a WorkspaceEdit");
+    let edits = changes
+        .iter()
+        .find(|(k, _)| k.ends_with("aura.toml"))
+        .and_then(|(_, v)| v.as_array())
+        .expect("edit targets aura.toml");
+    assert_eq!(edits[0].get("newText").and_then(|t| t.as_str()), Some("trusted = true"));
+    assert_eq!(edits[0].pointer("/range/start/line").and_then(|l| l.as_u64()), Some(5));
+
+    write_lsp(
+        &mut stdin,
+        &serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "shutdown",
+            "params": null
+        }),
+    );
+    let _ = wait_for_response(&rx, 3, Duration::from_secs(5));
+    drop(stdin);
+
+    let status = child.wait_timeout(Duration::from_secs(5)).unwrap();
+    if !status.success() {
+        let mut err = String::new();
+        let _ = stderr.read_to_string(&mut err);
+        panic!("aura-lsp exited non-zero: {status:?}\n--- stderr ---\n{err}");
+    }
+}
+
+#[test]
+fn aura_lsp_runs_test_cells() {
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::write(tmp.path().join("aura.toml"), "[project]\nname = \"Test\"\n").unwrap();
+
+    let file_path = tmp.path().join("main.aura");
+    let src = "test cell adds_up():\n    assert 1 + 1 == 2\n\ntest cell breaks_math():\n    val x: u32 = 2\n    assert x + x == 5\n\ncell main():\n    val y = 0\n";
+    std::fs::write(&file_path, src).unwrap();
+    let uri = file_uri_from_windows_path(&file_path);
+
+    let exe = env!("CARGO_BIN_EXE_aura-lsp");
+    let mut child = Command::new(exe)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stdin = child.stdin.take().unwrap();
+    let stdout = child.stdout.take().unwrap();
+    let mut stderr = child.stderr.take().unwrap();
+
+    let (tx, rx) = mpsc::channel::<serde_json::Value>();
+    std::thread::spawn(move || {
+        let mut r = BufReader::new(stdout);
+        while let Some(msg) = read_lsp(&mut r) {
+            let _ = tx.send(msg);
+        }
+    });
+
+    write_lsp(
+        &mut stdin,
+        &serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {
+                "processId": null,
+                "rootUri": null,
+                "capabilities": {}
+            }
+        }),
+    );
+    let init = wait_for_response(&rx, 1, Duration::from_secs(5));
+    let commands = init
+        .pointer("/result/capabilities/executeCommandProvider/commands")
+        .and_then(|c| c.as_array())
+        .expect("server advertises commands");
+    assert!(commands.iter().any(|c| c.as_str() == Some("aura.runTests")), "{commands:?}");
+
+    write_lsp(
+        &mut stdin,
+        &serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "initialized",
+            "params": {}
+        }),
+    );
+    write_lsp(
+        &mut stdin,
+        &serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": {
+                "textDocument": {
+                    "uri": uri,
+                    "languageId": "aura",
+                    "version": 1,
+                    "text": src
+                }
+            }
+        }),
+    );
+
+    // Every test cell in the file: one passes, one fails its assertion.
+    write_lsp(
+        &mut stdin,
+        &serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "workspace/executeCommand",
+            "params": {
+                "command": "aura.runTests",
+                "arguments": [uri]
+            }
+        }),
+    );
+    let resp = wait_for_response(&rx, 2, Duration::from_secs(30));
+    let result = resp.get("result").expect("runTests result");
+    assert_eq!(result.get("passed").and_then(|v| v.as_u64()), Some(1), "{result}");
+    assert_eq!(result.get("failed").and_then(|v| v.as_u64()), Some(1), "{result}");
+    let results = result.get("results").and_then(|r| r.as_array()).expect("per-test results");
+    let outcome: Vec<(&str, bool)> = results
+        .iter()
+        .map(|r| {
+            (
+                r.get("name").and_then(|n| n.as_str()).unwrap_or(""),
+                r.get("passed").and_then(|p| p.as_bool()).unwrap_or(false),
+            )
+        })
+        .collect();
+    assert_eq!(outcome, vec![("adds_up", true), ("breaks_math", false)]);
+    let message = results[1].get("message").and_then(|m| m.as_str()).unwrap_or("");
+    assert!(message.contains("assertion failed"), "{message}");
+    assert_eq!(results[1].pointer("/range/start/line").and_then(|l| l.as_u64()), Some(3));
+    assert!(results[0].get("message").is_none());
+
+    // A single test by name.
+    write_lsp(
+        &mut stdin,
+        &serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "workspace/executeCommand",
+            "params": {
+                "command": "aura.runTests",
+                "arguments": [uri, "adds_up"]
+            }
+        }),
+    );
+    let resp = wait_for_response(&rx, 3, Duration::from_secs(30));
+    assert_eq!(resp.pointer("/result/passed").and_then(|v| v.as_u64()), Some(1), "{resp}");
+    assert_eq!(resp.pointer("/result/failed").and_then(|v| v.as_u64()), Some(0), "{resp}");
+
+    write_lsp(
+        &mut stdin,
+        &serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 4,
+            "method": "shutdown",
+            "params": null
+        }),
+    );
+    let _ = wait_for_response(&rx, 4, Duration::from_secs(5));
+    drop(stdin);
+
+    let status = child.wait_timeout(Duration::from_secs(5)).unwrap();
+    if !status.success() {
+        let mut err = String::new();
+        let _ = stderr.read_to_string(&mut err);
+        panic!("aura-lsp exited non-zero: {status:?}\n--- stderr ---\n{err}");
+    }
+}
+
 trait WaitTimeout {
     fn wait_timeout(&mut self, timeout: Duration) -> std::io::Result<std::process::ExitStatus>;
 }
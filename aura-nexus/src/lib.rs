@@ -418,6 +418,17 @@ pub trait Z3CallEnv<'ctx> {
     fn eval_int(&mut self, expr: &Expr) -> Result<z3::ast::Int<'ctx>, NexusDiagnostic>;
 
     fn eval_bool(&mut self, expr: &Expr) -> Result<z3::ast::Bool<'ctx>, NexusDiagnostic>;
+
+    /// Whether `condition` can hold on the current path (also `true` when the solver gives up).
+    fn satisfiable(&mut self, condition: z3::ast::Bool<'ctx>) -> bool;
+
+    /// Plugin state that follows control flow like a program variable: the branches of an
+    /// `if` are merged at the join, and a loop body must leave it as it found it. `None`
+    /// until first set on the current path.
+    fn ghost_int(&mut self, name: &str) -> Option<z3::ast::Int<'ctx>>;
+
+    /// Sets ghost `name`. Paths that never set it are taken to hold `init`.
+    fn set_ghost_int(&mut self, name: &str, init: z3::ast::Int<'ctx>, value: z3::ast::Int<'ctx>);
 }
 
 pub fn format_ui_tree(root: &UiNode) -> String {
@@ -1,8 +1,8 @@
 #![forbid(unsafe_code)]
 
 use aura_ast::{
-    BinOp, Block, CallArg, CellDef, Expr, ExprKind, FlowBlock, FlowOp, Ident, IfStmt, LayoutBlock,
-    MatchStmt, Pattern, Program, RenderBlock, Span, Stmt, TypeArg, TypeRef, UnaryOp, WhileStmt,
+    BinOp, Block, BorrowKind, CallArg, CellDef, Expr, ExprKind, FlowBlock, FlowOp, ForStmt, Ident, IfStmt, InterpPart,
+    LayoutBlock, MatchStmt, Pattern, Program, RenderBlock, Span, Stmt, TypeArg, TypeParam, TypeRef, UnaryOp, WhileStmt,
 };
 
 const INDENT: &str = "    ";
@@ -68,20 +68,7 @@ fn fmt_stmt(out: &mut String, indent: usize, stmt: &Stmt) {
             out.push_str("type ");
             out.push_str(&s.name.node);
 
-            if !s.params.is_empty() {
-                out.push('<');
-                for (i, p) in s.params.iter().enumerate() {
-                    if i > 0 {
-                        out.push_str(", ");
-                    }
-                    out.push_str(&p.name.node);
-                    if let Some(b) = &p.bound {
-                        out.push_str(": ");
-                        out.push_str(&b.node);
-                    }
-                }
-                out.push('>');
-            }
+            fmt_type_params(out, &s.params);
 
             out.push_str(" = ");
             fmt_type_ref(out, &s.target);
@@ -94,23 +81,11 @@ fn fmt_stmt(out: &mut String, indent: usize, stmt: &Stmt) {
             out.push('\n');
         }
         Stmt::RecordDef(s) => {
+            fmt_doc(out, indent, s.doc.as_deref());
             indent_line(out, indent);
             out.push_str("type ");
             out.push_str(&s.name.node);
-            if !s.params.is_empty() {
-                out.push('<');
-                for (i, p) in s.params.iter().enumerate() {
-                    if i > 0 {
-                        out.push_str(", ");
-                    }
-                    out.push_str(&p.name.node);
-                    if let Some(b) = &p.bound {
-                        out.push_str(": ");
-                        out.push_str(&b.node);
-                    }
-                }
-                out.push('>');
-            }
+            fmt_type_params(out, &s.params);
             out.push_str(" = record { ");
             for (i, f) in s.fields.iter().enumerate() {
                 if i > 0 {
@@ -127,23 +102,11 @@ fn fmt_stmt(out: &mut String, indent: usize, stmt: &Stmt) {
             out.push_str(" }\n");
         }
         Stmt::EnumDef(s) => {
+            fmt_doc(out, indent, s.doc.as_deref());
             indent_line(out, indent);
             out.push_str("type ");
             out.push_str(&s.name.node);
-            if !s.params.is_empty() {
-                out.push('<');
-                for (i, p) in s.params.iter().enumerate() {
-                    if i > 0 {
-                        out.push_str(", ");
-                    }
-                    out.push_str(&p.name.node);
-                    if let Some(b) = &p.bound {
-                        out.push_str(": ");
-                        out.push_str(&b.node);
-                    }
-                }
-                out.push('>');
-            }
+            fmt_type_params(out, &s.params);
             out.push_str(" = enum { ");
             for (i, v) in s.variants.iter().enumerate() {
                 if i > 0 {
@@ -167,11 +130,14 @@ fn fmt_stmt(out: &mut String, indent: usize, stmt: &Stmt) {
         }
         Stmt::StrandDef(s) => {
             indent_line(out, indent);
-            out.push_str("val ");
+            out.push_str(if s.is_const { "const " } else { "val " });
             if s.mutable {
                 out.push_str("mut ");
             }
-            out.push_str(&s.name.node);
+            match &s.pattern {
+                Some(p) => fmt_pattern(out, p),
+                None => out.push_str(&s.name.node),
+            }
             if let Some(ty) = &s.ty {
                 out.push_str(": ");
                 fmt_type_ref(out, ty);
@@ -185,6 +151,7 @@ fn fmt_stmt(out: &mut String, indent: usize, stmt: &Stmt) {
             out.push('\n');
         }
         Stmt::ExternCell(s) => {
+            fmt_doc(out, indent, s.doc.as_deref());
             indent_line(out, indent);
             if s.trusted {
                 out.push_str("trusted ");
@@ -203,6 +170,22 @@ fn fmt_stmt(out: &mut String, indent: usize, stmt: &Stmt) {
             out.push_str("unsafe:\n");
             fmt_block_indent(out, indent + 1, &s.body);
         }
+        Stmt::Scope(s) => {
+            indent_line(out, indent);
+            out.push_str("scope");
+            if let Some(token) = &s.token {
+                out.push(' ');
+                out.push_str(&token.node);
+            }
+            out.push_str(":\n");
+            fmt_block_indent(out, indent + 1, &s.body);
+        }
+        Stmt::Region(s) => {
+            indent_line(out, indent);
+            out.push_str("region ");
+            fmt_brace_block(out, indent, &s.body);
+            out.push('\n');
+        }
         Stmt::CellDef(s) => fmt_cell_def(out, indent, s),
         Stmt::FlowBlock(s) => fmt_flow_block(out, indent, s),
         Stmt::Layout(s) => fmt_layout(out, indent, s),
@@ -224,6 +207,30 @@ fn fmt_stmt(out: &mut String, indent: usize, stmt: &Stmt) {
         Stmt::If(s) => fmt_if(out, indent, s),
         Stmt::Match(s) => fmt_match(out, indent, s),
         Stmt::While(s) => fmt_while(out, indent, s),
+        Stmt::For(s) => fmt_for(out, indent, s),
+        Stmt::Break(_) => {
+            indent_line(out, indent);
+            out.push_str("break\n");
+        }
+        Stmt::Continue(_) => {
+            indent_line(out, indent);
+            out.push_str("continue\n");
+        }
+        Stmt::Return(s) => {
+            indent_line(out, indent);
+            out.push_str("return");
+            if let Some(v) = &s.value {
+                out.push(' ');
+                fmt_expr(out, v, Prec::Lowest);
+            }
+            out.push('\n');
+        }
+        Stmt::Defer(s) => {
+            indent_line(out, indent);
+            out.push_str("defer ");
+            fmt_brace_block(out, indent, &s.body);
+            out.push('\n');
+        }
         Stmt::Requires(s) => {
             indent_line(out, indent);
             out.push_str("requires ");
@@ -276,30 +283,51 @@ fn fmt_pattern(out: &mut String, p: &Pattern) {
         Pattern::IntLit { value, .. } => out.push_str(&value.to_string()),
         Pattern::StringLit { value, .. } => fmt_string_lit(out, value),
         Pattern::Ctor {
-            ty,
-            variant,
-            binders,
-            ..
+            ty, variant, args, ..
         } => {
             out.push_str(&ty.node);
             out.push_str("::");
             out.push_str(&variant.node);
-            if !binders.is_empty() {
+            if !args.is_empty() {
                 out.push('(');
-                for (i, b) in binders.iter().enumerate() {
+                for (i, a) in args.iter().enumerate() {
                     if i > 0 {
                         out.push_str(", ");
                     }
-                    out.push_str(&b.node);
+                    fmt_pattern(out, a);
                 }
                 out.push(')');
             }
         }
+        Pattern::Tuple { elems, .. } => {
+            out.push('(');
+            for (i, e) in elems.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                fmt_pattern(out, e);
+            }
+            out.push(')');
+        }
+        Pattern::Bind { name, .. } => out.push_str(&name.node),
+        Pattern::Or { alts, .. } => {
+            for (i, a) in alts.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(" | ");
+                }
+                fmt_pattern(out, a);
+            }
+        }
     }
 }
 
 fn fmt_string_lit(out: &mut String, s: &str) {
     out.push('"');
+    fmt_string_chars(out, s);
+    out.push('"');
+}
+
+fn fmt_string_chars(out: &mut String, s: &str) {
     for ch in s.chars() {
         match ch {
             '\n' => out.push_str("\\n"),
@@ -307,6 +335,9 @@ fn fmt_string_lit(out: &mut String, s: &str) {
             '\r' => out.push_str("\\r"),
             '"' => out.push_str("\\\""),
             '\\' => out.push_str("\\\\"),
+            // Braces open interpolation segments; literal ones are doubled.
+            '{' => out.push_str("{{"),
+            '}' => out.push_str("}}"),
             other if other.is_control() => {
                 let cp = other as u32;
                 out.push_str(&format!("\\u{{{:x}}}", cp));
@@ -314,7 +345,6 @@ fn fmt_string_lit(out: &mut String, s: &str) {
             other => out.push(other),
         }
     }
-    out.push('"');
 }
 
 fn fmt_import_path(out: &mut String, path: &[Ident]) {
@@ -336,18 +366,54 @@ fn fmt_params(out: &mut String, params: &[aura_ast::Param]) {
         }
         out.push_str(&p.name.node);
         out.push_str(": ");
+        match p.borrow {
+            Some(BorrowKind::Shared) => out.push('&'),
+            Some(BorrowKind::Mut) => out.push_str("&mut "),
+            None => {}
+        }
         fmt_type_ref(out, &p.ty);
     }
 }
 
+fn fmt_type_params(out: &mut String, params: &[TypeParam]) {
+    if params.is_empty() {
+        return;
+    }
+    out.push('<');
+    for (i, p) in params.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        out.push_str(&p.name.node);
+        if let Some(b) = &p.bound {
+            out.push_str(": ");
+            out.push_str(&b.node);
+        }
+    }
+    out.push('>');
+}
+
 fn fmt_cell_def(out: &mut String, indent: usize, s: &CellDef) {
+    fmt_doc(out, indent, s.doc.as_deref());
     indent_line(out, indent);
+    if s.is_pub {
+        out.push_str("pub ");
+    }
+    if s.is_test {
+        out.push_str("test ");
+    }
     out.push_str("cell ");
     out.push_str(&s.name.node.replace('.', "::"));
+    fmt_type_params(out, &s.type_params);
     out.push('(');
     fmt_params(out, &s.params);
     out.push(')');
 
+    if let Some(ret) = &s.ret {
+        out.push_str(" -> ");
+        fmt_type_ref(out, ret);
+    }
+
     if let Some(flow) = s.flow {
         out.push(' ');
         out.push_str(match flow {
@@ -356,6 +422,22 @@ fn fmt_cell_def(out: &mut String, indent: usize, s: &CellDef) {
         });
     }
 
+    if let Some(effects) = &s.effects {
+        out.push_str(" uses [");
+        for (i, e) in effects.node.iter().enumerate() {
+            if i > 0 {
+                out.push_str(", ");
+            }
+            out.push_str(&e.node);
+        }
+        out.push(']');
+    }
+
+    if let Some(dec) = &s.decreases {
+        out.push_str(" decreases ");
+        fmt_expr(out, dec, Prec::Lowest);
+    }
+
     out.push_str(":\n");
     fmt_block_indent(out, indent + 1, &s.body);
 }
@@ -414,6 +496,22 @@ fn fmt_while(out: &mut String, indent: usize, s: &WhileStmt) {
     fmt_block_indent(out, indent + 1, &s.body);
 }
 
+fn fmt_for(out: &mut String, indent: usize, s: &ForStmt) {
+    indent_line(out, indent);
+    out.push_str("for ");
+    out.push_str(&s.var.node);
+    out.push_str(" in ");
+    fmt_expr(out, &s.start, Prec::Lowest);
+    out.push_str("..");
+    fmt_expr(out, &s.end, Prec::Lowest);
+    if let Some(inv) = &s.invariant {
+        out.push_str(" invariant ");
+        fmt_expr(out, inv, Prec::Lowest);
+    }
+    out.push_str(":\n");
+    fmt_block_indent(out, indent + 1, &s.body);
+}
+
 fn fmt_block_indent(out: &mut String, indent: usize, block: &Block) {
     for stmt in &block.stmts {
         fmt_stmt(out, indent, stmt);
@@ -427,6 +525,19 @@ fn fmt_block_indent(out: &mut String, indent: usize, block: &Block) {
 }
 
 fn fmt_type_ref(out: &mut String, t: &TypeRef) {
+    if t.name.node == "Tuple" && t.args.len() >= 2 {
+        out.push('(');
+        for (i, a) in t.args.iter().enumerate() {
+            if i > 0 {
+                out.push_str(", ");
+            }
+            if let TypeArg::Type(inner) = a {
+                fmt_type_ref(out, inner);
+            }
+        }
+        out.push(')');
+        return;
+    }
     out.push_str(&t.name.node);
     if !t.args.is_empty() {
         out.push('<');
@@ -467,6 +578,10 @@ enum Prec {
     Or,
     And,
     Cmp,
+    BitOr,
+    BitXor,
+    BitAnd,
+    Shift,
     Add,
     Mul,
     Unary,
@@ -480,6 +595,10 @@ fn bin_prec(op: &BinOp) -> Prec {
         BinOp::Or => Prec::Or,
         BinOp::And => Prec::And,
         BinOp::Eq | BinOp::Ne | BinOp::Lt | BinOp::Gt | BinOp::Le | BinOp::Ge => Prec::Cmp,
+        BinOp::BitOr => Prec::BitOr,
+        BinOp::BitXor => Prec::BitXor,
+        BinOp::BitAnd => Prec::BitAnd,
+        BinOp::Shl | BinOp::Shr => Prec::Shift,
         BinOp::Add | BinOp::Sub => Prec::Add,
         BinOp::Mul | BinOp::Div => Prec::Mul,
     }
@@ -493,20 +612,19 @@ fn fmt_expr(out: &mut String, expr: &Expr, parent_prec: Prec) {
     match &expr.kind {
         ExprKind::Ident(id) => out.push_str(&id.node),
         ExprKind::IntLit(n) => out.push_str(&n.to_string()),
-        ExprKind::StringLit(s) => {
+        // `{:?}` always keeps a fraction or exponent, so the output re-lexes as a float.
+        ExprKind::FloatLit(v) => out.push_str(&format!("{v:?}")),
+        ExprKind::StringLit(s) => fmt_string_lit(out, s),
+        ExprKind::InterpolatedString(parts) => {
             out.push('"');
-            for ch in s.chars() {
-                match ch {
-                    '\n' => out.push_str("\\n"),
-                    '\t' => out.push_str("\\t"),
-                    '\r' => out.push_str("\\r"),
-                    '"' => out.push_str("\\\""),
-                    '\\' => out.push_str("\\\\"),
-                    other if other.is_control() => {
-                        let cp = other as u32;
-                        out.push_str(&format!("\\u{{{:x}}}", cp));
+            for part in parts {
+                match part {
+                    InterpPart::Lit(l) => fmt_string_chars(out, l),
+                    InterpPart::Expr(e) => {
+                        out.push('{');
+                        fmt_expr(out, e, Prec::Lowest);
+                        out.push('}');
                     }
-                    other => out.push(other),
                 }
             }
             out.push('"');
@@ -563,6 +681,13 @@ fn fmt_expr(out: &mut String, expr: &Expr, parent_prec: Prec) {
                 out.push(')');
             }
         }
+        ExprKind::Borrow { kind, expr: inner } => {
+            out.push_str(match kind {
+                BorrowKind::Shared => "&",
+                BorrowKind::Mut => "&mut ",
+            });
+            fmt_expr(out, inner, Prec::Unary);
+        }
         ExprKind::Binary { left, op, right } => {
             let my = bin_prec(op);
             let parens = needs_parens(parent_prec, my);
@@ -584,6 +709,11 @@ fn fmt_expr(out: &mut String, expr: &Expr, parent_prec: Prec) {
                 BinOp::Ge => ">=",
                 BinOp::And => "&&",
                 BinOp::Or => "||",
+                BinOp::BitAnd => "&",
+                BinOp::BitOr => "|",
+                BinOp::BitXor => "^",
+                BinOp::Shl => "<<",
+                BinOp::Shr => ">>",
             });
             out.push(' ');
             fmt_expr(out, right, my);
@@ -603,6 +733,65 @@ fn fmt_expr(out: &mut String, expr: &Expr, parent_prec: Prec) {
             out.push_str(": ");
             fmt_expr(out, body, Prec::Lowest);
         }
+        ExprKind::Tuple(elems) => {
+            out.push('(');
+            for (i, e) in elems.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                fmt_expr(out, e, Prec::Lowest);
+            }
+            out.push(')');
+        }
+        ExprKind::TupleIndex { base, index } => {
+            let my = Prec::Postfix;
+            let parens = needs_parens(parent_prec, my);
+            if parens {
+                out.push('(');
+            }
+            fmt_expr(out, base, my);
+            out.push('.');
+            out.push_str(&index.to_string());
+            if parens {
+                out.push(')');
+            }
+        }
+        ExprKind::ListLit(elems) => {
+            out.push('[');
+            for (i, e) in elems.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                fmt_expr(out, e, Prec::Lowest);
+            }
+            out.push(']');
+        }
+        ExprKind::MapLit(entries) => {
+            out.push('{');
+            for (i, (k, v)) in entries.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                fmt_expr(out, k, Prec::Lowest);
+                out.push_str(": ");
+                fmt_expr(out, v, Prec::Lowest);
+            }
+            out.push('}');
+        }
+        ExprKind::Index { base, index } => {
+            let my = Prec::Postfix;
+            let parens = needs_parens(parent_prec, my);
+            if parens {
+                out.push('(');
+            }
+            fmt_expr(out, base, my);
+            out.push('[');
+            fmt_expr(out, index, Prec::Lowest);
+            out.push(']');
+            if parens {
+                out.push(')');
+            }
+        }
         ExprKind::Member { base, member } => {
             let my = Prec::Postfix;
             let parens = needs_parens(parent_prec, my);
@@ -708,6 +897,19 @@ fn fmt_brace_block(out: &mut String, indent: usize, block: &Block) {
     out.push('}');
 }
 
+fn fmt_doc(out: &mut String, indent: usize, doc: Option<&str>) {
+    let Some(doc) = doc else { return };
+    for line in doc.split('\n') {
+        indent_line(out, indent);
+        out.push_str("##");
+        if !line.is_empty() {
+            out.push(' ');
+            out.push_str(line);
+        }
+        out.push('\n');
+    }
+}
+
 fn indent_line(out: &mut String, indent: usize) {
     for _ in 0..indent {
         out.push_str(INDENT);
@@ -0,0 +1,506 @@
+#![forbid(unsafe_code)]
+
+//! Incremental re-parsing keyed by changed byte ranges.
+//!
+//! Top-level statements always start in column 0 and the lexer works line by line, so every
+//! top-level statement can be re-lexed and re-parsed from its own run of whole lines. After
+//! an edit only the statements whose lines were touched are parsed again; statements before
+//! the edit are reused as-is and statements after it are reused with their spans shifted.
+
+use std::ops::Range;
+
+use aura_ast::{
+    Block, CallArg, Expr, ExprKind, Ident, InterpPart, Param, Pattern, Program, QuantBinder, RangeConstraint,
+    Span, Stmt, TypeArg, TypeParam, TypeRef,
+};
+
+use crate::ParseConfig;
+
+/// Replace the bytes `start..end` with `text`.
+///
+/// Offsets refer to the document as it is when the edit is applied, so a list of edits is
+/// applied in order, like LSP content changes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextEdit {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct Reparse {
+    /// The document after applying every edit.
+    pub text: String,
+    pub program: Program,
+    /// Indices into `program.stmts` of the statements that were parsed again; all others
+    /// were reused from the previous program.
+    pub reparsed: Range<usize>,
+}
+
+/// Re-parse `old_text` after `edits`, reusing the top-level statements of `prev` that the
+/// edits did not touch.
+///
+/// `prev` must be the result of parsing `old_text` with the same `config`. When the touched
+/// region does not parse on its own, or macros are enabled (expansion rewrites the whole
+/// program), this falls back to a full parse so results and errors match `parse_source`.
+pub fn reparse_incremental(
+    prev: &Program,
+    old_text: &str,
+    edits: &[TextEdit],
+    config: &ParseConfig,
+) -> miette::Result<Reparse> {
+    let mut text = old_text.to_string();
+    // Changed window `lo..hi` in current-text coordinates; bytes outside it equal `old_text`.
+    let mut window: Option<(usize, usize)> = None;
+    for edit in edits {
+        if edit.start > edit.end
+            || edit.end > text.len()
+            || !text.is_char_boundary(edit.start)
+            || !text.is_char_boundary(edit.end)
+        {
+            return Err(miette::miette!(
+                "invalid edit range {}..{} for a document of {} bytes",
+                edit.start,
+                edit.end,
+                text.len()
+            ));
+        }
+        text.replace_range(edit.start..edit.end, &edit.text);
+        let grown = edit.text.len() as isize - (edit.end - edit.start) as isize;
+        window = Some(match window {
+            None => (edit.start, edit.start + edit.text.len()),
+            Some((lo, hi)) => (lo.min(edit.start), shift(hi.max(edit.end), grown)),
+        });
+    }
+
+    let Some((lo, new_hi)) = window else {
+        return Ok(Reparse {
+            text,
+            program: prev.clone(),
+            reparsed: 0..0,
+        });
+    };
+    if config.has_feature("macros") {
+        return full_reparse(text, config);
+    }
+
+    let delta = text.len() as isize - old_text.len() as isize;
+    let old_hi = shift(new_hi, -delta);
+
+    // `bounds[i]..bounds[i + 1]` is the run of whole lines owned by statement `i`; the last
+    // run holds whatever trails the final statement.
+    let n = prev.stmts.len();
+    let mut bounds = Vec::with_capacity(n + 2);
+    bounds.push(0);
+    for stmt in &prev.stmts {
+        let sp = stmt.span();
+        let end = next_line_start(old_text, sp.offset() + sp.len()).max(*bounds.last().unwrap_or(&0));
+        bounds.push(end);
+    }
+    bounds.push(old_text.len());
+
+    let touched = |i: usize| bounds[i] <= old_hi && lo <= bounds[i + 1];
+    let Some(first) = (0..=n).find(|&i| touched(i)) else {
+        return full_reparse(text, config);
+    };
+    let last = (first..=n).rev().find(|&i| touched(i)).unwrap_or(first);
+
+    let start = bounds[first];
+    let end = shift(bounds[last + 1], delta);
+    let fresh = match crate::parse_source_at(&text[start..end], start, config) {
+        Ok(p) => p.stmts,
+        Err(_) => return full_reparse(text, config),
+    };
+
+    let mut stmts = Vec::with_capacity(n + fresh.len());
+    stmts.extend(prev.stmts[..first].iter().cloned());
+    let reparsed = stmts.len()..stmts.len() + fresh.len();
+    stmts.extend(fresh);
+    for stmt in prev.stmts.iter().skip(last + 1) {
+        let mut stmt = stmt.clone();
+        shift_stmt(&mut stmt, delta);
+        stmts.push(stmt);
+    }
+
+    Ok(Reparse {
+        text,
+        program: Program { stmts },
+        reparsed,
+    })
+}
+
+fn full_reparse(text: String, config: &ParseConfig) -> miette::Result<Reparse> {
+    let program = crate::parse_source_with_config(&text, config)?;
+    let reparsed = 0..program.stmts.len();
+    Ok(Reparse {
+        text,
+        program,
+        reparsed,
+    })
+}
+
+/// `offset` itself when it starts a line, otherwise the start of the following line.
+fn next_line_start(text: &str, offset: usize) -> usize {
+    let offset = offset.min(text.len());
+    if offset == 0 || text.as_bytes()[offset - 1] == b'\n' {
+        return offset;
+    }
+    text[offset..]
+        .find('\n')
+        .map(|i| offset + i + 1)
+        .unwrap_or(text.len())
+}
+
+fn shift(offset: usize, delta: isize) -> usize {
+    offset.saturating_add_signed(delta)
+}
+
+fn shift_span(span: &mut Span, delta: isize) {
+    *span = aura_ast::span(shift(span.offset(), delta), span.len());
+}
+
+fn shift_ident(id: &mut Ident, delta: isize) {
+    shift_span(&mut id.span, delta);
+}
+
+fn shift_stmt(stmt: &mut Stmt, d: isize) {
+    match stmt {
+        Stmt::Import(s) => {
+            shift_span(&mut s.span, d);
+            s.path.iter_mut().for_each(|p| shift_ident(p, d));
+        }
+        Stmt::MacroDef(s) => {
+            shift_span(&mut s.span, d);
+            shift_ident(&mut s.name, d);
+            s.params.iter_mut().for_each(|p| shift_ident(p, d));
+            shift_block(&mut s.body, d);
+        }
+        Stmt::TypeAlias(s) => {
+            shift_span(&mut s.span, d);
+            shift_ident(&mut s.name, d);
+            s.params.iter_mut().for_each(|p| shift_type_param(p, d));
+            shift_type_ref(&mut s.target, d);
+        }
+        Stmt::TraitDef(s) => {
+            shift_span(&mut s.span, d);
+            shift_ident(&mut s.name, d);
+        }
+        Stmt::RecordDef(s) => {
+            shift_span(&mut s.span, d);
+            shift_ident(&mut s.name, d);
+            s.params.iter_mut().for_each(|p| shift_type_param(p, d));
+            for f in &mut s.fields {
+                shift_span(&mut f.span, d);
+                shift_ident(&mut f.name, d);
+                shift_type_ref(&mut f.ty, d);
+                if let Some(e) = &mut f.default {
+                    shift_expr(e, d);
+                }
+            }
+        }
+        Stmt::EnumDef(s) => {
+            shift_span(&mut s.span, d);
+            shift_ident(&mut s.name, d);
+            s.params.iter_mut().for_each(|p| shift_type_param(p, d));
+            for v in &mut s.variants {
+                shift_span(&mut v.span, d);
+                shift_ident(&mut v.name, d);
+                for f in &mut v.fields {
+                    shift_span(&mut f.span, d);
+                    shift_ident(&mut f.name, d);
+                    shift_type_ref(&mut f.ty, d);
+                }
+            }
+        }
+        Stmt::StrandDef(s) => {
+            shift_span(&mut s.span, d);
+            shift_ident(&mut s.name, d);
+            if let Some(t) = &mut s.ty {
+                shift_type_ref(t, d);
+            }
+            if let Some(e) = &mut s.where_clause {
+                shift_expr(e, d);
+            }
+            shift_expr(&mut s.expr, d);
+            if let Some(p) = &mut s.pattern {
+                shift_pattern(p, d);
+            }
+        }
+        Stmt::CellDef(s) => {
+            shift_span(&mut s.span, d);
+            shift_ident(&mut s.name, d);
+            s.type_params.iter_mut().for_each(|p| shift_type_param(p, d));
+            s.params.iter_mut().for_each(|p| shift_param(p, d));
+            if let Some(t) = &mut s.ret {
+                shift_type_ref(t, d);
+            }
+            if let Some(effects) = &mut s.effects {
+                shift_span(&mut effects.span, d);
+                effects.node.iter_mut().for_each(|e| shift_ident(e, d));
+            }
+            if let Some(e) = &mut s.decreases {
+                shift_expr(e, d);
+            }
+            shift_block(&mut s.body, d);
+        }
+        Stmt::ExternCell(s) => {
+            shift_span(&mut s.span, d);
+            shift_ident(&mut s.name, d);
+            s.params.iter_mut().for_each(|p| shift_param(p, d));
+            shift_type_ref(&mut s.ret, d);
+        }
+        Stmt::UnsafeBlock(s) => {
+            shift_span(&mut s.span, d);
+            shift_block(&mut s.body, d);
+        }
+        Stmt::Scope(s) => {
+            shift_span(&mut s.span, d);
+            if let Some(token) = &mut s.token {
+                shift_ident(token, d);
+            }
+            shift_block(&mut s.body, d);
+        }
+        Stmt::Region(s) => {
+            shift_span(&mut s.span, d);
+            shift_block(&mut s.body, d);
+        }
+        Stmt::Layout(s) => {
+            shift_span(&mut s.span, d);
+            shift_block(&mut s.body, d);
+        }
+        Stmt::Render(s) => {
+            shift_span(&mut s.span, d);
+            shift_block(&mut s.body, d);
+        }
+        Stmt::Prop(s) => {
+            shift_span(&mut s.span, d);
+            shift_ident(&mut s.name, d);
+            shift_expr(&mut s.expr, d);
+        }
+        Stmt::Assign(s) => {
+            shift_span(&mut s.span, d);
+            shift_ident(&mut s.target, d);
+            shift_expr(&mut s.expr, d);
+        }
+        Stmt::If(s) => {
+            shift_span(&mut s.span, d);
+            shift_expr(&mut s.cond, d);
+            shift_block(&mut s.then_block, d);
+            if let Some(b) = &mut s.else_block {
+                shift_block(b, d);
+            }
+        }
+        Stmt::Match(s) => {
+            shift_span(&mut s.span, d);
+            shift_expr(&mut s.scrutinee, d);
+            for arm in &mut s.arms {
+                shift_span(&mut arm.span, d);
+                shift_pattern(&mut arm.pat, d);
+                shift_block(&mut arm.body, d);
+            }
+        }
+        Stmt::While(s) => {
+            shift_span(&mut s.span, d);
+            shift_expr(&mut s.cond, d);
+            if let Some(e) = &mut s.invariant {
+                shift_expr(e, d);
+            }
+            if let Some(e) = &mut s.decreases {
+                shift_expr(e, d);
+            }
+            shift_block(&mut s.body, d);
+        }
+        Stmt::For(s) => {
+            shift_span(&mut s.span, d);
+            shift_ident(&mut s.var, d);
+            shift_expr(&mut s.start, d);
+            shift_expr(&mut s.end, d);
+            if let Some(e) = &mut s.invariant {
+                shift_expr(e, d);
+            }
+            shift_block(&mut s.body, d);
+        }
+        Stmt::Break(s) => shift_span(&mut s.span, d),
+        Stmt::Continue(s) => shift_span(&mut s.span, d),
+        Stmt::Return(s) => {
+            shift_span(&mut s.span, d);
+            if let Some(e) = &mut s.value {
+                shift_expr(e, d);
+            }
+        }
+        Stmt::Defer(s) => {
+            shift_span(&mut s.span, d);
+            shift_block(&mut s.body, d);
+        }
+        Stmt::Requires(s) => {
+            shift_span(&mut s.span, d);
+            shift_expr(&mut s.expr, d);
+        }
+        Stmt::Ensures(s) => {
+            shift_span(&mut s.span, d);
+            shift_expr(&mut s.expr, d);
+        }
+        Stmt::Assert(s) => {
+            shift_span(&mut s.span, d);
+            shift_expr(&mut s.expr, d);
+        }
+        Stmt::Assume(s) => {
+            shift_span(&mut s.span, d);
+            shift_expr(&mut s.expr, d);
+        }
+        Stmt::MacroCall(s) => {
+            shift_span(&mut s.span, d);
+            shift_ident(&mut s.name, d);
+            s.args.iter_mut().for_each(|e| shift_expr(e, d));
+        }
+        Stmt::FlowBlock(s) => {
+            shift_span(&mut s.span, d);
+            shift_ident(&mut s.name, d);
+            shift_block(&mut s.body, d);
+        }
+        Stmt::ExprStmt(e) => shift_expr(e, d),
+    }
+}
+
+fn shift_block(b: &mut Block, d: isize) {
+    shift_span(&mut b.span, d);
+    b.stmts.iter_mut().for_each(|s| shift_stmt(s, d));
+    if let Some(e) = &mut b.yield_expr {
+        shift_expr(e, d);
+    }
+}
+
+fn shift_param(p: &mut Param, d: isize) {
+    shift_span(&mut p.span, d);
+    shift_ident(&mut p.name, d);
+    shift_type_ref(&mut p.ty, d);
+}
+
+fn shift_type_param(p: &mut TypeParam, d: isize) {
+    shift_span(&mut p.span, d);
+    shift_ident(&mut p.name, d);
+    if let Some(b) = &mut p.bound {
+        shift_ident(b, d);
+    }
+}
+
+fn shift_type_ref(t: &mut TypeRef, d: isize) {
+    shift_span(&mut t.span, d);
+    shift_ident(&mut t.name, d);
+    for arg in &mut t.args {
+        if let TypeArg::Type(inner) = arg {
+            shift_type_ref(inner, d);
+        }
+    }
+    if let Some(RangeConstraint { span, lo, hi }) = &mut t.range {
+        shift_span(span, d);
+        shift_expr(lo, d);
+        shift_expr(hi, d);
+    }
+}
+
+fn shift_pattern(p: &mut Pattern, d: isize) {
+    match p {
+        Pattern::Wildcard { span } | Pattern::IntLit { span, .. } | Pattern::StringLit { span, .. } => {
+            shift_span(span, d)
+        }
+        Pattern::Ctor { span, ty, variant, args } => {
+            shift_span(span, d);
+            shift_ident(ty, d);
+            shift_ident(variant, d);
+            args.iter_mut().for_each(|a| shift_pattern(a, d));
+        }
+        Pattern::Tuple { span, elems } => {
+            shift_span(span, d);
+            elems.iter_mut().for_each(|e| shift_pattern(e, d));
+        }
+        Pattern::Bind { span, name } => {
+            shift_span(span, d);
+            shift_ident(name, d);
+        }
+        Pattern::Or { span, alts } => {
+            shift_span(span, d);
+            alts.iter_mut().for_each(|a| shift_pattern(a, d));
+        }
+    }
+}
+
+fn shift_binders(binders: &mut [QuantBinder], d: isize) {
+    for b in binders {
+        shift_span(&mut b.span, d);
+        shift_ident(&mut b.name, d);
+        if let Some(t) = &mut b.ty {
+            shift_type_ref(t, d);
+        }
+    }
+}
+
+fn shift_expr(e: &mut Expr, d: isize) {
+    shift_span(&mut e.span, d);
+    match &mut e.kind {
+        ExprKind::Ident(id) => shift_ident(id, d),
+        ExprKind::IntLit(_) | ExprKind::FloatLit(_) | ExprKind::StringLit(_) => {}
+        ExprKind::InterpolatedString(parts) => {
+            for part in parts {
+                if let InterpPart::Expr(inner) = part {
+                    shift_expr(inner, d);
+                }
+            }
+        }
+        ExprKind::StyleLit { fields } => {
+            for (k, v) in fields {
+                shift_ident(k, d);
+                shift_expr(v, d);
+            }
+        }
+        ExprKind::RecordLit { name, fields } => {
+            shift_ident(name, d);
+            for (k, v) in fields {
+                shift_ident(k, d);
+                shift_expr(v, d);
+            }
+        }
+        ExprKind::Unary { expr, .. } | ExprKind::Borrow { expr, .. } => shift_expr(expr, d),
+        ExprKind::Binary { left, right, .. } | ExprKind::Flow { left, right, .. } => {
+            shift_expr(left, d);
+            shift_expr(right, d);
+        }
+        ExprKind::Member { base, member } => {
+            shift_expr(base, d);
+            shift_ident(member, d);
+        }
+        ExprKind::Tuple(elems) | ExprKind::ListLit(elems) => elems.iter_mut().for_each(|x| shift_expr(x, d)),
+        ExprKind::TupleIndex { base, .. } => shift_expr(base, d),
+        ExprKind::MapLit(entries) => {
+            for (k, v) in entries {
+                shift_expr(k, d);
+                shift_expr(v, d);
+            }
+        }
+        ExprKind::Index { base, index } => {
+            shift_expr(base, d);
+            shift_expr(index, d);
+        }
+        ExprKind::Call { callee, args, trailing } => {
+            shift_expr(callee, d);
+            for arg in args {
+                match arg {
+                    CallArg::Positional(v) => shift_expr(v, d),
+                    CallArg::Named { name, value } => {
+                        shift_ident(name, d);
+                        shift_expr(value, d);
+                    }
+                }
+            }
+            if let Some(b) = trailing {
+                shift_block(b, d);
+            }
+        }
+        ExprKind::Lambda { body, .. } => shift_block(body, d),
+        ExprKind::ForAll { binders, body } | ExprKind::Exists { binders, body } => {
+            shift_binders(binders, d);
+            shift_expr(body, d);
+        }
+    }
+}
+
@@ -2,15 +2,17 @@
 
 mod error;
 mod fmt;
+mod incremental;
 mod parser;
 pub mod pattern_compiler;
 
-use aura_lex::Lexer;
+use aura_lex::{Lexer, StrPart, Token, TokenKind};
 use miette::IntoDiagnostic;
 use std::collections::BTreeSet;
 
 pub use error::ParseError;
 pub use fmt::{format_expr, format_program};
+pub use incremental::{reparse_incremental, Reparse, TextEdit};
 pub use parser::Parser;
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
@@ -28,23 +30,57 @@ impl ParseConfig {
 }
 
 pub fn parse_source(src: &str) -> miette::Result<aura_ast::Program> {
-    let tokens = Lexer::new(src).lex().into_diagnostic()?;
-    let mut parser = Parser::new(&tokens);
+    let (tokens, trivia) = Lexer::new(src).lex_with_trivia().into_diagnostic()?;
+    let mut parser = Parser::new(&tokens).with_trivia(&trivia);
     parser.parse_program().into_diagnostic()
 }
 
 pub fn parse_source_with_config(src: &str, config: &ParseConfig) -> miette::Result<aura_ast::Program> {
-    let tokens = Lexer::new(src).lex().into_diagnostic()?;
-    let mut parser = Parser::new_with_config(&tokens, config);
+    let (tokens, trivia) = Lexer::new(src).lex_with_trivia().into_diagnostic()?;
+    let mut parser = Parser::new_with_config(&tokens, config).with_trivia(&trivia);
+    parser.parse_program().into_diagnostic()
+}
+
+/// Parse one file of a multi-file program whose spans start at byte `base`.
+///
+/// Giving every file its own disjoint offset range keeps spans unambiguous after the
+/// files' definitions are merged into one `Program`.
+pub fn parse_source_at(src: &str, base: usize, config: &ParseConfig) -> miette::Result<aura_ast::Program> {
+    let (mut tokens, mut trivia) = Lexer::new(src)
+        .lex_with_trivia()
+        .map_err(|mut e| {
+            e.span = aura_ast::span(e.span.offset() + base, e.span.len());
+            e
+        })
+        .into_diagnostic()?;
+    shift_tokens(&mut tokens, base);
+    for doc in &mut trivia.doc_comments {
+        doc.span = aura_ast::span(doc.span.offset() + base, doc.span.len());
+        doc.target += base;
+    }
+    let mut parser = Parser::new_with_config(&tokens, config).with_trivia(&trivia);
     parser.parse_program().into_diagnostic()
 }
 
+fn shift_tokens(tokens: &mut [Token], base: usize) {
+    for t in tokens {
+        t.span = aura_ast::span(t.span.offset() + base, t.span.len());
+        if let TokenKind::InterpString(parts) = &mut t.kind {
+            for part in parts {
+                if let StrPart::Expr(inner) = part {
+                    shift_tokens(inner, base);
+                }
+            }
+        }
+    }
+}
+
 /// Parse a source file while attempting to recover from errors.
 ///
 /// Returns a best-effort AST and a list of encountered `ParseError`s.
 pub fn parse_source_with_recovery(src: &str) -> miette::Result<(aura_ast::Program, Vec<ParseError>)> {
-    let tokens = Lexer::new(src).lex().into_diagnostic()?;
-    let mut parser = Parser::new(&tokens);
+    let (tokens, trivia) = Lexer::new(src).lex_with_trivia().into_diagnostic()?;
+    let mut parser = Parser::new(&tokens).with_trivia(&trivia);
     Ok(parser.parse_program_with_recovery())
 }
 
@@ -52,8 +88,8 @@ pub fn parse_source_with_recovery_config(
     src: &str,
     config: &ParseConfig,
 ) -> miette::Result<(aura_ast::Program, Vec<ParseError>)> {
-    let tokens = Lexer::new(src).lex().into_diagnostic()?;
-    let mut parser = Parser::new_with_config(&tokens, config);
+    let (tokens, trivia) = Lexer::new(src).lex_with_trivia().into_diagnostic()?;
+    let mut parser = Parser::new_with_config(&tokens, config).with_trivia(&trivia);
     Ok(parser.parse_program_with_recovery())
 }
 
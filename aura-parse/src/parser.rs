@@ -3,13 +3,13 @@
 use std::mem;
 
 use aura_ast::{
-    span_between, AssignStmt, BinOp, Block, CallArg, CellDef, ExternCell, Expr, ExprKind, FlowBlock,
-    FlowOp, Ident, IfStmt, ImportStmt, LayoutBlock, MatchArm, MatchStmt, Param, Pattern, Program,
+    span_between, AssignStmt, BinOp, Block, BorrowKind, CallArg, CellDef, ExternCell, Expr, ExprKind, FlowBlock,
+    FlowOp, Ident, IfStmt, ImportStmt, InterpPart, LayoutBlock, MatchArm, MatchStmt, Param, Pattern, Program,
     PropStmt, RangeConstraint, RenderBlock, Span, Stmt, StrandDef, TraitDef, TypeAlias, TypeArg,
     TypeRef, UnaryOp, WhileStmt, EnumDef, EnumFieldDef, EnumVariantDef, RecordDef, RecordFieldDef,
     TypeParam, MacroDef, MacroCall, Spanned,
 };
-use aura_lex::{Token, TokenKind};
+use aura_lex::{StrPart, Token, TokenKind, Trivia};
 
 use crate::error::ParseError;
 use crate::ParseConfig;
@@ -20,6 +20,14 @@ pub struct Parser<'a> {
     tokens: &'a [Token],
     idx: usize,
     config: ParseConfig,
+    /// `##` doc comments keyed by the byte offset of the token they document.
+    docs: HashMap<usize, String>,
+    /// Set by `parse_program_with_recovery`: blocks record their errors here and resynchronize
+    /// instead of failing the enclosing definition.
+    recovering: bool,
+    errors: Vec<ParseError>,
+    /// Values of the `const` declarations seen so far, so tensor shapes can name them.
+    consts: HashMap<String, u64>,
 }
 
 #[derive(Clone, Debug)]
@@ -132,6 +140,14 @@ fn expand_stmt(
             u.body = expand_block(u.body, macros, gensym_counter)?;
             Ok(vec![Stmt::UnsafeBlock(u)])
         }
+        Stmt::Scope(mut sc) => {
+            sc.body = expand_block(sc.body, macros, gensym_counter)?;
+            Ok(vec![Stmt::Scope(sc)])
+        }
+        Stmt::Region(mut r) => {
+            r.body = expand_block(r.body, macros, gensym_counter)?;
+            Ok(vec![Stmt::Region(r)])
+        }
         Stmt::Layout(mut l) => {
             l.body = expand_block(l.body, macros, gensym_counter)?;
             Ok(vec![Stmt::Layout(l)])
@@ -157,6 +173,14 @@ fn expand_stmt(
             w.body = expand_block(w.body, macros, gensym_counter)?;
             Ok(vec![Stmt::While(w)])
         }
+        Stmt::For(mut f) => {
+            f.body = expand_block(f.body, macros, gensym_counter)?;
+            Ok(vec![Stmt::For(f)])
+        }
+        Stmt::Defer(mut d) => {
+            d.body = expand_block(d.body, macros, gensym_counter)?;
+            Ok(vec![Stmt::Defer(d)])
+        }
 
         other => Ok(vec![other]),
     }
@@ -208,11 +232,31 @@ fn collect_binder_idents_in_block(block: &Block, rename: &mut HashMap<String, St
             Stmt::While(w) => {
                 collect_binder_idents_in_block(&w.body, rename, gensym_counter);
             }
+            Stmt::For(f) => {
+                let n = f.var.node.clone();
+                rename.entry(n.clone()).or_insert_with(|| {
+                    *gensym_counter += 1;
+                    format!("{n}__m{}", *gensym_counter)
+                });
+                collect_binder_idents_in_block(&f.body, rename, gensym_counter);
+            }
             Stmt::UnsafeBlock(u) => {
                 collect_binder_idents_in_block(&u.body, rename, gensym_counter);
             }
+            Stmt::Scope(sc) => {
+                if let Some(token) = &sc.token {
+                    let n = token.node.clone();
+                    rename.entry(n.clone()).or_insert_with(|| {
+                        *gensym_counter += 1;
+                        format!("{n}__m{}", *gensym_counter)
+                    });
+                }
+                collect_binder_idents_in_block(&sc.body, rename, gensym_counter);
+            }
+            Stmt::Region(r) => collect_binder_idents_in_block(&r.body, rename, gensym_counter),
             Stmt::Layout(l) => collect_binder_idents_in_block(&l.body, rename, gensym_counter),
             Stmt::Render(r) => collect_binder_idents_in_block(&r.body, rename, gensym_counter),
+            Stmt::Defer(d) => collect_binder_idents_in_block(&d.body, rename, gensym_counter),
             Stmt::MacroDef(_) | Stmt::MacroCall(_) => {}
             _ => {}
         }
@@ -241,9 +285,11 @@ fn rewrite_stmt(stmt: &Stmt, subst: &HashMap<String, Expr>, rename: &HashMap<Str
                 span: sd.span,
                 name,
                 mutable: sd.mutable,
+                is_const: sd.is_const,
                 ty: sd.ty.clone(),
                 where_clause,
                 expr,
+                pattern: sd.pattern.clone(),
             })
         }
         Stmt::Assign(a) => {
@@ -282,10 +328,27 @@ fn rewrite_stmt(stmt: &Stmt, subst: &HashMap<String, Expr>, rename: &HashMap<Str
             decreases: w.decreases.as_ref().map(|e| rewrite_expr(e, subst, rename)),
             body: rewrite_block(&w.body, subst, rename),
         }),
+        Stmt::For(f) => Stmt::For(aura_ast::ForStmt {
+            span: f.span,
+            var: rewrite_ident(&f.var, subst, rename),
+            start: rewrite_expr(&f.start, subst, rename),
+            end: rewrite_expr(&f.end, subst, rename),
+            invariant: f.invariant.as_ref().map(|e| rewrite_expr(e, subst, rename)),
+            body: rewrite_block(&f.body, subst, rename),
+        }),
         Stmt::UnsafeBlock(u) => Stmt::UnsafeBlock(aura_ast::UnsafeBlock {
             span: u.span,
             body: rewrite_block(&u.body, subst, rename),
         }),
+        Stmt::Scope(sc) => Stmt::Scope(aura_ast::ScopeBlock {
+            span: sc.span,
+            token: sc.token.as_ref().map(|t| rewrite_ident(t, subst, rename)),
+            body: rewrite_block(&sc.body, subst, rename),
+        }),
+        Stmt::Region(r) => Stmt::Region(aura_ast::RegionBlock {
+            span: r.span,
+            body: rewrite_block(&r.body, subst, rename),
+        }),
         Stmt::Layout(l) => Stmt::Layout(aura_ast::LayoutBlock {
             span: l.span,
             body: rewrite_block(&l.body, subst, rename),
@@ -294,6 +357,10 @@ fn rewrite_stmt(stmt: &Stmt, subst: &HashMap<String, Expr>, rename: &HashMap<Str
             span: r.span,
             body: rewrite_block(&r.body, subst, rename),
         }),
+        Stmt::Defer(d) => Stmt::Defer(aura_ast::DeferStmt {
+            span: d.span,
+            body: rewrite_block(&d.body, subst, rename),
+        }),
         Stmt::Requires(r) => Stmt::Requires(aura_ast::RequiresStmt {
             span: r.span,
             expr: rewrite_expr(&r.expr, subst, rename),
@@ -333,7 +400,20 @@ fn rewrite_expr(expr: &Expr, subst: &HashMap<String, Expr>, rename: &HashMap<Str
             kind: ExprKind::Ident(rewrite_ident(id, subst, rename)),
         },
         ExprKind::IntLit(n) => Expr { span: expr.span, kind: ExprKind::IntLit(*n) },
+        ExprKind::FloatLit(v) => Expr { span: expr.span, kind: ExprKind::FloatLit(*v) },
         ExprKind::StringLit(s) => Expr { span: expr.span, kind: ExprKind::StringLit(s.clone()) },
+        ExprKind::InterpolatedString(parts) => Expr {
+            span: expr.span,
+            kind: ExprKind::InterpolatedString(
+                parts
+                    .iter()
+                    .map(|p| match p {
+                        InterpPart::Lit(l) => InterpPart::Lit(l.clone()),
+                        InterpPart::Expr(e) => InterpPart::Expr(rewrite_expr(e, subst, rename)),
+                    })
+                    .collect(),
+            ),
+        },
         ExprKind::StyleLit { fields } => Expr {
             span: expr.span,
             kind: ExprKind::StyleLit {
@@ -360,6 +440,13 @@ fn rewrite_expr(expr: &Expr, subst: &HashMap<String, Expr>, rename: &HashMap<Str
                 expr: Box::new(rewrite_expr(e, subst, rename)),
             },
         },
+        ExprKind::Borrow { kind, expr: e } => Expr {
+            span: expr.span,
+            kind: ExprKind::Borrow {
+                kind: *kind,
+                expr: Box::new(rewrite_expr(e, subst, rename)),
+            },
+        },
         ExprKind::Binary { left, op, right } => Expr {
             span: expr.span,
             kind: ExprKind::Binary {
@@ -375,6 +462,37 @@ fn rewrite_expr(expr: &Expr, subst: &HashMap<String, Expr>, rename: &HashMap<Str
                 member: rewrite_ident(member, subst, rename),
             },
         },
+        ExprKind::Tuple(elems) => Expr {
+            span: expr.span,
+            kind: ExprKind::Tuple(elems.iter().map(|e| rewrite_expr(e, subst, rename)).collect()),
+        },
+        ExprKind::TupleIndex { base, index } => Expr {
+            span: expr.span,
+            kind: ExprKind::TupleIndex {
+                base: Box::new(rewrite_expr(base, subst, rename)),
+                index: *index,
+            },
+        },
+        ExprKind::ListLit(elems) => Expr {
+            span: expr.span,
+            kind: ExprKind::ListLit(elems.iter().map(|e| rewrite_expr(e, subst, rename)).collect()),
+        },
+        ExprKind::MapLit(entries) => Expr {
+            span: expr.span,
+            kind: ExprKind::MapLit(
+                entries
+                    .iter()
+                    .map(|(k, v)| (rewrite_expr(k, subst, rename), rewrite_expr(v, subst, rename)))
+                    .collect(),
+            ),
+        },
+        ExprKind::Index { base, index } => Expr {
+            span: expr.span,
+            kind: ExprKind::Index {
+                base: Box::new(rewrite_expr(base, subst, rename)),
+                index: Box::new(rewrite_expr(index, subst, rename)),
+            },
+        },
         ExprKind::Call { callee, args, trailing } => Expr {
             span: expr.span,
             kind: ExprKind::Call {
@@ -438,6 +556,10 @@ impl<'a> Parser<'a> {
             tokens,
             idx: 0,
             config: ParseConfig::default(),
+            docs: HashMap::new(),
+            recovering: false,
+            errors: Vec::new(),
+            consts: HashMap::new(),
         }
     }
 
@@ -446,9 +568,23 @@ impl<'a> Parser<'a> {
             tokens,
             idx: 0,
             config: config.clone(),
+            docs: HashMap::new(),
+            recovering: false,
+            errors: Vec::new(),
+            consts: HashMap::new(),
         }
     }
 
+    /// Attach `##` doc comments from the lexer to the definitions they precede.
+    pub fn with_trivia(mut self, trivia: &Trivia) -> Self {
+        self.docs = trivia
+            .doc_comments
+            .iter()
+            .map(|d| (d.target, d.text.clone()))
+            .collect();
+        self
+    }
+
     pub fn parse_program(&mut self) -> Result<Program, ParseError> {
         let mut stmts = Vec::new();
         while !self.at(TokenKind::Eof) {
@@ -468,14 +604,18 @@ impl<'a> Parser<'a> {
 
     /// Parse a program while attempting to recover from errors.
     ///
-    /// Recovery strategy (MVP): on a statement parse error, skip tokens until a
-    /// reasonable statement boundary (`Newline`, `Dedent`, or `Eof`), then continue.
+    /// Recovery strategy: a statement that fails to parse inside an indented block is
+    /// skipped up to the next statement boundary of that block (together with any
+    /// indented body it owns), so the enclosing cell keeps its remaining statements.
+    /// Brace-delimited bodies that run into the next top-level definition, or into the
+    /// end of the file, are closed implicitly. Top-level errors skip to the next
+    /// top-level statement.
     ///
     /// This is intended for IDE diagnostics (best-effort), not for producing a
     /// guaranteed-correct AST.
     pub fn parse_program_with_recovery(&mut self) -> (Program, Vec<ParseError>) {
+        self.recovering = true;
         let mut stmts = Vec::new();
-        let mut errors = Vec::new();
 
         while !self.at(TokenKind::Eof) {
             self.skip_newlines();
@@ -483,15 +623,18 @@ impl<'a> Parser<'a> {
                 break;
             }
 
+            let from = self.idx;
             match self.parse_stmt() {
                 Ok(stmt) => stmts.push(stmt),
                 Err(err) => {
-                    errors.push(err);
-                    self.recover_to_stmt_boundary();
+                    self.errors.push(err);
+                    self.recover_to_stmt_boundary(from, false);
                 }
             }
         }
 
+        let mut errors = mem::take(&mut self.errors);
+        errors.sort_by_key(|e| e.span.offset());
         let program = Program { stmts };
         if self.config.has_feature("macros") {
             match expand_macros(program.clone()) {
@@ -506,27 +649,106 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn recover_to_stmt_boundary(&mut self) {
-        // Avoid infinite loops if we're already at EOF.
-        if self.at(TokenKind::Eof) {
-            return;
+    /// In recovery mode, record `err` so the caller can resynchronize; otherwise fail with it.
+    fn recover_or_fail(&mut self, err: ParseError) -> Result<(), ParseError> {
+        if self.recovering {
+            self.errors.push(err);
+            Ok(())
+        } else {
+            Err(err)
+        }
+    }
+
+    /// Skip the rest of a broken statement that began at token `from`, including an
+    /// indented body that follows it.
+    ///
+    /// Stops before the `Dedent` (or, with `in_braces`, the `}`) that closes the enclosing
+    /// block, so the block itself still terminates normally.
+    fn recover_to_stmt_boundary(&mut self, from: usize, in_braces: bool) {
+        // The failing parse may already have consumed the line break that ends the statement.
+        if self.idx > from && matches!(self.tokens.get(self.idx - 1).map(|t| &t.kind), Some(TokenKind::Newline)) {
+            self.idx -= 1;
         }
 
-        // Skip until we hit a boundary token.
-        while !self.at(TokenKind::Eof) {
-            if self.at(TokenKind::Newline) || self.at(TokenKind::Dedent) {
-                break;
+        let mut depth = 0usize;
+        let mut braces = 0usize;
+        loop {
+            match self.peek_kind() {
+                None | Some(TokenKind::Eof) => break,
+                Some(TokenKind::Dedent) if depth == 0 => break,
+                Some(TokenKind::Dedent) => {
+                    self.next();
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                Some(TokenKind::Indent) => {
+                    self.next();
+                    depth += 1;
+                }
+                Some(TokenKind::Newline) if depth == 0 => {
+                    self.next();
+                    if !self.at(TokenKind::Indent) {
+                        break;
+                    }
+                }
+                Some(TokenKind::LBrace) => {
+                    self.next();
+                    braces += 1;
+                }
+                Some(TokenKind::RBrace) if depth == 0 && braces == 0 && in_braces => break,
+                Some(TokenKind::RBrace) => {
+                    self.next();
+                    braces = braces.saturating_sub(1);
+                }
+                Some(_) => {
+                    self.next();
+                }
             }
-            self.next();
         }
 
-        // Consume one trailing newline to make progress.
-        if self.at(TokenKind::Newline) {
+        // Always make progress, e.g. past a stray top-level `Dedent`.
+        if self.idx <= from && !self.at(TokenKind::Eof) {
+            self.idx = from;
             self.next();
         }
     }
 
+    /// Keywords that can only start a top-level definition; while recovering, reaching one
+    /// inside a brace-delimited body means its `}` is missing.
+    fn at_definition_keyword(&self) -> bool {
+        matches!(
+            self.peek_kind(),
+            Some(
+                TokenKind::KwCell
+                    | TokenKind::KwConst
+                    | TokenKind::KwType
+                    | TokenKind::KwImport
+                    | TokenKind::KwExtern
+                    | TokenKind::KwTrusted
+                    | TokenKind::KwTrait
+                    | TokenKind::KwMacro
+            )
+        )
+    }
+
     fn parse_stmt(&mut self) -> Result<Stmt, ParseError> {
+        let doc = self
+            .peek_span()
+            .and_then(|span| self.docs.get(&span.offset()).cloned());
+        let mut stmt = self.parse_stmt_kind()?;
+        match &mut stmt {
+            Stmt::CellDef(s) => s.doc = doc,
+            Stmt::ExternCell(s) => s.doc = doc,
+            Stmt::RecordDef(s) => s.doc = doc,
+            Stmt::EnumDef(s) => s.doc = doc,
+            _ => {}
+        }
+        Ok(stmt)
+    }
+
+    fn parse_stmt_kind(&mut self) -> Result<Stmt, ParseError> {
         match self.peek_kind() {
             Some(TokenKind::KwImport) => Ok(Stmt::Import(self.parse_import_stmt()?)),
             Some(TokenKind::KwMacro) => {
@@ -542,11 +764,28 @@ impl<'a> Parser<'a> {
             Some(TokenKind::KwTrait) => Ok(Stmt::TraitDef(self.parse_trait_def()?)),
             Some(TokenKind::KwType) => self.parse_type_stmt(),
             Some(TokenKind::KwVal) => Ok(Stmt::StrandDef(self.parse_strand_def()?)),
+            Some(TokenKind::KwConst) => Ok(Stmt::StrandDef(self.parse_const_def()?)),
             Some(TokenKind::KwExtern) | Some(TokenKind::KwTrusted) => {
                 Ok(Stmt::ExternCell(self.parse_extern_cell()?))
             }
             Some(TokenKind::KwCell) => Ok(Stmt::CellDef(self.parse_cell_def()?)),
+            // `test` is contextual so it stays usable as an identifier elsewhere.
+            Some(TokenKind::Ident(s))
+                if s == "test" && matches!(self.peek_kind_n(1), Some(TokenKind::KwCell)) =>
+            {
+                Ok(Stmt::CellDef(self.parse_cell_def()?))
+            }
+            // `pub` is contextual too; it only marks cells as visible outside their module.
+            Some(TokenKind::Ident(s)) if s == "pub" && self.at_pub_item() => self.parse_pub_item(),
             Some(TokenKind::KwUnsafe) => Ok(Stmt::UnsafeBlock(self.parse_unsafe_block()?)),
+            // `scope` is contextual like `pub`: only `scope:` or `scope name:` open a block.
+            Some(TokenKind::Ident(s)) if s == "scope" && self.at_scope_block() => {
+                Ok(Stmt::Scope(self.parse_scope_block()?))
+            }
+            // So is `region`: only `region {` opens a block.
+            Some(TokenKind::Ident(s)) if s == "region" && self.at_region_block() => {
+                Ok(Stmt::Region(self.parse_region_block()?))
+            }
             Some(TokenKind::KwLayout) => Ok(Stmt::Layout(self.parse_layout_block()?)),
             Some(TokenKind::KwRender) => Ok(Stmt::Render(self.parse_render_block()?)),
             Some(TokenKind::KwRequires) => {
@@ -572,6 +811,35 @@ impl<'a> Parser<'a> {
             Some(TokenKind::KwIf) => Ok(Stmt::If(self.parse_if_stmt()?)),
             Some(TokenKind::KwMatch) => Ok(Stmt::Match(self.parse_match_stmt()?)),
             Some(TokenKind::KwWhile) => Ok(Stmt::While(self.parse_while_stmt()?)),
+            Some(TokenKind::KwFor) => Ok(Stmt::For(self.parse_for_stmt()?)),
+            Some(TokenKind::KwBreak) => {
+                let t = self.expect(TokenKind::KwBreak)?;
+                self.expect_stmt_terminator()?;
+                Ok(Stmt::Break(aura_ast::BreakStmt { span: t.span }))
+            }
+            Some(TokenKind::KwContinue) => {
+                let t = self.expect(TokenKind::KwContinue)?;
+                self.expect_stmt_terminator()?;
+                Ok(Stmt::Continue(aura_ast::ContinueStmt { span: t.span }))
+            }
+            Some(TokenKind::KwReturn) => {
+                let t = self.expect(TokenKind::KwReturn)?;
+                let value = if self.at(TokenKind::Newline) || self.at(TokenKind::RBrace) || self.at(TokenKind::Eof) {
+                    None
+                } else {
+                    Some(self.parse_expr()?)
+                };
+                self.expect_stmt_terminator()?;
+                let span = value.as_ref().map_or(t.span, |v| join(t.span, v.span));
+                Ok(Stmt::Return(aura_ast::ReturnStmt { span, value }))
+            }
+            Some(TokenKind::KwDefer) => {
+                let t = self.expect(TokenKind::KwDefer)?;
+                let body = self.parse_brace_block()?;
+                self.expect_stmt_terminator()?;
+                let span = join(t.span, body.span);
+                Ok(Stmt::Defer(aura_ast::DeferStmt { span, body }))
+            }
             Some(TokenKind::Ident(_)) => {
                 if self.peek_kind_n(1).is_some_and(|k| matches!(k, TokenKind::Bang)) {
                     if !self.config.has_feature("macros") {
@@ -598,6 +866,12 @@ impl<'a> Parser<'a> {
                     Ok(Stmt::ExprStmt(expr))
                 }
             }
+            // Parenthesized/tuple expression statement, e.g. a trailing `(b, a)` yield.
+            Some(TokenKind::LParen) => {
+                let expr = self.parse_expr()?;
+                self.expect_stmt_terminator()?;
+                Ok(Stmt::ExprStmt(expr))
+            }
             _ => {
                 let span = self.peek_span().unwrap_or_else(|| span_between(0, 0));
                 Err(ParseError {
@@ -720,45 +994,8 @@ impl<'a> Parser<'a> {
         let start = self.expect(TokenKind::KwType)?;
         let name = self.expect_ident()?;
 
-        let mut params: Vec<TypeParam> = Vec::new();
-        if self.at(TokenKind::Lt) {
-            // Generic type parameters: `type Foo<T, U> = ...`
-            self.next();
-
-            if self.at(TokenKind::Gt) {
-                return Err(ParseError {
-                    message: "type parameter list cannot be empty".to_string(),
-                    span: self.peek_span().unwrap_or(name.span),
-                });
-            }
-
-            loop {
-                let p_name = self.expect_ident()?;
-                let bound = if self.at(TokenKind::Colon) {
-                    self.next();
-                    Some(self.expect_ident()?)
-                } else {
-                    None
-                };
-                let p_span = if let Some(b) = &bound {
-                    join(p_name.span, b.span)
-                } else {
-                    p_name.span
-                };
-                params.push(TypeParam {
-                    span: p_span,
-                    name: p_name,
-                    bound,
-                });
-                if self.at(TokenKind::Comma) {
-                    self.next();
-                    continue;
-                }
-                break;
-            }
-
-            self.expect(TokenKind::Gt)?;
-        }
+        // Generic type parameters: `type Foo<T, U> = ...`
+        let params = self.parse_type_params(name.span)?;
 
         self.expect(TokenKind::Eq)?;
         let target = self.parse_type_ref()?;
@@ -788,41 +1025,7 @@ impl<'a> Parser<'a> {
         let start = self.expect(TokenKind::KwType)?;
         let name = self.expect_ident()?;
 
-        let mut params: Vec<TypeParam> = Vec::new();
-        if self.at(TokenKind::Lt) {
-            self.next();
-            if self.at(TokenKind::Gt) {
-                return Err(ParseError {
-                    message: "type parameter list cannot be empty".to_string(),
-                    span: self.peek_span().unwrap_or(name.span),
-                });
-            }
-            loop {
-                let p_name = self.expect_ident()?;
-                let bound = if self.at(TokenKind::Colon) {
-                    self.next();
-                    Some(self.expect_ident()?)
-                } else {
-                    None
-                };
-                let p_span = if let Some(b) = &bound {
-                    join(p_name.span, b.span)
-                } else {
-                    p_name.span
-                };
-                params.push(TypeParam {
-                    span: p_span,
-                    name: p_name,
-                    bound,
-                });
-                if self.at(TokenKind::Comma) {
-                    self.next();
-                    continue;
-                }
-                break;
-            }
-            self.expect(TokenKind::Gt)?;
-        }
+        let params = self.parse_type_params(name.span)?;
 
         self.expect(TokenKind::Eq)?;
 
@@ -847,6 +1050,47 @@ impl<'a> Parser<'a> {
         }))
     }
 
+    /// Optional `<T, U: Bound>` parameter list after a type or cell name.
+    fn parse_type_params(&mut self, name_span: Span) -> Result<Vec<TypeParam>, ParseError> {
+        let mut params: Vec<TypeParam> = Vec::new();
+        if !self.at(TokenKind::Lt) {
+            return Ok(params);
+        }
+        self.next();
+        if self.at(TokenKind::Gt) {
+            return Err(ParseError {
+                message: "type parameter list cannot be empty".to_string(),
+                span: self.peek_span().unwrap_or(name_span),
+            });
+        }
+        loop {
+            let p_name = self.expect_ident()?;
+            let bound = if self.at(TokenKind::Colon) {
+                self.next();
+                Some(self.expect_ident()?)
+            } else {
+                None
+            };
+            let p_span = if let Some(b) = &bound {
+                join(p_name.span, b.span)
+            } else {
+                p_name.span
+            };
+            params.push(TypeParam {
+                span: p_span,
+                name: p_name,
+                bound,
+            });
+            if self.at(TokenKind::Comma) {
+                self.next();
+                continue;
+            }
+            break;
+        }
+        self.expect(TokenKind::Gt)?;
+        Ok(params)
+    }
+
     fn parse_record_def_after_header(
         &mut self,
         start_span: Span,
@@ -870,6 +1114,7 @@ impl<'a> Parser<'a> {
                 name,
                 params,
                 fields,
+                doc: None,
             });
         }
 
@@ -886,12 +1131,21 @@ impl<'a> Parser<'a> {
                     name,
                     params,
                     fields,
+                    doc: None,
                 });
             }
-            if self.at(TokenKind::Eof) {
-                return Err(ParseError {
+            if self.at(TokenKind::Eof) || (self.recovering && self.at_definition_keyword()) {
+                self.recover_or_fail(ParseError {
                     message: "unterminated record type; expected '}'".to_string(),
                     span: join(start_span, lb.span),
+                })?;
+                let span = join(start_span, self.prev_span().unwrap_or(lb.span));
+                return Ok(RecordDef {
+                    span,
+                    name,
+                    params,
+                    fields,
+                    doc: None,
                 });
             }
 
@@ -923,7 +1177,7 @@ impl<'a> Parser<'a> {
                 continue;
             }
 
-            if self.at(TokenKind::RBrace) {
+            if self.at(TokenKind::RBrace) || (self.recovering && self.at_definition_keyword()) {
                 continue;
             }
 
@@ -957,6 +1211,7 @@ impl<'a> Parser<'a> {
                 name,
                 params,
                 variants,
+                doc: None,
             });
         }
 
@@ -973,12 +1228,21 @@ impl<'a> Parser<'a> {
                     name,
                     params,
                     variants,
+                    doc: None,
                 });
             }
-            if self.at(TokenKind::Eof) {
-                return Err(ParseError {
+            if self.at(TokenKind::Eof) || (self.recovering && self.at_definition_keyword()) {
+                self.recover_or_fail(ParseError {
                     message: "unterminated enum type; expected '}'".to_string(),
                     span: join(start_span, lb.span),
+                })?;
+                let span = join(start_span, self.prev_span().unwrap_or(lb.span));
+                return Ok(EnumDef {
+                    span,
+                    name,
+                    params,
+                    variants,
+                    doc: None,
                 });
             }
 
@@ -1024,7 +1288,7 @@ impl<'a> Parser<'a> {
                 continue;
             }
 
-            if self.at(TokenKind::RBrace) {
+            if self.at(TokenKind::RBrace) || (self.recovering && self.at_definition_keyword()) {
                 continue;
             }
 
@@ -1057,7 +1321,14 @@ impl<'a> Parser<'a> {
         } else {
             false
         };
-        let name = self.expect_ident()?;
+        // Destructuring: `val (x, y) = expr`
+        let (name, pattern) = if self.at(TokenKind::LParen) {
+            let pat = self.parse_tuple_pattern()?;
+            let span = pat_span(&pat);
+            (Spanned::new(span, "_".to_string()), Some(pat))
+        } else {
+            (self.expect_ident()?, None)
+        };
         let mut ty = None;
         let mut where_clause = None;
         if self.at(TokenKind::Colon) {
@@ -1078,34 +1349,142 @@ impl<'a> Parser<'a> {
             span,
             name,
             mutable,
+            is_const: false,
             ty,
             where_clause,
             expr,
+            pattern,
+        })
+    }
+
+    /// `const NAME[: T] = expr`. Range and initializer checks happen in `aura-core`'s const
+    /// evaluator; here we only remember literal-foldable values for later tensor shapes.
+    fn parse_const_def(&mut self) -> Result<StrandDef, ParseError> {
+        let start = self.expect(TokenKind::KwConst)?;
+        let name = self.expect_ident()?;
+        let mut ty = None;
+        if self.at(TokenKind::Colon) {
+            self.next();
+            ty = Some(self.parse_type_ref()?);
+        }
+        self.expect(TokenKind::Eq)?;
+        let expr = self.parse_expr()?;
+        self.expect_stmt_terminator()?;
+        if let Ok(n) = eval_const_u64(&expr, &self.consts) {
+            self.consts.insert(name.node.clone(), n);
+        }
+        let span = join(start.span, expr.span);
+        Ok(StrandDef {
+            span,
+            name,
+            mutable: false,
+            is_const: true,
+            ty,
+            where_clause: None,
+            expr,
+            pattern: None,
         })
     }
 
+    fn at_pub_item(&self) -> bool {
+        match self.peek_kind_n(1) {
+            Some(TokenKind::KwCell) => true,
+            Some(TokenKind::Ident(s)) => {
+                s == "test" && matches!(self.peek_kind_n(2), Some(TokenKind::KwCell))
+            }
+            _ => false,
+        }
+    }
+
+    fn parse_pub_item(&mut self) -> Result<Stmt, ParseError> {
+        let pub_span = self.next().map(|t| t.span).expect("pub");
+        let mut cell = self.parse_cell_def()?;
+        cell.span = join(pub_span, cell.span);
+        cell.is_pub = true;
+        Ok(Stmt::CellDef(cell))
+    }
+
     fn parse_cell_def(&mut self) -> Result<CellDef, ParseError> {
+        let test_start = match self.peek_kind() {
+            Some(TokenKind::Ident(s)) if s == "test" => self.next().map(|t| t.span),
+            _ => None,
+        };
         let start = self.expect(TokenKind::KwCell)?;
+        let start_span = test_start.unwrap_or(start.span);
         let name = self.parse_qualified_ident()?;
+        let type_params = self.parse_type_params(name.span)?;
         self.expect(TokenKind::LParen)?;
         let params = self.parse_params()?;
         self.expect(TokenKind::RParen)?;
 
-        let flow = if self.at(TokenKind::Arrow) || self.at(TokenKind::TildeArrow) {
+        // `-> T:` declares the return type; a bare `->:` / `~>:` is the flow annotation.
+        let ret = if self.at(TokenKind::Arrow)
+            && !self.peek_kind_n(1).is_some_and(|k| matches!(k, TokenKind::Colon))
+            && !self.at_effect_list(1)
+            && !self.peek_kind_n(1).is_some_and(|k| matches!(k, TokenKind::KwDecreases))
+        {
+            self.next();
+            Some(self.parse_type_ref()?)
+        } else {
+            None
+        };
+
+        let flow = if ret.is_none() && (self.at(TokenKind::Arrow) || self.at(TokenKind::TildeArrow)) {
             Some(self.parse_flow_op()?)
         } else {
             None
         };
 
+        // `uses [net, io]` declares the cell's effects; `uses` stays an ordinary identifier elsewhere.
+        let effects = if self.at_effect_list(0) { Some(self.parse_effect_list()?) } else { None };
+
+        let decreases = if self.at(TokenKind::KwDecreases) {
+            self.next();
+            Some(self.parse_expr()?)
+        } else {
+            None
+        };
+
         self.expect(TokenKind::Colon)?;
         let body = self.parse_logic_block()?;
-        let span = join(start.span, body.span);
+        let span = join(start_span, body.span);
         Ok(CellDef {
             span,
             name,
+            type_params,
             params,
             flow,
+            ret,
+            effects,
+            decreases,
             body,
+            is_test: test_start.is_some(),
+            is_pub: false,
+            doc: None,
+        })
+    }
+
+    fn at_effect_list(&self, n: usize) -> bool {
+        matches!(self.peek_kind_n(n), Some(TokenKind::Ident(s)) if s == "uses")
+            && matches!(self.peek_kind_n(n + 1), Some(TokenKind::LBracket))
+    }
+
+    fn parse_effect_list(&mut self) -> Result<Spanned<Vec<Ident>>, ParseError> {
+        let start = self.next().map(|t| t.span).expect("uses");
+        self.expect(TokenKind::LBracket)?;
+        let mut effects: Vec<Ident> = Vec::new();
+        while !self.at(TokenKind::RBracket) {
+            effects.push(self.expect_ident()?);
+            if self.at(TokenKind::Comma) {
+                self.next();
+                continue;
+            }
+            break;
+        }
+        let end = self.expect(TokenKind::RBracket)?;
+        Ok(Spanned {
+            span: join(start, end.span),
+            node: effects,
         })
     }
 
@@ -1133,6 +1512,7 @@ impl<'a> Parser<'a> {
             name,
             params,
             ret,
+            doc: None,
         })
     }
 
@@ -1144,6 +1524,33 @@ impl<'a> Parser<'a> {
         Ok(aura_ast::UnsafeBlock { span, body })
     }
 
+    fn at_scope_block(&self) -> bool {
+        matches!(
+            (self.peek_kind_n(1), self.peek_kind_n(2)),
+            (Some(TokenKind::Colon), Some(TokenKind::Newline)) | (Some(TokenKind::Ident(_)), Some(TokenKind::Colon))
+        )
+    }
+
+    fn parse_scope_block(&mut self) -> Result<aura_ast::ScopeBlock, ParseError> {
+        let start = self.expect_ident()?;
+        let token = if self.at(TokenKind::Colon) { None } else { Some(self.expect_ident()?) };
+        self.expect(TokenKind::Colon)?;
+        let body = self.parse_logic_block()?;
+        let span = join(start.span, body.span);
+        Ok(aura_ast::ScopeBlock { span, token, body })
+    }
+
+    fn at_region_block(&self) -> bool {
+        matches!(self.peek_kind_n(1), Some(TokenKind::LBrace))
+    }
+
+    fn parse_region_block(&mut self) -> Result<aura_ast::RegionBlock, ParseError> {
+        let start = self.expect_ident()?;
+        let body = self.parse_brace_block()?;
+        let span = join(start.span, body.span);
+        Ok(aura_ast::RegionBlock { span, body })
+    }
+
     fn parse_qualified_ident(&mut self) -> Result<Ident, ParseError> {
         let first = self.expect_ident()?;
         let mut full = first.node.clone();
@@ -1193,36 +1600,57 @@ impl<'a> Parser<'a> {
                 });
             }
             if self.at(TokenKind::Eof) {
-                let span = indent_tok.span;
-                return Err(ParseError {
+                self.recover_or_fail(ParseError {
                     message: "unterminated block; expected dedent".to_string(),
+                    span: indent_tok.span,
+                })?;
+                let span = join(indent_tok.span, self.prev_span().unwrap_or(indent_tok.span));
+                return Ok(Block {
                     span,
+                    stmts,
+                    yield_expr,
                 });
             }
 
             if self.at(TokenKind::KwYield) {
+                let from = self.idx;
                 if yield_expr.is_some() {
-                    return Err(ParseError {
+                    self.recover_or_fail(ParseError {
                         message: "multiple yield statements in one block".to_string(),
                         span: self.peek_span().unwrap_or(indent_tok.span),
-                    });
+                    })?;
+                    self.recover_to_stmt_boundary(from, false);
+                    continue;
                 }
                 let y = self.next().unwrap();
-                let expr = self.parse_expr()?;
-                self.expect_stmt_terminator()?;
+                let expr = match self.parse_expr().and_then(|e| self.expect_stmt_terminator().map(|()| e)) {
+                    Ok(expr) => expr,
+                    Err(err) => {
+                        self.recover_or_fail(err)?;
+                        self.recover_to_stmt_boundary(from, false);
+                        continue;
+                    }
+                };
                 yield_expr = Some(expr);
                 // Enforce yield is last.
                 self.skip_newlines();
                 if !self.at(TokenKind::Dedent) {
-                    return Err(ParseError {
+                    self.recover_or_fail(ParseError {
                         message: "yield must be the last statement in a block".to_string(),
                         span: y.span,
-                    });
+                    })?;
                 }
                 continue;
             }
 
-            stmts.push(self.parse_stmt()?);
+            let from = self.idx;
+            match self.parse_stmt() {
+                Ok(stmt) => stmts.push(stmt),
+                Err(err) => {
+                    self.recover_or_fail(err)?;
+                    self.recover_to_stmt_boundary(from, false);
+                }
+            }
         }
     }
 
@@ -1234,7 +1662,13 @@ impl<'a> Parser<'a> {
         loop {
             let name = self.expect_ident()?;
             self.expect(TokenKind::Colon)?;
-            let mutable = if self.at(TokenKind::KwMut) {
+            let borrow = if self.at(TokenKind::Amp) {
+                self.next();
+                Some(self.parse_borrow_kind())
+            } else {
+                None
+            };
+            let mutable = if borrow.is_none() && self.at(TokenKind::KwMut) {
                 self.next();
                 true
             } else {
@@ -1246,6 +1680,7 @@ impl<'a> Parser<'a> {
                 span,
                 name,
                 mutable,
+                borrow,
                 ty,
             });
 
@@ -1261,6 +1696,16 @@ impl<'a> Parser<'a> {
         Ok(params)
     }
 
+    /// The rest of `&` or `&mut`, after the `&`.
+    fn parse_borrow_kind(&mut self) -> BorrowKind {
+        if self.at(TokenKind::KwMut) {
+            self.next();
+            BorrowKind::Mut
+        } else {
+            BorrowKind::Shared
+        }
+    }
+
     fn parse_assign_stmt(&mut self) -> Result<AssignStmt, ParseError> {
         let target = self.expect_ident()?;
         self.expect(TokenKind::Eq)?;
@@ -1314,19 +1759,55 @@ impl<'a> Parser<'a> {
                 break;
             }
 
-            let pat = self.parse_pattern()?;
-            self.expect(TokenKind::Colon)?;
+            let from = self.idx;
+            let pat = match self.parse_pattern().and_then(|p| self.expect(TokenKind::Colon).map(|_| p)) {
+                Ok(pat) => pat,
+                Err(err) => {
+                    // Skip the broken arm header together with its body.
+                    self.recover_or_fail(err)?;
+                    self.recover_to_stmt_boundary(from, false);
+                    continue;
+                }
+            };
             let body = self.parse_logic_block()?;
             let span = join(pat_span(&pat), body.span);
             arms.push(MatchArm { span, pat, body });
         }
 
+        if self.at(TokenKind::Eof) {
+            self.recover_or_fail(ParseError {
+                message: "unterminated match; expected dedent".to_string(),
+                span: start.span,
+            })?;
+            let span = join(start.span, self.prev_span().unwrap_or(start.span));
+            return Ok(MatchStmt { span, scrutinee, arms });
+        }
         let end = self.expect(TokenKind::Dedent)?;
         let span = join(start.span, end.span);
         Ok(MatchStmt { span, scrutinee, arms })
     }
 
     fn parse_pattern(&mut self) -> Result<Pattern, ParseError> {
+        self.parse_or_pattern(false)
+    }
+
+    /// `p | q | ...`. `allow_bind` admits a bare name as an alternative, which is only
+    /// meaningful inside a tuple or constructor pattern.
+    fn parse_or_pattern(&mut self, allow_bind: bool) -> Result<Pattern, ParseError> {
+        let first = self.parse_pattern_atom(allow_bind)?;
+        if !self.at(TokenKind::Pipe) {
+            return Ok(first);
+        }
+        let mut alts = vec![first];
+        while self.at(TokenKind::Pipe) {
+            self.next();
+            alts.push(self.parse_pattern_atom(allow_bind)?);
+        }
+        let span = join(pat_span(&alts[0]), pat_span(alts.last().expect("alt")));
+        Ok(Pattern::Or { span, alts })
+    }
+
+    fn parse_pattern_atom(&mut self, allow_bind: bool) -> Result<Pattern, ParseError> {
         // Avoid borrowing `self` across `next()`.
         if matches!(self.peek_kind(), Some(TokenKind::Ident(name)) if name == "_") {
             let t = self.next().expect("token");
@@ -1349,19 +1830,23 @@ impl<'a> Parser<'a> {
             return Ok(Pattern::StringLit { span: t.span, value });
         }
 
-        // Constructor pattern: Type::Variant(x, y)
+        if self.at(TokenKind::LParen) {
+            return self.parse_tuple_pattern();
+        }
+
+        // Constructor pattern: Type::Variant(p, q)
         if matches!(self.peek_kind(), Some(TokenKind::Ident(_))) {
             let ty = self.expect_ident()?;
             if self.at(TokenKind::ColonColon) || self.at(TokenKind::Dot) {
                 self.next();
                 let variant = self.expect_ident()?;
-                let mut binders: Vec<Ident> = Vec::new();
+                let mut args: Vec<Pattern> = Vec::new();
 
                 if self.at(TokenKind::LParen) {
                     self.next();
                     if !self.at(TokenKind::RParen) {
                         loop {
-                            binders.push(self.expect_ident()?);
+                            args.push(self.parse_or_pattern(true)?);
                             if self.at(TokenKind::Comma) {
                                 self.next();
                                 continue;
@@ -1375,7 +1860,7 @@ impl<'a> Parser<'a> {
                         span,
                         ty,
                         variant,
-                        binders,
+                        args,
                     });
                 }
 
@@ -1384,11 +1869,15 @@ impl<'a> Parser<'a> {
                     span,
                     ty,
                     variant,
-                    binders,
+                    args,
                 });
             }
 
-            // If it's just an ident (not a ctor), treat as parse error for now.
+            if allow_bind {
+                return Ok(Pattern::Bind { span: ty.span, name: ty });
+            }
+
+            // A bare name is only a binder inside a tuple or constructor pattern.
             return Err(ParseError {
                 message: "expected a match pattern ('_', int, string, or Type::Variant)".to_string(),
                 span: ty.span,
@@ -1402,6 +1891,31 @@ impl<'a> Parser<'a> {
         })
     }
 
+    /// `(p1, p2, ...)`; a bare name inside the parens binds that element.
+    fn parse_tuple_pattern(&mut self) -> Result<Pattern, ParseError> {
+        let lp = self.expect(TokenKind::LParen)?;
+        let mut elems = Vec::new();
+        loop {
+            elems.push(self.parse_or_pattern(true)?);
+            if self.at(TokenKind::Comma) {
+                self.next();
+                continue;
+            }
+            break;
+        }
+        let rp = self.expect(TokenKind::RParen)?;
+        if elems.len() < 2 {
+            return Err(ParseError {
+                message: "tuple patterns need at least two elements".to_string(),
+                span: join(lp.span, rp.span),
+            });
+        }
+        Ok(Pattern::Tuple {
+            span: join(lp.span, rp.span),
+            elems,
+        })
+    }
+
     fn parse_while_stmt(&mut self) -> Result<WhileStmt, ParseError> {
         let start = self.expect(TokenKind::KwWhile)?;
         let cond = self.parse_expr()?;
@@ -1432,7 +1946,63 @@ impl<'a> Parser<'a> {
         })
     }
 
+    fn parse_for_stmt(&mut self) -> Result<aura_ast::ForStmt, ParseError> {
+        let start_tok = self.expect(TokenKind::KwFor)?;
+        let var = self.expect_ident()?;
+        self.expect(TokenKind::KwIn)?;
+        let start = self.parse_expr()?;
+        self.expect(TokenKind::DotDot)?;
+        let end = self.parse_expr()?;
+
+        let invariant = if self.at(TokenKind::KwInvariant) {
+            self.next();
+            Some(self.parse_expr()?)
+        } else {
+            None
+        };
+
+        self.expect(TokenKind::Colon)?;
+        let body = self.parse_logic_block()?;
+        let span = join(start_tok.span, body.span);
+        Ok(aura_ast::ForStmt {
+            span,
+            var,
+            start,
+            end,
+            invariant,
+            body,
+        })
+    }
+
     fn parse_type_ref(&mut self) -> Result<TypeRef, ParseError> {
+        // Tuple type: `(A, B)` is sugar for `Tuple<A, B>`.
+        if self.at(TokenKind::LParen) {
+            let lp = self.next().unwrap();
+            let mut args = Vec::new();
+            loop {
+                args.push(TypeArg::Type(Box::new(self.parse_type_ref()?)));
+                if self.at(TokenKind::Comma) {
+                    self.next();
+                    continue;
+                }
+                break;
+            }
+            let rp = self.expect(TokenKind::RParen)?;
+            let span = join(lp.span, rp.span);
+            if args.len() < 2 {
+                return Err(ParseError {
+                    message: "tuple types need at least two elements".to_string(),
+                    span,
+                });
+            }
+            return Ok(TypeRef {
+                span,
+                name: Spanned::new(lp.span, "Tuple".to_string()),
+                args,
+                range: None,
+            });
+        }
+
         let name = self.expect_ident()?;
         let mut end = name.span;
         let mut args = Vec::new();
@@ -1485,31 +2055,32 @@ impl<'a> Parser<'a> {
                         // CTFE MVP: allow const integer expressions in tensor shapes.
                         // Example: `Tensor<u32, [2 + 3*4, (2+3)*4]>`.
                         let expr = self.parse_expr()?;
-                        let n = eval_const_u64(&expr)?;
+                        let n = eval_const_u64(&expr, &self.consts)?;
                         dims.push(n);
                     } else {
                         let tok = self.next().ok_or_else(|| ParseError {
                             message: "unexpected end of input while parsing shape".to_string(),
                             span: self.peek_span().unwrap_or_else(|| span_between(0, 0)),
                         })?;
-                        match tok.kind {
-                            TokenKind::Int(n) => {
-                                dims.push(n);
-                                // In non-CTFE mode, shapes are a list of integer literals.
-                                // If we see an operator or other token, produce a gated diagnostic.
-                                if !self.at(TokenKind::Comma) && !self.at(TokenKind::RBracket) {
-                                    return Err(ParseError {
-                                        message: "shape dimensions must be integer literals (enable unstable feature 'ctfe' for const expressions)".to_string(),
-                                        span: self.peek_span().unwrap_or(tok.span),
-                                    });
-                                }
-                            }
+                        let n = match &tok.kind {
+                            TokenKind::Int(n) => *n,
+                            // A declared `const` names a literal dimension.
+                            TokenKind::Ident(name) if self.consts.contains_key(name) => self.consts[name],
                             _ => {
                                 return Err(ParseError {
                                     message: "shape dimensions must be integer literals (enable unstable feature 'ctfe' for const expressions)".to_string(),
                                     span: tok.span,
                                 })
                             }
+                        };
+                        dims.push(n);
+                        // In non-CTFE mode, shapes are a list of integer literals.
+                        // If we see an operator or other token, produce a gated diagnostic.
+                        if !self.at(TokenKind::Comma) && !self.at(TokenKind::RBracket) {
+                            return Err(ParseError {
+                                message: "shape dimensions must be integer literals (enable unstable feature 'ctfe' for const expressions)".to_string(),
+                                span: self.peek_span().unwrap_or(tok.span),
+                            });
                         }
                     }
 
@@ -1624,7 +2195,7 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_cmp_expr(&mut self) -> Result<Expr, ParseError> {
-        let left = self.parse_add_expr()?;
+        let left = self.parse_bitor_expr()?;
         let op = match self.peek_kind() {
             Some(TokenKind::EqEq) => Some(BinOp::Eq),
             Some(TokenKind::Neq) => Some(BinOp::Ne),
@@ -1637,7 +2208,7 @@ impl<'a> Parser<'a> {
 
         let Some(op) = op else { return Ok(left) };
         self.next();
-        let right = self.parse_add_expr()?;
+        let right = self.parse_bitor_expr()?;
         let span = join(left.span, right.span);
         let expr = Expr {
             span,
@@ -1671,6 +2242,99 @@ impl<'a> Parser<'a> {
         Ok(expr)
     }
 
+    // Bitwise operators bind tighter than comparisons, so `flags & MASK == 0` needs no parentheses.
+    fn parse_bitor_expr(&mut self) -> Result<Expr, ParseError> {
+        let mut left = self.parse_bitxor_expr()?;
+        while self.at(TokenKind::Pipe) {
+            self.next();
+            let right = self.parse_bitxor_expr()?;
+            let span = join(left.span, right.span);
+            left = Expr {
+                span,
+                kind: ExprKind::Binary {
+                    left: Box::new(left),
+                    op: BinOp::BitOr,
+                    right: Box::new(right),
+                },
+            };
+        }
+        Ok(left)
+    }
+
+    fn parse_bitxor_expr(&mut self) -> Result<Expr, ParseError> {
+        let mut left = self.parse_bitand_expr()?;
+        while self.at(TokenKind::Caret) {
+            self.next();
+            let right = self.parse_bitand_expr()?;
+            let span = join(left.span, right.span);
+            left = Expr {
+                span,
+                kind: ExprKind::Binary {
+                    left: Box::new(left),
+                    op: BinOp::BitXor,
+                    right: Box::new(right),
+                },
+            };
+        }
+        Ok(left)
+    }
+
+    fn parse_bitand_expr(&mut self) -> Result<Expr, ParseError> {
+        let mut left = self.parse_shift_expr()?;
+        while self.at(TokenKind::Amp) {
+            self.next();
+            let right = self.parse_shift_expr()?;
+            let span = join(left.span, right.span);
+            left = Expr {
+                span,
+                kind: ExprKind::Binary {
+                    left: Box::new(left),
+                    op: BinOp::BitAnd,
+                    right: Box::new(right),
+                },
+            };
+        }
+        Ok(left)
+    }
+
+    fn parse_shift_expr(&mut self) -> Result<Expr, ParseError> {
+        let mut left = self.parse_add_expr()?;
+        loop {
+            let op = if self.at(TokenKind::Shl) {
+                self.next();
+                BinOp::Shl
+            } else if self.at_shr() {
+                self.next();
+                self.next();
+                BinOp::Shr
+            } else {
+                break;
+            };
+            let right = self.parse_add_expr()?;
+            let span = join(left.span, right.span);
+            left = Expr {
+                span,
+                kind: ExprKind::Binary {
+                    left: Box::new(left),
+                    op,
+                    right: Box::new(right),
+                },
+            };
+        }
+        Ok(left)
+    }
+
+    /// `>>` is lexed as two `>` so that nested generics close; it is a shift when they touch.
+    fn at_shr(&self) -> bool {
+        match (self.tokens.get(self.idx), self.tokens.get(self.idx + 1)) {
+            (Some(a), Some(b)) => {
+                matches!((&a.kind, &b.kind), (TokenKind::Gt, TokenKind::Gt))
+                    && a.span.offset() + a.span.len() == b.span.offset()
+            }
+            _ => false,
+        }
+    }
+
     fn parse_add_expr(&mut self) -> Result<Expr, ParseError> {
         let mut left = self.parse_mul_expr()?;
         loop {
@@ -1720,6 +2384,19 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_unary_expr(&mut self) -> Result<Expr, ParseError> {
+        if self.at(TokenKind::Amp) {
+            let t = self.next().unwrap();
+            let kind = self.parse_borrow_kind();
+            let expr = self.parse_unary_expr()?;
+            let span = join(t.span, expr.span);
+            return Ok(Expr {
+                span,
+                kind: ExprKind::Borrow {
+                    kind,
+                    expr: Box::new(expr),
+                },
+            });
+        }
         if self.at(TokenKind::Bang) {
             let t = self.next().unwrap();
             let expr = self.parse_unary_expr()?;
@@ -1760,6 +2437,44 @@ impl<'a> Parser<'a> {
                 }
             }
 
+            // Tuple element access: `t.0`
+            if self.at(TokenKind::Dot) && matches!(self.peek_kind_n(1), Some(TokenKind::Int(_))) {
+                self.next();
+                let t = self.next().expect("token");
+                let TokenKind::Int(index) = t.kind else {
+                    unreachable!();
+                };
+                let index = u32::try_from(index).map_err(|_| ParseError {
+                    message: "tuple index out of range".to_string(),
+                    span: t.span,
+                })?;
+                let span = join(expr.span, t.span);
+                expr = Expr {
+                    span,
+                    kind: ExprKind::TupleIndex {
+                        base: Box::new(expr),
+                        index,
+                    },
+                };
+                continue;
+            }
+
+            // List indexing: `xs[i]`
+            if self.at(TokenKind::LBracket) {
+                self.next();
+                let index = self.parse_expr()?;
+                let rb = self.expect(TokenKind::RBracket)?;
+                let span = join(expr.span, rb.span);
+                expr = Expr {
+                    span,
+                    kind: ExprKind::Index {
+                        base: Box::new(expr),
+                        index: Box::new(index),
+                    },
+                };
+                continue;
+            }
+
             if self.at(TokenKind::Dot) || self.at(TokenKind::ColonColon) {
                 let _sep = self.next().unwrap();
                 let member = self.expect_ident()?;
@@ -1936,11 +2651,13 @@ impl<'a> Parser<'a> {
                 let span = join(lb.span, rb.span);
                 return Ok(Block { span, stmts, yield_expr });
             }
-            if self.at(TokenKind::Eof) {
-                return Err(ParseError {
+            if self.at(TokenKind::Eof) || (self.recovering && self.at_definition_keyword()) {
+                self.recover_or_fail(ParseError {
                     message: "unterminated brace block; expected '}'".to_string(),
                     span: lb.span,
-                });
+                })?;
+                let span = join(lb.span, self.prev_span().unwrap_or(lb.span));
+                return Ok(Block { span, stmts, yield_expr });
             }
 
             if self.at(TokenKind::KwYield) {
@@ -1962,15 +2679,22 @@ impl<'a> Parser<'a> {
                     break;
                 }
                 if !self.at(TokenKind::RBrace) {
-                    return Err(ParseError {
+                    self.recover_or_fail(ParseError {
                         message: "yield must be the last statement in a block".to_string(),
                         span: y.span,
-                    });
+                    })?;
                 }
                 continue;
             }
 
-            stmts.push(self.parse_stmt()?);
+            let from = self.idx;
+            match self.parse_stmt() {
+                Ok(stmt) => stmts.push(stmt),
+                Err(err) => {
+                    self.recover_or_fail(err)?;
+                    self.recover_to_stmt_boundary(from, true);
+                }
+            }
         }
     }
 
@@ -2100,15 +2824,68 @@ impl<'a> Parser<'a> {
                 span: tok.span,
                 kind: ExprKind::IntLit(n),
             }),
+            TokenKind::Float(v) => Ok(Expr {
+                span: tok.span,
+                kind: ExprKind::FloatLit(v),
+            }),
             TokenKind::String(s) => Ok(Expr {
                 span: tok.span,
                 kind: ExprKind::StringLit(s),
             }),
+            TokenKind::InterpString(parts) => {
+                let mut out = Vec::with_capacity(parts.len());
+                for part in parts {
+                    match part {
+                        StrPart::Lit(lit) => out.push(InterpPart::Lit(lit)),
+                        StrPart::Expr(tokens) => {
+                            let mut sub = Parser::new_with_config(&tokens, &self.config);
+                            out.push(InterpPart::Expr(sub.parse_expr_eof()?));
+                        }
+                    }
+                }
+                Ok(Expr {
+                    span: tok.span,
+                    kind: ExprKind::InterpolatedString(out),
+                })
+            }
             TokenKind::LParen => {
                 let expr = self.parse_expr()?;
-                self.expect(TokenKind::RParen)?;
-                Ok(expr)
+                if !self.at(TokenKind::Comma) {
+                    self.expect(TokenKind::RParen)?;
+                    return Ok(expr);
+                }
+                // Tuple literal: `(a, b, ...)`
+                let mut elems = vec![expr];
+                while self.at(TokenKind::Comma) {
+                    self.next();
+                    elems.push(self.parse_expr()?);
+                }
+                let rp = self.expect(TokenKind::RParen)?;
+                Ok(Expr {
+                    span: join(tok.span, rp.span),
+                    kind: ExprKind::Tuple(elems),
+                })
+            }
+            TokenKind::LBracket => {
+                // List literal: `[a, b, ...]` or `[]`
+                let mut elems = Vec::new();
+                if !self.at(TokenKind::RBracket) {
+                    loop {
+                        elems.push(self.parse_expr()?);
+                        if self.at(TokenKind::Comma) {
+                            self.next();
+                            continue;
+                        }
+                        break;
+                    }
+                }
+                let rb = self.expect(TokenKind::RBracket)?;
+                Ok(Expr {
+                    span: join(tok.span, rb.span),
+                    kind: ExprKind::ListLit(elems),
+                })
             }
+            TokenKind::LBrace => self.parse_map_lit(tok.span),
             _ => Err(ParseError {
                 message: "expected an expression".to_string(),
                 span: tok.span,
@@ -2116,6 +2893,52 @@ impl<'a> Parser<'a> {
         }
     }
 
+    fn parse_map_lit(&mut self, lb_span: Span) -> Result<Expr, ParseError> {
+        // Map literal: `{ key: value, ... }` or `{}`; the opening brace is already consumed.
+        let mut entries: Vec<(Expr, Expr)> = Vec::new();
+        loop {
+            while self.at(TokenKind::Newline) || self.at(TokenKind::Indent) || self.at(TokenKind::Dedent) {
+                self.next();
+            }
+            if self.at(TokenKind::RBrace) {
+                let rb = self.next().unwrap();
+                return Ok(Expr {
+                    span: join(lb_span, rb.span),
+                    kind: ExprKind::MapLit(entries),
+                });
+            }
+            if self.at(TokenKind::Eof) {
+                return Err(ParseError {
+                    message: "unterminated map literal; expected '}'".to_string(),
+                    span: lb_span,
+                });
+            }
+
+            let key = self.parse_expr()?;
+            self.expect(TokenKind::Colon)?;
+            let value = self.parse_expr()?;
+            entries.push((key, value));
+
+            while self.at(TokenKind::Newline) || self.at(TokenKind::Indent) || self.at(TokenKind::Dedent) {
+                self.next();
+            }
+
+            if self.at(TokenKind::Comma) {
+                self.next();
+                continue;
+            }
+
+            if self.at(TokenKind::RBrace) {
+                continue;
+            }
+
+            return Err(ParseError {
+                message: "expected ',' or '}' in map literal".to_string(),
+                span: self.peek_span().unwrap_or(lb_span),
+            });
+        }
+    }
+
     fn parse_quant_binders(&mut self) -> Result<Vec<aura_ast::QuantBinder>, ParseError> {
         let lp = self.expect(TokenKind::LParen)?;
         let mut binders: Vec<aura_ast::QuantBinder> = Vec::new();
@@ -2324,6 +3147,15 @@ impl<'a> Parser<'a> {
     fn peek_span(&self) -> Option<Span> {
         self.tokens.get(self.idx).map(|t| t.span)
     }
+
+    /// Span of the most recently consumed token, ignoring layout tokens.
+    fn prev_span(&self) -> Option<Span> {
+        self.tokens[..self.idx.min(self.tokens.len())]
+            .iter()
+            .rev()
+            .find(|t| !matches!(t.kind, TokenKind::Newline | TokenKind::Indent | TokenKind::Dedent))
+            .map(|t| t.span)
+    }
 }
 
 fn join(a: Span, b: Span) -> Span {
@@ -2338,12 +3170,13 @@ fn join(a: Span, b: Span) -> Span {
     }
 }
 
-fn eval_const_u64(expr: &Expr) -> Result<u64, ParseError> {
+fn eval_const_u64(expr: &Expr, consts: &HashMap<String, u64>) -> Result<u64, ParseError> {
     match &expr.kind {
         ExprKind::IntLit(n) => Ok(*n),
+        ExprKind::Ident(name) if consts.contains_key(&name.node) => Ok(consts[&name.node]),
         ExprKind::Unary { op, expr: inner } => match op {
             UnaryOp::Neg => {
-                let v = eval_const_u64(inner)?;
+                let v = eval_const_u64(inner, consts)?;
                 if v == 0 {
                     Ok(0)
                 } else {
@@ -2359,8 +3192,8 @@ fn eval_const_u64(expr: &Expr) -> Result<u64, ParseError> {
             }),
         },
         ExprKind::Binary { left, op, right } => {
-            let l = eval_const_u64(left)?;
-            let r = eval_const_u64(right)?;
+            let l = eval_const_u64(left, consts)?;
+            let r = eval_const_u64(right, consts)?;
             let out = match op {
                 BinOp::Add => l.checked_add(r),
                 BinOp::Sub => l.checked_sub(r),
@@ -2392,5 +3225,8 @@ fn pat_span(p: &Pattern) -> Span {
         Pattern::IntLit { span, .. } => *span,
         Pattern::StringLit { span, .. } => *span,
         Pattern::Ctor { span, .. } => *span,
+        Pattern::Tuple { span, .. } => *span,
+        Pattern::Bind { span, .. } => *span,
+        Pattern::Or { span, .. } => *span,
     }
 }
@@ -7,6 +7,7 @@
 /// - Constructor patterns: `Box(x)`, `Pair(a, b)`
 /// - Enum variants: `Ok(v)`, `Err(e)`
 /// - Nested patterns: `Some(Box(x))`
+/// - Or-patterns: `Red | Green`
 /// - Wildcard and literal matches
 
 use std::collections::BTreeMap;
@@ -83,19 +84,25 @@ impl PatternMatrix {
                 continue;
             }
 
-            match &row[0] {
-                Pattern::Wildcard(_) => has_wildcard = true,
-                Pattern::Variant {
-                    enum_name,
-                    variant_name,
-                    ..
-                } => {
-                    variants.insert(format!("{}::{}", enum_name, variant_name));
-                }
-                Pattern::Constructor { type_name, .. } => {
-                    variants.insert(type_name.clone());
+            let alts = match &row[0] {
+                Pattern::Or(alts) => alts.iter().collect::<Vec<_>>(),
+                p => vec![p],
+            };
+            for p in alts {
+                match p {
+                    Pattern::Wildcard(_) => has_wildcard = true,
+                    Pattern::Variant {
+                        enum_name,
+                        variant_name,
+                        ..
+                    } => {
+                        variants.insert(format!("{}::{}", enum_name, variant_name));
+                    }
+                    Pattern::Constructor { type_name, .. } => {
+                        variants.insert(type_name.clone());
+                    }
+                    _ => has_wildcard = true,
                 }
-                _ => has_wildcard = true,
             }
         }
 
@@ -128,6 +135,29 @@ impl PatternMatrix {
             return MatchExpr::Leaf { bindings };
         }
 
+        // Or-patterns become one row per alternative, each keeping the arm's action.
+        if rows.iter().any(|r| matches!(r.get(col), Some(Pattern::Or(_)))) {
+            let mut expanded_rows = Vec::new();
+            let mut expanded_actions = Vec::new();
+            for (row, action) in rows.iter().zip(actions.iter()) {
+                match row.get(col) {
+                    Some(Pattern::Or(alts)) => {
+                        for alt in alts {
+                            let mut r = row.clone();
+                            r[col] = alt.clone();
+                            expanded_rows.push(r);
+                            expanded_actions.push(action.clone());
+                        }
+                    }
+                    _ => {
+                        expanded_rows.push(row.clone());
+                        expanded_actions.push(action.clone());
+                    }
+                }
+            }
+            return self._compile_inner(&expanded_rows, &expanded_actions, col);
+        }
+
         // Check first column pattern
         let first_pat = &rows[0][col];
         match first_pat {
@@ -139,7 +169,7 @@ impl PatternMatrix {
                 // Build decision tree based on literal values
                 let mut branches: BTreeMap<String, Vec<usize>> = BTreeMap::new();
                 for (idx, row) in rows.iter().enumerate() {
-                    if idx >= row.len() {
+                    if col >= row.len() {
                         continue;
                     }
                     match &row[col] {
@@ -189,32 +219,43 @@ impl PatternMatrix {
                 variant_name: _,
                 arg_patterns: _,
             } => {
-                // Build decision tree based on enum variant
-                let mut branches: BTreeMap<String, Vec<usize>> = BTreeMap::new();
-                for (idx, row) in rows.iter().enumerate() {
-                    if idx >= row.len() {
-                        continue;
-                    }
-                    match &row[col] {
-                        Pattern::Variant {
-                            variant_name: v, ..
-                        } => {
-                            branches.entry(v.clone()).or_insert_with(Vec::new).push(idx);
-                        }
-                        Pattern::Wildcard(_) => {
-                            // Wildcard matches all variants
-                        }
-                        _ => {}
+                // Build decision tree based on enum variant; remember each variant's arity
+                // so wildcard rows can be widened to match its payload columns.
+                let mut arities: BTreeMap<String, usize> = BTreeMap::new();
+                for row in rows {
+                    if let Some(Pattern::Variant {
+                        variant_name: v,
+                        arg_patterns,
+                        ..
+                    }) = row.get(col)
+                    {
+                        arities.entry(v.clone()).or_insert(arg_patterns.len());
                     }
                 }
 
                 let mut decision_branches = Vec::new();
-                for (var, indices) in branches {
-                    let sub_rows: Vec<Vec<Pattern>> =
-                        indices.iter().map(|&i| rows[i].clone()).collect();
-                    let sub_actions: Vec<String> =
-                        indices.iter().map(|&i| actions[i].clone()).collect();
-                    let expr = self._compile_inner(&sub_rows, &sub_actions, col + 1);
+                for (var, arity) in arities {
+                    // Specialize: the variant's payload patterns replace the tested column,
+                    // so nested patterns are compiled as further columns.
+                    let mut sub_rows: Vec<Vec<Pattern>> = Vec::new();
+                    let mut sub_actions: Vec<String> = Vec::new();
+                    for (row, action) in rows.iter().zip(actions.iter()) {
+                        let args = match row.get(col) {
+                            Some(Pattern::Variant {
+                                variant_name: v,
+                                arg_patterns,
+                                ..
+                            }) if *v == var => arg_patterns.clone(),
+                            Some(Pattern::Wildcard(_)) => vec![Pattern::Wildcard(None); arity],
+                            _ => continue,
+                        };
+                        let mut r = row[..col].to_vec();
+                        r.extend(args);
+                        r.extend_from_slice(&row[col + 1..]);
+                        sub_rows.push(r);
+                        sub_actions.push(action.clone());
+                    }
+                    let expr = self._compile_inner(&sub_rows, &sub_actions, col);
                     decision_branches.push((format!("{}::{}", enum_name, var), expr));
                 }
 
@@ -289,4 +330,36 @@ mod tests {
             _ => panic!("Expected Decision node"),
         }
     }
+
+    #[test]
+    fn test_or_pattern_and_nested_variant() {
+        let some = |arg: Pattern| Pattern::Variant {
+            enum_name: "Opt".to_string(),
+            variant_name: "Some".to_string(),
+            arg_patterns: vec![arg],
+        };
+        let mut pm = PatternMatrix::new();
+        pm.add_arm(
+            vec![some(Pattern::Or(vec![
+                Pattern::Literal("0".to_string()),
+                Pattern::Literal("1".to_string()),
+            ]))],
+            "small".to_string(),
+        );
+        pm.add_arm(vec![Pattern::Wildcard(None)], "other".to_string());
+
+        let MatchExpr::Decision { branches, default, .. } = pm.compile() else {
+            panic!("Expected Decision node");
+        };
+        assert!(default.is_some());
+        assert_eq!(branches.len(), 1);
+        assert_eq!(branches[0].0, "Opt::Some");
+        // The payload column is tested next, with one branch per or-alternative.
+        let MatchExpr::Decision { column, branches, .. } = &branches[0].1 else {
+            panic!("Expected nested Decision node");
+        };
+        assert_eq!(*column, 0);
+        let values: Vec<&str> = branches.iter().map(|(v, _)| v.as_str()).collect();
+        assert_eq!(values, vec!["0", "1"]);
+    }
 }
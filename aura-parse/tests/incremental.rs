@@ -0,0 +1,58 @@
+use aura_parse::{parse_source, reparse_incremental, ParseConfig, TextEdit};
+
+const SRC: &str = "## Adds one.\ncell inc(x: u32) -> u32:\n    yield x + 1\n\nval k: u32 = 3\n\ncell main():\n    val y: u32 = inc(k)\n";
+
+fn edit_at(text: &str, needle: &str, replacement: &str) -> TextEdit {
+    let start = text.find(needle).expect("needle");
+    TextEdit {
+        start,
+        end: start + needle.len(),
+        text: replacement.to_string(),
+    }
+}
+
+fn check_against_full_parse(edits: &[TextEdit]) -> std::ops::Range<usize> {
+    let prev = parse_source(SRC).expect("parse");
+    let out = reparse_incremental(&prev, SRC, edits, &ParseConfig::default()).expect("reparse");
+    let full = parse_source(&out.text).expect("full parse");
+    assert_eq!(out.program, full, "incremental result must match a full parse of:\n{}", out.text);
+    out.reparsed
+}
+
+#[test]
+fn edit_inside_one_cell_reparses_only_that_cell() {
+    let reparsed = check_against_full_parse(&[edit_at(SRC, "x + 1", "x + 100")]);
+    assert_eq!(reparsed, 0..1);
+}
+
+#[test]
+fn later_statements_are_reused_with_shifted_spans() {
+    let reparsed = check_against_full_parse(&[edit_at(SRC, "k: u32 = 3", "kk: u32 = 3\nval j: u32 = 4")]);
+    assert_eq!(reparsed, 1..3);
+}
+
+#[test]
+fn editing_a_doc_comment_reparses_the_documented_cell() {
+    let reparsed = check_against_full_parse(&[edit_at(SRC, "Adds one.", "Increments.")]);
+    assert_eq!(reparsed, 0..1);
+}
+
+#[test]
+fn sequential_edits_and_appends_are_applied_in_order() {
+    let first = edit_at(SRC, "inc(k)", "inc(k + 1)");
+    let second = TextEdit {
+        start: SRC.len() + " + 1".len(),
+        end: SRC.len() + " + 1".len(),
+        text: "\ncell extra():\n    val z: u32 = 0\n".to_string(),
+    };
+    let reparsed = check_against_full_parse(&[first, second]);
+    assert_eq!(reparsed, 2..4);
+}
+
+#[test]
+fn broken_edits_report_the_full_parse_error() {
+    let prev = parse_source(SRC).expect("parse");
+    let edit = edit_at(SRC, "yield x + 1", "yield x +");
+    let err = reparse_incremental(&prev, SRC, &[edit], &ParseConfig::default()).expect_err("broken cell");
+    assert!(err.to_string().contains("parse error"), "{err}");
+}
@@ -0,0 +1,59 @@
+use aura_ast::Stmt;
+use aura_parse::parse_source_with_recovery;
+
+fn cell_names(program: &aura_ast::Program) -> Vec<&str> {
+    program
+        .stmts
+        .iter()
+        .filter_map(|s| match s {
+            Stmt::CellDef(c) => Some(c.name.node.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn broken_statement_inside_a_cell_keeps_the_cell_and_its_siblings() {
+    let src = "cell a():\n    val x: u32 = 1\n    val y: u32 = x +\n    val z: u32 = 3\n\ncell b():\n    val w: u32 = 4\n";
+    let (program, errors) = parse_source_with_recovery(src).expect("lex");
+    assert_eq!(errors.len(), 1, "{errors:?}");
+    assert_eq!(cell_names(&program), vec!["a", "b"]);
+
+    let Stmt::CellDef(a) = &program.stmts[0] else {
+        panic!("expected cell");
+    };
+    assert_eq!(a.body.stmts.len(), 2, "the broken statement is dropped, its neighbours kept: {errors:?}");
+}
+
+#[test]
+fn broken_header_skips_its_indented_body() {
+    let src = "cell a(n: u32):\n    if n >:\n        val q: u32 = 1\n        val r: u32 = 2\n    val s: u32 = n\n\ncell b():\n    val w: u32 = 4\n";
+    let (program, errors) = parse_source_with_recovery(src).expect("lex");
+    assert_eq!(errors.len(), 1, "the body must not produce follow-on errors: {errors:?}");
+    assert_eq!(cell_names(&program), vec!["a", "b"]);
+
+    let Stmt::CellDef(a) = &program.stmts[0] else {
+        panic!("expected cell");
+    };
+    assert_eq!(a.body.stmts.len(), 1);
+}
+
+#[test]
+fn missing_closing_brace_is_closed_at_the_next_definition() {
+    let src = "type Point = record {\n    x: u32,\n    y: u32,\n\ncell main():\n    val p: u32 = 1\n";
+    let (program, errors) = parse_source_with_recovery(src).expect("lex");
+    assert_eq!(errors.len(), 1, "{errors:?}");
+    assert!(errors[0].message.contains("expected '}'"), "{}", errors[0].message);
+
+    let Stmt::RecordDef(r) = &program.stmts[0] else {
+        panic!("expected record");
+    };
+    assert_eq!(r.fields.len(), 2);
+    assert_eq!(cell_names(&program), vec!["main"]);
+}
+
+#[test]
+fn strict_parsing_still_fails_on_the_first_error() {
+    let src = "cell a():\n    val y: u32 = 1 +\n    val z: u32 = 3\n";
+    assert!(aura_parse::parse_source(src).is_err());
+}
@@ -40,3 +40,349 @@ cell main() ->:
 "#;
     parse_source(src).expect("new 0.3 syntax should parse");
 }
+
+#[test]
+fn test_cells_parse_and_test_stays_an_identifier() {
+    let src = "test cell adds_up():\n    val test = 1\n    assert test + 1 == 2\n\ncell main():\n    val x = 0\n";
+    let program = parse_source(src).expect("test cell should parse");
+    let flags: Vec<(String, bool)> = program
+        .stmts
+        .iter()
+        .filter_map(|s| match s {
+            aura_ast::Stmt::CellDef(c) => Some((c.name.node.clone(), c.is_test)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        flags,
+        vec![("adds_up".to_string(), true), ("main".to_string(), false)]
+    );
+}
+
+#[test]
+fn for_loop_over_range_parses() {
+    let src = "cell main():\n    val mut s: u32 = 0\n    for i in 0..3 invariant s >= 0:\n        s = s + i\n";
+    let program = parse_source(src).expect("for-loop should parse");
+    let aura_ast::Stmt::CellDef(c) = &program.stmts[0] else {
+        panic!("expected cell");
+    };
+    let aura_ast::Stmt::For(f) = &c.body.stmts[1] else {
+        panic!("expected for-loop");
+    };
+    assert_eq!(f.var.node, "i");
+    assert!(f.invariant.is_some());
+}
+
+#[test]
+fn interpolated_strings_parse_and_format_round_trip() {
+    let src = "val n = 1\nval s = \"n = {n + 1} {{literal}}\"\n";
+    let program = parse_source(src).expect("interpolated string should parse");
+    let aura_ast::Stmt::StrandDef(sd) = &program.stmts[1] else {
+        panic!("expected val");
+    };
+    let aura_ast::ExprKind::InterpolatedString(parts) = &sd.expr.kind else {
+        panic!("expected interpolated string");
+    };
+    assert_eq!(parts.len(), 3);
+    assert!(matches!(&parts[1], aura_ast::InterpPart::Expr(_)));
+    assert_eq!(parts[2], aura_ast::InterpPart::Lit(" {literal}".to_string()));
+
+    let formatted = aura_parse::format_program(&program);
+    assert!(formatted.contains("\"n = {n + 1} {{literal}}\""), "{formatted}");
+}
+
+#[test]
+fn float_literals_parse_and_format_round_trip() {
+    let src = "val x = 1_000.5\nval y = 2.5e-3\n";
+    let program = parse_source(src).expect("float literals should parse");
+    let aura_ast::Stmt::StrandDef(sd) = &program.stmts[1] else {
+        panic!("expected val");
+    };
+    assert_eq!(sd.expr.kind, aura_ast::ExprKind::FloatLit(0.0025));
+
+    let formatted = aura_parse::format_program(&program);
+    assert!(formatted.contains("val x = 1000.5"), "{formatted}");
+    let reparsed = parse_source(&formatted).expect("formatted floats should reparse");
+    assert_eq!(reparsed.stmts.len(), 2);
+}
+
+#[test]
+fn doc_comments_attach_to_definitions_and_format_round_trip() {
+    let src = "## A point.\ntype Point = record { x: u32, y: u32 }\n\n## Adds one.\n##\n## Never overflows here.\ncell inc(x: u32):\n    x + 1\n\n# not a doc\ncell plain(x: u32):\n    x\n";
+    let program = parse_source(src).expect("doc comments should parse");
+    let aura_ast::Stmt::RecordDef(rd) = &program.stmts[0] else {
+        panic!("expected record");
+    };
+    assert_eq!(rd.doc.as_deref(), Some("A point."));
+    let aura_ast::Stmt::CellDef(inc) = &program.stmts[1] else {
+        panic!("expected cell");
+    };
+    assert_eq!(inc.doc.as_deref(), Some("Adds one.\n\nNever overflows here."));
+    let aura_ast::Stmt::CellDef(plain) = &program.stmts[2] else {
+        panic!("expected cell");
+    };
+    assert_eq!(plain.doc, None);
+
+    let formatted = aura_parse::format_program(&program);
+    assert!(formatted.contains("## Adds one.\n##\n## Never overflows here.\ncell inc"), "{formatted}");
+    let reparsed = parse_source(&formatted).expect("formatted docs should reparse");
+    let aura_ast::Stmt::CellDef(inc2) = &reparsed.stmts[1] else {
+        panic!("expected cell");
+    };
+    assert_eq!(inc2.doc, inc.doc);
+}
+
+#[test]
+fn generic_cell_type_params_parse_and_format() {
+    let src = "cell pair<T: Numeric, U>(a: T, b: U):\n    a\n";
+    let program = parse_source(src).expect("generic cell should parse");
+    let aura_ast::Stmt::CellDef(cell) = &program.stmts[0] else {
+        panic!("expected cell");
+    };
+    let names: Vec<&str> = cell.type_params.iter().map(|p| p.name.node.as_str()).collect();
+    assert_eq!(names, vec!["T", "U"]);
+    assert_eq!(cell.type_params[0].bound.as_ref().map(|b| b.node.as_str()), Some("Numeric"));
+
+    let formatted = aura_parse::format_program(&program);
+    assert!(formatted.starts_with("cell pair<T: Numeric, U>(a: T, b: U):"), "{formatted}");
+}
+
+#[test]
+fn pub_cells_parse_and_format() {
+    let src = "pub cell get(x: u32):\n    x\n\npub test cell smoke():\n    val a = 1\n";
+    let program = parse_source(src).expect("pub cells should parse");
+    let aura_ast::Stmt::CellDef(get) = &program.stmts[0] else {
+        panic!("expected cell");
+    };
+    assert!(get.is_pub && !get.is_test);
+    let aura_ast::Stmt::CellDef(smoke) = &program.stmts[1] else {
+        panic!("expected cell");
+    };
+    assert!(smoke.is_pub && smoke.is_test);
+
+    let formatted = aura_parse::format_program(&program);
+    assert!(formatted.contains("pub cell get(x: u32):"), "{formatted}");
+    assert!(formatted.contains("pub test cell smoke():"), "{formatted}");
+}
+
+#[test]
+fn tuple_syntax_parses_and_formats_round_trip() {
+    let src = "cell f(p: (u32, bool)):\n    val (a, _) = (p.0, p.1)\n    match p:\n        (1, b):\n            a\n        _:\n            p.0\n";
+    let program = parse_source(src).expect("tuple syntax should parse");
+    let formatted = aura_parse::format_program(&program);
+    assert!(formatted.contains("cell f(p: (u32, bool)):"), "{formatted}");
+    assert!(formatted.contains("val (a, _) = (p.0, p.1)"), "{formatted}");
+    assert!(formatted.contains("(1, b):"), "{formatted}");
+    let reparsed = parse_source(&formatted).expect("formatted tuples should reparse");
+    assert_eq!(aura_parse::format_program(&reparsed), formatted);
+}
+
+#[test]
+fn nested_tuple_indexing_parses_and_formats_round_trip() {
+    let src = "cell f(p: ((u32, bool), u32)) -> bool:\n    val x = p.0.1\n    return x\n";
+    let program = parse_source(src).expect("nested tuple index should parse");
+    let aura_ast::Stmt::CellDef(cell) = &program.stmts[0] else { panic!("expected a cell") };
+    let aura_ast::Stmt::StrandDef(x) = &cell.body.stmts[0] else { panic!("expected a val") };
+    let aura_ast::ExprKind::TupleIndex { base, index: 1 } = &x.expr.kind else {
+        panic!("expected .1, got {:?}", x.expr.kind)
+    };
+    assert!(matches!(base.kind, aura_ast::ExprKind::TupleIndex { index: 0, .. }), "{:?}", base.kind);
+    assert_eq!(&src[x.expr.span.offset()..x.expr.span.offset() + x.expr.span.len()], "p.0.1");
+
+    let formatted = aura_parse::format_program(&program);
+    assert!(formatted.contains("val x = p.0.1"), "{formatted}");
+    let reparsed = parse_source(&formatted).expect("formatted nested index should reparse");
+    assert_eq!(aura_parse::format_program(&reparsed), formatted);
+
+    // A float after anything but `.` is still a float.
+    let program = parse_source("cell g() -> f64:\n    return 0.5\n").expect("float parses");
+    assert!(aura_parse::format_program(&program).contains("return 0.5"));
+}
+
+#[test]
+fn list_literals_and_indexing_parse_and_format_round_trip() {
+    let src = "val xs: List<u32> = [1, 2, 3]\nval empty: List<u32> = []\nval y = xs[xs[0] + 1]\nval n = xs.len()\n";
+    let program = parse_source(src).expect("list syntax should parse");
+    let formatted = aura_parse::format_program(&program);
+    assert!(formatted.contains("val xs: List<u32> = [1, 2, 3]"), "{formatted}");
+    assert!(formatted.contains("= []"), "{formatted}");
+    assert!(formatted.contains("xs[xs[0] + 1]"), "{formatted}");
+    let reparsed = parse_source(&formatted).expect("formatted lists should reparse");
+    assert_eq!(aura_parse::format_program(&reparsed), formatted);
+}
+
+#[test]
+fn map_literals_parse_and_format_round_trip() {
+    let src = "val ages: Map<String, u32> = {\n    \"ann\": 31,\n    \"bob\": 42,\n}\nval none: Map<u32, u32> = {}\nval a = ages[\"ann\"]\n";
+    let program = parse_source(src).expect("map syntax should parse");
+    let formatted = aura_parse::format_program(&program);
+    assert!(formatted.contains("= {\"ann\": 31, \"bob\": 42}"), "{formatted}");
+    assert!(formatted.contains("val none: Map<u32, u32> = {}"), "{formatted}");
+    assert!(formatted.contains("ages[\"ann\"]"), "{formatted}");
+    let reparsed = parse_source(&formatted).expect("formatted maps should reparse");
+    assert_eq!(aura_parse::format_program(&reparsed), formatted);
+}
+
+#[test]
+fn or_and_nested_ctor_patterns_parse_and_format_round_trip() {
+    let src = "cell f(s: Shape):\n    match s:\n        Shape::Circle(Point::At(x, 0), r):\n            r\n        Shape::Square(1 | 2) | Shape::Dot:\n            s\n        _:\n            s\n";
+    let program = parse_source(src).expect("pattern syntax should parse");
+    let formatted = aura_parse::format_program(&program);
+    assert!(formatted.contains("Shape::Circle(Point::At(x, 0), r):"), "{formatted}");
+    assert!(formatted.contains("Shape::Square(1 | 2) | Shape::Dot:"), "{formatted}");
+    let reparsed = parse_source(&formatted).expect("formatted patterns should reparse");
+    assert_eq!(aura_parse::format_program(&reparsed), formatted);
+}
+
+#[test]
+fn break_and_continue_parse_and_format_round_trip() {
+    let src = "cell main():\n    val mut i: u32 = 0\n    while i < 10:\n        i = i + 1\n        if i == 2:\n            continue\n        if i == 5:\n            break\n";
+    let program = parse_source(src).expect("loop control should parse");
+    let formatted = aura_parse::format_program(&program);
+    assert!(formatted.contains("            continue\n"), "{formatted}");
+    assert!(formatted.contains("            break\n"), "{formatted}");
+    let reparsed = parse_source(&formatted).expect("formatted loop control should reparse");
+    assert_eq!(aura_parse::format_program(&reparsed), formatted);
+}
+
+#[test]
+fn return_statements_and_declared_return_types_round_trip() {
+    let src = "cell clamp(x: u32) -> u32:\n    if x > 10:\n        return 10\n    yield x\n\ncell main() ->:\n    return\n";
+    let program = parse_source(src).expect("return syntax should parse");
+    let aura_ast::Stmt::CellDef(c) = &program.stmts[0] else {
+        panic!("expected cell");
+    };
+    assert!(c.ret.is_some() && c.flow.is_none());
+    let aura_ast::Stmt::CellDef(m) = &program.stmts[1] else {
+        panic!("expected cell");
+    };
+    assert!(m.ret.is_none() && m.flow.is_some());
+
+    let formatted = aura_parse::format_program(&program);
+    assert!(formatted.contains("cell clamp(x: u32) -> u32:"), "{formatted}");
+    assert!(formatted.contains("        return 10\n"), "{formatted}");
+    let reparsed = parse_source(&formatted).expect("formatted returns should reparse");
+    assert_eq!(aura_parse::format_program(&reparsed), formatted);
+}
+
+#[test]
+fn bitwise_operators_follow_c_precedence_below_arithmetic() {
+    let src = "cell f(x: u32) -> u32:\n    val m: bool = x & 0xFF == 0\n    yield x | 1 << 4 ^ x >> 2 & 3\n";
+    let program = parse_source(src).expect("bitwise operators should parse");
+    let formatted = aura_parse::format_program(&program);
+    assert!(formatted.contains("x & 255 == 0"), "{formatted}");
+    assert!(formatted.contains("x | 1 << 4 ^ x >> 2 & 3"), "{formatted}");
+    let reparsed = parse_source(&formatted).expect("formatted bitwise operators should reparse");
+    assert_eq!(aura_parse::format_program(&reparsed), formatted);
+}
+
+#[test]
+fn nested_generic_closers_are_not_a_shift() {
+    let src = "cell f(xs: List<List<u32>>) -> u32:\n    yield 8 >> 1\n";
+    let program = parse_source(src).expect("nested generics and shifts should coexist");
+    let formatted = aura_parse::format_program(&program);
+    assert!(formatted.contains("List<List<u32>>"), "{formatted}");
+    assert!(formatted.contains("8 >> 1"), "{formatted}");
+}
+
+#[test]
+fn const_declarations_round_trip_and_size_tensor_shapes() {
+    let src = "const ROWS: u32 = 4\nconst COLS: u32 = ROWS * 2\n\ncell f(t: Tensor<u32, [ROWS, 3]>) -> u32:\n    yield COLS\n";
+    let program = parse_source(src).expect("const declarations should parse");
+    let aura_ast::Stmt::CellDef(cell) = &program.stmts[2] else { panic!("expected cell") };
+    assert_eq!(cell.params[0].ty.args[1], aura_ast::TypeArg::Shape(vec![4, 3]));
+    let formatted = aura_parse::format_program(&program);
+    assert!(formatted.contains("const COLS: u32 = ROWS * 2\n"), "{formatted}");
+    let reparsed = parse_source(&formatted).expect("formatted consts should reparse");
+    assert_eq!(aura_parse::format_program(&reparsed), formatted);
+}
+
+#[test]
+fn defer_statements_round_trip() {
+    let src = "cell f(x: u32) -> u32:\n    defer {\n        val y: u32 = x + 1\n    }\n    yield x\n";
+    let program = parse_source(src).expect("defer should parse");
+    let aura_ast::Stmt::CellDef(cell) = &program.stmts[0] else { panic!("expected cell") };
+    assert!(matches!(cell.body.stmts[0], aura_ast::Stmt::Defer(_)));
+    let formatted = aura_parse::format_program(&program);
+    assert!(formatted.contains("defer {"), "{formatted}");
+    let reparsed = parse_source(&formatted).expect("formatted defer should reparse");
+    assert_eq!(aura_parse::format_program(&reparsed), formatted);
+}
+
+#[test]
+fn scope_blocks_round_trip_and_scope_stays_an_identifier() {
+    let src = "cell f(x: u32):\n    scope tok:\n        x ~> g(tok)\n    scope:\n        x ~> g(0)\n    val scope = 1\n    scope: 2\n";
+    let program = parse_source(src).expect("scope blocks should parse");
+    let aura_ast::Stmt::CellDef(cell) = &program.stmts[0] else { panic!("expected cell") };
+    let aura_ast::Stmt::Scope(named) = &cell.body.stmts[0] else { panic!("expected scope") };
+    assert_eq!(named.token.as_ref().map(|t| t.node.as_str()), Some("tok"));
+    assert!(matches!(&cell.body.stmts[1], aura_ast::Stmt::Scope(s) if s.token.is_none()));
+    assert!(matches!(cell.body.stmts[3], aura_ast::Stmt::Prop(_)));
+    let formatted = aura_parse::format_program(&program);
+    assert!(formatted.contains("scope tok:\n"), "{formatted}");
+    let reparsed = parse_source(&formatted).expect("formatted scope should reparse");
+    assert_eq!(aura_parse::format_program(&reparsed), formatted);
+}
+
+#[test]
+fn region_blocks_round_trip() {
+    let src = "cell f(n: u32):\n    region {\n        val t = tensor.new(n)\n        tensor.set(t, 0, 1)\n    }\n    val region = 1\n";
+    let program = parse_source(src).expect("region blocks should parse");
+    let aura_ast::Stmt::CellDef(cell) = &program.stmts[0] else { panic!("expected cell") };
+    let aura_ast::Stmt::Region(r) = &cell.body.stmts[0] else { panic!("expected region") };
+    assert_eq!(r.body.stmts.len(), 2);
+    let formatted = aura_parse::format_program(&program);
+    assert!(formatted.contains("region {\n"), "{formatted}");
+    let reparsed = parse_source(&formatted).expect("formatted region should reparse");
+    assert_eq!(aura_parse::format_program(&reparsed), formatted);
+}
+
+#[test]
+fn borrows_parse_and_format_round_trip() {
+    let src = "cell fill(t: &mut Tensor, n: &u32, i: mut u32):\n    tensor.set(t, i, n)\n\nval mut t = tensor.new(2)\nval n = 1\nfill(&mut t, &n, 0)\nval r = &t\n";
+    let program = parse_source(src).expect("borrows should parse");
+    let aura_ast::Stmt::CellDef(cell) = &program.stmts[0] else { panic!("expected cell") };
+    let modes = cell.params.iter().map(|p| (p.borrow, p.mutable)).collect::<Vec<_>>();
+    assert_eq!(
+        modes,
+        [(Some(aura_ast::BorrowKind::Mut), false), (Some(aura_ast::BorrowKind::Shared), false), (None, true)]
+    );
+    let aura_ast::Stmt::StrandDef(sd) = &program.stmts[4] else { panic!("expected val") };
+    assert!(matches!(sd.expr.kind, aura_ast::ExprKind::Borrow { kind: aura_ast::BorrowKind::Shared, .. }));
+    let formatted = aura_parse::format_program(&program);
+    assert!(formatted.contains("fill(&mut t, &n, 0)"), "{formatted}");
+    assert!(formatted.contains("t: &mut Tensor, n: &u32"), "{formatted}");
+}
+
+#[test]
+fn uses_lists_round_trip_and_uses_stays_an_identifier() {
+    let src = "cell fetch(host: String) -> String uses [net, io]:\n    host\n\ncell add(a: u32) -> u32 uses []:\n    a\n\ncell tick() -> uses [hw]:\n    val uses = 1\n";
+    let program = parse_source(src).expect("uses lists should parse");
+    let names = |i: usize| {
+        let aura_ast::Stmt::CellDef(cell) = &program.stmts[i] else { panic!("expected cell") };
+        cell.effects.as_ref().map(|l| l.node.iter().map(|e| e.node.clone()).collect::<Vec<_>>())
+    };
+    assert_eq!(names(0), Some(vec!["net".to_string(), "io".to_string()]));
+    assert_eq!(names(1), Some(vec![]));
+    assert_eq!(names(2), Some(vec!["hw".to_string()]));
+    let formatted = aura_parse::format_program(&program);
+    assert!(formatted.contains("cell fetch(host: String) -> String uses [net, io]:"), "{formatted}");
+    assert!(formatted.contains("cell tick() -> uses [hw]:"), "{formatted}");
+    let reparsed = parse_source(&formatted).expect("formatted uses lists should reparse");
+    assert_eq!(aura_parse::format_program(&reparsed), formatted);
+}
+
+#[test]
+fn cell_decreases_clauses_round_trip() {
+    let src = "cell walk(n: u32) -> u32 uses [] decreases n:\n    n\n\ncell tick(n: u32) -> decreases n + 1:\n    io.println(\"tick\")\n";
+    let program = parse_source(src).expect("decreases clauses should parse");
+    let aura_ast::Stmt::CellDef(walk) = &program.stmts[0] else { panic!("expected cell") };
+    assert!(walk.ret.is_some() && walk.decreases.is_some());
+    let aura_ast::Stmt::CellDef(tick) = &program.stmts[1] else { panic!("expected cell") };
+    assert!(tick.ret.is_none() && tick.flow.is_some() && tick.decreases.is_some());
+    let formatted = aura_parse::format_program(&program);
+    assert!(formatted.contains("cell walk(n: u32) -> u32 uses [] decreases n:"), "{formatted}");
+    assert!(formatted.contains("cell tick(n: u32) -> decreases n + 1:"), "{formatted}");
+    let reparsed = parse_source(&formatted).expect("formatted decreases clauses should reparse");
+    assert_eq!(aura_parse::format_program(&reparsed), formatted);
+}
@@ -61,6 +61,40 @@ pub fn detect_host() -> HostKind {
     }
 }
 
+/// Classifies a target triple the way `detect_host` classifies the host.
+pub fn target_kind(triple: &str) -> HostKind {
+    let t = triple.to_ascii_lowercase();
+    if t.starts_with("x86_64-") && t.contains("-windows") && !t.ends_with("-gnu") {
+        HostKind::WindowsX64Msvc
+    } else {
+        HostKind::Other
+    }
+}
+
+/// The host as a target triple, used when `AddOptions::target` is unset.
+pub fn host_triple() -> String {
+    let arch = std::env::consts::ARCH;
+    match std::env::consts::OS {
+        "windows" if cfg!(target_env = "gnu") => format!("{arch}-pc-windows-gnu"),
+        "windows" => format!("{arch}-pc-windows-msvc"),
+        "macos" => format!("{arch}-apple-darwin"),
+        os => format!("{arch}-unknown-{os}-gnu"),
+    }
+}
+
+/// Extensions of link-time and run-time libraries for a target: `lib`/`dll` on Windows,
+/// `a`/`dylib` on macOS and `a`/`so` elsewhere.
+fn lib_extensions(triple: &str) -> (&'static str, &'static str) {
+    let t = triple.to_ascii_lowercase();
+    if t.contains("-windows") {
+        ("lib", "dll")
+    } else if t.contains("-apple-") || t.contains("-darwin") {
+        ("a", "dylib")
+    } else {
+        ("a", "so")
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct ProjectLayout {
     pub root: PathBuf,
@@ -105,6 +139,10 @@ pub struct AddOptions {
 
     /// If true, fail when selecting a deprecated package version.
     pub deny_deprecated: bool,
+
+    /// Target triple to fetch artifacts for (e.g. `x86_64-pc-windows-msvc` from a Linux
+    /// host); the host when unset.
+    pub target: Option<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -181,6 +219,10 @@ pub struct RegistryVersion {
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub deprecated: Option<Deprecation>,
+
+    /// Target triple the artifact was built for; entries without one suit every target.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -198,18 +240,18 @@ pub fn add_package(project_root: &Path, opts: &AddOptions) -> Result<InstallResu
     fs::create_dir_all(&layout.include_dir).into_diagnostic()?;
     fs::create_dir_all(&layout.cache_dir).into_diagnostic()?;
 
-    let host = detect_host();
-    if host != HostKind::WindowsX64Msvc {
-        return Err(pkg_msg(
-            "Stage 18: only Windows x64 artifact retrieval is implemented",
-        ));
-    }
-
     // If a registry is provided, use the registry workflow.
     if opts.registry.is_some() {
         return install_from_registry(&layout, opts);
     }
 
+    let target = opts.target.clone().unwrap_or_else(host_triple);
+    if target_kind(&target) != HostKind::WindowsX64Msvc {
+        return Err(pkg_msg(format!(
+            "Stage 18: only Windows x64 artifact retrieval is implemented (requested {target}; pass --target x86_64-pc-windows-msvc to fetch them from any host)"
+        )));
+    }
+
     // Back-compat: legacy, hardcoded native packages with discovery.
     let pkg = opts.package.to_ascii_lowercase();
     match pkg.as_str() {
@@ -229,7 +271,8 @@ fn install_from_registry(layout: &ProjectLayout, opts: &AddOptions) -> Result<In
 
     let index = load_registry_index(registry, &opts.package)?;
     let req = parse_version_req(opts.version.as_deref())?;
-    let selected = select_version(&index, req.as_ref())?;
+    let target = opts.target.clone().unwrap_or_else(host_triple);
+    let selected = select_version(&index, req.as_ref(), &target)?;
 
     if let Some(dep) = &selected.deprecated {
         let mut msg = format!("deprecated package version {} {}: {}", opts.package, selected.version, dep.message);
@@ -252,10 +295,13 @@ fn install_from_registry(layout: &ProjectLayout, opts: &AddOptions) -> Result<In
     // Resolve URL relative to registry root.
     let resolved_url = resolve_registry_url(registry, &opts.package, &selected.url);
 
-    let cache_pkg_dir = layout
+    let mut cache_pkg_dir = layout
         .cache_dir
         .join(sanitize_component(&opts.package))
         .join(sanitize_component(&selected.version));
+    if let Some(t) = &selected.target {
+        cache_pkg_dir = cache_pkg_dir.join(sanitize_component(t));
+    }
     fs::create_dir_all(&cache_pkg_dir).into_diagnostic()?;
     let zip_path = cache_pkg_dir.join("artifact.zip");
 
@@ -282,9 +328,13 @@ fn install_from_registry(layout: &ProjectLayout, opts: &AddOptions) -> Result<In
         })?;
     }
 
-    // TOFU lock: verify or record.
+    // TOFU lock: verify or record. Per-target artifacts are pinned separately.
+    let lock_key = match &selected.target {
+        Some(t) => format!("{}@{t}", opts.package),
+        None => opts.package.clone(),
+    };
     let mut lock = read_lock(&layout.lock_path)?;
-    let existing = lock.packages.get(&opts.package).cloned();
+    let existing = lock.packages.get(&lock_key).cloned();
     if let Some(existing) = &existing {
         if !opts.force && existing.sha256 != sha256 {
             return Err(pkg_msg(format!(
@@ -306,7 +356,7 @@ fn install_from_registry(layout: &ProjectLayout, opts: &AddOptions) -> Result<In
     };
 
     lock.packages.insert(
-        opts.package.clone(),
+        lock_key,
         LockedPackage {
             version: selected.version.clone(),
             url: resolved_url.clone(),
@@ -318,7 +368,7 @@ fn install_from_registry(layout: &ProjectLayout, opts: &AddOptions) -> Result<In
     );
     write_lock(&layout.lock_path, &lock)?;
 
-    let (libs, dlls, headers) = extract_zip_layout_zip(&zip_bytes, layout)?;
+    let (libs, dlls, headers) = extract_zip_layout_zip(&zip_bytes, layout, &target)?;
 
     Ok(InstallResult {
         package: opts.package.clone(),
@@ -350,9 +400,16 @@ fn parse_version_req(s: Option<&str>) -> Result<Option<VersionReq>, PkgError> {
         .map_err(|e| pkg_msg(format!("invalid version requirement '{s}': {e}")))
 }
 
-fn select_version<'a>(index: &'a RegistryIndex, req: Option<&VersionReq>) -> Result<&'a RegistryVersion, PkgError> {
+fn select_version<'a>(
+    index: &'a RegistryIndex,
+    req: Option<&VersionReq>,
+    target: &str,
+) -> Result<&'a RegistryVersion, PkgError> {
     let mut candidates: Vec<(&RegistryVersion, Version)> = Vec::new();
     for v in &index.versions {
+        if v.target.as_ref().is_some_and(|t| !t.eq_ignore_ascii_case(target)) {
+            continue;
+        }
         let ver = Version::parse(v.version.trim()).map_err(|e| {
             pkg_msg(format!("registry contains non-semver version '{}' for {}: {e}", v.version, index.package))
         })?;
@@ -364,13 +421,14 @@ fn select_version<'a>(index: &'a RegistryIndex, req: Option<&VersionReq>) -> Res
         candidates.push((v, ver));
     }
 
-    candidates.sort_by(|a, b| a.1.cmp(&b.1));
+    // A build for the exact target wins over a target-independent one of the same version.
+    candidates.sort_by(|a, b| a.1.cmp(&b.1).then(a.0.target.is_some().cmp(&b.0.target.is_some())));
     candidates
         .last()
         .map(|(v, _)| *v)
         .ok_or_else(|| {
             let req_s = req.map(|r| r.to_string()).unwrap_or_else(|| "(any)".to_string());
-            pkg_msg(format!("no matching versions for {} {req_s}", index.package))
+            pkg_msg(format!("no matching versions for {} {req_s} on {target}", index.package))
         })
 }
 
@@ -460,12 +518,18 @@ fn verify_signature_over_sha256(public_key_path: &Path, sha256_hex_str: &str, si
         .map_err(|e| format!("signature mismatch: {e}"))
 }
 
-/// Extracts a registry-published zip (expects `deps/**` and `include/**`).
-fn extract_zip_layout_zip(zip_bytes: &[u8], layout: &ProjectLayout) -> Result<(Vec<PathBuf>, Vec<PathBuf>, Vec<PathBuf>), PkgError> {
+/// Extracts a registry-published zip (expects `deps/**` and `include/**`). Libraries are
+/// recognized by `target`'s extensions.
+fn extract_zip_layout_zip(
+    zip_bytes: &[u8],
+    layout: &ProjectLayout,
+    target: &str,
+) -> Result<(Vec<PathBuf>, Vec<PathBuf>, Vec<PathBuf>), PkgError> {
     use zip::ZipArchive;
     let reader = std::io::Cursor::new(zip_bytes);
     let mut zip = ZipArchive::new(reader).into_diagnostic()?;
 
+    let (lib_ext, dll_ext) = lib_extensions(target);
     let mut libs = Vec::new();
     let mut dlls = Vec::new();
     let mut headers = Vec::new();
@@ -487,10 +551,10 @@ fn extract_zip_layout_zip(zip_bytes: &[u8], layout: &ProjectLayout) -> Result<(V
             fs::write(&out_path, &buf).into_diagnostic()?;
             let is_lib = out_path
                 .extension()
-                .is_some_and(|e| e.eq_ignore_ascii_case("lib"));
+                .is_some_and(|e| e.eq_ignore_ascii_case(lib_ext));
             let is_dll = out_path
                 .extension()
-                .is_some_and(|e| e.eq_ignore_ascii_case("dll"));
+                .is_some_and(|e| e.eq_ignore_ascii_case(dll_ext));
             if is_lib {
                 libs.push(out_path.clone());
             }
@@ -521,6 +585,8 @@ pub struct PublishOptions {
     pub version: String,
     pub registry_dir: PathBuf,
     pub from_dir: PathBuf,
+    /// Target triple the artifact was built for; `None` for target-independent packages.
+    pub target: Option<String>,
     /// Optional signing key file (hex-encoded 32-byte ed25519 secret key).
     pub signing_key: Option<PathBuf>,
     pub signature_key_id: Option<String>,
@@ -546,7 +612,10 @@ pub fn publish_package(opts: &PublishOptions) -> Result<(String, String), PkgErr
     }
     fs::create_dir_all(&pkg_dir).into_diagnostic()?;
 
-    let artifact_rel = format!("{}.zip", opts.version);
+    let artifact_rel = match &opts.target {
+        Some(t) => format!("{}-{}.zip", opts.version, sanitize_component(t)),
+        None => format!("{}.zip", opts.version),
+    };
     let artifact_path = pkg_dir.join(&artifact_rel);
     fs::write(&artifact_path, &zip_bytes).into_diagnostic()?;
 
@@ -562,8 +631,8 @@ pub fn publish_package(opts: &PublishOptions) -> Result<(String, String), PkgErr
         }
     };
 
-    // Upsert version.
-    index.versions.retain(|v| v.version != opts.version);
+    // Upsert version (one artifact per target).
+    index.versions.retain(|v| v.version != opts.version || v.target != opts.target);
     index.versions.push(RegistryVersion {
         version: opts.version.clone(),
         url: artifact_rel.clone(),
@@ -571,6 +640,7 @@ pub fn publish_package(opts: &PublishOptions) -> Result<(String, String), PkgErr
         signature: sig_b64.clone(),
         signature_key_id: key_id.clone(),
         deprecated: None,
+        target: opts.target.clone(),
     });
 
     // Ensure semver sorting in index.
@@ -728,6 +798,7 @@ mod tests {
             version: "1.0.0".to_string(),
             registry_dir: reg.clone(),
             from_dir: pkg_src.clone(),
+            target: None,
             signing_key: None,
             signature_key_id: None,
         })
@@ -740,6 +811,7 @@ mod tests {
             version: "1.2.0".to_string(),
             registry_dir: reg.clone(),
             from_dir: pkg_src.clone(),
+            target: None,
             signing_key: None,
             signature_key_id: None,
         })
@@ -757,6 +829,7 @@ mod tests {
                 require_signature: false,
                 trusted_public_key: None,
                 deny_deprecated: false,
+                target: None,
             },
         )
         .unwrap();
@@ -767,6 +840,68 @@ mod tests {
         assert!(proj.join("include").join("foo.h").exists());
     }
 
+    #[test]
+    fn registry_installs_artifacts_built_for_the_requested_target() {
+        let tmp = tempfile::tempdir().unwrap();
+        let reg = tmp.path().join("registry");
+        let win_src = tmp.path().join("win_src");
+        let linux_src = tmp.path().join("linux_src");
+        let proj = tmp.path().join("proj");
+        fs::create_dir_all(&reg).unwrap();
+        fs::create_dir_all(win_src.join("deps")).unwrap();
+        fs::create_dir_all(linux_src.join("deps")).unwrap();
+        fs::create_dir_all(&proj).unwrap();
+        fs::write(win_src.join("deps").join("foo.lib"), b"import lib").unwrap();
+        fs::write(win_src.join("deps").join("foo.dll"), b"dll").unwrap();
+        fs::write(linux_src.join("deps").join("libfoo.a"), b"archive").unwrap();
+
+        for (from, target) in [(&win_src, "x86_64-pc-windows-msvc"), (&linux_src, "x86_64-unknown-linux-gnu")] {
+            publish_package(&PublishOptions {
+                package: "acme/foo".to_string(),
+                version: "1.0.0".to_string(),
+                registry_dir: reg.clone(),
+                from_dir: from.clone(),
+                target: Some(target.to_string()),
+                signing_key: None,
+                signature_key_id: None,
+            })
+            .unwrap();
+        }
+
+        let add = |target: &str| {
+            add_package(
+                &proj,
+                &AddOptions {
+                    package: "acme/foo".to_string(),
+                    version: None,
+                    url: None,
+                    smoke_test: false,
+                    force: false,
+                    registry: Some(reg.to_string_lossy().to_string()),
+                    require_signature: false,
+                    trusted_public_key: None,
+                    deny_deprecated: false,
+                    target: Some(target.to_string()),
+                },
+            )
+        };
+
+        // A Windows build from any host gets the import library and the DLL.
+        let win = add("x86_64-pc-windows-msvc").unwrap();
+        assert_eq!(win.installed_libs, vec![proj.join("deps").join("foo.lib")]);
+        assert_eq!(win.installed_dlls, vec![proj.join("deps").join("foo.dll")]);
+
+        let linux = add("x86_64-unknown-linux-gnu").unwrap();
+        assert_eq!(linux.installed_libs, vec![proj.join("deps").join("libfoo.a")]);
+
+        let lock = fs::read_to_string(proj.join("aura.lock")).unwrap();
+        assert!(lock.contains("acme/foo@x86_64-pc-windows-msvc"), "{lock}");
+        assert!(lock.contains("acme/foo@x86_64-unknown-linux-gnu"), "{lock}");
+
+        let err = add("aarch64-apple-darwin").expect_err("no macOS build was published");
+        assert!(format!("{err:?}").contains("no matching versions"));
+    }
+
     #[test]
     fn registry_deprecation_can_be_denied() {
         let tmp = tempfile::tempdir().unwrap();
@@ -783,6 +918,7 @@ mod tests {
             version: "1.0.0".to_string(),
             registry_dir: reg.clone(),
             from_dir: pkg_src.clone(),
+            target: None,
             signing_key: None,
             signature_key_id: None,
         })
@@ -809,6 +945,7 @@ mod tests {
                 require_signature: false,
                 trusted_public_key: None,
                 deny_deprecated: true,
+                target: None,
             },
         )
         .expect_err("expected deny_deprecated to fail");
@@ -842,6 +979,7 @@ mod tests {
             version: "1.0.0".to_string(),
             registry_dir: reg.clone(),
             from_dir: pkg_src.clone(),
+            target: None,
             signing_key: Some(sk_path),
             signature_key_id: Some("test".to_string()),
         })
@@ -859,6 +997,7 @@ mod tests {
                 require_signature: true,
                 trusted_public_key: Some(vk_path),
                 deny_deprecated: false,
+                target: None,
             },
         )
         .unwrap();
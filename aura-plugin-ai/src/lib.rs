@@ -1,9 +1,11 @@
 #![forbid(unsafe_code)]
 
 use aura_ast::{ExprKind};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 
-use aura_nexus::{AuraPlugin, NexusDiagnostic, PluginCapability, Z3Call, Z3CallEnv};
+use aura_bridge::onnx::{OnnxElemType, OnnxQuantParams};
+use aura_nexus::{AuraPlugin, NexusContext, NexusDiagnostic, NexusFileContext, PluginCapability, Z3Call, Z3CallEnv};
 
 #[cfg(feature = "z3")]
 use aura_nexus::{record_proof, ProofNote};
@@ -11,6 +13,167 @@ use aura_nexus::{record_proof, ProofNote};
 #[cfg(feature = "z3")]
 use z3::ast::Ast;
 
+mod model_cache;
+
+pub use model_cache::ModelContractCache;
+
+/// Range a symbolic ONNX dimension may take (aura.toml `[ai.dims]`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DimBounds {
+    pub min: u64,
+    /// `None` leaves the dimension unbounded above.
+    pub max: Option<u64>,
+}
+
+impl Default for DimBounds {
+    fn default() -> Self {
+        Self { min: 1, max: None }
+    }
+}
+
+impl DimBounds {
+    pub fn describe(&self) -> String {
+        match self.max {
+            Some(max) => format!("[{}..{max}]", self.min),
+            None => format!(">= {}", self.min),
+        }
+    }
+}
+
+/// `[ai]` settings from aura.toml.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AiConfig {
+    /// `[ai.dims]`: bounds of symbolic dimensions, by name.
+    pub dims: BTreeMap<String, DimBounds>,
+    /// `max_batch`: the largest batch `ai.infer_batch` may run; `None` leaves it unbounded.
+    pub max_batch: Option<u64>,
+}
+
+/// Reads `[ai]` from the nearest aura.toml at or above `start`. Each `[ai.dims]` key names a
+/// symbolic dimension and maps to `{ min, max }` (`min` defaults to 1) or to a bare maximum.
+pub fn load_ai_config(start: &Path) -> miette::Result<AiConfig> {
+    let mut cur = if start.is_file() {
+        start.parent().unwrap_or_else(|| Path::new(".")).to_path_buf()
+    } else {
+        start.to_path_buf()
+    };
+    let manifest = loop {
+        let candidate = cur.join("aura.toml");
+        if candidate.exists() {
+            break candidate;
+        }
+        if !cur.pop() {
+            return Ok(AiConfig::default());
+        }
+    };
+
+    let raw = std::fs::read_to_string(&manifest).map_err(|e| miette::miette!("failed to read {}: {e}", manifest.display()))?;
+    let doc: toml::Value = raw
+        .parse()
+        .map_err(|e| miette::miette!("failed to parse {}: {e}", manifest.display()))?;
+    let Some(ai) = doc.get("ai") else {
+        return Ok(AiConfig::default());
+    };
+    let as_u64 = |v: &toml::Value| v.as_integer().and_then(|i| u64::try_from(i).ok());
+
+    let max_batch = match ai.get("max_batch") {
+        Some(v) => Some(as_u64(v).filter(|m| *m > 0).ok_or_else(|| {
+            miette::miette!("invalid [ai] max_batch in {}: expected a positive integer", manifest.display())
+        })?),
+        None => None,
+    };
+
+    let Some(dims) = ai.get("dims") else {
+        return Ok(AiConfig {
+            dims: BTreeMap::new(),
+            max_batch,
+        });
+    };
+    let dims = dims
+        .as_table()
+        .ok_or_else(|| miette::miette!("[ai.dims] in {} must be a table", manifest.display()))?;
+
+    let mut out = BTreeMap::new();
+    for (name, v) in dims {
+        let bad = |why: &str| miette::miette!("invalid [ai.dims] entry '{name}' in {}: {why}", manifest.display());
+        let bounds = match v {
+            toml::Value::Table(t) => DimBounds {
+                min: match t.get("min") {
+                    Some(v) => as_u64(v).ok_or_else(|| bad("min must be a non-negative integer"))?,
+                    None => 1,
+                },
+                max: match t.get("max") {
+                    Some(v) => Some(as_u64(v).ok_or_else(|| bad("max must be a non-negative integer"))?),
+                    None => None,
+                },
+            },
+            v => DimBounds {
+                min: 1,
+                max: Some(as_u64(v).ok_or_else(|| bad("expected { min, max } or a maximum"))?),
+            },
+        };
+        if bounds.max.is_some_and(|max| max < bounds.min) {
+            return Err(bad(&format!("min {} exceeds max {}", bounds.min, bounds.max.unwrap_or_default())));
+        }
+        out.insert(name.clone(), bounds);
+    }
+    Ok(AiConfig { dims: out, max_batch })
+}
+
+/// One dimension of a model's input or output.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ModelDim {
+    Fixed(u64),
+    /// A dynamic dimension; dimensions sharing a name take the same value in one inference.
+    Symbolic { name: Option<String>, bounds: DimBounds },
+}
+
+/// Shape contract of a loaded model.
+#[derive(Clone, Debug)]
+struct ModelContract {
+    input: Vec<ModelDim>,
+    output: Vec<ModelDim>,
+    /// Element types; `None` when the model does not declare one.
+    input_elem: Option<OnnxElemType>,
+    output_elem: Option<OnnxElemType>,
+    /// Scale and zero point when the model's input is quantized.
+    input_quant: Option<OnnxQuantParams>,
+}
+
+#[derive(Default)]
+struct AiState {
+    /// `[ai]` from aura.toml, read on first use.
+    config: Option<AiConfig>,
+    /// Contracts keyed by model handle, for `ai.infer`.
+    models: HashMap<String, ModelContract>,
+}
+
+fn ai_state(nexus: &mut NexusContext) -> &mut AiState {
+    if nexus.get::<AiState>().is_none() {
+        nexus.insert(AiState::default());
+    }
+    nexus.get_mut::<AiState>().expect("inserted")
+}
+
+#[cfg(feature = "z3")]
+fn ai_config<'ctx>(
+    env: &mut dyn Z3CallEnv<'ctx>,
+    span: aura_ast::Span,
+    plugin: &'static str,
+) -> Result<AiConfig, NexusDiagnostic> {
+    if let Some(config) = &ai_state(env.nexus()).config {
+        return Ok(config.clone());
+    }
+    let start = env
+        .nexus()
+        .get::<NexusFileContext>()
+        .and_then(|fc| fc.manifest_path.clone().or_else(|| fc.source_path.clone()))
+        .unwrap_or_else(|| PathBuf::from("."));
+    let config = load_ai_config(&start).map_err(|e| NexusDiagnostic::new(plugin, span, e.to_string()))?;
+    ai_state(env.nexus()).config = Some(config.clone());
+    Ok(config)
+}
+
 pub struct AuraAiPlugin;
 
 impl AuraAiPlugin {
@@ -36,7 +199,11 @@ impl AuraPlugin for AuraAiPlugin {
     ) -> Option<Result<z3::ast::Int<'ctx>, NexusDiagnostic>> {
         match call.name {
             "ai.load_model" => Some(ai_load_model(call, env, self.name())),
-            "ai.infer" => Some(ai_infer(call, env, self.name())),
+            "ai.infer" => Some(ai_infer(call, env, self.name(), false)),
+            "ai.infer_batch" => Some(ai_infer(call, env, self.name(), true)),
+            "tensor.matmul" => Some(tensor_matmul(call, env, self.name())),
+            "tensor.reshape" => Some(tensor_reshape(call, env, self.name())),
+            "tensor.concat" => Some(tensor_concat(call, env, self.name())),
             _ => None,
         }
     }
@@ -74,40 +241,56 @@ fn ai_load_model<'ctx>(
         }
     }
 
-    let shapes = aura_bridge::onnx::read_onnx_io_shapes(&path).map_err(|e| {
+    // Contracts are cached by path and content hash: in the NexusContext, and on disk next to
+    // the manifest (or under the working directory).
+    let cache = match env.nexus().get::<ModelContractCache>() {
+        Some(c) => c.clone(),
+        None => {
+            let c = ModelContractCache::default();
+            env.nexus().insert(c.clone());
+            c
+        }
+    };
+    let project_dir = env
+        .nexus()
+        .get::<NexusFileContext>()
+        .and_then(|fc| fc.manifest_path.as_deref()?.parent().map(Path::to_path_buf))
+        .unwrap_or_default();
+    let disk_dir = project_dir.join(".aura").join("cache").join("onnx");
+    let shapes = cache.io_shapes(&path, Some(&disk_dir)).map_err(|e| {
         NexusDiagnostic::new(
             plugin,
             call.args[0].span,
-            format!("failed to read ONNX model contract from '{path_s}': {e}"),
+            format!("failed to read model contract from '{path_s}': {e}"),
         )
     })?;
 
-    let to_static = |dims: Vec<aura_bridge::onnx::OnnxDim>| -> Result<Vec<u64>, NexusDiagnostic> {
-        let mut out: Vec<u64> = Vec::with_capacity(dims.len());
-        for d in dims {
-            match d {
-                aura_bridge::onnx::OnnxDim::Known(v) if v > 0 => out.push(v as u64),
-                aura_bridge::onnx::OnnxDim::Known(_) => {
-                    return Err(NexusDiagnostic::new(
-                        plugin,
-                        call.args[0].span,
-                        "dynamic/unknown ONNX dims are not supported yet (please export a fixed-shape model)",
-                    ));
-                }
-                aura_bridge::onnx::OnnxDim::Symbolic(_) => {
-                    return Err(NexusDiagnostic::new(
-                        plugin,
-                        call.args[0].span,
-                        "symbolic ONNX dims are not supported yet (please export a fixed-shape model)",
-                    ));
-                }
-            }
-        }
-        Ok(out)
+    let dim_bounds = ai_config(env, call.span, plugin)?.dims;
+
+    // Dynamic dims (`dim_param` names, or a missing/non-positive `dim_value`) become symbolic.
+    let to_contract = |dims: Vec<aura_bridge::onnx::OnnxDim>| -> Vec<ModelDim> {
+        dims.into_iter()
+            .map(|d| match d {
+                aura_bridge::onnx::OnnxDim::Known(v) if v > 0 => ModelDim::Fixed(v as u64),
+                aura_bridge::onnx::OnnxDim::Known(_) => ModelDim::Symbolic {
+                    name: None,
+                    bounds: DimBounds::default(),
+                },
+                aura_bridge::onnx::OnnxDim::Symbolic(name) => ModelDim::Symbolic {
+                    bounds: dim_bounds.get(&name).copied().unwrap_or_default(),
+                    name: Some(name),
+                },
+            })
+            .collect()
     };
 
-    let in_dims = to_static(shapes.input)?;
-    let out_dims = to_static(shapes.output)?;
+    let contract = ModelContract {
+        input: to_contract(shapes.input),
+        output: to_contract(shapes.output),
+        input_elem: shapes.input_elem,
+        output_elem: shapes.output_elem,
+        input_quant: shapes.input_quant,
+    };
 
     let h = env.fresh_int("model");
 
@@ -126,7 +309,8 @@ fn ai_load_model<'ctx>(
         &[&z3::Sort::int(env.ctx()), &z3::Sort::int(env.ctx())],
         &z3::Sort::int(env.ctx()),
     );
-    for (i, d) in in_dims.iter().copied().enumerate() {
+    for (i, d) in contract.input.iter().enumerate() {
+        let ModelDim::Fixed(d) = *d else { continue };
         let idx = z3::ast::Int::from_u64(env.ctx(), i as u64);
         let dim = f_in.apply(&[&h, &idx]).as_int().expect("int");
         let dv = z3::ast::Int::from_u64(env.ctx(), d);
@@ -139,36 +323,45 @@ fn ai_load_model<'ctx>(
         &[&z3::Sort::int(env.ctx()), &z3::Sort::int(env.ctx())],
         &z3::Sort::int(env.ctx()),
     );
-    for (i, d) in out_dims.iter().copied().enumerate() {
+    for (i, d) in contract.output.iter().enumerate() {
+        let ModelDim::Fixed(d) = *d else { continue };
         let idx = z3::ast::Int::from_u64(env.ctx(), i as u64);
         let dim = f_out.apply(&[&h, &idx]).as_int().expect("int");
         let dv = z3::ast::Int::from_u64(env.ctx(), d);
         env.push_constraint(dim._eq(&dv));
     }
 
-    // Store shapes keyed by the model handle so `ai.infer` can check them.
-    #[derive(Default)]
-    struct ModelShapesByHandle(HashMap<String, (Vec<u64>, Vec<u64>)>);
-
-    if env.nexus().get::<ModelShapesByHandle>().is_none() {
-        env.nexus().insert(ModelShapesByHandle::default());
+    // Element types: model_in_dtype(handle) / model_out_dtype(handle) == ONNX type code.
+    for (fname, elem) in [("model_in_dtype", contract.input_elem), ("model_out_dtype", contract.output_elem)] {
+        let Some(elem) = elem else { continue };
+        let f = z3::FuncDecl::new(env.ctx(), fname, &[&z3::Sort::int(env.ctx())], &z3::Sort::int(env.ctx()));
+        let dtype = f.apply(&[&h]).as_int().expect("int");
+        env.push_constraint(dtype._eq(&z3::ast::Int::from_i64(env.ctx(), elem.0.into())));
     }
-    env.nexus()
-        .get_mut::<ModelShapesByHandle>()
-        .expect("inserted")
-        .0
-        .insert(h.to_string(), (in_dims, out_dims));
+
+    // Store the contract keyed by the model handle so `ai.infer` can check it.
+    ai_state(env.nexus()).models.insert(h.to_string(), contract);
 
     Ok(h)
 }
 
 #[cfg(feature = "z3")]
+/// `ai.infer(model, input)`, or with `batched`, `ai.infer_batch(model, input, batch)`: the
+/// model's leading input and output dimension is the batch, which takes the size `batch`.
 fn ai_infer<'ctx>(
     call: &Z3Call<'_, '_>,
     env: &mut dyn Z3CallEnv<'ctx>,
     plugin: &'static str,
+    batched: bool,
 ) -> Result<z3::ast::Int<'ctx>, NexusDiagnostic> {
-    if call.args.len() != 2 {
+    if batched && call.args.len() != 3 {
+        return Err(NexusDiagnostic::new(
+            plugin,
+            call.span,
+            "ai.infer_batch expects 3 args (model, input, batch)",
+        ));
+    }
+    if !batched && call.args.len() != 2 {
         return Err(NexusDiagnostic::new(
             plugin,
             call.span,
@@ -179,13 +372,10 @@ fn ai_infer<'ctx>(
     let model = env.eval_int(call.args[0])?;
     let input = env.eval_int(call.args[1])?;
 
-    #[derive(Default)]
-    struct ModelShapesByHandle(HashMap<String, (Vec<u64>, Vec<u64>)>);
-
-    let shapes = env
-        .nexus()
-        .get::<ModelShapesByHandle>()
-        .and_then(|m| m.0.get(&model.to_string()).cloned())
+    let contract = ai_state(env.nexus())
+        .models
+        .get(&model.to_string())
+        .cloned()
         .ok_or_else(|| {
             NexusDiagnostic::new(
                 plugin,
@@ -194,9 +384,6 @@ fn ai_infer<'ctx>(
             )
         })?;
 
-    let in_shape = shapes.0;
-    let out_shape = shapes.1;
-
     let f_tdim = z3::FuncDecl::new(
         env.ctx(),
         "tensor_dim",
@@ -210,32 +397,207 @@ fn ai_infer<'ctx>(
         &z3::Sort::int(env.ctx()),
     );
 
+    // Bounds on a symbolic dim `v`.
+    let within = |ctx: &'ctx z3::Context, v: &z3::ast::Int<'ctx>, b: DimBounds| -> z3::ast::Bool<'ctx> {
+        let lo = v.ge(&z3::ast::Int::from_u64(ctx, b.min));
+        match b.max {
+            Some(max) => z3::ast::Bool::and(ctx, &[&lo, &v.le(&z3::ast::Int::from_u64(ctx, max))]),
+            None => lo,
+        }
+    };
+
+    // Symbolic dims are instantiated per call: each name takes the input tensor's size.
+    let mut symbols: BTreeMap<String, z3::ast::Int<'ctx>> = BTreeMap::new();
+    let mut symbol_notes: Vec<String> = Vec::new();
     let mut eqs: Vec<String> = Vec::new();
-    for (i, _d) in in_shape.iter().enumerate() {
+
+    // The batch size: at least 1 and at most `[ai] max_batch`, and the input's leading dim.
+    let batch = if batched {
+        if contract.input.is_empty() || contract.output.is_empty() {
+            return Err(NexusDiagnostic::new(
+                plugin,
+                call.args[0].span,
+                "Inference Error: ai.infer_batch needs a model whose input and output have a leading batch dim",
+            ));
+        }
+        let batch = env.eval_int(call.args[2])?;
+        let max_batch = ai_config(env, call.span, plugin)?.max_batch;
+        let mut facts = vec![(
+            within(env.ctx(), &batch, DimBounds { min: 1, max: max_batch }),
+            match max_batch {
+                Some(max) => format!("Inference Error: batch size may be outside [1..{max}] allowed by [ai] max_batch"),
+                None => "Inference Error: batch size may be zero".to_string(),
+            },
+            call.args[2].span,
+        )];
+        let td = f_tdim.apply(&[&input, &z3::ast::Int::from_u64(env.ctx(), 0)]).as_int().expect("int");
+        facts.push((
+            td._eq(&batch),
+            "Inference Error: input dim 0 may differ from the batch size".to_string(),
+            call.args[1].span,
+        ));
+        for (ok, message, span) in facts {
+            eqs.push(ok.to_string());
+            env.prove_implied(ok.not(), span, &message)?;
+            env.push_constraint(ok);
+        }
+        if let Some(max) = max_batch {
+            symbol_notes.push(format!("batch [1..{max}]"));
+        }
+        Some(batch)
+    } else {
+        None
+    };
+
+    for (i, d) in contract.input.iter().enumerate() {
         let idx = z3::ast::Int::from_u64(env.ctx(), i as u64);
         let td = f_tdim.apply(&[&input, &idx]).as_int().expect("int");
-        let md = f_mdim.apply(&[&model, &idx]).as_int().expect("int");
-        let ok = td._eq(&md);
+        let (ok, message) = match d {
+            // Input dim 0 is the batch size here, so these say what the batch must be.
+            ModelDim::Fixed(d) if i == 0 && batch.is_some() => {
+                let md = f_mdim.apply(&[&model, &idx]).as_int().expect("int");
+                (
+                    td._eq(&md),
+                    format!("Inference Error: batch size may differ from the model's fixed batch dim {d}"),
+                )
+            }
+            ModelDim::Symbolic { name, bounds } if i == 0 && batch.is_some() => {
+                let label = name.clone().unwrap_or_else(|| "input dim 0".to_string());
+                symbol_notes.push(format!("{label} {}", bounds.describe()));
+                if let Some(name) = name {
+                    symbols.insert(name.clone(), td.clone());
+                }
+                (
+                    within(env.ctx(), &td, *bounds),
+                    format!(
+                        "Inference Error: batch size may be outside {} allowed for symbolic dim '{label}'",
+                        bounds.describe()
+                    ),
+                )
+            }
+            ModelDim::Fixed(_) => {
+                let md = f_mdim.apply(&[&model, &idx]).as_int().expect("int");
+                (
+                    td._eq(&md),
+                    "Inference Error: input tensor shape does not match model contract".to_string(),
+                )
+            }
+            ModelDim::Symbolic { name: Some(name), .. } if symbols.contains_key(name) => (
+                td._eq(&symbols[name]),
+                format!("Inference Error: input dims that share symbolic dim '{name}' may differ (dim {i})"),
+            ),
+            ModelDim::Symbolic { name, bounds } => {
+                let label = name.clone().unwrap_or_else(|| format!("input dim {i}"));
+                symbol_notes.push(format!("{label} {}", bounds.describe()));
+                if let Some(name) = name {
+                    symbols.insert(name.clone(), td.clone());
+                }
+                (
+                    within(env.ctx(), &td, *bounds),
+                    format!(
+                        "Inference Error: input dim {i} may be outside {} allowed for symbolic dim '{label}'",
+                        bounds.describe()
+                    ),
+                )
+            }
+        };
 
         eqs.push(ok.to_string());
 
-        env.prove_implied(
-            ok.not(),
-            call.args[1].span,
-            "Inference Error: input tensor shape does not match model contract",
-        )?;
+        env.prove_implied(ok.not(), call.args[1].span, &message)?;
         env.push_constraint(ok);
     }
 
-    if !in_shape.is_empty() {
+    let f_tdtype = z3::FuncDecl::new(env.ctx(), "tensor_dtype", &[&z3::Sort::int(env.ctx())], &z3::Sort::int(env.ctx()));
+    if let Some(expected) = contract.input_elem {
+        let f_mdtype = z3::FuncDecl::new(env.ctx(), "model_in_dtype", &[&z3::Sort::int(env.ctx())], &z3::Sort::int(env.ctx()));
+        let td = f_tdtype.apply(&[&input]).as_int().expect("int");
+        let mut ok = td._eq(&f_mdtype.apply(&[&model]).as_int().expect("int"));
+        // A quantized model also takes refined values (`Tensor<u32[0..255], ...>`) that fit the
+        // domain of its stored integer type.
+        let quant = contract.input_quant.zip(expected.int_range());
+        let mut range_message = None;
+        if let Some((params, (qlo, qhi))) = quant {
+            let ctx = env.ctx();
+            let elem_fn = |name: &str| {
+                z3::FuncDecl::new(ctx, name, &[&z3::Sort::int(ctx)], &z3::Sort::int(ctx))
+                    .apply(&[&input])
+                    .as_int()
+                    .expect("int")
+            };
+            let ranged = elem_fn("tensor_elem_ranged")._eq(&z3::ast::Int::from_u64(ctx, 1));
+            let fits = z3::ast::Bool::and(
+                ctx,
+                &[
+                    &ranged,
+                    &elem_fn("tensor_elem_lo").ge(&z3::ast::Int::from_i64(ctx, qlo)),
+                    &elem_fn("tensor_elem_hi").le(&z3::ast::Int::from_i64(ctx, qhi)),
+                ],
+            );
+            if !env.satisfiable(ranged.not()) {
+                range_message = Some(format!(
+                    "Inference Error: input values may fall outside the model's quantized {} domain [{qlo}..{qhi}] (scale {}, zero point {})",
+                    expected.describe(),
+                    params.scale,
+                    params.zero_point
+                ));
+            }
+            ok = z3::ast::Bool::or(ctx, &[&ok, &fits]);
+        }
+        let message = if let Some(message) = range_message {
+            message
+        } else if env.satisfiable(ok.not()) {
+            // Name the element types the tensor may have, to say what was passed.
+            let ctx = env.ctx();
+            let possible: Vec<String> = OnnxElemType::all()
+                .filter(|t| *t != expected)
+                .filter(|t| env.satisfiable(td._eq(&z3::ast::Int::from_i64(ctx, t.0.into()))))
+                .map(|t| t.describe())
+                .collect();
+            match possible.as_slice() {
+                [actual] => format!(
+                    "Inference Error: model expects {} input but the tensor holds {actual}",
+                    expected.describe()
+                ),
+                _ => format!(
+                    "Inference Error: input tensor element type is not known to be {}; annotate it as Tensor<{}, ...>",
+                    expected.describe(),
+                    expected.describe()
+                ),
+            }
+        } else {
+            String::new()
+        };
+        eqs.push(ok.to_string());
+        env.prove_implied(ok.not(), call.args[1].span, &message)?;
+        env.push_constraint(ok);
+    }
+
+    if !contract.input.is_empty() || contract.input_elem.is_some() {
+        let symbolic = if symbol_notes.is_empty() {
+            String::new()
+        } else {
+            format!("; symbolic dims: {}", symbol_notes.join(", "))
+        };
         record_proof(
             env.nexus(),
             ProofNote {
                 plugin: plugin.to_string(),
                 span: call.span,
                 message: format!(
-                    "Verified by Aura-AI Plugin: input tensor shape matches model contract ({} dims).",
-                    in_shape.len()
+                    "Verified by Aura-AI Plugin: {}input tensor {} model contract ({} dims{symbolic}).",
+                    if batched { "batched " } else { "" },
+                    match (contract.input_elem, contract.input_quant) {
+                        (Some(elem), Some(q)) => format!(
+                            "shape and element type ({} quantized, scale {}, zero point {}) match",
+                            elem.describe(),
+                            q.scale,
+                            q.zero_point
+                        ),
+                        (Some(elem), None) => format!("shape and element type ({}) match", elem.describe()),
+                        (None, _) => "shape matches".to_string(),
+                    },
+                    contract.input.len()
                 ),
                 smt: Some(eqs.join(" AND ")),
                 related: Vec::new(),
@@ -254,20 +616,333 @@ fn ai_infer<'ctx>(
     env.push_constraint(out.ge(&lo));
     env.push_constraint(out.le(&hi));
 
-    if !out_shape.is_empty() {
+    if contract.output_elem.is_some() {
+        let f_mdtype = z3::FuncDecl::new(env.ctx(), "model_out_dtype", &[&z3::Sort::int(env.ctx())], &z3::Sort::int(env.ctx()));
+        let td = f_tdtype.apply(&[&out]).as_int().expect("int");
+        env.push_constraint(td._eq(&f_mdtype.apply(&[&model]).as_int().expect("int")));
+    }
+
+    if !contract.output.is_empty() {
         let f_mout = z3::FuncDecl::new(
             env.ctx(),
             "model_out_dim",
             &[&z3::Sort::int(env.ctx()), &z3::Sort::int(env.ctx())],
             &z3::Sort::int(env.ctx()),
         );
-        for (i, _d) in out_shape.iter().enumerate() {
+        for (i, d) in contract.output.iter().enumerate() {
             let idx = z3::ast::Int::from_u64(env.ctx(), i as u64);
             let td = f_tdim.apply(&[&out, &idx]).as_int().expect("int");
-            let md = f_mout.apply(&[&model, &idx]).as_int().expect("int");
-            env.push_constraint(td._eq(&md));
+            if i == 0
+                && let Some(batch) = &batch
+            {
+                // The output keeps the batch dim, which must fit the model's output dim 0.
+                let ok = match d {
+                    ModelDim::Fixed(_) => batch._eq(&f_mout.apply(&[&model, &idx]).as_int().expect("int")),
+                    ModelDim::Symbolic { name: Some(name), .. } if symbols.contains_key(name) => batch._eq(&symbols[name]),
+                    ModelDim::Symbolic { bounds, .. } => within(env.ctx(), batch, *bounds),
+                };
+                env.prove_implied(
+                    ok.not(),
+                    call.args[2].span,
+                    "Inference Error: the model's output dim 0 may not take the batch size",
+                )?;
+                env.push_constraint(ok);
+                env.push_constraint(td._eq(batch));
+                continue;
+            }
+            let fact = match d {
+                ModelDim::Fixed(_) => td._eq(&f_mout.apply(&[&model, &idx]).as_int().expect("int")),
+                ModelDim::Symbolic { name: Some(name), .. } if symbols.contains_key(name) => td._eq(&symbols[name]),
+                // Only in the output: some size within its bounds.
+                ModelDim::Symbolic { bounds, .. } => within(env.ctx(), &td, *bounds),
+            };
+            env.push_constraint(fact);
         }
+
+        // Rank and length too, so the result can feed tensor.matmul/reshape/concat.
+        let dims: Vec<z3::ast::Int<'ctx>> = (0..contract.output.len())
+            .map(|i| f_tdim.apply(&[&out, &z3::ast::Int::from_u64(env.ctx(), i as u64)]).as_int().expect("int"))
+            .collect();
+        note_tensor_rank_and_len(env, &out, &dims);
     }
 
     Ok(out)
 }
+
+/// Largest tensor rank the algebra intrinsics look for (as `AURA_AI_MAX_RANK` in aura-stdlib).
+#[cfg(feature = "z3")]
+const MAX_TENSOR_RANK: u64 = 8;
+
+/// The rank of `t`, which its `Tensor<T, [..]>` annotation (or the intrinsic that made it) fixes.
+#[cfg(feature = "z3")]
+fn tensor_rank<'ctx>(
+    env: &mut dyn Z3CallEnv<'ctx>,
+    t: &z3::ast::Int<'ctx>,
+    span: aura_ast::Span,
+    what: &str,
+    plugin: &'static str,
+) -> Result<u64, NexusDiagnostic> {
+    let f_rank = z3::FuncDecl::new(env.ctx(), "tensor_rank", &[&z3::Sort::int(env.ctx())], &z3::Sort::int(env.ctx()));
+    let rank = f_rank.apply(&[t]).as_int().expect("int");
+    for r in 0..=MAX_TENSOR_RANK {
+        let other = rank._eq(&z3::ast::Int::from_u64(env.ctx(), r)).not();
+        if !env.satisfiable(other) {
+            return Ok(r);
+        }
+    }
+    Err(NexusDiagnostic::new(
+        plugin,
+        span,
+        format!("Tensor Error: the shape of {what} is unknown; annotate it as Tensor<T, [..]>"),
+    ))
+}
+
+/// `tensor_dim(t, 0..rank)`.
+#[cfg(feature = "z3")]
+fn tensor_dims<'ctx>(env: &mut dyn Z3CallEnv<'ctx>, t: &z3::ast::Int<'ctx>, rank: u64) -> Vec<z3::ast::Int<'ctx>> {
+    let f_tdim = z3::FuncDecl::new(
+        env.ctx(),
+        "tensor_dim",
+        &[&z3::Sort::int(env.ctx()), &z3::Sort::int(env.ctx())],
+        &z3::Sort::int(env.ctx()),
+    );
+    (0..rank)
+        .map(|i| f_tdim.apply(&[t, &z3::ast::Int::from_u64(env.ctx(), i)]).as_int().expect("int"))
+        .collect()
+}
+
+/// Asserts `tensor_rank(t) == dims.len()` and `tensor_len(t) == product(dims)`.
+#[cfg(feature = "z3")]
+fn note_tensor_rank_and_len<'ctx>(env: &mut dyn Z3CallEnv<'ctx>, t: &z3::ast::Int<'ctx>, dims: &[z3::ast::Int<'ctx>]) {
+    let int = z3::Sort::int(env.ctx());
+    let f_rank = z3::FuncDecl::new(env.ctx(), "tensor_rank", &[&int], &int);
+    let rank = f_rank.apply(&[t]).as_int().expect("int");
+    env.push_constraint(rank._eq(&z3::ast::Int::from_u64(env.ctx(), dims.len() as u64)));
+
+    let f_len = z3::FuncDecl::new(env.ctx(), "tensor_len", &[&int], &int);
+    let len = f_len.apply(&[t]).as_int().expect("int");
+    let prod = dims
+        .iter()
+        .fold(z3::ast::Int::from_u64(env.ctx(), 1), |acc, d| acc * d);
+    env.push_constraint(len._eq(&prod));
+}
+
+/// A fresh tensor handle shaped `dims`, holding `like`'s element type.
+#[cfg(feature = "z3")]
+fn new_shaped_tensor<'ctx>(
+    env: &mut dyn Z3CallEnv<'ctx>,
+    dims: &[z3::ast::Int<'ctx>],
+    like: &z3::ast::Int<'ctx>,
+) -> z3::ast::Int<'ctx> {
+    let out = env.fresh_int("tensor");
+    let lo = z3::ast::Int::from_u64(env.ctx(), 0);
+    let hi = z3::ast::Int::from_u64(env.ctx(), 0xFFFF_FFFF);
+    env.push_constraint(out.ge(&lo));
+    env.push_constraint(out.le(&hi));
+
+    for (i, d) in tensor_dims(env, &out, dims.len() as u64).iter().zip(dims) {
+        env.push_constraint(i._eq(d));
+    }
+    note_tensor_rank_and_len(env, &out, dims);
+
+    let f_tdtype = z3::FuncDecl::new(env.ctx(), "tensor_dtype", &[&z3::Sort::int(env.ctx())], &z3::Sort::int(env.ctx()));
+    let dtype = f_tdtype.apply(&[&out]).as_int().expect("int");
+    env.push_constraint(dtype._eq(&f_tdtype.apply(&[like]).as_int().expect("int")));
+    out
+}
+
+/// Proves each `(fact, message)` in turn, assuming it afterwards, and records one ProofNote.
+#[cfg(feature = "z3")]
+fn prove_tensor_facts<'ctx>(
+    env: &mut dyn Z3CallEnv<'ctx>,
+    facts: Vec<(z3::ast::Bool<'ctx>, String)>,
+    span: aura_ast::Span,
+    plugin: &'static str,
+    message: String,
+) -> Result<(), NexusDiagnostic> {
+    let mut eqs: Vec<String> = Vec::new();
+    for (ok, why) in facts {
+        eqs.push(ok.to_string());
+        env.prove_implied(ok.not(), span, &why)?;
+        env.push_constraint(ok);
+    }
+    record_proof(
+        env.nexus(),
+        ProofNote {
+            plugin: plugin.to_string(),
+            span,
+            message,
+            smt: Some(eqs.join(" AND ")),
+            related: Vec::new(),
+            kind: "verified",
+            mask: None,
+            range: None,
+            unsat_core: Vec::new(),
+            interpolant: None,
+        },
+    );
+    Ok(())
+}
+
+/// `tensor.matmul(a, b)`: `[.., m, k] x [.., k, n] -> [.., m, n]`, with equal batch dims.
+#[cfg(feature = "z3")]
+fn tensor_matmul<'ctx>(
+    call: &Z3Call<'_, '_>,
+    env: &mut dyn Z3CallEnv<'ctx>,
+    plugin: &'static str,
+) -> Result<z3::ast::Int<'ctx>, NexusDiagnostic> {
+    if call.args.len() != 2 {
+        return Err(NexusDiagnostic::new(plugin, call.span, "tensor.matmul expects 2 args (a, b)"));
+    }
+
+    let a = env.eval_int(call.args[0])?;
+    let b = env.eval_int(call.args[1])?;
+    let rank = tensor_rank(env, &a, call.args[0].span, "the left operand", plugin)?;
+    let rank_b = tensor_rank(env, &b, call.args[1].span, "the right operand", plugin)?;
+    if rank < 2 || rank_b != rank {
+        return Err(NexusDiagnostic::new(
+            plugin,
+            call.span,
+            format!("Tensor Error: tensor.matmul needs operands of the same rank (at least 2), got rank {rank} and rank {rank_b}"),
+        ));
+    }
+    let r = rank as usize;
+    let da = tensor_dims(env, &a, rank);
+    let db = tensor_dims(env, &b, rank);
+
+    let mut facts = vec![(
+        da[r - 1]._eq(&db[r - 2]),
+        format!(
+            "Tensor Error: matmul inner dims may differ (dim {} of the left operand vs dim {} of the right)",
+            r - 1,
+            r - 2
+        ),
+    )];
+    for i in 0..r - 2 {
+        facts.push((da[i]._eq(&db[i]), format!("Tensor Error: matmul batch dim {i} may differ between the operands")));
+    }
+    prove_tensor_facts(
+        env,
+        facts,
+        call.span,
+        plugin,
+        format!("Verified by Aura-AI Plugin: matmul inner dims agree (rank {rank})."),
+    )?;
+
+    let mut dims = da[..r - 1].to_vec();
+    dims.push(db[r - 1].clone());
+    Ok(new_shaped_tensor(env, &dims, &a))
+}
+
+/// `tensor.reshape(t, [d0, d1, ..])`: same elements, new shape.
+#[cfg(feature = "z3")]
+fn tensor_reshape<'ctx>(
+    call: &Z3Call<'_, '_>,
+    env: &mut dyn Z3CallEnv<'ctx>,
+    plugin: &'static str,
+) -> Result<z3::ast::Int<'ctx>, NexusDiagnostic> {
+    if call.args.len() != 2 {
+        return Err(NexusDiagnostic::new(plugin, call.span, "tensor.reshape expects 2 args (tensor, shape)"));
+    }
+    let ExprKind::ListLit(shape) = &call.args[1].kind else {
+        return Err(NexusDiagnostic::new(
+            plugin,
+            call.args[1].span,
+            "tensor.reshape shape must be a list literal, e.g. [2, 6]",
+        ));
+    };
+    if shape.len() as u64 > MAX_TENSOR_RANK {
+        return Err(NexusDiagnostic::new(
+            plugin,
+            call.args[1].span,
+            format!("Tensor Error: tensor.reshape shape has rank {}, above the maximum of {MAX_TENSOR_RANK}", shape.len()),
+        ));
+    }
+
+    let t = env.eval_int(call.args[0])?;
+    let mut dims = Vec::with_capacity(shape.len());
+    for d in shape {
+        dims.push(env.eval_int(d)?);
+    }
+
+    let int = z3::Sort::int(env.ctx());
+    let f_len = z3::FuncDecl::new(env.ctx(), "tensor_len", &[&int], &int);
+    let len = f_len.apply(&[&t]).as_int().expect("int");
+    let prod = dims
+        .iter()
+        .fold(z3::ast::Int::from_u64(env.ctx(), 1), |acc, d| acc * d);
+    prove_tensor_facts(
+        env,
+        vec![(
+            prod._eq(&len),
+            "Tensor Error: reshape may change the element count (the new shape's product must equal tensor.len)".to_string(),
+        )],
+        call.args[1].span,
+        plugin,
+        format!("Verified by Aura-AI Plugin: reshape to rank {} preserves the element count.", dims.len()),
+    )?;
+
+    Ok(new_shaped_tensor(env, &dims, &t))
+}
+
+/// `tensor.concat(a, b, axis)`: joins along `axis`; the other dims must agree.
+#[cfg(feature = "z3")]
+fn tensor_concat<'ctx>(
+    call: &Z3Call<'_, '_>,
+    env: &mut dyn Z3CallEnv<'ctx>,
+    plugin: &'static str,
+) -> Result<z3::ast::Int<'ctx>, NexusDiagnostic> {
+    if call.args.len() != 3 {
+        return Err(NexusDiagnostic::new(plugin, call.span, "tensor.concat expects 3 args (a, b, axis)"));
+    }
+    let ExprKind::IntLit(axis) = call.args[2].kind else {
+        return Err(NexusDiagnostic::new(
+            plugin,
+            call.args[2].span,
+            "tensor.concat axis must be an integer literal",
+        ));
+    };
+
+    let a = env.eval_int(call.args[0])?;
+    let b = env.eval_int(call.args[1])?;
+    let rank = tensor_rank(env, &a, call.args[0].span, "the first operand", plugin)?;
+    let rank_b = tensor_rank(env, &b, call.args[1].span, "the second operand", plugin)?;
+    if rank_b != rank {
+        return Err(NexusDiagnostic::new(
+            plugin,
+            call.span,
+            format!("Tensor Error: tensor.concat needs operands of the same rank, got rank {rank} and rank {rank_b}"),
+        ));
+    }
+    if axis >= rank {
+        return Err(NexusDiagnostic::new(
+            plugin,
+            call.args[2].span,
+            format!("Tensor Error: concat axis {axis} is out of range for rank-{rank} tensors"),
+        ));
+    }
+    let axis = axis as usize;
+    let da = tensor_dims(env, &a, rank);
+    let db = tensor_dims(env, &b, rank);
+
+    let facts = (0..rank as usize)
+        .filter(|i| *i != axis)
+        .map(|i| {
+            (
+                da[i]._eq(&db[i]),
+                format!("Tensor Error: concat along axis {axis} needs equal dim {i}, which may differ"),
+            )
+        })
+        .collect();
+    prove_tensor_facts(
+        env,
+        facts,
+        call.span,
+        plugin,
+        format!("Verified by Aura-AI Plugin: concat along axis {axis} joins tensors whose other dims agree (rank {rank})."),
+    )?;
+
+    let mut dims = da.clone();
+    dims[axis] = &da[axis] + &db[axis];
+    Ok(new_shaped_tensor(env, &dims, &a))
+}
@@ -0,0 +1,168 @@
+//! Model IO contracts (ONNX, safetensors, GGUF), cached by model path and content hash.
+//!
+//! `ai.load_model` runs on every verification pass, and the LSP verifies on every edit.
+//! Contracts are kept in memory (a `ModelContractCache` in the `NexusContext`, which a
+//! long-lived caller can share across passes) and on disk under `.aura/cache/onnx`. A
+//! model whose size and modification time are unchanged is not read again; otherwise it
+//! is hashed, and only parsed when no cache holds a contract for that path and hash.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use aura_bridge::onnx::{OnnxDim, OnnxElemType, OnnxIoShapes, OnnxQuantParams};
+use miette::IntoDiagnostic;
+use sha2::Digest;
+
+/// Parsed model contracts by path. Clones share the same entries.
+#[derive(Clone, Debug, Default)]
+pub struct ModelContractCache {
+    entries: Arc<Mutex<HashMap<PathBuf, CachedModel>>>,
+}
+
+#[derive(Clone, Debug)]
+struct CachedModel {
+    len: u64,
+    modified: Option<SystemTime>,
+    sha256: String,
+    shapes: OnnxIoShapes,
+}
+
+impl ModelContractCache {
+    /// The contract of the model at `path`; `disk_dir` holds the persistent cache, if any.
+    pub fn io_shapes(&self, path: &Path, disk_dir: Option<&Path>) -> miette::Result<OnnxIoShapes> {
+        let meta = fs::metadata(path).into_diagnostic()?;
+        let (len, modified) = (meta.len(), meta.modified().ok());
+
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(hit) = entries.get(path)
+            && hit.len == len
+            && hit.modified.is_some()
+            && hit.modified == modified
+        {
+            return Ok(hit.shapes.clone());
+        }
+
+        let bytes = fs::read(path).into_diagnostic()?;
+        let mut hasher = sha2::Sha256::new();
+        hasher.update(&bytes);
+        let sha256 = hex::encode(hasher.finalize());
+        let cached = match entries.get(path) {
+            Some(hit) if hit.sha256 == sha256 => Some(hit.shapes.clone()),
+            _ => disk_dir.and_then(|dir| read_disk_entry(&disk_entry_path(dir, path, &sha256), &sha256)),
+        };
+        let shapes = match cached {
+            Some(shapes) => shapes,
+            None => {
+                let shapes = aura_bridge::model::parse_model_io_shapes(path, &bytes)?;
+                // Best effort: a cache that cannot be written only costs the next pass a parse.
+                if let Some(dir) = disk_dir {
+                    let _ = write_disk_entry(&disk_entry_path(dir, path, &sha256), path, &sha256, &shapes);
+                }
+                shapes
+            }
+        };
+
+        entries.insert(
+            path.to_path_buf(),
+            CachedModel {
+                len,
+                modified,
+                sha256,
+                shapes: shapes.clone(),
+            },
+        );
+        Ok(shapes)
+    }
+}
+
+/// Bumped when entries gain fields, so entries written by older builds are not read.
+const DISK_FORMAT: &[u8] = b"2";
+
+/// `<dir>/<sha256(format, path, model sha256)>.toml`.
+fn disk_entry_path(dir: &Path, model: &Path, sha256: &str) -> PathBuf {
+    let mut hasher = sha2::Sha256::new();
+    hasher.update(DISK_FORMAT);
+    hasher.update(b"\0");
+    hasher.update(model.to_string_lossy().as_bytes());
+    hasher.update(b"\0");
+    hasher.update(sha256.as_bytes());
+    dir.join(format!("{}.toml", hex::encode(hasher.finalize())))
+}
+
+fn read_disk_entry(file: &Path, sha256: &str) -> Option<OnnxIoShapes> {
+    let doc: toml::Table = fs::read_to_string(file).ok()?.parse().ok()?;
+    if doc.get("sha256")?.as_str()? != sha256 {
+        return None;
+    }
+    let dims = |key: &str| -> Option<Vec<OnnxDim>> {
+        doc.get(key)?
+            .as_array()?
+            .iter()
+            .map(|d| match d {
+                toml::Value::Integer(v) => Some(OnnxDim::Known(*v)),
+                toml::Value::String(name) => Some(OnnxDim::Symbolic(name.clone())),
+                _ => None,
+            })
+            .collect()
+    };
+    let elem = |key: &str| -> Option<OnnxElemType> {
+        doc.get(key)
+            .and_then(|v| v.as_integer())
+            .and_then(|code| i32::try_from(code).ok())
+            .map(OnnxElemType)
+    };
+    let input_quant = match (doc.get("input_scale"), doc.get("input_zero_point")) {
+        (Some(scale), Some(zero_point)) => Some(OnnxQuantParams {
+            scale: scale.as_float()? as f32,
+            zero_point: zero_point.as_integer()?,
+        }),
+        _ => None,
+    };
+    Some(OnnxIoShapes {
+        input: dims("input")?,
+        output: dims("output")?,
+        input_elem: elem("input_elem"),
+        output_elem: elem("output_elem"),
+        input_quant,
+    })
+}
+
+fn write_disk_entry(file: &Path, model: &Path, sha256: &str, shapes: &OnnxIoShapes) -> miette::Result<()> {
+    let dims = |dims: &[OnnxDim]| -> toml::Value {
+        toml::Value::Array(
+            dims.iter()
+                .map(|d| match d {
+                    OnnxDim::Known(v) => toml::Value::Integer(*v),
+                    OnnxDim::Symbolic(name) => toml::Value::String(name.clone()),
+                })
+                .collect(),
+        )
+    };
+
+    let mut doc = toml::Table::new();
+    doc.insert("model".to_string(), toml::Value::String(model.to_string_lossy().into_owned()));
+    doc.insert("sha256".to_string(), toml::Value::String(sha256.to_string()));
+    doc.insert("input".to_string(), dims(&shapes.input));
+    doc.insert("output".to_string(), dims(&shapes.output));
+    for (key, elem) in [("input_elem", shapes.input_elem), ("output_elem", shapes.output_elem)] {
+        if let Some(elem) = elem {
+            doc.insert(key.to_string(), toml::Value::Integer(elem.0.into()));
+        }
+    }
+    if let Some(quant) = shapes.input_quant {
+        doc.insert("input_scale".to_string(), toml::Value::Float(quant.scale.into()));
+        doc.insert("input_zero_point".to_string(), toml::Value::Integer(quant.zero_point));
+    }
+
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir).into_diagnostic()?;
+    }
+    let text = toml::to_string(&doc).into_diagnostic()?;
+    // Written aside and renamed, so a concurrent pass never reads half an entry.
+    let tmp = file.with_extension(format!("tmp{}", std::process::id()));
+    fs::write(&tmp, text).into_diagnostic()?;
+    fs::rename(&tmp, file).into_diagnostic()
+}
@@ -0,0 +1,111 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use aura_bridge::onnx::OnnxDim;
+use aura_plugin_ai::ModelContractCache;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("aura-model-cache-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("temp dir");
+    dir
+}
+
+/// A safetensors file with a `[vocab, hidden]` f32 output head.
+fn safetensors(vocab: u64, hidden: u64) -> Vec<u8> {
+    let size = vocab * hidden * 4;
+    let header = format!(r#"{{"lm_head.weight":{{"dtype":"F32","shape":[{vocab},{hidden}],"data_offsets":[0,{size}]}}}}"#);
+    let mut bytes = (header.len() as u64).to_le_bytes().to_vec();
+    bytes.extend_from_slice(header.as_bytes());
+    bytes.resize(bytes.len() + size as usize, 0);
+    bytes
+}
+
+/// Writes `bytes` to `path` and stamps it with `modified`.
+fn write_model(path: &Path, bytes: &[u8], modified: SystemTime) {
+    fs::write(path, bytes).expect("write model");
+    fs::File::options()
+        .write(true)
+        .open(path)
+        .and_then(|f| f.set_modified(modified))
+        .expect("set mtime");
+}
+
+fn vocab(cache: &ModelContractCache, path: &Path, disk: Option<&Path>) -> OnnxDim {
+    let shapes = cache.io_shapes(path, disk).expect("io shapes");
+    shapes.output.last().cloned().expect("vocab dim")
+}
+
+fn disk_entries(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .map(|rd| rd.filter_map(|e| Some(e.ok()?.path())).collect())
+        .unwrap_or_default()
+}
+
+#[test]
+fn an_unchanged_model_is_not_read_again() {
+    let dir = temp_dir("hit");
+    let model = dir.join("lm.safetensors");
+    let stamp = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    write_model(&model, &safetensors(8, 2), stamp);
+
+    let cache = ModelContractCache::default();
+    assert_eq!(vocab(&cache, &model, None), OnnxDim::Known(8));
+
+    // Same size and modification time: the cache answers without opening the file, so even
+    // bytes that no longer parse go unnoticed. Clones share the entries.
+    let len = fs::metadata(&model).unwrap().len() as usize;
+    write_model(&model, &vec![0xFF; len], stamp);
+    assert_eq!(vocab(&cache.clone(), &model, None), OnnxDim::Known(8));
+
+    // A fresh cache has to parse, and fails.
+    assert!(ModelContractCache::default().io_shapes(&model, None).is_err());
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn editing_the_model_changes_its_hash_and_forces_a_parse() {
+    let dir = temp_dir("edit");
+    let model = dir.join("lm.safetensors");
+    let disk = dir.join("cache");
+    let stamp = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    write_model(&model, &safetensors(8, 2), stamp);
+
+    let cache = ModelContractCache::default();
+    assert_eq!(vocab(&cache, &model, Some(&disk)), OnnxDim::Known(8));
+    assert_eq!(disk_entries(&disk).len(), 1);
+
+    // Same length, new contents and a later timestamp.
+    let edited = safetensors(4, 4);
+    assert_eq!(edited.len(), safetensors(8, 2).len());
+    write_model(&model, &edited, stamp + Duration::from_secs(1));
+    assert_eq!(vocab(&cache, &model, Some(&disk)), OnnxDim::Known(4));
+    assert_eq!(disk_entries(&disk).len(), 2, "one entry per content hash");
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn a_corrupt_disk_entry_falls_back_to_parsing() {
+    let dir = temp_dir("disk");
+    let model = dir.join("lm.safetensors");
+    let disk = dir.join("cache");
+    write_model(&model, &safetensors(8, 2), SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+    assert_eq!(vocab(&ModelContractCache::default(), &model, Some(&disk)), OnnxDim::Known(8));
+    let entries = disk_entries(&disk);
+    let [entry] = &entries[..] else {
+        panic!("expected one disk entry");
+    };
+
+    // A fresh cache takes a valid entry from disk as is, without parsing the model.
+    let text = fs::read_to_string(entry).unwrap();
+    assert!(text.contains("output = [\"sequence\", 8]"), "{text}");
+    fs::write(entry, text.replace("output = [\"sequence\", 8]", "output = [\"sequence\", 9]")).unwrap();
+    assert_eq!(vocab(&ModelContractCache::default(), &model, Some(&disk)), OnnxDim::Known(9));
+
+    // An entry that does not parse is ignored, and replaced by the parsed contract.
+    fs::write(entry, "input = [\n").unwrap();
+    assert_eq!(vocab(&ModelContractCache::default(), &model, Some(&disk)), OnnxDim::Known(8));
+    assert_eq!(fs::read_to_string(entry).unwrap(), text);
+    let _ = fs::remove_dir_all(&dir);
+}
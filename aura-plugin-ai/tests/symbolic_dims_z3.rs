@@ -0,0 +1,96 @@
+#![cfg(feature = "z3")]
+
+use aura_nexus::{NexusContext, NexusFileContext, ProofNotes};
+use aura_plugin_ai::AuraAiPlugin;
+use aura_verify::{verify_program_z3_profile, SmtProfile, Z3Prover};
+
+/// Verifies `body` in a fresh project named `name`, whose aura.toml holds `manifest` and whose
+/// `lm.safetensors` maps `[sequence]` token ids to `[sequence, 8]` logits. Returns the proof
+/// notes, or the first failure's message.
+fn verify(name: &str, manifest: &str, body: &str) -> Result<Vec<String>, String> {
+    let dir = std::env::temp_dir().join(format!("aura-ai-dims-{name}-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("temp dir");
+    let manifest_path = dir.join("aura.toml");
+    std::fs::write(&manifest_path, manifest).expect("write aura.toml");
+    let model = dir.join("lm.safetensors");
+    let header = r#"{"lm_head.weight":{"dtype":"F32","shape":[8,2],"data_offsets":[0,64]}}"#;
+    let mut bytes = (header.len() as u64).to_le_bytes().to_vec();
+    bytes.extend_from_slice(header.as_bytes());
+    bytes.resize(bytes.len() + 64, 0);
+    std::fs::write(&model, bytes).expect("write model");
+
+    let src = format!("cell main():\n    val model = ai.load_model({:?})\n{body}", model.to_string_lossy());
+    let program = aura_parse::parse_source(&src).expect("parse");
+    let mut prover = Z3Prover::new();
+    let mut nexus = NexusContext::default();
+    nexus.insert(NexusFileContext {
+        source_path: None,
+        manifest_path: Some(manifest_path),
+    });
+    let plugins = (AuraAiPlugin::new(),);
+    let out = verify_program_z3_profile(&program, &mut prover, &plugins, &mut nexus, SmtProfile::Fast);
+    let _ = std::fs::remove_dir_all(&dir);
+    out.map_err(|e| e.message)?;
+    let notes = nexus.get::<ProofNotes>().map(|n| n.0.iter().map(|p| p.message.clone()).collect());
+    Ok(notes.unwrap_or_default())
+}
+
+fn verify_err(name: &str, manifest: &str, body: &str) -> String {
+    match verify(name, manifest, body) {
+        Ok(notes) => panic!("expected a verification failure, got notes {notes:?}"),
+        Err(e) => e,
+    }
+}
+
+/// Batched inference over a `[len]` token tensor, `batch` rows at once.
+fn infer_batch(len: u64, batch: &str) -> String {
+    format!("    val input: Tensor<u32, [{len}]> = tensor.new({len})\n    val _out = ai.infer_batch(model, input, {batch})\n")
+}
+
+#[test]
+fn the_batch_argument_is_bounded_by_aura_toml() {
+    let manifest = "[ai]\nmax_batch = 4\n\n[ai.dims]\nsequence = { min = 1, max = 16 }\n";
+    let notes = verify("batch-ok", manifest, &infer_batch(4, "4")).expect("verify");
+    assert!(
+        notes.contains(
+            &"Verified by Aura-AI Plugin: batched input tensor shape and element type (u32) match model contract (1 dims; symbolic dims: batch [1..4], sequence [1..16])."
+                .to_string()
+        ),
+        "{notes:?}"
+    );
+
+    // The batch argument has to agree with the tensor it describes.
+    let err = verify_err("batch-mismatch", manifest, &infer_batch(4, "3"));
+    assert!(err.contains("Inference Error: input dim 0 may differ from the batch size"), "{err}");
+}
+
+#[test]
+fn a_batch_outside_its_bounds_is_rejected() {
+    let err = verify_err("max-batch", "[ai]\nmax_batch = 2\n", &infer_batch(4, "4"));
+    assert!(err.contains("Inference Error: batch size may be outside [1..2] allowed by [ai] max_batch"), "{err}");
+
+    // Within max_batch, but past the bound of the symbolic dim the batch fills.
+    let err = verify_err("dim-bound", "[ai]\nmax_batch = 8\n\n[ai.dims]\nsequence = 3\n", &infer_batch(4, "4"));
+    assert!(err.contains("Inference Error: batch size may be outside [1..3] allowed for symbolic dim 'sequence'"), "{err}");
+
+    // With no max_batch, the batch only has to be positive.
+    let err = verify_err("zero", "", &infer_batch(0, "0"));
+    assert!(err.contains("Inference Error: batch size may be zero"), "{err}");
+}
+
+#[test]
+fn an_unbounded_symbolic_dim_only_has_to_be_positive() {
+    let infer = |len: u64| format!("    val input: Tensor<u32, [{len}]> = tensor.new({len})\n    val _out = ai.infer(model, input)\n");
+    let notes = verify("unbounded", "", &infer(100_000)).expect("verify");
+    assert!(
+        notes.contains(
+            &"Verified by Aura-AI Plugin: input tensor shape and element type (u32) match model contract (1 dims; symbolic dims: sequence >= 1)."
+                .to_string()
+        ),
+        "{notes:?}"
+    );
+
+    // The same tensor is rejected once aura.toml bounds the dim.
+    let err = verify_err("bounded", "[ai.dims]\nsequence = { min = 1, max = 512 }\n", &infer(100_000));
+    assert!(err.contains("Inference Error: input dim 0 may be outside [1..512] allowed for symbolic dim 'sequence'"), "{err}");
+}
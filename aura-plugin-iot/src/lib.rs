@@ -1,5 +1,6 @@
 #![forbid(unsafe_code)]
 
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 
 use aura_ast::{Expr, ExprKind, Span};
@@ -7,7 +8,9 @@ use aura_nexus::{AuraPlugin, NexusContext, NexusDiagnostic, NexusFileContext, Pl
 
 #[cfg(feature = "z3")]
 use aura_nexus::{record_proof, ProofNote};
-use miette::IntoDiagnostic;
+#[cfg(feature = "z3")]
+use z3::ast::Ast;
+use miette::{IntoDiagnostic, LabeledSpan, NamedSource};
 
 #[derive(Clone, Debug)]
 pub struct HwRegister {
@@ -15,14 +18,292 @@ pub struct HwRegister {
     pub base: u64,
     pub size: u64,
     pub mask: Option<u64>,
+    pub access: HwAccess,
+    /// Number of identical peripheral instances (manifest `instances`, default 1).
+    pub instances: u64,
+    /// Bytes between the bases of consecutive instances (manifest `stride`, default `size`).
+    pub stride: u64,
+    /// Named bitfields; bits outside every field are reserved.
+    pub fields: Vec<HwField>,
+    /// Power states the register may be accessed in (manifest `states`); any state when absent.
+    pub states: Option<Vec<String>>,
+    /// Byte range of the register's entry in the manifest, when known.
+    pub span: Option<Range<usize>>,
+}
+
+/// Which accesses a register allows (manifest `access = "ro" | "wo" | "rw"`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HwAccess {
+    ReadOnly,
+    WriteOnly,
+    #[default]
+    ReadWrite,
+}
+
+impl HwAccess {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "ro" | "r" | "read-only" | "readonly" => Some(Self::ReadOnly),
+            "wo" | "w" | "write-only" | "writeonly" => Some(Self::WriteOnly),
+            "rw" | "read-write" | "readwrite" => Some(Self::ReadWrite),
+            _ => None,
+        }
+    }
+
+    pub fn allows(self, write: bool) -> bool {
+        match self {
+            Self::ReadOnly => !write,
+            Self::WriteOnly => write,
+            Self::ReadWrite => true,
+        }
+    }
+
+    /// The allowed access set, e.g. `read-only (reads allowed)`.
+    pub fn describe(self) -> &'static str {
+        match self {
+            Self::ReadOnly => "read-only (reads allowed)",
+            Self::WriteOnly => "write-only (writes allowed)",
+            Self::ReadWrite => "read-write (reads and writes allowed)",
+        }
+    }
+}
+
+/// A bitfield of a 32-bit register: `width` bits starting at bit `offset`.
+#[derive(Clone, Debug)]
+pub struct HwField {
+    pub name: String,
+    pub offset: u32,
+    pub width: u32,
+    /// Named values; when present, the field may only hold one of them.
+    pub values: Vec<(String, u64)>,
+}
+
+impl HwField {
+    /// Largest value the field can hold.
+    pub fn max_value(&self) -> u64 {
+        (1u64 << self.width) - 1
+    }
+
+    /// The field's bits in the register.
+    pub fn mask(&self) -> u64 {
+        self.max_value() << self.offset
+    }
+
+    pub fn value(&self, name: &str) -> Option<u64> {
+        self.values
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| *v)
+    }
+}
+
+impl HwRegister {
+    /// Base address of instance `index`.
+    pub fn instance_base(&self, index: u64) -> u64 {
+        self.base.saturating_add(index.saturating_mul(self.stride))
+    }
+
+    /// The register block of instance `index` on its own, e.g. `UART[2]`.
+    pub fn instance(&self, index: u64) -> HwRegister {
+        HwRegister {
+            name: format!("{}[{index}]", self.name),
+            base: self.instance_base(index),
+            instances: 1,
+            ..self.clone()
+        }
+    }
+
+    pub fn field(&self, name: &str) -> Option<&HwField> {
+        self.fields.iter().find(|f| f.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Bits a field write must leave untouched: those outside every field and outside the mask.
+    pub fn reserved_mask(&self) -> u64 {
+        let used = self.fields.iter().fold(0, |acc, f| acc | f.mask());
+        let mask = self.mask.unwrap_or(0xFFFF_FFFF);
+        0xFFFF_FFFF & !(used & mask)
+    }
+
+    /// Checks that the fields fit the register (and its mask), don't overlap, and that their
+    /// named values fit them.
+    pub fn check_fields(&self) -> Result<(), String> {
+        let mut used = 0u64;
+        for f in &self.fields {
+            if f.width == 0 || f.width > 32 || f.offset > 32 - f.width {
+                return Err(format!(
+                    "field '{}' of register '{}' (offset {}, width {}) does not fit in 32 bits",
+                    f.name, self.name, f.offset, f.width
+                ));
+            }
+            if used & f.mask() != 0 {
+                return Err(format!("field '{}' of register '{}' overlaps another field", f.name, self.name));
+            }
+            used |= f.mask();
+            if let Some(mask) = self.mask
+                && f.mask() & !mask != 0
+            {
+                return Err(format!(
+                    "field '{}' of register '{}' lies outside the register mask 0x{mask:X}",
+                    f.name, self.name
+                ));
+            }
+            if let Some((n, v)) = f.values.iter().find(|(_, v)| *v > f.max_value()) {
+                return Err(format!(
+                    "value {n} = {v} does not fit field '{}' of register '{}' (width {})",
+                    f.name, self.name, f.width
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An interrupt line (manifest `[hardware] interrupts`).
+#[derive(Clone, Debug)]
+pub struct HwInterrupt {
+    pub name: String,
+    pub line: u32,
+    /// Highest priority the line may be configured with (manifest `max_priority`, default 15).
+    pub max_priority: u64,
+    /// Byte range of the entry in the manifest, when known.
+    pub span: Option<Range<usize>>,
+}
+
+/// A DMA channel (manifest `[hardware] dma`).
+#[derive(Clone, Debug)]
+pub struct HwDmaChannel {
+    pub name: String,
+    /// Longest transfer the channel supports, in bytes (manifest `max_len`).
+    pub max_len: Option<u64>,
+    /// Byte range of the entry in the manifest, when known.
+    pub span: Option<Range<usize>>,
+}
+
+/// Device power states and the moves between them (manifest `[hardware.power]`).
+#[derive(Clone, Debug)]
+pub struct HwPower {
+    pub states: Vec<String>,
+    /// State every cell starts in (manifest `initial`, default the first state).
+    pub initial: String,
+    /// Legal `(from, to)` moves.
+    pub transitions: Vec<(String, String)>,
+    /// Byte range of the section in the manifest, when known.
+    pub span: Option<Range<usize>>,
+}
+
+impl HwPower {
+    /// Position of state `name` in `states`.
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.states.iter().position(|s| s.eq_ignore_ascii_case(name))
+    }
+
+    /// States with a declared move to `to`.
+    pub fn sources(&self, to: &str) -> Vec<usize> {
+        self.transitions
+            .iter()
+            .filter(|(_, t)| t.eq_ignore_ascii_case(to))
+            .filter_map(|(from, _)| self.index(from))
+            .collect()
+    }
+}
+
+/// A family of topics `net.publish` may send to (manifest `[hardware.net] topics`).
+#[derive(Clone, Debug)]
+pub struct NetTopic {
+    /// MQTT-style filter: `+` matches one level, a trailing `#` any number of levels.
+    pub pattern: String,
+    /// Largest payload, in bytes (manifest `max_payload`).
+    pub max_payload: Option<u64>,
+    /// Highest QoS level publishes may use (manifest `max_qos`, default 2).
+    pub max_qos: u64,
+    /// Byte range of the entry in the manifest, when known.
+    pub span: Option<Range<usize>>,
+}
+
+impl NetTopic {
+    /// Whether concrete topic `topic` falls under this topic's pattern.
+    pub fn matches(&self, topic: &str) -> bool {
+        let mut levels = topic.split('/');
+        for p in self.pattern.split('/') {
+            if p == "#" {
+                return true;
+            }
+            match levels.next() {
+                Some(l) if p == "+" || p == l => {}
+                _ => return false,
+            }
+        }
+        levels.next().is_none()
+    }
+
+    /// Why `pattern` is not a valid topic filter, if it is not.
+    fn pattern_error(&self) -> Option<String> {
+        let levels: Vec<&str> = self.pattern.split('/').collect();
+        if self.pattern.is_empty() {
+            return Some("topic pattern is empty".to_string());
+        }
+        for (i, l) in levels.iter().enumerate() {
+            if *l == "#" && i + 1 != levels.len() {
+                return Some(format!("topic pattern '{}' has '#' before its last level", self.pattern));
+            }
+            if *l != "+" && *l != "#" && (l.contains('+') || l.contains('#')) {
+                return Some(format!("topic pattern '{}' has a wildcard inside level '{l}'", self.pattern));
+            }
+        }
+        None
+    }
+}
+
+/// A peer `net.send` may address (manifest `[hardware.net] endpoints`).
+#[derive(Clone, Debug)]
+pub struct NetEndpoint {
+    pub name: String,
+    /// Largest message, in bytes (manifest `max_payload`).
+    pub max_payload: Option<u64>,
+    /// Byte range of the entry in the manifest, when known.
+    pub span: Option<Range<usize>>,
 }
 
 #[derive(Clone, Debug, Default)]
 pub struct HardwareManifest {
     pub registers: Vec<HwRegister>,
+    pub interrupts: Vec<HwInterrupt>,
+    pub dma: Vec<HwDmaChannel>,
+    pub power: Option<HwPower>,
+    pub topics: Vec<NetTopic>,
+    pub endpoints: Vec<NetEndpoint>,
     pub manifest_path: Option<PathBuf>,
 }
 
+/// A problem with the registers of a hardware manifest.
+#[derive(Clone, Debug)]
+pub struct ManifestIssue {
+    pub message: String,
+    /// Byte range in the manifest of the register it is about.
+    pub span: Option<Range<usize>>,
+}
+
+/// Just enough of `aura.toml` to find where each register is declared.
+#[derive(serde::Deserialize)]
+struct SpannedManifest {
+    hardware: Option<SpannedHardware>,
+}
+
+#[derive(serde::Deserialize)]
+struct SpannedHardware {
+    registers: Option<Vec<toml::Spanned<toml::Value>>>,
+    interrupts: Option<Vec<toml::Spanned<toml::Value>>>,
+    dma: Option<Vec<toml::Spanned<toml::Value>>>,
+    power: Option<toml::Spanned<toml::Value>>,
+    net: Option<SpannedNet>,
+}
+
+#[derive(serde::Deserialize)]
+struct SpannedNet {
+    topics: Option<Vec<toml::Spanned<toml::Value>>>,
+    endpoints: Option<Vec<toml::Spanned<toml::Value>>>,
+}
+
 impl HardwareManifest {
     pub fn find_from(start: &Path) -> miette::Result<Option<PathBuf>> {
         let mut cur = if start.is_file() {
@@ -56,35 +337,247 @@ impl HardwareManifest {
             .map_err(|e| miette::miette!("failed to parse {}: {e}", manifest_path.display()))?;
 
         let mut out = HardwareManifest {
-            registers: Vec::new(),
             manifest_path: Some(manifest_path),
+            ..Self::default()
         };
 
         let Some(hw) = doc.get("hardware") else {
             return Ok(out);
         };
-        let regs = match hw.get("registers") {
-            Some(v) => v,
-            None => return Ok(out),
+
+        let spans = toml::from_str::<SpannedManifest>(&raw).ok().and_then(|m| m.hardware);
+        let spans_of = |list: fn(&SpannedHardware) -> Option<&Vec<toml::Spanned<toml::Value>>>| -> Vec<Range<usize>> {
+            spans
+                .as_ref()
+                .and_then(list)
+                .map(|items| items.iter().map(|it| it.span()).collect())
+                .unwrap_or_default()
         };
 
-        match regs {
-            toml::Value::Array(items) => {
-                for it in items {
-                    if let Some(r) = parse_register(it) {
-                        out.registers.push(r);
-                    }
+        out.registers = parse_list(hw.get("registers"), &spans_of(|h| h.registers.as_ref()), parse_register)
+            .into_iter()
+            .map(|(mut r, span)| {
+                r.span = span;
+                r
+            })
+            .collect();
+        out.interrupts = parse_list(hw.get("interrupts"), &spans_of(|h| h.interrupts.as_ref()), parse_interrupt)
+            .into_iter()
+            .map(|(mut irq, span)| {
+                irq.span = span;
+                irq
+            })
+            .collect();
+        out.dma = parse_list(hw.get("dma"), &spans_of(|h| h.dma.as_ref()), parse_dma_channel)
+            .into_iter()
+            .map(|(mut ch, span)| {
+                ch.span = span;
+                ch
+            })
+            .collect();
+        let net = hw.get("net");
+        out.topics = parse_list(
+            net.and_then(|n| n.get("topics")),
+            &spans_of(|h| h.net.as_ref()?.topics.as_ref()),
+            parse_topic,
+        )
+        .into_iter()
+        .map(|(mut t, span)| {
+            t.span = span;
+            t
+        })
+        .collect();
+        out.endpoints = parse_list(
+            net.and_then(|n| n.get("endpoints")),
+            &spans_of(|h| h.net.as_ref()?.endpoints.as_ref()),
+            parse_endpoint,
+        )
+        .into_iter()
+        .map(|(mut e, span)| {
+            e.span = span;
+            e
+        })
+        .collect();
+        out.power = hw.get("power").and_then(parse_power).map(|mut p| {
+            p.span = spans.as_ref().and_then(|h| h.power.as_ref()).map(|p| p.span());
+            p
+        });
+
+        let issues = out.validate();
+        if !issues.is_empty() {
+            let path = out.manifest_path.as_deref().unwrap_or(Path::new("aura.toml")).display().to_string();
+            let labels: Vec<LabeledSpan> = issues
+                .iter()
+                .filter_map(|i| Some(LabeledSpan::at(i.span.clone()?, i.message.clone())))
+                .collect();
+            let summary = issues.iter().map(|i| i.message.as_str()).collect::<Vec<_>>().join("; ");
+            return Err(miette::miette!(labels = labels, "invalid hardware manifest {path}: {summary}")
+                .with_source_code(NamedSource::new(path, raw)));
+        }
+
+        Ok(out)
+    }
+
+    pub fn interrupt(&self, name: &str) -> Option<&HwInterrupt> {
+        self.interrupts.iter().find(|i| i.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn dma_channel(&self, name: &str) -> Option<&HwDmaChannel> {
+        self.dma.iter().find(|c| c.name.eq_ignore_ascii_case(name))
+    }
+
+    /// The first declared topic whose pattern matches `topic`.
+    pub fn topic(&self, topic: &str) -> Option<&NetTopic> {
+        self.topics.iter().find(|t| t.matches(topic))
+    }
+
+    pub fn endpoint(&self, name: &str) -> Option<&NetEndpoint> {
+        self.endpoints.iter().find(|e| e.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Checks that register names are unique, bases and sizes are 4-byte aligned, no two
+    /// registers' `[base, base + size)` ranges overlap, interrupt lines and DMA channels
+    /// are declared once, power transitions and register states name declared states, and
+    /// topic patterns and endpoints are well-formed and declared once.
+    pub fn validate(&self) -> Vec<ManifestIssue> {
+        let mut issues = Vec::new();
+
+        if let Some(p) = &self.power {
+            let mut power_issue = |message: String| {
+                issues.push(ManifestIssue {
+                    message,
+                    span: p.span.clone(),
+                })
+            };
+            if p.states.is_empty() {
+                power_issue("[hardware.power] declares no states".to_string());
+            }
+            for (i, st) in p.states.iter().enumerate() {
+                if p.states[..i].iter().any(|o| o.eq_ignore_ascii_case(st)) {
+                    power_issue(format!("power state '{st}' is declared twice"));
                 }
             }
-            toml::Value::Table(_) => {
-                if let Some(r) = parse_register(regs) {
-                    out.registers.push(r);
+            if p.index(&p.initial).is_none() {
+                power_issue(format!("initial power state '{}' is not declared", p.initial));
+            }
+            for (from, to) in &p.transitions {
+                for st in [from, to] {
+                    if p.index(st).is_none() {
+                        power_issue(format!("power transition {from} -> {to} names undeclared state '{st}'"));
+                    }
                 }
             }
-            _ => {}
+        }
+        for r in &self.registers {
+            let Some(states) = &r.states else { continue };
+            let Some(p) = &self.power else {
+                issues.push(ManifestIssue {
+                    message: format!("register '{}' lists power states but there is no [hardware.power]", r.name),
+                    span: r.span.clone(),
+                });
+                continue;
+            };
+            for st in states.iter().filter(|st| p.index(st).is_none()) {
+                issues.push(ManifestIssue {
+                    message: format!("register '{}' is accessible in undeclared power state '{st}'", r.name),
+                    span: r.span.clone(),
+                });
+            }
         }
 
-        Ok(out)
+        for (i, irq) in self.interrupts.iter().enumerate() {
+            let earlier = &self.interrupts[..i];
+            if let Some(first) = earlier.iter().find(|o| o.name.eq_ignore_ascii_case(&irq.name)) {
+                issues.push(ManifestIssue {
+                    message: format!("interrupt '{}' is declared twice (also as '{}')", irq.name, first.name),
+                    span: irq.span.clone(),
+                });
+            } else if let Some(first) = earlier.iter().find(|o| o.line == irq.line) {
+                issues.push(ManifestIssue {
+                    message: format!("interrupt '{}' uses line {} of '{}'", irq.name, irq.line, first.name),
+                    span: irq.span.clone(),
+                });
+            }
+        }
+        for (i, ch) in self.dma.iter().enumerate() {
+            if let Some(first) = self.dma[..i].iter().find(|o| o.name.eq_ignore_ascii_case(&ch.name)) {
+                issues.push(ManifestIssue {
+                    message: format!("DMA channel '{}' is declared twice (also as '{}')", ch.name, first.name),
+                    span: ch.span.clone(),
+                });
+            }
+        }
+
+        for (i, t) in self.topics.iter().enumerate() {
+            let message = if let Some(err) = t.pattern_error() {
+                err
+            } else if self.topics[..i].iter().any(|o| o.pattern == t.pattern) {
+                format!("topic pattern '{}' is declared twice", t.pattern)
+            } else if t.max_qos > 2 {
+                format!("topic pattern '{}' max_qos {} is not a QoS level (0, 1 or 2)", t.pattern, t.max_qos)
+            } else {
+                continue;
+            };
+            issues.push(ManifestIssue {
+                message,
+                span: t.span.clone(),
+            });
+        }
+        for (i, e) in self.endpoints.iter().enumerate() {
+            if let Some(first) = self.endpoints[..i].iter().find(|o| o.name.eq_ignore_ascii_case(&e.name)) {
+                issues.push(ManifestIssue {
+                    message: format!("endpoint '{}' is declared twice (also as '{}')", e.name, first.name),
+                    span: e.span.clone(),
+                });
+            }
+        }
+
+        let issue = |r: &HwRegister, message: String| ManifestIssue {
+            message,
+            span: r.span.clone(),
+        };
+
+        for (i, r) in self.registers.iter().enumerate() {
+            if let Some(first) = self.registers[..i].iter().find(|o| o.name.eq_ignore_ascii_case(&r.name)) {
+                issues.push(issue(r, format!("register '{}' is declared twice (also as '{}')", r.name, first.name)));
+            }
+            if r.base % 4 != 0 {
+                issues.push(issue(r, format!("register '{}' base 0x{:X} is not 4-byte aligned", r.name, r.base)));
+            }
+            if r.size % 4 != 0 {
+                issues.push(issue(r, format!("register '{}' size {} is not a multiple of 4 bytes", r.name, r.size)));
+            }
+            if r.instances == 0 {
+                issues.push(issue(r, format!("register '{}' declares 0 instances", r.name)));
+            }
+            if r.instances > 1 {
+                if r.stride % 4 != 0 {
+                    issues.push(issue(r, format!("register '{}' stride {} is not a multiple of 4 bytes", r.name, r.stride)));
+                }
+                if r.stride < r.size {
+                    issues.push(issue(
+                        r,
+                        format!("register '{}' instances overlap: stride {} is smaller than size {}", r.name, r.stride, r.size),
+                    ));
+                }
+            }
+            for o in &self.registers[..i] {
+                if let Some((k, ok)) = first_overlap(r, o) {
+                    let (start, o_start) = (r.instance_base(k), o.instance_base(ok));
+                    issues.push(issue(
+                        r,
+                        format!(
+                            "register '{}' [0x{start:X}, 0x{:X}) overlaps '{}' [0x{o_start:X}, 0x{:X})",
+                            instance_name(r, k),
+                            start.saturating_add(r.size),
+                            instance_name(o, ok),
+                            o_start.saturating_add(o.size)
+                        ),
+                    ));
+                }
+            }
+        }
+        issues
     }
 
     pub fn get(&self, name: &str) -> Option<&HwRegister> {
@@ -94,6 +587,110 @@ impl HardwareManifest {
     }
 }
 
+/// Parses a manifest list (or a single table) with `parse`, pairing each entry with its span
+/// among `spans`.
+fn parse_list<T>(
+    v: Option<&toml::Value>,
+    spans: &[Range<usize>],
+    parse: fn(&toml::Value) -> Option<T>,
+) -> Vec<(T, Option<Range<usize>>)> {
+    match v {
+        Some(toml::Value::Array(items)) => items
+            .iter()
+            .enumerate()
+            .filter_map(|(i, it)| Some((parse(it)?, spans.get(i).cloned())))
+            .collect(),
+        Some(t @ toml::Value::Table(_)) => parse(t).map(|x| (x, None)).into_iter().collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn parse_interrupt(v: &toml::Value) -> Option<HwInterrupt> {
+    let t = v.as_table()?;
+
+    let name = t.get("name")?.as_str()?.to_string();
+    let line = parse_u64(t.get("line")?)?.try_into().ok()?;
+    let max_priority = match t.get("max_priority") {
+        Some(v) => parse_u64(v)?,
+        None => 15,
+    };
+
+    Some(HwInterrupt {
+        name,
+        line,
+        max_priority,
+        span: None,
+    })
+}
+
+fn parse_dma_channel(v: &toml::Value) -> Option<HwDmaChannel> {
+    let t = v.as_table()?;
+
+    let name = t.get("name")?.as_str()?.to_string();
+    let max_len = match t.get("max_len") {
+        Some(v) => Some(parse_u64(v)?),
+        None => None,
+    };
+
+    Some(HwDmaChannel { name, max_len, span: None })
+}
+
+fn parse_topic(v: &toml::Value) -> Option<NetTopic> {
+    let t = v.as_table()?;
+
+    let pattern = t.get("pattern")?.as_str()?.to_string();
+    let max_payload = match t.get("max_payload") {
+        Some(v) => Some(parse_u64(v)?),
+        None => None,
+    };
+    let max_qos = match t.get("max_qos") {
+        Some(v) => parse_u64(v)?,
+        None => 2,
+    };
+
+    Some(NetTopic {
+        pattern,
+        max_payload,
+        max_qos,
+        span: None,
+    })
+}
+
+fn parse_endpoint(v: &toml::Value) -> Option<NetEndpoint> {
+    let t = v.as_table()?;
+
+    let name = t.get("name")?.as_str()?.to_string();
+    let max_payload = match t.get("max_payload") {
+        Some(v) => Some(parse_u64(v)?),
+        None => None,
+    };
+
+    Some(NetEndpoint { name, max_payload, span: None })
+}
+
+/// The first pair of instances of `a` and `b` whose address ranges overlap.
+fn first_overlap(a: &HwRegister, b: &HwRegister) -> Option<(u64, u64)> {
+    if a.size == 0 || b.size == 0 {
+        return None;
+    }
+    (0..a.instances.max(1)).find_map(|k| {
+        let start = a.instance_base(k);
+        let end = start.saturating_add(a.size);
+        (0..b.instances.max(1)).find_map(|ok| {
+            let o_start = b.instance_base(ok);
+            (start < o_start.saturating_add(b.size) && o_start < end).then_some((k, ok))
+        })
+    })
+}
+
+fn instance_name(r: &HwRegister, index: u64) -> String {
+    if r.instances > 1 {
+        format!("{}[{index}]", r.name)
+    } else {
+        r.name.clone()
+    }
+}
+
 fn parse_register(v: &toml::Value) -> Option<HwRegister> {
     let t = v.as_table()?;
 
@@ -101,12 +698,94 @@ fn parse_register(v: &toml::Value) -> Option<HwRegister> {
     let base = parse_u64(t.get("base")?)?;
     let size = parse_u64(t.get("size")?)?;
     let mask = t.get("mask").and_then(parse_u64);
+    let access = match t.get("access") {
+        Some(v) => HwAccess::parse(v.as_str()?)?,
+        None => HwAccess::default(),
+    };
+    let instances = match t.get("instances") {
+        Some(v) => parse_u64(v)?,
+        None => 1,
+    };
+    let stride = match t.get("stride") {
+        Some(v) => parse_u64(v)?,
+        None => size,
+    };
+    let fields = match t.get("fields") {
+        Some(toml::Value::Array(items)) => items.iter().filter_map(parse_field).collect(),
+        _ => Vec::new(),
+    };
+    let states = match t.get("states") {
+        Some(v) => Some(parse_names(v)?),
+        None => None,
+    };
 
     Some(HwRegister {
         name,
         base,
         size,
         mask,
+        access,
+        instances,
+        stride,
+        fields,
+        states,
+        span: None,
+    })
+}
+
+fn parse_power(v: &toml::Value) -> Option<HwPower> {
+    let t = v.as_table()?;
+
+    let states = parse_names(t.get("states")?)?;
+    let initial = match t.get("initial") {
+        Some(v) => v.as_str()?.to_string(),
+        None => states.first()?.clone(),
+    };
+    let mut transitions = Vec::new();
+    if let Some(items) = t.get("transitions") {
+        for item in items.as_array()? {
+            let item = item.as_table()?;
+            let to = item.get("to")?.as_str()?;
+            // `from` may list several states.
+            let from = match item.get("from")? {
+                v @ toml::Value::Array(_) => parse_names(v)?,
+                v => vec![v.as_str()?.to_string()],
+            };
+            transitions.extend(from.into_iter().map(|f| (f, to.to_string())));
+        }
+    }
+
+    Some(HwPower {
+        states,
+        initial,
+        transitions,
+        span: None,
+    })
+}
+
+fn parse_names(v: &toml::Value) -> Option<Vec<String>> {
+    v.as_array()?.iter().map(|s| Some(s.as_str()?.to_string())).collect()
+}
+
+fn parse_field(v: &toml::Value) -> Option<HwField> {
+    let t = v.as_table()?;
+
+    let name = t.get("name")?.as_str()?.to_string();
+    let offset = parse_u64(t.get("offset")?)?.try_into().ok()?;
+    let width = parse_u64(t.get("width")?)?.try_into().ok()?;
+    let values = match t.get("values") {
+        Some(toml::Value::Table(vals)) => vals
+            .iter()
+            .filter_map(|(k, v)| Some((k.clone(), parse_u64(v)?)))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    Some(HwField {
+        name,
+        offset,
+        width,
+        values,
     })
 }
 
@@ -136,6 +815,42 @@ fn is_contiguous_lsb_mask(mask: u64) -> bool {
 struct IotState {
     hw: HardwareManifest,
     cap_regs_by_handle: std::collections::HashMap<String, HwRegister>,
+    /// `hw.set_power` calls of the cell being verified, in order, for counterexample traces.
+    power_steps: Vec<PowerStep>,
+}
+
+/// A power transition: the states the device may have left, and the one it entered.
+#[derive(Clone)]
+struct PowerStep {
+    span: Span,
+    from: Vec<usize>,
+    to: usize,
+}
+
+/// Ghost holding the device's power state, as an index into the manifest's states.
+#[cfg(feature = "z3")]
+const POWER_GHOST: &str = "aura-iot.power";
+
+/// Logged transitions that can leave the device in `state`, oldest first: each step's
+/// source is the state the one before it entered (or the initial state).
+fn power_trace(steps: &[PowerStep], initial: usize, state: usize) -> Vec<(usize, &PowerStep)> {
+    let mut trace = Vec::new();
+    let (mut state, mut end) = (state, steps.len());
+    while let Some(i) = steps[..end].iter().rposition(|s| s.to == state && s.from.iter().any(|&f| f != state)) {
+        let step = &steps[i];
+        let reached = |f: usize| f == initial || steps[..i].iter().any(|p| p.to == f);
+        let from = step
+            .from
+            .iter()
+            .copied()
+            .filter(|&f| f != state)
+            .find(|&f| reached(f))
+            .unwrap_or(step.from[0]);
+        trace.push((from, step));
+        (state, end) = (from, i);
+    }
+    trace.reverse();
+    trace
 }
 
 pub struct AuraIotPlugin;
@@ -210,6 +925,13 @@ impl AuraPlugin for AuraIotPlugin {
             "hw.open" => Some(self.z3_hw_open(call, env)),
             "hw.read_u32" => Some(self.z3_hw_read_u32(call, env)),
             "hw.write_u32" => Some(self.z3_hw_write_u32(call, env)),
+            "hw.read_field" => Some(self.z3_hw_read_field(call, env)),
+            "hw.write_field" => Some(self.z3_hw_write_field(call, env)),
+            "hw.enable_irq" => Some(self.z3_hw_enable_irq(call, env)),
+            "hw.dma_start" => Some(self.z3_hw_dma_start(call, env)),
+            "hw.set_power" => Some(self.z3_hw_set_power(call, env)),
+            "net.publish" => Some(self.z3_net_publish(call, env)),
+            "net.send" => Some(self.z3_net_send(call, env)),
             _ => None,
         }
     }
@@ -222,11 +944,11 @@ impl AuraIotPlugin {
         call: &Z3Call<'_, '_>,
         env: &mut dyn Z3CallEnv<'ctx>,
     ) -> Result<z3::ast::Int<'ctx>, NexusDiagnostic> {
-        if call.args.len() != 1 {
+        if call.args.is_empty() || call.args.len() > 2 {
             return Err(NexusDiagnostic::new(
                 self.name(),
                 call.span,
-                "hw.open expects 1 arg (string literal register name)",
+                "hw.open expects 1 or 2 args (string literal register name, instance index)",
             ));
         }
 
@@ -266,6 +988,24 @@ impl AuraIotPlugin {
             }
         }
 
+        reg.check_fields()
+            .map_err(|e| NexusDiagnostic::new(self.name(), call.args[0].span, e))?;
+
+        let reg = match call.args.get(1) {
+            Some(arg) => self.z3_hw_instance(call, env, &reg, arg)?,
+            None if reg.instances > 1 => {
+                return Err(NexusDiagnostic::new(
+                    self.name(),
+                    call.span,
+                    format!(
+                        "hardware register '{}' has {} instances; pass an instance index, e.g. hw.open(\"{}\", 0)",
+                        reg.name, reg.instances, reg.name
+                    ),
+                ));
+            }
+            None => reg,
+        };
+
         let cap = env.fresh_int("hwcap");
         let lo = z3::ast::Int::from_u64(env.ctx(), 0);
         let hi = z3::ast::Int::from_u64(env.ctx(), 0xFFFF_FFFF);
@@ -311,6 +1051,8 @@ impl AuraIotPlugin {
                 })?
         };
 
+        self.z3_check_access(call, env, &reg, true)?;
+
         if reg.size == 0 {
             return Err(NexusDiagnostic::new(
                 self.name(),
@@ -398,6 +1140,8 @@ impl AuraIotPlugin {
                 })?
         };
 
+        self.z3_check_access(call, env, &reg, false)?;
+
         if reg.size == 0 {
             return Err(NexusDiagnostic::new(
                 self.name(),
@@ -425,4 +1169,797 @@ impl AuraIotPlugin {
 
         Ok(v)
     }
+
+    /// Proves the instance index of `hw.open(name, index)` is in range and returns the selected
+    /// instance. A symbolic index keeps the whole register block, whose instances share one size.
+    #[cfg(feature = "z3")]
+    fn z3_hw_instance<'ctx>(
+        &self,
+        call: &Z3Call<'_, '_>,
+        env: &mut dyn Z3CallEnv<'ctx>,
+        reg: &HwRegister,
+        arg: &Expr,
+    ) -> Result<HwRegister, NexusDiagnostic> {
+        let index = env.eval_int(arg)?;
+        let count = reg.instances;
+        let lo = z3::ast::Int::from_u64(env.ctx(), 0);
+        let hi = z3::ast::Int::from_u64(env.ctx(), count);
+        let ok = z3::ast::Bool::and(env.ctx(), &[&index.ge(&lo), &index.lt(&hi)]);
+        env.prove_implied(
+            ok.not(),
+            arg.span,
+            &format!("instance index may be out of range for '{}' ({count} instance(s))", reg.name),
+        )?;
+        env.push_constraint(ok.clone());
+
+        let last = count.saturating_sub(1);
+        let (selected, base) = match index.simplify().as_u64() {
+            Some(k) => (reg.instance(k), format!("base 0x{:X}", reg.instance_base(k))),
+            None => (reg.clone(), format!("base 0x{:X} + index * 0x{:X}", reg.base, reg.stride)),
+        };
+        record_proof(
+            env.nexus(),
+            ProofNote {
+                plugin: self.name().to_string(),
+                span: call.span,
+                message: format!(
+                    "Verified by Aura-IoT Plugin: instance index of '{}' is within [0..{last}] ({base}).",
+                    reg.name
+                ),
+                smt: Some(ok.to_string()),
+                related: vec![aura_nexus::NexusRelated {
+                    span: arg.span,
+                    message: format!("Instance range: [0..{last}], stride 0x{:X}", reg.stride),
+                }],
+                kind: "verified",
+                mask: None,
+                range: Some((0, last)),
+                unsat_core: Vec::new(),
+                interpolant: None,
+            },
+        );
+        Ok(selected)
+    }
+
+    /// Rejects accesses the register's manifest `access` doesn't allow, or that may happen in a
+    /// power state it isn't accessible in, and notes the allowed access set for the ones it does.
+    #[cfg(feature = "z3")]
+    fn z3_check_access<'ctx>(
+        &self,
+        call: &Z3Call<'_, '_>,
+        env: &mut dyn Z3CallEnv<'ctx>,
+        reg: &HwRegister,
+        write: bool,
+    ) -> Result<(), NexusDiagnostic> {
+        let what = if write { "write to" } else { "read of" };
+        let allowed = aura_nexus::NexusRelated {
+            span: call.args[0].span,
+            message: format!("'{}' is {}", reg.name, reg.access.describe()),
+        };
+        if !reg.access.allows(write) {
+            let mut d = NexusDiagnostic::new(
+                self.name(),
+                call.span,
+                format!("{what} register '{}' is not allowed: it is {}", reg.name, reg.access.describe()),
+            );
+            d.related.push(allowed);
+            return Err(d);
+        }
+
+        record_proof(
+            env.nexus(),
+            ProofNote {
+                plugin: self.name().to_string(),
+                span: call.span,
+                message: format!(
+                    "Verified by Aura-IoT Plugin: {what} '{}' is allowed; the register is {}.",
+                    reg.name,
+                    reg.access.describe()
+                ),
+                smt: None,
+                related: vec![allowed],
+                kind: "verified",
+                mask: None,
+                range: None,
+                unsat_core: Vec::new(),
+                interpolant: None,
+            },
+        );
+        self.z3_check_power(call, env, reg)
+    }
+
+    /// Power states the device may be in on the current path. Each cell starts in the
+    /// manifest's initial state.
+    #[cfg(feature = "z3")]
+    fn z3_power_states<'ctx>(env: &mut dyn Z3CallEnv<'ctx>, power: &HwPower) -> Vec<usize> {
+        let current = match env.ghost_int(POWER_GHOST) {
+            Some(current) => current,
+            None => {
+                Self::state(env.nexus()).power_steps.clear();
+                let init = z3::ast::Int::from_u64(env.ctx(), power.index(&power.initial).unwrap_or(0) as u64);
+                env.set_ghost_int(POWER_GHOST, init.clone(), init.clone());
+                init
+            }
+        };
+        if let Some(s) = current.simplify().as_u64() {
+            return vec![s as usize];
+        }
+        (0..power.states.len())
+            .filter(|&s| {
+                let at = current._eq(&z3::ast::Int::from_u64(env.ctx(), s as u64));
+                env.satisfiable(at)
+            })
+            .collect()
+    }
+
+    /// `message` with a counterexample trace: the transitions that can leave the device in `state`.
+    #[cfg(feature = "z3")]
+    fn power_violation(&self, nexus: &mut NexusContext, power: &HwPower, span: Span, message: String, state: usize) -> NexusDiagnostic {
+        let steps = Self::state(nexus).power_steps.clone();
+        let initial = power.index(&power.initial).unwrap_or(0);
+        let trace = power_trace(&steps, initial, state);
+
+        let mut path = vec![power.states[trace.first().map_or(state, |(from, _)| *from)].as_str()];
+        path.extend(trace.iter().map(|(_, step)| power.states[step.to].as_str()));
+        let mut d = NexusDiagnostic::new(self.name(), span, format!("{message} (trace: {})", path.join(" -> ")));
+        d.related = trace
+            .iter()
+            .map(|(from, step)| aura_nexus::NexusRelated {
+                span: step.span,
+                message: format!("moves the device from '{}' to '{}'", power.states[*from], power.states[step.to]),
+            })
+            .collect();
+        d
+    }
+
+    /// Proves `reg` is only accessed in the power states its manifest entry lists.
+    #[cfg(feature = "z3")]
+    fn z3_check_power<'ctx>(
+        &self,
+        call: &Z3Call<'_, '_>,
+        env: &mut dyn Z3CallEnv<'ctx>,
+        reg: &HwRegister,
+    ) -> Result<(), NexusDiagnostic> {
+        let Some(allowed) = &reg.states else {
+            return Ok(());
+        };
+        // `validate` rejects register states without a `[hardware.power]` section.
+        let Some(power) = Self::state(env.nexus()).hw.power.clone() else {
+            return Ok(());
+        };
+
+        let possible = Self::z3_power_states(env, &power);
+        let permitted = |s: &usize| allowed.iter().any(|a| a.eq_ignore_ascii_case(&power.states[*s]));
+        if let Some(&bad) = possible.iter().find(|s| !permitted(s)) {
+            let message = format!(
+                "register '{}' may be accessed in power state '{}'; it is accessible only in {}",
+                reg.name,
+                power.states[bad],
+                allowed.join(", ")
+            );
+            return Err(self.power_violation(env.nexus(), &power, call.span, message, bad));
+        }
+
+        let names: Vec<&str> = possible.iter().map(|s| power.states[*s].as_str()).collect();
+        record_proof(
+            env.nexus(),
+            ProofNote {
+                plugin: self.name().to_string(),
+                span: call.span,
+                message: format!(
+                    "Verified by Aura-IoT Plugin: '{}' is accessed only in power states it allows ({}).",
+                    reg.name,
+                    allowed.join(", ")
+                ),
+                smt: None,
+                related: vec![aura_nexus::NexusRelated {
+                    span: call.args[0].span,
+                    message: format!("Device may be in: {}", names.join(", ")),
+                }],
+                kind: "verified",
+                mask: None,
+                range: None,
+                unsat_core: Vec::new(),
+                interpolant: None,
+            },
+        );
+        Ok(())
+    }
+
+    /// Resolves the `(cap, offset, "FIELD", ...)` arguments of a field access, proving the
+    /// offset is in bounds.
+    #[cfg(feature = "z3")]
+    fn z3_hw_field<'ctx>(
+        &self,
+        call: &Z3Call<'_, '_>,
+        env: &mut dyn Z3CallEnv<'ctx>,
+        what: &str,
+        write: bool,
+    ) -> Result<(HwRegister, HwField), NexusDiagnostic> {
+        let cap = env.eval_int(call.args[0])?;
+        let offset = env.eval_int(call.args[1])?;
+
+        let reg = {
+            let st = Self::state(env.nexus());
+            st.cap_regs_by_handle
+                .get(&cap.to_string())
+                .cloned()
+                .ok_or_else(|| {
+                    NexusDiagnostic::new(
+                        self.name(),
+                        call.args[0].span,
+                        format!("{what} requires a capability returned by hw.open"),
+                    )
+                })?
+        };
+
+        self.z3_check_access(call, env, &reg, write)?;
+
+        let field_name = Self::eval_string_lit(call.args[2]).ok_or_else(|| {
+            NexusDiagnostic::new(
+                self.name(),
+                call.args[2].span,
+                format!("{what} field name must be a string literal"),
+            )
+        })?;
+        let field = reg.field(field_name).cloned().ok_or_else(|| {
+            NexusDiagnostic::new(
+                self.name(),
+                call.args[2].span,
+                format!("hardware register '{}' has no field '{field_name}'", reg.name),
+            )
+        })?;
+
+        if reg.size == 0 {
+            return Err(NexusDiagnostic::new(
+                self.name(),
+                call.span,
+                format!("hardware register '{}' has size 0 in manifest", reg.name),
+            ));
+        }
+
+        let max_off = reg.size.saturating_sub(4);
+        let max_off_i = z3::ast::Int::from_u64(env.ctx(), max_off);
+        let ok_off = offset.le(&max_off_i);
+        env.prove_implied(ok_off.not(), call.args[1].span, "MMIO field offset may be out of bounds")?;
+        env.push_constraint(ok_off);
+
+        Ok((reg, field))
+    }
+
+    /// `v` is one of the field's named values.
+    #[cfg(feature = "z3")]
+    fn z3_field_value_is_named<'ctx>(
+        env: &mut dyn Z3CallEnv<'ctx>,
+        field: &HwField,
+        v: &z3::ast::Int<'ctx>,
+    ) -> z3::ast::Bool<'ctx> {
+        let ctx = env.ctx();
+        let eqs: Vec<z3::ast::Bool<'ctx>> = field
+            .values
+            .iter()
+            .map(|(_, n)| v._eq(&z3::ast::Int::from_u64(ctx, *n)))
+            .collect();
+        let refs: Vec<&z3::ast::Bool<'ctx>> = eqs.iter().collect();
+        z3::ast::Bool::or(ctx, &refs)
+    }
+
+    #[cfg(feature = "z3")]
+    fn z3_hw_read_field<'ctx>(
+        &self,
+        call: &Z3Call<'_, '_>,
+        env: &mut dyn Z3CallEnv<'ctx>,
+    ) -> Result<z3::ast::Int<'ctx>, NexusDiagnostic> {
+        if call.args.len() != 3 {
+            return Err(NexusDiagnostic::new(
+                self.name(),
+                call.span,
+                "hw.read_field expects 3 args (cap, offset, field name)",
+            ));
+        }
+
+        let (_reg, field) = self.z3_hw_field(call, env, "hw.read_field", false)?;
+
+        let v = env.fresh_int("mmio_field");
+        let lo = z3::ast::Int::from_u64(env.ctx(), 0);
+        let hi = z3::ast::Int::from_u64(env.ctx(), field.max_value());
+        env.push_constraint(v.ge(&lo));
+        env.push_constraint(v.le(&hi));
+        if !field.values.is_empty() {
+            let named = Self::z3_field_value_is_named(env, &field, &v);
+            env.push_constraint(named);
+        }
+
+        Ok(v)
+    }
+
+    #[cfg(feature = "z3")]
+    fn z3_hw_write_field<'ctx>(
+        &self,
+        call: &Z3Call<'_, '_>,
+        env: &mut dyn Z3CallEnv<'ctx>,
+    ) -> Result<z3::ast::Int<'ctx>, NexusDiagnostic> {
+        if call.args.len() != 4 {
+            return Err(NexusDiagnostic::new(
+                self.name(),
+                call.span,
+                "hw.write_field expects 4 args (cap, offset, field name, value)",
+            ));
+        }
+
+        let (reg, field) = self.z3_hw_field(call, env, "hw.write_field", true)?;
+        let value_span = call.args[3].span;
+
+        // A string literal names one of the field's values.
+        let value = match Self::eval_string_lit(call.args[3]) {
+            Some(name) => {
+                let n = field.value(name).ok_or_else(|| {
+                    NexusDiagnostic::new(
+                        self.name(),
+                        value_span,
+                        format!("field '{}' of register '{}' has no value '{name}'", field.name, reg.name),
+                    )
+                })?;
+                z3::ast::Int::from_u64(env.ctx(), n)
+            }
+            None => env.eval_int(call.args[3])?,
+        };
+
+        let max = field.max_value();
+        let lo = z3::ast::Int::from_u64(env.ctx(), 0);
+        let hi = z3::ast::Int::from_u64(env.ctx(), max);
+        let fits = z3::ast::Bool::and(env.ctx(), &[&value.ge(&lo), &value.le(&hi)]);
+        env.prove_implied(
+            fits.not(),
+            value_span,
+            &format!("value may not fit field '{}' ({} bits)", field.name, field.width),
+        )?;
+        env.push_constraint(fits.clone());
+
+        if !field.values.is_empty() {
+            let named = Self::z3_field_value_is_named(env, &field, &value);
+            env.prove_implied(
+                named.not(),
+                value_span,
+                &format!("value may not be one of the named values of field '{}'", field.name),
+            )?;
+            env.push_constraint(named);
+        }
+
+        // The write replaces the field's bits of the current register value; every reserved bit
+        // must come out as it went in.
+        let ctx = env.ctx();
+        let old = z3::ast::BV::from_int(&env.fresh_int("mmio"), 32);
+        let shifted = z3::ast::BV::from_int(&value, 32).bvshl(&z3::ast::BV::from_u64(ctx, field.offset.into(), 32));
+        let new = old
+            .bvand(&z3::ast::BV::from_u64(ctx, !field.mask() & 0xFFFF_FFFF, 32))
+            .bvor(&shifted);
+        let reserved = reg.reserved_mask();
+        let reserved_bv = z3::ast::BV::from_u64(ctx, reserved, 32);
+        let untouched = new
+            .bvxor(&old)
+            .bvand(&reserved_bv)
+            ._eq(&z3::ast::BV::from_u64(ctx, 0, 32));
+        env.prove_implied(
+            untouched.not(),
+            call.span,
+            &format!("field write may change reserved bits of register '{}'", reg.name),
+        )?;
+
+        let field_mask = field.mask();
+        record_proof(
+            env.nexus(),
+            ProofNote {
+                plugin: self.name().to_string(),
+                span: call.span,
+                message: format!(
+                    "Verified by Aura-IoT Plugin: '{}.{}' write fits its {} bit(s) and leaves reserved bits (0x{reserved:X}) untouched.",
+                    reg.name, field.name, field.width
+                ),
+                smt: Some(fits.to_string()),
+                related: vec![
+                    aura_nexus::NexusRelated {
+                        span: value_span,
+                        message: format!("Field range: [0x0..0x{max:X}]"),
+                    },
+                    aura_nexus::NexusRelated {
+                        span: call.args[2].span,
+                        message: format!("Field bits: [{}..{}] (mask 0x{field_mask:X})", field.offset, field.offset + field.width - 1),
+                    },
+                ],
+                kind: "verified",
+                mask: Some(field_mask),
+                range: Some((0, max)),
+                unsat_core: Vec::new(),
+                interpolant: None,
+            },
+        );
+
+        Ok(z3::ast::Int::from_u64(env.ctx(), 0))
+    }
+
+    /// Looks up the manifest entry named by string literal `arg` with `find`.
+    #[cfg(feature = "z3")]
+    fn z3_manifest_entry<'ctx, T: Clone>(
+        &self,
+        call: &Z3Call<'_, '_>,
+        env: &mut dyn Z3CallEnv<'ctx>,
+        arg: &Expr,
+        kind: &str,
+        find: for<'m> fn(&'m HardwareManifest, &str) -> Option<&'m T>,
+    ) -> Result<T, NexusDiagnostic> {
+        let name = Self::eval_string_lit(arg).ok_or_else(|| {
+            NexusDiagnostic::new(self.name(), arg.span, format!("{} {kind} name must be a string literal", call.name))
+        })?;
+
+        Self::ensure_loaded(env.nexus(), call.span)?;
+
+        let st = Self::state(env.nexus());
+        find(&st.hw, name)
+            .cloned()
+            .ok_or_else(|| NexusDiagnostic::new(self.name(), arg.span, format!("unknown {kind} '{name}'")))
+    }
+
+    #[cfg(feature = "z3")]
+    fn z3_hw_enable_irq<'ctx>(
+        &self,
+        call: &Z3Call<'_, '_>,
+        env: &mut dyn Z3CallEnv<'ctx>,
+    ) -> Result<z3::ast::Int<'ctx>, NexusDiagnostic> {
+        if call.args.len() != 2 {
+            return Err(NexusDiagnostic::new(
+                self.name(),
+                call.span,
+                "hw.enable_irq expects 2 args (string literal interrupt name, priority)",
+            ));
+        }
+
+        let irq = self.z3_manifest_entry(call, env, call.args[0], "interrupt", HardwareManifest::interrupt)?;
+        let priority = env.eval_int(call.args[1])?;
+
+        let max = irq.max_priority;
+        let lo = z3::ast::Int::from_u64(env.ctx(), 0);
+        let hi = z3::ast::Int::from_u64(env.ctx(), max);
+        let ok = z3::ast::Bool::and(env.ctx(), &[&priority.ge(&lo), &priority.le(&hi)]);
+        env.prove_implied(
+            ok.not(),
+            call.args[1].span,
+            &format!("IRQ priority may be outside [0..{max}] for interrupt '{}'", irq.name),
+        )?;
+        env.push_constraint(ok.clone());
+
+        record_proof(
+            env.nexus(),
+            ProofNote {
+                plugin: self.name().to_string(),
+                span: call.span,
+                message: format!(
+                    "Verified by Aura-IoT Plugin: interrupt '{}' (line {}) is enabled with a priority within its configured range.",
+                    irq.name, irq.line
+                ),
+                smt: Some(ok.to_string()),
+                related: vec![aura_nexus::NexusRelated {
+                    span: call.args[1].span,
+                    message: format!("Priority range: [0..{max}]"),
+                }],
+                kind: "verified",
+                mask: None,
+                range: Some((0, max)),
+                unsat_core: Vec::new(),
+                interpolant: None,
+            },
+        );
+
+        Ok(z3::ast::Int::from_u64(env.ctx(), 0))
+    }
+
+    #[cfg(feature = "z3")]
+    fn z3_hw_dma_start<'ctx>(
+        &self,
+        call: &Z3Call<'_, '_>,
+        env: &mut dyn Z3CallEnv<'ctx>,
+    ) -> Result<z3::ast::Int<'ctx>, NexusDiagnostic> {
+        if call.args.len() != 3 {
+            return Err(NexusDiagnostic::new(
+                self.name(),
+                call.span,
+                "hw.dma_start expects 3 args (string literal channel name, buffer size, length)",
+            ));
+        }
+
+        let ch = self.z3_manifest_entry(call, env, call.args[0], "DMA channel", HardwareManifest::dma_channel)?;
+        let buffer = env.eval_int(call.args[1])?;
+        let len = env.eval_int(call.args[2])?;
+        let len_span = call.args[2].span;
+
+        let zero = z3::ast::Int::from_u64(env.ctx(), 0);
+        let ok_len = z3::ast::Bool::and(env.ctx(), &[&len.ge(&zero), &len.le(&buffer)]);
+        env.prove_implied(ok_len.not(), len_span, "DMA length may exceed the buffer size")?;
+        env.push_constraint(ok_len.clone());
+
+        let mut related = vec![aura_nexus::NexusRelated {
+            span: call.args[1].span,
+            message: "DMA length <= buffer size".to_string(),
+        }];
+        if let Some(max_len) = ch.max_len {
+            let max_i = z3::ast::Int::from_u64(env.ctx(), max_len);
+            let ok_max = len.le(&max_i);
+            env.prove_implied(
+                ok_max.not(),
+                len_span,
+                &format!("DMA length may exceed the {max_len}-byte limit of channel '{}'", ch.name),
+            )?;
+            env.push_constraint(ok_max);
+            related.push(aura_nexus::NexusRelated {
+                span: len_span,
+                message: format!("Channel limit: <= {max_len} bytes"),
+            });
+        }
+
+        record_proof(
+            env.nexus(),
+            ProofNote {
+                plugin: self.name().to_string(),
+                span: call.span,
+                message: format!(
+                    "Verified by Aura-IoT Plugin: DMA transfer on '{}' fits its buffer{}.",
+                    ch.name,
+                    if ch.max_len.is_some() { " and the channel limit" } else { "" }
+                ),
+                smt: Some(ok_len.to_string()),
+                related,
+                kind: "verified",
+                mask: None,
+                range: ch.max_len.map(|max| (0, max)),
+                unsat_core: Vec::new(),
+                interpolant: None,
+            },
+        );
+
+        Ok(z3::ast::Int::from_u64(env.ctx(), 0))
+    }
+
+    /// `hw.set_power("STATE")`: proves every state the device may be in has a declared move to
+    /// `STATE` (staying put is always allowed), then moves it there.
+    #[cfg(feature = "z3")]
+    fn z3_hw_set_power<'ctx>(
+        &self,
+        call: &Z3Call<'_, '_>,
+        env: &mut dyn Z3CallEnv<'ctx>,
+    ) -> Result<z3::ast::Int<'ctx>, NexusDiagnostic> {
+        if call.args.len() != 1 {
+            return Err(NexusDiagnostic::new(
+                self.name(),
+                call.span,
+                "hw.set_power expects 1 arg (string literal power state)",
+            ));
+        }
+
+        let name = Self::eval_string_lit(call.args[0]).ok_or_else(|| {
+            NexusDiagnostic::new(self.name(), call.args[0].span, "hw.set_power state must be a string literal")
+        })?;
+
+        Self::ensure_loaded(env.nexus(), call.span)?;
+
+        let power = Self::state(env.nexus()).hw.power.clone().ok_or_else(|| {
+            NexusDiagnostic::new(self.name(), call.span, "hw.set_power needs a [hardware.power] section in the manifest")
+        })?;
+        let to = power
+            .index(name)
+            .ok_or_else(|| NexusDiagnostic::new(self.name(), call.args[0].span, format!("unknown power state '{name}'")))?;
+
+        let from = Self::z3_power_states(env, &power);
+        let sources = power.sources(&power.states[to]);
+        if let Some(&bad) = from.iter().find(|&&s| s != to && !sources.contains(&s)) {
+            let message = format!(
+                "power transition {} -> {} is not declared in [hardware.power]",
+                power.states[bad], power.states[to]
+            );
+            return Err(self.power_violation(env.nexus(), &power, call.span, message, bad));
+        }
+
+        let names: Vec<&str> = from.iter().map(|s| power.states[*s].as_str()).collect();
+        record_proof(
+            env.nexus(),
+            ProofNote {
+                plugin: self.name().to_string(),
+                span: call.span,
+                message: format!(
+                    "Verified by Aura-IoT Plugin: moving to power state '{}' is legal from every state the device may be in ({}).",
+                    power.states[to],
+                    names.join(", ")
+                ),
+                smt: None,
+                related: Vec::new(),
+                kind: "verified",
+                mask: None,
+                range: None,
+                unsat_core: Vec::new(),
+                interpolant: None,
+            },
+        );
+
+        Self::state(env.nexus()).power_steps.push(PowerStep {
+            span: call.span,
+            from,
+            to,
+        });
+        let ctx = env.ctx();
+        let init = z3::ast::Int::from_u64(ctx, power.index(&power.initial).unwrap_or(0) as u64);
+        env.set_ghost_int(POWER_GHOST, init, z3::ast::Int::from_u64(ctx, to as u64));
+
+        Ok(z3::ast::Int::from_u64(ctx, 0))
+    }
+
+    /// Proves the payload length `len` is non-negative and within `max_payload` bytes of
+    /// `target`, and returns the proven condition.
+    #[cfg(feature = "z3")]
+    fn z3_payload_fits<'ctx>(
+        &self,
+        env: &mut dyn Z3CallEnv<'ctx>,
+        len: &Expr,
+        max_payload: Option<u64>,
+        target: &str,
+    ) -> Result<z3::ast::Bool<'ctx>, NexusDiagnostic> {
+        let len_i = env.eval_int(len)?;
+        let zero = z3::ast::Int::from_u64(env.ctx(), 0);
+        let mut ok = len_i.ge(&zero);
+        env.prove_implied(ok.not(), len.span, &format!("payload length for {target} may be negative"))?;
+        if let Some(max) = max_payload {
+            let max_i = z3::ast::Int::from_u64(env.ctx(), max);
+            let fits = len_i.le(&max_i);
+            env.prove_implied(
+                fits.not(),
+                len.span,
+                &format!("payload for {target} may exceed its {max}-byte limit"),
+            )?;
+            ok = z3::ast::Bool::and(env.ctx(), &[&ok, &fits]);
+        }
+        env.push_constraint(ok.clone());
+        Ok(ok)
+    }
+
+    /// `net.publish("topic", len, qos)`: the topic must match a declared pattern; the payload
+    /// length and QoS level (default 0) are proven within the first matching pattern's limits.
+    #[cfg(feature = "z3")]
+    fn z3_net_publish<'ctx>(
+        &self,
+        call: &Z3Call<'_, '_>,
+        env: &mut dyn Z3CallEnv<'ctx>,
+    ) -> Result<z3::ast::Int<'ctx>, NexusDiagnostic> {
+        if call.args.len() < 2 || call.args.len() > 3 {
+            return Err(NexusDiagnostic::new(
+                self.name(),
+                call.span,
+                "net.publish expects 2 or 3 args (string literal topic, payload length, QoS level)",
+            ));
+        }
+
+        let topic_span = call.args[0].span;
+        let topic = Self::eval_string_lit(call.args[0]).ok_or_else(|| {
+            NexusDiagnostic::new(self.name(), topic_span, "net.publish topic must be a string literal")
+        })?;
+        if topic.is_empty() || topic.contains(['+', '#']) {
+            return Err(NexusDiagnostic::new(
+                self.name(),
+                topic_span,
+                format!("cannot publish to '{topic}': topics must be non-empty and free of the wildcards '+' and '#'"),
+            ));
+        }
+
+        Self::ensure_loaded(env.nexus(), call.span)?;
+
+        let entry = Self::state(env.nexus()).hw.topic(topic).cloned().ok_or_else(|| {
+            NexusDiagnostic::new(
+                self.name(),
+                topic_span,
+                format!("topic '{topic}' matches no pattern in [hardware.net] topics"),
+            )
+        })?;
+
+        let target = format!("topic '{topic}'");
+        let ok_len = self.z3_payload_fits(env, call.args[1], entry.max_payload, &target)?;
+
+        let mut related = vec![aura_nexus::NexusRelated {
+            span: topic_span,
+            message: format!("Matches topic pattern '{}'", entry.pattern),
+        }];
+        if let Some(max) = entry.max_payload {
+            related.push(aura_nexus::NexusRelated {
+                span: call.args[1].span,
+                message: format!("Payload limit: <= {max} bytes"),
+            });
+        }
+
+        let max_qos = entry.max_qos;
+        let ctx = env.ctx();
+        let (qos, qos_span) = match call.args.get(2) {
+            Some(arg) => (env.eval_int(arg)?, arg.span),
+            None => (z3::ast::Int::from_u64(ctx, 0), call.span),
+        };
+        let ok_qos = z3::ast::Bool::and(
+            ctx,
+            &[&qos.ge(&z3::ast::Int::from_u64(ctx, 0)), &qos.le(&z3::ast::Int::from_u64(ctx, max_qos))],
+        );
+        env.prove_implied(
+            ok_qos.not(),
+            qos_span,
+            &format!("QoS level may be outside [0..{max_qos}] for {target}"),
+        )?;
+        env.push_constraint(ok_qos.clone());
+        related.push(aura_nexus::NexusRelated {
+            span: qos_span,
+            message: format!("QoS range: [0..{max_qos}]"),
+        });
+
+        let smt = z3::ast::Bool::and(env.ctx(), &[&ok_len, &ok_qos]).to_string();
+        record_proof(
+            env.nexus(),
+            ProofNote {
+                plugin: self.name().to_string(),
+                span: call.span,
+                message: format!(
+                    "Verified by Aura-IoT Plugin: publishing to '{topic}' meets the message contract of '{}'.",
+                    entry.pattern
+                ),
+                smt: Some(smt),
+                related,
+                kind: "verified",
+                mask: None,
+                range: entry.max_payload.map(|max| (0, max)),
+                unsat_core: Vec::new(),
+                interpolant: None,
+            },
+        );
+
+        Ok(z3::ast::Int::from_u64(env.ctx(), 0))
+    }
+
+    /// `net.send("ENDPOINT", len)`: proves the message fits the endpoint's `max_payload`.
+    #[cfg(feature = "z3")]
+    fn z3_net_send<'ctx>(
+        &self,
+        call: &Z3Call<'_, '_>,
+        env: &mut dyn Z3CallEnv<'ctx>,
+    ) -> Result<z3::ast::Int<'ctx>, NexusDiagnostic> {
+        if call.args.len() != 2 {
+            return Err(NexusDiagnostic::new(
+                self.name(),
+                call.span,
+                "net.send expects 2 args (string literal endpoint name, payload length)",
+            ));
+        }
+
+        let ep = self.z3_manifest_entry(call, env, call.args[0], "endpoint", HardwareManifest::endpoint)?;
+        let ok = self.z3_payload_fits(env, call.args[1], ep.max_payload, &format!("endpoint '{}'", ep.name))?;
+
+        record_proof(
+            env.nexus(),
+            ProofNote {
+                plugin: self.name().to_string(),
+                span: call.span,
+                message: format!(
+                    "Verified by Aura-IoT Plugin: the message to '{}' has a valid length{}.",
+                    ep.name,
+                    if ep.max_payload.is_some() { " within its payload limit" } else { "" }
+                ),
+                smt: Some(ok.to_string()),
+                related: ep
+                    .max_payload
+                    .map(|max| aura_nexus::NexusRelated {
+                        span: call.args[1].span,
+                        message: format!("Payload limit: <= {max} bytes"),
+                    })
+                    .into_iter()
+                    .collect(),
+                kind: "verified",
+                mask: None,
+                range: ep.max_payload.map(|max| (0, max)),
+                unsat_core: Vec::new(),
+                interpolant: None,
+            },
+        );
+
+        Ok(z3::ast::Int::from_u64(env.ctx(), 0))
+    }
 }
@@ -0,0 +1,53 @@
+#![cfg(feature = "z3")]
+
+mod common;
+use common::{verify, verify_err};
+
+const REGS: &str = r#"[[hardware.registers]]
+name = "STATUS"
+base = "0x40000000"
+size = 4
+access = "ro"
+
+[[hardware.registers]]
+name = "TXDATA"
+base = "0x40000004"
+size = 4
+access = "wo"
+"#;
+
+fn program(body: &str) -> String {
+    format!("import aura::iot\n\ncell main():\n{body}")
+}
+
+#[test]
+fn a_read_only_register_can_be_read_but_not_written() {
+    let notes = verify("ro-read", REGS, &program("    val cap = hw.open(\"STATUS\")\n    val _v = hw.read_u32(cap, 0)\n"))
+        .expect("verify");
+    assert!(
+        notes.contains(&"Verified by Aura-IoT Plugin: read of 'STATUS' is allowed; the register is read-only (reads allowed).".to_string()),
+        "{notes:?}"
+    );
+
+    let err = verify_err("ro-write", REGS, &program("    val cap = hw.open(\"STATUS\")\n    val _w = hw.write_u32(cap, 0, 1)\n"));
+    assert!(
+        err.contains("write to register 'STATUS' is not allowed: it is read-only (reads allowed)"),
+        "{err}"
+    );
+}
+
+#[test]
+fn a_write_only_register_can_be_written_but_not_read() {
+    let notes = verify("wo-write", REGS, &program("    val cap = hw.open(\"TXDATA\")\n    val _w = hw.write_u32(cap, 0, 65)\n"))
+        .expect("verify");
+    assert!(
+        notes.contains(&"Verified by Aura-IoT Plugin: write to 'TXDATA' is allowed; the register is write-only (writes allowed).".to_string()),
+        "{notes:?}"
+    );
+
+    let err = verify_err("wo-read", REGS, &program("    val cap = hw.open(\"TXDATA\")\n    val _v = hw.read_u32(cap, 0)\n"));
+    assert!(
+        err.contains("read of register 'TXDATA' is not allowed: it is write-only (writes allowed)"),
+        "{err}"
+    );
+}
@@ -0,0 +1,36 @@
+#![allow(dead_code)]
+
+use aura_nexus::{NexusContext, NexusFileContext, ProofNotes};
+use aura_plugin_iot::AuraIotPlugin;
+use aura_verify::{verify_program_z3_profile, SmtProfile, Z3Prover};
+
+/// Verifies `src` against the hardware declared by `manifest`, an `aura.toml` written to a
+/// fresh project named `name`. Returns the plugin's proof notes, or the first failure's message.
+pub fn verify(name: &str, manifest: &str, src: &str) -> Result<Vec<String>, String> {
+    let dir = std::env::temp_dir().join(format!("aura-iot-{name}-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("temp dir");
+    let manifest_path = dir.join("aura.toml");
+    std::fs::write(&manifest_path, manifest).expect("write aura.toml");
+
+    let program = aura_parse::parse_source(src).expect("parse");
+    let mut prover = Z3Prover::new();
+    let mut nexus = NexusContext::default();
+    nexus.insert(NexusFileContext {
+        source_path: None,
+        manifest_path: Some(manifest_path),
+    });
+    let plugins = (AuraIotPlugin::new(),);
+    let out = verify_program_z3_profile(&program, &mut prover, &plugins, &mut nexus, SmtProfile::Fast);
+    let _ = std::fs::remove_dir_all(&dir);
+    out.map_err(|e| e.message)?;
+    let notes = nexus.get::<ProofNotes>().map(|n| n.0.iter().map(|p| p.message.clone()).collect());
+    Ok(notes.unwrap_or_default())
+}
+
+/// Like `verify`, for a program that must fail; returns the failure's message.
+pub fn verify_err(name: &str, manifest: &str, src: &str) -> String {
+    match verify(name, manifest, src) {
+        Ok(notes) => panic!("expected a verification failure, got notes {notes:?}"),
+        Err(e) => e,
+    }
+}
@@ -0,0 +1,63 @@
+#![cfg(feature = "z3")]
+
+mod common;
+use common::{verify, verify_err};
+
+const SPI: &str = r#"[[hardware.registers]]
+name = "SPI_CTRL"
+base = "0x40000000"
+size = 4
+mask = "0x3FF"
+fields = [
+  { name = "EN", offset = 0, width = 1 },
+  { name = "MODE", offset = 1, width = 2, values = { IDLE = 0, TX = 1, RX = 2 } },
+  { name = "DIV", offset = 4, width = 6 },
+]
+"#;
+
+fn write_div(value: &str) -> String {
+    format!("import aura::iot\n\ncell main():\n    val cap = hw.open(\"SPI_CTRL\")\n    val _w = hw.write_field(cap, 0, \"DIV\", {value})\n")
+}
+
+#[test]
+fn a_value_that_fits_its_field_is_written_without_touching_reserved_bits() {
+    let notes = verify("fits", SPI, &write_div("63")).expect("verify");
+    assert!(
+        notes.contains(
+            &"Verified by Aura-IoT Plugin: 'SPI_CTRL.DIV' write fits its 6 bit(s) and leaves reserved bits (0xFFFFFC08) untouched."
+                .to_string()
+        ),
+        "{notes:?}"
+    );
+
+    let src = "import aura::iot\n\ncell main():\n    val cap = hw.open(\"SPI_CTRL\")\n    val _w = hw.write_field(cap, 0, \"MODE\", \"RX\")\n    val _m = hw.read_field(cap, 0, \"MODE\")\n";
+    verify("named", SPI, src).expect("verify");
+}
+
+#[test]
+fn a_value_wider_than_its_field_is_rejected() {
+    let err = verify_err("overflow", SPI, &write_div("64"));
+    assert!(err.contains("value may not fit field 'DIV' (6 bits)"), "{err}");
+
+    let src = "import aura::iot\n\ncell main():\n    val cap = hw.open(\"SPI_CTRL\")\n    val _w = hw.write_field(cap, 0, \"MODE\", 3)\n";
+    let err = verify_err("unnamed", SPI, src);
+    assert!(err.contains("value may not be one of the named values of field 'MODE'"), "{err}");
+}
+
+#[test]
+fn a_field_over_reserved_bits_is_rejected() {
+    // Bit 10 lies outside the register mask, so it is reserved.
+    let manifest = SPI.replace("{ name = \"DIV\", offset = 4, width = 6 }", "{ name = \"DIV\", offset = 5, width = 6 }");
+    let err = verify_err("reserved", &manifest, &write_div("1"));
+    assert!(err.contains("field 'DIV' of register 'SPI_CTRL' lies outside the register mask 0x3FF"), "{err}");
+}
+
+#[test]
+fn an_unknown_field_is_rejected_for_reads_and_writes() {
+    let err = verify_err("unknown-write", SPI, &write_div("1").replace("\"DIV\"", "\"BAUD\""));
+    assert!(err.contains("hardware register 'SPI_CTRL' has no field 'BAUD'"), "{err}");
+
+    let src = "import aura::iot\n\ncell main():\n    val cap = hw.open(\"SPI_CTRL\")\n    val _r = hw.read_field(cap, 0, \"BAUD\")\n";
+    let err = verify_err("unknown-read", SPI, src);
+    assert!(err.contains("hardware register 'SPI_CTRL' has no field 'BAUD'"), "{err}");
+}
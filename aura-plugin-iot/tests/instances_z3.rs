@@ -0,0 +1,57 @@
+#![cfg(feature = "z3")]
+
+mod common;
+use common::{verify, verify_err};
+
+const UARTS: &str = r#"[[hardware.registers]]
+name = "UART"
+base = "0x40001000"
+size = 8
+instances = 4
+stride = "0x100"
+"#;
+
+fn open(index: &str, body: &str) -> String {
+    format!("import aura::iot\n\ncell main():\n    val cap = hw.open(\"UART\", {index})\n{body}")
+}
+
+#[test]
+fn the_instance_count_is_out_of_range() {
+    let err = verify_err("past-end", UARTS, &open("4", ""));
+    assert!(err.contains("instance index may be out of range for 'UART' (4 instance(s))"), "{err}");
+
+    let notes = verify("last", UARTS, &open("3", "")).expect("verify");
+    assert!(
+        notes.contains(&"Verified by Aura-IoT Plugin: instance index of 'UART' is within [0..3] (base 0x40001300).".to_string()),
+        "{notes:?}"
+    );
+
+    // A symbolic index must be proven below the count too.
+    let src = |hi: u32| format!("import aura::iot\n\ncell pick(n: u32[0..{hi}]):\n    val cap = hw.open(\"UART\", n)\n");
+    let notes = verify("symbolic", UARTS, &src(3)).expect("verify");
+    assert!(
+        notes.contains(
+            &"Verified by Aura-IoT Plugin: instance index of 'UART' is within [0..3] (base 0x40001000 + index * 0x100)."
+                .to_string()
+        ),
+        "{notes:?}"
+    );
+    let err = verify_err("symbolic-past-end", UARTS, &src(4));
+    assert!(err.contains("instance index may be out of range for 'UART' (4 instance(s))"), "{err}");
+}
+
+#[test]
+fn accesses_are_bounds_checked_against_the_selected_instance() {
+    let notes = verify("in-bounds", UARTS, &open("2", "    val _w = hw.write_u32(cap, 4, 1)\n    val _r = hw.read_u32(cap, 4)\n"))
+        .expect("verify");
+    assert!(
+        notes.contains(&"Verified by Aura-IoT Plugin: write to 'UART[2]' is allowed; the register is read-write (reads and writes allowed).".to_string()),
+        "{notes:?}"
+    );
+
+    // Each instance spans `size` bytes from `base + n * stride`, not up to the next instance.
+    let err = verify_err("past-size", UARTS, &open("2", "    val _w = hw.write_u32(cap, 8, 1)\n"));
+    assert!(err.contains("MMIO write offset may be out of bounds"), "{err}");
+    let err = verify_err("read-past-size", UARTS, &open("1", "    val _r = hw.read_u32(cap, 12)\n"));
+    assert!(err.contains("MMIO read offset may be out of bounds"), "{err}");
+}
@@ -0,0 +1,58 @@
+#![cfg(feature = "z3")]
+
+mod common;
+use common::{verify, verify_err};
+
+const HW: &str = r#"[[hardware.interrupts]]
+name = "TIMER0"
+line = 3
+max_priority = 7
+
+[[hardware.dma]]
+name = "DMA0"
+max_len = 256
+"#;
+
+fn program(params: &str, body: &str) -> String {
+    format!("import aura::iot\n\ncell main({params}):\n{body}")
+}
+
+#[test]
+fn irq_priorities_stay_within_the_configured_range() {
+    let notes = verify("irq-max", HW, &program("", "    val _e = hw.enable_irq(\"TIMER0\", 7)\n")).expect("verify");
+    assert!(
+        notes.contains(
+            &"Verified by Aura-IoT Plugin: interrupt 'TIMER0' (line 3) is enabled with a priority within its configured range."
+                .to_string()
+        ),
+        "{notes:?}"
+    );
+    verify("irq-symbolic", HW, &program("p: u32[0..7]", "    val _e = hw.enable_irq(\"TIMER0\", p)\n")).expect("verify");
+
+    let err = verify_err("irq-high", HW, &program("", "    val _e = hw.enable_irq(\"TIMER0\", 8)\n"));
+    assert!(err.contains("IRQ priority may be outside [0..7] for interrupt 'TIMER0'"), "{err}");
+    let err = verify_err("irq-symbolic-high", HW, &program("p: u32[0..8]", "    val _e = hw.enable_irq(\"TIMER0\", p)\n"));
+    assert!(err.contains("IRQ priority may be outside [0..7] for interrupt 'TIMER0'"), "{err}");
+
+    let err = verify_err("irq-unknown", HW, &program("", "    val _e = hw.enable_irq(\"TIMER9\", 1)\n"));
+    assert!(err.contains("unknown interrupt 'TIMER9'"), "{err}");
+}
+
+#[test]
+fn dma_lengths_fit_the_buffer_and_the_channel() {
+    let notes = verify("dma-fits", HW, &program("", "    val _d = hw.dma_start(\"DMA0\", 64, 64)\n")).expect("verify");
+    assert!(
+        notes.contains(&"Verified by Aura-IoT Plugin: DMA transfer on 'DMA0' fits its buffer and the channel limit.".to_string()),
+        "{notes:?}"
+    );
+    verify("dma-symbolic", HW, &program("n: u32[0..64]", "    val _d = hw.dma_start(\"DMA0\", 64, n)\n")).expect("verify");
+
+    let err = verify_err("dma-over-buffer", HW, &program("", "    val _d = hw.dma_start(\"DMA0\", 64, 65)\n"));
+    assert!(err.contains("DMA length may exceed the buffer size"), "{err}");
+    let err = verify_err("dma-symbolic-over", HW, &program("n: u32[0..65]", "    val _d = hw.dma_start(\"DMA0\", 64, n)\n"));
+    assert!(err.contains("DMA length may exceed the buffer size"), "{err}");
+
+    // The buffer is big enough, but the channel is not.
+    let err = verify_err("dma-over-channel", HW, &program("", "    val _d = hw.dma_start(\"DMA0\", 512, 300)\n"));
+    assert!(err.contains("DMA length may exceed the 256-byte limit of channel 'DMA0'"), "{err}");
+}
@@ -0,0 +1,87 @@
+use aura_plugin_iot::HardwareManifest;
+
+/// A register's `(name, base, size)`.
+type Reg<'a> = (&'a str, &'a str, u64);
+
+/// `[[hardware.registers]]` entries, one per register.
+fn registers(regs: &[Reg]) -> String {
+    regs.iter()
+        .map(|(name, base, size)| format!("[[hardware.registers]]\nname = \"{name}\"\nbase = \"{base}\"\nsize = {size}\n"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Loads `raw` as a project's `aura.toml` and returns each issue it reports with the
+/// manifest text its span covers.
+fn issues(name: &str, raw: &str) -> Vec<(String, String)> {
+    let dir = std::env::temp_dir().join(format!("aura-iot-manifest-{name}-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("temp dir");
+    std::fs::write(dir.join("aura.toml"), raw).expect("write aura.toml");
+    let loaded = HardwareManifest::load_from(&dir);
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let Err(err) = loaded else {
+        return Vec::new();
+    };
+    err.labels()
+        .into_iter()
+        .flatten()
+        .map(|l| {
+            let text = raw[l.offset()..l.offset() + l.len()].to_string();
+            (l.label().unwrap_or_default().to_string(), text)
+        })
+        .collect()
+}
+
+#[test]
+fn each_register_issue_points_at_its_entry() {
+    let cases: &[(&str, &[Reg], &str, &str)] = &[
+        (
+            "overlap",
+            &[("CTRL", "0x1000", 8), ("DATA", "0x1004", 4)],
+            "register 'DATA' [0x1004, 0x1008) overlaps 'CTRL' [0x1000, 0x1008)",
+            "DATA",
+        ),
+        (
+            "base",
+            &[("CTRL", "0x1000", 4), ("DATA", "0x1006", 4)],
+            "register 'DATA' base 0x1006 is not 4-byte aligned",
+            "DATA",
+        ),
+        (
+            "size",
+            &[("CTRL", "0x1000", 6), ("DATA", "0x1008", 4)],
+            "register 'CTRL' size 6 is not a multiple of 4 bytes",
+            "CTRL",
+        ),
+        (
+            "duplicate",
+            &[("UART", "0x1000", 4), ("SPI", "0x1004", 4), ("UART", "0x1008", 4)],
+            "register 'UART' is declared twice (also as 'UART')",
+            "UART\"\nbase = \"0x1008",
+        ),
+        (
+            "duplicate-case",
+            &[("UART", "0x1000", 4), ("uart", "0x1004", 4)],
+            "register 'uart' is declared twice (also as 'UART')",
+            "uart",
+        ),
+    ];
+
+    for (name, regs, message, entry) in cases {
+        let raw = registers(regs);
+        let found = issues(name, &raw);
+        assert_eq!(found.len(), 1, "{name}: {found:?}");
+        let (label, text) = &found[0];
+        assert_eq!(label, message, "{name}");
+        assert!(text.contains(&format!("name = \"{entry}")), "{name}: span covers {text:?}");
+        assert!(text.starts_with("[[hardware.registers]]"), "{name}: span covers {text:?}");
+        assert_eq!(text.matches("[[hardware.registers]]").count(), 1, "{name}: span covers {text:?}");
+    }
+}
+
+#[test]
+fn a_well_formed_manifest_has_no_issues() {
+    let raw = registers(&[("CTRL", "0x1000", 8), ("DATA", "0x1008", 4), ("uart0", "0x2000", 4)]);
+    assert!(issues("ok", &raw).is_empty());
+}
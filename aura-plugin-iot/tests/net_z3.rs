@@ -0,0 +1,52 @@
+#![cfg(feature = "z3")]
+
+mod common;
+use common::{verify, verify_err};
+
+const NET: &str = r#"[[hardware.net.topics]]
+pattern = "sensors/+/temp"
+max_payload = 128
+max_qos = 1
+"#;
+
+fn publish(params: &str, args: &str) -> String {
+    format!("import aura::iot\n\ncell main({params}):\n    val _p = net.publish({args})\n")
+}
+
+#[test]
+fn a_publish_within_the_contract_is_verified() {
+    let notes = verify("ok", NET, &publish("", "\"sensors/kitchen/temp\", 128, 1")).expect("verify");
+    assert!(
+        notes.contains(
+            &"Verified by Aura-IoT Plugin: publishing to 'sensors/kitchen/temp' meets the message contract of 'sensors/+/temp'."
+                .to_string()
+        ),
+        "{notes:?}"
+    );
+    // QoS defaults to 0; a bounded length stays within the limit.
+    verify("symbolic", NET, &publish("n: u32[0..128]", "\"sensors/hall/temp\", n")).expect("verify");
+}
+
+#[test]
+fn a_payload_over_the_limit_is_rejected() {
+    let err = verify_err("payload", NET, &publish("", "\"sensors/kitchen/temp\", 129, 0"));
+    assert!(err.contains("payload for topic 'sensors/kitchen/temp' may exceed its 128-byte limit"), "{err}");
+    let err = verify_err("payload-symbolic", NET, &publish("n: u32[0..200]", "\"sensors/kitchen/temp\", n"));
+    assert!(err.contains("payload for topic 'sensors/kitchen/temp' may exceed its 128-byte limit"), "{err}");
+}
+
+#[test]
+fn a_topic_outside_every_pattern_is_rejected() {
+    for topic in ["sensors/kitchen/humidity", "sensors/temp", "sensors/kitchen/temp/raw"] {
+        let err = verify_err("topic", NET, &publish("", &format!("\"{topic}\", 4")));
+        assert!(err.contains(&format!("topic '{topic}' matches no pattern in [hardware.net] topics")), "{err}");
+    }
+    let err = verify_err("wildcard", NET, &publish("", "\"sensors/+/temp\", 4"));
+    assert!(err.contains("cannot publish to 'sensors/+/temp'"), "{err}");
+}
+
+#[test]
+fn a_qos_above_the_topic_maximum_is_rejected() {
+    let err = verify_err("qos", NET, &publish("", "\"sensors/kitchen/temp\", 4, 2"));
+    assert!(err.contains("QoS level may be outside [0..1] for topic 'sensors/kitchen/temp'"), "{err}");
+}
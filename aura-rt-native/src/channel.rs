@@ -0,0 +1,206 @@
+//! Multi-producer, single-consumer channels between `~>` flows.
+//!
+//! Channels are addressed by `u32` handles, as compiled programs address them through the
+//! `aura_chan_*` functions of `aura_rt.h`: handle 0 is never issued, a bounded channel
+//! holds at most its capacity (at least 1) of values, and an unbounded one never makes a
+//! sender wait. Values are received in the order they were sent. Closing a channel stops
+//! further sends; the receiver still gets everything sent before, then `Closed`.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+
+/// Why a channel operation did not go through.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChanError {
+    /// No channel has this handle.
+    Unknown(u32),
+    /// A bounded channel was asked for with capacity 0.
+    ZeroCapacity,
+    /// The channel is closed (and, for receives, drained).
+    Closed,
+    /// `try_send` found the bounded channel full.
+    Full,
+    /// `try_recv` found the open channel empty.
+    Empty,
+}
+
+impl fmt::Display for ChanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChanError::Unknown(ch) => write!(f, "unknown channel {ch}"),
+            ChanError::ZeroCapacity => write!(f, "a bounded channel needs a capacity of at least 1"),
+            ChanError::Closed => write!(f, "channel is closed"),
+            ChanError::Full => write!(f, "channel is full"),
+            ChanError::Empty => write!(f, "channel is empty"),
+        }
+    }
+}
+
+impl std::error::Error for ChanError {}
+
+struct Channel<T> {
+    /// `None` for unbounded channels.
+    capacity: Option<usize>,
+    state: Mutex<State<T>>,
+    /// Signalled when a value arrives or the channel closes.
+    readable: Condvar,
+    /// Signalled when a value is taken or the channel closes.
+    writable: Condvar,
+}
+
+struct State<T> {
+    queue: VecDeque<T>,
+    closed: bool,
+}
+
+impl<T> Channel<T> {
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn is_full(&self, state: &State<T>) -> bool {
+        self.capacity.is_some_and(|cap| state.queue.len() >= cap)
+    }
+}
+
+/// The channels of one program run, shared by every worker running its flows.
+pub struct ChannelTable<T> {
+    channels: Mutex<Vec<Arc<Channel<T>>>>,
+}
+
+impl<T> Default for ChannelTable<T> {
+    fn default() -> Self {
+        Self {
+            channels: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl<T> fmt::Debug for ChannelTable<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = self.channels.lock().unwrap_or_else(|e| e.into_inner()).len();
+        f.debug_struct("ChannelTable").field("channels", &count).finish()
+    }
+}
+
+impl<T> ChannelTable<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A channel holding at most `capacity` values; `send` waits while it is full.
+    pub fn bounded(&self, capacity: u32) -> Result<u32, ChanError> {
+        if capacity == 0 {
+            return Err(ChanError::ZeroCapacity);
+        }
+        Ok(self.insert(Some(capacity as usize)))
+    }
+
+    /// A channel `send` never waits on.
+    pub fn unbounded(&self) -> u32 {
+        self.insert(None)
+    }
+
+    fn insert(&self, capacity: Option<usize>) -> u32 {
+        let mut channels = self.channels.lock().unwrap_or_else(|e| e.into_inner());
+        channels.push(Arc::new(Channel {
+            capacity,
+            state: Mutex::new(State {
+                queue: VecDeque::new(),
+                closed: false,
+            }),
+            readable: Condvar::new(),
+            writable: Condvar::new(),
+        }));
+        u32::try_from(channels.len()).expect("fewer than 2^32 channels")
+    }
+
+    fn get(&self, ch: u32) -> Result<Arc<Channel<T>>, ChanError> {
+        let channels = self.channels.lock().unwrap_or_else(|e| e.into_inner());
+        (ch as usize)
+            .checked_sub(1)
+            .and_then(|i| channels.get(i))
+            .cloned()
+            .ok_or(ChanError::Unknown(ch))
+    }
+
+    /// The capacity of channel `ch`, `None` if it is unbounded.
+    pub fn capacity(&self, ch: u32) -> Result<Option<u32>, ChanError> {
+        Ok(self.get(ch)?.capacity.map(|cap| cap as u32))
+    }
+
+    /// The number of values sent to `ch` and not yet received.
+    pub fn len(&self, ch: u32) -> Result<usize, ChanError> {
+        Ok(self.get(ch)?.lock().queue.len())
+    }
+
+    /// Sends `value`, waiting for room while a bounded channel is full.
+    pub fn send(&self, ch: u32, value: T) -> Result<(), ChanError> {
+        let chan = self.get(ch)?;
+        let mut state = chan.lock();
+        while !state.closed && chan.is_full(&state) {
+            state = chan.writable.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+        if state.closed {
+            return Err(ChanError::Closed);
+        }
+        state.queue.push_back(value);
+        chan.readable.notify_one();
+        Ok(())
+    }
+
+    /// Sends `value` if there is room now.
+    pub fn try_send(&self, ch: u32, value: T) -> Result<(), ChanError> {
+        let chan = self.get(ch)?;
+        let mut state = chan.lock();
+        if state.closed {
+            return Err(ChanError::Closed);
+        }
+        if chan.is_full(&state) {
+            return Err(ChanError::Full);
+        }
+        state.queue.push_back(value);
+        chan.readable.notify_one();
+        Ok(())
+    }
+
+    /// The oldest value sent to `ch`, waiting for one while the channel is empty and open.
+    pub fn recv(&self, ch: u32) -> Result<T, ChanError> {
+        let chan = self.get(ch)?;
+        let mut state = chan.lock();
+        loop {
+            if let Some(value) = state.queue.pop_front() {
+                chan.writable.notify_one();
+                return Ok(value);
+            }
+            if state.closed {
+                return Err(ChanError::Closed);
+            }
+            state = chan.readable.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+    }
+
+    /// The oldest value sent to `ch`, if one is waiting.
+    pub fn try_recv(&self, ch: u32) -> Result<T, ChanError> {
+        let chan = self.get(ch)?;
+        let mut state = chan.lock();
+        match state.queue.pop_front() {
+            Some(value) => {
+                chan.writable.notify_one();
+                Ok(value)
+            }
+            None if state.closed => Err(ChanError::Closed),
+            None => Err(ChanError::Empty),
+        }
+    }
+
+    /// Closes `ch`, waking every sender and receiver waiting on it. Closing twice is allowed.
+    pub fn close(&self, ch: u32) -> Result<(), ChanError> {
+        let chan = self.get(ch)?;
+        chan.lock().closed = true;
+        chan.readable.notify_all();
+        chan.writable.notify_all();
+        Ok(())
+    }
+}
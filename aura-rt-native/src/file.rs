@@ -0,0 +1,146 @@
+//! Open files of `fs.*` calls.
+//!
+//! A file is opened for reading (`fs.open`) or writing (`fs.create` truncates, `fs.append`
+//! does not), read or written through its handle, and closed once. Handles are `u32`s like
+//! channels, handle 0 is never issued, and using a handle after `close` is an error. The
+//! compiler already treats a `File` as linear, so these checks only catch hosts that bypass it.
+
+use std::fmt;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// How a file is opened.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileMode {
+    Read,
+    Create,
+    Append,
+}
+
+/// Why a file operation did not go through.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FileError {
+    /// No `open` issued this handle.
+    Unknown(u32),
+    /// The file was closed.
+    Closed(u32),
+    /// Reading a file opened for writing, or the other way round.
+    WrongMode(PathBuf, FileMode),
+    /// The operating system refused.
+    Io(String),
+}
+
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileError::Unknown(h) => write!(f, "unknown file {h}"),
+            FileError::Closed(h) => write!(f, "file {h} was used after it was closed"),
+            FileError::WrongMode(p, FileMode::Read) => write!(f, "{} was opened for reading", p.display()),
+            FileError::WrongMode(p, _) => write!(f, "{} was opened for writing", p.display()),
+            FileError::Io(e) => f.write_str(e),
+        }
+    }
+}
+
+impl std::error::Error for FileError {}
+
+struct OpenFile {
+    path: PathBuf,
+    mode: FileMode,
+    // `None` once closed.
+    file: Option<fs::File>,
+}
+
+/// The files of one program run, shared by every worker running its flows.
+#[derive(Default)]
+pub struct FileTable {
+    files: Mutex<Vec<OpenFile>>,
+}
+
+impl fmt::Debug for FileTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = self.files.lock().unwrap_or_else(|e| e.into_inner()).len();
+        f.debug_struct("FileTable").field("files", &count).finish()
+    }
+}
+
+impl FileTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Opens `path` for `mode`; `Create` and `Append` make the file if it is missing.
+    pub fn open(&self, path: &Path, mode: FileMode) -> Result<u32, FileError> {
+        let mut opts = fs::OpenOptions::new();
+        match mode {
+            FileMode::Read => opts.read(true),
+            FileMode::Create => opts.write(true).create(true).truncate(true),
+            FileMode::Append => opts.append(true).create(true),
+        };
+        let file = opts
+            .open(path)
+            .map_err(|e| FileError::Io(format!("failed to open {}: {e}", path.display())))?;
+        let mut files = self.files.lock().unwrap_or_else(|e| e.into_inner());
+        files.push(OpenFile {
+            path: path.to_path_buf(),
+            mode,
+            file: Some(file),
+        });
+        Ok(u32::try_from(files.len()).expect("fewer than 2^32 files"))
+    }
+
+    fn with<R>(
+        &self,
+        h: u32,
+        f: impl FnOnce(&Path, FileMode, &mut fs::File) -> Result<R, FileError>,
+    ) -> Result<R, FileError> {
+        let mut files = self.files.lock().unwrap_or_else(|e| e.into_inner());
+        let open = (h as usize)
+            .checked_sub(1)
+            .and_then(|i| files.get_mut(i))
+            .ok_or(FileError::Unknown(h))?;
+        let file = open.file.as_mut().ok_or(FileError::Closed(h))?;
+        f(&open.path, open.mode, file)
+    }
+
+    /// The path `h` was opened with.
+    pub fn path(&self, h: u32) -> Result<PathBuf, FileError> {
+        self.with(h, |path, _, _| Ok(path.to_path_buf()))
+    }
+
+    /// The rest of a file opened for reading, as UTF-8.
+    pub fn read_to_string(&self, h: u32) -> Result<String, FileError> {
+        self.with(h, |path, mode, file| {
+            if mode != FileMode::Read {
+                return Err(FileError::WrongMode(path.to_path_buf(), mode));
+            }
+            let mut out = String::new();
+            file.read_to_string(&mut out)
+                .map_err(|e| FileError::Io(format!("failed to read {}: {e}", path.display())))?;
+            Ok(out)
+        })
+    }
+
+    /// Writes `text` to a file opened for writing.
+    pub fn write(&self, h: u32, text: &str) -> Result<(), FileError> {
+        self.with(h, |path, mode, file| {
+            if mode == FileMode::Read {
+                return Err(FileError::WrongMode(path.to_path_buf(), mode));
+            }
+            file.write_all(text.as_bytes())
+                .map_err(|e| FileError::Io(format!("failed to write {}: {e}", path.display())))
+        })
+    }
+
+    /// Closes `h`; every later use of the handle fails with `Closed`.
+    pub fn close(&self, h: u32) -> Result<(), FileError> {
+        let mut files = self.files.lock().unwrap_or_else(|e| e.into_inner());
+        let open = (h as usize)
+            .checked_sub(1)
+            .and_then(|i| files.get_mut(i))
+            .ok_or(FileError::Unknown(h))?;
+        open.file.take().map(drop).ok_or(FileError::Closed(h))
+    }
+}
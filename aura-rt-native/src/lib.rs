@@ -3,6 +3,17 @@
 use std::sync::mpsc;
 
 pub mod allocator;
+pub mod channel;
+pub mod file;
+pub mod scope;
+pub mod socket;
+pub mod timer;
+
+pub use channel::{ChanError, ChannelTable};
+pub use file::{FileError, FileMode, FileTable};
+pub use scope::{ScopeError, ScopeTable};
+pub use socket::{SocketError, SocketTable};
+pub use timer::Clock;
 
 /// Minimal native runtime facade for `~>`.
 ///
@@ -28,4 +39,13 @@ impl<T> JoinHandle<T> {
     pub fn join(self) -> T {
         self.rx.recv().expect("task panicked")
     }
+
+    /// The task's result if it has finished, without blocking.
+    pub fn try_join(&self) -> Option<T> {
+        match self.rx.try_recv() {
+            Ok(v) => Some(v),
+            Err(mpsc::TryRecvError::Empty) => None,
+            Err(mpsc::TryRecvError::Disconnected) => panic!("task panicked"),
+        }
+    }
 }
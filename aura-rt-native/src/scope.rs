@@ -0,0 +1,88 @@
+//! Cancellation tokens of `scope` blocks.
+//!
+//! A `scope` opens a token, its flows poll it, and any of them (or the scope's own body) can
+//! cancel it. Cancelling is cooperative: nothing is interrupted, `is_cancelled` just starts
+//! answering `true`. Tokens are addressed by `u32` handles like channels, handle 0 is never
+//! issued, and a token is closed when its scope ends, after which using it is an error.
+
+use std::fmt;
+use std::sync::Mutex;
+
+/// Why a token operation did not go through.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScopeError {
+    /// No scope issued this handle.
+    Unknown(u32),
+    /// The token's scope has ended.
+    Closed(u32),
+}
+
+impl fmt::Display for ScopeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScopeError::Unknown(tok) => write!(f, "unknown cancellation token {tok}"),
+            ScopeError::Closed(tok) => write!(f, "cancellation token {tok} was used after its scope ended"),
+        }
+    }
+}
+
+impl std::error::Error for ScopeError {}
+
+#[derive(Clone, Copy, Default)]
+struct Token {
+    cancelled: bool,
+    closed: bool,
+}
+
+/// The tokens of one program run, shared by every worker running its flows.
+#[derive(Default)]
+pub struct ScopeTable {
+    tokens: Mutex<Vec<Token>>,
+}
+
+impl fmt::Debug for ScopeTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = self.tokens.lock().unwrap_or_else(|e| e.into_inner()).len();
+        f.debug_struct("ScopeTable").field("tokens", &count).finish()
+    }
+}
+
+impl ScopeTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A fresh, uncancelled token for a scope that is starting.
+    pub fn open(&self) -> u32 {
+        let mut tokens = self.tokens.lock().unwrap_or_else(|e| e.into_inner());
+        tokens.push(Token::default());
+        u32::try_from(tokens.len()).expect("fewer than 2^32 scopes")
+    }
+
+    fn with<R>(&self, tok: u32, f: impl FnOnce(&mut Token) -> R) -> Result<R, ScopeError> {
+        let mut tokens = self.tokens.lock().unwrap_or_else(|e| e.into_inner());
+        let token = (tok as usize)
+            .checked_sub(1)
+            .and_then(|i| tokens.get_mut(i))
+            .ok_or(ScopeError::Unknown(tok))?;
+        if token.closed {
+            return Err(ScopeError::Closed(tok));
+        }
+        Ok(f(token))
+    }
+
+    /// Asks every flow polling `tok` to stop. Cancelling twice is allowed.
+    pub fn cancel(&self, tok: u32) -> Result<(), ScopeError> {
+        self.with(tok, |t| t.cancelled = true)
+    }
+
+    /// Whether `tok` was cancelled.
+    pub fn is_cancelled(&self, tok: u32) -> Result<bool, ScopeError> {
+        self.with(tok, |t| t.cancelled)
+    }
+
+    /// Ends `tok`'s scope; every later use of the token fails with `Closed`.
+    pub fn close(&self, tok: u32) -> Result<(), ScopeError> {
+        self.with(tok, |t| t.closed = true)
+    }
+}
@@ -0,0 +1,157 @@
+//! TCP connections of `net.*` calls.
+//!
+//! A connection is made with `connect`, written and read through its handle, and closed once.
+//! Handles are `u32`s like files, handle 0 is never issued, and using a handle after `close`
+//! is an error. Received bytes are handed out as UTF-8 text; a character split across two
+//! reads is held back until the rest of it arrives.
+
+use std::fmt;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::sync::{Arc, Mutex};
+
+/// Bytes one `recv` reads at most.
+const RECV_CHUNK: usize = 64 * 1024;
+
+/// Why a socket operation did not go through.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SocketError {
+    /// No `connect` issued this handle.
+    Unknown(u32),
+    /// The socket was closed.
+    Closed(u32),
+    /// The operating system refused, or the peer sent bytes that are not UTF-8.
+    Io(String),
+}
+
+impl fmt::Display for SocketError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SocketError::Unknown(h) => write!(f, "unknown socket {h}"),
+            SocketError::Closed(h) => write!(f, "socket {h} was used after it was closed"),
+            SocketError::Io(e) => f.write_str(e),
+        }
+    }
+}
+
+impl std::error::Error for SocketError {}
+
+struct OpenSocket {
+    peer: String,
+    // `None` once closed.
+    stream: Option<TcpStream>,
+    // The start of a UTF-8 character whose other bytes have not arrived yet.
+    pending: Vec<u8>,
+}
+
+/// The connections of one program run, shared by every worker running its flows. Each
+/// socket has its own lock, so a flow waiting in `recv` does not hold up the others.
+#[derive(Default)]
+pub struct SocketTable {
+    sockets: Mutex<Vec<Arc<Mutex<OpenSocket>>>>,
+}
+
+impl fmt::Debug for SocketTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = self.sockets.lock().unwrap_or_else(|e| e.into_inner()).len();
+        f.debug_struct("SocketTable").field("sockets", &count).finish()
+    }
+}
+
+impl SocketTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Connects to `host:port`.
+    pub fn connect(&self, host: &str, port: u16) -> Result<u32, SocketError> {
+        let peer = format!("{host}:{port}");
+        let stream = TcpStream::connect((host, port))
+            .map_err(|e| SocketError::Io(format!("failed to connect to {peer}: {e}")))?;
+        let mut sockets = self.sockets.lock().unwrap_or_else(|e| e.into_inner());
+        sockets.push(Arc::new(Mutex::new(OpenSocket {
+            peer,
+            stream: Some(stream),
+            pending: Vec::new(),
+        })));
+        Ok(u32::try_from(sockets.len()).expect("fewer than 2^32 sockets"))
+    }
+
+    fn with<R>(&self, h: u32, f: impl FnOnce(&mut OpenSocket) -> Result<R, SocketError>) -> Result<R, SocketError> {
+        let socket = {
+            let sockets = self.sockets.lock().unwrap_or_else(|e| e.into_inner());
+            let i = (h as usize).checked_sub(1).ok_or(SocketError::Unknown(h))?;
+            Arc::clone(sockets.get(i).ok_or(SocketError::Unknown(h))?)
+        };
+        let mut open = socket.lock().unwrap_or_else(|e| e.into_inner());
+        if open.stream.is_none() {
+            return Err(SocketError::Closed(h));
+        }
+        f(&mut open)
+    }
+
+    /// The `host:port` `h` was connected to.
+    pub fn peer(&self, h: u32) -> Result<String, SocketError> {
+        self.with(h, |open| Ok(open.peer.clone()))
+    }
+
+    /// Sends all of `data`.
+    pub fn send(&self, h: u32, data: &str) -> Result<(), SocketError> {
+        self.with(h, |open| {
+            let stream = open.stream.as_mut().expect("checked open");
+            stream
+                .write_all(data.as_bytes())
+                .map_err(|e| SocketError::Io(format!("failed to send to {}: {e}", open.peer)))
+        })
+    }
+
+    /// Waits for the next bytes from the peer; empty once the peer has closed its side.
+    pub fn recv(&self, h: u32) -> Result<String, SocketError> {
+        self.with(h, |open| {
+            let stream = open.stream.as_mut().expect("checked open");
+            let mut buf = std::mem::take(&mut open.pending);
+            loop {
+                let start = buf.len();
+                buf.resize(start + RECV_CHUNK, 0);
+                let n = stream
+                    .read(&mut buf[start..])
+                    .map_err(|e| SocketError::Io(format!("failed to receive from {}: {e}", open.peer)))?;
+                buf.truncate(start + n);
+                let not_utf8 = || SocketError::Io(format!("{} sent bytes that are not UTF-8", open.peer));
+                let valid = match std::str::from_utf8(&buf) {
+                    Ok(_) => buf.len(),
+                    Err(e) if e.error_len().is_some() => return Err(not_utf8()),
+                    Err(_) if n == 0 => return Err(not_utf8()),
+                    Err(e) => e.valid_up_to(),
+                };
+                // Only a partial character arrived: wait for the rest of it.
+                if valid == 0 && n > 0 {
+                    continue;
+                }
+                open.pending = buf.split_off(valid);
+                return Ok(String::from_utf8(buf).expect("validated above"));
+            }
+        })
+    }
+
+    /// Everything the peer sends until it closes its side.
+    pub fn recv_all(&self, h: u32) -> Result<String, SocketError> {
+        self.with(h, |open| {
+            let stream = open.stream.as_mut().expect("checked open");
+            let mut buf = std::mem::take(&mut open.pending);
+            stream
+                .read_to_end(&mut buf)
+                .map_err(|e| SocketError::Io(format!("failed to receive from {}: {e}", open.peer)))?;
+            String::from_utf8(buf).map_err(|_| SocketError::Io(format!("{} sent bytes that are not UTF-8", open.peer)))
+        })
+    }
+
+    /// Closes `h`; every later use of the handle fails with `Closed`.
+    pub fn close(&self, h: u32) -> Result<(), SocketError> {
+        self.with(h, |open| {
+            open.stream.take();
+            open.pending.clear();
+            Ok(())
+        })
+    }
+}
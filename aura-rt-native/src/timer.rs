@@ -0,0 +1,55 @@
+//! Monotonic time and sleeping for `time.*` calls.
+//!
+//! A `Clock` counts milliseconds from when it was made, as compiled programs count them
+//! from start-up through `aura_time_now_ms` in `aura_rt.h`. Every worker of a run shares one
+//! clock, so readings taken in different flows can be compared. Deadlines are clock readings:
+//! `deadline_after` turns a delay into one and `sleep_until` blocks the calling thread until
+//! it has passed.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The monotonic clock of one program run.
+#[derive(Clone, Copy, Debug)]
+pub struct Clock {
+    origin: Instant,
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self { origin: Instant::now() }
+    }
+}
+
+impl Clock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Milliseconds since the clock was made. Never decreases.
+    pub fn now_ms(&self) -> u64 {
+        u64::try_from(self.origin.elapsed().as_millis()).unwrap_or(u64::MAX)
+    }
+
+    /// The reading `ms` milliseconds from now.
+    pub fn deadline_after(&self, ms: u64) -> u64 {
+        self.now_ms().saturating_add(ms)
+    }
+
+    /// How long until the clock reads `deadline`; zero once it has.
+    pub fn remaining(&self, deadline: u64) -> Duration {
+        let at = self.origin + Duration::from_millis(deadline);
+        at.saturating_duration_since(Instant::now())
+    }
+
+    /// Blocks the calling thread until the clock reads `deadline`.
+    pub fn sleep_until(&self, deadline: u64) {
+        loop {
+            let left = self.remaining(deadline);
+            if left.is_zero() {
+                return;
+            }
+            thread::sleep(left);
+        }
+    }
+}
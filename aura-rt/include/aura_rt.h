@@ -3,13 +3,90 @@
 // Aura runtime header (C23 ABI).
 // This is intentionally minimal for the Stage 5 pipeline.
 
+#include <stdbool.h>
+#include <stdint.h>
+
 #ifdef __cplusplus
 extern "C" {
 #endif
 
-// Entry point emitted by the compiler into module.ll
+// Entry point emitted by the compiler (its `aura_entry` unit)
 int aura_entry(void);
 
+// ---- `~>` task scheduler ----
+// Compiled code starts `x ~> f(...)` by filling a task frame on its stack and spawning
+// it; the frame begins with this header, followed by the arguments and the result slot.
+// The frame must stay alive until `aura_task_join` returns.
+enum {
+    AURA_TASK_QUEUED = 0,
+    AURA_TASK_RUNNING = 1,
+    AURA_TASK_DONE = 2,
+};
+
+typedef struct AuraTask {
+    uint32_t state;
+    void (*entry)(struct AuraTask* task);
+    struct AuraTask* next;
+} AuraTask;
+
+// Worker threads default to 4; `AURA_WORKERS=<n>` overrides it (0 runs tasks on join).
+void aura_sched_init(void);
+void aura_sched_shutdown(void);
+
+void aura_task_spawn(AuraTask* task, void (*entry)(AuraTask* task));
+void aura_task_join(AuraTask* task);
+
+// ---- channels ----
+// Multi-producer, single-consumer channels between `~>` flows, addressed by handle (0 is
+// never a channel) and carrying 32-bit slots. A bounded channel holds at most `capacity`
+// values (at least 1) and `aura_chan_send` waits for room; an unbounded one never makes a
+// sender wait. `aura_chan_recv` waits for the oldest value. Closing a channel stops further
+// sends; receiving from it once it is drained aborts, as does sending to it or using an
+// unknown handle. Without worker threads (`AURA_WORKERS=0`) no task could run while a call
+// waits, so a send to a full or a receive from an empty channel aborts instead.
+uint32_t aura_chan_bounded(uint32_t capacity);
+uint32_t aura_chan_unbounded(void);
+void aura_chan_send(uint32_t ch, uint32_t value);
+uint32_t aura_chan_recv(uint32_t ch);
+void aura_chan_close(uint32_t ch);
+uint32_t aura_chan_len(uint32_t ch);
+
+// ---- scopes ----
+// Cancellation tokens of `scope` blocks, addressed by handle (0 is never a token). Compiled
+// code joins a scope's tasks before it calls `aura_scope_close`; cancelling, checking or
+// closing a token after it is closed aborts, as does using an unknown handle.
+uint32_t aura_scope_open(void);
+void aura_scope_cancel(uint32_t scope);
+bool aura_scope_cancelled(uint32_t scope);
+void aura_scope_close(uint32_t scope);
+
+// ---- timers ----
+// `aura_time_now_ms` reads a monotonic clock in milliseconds, counted from
+// `aura_time_init` (which the runtime's `main` calls) and wrapping at 2^32.
+// `aura_time_sleep_ms` returns once at least `ms` milliseconds have passed. While it waits,
+// the calling thread runs queued tasks, so a sleeping cell never holds up the flows it
+// started, and it never spins.
+void aura_time_init(void);
+uint32_t aura_time_now_ms(void);
+void aura_time_sleep_ms(uint32_t ms);
+// `aura_time_unix_s` reads the wall clock: whole seconds since 1970-01-01T00:00:00Z, which
+// fit a u32 until 2106. `aura_time_add_ms` adds a duration and traps (aborts) when the sum
+// does not fit a u32; `aura_time_elapsed_ms` is how far `end` is past `start` on the
+// wrapping monotonic clock, correct across one wrap.
+uint32_t aura_time_unix_s(void);
+uint32_t aura_time_add_ms(uint32_t t, uint32_t ms);
+uint32_t aura_time_elapsed_ms(uint32_t start, uint32_t end);
+
+// ---- stack overflow protection ----
+// Recursive cells compare their frame address with `aura_stack_limit`, the lowest address
+// the current thread's stack may reach while leaving room to report the overflow. It is 0,
+// disabling the check, until `aura_stack_init` ran on the thread or when the platform does
+// not expose stack bounds. `main` and the scheduler's workers call `aura_stack_init`.
+extern _Thread_local uintptr_t aura_stack_limit;
+void aura_stack_init(void);
+// Reports that `cell` recursed past `aura_stack_limit` and aborts.
+void aura_stack_overflow(const char* cell);
+
 #ifdef __cplusplus
 }
 #endif
@@ -1,7 +1,658 @@
+#if defined(__linux__)
+#define _GNU_SOURCE // pthread_getattr_np
+#endif
+
 #include "aura_rt.h"
 
+#include <stdbool.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <time.h>
+
+#if defined(__linux__) || defined(__APPLE__)
+#include <pthread.h>
+#endif
+
 // Stage 5.2 (prototype): runtime-provided C main.
-// Later phases will initialize the async scheduler and capability handlers here.
+// Later phases will initialize capability handlers here.
 int main(void) {
-    return aura_entry();
+    aura_time_init();
+    aura_stack_init();
+    aura_sched_init();
+    int rc = aura_entry();
+    aura_sched_shutdown();
+    return rc;
+}
+
+// ---- sanitizer defaults (`aura build --sanitize address`) ----
+// Strings and tensors live until exit by design, so leak reports would only be noise;
+// AddressSanitizer still reports every invalid access.
+#if defined(__SANITIZE_ADDRESS__)
+#define AURA_ASAN 1
+#elif defined(__has_feature)
+#if __has_feature(address_sanitizer)
+#define AURA_ASAN 1
+#endif
+#endif
+
+#if defined(AURA_ASAN)
+const char* __asan_default_options(void);
+const char* __asan_default_options(void) {
+    return "detect_leaks=0";
+}
+#endif
+
+// ---- stack overflow protection ----
+
+// Stack kept free below the limit for the overflow report (and the C calls it makes).
+#define AURA_STACK_RESERVE ((uintptr_t)64 * 1024)
+
+#if defined(_WIN32)
+__declspec(dllimport) void __stdcall GetCurrentThreadStackLimits(uintptr_t* low, uintptr_t* high);
+#endif
+
+_Thread_local uintptr_t aura_stack_limit = 0;
+
+void aura_stack_init(void) {
+    uintptr_t low = 0;
+#if defined(_WIN32)
+    uintptr_t high = 0;
+    GetCurrentThreadStackLimits(&low, &high);
+#elif defined(__APPLE__)
+    pthread_t self = pthread_self();
+    low = (uintptr_t)pthread_get_stackaddr_np(self) - (uintptr_t)pthread_get_stacksize_np(self);
+#elif defined(__linux__)
+    pthread_attr_t attr;
+    if (pthread_getattr_np(pthread_self(), &attr) == 0) {
+        void* addr = NULL;
+        size_t size = 0;
+        if (pthread_attr_getstack(&attr, &addr, &size) == 0) {
+            low = (uintptr_t)addr;
+        }
+        pthread_attr_destroy(&attr);
+    }
+#endif
+    aura_stack_limit = low != 0 ? low + AURA_STACK_RESERVE : 0;
+}
+
+void aura_stack_overflow(const char* cell) {
+    // Output the program already printed should survive the abort.
+    fflush(stdout);
+    fprintf(stderr, "Aura stack overflow: cell '%s' recursed too deeply\n", cell);
+    abort();
+}
+
+// ---- `~>` task scheduler ----
+// A fixed pool of workers runs queued tasks in FIFO order. A caller joining a task that
+// has not finished runs queued tasks itself meanwhile, so nested flows cannot starve the
+// pool, and a pool of zero workers runs everything on join.
+
+#if defined(__STDC_NO_THREADS__)
+
+void aura_sched_init(void) {}
+
+void aura_sched_shutdown(void) {}
+
+void aura_task_spawn(AuraTask* task, void (*entry)(AuraTask* task)) {
+    task->entry = entry;
+    task->next = NULL;
+    task->state = AURA_TASK_RUNNING;
+    entry(task);
+    task->state = AURA_TASK_DONE;
+}
+
+void aura_task_join(AuraTask* task) {
+    (void)task;
+}
+
+static bool aura_sched_has_workers(void) {
+    return false;
+}
+
+#else
+
+#include <threads.h>
+
+#define AURA_DEFAULT_WORKERS 4
+#define AURA_MAX_WORKERS 64
+
+static mtx_t g_sched_lock;
+static cnd_t g_sched_queued; // A task was queued, or the scheduler is shutting down.
+static cnd_t g_sched_done;   // A task finished.
+static AuraTask* g_queue_head = NULL;
+static AuraTask* g_queue_tail = NULL;
+static thrd_t g_workers[AURA_MAX_WORKERS];
+static int g_worker_count = 0;
+static bool g_sched_ready = false;
+static bool g_sched_stopping = false;
+
+// Takes the oldest queued task. Called with the lock held.
+static AuraTask* aura_sched_pop(void) {
+    AuraTask* task = g_queue_head;
+    if (task != NULL) {
+        g_queue_head = task->next;
+        if (g_queue_head == NULL) {
+            g_queue_tail = NULL;
+        }
+        task->next = NULL;
+        task->state = AURA_TASK_RUNNING;
+    }
+    return task;
+}
+
+// Runs `task` outside the lock. Called, and returns, with the lock held.
+static void aura_sched_run(AuraTask* task) {
+    mtx_unlock(&g_sched_lock);
+    task->entry(task);
+    mtx_lock(&g_sched_lock);
+    task->state = AURA_TASK_DONE;
+    cnd_broadcast(&g_sched_done);
+}
+
+static int aura_sched_worker(void* arg) {
+    (void)arg;
+    aura_stack_init();
+    mtx_lock(&g_sched_lock);
+    for (;;) {
+        AuraTask* task = aura_sched_pop();
+        if (task != NULL) {
+            aura_sched_run(task);
+        } else if (g_sched_stopping) {
+            break;
+        } else {
+            cnd_wait(&g_sched_queued, &g_sched_lock);
+        }
+    }
+    mtx_unlock(&g_sched_lock);
+    return 0;
+}
+
+void aura_sched_init(void) {
+    if (g_sched_ready) {
+        return;
+    }
+    int workers = AURA_DEFAULT_WORKERS;
+    const char* env = getenv("AURA_WORKERS");
+    if (env != NULL && *env != '\0') {
+        workers = atoi(env);
+    }
+    if (workers < 0) {
+        workers = 0;
+    }
+    if (workers > AURA_MAX_WORKERS) {
+        workers = AURA_MAX_WORKERS;
+    }
+
+    mtx_init(&g_sched_lock, mtx_plain);
+    cnd_init(&g_sched_queued);
+    cnd_init(&g_sched_done);
+    g_sched_ready = true;
+    for (int i = 0; i < workers; i++) {
+        if (thrd_create(&g_workers[g_worker_count], aura_sched_worker, NULL) == thrd_success) {
+            g_worker_count++;
+        }
+    }
+}
+
+void aura_sched_shutdown(void) {
+    if (!g_sched_ready) {
+        return;
+    }
+    mtx_lock(&g_sched_lock);
+    g_sched_stopping = true;
+    cnd_broadcast(&g_sched_queued);
+    mtx_unlock(&g_sched_lock);
+    for (int i = 0; i < g_worker_count; i++) {
+        thrd_join(g_workers[i], NULL);
+    }
+    g_worker_count = 0;
+    cnd_destroy(&g_sched_done);
+    cnd_destroy(&g_sched_queued);
+    mtx_destroy(&g_sched_lock);
+    g_sched_ready = false;
+    g_sched_stopping = false;
+}
+
+void aura_task_spawn(AuraTask* task, void (*entry)(AuraTask* task)) {
+    task->entry = entry;
+    task->next = NULL;
+    if (!g_sched_ready) {
+        // No scheduler (e.g. the module is embedded without this `main`): run inline.
+        task->state = AURA_TASK_RUNNING;
+        entry(task);
+        task->state = AURA_TASK_DONE;
+        return;
+    }
+    mtx_lock(&g_sched_lock);
+    task->state = AURA_TASK_QUEUED;
+    if (g_queue_tail != NULL) {
+        g_queue_tail->next = task;
+    } else {
+        g_queue_head = task;
+    }
+    g_queue_tail = task;
+    cnd_signal(&g_sched_queued);
+    mtx_unlock(&g_sched_lock);
+}
+
+// Whether worker threads run queued tasks; without them tasks only run when joined.
+static bool aura_sched_has_workers(void) {
+    return g_sched_ready && g_worker_count > 0;
+}
+
+void aura_task_join(AuraTask* task) {
+    if (!g_sched_ready) {
+        return;
+    }
+    mtx_lock(&g_sched_lock);
+    while (task->state != AURA_TASK_DONE) {
+        AuraTask* queued = aura_sched_pop();
+        if (queued != NULL) {
+            aura_sched_run(queued);
+        } else {
+            cnd_wait(&g_sched_done, &g_sched_lock);
+        }
+    }
+    mtx_unlock(&g_sched_lock);
+}
+
+#endif
+
+// ---- channels ----
+// Each channel is a ring buffer of slots, which unbounded channels grow. Channels live until
+// exit, like strings and tensors; handles are 1 + their index in the channel table.
+
+typedef struct AuraChan {
+    uint32_t* slots;
+    uint32_t slot_count;
+    uint32_t head; // Slot of the oldest value.
+    uint32_t len;
+    uint32_t capacity; // 0 for unbounded channels.
+    bool closed;
+#if !defined(__STDC_NO_THREADS__)
+    mtx_t lock;
+    cnd_t readable; // A value arrived, or the channel closed.
+    cnd_t writable; // A value was taken, or the channel closed.
+#endif
+} AuraChan;
+
+#define AURA_CHAN_INITIAL_SLOTS 16
+
+static AuraChan** g_chans = NULL;
+static uint32_t g_chan_count = 0;
+static uint32_t g_chan_table_size = 0;
+
+#if !defined(__STDC_NO_THREADS__)
+static mtx_t g_chan_table_lock;
+static once_flag g_chan_table_once = ONCE_FLAG_INIT;
+
+static void aura_chan_table_init(void) {
+    mtx_init(&g_chan_table_lock, mtx_plain);
+}
+#endif
+
+static void aura_chan_table_lock(void) {
+#if !defined(__STDC_NO_THREADS__)
+    call_once(&g_chan_table_once, aura_chan_table_init);
+    mtx_lock(&g_chan_table_lock);
+#endif
+}
+
+static void aura_chan_table_unlock(void) {
+#if !defined(__STDC_NO_THREADS__)
+    mtx_unlock(&g_chan_table_lock);
+#endif
+}
+
+static void aura_chan_lock(AuraChan* chan) {
+#if !defined(__STDC_NO_THREADS__)
+    mtx_lock(&chan->lock);
+#else
+    (void)chan;
+#endif
+}
+
+static void aura_chan_unlock(AuraChan* chan) {
+#if !defined(__STDC_NO_THREADS__)
+    mtx_unlock(&chan->lock);
+#else
+    (void)chan;
+#endif
+}
+
+_Noreturn static void aura_chan_fail(uint32_t ch, const char* what) {
+    // Output the program already printed should survive the abort.
+    fflush(stdout);
+    fprintf(stderr, "Aura channel %u: %s\n", (unsigned)ch, what);
+    abort();
+}
+
+static uint32_t aura_chan_new(uint32_t capacity) {
+    uint32_t slot_count = capacity != 0 ? capacity : AURA_CHAN_INITIAL_SLOTS;
+    AuraChan* chan = calloc(1, sizeof(AuraChan));
+    uint32_t* slots = malloc(sizeof(uint32_t) * (size_t)slot_count);
+    if (chan == NULL || slots == NULL) {
+        aura_chan_fail(0, "out of memory creating a channel");
+    }
+    chan->slots = slots;
+    chan->slot_count = slot_count;
+    chan->capacity = capacity;
+#if !defined(__STDC_NO_THREADS__)
+    mtx_init(&chan->lock, mtx_plain);
+    cnd_init(&chan->readable);
+    cnd_init(&chan->writable);
+#endif
+
+    aura_chan_table_lock();
+    if (g_chan_count == g_chan_table_size) {
+        uint32_t size = g_chan_table_size != 0 ? g_chan_table_size * 2 : 16;
+        AuraChan** table = realloc(g_chans, sizeof(AuraChan*) * (size_t)size);
+        if (table == NULL || size <= g_chan_table_size) {
+            aura_chan_fail(0, "out of memory creating a channel");
+        }
+        g_chans = table;
+        g_chan_table_size = size;
+    }
+    g_chans[g_chan_count++] = chan;
+    uint32_t handle = g_chan_count;
+    aura_chan_table_unlock();
+    return handle;
+}
+
+static AuraChan* aura_chan_get(uint32_t ch) {
+    aura_chan_table_lock();
+    AuraChan* chan = ch != 0 && ch <= g_chan_count ? g_chans[ch - 1] : NULL;
+    aura_chan_table_unlock();
+    if (chan == NULL) {
+        aura_chan_fail(ch, "unknown channel");
+    }
+    return chan;
+}
+
+// Makes room for one more value in an unbounded channel, keeping the values in order.
+static void aura_chan_grow(AuraChan* chan, uint32_t ch) {
+    if (chan->slot_count > UINT32_MAX / 2) {
+        aura_chan_fail(ch, "too many values waiting in an unbounded channel");
+    }
+    uint32_t slot_count = chan->slot_count * 2;
+    uint32_t* slots = malloc(sizeof(uint32_t) * (size_t)slot_count);
+    if (slots == NULL) {
+        aura_chan_fail(ch, "out of memory growing an unbounded channel");
+    }
+    for (uint32_t i = 0; i < chan->len; i++) {
+        slots[i] = chan->slots[(chan->head + i) % chan->slot_count];
+    }
+    free(chan->slots);
+    chan->slots = slots;
+    chan->slot_count = slot_count;
+    chan->head = 0;
+}
+
+uint32_t aura_chan_bounded(uint32_t capacity) {
+    if (capacity == 0) {
+        aura_chan_fail(0, "a bounded channel needs a capacity of at least 1");
+    }
+    return aura_chan_new(capacity);
+}
+
+uint32_t aura_chan_unbounded(void) {
+    return aura_chan_new(0);
+}
+
+void aura_chan_send(uint32_t ch, uint32_t value) {
+    AuraChan* chan = aura_chan_get(ch);
+    aura_chan_lock(chan);
+    while (!chan->closed && chan->capacity != 0 && chan->len == chan->capacity) {
+        // No other thread could run the task this would wait for.
+        if (!aura_sched_has_workers()) {
+            aura_chan_fail(ch, "send to a full channel would wait forever");
+        }
+#if !defined(__STDC_NO_THREADS__)
+        cnd_wait(&chan->writable, &chan->lock);
+#endif
+    }
+    if (chan->closed) {
+        aura_chan_fail(ch, "send to a closed channel");
+    }
+    if (chan->len == chan->slot_count) {
+        aura_chan_grow(chan, ch);
+    }
+    chan->slots[(chan->head + chan->len) % chan->slot_count] = value;
+    chan->len++;
+#if !defined(__STDC_NO_THREADS__)
+    cnd_signal(&chan->readable);
+#endif
+    aura_chan_unlock(chan);
+}
+
+uint32_t aura_chan_recv(uint32_t ch) {
+    AuraChan* chan = aura_chan_get(ch);
+    aura_chan_lock(chan);
+    while (!chan->closed && chan->len == 0) {
+        // No other thread could run the task this would wait for.
+        if (!aura_sched_has_workers()) {
+            aura_chan_fail(ch, "recv from an empty channel would wait forever");
+        }
+#if !defined(__STDC_NO_THREADS__)
+        cnd_wait(&chan->readable, &chan->lock);
+#endif
+    }
+    if (chan->len == 0) {
+        aura_chan_fail(ch, "recv from a closed channel");
+    }
+    uint32_t value = chan->slots[chan->head];
+    chan->head = (chan->head + 1) % chan->slot_count;
+    chan->len--;
+#if !defined(__STDC_NO_THREADS__)
+    cnd_signal(&chan->writable);
+#endif
+    aura_chan_unlock(chan);
+    return value;
+}
+
+void aura_chan_close(uint32_t ch) {
+    AuraChan* chan = aura_chan_get(ch);
+    aura_chan_lock(chan);
+    chan->closed = true;
+#if !defined(__STDC_NO_THREADS__)
+    cnd_broadcast(&chan->readable);
+    cnd_broadcast(&chan->writable);
+#endif
+    aura_chan_unlock(chan);
+}
+
+uint32_t aura_chan_len(uint32_t ch) {
+    AuraChan* chan = aura_chan_get(ch);
+    aura_chan_lock(chan);
+    uint32_t len = chan->len;
+    aura_chan_unlock(chan);
+    return len;
+}
+
+// ---- scopes ----
+// A token is a byte of flags in the scope table, read and written under the table's lock.
+// Tokens live until exit; handles are 1 + their index in the table.
+
+#define AURA_SCOPE_CANCELLED 1u
+#define AURA_SCOPE_CLOSED 2u
+
+static uint8_t* g_scopes = NULL;
+static uint32_t g_scope_count = 0;
+static uint32_t g_scope_table_size = 0;
+
+#if !defined(__STDC_NO_THREADS__)
+static mtx_t g_scope_table_lock;
+static once_flag g_scope_table_once = ONCE_FLAG_INIT;
+
+static void aura_scope_table_init(void) {
+    mtx_init(&g_scope_table_lock, mtx_plain);
+}
+#endif
+
+static void aura_scope_table_lock(void) {
+#if !defined(__STDC_NO_THREADS__)
+    call_once(&g_scope_table_once, aura_scope_table_init);
+    mtx_lock(&g_scope_table_lock);
+#endif
+}
+
+static void aura_scope_table_unlock(void) {
+#if !defined(__STDC_NO_THREADS__)
+    mtx_unlock(&g_scope_table_lock);
+#endif
+}
+
+_Noreturn static void aura_scope_fail(uint32_t scope, const char* what) {
+    fflush(stdout);
+    fprintf(stderr, "Aura scope %u: %s\n", (unsigned)scope, what);
+    abort();
+}
+
+// The flags of open token `scope`; the caller holds the table lock.
+static uint8_t* aura_scope_flags(uint32_t scope) {
+    if (scope == 0 || scope > g_scope_count) {
+        aura_scope_fail(scope, "unknown scope");
+    }
+    uint8_t* flags = &g_scopes[scope - 1];
+    if (*flags & AURA_SCOPE_CLOSED) {
+        aura_scope_fail(scope, "scope is closed");
+    }
+    return flags;
+}
+
+uint32_t aura_scope_open(void) {
+    aura_scope_table_lock();
+    if (g_scope_count == g_scope_table_size) {
+        uint32_t size = g_scope_table_size != 0 ? g_scope_table_size * 2 : 16;
+        uint8_t* table = realloc(g_scopes, (size_t)size);
+        if (table == NULL || size <= g_scope_table_size) {
+            aura_scope_fail(0, "out of memory opening a scope");
+        }
+        g_scopes = table;
+        g_scope_table_size = size;
+    }
+    g_scopes[g_scope_count++] = 0;
+    uint32_t handle = g_scope_count;
+    aura_scope_table_unlock();
+    return handle;
+}
+
+void aura_scope_cancel(uint32_t scope) {
+    aura_scope_table_lock();
+    *aura_scope_flags(scope) |= AURA_SCOPE_CANCELLED;
+    aura_scope_table_unlock();
+}
+
+bool aura_scope_cancelled(uint32_t scope) {
+    aura_scope_table_lock();
+    bool cancelled = (*aura_scope_flags(scope) & AURA_SCOPE_CANCELLED) != 0;
+    aura_scope_table_unlock();
+    return cancelled;
+}
+
+void aura_scope_close(uint32_t scope) {
+    aura_scope_table_lock();
+    *aura_scope_flags(scope) |= AURA_SCOPE_CLOSED;
+    aura_scope_table_unlock();
+}
+
+// ---- timers ----
+// Readings come from the monotonic clock where the platform has one. `cnd_timedwait` takes
+// a wall-clock deadline, so a sleep converts what is left of its wait on every round.
+
+static uint64_t g_time_origin_ms = 0;
+
+static uint64_t aura_time_monotonic_ms(void) {
+    struct timespec ts;
+#if defined(TIME_MONOTONIC)
+    timespec_get(&ts, TIME_MONOTONIC);
+#elif defined(CLOCK_MONOTONIC)
+    clock_gettime(CLOCK_MONOTONIC, &ts);
+#else
+    timespec_get(&ts, TIME_UTC);
+#endif
+    return (uint64_t)ts.tv_sec * 1000u + (uint64_t)ts.tv_nsec / 1000000u;
+}
+
+// `ts` plus `ms` milliseconds.
+static struct timespec aura_timespec_add_ms(struct timespec ts, uint64_t ms) {
+    ts.tv_sec += (time_t)(ms / 1000u);
+    ts.tv_nsec += (long)(ms % 1000u) * 1000000L;
+    if (ts.tv_nsec >= 1000000000L) {
+        ts.tv_sec += 1;
+        ts.tv_nsec -= 1000000000L;
+    }
+    return ts;
+}
+
+void aura_time_init(void) {
+    g_time_origin_ms = aura_time_monotonic_ms();
+}
+
+uint32_t aura_time_now_ms(void) {
+    return (uint32_t)(aura_time_monotonic_ms() - g_time_origin_ms);
+}
+
+uint32_t aura_time_unix_s(void) {
+    struct timespec ts;
+    timespec_get(&ts, TIME_UTC);
+    return (uint32_t)ts.tv_sec;
+}
+
+uint32_t aura_time_add_ms(uint32_t t, uint32_t ms) {
+    if (ms > UINT32_MAX - t) {
+        fprintf(stderr, "Aura time.add_ms: %u + %u ms does not fit a u32\n", (unsigned)t, (unsigned)ms);
+        fflush(stderr);
+        abort();
+    }
+    return t + ms;
+}
+
+uint32_t aura_time_elapsed_ms(uint32_t start, uint32_t end) {
+    // Unsigned subtraction wraps exactly like the clock does.
+    return end - start;
+}
+
+#if defined(_WIN32)
+__declspec(dllimport) void __stdcall Sleep(unsigned long ms);
+#endif
+
+// Blocks the calling thread until the monotonic clock reads `deadline`.
+static void aura_time_wait_until(uint64_t deadline) {
+    for (uint64_t now = aura_time_monotonic_ms(); now < deadline; now = aura_time_monotonic_ms()) {
+#if !defined(__STDC_NO_THREADS__)
+        struct timespec left = aura_timespec_add_ms((struct timespec){0}, deadline - now);
+        thrd_sleep(&left, NULL);
+#elif defined(_WIN32)
+        Sleep((unsigned long)(deadline - now));
+#elif defined(__linux__) || defined(__APPLE__)
+        struct timespec left = aura_timespec_add_ms((struct timespec){0}, deadline - now);
+        nanosleep(&left, NULL);
+#endif
+        // Elsewhere, without threads, there is nothing to block on: the loop polls the clock.
+    }
+}
+
+void aura_time_sleep_ms(uint32_t ms) {
+    uint64_t deadline = aura_time_monotonic_ms() + ms;
+#if !defined(__STDC_NO_THREADS__)
+    if (g_sched_ready) {
+        mtx_lock(&g_sched_lock);
+        for (uint64_t now = aura_time_monotonic_ms(); now < deadline; now = aura_time_monotonic_ms()) {
+            AuraTask* queued = aura_sched_pop();
+            if (queued != NULL) {
+                aura_sched_run(queued);
+                continue;
+            }
+            // Wake for the deadline, or earlier to run a task queued meanwhile.
+            struct timespec until;
+            timespec_get(&until, TIME_UTC);
+            until = aura_timespec_add_ms(until, deadline - now);
+            cnd_timedwait(&g_sched_queued, &g_sched_lock, &until);
+        }
+        mtx_unlock(&g_sched_lock);
+        return;
+    }
+#endif
+    aura_time_wait_until(deadline);
 }
@@ -0,0 +1,143 @@
+//! `[fs]` in aura.toml: the paths `fs.*` calls may open.
+//!
+//! ```toml
+//! [fs]
+//! read = ["data"]
+//! write = ["out", "logs/app.log"]
+//! ```
+//!
+//! Entries are files or directories, relative to the manifest's directory; a directory covers
+//! everything below it. `fs.open` needs a `read` entry, `fs.create` and `fs.append` a `write`
+//! entry. A project without an `[fs]` table may open any path. Paths are compared lexically
+//! once `.` and `..` are resolved, so symlinks are not followed.
+
+use std::path::{Component, Path, PathBuf};
+
+/// What a file is opened for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FsAccess {
+    Read,
+    Write,
+}
+
+impl FsAccess {
+    pub fn display(self) -> &'static str {
+        match self {
+            FsAccess::Read => "reading",
+            FsAccess::Write => "writing",
+        }
+    }
+}
+
+/// The `[fs]` table, with its entries made absolute.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FsPolicy {
+    pub read: Vec<PathBuf>,
+    pub write: Vec<PathBuf>,
+}
+
+impl FsPolicy {
+    /// The entries that grant `access`.
+    pub fn roots(&self, access: FsAccess) -> &[PathBuf] {
+        match access {
+            FsAccess::Read => &self.read,
+            FsAccess::Write => &self.write,
+        }
+    }
+
+    /// Whether `path`, relative to the working directory, may be opened for `access`.
+    pub fn allows(&self, path: &Path, access: FsAccess) -> bool {
+        let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        let path = normalize_path(&cwd.join(path));
+        self.roots(access).iter().any(|root| path.starts_with(root))
+    }
+}
+
+/// Drops `.` components and resolves `..` against the components before it, without
+/// touching the file system.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for c in path.components() {
+        match c {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Reads `[fs]` from the nearest aura.toml at or above `start`; `None` when there is no
+/// manifest or it has no `[fs]` table.
+pub fn load_fs_policy(start: &Path) -> miette::Result<Option<FsPolicy>> {
+    let mut cur = if start.is_file() {
+        start.parent().unwrap_or_else(|| Path::new(".")).to_path_buf()
+    } else {
+        start.to_path_buf()
+    };
+    if cur.as_os_str().is_empty() {
+        cur = PathBuf::from(".");
+    }
+    let manifest = loop {
+        let candidate = cur.join("aura.toml");
+        if candidate.exists() {
+            break candidate;
+        }
+        if !cur.pop() {
+            return Ok(None);
+        }
+    };
+
+    let raw = std::fs::read_to_string(&manifest)
+        .map_err(|e| miette::miette!("failed to read {}: {e}", manifest.display()))?;
+    let doc: toml::Value = raw
+        .parse()
+        .map_err(|e| miette::miette!("failed to parse {}: {e}", manifest.display()))?;
+    let Some(fs) = doc.get("fs") else {
+        return Ok(None);
+    };
+    let fs = fs
+        .as_table()
+        .ok_or_else(|| miette::miette!("[fs] in {} must be a table", manifest.display()))?;
+
+    let base = manifest.parent().unwrap_or_else(|| Path::new("."));
+    let base = std::path::absolute(base).unwrap_or_else(|_| base.to_path_buf());
+    let roots = |key: &str| -> miette::Result<Vec<PathBuf>> {
+        let Some(v) = fs.get(key) else {
+            return Ok(Vec::new());
+        };
+        let bad = || miette::miette!("[fs] {key} in {} must be a list of paths", manifest.display());
+        v.as_array()
+            .ok_or_else(bad)?
+            .iter()
+            .map(|p| p.as_str().map(|p| normalize_path(&base.join(p))).ok_or_else(bad))
+            .collect()
+    };
+    Ok(Some(FsPolicy {
+        read: roots("read")?,
+        write: roots("write")?,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_cover_their_directories_and_nothing_beside_them() {
+        let dir = std::env::temp_dir().join(format!("aura-fs-policy-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::write(dir.join("aura.toml"), "[fs]\nread = [\"data\"]\nwrite = [\"out/log.txt\"]\n").unwrap();
+
+        let policy = load_fs_policy(&dir.join("src")).expect("load").expect("[fs] present");
+        assert!(policy.allows(&dir.join("data/a.csv"), FsAccess::Read));
+        assert!(policy.allows(&dir.join("src/../data/b/c.csv"), FsAccess::Read));
+        assert!(!policy.allows(&dir.join("data/../secret.txt"), FsAccess::Read));
+        assert!(!policy.allows(&dir.join("database.txt"), FsAccess::Read));
+        assert!(!policy.allows(&dir.join("data/a.csv"), FsAccess::Write));
+        assert!(policy.allows(&dir.join("out/log.txt"), FsAccess::Write));
+        assert!(!policy.allows(&dir.join("out/other.txt"), FsAccess::Write));
+    }
+}
@@ -1,5 +1,9 @@
 #![forbid(unsafe_code)]
 
+pub mod fs_policy;
+pub mod merkle;
+pub mod modules;
+
 use std::collections::BTreeSet;
 use std::env;
 use std::fs;
@@ -8,6 +12,11 @@ use std::path::{Path, PathBuf};
 
 pub const AURA_HOME_ENV: &str = "AURA_HOME";
 
+/// Separates the user's source from the stdlib modules `augment_source_with_std` appends.
+const STD_BANNER: &str = "\n\n# --- AuraSDK stdlib (auto-injected) ---\n";
+/// Starts each appended stdlib module's header line: `# --- std:aura::<name> ---`.
+const STD_MODULE_HEADER: &str = "\n\n# --- std:aura::";
+
 pub fn detect_aura_home() -> Option<PathBuf> {
     if let Ok(v) = env::var(AURA_HOME_ENV) {
         let p = PathBuf::from(v);
@@ -124,7 +133,7 @@ pub fn augment_source_with_std(source: &str, aura_home: &Path) -> io::Result<Str
     }
 
     let mut out = String::from(source);
-    out.push_str("\n\n# --- AuraSDK stdlib (auto-injected) ---\n");
+    out.push_str(STD_BANNER);
 
     let mut injected: BTreeSet<String> = BTreeSet::new();
 
@@ -135,7 +144,7 @@ pub fn augment_source_with_std(source: &str, aura_home: &Path) -> io::Result<Str
         }
         if let Some(text) = load_std_module(&std_dir, &name)? {
             injected.insert(name.clone());
-            out.push_str("\n\n# --- std:aura::");
+            out.push_str(STD_MODULE_HEADER);
             out.push_str(&name);
             out.push_str(" ---\n");
             out.push_str(&text);
@@ -146,6 +155,23 @@ pub fn augment_source_with_std(source: &str, aura_home: &Path) -> io::Result<Str
     Ok(out)
 }
 
+/// Stdlib modules appended to `text` by `augment_source_with_std`: each module's name and the
+/// offset its text starts at, in order. Empty when nothing was appended.
+pub fn injected_std_modules(text: &str) -> Vec<(String, usize)> {
+    let Some(banner) = text.find(STD_BANNER) else {
+        return Vec::new();
+    };
+    text[banner..]
+        .match_indices(STD_MODULE_HEADER)
+        .filter_map(|(i, _)| {
+            let header = banner + i + STD_MODULE_HEADER.len();
+            let line = &text[header..];
+            let name = line.split(" ---").next()?;
+            Some((name.to_string(), header + line.find('\n')? + 1))
+        })
+        .collect()
+}
+
 pub fn augment_source_with_default_std(source: &str) -> io::Result<String> {
     let Some(home) = detect_aura_home() else {
         return Ok(source.to_string());
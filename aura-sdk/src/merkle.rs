@@ -0,0 +1,316 @@
+//! Statement-level merkle hashes, shared by the language server's proof cache and the
+//! build driver's object cache.
+
+use std::collections::HashMap;
+
+use sha2::{Digest, Sha256};
+
+pub fn sha256_hex(s: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(s.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// `f` for `f(...)` and `io.println` for `io.println(...)`.
+pub fn expr_callee_name(expr: &aura_ast::Expr) -> Option<String> {
+    use aura_ast::ExprKind;
+    match &expr.kind {
+        ExprKind::Ident(id) => Some(id.node.clone()),
+        ExprKind::Member { base, member } => {
+            if let ExprKind::Ident(b) = &base.kind {
+                Some(format!("{}.{}", b.node, member.node))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct StmtHashInfo {
+    pub hash: String,
+    pub is_ui: bool,
+}
+
+/// Merkle hash of every checkable top-level statement (cells, flows, layouts, renders);
+/// `None` for the others. A statement's hash covers its own text, `dep_hash` (whatever else
+/// it may depend on, such as imports) and the text of every callable it transitively calls,
+/// so editing a callee changes the hashes of all its callers but nothing unrelated.
+pub fn stmt_merkle_hashes(program: &aura_ast::Program, text: &str, dep_hash: &str) -> Vec<Option<StmtHashInfo>> {
+    use aura_ast::{Expr, ExprKind, Stmt};
+
+    fn walk_expr_call_names(out: &mut std::collections::BTreeSet<String>, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Call { callee, args, trailing } => {
+                if let Some(name) = expr_callee_name(callee) {
+                    out.insert(name);
+                }
+                walk_expr_call_names(out, callee);
+                for a in args {
+                    match a {
+                        aura_ast::CallArg::Positional(e) => walk_expr_call_names(out, e),
+                        aura_ast::CallArg::Named { value, .. } => walk_expr_call_names(out, value),
+                    }
+                }
+                if let Some(b) = trailing {
+                    walk_block_call_names(out, b);
+                }
+            }
+            ExprKind::Unary { expr: inner, .. } | ExprKind::Borrow { expr: inner, .. } => {
+                walk_expr_call_names(out, inner)
+            }
+            ExprKind::Binary { left, right, .. } => {
+                walk_expr_call_names(out, left);
+                walk_expr_call_names(out, right);
+            }
+            ExprKind::Member { base, .. } => walk_expr_call_names(out, base),
+            ExprKind::Tuple(elems) => {
+                for e in elems {
+                    walk_expr_call_names(out, e);
+                }
+            }
+            ExprKind::TupleIndex { base, .. } => walk_expr_call_names(out, base),
+            ExprKind::ListLit(elems) => {
+                for e in elems {
+                    walk_expr_call_names(out, e);
+                }
+            }
+            ExprKind::MapLit(entries) => {
+                for (k, v) in entries {
+                    walk_expr_call_names(out, k);
+                    walk_expr_call_names(out, v);
+                }
+            }
+            ExprKind::Index { base, index } => {
+                walk_expr_call_names(out, base);
+                walk_expr_call_names(out, index);
+            }
+            ExprKind::Lambda { body, .. } => walk_block_call_names(out, body),
+            ExprKind::Flow { left, right, .. } => {
+                walk_expr_call_names(out, left);
+                walk_expr_call_names(out, right);
+            }
+            ExprKind::StyleLit { fields } => {
+                for (_, v) in fields {
+                    walk_expr_call_names(out, v);
+                }
+            }
+            ExprKind::RecordLit { fields, .. } => {
+                for (_, v) in fields {
+                    walk_expr_call_names(out, v);
+                }
+            }
+            ExprKind::ForAll { binders: _, body } | ExprKind::Exists { binders: _, body } => {
+                walk_expr_call_names(out, body);
+            }
+            ExprKind::InterpolatedString(parts) => {
+                for part in parts {
+                    if let aura_ast::InterpPart::Expr(e) = part {
+                        walk_expr_call_names(out, e);
+                    }
+                }
+            }
+            ExprKind::Ident(_) | ExprKind::IntLit(_) | ExprKind::FloatLit(_) | ExprKind::StringLit(_) => {}
+        }
+    }
+
+    fn walk_stmt_call_names(out: &mut std::collections::BTreeSet<String>, stmt: &Stmt) {
+        match stmt {
+            Stmt::StrandDef(sd) => walk_expr_call_names(out, &sd.expr),
+            Stmt::Assign(a) => walk_expr_call_names(out, &a.expr),
+            Stmt::Prop(p) => walk_expr_call_names(out, &p.expr),
+            Stmt::ExprStmt(e) => walk_expr_call_names(out, e),
+            Stmt::Return(r) => {
+                if let Some(v) = &r.value {
+                    walk_expr_call_names(out, v);
+                }
+            }
+            Stmt::Requires(r) => walk_expr_call_names(out, &r.expr),
+            Stmt::Ensures(e) => walk_expr_call_names(out, &e.expr),
+            Stmt::Assert(a) => walk_expr_call_names(out, &a.expr),
+            Stmt::Assume(a) => walk_expr_call_names(out, &a.expr),
+            Stmt::If(i) => {
+                walk_expr_call_names(out, &i.cond);
+                walk_block_call_names(out, &i.then_block);
+                if let Some(b) = &i.else_block {
+                    walk_block_call_names(out, b);
+                }
+            }
+            Stmt::Match(m) => {
+                walk_expr_call_names(out, &m.scrutinee);
+                for arm in &m.arms {
+                    walk_block_call_names(out, &arm.body);
+                }
+            }
+            Stmt::While(w) => {
+                walk_expr_call_names(out, &w.cond);
+                if let Some(inv) = &w.invariant {
+                    walk_expr_call_names(out, inv);
+                }
+                if let Some(dec) = &w.decreases {
+                    walk_expr_call_names(out, dec);
+                }
+                walk_block_call_names(out, &w.body);
+            }
+            Stmt::For(f) => {
+                walk_expr_call_names(out, &f.start);
+                walk_expr_call_names(out, &f.end);
+                if let Some(inv) = &f.invariant {
+                    walk_expr_call_names(out, inv);
+                }
+                walk_block_call_names(out, &f.body);
+            }
+            Stmt::CellDef(c) => walk_block_call_names(out, &c.body),
+            Stmt::FlowBlock(f) => walk_block_call_names(out, &f.body),
+            Stmt::Layout(l) => walk_block_call_names(out, &l.body),
+            Stmt::Render(r) => walk_block_call_names(out, &r.body),
+            Stmt::MacroCall(m) => {
+                for a in &m.args {
+                    walk_expr_call_names(out, a);
+                }
+            }
+            Stmt::MacroDef(m) => walk_block_call_names(out, &m.body),
+            Stmt::UnsafeBlock(ub) => walk_block_call_names(out, &ub.body),
+            Stmt::Scope(sc) => walk_block_call_names(out, &sc.body),
+            Stmt::Region(r) => walk_block_call_names(out, &r.body),
+            Stmt::Defer(d) => walk_block_call_names(out, &d.body),
+            Stmt::Import(_) | Stmt::ExternCell(_) | Stmt::TypeAlias(_) | Stmt::TraitDef(_) | Stmt::RecordDef(_) | Stmt::EnumDef(_) => {}
+            Stmt::Break(_) | Stmt::Continue(_) => {}
+        }
+    }
+
+    fn walk_block_call_names(out: &mut std::collections::BTreeSet<String>, block: &aura_ast::Block) {
+        for s in &block.stmts {
+            walk_stmt_call_names(out, s);
+        }
+        if let Some(y) = &block.yield_expr {
+            walk_expr_call_names(out, y);
+        }
+    }
+
+    let n = program.stmts.len();
+    let mut out: Vec<Option<StmtHashInfo>> = vec![None; n];
+
+    // Map top-level callable defs (cells/flows) to statement indices.
+    let mut top_level_callables: HashMap<String, usize> = HashMap::new();
+    for (i, stmt) in program.stmts.iter().enumerate() {
+        match stmt {
+            Stmt::CellDef(c) => {
+                top_level_callables.insert(c.name.node.clone(), i);
+            }
+            Stmt::FlowBlock(f) => {
+                top_level_callables.insert(f.name.node.clone(), i);
+            }
+            _ => {}
+        }
+    }
+
+    // Content hashes for checkable top-level units.
+    let mut content_hash_by_idx: HashMap<usize, String> = HashMap::new();
+    let mut kind_by_idx: HashMap<usize, &'static str> = HashMap::new();
+    let mut ui_by_idx: HashMap<usize, bool> = HashMap::new();
+
+    for (i, stmt) in program.stmts.iter().enumerate() {
+        let (is_checkable, is_ui) = match stmt {
+            Stmt::CellDef(_) => (true, true),
+            Stmt::FlowBlock(_) => (true, false),
+            Stmt::Layout(_) => (true, true),
+            Stmt::Render(_) => (true, true),
+            _ => (false, false),
+        };
+        if !is_checkable {
+            continue;
+        }
+
+        let (kind, sp) = match stmt {
+            Stmt::CellDef(s) => ("cell", s.span),
+            Stmt::FlowBlock(s) => ("flow", s.span),
+            Stmt::Layout(s) => ("layout", s.span),
+            Stmt::Render(s) => ("render", s.span),
+            _ => continue,
+        };
+        let start = sp.offset();
+        let end = start.saturating_add(sp.len());
+        let slice = text.get(start..end).unwrap_or("");
+        let content_hash = sha256_hex(&format!("stmt-content-v1\nkind={kind}\n{slice}"));
+
+        content_hash_by_idx.insert(i, content_hash);
+        kind_by_idx.insert(i, kind);
+        ui_by_idx.insert(i, is_ui);
+    }
+
+    // Direct deps via call edges.
+    let mut deps: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for (i, stmt) in program.stmts.iter().enumerate() {
+        if !content_hash_by_idx.contains_key(&i) {
+            continue;
+        }
+        let mut names: std::collections::BTreeSet<String> = std::collections::BTreeSet::new();
+        walk_stmt_call_names(&mut names, stmt);
+
+        let mut edges: Vec<usize> = Vec::new();
+        for name in names {
+            if let Some(&j) = top_level_callables.get(&name)
+                && j != i
+            {
+                edges.push(j);
+            }
+        }
+        edges.sort();
+        edges.dedup();
+        deps[i] = edges;
+    }
+
+    // Transitive closure of deps (by index), restricted to checkable nodes.
+    for i in 0..n {
+        if !content_hash_by_idx.contains_key(&i) {
+            continue;
+        }
+        let mut stack: Vec<usize> = deps[i].clone();
+        let mut seen: std::collections::BTreeSet<usize> = std::collections::BTreeSet::new();
+        while let Some(j) = stack.pop() {
+            if j == i {
+                continue;
+            }
+            if !content_hash_by_idx.contains_key(&j) {
+                continue;
+            }
+            if !seen.insert(j) {
+                continue;
+            }
+            for &k in &deps[j] {
+                stack.push(k);
+            }
+        }
+
+        let kind = kind_by_idx.get(&i).copied().unwrap_or("stmt");
+        let content_hash = content_hash_by_idx.get(&i).cloned().unwrap_or_else(|| sha256_hex(""));
+
+        let mut input = String::new();
+        input.push_str("stmt-merkle-v1\n");
+        input.push_str(&format!("kind={kind}\n"));
+        input.push_str(&format!("content={content_hash}\n"));
+        input.push_str(&format!("imports={dep_hash}\n"));
+        for j in seen {
+            // Encode deps deterministically by name if we have one, else by index.
+            let dep_name = top_level_callables
+                .iter()
+                .find_map(|(name, idx)| if *idx == j { Some(name.clone()) } else { None })
+                .unwrap_or_else(|| format!("#{j}"));
+            let dep_content = content_hash_by_idx
+                .get(&j)
+                .cloned()
+                .unwrap_or_else(|| sha256_hex(""));
+            input.push_str(&format!("dep={dep_name}:{dep_content}\n"));
+        }
+
+        let hash = sha256_hex(&input);
+        out[i] = Some(StmtHashInfo {
+            hash,
+            is_ui: ui_by_idx.get(&i).copied().unwrap_or(false),
+        });
+    }
+
+    out
+}
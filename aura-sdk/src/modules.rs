@@ -0,0 +1,443 @@
+//! Multi-file module resolution.
+//!
+//! `import net::http` loads `net/http.aura` (or `net/http/mod.aura`) relative to the entry
+//! file's directory. Every file is parsed once into its own slice of one program-wide span
+//! space, so merged spans still point at the file they came from. Cells a module defines are
+//! qualified with its last path segment (`http::get`); only `pub` cells may be called from
+//! outside it. Types, traits and extern cells stay in the shared global namespace.
+//! `aura::` / `std::` imports are left to the SDK stdlib injection. Every definition is also
+//! entered in a [`SymbolTable`] under its full module path, which rejects duplicates.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use aura_ast::{ImportStmt, Program, Span, Stmt};
+use aura_core::{DuplicateDefinition, SemanticError, SymbolTable};
+use aura_parse::ParseConfig;
+use miette::{IntoDiagnostic, LabeledSpan, NamedSource};
+
+#[derive(Clone, Debug)]
+pub struct SourceFile {
+    pub path: PathBuf,
+    /// Module path (`["net", "http"]`); empty for the entry file.
+    pub module: Vec<String>,
+    /// Offset of the file's first byte in the program-wide span space.
+    pub base: usize,
+    pub text: String,
+    /// Span of the import that first loaded this file (`None` for the entry file).
+    pub imported_at: Option<Span>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ModuleGraph {
+    /// The entry file first, then modules in load order.
+    pub files: Vec<SourceFile>,
+    /// Every module's (qualified) definitions in dependency order, then the entry statements.
+    pub program: Program,
+    /// Every top-level definition; `Symbol::file` indexes `files`.
+    pub symbols: SymbolTable,
+}
+
+impl ModuleGraph {
+    /// File containing a program-wide offset, and the offset local to that file.
+    pub fn locate(&self, offset: usize) -> Option<(&SourceFile, usize)> {
+        locate(&self.files, offset).map(|f| (f, offset - f.base))
+    }
+
+    /// Maps a span to the entry file: spans inside imported modules map to the import
+    /// statement that (transitively) pulled the module in.
+    pub fn entry_span(&self, span: Span) -> Span {
+        entry_span(&self.files, span)
+    }
+
+    /// All files laid out at their base offsets, one newline apart.
+    ///
+    /// Use this as the diagnostic source so any merged span renders the right snippet.
+    pub fn combined_source(&self) -> String {
+        let mut out = String::new();
+        for f in &self.files {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            out.push_str(&f.text);
+        }
+        out
+    }
+}
+
+fn locate(files: &[SourceFile], offset: usize) -> Option<&SourceFile> {
+    files
+        .iter()
+        .rev()
+        .find(|f| f.base <= offset && offset <= f.base + f.text.len())
+}
+
+fn entry_span(files: &[SourceFile], span: Span) -> Span {
+    let mut span = span;
+    while let Some(file) = locate(files, span.offset()) {
+        match file.imported_at {
+            Some(at) => span = at,
+            None => break,
+        }
+    }
+    span
+}
+
+/// `net::http` -> `<root>/net/http.aura`, falling back to `<root>/net/http/mod.aura`.
+pub fn find_module_file(root: &Path, segments: &[String]) -> Option<PathBuf> {
+    let (last, dirs) = segments.split_last()?;
+    let mut dir = root.to_path_buf();
+    for d in dirs {
+        dir.push(d);
+    }
+    let file = dir.join(format!("{last}.aura"));
+    if file.is_file() {
+        return Some(file);
+    }
+    let module_dir = dir.join(last).join("mod.aura");
+    module_dir.is_file().then_some(module_dir)
+}
+
+fn is_std_import(import: &ImportStmt) -> bool {
+    matches!(
+        import.path.first().map(|s| s.node.as_str()),
+        Some("aura") | Some("std")
+    )
+}
+
+/// Parse `entry_src` (the contents of `entry_path`) and every user module it imports.
+pub fn resolve_modules(
+    entry_path: &Path,
+    entry_src: &str,
+    config: &ParseConfig,
+) -> miette::Result<ModuleGraph> {
+    let root = entry_path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+        .to_path_buf();
+
+    let entry = aura_parse::parse_source_at(entry_src, 0, config)
+        .map_err(|e| e.with_source_code(NamedSource::new(entry_path.display().to_string(), entry_src.to_string())))?;
+
+    let mut loader = Loader {
+        root,
+        entry_name: entry_path.display().to_string(),
+        config,
+        combined: entry_src.to_string(),
+        files: vec![SourceFile {
+            path: entry_path.to_path_buf(),
+            module: Vec::new(),
+            base: 0,
+            text: entry_src.to_string(),
+            imported_at: None,
+        }],
+        loaded: HashMap::new(),
+        defs: Vec::new(),
+        symbols: SymbolTable::new(),
+    };
+
+    // The entry's own definitions, then the stdlib modules appended to it.
+    let std_modules = crate::injected_std_modules(entry_src);
+    let in_range = |start: usize, end: usize| -> Vec<Stmt> {
+        entry
+            .stmts
+            .iter()
+            .filter(|s| (start..end).contains(&s.span().offset()))
+            .cloned()
+            .collect()
+    };
+    let std_start = std_modules.first().map_or(usize::MAX, |(_, start)| *start);
+    let added = loader.symbols.add_file(0, &[], 0, &in_range(0, std_start));
+    loader.check_duplicate(added)?;
+    for (i, (name, start)) in std_modules.iter().enumerate() {
+        let end = std_modules.get(i + 1).map_or(usize::MAX, |(_, next)| *next);
+        let module = ["aura".to_string(), name.clone()];
+        let added = loader.symbols.add_stdlib(0, &module, 0, &in_range(*start, end));
+        loader.check_duplicate(added)?;
+    }
+
+    for import in user_imports(&entry.stmts) {
+        loader.load(&import)?;
+    }
+
+    // Sema checks bodies in order, so dependencies precede their importers.
+    let mut stmts = loader.defs;
+    stmts.extend(entry.stmts);
+    Ok(ModuleGraph {
+        files: loader.files,
+        program: Program { stmts },
+        symbols: loader.symbols,
+    })
+}
+
+struct Loader<'a> {
+    root: PathBuf,
+    entry_name: String,
+    config: &'a ParseConfig,
+    /// Text of every file loaded so far, laid out at its base offset.
+    combined: String,
+    files: Vec<SourceFile>,
+    /// Namespace -> module key (`http` -> `net::http`).
+    loaded: HashMap<String, String>,
+    defs: Vec<Stmt>,
+    symbols: SymbolTable,
+}
+
+impl Loader<'_> {
+    fn load(&mut self, import: &ImportStmt) -> miette::Result<()> {
+        let segments: Vec<String> = import.path.iter().map(|s| s.node.clone()).collect();
+        let key = segments.join("::");
+        let namespace = segments.last().cloned().expect("import path is non-empty");
+        match self.loaded.get(&namespace) {
+            Some(other) if *other == key => return Ok(()),
+            Some(other) => {
+                return Err(self.error_at(
+                    import.span,
+                    format!("module namespace '{namespace}' is ambiguous: both '{other}' and '{key}' are imported"),
+                ));
+            }
+            None => {}
+        }
+
+        let Some(path) = find_module_file(&self.root, &segments) else {
+            let rel = segments.join("/");
+            return Err(self.error_at(
+                import.span,
+                format!(
+                    "unresolved module '{key}': expected {rel}.aura or {rel}/mod.aura under {}",
+                    self.root.display()
+                ),
+            ));
+        };
+        let text = fs::read_to_string(&path).into_diagnostic()?;
+
+        self.combined.push('\n');
+        let base = self.combined.len();
+        self.combined.push_str(&text);
+        let module = aura_parse::parse_source_at(&text, base, self.config).map_err(|e| {
+            e.with_source_code(NamedSource::new(path.display().to_string(), self.combined.clone()))
+        })?;
+
+        // Registered before recursing so import cycles terminate.
+        self.loaded.insert(namespace.clone(), key.clone());
+        self.files.push(SourceFile {
+            path: path.clone(),
+            module: segments,
+            base,
+            text,
+            imported_at: Some(import.span),
+        });
+        let file = self.files.len() - 1;
+        let added = self.symbols.add_file(file, &self.files[file].module, base, &module.stmts);
+        self.check_duplicate(added)?;
+
+        for nested in user_imports(&module.stmts) {
+            self.load(&nested)?;
+        }
+
+        for stmt in module.stmts {
+            match stmt {
+                Stmt::CellDef(mut cell) => {
+                    cell.name.node = format!("{namespace}.{}", cell.name.node);
+                    self.defs.push(Stmt::CellDef(cell));
+                }
+                Stmt::Import(_)
+                | Stmt::ExternCell(_)
+                | Stmt::RecordDef(_)
+                | Stmt::EnumDef(_)
+                | Stmt::TypeAlias(_)
+                | Stmt::TraitDef(_) => self.defs.push(stmt),
+                _ => {
+                    return Err(miette::miette!(
+                        "module '{key}' ({}) may only contain imports and definitions at the top level",
+                        path.display()
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reports a duplicate definition on the entry-file statement that leads to it: the
+    /// definition itself, or the import that pulled its module in.
+    fn check_duplicate(&self, added: Result<(), Box<DuplicateDefinition>>) -> miette::Result<()> {
+        let Err(dup) = added else { return Ok(()) };
+        let file = &self.files[dup.second.file];
+        let message = match file.imported_at {
+            Some(_) => format!("{}: {}", file.path.display(), dup.message),
+            None => dup.message,
+        };
+        let err = SemanticError {
+            message,
+            span: entry_span(&self.files, dup.second.span),
+        };
+        Err(miette::Report::new(err)
+            .with_source_code(NamedSource::new(self.entry_name.clone(), self.combined.clone())))
+    }
+
+    fn error_at(&self, span: Span, message: String) -> miette::Report {
+        miette::miette!(labels = vec![LabeledSpan::at(span, "imported here")], "{message}")
+            .with_source_code(NamedSource::new(self.entry_name.clone(), self.combined.clone()))
+    }
+}
+
+fn user_imports(stmts: &[Stmt]) -> Vec<ImportStmt> {
+    stmts
+        .iter()
+        .filter_map(|s| match s {
+            Stmt::Import(i) if !is_std_import(i) => Some(i.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("aura-modules-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn imported_cells_are_qualified_and_spans_stay_in_their_file() {
+        let dir = scratch_dir("qualify");
+        fs::create_dir_all(dir.join("net")).unwrap();
+        fs::write(
+            dir.join("net").join("http.aura"),
+            "cell helper(x: u32):\n    yield x\n\npub cell get(x: u32):\n    yield helper(x)\n",
+        )
+        .unwrap();
+        let entry = dir.join("main.aura");
+        let src = "import net::http\n\ncell main():\n    val r: u32 = http::get(1)\n";
+
+        let graph = resolve_modules(&entry, src, &ParseConfig::default()).expect("resolve");
+        let names: Vec<&str> = graph
+            .program
+            .stmts
+            .iter()
+            .filter_map(|s| match s {
+                Stmt::CellDef(c) => Some(c.name.node.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(names, vec!["http.helper", "http.get", "main"]);
+
+        let Some(Stmt::CellDef(helper)) = graph.program.stmts.first() else {
+            panic!("expected helper cell");
+        };
+        let (file, local) = graph.locate(helper.span.offset()).expect("locate");
+        assert!(file.path.ends_with("net/http.aura"));
+        assert_eq!(&file.text[local..local + 4], "cell");
+        assert_eq!(graph.entry_span(helper.span).offset(), 0);
+        assert_eq!(
+            &graph.combined_source()[helper.span.offset()..helper.span.offset() + 11],
+            "cell helper"
+        );
+    }
+
+    #[test]
+    fn symbols_have_stable_qualified_names_and_local_spans() {
+        let dir = scratch_dir("symbols");
+        fs::create_dir_all(dir.join("geo")).unwrap();
+        fs::write(
+            dir.join("geo").join("shapes.aura"),
+            "type Point = record { x: u32, y: u32 }\n\npub cell area(w: u32, h: u32) -> u32:\n    return w * h\n",
+        )
+        .unwrap();
+        let src = "import geo::shapes\n\ncell main():\n    val a: u32 = shapes::area(2, 3)\n";
+        let graph = resolve_modules(&dir.join("main.aura"), src, &ParseConfig::default()).expect("resolve");
+
+        let area = graph.symbols.get("geo::shapes::area").expect("area");
+        assert_eq!(area.resolved, "shapes.area");
+        assert!(area.is_pub);
+        let file = &graph.files[area.file];
+        assert!(file.path.ends_with("geo/shapes.aura"));
+        let local = area.local_span;
+        assert_eq!(&file.text[local.offset()..local.offset() + local.len()], "area");
+        assert_eq!(graph.symbols.resolve("shapes.area"), Some(area));
+        assert_eq!(graph.symbols.resolve("Point").map(|s| s.qualified.as_str()), Some("geo::shapes::Point"));
+        assert_eq!(graph.symbols.resolve("main").map(|s| s.file), Some(0));
+
+        // Any offset inside a definition finds it.
+        let offset = area.def_span.offset() + 5;
+        assert_eq!(graph.symbols.at(offset).map(|s| s.qualified.as_str()), Some("geo::shapes::area"));
+    }
+
+    #[test]
+    fn sema_checks_visibility_against_the_defining_symbol() {
+        let dir = scratch_dir("visibility");
+        fs::create_dir_all(dir.join("net")).unwrap();
+        fs::write(
+            dir.join("net").join("http.aura"),
+            "cell helper(x: u32) -> u32:\n    return x + 1\n\npub cell get(x: u32) -> u32:\n    return helper(x)\n",
+        )
+        .unwrap();
+        let check = |src: &str| {
+            let graph = resolve_modules(&dir.join("main.aura"), src, &ParseConfig::default()).expect("resolve");
+            let mut checker = aura_core::Checker::new();
+            checker.set_symbols(graph.symbols);
+            checker.check_program(&graph.program).map_err(|e| e.message)
+        };
+
+        check("import net::http\n\ncell main():\n    val r: u32 = http::get(1)\n").expect("get is pub");
+        let err = check("import net::http\n\ncell main():\n    val r: u32 = http::helper(1)\n")
+            .expect_err("helper is private");
+        assert!(err.contains("cell 'net::http::helper' is private to module 'net::http'"), "{err}");
+    }
+
+    #[test]
+    fn duplicate_definitions_are_rejected_across_files() {
+        let dir = scratch_dir("duplicates");
+        fs::write(dir.join("a.aura"), "type Point = record { x: u32 }\n").unwrap();
+        fs::write(dir.join("b.aura"), "type Point = enum { Origin }\n").unwrap();
+        let src = "import a\nimport b\n";
+        let err = resolve_modules(&dir.join("main.aura"), src, &ParseConfig::default()).expect_err("two Points");
+        let msg = err.to_string();
+        assert!(msg.contains("b.aura: enum 'b::Point' conflicts with record 'a::Point'"), "{msg}");
+
+        let src = "cell f():\n    val a = 1\n\ncell f():\n    val b = 2\n";
+        let err = resolve_modules(&dir.join("main.aura"), src, &ParseConfig::default()).expect_err("two fs");
+        assert!(err.to_string().contains("cell 'f' is defined twice"), "{err}");
+
+        // Cells of different modules don't clash: they are qualified.
+        fs::write(dir.join("c.aura"), "pub cell f():\n    val a = 1\n").unwrap();
+        resolve_modules(&dir.join("main.aura"), "import c\n\ncell f():\n    c::f()\n", &ParseConfig::default())
+            .expect("c.f and f are distinct");
+    }
+
+    #[test]
+    fn stdlib_modules_may_overlap_but_user_code_may_not_redefine_them() {
+        let home = scratch_dir("stdhome");
+        fs::create_dir_all(home.join("std")).unwrap();
+        fs::write(home.join("std").join("one.aura"), "cell log(msg: String):\n    val m = msg\n").unwrap();
+        fs::write(home.join("std").join("two.aura"), "cell log(msg: String):\n    val m = msg\n").unwrap();
+        let entry = home.join("main.aura");
+
+        let src = crate::augment_source_with_std("import aura::one\nimport aura::two\n", &home).unwrap();
+        let graph = resolve_modules(&entry, &src, &ParseConfig::default()).expect("std overlap is tolerated");
+        let log = graph.symbols.resolve("log").expect("log");
+        assert_eq!((log.qualified.as_str(), log.from_std), ("aura::one::log", true));
+
+        let src = crate::augment_source_with_std("import aura::one\n\ncell log(msg: String):\n    val m = msg\n", &home)
+            .unwrap();
+        let err = resolve_modules(&entry, &src, &ParseConfig::default()).expect_err("user log");
+        assert!(
+            err.to_string().contains("cell 'log' redefines cell 'aura::one::log' from the standard library"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn missing_module_is_reported() {
+        let dir = scratch_dir("missing");
+        let err = resolve_modules(&dir.join("main.aura"), "import nope::gone\n", &ParseConfig::default())
+            .expect_err("module does not exist");
+        assert!(err.to_string().contains("unresolved module 'nope::gone'"), "{err}");
+    }
+}
@@ -1,5 +1,7 @@
 #pragma once
 
+#include <stdbool.h>
+#include <stddef.h>
 #include <stdint.h>
 
 #ifdef __cplusplus
@@ -11,21 +13,152 @@ extern "C" {
 // `io.println(String)` (prototype): expects a null-terminated UTF-8 string.
 void aura_io_println(const char* s);
 
+// String interpolation runtime. Results are heap (or region) allocated and never freed.
+const char* aura_str_concat(const char* a, const char* b);
+const char* aura_str_from_u32(uint32_t v);
+const char* aura_str_from_bool(bool v);
+const char* aura_str_from_f64(double v);
+
+// Length-prefixed strings, used by the native (LLVM) backend. A string is still a
+// pointer to NUL-terminated UTF-8 bytes, so it can be passed to C unchanged, but an
+// AuraStrHeader sits immediately before the first byte. Literals are emitted by the
+// compiler with a static header; strings of up to AURA_STR_SMALL_MAX bytes are
+// carved from per-thread slabs of fixed-size cells instead of separate allocations.
+typedef struct AuraStrHeader {
+    uint32_t len;   // Bytes, excluding the NUL terminator.
+    uint32_t flags; // AURA_STR_* bits.
+} AuraStrHeader;
+
+#define AURA_STR_STATIC 1u // Compile-time literal or runtime constant.
+#define AURA_STR_SMALL 2u  // Lives in a small-string cell.
+#define AURA_STR_SMALL_MAX 23u
+
+uint32_t aura_string_len(const char* s);
+const char* aura_string_concat(const char* a, const char* b);
+const char* aura_string_from_u32(uint32_t v);
+const char* aura_string_from_u64(uint64_t v);
+const char* aura_string_from_i64(int64_t v);
+const char* aura_string_from_bool(bool v);
+const char* aura_string_from_f64(double v);
+// Bytes [start, end) of `s`, clamped to its length; a slice of the whole string is `s` itself.
+const char* aura_string_slice(const char* s, uint32_t start, uint32_t end);
+// Copies a plain C string (e.g. an extern's result) into a length-prefixed string.
+const char* aura_string_adopt(const char* s);
+// `str.*` (std::string). Lengths and offsets count bytes. aura_string_find returns the
+// first offset of `needle`, or the length of `s` when it does not occur. Splitting on an
+// empty separator gives the whole string as the only field, and asking aura_string_split_nth
+// for a field past the last traps. aura_string_trim drops ASCII whitespace at both ends;
+// aura_string_pad_left/right add spaces up to `width` bytes and never shorten `s`.
+uint32_t aura_string_find(const char* s, const char* needle);
+uint32_t aura_string_split_count(const char* s, const char* sep);
+const char* aura_string_split_nth(const char* s, const char* sep, uint32_t index);
+const char* aura_string_trim(const char* s);
+const char* aura_string_pad_left(const char* s, uint32_t width);
+const char* aura_string_pad_right(const char* s, uint32_t width);
+// `time.format_utc` and `time.format_ms` (std::time). aura_time_format_utc renders Unix
+// seconds as ISO 8601 UTC, always 20 bytes ("2024-05-01T12:00:00Z"); aura_time_format_ms
+// renders a duration as "HH:MM:SS.mmm", 12 to 14 bytes since hours take 2 to 4 digits.
+const char* aura_time_format_utc(uint32_t unix_s);
+const char* aura_time_format_ms(uint32_t ms);
+
 // Runtime-enforced range check used by the native pipeline.
 // Traps (aborts) on failure.
 void aura_range_check_u32(uint32_t v, uint32_t lo, uint32_t hi);
 
+// `math.pow` on u32. Traps (aborts) when the result does not fit 32 bits; the other
+// `math.*` calls lower to LLVM intrinsics.
+uint32_t aura_math_pow_u32(uint32_t base, uint32_t exp);
+
 // Minimal Tensor model for Stage 11/12 prototyping.
 // We represent `Tensor` as an opaque u32 handle in the compiler.
 uint32_t aura_tensor_new(uint32_t len);
 uint32_t aura_tensor_len(uint32_t t);
 uint32_t aura_tensor_get(uint32_t t, uint32_t index);
 void aura_tensor_set(uint32_t t, uint32_t index, uint32_t value);
+// Contiguous elements of `t` (NULL for an invalid handle), read and written directly by
+// vectorized tensor loops after they checked `aura_tensor_len`.
+uint32_t* aura_tensor_data(uint32_t t);
+
+// CPU features that select a version of a multi-versioned tensor kernel (aura-ai-opt
+// clones them): AURA_CPU_AVX512 when the CPU and OS support AVX-512 (F, BW, VL, DQ), and
+// AURA_CPU_AMX when AMX tiles are usable as well. 0 on other architectures.
+#define AURA_CPU_AVX512 1u
+#define AURA_CPU_AMX 2u
+uint32_t aura_cpu_features(void);
+
+// `Map<K, V>` runtime. Maps are opaque u32 handles; keys are u32 or strings
+// (the `_str` variants) and values occupy u32 slots. Missing keys read as 0.
+uint32_t aura_map_new(uint32_t capacity);
+uint32_t aura_map_len(uint32_t m);
+void aura_map_insert(uint32_t m, uint32_t key, uint32_t value);
+void aura_map_insert_str(uint32_t m, const char* key, uint32_t value);
+uint32_t aura_map_get(uint32_t m, uint32_t key);
+uint32_t aura_map_get_str(uint32_t m, const char* key);
+bool aura_map_contains(uint32_t m, uint32_t key);
+bool aura_map_contains_str(uint32_t m, const char* key);
 
-// === AI builtins (prototype) ===
-// Model handles are opaque u32 values.
+// `region` blocks. aura_region_enter opens a region on the calling thread and returns its
+// handle; the tensors and maps the thread makes until aura_region_exit of that handle are
+// freed then. Regions close innermost first. Task entry points run between
+// aura_region_detach and aura_region_reattach, outside the regions of the thread.
+// Under AURA_ALLOC_REGION the freed data stays in the bump arena.
+uint32_t aura_region_enter(void);
+void aura_region_exit(uint32_t r);
+void aura_region_detach(void);
+void aura_region_reattach(void);
+
+// `std::fs`. Files are opaque u32 handles: aura_fs_open opens one for reading,
+// aura_fs_create (truncating) and aura_fs_append for writing, and aura_fs_read returns the
+// rest of a file. Built with AURA_FS_POLICY, the stdlib only opens paths at or below an
+// entry of AURA_FS_READ_ROOTS or AURA_FS_WRITE_ROOTS, comma-terminated lists of string
+// literals the driver takes from the [fs] table of aura.toml. Failures trap, as does using
+// a handle after aura_fs_close.
+uint32_t aura_fs_open(const char* path);
+uint32_t aura_fs_create(const char* path);
+uint32_t aura_fs_append(const char* path);
+const char* aura_fs_read(uint32_t f);
+void aura_fs_write(uint32_t f, const char* text);
+void aura_fs_close(uint32_t f);
+
+// `std::net`. Sockets are opaque u32 handles to TCP connections: aura_net_connect resolves
+// and connects to host:port, aura_net_send writes all of its string, aura_net_recv waits
+// for the next bytes (at most 64 KiB) and returns "" once the peer has closed its side, and
+// aura_net_recv_all returns everything up to then. Each socket follows the
+// Open -> Reading/Writing -> Closed machine of aura-core's NetworkVerifier; failures trap,
+// as does using a socket after aura_net_close. On Windows the program links ws2_32.
+uint32_t aura_net_connect(const char* host, uint32_t port);
+void aura_net_send(uint32_t s, const char* data);
+const char* aura_net_recv(uint32_t s);
+const char* aura_net_recv_all(uint32_t s);
+void aura_net_close(uint32_t s);
+
+// === AI builtins ===
+// Model handles are opaque u32 values. Models run on the inference backend a runtime
+// binding (e.g. tools/onnxruntime_bridge.c) registers at startup; without one, loading a
+// model traps. Both sides of every `aura_ai_infer` call are checked against the model's
+// shapes, and a tensor that does not fit them traps instead of reaching the model.
 uint32_t aura_ai_load_model(const char* path);
 uint32_t aura_ai_infer(uint32_t model, uint32_t input);
+// As aura_ai_infer, for a model whose leading input and output dimension is a batch of
+// `batch` samples (at least 1).
+uint32_t aura_ai_infer_batch(uint32_t model, uint32_t input, uint32_t batch);
+
+// Shapes have at most AURA_AI_MAX_RANK dimensions; -1 marks one the model leaves open.
+#define AURA_AI_MAX_RANK 8u
+
+typedef struct AuraAiBackend {
+    // Loads the model at `path` and reports the shapes of its first input and output.
+    // Returns NULL, with a message in `err`, on failure.
+    void* (*load)(const char* path, int64_t* in_dims, uint32_t* in_rank, int64_t* out_dims, uint32_t* out_rank,
+                  char* err, size_t err_cap);
+    // Runs a model on tensor `input`, shaped `in_dims`, and returns a new tensor (see
+    // aura_tensor_new) holding its first output, whose shape goes to `out_dims`. Returns 0,
+    // with a message in `err`, on failure.
+    uint32_t (*run)(void* model, uint32_t input, const int64_t* in_dims, uint32_t in_rank, int64_t* out_dims,
+                    uint32_t* out_rank, char* err, size_t err_cap);
+} AuraAiBackend;
+
+void aura_ai_register_backend(const AuraAiBackend* backend);
 
 // Compatibility with existing demo program builtins.
 uint32_t io_load_tensor(const char* path);
@@ -1,9 +1,37 @@
+// For syscall() under strict -std modes (used to request AMX state on Linux).
+#if defined(__linux__) && !defined(_DEFAULT_SOURCE)
+#define _DEFAULT_SOURCE
+#endif
 #include "aura_stdlib.h"
 
 #include <stdlib.h>
 #include <stdio.h>
 #include <string.h>
 #include <stddef.h>
+#include <stdatomic.h>
+#include <errno.h>
+#if defined(_WIN32)
+#include <winsock2.h>
+#include <ws2tcpip.h>
+#include <direct.h>
+#else
+#include <netdb.h>
+#include <sys/socket.h>
+#include <unistd.h>
+#endif
+
+#if defined(__x86_64__) || defined(__i386__) || defined(_M_X64) || defined(_M_IX86)
+#define AURA_X86 1
+#if defined(_MSC_VER)
+#include <intrin.h>
+#else
+#include <cpuid.h>
+#endif
+#if defined(__linux__)
+#include <sys/syscall.h>
+#include <unistd.h>
+#endif
+#endif
 
 #define AURA_MAX_TENSORS 1024u
 
@@ -49,16 +77,64 @@ static void* aura_alloc_zeroed(size_t count, size_t elem_size) {
 }
 #endif
 
+// `region` blocks. Each thread keeps a stack of open regions; tensors and maps belong to
+// the innermost one open on the thread that made them, and are freed when it exits. Tasks
+// push a 0 entry while they run, so what they make belongs to no region.
+#define AURA_MAX_REGION_DEPTH 64u
+
+static atomic_uint g_next_region = 1u; // 0 is "no region".
+static _Thread_local uint32_t t_regions[AURA_MAX_REGION_DEPTH];
+static _Thread_local uint32_t t_region_depth = 0u;
+
+static uint32_t aura_region_current(void) {
+    return t_region_depth ? t_regions[t_region_depth - 1u] : 0u;
+}
+
 typedef struct AuraTensor {
     uint32_t len;
     uint32_t* data;
+    uint32_t region;
+    bool freed;
 } AuraTensor;
 
 static AuraTensor g_tensors[AURA_MAX_TENSORS];
 static uint32_t g_next_tensor = 1u; // 0 is reserved as "invalid".
 
+// Maps keep their entries in insertion order and look keys up linearly; map
+// literals are small and key presence is proven statically by aura-verify.
+#define AURA_MAX_MAPS 1024u
+
+typedef struct AuraMapEntry {
+    const char* skey; // NULL for u32 keys.
+    uint32_t ukey;
+    uint32_t value;
+} AuraMapEntry;
+
+typedef struct AuraMap {
+    uint32_t len;
+    uint32_t cap;
+    AuraMapEntry* entries;
+    uint32_t region;
+    bool freed;
+} AuraMap;
+
+static AuraMap g_maps[AURA_MAX_MAPS];
+static uint32_t g_next_map = 1u; // 0 is reserved as "invalid".
+
 #define AURA_MAX_MODELS 256u
+
+typedef struct AuraModel {
+    void* handle; // The backend's model.
+    const char* path;
+    int64_t in_dims[AURA_AI_MAX_RANK];
+    uint32_t in_rank;
+    int64_t out_dims[AURA_AI_MAX_RANK];
+    uint32_t out_rank;
+} AuraModel;
+
+static AuraModel g_models[AURA_MAX_MODELS];
 static uint32_t g_next_model = 1u; // 0 is reserved as "invalid".
+static const AuraAiBackend* g_ai_backend = NULL;
 
 void aura_io_println(const char* s) {
     if (!s) {
@@ -68,6 +144,291 @@ void aura_io_println(const char* s) {
     puts(s);
 }
 
+static char* aura_str_alloc(size_t bytes) {
+#if defined(AURA_ALLOC_REGION)
+    // Aligned for the AuraStrHeader of length-prefixed strings.
+    return (char*)aura_arena_alloc(bytes, sizeof(AuraStrHeader));
+#else
+    char* p = (char*)malloc(bytes);
+    if (!p) {
+        fprintf(stderr, "Aura string allocation failed: %zu bytes\n", bytes);
+        fflush(stderr);
+        abort();
+    }
+    return p;
+#endif
+}
+
+const char* aura_str_concat(const char* a, const char* b) {
+    if (!a) {
+        a = "";
+    }
+    if (!b) {
+        b = "";
+    }
+    size_t la = strlen(a);
+    size_t lb = strlen(b);
+    char* out = aura_str_alloc(la + lb + 1u);
+    memcpy(out, a, la);
+    memcpy(out + la, b, lb);
+    out[la + lb] = '\0';
+    return out;
+}
+
+const char* aura_str_from_u32(uint32_t v) {
+    char* out = aura_str_alloc(11u);
+    snprintf(out, 11u, "%u", (unsigned)v);
+    return out;
+}
+
+const char* aura_str_from_bool(bool v) {
+    return v ? "true" : "false";
+}
+
+const char* aura_str_from_f64(double v) {
+    // %.17g round-trips every double; 32 bytes covers sign, exponent, and NaN/inf.
+    char* out = aura_str_alloc(32u);
+    snprintf(out, 32u, "%.17g", v);
+    return out;
+}
+
+// ---- Length-prefixed strings ----
+
+// A small string occupies one cell: its header, up to AURA_STR_SMALL_MAX bytes and the NUL.
+#define AURA_STR_CELL_BYTES (sizeof(AuraStrHeader) + AURA_STR_SMALL_MAX + 1u)
+#define AURA_STR_SLAB_CELLS 256u
+
+typedef struct AuraStaticStr6 {
+    AuraStrHeader header;
+    char data[6];
+} AuraStaticStr6;
+
+static const AuraStaticStr6 g_aura_str_empty = {{0u, AURA_STR_STATIC}, ""};
+static const AuraStaticStr6 g_aura_str_true = {{4u, AURA_STR_STATIC}, "true"};
+static const AuraStaticStr6 g_aura_str_false = {{5u, AURA_STR_STATIC}, "false"};
+
+// Each thread (scheduler workers included) fills its own slab, so small strings need no lock.
+static _Thread_local unsigned char* t_aura_str_slab = NULL;
+static _Thread_local size_t t_aura_str_slab_used = 0u;
+
+static AuraStrHeader* aura_string_header(const char* s) {
+    return (AuraStrHeader*)(s - sizeof(AuraStrHeader));
+}
+
+// Allocates a string of `len` bytes plus its NUL; the caller fills in the bytes.
+static char* aura_string_new(size_t len) {
+    if (len > (size_t)UINT32_MAX) {
+        fprintf(stderr, "Aura string too long: %zu bytes\n", len);
+        fflush(stderr);
+        abort();
+    }
+    AuraStrHeader* h;
+    if (len <= AURA_STR_SMALL_MAX) {
+        if (!t_aura_str_slab || t_aura_str_slab_used == AURA_STR_SLAB_CELLS) {
+            t_aura_str_slab = (unsigned char*)aura_str_alloc(AURA_STR_CELL_BYTES * AURA_STR_SLAB_CELLS);
+            t_aura_str_slab_used = 0u;
+        }
+        h = (AuraStrHeader*)(t_aura_str_slab + AURA_STR_CELL_BYTES * t_aura_str_slab_used);
+        t_aura_str_slab_used += 1u;
+        h->flags = AURA_STR_SMALL;
+    } else {
+        h = (AuraStrHeader*)aura_str_alloc(sizeof(AuraStrHeader) + len + 1u);
+        h->flags = 0u;
+    }
+    h->len = (uint32_t)len;
+    char* data = (char*)(h + 1);
+    data[len] = '\0';
+    return data;
+}
+
+static const char* aura_string_from_bytes(const char* bytes, size_t len) {
+    if (len == 0u) {
+        return g_aura_str_empty.data;
+    }
+    char* out = aura_string_new(len);
+    memcpy(out, bytes, len);
+    return out;
+}
+
+uint32_t aura_string_len(const char* s) {
+    return s ? aura_string_header(s)->len : 0u;
+}
+
+const char* aura_string_concat(const char* a, const char* b) {
+    uint32_t la = aura_string_len(a);
+    uint32_t lb = aura_string_len(b);
+    // Strings are immutable, so an empty side lets the other be shared.
+    if (lb == 0u) {
+        return a ? a : g_aura_str_empty.data;
+    }
+    if (la == 0u) {
+        return b;
+    }
+    char* out = aura_string_new((size_t)la + (size_t)lb);
+    memcpy(out, a, la);
+    memcpy(out + la, b, lb);
+    return out;
+}
+
+const char* aura_string_from_u32(uint32_t v) {
+    char buf[11];
+    int n = snprintf(buf, sizeof buf, "%u", (unsigned)v);
+    return aura_string_from_bytes(buf, (size_t)n);
+}
+
+const char* aura_string_from_u64(uint64_t v) {
+    char buf[21];
+    int n = snprintf(buf, sizeof buf, "%llu", (unsigned long long)v);
+    return aura_string_from_bytes(buf, (size_t)n);
+}
+
+const char* aura_string_from_i64(int64_t v) {
+    char buf[21];
+    int n = snprintf(buf, sizeof buf, "%lld", (long long)v);
+    return aura_string_from_bytes(buf, (size_t)n);
+}
+
+const char* aura_string_from_bool(bool v) {
+    return v ? g_aura_str_true.data : g_aura_str_false.data;
+}
+
+const char* aura_string_from_f64(double v) {
+    char buf[32];
+    int n = snprintf(buf, sizeof buf, "%.17g", v);
+    return aura_string_from_bytes(buf, (size_t)n);
+}
+
+const char* aura_string_slice(const char* s, uint32_t start, uint32_t end) {
+    uint32_t len = aura_string_len(s);
+    if (end > len) {
+        end = len;
+    }
+    if (start >= end) {
+        return g_aura_str_empty.data;
+    }
+    if (start == 0u && end == len) {
+        return s;
+    }
+    return aura_string_from_bytes(s + start, (size_t)(end - start));
+}
+
+const char* aura_string_adopt(const char* s) {
+    if (!s) {
+        return g_aura_str_empty.data;
+    }
+    return aura_string_from_bytes(s, strlen(s));
+}
+
+// First byte offset of `needle` in `s` at or after `from`, or the length of `s`.
+static uint32_t aura_string_find_from(const char* s, uint32_t len, const char* needle, uint32_t from) {
+    uint32_t n = aura_string_len(needle);
+    if (n > len) {
+        return len;
+    }
+    for (uint32_t i = from; i + n <= len; i++) {
+        if (memcmp(s + i, needle, n) == 0) {
+            return i;
+        }
+    }
+    return len;
+}
+
+uint32_t aura_string_find(const char* s, const char* needle) {
+    return aura_string_find_from(s, aura_string_len(s), needle, 0u);
+}
+
+uint32_t aura_string_split_count(const char* s, const char* sep) {
+    uint32_t len = aura_string_len(s);
+    uint32_t n = aura_string_len(sep);
+    if (n == 0u) {
+        return 1u;
+    }
+    uint32_t count = 1u;
+    uint32_t at = aura_string_find_from(s, len, sep, 0u);
+    while (at < len) {
+        count++;
+        at = aura_string_find_from(s, len, sep, at + n);
+    }
+    return count;
+}
+
+const char* aura_string_split_nth(const char* s, const char* sep, uint32_t index) {
+    uint32_t len = aura_string_len(s);
+    uint32_t n = aura_string_len(sep);
+    uint32_t start = 0u;
+    uint32_t end = n == 0u ? len : aura_string_find_from(s, len, sep, 0u);
+    for (uint32_t i = 0u; i < index; i++) {
+        if (n == 0u || end == len) {
+            fprintf(stderr, "Aura str.split_nth: field %u of a string with %u fields\n", (unsigned)index,
+                    (unsigned)(i + 1u));
+            fflush(stderr);
+            abort();
+        }
+        start = end + n;
+        end = aura_string_find_from(s, len, sep, start);
+    }
+    return aura_string_slice(s, start, end);
+}
+
+const char* aura_string_trim(const char* s) {
+    uint32_t start = 0u;
+    uint32_t end = aura_string_len(s);
+    while (start < end && (s[start] == ' ' || (s[start] >= '\t' && s[start] <= '\r'))) {
+        start++;
+    }
+    while (end > start && (s[end - 1u] == ' ' || (s[end - 1u] >= '\t' && s[end - 1u] <= '\r'))) {
+        end--;
+    }
+    return aura_string_slice(s, start, end);
+}
+
+static const char* aura_string_pad(const char* s, uint32_t width, bool left) {
+    uint32_t len = aura_string_len(s);
+    if (len >= width) {
+        return s;
+    }
+    char* out = aura_string_new(width);
+    size_t fill = (size_t)(width - len);
+    memset(left ? out : out + len, ' ', fill);
+    memcpy(left ? out + fill : out, s, len);
+    return out;
+}
+
+const char* aura_string_pad_left(const char* s, uint32_t width) {
+    return aura_string_pad(s, width, true);
+}
+
+const char* aura_string_pad_right(const char* s, uint32_t width) {
+    return aura_string_pad(s, width, false);
+}
+
+const char* aura_time_format_utc(uint32_t unix_s) {
+    // Civil date of a day count (Howard Hinnant's days_from_civil, inverted), in
+    // 400-year eras of 146097 days starting on 0000-03-01.
+    uint32_t days = unix_s / 86400u;
+    uint32_t secs = unix_s % 86400u;
+    uint32_t z = days + 719468u;
+    uint32_t era = z / 146097u;
+    uint32_t doe = z - era * 146097u;
+    uint32_t yoe = (doe - doe / 1460u + doe / 36524u - doe / 146096u) / 365u;
+    uint32_t doy = doe - (365u * yoe + yoe / 4u - yoe / 100u);
+    uint32_t mp = (5u * doy + 2u) / 153u;
+    uint32_t day = doy - (153u * mp + 2u) / 5u + 1u;
+    uint32_t month = mp < 10u ? mp + 3u : mp - 9u;
+    uint32_t year = yoe + era * 400u + (month <= 2u ? 1u : 0u);
+    char buf[24];
+    int n = snprintf(buf, sizeof buf, "%04u-%02u-%02uT%02u:%02u:%02uZ", (unsigned)year, (unsigned)month,
+                     (unsigned)day, (unsigned)(secs / 3600u), (unsigned)(secs / 60u % 60u), (unsigned)(secs % 60u));
+    return aura_string_from_bytes(buf, (size_t)n);
+}
+
+const char* aura_time_format_ms(uint32_t ms) {
+    char buf[24];
+    int n = snprintf(buf, sizeof buf, "%02u:%02u:%02u.%03u", (unsigned)(ms / 3600000u),
+                     (unsigned)(ms / 60000u % 60u), (unsigned)(ms / 1000u % 60u), (unsigned)(ms % 1000u));
+    return aura_string_from_bytes(buf, (size_t)n);
+}
+
 void aura_range_check_u32(uint32_t v, uint32_t lo, uint32_t hi) {
     if (v < lo || v > hi) {
         fprintf(stderr, "Aura range check failed: %u not in [%u..%u]\n", (unsigned)v, (unsigned)lo, (unsigned)hi);
@@ -76,12 +437,34 @@ void aura_range_check_u32(uint32_t v, uint32_t lo, uint32_t hi) {
     }
 }
 
+uint32_t aura_math_pow_u32(uint32_t base, uint32_t exp) {
+    // Square-and-multiply: once the square passes u32 and bits remain, so does the result.
+    uint64_t acc = 1u;
+    uint64_t sq = base;
+    bool overflow = false;
+    for (uint32_t e = exp; e != 0u && !overflow; e >>= 1) {
+        overflow = sq > UINT32_MAX;
+        if (e & 1u) {
+            acc *= sq;
+        }
+        sq *= sq;
+    }
+    if (overflow || acc > UINT32_MAX) {
+        fprintf(stderr, "Aura math.pow: %u^%u does not fit a u32\n", (unsigned)base, (unsigned)exp);
+        fflush(stderr);
+        abort();
+    }
+    return (uint32_t)acc;
+}
+
 uint32_t aura_tensor_new(uint32_t len) {
     if (g_next_tensor >= AURA_MAX_TENSORS) {
         return 0u;
     }
     uint32_t h = g_next_tensor++;
     g_tensors[h].len = len;
+    g_tensors[h].region = aura_region_current();
+    g_tensors[h].freed = false;
 #if defined(AURA_ALLOC_REGION)
     g_tensors[h].data = (uint32_t*)aura_alloc_zeroed((size_t)len, sizeof(uint32_t));
 #else
@@ -97,6 +480,13 @@ uint32_t aura_tensor_len(uint32_t t) {
     return g_tensors[t].len;
 }
 
+uint32_t* aura_tensor_data(uint32_t t) {
+    if (t == 0u || t >= g_next_tensor) {
+        return NULL;
+    }
+    return g_tensors[t].data;
+}
+
 uint32_t aura_tensor_get(uint32_t t, uint32_t index) {
     if (t == 0u || t >= g_next_tensor) {
         return 0u;
@@ -117,28 +507,807 @@ void aura_tensor_set(uint32_t t, uint32_t index, uint32_t value) {
     g_tensors[t].data[index] = value;
 }
 
-uint32_t aura_ai_load_model(const char* path) {
+#if defined(AURA_X86)
+static void aura_cpuid(uint32_t leaf, uint32_t sub, uint32_t r[4]) {
+#if defined(_MSC_VER)
+    int v[4];
+    __cpuidex(v, (int)leaf, (int)sub);
+    for (int i = 0; i < 4; i++) {
+        r[i] = (uint32_t)v[i];
+    }
+#else
+    __cpuid_count(leaf, sub, r[0], r[1], r[2], r[3]);
+#endif
+}
+
+// XCR0: the register state the OS saves on context switches.
+static uint64_t aura_xcr0(void) {
+#if defined(_MSC_VER)
+    return (uint64_t)_xgetbv(0);
+#else
+    uint32_t lo, hi;
+    __asm__ volatile("xgetbv" : "=a"(lo), "=d"(hi) : "c"(0));
+    return ((uint64_t)hi << 32) | lo;
+#endif
+}
+#endif
+
+uint32_t aura_cpu_features(void) {
+    // Every caller computes the same word, so threads racing to fill the cache agree.
+    static volatile int32_t cached = -1;
+    if (cached >= 0) {
+        return (uint32_t)cached;
+    }
+    uint32_t features = 0u;
+#if defined(AURA_X86)
+    uint32_t r[4];
+    aura_cpuid(0u, 0u, r);
+    uint32_t max_leaf = r[0];
+    aura_cpuid(1u, 0u, r);
+    bool osxsave = (r[2] >> 27) & 1u;
+    if (max_leaf >= 7u && osxsave) {
+        uint64_t xcr0 = aura_xcr0();
+        aura_cpuid(7u, 0u, r);
+        // AVX512F, DQ, BW and VL (EBX bits 16, 17, 30, 31), with the SSE, AVX, opmask and
+        // ZMM state saved (XCR0 bits 1, 2 and 5-7).
+        uint32_t avx512 = (1u << 16) | (1u << 17) | (1u << 30) | (1u << 31);
+        if ((r[1] & avx512) == avx512 && (xcr0 & 0xE6u) == 0xE6u) {
+            features |= AURA_CPU_AVX512;
+        }
+        // AMX-TILE (EDX bit 24), with the tile state saved (XCR0 bits 17 and 18).
+        if ((features & AURA_CPU_AVX512) && ((r[3] >> 24) & 1u) && (xcr0 & 0x60000u) == 0x60000u) {
+#if defined(__linux__)
+            // Linux only lets processes that ask for it (ARCH_REQ_XCOMP_PERM) use tile data.
+            if (syscall(SYS_arch_prctl, 0x1023, 18) == 0) {
+                features |= AURA_CPU_AMX;
+            }
+#else
+            features |= AURA_CPU_AMX;
+#endif
+        }
+    }
+#endif
+    cached = (int32_t)features;
+    return features;
+}
+
+uint32_t aura_map_new(uint32_t capacity) {
+    if (g_next_map >= AURA_MAX_MAPS) {
+        return 0u;
+    }
+    uint32_t h = g_next_map++;
+    if (capacity == 0u) {
+        capacity = 4u;
+    }
+    g_maps[h].len = 0u;
+    g_maps[h].cap = capacity;
+    g_maps[h].region = aura_region_current();
+    g_maps[h].freed = false;
+#if defined(AURA_ALLOC_REGION)
+    g_maps[h].entries = (AuraMapEntry*)aura_alloc_zeroed((size_t)capacity, sizeof(AuraMapEntry));
+#else
+    g_maps[h].entries = (AuraMapEntry*)calloc((size_t)capacity, sizeof(AuraMapEntry));
+#endif
+    return h;
+}
+
+uint32_t aura_map_len(uint32_t m) {
+    if (m == 0u || m >= g_next_map) {
+        return 0u;
+    }
+    return g_maps[m].len;
+}
+
+static AuraMapEntry* aura_map_find(uint32_t m, const char* skey, uint32_t ukey) {
+    if (m == 0u || m >= g_next_map) {
+        return NULL;
+    }
+    AuraMap* map = &g_maps[m];
+    for (uint32_t i = 0u; i < map->len; i++) {
+        AuraMapEntry* e = &map->entries[i];
+        if (skey ? (e->skey && strcmp(e->skey, skey) == 0) : (!e->skey && e->ukey == ukey)) {
+            return e;
+        }
+    }
+    return NULL;
+}
+
+static void aura_map_put(uint32_t m, const char* skey, uint32_t ukey, uint32_t value) {
+    if (m == 0u || m >= g_next_map) {
+        return;
+    }
+    AuraMapEntry* existing = aura_map_find(m, skey, ukey);
+    if (existing) {
+        existing->value = value;
+        return;
+    }
+    AuraMap* map = &g_maps[m];
+    if (map->len == map->cap) {
+        uint32_t cap = map->cap * 2u;
+#if defined(AURA_ALLOC_REGION)
+        AuraMapEntry* grown = (AuraMapEntry*)aura_alloc_zeroed((size_t)cap, sizeof(AuraMapEntry));
+        memcpy(grown, map->entries, (size_t)map->len * sizeof(AuraMapEntry));
+#else
+        AuraMapEntry* grown = (AuraMapEntry*)realloc(map->entries, (size_t)cap * sizeof(AuraMapEntry));
+        if (!grown) {
+            fprintf(stderr, "Aura map allocation failed: %u entries\n", (unsigned)cap);
+            fflush(stderr);
+            abort();
+        }
+#endif
+        map->entries = grown;
+        map->cap = cap;
+    }
+    map->entries[map->len].skey = skey;
+    map->entries[map->len].ukey = ukey;
+    map->entries[map->len].value = value;
+    map->len++;
+}
+
+void aura_map_insert(uint32_t m, uint32_t key, uint32_t value) {
+    aura_map_put(m, NULL, key, value);
+}
+
+void aura_map_insert_str(uint32_t m, const char* key, uint32_t value) {
+    aura_map_put(m, key ? key : "", 0u, value);
+}
+
+uint32_t aura_map_get(uint32_t m, uint32_t key) {
+    AuraMapEntry* e = aura_map_find(m, NULL, key);
+    return e ? e->value : 0u;
+}
+
+uint32_t aura_map_get_str(uint32_t m, const char* key) {
+    AuraMapEntry* e = aura_map_find(m, key ? key : "", 0u);
+    return e ? e->value : 0u;
+}
+
+bool aura_map_contains(uint32_t m, uint32_t key) {
+    return aura_map_find(m, NULL, key) != NULL;
+}
+
+bool aura_map_contains_str(uint32_t m, const char* key) {
+    return aura_map_find(m, key ? key : "", 0u) != NULL;
+}
+
+static void aura_region_push(uint32_t r) {
+    if (t_region_depth == AURA_MAX_REGION_DEPTH) {
+        fprintf(stderr, "Aura regions nested more than %u deep\n", (unsigned)AURA_MAX_REGION_DEPTH);
+        fflush(stderr);
+        abort();
+    }
+    t_regions[t_region_depth++] = r;
+}
+
+static void aura_region_pop(uint32_t r) {
+    if (t_region_depth == 0u || t_regions[t_region_depth - 1u] != r) {
+        fprintf(stderr, "Aura region %u is not the innermost one open\n", (unsigned)r);
+        fflush(stderr);
+        abort();
+    }
+    t_region_depth--;
+}
+
+uint32_t aura_region_enter(void) {
+    uint32_t r = atomic_fetch_add(&g_next_region, 1u);
+    aura_region_push(r);
+    return r;
+}
+
+void aura_region_exit(uint32_t r) {
+    aura_region_pop(r);
+    for (uint32_t h = 1u; h < g_next_tensor; h++) {
+        AuraTensor* t = &g_tensors[h];
+        if (t->freed || t->region != r) {
+            continue;
+        }
+#if !defined(AURA_ALLOC_REGION)
+        free(t->data);
+#endif
+        t->data = NULL;
+        t->len = 0u;
+        t->freed = true;
+    }
+    while (g_next_tensor > 1u && g_tensors[g_next_tensor - 1u].freed) {
+        g_next_tensor--;
+    }
+    for (uint32_t h = 1u; h < g_next_map; h++) {
+        AuraMap* m = &g_maps[h];
+        if (m->freed || m->region != r) {
+            continue;
+        }
+#if !defined(AURA_ALLOC_REGION)
+        free(m->entries);
+#endif
+        m->entries = NULL;
+        m->len = 0u;
+        m->cap = 0u;
+        m->freed = true;
+    }
+    while (g_next_map > 1u && g_maps[g_next_map - 1u].freed) {
+        g_next_map--;
+    }
+}
+
+void aura_region_detach(void) {
+    aura_region_push(0u);
+}
+
+void aura_region_reattach(void) {
+    aura_region_pop(0u);
+}
+
+#define AURA_MAX_FILES 1024u
+#define AURA_FS_PATH_MAX 4096u
+
+typedef struct AuraFile {
+    FILE* fp; // NULL once closed.
+    const char* path;
+    bool writable;
+} AuraFile;
+
+static AuraFile g_files[AURA_MAX_FILES];
+static atomic_uint g_next_file = 1u; // 0 is reserved as "invalid".
+
+static void aura_fs_trap(const char* op, const char* path, const char* why) {
+    fprintf(stderr, "Aura %s failed for '%s': %s\n", op, path ? path : "<null>", why);
+    fflush(stderr);
+    abort();
+}
+
+#if defined(AURA_FS_POLICY)
+#ifndef AURA_FS_READ_ROOTS
+#define AURA_FS_READ_ROOTS
+#endif
+#ifndef AURA_FS_WRITE_ROOTS
+#define AURA_FS_WRITE_ROOTS
+#endif
+// The [fs] table of aura.toml, as absolute paths the driver passes in (each one followed by a comma).
+static const char* const g_fs_read_roots[] = {AURA_FS_READ_ROOTS NULL};
+static const char* const g_fs_write_roots[] = {AURA_FS_WRITE_ROOTS NULL};
+
+static bool aura_fs_is_sep(char c) {
+    return c == '/' || c == '\\';
+}
+
+static bool aura_fs_cwd(char* buf, size_t cap) {
+#if defined(_WIN32)
+    return _getcwd(buf, (int)cap) != NULL;
+#else
+    return getcwd(buf, cap) != NULL;
+#endif
+}
+
+// Resolves `path` against the working directory and drops its `.` and `..` components,
+// writing it with `/` separators. Returns false when it does not fit `cap` bytes.
+static bool aura_fs_normalize(const char* path, char* out, size_t cap) {
+    char joined[AURA_FS_PATH_MAX];
+    bool drive = ((path[0] >= 'A' && path[0] <= 'Z') || (path[0] >= 'a' && path[0] <= 'z')) && path[1] == ':';
+    int n;
+    if (drive || aura_fs_is_sep(path[0])) {
+        n = snprintf(joined, sizeof joined, "%s", path);
+    } else {
+        char cwd[AURA_FS_PATH_MAX];
+        if (!aura_fs_cwd(cwd, sizeof cwd)) {
+            return false;
+        }
+        n = snprintf(joined, sizeof joined, "%s/%s", cwd, path);
+    }
+    if (n < 0 || (size_t)n >= sizeof joined || cap < 3u) {
+        return false;
+    }
+
+    const char* p = joined;
+    size_t len = 0u;
+    if (p[0] != '\0' && p[1] == ':') {
+        out[len++] = p[0];
+        out[len++] = ':';
+        p += 2;
+    }
+    size_t root = len;
+    while (*p) {
+        while (aura_fs_is_sep(*p)) {
+            p++;
+        }
+        const char* part = p;
+        while (*p && !aura_fs_is_sep(*p)) {
+            p++;
+        }
+        size_t part_len = (size_t)(p - part);
+        if (part_len == 0u || (part_len == 1u && part[0] == '.')) {
+            continue;
+        }
+        if (part_len == 2u && part[0] == '.' && part[1] == '.') {
+            while (len > root && out[len - 1u] != '/') {
+                len--;
+            }
+            if (len > root) {
+                len--;
+            }
+            continue;
+        }
+        if (len + 1u + part_len + 1u > cap) {
+            return false;
+        }
+        out[len++] = '/';
+        memcpy(out + len, part, part_len);
+        len += part_len;
+    }
+    if (len == root) {
+        out[len++] = '/';
+    }
+    out[len] = '\0';
+    return true;
+}
+
+// Whether the policy has an entry at or above `path` for its access.
+static bool aura_fs_allowed(const char* path, bool writable) {
+    char want[AURA_FS_PATH_MAX];
+    if (!aura_fs_normalize(path, want, sizeof want)) {
+        return false;
+    }
+    for (const char* const* r = writable ? g_fs_write_roots : g_fs_read_roots; *r; r++) {
+        char root[AURA_FS_PATH_MAX];
+        if (!aura_fs_normalize(*r, root, sizeof root)) {
+            continue;
+        }
+        size_t n = strlen(root);
+        if (strncmp(want, root, n) == 0 && (want[n] == '\0' || want[n] == '/' || root[n - 1u] == '/')) {
+            return true;
+        }
+    }
+    return false;
+}
+#else
+static bool aura_fs_allowed(const char* path, bool writable) {
     (void)path;
+    (void)writable;
+    return true;
+}
+#endif
+
+static uint32_t aura_fs_open_as(const char* op, const char* path, const char* mode, bool writable) {
+    if (!path) {
+        aura_fs_trap(op, path, "no path");
+    }
+    if (!aura_fs_allowed(path, writable)) {
+        aura_fs_trap(op, path, writable ? "the [fs] policy of aura.toml does not allow writing it"
+                                        : "the [fs] policy of aura.toml does not allow reading it");
+    }
+    uint32_t f = atomic_fetch_add(&g_next_file, 1u);
+    if (f >= AURA_MAX_FILES) {
+        aura_fs_trap(op, path, "too many files were opened");
+    }
+    FILE* fp = fopen(path, mode);
+    if (!fp) {
+        aura_fs_trap(op, path, strerror(errno));
+    }
+    g_files[f] = (AuraFile){fp, path, writable};
+    return f;
+}
+
+static AuraFile* aura_fs_file(const char* op, uint32_t f) {
+    if (f == 0u || f >= AURA_MAX_FILES || f >= atomic_load(&g_next_file)) {
+        fprintf(stderr, "Aura %s: unknown file %u\n", op, (unsigned)f);
+        fflush(stderr);
+        abort();
+    }
+    AuraFile* file = &g_files[f];
+    if (!file->fp) {
+        fprintf(stderr, "Aura %s: file %u was used after it was closed\n", op, (unsigned)f);
+        fflush(stderr);
+        abort();
+    }
+    return file;
+}
+
+uint32_t aura_fs_open(const char* path) {
+    return aura_fs_open_as("fs.open", path, "rb", false);
+}
+
+uint32_t aura_fs_create(const char* path) {
+    return aura_fs_open_as("fs.create", path, "wb", true);
+}
+
+uint32_t aura_fs_append(const char* path) {
+    return aura_fs_open_as("fs.append", path, "ab", true);
+}
+
+const char* aura_fs_read(uint32_t f) {
+    AuraFile* file = aura_fs_file("fs.read", f);
+    if (file->writable) {
+        aura_fs_trap("fs.read", file->path, "the file was opened for writing");
+    }
+    size_t cap = 4096u;
+    size_t len = 0u;
+    char* buf = (char*)malloc(cap);
+    if (!buf) {
+        aura_fs_trap("fs.read", file->path, "out of memory");
+    }
+    size_t n;
+    while ((n = fread(buf + len, 1u, cap - len, file->fp)) > 0u) {
+        len += n;
+        if (len == cap) {
+            cap *= 2u;
+            char* grown = (char*)realloc(buf, cap);
+            if (!grown) {
+                free(buf);
+                aura_fs_trap("fs.read", file->path, "out of memory");
+            }
+            buf = grown;
+        }
+    }
+    if (ferror(file->fp)) {
+        free(buf);
+        aura_fs_trap("fs.read", file->path, strerror(errno));
+    }
+    const char* out = aura_string_from_bytes(buf, len);
+    free(buf);
+    return out;
+}
+
+void aura_fs_write(uint32_t f, const char* text) {
+    AuraFile* file = aura_fs_file("fs.write", f);
+    if (!file->writable) {
+        aura_fs_trap("fs.write", file->path, "the file was opened for reading");
+    }
+    size_t len = aura_string_len(text);
+    if (len > 0u && fwrite(text, 1u, len, file->fp) != len) {
+        aura_fs_trap("fs.write", file->path, strerror(errno));
+    }
+}
+
+void aura_fs_close(uint32_t f) {
+    AuraFile* file = aura_fs_file("fs.close", f);
+    if (fclose(file->fp) != 0) {
+        file->fp = NULL;
+        aura_fs_trap("fs.close", file->path, strerror(errno));
+    }
+    file->fp = NULL;
+}
+
+#define AURA_MAX_SOCKETS 1024u
+#define AURA_NET_RECV_BYTES (64u * 1024u)
+
+#if defined(_WIN32)
+typedef SOCKET AuraSocketFd;
+#define AURA_SOCKET_INVALID INVALID_SOCKET
+#define aura_socket_close closesocket
+#else
+typedef int AuraSocketFd;
+#define AURA_SOCKET_INVALID (-1)
+#define aura_socket_close close
+#endif
+
+// The states of aura-core's NetworkVerifier a client socket moves through. A connected
+// socket sends and receives in any order; once closed it cannot be used again.
+typedef enum AuraSocketState {
+    AURA_SOCKET_OPEN = 1,
+    AURA_SOCKET_READING,
+    AURA_SOCKET_WRITING,
+    AURA_SOCKET_CLOSED,
+} AuraSocketState;
+
+typedef struct AuraSocket {
+    AuraSocketFd fd;
+    AuraSocketState state;
+    char peer[288]; // "host:port", for messages.
+} AuraSocket;
+
+static AuraSocket g_sockets[AURA_MAX_SOCKETS];
+static atomic_uint g_next_socket = 1u; // 0 is reserved as "invalid".
+
+static void aura_net_trap(const char* op, const char* peer, const char* why) {
+    fprintf(stderr, "Aura %s failed for '%s': %s\n", op, peer, why);
+    fflush(stderr);
+    abort();
+}
+
+static const char* aura_net_error(void) {
+#if defined(_WIN32)
+    static _Thread_local char buf[32];
+    snprintf(buf, sizeof buf, "winsock error %d", WSAGetLastError());
+    return buf;
+#else
+    return strerror(errno);
+#endif
+}
+
+static const char* aura_net_state_name(AuraSocketState state) {
+    switch (state) {
+    case AURA_SOCKET_OPEN:
+        return "Open";
+    case AURA_SOCKET_READING:
+        return "Reading";
+    case AURA_SOCKET_WRITING:
+        return "Writing";
+    case AURA_SOCKET_CLOSED:
+        return "Closed";
+    }
+    return "Uninitialized";
+}
+
+// Moves socket `s` to `to`, trapping like the verifier rejects a step out of Closed.
+static AuraSocket* aura_net_socket(const char* op, uint32_t s, AuraSocketState to) {
+    if (s == 0u || s >= AURA_MAX_SOCKETS || s >= atomic_load(&g_next_socket)) {
+        fprintf(stderr, "Aura %s: unknown socket %u\n", op, (unsigned)s);
+        fflush(stderr);
+        abort();
+    }
+    AuraSocket* sock = &g_sockets[s];
+    if (sock->state == AURA_SOCKET_CLOSED) {
+        fprintf(stderr, "Aura %s: socket %u: Invalid socket transition Closed -> %s\n", op, (unsigned)s,
+                aura_net_state_name(to));
+        fflush(stderr);
+        abort();
+    }
+    sock->state = to;
+    return sock;
+}
+
+uint32_t aura_net_connect(const char* host, uint32_t port) {
+    char peer[sizeof g_sockets[0].peer];
+    snprintf(peer, sizeof peer, "%s:%u", host ? host : "<null>", (unsigned)port);
+    if (!host || port == 0u || port > 65535u) {
+        aura_net_trap("net.connect", peer, "no such host or port");
+    }
+#if defined(_WIN32)
+    // Started once and never cleaned up; the process exit releases Winsock.
+    static atomic_flag started = ATOMIC_FLAG_INIT;
+    WSADATA wsa;
+    if (!atomic_flag_test_and_set(&started) && WSAStartup(MAKEWORD(2, 2), &wsa) != 0) {
+        aura_net_trap("net.connect", peer, "Winsock is unavailable");
+    }
+#endif
+    char service[8];
+    snprintf(service, sizeof service, "%u", (unsigned)port);
+    struct addrinfo hints;
+    memset(&hints, 0, sizeof hints);
+    hints.ai_family = AF_UNSPEC;
+    hints.ai_socktype = SOCK_STREAM;
+    struct addrinfo* addrs = NULL;
+    int rc = getaddrinfo(host, service, &hints, &addrs);
+    if (rc != 0) {
+        aura_net_trap("net.connect", peer, gai_strerror(rc));
+    }
+    AuraSocketFd fd = AURA_SOCKET_INVALID;
+    for (struct addrinfo* a = addrs; a; a = a->ai_next) {
+        fd = socket(a->ai_family, a->ai_socktype, a->ai_protocol);
+        if (fd == AURA_SOCKET_INVALID) {
+            continue;
+        }
+        if (connect(fd, a->ai_addr, (int)a->ai_addrlen) == 0) {
+            break;
+        }
+        aura_socket_close(fd);
+        fd = AURA_SOCKET_INVALID;
+    }
+    freeaddrinfo(addrs);
+    if (fd == AURA_SOCKET_INVALID) {
+        aura_net_trap("net.connect", peer, aura_net_error());
+    }
+
+    uint32_t s = atomic_fetch_add(&g_next_socket, 1u);
+    if (s >= AURA_MAX_SOCKETS) {
+        aura_net_trap("net.connect", peer, "too many sockets were opened");
+    }
+    g_sockets[s].fd = fd;
+    g_sockets[s].state = AURA_SOCKET_OPEN;
+    memcpy(g_sockets[s].peer, peer, sizeof peer);
+    return s;
+}
+
+void aura_net_send(uint32_t s, const char* data) {
+    AuraSocket* sock = aura_net_socket("net.send", s, AURA_SOCKET_WRITING);
+    size_t len = aura_string_len(data);
+    size_t sent = 0u;
+    while (sent < len) {
+        size_t chunk = len - sent > 0x7fffffffu ? 0x7fffffffu : len - sent;
+#if defined(MSG_NOSIGNAL)
+        // A peer that hung up fails the call instead of raising SIGPIPE.
+        long n = (long)send(sock->fd, data + sent, chunk, MSG_NOSIGNAL);
+#else
+        long n = (long)send(sock->fd, data + sent, (int)chunk, 0);
+#endif
+        if (n <= 0) {
+            aura_net_trap("net.send", sock->peer, aura_net_error());
+        }
+        sent += (size_t)n;
+    }
+}
+
+const char* aura_net_recv(uint32_t s) {
+    AuraSocket* sock = aura_net_socket("net.recv", s, AURA_SOCKET_READING);
+    char* buf = (char*)malloc(AURA_NET_RECV_BYTES);
+    if (!buf) {
+        aura_net_trap("net.recv", sock->peer, "out of memory");
+    }
+    long n = (long)recv(sock->fd, buf, (int)AURA_NET_RECV_BYTES, 0);
+    if (n < 0) {
+        free(buf);
+        aura_net_trap("net.recv", sock->peer, aura_net_error());
+    }
+    const char* out = aura_string_from_bytes(buf, (size_t)n);
+    free(buf);
+    return out;
+}
+
+const char* aura_net_recv_all(uint32_t s) {
+    AuraSocket* sock = aura_net_socket("net.recv_all", s, AURA_SOCKET_READING);
+    size_t cap = AURA_NET_RECV_BYTES;
+    size_t len = 0u;
+    char* buf = (char*)malloc(cap);
+    if (!buf) {
+        aura_net_trap("net.recv_all", sock->peer, "out of memory");
+    }
+    for (;;) {
+        if (len == cap) {
+            cap *= 2u;
+            char* grown = (char*)realloc(buf, cap);
+            if (!grown) {
+                free(buf);
+                aura_net_trap("net.recv_all", sock->peer, "out of memory");
+            }
+            buf = grown;
+        }
+        size_t room = cap - len > 0x7fffffffu ? 0x7fffffffu : cap - len;
+        long n = (long)recv(sock->fd, buf + len, (int)room, 0);
+        if (n < 0) {
+            free(buf);
+            aura_net_trap("net.recv_all", sock->peer, aura_net_error());
+        }
+        if (n == 0) {
+            break;
+        }
+        len += (size_t)n;
+    }
+    const char* out = aura_string_from_bytes(buf, len);
+    free(buf);
+    return out;
+}
+
+void aura_net_close(uint32_t s) {
+    AuraSocket* sock = aura_net_socket("net.close", s, AURA_SOCKET_CLOSED);
+    aura_socket_close(sock->fd);
+    sock->fd = AURA_SOCKET_INVALID;
+}
+
+void aura_ai_register_backend(const AuraAiBackend* backend) {
+    g_ai_backend = backend;
+}
+
+static void aura_ai_trap(const char* op, const char* path, const char* why) {
+    fprintf(stderr, "Aura %s failed for model '%s': %s\n", op, path ? path : "<null>", why);
+    fflush(stderr);
+    abort();
+}
+
+static void aura_ai_format_shape(char* buf, size_t cap, const int64_t* dims, uint32_t rank) {
+    size_t off = (size_t)snprintf(buf, cap, "[");
+    for (uint32_t i = 0u; i < rank && off < cap; i++) {
+        if (dims[i] < 0) {
+            off += (size_t)snprintf(buf + off, cap - off, "%s?", i ? ", " : "");
+        } else {
+            off += (size_t)snprintf(buf + off, cap - off, "%s%lld", i ? ", " : "", (long long)dims[i]);
+        }
+    }
+    if (off < cap) {
+        snprintf(buf + off, cap - off, "]");
+    }
+}
+
+uint32_t aura_ai_load_model(const char* path) {
+    if (!g_ai_backend) {
+        aura_ai_trap("ai.load_model", path, "no inference runtime is linked (import onnxruntime)");
+    }
     if (g_next_model >= AURA_MAX_MODELS) {
-        return 0u;
+        aura_ai_trap("ai.load_model", path, "too many models are loaded");
     }
+    AuraModel* m = &g_models[g_next_model];
+    char err[512] = {0};
+    m->handle = g_ai_backend->load(path, m->in_dims, &m->in_rank, m->out_dims, &m->out_rank, err, sizeof(err));
+    if (!m->handle) {
+        aura_ai_trap("ai.load_model", path, err);
+    }
+    if (m->in_rank > AURA_AI_MAX_RANK || m->out_rank > AURA_AI_MAX_RANK) {
+        aura_ai_trap("ai.load_model", path, "the model's tensors have too many dimensions");
+    }
+    m->path = path;
     return g_next_model++;
 }
 
-uint32_t aura_ai_infer(uint32_t model, uint32_t input) {
-    (void)model;
-    uint32_t len = aura_tensor_len(input);
-    uint32_t out = aura_tensor_new(len);
+// Runs `model` on `input`. A nonzero `batch` is the size of the leading input and output
+// dimension (ai.infer_batch); the other dimensions are checked as for ai.infer.
+static uint32_t aura_ai_run(const char* op, uint32_t model, uint32_t input, uint32_t batch) {
+    if (model == 0u || model >= g_next_model) {
+        aura_ai_trap(op, NULL, "invalid model handle");
+    }
+    AuraModel* m = &g_models[model];
+    char err[512] = {0};
+    char want[160];
+
+    uint32_t first = 0u;
+    if (batch != 0u) {
+        if (m->in_rank == 0u || m->out_rank == 0u) {
+            aura_ai_trap(op, m->path, "the model's input and output have no leading batch dimension");
+        }
+        if (m->in_dims[0] >= 0 && m->in_dims[0] != (int64_t)batch) {
+            snprintf(err, sizeof(err), "a batch of %u does not fit the model's batch dimension %lld", (unsigned)batch,
+                     (long long)m->in_dims[0]);
+            aura_ai_trap(op, m->path, err);
+        }
+        first = 1u;
+    }
+
+    // The verifier proved the input fits the model's contract; re-check it here, since the
+    // model file is read again at run time. An open dimension takes the size left over by
+    // the fixed ones.
+    uint64_t len = aura_tensor_len(input);
+    uint64_t fixed = batch != 0u ? (uint64_t)batch : 1u;
+    uint32_t open = 0u;
+    for (uint32_t i = first; i < m->in_rank; i++) {
+        if (m->in_dims[i] < 0) {
+            open++;
+        } else {
+            fixed *= (uint64_t)m->in_dims[i];
+        }
+    }
+    // Several open dimensions cannot be told apart by the length alone.
+    bool fits = fixed != 0u && len % fixed == 0u && open <= 1u && (open == 1u ? len > 0u : len == fixed);
+    if (!fits) {
+        aura_ai_format_shape(want, sizeof(want), m->in_dims, m->in_rank);
+        snprintf(err, sizeof(err), "a tensor of %llu element(s) does not fit input %s", (unsigned long long)len, want);
+        if (batch != 0u) {
+            size_t off = strlen(err);
+            snprintf(err + off, sizeof(err) - off, " with a batch of %u", (unsigned)batch);
+        }
+        aura_ai_trap(op, m->path, err);
+    }
+    int64_t in_dims[AURA_AI_MAX_RANK];
+    for (uint32_t i = 0u; i < m->in_rank; i++) {
+        in_dims[i] = m->in_dims[i] < 0 ? (int64_t)(len / fixed) : m->in_dims[i];
+    }
+    if (batch != 0u) {
+        in_dims[0] = (int64_t)batch;
+    }
+
+    int64_t out_dims[AURA_AI_MAX_RANK];
+    uint32_t out_rank = 0u;
+    uint32_t out = g_ai_backend->run(m->handle, input, in_dims, m->in_rank, out_dims, &out_rank, err, sizeof(err));
     if (out == 0u) {
-        return 0u;
+        aura_ai_trap(op, m->path, err);
     }
-    for (uint32_t i = 0u; i < len; i++) {
-        uint32_t v = aura_tensor_get(input, i);
-        aura_tensor_set(out, i, v);
+
+    // The result must have the model's output shape (led by the batch, if any) and exactly
+    // that many elements.
+    bool ok = out_rank == m->out_rank && (batch == 0u || out_dims[0] == (int64_t)batch);
+    uint64_t count = 1u;
+    for (uint32_t i = 0u; ok && i < out_rank; i++) {
+        ok = out_dims[i] >= 0 && (m->out_dims[i] < 0 || out_dims[i] == m->out_dims[i]);
+        count *= (uint64_t)out_dims[i];
+    }
+    if (!ok || count != aura_tensor_len(out)) {
+        char got[160];
+        aura_ai_format_shape(got, sizeof(got), out_dims, out_rank < AURA_AI_MAX_RANK ? out_rank : AURA_AI_MAX_RANK);
+        aura_ai_format_shape(want, sizeof(want), m->out_dims, m->out_rank);
+        snprintf(err, sizeof(err), "the model returned %s, which breaks its output contract %s", got, want);
+        aura_ai_trap(op, m->path, err);
     }
     return out;
 }
 
+uint32_t aura_ai_infer(uint32_t model, uint32_t input) {
+    return aura_ai_run("ai.infer", model, input, 0u);
+}
+
+uint32_t aura_ai_infer_batch(uint32_t model, uint32_t input, uint32_t batch) {
+    if (batch == 0u) {
+        aura_ai_trap("ai.infer_batch", model != 0u && model < g_next_model ? g_models[model].path : NULL,
+                     "the batch size must be at least 1");
+    }
+    return aura_ai_run("ai.infer_batch", model, input, batch);
+}
+
 uint32_t io_load_tensor(const char* path) {
     (void)path;
     return aura_tensor_new(16u);
@@ -76,6 +76,7 @@ fn is_ui_call(name: &str, has_trailing: bool) -> bool {
 fn literal_to_string(e: &Expr) -> Option<String> {
     match &e.kind {
         ExprKind::IntLit(n) => Some(n.to_string()),
+        ExprKind::FloatLit(v) => Some(v.to_string()),
         ExprKind::StringLit(s) => Some(s.clone()),
         _ => None,
     }
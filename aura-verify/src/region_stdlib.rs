@@ -25,7 +25,8 @@ impl BoundsContract {
 
     /// Check if an index is within bounds.
     pub fn is_valid_index(&self, index: usize) -> bool {
-        index <= self.max_index
+        // `max_index` saturates to 0 for empty collections, so check capacity too.
+        index < self.capacity && index <= self.max_index
     }
 
     /// Check if a range [start, end) is valid.
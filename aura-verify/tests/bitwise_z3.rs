@@ -0,0 +1,24 @@
+#![cfg(feature = "z3")]
+
+mod common;
+use common::verify;
+
+#[test]
+fn bitwise_results_are_proved_with_bitvector_semantics() {
+    let src = "cell f(x: u32) -> u32:\n    val lo: u32 = x & 15\n    assert lo <= 15\n    assert (x | 1) >= 1\n    assert (x ^ x) == 0\n    yield lo\n";
+    verify(src).expect("mask, or, and xor facts hold");
+
+    let src = "cell f(x: u32) -> u32:\n    assert (x << 1) >= x\n    yield x\n";
+    let err = verify(src).expect_err("a left shift drops the high bit");
+    assert!(err.contains("assertion failed"), "{err}");
+}
+
+#[test]
+fn shift_amount_must_be_below_32() {
+    let src = "cell f(x: u32, n: u32) -> u32:\n    yield x << n\n";
+    let err = verify(src).expect_err("n is unbounded");
+    assert!(err.contains("shift amount may be 32 or more"), "{err}");
+
+    let src = "cell f(x: u32, n: u32[0..31]) -> u32:\n    yield x >> n\n";
+    verify(src).expect("bounded shift amount");
+}
@@ -0,0 +1,10 @@
+use aura_nexus::NexusContext;
+use aura_verify::{verify_program_z3_profile, SmtProfile, Z3Prover};
+
+/// Parses `src` and verifies it with Z3 under the fast profile, returning the first failure's message.
+pub fn verify(src: &str) -> Result<(), String> {
+    let program = aura_parse::parse_source(src).expect("parse");
+    let mut prover = Z3Prover::new();
+    let mut nexus = NexusContext::default();
+    verify_program_z3_profile(&program, &mut prover, &(), &mut nexus, SmtProfile::Fast).map_err(|e| e.message)
+}
@@ -0,0 +1,21 @@
+#![cfg(feature = "z3")]
+
+mod common;
+use common::verify;
+
+#[test]
+fn const_bounds_are_verified_with_their_exact_values() {
+    let src = "const MAX: u32 = 16\n\ncell f(x: u32[0..MAX - 1]) -> u32:\n    assert x < MAX\n    yield x\n";
+    verify(src).expect("x is at most 15");
+
+    let src = "const MAX: u32 = 16\n\ncell f(x: u32[0..MAX]) -> u32:\n    assert x < MAX\n    yield x\n";
+    let err = verify(src).expect_err("x may be 16");
+    assert!(err.contains("assertion failed"), "{err}");
+}
+
+#[test]
+fn const_errors_fail_verification() {
+    let src = "const MAX: u32 = 1\n\ncell f() -> u32:\n    MAX = 2\n    yield 0\n";
+    let err = verify(src).expect_err("assigning a const");
+    assert!(err.contains("cannot assign to constant 'MAX'"), "{err}");
+}
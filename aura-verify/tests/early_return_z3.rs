@@ -0,0 +1,14 @@
+#![cfg(feature = "z3")]
+
+mod common;
+use common::verify;
+
+#[test]
+fn postcondition_is_checked_at_every_return() {
+    let src = "cell f(x: u32) -> u32:\n    ensures result <= 10\n    if x > 20:\n        return x\n    if x > 10:\n        return 10\n    yield x\n";
+    let err = verify(src).expect_err("the first return can exceed 10");
+    assert!(err.contains("postcondition may not hold at this 'return'"), "{err}");
+
+    let src = "cell f(x: u32) -> u32:\n    ensures result <= 10\n    if x > 10:\n        return 10\n    yield x\n";
+    verify(src).expect("both exits respect the bound");
+}
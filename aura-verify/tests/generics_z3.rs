@@ -0,0 +1,21 @@
+#![cfg(feature = "z3")]
+
+mod common;
+use common::verify;
+
+#[test]
+fn generic_bodies_are_verified_per_instance() {
+    let body = "trait Numeric\n\ncell check<T: Numeric>(x: T):\n    val n: u32 = 5\n    assert n > 10\n\n";
+    // Never instantiated, so there is nothing to verify.
+    verify(body).expect("no instances");
+
+    let err = verify(&format!("{body}cell main():\n    check(1)\n")).expect_err("the u32 instance fails");
+    assert!(err.contains("in instantiation 'check__u32'"), "{err}");
+}
+
+#[test]
+fn instances_see_their_type_arguments() {
+    // `x` is a u8 in `wrap__u8`, so it fits the u8 `val`; modeled as a u32 it would not.
+    let src = "cell wrap<T>(x: T) -> T:\n    val y: T = x\n    return y\n\ncell main(v: u8):\n    val a = wrap(v)\n";
+    verify(src).expect("u8 instance");
+}
@@ -0,0 +1,24 @@
+#![cfg(feature = "z3")]
+
+mod common;
+use common::verify;
+
+#[test]
+fn list_index_within_literal_length_is_proven() {
+    let src = "cell main(k: u32):\n    val xs: List<u32> = [10, 20, k]\n    val y: u32 = xs[2]\n    assert y == k\n";
+    verify(src).expect("index 2 of a 3-element list is in bounds");
+}
+
+#[test]
+fn list_index_past_the_end_is_rejected() {
+    let src = "cell main():\n    val xs: List<u32> = [10, 20, 30]\n    val y: u32 = xs[3]\n";
+    let err = verify(src).expect_err("index 3 is out of bounds");
+    assert!(err.contains("list index may be out of bounds"), "{err}");
+}
+
+#[test]
+fn list_index_guarded_by_requires_is_proven() {
+    let src = "cell get(xs: List<u32>, i: u32):\n    requires i < xs.len()\n    yield xs[i]\n";
+    verify(src).expect("requires bounds the index");
+}
+
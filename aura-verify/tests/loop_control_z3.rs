@@ -0,0 +1,30 @@
+#![cfg(feature = "z3")]
+
+mod common;
+use common::verify;
+
+#[test]
+fn invariant_is_rechecked_at_continue() {
+    let src = "cell main(n: u32, a: u32, b: u32):\n    requires b <= a\n    val mut i: u32 = a\n    val mut s: u32 = b\n    while i < n invariant s <= i:\n        i = i + 1\n        if i == 3:\n            s = s + 5\n            continue\n        s = s + 1\n";
+    let err = verify(src).expect_err("s can overtake i on the continue path");
+    assert!(err.contains("loop invariant may not hold at 'continue'"), "{err}");
+}
+
+#[test]
+fn continue_that_keeps_the_invariant_is_proven() {
+    let src = "cell main(n: u32, a: u32, b: u32):\n    requires b <= a\n    val mut i: u32 = a\n    val mut s: u32 = b\n    while i < n invariant s <= i:\n        i = i + 1\n        if i == 3:\n            continue\n        s = s + 1\n";
+    verify(src).expect("skipping the increment keeps s <= i");
+}
+
+#[test]
+fn break_path_does_not_assume_the_loop_condition_is_false() {
+    let src = "cell main(n: u32):\n    requires n > 4\n    val mut i: u32 = 0\n    while i < n invariant i <= n:\n        if i == 2:\n            break\n        i = i + 1\n    assert i >= n\n";
+    let err = verify(src).expect_err("the loop can exit early through break");
+    assert!(err.contains("assertion failed"), "{err}");
+}
+
+#[test]
+fn for_loop_continue_advances_the_variable() {
+    let src = "cell main(k: u32):\n    val mut s: u32 = 0\n    for i in 0..10 invariant s <= i:\n        if i == 4:\n            continue\n        s = s + 1\n";
+    verify(src).expect("at continue s <= i < i + 1");
+}
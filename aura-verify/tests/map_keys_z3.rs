@@ -0,0 +1,27 @@
+#![cfg(feature = "z3")]
+
+mod common;
+use common::verify;
+
+#[test]
+fn lookup_of_a_literal_key_is_proven() {
+    let src = "cell main(k: u32):\n    val ages: Map<String, u32> = {\"ann\": k, \"bob\": 42}\n    val a: u32 = ages[\"ann\"]\n    assert a == k\n    assert ages.len() == 2\n";
+    verify(src).expect("\"ann\" is a key of the literal");
+}
+
+#[test]
+fn lookup_of_a_missing_key_is_rejected() {
+    let src = "cell main(k: u32):\n    val ages: Map<String, u32> = {\"ann\": k}\n    val b: u32 = ages[\"bob\"]\n";
+    let err = verify(src).expect_err("\"bob\" is not a key");
+    assert!(err.contains("map key may be missing"), "{err}");
+}
+
+#[test]
+fn lookup_guarded_by_contains_is_proven() {
+    let src = "cell get(m: Map<u32, u32>, k: u32):\n    if m.contains(k):\n        val v: u32 = m[k]\n";
+    verify(src).expect("contains guards the lookup");
+
+    let src = "cell get(m: Map<u32, u32>, k: u32):\n    val v: u32 = m[k]\n";
+    let err = verify(src).expect_err("unguarded lookup on an unknown map");
+    assert!(err.contains("map key may be missing"), "{err}");
+}
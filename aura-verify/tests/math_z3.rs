@@ -0,0 +1,32 @@
+#![cfg(feature = "z3")]
+
+mod common;
+use common::verify;
+
+#[test]
+fn clamp_result_is_within_its_bounds() {
+    let src = "cell clamp(x: u32, lo: u32, hi: u32) -> u32:\n    requires lo <= hi\n    val r = math.min(math.max(x, lo), hi)\n    ensures lo <= r\n    ensures r <= hi\n    return r\n";
+    verify(src).expect("clamp stays in [lo, hi]");
+}
+
+#[test]
+fn clamp_without_ordered_bounds_is_rejected() {
+    let src = "cell clamp(x: u32, lo: u32, hi: u32) -> u32:\n    val r = math.min(math.max(x, lo), hi)\n    ensures lo <= r\n    return r\n";
+    let err = verify(src).expect_err("hi < lo gives hi");
+    assert!(err.contains("postcondition"), "{err}");
+}
+
+#[test]
+fn sqrt_needs_a_non_negative_argument() {
+    let ok = "cell norm(x: f64, y: f64) -> f64:\n    val r = math.sqrt(x * x + y * y)\n    ensures r >= 0.0\n    return r\n";
+    verify(ok).expect("a sum of squares is non-negative");
+    let bad = "cell root(x: f64) -> f64:\n    return math.sqrt(x - 1.0)\n";
+    let err = verify(bad).expect_err("x may be below 1");
+    assert!(err.contains("math.sqrt argument may be negative"), "{err}");
+}
+
+#[test]
+fn trig_results_stay_within_one() {
+    let src = "cell wave(t: f64) -> f64:\n    val r = math.sin(t) * 0.5\n    assert r <= 0.5\n    assert math.cos(t) >= -1.0\n    return r\n";
+    verify(src).expect("sin and cos are bounded");
+}
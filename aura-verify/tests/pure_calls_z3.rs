@@ -0,0 +1,17 @@
+#![cfg(feature = "z3")]
+
+mod common;
+use common::verify;
+
+#[test]
+fn pure_cells_return_equal_results_for_equal_arguments() {
+    let src = "cell score(x: u32) -> u32:\n    return x & 7\n\ncell f(a: u32):\n    val s = score(a)\n    val t = score(a)\n    assert s == t\n";
+    verify(src).expect("a pure call is a function of its arguments");
+}
+
+#[test]
+fn cells_with_effects_may_differ_between_calls() {
+    let src = "cell clock(x: u32) -> u32:\n    return time.now_ms() + x\n\ncell f(a: u32):\n    val s = clock(a)\n    val t = clock(a)\n    assert s == t\n";
+    let err = verify(src).expect_err("the clock moves");
+    assert!(err.contains("assertion failed"), "{err}");
+}
@@ -0,0 +1,17 @@
+#![cfg(feature = "z3")]
+
+mod common;
+use common::verify;
+
+#[test]
+fn declared_measures_shrink_at_every_recursive_call() {
+    let src = "cell is_even(n: u32) -> u32 decreases 2 * n:\n    if n == 0:\n        return 1\n    return is_odd(n - 1)\n\ncell is_odd(n: u32) -> u32 decreases 2 * n + 1:\n    if n == 0:\n        return 0\n    return is_even(n)\n";
+    verify(src).expect("each call lowers the measure");
+}
+
+#[test]
+fn a_measure_that_does_not_shrink_is_rejected() {
+    let src = "cell spin(n: u32, m: u32) -> u32 decreases n:\n    if n == 0:\n        return m\n    return spin(n, m - 1)\n";
+    let err = verify(src).expect_err("n stays the same");
+    assert!(err.contains("recursive call to 'spin' may not terminate"), "{err}");
+}
@@ -0,0 +1,32 @@
+#![cfg(feature = "z3")]
+
+mod common;
+use common::verify;
+
+#[test]
+fn slice_lengths_follow_from_the_bounds() {
+    let src = "cell take(s: String, n: u32) -> String:\n    requires n <= str.len(s)\n    val r = str.slice(s, 0, n)\n    ensures str.len(r) == n\n    return r\n";
+    verify(src).expect("a prefix of n bytes is n bytes long");
+}
+
+#[test]
+fn slice_past_the_end_is_rejected() {
+    let src = "cell main():\n    val s = \"abc\"\n    val t = str.slice(s, 1, 4)\n";
+    let err = verify(src).expect_err("byte 4 of a 3-byte string");
+    assert!(err.contains("string slice may be out of bounds"), "{err}");
+}
+
+#[test]
+fn lengths_add_up_across_concat_and_pad() {
+    let src = "cell main(a: String, w: u32):\n    val b = \"--\"\n    val c = str.concat(a, b)\n    assert str.len(c) == str.len(a) + 2\n    val p = str.pad_left(c, w)\n    assert str.len(p) >= w\n    assert str.len(p) >= str.len(c)\n";
+    verify(src).expect("concat adds lengths and padding never shortens");
+}
+
+#[test]
+fn split_nth_needs_a_field_index_below_the_count() {
+    let ok = "cell last(s: String) -> String:\n    val sep = \",\"\n    val n = str.split_count(s, sep)\n    return str.split_nth(s, sep, n - 1)\n";
+    verify(ok).expect("the last field exists");
+    let bad = "cell second(s: String) -> String:\n    val sep = \",\"\n    return str.split_nth(s, sep, 1)\n";
+    let err = verify(bad).expect_err("a string may have a single field");
+    assert!(err.contains("string field may be out of range"), "{err}");
+}
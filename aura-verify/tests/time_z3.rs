@@ -0,0 +1,23 @@
+#![cfg(feature = "z3")]
+
+mod common;
+use common::verify;
+
+#[test]
+fn adding_to_a_clock_reading_may_overflow() {
+    let src = "cell deadline(timeout: u32) -> u32:\n    return time.add_ms(time.now_ms(), timeout)\n";
+    let err = verify(src).expect_err("now_ms can be close to 2^32");
+    assert!(err.contains("time.add_ms may overflow u32"), "{err}");
+}
+
+#[test]
+fn a_requires_rules_out_duration_overflow() {
+    let src = "cell add(t: u32, d: u32) -> u32:\n    requires d <= 4294967295 - t\n    val r = time.add_ms(t, d)\n    ensures r >= t\n    return r\n";
+    verify(src).expect("the sum fits");
+}
+
+#[test]
+fn elapsed_time_is_correct_across_a_wrap_and_timestamps_have_a_fixed_length() {
+    let src = "cell main():\n    val e = time.elapsed_ms(4294967000, 704)\n    assert e == 1000\n    val at = time.format_utc(time.unix_s())\n    assert str.len(at) == 20\n";
+    verify(src).expect("wrapping difference and ISO 8601 length");
+}
@@ -4,7 +4,11 @@ use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::{fs, io};
 
+use aura_backend_llvm::{NativeTarget, OptConfig, OptLevel, TargetArch, TargetOs};
+use aura_sdk::fs_policy::{FsAccess, FsPolicy};
+use aura_sdk::merkle::sha256_hex;
 use miette::{Diagnostic, IntoDiagnostic};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 #[derive(Debug, Error, Diagnostic)]
@@ -14,9 +18,97 @@ pub struct LinkerError {
     pub message: String,
 }
 
+/// One separately compiled LLVM unit of the program (see `aura_backend_llvm::LlvmUnit`).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UnitObject {
+    pub name: String,
+    /// Identifies the unit's object in the object cache, together with the compile flags.
+    pub key: String,
+    /// Freshly emitted IR; `None` when the build found the object already cached.
+    pub ll: Option<PathBuf>,
+}
+
+/// How to reach a target's system headers and libraries, from `[target.<triple>]` in
+/// `aura.toml`. Only cross builds usually need one.
+#[derive(Clone, Debug, Default)]
+pub struct Toolchain {
+    /// Root of the target's headers and libraries. For Windows targets this is a splatted
+    /// MSVC CRT and Windows SDK (`crt/` and `sdk/`, as laid out by `xwin splat`); elsewhere it
+    /// is passed to clang as `--sysroot`.
+    pub sysroot: Option<PathBuf>,
+    /// Linker for `-fuse-ld=`. Defaults to `lld` for Windows targets and cross builds, where
+    /// the host's system linker cannot produce the target's executables.
+    pub linker: Option<String>,
+}
+
+impl Toolchain {
+    fn linker(&self, target: &NativeTarget) -> Option<String> {
+        self.linker
+            .clone()
+            .or_else(|| (target.os() == TargetOs::Windows || !target.is_host()).then(|| "lld".to_string()))
+    }
+
+    /// Flags that point clang's preprocessor at the target's system headers.
+    fn include_flags(&self, target: &NativeTarget) -> Vec<String> {
+        let Some(root) = &self.sysroot else { return Vec::new() };
+        if target.os() != TargetOs::Windows {
+            return vec![format!("--sysroot={}", root.display())];
+        }
+        ["crt/include", "sdk/include/ucrt", "sdk/include/um", "sdk/include/shared"]
+            .iter()
+            .flat_map(|dir| ["-isystem".to_string(), root.join(dir).display().to_string()])
+            .collect()
+    }
+
+    /// Flags that point the link at the target's system libraries.
+    fn lib_flags(&self, target: &NativeTarget) -> Vec<String> {
+        let Some(root) = &self.sysroot else { return Vec::new() };
+        if target.os() != TargetOs::Windows {
+            return vec![format!("--sysroot={}", root.display())];
+        }
+        let arch = match target.arch() {
+            TargetArch::X86 => "x86",
+            TargetArch::Aarch64 => "aarch64",
+            TargetArch::X86_64 | TargetArch::Other => "x86_64",
+        };
+        ["crt/lib", "sdk/lib/um", "sdk/lib/ucrt"]
+            .iter()
+            .map(|dir| format!("-L{}", root.join(dir).join(arch).display()))
+            .collect()
+    }
+}
+
+/// Compiled units and runtime objects, shared by every build under the working directory.
+pub fn object_cache_dir() -> PathBuf {
+    PathBuf::from(".aura").join("cache").join("obj")
+}
+
+/// Where the object for a unit `key` lives once compiled for `target` at `opt`.
+pub fn unit_object_path(key: &str, target: &NativeTarget, opt: &OptConfig) -> PathBuf {
+    let flags = compile_flags(target, opt).join(" ");
+    object_cache_dir().join(format!("{}.o", sha256_hex(&format!("unit-obj-v1\n{key}\n{flags}"))))
+}
+
+/// Codegen flags shared by unit and runtime objects; they are part of every object's key.
+fn compile_flags(target: &NativeTarget, opt: &OptConfig) -> Vec<String> {
+    let mut args = Vec::new();
+    if !target.is_host() {
+        args.push(format!("--target={}", target.triple()));
+    }
+    args.extend(opt.clang_args());
+    if opt.level > OptLevel::O0 {
+        if target.is_host() {
+            args.push("-march=native".to_string());
+        }
+        args.push("-flto".to_string());
+    }
+    args
+}
+
 pub fn find_clang() -> Option<PathBuf> {
     // Prefer PATH.
-    if let Ok(out) = Command::new("where").arg("clang").output() {
+    let lookup = if cfg!(windows) { "where" } else { "which" };
+    if let Ok(out) = Command::new(lookup).arg("clang").output() {
         if out.status.success() {
             if let Ok(s) = String::from_utf8(out.stdout) {
                 if let Some(first) = s.lines().next() {
@@ -29,25 +121,32 @@ pub fn find_clang() -> Option<PathBuf> {
         }
     }
 
-    // Fallback: default LLVM install location.
-    let fallback = PathBuf::from(r"C:\Program Files\LLVM\bin\clang.exe");
-    if fallback.exists() {
-        return Some(fallback);
-    }
-
-    None
+    // Fallback: default LLVM install locations.
+    let fallbacks: &[&str] = if cfg!(windows) {
+        &[r"C:\Program Files\LLVM\bin\clang.exe"]
+    } else {
+        &["/usr/bin/clang", "/usr/local/bin/clang", "/opt/homebrew/opt/llvm/bin/clang"]
+    };
+    fallbacks.iter().map(PathBuf::from).find(|p| p.exists())
 }
 
+/// Compiles the units whose objects are not cached yet, then links every unit object with the
+/// runtime into `out_exe`. When no unit changed, this only relinks.
 pub fn link_with_clang(
-    module_ll: &Path,
+    units: &[UnitObject],
     out_exe: &Path,
+    target: &NativeTarget,
+    toolchain: &Toolchain,
+    opt: &OptConfig,
     lib_dirs: &[PathBuf],
     libs: &[String],
     c_sources: &[PathBuf],
+    c_include_dirs: &[PathBuf],
     runtime_dlls: &[PathBuf],
+    fs_policy: Option<&FsPolicy>,
 ) -> Result<(), LinkerError> {
     let clang = find_clang().ok_or_else(|| LinkerError {
-        message: "could not locate clang.exe (install LLVM or put clang in PATH)".to_string(),
+        message: "could not locate clang (install LLVM or put clang in PATH)".to_string(),
     })?;
 
     let runtime_c = aura_rt::runtime_c_path();
@@ -68,21 +167,36 @@ pub fn link_with_clang(
         })?
         .to_path_buf();
 
-    // Spec target command (adapted): clang module.ll -I<rt/include> <rt/runtime.c> -o main.exe -O3 -march=native -flto
-    // We statically link the runtime by compiling it into the final binary.
+    // Spec target command (adapted): clang -c <unit>.ll -O<n> -march=native -flto per unit, then
+    // clang <unit objects> <runtime objects> -o main.exe. The runtime is linked statically.
     let verbose = std::env::var_os("AURA_LINK_VERBOSE").is_some();
 
-    let discovered_libs = discover_libs_in_dirs(lib_dirs, libs).map_err(|e| LinkerError {
-        message: format!("failed to discover .lib files: {e}"),
+    let discovered_libs = discover_libs_in_dirs(target, lib_dirs, libs).map_err(|e| LinkerError {
+        message: format!("failed to discover .{} files: {e}", target.link_lib_ext()),
     })?;
     let libs = merge_libs(libs, &discovered_libs);
 
-    let mut args: Vec<String> = Vec::new();
-    args.push(module_ll.display().to_string());
-    args.push(runtime_c.display().to_string());
-    args.push(format!("-I{}", include_dir.display()));
-    args.push(stdlib_c.display().to_string());
-    args.push(format!("-I{}", stdlib_include_dir.display()));
+    fs::create_dir_all(object_cache_dir()).map_err(|e| LinkerError {
+        message: format!("failed to create {}: {e}", object_cache_dir().display()),
+    })?;
+    let flags = compile_flags(target, opt);
+    let mut objects = Vec::new();
+    for unit in units {
+        let obj = unit_object_path(&unit.key, target, opt);
+        if !obj.exists() {
+            let ll = unit.ll.as_ref().ok_or_else(|| LinkerError {
+                message: format!("object for unit '{}' is no longer cached; rebuild to re-emit it", unit.name),
+            })?;
+            compile_object(&clang, ll, &flags, &obj, verbose)?;
+        }
+        objects.push(obj);
+    }
+
+    let mut runtime_flags = vec![
+        format!("-I{}", include_dir.display()),
+        format!("-I{}", stdlib_include_dir.display()),
+        "-std=c2x".to_string(),
+    ];
 
     // Optional runtime allocator modes.
     // This is intentionally env-driven to avoid adding new CLI UX.
@@ -93,11 +207,11 @@ pub fn link_with_clang(
         let mode = mode.trim().to_ascii_lowercase();
         match mode.as_str() {
             "region" => {
-                args.push("-DAURA_ALLOC_REGION=1".to_string());
+                runtime_flags.push("-DAURA_ALLOC_REGION=1".to_string());
                 if let Ok(bytes) = std::env::var("AURA_ARENA_BYTES") {
                     let bytes = bytes.trim();
                     if !bytes.is_empty() {
-                        args.push(format!("-DAURA_ARENA_BYTES={bytes}"));
+                        runtime_flags.push(format!("-DAURA_ARENA_BYTES={bytes}"));
                     }
                 }
             }
@@ -112,15 +226,74 @@ pub fn link_with_clang(
         }
     }
 
+    // `[fs]` in aura.toml: the stdlib only opens the paths it lists (see aura_stdlib.h).
+    if let Some(policy) = fs_policy {
+        runtime_flags.push("-DAURA_FS_POLICY=1".to_string());
+        for (name, access) in [("AURA_FS_READ_ROOTS", FsAccess::Read), ("AURA_FS_WRITE_ROOTS", FsAccess::Write)] {
+            let roots: String = policy
+                .roots(access)
+                .iter()
+                .map(|root| format!("\"{}\",", root.display().to_string().replace('\\', "\\\\").replace('"', "\\\"")))
+                .collect();
+            runtime_flags.push(format!("-D{name}={roots}"));
+        }
+    }
+
+    runtime_flags.extend(toolchain.include_flags(target));
+    runtime_flags.extend(flags.iter().cloned());
+    // The runtime only changes with the toolchain install, so its objects are keyed by content.
+    let headers = [runtime_h.as_path(), stdlib_h.as_path()]
+        .iter()
+        .map(|h| fs::read_to_string(h).unwrap_or_default())
+        .collect::<String>();
+    for src in [&runtime_c, &stdlib_c] {
+        let text = fs::read_to_string(src).map_err(|e| LinkerError {
+            message: format!("failed to read {}: {e}", src.display()),
+        })?;
+        let key = sha256_hex(&format!("runtime-obj-v1\n{}\n{text}\n{headers}", runtime_flags.join(" ")));
+        let obj = object_cache_dir().join(format!("{key}.o"));
+        if !obj.exists() {
+            compile_object(&clang, src, &runtime_flags, &obj, verbose)?;
+        }
+        objects.push(obj);
+    }
+
+    let mut args: Vec<String> = objects.iter().map(|o| o.display().to_string()).collect();
+
     // Compile additional shim sources (e.g., Raylib ABI adapters).
-    for src in c_sources {
-        args.push(src.display().to_string());
+    if !c_sources.is_empty() {
+        args.push(format!("-I{}", include_dir.display()));
+        args.push(format!("-I{}", stdlib_include_dir.display()));
+        for dir in c_include_dirs {
+            args.push(format!("-I{}", dir.display()));
+        }
+        for src in c_sources {
+            args.push(src.display().to_string());
+        }
+        args.push("-std=c2x".to_string());
+        // Elsewhere the `--sysroot` from `lib_flags` below covers headers as well.
+        if target.os() == TargetOs::Windows {
+            args.extend(toolchain.include_flags(target));
+        }
+    }
+    // Besides codegen, these link the sanitizer runtimes when instrumentation is on.
+    args.extend(flags);
+    if let Some(linker) = toolchain.linker(target) {
+        args.push(format!("-fuse-ld={linker}"));
+    }
+    args.extend(toolchain.lib_flags(target));
+    match target.os() {
+        // `net.*` calls go through Winsock.
+        TargetOs::Windows => args.push("-lws2_32".to_string()),
+        // Shared libraries are copied next to the executable, so look for them there.
+        TargetOs::Linux => {
+            args.push("-Wl,-rpath,$ORIGIN".to_string());
+            args.push("-lm".to_string());
+            // The `~>` scheduler runs on C11 threads.
+            args.push("-pthread".to_string());
+        }
+        TargetOs::MacOs => args.push("-Wl,-rpath,@executable_path".to_string()),
     }
-    args.push("-std=c2x".to_string());
-    args.push("-O3".to_string());
-    args.push("-march=native".to_string());
-    args.push("-flto".to_string());
-    args.push("-fuse-ld=lld".to_string());
 
     for dir in lib_dirs {
         args.push(format!("-L{}", dir.display()));
@@ -135,18 +308,7 @@ pub fn link_with_clang(
             continue;
         }
 
-        // On Windows (MSVC flavor), prefer `-l<name>` which maps to `name.lib`
-        // and participates in the linker's search paths.
-        if let Some(stem) = lib.strip_suffix(".lib") {
-            args.push(format!("-l{}", stem));
-            continue;
-        }
-        if let Some(stem) = lib.strip_suffix(".a") {
-            args.push(format!("-l{}", stem));
-            continue;
-        }
-
-        args.push(format!("-l{}", lib));
+        args.push(format!("-l{}", link_lib_name(target, lib)));
     }
 
     if verbose {
@@ -156,10 +318,32 @@ pub fn link_with_clang(
     args.push("-o".to_string());
     args.push(out_exe.display().to_string());
 
-    let mut cmd = Command::new(clang);
-    cmd.args(&args);
+    run_clang(&clang, &args)?;
 
-    let out = cmd.output().into_diagnostic().map_err(|e| LinkerError {
+    post_link_copy_shared_libs(target, out_exe, lib_dirs, runtime_dlls)?;
+
+    Ok(())
+}
+
+/// Compiles one source (`.ll` or `.c`) to `obj`. The object is written under a temporary
+/// name and renamed into place, so concurrent builds never see a partial object.
+fn compile_object(clang: &Path, src: &Path, flags: &[String], obj: &Path, verbose: bool) -> Result<(), LinkerError> {
+    let tmp = obj.with_extension(format!("o.{}", std::process::id()));
+    let mut args = vec!["-c".to_string(), src.display().to_string()];
+    args.extend(flags.iter().cloned());
+    if verbose {
+        args.push("-v".to_string());
+    }
+    args.push("-o".to_string());
+    args.push(tmp.display().to_string());
+    run_clang(clang, &args)?;
+    fs::rename(&tmp, obj).map_err(|e| LinkerError {
+        message: format!("failed to store {}: {e}", obj.display()),
+    })
+}
+
+fn run_clang(clang: &Path, args: &[String]) -> Result<(), LinkerError> {
+    let out = Command::new(clang).args(args).output().into_diagnostic().map_err(|e| LinkerError {
         message: e.to_string(),
     })?;
 
@@ -176,13 +360,25 @@ pub fn link_with_clang(
             ),
         });
     }
-
-    post_link_copy_dlls(out_exe, lib_dirs, runtime_dlls)?;
-
     Ok(())
 }
 
-fn discover_libs_in_dirs(dirs: &[PathBuf], explicit: &[String]) -> io::Result<Vec<String>> {
+/// The `-l` name for a library given as `foo`, `foo.lib` or `libfoo.{a,so,dylib}`.
+///
+/// On Windows (MSVC flavor) `-lfoo` maps to `foo.lib`; elsewhere it maps to `libfoo.a`,
+/// `libfoo.so` or `libfoo.dylib`, so the `lib` prefix is dropped as well.
+fn link_lib_name<'a>(target: &NativeTarget, lib: &'a str) -> &'a str {
+    if target.os() == TargetOs::Windows {
+        return lib.strip_suffix(".lib").or_else(|| lib.strip_suffix(".a")).unwrap_or(lib);
+    }
+    [".a", ".so", ".dylib"]
+        .iter()
+        .find_map(|ext| lib.strip_suffix(ext))
+        .map(|stem| stem.strip_prefix("lib").unwrap_or(stem))
+        .unwrap_or(lib)
+}
+
+fn discover_libs_in_dirs(target: &NativeTarget, dirs: &[PathBuf], explicit: &[String]) -> io::Result<Vec<String>> {
     // If no explicit libs were requested, do not auto-add any.
     // This avoids accidentally linking unrelated packages that happen to be present in ./deps.
     if explicit.is_empty() {
@@ -192,10 +388,7 @@ fn discover_libs_in_dirs(dirs: &[PathBuf], explicit: &[String]) -> io::Result<Ve
     let mut prefixes: Vec<String> = Vec::new();
     for s in explicit {
         let lower = s.to_ascii_lowercase();
-        let stem = lower
-            .strip_suffix(".lib")
-            .or_else(|| lower.strip_suffix(".a"))
-            .unwrap_or(&lower);
+        let stem = link_lib_name(target, &lower);
         // Ignore obvious path-y things.
         if stem.contains('\\') || stem.contains('/') {
             continue;
@@ -203,18 +396,15 @@ fn discover_libs_in_dirs(dirs: &[PathBuf], explicit: &[String]) -> io::Result<Ve
         prefixes.push(stem.to_string());
     }
 
+    let ext = target.link_lib_ext();
     let mut out = Vec::new();
     for d in dirs {
         let Ok(rd) = fs::read_dir(d) else { continue };
         for ent in rd.flatten() {
             let p = ent.path();
-            if p.extension().is_some_and(|e| e.to_string_lossy().eq_ignore_ascii_case("lib")) {
+            if p.extension().is_some_and(|e| e.to_string_lossy().eq_ignore_ascii_case(ext)) {
                 if let Some(name) = p.file_name().and_then(|n| n.to_str()) {
-                    let stem = name
-                        .to_ascii_lowercase()
-                        .strip_suffix(".lib")
-                        .unwrap_or(&name.to_ascii_lowercase())
-                        .to_string();
+                    let stem = link_lib_name(target, &name.to_ascii_lowercase()).to_string();
 
                     // Only include discovered libs that look related to an explicitly requested one.
                     // Example: explicit onnxruntime.lib => include onnxruntime_providers_shared.lib.
@@ -254,7 +444,8 @@ fn merge_libs(explicit: &[String], discovered: &[String]) -> Vec<String> {
     out
 }
 
-fn post_link_copy_dlls(
+fn post_link_copy_shared_libs(
+    target: &NativeTarget,
     out_exe: &Path,
     lib_dirs: &[PathBuf],
     runtime_dlls: &[PathBuf],
@@ -263,7 +454,7 @@ fn post_link_copy_dlls(
         message: "output exe has no parent directory".to_string(),
     })?;
 
-    // 1) Copy DLLs explicitly provided by the bridge (best-effort)
+    // 1) Copy shared libraries explicitly provided by the bridge (best-effort)
     for dll in runtime_dlls {
         if let Some(name) = dll.file_name() {
             let dst = out_dir.join(name);
@@ -271,12 +462,13 @@ fn post_link_copy_dlls(
         }
     }
 
-    // 2) Copy any DLLs in lib search dirs (best-effort)
+    // 2) Copy any shared libraries of the target's kind in lib search dirs (best-effort)
+    let ext = target.shared_lib_ext();
     for d in lib_dirs {
         let Ok(rd) = fs::read_dir(d) else { continue };
         for ent in rd.flatten() {
             let p = ent.path();
-            if p.extension().is_some_and(|e| e.to_string_lossy().eq_ignore_ascii_case("dll")) {
+            if p.extension().is_some_and(|e| e.to_string_lossy().eq_ignore_ascii_case(ext)) {
                 if let Some(name) = p.file_name() {
                     let dst = out_dir.join(name);
                     // Avoid spamming writes if identical file is already there.
@@ -54,11 +54,17 @@ fn debug_pair() -> Option<(DebugSession, DebugHandle)> {
             capabilities: vec![
                 "devvm.pause".to_string(),
                 "devvm.step".to_string(),
+                "devvm.stepFrames".to_string(),
                 "devvm.breakpoints".to_string(),
                 "devvm.watches".to_string(),
+                "devvm.evaluate".to_string(),
+                "devvm.tasks".to_string(),
+                "devvm.variables".to_string(),
+                "devvm.reload".to_string(),
                 "perf.timeline".to_string(),
                 "perf.flame.folded".to_string(),
                 "perf.memory".to_string(),
+                "perf.cells".to_string(),
                 "native.launch".to_string(),
                 "native.terminate".to_string(),
                 "native.exit".to_string(),
@@ -90,6 +96,7 @@ fn debug_pair() -> Option<(DebugSession, DebugHandle)> {
     handle.send(DebugCommand::Enable {
         start_paused: false,
         perf: true,
+        perf_frame_interval: std::env::var("AURA_PERF_FRAME_INTERVAL").ok().and_then(|s| s.parse().ok()),
     });
 
     Some((sess.clone(), handle.clone()))
@@ -256,11 +263,11 @@ enum BuildProfileArg {
 }
 
 impl BuildProfileArg {
-    fn default_optimize(&self) -> &'static str {
+    fn default_opt_level(&self) -> aura_backend_llvm::OptLevel {
         match self {
-            BuildProfileArg::Dev => "none",
-            BuildProfileArg::Release => "full",
-            BuildProfileArg::Verify => "none",
+            BuildProfileArg::Dev => aura_backend_llvm::OptLevel::O0,
+            BuildProfileArg::Release => aura_backend_llvm::OptLevel::O3,
+            BuildProfileArg::Verify => aura_backend_llvm::OptLevel::O0,
         }
     }
 
@@ -305,10 +312,27 @@ enum Cmd {
         #[arg(long = "link-lib")]
         link_libs: Vec<String>,
 
-        /// Optimization level for LLVM post-pass: `none` or `full`
-        #[arg(long)]
+        /// Optimization level: `0`-`3` (e.g. `-O2`); `none` and `full` mean `0` and `3`
+        #[arg(short = 'O', long)]
         optimize: Option<String>,
 
+        /// Disable inlining of Aura cells (on from -O1)
+        #[arg(long, default_value_t = false)]
+        no_inline: bool,
+
+        /// Disable loop and SLP vectorization (on from -O2)
+        #[arg(long, default_value_t = false)]
+        no_vectorize: bool,
+
+        /// Instrument native code and the bundled runtime with sanitizers: `address`,
+        /// `undefined` (comma-separated)
+        #[arg(long)]
+        sanitize: Option<String>,
+
+        /// Target triple for native builds (e.g. `x86_64-pc-windows-msvc`); defaults to the host
+        #[arg(long)]
+        target: Option<String>,
+
         /// SMT solver profile for verification: `fast`, `ci`, or `thorough`
         #[arg(long, value_enum)]
         smt_profile: Option<SmtProfileArg>,
@@ -343,10 +367,27 @@ enum Cmd {
         #[arg(long = "link-lib")]
         link_libs: Vec<String>,
 
-        /// Optimization level for LLVM post-pass: `none` or `full`
-        #[arg(long)]
+        /// Optimization level: `0`-`3` (e.g. `-O2`); `none` and `full` mean `0` and `3`
+        #[arg(short = 'O', long)]
         optimize: Option<String>,
 
+        /// Disable inlining of Aura cells (on from -O1)
+        #[arg(long, default_value_t = false)]
+        no_inline: bool,
+
+        /// Disable loop and SLP vectorization (on from -O2)
+        #[arg(long, default_value_t = false)]
+        no_vectorize: bool,
+
+        /// Instrument native code and the bundled runtime with sanitizers: `address`,
+        /// `undefined` (comma-separated)
+        #[arg(long)]
+        sanitize: Option<String>,
+
+        /// Target triple for native builds (e.g. `x86_64-pc-windows-msvc`); defaults to the host
+        #[arg(long)]
+        target: Option<String>,
+
         /// SMT solver profile for verification: `fast`, `ci`, or `thorough`
         #[arg(long, value_enum)]
         smt_profile: Option<SmtProfileArg>,
@@ -487,6 +528,10 @@ enum PkgCmd {
         #[arg(long)]
         trusted_key: Option<PathBuf>,
 
+        /// Fetch artifacts for this target triple instead of the host
+        #[arg(long)]
+        target: Option<String>,
+
         /// Overwrite cached artifacts and lock entries
         #[arg(long, default_value_t = false)]
         force: bool,
@@ -512,6 +557,10 @@ enum PkgCmd {
         #[arg(long)]
         from: PathBuf,
 
+        /// Target triple the artifact was built for; omit for target-independent packages
+        #[arg(long)]
+        target: Option<String>,
+
         /// Optional ed25519 signing key (hex-encoded 32 bytes)
         #[arg(long)]
         signing_key: Option<PathBuf>,
@@ -547,11 +596,52 @@ enum PkgCmd {
 struct BuildOutputs {
     out_dir: PathBuf,
     module_c: Option<PathBuf>,
-    llvm_ll: Option<PathBuf>,
-    llvm_opt_ll: Option<PathBuf>,
+    /// Separately compiled LLVM units, also listed in `units.json`.
+    llvm_units: Vec<linker::UnitObject>,
     link: aura_bridge::LinkInputs,
 }
 
+/// Native build settings recorded next to `units/` as `build-info.json`.
+#[derive(Debug, Serialize)]
+struct BuildInfo {
+    target: String,
+    opt_level: &'static str,
+    inline: bool,
+    vectorize: bool,
+    clang_args: Vec<String>,
+}
+
+/// Resolves `-O`/`--no-inline`/`--no-vectorize`/`--sanitize` against the build profile's
+/// default level. Tensor loops use the host's widest vectors when building for the host.
+fn opt_config(
+    optimize: Option<&str>,
+    profile: &BuildProfileArg,
+    no_inline: bool,
+    no_vectorize: bool,
+    sanitize: Option<&str>,
+    target: &aura_backend_llvm::NativeTarget,
+) -> miette::Result<aura_backend_llvm::OptConfig> {
+    let level = match optimize {
+        Some(s) => aura_backend_llvm::OptLevel::parse(s).map_err(miette::Report::new)?,
+        None => profile.default_opt_level(),
+    };
+    let mut opt = aura_backend_llvm::OptConfig::new(level);
+    if no_inline {
+        opt.inline = false;
+    }
+    if no_vectorize {
+        opt.vectorize = false;
+    }
+    if let Some(list) = sanitize {
+        opt.sanitize = aura_backend_llvm::Sanitizers::parse(list).map_err(miette::Report::new)?;
+    }
+    if target.is_host() && opt.simd != aura_backend_llvm::SimdWidth::Scalar {
+        let bits = aura_ai_opt::host_features().vector_bits();
+        opt.simd = aura_backend_llvm::SimdWidth::from_bits(bits);
+    }
+    Ok(opt)
+}
+
 fn main() -> miette::Result<()> {
     let cli = Cli::parse();
     match cli.cmd {
@@ -564,28 +654,38 @@ fn main() -> miette::Result<()> {
             link_dirs,
             link_libs,
             optimize,
+            no_inline,
+            no_vectorize,
+            sanitize,
+            target,
             smt_profile,
         } => {
-            let resolved = resolve_manifest_config(&path, &bridge, &link_dirs, &link_libs)?;
+            let target = native_target(target.as_deref())?;
+            let resolved = resolve_manifest_config(&path, &bridge, &link_dirs, &link_libs, Some(&target))?;
             let parse_cfg = build_parse_config(&cli.edition, &cli.feature, &resolved);
+            let toolchain = resolved.toolchain_for(&target);
 
-            let optimize = optimize.unwrap_or_else(|| profile.default_optimize().to_string());
+            let opt = opt_config(optimize.as_deref(), &profile, no_inline, no_vectorize, sanitize.as_deref(), &target)?;
             let smt_profile: aura_verify::SmtProfile = smt_profile
                 .unwrap_or_else(|| profile.default_smt_profile())
                 .into();
 
             let targets = expand_workspace_targets(&path, &resolved);
             for t in targets {
-                build_one(
+                let out = build_one(
                     &t,
                     &parse_cfg,
                     &profile,
                     mode,
                     &backend_cli,
                     &resolved,
-                    &optimize,
+                    &target,
+                    &opt,
                     smt_profile,
                 )?;
+                if !out.llvm_units.is_empty() {
+                    link_build_outputs(&t, &out, &target, &toolchain, &opt)?;
+                }
             }
             Ok(())
         }
@@ -598,12 +698,17 @@ fn main() -> miette::Result<()> {
             link_dirs,
             link_libs,
             optimize,
+            no_inline,
+            no_vectorize,
+            sanitize,
+            target,
             smt_profile,
             hot,
         } => {
-            let resolved = resolve_manifest_config(&path, &bridge, &link_dirs, &link_libs)?;
+            let target = native_target(target.as_deref())?;
+            let resolved = resolve_manifest_config(&path, &bridge, &link_dirs, &link_libs, Some(&target))?;
             let parse_cfg = build_parse_config(&cli.edition, &cli.feature, &resolved);
-            let optimize = optimize.unwrap_or_else(|| profile.default_optimize().to_string());
+            let opt = opt_config(optimize.as_deref(), &profile, no_inline, no_vectorize, sanitize.as_deref(), &target)?;
             let smt_profile: aura_verify::SmtProfile = smt_profile
                 .unwrap_or_else(|| profile.default_smt_profile())
                 .into();
@@ -616,7 +721,9 @@ fn main() -> miette::Result<()> {
                     &resolved.lib_dirs,
                     &resolved.libs,
                     &resolved.nexus_plugins,
-                    &optimize,
+                    &target,
+                    &resolved.toolchain_for(&target),
+                    &opt,
                     smt_profile,
                     hot,
                 ),
@@ -626,7 +733,7 @@ fn main() -> miette::Result<()> {
                     } else {
                         // Hybrid run starts in AVM. Promotion to LLVM is handled inside the AVM
                         // (prototype: not yet implemented).
-                        run_avm(&path, smt_profile)
+                        run_avm(&path, smt_profile, false)
                     }
                 }
             }
@@ -638,7 +745,7 @@ fn main() -> miette::Result<()> {
             smt_profile,
             report,
         } => {
-            let resolved = resolve_manifest_config(&path, &[], &[], &[])?;
+            let resolved = resolve_manifest_config(&path, &[], &[], &[], None)?;
             let parse_cfg = build_parse_config(&cli.edition, &cli.feature, &resolved);
             let smt_profile: aura_verify::SmtProfile = smt_profile
                 .unwrap_or_else(|| profile.default_smt_profile())
@@ -685,7 +792,7 @@ fn main() -> miette::Result<()> {
         }
 
         Cmd::Test { path, smt_profile } => {
-            let resolved = resolve_manifest_config(&path, &[], &[], &[])?;
+            let resolved = resolve_manifest_config(&path, &[], &[], &[], None)?;
             let parse_cfg = build_parse_config(&cli.edition, &cli.feature, &resolved);
             let smt_profile: aura_verify::SmtProfile = smt_profile.into();
 
@@ -721,7 +828,7 @@ fn main() -> miette::Result<()> {
         }
 
         Cmd::Lint { path } => {
-            let resolved = resolve_manifest_config(&path, &[], &[], &[])?;
+            let resolved = resolve_manifest_config(&path, &[], &[], &[], None)?;
             let parse_cfg = build_parse_config(&cli.edition, &cli.feature, &resolved);
             let targets = expand_workspace_targets(&path, &resolved);
             let mut failed = 0usize;
@@ -748,6 +855,7 @@ fn main() -> miette::Result<()> {
                 deny_deprecated,
                 require_signature,
                 trusted_key,
+                target,
                 force,
                 no_smoke,
             } => pkg_add(
@@ -758,6 +866,7 @@ fn main() -> miette::Result<()> {
                 deny_deprecated,
                 require_signature,
                 trusted_key.as_deref(),
+                target.as_deref(),
                 force,
                 !no_smoke,
             ),
@@ -767,6 +876,7 @@ fn main() -> miette::Result<()> {
                 version,
                 registry,
                 from,
+                target,
                 signing_key,
                 key_id,
             } => {
@@ -775,6 +885,7 @@ fn main() -> miette::Result<()> {
                     version,
                     registry_dir: registry,
                     from_dir: from,
+                    target,
                     signing_key,
                     signature_key_id: key_id,
                 })?;
@@ -1043,13 +1154,13 @@ fn verify_file(
 ) -> miette::Result<()> {
     let src = fs::read_to_string(path).into_diagnostic()?;
     let src = augment_with_sdk_std(&src)?;
-    let source = NamedSource::new(display_path(path), src.clone());
-
-    let program =
-        aura_parse::parse_source_with_config(&src, parse_cfg).map_err(|e| e.with_source_code(source.clone()))?;
+    let graph = aura_sdk::modules::resolve_modules(path, &src, parse_cfg)?;
+    let source = NamedSource::new(display_path(path), graph.combined_source());
+    let program = graph.program;
 
     let mut checker = aura_core::Checker::new();
     checker.set_defer_range_proofs(true);
+    checker.set_symbols(graph.symbols);
     checker
         .check_program(&program)
         .map_err(|e| miette::Report::new(e).with_source_code(source.clone()))?;
@@ -1079,12 +1190,9 @@ fn verify_file_with_report(
 ) -> miette::Result<()> {
     let src = fs::read_to_string(path).into_diagnostic()?;
     let src = augment_with_sdk_std(&src)?;
-    let source = NamedSource::new(display_path(path), src.clone());
 
-    let program = match aura_parse::parse_source_with_config(&src, parse_cfg)
-        .map_err(|e| e.with_source_code(source.clone()))
-    {
-        Ok(p) => p,
+    let graph = match aura_sdk::modules::resolve_modules(path, &src, parse_cfg) {
+        Ok(g) => g,
         Err(e) => {
             let _ = report::write_verify_report(
                 path,
@@ -1097,9 +1205,12 @@ fn verify_file_with_report(
             return Err(e);
         }
     };
+    let source = NamedSource::new(display_path(path), graph.combined_source());
+    let program = graph.program;
 
     let mut checker = aura_core::Checker::new();
     checker.set_defer_range_proofs(true);
+    checker.set_symbols(graph.symbols);
     if let Err(e) = checker
         .check_program(&program)
         .map_err(|e| miette::Report::new(e).with_source_code(source.clone()))
@@ -1164,6 +1275,15 @@ fn lint_file(path: &Path, parse_cfg: &ParseConfig) -> miette::Result<()> {
     let mut checker = aura_core::Checker::new();
     checker.set_defer_range_proofs(true);
     checker.check_program(&program).map_err(miette::Report::new)?;
+    // Warnings don't fail the lint; the stdlib appended to the source is not reported.
+    let file = display_path(path);
+    let mut lints = aura_core::lint_program(&program, &file, &src);
+    lints.extend(checker.match_lints(&file, &src));
+    lints.extend(aura_core::detect_flow_races(&program, &src_aug).iter().filter_map(|r| r.lint(&file, &src)));
+    lints.sort_by_key(|l| l.span.offset());
+    for lint in lints {
+        eprint!("{}", lint.diagnostic.display());
+    }
     Ok(())
 }
 
@@ -1174,7 +1294,8 @@ fn build_one(
     mode: Mode,
     backend_cli: &str,
     resolved: &manifest::ResolvedManifest,
-    optimize: &str,
+    target: &aura_backend_llvm::NativeTarget,
+    opt: &aura_backend_llvm::OptConfig,
     smt_profile: aura_verify::SmtProfile,
 ) -> miette::Result<BuildOutputs> {
     if mode == Mode::Avm {
@@ -1183,8 +1304,7 @@ fn build_one(
         return Ok(BuildOutputs {
             out_dir: build_dir(path),
             module_c: None,
-            llvm_ll: None,
-            llvm_opt_ll: None,
+            llvm_units: Vec::new(),
             link: aura_bridge::LinkInputs::default(),
         });
     }
@@ -1204,7 +1324,8 @@ fn build_one(
         &resolved.lib_dirs,
         &resolved.libs,
         &resolved.nexus_plugins,
-        optimize,
+        target,
+        opt,
         smt_profile,
     )
 }
@@ -1217,7 +1338,8 @@ fn build_cached(
     link_dirs: &[PathBuf],
     link_libs: &[String],
     nexus_plugins: &[PluginManifest],
-    optimize: &str,
+    target: &aura_backend_llvm::NativeTarget,
+    opt: &aura_backend_llvm::OptConfig,
     smt_profile: aura_verify::SmtProfile,
 ) -> miette::Result<BuildOutputs> {
     let cache_root = PathBuf::from(".aura").join("cache");
@@ -1246,7 +1368,10 @@ fn build_cached(
         hasher.update(f.as_bytes());
     }
     hasher.update(backend.as_bytes());
-    hasher.update(optimize.as_bytes());
+    if backend == "llvm" {
+        hasher.update(target.triple().as_bytes());
+    }
+    hasher.update(opt.summary().as_bytes());
     hasher.update(format!("{:?}", smt_profile).as_bytes());
     for d in link_dirs {
         hasher.update(d.to_string_lossy().as_bytes());
@@ -1264,14 +1389,16 @@ fn build_cached(
     let out_dir = build_dir(path);
     fs::create_dir_all(&out_dir).into_diagnostic()?;
 
-    // Cache hit: restore known artifacts.
-    if entry_dir.exists() {
+    // Cache hit: restore known artifacts. Bridged native builds are rebuilt instead, since
+    // linking them needs the bridge's link inputs, which are not cached.
+    let bridged_native = backend == "llvm" && !bridge_headers.is_empty();
+    if entry_dir.exists() && !bridged_native {
         let mut restored_any = false;
         for f in [
             "module.c",
             "aura_runtime.h",
-            "module.ll",
-            "module.opt.ll",
+            "units.json",
+            "build-info.json",
             "combined.aura",
         ] {
             let srcp = entry_dir.join(f);
@@ -1290,25 +1417,20 @@ fn build_cached(
             } else {
                 None
             };
-            let llvm_ll = if backend == "llvm" {
-                let p = out_dir.join("module.ll");
-                if p.exists() { Some(p) } else { None }
-            } else {
-                None
-            };
-            let llvm_opt_ll = if backend == "llvm" {
-                let p = out_dir.join("module.opt.ll");
-                if p.exists() { Some(p) } else { None }
+            let llvm_units = if backend == "llvm" {
+                read_units_manifest(&out_dir.join("units.json"))?
             } else {
-                None
+                Vec::new()
             };
             return Ok(BuildOutputs {
                 out_dir,
                 module_c,
-                llvm_ll,
-                llvm_opt_ll,
-                // LinkInputs are only needed for full native linking; rebuild would be required.
-                link: aura_bridge::LinkInputs::default(),
+                llvm_units,
+                link: aura_bridge::LinkInputs {
+                    lib_dirs: link_dirs.to_vec(),
+                    libs: link_libs.to_vec(),
+                    ..Default::default()
+                },
             });
         }
     }
@@ -1322,7 +1444,8 @@ fn build_cached(
         link_dirs,
         link_libs,
         nexus_plugins,
-        optimize,
+        target,
+        opt,
         smt_profile,
     )?;
 
@@ -1330,8 +1453,8 @@ fn build_cached(
     for f in [
         out.out_dir.join("module.c"),
         out.out_dir.join("aura_runtime.h"),
-        out.out_dir.join("module.ll"),
-        out.out_dir.join("module.opt.ll"),
+        out.out_dir.join("units.json"),
+        out.out_dir.join("build-info.json"),
         out.out_dir.join("combined.aura"),
     ] {
         if f.exists() {
@@ -1344,6 +1467,108 @@ fn build_cached(
     Ok(out)
 }
 
+/// Links a native build's units into an executable for `target` next to them. Without clang
+/// the units are left for a later link.
+fn link_build_outputs(
+    path: &Path,
+    out: &BuildOutputs,
+    target: &aura_backend_llvm::NativeTarget,
+    toolchain: &linker::Toolchain,
+    opt: &aura_backend_llvm::OptConfig,
+) -> miette::Result<()> {
+    if linker::find_clang().is_none() {
+        println!("clang not found: skipped linking for {}", target.triple());
+        return Ok(());
+    }
+    let exe = out.out_dir.join(exe_name(path, target.exe_suffix()));
+    let fs_policy = aura_sdk::fs_policy::load_fs_policy(path)?;
+    linker::link_with_clang(
+        &out.llvm_units,
+        &exe,
+        target,
+        toolchain,
+        opt,
+        &out.link.lib_dirs,
+        &out.link.libs,
+        &out.link.c_sources,
+        &out.link.include_dirs,
+        &out.link.runtime_dlls,
+        fs_policy.as_ref(),
+    )
+    .map_err(miette::Report::new)?;
+    println!("wrote {}", exe.display());
+    Ok(())
+}
+
+/// Units recorded by an earlier build. IR files that have since been removed are dropped;
+/// their objects are expected in the object cache.
+fn read_units_manifest(path: &Path) -> miette::Result<Vec<linker::UnitObject>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let text = fs::read_to_string(path).into_diagnostic()?;
+    let mut units: Vec<linker::UnitObject> = serde_json::from_str(&text).into_diagnostic()?;
+    for unit in &mut units {
+        unit.ll = unit.ll.take().filter(|ll| ll.exists());
+    }
+    Ok(units)
+}
+
+/// Object-cache key for each function's unit: the merkle hash of the top-level statement it
+/// was lowered from (which covers everything it calls), its signature, the statement's line
+/// (debug info records lines), the register map `hw.*` calls compile against, and the target
+/// and optimization settings. Functions lowered from no cell or flow get no key; their units
+/// are keyed by their emitted IR instead.
+#[cfg(all(feature = "z3", feature = "llvm"))]
+fn unit_cache_keys(
+    program: &aura_ast::Program,
+    text: &str,
+    module_ir: &aura_ir::ModuleIR,
+    debug: &DebugSource,
+    target: &aura_backend_llvm::NativeTarget,
+    opt: &aura_backend_llvm::OptConfig,
+) -> std::collections::BTreeMap<String, String> {
+    use aura_ast::Stmt;
+    use aura_sdk::merkle::{sha256_hex, stmt_merkle_hashes};
+
+    let slice = |span: aura_ast::Span| text.get(span.offset()..span.offset() + span.len()).unwrap_or("");
+
+    // Types, externs and imports can change any unit's code, so every hash covers them.
+    let mut shared = String::new();
+    for stmt in &program.stmts {
+        if !matches!(stmt, Stmt::CellDef(_) | Stmt::FlowBlock(_) | Stmt::Layout(_) | Stmt::Render(_)) {
+            shared.push_str(slice(stmt.span()));
+            shared.push('\n');
+        }
+    }
+    let hashes = stmt_merkle_hashes(program, text, &sha256_hex(&shared));
+
+    let mut keys = std::collections::BTreeMap::new();
+    for (name, f) in &module_ir.functions {
+        let at = f.span.offset();
+        let owner = program.stmts.iter().zip(&hashes).find_map(|(stmt, hash)| {
+            let span = stmt.span();
+            let hash = hash.as_ref()?;
+            (span.offset() <= at && at < span.offset() + span.len()).then_some((span, &hash.hash))
+        });
+        let Some((span, hash)) = owner else { continue };
+        let params: Vec<&aura_ir::Type> = f.params.iter().map(|p| &p.ty).collect();
+        let input = format!(
+            "unit-v1\naura={}\nstmt={hash}\nfile={}\nline={}\nfn={name}\nsig={params:?}->{:?}\nclosure={}\nmmio={:?}\ntarget={}\nopt={}\n",
+            env!("CARGO_PKG_VERSION"),
+            debug.file_name,
+            debug.line_col(span).line,
+            f.ret,
+            f.is_closure,
+            module_ir.mmio,
+            target.triple(),
+            opt.summary(),
+        );
+        keys.insert(name.clone(), sha256_hex(&input));
+    }
+    keys
+}
+
 fn pkg_add(
     package: &str,
     version: Option<&str>,
@@ -1352,6 +1577,7 @@ fn pkg_add(
     deny_deprecated: bool,
     require_signature: bool,
     trusted_key: Option<&Path>,
+    target: Option<&str>,
     force: bool,
     smoke: bool,
 ) -> miette::Result<()> {
@@ -1363,6 +1589,7 @@ fn pkg_add(
         .as_ref()
         .and_then(|p| p.parent().map(|p| p.to_path_buf()))
         .unwrap_or(cwd);
+    let target = native_target(target)?;
 
     // Install artifacts.
     let result = aura_pkg::add_package(
@@ -1377,6 +1604,7 @@ fn pkg_add(
             require_signature,
             trusted_public_key: trusted_key.map(|p| p.to_path_buf()),
             deny_deprecated,
+            target: Some(target.triple()),
         },
     )?;
 
@@ -1389,7 +1617,7 @@ fn pkg_add(
     );
 
     if smoke {
-        pkg_smoke_test(&project_root, &result.package)?;
+        pkg_smoke_test(&project_root, &result.package, &target)?;
         println!("smoke test: ok");
     }
 
@@ -1505,7 +1733,11 @@ fn push_string_array_unique(doc: &mut toml::Value, table_path: &[&str], key: &st
     }
 }
 
-fn pkg_smoke_test(project_root: &Path, package: &str) -> miette::Result<()> {
+fn pkg_smoke_test(
+    project_root: &Path,
+    package: &str,
+    target: &aura_backend_llvm::NativeTarget,
+) -> miette::Result<()> {
     // Build-only + link-only test: does not execute.
     let smoke_dir = project_root.join("build").join("pkg_smoke");
     fs::create_dir_all(&smoke_dir).into_diagnostic()?;
@@ -1520,8 +1752,9 @@ fn pkg_smoke_test(project_root: &Path, package: &str) -> miette::Result<()> {
     };
     fs::write(&smoke_file, src).into_diagnostic()?;
 
-    let resolved = resolve_manifest_config(&smoke_file, &[], &[], &[])?;
+    let resolved = resolve_manifest_config(&smoke_file, &[], &[], &[], Some(target))?;
     let parse_cfg = build_parse_config(&None, &[], &resolved);
+    let opt = aura_backend_llvm::OptConfig::new(aura_backend_llvm::OptLevel::O0);
     let out = build(
         &smoke_file,
         &parse_cfg,
@@ -1530,24 +1763,25 @@ fn pkg_smoke_test(project_root: &Path, package: &str) -> miette::Result<()> {
         &resolved.lib_dirs,
         &resolved.libs,
         &resolved.nexus_plugins,
-        "none",
+        target,
+        &opt,
         aura_verify::SmtProfile::Ci,
     )?;
 
-    let ll = out
-        .llvm_opt_ll
-        .as_ref()
-        .or(out.llvm_ll.as_ref())
-        .expect("LLVM backend produces module.ll");
-
-    let exe = out.out_dir.join(exe_name(&smoke_file));
+    let exe = out.out_dir.join(exe_name(&smoke_file, target.exe_suffix()));
+    let fs_policy = aura_sdk::fs_policy::load_fs_policy(&smoke_file)?;
     linker::link_with_clang(
-        ll,
+        &out.llvm_units,
         &exe,
+        target,
+        &resolved.toolchain_for(target),
+        &opt,
         &out.link.lib_dirs,
         &out.link.libs,
         &out.link.c_sources,
+        &out.link.include_dirs,
         &out.link.runtime_dlls,
+        fs_policy.as_ref(),
     )
     .map_err(miette::Report::new)?;
 
@@ -1559,6 +1793,7 @@ fn resolve_manifest_config(
     cli_bridge: &[PathBuf],
     cli_link_dirs: &[PathBuf],
     cli_link_libs: &[String],
+    target: Option<&aura_backend_llvm::NativeTarget>,
 ) -> miette::Result<manifest::ResolvedManifest> {
     // Start searching from the aura file directory.
     let resolved = manifest::load_resolved_manifest(aura_file).map_err(miette::Report::new)?;
@@ -1613,7 +1848,7 @@ fn resolve_manifest_config(
 
     // Zero-config native deps: if the source imports a known native package
     // and its artifacts are missing, install it automatically.
-    out = maybe_auto_install_native_deps(aura_file, out, cli_bridge, cli_link_dirs, cli_link_libs)?;
+    out = maybe_auto_install_native_deps(aura_file, out, cli_bridge, cli_link_dirs, cli_link_libs, target)?;
 
     Ok(out)
 }
@@ -1624,6 +1859,7 @@ fn maybe_auto_install_native_deps(
     cli_bridge: &[PathBuf],
     cli_link_dirs: &[PathBuf],
     cli_link_libs: &[String],
+    target: Option<&aura_backend_llvm::NativeTarget>,
 ) -> miette::Result<manifest::ResolvedManifest> {
     let src = fs::read_to_string(aura_file).into_diagnostic()?;
 
@@ -1664,6 +1900,7 @@ fn maybe_auto_install_native_deps(
                 require_signature: false,
                 trusted_public_key: None,
                 deny_deprecated: false,
+                target: target.map(|t| t.triple()),
             },
         )?;
         update_manifest_for_install(&resolved.project_root, &install)?;
@@ -1693,6 +1930,7 @@ fn maybe_auto_install_native_deps(
                 require_signature: false,
                 trusted_public_key: None,
                 deny_deprecated: false,
+                target: target.map(|t| t.triple()),
             },
         )?;
         update_manifest_for_install(&resolved.project_root, &install)?;
@@ -1764,11 +2002,11 @@ fn build(
     link_dirs: &[PathBuf],
     link_libs: &[String],
     nexus_plugins: &[PluginManifest],
-    optimize: &str,
+    target: &aura_backend_llvm::NativeTarget,
+    opt: &aura_backend_llvm::OptConfig,
     smt_profile: aura_verify::SmtProfile,
 ) -> miette::Result<BuildOutputs> {
     let _ = nexus_plugins;
-    let _ = optimize;
     let _ = smt_profile;
 
     let src = fs::read_to_string(path).into_diagnostic()?;
@@ -1787,7 +2025,12 @@ fn build(
         let outputs = aura_bridge::run_bridge(
             &aura_bridge::BridgeConfig {
                 headers: bridge_headers.to_vec(),
-                include_dirs: vec![],
+                // Shims include the headers packages unpack into the project's `include/`.
+                include_dirs: manifest::find_manifest(path)
+                    .and_then(|m| m.parent().map(|root| root.join("include")))
+                    .filter(|dir| dir.is_dir())
+                    .into_iter()
+                    .collect(),
                 lib_dirs: link_dirs.to_vec(),
                 libs: link_libs.to_vec(),
                 refine_types: false,
@@ -1811,18 +2054,19 @@ fn build(
         combined_src = src.clone();
     }
 
-    // Stage 0: SDK stdlib augmentation.
+    // Stage 0: SDK stdlib augmentation, then user modules.
     combined_src = augment_with_sdk_std(&combined_src)?;
+    let graph = aura_sdk::modules::resolve_modules(path, &combined_src, parse_cfg)?;
+    combined_src = graph.combined_source();
 
     let source = NamedSource::new(display_path(path), combined_src.clone());
-
-    let program = aura_parse::parse_source_with_config(&combined_src, parse_cfg)
-        .map_err(|e| e.with_source_code(source.clone()))?;
+    let program = graph.program;
 
     let mut checker = aura_core::Checker::new();
     // Prototype: defer non-trivial constrained-range proofs to `aura-verify`.
     // This is required for loop-local facts (e.g., `i = i + 1` under `while i < 10`).
     checker.set_defer_range_proofs(true);
+    checker.set_symbols(graph.symbols);
     checker
         .check_program(&program)
         .map_err(|e| miette::Report::new(e).with_source_code(source.clone()))?;
@@ -1844,8 +2088,7 @@ fn build(
             Ok(BuildOutputs {
                 out_dir,
                 module_c: Some(module_c),
-                llvm_ll: None,
-                llvm_opt_ll: None,
+                llvm_units: Vec::new(),
                 link,
             })
         }
@@ -1872,8 +2115,7 @@ fn build(
             Ok(BuildOutputs {
                 out_dir,
                 module_c: Some(module_c),
-                llvm_ll: None,
-                llvm_opt_ll: None,
+                llvm_units: Vec::new(),
                 link,
             })
         }
@@ -1897,31 +2139,74 @@ fn build(
                 )
                     .map_err(|e| miette::Report::new(e).with_source_code(source.clone()))?;
 
-                let artifacts = aura_backend_llvm::emit_llvm_ir(&module_ir, Some(&debug)).into_diagnostic()?;
-                let ll = out_dir.join("module.ll");
-                fs::write(&ll, artifacts.llvm_ir).into_diagnostic()?;
-                println!("wrote {}", ll.display());
-
-                let llvm_opt_ll = match optimize {
-                    "none" => None,
-                    "full" => {
-                        let opt_ll = out_dir.join("module.opt.ll");
-                        aura_ai_opt::optimize_ll_file(&ll, &opt_ll)?;
-                        println!("wrote {}", opt_ll.display());
-                        Some(opt_ll)
-                    }
-                    other => {
-                        return Err(miette::miette!(
-                            "unknown --optimize value: {other} (expected 'none' or 'full')"
-                        ));
+                // `hw.*` calls compile to volatile accesses at the manifest's addresses.
+                let mut module_ir = module_ir;
+                let hardware = aura_plugin_iot::HardwareManifest::load_from(path)?;
+                module_ir.mmio = hardware
+                    .registers
+                    .iter()
+                    .map(|r| {
+                        let region = aura_ir::MmioRegion {
+                            base: r.base,
+                            size: r.size,
+                            instances: r.instances,
+                            stride: r.stride,
+                        };
+                        (r.name.clone(), region)
+                    })
+                    .collect();
+
+                // Each function is its own unit; only those without a cached object are re-emitted.
+                let keys = unit_cache_keys(&program, &combined_src, &module_ir, &debug, target, opt);
+                let units = aura_backend_llvm::emit_llvm_units_for_target(&module_ir, Some(&debug), target, opt, |f| {
+                    keys.get(f).is_none_or(|key| !linker::unit_object_path(key, target, opt).exists())
+                })
+                .into_diagnostic()?;
+
+                let units_dir = out_dir.join("units");
+                fs::create_dir_all(&units_dir).into_diagnostic()?;
+                let total = units.len();
+                let mut llvm_units = Vec::with_capacity(total);
+                for unit in units {
+                    let key = unit.function.as_ref().and_then(|f| keys.get(f)).cloned();
+                    let Some(ir) = unit.llvm_ir else {
+                        let key = key.expect("units without a key are always emitted");
+                        llvm_units.push(linker::UnitObject { name: unit.name, key, ll: None });
+                        continue;
+                    };
+                    let key = key.unwrap_or_else(|| aura_sdk::merkle::sha256_hex(&ir));
+                    let mut ll = units_dir.join(format!("{}.ll", unit.name));
+                    fs::write(&ll, ir).into_diagnostic()?;
+                    // Metadata post-pass (vectorization hints, range facts) for the optimizing levels.
+                    if opt.level >= aura_backend_llvm::OptLevel::O2 {
+                        let opt_ll = units_dir.join(format!("{}.opt.ll", unit.name));
+                        optimize_unit_ll(&ll, &opt_ll)?;
+                        ll = opt_ll;
                     }
+                    llvm_units.push(linker::UnitObject { name: unit.name, key, ll: Some(ll) });
+                }
+                let emitted = llvm_units.iter().filter(|u| u.ll.is_some()).count();
+                println!("wrote {} ({emitted} of {total} units; the rest are cached)", units_dir.display());
+                let manifest = out_dir.join("units.json");
+                fs::write(&manifest, serde_json::to_string_pretty(&llvm_units).into_diagnostic()?)
+                    .into_diagnostic()?;
+
+                let info = BuildInfo {
+                    target: target.triple(),
+                    opt_level: opt.level.as_str(),
+                    inline: opt.inline,
+                    vectorize: opt.vectorize,
+                    clang_args: opt.clang_args(),
                 };
+                let info_path = out_dir.join("build-info.json");
+                fs::write(&info_path, serde_json::to_string_pretty(&info).into_diagnostic()?)
+                    .into_diagnostic()?;
+                println!("wrote {}", info_path.display());
 
                 Ok(BuildOutputs {
                     out_dir,
                     module_c: None,
-                    llvm_ll: Some(ll),
-                    llvm_opt_ll,
+                    llvm_units,
                     link,
                 })
             }
@@ -1940,7 +2225,9 @@ fn run(
     link_dirs: &[PathBuf],
     link_libs: &[String],
     nexus_plugins: &[PluginManifest],
-    optimize: &str,
+    target: &aura_backend_llvm::NativeTarget,
+    toolchain: &linker::Toolchain,
+    opt: &aura_backend_llvm::OptConfig,
     smt_profile: aura_verify::SmtProfile,
     hot: bool,
 ) -> miette::Result<()> {
@@ -1968,7 +2255,9 @@ fn run(
             link_dirs,
             link_libs,
             nexus_plugins,
-            optimize,
+            target,
+            toolchain,
+            opt,
             smt_profile,
         );
     }
@@ -1981,16 +2270,17 @@ fn run(
         link_dirs,
         link_libs,
         nexus_plugins,
-        optimize,
+        target,
+        opt,
         smt_profile,
     )?;
 
     match backend {
         "c" => {
             let module_c = out.module_c.as_ref().expect("C backend produces module.c");
-            let exe = out.out_dir.join(exe_name(path));
+            let exe = out.out_dir.join(exe_name(path, std::env::consts::EXE_SUFFIX));
             if let Some((cc, kind)) = find_c_compiler() {
-                compile_c(&cc, kind, module_c, &exe)?;
+                compile_c(&cc, kind, module_c, &exe, &opt.sanitize)?;
 
                 if let Some((sess, _handle)) = debug_pair() {
                     sess.emit(DebugEvent::NativeLaunch {
@@ -2012,20 +2302,26 @@ fn run(
             }
         }
         "llvm" => {
-            let ll = out
-                .llvm_opt_ll
-                .as_ref()
-                .or(out.llvm_ll.as_ref())
-                .expect("LLVM backend produces module.ll");
-
-            let exe = out.out_dir.join(exe_name(path));
+            if !target.is_host() {
+                return Err(miette::miette!(
+                    "cannot run an executable built for '{}' on this machine (drop --target to run natively)",
+                    target.triple()
+                ));
+            }
+            let exe = out.out_dir.join(exe_name(path, target.exe_suffix()));
+            let fs_policy = aura_sdk::fs_policy::load_fs_policy(path)?;
             linker::link_with_clang(
-                ll,
+                &out.llvm_units,
                 &exe,
+                target,
+                toolchain,
+                opt,
                 &out.link.lib_dirs,
                 &out.link.libs,
                 &out.link.c_sources,
+                &out.link.include_dirs,
                 &out.link.runtime_dlls,
+                fs_policy.as_ref(),
             )
             .map_err(miette::Report::new)?;
 
@@ -2060,7 +2356,9 @@ fn run_hot(
     link_dirs: &[PathBuf],
     link_libs: &[String],
     nexus_plugins: &[PluginManifest],
-    optimize: &str,
+    target: &aura_backend_llvm::NativeTarget,
+    toolchain: &linker::Toolchain,
+    opt: &aura_backend_llvm::OptConfig,
     smt_profile: aura_verify::SmtProfile,
 ) -> miette::Result<()> {
     let (tx, rx) = std::sync::mpsc::channel::<notify::Result<notify::Event>>();
@@ -2096,27 +2394,34 @@ fn run_hot(
             link_dirs,
             link_libs,
             nexus_plugins,
-            optimize,
+            target,
+            opt,
             smt_profile,
         )?;
         if backend != "llvm" {
             return Err(miette::miette!("--hot is currently supported only for --backend llvm"));
         }
 
-        let ll = out
-            .llvm_opt_ll
-            .as_ref()
-            .or(out.llvm_ll.as_ref())
-            .expect("LLVM backend produces module.ll");
-
-        let exe = out.out_dir.join(exe_name(path));
+        if !target.is_host() {
+            return Err(miette::miette!(
+                "cannot run an executable built for '{}' on this machine (drop --target to run natively)",
+                target.triple()
+            ));
+        }
+        let exe = out.out_dir.join(exe_name(path, target.exe_suffix()));
+        let fs_policy = aura_sdk::fs_policy::load_fs_policy(path)?;
         linker::link_with_clang(
-            ll,
+            &out.llvm_units,
             &exe,
+            target,
+            toolchain,
+            opt,
             &out.link.lib_dirs,
             &out.link.libs,
             &out.link.c_sources,
+            &out.link.include_dirs,
             &out.link.runtime_dlls,
+            fs_policy.as_ref(),
         )
         .map_err(miette::Report::new)?;
 
@@ -2267,7 +2572,9 @@ fn verify_program_z3_report_with_manifest_plugins(
     }
 }
 
-fn run_avm(path: &Path, smt_profile: aura_verify::SmtProfile) -> miette::Result<()> {
+/// Runs `path` on the AVM. With `hot_reload`, edits saved while a UI loop runs are swapped
+/// into the running program between frames.
+fn run_avm(path: &Path, smt_profile: aura_verify::SmtProfile, hot_reload: bool) -> miette::Result<()> {
     let src = fs::read_to_string(path).into_diagnostic()?;
     let src = augment_with_sdk_std(&src)?;
 
@@ -2277,6 +2584,10 @@ fn run_avm(path: &Path, smt_profile: aura_verify::SmtProfile) -> miette::Result<
         cfg.enable_z3_gate = false;
     }
     cfg.smt_profile = smt_profile;
+    cfg.fs_policy = aura_sdk::fs_policy::load_fs_policy(path)?;
+    if hot_reload {
+        cfg.hot_reload = Some(aura_interpret::HotReload::new(path).with_prepare(augment_with_sdk_std));
+    }
 
     if let Some((sess, _handle)) = debug_pair() {
         sess.set_source(path.display().to_string(), &src);
@@ -2289,7 +2600,10 @@ fn run_avm(path: &Path, smt_profile: aura_verify::SmtProfile) -> miette::Result<
     let out = match avm.exec_entry_cell_with_ui_plugins(&src, "main", &ui_plugins, &mut nexus) {
         Ok(out) => out,
         Err(e) => {
-            if e.downcast_ref::<aura_interpret::AvmTerminated>().is_some() {
+            if matches!(
+                e.downcast_ref::<aura_interpret::AvmTerminated>(),
+                Some(aura_interpret::AvmTerminated::Cancelled)
+            ) {
                 return Ok(());
             }
             return Err(e);
@@ -2339,7 +2653,7 @@ fn run_avm_hot(path: &Path, smt_profile: aura_verify::SmtProfile) -> miette::Res
     println!("hot reload enabled (avm): watching {}", path.display());
 
     loop {
-        if let Err(e) = run_avm(path, smt_profile) {
+        if let Err(e) = run_avm(path, smt_profile, true) {
             eprintln!("avm run failed: {e:?}");
         }
 
@@ -2416,7 +2730,13 @@ enum CcKind {
     ClangOrGcc,
 }
 
-fn compile_c(cc: &str, kind: CcKind, module_c: &Path, exe: &Path) -> miette::Result<()> {
+fn compile_c(
+    cc: &str,
+    kind: CcKind,
+    module_c: &Path,
+    exe: &Path,
+    sanitize: &aura_backend_llvm::Sanitizers,
+) -> miette::Result<()> {
     match kind {
         CcKind::ClangOrGcc => {
             let stdlib_c = aura_stdlib::stdlib_c_path();
@@ -2430,6 +2750,7 @@ fn compile_c(cc: &str, kind: CcKind, module_c: &Path, exe: &Path) -> miette::Res
                 .arg("-g")
                 .arg("-O2")
                 .arg(format!("-I{}", stdlib_include_dir.display()))
+                .args(sanitize.clang_args())
                 .arg(module_c)
                 .arg(stdlib_c)
                 .arg("-o")
@@ -2466,6 +2787,35 @@ fn compile_wasm_wasi(clang: &Path, module_c: &Path, wasm: &Path) -> miette::Resu
     Ok(())
 }
 
+/// Runs the metadata post-pass over a unit's IR. `aura-ai-opt-llvm` (next to this executable,
+/// or on PATH) runs it over the parsed module where LLVM is installed; without it, or when it
+/// fails, `aura-ai-opt` rewrites the IR text.
+fn optimize_unit_ll(ll: &Path, opt_ll: &Path) -> miette::Result<()> {
+    if let Some(tool) = find_ai_opt_llvm() {
+        match Command::new(&tool).arg(ll).arg("--output").arg(opt_ll).output() {
+            Ok(out) if out.status.success() => return Ok(()),
+            Ok(out) => eprintln!(
+                "{} failed on {}; rewriting the IR text instead: {}",
+                tool.display(),
+                ll.display(),
+                String::from_utf8_lossy(&out.stderr).trim()
+            ),
+            Err(e) => eprintln!("could not run {}; rewriting the IR text instead: {e}", tool.display()),
+        }
+    }
+    aura_ai_opt::optimize_ll_file(ll, opt_ll)
+}
+
+fn find_ai_opt_llvm() -> Option<PathBuf> {
+    let name = format!("aura-ai-opt-llvm{}", std::env::consts::EXE_SUFFIX);
+    let beside = std::env::current_exe().ok().and_then(|exe| Some(exe.parent()?.join(&name)));
+    if let Some(p) = beside.filter(|p| p.is_file()) {
+        return Some(p);
+    }
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths).map(|dir| dir.join(&name)).find(|p| p.is_file())
+}
+
 fn find_c_compiler() -> Option<(String, CcKind)> {
     // Prefer clang then gcc.
     if Command::new("clang").arg("--version").output().is_ok() {
@@ -2485,12 +2835,24 @@ fn build_dir(input: &Path) -> PathBuf {
     PathBuf::from("build").join(stem)
 }
 
-fn exe_name(input: &Path) -> String {
+fn exe_name(input: &Path, suffix: &str) -> String {
     let stem = input
         .file_stem()
         .and_then(|s| s.to_str())
         .unwrap_or("aura");
-    format!("{stem}.exe")
+    format!("{stem}{suffix}")
+}
+
+/// Target for LLVM emission and native linking: the `--target` triple, else the one in
+/// `AURA_TARGET`, else the host (e.g. `x86_64-unknown-linux-gnu`, `aarch64-apple-darwin`,
+/// `x86_64-pc-windows-msvc`).
+fn native_target(cli: Option<&str>) -> miette::Result<aura_backend_llvm::NativeTarget> {
+    let env = std::env::var("AURA_TARGET").ok();
+    let target = match cli.or(env.as_deref()).map(str::trim) {
+        Some(triple) if !triple.is_empty() => aura_backend_llvm::NativeTarget::parse(triple),
+        _ => aura_backend_llvm::NativeTarget::host(),
+    };
+    target.map_err(miette::Report::new)
 }
 
 fn wasm_name(input: &Path) -> String {
@@ -34,6 +34,9 @@ pub struct ResolvedManifest {
 
     /// Enabled unstable features.
     pub features: Vec<String>,
+
+    /// Per-target sysroot and linker settings (`[target.<triple>]`), keyed by triple.
+    pub toolchains: BTreeMap<String, crate::linker::Toolchain>,
 }
 
 impl ResolvedManifest {
@@ -48,8 +51,18 @@ impl ResolvedManifest {
             nexus_plugins: Vec::new(),
             edition: None,
             features: Vec::new(),
+            toolchains: BTreeMap::new(),
         }
     }
+
+    /// Settings for `target` from `[target.<triple>]`, or defaults when it has none.
+    pub fn toolchain_for(&self, target: &aura_backend_llvm::NativeTarget) -> crate::linker::Toolchain {
+        self.toolchains
+            .iter()
+            .find(|(triple, _)| aura_backend_llvm::NativeTarget::parse(triple).is_ok_and(|t| t == *target))
+            .map(|(_, toolchain)| toolchain.clone())
+            .unwrap_or_default()
+    }
 }
 
 #[allow(dead_code)]
@@ -78,6 +91,10 @@ struct Manifest {
     // Aura Nexus plugin list.
     #[serde(default)]
     plugins: Vec<aura_nexus::PluginManifest>,
+
+    // Cross-compilation settings: `[target.x86_64-pc-windows-msvc] sysroot = "..."`.
+    #[serde(default)]
+    target: BTreeMap<String, TargetToolchain>,
 }
 
 #[allow(dead_code)]
@@ -127,6 +144,15 @@ struct Linking {
     libs: Vec<String>,
 }
 
+#[derive(Clone, Debug, Default, serde::Deserialize)]
+struct TargetToolchain {
+    #[serde(default)]
+    sysroot: Option<String>,
+
+    #[serde(default)]
+    linker: Option<String>,
+}
+
 pub fn find_manifest(start: &Path) -> Option<PathBuf> {
     let mut cur = if start.is_file() {
         start.parent()?.to_path_buf()
@@ -185,6 +211,7 @@ pub fn load_resolved_manifest(start: &Path) -> Result<ResolvedManifest, Manifest
         nexus_plugins: Vec::new(),
         edition: None,
         features: Vec::new(),
+        toolchains: BTreeMap::new(),
     };
 
     if let Some(project) = parsed.project {
@@ -222,6 +249,14 @@ pub fn load_resolved_manifest(start: &Path) -> Result<ResolvedManifest, Manifest
         out.libs.extend(linking.libs);
     }
 
+    for (triple, toolchain) in parsed.target {
+        let toolchain = crate::linker::Toolchain {
+            sysroot: toolchain.sysroot.map(|p| resolve_path(&manifest_dir, &p)),
+            linker: toolchain.linker,
+        };
+        out.toolchains.insert(triple, toolchain);
+    }
+
     // Nexus plugins (top-level `plugins = [...]`).
     out.nexus_plugins = parsed.plugins;
 
@@ -2,7 +2,7 @@
 
 use std::path::Path;
 
-use aura_ast::{ExternCell, Program, Span, Stmt, UnsafeBlock};
+use aura_ast::{ExternCell, Program, RegionBlock, ScopeBlock, Span, Stmt, UnsafeBlock};
 use miette::IntoDiagnostic;
 use serde::Serialize;
 
@@ -91,6 +91,11 @@ fn analyze_stmt(out: &mut TrustedSurface, stmt: &Stmt) {
                 analyze_stmt(out, s);
             }
         }
+        Stmt::Scope(ScopeBlock { body, .. }) | Stmt::Region(RegionBlock { body, .. }) => {
+            for s in &body.stmts {
+                analyze_stmt(out, s);
+            }
+        }
         Stmt::If(s) => {
             for st in &s.then_block.stmts {
                 analyze_stmt(out, st);
@@ -113,6 +118,11 @@ fn analyze_stmt(out: &mut TrustedSurface, stmt: &Stmt) {
                 analyze_stmt(out, st);
             }
         }
+        Stmt::For(s) => {
+            for st in &s.body.stmts {
+                analyze_stmt(out, st);
+            }
+        }
         Stmt::Layout(s) => {
             for st in &s.body.stmts {
                 analyze_stmt(out, st);
@@ -174,7 +184,9 @@ fn analyze_verify_stmt(assumptions: &mut Vec<SpanRange>, obligations: &mut Vec<S
                 analyze_verify_stmt(assumptions, obligations, st);
             }
         }
-        Stmt::UnsafeBlock(UnsafeBlock { body, .. }) => {
+        Stmt::UnsafeBlock(UnsafeBlock { body, .. })
+        | Stmt::Scope(ScopeBlock { body, .. })
+        | Stmt::Region(RegionBlock { body, .. }) => {
             for st in &body.stmts {
                 analyze_verify_stmt(assumptions, obligations, st);
             }
@@ -202,6 +214,11 @@ fn analyze_verify_stmt(assumptions: &mut Vec<SpanRange>, obligations: &mut Vec<S
                 analyze_verify_stmt(assumptions, obligations, st);
             }
         }
+        Stmt::For(s) => {
+            for st in &s.body.stmts {
+                analyze_verify_stmt(assumptions, obligations, st);
+            }
+        }
         Stmt::Layout(s) => {
             for st in &s.body.stmts {
                 analyze_verify_stmt(assumptions, obligations, st);
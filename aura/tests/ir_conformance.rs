@@ -17,6 +17,8 @@ fn module_with_single_function(insts: Vec<Inst>, term: Terminator) -> ModuleIR {
         params: Vec::new(),
         ret: Type::Unit,
         entry,
+        debug: Default::default(),
+        is_closure: false,
         blocks: vec![aura_ir::BasicBlock {
             id: entry,
             span: span0(),
@@ -113,6 +115,8 @@ fn conformance_emit_c_backend_for_ir_features() {
                 params: Vec::new(),
                 ret: Type::Unit,
                 entry,
+                debug: Default::default(),
+                is_closure: false,
                 blocks: vec![aura_ir::BasicBlock {
                     id: entry,
                     span: span0(),
@@ -273,6 +277,8 @@ fn conformance_phi_and_branches_do_not_panic_in_c_backend() {
         ret: Type::Unit,
         blocks,
         entry,
+        debug: Default::default(),
+        is_closure: false,
     };
 
     m.functions.insert("main".to_string(), f);
@@ -352,6 +352,8 @@ fn parse_fixture_to_module(src: &str) -> Result<ModuleIR> {
             ret: aura_ir::Type::Unit,
             blocks: f_blocks,
             entry,
+            debug: Default::default(),
+            is_closure: false,
         },
     );
 
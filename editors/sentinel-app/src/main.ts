@@ -116,6 +116,7 @@ type ProofStreamPayload = {
   diagnostics?: Diagnostic[];
   telemetry?: any;
   error?: string;
+  partial?: { stmtHash: string; done: number; total: number; cached: boolean };
 };
 
 // sevLabel imported
@@ -1325,7 +1326,9 @@ app.innerHTML = `
             <button id="dbgEnable" class="miniBtn" type="button">Enable</button>
             <button id="dbgPause" class="miniBtn" type="button">Pause</button>
             <button id="dbgContinue" class="miniBtn" type="button">Continue</button>
-            <button id="dbgStep" class="miniBtn" type="button">Step</button>
+            <button id="dbgStep" class="miniBtn" type="button" >Step Over</button>
+            <button id="dbgStepIn" class="miniBtn" type="button">Step In</button>
+            <button id="dbgStepOut" class="miniBtn" type="button">Step Out</button>
             <button id="dbgToggleBp" class="miniBtn" type="button">Toggle BP @ cursor</button>
             <button id="dbgClearBps" class="miniBtn" type="button">Clear BPs</button>
           </div>
@@ -1429,6 +1432,13 @@ void listen<ProofStreamPayload>("proofs/stream", (event) => {
     return;
   }
 
+  if (payload.state === "partial") {
+    // Per-unit results; the final `done` event carries the full diagnostic set.
+    const p = payload.partial;
+    if (p) setStatus(`Proofs: running… (${p.done}/${p.total})`);
+    return;
+  }
+
   if (payload.state === "cancelled") {
     return;
   }
@@ -1639,6 +1649,8 @@ const dbgEnableBtn = document.querySelector<HTMLButtonElement>("#dbgEnable")!;
 const dbgPauseBtn = document.querySelector<HTMLButtonElement>("#dbgPause")!;
 const dbgContinueBtn = document.querySelector<HTMLButtonElement>("#dbgContinue")!;
 const dbgStepBtn = document.querySelector<HTMLButtonElement>("#dbgStep")!;
+const dbgStepInBtn = document.querySelector<HTMLButtonElement>("#dbgStepIn")!;
+const dbgStepOutBtn = document.querySelector<HTMLButtonElement>("#dbgStepOut")!;
 const dbgToggleBpBtn = document.querySelector<HTMLButtonElement>("#dbgToggleBp")!;
 const dbgClearBpsBtn = document.querySelector<HTMLButtonElement>("#dbgClearBps")!;
 
@@ -1782,6 +1794,17 @@ type DebugStoppedEvent = {
   col: number;
   env: Record<string, string>;
   watches: Array<{ expr: string; value?: string | null; error?: string | null }>;
+  variables?: DebugVariable[];
+};
+
+type DebugVariable = { name: string; value: string; type: string; reference: number; children?: number };
+type DebugExpandedEvent = {
+  event: "expanded";
+  reference: number;
+  start: number;
+  total: number;
+  children: DebugVariable[];
+  error?: string | null;
 };
 
 type PerfReportEvent = {
@@ -1790,9 +1813,13 @@ type PerfReportEvent = {
     timeline: Array<{ line: number; col: number; kind: string; dur_ns: number }>;
     flame_folded: string;
     memory: Record<string, number>;
+    cells?: Array<{ name: string; calls: number; self_ns: number; total_ns: number }>;
+    cell_stacks?: string;
   };
 };
 
+type ReloadedEvent = { event: "reloaded"; units: string[]; error?: string | null };
+
 type NativeLaunchEvent = { event: "nativeLaunch"; exe: string };
 type NativeExitEvent = { event: "nativeExit"; code?: number | null };
 type HelloEvent = { event: "hello"; protocol: number; capabilities: string[] };
@@ -1804,12 +1831,16 @@ type AuraDebugEvent =
   | NativeExitEvent
   | HelloEvent
   | TerminatedEvent
+  | ReloadedEvent
   | { event: string; [k: string]: any };
 
 let dbgEnabled = false;
 let dbgBreakpoints: DebugBreakpoint[] = [];
 let dbgWatches: string[] = [];
 let dbgLastStopped: DebugStoppedEvent | undefined;
+// Children fetched with `expand` so far, and the variables shown open; both by reference.
+let dbgChildren = new Map<number, DebugVariable[]>();
+let dbgOpen = new Set<number>();
 let dbgLastNativeExe: string | undefined;
 let dbgLastNativeExit: number | null | undefined;
 let dbgHelloCaps: Set<string> | undefined;
@@ -1878,9 +1909,12 @@ function renderDebugPanel() {
 
   const envEntries = Object.entries(dbgLastStopped.env ?? {}).slice(0, 120);
   envEntries.sort((a, b) => a[0].localeCompare(b[0]));
-  const envHtml = envEntries
-    .map(([k, v]) => `<div class="meta"><code>${escapeHtml(k)}</code> = <code>${escapeHtml(String(v))}</code></div>`)
-    .join("\n");
+  // Older Aura builds only report the flat `env`.
+  const envHtml = dbgLastStopped.variables
+    ? renderDebugVariables(dbgLastStopped.variables, 0)
+    : envEntries
+        .map(([k, v]) => `<div class="meta"><code>${escapeHtml(k)}</code> = <code>${escapeHtml(String(v))}</code></div>`)
+        .join("\n");
 
   const watchHtml = (dbgLastStopped.watches ?? [])
     .map((w) => {
@@ -1901,6 +1935,27 @@ function renderDebugPanel() {
   `;
 }
 
+function renderDebugVariables(vars: DebugVariable[], depth: number): string {
+  return vars
+    .map((v) => {
+      const pad = `padding-left:${depth * 14}px;`;
+      const open = v.reference > 0 && dbgOpen.has(v.reference);
+      const toggle =
+        v.reference > 0
+          ? `<button class="miniBtn" data-dbg-expand="${v.reference}">${open ? "-" : "+"}</button> `
+          : "";
+      const line = `<div class="meta" style="${pad}">${toggle}<code>${escapeHtml(v.name)}</code> = <code>${escapeHtml(v.value)}</code></div>`;
+      if (!open) return line;
+      const loaded = dbgChildren.get(v.reference) ?? [];
+      const more =
+        loaded.length < (v.children ?? 0)
+          ? `<div class="meta" style="padding-left:${(depth + 1) * 14}px;"><button class="miniBtn" data-dbg-more="${v.reference}">${loaded.length} of ${v.children} shown, load more</button></div>`
+          : "";
+      return line + renderDebugVariables(loaded, depth + 1) + more;
+    })
+    .join("\n");
+}
+
 function renderPerfPanel() {
   if (!perfLastReport) {
     perfOutEl.innerHTML = `<div class="meta">(no perf report yet)</div>`;
@@ -1932,12 +1987,24 @@ function renderPerfPanel() {
     })
     .join("\n");
 
+  const ms = (ns: number) => (ns / 1e6).toFixed(2);
+  const cellRows = [...(perfLastReport.cells ?? [])]
+    .sort((a, b) => b.total_ns - a.total_ns)
+    .slice(0, 30)
+    .map(
+      (c) =>
+        `<div class="meta"><code>${escapeHtml(c.name)}</code>: ${c.calls} call(s), self <code>${ms(c.self_ns)} ms</code>, total <code>${ms(c.total_ns)} ms</code></div>`
+    )
+    .join("\n");
+
   const timelineCount = (perfLastReport.timeline ?? []).length;
   const flamePreview = (perfLastReport.flame_folded ?? "").split("\n").slice(0, 60).join("\n");
   perfOutEl.innerHTML = `
     <div class="meta">Timeline events: <b>${timelineCount}</b></div>
     <div class="meta" style="margin-top:8px;"><b>Memory</b></div>
     ${memLines || `<div class="meta">(none)</div>`}
+    <div class="meta" style="margin-top:8px;"><b>Cells</b></div>
+    ${cellRows || `<div class="meta">(none)</div>`}
     <div class="meta" style="margin-top:10px;"><b>Flamegraph (folded preview)</b></div>
     <pre class="meta" style="white-space:pre-wrap;">${escapeHtml(flamePreview || "(empty)")}</pre>
   `;
@@ -1957,8 +2024,27 @@ function tryHandleAuraDebugLine(line: string): boolean {
       renderDebugPanel();
     } else if (ev.event === "stopped") {
       dbgLastStopped = ev as DebugStoppedEvent;
+      // References are only valid while this stop lasts.
+      dbgChildren = new Map();
+      dbgOpen = new Set();
       dbgEnabled = true;
       renderDebugPanel();
+    } else if (ev.event === "expanded") {
+      const x = ev as DebugExpandedEvent;
+      if (!x.error) {
+        const loaded = (dbgChildren.get(x.reference) ?? []).slice(0, x.start);
+        dbgChildren.set(x.reference, [...loaded, ...(x.children ?? [])]);
+        renderDebugPanel();
+      }
+    } else if (ev.event === "reloaded") {
+      const r = ev as ReloadedEvent;
+      if (r.error) {
+        setStatus("Hot reload: edit not applied");
+        consoleAppendLineHtml("stderr", `Hot reload failed: ${r.error}`);
+      } else {
+        const units = r.units ?? [];
+        setStatus(units.length ? `Hot reload: ${units.join(", ")}` : "Hot reload: no changes");
+      }
     } else if (ev.event === "perfReport") {
       perfLastReport = (ev as PerfReportEvent).report;
       renderPerfPanel();
@@ -4371,7 +4457,15 @@ dbgContinueBtn.addEventListener("click", () => {
 });
 
 dbgStepBtn.addEventListener("click", () => {
-  void auraSend({ cmd: "step" });
+  void auraSend({ cmd: "stepOver" });
+});
+
+dbgStepInBtn.addEventListener("click", () => {
+  void auraSend({ cmd: "stepIn" });
+});
+
+dbgStepOutBtn.addEventListener("click", () => {
+  void auraSend({ cmd: "stepOut" });
 });
 
 dbgToggleBpBtn.addEventListener("click", () => {
@@ -4418,6 +4512,25 @@ dbgBpsEl.addEventListener("click", (ev) => {
   }
 });
 
+dbgStoppedEl.addEventListener("click", (ev) => {
+  const t = ev.target as HTMLElement;
+  const toggle = t.closest<HTMLButtonElement>("button[data-dbg-expand]");
+  const more = t.closest<HTMLButtonElement>("button[data-dbg-more]");
+  if (toggle) {
+    const reference = Number(toggle.dataset.dbgExpand);
+    if (dbgOpen.has(reference)) {
+      dbgOpen.delete(reference);
+    } else {
+      dbgOpen.add(reference);
+      if (!dbgChildren.has(reference)) void auraSend({ cmd: "expand", reference });
+    }
+    renderDebugPanel();
+  } else if (more) {
+    const reference = Number(more.dataset.dbgMore);
+    void auraSend({ cmd: "expand", reference, start: (dbgChildren.get(reference) ?? []).length });
+  }
+});
+
 dbgWatchAddBtn.addEventListener("click", () => {
   const expr = (dbgWatchExprEl.value ?? "").trim();
   if (!expr) return;
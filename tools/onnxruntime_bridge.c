@@ -1,21 +1,297 @@
-// Minimal ONNX Runtime ABI shim for Aura.
+// ONNX Runtime binding for Aura.
 //
-// We intentionally avoid including ONNX Runtime headers to keep the shim
-// independent of include paths and bridge parser limitations.
+// Besides the `onnxruntime_available` shim the bridge exposes to Aura code, this file
+// registers ONNX Runtime as the stdlib's inference backend, so `ai.load_model` and
+// `ai.infer` run models with it. It needs the ONNX Runtime C API header, which
+// `aura pkg add onnxruntime` unpacks into the project's include/ directory.
+
+#include "aura_stdlib.h"
+
+#include <onnxruntime_c_api.h>
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#if defined(_WIN32)
+#include <windows.h>
+#endif
 
 #ifdef __cplusplus
 extern "C" {
 #endif
 
-// ORT exports this symbol from onnxruntime.dll.
-// In official headers it is: const OrtApiBase* OrtGetApiBase(void);
-// For this shim we only need the address to exist.
-const void* OrtGetApiBase(void);
-
 unsigned int onnxruntime_available(void) {
     return OrtGetApiBase() ? 1u : 0u;
 }
 
+typedef struct AuraOrtModel {
+    OrtSession* session;
+    char* input_name;
+    char* output_name;
+    ONNXTensorElementDataType input_elem;
+} AuraOrtModel;
+
+static const OrtApi* g_ort = NULL;
+static OrtEnv* g_ort_env = NULL;
+
+// Copies the message of a failed call into `err` and releases the status. Returns 1 on
+// failure.
+static int aura_ort_failed(OrtStatus* status, char* err, size_t err_cap) {
+    if (!status) {
+        return 0;
+    }
+    snprintf(err, err_cap, "%s", g_ort->GetErrorMessage(status));
+    g_ort->ReleaseStatus(status);
+    return 1;
+}
+
+// Bytes an element of `t` takes in a tensor; 0 for types wider than the 32-bit slots
+// Aura tensors store elements in.
+static size_t aura_ort_elem_width(ONNXTensorElementDataType t) {
+    switch (t) {
+    case ONNX_TENSOR_ELEMENT_DATA_TYPE_UINT8:
+    case ONNX_TENSOR_ELEMENT_DATA_TYPE_INT8:
+    case ONNX_TENSOR_ELEMENT_DATA_TYPE_BOOL:
+        return 1u;
+    case ONNX_TENSOR_ELEMENT_DATA_TYPE_UINT16:
+    case ONNX_TENSOR_ELEMENT_DATA_TYPE_INT16:
+        return 2u;
+    case ONNX_TENSOR_ELEMENT_DATA_TYPE_FLOAT:
+    case ONNX_TENSOR_ELEMENT_DATA_TYPE_UINT32:
+    case ONNX_TENSOR_ELEMENT_DATA_TYPE_INT32:
+        return 4u;
+    default:
+        return 0u;
+    }
+}
+
+// Shape of a model's first input (`output` == 0) or output. Open dimensions read as -1.
+static int aura_ort_io_shape(OrtSession* session, int output, ONNXTensorElementDataType* elem, int64_t* dims,
+                             uint32_t* rank, char* err, size_t err_cap) {
+    OrtTypeInfo* info = NULL;
+    const OrtTensorTypeAndShapeInfo* tensor = NULL;
+    size_t n = 0u;
+    OrtStatus* st = output ? g_ort->SessionGetOutputTypeInfo(session, 0, &info)
+                           : g_ort->SessionGetInputTypeInfo(session, 0, &info);
+    if (aura_ort_failed(st, err, err_cap)) {
+        return 0;
+    }
+    int ok = !aura_ort_failed(g_ort->CastTypeInfoToTensorInfo(info, &tensor), err, err_cap);
+    if (ok && !tensor) {
+        snprintf(err, err_cap, "the model's first %s is not a tensor", output ? "output" : "input");
+        ok = 0;
+    }
+    ok = ok && !aura_ort_failed(g_ort->GetTensorElementType(tensor, elem), err, err_cap);
+    ok = ok && !aura_ort_failed(g_ort->GetDimensionsCount(tensor, &n), err, err_cap);
+    if (ok && n > AURA_AI_MAX_RANK) {
+        snprintf(err, err_cap, "the model's first %s has %zu dimensions", output ? "output" : "input", n);
+        ok = 0;
+    }
+    ok = ok && !aura_ort_failed(g_ort->GetDimensions(tensor, dims, n), err, err_cap);
+    g_ort->ReleaseTypeInfo(info);
+    if (ok) {
+        *rank = (uint32_t)n;
+        for (size_t i = 0u; i < n; i++) {
+            if (dims[i] <= 0) {
+                dims[i] = -1;
+            }
+        }
+    }
+    return ok;
+}
+
+static char* aura_ort_io_name(OrtSession* session, int output, char* err, size_t err_cap) {
+    OrtAllocator* alloc = NULL;
+    char* name = NULL;
+    if (aura_ort_failed(g_ort->GetAllocatorWithDefaultOptions(&alloc), err, err_cap)) {
+        return NULL;
+    }
+    OrtStatus* st = output ? g_ort->SessionGetOutputName(session, 0, alloc, &name)
+                           : g_ort->SessionGetInputName(session, 0, alloc, &name);
+    if (aura_ort_failed(st, err, err_cap)) {
+        return NULL;
+    }
+    char* copy = (char*)malloc(strlen(name) + 1u);
+    if (copy) {
+        strcpy(copy, name);
+    }
+    g_ort->AllocatorFree(alloc, name);
+    return copy;
+}
+
+static void* aura_ort_load(const char* path, int64_t* in_dims, uint32_t* in_rank, int64_t* out_dims, uint32_t* out_rank,
+                           char* err, size_t err_cap) {
+    if (!g_ort_env && aura_ort_failed(g_ort->CreateEnv(ORT_LOGGING_LEVEL_WARNING, "aura", &g_ort_env), err, err_cap)) {
+        return NULL;
+    }
+    OrtSessionOptions* options = NULL;
+    if (aura_ort_failed(g_ort->CreateSessionOptions(&options), err, err_cap)) {
+        return NULL;
+    }
+    OrtSession* session = NULL;
+#if defined(_WIN32)
+    wchar_t wpath[4096];
+    if (!MultiByteToWideChar(CP_UTF8, 0, path, -1, wpath, (int)(sizeof(wpath) / sizeof(wpath[0])))) {
+        g_ort->ReleaseSessionOptions(options);
+        snprintf(err, err_cap, "the model path is not valid UTF-8 or is too long");
+        return NULL;
+    }
+    OrtStatus* st = g_ort->CreateSession(g_ort_env, wpath, options, &session);
+#else
+    OrtStatus* st = g_ort->CreateSession(g_ort_env, path, options, &session);
+#endif
+    g_ort->ReleaseSessionOptions(options);
+    if (aura_ort_failed(st, err, err_cap)) {
+        return NULL;
+    }
+
+    AuraOrtModel* m = (AuraOrtModel*)calloc(1u, sizeof(AuraOrtModel));
+    ONNXTensorElementDataType out_elem;
+    int ok = m != NULL;
+    ok = ok && aura_ort_io_shape(session, 0, &m->input_elem, in_dims, in_rank, err, err_cap);
+    ok = ok && aura_ort_io_shape(session, 1, &out_elem, out_dims, out_rank, err, err_cap);
+    if (ok && (!aura_ort_elem_width(m->input_elem) || !aura_ort_elem_width(out_elem))) {
+        snprintf(err, err_cap, "the model's element types are wider than Aura's 32-bit tensor slots");
+        ok = 0;
+    }
+    ok = ok && (m->input_name = aura_ort_io_name(session, 0, err, err_cap)) != NULL;
+    ok = ok && (m->output_name = aura_ort_io_name(session, 1, err, err_cap)) != NULL;
+    if (!ok) {
+        if (m) {
+            free(m->input_name);
+            free(m);
+        }
+        g_ort->ReleaseSession(session);
+        return NULL;
+    }
+    m->session = session;
+    return m;
+}
+
+static uint32_t aura_ort_run(void* model, uint32_t input, const int64_t* in_dims, uint32_t in_rank, int64_t* out_dims,
+                             uint32_t* out_rank, char* err, size_t err_cap) {
+    AuraOrtModel* m = (AuraOrtModel*)model;
+    uint32_t len = aura_tensor_len(input);
+    const uint32_t* slots = aura_tensor_data(input);
+    size_t width = aura_ort_elem_width(m->input_elem);
+
+    // Elements are the low bytes of their slots; f32 slots hold the float's bits.
+    unsigned char* bytes = (unsigned char*)malloc((size_t)len * width + 1u);
+    if (!bytes) {
+        snprintf(err, err_cap, "out of memory");
+        return 0u;
+    }
+    for (uint32_t i = 0u; i < len; i++) {
+        uint32_t v = slots[i];
+        if (width == 1u) {
+            bytes[i] = (unsigned char)v;
+        } else if (width == 2u) {
+            uint16_t h = (uint16_t)v;
+            memcpy(bytes + (size_t)i * 2u, &h, 2u);
+        } else {
+            memcpy(bytes + (size_t)i * 4u, &v, 4u);
+        }
+    }
+
+    OrtMemoryInfo* mem = NULL;
+    OrtValue* in_value = NULL;
+    OrtValue* out_value = NULL;
+    uint32_t out = 0u;
+    if (aura_ort_failed(g_ort->CreateCpuMemoryInfo(OrtArenaAllocator, OrtMemTypeDefault, &mem), err, err_cap)) {
+        free(bytes);
+        return 0u;
+    }
+    OrtStatus* st = g_ort->CreateTensorWithDataAsOrtValue(mem, bytes, (size_t)len * width, in_dims, in_rank,
+                                                           m->input_elem, &in_value);
+    g_ort->ReleaseMemoryInfo(mem);
+    if (aura_ort_failed(st, err, err_cap)) {
+        free(bytes);
+        return 0u;
+    }
+    const char* in_names[1] = {m->input_name};
+    const char* out_names[1] = {m->output_name};
+    const OrtValue* inputs[1] = {in_value};
+    st = g_ort->Run(m->session, NULL, in_names, inputs, 1u, out_names, 1u, &out_value);
+    g_ort->ReleaseValue(in_value);
+    free(bytes);
+    if (aura_ort_failed(st, err, err_cap)) {
+        return 0u;
+    }
+
+    OrtTensorTypeAndShapeInfo* info = NULL;
+    ONNXTensorElementDataType elem;
+    size_t rank = 0u;
+    void* data = NULL;
+    int ok = !aura_ort_failed(g_ort->GetTensorTypeAndShape(out_value, &info), err, err_cap);
+    ok = ok && !aura_ort_failed(g_ort->GetTensorElementType(info, &elem), err, err_cap);
+    ok = ok && !aura_ort_failed(g_ort->GetDimensionsCount(info, &rank), err, err_cap);
+    if (ok && rank > AURA_AI_MAX_RANK) {
+        snprintf(err, err_cap, "the model returned a tensor with %zu dimensions", rank);
+        ok = 0;
+    }
+    ok = ok && !aura_ort_failed(g_ort->GetDimensions(info, out_dims, rank), err, err_cap);
+    ok = ok && !aura_ort_failed(g_ort->GetTensorMutableData(out_value, &data), err, err_cap);
+    if (info) {
+        g_ort->ReleaseTensorTypeAndShapeInfo(info);
+    }
+    if (ok) {
+        uint64_t count = 1u;
+        for (size_t i = 0u; i < rank; i++) {
+            count *= out_dims[i] > 0 ? (uint64_t)out_dims[i] : 0u;
+        }
+        size_t out_width = aura_ort_elem_width(elem);
+        out = count <= 0xFFFFFFFFu ? aura_tensor_new((uint32_t)count) : 0u;
+        uint32_t* dst = aura_tensor_data(out);
+        if (!out_width || !dst) {
+            snprintf(err, err_cap, "cannot store the model's output in an Aura tensor");
+            out = 0u;
+        }
+        // Signed elements are widened as two's complement.
+        for (uint64_t i = 0u; out && i < count; i++) {
+            const unsigned char* p = (const unsigned char*)data + i * out_width;
+            switch (elem) {
+            case ONNX_TENSOR_ELEMENT_DATA_TYPE_INT8:
+                dst[i] = (uint32_t)(int32_t)(int8_t)p[0];
+                break;
+            case ONNX_TENSOR_ELEMENT_DATA_TYPE_UINT8:
+            case ONNX_TENSOR_ELEMENT_DATA_TYPE_BOOL:
+                dst[i] = p[0];
+                break;
+            case ONNX_TENSOR_ELEMENT_DATA_TYPE_INT16: {
+                int16_t h;
+                memcpy(&h, p, 2u);
+                dst[i] = (uint32_t)(int32_t)h;
+                break;
+            }
+            case ONNX_TENSOR_ELEMENT_DATA_TYPE_UINT16: {
+                uint16_t h;
+                memcpy(&h, p, 2u);
+                dst[i] = h;
+                break;
+            }
+            default:
+                memcpy(&dst[i], p, 4u);
+                break;
+            }
+        }
+        *out_rank = (uint32_t)rank;
+    }
+    g_ort->ReleaseValue(out_value);
+    return out;
+}
+
+static const AuraAiBackend g_aura_ort_backend = {aura_ort_load, aura_ort_run};
+
+// Runs before `main`, so every model the program loads goes to ONNX Runtime.
+__attribute__((constructor)) static void aura_ort_register(void) {
+    const OrtApiBase* base = OrtGetApiBase();
+    g_ort = base ? base->GetApi(ORT_API_VERSION) : NULL;
+    if (g_ort) {
+        aura_ai_register_backend(&g_aura_ort_backend);
+    }
+}
+
 #ifdef __cplusplus
 }
 #endif